@@ -9,14 +9,16 @@ use chrono::{DateTime, Utc};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use tracing::{debug, warn};
 use uuid::Uuid;
 use std::time::Duration;
 
 use hive_core::channels::{ChannelMessage, MessageAuthor};
 use hive_core::config::HiveConfig;
-use hive_core::kanban::{KanbanBoard, Priority};
+use hive_core::kanban::{KanbanStore, Priority};
 use hive_core::notifications::{AppNotification, NotificationType};
+use hive_integrations::webhooks::{WebhookEvent, WebhookSubscription};
 use hive_terminal::executor::CommandExecutor;
 
 // ---------------------------------------------------------------------------
@@ -558,6 +560,21 @@ impl AutomationService {
             .collect()
     }
 
+    /// Active workflows whose `WebhookReceived` trigger matches `event`.
+    ///
+    /// The trigger's `event` is a bus pattern: `github.push`, `jira.*`, or
+    /// a bare `push` for any source (see [`WebhookEvent::matches`]).
+    pub fn workflows_for_webhook(&self, event: &WebhookEvent) -> Vec<&Workflow> {
+        self.workflows
+            .iter()
+            .filter(|w| w.status == WorkflowStatus::Active)
+            .filter(|w| match &w.trigger {
+                TriggerType::WebhookReceived { event: pattern } => event.matches(pattern),
+                _ => false,
+            })
+            .collect()
+    }
+
     /// Simulate executing a workflow. All steps are "run" in order and a
     /// `WorkflowRunResult` is produced. The workflow's `run_count` and
     /// `last_run` are updated.
@@ -876,23 +893,20 @@ impl AutomationService {
 
     /// Create a task on the kanban board at `~/.hive/kanban.json`.
     fn execute_create_task(title: &str) -> std::result::Result<(), String> {
-        let base_dir = match HiveConfig::base_dir() {
-            Ok(d) => d,
+        let store = match KanbanStore::open_default() {
+            Ok(store) => store,
             Err(e) => {
                 warn!("Cannot resolve ~/.hive directory for CreateTask: {e}");
                 return Ok(()); // fire-and-forget
             }
         };
 
-        let kanban_path = base_dir.join("kanban.json");
-        let mut board = KanbanBoard::load_from_file(&kanban_path)
-            .unwrap_or_else(|_| KanbanBoard::new());
-
-        board.add_task(title, None, Priority::Medium);
-
-        board
-            .save_to_file(&kanban_path)
-            .map_err(|e| format!("Failed to save kanban board: {e}"))?;
+        store
+            .update(|board| {
+                board.add_task(title, None, Priority::Medium);
+                Ok(())
+            })
+            .map_err(|e| format!("Failed to add task to kanban board: {e}"))?;
 
         debug!(title, "CreateTask action completed");
         Ok(())
@@ -1163,6 +1177,52 @@ impl Default for AutomationService {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Webhook dispatch
+// ---------------------------------------------------------------------------
+
+/// Run the matching workflows for each event on `subscription` until the
+/// webhook bus closes. Each run is recorded in the service's history.
+pub async fn dispatch_webhook_workflows(
+    automation: Arc<Mutex<AutomationService>>,
+    mut subscription: WebhookSubscription,
+    working_dir: PathBuf,
+) {
+    while let Some(event) = subscription.recv().await {
+        let workflows: Vec<Workflow> = match automation.lock() {
+            Ok(svc) => svc
+                .workflows_for_webhook(&event)
+                .into_iter()
+                .cloned()
+                .collect(),
+            Err(e) => {
+                warn!("Automation lock poisoned: {e}");
+                continue;
+            }
+        };
+
+        for workflow in workflows {
+            debug!(workflow_id = %workflow.id, topic = %event.topic(), "Webhook triggered workflow");
+            let dir = working_dir.clone();
+            let to_run = workflow.clone();
+            let run = tokio::task::spawn_blocking(move || {
+                AutomationService::execute_workflow_blocking(&to_run, dir)
+            })
+            .await;
+            let (success, steps_completed, error) = match run {
+                Ok(Ok(result)) => (result.success, result.steps_completed, result.error),
+                Ok(Err(e)) => (false, 0, Some(format!("{e:#}"))),
+                Err(e) => (false, 0, Some(format!("Workflow task panicked: {e}"))),
+            };
+            if let Ok(mut svc) = automation.lock()
+                && let Err(e) = svc.record_run(&workflow.id, success, steps_completed, error)
+            {
+                warn!(workflow_id = %workflow.id, "Failed to record webhook run: {e}");
+            }
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -1646,6 +1706,97 @@ mod tests {
         }
     }
 
+    // -- webhook triggers ---------------------------------------------------
+
+    fn webhook_event(
+        source: hive_integrations::webhooks::WebhookSource,
+        name: &str,
+    ) -> WebhookEvent {
+        WebhookEvent {
+            id: "evt-1".into(),
+            source,
+            event: name.into(),
+            delivery_id: "d-1".into(),
+            fingerprint: "f-1".into(),
+            actor: None,
+            subject: None,
+            payload: serde_json::json!({}),
+            received_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn workflows_for_webhook_matches_active_patterns() {
+        use hive_integrations::webhooks::WebhookSource;
+
+        let mut svc = AutomationService::new();
+        let push = svc.create_workflow(
+            "On push",
+            "",
+            TriggerType::WebhookReceived {
+                event: "github.push".into(),
+            },
+        );
+        let jira = svc.create_workflow(
+            "On Jira",
+            "",
+            TriggerType::WebhookReceived {
+                event: "jira.*".into(),
+            },
+        );
+        svc.create_workflow(
+            "Draft",
+            "",
+            TriggerType::WebhookReceived { event: "*".into() },
+        );
+        svc.activate_workflow(&push.id).unwrap();
+        svc.activate_workflow(&jira.id).unwrap();
+
+        let event = webhook_event(WebhookSource::GitHub, "push");
+        let ids: Vec<_> = svc
+            .workflows_for_webhook(&event)
+            .iter()
+            .map(|w| w.id.clone())
+            .collect();
+        assert_eq!(ids, [push.id]);
+
+        let event = webhook_event(WebhookSource::Jira, "issue_updated");
+        assert_eq!(svc.workflows_for_webhook(&event)[0].id, jira.id);
+        let event = webhook_event(WebhookSource::GitLab, "push");
+        assert!(svc.workflows_for_webhook(&event).is_empty());
+    }
+
+    #[tokio::test]
+    async fn dispatch_runs_and_records_webhook_workflows() {
+        use hive_integrations::webhooks::{WebhookEventBus, WebhookSource};
+
+        let mut svc = AutomationService::new();
+        let wf = svc.create_workflow(
+            "On Stripe",
+            "",
+            TriggerType::WebhookReceived {
+                event: "stripe.invoice.paid".into(),
+            },
+        );
+        svc.activate_workflow(&wf.id).unwrap();
+        let automation = Arc::new(Mutex::new(svc));
+
+        let bus = WebhookEventBus::new();
+        let dispatcher = tokio::spawn(dispatch_webhook_workflows(
+            Arc::clone(&automation),
+            bus.subscribe("*"),
+            std::env::temp_dir(),
+        ));
+        bus.publish(webhook_event(WebhookSource::Stripe, "invoice.paid"));
+        bus.publish(webhook_event(WebhookSource::Stripe, "charge.failed"));
+        drop(bus);
+        dispatcher.await.unwrap();
+
+        let svc = automation.lock().unwrap();
+        assert_eq!(svc.get_workflow(&wf.id).unwrap().run_count, 1);
+        assert!(svc.get_run_history(&wf.id, 1)[0].success);
+    }
+
     // -- action types -------------------------------------------------------
 
     #[test]
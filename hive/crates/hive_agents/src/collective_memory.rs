@@ -1,4 +1,5 @@
 use chrono::Utc;
+use hive_core::search::{SearchDocument, SearchSource};
 use rusqlite::{Connection, params};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -69,6 +70,24 @@ pub struct MemoryEntry {
     pub access_count: u64,
 }
 
+impl From<&MemoryEntry> for SearchDocument {
+    fn from(entry: &MemoryEntry) -> Self {
+        let first_line = entry.content.lines().next().unwrap_or_default();
+        let title: String = first_line.chars().take(80).collect();
+        let mut doc = SearchDocument::new(
+            SearchSource::Memory,
+            entry.id.to_string(),
+            format!("{}: {title}", entry.category),
+            &entry.content,
+        )
+        .with_tags(entry.tags.iter().cloned());
+        if let Ok(created_at) = chrono::DateTime::parse_from_rfc3339(&entry.created_at) {
+            doc = doc.updated(created_at.with_timezone(&Utc));
+        }
+        doc
+    }
+}
+
 impl MemoryEntry {
     /// Convenience constructor with sensible defaults.
     pub fn new(category: MemoryCategory, content: impl Into<String>) -> Self {
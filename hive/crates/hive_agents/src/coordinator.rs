@@ -7,12 +7,15 @@
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use tracing::warn;
 
 use hive_ai::types::{ChatMessage, ChatRequest, MessageRole, ModelTier};
 
 use crate::hivemind::{AiExecutor, default_model_for_tier};
+use crate::kanban_tracker::KanbanTracker;
 use crate::personas::{Persona, PersonaKind, PersonaRegistry, execute_with_persona};
+use crate::remote_exec::{RemoteExecService, RemoteWork};
 use crate::specs::Spec;
 
 // ---------------------------------------------------------------------------
@@ -186,6 +189,8 @@ pub struct Coordinator<E: AiExecutor> {
     pub config: CoordinatorConfig,
     executor: Arc<E>,
     registry: PersonaRegistry,
+    tracker: Option<Arc<KanbanTracker>>,
+    remote: Option<Arc<RemoteExecService>>,
 }
 
 impl<E: AiExecutor + 'static> Coordinator<E> {
@@ -194,6 +199,8 @@ impl<E: AiExecutor + 'static> Coordinator<E> {
             config,
             executor: Arc::new(executor),
             registry: PersonaRegistry::new(),
+            tracker: None,
+            remote: None,
         }
     }
 
@@ -207,9 +214,22 @@ impl<E: AiExecutor + 'static> Coordinator<E> {
             config,
             executor: Arc::new(executor),
             registry,
+            tracker: None,
+            remote: None,
         }
     }
 
+    /// Mirror task progress onto Kanban cards (and the linked tracker).
+    pub fn set_tracker(&mut self, tracker: Arc<KanbanTracker>) {
+        self.tracker = Some(tracker);
+    }
+
+    /// Offload tasks to trusted peers that can run them. Tasks no peer takes
+    /// run locally.
+    pub fn set_remote(&mut self, remote: Arc<RemoteExecService>) {
+        self.remote = Some(remote);
+    }
+
     /// Use AI to decompose a specification into a task plan.
     pub async fn plan_from_spec(&self, spec: &Spec) -> Result<TaskPlan, String> {
         let prompt = format!(
@@ -248,6 +268,10 @@ impl<E: AiExecutor + 'static> Coordinator<E> {
         let mut completed: HashSet<String> = HashSet::new();
         let mut remaining: Vec<PlannedTask> = plan.tasks.clone();
 
+        if let Some(remote) = &self.remote {
+            remote.discover_executors().await;
+        }
+
         // Process tasks in waves: each wave contains tasks whose dependencies
         // are all satisfied.
         while !remaining.is_empty() {
@@ -284,38 +308,25 @@ impl<E: AiExecutor + 'static> Coordinator<E> {
             // non-Send future, preventing tokio::spawn. The wave structure
             // still provides correct dependency ordering across waves.
             for task in &batch {
-                let persona = self
-                    .registry
-                    .get(&task.persona)
-                    .or_else(|| self.registry.get(&PersonaKind::Implement))
-                    .cloned()
-                    .unwrap_or_else(|| {
-                        // Last resort: synthesize a minimal persona so we never panic.
-                        Persona {
-                            name: "fallback".into(),
-                            kind: PersonaKind::Implement,
-                            description: "Fallback persona".into(),
-                            system_prompt: String::new(),
-                            model_tier: ModelTier::Mid,
-                            tools: Vec::new(),
-                            max_tokens: 4096,
-                        }
-                    });
-
-                let output =
-                    execute_with_persona(&persona, &task.description, self.executor.as_ref(), None)
-                        .await;
-
-                let task_result = TaskResult {
-                    task_id: task.id.clone(),
-                    persona: task.persona.clone(),
-                    output: output.content,
-                    cost: output.cost,
-                    duration_ms: output.duration_ms,
-                    success: output.success,
-                    error: output.error,
+                if let Some(tracker) = &self.tracker
+                    && let Err(e) = tracker.task_started(task).await
+                {
+                    warn!(task_id = %task.id, "Kanban tracker: {e}");
+                }
+
+                let time_left = Duration::from_secs(self.config.time_limit_secs)
+                    .saturating_sub(start.elapsed());
+                let task_result = match self.execute_remote(task, time_left).await {
+                    Some(result) => result,
+                    None => self.execute_local(task).await,
                 };
 
+                if let Some(tracker) = &self.tracker
+                    && let Err(e) = tracker.task_finished(task, &task_result).await
+                {
+                    warn!(task_id = %task.id, "Kanban tracker: {e}");
+                }
+
                 completed.insert(task_result.task_id.clone());
                 results.push(task_result);
             }
@@ -340,6 +351,50 @@ impl<E: AiExecutor + 'static> Coordinator<E> {
         }
     }
 
+    /// Run `task` on a trusted peer, if a remote service is set and a peer
+    /// takes it.
+    async fn execute_remote(&self, task: &PlannedTask, timeout: Duration) -> Option<TaskResult> {
+        let remote = self.remote.as_ref()?;
+        let result = remote
+            .run_remote(RemoteWork::Task(task.clone()), timeout)
+            .await?;
+        Some(result.to_task_result(task))
+    }
+
+    /// Run `task` with its persona on this node's executor.
+    async fn execute_local(&self, task: &PlannedTask) -> TaskResult {
+        let persona = self
+            .registry
+            .get(&task.persona)
+            .or_else(|| self.registry.get(&PersonaKind::Implement))
+            .cloned()
+            .unwrap_or_else(|| {
+                // Last resort: synthesize a minimal persona so we never panic.
+                Persona {
+                    name: "fallback".into(),
+                    kind: PersonaKind::Implement,
+                    description: "Fallback persona".into(),
+                    system_prompt: String::new(),
+                    model_tier: ModelTier::Mid,
+                    tools: Vec::new(),
+                    max_tokens: 4096,
+                }
+            });
+
+        let output =
+            execute_with_persona(&persona, &task.description, self.executor.as_ref(), None).await;
+
+        TaskResult {
+            task_id: task.id.clone(),
+            persona: task.persona.clone(),
+            output: output.content,
+            cost: output.cost,
+            duration_ms: output.duration_ms,
+            success: output.success,
+            error: output.error,
+        }
+    }
+
     /// Plan from a spec and then execute the plan.
     pub async fn execute_spec(&self, spec: &Spec) -> Result<CoordinatorResult, String> {
         let plan = self.plan_from_spec(spec).await?;
@@ -653,6 +708,29 @@ mod tests {
         assert!(pos_2 < pos_3);
     }
 
+    #[tokio::test]
+    async fn execute_plan_moves_tracked_cards() {
+        let dir = std::env::temp_dir().join(format!("hive_coordinator_test_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let board_path = dir.join("kanban.json");
+        let tracker = Arc::new(KanbanTracker::new(hive_core::kanban::KanbanStore::new(
+            &board_path,
+        )));
+        let mut coordinator = Coordinator::new(CoordinatorConfig::default(), MockExecutor::new("ok"));
+        coordinator.set_tracker(tracker.clone());
+
+        let result = coordinator.execute_plan(&sample_plan()).await;
+        assert_eq!(result.successful_tasks(), 3);
+
+        let board = hive_core::kanban::KanbanBoard::load_from_file(&board_path).unwrap();
+        assert_eq!(board.all_tasks().len(), 3);
+        for task in &sample_plan().tasks {
+            let card = board.get_task(&tracker.card_for(&task.id).unwrap()).unwrap();
+            assert_eq!(card.column, hive_core::kanban::KanbanColumn::Done);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[tokio::test]
     async fn execute_plan_handles_failures() {
         let executor = MockExecutor::failing();
@@ -0,0 +1,989 @@
+//! Fleet sharing — the policy for what a node tells its peers about what it
+//! has learned.
+//!
+//! Raw [`LearningPattern`](hive_ai::LearningPattern)s and model records can
+//! carry prompt fragments and project details, so they never leave the node.
+//! [`FleetSharing`] only releases per-task statistics from
+//! [`FleetLearningService`]: request count, success rate and average quality
+//! for each (task type, model) pair, plus the routing adjustment they imply.
+//! Before anything is sent:
+//!
+//! - every task type and model id is run through [`HiveShield`], and pairs
+//!   whose labels contain secrets, PII or blocked content are withheld;
+//! - pairs with fewer than `min_requests` samples are withheld;
+//! - each released value gets Laplace noise calibrated to the policy's
+//!   `epsilon`. A request only counts towards one pair, so a whole release
+//!   is `epsilon`-differentially private per request.
+//!
+//! Statistics are noised once per aggregation window and that one release
+//! goes to every peer, so sharing with more peers costs no extra privacy.
+//! Each new release spends `epsilon` from the policy's `epsilon_budget`;
+//! once the budget is spent nothing more is shared. The spent budget and the
+//! current release are kept in a state file so a restart does not refill it.
+//!
+//! Incoming aggregates must be signed by a trusted peer and pass the same
+//! label check. Every exchange lands in a per-peer ledger
+//! ([`PeerFleetView`]), so users can see exactly what was shared and
+//! received. [`FleetSharingService`] carries aggregates over
+//! [`MessageKind::FleetLearn`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use hive_ai::FleetLearningService;
+use hive_network::sync::FleetSyncHelper;
+use hive_network::{Envelope, HiveNode, MessageKind, PeerId};
+use hive_shield::{HiveShield, ShieldAction, ShieldConfig};
+
+/// `outcome_type` of fleet learning payloads carrying a [`FleetAggregate`].
+pub const AGGREGATE_OUTCOME: &str = "aggregate_stats";
+
+/// Provider name labels are checked against in the shield's access policies.
+const SHIELD_PROVIDER: &str = "fleet";
+
+/// Values released per (task type, model) pair; the budget is split evenly.
+const RELEASED_VALUES: f64 = 3.0;
+
+// ---------------------------------------------------------------------------
+// Policy and wire types
+// ---------------------------------------------------------------------------
+
+/// What a node is willing to share with, and accept from, its peers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FleetSharingPolicy {
+    /// Share and accept fleet statistics at all.
+    pub enabled: bool,
+    /// Differential-privacy budget spent per release. Smaller values add
+    /// more noise; `None` releases exact statistics.
+    pub epsilon: Option<f64>,
+    /// Total budget all releases may spend. `None` leaves it unbounded.
+    pub epsilon_budget: Option<f64>,
+    /// Seconds one release is reused for, across all peers, before the
+    /// statistics are aggregated and noised again.
+    pub window_secs: u64,
+    /// Pairs with fewer requests than this are never released.
+    pub min_requests: u64,
+    /// Include routing adjustments derived from the statistics.
+    pub share_routing: bool,
+    /// Exchanges kept per peer and direction in the ledger.
+    pub history_per_peer: usize,
+}
+
+impl Default for FleetSharingPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            epsilon: Some(1.0),
+            epsilon_budget: Some(30.0),
+            window_secs: 24 * 60 * 60,
+            min_requests: 10,
+            share_routing: true,
+            history_per_peer: 20,
+        }
+    }
+}
+
+/// How one model did on one kind of task.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskModelStat {
+    pub task_type: String,
+    pub model_id: String,
+    pub requests: u64,
+    /// Fraction of requests that succeeded (0.0 - 1.0).
+    pub success_rate: f64,
+    /// Average quality score (0.0 - 1.0).
+    pub avg_quality: f64,
+}
+
+/// How far a model's quality on a task type sits from the task's average,
+/// for routers to nudge their preferences by.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoutingAdjustment {
+    pub task_type: String,
+    pub model_id: String,
+    pub quality_delta: f64,
+}
+
+/// The statistics one node releases to the fleet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FleetAggregate {
+    pub stats: Vec<TaskModelStat>,
+    #[serde(default)]
+    pub routing: Vec<RoutingAdjustment>,
+    /// Budget the noise was calibrated to, or `None` for exact values.
+    pub epsilon: Option<f64>,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl FleetAggregate {
+    pub fn is_empty(&self) -> bool {
+        self.stats.is_empty()
+    }
+}
+
+/// One aggregate sent to or received from a peer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FleetExchange {
+    pub at: DateTime<Utc>,
+    pub aggregate: FleetAggregate,
+    /// Pairs left out by the shield or the sample threshold.
+    pub withheld: usize,
+}
+
+/// Everything exchanged with one peer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerFleetView {
+    pub peer_id: PeerId,
+    /// Most recent last.
+    pub shared: Vec<FleetExchange>,
+    /// Most recent last.
+    pub received: Vec<FleetExchange>,
+    /// Fleet payloads from this peer that were refused.
+    pub rejected: usize,
+}
+
+impl PeerFleetView {
+    fn new(peer_id: PeerId) -> Self {
+        Self {
+            peer_id,
+            shared: Vec::new(),
+            received: Vec::new(),
+            rejected: 0,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// FleetSharing
+// ---------------------------------------------------------------------------
+
+/// Privacy bookkeeping that must outlive the process.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BudgetState {
+    spent_epsilon: f64,
+    /// The current window's release and its withheld count.
+    release: Option<(FleetAggregate, usize)>,
+}
+
+/// Applies a [`FleetSharingPolicy`] to outgoing and incoming fleet data and
+/// keeps the per-peer ledger.
+pub struct FleetSharing {
+    policy: FleetSharingPolicy,
+    shield: HiveShield,
+    ledger: HashMap<PeerId, PeerFleetView>,
+    budget: BudgetState,
+    /// Where `budget` is saved; `None` keeps it in memory only.
+    state_path: Option<PathBuf>,
+}
+
+impl FleetSharing {
+    pub fn new(policy: FleetSharingPolicy) -> Self {
+        Self::with_shield(policy, HiveShield::new(ShieldConfig::default()))
+    }
+
+    /// Use a configured shield, e.g. one carrying the user's blocking rules.
+    pub fn with_shield(policy: FleetSharingPolicy, shield: HiveShield) -> Self {
+        Self {
+            policy,
+            shield,
+            ledger: HashMap::new(),
+            budget: BudgetState::default(),
+            state_path: None,
+        }
+    }
+
+    /// Keep the spent budget and the current release in `path`, resuming
+    /// from what an earlier run saved there. Fails on an unreadable file
+    /// rather than starting over with a fresh budget.
+    pub fn with_state_file(mut self, path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        match std::fs::read_to_string(&path) {
+            Ok(json) => {
+                self.budget = serde_json::from_str(&json)
+                    .with_context(|| format!("Corrupt fleet budget in {}", path.display()))?;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", path.display()));
+            }
+        }
+        self.state_path = Some(path);
+        Ok(self)
+    }
+
+    pub fn policy(&self) -> &FleetSharingPolicy {
+        &self.policy
+    }
+
+    /// Privacy budget spent on releases so far.
+    pub fn spent_epsilon(&self) -> f64 {
+        self.budget.spent_epsilon
+    }
+
+    /// Build the aggregate this node may release, and how many pairs were
+    /// withheld from it.
+    pub fn aggregate(
+        &self,
+        fleet: &FleetLearningService,
+        rng: &mut impl Rng,
+    ) -> (FleetAggregate, usize) {
+        let mut stats = Vec::new();
+        let mut withheld = 0;
+        for perf in fleet.task_performance() {
+            if perf.total_requests < self.policy.min_requests.max(1)
+                || !self.labels_allowed(&perf.task_type, &perf.model_id)
+            {
+                withheld += 1;
+                continue;
+            }
+            let requests = perf.total_requests as f64;
+            stats.push(self.add_noise(
+                TaskModelStat {
+                    task_type: perf.task_type.clone(),
+                    model_id: perf.model_id.clone(),
+                    requests: perf.total_requests,
+                    success_rate: perf.success_count as f64 / requests,
+                    avg_quality: perf.avg_quality_score.clamp(0.0, 1.0),
+                },
+                rng,
+            ));
+        }
+
+        let routing = if self.policy.share_routing {
+            routing_adjustments(&stats)
+        } else {
+            Vec::new()
+        };
+        let aggregate = FleetAggregate {
+            stats,
+            routing,
+            epsilon: self.policy.epsilon,
+            generated_at: Utc::now(),
+        };
+        (aggregate, withheld)
+    }
+
+    /// The release for the current aggregation window, built and charged to
+    /// the privacy budget if the window has passed. `None` when the budget
+    /// cannot cover a new release or the charge could not be saved.
+    fn release(&mut self, fleet: &FleetLearningService) -> Option<(FleetAggregate, usize)> {
+        let window = chrono::TimeDelta::try_seconds(
+            i64::try_from(self.policy.window_secs).unwrap_or(i64::MAX),
+        )
+        .unwrap_or(chrono::TimeDelta::MAX);
+        if let Some((aggregate, withheld)) = &self.budget.release
+            && Utc::now() - aggregate.generated_at < window
+        {
+            return Some((aggregate.clone(), *withheld));
+        }
+
+        let cost = self.policy.epsilon.filter(|e| *e > 0.0).unwrap_or(0.0);
+        if let Some(budget) = self.policy.epsilon_budget
+            && self.budget.spent_epsilon + cost > budget
+        {
+            warn!(
+                spent = self.budget.spent_epsilon,
+                budget, "Fleet privacy budget spent; not sharing statistics"
+            );
+            return None;
+        }
+        let release = self.aggregate(fleet, &mut rand::rng());
+        // Nothing is released from an empty aggregate, so it costs nothing.
+        if !release.0.is_empty() {
+            self.budget.spent_epsilon += cost;
+            self.budget.release = Some(release.clone());
+            // A release whose charge is lost would be free after a restart.
+            if let Some(path) = &self.state_path
+                && let Err(e) = save_budget(path, &self.budget)
+            {
+                warn!("Not sharing fleet statistics: {e:#}");
+                return None;
+            }
+        }
+        Some(release)
+    }
+
+    /// The envelope to send `to`, recorded in the ledger. `None` when
+    /// sharing is disabled, the privacy budget is spent or nothing may be
+    /// released.
+    pub fn share(
+        &mut self,
+        fleet: &FleetLearningService,
+        from: PeerId,
+        to: PeerId,
+    ) -> Option<Envelope> {
+        if !self.policy.enabled {
+            return None;
+        }
+        let (aggregate, withheld) = self.release(fleet)?;
+        if aggregate.is_empty() {
+            debug!(withheld, "Nothing to share with {}", to.short());
+            return None;
+        }
+
+        let envelope = FleetSyncHelper::share_with_peer(
+            from,
+            to.clone(),
+            AGGREGATE_OUTCOME,
+            "",
+            serde_json::to_value(&aggregate).unwrap_or_default(),
+            1.0,
+        );
+        let limit = self.policy.history_per_peer;
+        let view = self
+            .ledger
+            .entry(to.clone())
+            .or_insert_with(|| PeerFleetView::new(to));
+        push_capped(
+            &mut view.shared,
+            FleetExchange {
+                at: Utc::now(),
+                aggregate,
+                withheld,
+            },
+            limit,
+        );
+        Some(envelope)
+    }
+
+    /// Check and record a fleet learning envelope from `envelope.from`.
+    ///
+    /// `trusted` says whether the sender is on this node's trust list; only
+    /// trusted peers' statistics are accepted.
+    pub fn receive(&mut self, envelope: &Envelope, trusted: bool) -> Result<FleetAggregate> {
+        // An unsigned or forged envelope says nothing about its claimed
+        // sender, so it is not charged to that peer's ledger.
+        if !envelope.verify_signature() {
+            bail!("Fleet data is not signed by {}", envelope.from.short());
+        }
+        let result = self.check(envelope, trusted);
+        let limit = self.policy.history_per_peer;
+        let view = self
+            .ledger
+            .entry(envelope.from.clone())
+            .or_insert_with(|| PeerFleetView::new(envelope.from.clone()));
+        match result {
+            Ok((aggregate, withheld)) => {
+                push_capped(
+                    &mut view.received,
+                    FleetExchange {
+                        at: Utc::now(),
+                        aggregate: aggregate.clone(),
+                        withheld,
+                    },
+                    limit,
+                );
+                Ok(aggregate)
+            }
+            Err(e) => {
+                view.rejected += 1;
+                Err(e)
+            }
+        }
+    }
+
+    /// What was exchanged with `peer`.
+    pub fn peer_view(&self, peer: &PeerId) -> Option<&PeerFleetView> {
+        self.ledger.get(peer)
+    }
+
+    /// The ledger for every peer data was exchanged with.
+    pub fn peer_views(&self) -> Vec<&PeerFleetView> {
+        let mut views: Vec<&PeerFleetView> = self.ledger.values().collect();
+        views.sort_by(|a, b| a.peer_id.as_str().cmp(b.peer_id.as_str()));
+        views
+    }
+
+    /// Fleet-wide statistics: each peer's latest aggregate combined,
+    /// weighted by request count.
+    pub fn fleet_stats(&self) -> Vec<TaskModelStat> {
+        let latest = self
+            .ledger
+            .values()
+            .filter_map(|view| view.received.last())
+            .flat_map(|exchange| &exchange.aggregate.stats);
+        merge_stats(latest)
+    }
+
+    fn check(&self, envelope: &Envelope, trusted: bool) -> Result<(FleetAggregate, usize)> {
+        if !self.policy.enabled {
+            bail!("Fleet sharing is disabled");
+        }
+        if !trusted {
+            bail!("Peer {} is not trusted", envelope.from.short());
+        }
+        let payload = FleetSyncHelper::parse_payload(envelope).map_err(anyhow::Error::msg)?;
+        if payload.outcome_type != AGGREGATE_OUTCOME {
+            bail!(
+                "Refusing raw fleet learning '{}'; only aggregates are accepted",
+                payload.outcome_type
+            );
+        }
+        let mut aggregate: FleetAggregate =
+            serde_json::from_value(payload.data).context("Invalid fleet aggregate")?;
+
+        let before = aggregate.stats.len() + aggregate.routing.len();
+        aggregate.stats.retain(|stat| {
+            stat.success_rate.is_finite()
+                && stat.avg_quality.is_finite()
+                && self.labels_allowed(&stat.task_type, &stat.model_id)
+        });
+        for stat in &mut aggregate.stats {
+            stat.success_rate = stat.success_rate.clamp(0.0, 1.0);
+            stat.avg_quality = stat.avg_quality.clamp(0.0, 1.0);
+        }
+        aggregate.routing.retain(|adjustment| {
+            adjustment.quality_delta.is_finite()
+                && self.labels_allowed(&adjustment.task_type, &adjustment.model_id)
+        });
+        let withheld = before - aggregate.stats.len() - aggregate.routing.len();
+        Ok((aggregate, withheld))
+    }
+
+    /// Whether both labels pass the shield untouched.
+    fn labels_allowed(&self, task_type: &str, model_id: &str) -> bool {
+        [task_type, model_id].iter().all(|label| {
+            matches!(
+                self.shield.process_outgoing(label, SHIELD_PROVIDER).action,
+                ShieldAction::Allow
+            )
+        })
+    }
+
+    fn add_noise(&self, stat: TaskModelStat, rng: &mut impl Rng) -> TaskModelStat {
+        let Some(epsilon) = self.policy.epsilon.filter(|e| *e > 0.0) else {
+            return stat;
+        };
+        let per_value = epsilon / RELEASED_VALUES;
+        // One request moves a count by 1 and a mean over at least
+        // `min_requests` values in [0, 1] by at most 1 / min_requests.
+        let mean_sensitivity = 1.0 / self.policy.min_requests.max(1) as f64;
+        let requests = stat.requests as f64 + laplace(rng, 1.0 / per_value);
+        TaskModelStat {
+            requests: requests.round().max(1.0) as u64,
+            success_rate: (stat.success_rate + laplace(rng, mean_sensitivity / per_value))
+                .clamp(0.0, 1.0),
+            avg_quality: (stat.avg_quality + laplace(rng, mean_sensitivity / per_value))
+                .clamp(0.0, 1.0),
+            ..stat
+        }
+    }
+}
+
+/// A sample from the Laplace distribution centred on zero.
+fn laplace(rng: &mut impl Rng, scale: f64) -> f64 {
+    let u: f64 = rng.random_range(-0.5..0.5);
+    -scale * u.signum() * (1.0 - 2.0 * u.abs()).ln()
+}
+
+/// Each model's distance from the request-weighted average quality of its
+/// task type, for task types with more than one model.
+fn routing_adjustments(stats: &[TaskModelStat]) -> Vec<RoutingAdjustment> {
+    let mut by_task: BTreeMap<&str, Vec<&TaskModelStat>> = BTreeMap::new();
+    for stat in stats {
+        by_task.entry(&stat.task_type).or_default().push(stat);
+    }
+    by_task
+        .into_values()
+        .filter(|models| models.len() > 1)
+        .flat_map(|models| {
+            let total: f64 = models.iter().map(|s| s.requests as f64).sum();
+            let mean = models
+                .iter()
+                .map(|s| s.avg_quality * s.requests as f64)
+                .sum::<f64>()
+                / total.max(1.0);
+            models.into_iter().map(move |s| RoutingAdjustment {
+                task_type: s.task_type.clone(),
+                model_id: s.model_id.clone(),
+                quality_delta: s.avg_quality - mean,
+            })
+        })
+        .collect()
+}
+
+/// Combine statistics for the same pair, weighting rates by request count.
+fn merge_stats<'a>(stats: impl Iterator<Item = &'a TaskModelStat>) -> Vec<TaskModelStat> {
+    let mut merged: BTreeMap<(&str, &str), TaskModelStat> = BTreeMap::new();
+    for stat in stats {
+        let entry = merged
+            .entry((&stat.task_type, &stat.model_id))
+            .or_insert_with(|| TaskModelStat {
+                requests: 0,
+                success_rate: 0.0,
+                avg_quality: 0.0,
+                ..stat.clone()
+            });
+        let total = (entry.requests + stat.requests).max(1) as f64;
+        let (old, new) = (entry.requests as f64, stat.requests as f64);
+        entry.success_rate = (entry.success_rate * old + stat.success_rate * new) / total;
+        entry.avg_quality = (entry.avg_quality * old + stat.avg_quality * new) / total;
+        entry.requests += stat.requests;
+    }
+    merged.into_values().collect()
+}
+
+/// Write `budget` to `path` via a temporary file, so a crash never leaves a
+/// truncated state behind.
+fn save_budget(path: &Path, budget: &BudgetState) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    let json = serde_json::to_string(budget).context("Failed to serialize fleet budget")?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).with_context(|| format!("Failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, path).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn push_capped(history: &mut Vec<FleetExchange>, exchange: FleetExchange, limit: usize) {
+    history.push(exchange);
+    let excess = history.len().saturating_sub(limit.max(1));
+    history.drain(..excess);
+}
+
+// ---------------------------------------------------------------------------
+// FleetSharingService
+// ---------------------------------------------------------------------------
+
+/// Exchanges fleet aggregates with peers over a [`HiveNode`].
+pub struct FleetSharingService {
+    node: Arc<HiveNode>,
+    fleet: Arc<Mutex<FleetLearningService>>,
+    sharing: Arc<Mutex<FleetSharing>>,
+}
+
+impl FleetSharingService {
+    /// Register the [`MessageKind::FleetLearn`] handler on a node.
+    pub async fn attach(
+        node: Arc<HiveNode>,
+        fleet: Arc<Mutex<FleetLearningService>>,
+        sharing: FleetSharing,
+    ) -> Self {
+        let sharing = Arc::new(Mutex::new(sharing));
+        let handler_sharing = Arc::clone(&sharing);
+        let security = Arc::clone(node.security());
+        node.on_message(
+            MessageKind::FleetLearn,
+            Arc::new(move |envelope: Envelope| {
+                let trusted = security.is_trusted(&envelope.from);
+                match handler_sharing.lock().unwrap().receive(&envelope, trusted) {
+                    Ok(aggregate) => debug!(
+                        pairs = aggregate.stats.len(),
+                        "Received fleet statistics from {}",
+                        envelope.from.short()
+                    ),
+                    Err(e) => warn!("Rejected fleet data from {}: {e}", envelope.from.short()),
+                }
+                Box::pin(async { None })
+            }),
+        )
+        .await;
+
+        Self {
+            node,
+            fleet,
+            sharing,
+        }
+    }
+
+    /// Send this node's aggregate to `peer`. Returns `false` when there was
+    /// nothing the policy allows sharing.
+    pub async fn share_with(&self, peer: &PeerId) -> Result<bool> {
+        let envelope = {
+            let fleet = self.fleet.lock().unwrap();
+            self.sharing
+                .lock()
+                .unwrap()
+                .share(&fleet, self.node.peer_id().clone(), peer.clone())
+        };
+        let Some(envelope) = envelope else {
+            return Ok(false);
+        };
+        self.node
+            .send_to_peer(peer, &envelope)
+            .await
+            .context("Failed to send fleet statistics")?;
+        Ok(true)
+    }
+
+    /// Send this node's aggregate to every connected trusted peer. Returns
+    /// the number of peers it was sent to.
+    pub async fn share_all(&self) -> Result<usize> {
+        let mut sent = 0;
+        for peer in self.node.connected_peers().await {
+            if self.node.security().is_trusted(&peer.id) && self.share_with(&peer.id).await? {
+                sent += 1;
+            }
+        }
+        Ok(sent)
+    }
+
+    /// What was exchanged with `peer`.
+    pub fn peer_view(&self, peer: &PeerId) -> Option<PeerFleetView> {
+        self.sharing.lock().unwrap().peer_view(peer).cloned()
+    }
+
+    /// The ledger for every peer.
+    pub fn peer_views(&self) -> Vec<PeerFleetView> {
+        self.sharing
+            .lock()
+            .unwrap()
+            .peer_views()
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Statistics received from the fleet, combined.
+    pub fn fleet_stats(&self) -> Vec<TaskModelStat> {
+        self.sharing.lock().unwrap().fleet_stats()
+    }
+
+    /// Privacy budget spent on releases so far, and the policy's total.
+    pub fn budget(&self) -> (f64, Option<f64>) {
+        let sharing = self.sharing.lock().unwrap();
+        (sharing.spent_epsilon(), sharing.policy().epsilon_budget)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hive_network::NodeKeypair;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use std::time::Duration;
+
+    fn exact() -> FleetSharingPolicy {
+        FleetSharingPolicy {
+            epsilon: None,
+            min_requests: 3,
+            ..FleetSharingPolicy::default()
+        }
+    }
+
+    fn record(fleet: &mut FleetLearningService, task: &str, model: &str, n: usize, quality: f64) {
+        for i in 0..n {
+            fleet.record_task_performance(task, model, i % 4 != 0, quality);
+        }
+    }
+
+    fn signed(keypair: &NodeKeypair, outcome_type: &str, data: serde_json::Value) -> Envelope {
+        let mut envelope = FleetSyncHelper::share_with_peer(
+            keypair.peer_id(),
+            PeerId::generate(),
+            outcome_type,
+            "",
+            data,
+            1.0,
+        );
+        envelope.sign(keypair);
+        envelope
+    }
+
+    #[test]
+    fn test_aggregate_releases_only_well_sampled_pairs() {
+        let mut fleet = FleetLearningService::new();
+        record(&mut fleet, "code_gen", "claude-sonnet-4", 8, 0.9);
+        record(&mut fleet, "code_gen", "gpt-4o-2024-08-06", 4, 0.5);
+        record(&mut fleet, "chat", "llama3:8b", 2, 1.0);
+        fleet.record_pattern(
+            hive_ai::PatternType::PromptPattern,
+            "refactor acme billing",
+            0.9,
+        );
+
+        let sharing = FleetSharing::new(exact());
+        let (aggregate, withheld) = sharing.aggregate(&fleet, &mut rand::rng());
+        assert_eq!(withheld, 1);
+        assert_eq!(aggregate.stats.len(), 2);
+        let claude = &aggregate.stats[0];
+        assert_eq!(claude.model_id, "claude-sonnet-4");
+        assert_eq!(claude.requests, 8);
+        assert!((claude.success_rate - 0.75).abs() < 1e-9);
+        assert!((claude.avg_quality - 0.9).abs() < 1e-9);
+
+        // Quality 0.9 * 8 and 0.5 * 4 average to 0.7667.
+        let routing = &aggregate.routing;
+        assert_eq!(routing.len(), 2);
+        assert!((routing[0].quality_delta - (0.9 - 2.3 / 3.0)).abs() < 1e-9);
+        assert!(routing[1].quality_delta < 0.0);
+
+        // Patterns are never part of what is shared.
+        let wire = serde_json::to_string(&aggregate).unwrap();
+        assert!(!wire.contains("acme"));
+    }
+
+    #[test]
+    fn test_shield_withholds_sensitive_labels() {
+        let mut fleet = FleetLearningService::new();
+        record(&mut fleet, "email alice@example.com", "claude", 5, 0.8);
+        record(
+            &mut fleet,
+            "code_gen",
+            &format!("AKIA{}", "IOSFODNN7EXAMPLE"),
+            5,
+            0.8,
+        );
+        record(&mut fleet, "debugging", "claude", 5, 0.8);
+
+        let sharing = FleetSharing::new(exact());
+        let (aggregate, withheld) = sharing.aggregate(&fleet, &mut rand::rng());
+        assert_eq!(withheld, 2);
+        assert_eq!(aggregate.stats.len(), 1);
+        assert_eq!(aggregate.stats[0].task_type, "debugging");
+    }
+
+    #[test]
+    fn test_noise_is_calibrated_and_bounded() {
+        let mut fleet = FleetLearningService::new();
+        record(&mut fleet, "code_gen", "claude", 40, 0.5);
+        let sharing = FleetSharing::new(FleetSharingPolicy {
+            epsilon: Some(1.0),
+            ..FleetSharingPolicy::default()
+        });
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let releases: Vec<TaskModelStat> = (0..2000)
+            .map(|_| sharing.aggregate(&fleet, &mut rng).0.stats.remove(0))
+            .collect();
+        assert!(releases.iter().any(|s| s.requests != 40));
+        assert!(releases.iter().any(|s| s.avg_quality != 0.5));
+        assert!(
+            releases
+                .iter()
+                .all(|s| (0.0..=1.0).contains(&s.avg_quality) && s.requests >= 1)
+        );
+
+        // Noise is zero-mean, so averages across releases converge.
+        let n = releases.len() as f64;
+        let quality = releases.iter().map(|s| s.avg_quality).sum::<f64>() / n;
+        let requests = releases.iter().map(|s| s.requests as f64).sum::<f64>() / n;
+        assert!((quality - 0.5).abs() < 0.03, "mean quality {quality}");
+        assert!((requests - 40.0).abs() < 1.0, "mean requests {requests}");
+    }
+
+    #[test]
+    fn test_one_noisy_release_per_window_is_sent_to_every_peer() {
+        let mut fleet = FleetLearningService::new();
+        record(&mut fleet, "code_gen", "claude", 40, 0.5);
+        let mut sharing = FleetSharing::new(FleetSharingPolicy::default());
+        let from = PeerId::generate();
+        let (b, c) = (PeerId::generate(), PeerId::generate());
+
+        let to_b = sharing.share(&fleet, from.clone(), b.clone()).unwrap();
+        let to_c = sharing.share(&fleet, from.clone(), c.clone()).unwrap();
+        assert_eq!(to_b.payload["data"], to_c.payload["data"]);
+        sharing.share(&fleet, from, b.clone()).unwrap();
+        assert_eq!(sharing.spent_epsilon(), 1.0);
+        assert_eq!(
+            sharing.peer_view(&b).unwrap().shared[1].aggregate,
+            sharing.peer_view(&c).unwrap().shared[0].aggregate
+        );
+    }
+
+    #[test]
+    fn test_sharing_stops_when_the_budget_is_spent() {
+        let mut fleet = FleetLearningService::new();
+        record(&mut fleet, "code_gen", "claude", 40, 0.5);
+        let mut sharing = FleetSharing::new(FleetSharingPolicy {
+            epsilon: Some(0.5),
+            epsilon_budget: Some(1.0),
+            window_secs: 0,
+            ..FleetSharingPolicy::default()
+        });
+        let (from, to) = (PeerId::generate(), PeerId::generate());
+
+        assert!(sharing.share(&fleet, from.clone(), to.clone()).is_some());
+        assert!(sharing.share(&fleet, from.clone(), to.clone()).is_some());
+        assert!(sharing.share(&fleet, from.clone(), to.clone()).is_none());
+        assert_eq!(sharing.spent_epsilon(), 1.0);
+        assert_eq!(sharing.peer_view(&to).unwrap().shared.len(), 2);
+
+        // Exact statistics carry no privacy cost.
+        let mut exact = FleetSharing::new(FleetSharingPolicy {
+            epsilon_budget: Some(0.0),
+            window_secs: 0,
+            ..exact()
+        });
+        assert!(exact.share(&fleet, from.clone(), to.clone()).is_some());
+        assert!(exact.share(&fleet, from, to).is_some());
+    }
+
+    #[test]
+    fn test_budget_and_release_survive_a_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fleet_sharing.json");
+        let mut fleet = FleetLearningService::new();
+        record(&mut fleet, "code_gen", "claude", 40, 0.5);
+        let (from, to) = (PeerId::generate(), PeerId::generate());
+
+        let mut sharing = FleetSharing::new(FleetSharingPolicy::default())
+            .with_state_file(&path)
+            .unwrap();
+        let before = sharing.share(&fleet, from.clone(), to.clone()).unwrap();
+
+        let mut restarted = FleetSharing::new(FleetSharingPolicy::default())
+            .with_state_file(&path)
+            .unwrap();
+        assert_eq!(restarted.spent_epsilon(), 1.0);
+        let after = restarted.share(&fleet, from, to).unwrap();
+        assert_eq!(before.payload["data"], after.payload["data"]);
+        assert_eq!(restarted.spent_epsilon(), 1.0);
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(
+            FleetSharing::new(FleetSharingPolicy::default())
+                .with_state_file(&path)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_receive_requires_signed_trusted_aggregates() {
+        let mut fleet = FleetLearningService::new();
+        record(&mut fleet, "code_gen", "claude", 5, 0.8);
+        let peer = NodeKeypair::generate();
+        let mut sharing = FleetSharing::new(exact());
+        let (aggregate, _) = sharing.aggregate(&fleet, &mut rand::rng());
+        let data = serde_json::to_value(&aggregate).unwrap();
+
+        // Unsigned and tampered envelopes are not charged to the peer.
+        let mut tampered = signed(&peer, AGGREGATE_OUTCOME, data.clone());
+        tampered.payload["data"]["stats"][0]["avg_quality"] = serde_json::json!(0.1);
+        assert!(sharing.receive(&tampered, true).is_err());
+        assert!(sharing.peer_view(&peer.peer_id()).is_none());
+
+        let envelope = signed(&peer, AGGREGATE_OUTCOME, data);
+        assert!(sharing.receive(&envelope, false).is_err());
+        let raw = signed(
+            &peer,
+            "model_preference",
+            serde_json::json!({"prompt": "x"}),
+        );
+        assert!(sharing.receive(&raw, true).is_err());
+        assert_eq!(sharing.receive(&envelope, true).unwrap(), aggregate);
+
+        let view = sharing.peer_view(&peer.peer_id()).unwrap();
+        assert_eq!(view.rejected, 2);
+        assert_eq!(view.received.len(), 1);
+        assert_eq!(sharing.fleet_stats(), aggregate.stats);
+    }
+
+    #[test]
+    fn test_received_sensitive_labels_are_dropped() {
+        let peer = NodeKeypair::generate();
+        let stat = |task: &str| TaskModelStat {
+            task_type: task.to_string(),
+            model_id: "claude".to_string(),
+            requests: 10,
+            success_rate: 1.5,
+            avg_quality: 0.9,
+        };
+        let aggregate = FleetAggregate {
+            stats: vec![stat("code_gen"), stat("call 555-867-5309 about billing")],
+            routing: Vec::new(),
+            epsilon: None,
+            generated_at: Utc::now(),
+        };
+        let envelope = signed(
+            &peer,
+            AGGREGATE_OUTCOME,
+            serde_json::to_value(&aggregate).unwrap(),
+        );
+
+        let mut sharing = FleetSharing::new(exact());
+        let received = sharing.receive(&envelope, true).unwrap();
+        assert_eq!(received.stats.len(), 1);
+        assert_eq!(received.stats[0].success_rate, 1.0);
+        let view = sharing.peer_view(&peer.peer_id()).unwrap();
+        assert_eq!(view.received[0].withheld, 1);
+    }
+
+    #[test]
+    fn test_fleet_stats_weight_peers_by_requests() {
+        let stat = |requests, avg_quality| TaskModelStat {
+            task_type: "chat".to_string(),
+            model_id: "claude".to_string(),
+            requests,
+            success_rate: 1.0,
+            avg_quality,
+        };
+        let merged = merge_stats([stat(30, 0.9), stat(10, 0.5)].iter());
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].requests, 40);
+        assert!((merged[0].avg_quality - 0.8).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_nodes_exchange_aggregates() {
+        use hive_network::{NetworkConfig, NodeIdentity};
+
+        let free_addr = || {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap()
+        };
+        let local = |addr| NetworkConfig {
+            listen_addr: addr,
+            discovery_enabled: false,
+            ..NetworkConfig::default()
+        };
+        let addr_b = free_addr();
+        let mut node_a = HiveNode::new(NodeIdentity::generate("a"), local(free_addr()));
+        let mut node_b = HiveNode::new(NodeIdentity::generate("b"), local(addr_b));
+        node_a.trust_peer(node_b.peer_id().clone(), "b");
+        node_b.trust_peer(node_a.peer_id().clone(), "a");
+        node_a.start().await.unwrap();
+        node_b.start().await.unwrap();
+        let (node_a, node_b) = (Arc::new(node_a), Arc::new(node_b));
+
+        let mut fleet_a = FleetLearningService::new();
+        record(&mut fleet_a, "code_gen", "claude", 12, 0.9);
+        let fleet_a = Arc::new(Mutex::new(fleet_a));
+        let fleet_b = Arc::new(Mutex::new(FleetLearningService::new()));
+        let sharing_a =
+            FleetSharingService::attach(Arc::clone(&node_a), fleet_a, FleetSharing::new(exact()))
+                .await;
+        let sharing_b =
+            FleetSharingService::attach(Arc::clone(&node_b), fleet_b, FleetSharing::new(exact()))
+                .await;
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        node_a.connect_to(&addr_b.to_string()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(sharing_a.share_all().await.unwrap(), 1);
+        // b has nothing worth sharing yet.
+        assert!(!sharing_b.share_with(node_a.peer_id()).await.unwrap());
+
+        for _ in 0..50 {
+            if !sharing_b.fleet_stats().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        let stats = sharing_b.fleet_stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].requests, 12);
+
+        let shared = sharing_a.peer_view(node_b.peer_id()).unwrap();
+        assert_eq!(shared.shared.len(), 1);
+        assert!(shared.received.is_empty());
+        let received = sharing_b.peer_view(node_a.peer_id()).unwrap();
+        assert_eq!(received.received[0].aggregate, shared.shared[0].aggregate);
+        assert_eq!(sharing_b.peer_views().len(), 1);
+    }
+}
@@ -376,6 +376,36 @@ pub trait AiExecutor: Send + Sync {
     async fn execute(&self, request: &ChatRequest) -> Result<ChatResponse, String>;
 }
 
+/// The app's executor: requests go through the service's model routing,
+/// routing rules and provider failover, like chat does. Rules match on the
+/// shield's classification of the request's messages.
+impl AiExecutor for hive_ai::AiService {
+    async fn execute(&self, request: &ChatRequest) -> Result<ChatResponse, String> {
+        let context = self.classification_context(&request.messages);
+        let (provider, routed) = self
+            .prepare_stream_with_context(
+                request.messages.clone(),
+                &request.model,
+                request.system_prompt.clone(),
+                request.tools.clone(),
+                Some(&context),
+            )
+            .map_err(|e| e.to_string())?;
+        let routed = ChatRequest {
+            max_tokens: request.max_tokens,
+            temperature: request.temperature,
+            ..routed
+        };
+        provider.chat(&routed).await.map_err(|e| e.to_string())
+    }
+}
+
+impl<E: AiExecutor> AiExecutor for Arc<E> {
+    async fn execute(&self, request: &ChatRequest) -> Result<ChatResponse, String> {
+        self.as_ref().execute(request).await
+    }
+}
+
 // ---------------------------------------------------------------------------
 // HiveMind Orchestrator
 // ---------------------------------------------------------------------------
@@ -375,7 +375,10 @@ pub fn wire_integration_handlers(services: IntegrationServices) -> Vec<(McpTool,
             let max_results = args["max_results"].as_u64().unwrap_or(10) as usize;
             let svc = Arc::clone(&svc);
             // search is sync
-            let results = svc.search("default", &query, max_results);
+            let results = match args["index"].as_str() {
+                Some(index) => svc.search(index, &query, max_results),
+                None => svc.search_all(&query, max_results),
+            };
             let items: Vec<serde_json::Value> = results.iter().map(|r| json!({
                 "title": r.title,
                 "url": r.page_url,
@@ -661,11 +664,12 @@ fn browse_url_tool() -> McpTool {
 fn search_docs_tool() -> McpTool {
     McpTool {
         name: "search_docs".into(),
-        description: "Search indexed documentation for the current project".into(),
+        description: "Search indexed documentation: crawled sites and offline doc sets (rustdoc, DevDocs, Dash)".into(),
         input_schema: json!({
             "type": "object",
             "properties": {
                 "query": { "type": "string", "description": "Search query" },
+                "index": { "type": "string", "description": "Only search this doc set, e.g. 'tokio@1.40.0' (default: all)" },
                 "max_results": { "type": "integer", "description": "Maximum results to return" }
             },
             "required": ["query"]
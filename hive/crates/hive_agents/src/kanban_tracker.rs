@@ -0,0 +1,324 @@
+//! Kanban Tracker — mirrors coordinator progress onto the Kanban board.
+//!
+//! Each planned task gets a card on the shared [`KanbanStore`] — the same
+//! store the Kanban panel writes through, so moves from both sides land on
+//! one board. The card moves to In Progress when the task starts and to
+//! Done (or Blocked, with the error as a comment) when it finishes. When the
+//! board is linked to a remote tracker (a `kanban_sync.json` mapping exists)
+//! every move is pushed to the linked Jira/Linear/Asana issue straight away.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use hive_core::kanban::{KanbanBoard, KanbanColumn, KanbanStore, Priority};
+use hive_integrations::project_management::{KanbanSync, ProjectManagementHub, SyncState};
+use tracing::debug;
+
+use crate::coordinator::{PlannedTask, TaskResult};
+
+/// Author recorded on comments the tracker adds to cards.
+const COMMENT_AUTHOR: &str = "Coordinator";
+
+/// Longest card title before the task description is cut.
+const MAX_TITLE_CHARS: usize = 80;
+
+struct RemoteSync {
+    hub: Arc<ProjectManagementHub>,
+    state_path: PathBuf,
+}
+
+/// Moves Kanban cards as coordinator tasks progress.
+pub struct KanbanTracker {
+    store: KanbanStore,
+    sync: Option<RemoteSync>,
+    /// Planned task id → card id.
+    cards: Mutex<HashMap<String, String>>,
+}
+
+impl KanbanTracker {
+    /// Track on the board behind `store`.
+    pub fn new(store: KanbanStore) -> Self {
+        Self {
+            store,
+            sync: None,
+            cards: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Push every move to the tracker linked by the mapping at `state_path`.
+    /// Without a mapping file the tracker only updates the local board.
+    pub fn with_sync(
+        mut self,
+        hub: Arc<ProjectManagementHub>,
+        state_path: impl Into<PathBuf>,
+    ) -> Self {
+        self.sync = Some(RemoteSync {
+            hub,
+            state_path: state_path.into(),
+        });
+        self
+    }
+
+    /// The card created for a planned task, if it has started.
+    pub fn card_for(&self, planned_task_id: &str) -> Option<String> {
+        self.cards
+            .lock()
+            .ok()
+            .and_then(|cards| cards.get(planned_task_id).cloned())
+    }
+
+    /// Create the task's card if needed and move it to In Progress.
+    pub async fn task_started(&self, task: &PlannedTask) -> Result<(), String> {
+        self.move_card(task, KanbanColumn::InProgress, None).await
+    }
+
+    /// Move the task's card to Done, or to Blocked with the error attached.
+    pub async fn task_finished(
+        &self,
+        task: &PlannedTask,
+        result: &TaskResult,
+    ) -> Result<(), String> {
+        if result.success {
+            self.move_card(task, KanbanColumn::Done, None).await
+        } else {
+            let error = result.error.as_deref().unwrap_or("unknown error");
+            let note = format!("Task failed: {error}");
+            self.move_card(task, KanbanColumn::Blocked, Some(note))
+                .await
+        }
+    }
+
+    async fn move_card(
+        &self,
+        task: &PlannedTask,
+        column: KanbanColumn,
+        note: Option<String>,
+    ) -> Result<(), String> {
+        // The local move is saved before the push so it survives an
+        // unreachable tracker.
+        let card_id = self
+            .store
+            .update(|board| {
+                let card_id = self.ensure_card(board, task)?;
+                if let Some(note) = note {
+                    board.add_comment(&card_id, COMMENT_AUTHOR, note)?;
+                }
+                board.move_task(&card_id, column)?;
+                Ok(card_id)
+            })
+            .map_err(|e| format!("Failed to move card: {e:#}"))?;
+        debug!(task_id = %task.id, card_id = %card_id, column = ?column, "moved coordinator card");
+        self.push(&card_id).await
+    }
+
+    fn ensure_card(&self, board: &mut KanbanBoard, task: &PlannedTask) -> anyhow::Result<String> {
+        let mut cards = self
+            .cards
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+        if let Some(card_id) = cards
+            .get(&task.id)
+            .filter(|id| board.get_task(id).is_some())
+        {
+            return Ok(card_id.clone());
+        }
+        let card = board.add_task(
+            card_title(&task.description),
+            Some(task.description.clone()),
+            card_priority(task.priority),
+        );
+        if let Some(card) = board.get_task_mut(&card.id) {
+            card.labels.push(task.persona.to_string());
+        }
+        cards.insert(task.id.clone(), card.id.clone());
+        Ok(card.id)
+    }
+
+    /// Push the card to its linked issue. The push runs on a snapshot of the
+    /// board so the store is not locked across network calls; whatever the
+    /// sync merged into the card is written back only if nobody edited the
+    /// card in the meantime (the next push reconciles it otherwise).
+    async fn push(&self, card_id: &str) -> Result<(), String> {
+        let Some(sync) = &self.sync else {
+            return Ok(());
+        };
+        if !sync.state_path.exists() {
+            return Ok(());
+        }
+        let mut board = self
+            .store
+            .load()
+            .map_err(|e| format!("Failed to load kanban board: {e:#}"))?;
+        let pushed_from = board.get_task(card_id).map(|card| card.updated_at);
+        let mut state = SyncState::load(&sync.state_path).map_err(|e| format!("{e:#}"))?;
+        let result = KanbanSync::new(&sync.hub)
+            .push_task(&mut board, &mut state, card_id)
+            .await;
+        state
+            .save(&sync.state_path)
+            .map_err(|e| format!("Failed to save kanban sync state: {e:#}"))?;
+        if let Some(merged) = board.get_task(card_id).cloned() {
+            self.store
+                .update(|stored| {
+                    if let Some(card) = stored.get_task_mut(card_id)
+                        && Some(card.updated_at) == pushed_from
+                    {
+                        *card = merged;
+                    }
+                    Ok(())
+                })
+                .map_err(|e| format!("Failed to save kanban board: {e:#}"))?;
+        }
+        let report = result.map_err(|e| format!("Failed to push card to tracker: {e:#}"))?;
+        match report.conflicts.first() {
+            Some(conflict) => Err(format!(
+                "Card {card_id} conflicts with its issue on {:?}",
+                conflict.fields
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// First line of the description, shortened to fit a card.
+fn card_title(description: &str) -> String {
+    let line = description.lines().next().unwrap_or_default().trim();
+    if line.chars().count() <= MAX_TITLE_CHARS {
+        return line.to_string();
+    }
+    let cut: String = line.chars().take(MAX_TITLE_CHARS - 1).collect();
+    format!("{}…", cut.trim_end())
+}
+
+/// Planned priorities run from 1 (highest) to 5 (lowest).
+fn card_priority(priority: u8) -> Priority {
+    match priority {
+        0 | 1 => Priority::Critical,
+        2 => Priority::High,
+        3 => Priority::Medium,
+        _ => Priority::Low,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::personas::PersonaKind;
+    use hive_integrations::project_management::{BoardLink, PMPlatform};
+
+    fn temp_dir() -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("hive_kanban_tracker_test_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn planned(id: &str, description: &str) -> PlannedTask {
+        PlannedTask {
+            id: id.into(),
+            description: description.into(),
+            persona: PersonaKind::Implement,
+            dependencies: vec![],
+            priority: 2,
+        }
+    }
+
+    fn result(task_id: &str, error: Option<&str>) -> TaskResult {
+        TaskResult {
+            task_id: task_id.into(),
+            persona: PersonaKind::Implement,
+            output: String::new(),
+            cost: 0.0,
+            duration_ms: 1,
+            success: error.is_none(),
+            error: error.map(String::from),
+        }
+    }
+
+    #[tokio::test]
+    async fn cards_follow_task_lifecycle() {
+        let dir = temp_dir();
+        let board_path = dir.join("kanban.json");
+        let store = KanbanStore::new(&board_path);
+        let tracker = KanbanTracker::new(store.clone());
+        let ok = planned("task-1", "Add login form\nwith validation");
+        let failing = planned("task-2", "Wire up OAuth");
+
+        tracker.task_started(&ok).await.unwrap();
+        let card_id = tracker.card_for("task-1").unwrap();
+        let board = KanbanBoard::load_from_file(&board_path).unwrap();
+        let card = board.get_task(&card_id).unwrap();
+        assert_eq!(card.title, "Add login form");
+        assert_eq!(card.column, KanbanColumn::InProgress);
+        assert_eq!(card.priority, Priority::High);
+        assert_eq!(card.labels, ["Implement"]);
+
+        // A card added from the panel between moves is kept.
+        store
+            .update(|board| Ok(board.add_task("From the panel", None, Priority::Low)))
+            .unwrap();
+        tracker
+            .task_finished(&ok, &result("task-1", None))
+            .await
+            .unwrap();
+        tracker.task_started(&failing).await.unwrap();
+        tracker
+            .task_finished(&failing, &result("task-2", Some("token expired")))
+            .await
+            .unwrap();
+
+        let board = KanbanBoard::load_from_file(&board_path).unwrap();
+        assert_eq!(board.all_tasks().len(), 3);
+        assert_eq!(board.get_task(&card_id).unwrap().column, KanbanColumn::Done);
+        let blocked = board
+            .get_task(&tracker.card_for("task-2").unwrap())
+            .unwrap();
+        assert_eq!(blocked.column, KanbanColumn::Blocked);
+        assert_eq!(blocked.comments[0].content, "Task failed: token expired");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn unreachable_tracker_still_moves_card_locally() {
+        let dir = temp_dir();
+        let board_path = dir.join("kanban.json");
+        let state_path = dir.join("kanban_sync.json");
+        SyncState::new(BoardLink {
+            platform: PMPlatform::Jira,
+            project_id: "HIVE".into(),
+        })
+        .save(&state_path)
+        .unwrap();
+        // No Jira provider is registered, so every push fails.
+        let tracker = KanbanTracker::new(KanbanStore::new(&board_path))
+            .with_sync(Arc::new(ProjectManagementHub::new()), &state_path);
+
+        let task = planned("task-1", "Ship it");
+        let err = tracker.task_started(&task).await.unwrap_err();
+        assert!(err.contains("Failed to push card to tracker"), "{err}");
+
+        let board = KanbanBoard::load_from_file(&board_path).unwrap();
+        let card = board
+            .get_task(&tracker.card_for("task-1").unwrap())
+            .unwrap();
+        assert_eq!(card.column, KanbanColumn::InProgress);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn card_title_is_first_line_and_bounded() {
+        assert_eq!(card_title("Short\nmore"), "Short");
+        let long = "x".repeat(200);
+        let title = card_title(&long);
+        assert_eq!(title.chars().count(), MAX_TITLE_CHARS);
+        assert!(title.ends_with('…'));
+    }
+
+    #[test]
+    fn card_priority_maps_planned_scale() {
+        assert_eq!(card_priority(1), Priority::Critical);
+        assert_eq!(card_priority(3), Priority::Medium);
+        assert_eq!(card_priority(5), Priority::Low);
+    }
+}
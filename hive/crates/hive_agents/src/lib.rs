@@ -3,17 +3,22 @@ pub mod automation;
 pub mod collective_memory;
 pub mod competence_detection;
 pub mod coordinator;
+pub mod fleet_sharing;
 pub mod guardian;
 pub mod heartbeat;
 pub mod hiveloop;
 pub mod hivemind;
 pub mod integration_tools;
+pub mod kanban_tracker;
 pub mod knowledge_acquisition;
 pub mod mcp_client;
 pub mod mcp_server;
+pub mod messaging_bot;
 pub mod persistence;
 pub mod personas;
 pub mod queen;
+pub mod remote_exec;
+pub mod replication;
 pub mod skill_authoring;
 pub mod skill_marketplace;
 pub mod skills;
@@ -29,7 +34,7 @@ pub use auto_commit::{AutoCommitConfig, AutoCommitService, CommitResult};
 pub use automation::{
     ActionType, AutomationService, Condition, ConditionOp, TriggerType, Workflow,
     WorkflowLoadReport, WorkflowRunResult, WorkflowStatus, WorkflowStep, BUILTIN_DOGFOOD_WORKFLOW_ID,
-    USER_WORKFLOW_DIR,
+    USER_WORKFLOW_DIR, dispatch_webhook_workflows,
 };
 pub use collective_memory::{CollectiveMemory, MemoryCategory, MemoryEntry, MemoryStats};
 pub use competence_detection::{
@@ -39,10 +44,22 @@ pub use competence_detection::{
 pub use coordinator::{
     Coordinator, CoordinatorConfig, CoordinatorResult, PlannedTask, TaskPlan, TaskResult,
 };
+pub use fleet_sharing::{
+    FleetAggregate, FleetExchange, FleetSharing, FleetSharingPolicy, FleetSharingService,
+    PeerFleetView, RoutingAdjustment, TaskModelStat,
+};
 pub use heartbeat::{AgentHeartbeat, HeartbeatService};
+pub use kanban_tracker::KanbanTracker;
+pub use messaging_bot::{BotConfig, BotSettings, BotTarget, MessagingBot};
 pub use persistence::{AgentPersistenceService, AgentSnapshot, CompletedTask};
 pub use personas::{Persona, PersonaKind, PersonaRegistry, PromptOverride, execute_with_persona};
 pub use queen::Queen;
+pub use remote_exec::{
+    ExecutorCost, LocalModel, NodeCapabilities, RelayMessage, RemoteExecService, RemoteOutcome,
+    RemoteOutput, RemoteProgress, RemoteStage, RemoteTask, RemoteTaskRequest, RemoteTaskResult,
+    RemoteWork,
+};
+pub use replication::{ReplicaStore, Replicated, ReplicationService, SyncOutcome};
 pub use knowledge_acquisition::{
     AcquisitionResult, CodeBlock, KnowledgeAcquisitionAgent, KnowledgeConfig, KnowledgePage,
     KnowledgeSummary,
@@ -0,0 +1,547 @@
+//! Bot mode — answer mentions and direct messages on a messaging platform.
+//!
+//! A [`MessagingBot`] consumes a provider's real-time
+//! [`event_stream`](MessagingProvider::event_stream), decides whether each
+//! message is addressed to it, runs the text through the persona or skill
+//! configured for that channel, and replies in the message's thread.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use hive_ai::types::ModelTier;
+use hive_core::config::HiveConfig;
+use hive_integrations::messaging::{
+    DiscordProvider, MessageEvent, MessagingProvider, Platform, SentMessage, SlackProvider,
+    TelegramProvider,
+};
+
+use crate::hivemind::AiExecutor;
+use crate::personas::{Persona, PersonaKind, PersonaRegistry, execute_with_persona};
+use crate::skills::SkillsRegistry;
+
+/// Token limit for replies produced by a skill.
+const SKILL_MAX_TOKENS: u32 = 4096;
+
+// ---------------------------------------------------------------------------
+// Configuration
+// ---------------------------------------------------------------------------
+
+/// Who answers a message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BotTarget {
+    /// A built-in or custom persona.
+    Persona(PersonaKind),
+    /// An installed skill, by name (with or without the leading `/`).
+    Skill(String),
+}
+
+impl Default for BotTarget {
+    fn default() -> Self {
+        Self::Persona(PersonaKind::Investigate)
+    }
+}
+
+/// What the bot answers and with whom.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotConfig {
+    /// Answer messages that mention the bot.
+    pub respond_to_mentions: bool,
+    /// Answer direct messages.
+    pub respond_to_direct: bool,
+    /// Channels the bot may answer mentions in. Empty allows every channel.
+    #[serde(default)]
+    pub allowed_channels: HashSet<String>,
+    /// Users (author ids) who may direct-message the bot. Empty allows
+    /// everyone who can reach it.
+    #[serde(default)]
+    pub allowed_users: HashSet<String>,
+    /// Target used when a channel has no route of its own.
+    #[serde(default)]
+    pub default_target: BotTarget,
+    /// Per-channel targets, keyed by channel id.
+    #[serde(default)]
+    pub channel_routes: HashMap<String, BotTarget>,
+}
+
+impl Default for BotConfig {
+    fn default() -> Self {
+        Self {
+            respond_to_mentions: true,
+            respond_to_direct: true,
+            allowed_channels: HashSet::new(),
+            allowed_users: HashSet::new(),
+            default_target: BotTarget::default(),
+            channel_routes: HashMap::new(),
+        }
+    }
+}
+
+impl BotConfig {
+    /// Whether the bot should answer this event.
+    pub fn should_respond(&self, event: &MessageEvent) -> bool {
+        if event.text.trim().is_empty() {
+            return false;
+        }
+        if event.is_direct {
+            return self.respond_to_direct
+                && (self.allowed_users.is_empty()
+                    || self.allowed_users.contains(&event.message.author));
+        }
+        self.respond_to_mentions
+            && event.mentions_bot
+            && (self.allowed_channels.is_empty()
+                || self.allowed_channels.contains(&event.message.channel_id))
+    }
+
+    /// The target for a channel.
+    pub fn target_for(&self, channel_id: &str) -> &BotTarget {
+        self.channel_routes
+            .get(channel_id)
+            .unwrap_or(&self.default_target)
+    }
+}
+
+/// What `~/.hive/messaging_bot.json` holds: the platform to connect to,
+/// its tokens, and the [`BotConfig`] fields.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotSettings {
+    pub platform: Platform,
+    pub bot_token: String,
+    /// Slack app-level token (`xapp-…`) that Socket Mode connects with.
+    #[serde(default)]
+    pub app_token: Option<String>,
+    /// Discord guild the bot serves.
+    #[serde(default)]
+    pub guild_id: Option<String>,
+    #[serde(flatten)]
+    pub bot: BotConfig,
+}
+
+impl BotSettings {
+    /// `~/.hive/messaging_bot.json`
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(HiveConfig::base_dir()?.join("messaging_bot.json"))
+    }
+
+    /// Read the settings at `path`, or `None` if the file does not exist.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&raw)
+            .map(Some)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// A provider for the configured platform that can stream events.
+    pub fn provider(&self) -> Result<Arc<dyn MessagingProvider>> {
+        Ok(match self.platform {
+            Platform::Slack => {
+                let app_token = self
+                    .app_token
+                    .as_deref()
+                    .context("Slack bot needs an app_token for Socket Mode")?;
+                Arc::new(SlackProvider::new(&self.bot_token)?.with_app_token(app_token))
+            }
+            Platform::Discord => {
+                let guild_id = self
+                    .guild_id
+                    .as_deref()
+                    .context("Discord bot needs a guild_id")?;
+                Arc::new(DiscordProvider::new(&self.bot_token, guild_id)?)
+            }
+            Platform::Telegram => Arc::new(TelegramProvider::new(&self.bot_token)?),
+            other => anyhow::bail!("Bot mode is not supported on {other}"),
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Bot
+// ---------------------------------------------------------------------------
+
+/// Answers a messaging provider's mentions and DMs with a persona or skill.
+pub struct MessagingBot<E: AiExecutor> {
+    provider: Arc<dyn MessagingProvider>,
+    executor: E,
+    config: BotConfig,
+    personas: PersonaRegistry,
+    skills: SkillsRegistry,
+}
+
+impl<E: AiExecutor> MessagingBot<E> {
+    pub fn new(provider: Arc<dyn MessagingProvider>, executor: E, config: BotConfig) -> Self {
+        Self {
+            provider,
+            executor,
+            config,
+            personas: PersonaRegistry::new(),
+            skills: SkillsRegistry::new(),
+        }
+    }
+
+    /// Resolve persona targets against `personas` instead of the built-ins.
+    pub fn with_personas(mut self, personas: PersonaRegistry) -> Self {
+        self.personas = personas;
+        self
+    }
+
+    /// Resolve skill targets against `skills` instead of the built-ins.
+    pub fn with_skills(mut self, skills: SkillsRegistry) -> Self {
+        self.skills = skills;
+        self
+    }
+
+    pub fn config(&self) -> &BotConfig {
+        &self.config
+    }
+
+    /// Answer events from the provider's stream until it ends. Failures to
+    /// answer one message are logged and do not stop the bot.
+    pub async fn run(&self) -> Result<()> {
+        let platform = self.provider.platform();
+        let mut stream = self
+            .provider
+            .event_stream()
+            .await
+            .with_context(|| format!("failed to open {platform} event stream"))?;
+        info!(%platform, "Messaging bot listening");
+
+        while let Some(event) = stream.next().await {
+            if let Err(e) = self.handle(&event).await {
+                warn!(
+                    %platform,
+                    channel = %event.message.channel_id,
+                    "Bot failed to answer message: {e:#}"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Answer a single event if it is addressed to the bot. Returns the
+    /// reply, or `None` when the event was ignored.
+    pub async fn handle(&self, event: &MessageEvent) -> Result<Option<SentMessage>> {
+        if !self.config.should_respond(event) {
+            return Ok(None);
+        }
+        let channel = &event.message.channel_id;
+        let persona = self.resolve(self.config.target_for(channel))?;
+        debug!(channel = %channel, persona = %persona.name, "Bot answering message");
+
+        let output = execute_with_persona(&persona, &event.text, &self.executor, None).await;
+        if !output.success {
+            anyhow::bail!(
+                "{} failed: {}",
+                persona.name,
+                output.error.unwrap_or_else(|| "unknown error".into())
+            );
+        }
+
+        let sent = match &event.thread_id {
+            Some(thread_id) => {
+                self.provider
+                    .reply_in_thread(channel, thread_id, &output.content)
+                    .await?
+            }
+            None => self.provider.send_message(channel, &output.content).await?,
+        };
+        Ok(Some(sent))
+    }
+
+    /// The persona that answers for `target`. A skill runs as a persona whose
+    /// system prompt is the skill's instructions.
+    ///
+    /// Personas use their promoted prompt rather than an experiment arm: the
+    /// bot sees no reaction to its replies, so it has no outcome to report.
+    fn resolve(&self, target: &BotTarget) -> Result<Persona> {
+        match target {
+            BotTarget::Persona(kind) => self
+                .personas
+                .get_evolved(kind)
+                .with_context(|| format!("unknown persona {kind}")),
+            BotTarget::Skill(name) => {
+                let name = name.strip_prefix('/').unwrap_or(name);
+                let instructions = self.skills.dispatch(name)?;
+                Ok(Persona {
+                    kind: PersonaKind::Custom(name.to_string()),
+                    name: format!("/{name}"),
+                    system_prompt: instructions.to_string(),
+                    model_tier: ModelTier::Mid,
+                    description: format!("Skill /{name}"),
+                    tools: Vec::new(),
+                    max_tokens: SKILL_MAX_TOKENS,
+                })
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::personas::PromptOverride;
+    use async_trait::async_trait;
+    use chrono::Utc;
+    use hive_ai::types::{ChatRequest, ChatResponse, FinishReason, TokenUsage};
+    use hive_integrations::messaging::{Channel, IncomingMessage, MessageStream, Platform};
+    use std::sync::Mutex;
+
+    /// Replies with the system prompt and the user text, separated by `|`.
+    struct EchoExecutor;
+
+    impl AiExecutor for EchoExecutor {
+        async fn execute(&self, request: &ChatRequest) -> Result<ChatResponse, String> {
+            let system = request.system_prompt.clone().unwrap_or_default();
+            Ok(ChatResponse {
+                content: format!("{system}|{}", request.messages[0].content),
+                model: "mock-model".into(),
+                usage: TokenUsage::default(),
+                finish_reason: FinishReason::Stop,
+                thinking: None,
+                tool_calls: None,
+            })
+        }
+    }
+
+    /// Streams a fixed set of events and records replies instead of
+    /// sending them.
+    #[derive(Default)]
+    struct RecordingProvider {
+        incoming: Mutex<Vec<MessageEvent>>,
+        sent: Mutex<Vec<(String, Option<String>, String)>>,
+    }
+
+    impl RecordingProvider {
+        fn record(&self, channel: &str, thread: Option<&str>, text: &str) -> SentMessage {
+            self.sent
+                .lock()
+                .unwrap()
+                .push((channel.into(), thread.map(String::from), text.into()));
+            SentMessage {
+                id: "reply".into(),
+                channel_id: channel.into(),
+                timestamp: Utc::now(),
+            }
+        }
+
+        fn replies(&self) -> Vec<(String, Option<String>, String)> {
+            self.sent.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl MessagingProvider for RecordingProvider {
+        fn platform(&self) -> Platform {
+            Platform::Slack
+        }
+        async fn send_message(&self, channel: &str, text: &str) -> Result<SentMessage> {
+            Ok(self.record(channel, None, text))
+        }
+        async fn list_channels(&self) -> Result<Vec<Channel>> {
+            Ok(vec![])
+        }
+        async fn get_messages(&self, _: &str, _: u32) -> Result<Vec<IncomingMessage>> {
+            Ok(vec![])
+        }
+        async fn add_reaction(&self, _: &str, _: &str, _: &str) -> Result<()> {
+            Ok(())
+        }
+        async fn search_messages(&self, _: &str, _: u32) -> Result<Vec<IncomingMessage>> {
+            Ok(vec![])
+        }
+        async fn event_stream(&self) -> Result<MessageStream> {
+            let events = std::mem::take(&mut *self.incoming.lock().unwrap());
+            Ok(MessageStream::spawn(|tx| async move {
+                for event in events {
+                    let _ = tx.send(event).await;
+                }
+            }))
+        }
+        async fn reply_in_thread(
+            &self,
+            channel: &str,
+            thread_id: &str,
+            text: &str,
+        ) -> Result<SentMessage> {
+            Ok(self.record(channel, Some(thread_id), text))
+        }
+    }
+
+    fn event(channel: &str, text: &str, is_direct: bool, mentions_bot: bool) -> MessageEvent {
+        MessageEvent {
+            message: IncomingMessage {
+                id: "1.0".into(),
+                channel_id: channel.into(),
+                author: "alice".into(),
+                content: text.into(),
+                timestamp: Utc::now(),
+                attachments: vec![],
+                platform: Platform::Slack,
+            },
+            thread_id: Some("1.0".into()),
+            is_direct,
+            mentions_bot,
+            text: text.into(),
+        }
+    }
+
+    fn persona_prompt(kind: PersonaKind) -> String {
+        PersonaRegistry::new()
+            .get(&kind)
+            .unwrap()
+            .system_prompt
+            .clone()
+    }
+
+    #[test]
+    fn should_respond_honours_mentions_dms_and_allowlist() {
+        let mut config = BotConfig::default();
+        assert!(config.should_respond(&event("C1", "hi", false, true)));
+        assert!(!config.should_respond(&event("C1", "chatter", false, false)));
+        assert!(config.should_respond(&event("D1", "hi", true, false)));
+        assert!(!config.should_respond(&event("C1", "  ", false, true)));
+
+        config.allowed_channels.insert("C2".into());
+        assert!(!config.should_respond(&event("C1", "hi", false, true)));
+        assert!(config.should_respond(&event("C2", "hi", false, true)));
+        assert!(config.should_respond(&event("D1", "hi", true, false)));
+
+        config.allowed_users.insert("bob".into());
+        assert!(!config.should_respond(&event("D1", "hi", true, false)));
+        config.allowed_users.insert("alice".into());
+        assert!(config.should_respond(&event("D1", "hi", true, false)));
+
+        config.respond_to_direct = false;
+        assert!(!config.should_respond(&event("D1", "hi", true, false)));
+    }
+
+    #[test]
+    fn settings_build_the_configured_provider() {
+        let settings: BotSettings = serde_json::from_value(serde_json::json!({
+            "platform": "slack",
+            "bot_token": "xoxb-1",
+            "respond_to_mentions": true,
+            "respond_to_direct": true,
+            "allowed_users": ["U1"],
+        }))
+        .unwrap();
+        assert!(settings.bot.allowed_users.contains("U1"));
+        assert!(settings.provider().is_err());
+
+        let settings = BotSettings {
+            app_token: Some("xapp-1".into()),
+            ..settings
+        };
+        assert_eq!(settings.provider().unwrap().platform(), Platform::Slack);
+    }
+
+    #[tokio::test]
+    async fn handle_routes_by_channel_and_replies_in_thread() {
+        let provider = Arc::new(RecordingProvider::default());
+        let mut config = BotConfig::default();
+        config.channel_routes.insert(
+            "C-review".into(),
+            BotTarget::Persona(PersonaKind::CodeReview),
+        );
+        config
+            .channel_routes
+            .insert("C-search".into(), BotTarget::Skill("/web-search".into()));
+        let bot = MessagingBot::new(provider.clone(), EchoExecutor, config);
+
+        for channel in ["C-review", "C-search", "C-other"] {
+            let reply = bot.handle(&event(channel, "status?", false, true)).await;
+            assert!(reply.unwrap().is_some());
+        }
+        let ignored = bot.handle(&event("C-other", "chatter", false, false)).await;
+        assert!(ignored.unwrap().is_none());
+
+        let sent = provider.replies();
+        assert_eq!(sent.len(), 3);
+        assert_eq!(sent[0].0, "C-review");
+        assert_eq!(sent[0].1.as_deref(), Some("1.0"));
+        assert_eq!(
+            sent[0].2,
+            format!("{}|status?", persona_prompt(PersonaKind::CodeReview))
+        );
+        let web_search = SkillsRegistry::new()
+            .dispatch("web-search")
+            .unwrap()
+            .to_string();
+        assert_eq!(sent[1].2, format!("{web_search}|status?"));
+        assert_eq!(
+            sent[2].2,
+            format!("{}|status?", persona_prompt(PersonaKind::Investigate))
+        );
+    }
+
+    /// Promotes one prompt and would serve every conversation an experiment arm.
+    struct ExperimentOverride;
+
+    impl PromptOverride for ExperimentOverride {
+        fn get_prompt(&self, _kind: &PersonaKind) -> Option<String> {
+            Some("promoted".into())
+        }
+
+        fn serve_prompt(&self, _kind: &PersonaKind, _conversation_id: &str) -> Option<String> {
+            Some("arm".into())
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_answers_with_the_promoted_prompt_not_an_experiment_arm() {
+        let provider = Arc::new(RecordingProvider::default());
+        let mut personas = PersonaRegistry::new();
+        personas.set_prompt_override(Arc::new(ExperimentOverride));
+        let bot = MessagingBot::new(provider.clone(), EchoExecutor, BotConfig::default())
+            .with_personas(personas);
+
+        bot.handle(&event("C1", "status?", false, true))
+            .await
+            .unwrap();
+        assert_eq!(provider.replies()[0].2, "promoted|status?");
+    }
+
+    #[tokio::test]
+    async fn handle_rejects_unknown_skill() {
+        let provider = Arc::new(RecordingProvider::default());
+        let config = BotConfig {
+            default_target: BotTarget::Skill("no-such-skill".into()),
+            ..BotConfig::default()
+        };
+        let bot = MessagingBot::new(provider.clone(), EchoExecutor, config);
+        assert!(bot.handle(&event("C1", "hi", false, true)).await.is_err());
+        assert!(provider.replies().is_empty());
+    }
+
+    #[tokio::test]
+    async fn run_answers_until_stream_ends() {
+        let provider = Arc::new(RecordingProvider::default());
+        provider.incoming.lock().unwrap().extend([
+            event("C1", "first", false, true),
+            event("C1", "ignored", false, false),
+            event("D1", "second", true, false),
+        ]);
+        let bot = MessagingBot::new(provider.clone(), EchoExecutor, BotConfig::default());
+        bot.run().await.unwrap();
+
+        let texts: Vec<_> = provider
+            .replies()
+            .into_iter()
+            .map(|(_, _, text)| text.rsplit('|').next().unwrap().to_string())
+            .collect();
+        assert_eq!(texts, ["first", "second"]);
+    }
+}
@@ -209,6 +209,37 @@ fn code_review_persona() -> Persona {
 /// kind, or `None` to use the built-in default.
 pub trait PromptOverride: Send + Sync {
     fn get_prompt(&self, kind: &PersonaKind) -> Option<String>;
+
+    /// The prompt to serve for `kind` in one conversation. Overrides that
+    /// run prompt experiments pin each conversation to a variant here.
+    fn serve_prompt(&self, kind: &PersonaKind, conversation_id: &str) -> Option<String> {
+        let _ = conversation_id;
+        self.get_prompt(kind)
+    }
+}
+
+/// Learned prompts, keyed by the persona's display name. Serving goes
+/// through the prompt evolver so running A/B experiments see every
+/// conversation they assign.
+impl PromptOverride for hive_learn::LearningService {
+    fn get_prompt(&self, kind: &PersonaKind) -> Option<String> {
+        self.prompt_evolver
+            .get_prompt(&kind.to_string())
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to load learned prompt for {kind}: {e}");
+                None
+            })
+    }
+
+    fn serve_prompt(&self, kind: &PersonaKind, conversation_id: &str) -> Option<String> {
+        match hive_learn::LearningService::serve_prompt(self, &kind.to_string(), conversation_id) {
+            Ok(version) => version.map(|v| v.prompt_text),
+            Err(e) => {
+                tracing::warn!("Failed to serve learned prompt for {kind}: {e}");
+                None
+            }
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -285,6 +316,19 @@ impl PersonaRegistry {
         Some(persona.clone())
     }
 
+    /// Like `get_evolved()`, but lets the override pick the prompt for one
+    /// conversation, so prompt experiments can assign and track it.
+    pub fn serve(&self, kind: &PersonaKind, conversation_id: &str) -> Option<Persona> {
+        let persona = self.get(kind)?;
+        let mut served = persona.clone();
+        if let Some(ref provider) = self.prompt_override
+            && let Some(prompt) = provider.serve_prompt(kind, conversation_id)
+        {
+            served.system_prompt = prompt;
+        }
+        Some(served)
+    }
+
     /// Register a custom persona. Overwrites any existing custom persona
     /// with the same kind.
     pub fn register_custom(&mut self, persona: Persona) {
@@ -705,4 +749,34 @@ mod tests {
         let unknown = estimate_persona_cost("local-model", &usage);
         assert_eq!(unknown, 0.0);
     }
+
+    #[test]
+    fn serve_pins_conversation_to_experiment_arm() {
+        let learning = std::sync::Arc::new(hive_learn::LearningService::in_memory().unwrap());
+        learning
+            .accept_prompt_refinement("Implement", "Control.")
+            .unwrap();
+        let exp = learning
+            .propose_prompt_refinement("Implement", "Candidate.", 0.5)
+            .unwrap();
+        let mut registry = PersonaRegistry::new();
+        registry.set_prompt_override(learning.clone());
+
+        let arm = exp.arm_for("conv-7").unwrap();
+        let expected = if arm == exp.candidate_version {
+            "Candidate."
+        } else {
+            "Control."
+        };
+        let served = registry.serve(&PersonaKind::Implement, "conv-7").unwrap();
+        assert_eq!(served.system_prompt, expected);
+        assert_eq!(
+            learning
+                .prompt_evolver
+                .get_prompt("Implement")
+                .unwrap()
+                .as_deref(),
+            Some("Control.")
+        );
+    }
 }
@@ -9,7 +9,7 @@
 use std::collections::HashSet;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use hive_ai::types::{ChatMessage, ChatRequest, ChatResponse, MessageRole, ModelTier};
 
@@ -18,6 +18,8 @@ use crate::coordinator::{Coordinator, CoordinatorConfig, CoordinatorResult};
 use crate::hivemind::{
     AiExecutor, HiveMind, HiveMindConfig, OrchestrationResult, default_model_for_tier,
 };
+use crate::kanban_tracker::KanbanTracker;
+use crate::remote_exec::{RemoteExecService, RemoteWork};
 use crate::swarm::{
     InnerResult, OrchestrationMode, SwarmConfig, SwarmPlan, SwarmResult, SwarmStatus,
     SwarmStatusCallback, TeamObjective, TeamResult, TeamStatus,
@@ -53,6 +55,8 @@ pub struct Queen<E: AiExecutor> {
     executor: Arc<E>,
     memory: Option<Arc<CollectiveMemory>>,
     status_callback: Option<SwarmStatusCallback>,
+    tracker: Option<Arc<KanbanTracker>>,
+    remote: Option<Arc<RemoteExecService>>,
     /// Accumulated cost stored as the bit-pattern of an f64 so we can use
     /// atomic operations without a mutex.
     accumulated_cost: AtomicU64,
@@ -66,6 +70,8 @@ impl<E: AiExecutor + 'static> Queen<E> {
             executor,
             memory: None,
             status_callback: None,
+            tracker: None,
+            remote: None,
             accumulated_cost: AtomicU64::new(0f64.to_bits()),
         }
     }
@@ -76,6 +82,19 @@ impl<E: AiExecutor + 'static> Queen<E> {
         self
     }
 
+    /// Mirror the tasks of coordinator-mode teams onto Kanban cards.
+    pub fn with_tracker(mut self, tracker: Arc<KanbanTracker>) -> Self {
+        self.tracker = Some(tracker);
+        self
+    }
+
+    /// Offload team objectives to trusted peers that can run them. Teams no
+    /// peer takes run locally.
+    pub fn with_remote(mut self, remote: Arc<RemoteExecService>) -> Self {
+        self.remote = Some(remote);
+        self
+    }
+
     /// Register a callback for swarm-level status updates.
     pub fn with_status_callback(mut self, cb: SwarmStatusCallback) -> Self {
         self.status_callback = Some(cb);
@@ -303,6 +322,10 @@ impl<E: AiExecutor + 'static> Queen<E> {
         let mut failed_ids: HashSet<String> = HashSet::new();
         let mut remaining: Vec<TeamObjective> = plan.teams.clone();
 
+        if let Some(remote) = &self.remote {
+            remote.discover_executors().await;
+        }
+
         while !remaining.is_empty() {
             // Time enforcement.
             if start.elapsed().as_secs() >= self.config.total_time_limit_secs {
@@ -461,7 +484,7 @@ impl<E: AiExecutor + 'static> Queen<E> {
     ///
     /// Builds enriched context from prior team results and dispatches to
     /// the appropriate orchestrator.
-    async fn execute_team(
+    pub(crate) async fn execute_team(
         &self,
         objective: &TeamObjective,
         prior_results: &[TeamResult],
@@ -479,6 +502,20 @@ impl<E: AiExecutor + 'static> Queen<E> {
             )
         };
 
+        if let Some(remote) = &self.remote {
+            let work = TeamObjective {
+                description: enriched_description.clone(),
+                ..objective.clone()
+            };
+            let timeout = Duration::from_secs(self.config.per_team_time_limit_secs);
+            if let Some(result) = remote
+                .run_remote(RemoteWork::Objective(work), timeout)
+                .await
+            {
+                return result.to_team_result(objective);
+            }
+        }
+
         let result = match objective.orchestration_mode {
             OrchestrationMode::HiveMind => {
                 self.execute_team_hivemind(objective, &enriched_description)
@@ -581,7 +618,10 @@ impl<E: AiExecutor + 'static> Queen<E> {
         let plan = build_coordinator_plan_from_objective(objective, description);
 
         let arc_exec = ArcExecutor(Arc::clone(&self.executor));
-        let coordinator = Coordinator::new(config, arc_exec);
+        let mut coordinator = Coordinator::new(config, arc_exec);
+        if let Some(tracker) = &self.tracker {
+            coordinator.set_tracker(Arc::clone(tracker));
+        }
         let result = coordinator.execute_plan(&plan).await;
 
         let cost = result.total_cost;
@@ -1288,6 +1328,39 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn coordinator_team_moves_tracked_cards() {
+        let dir = std::env::temp_dir().join(format!("hive_queen_test_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = hive_core::kanban::KanbanStore::new(dir.join("kanban.json"));
+        let executor = Arc::new(MockExecutor::new("done"));
+        let queen = Queen::new(SwarmConfig::default(), executor)
+            .with_tracker(Arc::new(KanbanTracker::new(store.clone())));
+
+        let objective = TeamObjective {
+            id: "team-1".into(),
+            name: "Tracked".into(),
+            description: "Build the thing".into(),
+            dependencies: vec![],
+            orchestration_mode: OrchestrationMode::Coordinator,
+            scope_paths: vec![],
+            priority: 0,
+            preferred_model: None,
+        };
+        let result = queen.execute_team(&objective, &[]).await;
+        assert_eq!(result.status, TeamStatus::Completed);
+
+        let board = store.load().unwrap();
+        assert!(!board.all_tasks().is_empty());
+        assert!(
+            board
+                .all_tasks()
+                .iter()
+                .all(|card| card.column == hive_core::kanban::KanbanColumn::Done)
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     // -- Cost tracking -------------------------------------------------------
 
     #[test]
@@ -0,0 +1,1377 @@
+//! Remote execution — offload planned tasks and team objectives to trusted
+//! peers on the Hive network.
+//!
+//! Every node runs a [`RemoteExecService`] on top of its [`HiveNode`]. The
+//! service advertises what the node can run ([`NodeCapabilities`]: local
+//! models, CPU slots, installed skills), lets a Coordinator or Queen
+//! [`dispatch`](RemoteExecService::dispatch) a [`PlannedTask`] or
+//! [`TeamObjective`] to a trusted peer, and — when
+//! [`serve`](RemoteExecService::serve) is running — executes work sent by
+//! other nodes.
+//!
+//! Wire protocol, all payloads JSON:
+//! - [`MessageKind::TaskRequest`] carries a [`RemoteTaskRequest`].
+//! - [`MessageKind::AgentRelay`] carries a [`RelayMessage`]: progress,
+//!   cancellation and capability exchange.
+//! - [`MessageKind::TaskResult`] carries the final [`RemoteTaskResult`],
+//!   including the cost the executing node spent on it.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+use futures::StreamExt;
+use futures::stream::FuturesUnordered;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot, watch};
+use tracing::{debug, info, warn};
+
+use hive_network::{Envelope, HiveNode, MessageKind, PeerId, SecurityContext};
+use hive_terminal::local_ai::LocalAiDetector;
+
+use crate::coordinator::{PlannedTask, TaskResult};
+use crate::hivemind::AiExecutor;
+use crate::personas::{PersonaKind, PersonaRegistry, execute_with_persona};
+use crate::queen::Queen;
+use crate::skills::SkillsRegistry;
+use crate::swarm::{SwarmConfig, TeamObjective, TeamResult, TeamStatus};
+
+/// Jobs that may wait for a free slot before new requests are turned away.
+const MAX_QUEUED_JOBS: usize = 64;
+
+/// Default interval between progress updates for a running job.
+const DEFAULT_PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long capability discovery waits for each peer to answer.
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Extra time the requester waits past the deadline for the executor's own
+/// timeout result before giving up on it.
+const RESULT_GRACE: Duration = Duration::from_secs(5);
+
+// ---------------------------------------------------------------------------
+// Capabilities
+// ---------------------------------------------------------------------------
+
+/// A model served by a local AI provider on a node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalModel {
+    /// Provider name, e.g. `"Ollama"`.
+    pub provider: String,
+    /// Model id as the provider reports it.
+    pub model: String,
+}
+
+/// What a node offers to run for its peers.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeCapabilities {
+    /// Models available from local providers (no API cost).
+    pub local_models: Vec<LocalModel>,
+    /// Remote jobs the node runs at once. Zero means it does not accept work.
+    pub cpu_slots: usize,
+    /// Names of enabled skills.
+    pub skills: Vec<String>,
+}
+
+impl NodeCapabilities {
+    /// Detect local models and enabled skills, offering one slot per CPU.
+    pub async fn detect(skills: &SkillsRegistry) -> Self {
+        let local_models = LocalAiDetector::new()
+            .detect_all()
+            .await
+            .into_iter()
+            .filter(|provider| provider.available)
+            .flat_map(|provider| {
+                provider.models.into_iter().map(move |model| LocalModel {
+                    provider: provider.name.clone(),
+                    model,
+                })
+            })
+            .collect();
+
+        Self {
+            local_models,
+            cpu_slots: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            skills: skills
+                .list_enabled()
+                .into_iter()
+                .map(|s| s.name.clone())
+                .collect(),
+        }
+    }
+
+    /// Whether the node accepts remote work at all.
+    pub fn accepts_work(&self) -> bool {
+        self.cpu_slots > 0
+    }
+
+    /// Whether a local provider serves `model`.
+    pub fn has_model(&self, model: &str) -> bool {
+        self.local_models.iter().any(|m| m.model == model)
+    }
+
+    /// Whether the skill `name` is installed and enabled.
+    pub fn has_skill(&self, name: &str) -> bool {
+        let name = name.trim_start_matches('/');
+        self.skills.iter().any(|s| s == name)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Wire types
+// ---------------------------------------------------------------------------
+
+/// Work that can be sent to another node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RemoteWork {
+    /// A single task from a Coordinator plan.
+    Task(PlannedTask),
+    /// A team objective from a Queen plan, run with its orchestration mode.
+    Objective(TeamObjective),
+}
+
+impl RemoteWork {
+    /// The id of the planned task or objective.
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Task(task) => &task.id,
+            Self::Objective(objective) => &objective.id,
+        }
+    }
+
+    /// The model the work asks for, if any.
+    pub fn preferred_model(&self) -> Option<&str> {
+        match self {
+            Self::Task(_) => None,
+            Self::Objective(objective) => objective.preferred_model.as_deref(),
+        }
+    }
+}
+
+/// Payload of a [`MessageKind::TaskRequest`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteTaskRequest {
+    /// Id of this remote execution, chosen by the requester.
+    pub task_id: String,
+    /// What to run.
+    pub work: RemoteWork,
+    /// How long the executor may spend before giving up.
+    pub timeout_secs: u64,
+}
+
+/// Where a remote job is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteStage {
+    /// Accepted and waiting for a free slot.
+    Queued,
+    /// Execution has begun.
+    Started,
+    /// Still executing (periodic heartbeat).
+    Running,
+}
+
+/// A progress update streamed back to the requester.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteProgress {
+    pub task_id: String,
+    pub stage: RemoteStage,
+    pub detail: String,
+    /// Time since the executor accepted the job.
+    pub elapsed_ms: u64,
+}
+
+/// Payload of a [`MessageKind::AgentRelay`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RelayMessage {
+    /// Executor to requester: the job moved along.
+    Progress(RemoteProgress),
+    /// Requester to executor: stop the job.
+    Cancel { task_id: String },
+    /// Ask a peer for its capabilities.
+    CapabilitiesQuery,
+    /// A peer's capabilities, sent in answer to a query.
+    Capabilities(NodeCapabilities),
+}
+
+/// How a remote job ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteOutcome {
+    Completed,
+    Failed,
+    Cancelled,
+    TimedOut,
+    /// The executor refused the job (untrusted, busy, or not accepting work).
+    Rejected,
+}
+
+/// What the executor produced.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RemoteOutput {
+    Task(TaskResult),
+    Objective(TeamResult),
+}
+
+/// Payload of a [`MessageKind::TaskResult`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteTaskResult {
+    pub task_id: String,
+    /// The node that ran the job and spent `cost`.
+    pub executor: PeerId,
+    pub outcome: RemoteOutcome,
+    pub output: Option<RemoteOutput>,
+    /// Cost in USD incurred by the executor.
+    pub cost: f64,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+impl RemoteTaskResult {
+    fn without_output(
+        task_id: &str,
+        executor: PeerId,
+        outcome: RemoteOutcome,
+        error: impl Into<String>,
+    ) -> Self {
+        Self {
+            task_id: task_id.to_string(),
+            executor,
+            outcome,
+            output: None,
+            cost: 0.0,
+            duration_ms: 0,
+            error: Some(error.into()),
+        }
+    }
+
+    /// Whether the job completed successfully.
+    pub fn is_success(&self) -> bool {
+        self.outcome == RemoteOutcome::Completed
+    }
+
+    /// Convert into a Coordinator [`TaskResult`] for `task`.
+    pub fn to_task_result(&self, task: &PlannedTask) -> TaskResult {
+        match &self.output {
+            Some(RemoteOutput::Task(result)) => result.clone(),
+            _ => TaskResult {
+                task_id: task.id.clone(),
+                persona: task.persona.clone(),
+                output: String::new(),
+                cost: self.cost,
+                duration_ms: self.duration_ms,
+                success: false,
+                error: self.error.clone(),
+            },
+        }
+    }
+
+    /// Convert into a Queen [`TeamResult`] for `objective`.
+    pub fn to_team_result(&self, objective: &TeamObjective) -> TeamResult {
+        match &self.output {
+            Some(RemoteOutput::Objective(result)) => result.clone(),
+            _ => TeamResult {
+                team_id: objective.id.clone(),
+                team_name: objective.name.clone(),
+                status: TeamStatus::Failed,
+                inner: None,
+                cost: self.cost,
+                duration_ms: self.duration_ms,
+                insights: vec![],
+                error: self.error.clone(),
+            },
+        }
+    }
+}
+
+/// Remote spend attributed to one executing peer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExecutorCost {
+    /// Jobs that returned a result.
+    pub jobs: u32,
+    /// Total cost in USD reported by the executor.
+    pub cost: f64,
+}
+
+// ---------------------------------------------------------------------------
+// Shared state
+// ---------------------------------------------------------------------------
+
+/// Messages delivered to a waiting [`RemoteTask`].
+enum Reply {
+    Progress(RemoteProgress),
+    Done(Box<RemoteTaskResult>),
+}
+
+/// A job received from a peer, waiting for [`RemoteExecService::serve`].
+struct IncomingJob {
+    requester: PeerId,
+    request: RemoteTaskRequest,
+    accepted_at: Instant,
+    cancel: watch::Receiver<bool>,
+}
+
+/// A job this node dispatched and is waiting on.
+struct Outstanding {
+    executor: PeerId,
+    replies: mpsc::UnboundedSender<Reply>,
+}
+
+/// A job this node accepted from a peer.
+struct Accepted {
+    requester: PeerId,
+    cancel: watch::Sender<bool>,
+}
+
+/// State reachable from the router handlers.
+struct Shared {
+    local: PeerId,
+    security: Arc<SecurityContext>,
+    capabilities: NodeCapabilities,
+    jobs_tx: mpsc::Sender<IncomingJob>,
+    outstanding: Mutex<HashMap<String, Outstanding>>,
+    accepted: Mutex<HashMap<String, Accepted>>,
+    peer_capabilities: Mutex<HashMap<PeerId, NodeCapabilities>>,
+    capability_waiters: Mutex<HashMap<PeerId, Vec<oneshot::Sender<NodeCapabilities>>>>,
+    ledger: Mutex<HashMap<PeerId, ExecutorCost>>,
+}
+
+impl Shared {
+    fn envelope<T: Serialize>(&self, to: &PeerId, kind: MessageKind, payload: &T) -> Envelope {
+        Envelope::new(
+            self.local.clone(),
+            Some(to.clone()),
+            kind,
+            serde_json::to_value(payload).unwrap_or_default(),
+        )
+    }
+
+    fn reject(&self, to: &PeerId, task_id: &str, reason: &str) -> Option<Envelope> {
+        warn!(task_id, peer = %to.short(), "Rejecting remote task: {reason}");
+        let result = RemoteTaskResult::without_output(
+            task_id,
+            self.local.clone(),
+            RemoteOutcome::Rejected,
+            reason,
+        );
+        Some(self.envelope(to, MessageKind::TaskResult, &result))
+    }
+
+    /// A peer asked us to run something.
+    fn on_task_request(&self, envelope: Envelope) -> Option<Envelope> {
+        let request: RemoteTaskRequest = match serde_json::from_value(envelope.payload) {
+            Ok(request) => request,
+            Err(e) => {
+                warn!(
+                    "Malformed remote task request from {}: {e}",
+                    envelope.from.short()
+                );
+                return None;
+            }
+        };
+        let requester = envelope.from;
+
+        if !self.security.is_trusted(&requester) {
+            return self.reject(&requester, &request.task_id, "requester is not trusted");
+        }
+        if !self.capabilities.accepts_work() {
+            return self.reject(
+                &requester,
+                &request.task_id,
+                "node does not accept remote work",
+            );
+        }
+
+        let (cancel_tx, cancel_rx) = watch::channel(false);
+        let task_id = request.task_id.clone();
+        let job = IncomingJob {
+            requester: requester.clone(),
+            request,
+            accepted_at: Instant::now(),
+            cancel: cancel_rx,
+        };
+        if self.jobs_tx.try_send(job).is_err() {
+            return self.reject(&requester, &task_id, "executor queue is full");
+        }
+        self.accepted.lock().unwrap().insert(
+            task_id.clone(),
+            Accepted {
+                requester: requester.clone(),
+                cancel: cancel_tx,
+            },
+        );
+
+        debug!(task_id, peer = %requester.short(), "Queued remote task");
+        let progress = RelayMessage::Progress(RemoteProgress {
+            task_id,
+            stage: RemoteStage::Queued,
+            detail: "queued".into(),
+            elapsed_ms: 0,
+        });
+        Some(self.envelope(&requester, MessageKind::AgentRelay, &progress))
+    }
+
+    /// An executor finished a job we dispatched.
+    fn on_task_result(&self, envelope: Envelope) {
+        let mut result: RemoteTaskResult = match serde_json::from_value(envelope.payload) {
+            Ok(result) => result,
+            Err(e) => {
+                warn!(
+                    "Malformed remote task result from {}: {e}",
+                    envelope.from.short()
+                );
+                return;
+            }
+        };
+
+        let mut outstanding = self.outstanding.lock().unwrap();
+        let Some(entry) = outstanding.get(&result.task_id) else {
+            debug!(task_id = %result.task_id, "Result for unknown remote task");
+            return;
+        };
+        if entry.executor != envelope.from {
+            warn!(task_id = %result.task_id, "Ignoring result from a peer that was not asked");
+            return;
+        }
+        let entry = outstanding
+            .remove(&result.task_id)
+            .expect("entry checked above");
+        drop(outstanding);
+
+        // Attribute the cost to the authenticated sender, whatever it claims.
+        result.executor = envelope.from;
+        {
+            let mut ledger = self.ledger.lock().unwrap();
+            let spent = ledger.entry(result.executor.clone()).or_default();
+            spent.jobs += 1;
+            spent.cost += result.cost;
+        }
+        let _ = entry.replies.send(Reply::Done(Box::new(result)));
+    }
+
+    fn on_relay(&self, envelope: Envelope) -> Option<Envelope> {
+        let message: RelayMessage = match serde_json::from_value(envelope.payload) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("Malformed agent relay from {}: {e}", envelope.from.short());
+                return None;
+            }
+        };
+
+        match message {
+            RelayMessage::Progress(progress) => {
+                let outstanding = self.outstanding.lock().unwrap();
+                if let Some(entry) = outstanding.get(&progress.task_id)
+                    && entry.executor == envelope.from
+                {
+                    let _ = entry.replies.send(Reply::Progress(progress));
+                }
+                None
+            }
+            RelayMessage::Cancel { task_id } => {
+                let accepted = self.accepted.lock().unwrap();
+                if let Some(job) = accepted.get(&task_id)
+                    && job.requester == envelope.from
+                {
+                    info!(task_id, "Remote task cancelled by requester");
+                    let _ = job.cancel.send(true);
+                }
+                None
+            }
+            RelayMessage::CapabilitiesQuery => {
+                let answer = RelayMessage::Capabilities(self.capabilities.clone());
+                Some(self.envelope(&envelope.from, MessageKind::AgentRelay, &answer))
+            }
+            RelayMessage::Capabilities(capabilities) => {
+                let waiters = self
+                    .capability_waiters
+                    .lock()
+                    .unwrap()
+                    .remove(&envelope.from)
+                    .unwrap_or_default();
+                for waiter in waiters {
+                    let _ = waiter.send(capabilities.clone());
+                }
+                self.peer_capabilities
+                    .lock()
+                    .unwrap()
+                    .insert(envelope.from, capabilities);
+                None
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Service
+// ---------------------------------------------------------------------------
+
+/// Dispatches work to peers and runs work sent by them.
+pub struct RemoteExecService {
+    node: Arc<HiveNode>,
+    shared: Arc<Shared>,
+    jobs_rx: tokio::sync::Mutex<mpsc::Receiver<IncomingJob>>,
+    progress_interval: Duration,
+}
+
+impl RemoteExecService {
+    /// Register the remote execution handlers on a running node.
+    ///
+    /// The node handles [`MessageKind::TaskRequest`], [`MessageKind::TaskResult`]
+    /// and [`MessageKind::AgentRelay`] from then on. Incoming jobs queue until
+    /// [`serve`](Self::serve) runs them.
+    pub async fn attach(node: Arc<HiveNode>, capabilities: NodeCapabilities) -> Self {
+        let (jobs_tx, jobs_rx) = mpsc::channel(MAX_QUEUED_JOBS);
+        let shared = Arc::new(Shared {
+            local: node.peer_id().clone(),
+            security: Arc::clone(node.security()),
+            capabilities,
+            jobs_tx,
+            outstanding: Mutex::new(HashMap::new()),
+            accepted: Mutex::new(HashMap::new()),
+            peer_capabilities: Mutex::new(HashMap::new()),
+            capability_waiters: Mutex::new(HashMap::new()),
+            ledger: Mutex::new(HashMap::new()),
+        });
+
+        let handler_state = Arc::clone(&shared);
+        node.on_message(
+            MessageKind::TaskRequest,
+            Arc::new(move |envelope| {
+                let reply = handler_state.on_task_request(envelope);
+                Box::pin(async move { reply })
+            }),
+        )
+        .await;
+
+        let handler_state = Arc::clone(&shared);
+        node.on_message(
+            MessageKind::TaskResult,
+            Arc::new(move |envelope| {
+                handler_state.on_task_result(envelope);
+                Box::pin(async { None })
+            }),
+        )
+        .await;
+
+        let handler_state = Arc::clone(&shared);
+        node.on_message(
+            MessageKind::AgentRelay,
+            Arc::new(move |envelope| {
+                let reply = handler_state.on_relay(envelope);
+                Box::pin(async move { reply })
+            }),
+        )
+        .await;
+
+        Self {
+            node,
+            shared,
+            jobs_rx: tokio::sync::Mutex::new(jobs_rx),
+            progress_interval: DEFAULT_PROGRESS_INTERVAL,
+        }
+    }
+
+    /// Set how often running jobs report progress.
+    pub fn with_progress_interval(mut self, interval: Duration) -> Self {
+        self.progress_interval = interval;
+        self
+    }
+
+    /// The capabilities this node advertises.
+    pub fn capabilities(&self) -> &NodeCapabilities {
+        &self.shared.capabilities
+    }
+
+    // -----------------------------------------------------------------------
+    // Requester side
+    // -----------------------------------------------------------------------
+
+    /// Ask a connected peer for its capabilities.
+    pub async fn query_capabilities(
+        &self,
+        peer: &PeerId,
+        timeout: Duration,
+    ) -> Result<NodeCapabilities> {
+        let (tx, rx) = oneshot::channel();
+        self.shared
+            .capability_waiters
+            .lock()
+            .unwrap()
+            .entry(peer.clone())
+            .or_default()
+            .push(tx);
+
+        let query = self.shared.envelope(
+            peer,
+            MessageKind::AgentRelay,
+            &RelayMessage::CapabilitiesQuery,
+        );
+        self.node
+            .send_to_peer(peer, &query)
+            .await
+            .context("Failed to send capabilities query")?;
+
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(capabilities)) => Ok(capabilities),
+            _ => bail!("Peer {} did not report its capabilities", peer.short()),
+        }
+    }
+
+    /// Capabilities last reported by `peer`.
+    pub fn known_capabilities(&self, peer: &PeerId) -> Option<NodeCapabilities> {
+        self.shared
+            .peer_capabilities
+            .lock()
+            .unwrap()
+            .get(peer)
+            .cloned()
+    }
+
+    /// Pick a trusted peer that can run `work`, preferring one that serves
+    /// the requested model locally and then the one with the most slots.
+    pub fn choose_executor(&self, work: &RemoteWork) -> Option<PeerId> {
+        let peers = self.shared.peer_capabilities.lock().unwrap();
+        peers
+            .iter()
+            .filter(|(peer, caps)| caps.accepts_work() && self.shared.security.is_trusted(peer))
+            .max_by_key(|(_, caps)| {
+                let has_model = work.preferred_model().is_some_and(|m| caps.has_model(m));
+                (has_model, caps.cpu_slots)
+            })
+            .map(|(peer, _)| peer.clone())
+    }
+
+    /// Ask every connected trusted peer for its capabilities so
+    /// [`choose_executor`](Self::choose_executor) can pick among them.
+    /// Returns how many peers answered.
+    pub async fn discover_executors(&self) -> usize {
+        let peers: Vec<PeerId> = self
+            .node
+            .connected_peers()
+            .await
+            .into_iter()
+            .map(|peer| peer.id)
+            .filter(|peer| self.shared.security.is_trusted(peer))
+            .collect();
+        futures::future::join_all(
+            peers
+                .iter()
+                .map(|peer| self.query_capabilities(peer, DISCOVERY_TIMEOUT)),
+        )
+        .await
+        .into_iter()
+        .filter(Result::is_ok)
+        .count()
+    }
+
+    /// Run `work` on the best known executor and wait for its result.
+    ///
+    /// Returns `None` when no peer takes the work — none is capable, the
+    /// dispatch fails, or the executor rejects it — so the caller can run it
+    /// locally instead.
+    pub async fn run_remote(
+        &self,
+        work: RemoteWork,
+        timeout: Duration,
+    ) -> Option<RemoteTaskResult> {
+        let peer = self.choose_executor(&work)?;
+        let work_id = work.id().to_string();
+        let result = match self.dispatch(&peer, work, timeout).await {
+            Ok(task) => task.wait().await,
+            Err(e) => {
+                warn!(work = work_id, peer = %peer.short(), "Remote dispatch failed: {e:#}");
+                return None;
+            }
+        };
+        if result.outcome == RemoteOutcome::Rejected {
+            debug!(work = work_id, peer = %peer.short(), "Remote executor rejected work");
+            return None;
+        }
+        Some(result)
+    }
+
+    /// Send `work` to a trusted peer. The returned handle streams progress
+    /// and resolves to the executor's result.
+    pub async fn dispatch(
+        &self,
+        peer: &PeerId,
+        work: RemoteWork,
+        timeout: Duration,
+    ) -> Result<RemoteTask> {
+        if !self.shared.security.is_trusted(peer) {
+            bail!("Peer {} is not trusted", peer.short());
+        }
+
+        let task_id = uuid::Uuid::new_v4().to_string();
+        let request = RemoteTaskRequest {
+            task_id: task_id.clone(),
+            work,
+            timeout_secs: timeout.as_secs().max(1),
+        };
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.shared.outstanding.lock().unwrap().insert(
+            task_id.clone(),
+            Outstanding {
+                executor: peer.clone(),
+                replies: tx,
+            },
+        );
+
+        let envelope = self
+            .shared
+            .envelope(peer, MessageKind::TaskRequest, &request);
+        if let Err(e) = self.node.send_to_peer(peer, &envelope).await {
+            self.shared.outstanding.lock().unwrap().remove(&task_id);
+            return Err(e).context("Failed to send remote task");
+        }
+
+        info!(task_id, work = request.work.id(), peer = %peer.short(), "Dispatched remote task");
+        Ok(RemoteTask {
+            task_id,
+            executor: peer.clone(),
+            deadline: tokio::time::Instant::now() + timeout + RESULT_GRACE,
+            replies: rx,
+            node: Arc::clone(&self.node),
+            shared: Arc::clone(&self.shared),
+        })
+    }
+
+    /// Ask the executor of a dispatched task to stop it. The task's handle
+    /// then resolves with [`RemoteOutcome::Cancelled`].
+    pub async fn cancel(&self, task_id: &str) -> Result<()> {
+        let executor = self
+            .shared
+            .outstanding
+            .lock()
+            .unwrap()
+            .get(task_id)
+            .map(|entry| entry.executor.clone());
+        match executor {
+            Some(executor) => send_cancel(&self.node, &self.shared, &executor, task_id).await,
+            None => bail!("No outstanding remote task {task_id}"),
+        }
+    }
+
+    /// Cost of remote work so far, per executing peer.
+    pub fn cost_by_executor(&self) -> HashMap<PeerId, ExecutorCost> {
+        self.shared.ledger.lock().unwrap().clone()
+    }
+
+    /// Total cost of remote work across all executors.
+    pub fn total_remote_cost(&self) -> f64 {
+        self.shared
+            .ledger
+            .lock()
+            .unwrap()
+            .values()
+            .map(|c| c.cost)
+            .sum()
+    }
+
+    // -----------------------------------------------------------------------
+    // Executor side
+    // -----------------------------------------------------------------------
+
+    /// Run jobs sent by peers, up to `cpu_slots` at a time, until the
+    /// returned future is dropped.
+    ///
+    /// Executor futures are not `Send`, so jobs run concurrently inside this
+    /// future rather than on spawned tasks; await it from its own task.
+    pub async fn serve<E: AiExecutor + 'static>(&self, executor: Arc<E>) -> Result<()> {
+        let mut jobs = self
+            .jobs_rx
+            .try_lock()
+            .map_err(|_| anyhow::anyhow!("Remote execution service is already serving"))?;
+        let slots = self.shared.capabilities.cpu_slots.max(1);
+        let mut running = FuturesUnordered::new();
+
+        loop {
+            tokio::select! {
+                Some(job) = jobs.recv(), if running.len() < slots => {
+                    running.push(self.run_job(job, &executor));
+                }
+                Some(()) = running.next(), if !running.is_empty() => {}
+                else => return Ok(()),
+            }
+        }
+    }
+
+    async fn run_job<E: AiExecutor + 'static>(&self, mut job: IncomingJob, executor: &Arc<E>) {
+        let task_id = job.request.task_id.clone();
+        let timeout = Duration::from_secs(job.request.timeout_secs);
+        let started = Instant::now();
+
+        let result = if *job.cancel.borrow() {
+            RemoteTaskResult::without_output(
+                &task_id,
+                self.shared.local.clone(),
+                RemoteOutcome::Cancelled,
+                "cancelled before start",
+            )
+        } else {
+            self.send_progress(&job, RemoteStage::Started, "started")
+                .await;
+
+            let work = execute_work(&job.request.work, executor);
+            tokio::pin!(work);
+            let deadline = tokio::time::sleep(timeout.saturating_sub(job.accepted_at.elapsed()));
+            tokio::pin!(deadline);
+            let mut ticker = tokio::time::interval(self.progress_interval);
+            ticker.tick().await;
+
+            loop {
+                tokio::select! {
+                    (output, cost) = &mut work => {
+                        let (outcome, error) = match &output {
+                            RemoteOutput::Task(r) if !r.success => (RemoteOutcome::Failed, r.error.clone()),
+                            RemoteOutput::Objective(r) if r.status != TeamStatus::Completed => {
+                                (RemoteOutcome::Failed, r.error.clone())
+                            }
+                            _ => (RemoteOutcome::Completed, None),
+                        };
+                        break RemoteTaskResult {
+                            task_id: task_id.clone(),
+                            executor: self.shared.local.clone(),
+                            outcome,
+                            output: Some(output),
+                            cost,
+                            duration_ms: started.elapsed().as_millis() as u64,
+                            error,
+                        };
+                    }
+                    _ = &mut deadline => {
+                        break RemoteTaskResult::without_output(
+                            &task_id,
+                            self.shared.local.clone(),
+                            RemoteOutcome::TimedOut,
+                            format!("timed out after {}s", timeout.as_secs()),
+                        );
+                    }
+                    Ok(()) = job.cancel.changed() => {
+                        if *job.cancel.borrow() {
+                            break RemoteTaskResult::without_output(
+                                &task_id,
+                                self.shared.local.clone(),
+                                RemoteOutcome::Cancelled,
+                                "cancelled by requester",
+                            );
+                        }
+                    }
+                    _ = ticker.tick() => {
+                        let detail = format!("running for {}s", started.elapsed().as_secs());
+                        self.send_progress(&job, RemoteStage::Running, &detail).await;
+                    }
+                }
+            }
+        };
+
+        self.shared.accepted.lock().unwrap().remove(&task_id);
+        info!(task_id, outcome = ?result.outcome, cost = result.cost, "Remote task finished");
+
+        let envelope = self
+            .shared
+            .envelope(&job.requester, MessageKind::TaskResult, &result);
+        if let Err(e) = self.node.send_to_peer(&job.requester, &envelope).await {
+            warn!(task_id, "Failed to return remote task result: {e}");
+        }
+    }
+
+    async fn send_progress(&self, job: &IncomingJob, stage: RemoteStage, detail: &str) {
+        let progress = RelayMessage::Progress(RemoteProgress {
+            task_id: job.request.task_id.clone(),
+            stage,
+            detail: detail.to_string(),
+            elapsed_ms: job.accepted_at.elapsed().as_millis() as u64,
+        });
+        let envelope = self
+            .shared
+            .envelope(&job.requester, MessageKind::AgentRelay, &progress);
+        if let Err(e) = self.node.send_to_peer(&job.requester, &envelope).await {
+            debug!(task_id = %job.request.task_id, "Failed to send progress: {e}");
+        }
+    }
+}
+
+/// Run `work` locally, returning its output and cost.
+async fn execute_work<E: AiExecutor + 'static>(
+    work: &RemoteWork,
+    executor: &Arc<E>,
+) -> (RemoteOutput, f64) {
+    match work {
+        RemoteWork::Task(task) => {
+            let registry = PersonaRegistry::new();
+            let Some(persona) = registry
+                .get(&task.persona)
+                .or_else(|| registry.get(&PersonaKind::Implement))
+            else {
+                let result = TaskResult {
+                    task_id: task.id.clone(),
+                    persona: task.persona.clone(),
+                    output: String::new(),
+                    cost: 0.0,
+                    duration_ms: 0,
+                    success: false,
+                    error: Some(format!("persona {:?} is not available", task.persona)),
+                };
+                return (RemoteOutput::Task(result), 0.0);
+            };
+
+            let output =
+                execute_with_persona(persona, &task.description, executor.as_ref(), None).await;
+            let cost = output.cost;
+            let result = TaskResult {
+                task_id: task.id.clone(),
+                persona: task.persona.clone(),
+                output: output.content,
+                cost,
+                duration_ms: output.duration_ms,
+                success: output.success,
+                error: output.error,
+            };
+            (RemoteOutput::Task(result), cost)
+        }
+        RemoteWork::Objective(objective) => {
+            let queen = Queen::new(SwarmConfig::default(), Arc::clone(executor));
+            let result = queen.execute_team(objective, &[]).await;
+            let cost = result.cost;
+            (RemoteOutput::Objective(result), cost)
+        }
+    }
+}
+
+async fn send_cancel(
+    node: &HiveNode,
+    shared: &Shared,
+    executor: &PeerId,
+    task_id: &str,
+) -> Result<()> {
+    let cancel = RelayMessage::Cancel {
+        task_id: task_id.to_string(),
+    };
+    let envelope = shared.envelope(executor, MessageKind::AgentRelay, &cancel);
+    node.send_to_peer(executor, &envelope)
+        .await
+        .context("Failed to send cancellation")
+}
+
+// ---------------------------------------------------------------------------
+// Remote task handle
+// ---------------------------------------------------------------------------
+
+/// A job running on another node.
+pub struct RemoteTask {
+    task_id: String,
+    executor: PeerId,
+    deadline: tokio::time::Instant,
+    replies: mpsc::UnboundedReceiver<Reply>,
+    node: Arc<HiveNode>,
+    shared: Arc<Shared>,
+}
+
+impl RemoteTask {
+    /// Id of this remote execution.
+    pub fn task_id(&self) -> &str {
+        &self.task_id
+    }
+
+    /// The peer running the job.
+    pub fn executor(&self) -> &PeerId {
+        &self.executor
+    }
+
+    /// Ask the executor to stop the job.
+    pub async fn cancel(&self) -> Result<()> {
+        send_cancel(&self.node, &self.shared, &self.executor, &self.task_id).await
+    }
+
+    /// Wait for the result, ignoring progress.
+    pub async fn wait(self) -> RemoteTaskResult {
+        self.wait_with_progress(|_| {}).await
+    }
+
+    /// Wait for the result, passing each progress update to `on_progress`.
+    ///
+    /// If the executor goes silent past the deadline the job is cancelled and
+    /// a [`RemoteOutcome::TimedOut`] result is returned.
+    pub async fn wait_with_progress(
+        mut self,
+        mut on_progress: impl FnMut(&RemoteProgress),
+    ) -> RemoteTaskResult {
+        loop {
+            tokio::select! {
+                reply = self.replies.recv() => match reply {
+                    Some(Reply::Progress(progress)) => on_progress(&progress),
+                    Some(Reply::Done(result)) => return *result,
+                    None => {
+                        return RemoteTaskResult::without_output(
+                            &self.task_id,
+                            self.executor.clone(),
+                            RemoteOutcome::Failed,
+                            "remote execution service shut down",
+                        );
+                    }
+                },
+                _ = tokio::time::sleep_until(self.deadline) => {
+                    let _ = self.cancel().await;
+                    return RemoteTaskResult::without_output(
+                        &self.task_id,
+                        self.executor.clone(),
+                        RemoteOutcome::TimedOut,
+                        "executor did not answer before the deadline",
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl Drop for RemoteTask {
+    fn drop(&mut self) {
+        self.shared
+            .outstanding
+            .lock()
+            .unwrap()
+            .remove(&self.task_id);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinator::{Coordinator, CoordinatorConfig, TaskPlan};
+    use hive_ai::types::{ChatRequest, ChatResponse, FinishReason, TokenUsage};
+    use hive_network::{NetworkConfig, NodeIdentity};
+
+    /// Answers after `delay` with the user text, billing a fixed token usage.
+    struct SlowExecutor {
+        delay: Duration,
+    }
+
+    impl AiExecutor for SlowExecutor {
+        async fn execute(&self, request: &ChatRequest) -> Result<ChatResponse, String> {
+            tokio::time::sleep(self.delay).await;
+            Ok(ChatResponse {
+                content: format!("done: {}", request.messages[0].content),
+                model: request.model.clone(),
+                usage: TokenUsage {
+                    prompt_tokens: 1_000,
+                    completion_tokens: 1_000,
+                    ..TokenUsage::default()
+                },
+                finish_reason: FinishReason::Stop,
+                thinking: None,
+                tool_calls: None,
+            })
+        }
+    }
+
+    fn free_addr() -> std::net::SocketAddr {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap()
+    }
+
+    fn capabilities(slots: usize) -> NodeCapabilities {
+        NodeCapabilities {
+            local_models: vec![LocalModel {
+                provider: "Ollama".into(),
+                model: "llama3.2".into(),
+            }],
+            cpu_slots: slots,
+            skills: vec!["code-review".into()],
+        }
+    }
+
+    fn task(description: &str) -> PlannedTask {
+        PlannedTask {
+            id: "t1".into(),
+            description: description.into(),
+            persona: PersonaKind::Implement,
+            dependencies: vec![],
+            priority: 1,
+        }
+    }
+
+    /// Two started nodes on localhost that trust each other, with the
+    /// requester connected to the executor.
+    async fn connected_pair() -> (Arc<HiveNode>, Arc<HiveNode>) {
+        let local = |addr| NetworkConfig {
+            listen_addr: addr,
+            discovery_enabled: false,
+            ..NetworkConfig::default()
+        };
+        let executor_addr = free_addr();
+        let mut executor = HiveNode::new(NodeIdentity::generate("executor"), local(executor_addr));
+        let mut requester = HiveNode::new(NodeIdentity::generate("requester"), local(free_addr()));
+        executor.trust_peer(requester.peer_id().clone(), "requester");
+        requester.trust_peer(executor.peer_id().clone(), "executor");
+        executor.start().await.unwrap();
+        requester.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        requester
+            .connect_to(&executor_addr.to_string())
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        (Arc::new(requester), Arc::new(executor))
+    }
+
+    #[test]
+    fn capabilities_lookup() {
+        let caps = capabilities(2);
+        assert!(caps.accepts_work());
+        assert!(caps.has_model("llama3.2"));
+        assert!(!caps.has_model("gpt-4o"));
+        assert!(caps.has_skill("/code-review"));
+        assert!(!NodeCapabilities::default().accepts_work());
+    }
+
+    #[test]
+    fn relay_messages_roundtrip() {
+        let message = RelayMessage::Cancel {
+            task_id: "abc".into(),
+        };
+        let json = serde_json::to_value(&message).unwrap();
+        assert_eq!(json["type"], "cancel");
+        assert!(matches!(
+            serde_json::from_value(json).unwrap(),
+            RelayMessage::Cancel { task_id } if task_id == "abc"
+        ));
+
+        let work = RemoteWork::Task(task("x"));
+        let json = serde_json::to_value(&work).unwrap();
+        assert_eq!(json["type"], "task");
+        let back: RemoteWork = serde_json::from_value(json).unwrap();
+        assert_eq!(back.id(), "t1");
+    }
+
+    #[test]
+    fn failed_result_converts_for_coordinator() {
+        let result = RemoteTaskResult::without_output(
+            "r1",
+            PeerId::generate(),
+            RemoteOutcome::TimedOut,
+            "too slow",
+        );
+        let converted = result.to_task_result(&task("x"));
+        assert_eq!(converted.task_id, "t1");
+        assert!(!converted.success);
+        assert_eq!(converted.error.as_deref(), Some("too slow"));
+    }
+
+    #[tokio::test]
+    async fn executes_task_on_peer_with_progress_and_cost() {
+        let (requester, executor) = connected_pair().await;
+        let client =
+            RemoteExecService::attach(Arc::clone(&requester), NodeCapabilities::default()).await;
+        let server = RemoteExecService::attach(Arc::clone(&executor), capabilities(2))
+            .await
+            .with_progress_interval(Duration::from_millis(50));
+        let ai = Arc::new(SlowExecutor {
+            delay: Duration::from_millis(200),
+        });
+
+        let run = async {
+            let caps = client
+                .query_capabilities(executor.peer_id(), Duration::from_secs(5))
+                .await
+                .unwrap();
+            assert_eq!(caps, capabilities(2));
+            assert_eq!(
+                client
+                    .choose_executor(&RemoteWork::Task(task("x")))
+                    .as_ref(),
+                Some(executor.peer_id())
+            );
+
+            let handle = client
+                .dispatch(
+                    executor.peer_id(),
+                    RemoteWork::Task(task("write tests")),
+                    Duration::from_secs(10),
+                )
+                .await
+                .unwrap();
+            let mut stages = Vec::new();
+            let result = handle.wait_with_progress(|p| stages.push(p.stage)).await;
+            (result, stages)
+        };
+
+        let (result, stages) = tokio::select! {
+            out = run => out,
+            _ = server.serve(ai) => unreachable!("serve only returns on shutdown"),
+        };
+
+        assert_eq!(
+            result.outcome,
+            RemoteOutcome::Completed,
+            "{:?}",
+            result.error
+        );
+        assert_eq!(&result.executor, executor.peer_id());
+        assert!(stages.contains(&RemoteStage::Queued));
+        assert!(stages.contains(&RemoteStage::Started));
+        assert!(stages.contains(&RemoteStage::Running));
+
+        let task_result = result.to_task_result(&task("write tests"));
+        assert!(task_result.success);
+        assert!(task_result.output.contains("write tests"));
+
+        let ledger = client.cost_by_executor();
+        let spent = ledger[executor.peer_id()];
+        assert_eq!(spent.jobs, 1);
+        assert_eq!(spent.cost, result.cost);
+        assert_eq!(client.total_remote_cost(), result.cost);
+    }
+
+    #[tokio::test]
+    async fn cancellation_and_timeout_stop_remote_work() {
+        let (requester, executor) = connected_pair().await;
+        let client =
+            RemoteExecService::attach(Arc::clone(&requester), NodeCapabilities::default()).await;
+        let server = RemoteExecService::attach(Arc::clone(&executor), capabilities(2)).await;
+        let ai = Arc::new(SlowExecutor {
+            delay: Duration::from_secs(30),
+        });
+
+        let run = async {
+            let cancelled = client
+                .dispatch(
+                    executor.peer_id(),
+                    RemoteWork::Task(task("long job")),
+                    Duration::from_secs(30),
+                )
+                .await
+                .unwrap();
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            client.cancel(cancelled.task_id()).await.unwrap();
+            let cancelled = cancelled.wait().await;
+
+            let timed_out = client
+                .dispatch(
+                    executor.peer_id(),
+                    RemoteWork::Task(task("long job")),
+                    Duration::from_secs(1),
+                )
+                .await
+                .unwrap()
+                .wait()
+                .await;
+            (cancelled, timed_out)
+        };
+
+        let (cancelled, timed_out) = tokio::select! {
+            out = run => out,
+            _ = server.serve(ai) => unreachable!("serve only returns on shutdown"),
+        };
+        assert_eq!(cancelled.outcome, RemoteOutcome::Cancelled);
+        assert_eq!(timed_out.outcome, RemoteOutcome::TimedOut);
+        assert_eq!(&timed_out.executor, executor.peer_id());
+    }
+
+    #[tokio::test]
+    async fn untrusted_or_closed_executors_reject_work() {
+        let (requester, executor) = connected_pair().await;
+        let client =
+            RemoteExecService::attach(Arc::clone(&requester), NodeCapabilities::default()).await;
+        let _server =
+            RemoteExecService::attach(Arc::clone(&executor), NodeCapabilities::default()).await;
+
+        // The executor offers no slots.
+        let result = client
+            .dispatch(
+                executor.peer_id(),
+                RemoteWork::Task(task("x")),
+                Duration::from_secs(5),
+            )
+            .await
+            .unwrap()
+            .wait()
+            .await;
+        assert_eq!(result.outcome, RemoteOutcome::Rejected);
+        assert_eq!(client.cost_by_executor()[executor.peer_id()].cost, 0.0);
+
+        // Dispatching to a peer we do not trust fails locally.
+        assert!(
+            client
+                .dispatch(
+                    &PeerId::generate(),
+                    RemoteWork::Task(task("x")),
+                    Duration::from_secs(5)
+                )
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn coordinator_offloads_tasks_to_capable_peers() {
+        let (requester, executor) = connected_pair().await;
+        let client = Arc::new(
+            RemoteExecService::attach(Arc::clone(&requester), NodeCapabilities::default()).await,
+        );
+        let server = RemoteExecService::attach(Arc::clone(&executor), capabilities(2)).await;
+        let ai = Arc::new(SlowExecutor {
+            delay: Duration::from_millis(50),
+        });
+
+        let mut coordinator = Coordinator::new(
+            CoordinatorConfig::default(),
+            SlowExecutor {
+                delay: Duration::ZERO,
+            },
+        );
+        coordinator.set_remote(Arc::clone(&client));
+        let plan = TaskPlan {
+            tasks: vec![task("write tests")],
+        };
+
+        let result = tokio::select! {
+            result = coordinator.execute_plan(&plan) => result,
+            _ = server.serve(ai) => unreachable!("serve only returns on shutdown"),
+        };
+        assert_eq!(result.successful_tasks(), 1);
+        assert!(result.results[0].output.contains("write tests"));
+        assert_eq!(client.cost_by_executor()[executor.peer_id()].jobs, 1);
+    }
+
+    #[tokio::test]
+    async fn coordinator_runs_locally_without_capable_peers() {
+        let (requester, executor) = connected_pair().await;
+        let client = Arc::new(
+            RemoteExecService::attach(Arc::clone(&requester), NodeCapabilities::default()).await,
+        );
+        let _server =
+            RemoteExecService::attach(Arc::clone(&executor), NodeCapabilities::default()).await;
+
+        let mut coordinator = Coordinator::new(
+            CoordinatorConfig::default(),
+            SlowExecutor {
+                delay: Duration::ZERO,
+            },
+        );
+        coordinator.set_remote(Arc::clone(&client));
+        let plan = TaskPlan {
+            tasks: vec![task("write tests")],
+        };
+
+        let result = coordinator.execute_plan(&plan).await;
+        assert_eq!(result.successful_tasks(), 1);
+        assert!(
+            client
+                .known_capabilities(executor.peer_id())
+                .is_some_and(|caps| !caps.accepts_work())
+        );
+        assert!(client.cost_by_executor().is_empty());
+    }
+}
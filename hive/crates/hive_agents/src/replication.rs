@@ -0,0 +1,1179 @@
+//! Replication — CRDT-backed sharing of channels, specs and kanban boards
+//! between peers.
+//!
+//! Each shared value maps onto a [`CrdtDoc`] through the [`Replicated`]
+//! trait: scalar fields become last-writer-wins keys, and lists (messages,
+//! spec entries, tasks, comments) become keyed collections ordered by when
+//! each item was first written. Concurrent edits to different fields of the
+//! same item both survive; edits to the same field resolve identically on
+//! every node.
+//!
+//! A [`ReplicaStore`] keeps one document per value plus the last state the
+//! application saw, so [`record`](ReplicaStore::record) can turn a plain
+//! edited value into ops without clobbering remote changes that arrived in
+//! the meantime. [`ReplicationService`] carries those ops over
+//! [`MessageKind::CrdtSync`], pushes local edits as they happen, and
+//! reconciles whole stores on [`sync_with`](ReplicationService::sync_with)
+//! so offline edits merge on reconnect. Ops are signed with the authoring
+//! node's key, history every peer has acknowledged is compacted away, and
+//! each document is saved to its own file as it changes.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
+
+use hive_core::channels::AgentChannel;
+use hive_core::kanban::KanbanBoard;
+use hive_network::crdt::{key, unescape_segment};
+use hive_network::{
+    CrdtDelta, CrdtDoc, CrdtSyncHelper, CrdtSyncPayload, HiveNode, MessageKind, NodeKeypair, Op,
+    PeerId, VectorClock,
+};
+
+use crate::specs::{Spec, SpecSection};
+
+/// Capacity of the change notification channel.
+const CHANGE_CHANNEL_CAPACITY: usize = 64;
+
+// ---------------------------------------------------------------------------
+// Mapping values onto documents
+// ---------------------------------------------------------------------------
+
+/// A value that can be replicated through a [`CrdtDoc`].
+pub trait Replicated: Sized {
+    /// Document id, unique across kinds (e.g. `spec:{id}`).
+    fn doc_id(&self) -> String;
+
+    /// Flatten the value into document keys and values.
+    fn to_fields(&self) -> BTreeMap<String, Value>;
+
+    /// Rebuild the value from a document. Items left incomplete by a
+    /// concurrent delete are dropped.
+    fn from_doc(doc: &CrdtDoc) -> Option<Self>;
+}
+
+/// Insert the fields of `value` under `prefix`, except those in `skip`.
+fn insert_fields(
+    out: &mut BTreeMap<String, Value>,
+    prefix: &[&str],
+    value: &impl Serialize,
+    skip: &[&str],
+) {
+    let Ok(Value::Object(fields)) = serde_json::to_value(value) else {
+        return;
+    };
+    for (name, field) in fields {
+        if skip.contains(&name.as_str()) {
+            continue;
+        }
+        let mut path = prefix.to_vec();
+        path.push(&name);
+        out.insert(key(&path), field);
+    }
+}
+
+/// Read the fields stored directly under `prefix` back into an object.
+fn read_fields(doc: &CrdtDoc, prefix: &str) -> Map<String, Value> {
+    let scan = format!("{prefix}/");
+    doc.scan(&scan)
+        .filter_map(|(k, v)| {
+            let rest = &k[scan.len()..];
+            (!rest.contains('/')).then(|| (unescape_segment(rest), v.clone()))
+        })
+        .collect()
+}
+
+/// Items of a collection stored as one value per key, in causal order.
+fn read_items<T: DeserializeOwned>(doc: &CrdtDoc, prefix: &str) -> Vec<T> {
+    doc.children(&format!("{prefix}/"))
+        .into_iter()
+        .filter_map(|child| doc.get(&format!("{prefix}/{child}")).cloned())
+        .filter_map(|value| serde_json::from_value(value).ok())
+        .collect()
+}
+
+/// Names of a set stored as one `true` key per member, in causal order.
+fn read_set(doc: &CrdtDoc, prefix: &str) -> Vec<String> {
+    doc.children(&format!("{prefix}/"))
+        .iter()
+        .map(|child| unescape_segment(child))
+        .collect()
+}
+
+/// The serde name of a unit enum variant.
+fn variant_name(value: &impl Serialize) -> String {
+    match serde_json::to_value(value) {
+        Ok(Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+impl Replicated for AgentChannel {
+    fn doc_id(&self) -> String {
+        format!("channel:{}", self.id)
+    }
+
+    fn to_fields(&self) -> BTreeMap<String, Value> {
+        let mut out = BTreeMap::new();
+        insert_fields(
+            &mut out,
+            &["meta"],
+            self,
+            &[
+                "id",
+                "assigned_agents",
+                "messages",
+                "threads",
+                "pinned_files",
+            ],
+        );
+        for agent in &self.assigned_agents {
+            out.insert(key(&["agents", agent]), Value::Bool(true));
+        }
+        for path in &self.pinned_files {
+            out.insert(key(&["pinned", path]), Value::Bool(true));
+        }
+        for message in &self.messages {
+            let value = serde_json::to_value(message).unwrap_or_default();
+            out.insert(key(&["messages", &message.id]), value);
+        }
+        for thread in &self.threads {
+            let value = serde_json::to_value(thread).unwrap_or_default();
+            out.insert(key(&["threads", &thread.id]), value);
+        }
+        out
+    }
+
+    fn from_doc(doc: &CrdtDoc) -> Option<Self> {
+        let mut fields = read_fields(doc, "meta");
+        fields.insert("id".into(), doc.id().strip_prefix("channel:")?.into());
+        fields.insert("assigned_agents".into(), read_set(doc, "agents").into());
+        fields.insert("pinned_files".into(), read_set(doc, "pinned").into());
+        fields.insert(
+            "messages".into(),
+            Value::Array(read_items::<Value>(doc, "messages")),
+        );
+        fields.insert(
+            "threads".into(),
+            Value::Array(read_items::<Value>(doc, "threads")),
+        );
+        serde_json::from_value(Value::Object(fields)).ok()
+    }
+}
+
+impl Replicated for Spec {
+    fn doc_id(&self) -> String {
+        format!("spec:{}", self.id)
+    }
+
+    fn to_fields(&self) -> BTreeMap<String, Value> {
+        let mut out = BTreeMap::new();
+        insert_fields(&mut out, &["meta"], self, &["id", "sections"]);
+        for (section, entries) in &self.sections {
+            let section = variant_name(section);
+            for entry in entries {
+                insert_fields(&mut out, &["sections", &section, &entry.id], entry, &["id"]);
+            }
+        }
+        out
+    }
+
+    fn from_doc(doc: &CrdtDoc) -> Option<Self> {
+        let mut fields = read_fields(doc, "meta");
+        fields.insert("id".into(), doc.id().strip_prefix("spec:")?.into());
+
+        let mut sections = Map::new();
+        for section in SpecSection::ALL {
+            let name = variant_name(&section);
+            let prefix = key(&["sections", &name]);
+            let entries: Vec<Value> = doc
+                .children(&format!("{prefix}/"))
+                .into_iter()
+                .filter_map(|id| {
+                    let mut entry = read_fields(doc, &format!("{prefix}/{id}"));
+                    entry.insert("id".into(), unescape_segment(&id).into());
+                    let entry = Value::Object(entry);
+                    // Skip entries a concurrent delete left incomplete.
+                    serde_json::from_value::<crate::specs::SpecEntry>(entry.clone())
+                        .ok()
+                        .map(|_| entry)
+                })
+                .collect();
+            sections.insert(name, Value::Array(entries));
+        }
+        fields.insert("sections".into(), Value::Object(sections));
+        serde_json::from_value(Value::Object(fields)).ok()
+    }
+}
+
+/// The workspace kanban board is a single shared document.
+impl Replicated for KanbanBoard {
+    fn doc_id(&self) -> String {
+        "kanban:board".into()
+    }
+
+    fn to_fields(&self) -> BTreeMap<String, Value> {
+        let mut out = BTreeMap::new();
+        if let Ok(Value::Object(board)) = serde_json::to_value(self)
+            && let Some(Value::Object(limits)) = board.get("wip_limits")
+        {
+            for (column, limit) in limits {
+                out.insert(key(&["wip", column]), limit.clone());
+            }
+        }
+        for task in self.all_tasks() {
+            let id = task.id.as_str();
+            insert_fields(
+                &mut out,
+                &["tasks", id],
+                task,
+                &["id", "subtasks", "comments"],
+            );
+            for subtask in &task.subtasks {
+                let value = serde_json::to_value(subtask).unwrap_or_default();
+                out.insert(key(&["tasks", id, "subtasks", &subtask.id]), value);
+            }
+            for comment in &task.comments {
+                let value = serde_json::to_value(comment).unwrap_or_default();
+                out.insert(key(&["tasks", id, "comments", &comment.id]), value);
+            }
+        }
+        out
+    }
+
+    fn from_doc(doc: &CrdtDoc) -> Option<Self> {
+        let tasks: Vec<Value> = doc
+            .children("tasks/")
+            .into_iter()
+            .filter_map(|id| {
+                let prefix = format!("tasks/{id}");
+                let mut task = read_fields(doc, &prefix);
+                task.insert("id".into(), unescape_segment(&id).into());
+                task.insert(
+                    "subtasks".into(),
+                    Value::Array(read_items(doc, &format!("{prefix}/subtasks"))),
+                );
+                task.insert(
+                    "comments".into(),
+                    Value::Array(read_items(doc, &format!("{prefix}/comments"))),
+                );
+                let task = Value::Object(task);
+                serde_json::from_value::<hive_core::kanban::KanbanTask>(task.clone())
+                    .ok()
+                    .map(|_| task)
+            })
+            .collect();
+        let limits = read_fields(doc, "wip");
+        serde_json::from_value(serde_json::json!({ "tasks": tasks, "wip_limits": limits })).ok()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Replica store
+// ---------------------------------------------------------------------------
+
+/// One replicated document and the state the application last saw of it.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct Replica {
+    doc: CrdtDoc,
+    base: BTreeMap<String, Value>,
+    /// Latest clock each peer has reported for the document.
+    #[serde(default)]
+    acked: BTreeMap<String, VectorClock>,
+}
+
+impl Replica {
+    /// The document's clock with no ops.
+    fn digest(&self) -> CrdtDelta {
+        CrdtDelta {
+            doc_id: self.doc.id().to_string(),
+            clock: self.doc.clock().clone(),
+            ops: Vec::new(),
+            snapshot: None,
+        }
+    }
+
+    /// What a peer at `clock` needs to catch up, or a bare clock if it has
+    /// ops we lack. `None` if both sides are in step.
+    fn catch_up(&self, clock: &VectorClock) -> Option<CrdtDelta> {
+        let doc = &self.doc;
+        let (ops, snapshot) = if doc.has_ops_since(clock) {
+            (doc.delta_since(clock), None)
+        } else {
+            (Vec::new(), Some(doc.snapshot()))
+        };
+        if ops.is_empty() && snapshot.is_none() && doc.clock().dominates(clock) {
+            return None;
+        }
+        Some(CrdtDelta {
+            doc_id: doc.id().to_string(),
+            clock: doc.clock().clone(),
+            ops,
+            snapshot,
+        })
+    }
+
+    /// Drop ops every peer that has synced the document has acknowledged.
+    fn compact(&mut self) -> bool {
+        let mut clocks = self.acked.values();
+        let Some(first) = clocks.next() else {
+            return false;
+        };
+        let acked = clocks.fold(first.clone(), |acked, clock| acked.meet(clock));
+        self.doc.compact(&acked) > 0
+    }
+}
+
+/// What a [`ReplicaStore`] did with a peer's sync payload.
+#[derive(Debug, Default)]
+pub struct SyncOutcome {
+    /// Documents that changed and should be re-materialized.
+    pub changed: Vec<String>,
+    /// Documents whose stored state changed, including acknowledgements and
+    /// compaction, and should be saved.
+    pub modified: Vec<String>,
+    /// What to send back, if the peer lacks ops or has ops we lack.
+    pub reply: Option<CrdtSyncPayload>,
+}
+
+/// All documents replicated by this node.
+#[derive(Debug, Clone)]
+pub struct ReplicaStore {
+    signer: NodeKeypair,
+    replica: String,
+    docs: BTreeMap<String, Replica>,
+}
+
+impl ReplicaStore {
+    /// Create an empty store whose edits are attributed to, and signed by,
+    /// the node holding `signer`.
+    pub fn new(signer: NodeKeypair) -> Self {
+        Self {
+            replica: signer.peer_id().to_string(),
+            signer,
+            docs: BTreeMap::new(),
+        }
+    }
+
+    /// The replica id local edits are attributed to: the node's peer id.
+    pub fn replica(&self) -> &str {
+        &self.replica
+    }
+
+    /// Ids of all documents in the store.
+    pub fn doc_ids(&self) -> impl Iterator<Item = &str> {
+        self.docs.keys().map(String::as_str)
+    }
+
+    /// The document with `doc_id`, if present.
+    pub fn doc(&self, doc_id: &str) -> Option<&CrdtDoc> {
+        self.docs.get(doc_id).map(|r| &r.doc)
+    }
+
+    fn replica_mut(&mut self, doc_id: &str) -> &mut Replica {
+        let signer = &self.signer;
+        self.docs
+            .entry(doc_id.to_string())
+            .or_insert_with(|| Replica {
+                doc: CrdtDoc::signed(doc_id, signer.clone()),
+                base: BTreeMap::new(),
+                acked: BTreeMap::new(),
+            })
+    }
+
+    /// Record the local edits made to `value` since it was last recorded or
+    /// materialized, returning the new ops.
+    ///
+    /// Only fields that differ from that earlier state become ops, so remote
+    /// changes applied in between are not overwritten.
+    pub fn record<T: Replicated>(&mut self, value: &T) -> Vec<Op> {
+        let after = value.to_fields();
+        let replica = self.replica_mut(&value.doc_id());
+
+        let mut ops = Vec::new();
+        for (k, v) in &after {
+            if replica.base.get(k) != Some(v) {
+                ops.push(replica.doc.set(k.clone(), v.clone()));
+            }
+        }
+        for k in replica.base.keys() {
+            if !after.contains_key(k) {
+                ops.push(replica.doc.delete(k.clone()));
+            }
+        }
+        replica.base = after;
+        ops
+    }
+
+    /// Current merged value of a document, which also becomes the baseline
+    /// for the next [`record`](Self::record).
+    pub fn materialize<T: Replicated>(&mut self, doc_id: &str) -> Option<T> {
+        let replica = self.docs.get_mut(doc_id)?;
+        let value = T::from_doc(&replica.doc)?;
+        replica.base = value.to_fields();
+        Some(value)
+    }
+
+    /// Clocks for every document, asking a peer for whatever we lack.
+    pub fn digest(&self) -> CrdtSyncPayload {
+        CrdtSyncPayload {
+            docs: self.docs.values().map(Replica::digest).collect(),
+            complete: true,
+        }
+    }
+
+    /// Ops to push to peers after a local edit.
+    pub fn delta(&self, doc_id: &str, ops: Vec<Op>) -> CrdtSyncPayload {
+        CrdtSyncPayload {
+            docs: self
+                .doc(doc_id)
+                .map(|doc| CrdtDelta {
+                    doc_id: doc_id.to_string(),
+                    clock: doc.clock().clone(),
+                    ops,
+                    snapshot: None,
+                })
+                .into_iter()
+                .collect(),
+            complete: false,
+        }
+    }
+
+    /// Apply a payload from the authenticated peer `from` and work out the
+    /// reply.
+    ///
+    /// Ops not signed by the replica they claim are dropped. The clock the
+    /// peer reports is recorded as its acknowledgement, and ops every known
+    /// peer has acknowledged are compacted out of the log.
+    pub fn receive(&mut self, from: &str, payload: CrdtSyncPayload) -> SyncOutcome {
+        let mut outcome = SyncOutcome::default();
+        let mut reply = Vec::new();
+        let listed: HashSet<String> = payload.docs.iter().map(|d| d.doc_id.clone()).collect();
+
+        for delta in payload.docs {
+            let replica = self.replica_mut(&delta.doc_id);
+            let mut changed = false;
+            if let Some(snapshot) = delta.snapshot {
+                changed |= replica.doc.apply_snapshot(snapshot);
+            }
+            changed |= replica.doc.apply_all_remote(delta.ops) > 0;
+
+            let acked = replica.acked.entry(from.to_string()).or_default();
+            let acknowledged = !acked.dominates(&delta.clock);
+            acked.merge(&delta.clock);
+
+            reply.extend(replica.catch_up(&delta.clock));
+            let compacted = replica.compact();
+            if changed {
+                outcome.changed.push(delta.doc_id.clone());
+            }
+            if changed || acknowledged || compacted {
+                outcome.modified.push(delta.doc_id);
+            }
+        }
+
+        if payload.complete {
+            // Send unlisted documents even when empty, so the peer has them.
+            for (doc_id, replica) in &self.docs {
+                if !listed.contains(doc_id) {
+                    let delta = replica.catch_up(&VectorClock::new());
+                    reply.push(delta.unwrap_or_else(|| replica.digest()));
+                }
+            }
+        }
+
+        if !reply.is_empty() {
+            outcome.reply = Some(CrdtSyncPayload {
+                docs: reply,
+                complete: false,
+            });
+        }
+        outcome
+    }
+
+    /// Save one document, including unsynced offline edits, to its own JSON
+    /// file in `dir`.
+    pub fn save_doc(&self, dir: &Path, doc_id: &str) -> Result<()> {
+        let Some(replica) = self.docs.get(doc_id) else {
+            return Ok(());
+        };
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        let json = serde_json::to_string(replica)
+            .with_context(|| format!("Failed to serialize replica {doc_id}"))?;
+        let path = dir.join(doc_file_name(doc_id));
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json).with_context(|| format!("Failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, &path).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Load the documents saved in `dir` for the node holding `signer`.
+    /// Unreadable files and documents of another replica are skipped.
+    pub fn load_or_new(dir: &Path, signer: NodeKeypair) -> Self {
+        let mut store = Self::new(signer);
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    warn!("Cannot read replica store, starting empty: {e}");
+                }
+                return store;
+            }
+        };
+        for path in entries.flatten().map(|e| e.path()) {
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let replica = std::fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|data| Ok(serde_json::from_str::<Replica>(&data)?));
+            match replica {
+                Ok(mut replica) if replica.doc.replica() == store.replica => {
+                    replica.doc.set_signer(store.signer.clone());
+                    store.docs.insert(replica.doc.id().to_string(), replica);
+                }
+                Ok(_) => warn!("Replica at {} belongs to another node", path.display()),
+                Err(e) => warn!("Skipping corrupt replica {}: {e}", path.display()),
+            }
+        }
+        store
+    }
+}
+
+/// File name for a document, with anything but ASCII letters, digits, `-`
+/// and `_` percent-encoded.
+fn doc_file_name(doc_id: &str) -> String {
+    let mut name = String::with_capacity(doc_id.len() + 5);
+    for byte in doc_id.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' {
+            name.push(byte as char);
+        } else {
+            name.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    name.push_str(".json");
+    name
+}
+
+// ---------------------------------------------------------------------------
+// Network service
+// ---------------------------------------------------------------------------
+
+/// State reachable from the router handler.
+struct Shared {
+    local: PeerId,
+    store: Mutex<ReplicaStore>,
+    storage: Option<PathBuf>,
+    changes: broadcast::Sender<String>,
+}
+
+impl Shared {
+    fn persist(&self, store: &ReplicaStore, doc_id: &str) {
+        if let Some(dir) = &self.storage
+            && let Err(e) = store.save_doc(dir, doc_id)
+        {
+            warn!("Failed to persist replica {doc_id}: {e:#}");
+        }
+    }
+
+    fn on_sync(&self, envelope: hive_network::Envelope) -> Option<hive_network::Envelope> {
+        let payload = match CrdtSyncHelper::parse_payload(&envelope) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("{e} from {}", envelope.from.short());
+                return None;
+            }
+        };
+
+        let outcome = {
+            let mut store = self.store.lock().unwrap();
+            let outcome = store.receive(envelope.from.as_str(), payload);
+            for doc_id in &outcome.modified {
+                self.persist(&store, doc_id);
+            }
+            outcome
+        };
+        for doc_id in outcome.changed {
+            debug!(doc_id, peer = %envelope.from.short(), "Merged remote edits");
+            let _ = self.changes.send(doc_id);
+        }
+        outcome
+            .reply
+            .map(|reply| CrdtSyncHelper::sync(self.local.clone(), Some(envelope.from), &reply))
+    }
+}
+
+/// Replicates a [`ReplicaStore`] with connected peers.
+pub struct ReplicationService {
+    node: Arc<HiveNode>,
+    shared: Arc<Shared>,
+}
+
+impl ReplicationService {
+    /// Register the [`MessageKind::CrdtSync`] handler on a node.
+    ///
+    /// Local edits are signed with the node's key. With `storage`, the store
+    /// is loaded from that directory and each document is saved after every
+    /// local or merged edit, so offline edits survive a restart.
+    pub async fn attach(node: Arc<HiveNode>, storage: Option<PathBuf>) -> Result<Self> {
+        let signer = node
+            .identity()
+            .keypair()
+            .cloned()
+            .context("Replication needs the node's private key")?;
+        let store = match &storage {
+            Some(dir) => ReplicaStore::load_or_new(dir, signer),
+            None => ReplicaStore::new(signer),
+        };
+        let (changes, _) = broadcast::channel(CHANGE_CHANNEL_CAPACITY);
+        let shared = Arc::new(Shared {
+            local: node.peer_id().clone(),
+            store: Mutex::new(store),
+            storage,
+            changes,
+        });
+
+        let handler_state = Arc::clone(&shared);
+        node.on_message(
+            MessageKind::CrdtSync,
+            Arc::new(move |envelope| {
+                let reply = handler_state.on_sync(envelope);
+                Box::pin(async move { reply })
+            }),
+        )
+        .await;
+
+        Ok(Self { node, shared })
+    }
+
+    /// Ids of documents changed by remote edits, as they are merged.
+    pub fn subscribe(&self) -> broadcast::Receiver<String> {
+        self.shared.changes.subscribe()
+    }
+
+    /// Record local edits to `value` and push them to connected peers.
+    ///
+    /// Edits made while offline are kept and exchanged on the next
+    /// [`sync_with`](Self::sync_with). Returns the number of new ops.
+    pub async fn record<T: Replicated>(&self, value: &T) -> usize {
+        let doc_id = value.doc_id();
+        let payload = {
+            let mut store = self.shared.store.lock().unwrap();
+            let ops = store.record(value);
+            if ops.is_empty() {
+                return 0;
+            }
+            self.shared.persist(&store, &doc_id);
+            store.delta(&doc_id, ops)
+        };
+        let count = payload.docs.first().map_or(0, |d| d.ops.len());
+
+        let envelope = CrdtSyncHelper::sync(self.shared.local.clone(), None, &payload);
+        if let Err(e) = self.node.broadcast(&envelope).await {
+            debug!(doc_id, "Edit kept for later sync: {e}");
+        }
+        count
+    }
+
+    /// Current merged value of a document.
+    pub fn materialize<T: Replicated>(&self, doc_id: &str) -> Option<T> {
+        self.shared.store.lock().unwrap().materialize(doc_id)
+    }
+
+    /// Reconcile every document with a connected peer.
+    pub async fn sync_with(&self, peer: &PeerId) -> Result<()> {
+        let digest = self.shared.store.lock().unwrap().digest();
+        let envelope = CrdtSyncHelper::sync(self.shared.local.clone(), Some(peer.clone()), &digest);
+        self.node
+            .send_to_peer(peer, &envelope)
+            .await
+            .context("Failed to send sync digest")
+    }
+
+    /// Reconcile every document with all connected peers. Returns the number
+    /// of peers asked.
+    pub async fn sync_all(&self) -> Result<usize> {
+        let digest = self.shared.store.lock().unwrap().digest();
+        let envelope = CrdtSyncHelper::sync(self.shared.local.clone(), None, &digest);
+        self.node
+            .broadcast(&envelope)
+            .await
+            .context("Failed to broadcast sync digest")
+    }
+
+    /// A snapshot of the store.
+    pub fn store(&self) -> ReplicaStore {
+        self.shared.store.lock().unwrap().clone()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use hive_core::channels::{ChannelMessage, MessageAuthor};
+    use hive_core::kanban::{KanbanColumn, Priority};
+    use proptest::prelude::*;
+    use std::time::Duration;
+
+    use crate::specs::SpecEntry;
+
+    /// Run the sync protocol between two stores until neither has a reply.
+    fn exchange(a: &mut ReplicaStore, b: &mut ReplicaStore) {
+        let mut message = Some(a.digest());
+        let mut to_b = true;
+        while let Some(payload) = message.take() {
+            let (sender, receiver) = if to_b { (&*a, &mut *b) } else { (&*b, &mut *a) };
+            message = receiver.receive(sender.replica(), payload).reply;
+            to_b = !to_b;
+        }
+    }
+
+    fn store() -> ReplicaStore {
+        ReplicaStore::new(NodeKeypair::generate())
+    }
+
+    fn board_json(board: &KanbanBoard) -> Value {
+        serde_json::to_value(board).unwrap()
+    }
+
+    fn message(id: &str, content: &str) -> ChannelMessage {
+        ChannelMessage {
+            id: id.into(),
+            author: MessageAuthor::User,
+            content: content.into(),
+            timestamp: Utc::now(),
+            thread_id: None,
+            model: None,
+            cost: None,
+        }
+    }
+
+    fn channel() -> AgentChannel {
+        AgentChannel {
+            id: "general".into(),
+            name: "General".into(),
+            icon: "#".into(),
+            description: "Team chat".into(),
+            assigned_agents: vec!["investigate".into()],
+            messages: vec![],
+            threads: vec![],
+            pinned_files: vec!["src/main.rs".into()],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn concurrent_kanban_edits_to_different_fields_both_survive() {
+        let mut a = store();
+        let mut b = store();
+
+        let mut board = KanbanBoard::new();
+        let task = board.add_task("Write docs", None, Priority::Medium);
+        a.record(&board);
+        exchange(&mut a, &mut b);
+
+        let mut board_a: KanbanBoard = a.materialize("kanban:board").unwrap();
+        let mut board_b: KanbanBoard = b.materialize("kanban:board").unwrap();
+        board_a.move_task(&task.id, KanbanColumn::Done).unwrap();
+        board_a.add_comment(&task.id, "alice", "shipped").unwrap();
+        board_b.get_task_mut(&task.id).unwrap().title = "Write API docs".into();
+        board_b.add_comment(&task.id, "bob", "reviewed").unwrap();
+        a.record(&board_a);
+        b.record(&board_b);
+
+        exchange(&mut a, &mut b);
+        let merged_a: KanbanBoard = a.materialize("kanban:board").unwrap();
+        let merged_b: KanbanBoard = b.materialize("kanban:board").unwrap();
+        assert_eq!(board_json(&merged_a), board_json(&merged_b));
+
+        let merged = merged_a.get_task(&task.id).unwrap();
+        assert_eq!(merged.column, KanbanColumn::Done);
+        assert_eq!(merged.title, "Write API docs");
+        assert_eq!(merged.comments.len(), 2);
+    }
+
+    #[test]
+    fn spec_check_and_content_edit_merge() {
+        let mut a = store();
+        let mut b = store();
+
+        let mut spec = Spec::new("auth", "Auth", "Login flow");
+        spec.sections
+            .get_mut(&SpecSection::Requirements)
+            .unwrap()
+            .push(SpecEntry::new("r1", "OAuth", "Support OAuth"));
+        a.record(&spec);
+        exchange(&mut a, &mut b);
+
+        let mut spec_a: Spec = a.materialize("spec:auth").unwrap();
+        let mut spec_b: Spec = b.materialize("spec:auth").unwrap();
+        spec_a.sections.get_mut(&SpecSection::Requirements).unwrap()[0].checked = true;
+        spec_b.sections.get_mut(&SpecSection::Requirements).unwrap()[0].content =
+            "Support OAuth and SSO".into();
+        spec_b
+            .sections
+            .get_mut(&SpecSection::Plan)
+            .unwrap()
+            .push(SpecEntry::new("p1", "Design", "Draft the flow"));
+        a.record(&spec_a);
+        b.record(&spec_b);
+        exchange(&mut b, &mut a);
+
+        let merged: Spec = a.materialize("spec:auth").unwrap();
+        let entry = &merged.sections[&SpecSection::Requirements][0];
+        assert!(entry.checked);
+        assert_eq!(entry.content, "Support OAuth and SSO");
+        assert_eq!(merged.sections[&SpecSection::Plan].len(), 1);
+        let other: Spec = b.materialize("spec:auth").unwrap();
+        assert_eq!(
+            serde_json::to_value(&merged.sections).unwrap(),
+            serde_json::to_value(&other.sections).unwrap()
+        );
+    }
+
+    #[test]
+    fn offline_channel_messages_merge_in_the_same_order() {
+        let mut a = store();
+        let mut b = store();
+        let mut base = channel();
+        base.messages.push(message("m0", "hello"));
+        a.record(&base);
+        exchange(&mut a, &mut b);
+
+        // Both sides post while disconnected; b also unpins a file.
+        let mut chan_a: AgentChannel = a.materialize("channel:general").unwrap();
+        let mut chan_b: AgentChannel = b.materialize("channel:general").unwrap();
+        chan_a.messages.push(message("m-a", "from a"));
+        chan_b.messages.push(message("m-b", "from b"));
+        chan_b.pinned_files.clear();
+        a.record(&chan_a);
+        b.record(&chan_b);
+
+        exchange(&mut a, &mut b);
+        let merged_a: AgentChannel = a.materialize("channel:general").unwrap();
+        let merged_b: AgentChannel = b.materialize("channel:general").unwrap();
+        let ids = |c: &AgentChannel| c.messages.iter().map(|m| m.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&merged_a), ids(&merged_b));
+        assert_eq!(ids(&merged_a)[0], "m0");
+        assert_eq!(merged_a.messages.len(), 3);
+        assert!(merged_a.pinned_files.is_empty());
+        assert_eq!(merged_a.assigned_agents, vec!["investigate".to_string()]);
+    }
+
+    #[test]
+    fn record_does_not_clobber_unseen_remote_edits() {
+        let mut a = store();
+        let mut b = store();
+        let mut spec = Spec::new("s", "Title", "Desc");
+        a.record(&spec);
+        exchange(&mut a, &mut b);
+
+        let mut remote: Spec = b.materialize("spec:s").unwrap();
+        remote.title = "Remote title".into();
+        b.record(&remote);
+        exchange(&mut b, &mut a);
+
+        // a edits its stale copy without re-materializing first.
+        spec.description = "Local desc".into();
+        a.record(&spec);
+        let merged: Spec = a.materialize("spec:s").unwrap();
+        assert_eq!(merged.title, "Remote title");
+        assert_eq!(merged.description, "Local desc");
+    }
+
+    #[test]
+    fn store_persists_unsynced_edits() {
+        let dir = tempfile::tempdir().unwrap();
+        let keypair = NodeKeypair::generate();
+        let mut store = ReplicaStore::new(keypair.clone());
+        store.record(&Spec::new("s", "Title", "Desc"));
+        store.record(&Spec::new("a/b", "Other", "Desc"));
+        store.save_doc(dir.path(), "spec:s").unwrap();
+        store.save_doc(dir.path(), "spec:a/b").unwrap();
+        assert!(dir.path().join("spec%3As.json").exists());
+
+        let mut loaded = ReplicaStore::load_or_new(dir.path(), keypair);
+        let spec: Spec = loaded.materialize("spec:s").unwrap();
+        assert_eq!(spec.title, "Title");
+        assert_eq!(loaded.doc_ids().count(), 2);
+
+        // Reloaded edits are still signed, so peers accept them.
+        let mut peer = self::store();
+        exchange(&mut loaded, &mut peer);
+        assert!(peer.materialize::<Spec>("spec:a/b").is_some());
+
+        let other = ReplicaStore::load_or_new(dir.path(), NodeKeypair::generate());
+        assert!(other.doc("spec:s").is_none());
+    }
+
+    #[test]
+    fn ops_forged_for_another_replica_are_dropped() {
+        let mut a = store();
+        let mut b = store();
+        let mut mallory = store();
+        a.record(&Spec::new("s", "Title", "Desc"));
+        exchange(&mut a, &mut b);
+
+        // Mallory relays a's ops faithfully, but cannot rewrite them.
+        let mut payload = a.delta(
+            "spec:s",
+            a.doc("spec:s").unwrap().delta_since(&VectorClock::new()),
+        );
+        for op in &mut payload.docs[0].ops {
+            if op.key == "title" {
+                op.value = Some(Value::from("Forged"));
+            }
+        }
+        let outcome = b.receive(mallory.replica(), payload.clone());
+        assert!(outcome.changed.is_empty());
+        mallory.receive(a.replica(), payload);
+        assert!(mallory.doc("spec:s").unwrap().get("title").is_none());
+
+        let spec: Spec = b.materialize("spec:s").unwrap();
+        assert_eq!(spec.title, "Title");
+    }
+
+    #[test]
+    fn acknowledged_history_is_compacted_and_newcomers_get_snapshots() {
+        let mut a = store();
+        let mut b = store();
+        let mut spec = Spec::new("s", "Title", "Desc");
+        a.record(&spec);
+        spec.title = "Renamed".into();
+        a.record(&spec);
+        exchange(&mut a, &mut b);
+        exchange(&mut b, &mut a);
+
+        // Both stores saw every op and told each other so.
+        let log = |s: &ReplicaStore| {
+            s.doc("spec:s")
+                .unwrap()
+                .delta_since(&VectorClock::new())
+                .len()
+        };
+        assert_eq!(log(&a), 0);
+        assert_eq!(log(&b), 0);
+
+        // A node joining later is caught up from a snapshot.
+        let mut c = store();
+        exchange(&mut c, &mut a);
+        let caught_up: Spec = c.materialize("spec:s").unwrap();
+        assert_eq!(caught_up.title, "Renamed");
+
+        // c has not acknowledged anything new to a yet, so a keeps new ops.
+        spec.description = "Edited".into();
+        a.record(&spec);
+        assert_eq!(log(&a), 1);
+        exchange(&mut a, &mut c);
+        exchange(&mut a, &mut b);
+        let merged: Spec = c.materialize("spec:s").unwrap();
+        assert_eq!(merged.description, "Edited");
+        assert_eq!(
+            b.materialize::<Spec>("spec:s").unwrap().description,
+            "Edited"
+        );
+    }
+
+    // -----------------------------------------------------------------------
+    // Convergence property
+    // -----------------------------------------------------------------------
+
+    #[derive(Debug, Clone)]
+    enum BoardEdit {
+        Add {
+            node: usize,
+            title: u8,
+        },
+        Move {
+            node: usize,
+            task: usize,
+            column: usize,
+        },
+        Rename {
+            node: usize,
+            task: usize,
+            title: u8,
+        },
+        Comment {
+            node: usize,
+            task: usize,
+        },
+        Delete {
+            node: usize,
+            task: usize,
+        },
+        Sync,
+    }
+
+    fn board_edit() -> impl Strategy<Value = BoardEdit> {
+        prop_oneof![
+            (0..2usize, any::<u8>()).prop_map(|(node, title)| BoardEdit::Add { node, title }),
+            (0..2usize, 0..8usize, 0..5usize).prop_map(|(node, task, column)| BoardEdit::Move {
+                node,
+                task,
+                column
+            }),
+            (0..2usize, 0..8usize, any::<u8>()).prop_map(|(node, task, title)| BoardEdit::Rename {
+                node,
+                task,
+                title
+            }),
+            (0..2usize, 0..8usize).prop_map(|(node, task)| BoardEdit::Comment { node, task }),
+            (0..2usize, 0..8usize).prop_map(|(node, task)| BoardEdit::Delete { node, task }),
+            Just(BoardEdit::Sync),
+        ]
+    }
+
+    proptest! {
+        /// Two nodes editing a board independently converge after syncing.
+        #[test]
+        fn prop_boards_converge(edits in prop::collection::vec(board_edit(), 0..40)) {
+            let mut stores = [store(), store()];
+            let mut boards = [KanbanBoard::new(), KanbanBoard::new()];
+
+            for edit in edits {
+                let node = match edit {
+                    BoardEdit::Sync => {
+                        let [a, b] = &mut stores;
+                        exchange(a, b);
+                        for (store, board) in stores.iter_mut().zip(boards.iter_mut()) {
+                            if let Some(merged) = store.materialize("kanban:board") {
+                                *board = merged;
+                            }
+                        }
+                        continue;
+                    }
+                    BoardEdit::Add { node, .. }
+                    | BoardEdit::Move { node, .. }
+                    | BoardEdit::Rename { node, .. }
+                    | BoardEdit::Comment { node, .. }
+                    | BoardEdit::Delete { node, .. } => node,
+                };
+                let board = &mut boards[node];
+                let ids: Vec<String> = board.all_tasks().iter().map(|t| t.id.clone()).collect();
+                let pick = |i: usize| ids.get(i % ids.len().max(1)).cloned();
+                match edit {
+                    BoardEdit::Add { title, .. } => {
+                        board.add_task(format!("task {title}"), None, Priority::Low);
+                    }
+                    BoardEdit::Move { task, column, .. } => {
+                        if let Some(id) = pick(task) {
+                            board.move_task(&id, KanbanColumn::all()[column]).unwrap();
+                        }
+                    }
+                    BoardEdit::Rename { task, title, .. } => {
+                        if let Some(id) = pick(task) {
+                            board.get_task_mut(&id).unwrap().title = format!("renamed {title}");
+                        }
+                    }
+                    BoardEdit::Comment { task, .. } => {
+                        if let Some(id) = pick(task) {
+                            board.add_comment(&id, "agent", "note").unwrap();
+                        }
+                    }
+                    BoardEdit::Delete { task, .. } => {
+                        if let Some(id) = pick(task) {
+                            board.delete_task(&id).unwrap();
+                        }
+                    }
+                    BoardEdit::Sync => unreachable!(),
+                }
+                stores[node].record(&boards[node]);
+            }
+
+            let [a, b] = &mut stores;
+            exchange(a, b);
+            let merged_a = a.materialize::<KanbanBoard>("kanban:board").map(|b| board_json(&b));
+            let merged_b = b.materialize::<KanbanBoard>("kanban:board").map(|b| board_json(&b));
+            prop_assert_eq!(merged_a, merged_b);
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Over the network
+    // -----------------------------------------------------------------------
+
+    async fn wait_for<T>(mut probe: impl FnMut() -> Option<T>) -> T {
+        for _ in 0..100 {
+            if let Some(value) = probe() {
+                return value;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("condition not reached");
+    }
+
+    #[tokio::test]
+    async fn nodes_push_edits_and_reconcile_offline_changes() {
+        use hive_network::{NetworkConfig, NodeIdentity};
+
+        let free_addr = || {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap()
+        };
+        let local = |addr| NetworkConfig {
+            listen_addr: addr,
+            discovery_enabled: false,
+            ..NetworkConfig::default()
+        };
+        let addr_b = free_addr();
+        let mut node_a = HiveNode::new(NodeIdentity::generate("a"), local(free_addr()));
+        let mut node_b = HiveNode::new(NodeIdentity::generate("b"), local(addr_b));
+        node_a.trust_peer(node_b.peer_id().clone(), "b");
+        node_b.trust_peer(node_a.peer_id().clone(), "a");
+        node_a.start().await.unwrap();
+        node_b.start().await.unwrap();
+        let (node_a, node_b) = (Arc::new(node_a), Arc::new(node_b));
+
+        let sync_a = ReplicationService::attach(Arc::clone(&node_a), None)
+            .await
+            .unwrap();
+        let sync_b = ReplicationService::attach(Arc::clone(&node_b), None)
+            .await
+            .unwrap();
+
+        // Edits made before connecting stay local...
+        let mut spec = Spec::new("offline", "Offline", "Written before connecting");
+        assert!(sync_a.record(&spec).await > 0);
+        let mut board = KanbanBoard::new();
+        board.add_task("Triage", None, Priority::High);
+        sync_b.record(&board).await;
+        assert!(sync_b.materialize::<Spec>("spec:offline").is_none());
+
+        // ...and merge on reconnect.
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        node_a.connect_to(&addr_b.to_string()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let mut changes = sync_b.subscribe();
+        sync_a.sync_with(node_b.peer_id()).await.unwrap();
+
+        let synced: Spec = wait_for(|| sync_b.materialize("spec:offline")).await;
+        assert_eq!(synced.title, "Offline");
+        let board_a: KanbanBoard = wait_for(|| sync_a.materialize("kanban:board")).await;
+        assert_eq!(board_a.all_tasks()[0].title, "Triage");
+        assert_eq!(changes.recv().await.unwrap(), "spec:offline");
+
+        // Live edits are pushed as they are recorded.
+        spec.status = crate::specs::SpecStatus::Active;
+        sync_a.record(&spec).await;
+        wait_for(|| {
+            sync_b
+                .materialize::<Spec>("spec:offline")
+                .filter(|s| s.status == crate::specs::SpecStatus::Active)
+        })
+        .await;
+    }
+}
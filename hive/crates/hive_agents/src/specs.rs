@@ -4,6 +4,7 @@
 //! versioning, and auto-update support. Agents can update specs as they work,
 //! keeping the living document in sync with progress.
 
+use hive_core::search::{SearchDocument, SearchSource};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -151,6 +152,24 @@ impl Spec {
     }
 }
 
+impl From<&Spec> for SearchDocument {
+    fn from(spec: &Spec) -> Self {
+        let mut body = spec.description.clone();
+        for section in SpecSection::ALL {
+            let entries = spec.sections.get(&section).map_or(&[][..], Vec::as_slice);
+            if entries.is_empty() {
+                continue;
+            }
+            body.push_str(&format!("\n\n## {}", section.label()));
+            for entry in entries {
+                body.push_str(&format!("\n- {}: {}", entry.title, entry.content));
+            }
+        }
+        SearchDocument::new(SearchSource::Spec, &spec.id, &spec.title, body)
+            .updated(spec.updated_at)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Spec Manager
 // ---------------------------------------------------------------------------
@@ -390,6 +409,27 @@ mod tests {
         (mgr, id)
     }
 
+    #[test]
+    fn spec_converts_to_search_document() {
+        let (mut mgr, id) = make_manager_with_spec();
+        mgr.add_entry(
+            &id,
+            SpecSection::Plan,
+            SpecEntry::new("p1", "Index docs", "Feed crawled pages into search"),
+        )
+        .unwrap();
+
+        let doc = SearchDocument::from(mgr.get_spec(&id).unwrap());
+        assert_eq!(doc.source, SearchSource::Spec);
+        assert_eq!(doc.title, "Test Spec");
+        assert!(doc.body.starts_with("A test specification"));
+        assert!(
+            doc.body
+                .contains("## Plan\n- Index docs: Feed crawled pages into search")
+        );
+        assert!(!doc.body.contains("## Notes"));
+    }
+
     #[test]
     fn create_spec_returns_unique_id() {
         let mut mgr = SpecManager::new();
@@ -3,9 +3,11 @@
 
 use anyhow::Result;
 use hive_core::SecurityGateway;
+use hive_core::search::{SearchHub, SearchQuery, SearchSource, render_context};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Arc;
 use tracing::debug;
 
 // ---------------------------------------------------------------------------
@@ -630,6 +632,79 @@ pub fn format_openai_result(result: &ToolResult) -> serde_json::Value {
     })
 }
 
+/// Searches the unified knowledge index (conversations, notes, docs, code,
+/// specs, tasks, collective memory).
+///
+/// Not part of the built-in set because it needs the app's `SearchHub`;
+/// register it with `ToolRegistry::register_tool`.
+pub struct SearchKnowledgeTool {
+    hub: Arc<SearchHub>,
+}
+
+impl SearchKnowledgeTool {
+    pub fn new(hub: Arc<SearchHub>) -> Self {
+        Self { hub }
+    }
+}
+
+impl ToolHandler for SearchKnowledgeTool {
+    fn name(&self) -> &str {
+        "search_knowledge"
+    }
+
+    fn description(&self) -> &str {
+        "Full-text search over past conversations, notes, indexed documentation, code, \
+         specs, kanban tasks, and collective memory. Supports \"phrases\", title:term, \
+         -excluded and prefix* terms."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string", "description": "The search query" },
+                "sources": {
+                    "type": "array",
+                    "items": {
+                        "type": "string",
+                        "enum": ["conversation", "note", "docs", "code", "spec", "task", "memory"]
+                    },
+                    "description": "Limit the search to these sources (default: all)"
+                },
+                "limit": { "type": "integer", "description": "Maximum results (default: 10)" }
+            },
+            "required": ["query"]
+        })
+    }
+
+    fn execute(&self, args: serde_json::Value) -> Result<String, String> {
+        let text = args
+            .get("query")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "Missing required argument: query".to_string())?;
+        let sources = args
+            .get("sources")
+            .and_then(|v| v.as_array())
+            .map(|names| {
+                names
+                    .iter()
+                    .filter_map(|n| n.as_str().and_then(SearchSource::parse))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
+
+        let query = SearchQuery::new(text)
+            .with_sources(sources)
+            .with_limit(limit);
+        let hits = self.hub.query(&query).map_err(|e| format!("{e}"))?;
+        if hits.is_empty() {
+            return Ok("No matches found.".into());
+        }
+        Ok(render_context(&hits))
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tool registry
 // ---------------------------------------------------------------------------
@@ -1829,4 +1904,44 @@ mod tests {
         let schema = tool.parameters_schema();
         assert!(schema["properties"]["command"].is_object());
     }
+
+    #[test]
+    fn test_search_knowledge_tool_queries_hub() {
+        use hive_core::search::SearchDocument;
+
+        let hub = Arc::new(SearchHub::in_memory().unwrap());
+        hub.index(&SearchDocument::new(
+            SearchSource::Docs,
+            "tokio/spawn",
+            "tokio::spawn",
+            "Spawns a new asynchronous task on the runtime.",
+        ))
+        .unwrap();
+        hub.index(&SearchDocument::new(
+            SearchSource::Task,
+            "7",
+            "Fix release build",
+            "The async runtime panics on shutdown.",
+        ))
+        .unwrap();
+
+        let tool = SearchKnowledgeTool::new(hub);
+        let all = tool
+            .execute(serde_json::json!({ "query": "runtime" }))
+            .unwrap();
+        assert!(all.contains("tokio::spawn"));
+        assert!(all.contains("Fix release build"));
+
+        let docs_only = tool
+            .execute(serde_json::json!({ "query": "runtime", "sources": ["docs"] }))
+            .unwrap();
+        assert!(docs_only.contains("tokio::spawn"));
+        assert!(!docs_only.contains("Fix release build"));
+
+        let none = tool
+            .execute(serde_json::json!({ "query": "kubernetes" }))
+            .unwrap();
+        assert_eq!(none, "No matches found.");
+        assert!(tool.execute(serde_json::json!({})).is_err());
+    }
 }
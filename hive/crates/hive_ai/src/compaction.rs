@@ -0,0 +1,200 @@
+//! Context compaction — asks a cheap model to fold the oldest part of a long
+//! conversation into a structured session memory.
+//!
+//! Choosing what to compact, and keeping the originals for search and undo,
+//! is handled by [`hive_core::context::ContextWindow`]; this module only
+//! picks the summarizer model and runs the call.
+
+use hive_core::context::{
+    CompactionConfig, CompactionPlan, CompactionRecord, ContextWindow, SessionMemory,
+};
+use tracing::{debug, info};
+
+use crate::providers::{AiProvider, ProviderError};
+use crate::speculative::infer_tier_from_model;
+use crate::types::{ChatMessage, ChatRequest, MessageRole, ModelTier};
+
+/// Summarizer used when the primary model is a paid cloud model.
+pub const DEFAULT_COMPACTION_MODEL: &str = "gpt-4o-mini";
+
+/// Upper bound on the summary length.
+const SUMMARY_MAX_TOKENS: u32 = 1024;
+
+// ---------------------------------------------------------------------------
+// Model selection
+// ---------------------------------------------------------------------------
+
+/// Pick the model that writes session memories for `primary_model`.
+///
+/// An explicit `override_model` wins. Local models summarize their own
+/// conversations so nothing leaves the machine; everything else uses
+/// [`DEFAULT_COMPACTION_MODEL`].
+pub fn select_compaction_model(primary_model: &str, override_model: Option<&str>) -> String {
+    if let Some(model) = override_model.filter(|m| !m.is_empty()) {
+        return model.to_string();
+    }
+    match infer_tier_from_model(primary_model) {
+        ModelTier::Free => primary_model.to_string(),
+        _ => DEFAULT_COMPACTION_MODEL.to_string(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Summarization
+// ---------------------------------------------------------------------------
+
+/// Ask `model` to summarize the messages in `plan`.
+pub async fn summarize(
+    provider: &dyn AiProvider,
+    model: &str,
+    plan: &CompactionPlan,
+) -> Result<SessionMemory, ProviderError> {
+    let request = ChatRequest {
+        messages: vec![ChatMessage::text(MessageRole::User, plan.prompt())],
+        model: model.to_string(),
+        max_tokens: SUMMARY_MAX_TOKENS,
+        temperature: Some(0.0),
+        system_prompt: None,
+        tools: None,
+    };
+    debug!(
+        "Compacting {} messages ({} tokens) with {model}",
+        plan.messages.len(),
+        plan.tokens
+    );
+
+    let response = provider.chat(&request).await?;
+    let memory = SessionMemory::parse(&response.content);
+    if memory.summary.trim().is_empty() {
+        return Err(ProviderError::Other(
+            "Summarizer returned an empty summary".into(),
+        ));
+    }
+    Ok(memory)
+}
+
+/// Compact `window` if its usage has crossed `config.threshold`.
+///
+/// Returns the applied compaction, or `None` if none was needed or nothing
+/// could be compacted.
+pub async fn compact_if_needed(
+    provider: &dyn AiProvider,
+    model: &str,
+    window: &mut ContextWindow,
+    config: &CompactionConfig,
+) -> Result<Option<CompactionRecord>, ProviderError> {
+    if !window.needs_compaction(config) {
+        return Ok(None);
+    }
+    let Some(plan) = window.compaction_plan(config) else {
+        return Ok(None);
+    };
+
+    let memory = summarize(provider, model, &plan).await?;
+    let record = window
+        .apply_compaction(&plan, memory, Some(model))
+        .map_err(|e| ProviderError::Other(e.to_string()))?;
+    info!(
+        "Compacted {} messages: {} -> {} tokens",
+        record.message_ids.len(),
+        record.tokens_before,
+        record.tokens_after
+    );
+    Ok(Some(record))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::mock::MockProvider;
+    use hive_core::context::ContextMessage;
+
+    fn full_window() -> ContextWindow {
+        let mut window = ContextWindow::new(100);
+        for i in 0..10 {
+            window.push(ContextMessage::new("user", format!("{i:0>40}")));
+        }
+        window
+    }
+
+    #[test]
+    fn select_model_prefers_override_then_local() {
+        assert_eq!(
+            select_compaction_model("claude-opus-4", Some("haiku")),
+            "haiku"
+        );
+        assert_eq!(
+            select_compaction_model("claude-opus-4", Some("")),
+            DEFAULT_COMPACTION_MODEL
+        );
+        assert_eq!(select_compaction_model("phi3", None), "phi3");
+    }
+
+    #[tokio::test]
+    async fn compact_if_needed_applies_model_summary() {
+        let provider =
+            MockProvider::new(r#"{"summary": "Agreed on the plan", "decisions": ["Ship Friday"]}"#);
+        let mut window = full_window();
+
+        let record = compact_if_needed(
+            &provider,
+            "gpt-4o-mini",
+            &mut window,
+            &CompactionConfig::default(),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(record.memory.decisions, ["Ship Friday"]);
+        assert_eq!(record.model.as_deref(), Some("gpt-4o-mini"));
+        assert!(record.tokens_after < record.tokens_before);
+        assert!(window.messages()[0].content.contains("Agreed on the plan"));
+
+        let requests = provider.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].temperature, Some(0.0));
+        assert!(requests[0].messages[0].content.contains(&"0".repeat(40)));
+    }
+
+    #[tokio::test]
+    async fn compact_if_needed_skips_below_threshold() {
+        let provider = MockProvider::new("unused");
+        let mut window = ContextWindow::new(1000);
+        window.push(ContextMessage::new("user", "hello"));
+
+        let result = compact_if_needed(
+            &provider,
+            "gpt-4o-mini",
+            &mut window,
+            &CompactionConfig::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(result.is_none());
+        assert!(provider.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn empty_summary_is_an_error() {
+        let provider = MockProvider::new("   ");
+        let mut window = full_window();
+        let before = window.message_count();
+
+        let result = compact_if_needed(
+            &provider,
+            "gpt-4o-mini",
+            &mut window,
+            &CompactionConfig::default(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(window.message_count(), before);
+    }
+}
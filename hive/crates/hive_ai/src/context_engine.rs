@@ -72,6 +72,10 @@ pub struct ContextBudget {
     pub max_sources: usize,
     /// Tokens reserved for the prompt/response (subtracted from max_tokens).
     pub reserved_tokens: usize,
+    /// Target model. When set, sources are measured with the model's
+    /// tokenizer instead of the `chars / 4` heuristic.
+    #[serde(default)]
+    pub model: Option<String>,
 }
 
 impl Default for ContextBudget {
@@ -80,6 +84,7 @@ impl Default for ContextBudget {
             max_tokens: 8000,
             max_sources: 50,
             reserved_tokens: 0,
+            model: None,
         }
     }
 }
@@ -256,7 +261,10 @@ impl ContextEngine {
                 break;
             }
             let source = &self.sources[rs.source_idx];
-            let tokens = self.estimate_source_tokens(source);
+            let tokens = match &budget.model {
+                Some(model) => crate::cost::count_tokens(model, &source.content),
+                None => self.estimate_source_tokens(source),
+            };
             if total_tokens + tokens > available_tokens {
                 continue;
             }
@@ -575,6 +583,7 @@ mod tests {
             max_tokens: 100_000,
             max_sources: 100,
             reserved_tokens: 0,
+            model: None,
         }
     }
 
@@ -646,6 +655,7 @@ mod tests {
             max_tokens: 30, // Very tight — should fit only a couple.
             max_sources: 100,
             reserved_tokens: 0,
+            model: None,
         };
         let result = engine.curate("func", &budget);
 
@@ -664,6 +674,7 @@ mod tests {
             max_tokens: 100_000,
             max_sources: 3,
             reserved_tokens: 0,
+            model: None,
         };
         let result = engine.curate("hello", &budget);
 
@@ -844,6 +855,7 @@ mod tests {
             max_tokens: 60,
             max_sources: 100,
             reserved_tokens: 40,
+            model: None,
         };
         let result = engine.curate("function", &tight_budget);
 
@@ -851,6 +863,24 @@ mod tests {
         assert!(result.total_tokens <= 20);
     }
 
+    #[test]
+    fn test_budget_with_model_uses_tokenizer() {
+        let mut engine = ContextEngine::new();
+        // 134 CJK chars (402 UTF-8 bytes): the bytes/4 heuristic says ~100
+        // tokens, cl100k far more.
+        engine.add_file("notes.md", &"\u{65e5}\u{672c}".repeat(67));
+
+        let heuristic = engine.curate("notes", &default_budget());
+        let exact = engine.curate(
+            "notes",
+            &ContextBudget {
+                model: Some("gpt-4-turbo".into()),
+                ..default_budget()
+            },
+        );
+        assert!(exact.total_tokens > heuristic.total_tokens);
+    }
+
     #[test]
     fn test_curated_context_serialization() {
         let mut engine = ContextEngine::new();
@@ -1,22 +1,41 @@
 //! Cost tracking and token estimation for AI model usage.
 //!
-//! Provides token counting (heuristic), cost calculation from model pricing,
-//! budget tracking with daily/monthly limits, and cost history.
+//! Provides token counting (model tokenizers with a heuristic fallback), cost
+//! calculation from model pricing, budget tracking with daily/monthly limits,
+//! cost history, and response cache savings.
 
 use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::model_registry::MODEL_REGISTRY;
+use hive_core::tokenizer::{TokenizerFamily, TokenizerRegistry};
+use hive_docs::xlsx::{
+    CellValue, ChartKind, ChartSpec, Column, ConditionalRule, Sheet, generate_xlsx_multi_sheet,
+};
+
+use crate::model_registry::{MODEL_REGISTRY, lookup_by_id};
 
 // ---------------------------------------------------------------------------
 // Token estimation
 // ---------------------------------------------------------------------------
 
+/// Count tokens in `text` with the tokenizer for `model_id`.
+///
+/// Registry models use [`ModelInfo::tokenizer_family`](crate::types::ModelInfo::tokenizer_family);
+/// unknown IDs are resolved by name. Falls back to [`estimate_tokens`] when
+/// no tokenizer is available.
+pub fn count_tokens(model_id: &str, text: &str) -> usize {
+    let family = lookup_by_id(model_id)
+        .map(|m| m.tokenizer_family())
+        .unwrap_or_else(|| TokenizerFamily::for_model(model_id));
+    TokenizerRegistry::global().count(&family, text)
+}
+
 /// Estimate token count from text using a character-based heuristic.
 ///
 /// Uses ~4 characters per token for English text (GPT/Claude average).
-/// This is a rough estimate — actual tokenization varies by model.
+/// This is a rough estimate used when the model is unknown — prefer
+/// [`count_tokens`].
 pub fn estimate_tokens(text: &str) -> usize {
     // ~4 chars per token for English, slightly less for code
     let chars = text.len();
@@ -73,7 +92,7 @@ pub fn calculate_cost(model_id: &str, input_tokens: usize, output_tokens: usize)
 ///
 /// Estimates output tokens as 2x the input (typical for chat responses).
 pub fn predict_cost(model_id: &str, input_text: &str) -> CostBreakdown {
-    let input_tokens = estimate_tokens(input_text);
+    let input_tokens = count_tokens(model_id, input_text);
     let estimated_output = input_tokens * 2; // rough heuristic
     calculate_cost(model_id, input_tokens, estimated_output)
 }
@@ -103,6 +122,35 @@ pub struct CostRecord {
     pub cost: f64,
 }
 
+/// Response cache effectiveness. Cached answers cost nothing, so they are
+/// counted here instead of as [`CostRecord`]s.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheStats {
+    /// Requests answered from the cache (exact and semantic).
+    pub hits: usize,
+    /// The subset of `hits` that matched a near-duplicate prompt.
+    pub semantic_hits: usize,
+    /// Cacheable requests that had to go to the provider.
+    pub misses: usize,
+    /// Requests that skipped the cache (non-zero temperature).
+    pub bypassed: usize,
+    /// Tokens the cached answers would have used.
+    pub saved_tokens: usize,
+    /// What the cached answers would have cost, in USD.
+    pub saved_cost: f64,
+}
+
+impl CacheStats {
+    /// Fraction of cacheable requests served from the cache (0.0–1.0).
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / lookups as f64
+    }
+}
+
 /// Budget limits for cost control.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[derive(Default)]
@@ -120,6 +168,7 @@ pub struct BudgetLimits {
 pub struct CostTracker {
     records: Vec<CostRecord>,
     budget: BudgetLimits,
+    cache: CacheStats,
 }
 
 impl CostTracker {
@@ -128,6 +177,7 @@ impl CostTracker {
         Self {
             records: Vec::new(),
             budget,
+            cache: CacheStats::default(),
         }
     }
 
@@ -143,6 +193,39 @@ impl CostTracker {
         });
     }
 
+    /// Record a request answered from the response cache. The tokens and
+    /// cost it would have taken count as savings, not spend.
+    pub fn record_cache_hit(
+        &mut self,
+        model_id: &str,
+        input_tokens: usize,
+        output_tokens: usize,
+        semantic: bool,
+    ) {
+        let breakdown = calculate_cost(model_id, input_tokens, output_tokens);
+        self.cache.hits += 1;
+        if semantic {
+            self.cache.semantic_hits += 1;
+        }
+        self.cache.saved_tokens += input_tokens + output_tokens;
+        self.cache.saved_cost += breakdown.total_cost;
+    }
+
+    /// Record a cacheable request that was not in the cache.
+    pub fn record_cache_miss(&mut self) {
+        self.cache.misses += 1;
+    }
+
+    /// Record a request that skipped the cache.
+    pub fn record_cache_bypass(&mut self) {
+        self.cache.bypassed += 1;
+    }
+
+    /// Response cache hit/miss counters and savings.
+    pub fn cache_stats(&self) -> &CacheStats {
+        &self.cache
+    }
+
     /// Total cost of all recorded requests.
     pub fn total_cost(&self) -> f64 {
         self.records.iter().map(|r| r.cost).sum()
@@ -242,9 +325,10 @@ impl CostTracker {
         self.records.retain(|r| r.timestamp.date_naive() != today);
     }
 
-    /// Clear all records.
+    /// Clear all records and cache statistics.
     pub fn clear(&mut self) {
         self.records.clear();
+        self.cache = CacheStats::default();
     }
 
     /// Get all records (for CSV export, etc.).
@@ -272,6 +356,92 @@ impl CostTracker {
         }
         csv
     }
+
+    /// Export a spreadsheet report: every request, cost per day with a line
+    /// chart, and cost per model with a bar chart and a total row.
+    ///
+    /// Requests that cost more than the daily budget (when one is set) are
+    /// highlighted.
+    pub fn export_xlsx(&self) -> anyhow::Result<Vec<u8>> {
+        const CURRENCY: &str = "$#,##0.0000";
+        const HIGHLIGHT: u32 = 0xFFC7CE;
+
+        let mut requests = Sheet::new(
+            "Requests",
+            vec![
+                Column::new("Time").with_width(18.0),
+                Column::new("Model"),
+                Column::new("Input tokens").with_number_format("#,##0"),
+                Column::new("Output tokens").with_number_format("#,##0"),
+                Column::new("Cost").with_number_format(CURRENCY),
+            ],
+        )
+        .with_frozen_header();
+        for r in &self.records {
+            requests.push_row([
+                CellValue::DateTime(r.timestamp.naive_utc()),
+                r.model_id.clone().into(),
+                r.input_tokens.into(),
+                r.output_tokens.into(),
+                r.cost.into(),
+            ]);
+        }
+        if let Some(limit) = self.budget.daily_limit {
+            requests = requests.with_conditional_format(
+                4,
+                ConditionalRule::GreaterThan {
+                    value: limit,
+                    color: HIGHLIGHT,
+                },
+            );
+        }
+
+        let mut daily = Sheet::new(
+            "Daily",
+            vec![
+                Column::new("Date").with_width(12.0),
+                Column::new("Cost").with_number_format(CURRENCY),
+            ],
+        )
+        .with_frozen_header()
+        .with_chart(ChartSpec::new(ChartKind::Line, "Cost per day", 0, [1]));
+        for (date, cost) in self.daily_history() {
+            daily.push_row([CellValue::Date(date), cost.into()]);
+        }
+        if let Some(limit) = self.budget.daily_limit {
+            daily = daily.with_conditional_format(
+                1,
+                ConditionalRule::GreaterThan {
+                    value: limit,
+                    color: HIGHLIGHT,
+                },
+            );
+        }
+
+        let mut models: Vec<_> = self.cost_by_model().into_iter().collect();
+        models.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let mut by_model = Sheet::new(
+            "By Model",
+            vec![
+                Column::new("Model"),
+                Column::new("Cost").with_number_format(CURRENCY),
+            ],
+        )
+        .with_frozen_header()
+        .with_conditional_format(1, ConditionalRule::DataBar { color: 0x638EC6 })
+        .with_chart(ChartSpec::new(ChartKind::Bar, "Cost by model", 0, [1]));
+        for (model, cost) in models {
+            by_model.push_row([CellValue::from(model), cost.into()]);
+        }
+        if let Some(range) = by_model.data_range(1) {
+            by_model.push_footer_row([
+                CellValue::from("Total"),
+                CellValue::formula(format!("SUM({range})")).with_result(self.total_cost()),
+            ]);
+        }
+
+        generate_xlsx_multi_sheet(&[requests, daily, by_model])
+    }
 }
 
 impl Default for CostTracker {
@@ -339,6 +509,14 @@ mod tests {
         assert_eq!(breakdown.total_cost, 0.0);
     }
 
+    #[test]
+    fn count_tokens_uses_model_tokenizer() {
+        // o200k: "hello world" is 2 tokens; the heuristic would say 3.
+        assert_eq!(count_tokens("gpt-4o", "hello world"), 2);
+        // Unknown model without a tokenizer falls back to the heuristic.
+        assert_eq!(count_tokens("unknown-model", "hello world"), 3);
+    }
+
     #[test]
     fn predict_cost_produces_estimate() {
         let prediction = predict_cost("claude-sonnet-4-5-20250929", "Hello, how are you?");
@@ -442,6 +620,31 @@ mod tests {
         assert!(csv.contains("500"));
     }
 
+    #[test]
+    fn cost_tracker_export_xlsx() {
+        let mut tracker = CostTracker::new(BudgetLimits {
+            daily_limit: Some(10.0),
+            monthly_limit: None,
+        });
+        tracker.record("claude-haiku-4-5-20251001", 1000, 500);
+        tracker.record("claude-sonnet-4-5-20250929", 2000, 1000);
+
+        let bytes = tracker.export_xlsx().unwrap();
+        let doc =
+            hive_docs::extract::extract(&bytes, hive_docs::extract::DocumentFormat::Xlsx).unwrap();
+        let titles: Vec<_> = doc
+            .sections
+            .iter()
+            .filter_map(|s| s.title.as_deref())
+            .collect();
+        assert_eq!(titles, ["Requests", "Daily", "By Model"]);
+
+        let text = doc.to_markdown();
+        assert!(text.contains("claude-haiku-4-5-20251001"));
+        assert!(text.contains("| Total |"));
+        assert!(text.contains(&Utc::now().format("%Y-%m-%d").to_string()));
+    }
+
     #[test]
     fn cost_tracker_daily_history() {
         let mut tracker = CostTracker::default();
@@ -461,4 +664,27 @@ mod tests {
         assert_eq!(deserialized.input_tokens, breakdown.input_tokens);
         assert_eq!(deserialized.model_id, breakdown.model_id);
     }
+
+    #[test]
+    fn cost_tracker_cache_stats() {
+        let mut tracker = CostTracker::default();
+        tracker.record_cache_miss();
+        tracker.record_cache_hit("claude-haiku-4-5-20251001", 1000, 500, false);
+        tracker.record_cache_hit("claude-haiku-4-5-20251001", 1000, 500, true);
+        tracker.record_cache_bypass();
+
+        let stats = tracker.cache_stats();
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.semantic_hits, 1);
+        assert_eq!(stats.bypassed, 1);
+        assert_eq!(stats.saved_tokens, 3000);
+        assert!(stats.saved_cost > 0.0);
+        assert!((stats.hit_rate() - 2.0 / 3.0).abs() < 1e-9);
+        // Cached answers are savings, not spend.
+        assert_eq!(tracker.total_calls(), 0);
+        assert_eq!(tracker.total_cost(), 0.0);
+
+        tracker.clear();
+        assert_eq!(tracker.cache_stats().hits, 0);
+    }
 }
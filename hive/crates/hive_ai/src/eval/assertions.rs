@@ -0,0 +1,550 @@
+//! Assertions that score a candidate model's answer.
+
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::process::Command;
+
+use super::runner::EvalTarget;
+use super::suite::EvalCase;
+use crate::types::{ChatMessage, ChatRequest, MessageRole};
+
+/// How long a compiler may run before the check fails.
+const COMPILE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Compiler output kept in a failed check's detail.
+const MAX_DETAIL_CHARS: usize = 400;
+
+fn default_min_score() -> f64 {
+    0.7
+}
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A check applied to a model's answer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Assertion {
+    /// The answer matches a regular expression.
+    Regex { pattern: String },
+    /// The answer (or its first fenced JSON block) validates against a JSON
+    /// schema. Supports `type`, `enum`, `const`, `required`, `properties`,
+    /// `additionalProperties`, `items` and the min/max bounds.
+    JsonSchema { schema: Value },
+    /// The first fenced code block compiles. Supports `rust`, `python` and
+    /// `javascript`.
+    CodeCompiles { language: String },
+    /// An LLM judge scores the answer against a rubric; passes at
+    /// `min_score` (0.0–1.0) or above.
+    Judge {
+        rubric: String,
+        #[serde(default = "default_min_score")]
+        min_score: f64,
+    },
+}
+
+/// Outcome of one assertion.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssertionResult {
+    pub assertion: String,
+    pub passed: bool,
+    /// 0.0–1.0. Binary checks score 0 or 1; the judge scores in between.
+    pub score: f64,
+    pub detail: String,
+}
+
+impl AssertionResult {
+    fn pass(assertion: String, detail: impl Into<String>) -> Self {
+        Self {
+            assertion,
+            passed: true,
+            score: 1.0,
+            detail: detail.into(),
+        }
+    }
+
+    fn fail(assertion: String, detail: impl Into<String>) -> Self {
+        Self {
+            assertion,
+            passed: false,
+            score: 0.0,
+            detail: detail.into(),
+        }
+    }
+}
+
+impl Assertion {
+    /// Short label for reports.
+    pub fn label(&self) -> String {
+        match self {
+            Self::Regex { pattern } => format!("regex /{pattern}/"),
+            Self::JsonSchema { .. } => "json_schema".into(),
+            Self::CodeCompiles { language } => format!("compiles ({language})"),
+            Self::Judge { .. } => "judge".into(),
+        }
+    }
+
+    /// Reject assertions that can never pass.
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Self::Regex { pattern } => {
+                Regex::new(pattern).with_context(|| format!("Invalid regex '{pattern}'"))?;
+            }
+            Self::JsonSchema { schema } if !schema.is_object() => {
+                bail!("JSON schema must be an object")
+            }
+            Self::CodeCompiles { language } if compiler_for(language).is_none() => {
+                bail!("No compiler configured for '{language}'")
+            }
+            Self::Judge { min_score, .. } if !(0.0..=1.0).contains(min_score) => {
+                bail!("Judge min_score must be between 0 and 1")
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Score `answer`. The judge assertion needs a judge model and fails
+    /// without one.
+    pub async fn check(
+        &self,
+        case: &EvalCase,
+        answer: &str,
+        judge: Option<&EvalTarget>,
+    ) -> AssertionResult {
+        let label = self.label();
+        match self {
+            Self::Regex { pattern } => match Regex::new(pattern) {
+                Ok(re) if re.is_match(answer) => AssertionResult::pass(label, "matched"),
+                Ok(_) => AssertionResult::fail(label, "no match"),
+                Err(e) => AssertionResult::fail(label, e.to_string()),
+            },
+            Self::JsonSchema { schema } => {
+                let text = extract_code_block(answer, "json").unwrap_or(answer.trim());
+                match serde_json::from_str::<Value>(text) {
+                    Ok(value) => match validate_schema(&value, schema, "$") {
+                        Ok(()) => AssertionResult::pass(label, "valid"),
+                        Err(e) => AssertionResult::fail(label, e),
+                    },
+                    Err(e) => AssertionResult::fail(label, format!("not JSON: {e}")),
+                }
+            }
+            Self::CodeCompiles { language } => {
+                let code = extract_code_block(answer, language).unwrap_or(answer);
+                match compile(language, code).await {
+                    Ok(()) => AssertionResult::pass(label, "compiled"),
+                    Err(e) => AssertionResult::fail(label, truncate(&format!("{e:#}"))),
+                }
+            }
+            Self::Judge { rubric, min_score } => {
+                let Some(judge) = judge else {
+                    return AssertionResult::fail(label, "no judge model configured");
+                };
+                match run_judge(judge, case, answer, rubric).await {
+                    Ok((score, reason)) => AssertionResult {
+                        assertion: label,
+                        passed: score >= *min_score,
+                        score,
+                        detail: reason,
+                    },
+                    Err(e) => AssertionResult::fail(label, format!("judge failed: {e:#}")),
+                }
+            }
+        }
+    }
+}
+
+fn truncate(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(MAX_DETAIL_CHARS) {
+        Some((i, _)) => format!("{}…", &text[..i]),
+        None => text.to_string(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Fenced code blocks
+// ---------------------------------------------------------------------------
+
+/// The body of the first fenced block tagged `language` (or a common alias),
+/// else of the first fenced block. `None` if the text has no fences.
+pub fn extract_code_block<'a>(text: &'a str, language: &str) -> Option<&'a str> {
+    let mut blocks = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("```") {
+        let after = &rest[start + 3..];
+        let Some(newline) = after.find('\n') else {
+            break;
+        };
+        let info = after[..newline].trim().to_ascii_lowercase();
+        let body = &after[newline + 1..];
+        let Some(end) = body.find("```") else {
+            break;
+        };
+        blocks.push((info, &body[..end]));
+        rest = &body[end + 3..];
+    }
+
+    let wanted = language.to_ascii_lowercase();
+    let is_wanted = |info: &str| match wanted.as_str() {
+        "rust" => matches!(info, "rust" | "rs"),
+        "python" => matches!(info, "python" | "py" | "python3"),
+        "javascript" => matches!(info, "javascript" | "js" | "node"),
+        other => info == other,
+    };
+    blocks
+        .iter()
+        .find(|(info, _)| is_wanted(info))
+        .or(blocks.first())
+        .map(|(_, body)| *body)
+}
+
+// ---------------------------------------------------------------------------
+// JSON schema (subset)
+// ---------------------------------------------------------------------------
+
+fn type_matches(value: &Value, ty: &str) -> bool {
+    match ty {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => false,
+    }
+}
+
+/// Validate `value` against a JSON schema subset. Errors name the failing
+/// path, e.g. `$.users[0].name: expected string`.
+pub fn validate_schema(value: &Value, schema: &Value, path: &str) -> Result<(), String> {
+    let Some(schema) = schema.as_object() else {
+        return Ok(());
+    };
+
+    if let Some(ty) = schema.get("type") {
+        let types: Vec<&str> = match ty {
+            Value::String(s) => vec![s.as_str()],
+            Value::Array(list) => list.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|t| type_matches(value, t)) {
+            return Err(format!("{path}: expected {}", types.join(" or ")));
+        }
+    }
+    if let Some(options) = schema.get("enum").and_then(Value::as_array)
+        && !options.contains(value)
+    {
+        return Err(format!("{path}: not one of the allowed values"));
+    }
+    if let Some(expected) = schema.get("const")
+        && expected != value
+    {
+        return Err(format!("{path}: expected {expected}"));
+    }
+
+    if let Some(n) = value.as_f64() {
+        if let Some(min) = schema.get("minimum").and_then(Value::as_f64)
+            && n < min
+        {
+            return Err(format!("{path}: {n} is below {min}"));
+        }
+        if let Some(max) = schema.get("maximum").and_then(Value::as_f64)
+            && n > max
+        {
+            return Err(format!("{path}: {n} is above {max}"));
+        }
+    }
+    if let Some(s) = value.as_str() {
+        let len = s.chars().count() as u64;
+        if let Some(min) = schema.get("minLength").and_then(Value::as_u64)
+            && len < min
+        {
+            return Err(format!("{path}: shorter than {min}"));
+        }
+        if let Some(max) = schema.get("maxLength").and_then(Value::as_u64)
+            && len > max
+        {
+            return Err(format!("{path}: longer than {max}"));
+        }
+    }
+
+    if let Some(items) = value.as_array() {
+        let len = items.len() as u64;
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64)
+            && len < min
+        {
+            return Err(format!("{path}: fewer than {min} items"));
+        }
+        if let Some(max) = schema.get("maxItems").and_then(Value::as_u64)
+            && len > max
+        {
+            return Err(format!("{path}: more than {max} items"));
+        }
+        if let Some(item_schema) = schema.get("items") {
+            for (i, item) in items.iter().enumerate() {
+                validate_schema(item, item_schema, &format!("{path}[{i}]"))?;
+            }
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(key) {
+                    return Err(format!("{path}: missing required property '{key}'"));
+                }
+            }
+        }
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (key, field) in object {
+            match properties.and_then(|p| p.get(key)) {
+                Some(field_schema) => {
+                    validate_schema(field, field_schema, &format!("{path}.{key}"))?
+                }
+                None if schema.get("additionalProperties") == Some(&Value::Bool(false)) => {
+                    return Err(format!("{path}: unexpected property '{key}'"));
+                }
+                None => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Compilation
+// ---------------------------------------------------------------------------
+
+/// Source file name and checker command for a language. The file path is
+/// appended to the arguments.
+fn compiler_for(language: &str) -> Option<(&'static str, &'static str, &'static [&'static str])> {
+    match language.to_ascii_lowercase().as_str() {
+        "rust" | "rs" => Some((
+            "snippet.rs",
+            "rustc",
+            &[
+                "--edition",
+                "2021",
+                "--crate-type",
+                "lib",
+                "--emit=metadata",
+            ],
+        )),
+        "python" | "py" => Some(("snippet.py", "python3", &["-m", "py_compile"])),
+        "javascript" | "js" => Some(("snippet.js", "node", &["--check"])),
+        _ => None,
+    }
+}
+
+async fn compile(language: &str, code: &str) -> Result<()> {
+    let Some((file_name, program, args)) = compiler_for(language) else {
+        bail!("No compiler configured for '{language}'");
+    };
+    let dir = std::env::temp_dir().join(format!("hive-eval-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).context("Failed to create scratch directory")?;
+    let result = run_compiler(&dir, file_name, program, args, code).await;
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
+
+async fn run_compiler(
+    dir: &Path,
+    file_name: &str,
+    program: &str,
+    args: &[&str],
+    code: &str,
+) -> Result<()> {
+    let file = dir.join(file_name);
+    std::fs::write(&file, code).context("Failed to write snippet")?;
+
+    let child = Command::new(program)
+        .args(args)
+        .arg(&file)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(COMPILE_TIMEOUT, child)
+        .await
+        .with_context(|| format!("{program} timed out"))?
+        .with_context(|| format!("Failed to run {program}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        bail!("{}", String::from_utf8_lossy(&output.stderr))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// LLM-as-judge
+// ---------------------------------------------------------------------------
+
+const JUDGE_SYSTEM_PROMPT: &str = "You grade answers written by an AI assistant. \
+Score the answer from 0 to 10 against the rubric. Reply with only a JSON object: \
+{\"score\": <0-10>, \"reason\": \"<one sentence>\"}";
+
+#[derive(Deserialize)]
+struct Verdict {
+    score: f64,
+    #[serde(default)]
+    reason: String,
+}
+
+async fn run_judge(
+    judge: &EvalTarget,
+    case: &EvalCase,
+    answer: &str,
+    rubric: &str,
+) -> Result<(f64, String)> {
+    let mut prompt = format!("## Rubric\n{rubric}\n\n## Prompt\n{}\n\n", case.prompt);
+    if let Some(reference) = &case.reference {
+        prompt.push_str(&format!("## Reference answer\n{reference}\n\n"));
+    }
+    prompt.push_str(&format!("## Answer to grade\n{answer}"));
+
+    let request = ChatRequest {
+        messages: vec![ChatMessage::text(MessageRole::User, prompt)],
+        model: judge.model.clone(),
+        max_tokens: 512,
+        temperature: Some(0.0),
+        system_prompt: Some(JUDGE_SYSTEM_PROMPT.into()),
+        tools: None,
+    };
+    let response = judge.provider.chat(&request).await?;
+    let verdict = parse_verdict(&response.content)?;
+    Ok(((verdict.score / 10.0).clamp(0.0, 1.0), verdict.reason))
+}
+
+fn parse_verdict(text: &str) -> Result<Verdict> {
+    let start = text.find('{').context("Judge reply has no JSON object")?;
+    let end = text.rfind('}').context("Judge reply has no JSON object")?;
+    if end < start {
+        bail!("Judge reply has no JSON object");
+    }
+    serde_json::from_str(&text[start..=end]).context("Judge reply is not a verdict")
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn case(prompt: &str) -> EvalCase {
+        EvalCase {
+            id: "c".into(),
+            system_prompt: None,
+            history: Vec::new(),
+            prompt: prompt.into(),
+            reference: None,
+            assertions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn extracts_matching_fenced_block() {
+        let text = "Here:\n```text\nignore\n```\nand\n```py\nprint(1)\n```\n";
+        assert_eq!(extract_code_block(text, "python"), Some("print(1)\n"));
+        assert_eq!(extract_code_block(text, "go"), Some("ignore\n"));
+        assert_eq!(extract_code_block("no fences", "rust"), None);
+    }
+
+    #[test]
+    fn schema_reports_failing_path() {
+        let schema = json!({
+            "type": "object",
+            "required": ["users"],
+            "properties": {
+                "users": {
+                    "type": "array",
+                    "minItems": 1,
+                    "items": {
+                        "type": "object",
+                        "required": ["name"],
+                        "properties": {"name": {"type": "string"}, "age": {"type": "integer", "minimum": 0}},
+                        "additionalProperties": false
+                    }
+                }
+            }
+        });
+
+        assert!(
+            validate_schema(&json!({"users": [{"name": "a", "age": 3}]}), &schema, "$").is_ok()
+        );
+        assert_eq!(
+            validate_schema(&json!({"users": [{"name": 1}]}), &schema, "$").unwrap_err(),
+            "$.users[0].name: expected string"
+        );
+        assert!(validate_schema(&json!({"users": []}), &schema, "$").is_err());
+        assert!(validate_schema(&json!({"users": [{"name": "a", "x": 1}]}), &schema, "$").is_err());
+        assert!(
+            validate_schema(&json!({"users": [{"name": "a", "age": -1}]}), &schema, "$").is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn regex_and_json_checks() {
+        let c = case("p");
+        let regex = Assertion::Regex {
+            pattern: r"\bfoo\b".into(),
+        };
+        assert!(regex.check(&c, "a foo b", None).await.passed);
+        assert!(!regex.check(&c, "food", None).await.passed);
+
+        let schema = Assertion::JsonSchema {
+            schema: json!({"type": "object", "required": ["ok"]}),
+        };
+        assert!(
+            schema
+                .check(&c, "```json\n{\"ok\": true}\n```", None)
+                .await
+                .passed
+        );
+        let failed = schema.check(&c, "sure thing", None).await;
+        assert!(!failed.passed);
+        assert!(failed.detail.starts_with("not JSON"));
+    }
+
+    #[tokio::test]
+    async fn judge_without_model_fails() {
+        let judge = Assertion::Judge {
+            rubric: "r".into(),
+            min_score: 0.5,
+        };
+        let result = judge.check(&case("p"), "answer", None).await;
+        assert!(!result.passed);
+        assert_eq!(result.detail, "no judge model configured");
+    }
+
+    #[test]
+    fn parses_verdict_inside_prose() {
+        let verdict = parse_verdict("Sure! {\"score\": 7.5, \"reason\": \"ok\"} done").unwrap();
+        assert!((verdict.score - 7.5).abs() < f64::EPSILON);
+        assert!(parse_verdict("no json").is_err());
+    }
+
+    #[test]
+    fn validate_rejects_unknown_language() {
+        assert!(
+            Assertion::CodeCompiles {
+                language: "cobol".into()
+            }
+            .validate()
+            .is_err()
+        );
+    }
+}
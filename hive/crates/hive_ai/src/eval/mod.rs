@@ -0,0 +1,20 @@
+//! Offline evaluation harness.
+//!
+//! Replays a suite of recorded prompts against several candidate models,
+//! scores every answer with the case's assertions (regex, JSON schema,
+//! "code compiles", LLM-as-judge), stores the run in SQLite and renders a
+//! Markdown or HTML comparison report. Backs `hive eval`, which is used to
+//! decide whether a new model should replace `default_model` or a fallback
+//! chain entry.
+
+pub mod assertions;
+pub mod report;
+pub mod runner;
+pub mod store;
+pub mod suite;
+
+pub use assertions::{Assertion, AssertionResult};
+pub use report::ReportFormat;
+pub use runner::{CaseResult, EvalConfig, EvalRun, EvalRunner, EvalTarget, ModelSummary};
+pub use store::{EvalStore, RunInfo};
+pub use suite::{EvalCase, EvalSuite, EvalTurn};
@@ -0,0 +1,271 @@
+//! Comparison reports for eval runs, rendered with `hive_docs`.
+
+use hive_docs::html::{escape_html, generate_html, generate_html_table};
+use hive_docs::markdown::{generate_markdown_document, generate_markdown_table};
+
+use super::runner::{CaseResult, EvalRun, ModelSummary};
+
+const SUMMARY_HEADERS: [&str; 7] = [
+    "Model",
+    "Passed",
+    "Avg score",
+    "Errors",
+    "Cost (USD)",
+    "Avg latency",
+    "p95 latency",
+];
+
+/// Report output format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    /// Pick a format from a file extension; anything but `.html`/`.htm` is
+    /// Markdown.
+    pub fn from_path(path: &std::path::Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("html" | "htm") => Self::Html,
+            _ => Self::Markdown,
+        }
+    }
+}
+
+/// Render `run` in `format`.
+pub fn render(run: &EvalRun, format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Markdown => markdown_report(run),
+        ReportFormat::Html => html_report(run),
+    }
+}
+
+/// The model with the best average score, cheaper first on ties.
+pub fn best_model(summaries: &[ModelSummary]) -> Option<&ModelSummary> {
+    summaries.iter().min_by(|a, b| {
+        b.avg_score
+            .total_cmp(&a.avg_score)
+            .then(a.total_cost.total_cmp(&b.total_cost))
+    })
+}
+
+fn summary_rows(summaries: &[ModelSummary]) -> Vec<Vec<String>> {
+    summaries
+        .iter()
+        .map(|s| {
+            vec![
+                s.model.clone(),
+                format!("{}/{}", s.passed, s.cases),
+                format!("{:.2}", s.avg_score),
+                s.errors.to_string(),
+                format!("{:.4}", s.total_cost),
+                format!("{} ms", s.avg_latency_ms),
+                format!("{} ms", s.p95_latency_ms),
+            ]
+        })
+        .collect()
+}
+
+fn case_cell(result: Option<&CaseResult>) -> String {
+    match result {
+        None => "—".into(),
+        Some(r) if r.error.is_some() => "error".into(),
+        Some(r) if r.passed => format!("✓ {:.2}", r.score),
+        Some(r) => format!("✗ {:.2}", r.score),
+    }
+}
+
+/// Case IDs in run order, without repeats.
+fn case_ids(run: &EvalRun) -> Vec<&str> {
+    let mut ids: Vec<&str> = Vec::new();
+    for r in &run.results {
+        if !ids.contains(&r.case_id.as_str()) {
+            ids.push(&r.case_id);
+        }
+    }
+    ids
+}
+
+fn case_table(run: &EvalRun) -> (Vec<&str>, Vec<Vec<String>>) {
+    let headers: Vec<&str> = std::iter::once("Case")
+        .chain(run.models.iter().map(String::as_str))
+        .collect();
+    let rows = case_ids(run)
+        .into_iter()
+        .map(|id| {
+            std::iter::once(id.to_string())
+                .chain(run.models.iter().map(|m| case_cell(run.result(id, m))))
+                .collect()
+        })
+        .collect();
+    (headers, rows)
+}
+
+fn failure_rows(run: &EvalRun) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    for r in &run.results {
+        if let Some(error) = &r.error {
+            rows.push(vec![
+                r.case_id.clone(),
+                r.model.clone(),
+                "request".into(),
+                error.clone(),
+            ]);
+        }
+        for a in r.assertions.iter().filter(|a| !a.passed) {
+            rows.push(vec![
+                r.case_id.clone(),
+                r.model.clone(),
+                a.assertion.clone(),
+                a.detail.clone(),
+            ]);
+        }
+    }
+    rows
+}
+
+fn verdict(summaries: &[ModelSummary]) -> String {
+    match best_model(summaries) {
+        Some(best) => format!(
+            "Best: {} (avg score {:.2}, ${:.4})",
+            best.model, best.avg_score, best.total_cost
+        ),
+        None => "No models were evaluated.".into(),
+    }
+}
+
+/// Markdown comparison report.
+pub fn markdown_report(run: &EvalRun) -> String {
+    let summaries = run.summaries();
+    let overview = format!(
+        "Run `{}` started {}.\n\n{}\n\n{}",
+        run.id,
+        run.started_at.format("%Y-%m-%d %H:%M UTC"),
+        verdict(&summaries),
+        generate_markdown_table(&SUMMARY_HEADERS, &summary_rows(&summaries)),
+    );
+    let (headers, rows) = case_table(run);
+    let cases = generate_markdown_table(&headers, &rows);
+    let failures = failure_rows(run);
+    let failures = if failures.is_empty() {
+        "None.".to_string()
+    } else {
+        generate_markdown_table(&["Case", "Model", "Check", "Detail"], &failures)
+    };
+
+    generate_markdown_document(
+        &format!("Eval: {}", run.suite),
+        &[
+            ("Summary", &overview),
+            ("Cases", &cases),
+            ("Failures", &failures),
+        ],
+    )
+}
+
+/// Standalone HTML comparison report.
+pub fn html_report(run: &EvalRun) -> String {
+    let summaries = run.summaries();
+    let (headers, rows) = case_table(run);
+    let failures = failure_rows(run);
+
+    let mut body = format!(
+        "<h1>Eval: {}</h1>\n<p>Run <code>{}</code> started {}.</p>\n<p><strong>{}</strong></p>\n",
+        escape_html(&run.suite),
+        escape_html(&run.id),
+        run.started_at.format("%Y-%m-%d %H:%M UTC"),
+        escape_html(&verdict(&summaries)),
+    );
+    body.push_str("<h2>Summary</h2>\n");
+    body.push_str(&generate_html_table(
+        &SUMMARY_HEADERS,
+        &summary_rows(&summaries),
+    ));
+    body.push_str("\n<h2>Cases</h2>\n");
+    body.push_str(&generate_html_table(&headers, &rows));
+    body.push_str("\n<h2>Failures</h2>\n");
+    if failures.is_empty() {
+        body.push_str("<p>None.</p>");
+    } else {
+        body.push_str(&generate_html_table(
+            &["Case", "Model", "Check", "Detail"],
+            &failures,
+        ));
+    }
+
+    generate_html(&format!("Eval: {}", escape_html(&run.suite)), &body)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::assertions::AssertionResult;
+    use chrono::Utc;
+
+    fn result(case_id: &str, model: &str, score: f64, cost: f64) -> CaseResult {
+        CaseResult {
+            case_id: case_id.into(),
+            model: model.into(),
+            provider: "mock".into(),
+            response: String::new(),
+            error: None,
+            latency_ms: 100,
+            prompt_tokens: 10,
+            completion_tokens: 10,
+            cost,
+            score,
+            passed: score >= 1.0,
+            assertions: vec![AssertionResult {
+                assertion: "regex /<ok>/".into(),
+                passed: score >= 1.0,
+                score,
+                detail: if score >= 1.0 { "matched" } else { "no match" }.into(),
+            }],
+        }
+    }
+
+    fn run() -> EvalRun {
+        EvalRun {
+            id: "r1".into(),
+            suite: "smoke".into(),
+            started_at: Utc::now(),
+            models: vec!["cheap".into(), "pricey".into()],
+            results: vec![
+                result("a", "cheap", 1.0, 0.001),
+                result("a", "pricey", 1.0, 0.01),
+                result("b", "cheap", 0.0, 0.001),
+                result("b", "pricey", 1.0, 0.01),
+            ],
+        }
+    }
+
+    #[test]
+    fn markdown_report_compares_models() {
+        let report = markdown_report(&run());
+        assert!(report.starts_with("# Eval: smoke"));
+        assert!(report.contains("Best: pricey"));
+        assert!(report.contains("| cheap | 1/2 | 0.50 |"));
+        assert!(report.contains("| b | ✗ 0.00 | ✓ 1.00 |"));
+        assert!(report.contains("| b | cheap | regex /<ok>/ | no match |"));
+    }
+
+    #[test]
+    fn html_report_escapes_content() {
+        let report = html_report(&run());
+        assert!(report.contains("<h2>Cases</h2>"));
+        assert!(report.contains("regex /&lt;ok&gt;/"));
+        assert!(!report.contains("regex /<ok>/"));
+    }
+
+    #[test]
+    fn best_model_breaks_ties_on_cost() {
+        let mut tied = run();
+        tied.results.retain(|r| r.case_id == "a");
+        assert_eq!(best_model(&tied.summaries()).unwrap().model, "cheap");
+    }
+}
@@ -0,0 +1,364 @@
+//! Replays a suite against candidate models and scores the answers.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use super::assertions::AssertionResult;
+use super::suite::{EvalCase, EvalSuite};
+use crate::cost::calculate_cost;
+use crate::providers::AiProvider;
+use crate::types::ChatRequest;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A model to evaluate (or to judge with) and the provider that serves it.
+#[derive(Clone)]
+pub struct EvalTarget {
+    pub provider: Arc<dyn AiProvider>,
+    pub model: String,
+}
+
+impl EvalTarget {
+    pub fn new(provider: Arc<dyn AiProvider>, model: impl Into<String>) -> Self {
+        Self {
+            provider,
+            model: model.into(),
+        }
+    }
+}
+
+/// Replay settings.
+#[derive(Debug, Clone)]
+pub struct EvalConfig {
+    /// Requests in flight at once, across all models.
+    pub concurrency: usize,
+    pub max_tokens: u32,
+    /// Sampling temperature for candidates. Zero keeps runs comparable.
+    pub temperature: Option<f32>,
+}
+
+impl Default for EvalConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            max_tokens: 4096,
+            temperature: Some(0.0),
+        }
+    }
+}
+
+/// One model's answer to one case.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaseResult {
+    pub case_id: String,
+    pub model: String,
+    pub provider: String,
+    pub response: String,
+    /// Set when the provider call failed; the case then scores zero.
+    pub error: Option<String>,
+    pub latency_ms: u64,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub cost: f64,
+    /// Mean assertion score, 0.0–1.0.
+    pub score: f64,
+    /// Every assertion passed.
+    pub passed: bool,
+    pub assertions: Vec<AssertionResult>,
+}
+
+/// A completed replay of a suite.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalRun {
+    pub id: String,
+    pub suite: String,
+    pub started_at: DateTime<Utc>,
+    /// Candidate models, in the order they were given.
+    pub models: Vec<String>,
+    /// Ordered by case, then by model.
+    pub results: Vec<CaseResult>,
+}
+
+/// Aggregate figures for one model in a run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelSummary {
+    pub model: String,
+    pub cases: usize,
+    pub passed: usize,
+    pub errors: usize,
+    pub avg_score: f64,
+    pub total_cost: f64,
+    pub avg_latency_ms: u64,
+    pub p95_latency_ms: u64,
+}
+
+impl EvalRun {
+    /// Per-model totals, in candidate order.
+    pub fn summaries(&self) -> Vec<ModelSummary> {
+        self.models
+            .iter()
+            .map(|model| {
+                let results: Vec<&CaseResult> =
+                    self.results.iter().filter(|r| &r.model == model).collect();
+                let cases = results.len();
+                let mut latencies: Vec<u64> = results.iter().map(|r| r.latency_ms).collect();
+                latencies.sort_unstable();
+                let p95_latency_ms = latencies
+                    .get((cases * 95).div_ceil(100).saturating_sub(1))
+                    .copied()
+                    .unwrap_or(0);
+                ModelSummary {
+                    model: model.clone(),
+                    cases,
+                    passed: results.iter().filter(|r| r.passed).count(),
+                    errors: results.iter().filter(|r| r.error.is_some()).count(),
+                    avg_score: mean(results.iter().map(|r| r.score), cases),
+                    total_cost: results.iter().map(|r| r.cost).sum(),
+                    avg_latency_ms: latencies.iter().sum::<u64>() / cases.max(1) as u64,
+                    p95_latency_ms,
+                }
+            })
+            .collect()
+    }
+
+    /// The result for `case_id` on `model`, if it ran.
+    pub fn result(&self, case_id: &str, model: &str) -> Option<&CaseResult> {
+        self.results
+            .iter()
+            .find(|r| r.case_id == case_id && r.model == model)
+    }
+}
+
+fn mean(values: impl Iterator<Item = f64>, count: usize) -> f64 {
+    if count == 0 {
+        0.0
+    } else {
+        values.sum::<f64>() / count as f64
+    }
+}
+
+// ---------------------------------------------------------------------------
+// EvalRunner
+// ---------------------------------------------------------------------------
+
+/// Replays suites against candidate models concurrently.
+pub struct EvalRunner {
+    config: EvalConfig,
+    judge: Option<EvalTarget>,
+}
+
+impl EvalRunner {
+    pub fn new(config: EvalConfig) -> Self {
+        Self {
+            config,
+            judge: None,
+        }
+    }
+
+    /// Model used for `judge` assertions.
+    pub fn with_judge(mut self, judge: EvalTarget) -> Self {
+        self.judge = Some(judge);
+        self
+    }
+
+    /// Run every case against every target.
+    pub async fn run(&self, suite: &EvalSuite, targets: &[EvalTarget]) -> EvalRun {
+        let started_at = Utc::now();
+        info!(
+            suite = %suite.name,
+            cases = suite.cases.len(),
+            models = targets.len(),
+            "Starting eval run"
+        );
+
+        let jobs = suite.cases.iter().enumerate().flat_map(|(ci, case)| {
+            targets
+                .iter()
+                .enumerate()
+                .map(move |(ti, target)| (ci, ti, case, target))
+        });
+        let mut results: Vec<(usize, usize, CaseResult)> = futures::stream::iter(jobs)
+            .map(|(ci, ti, case, target)| async move {
+                (ci, ti, self.run_case(suite, case, target).await)
+            })
+            .buffer_unordered(self.config.concurrency.max(1))
+            .collect()
+            .await;
+        results.sort_by_key(|(ci, ti, _)| (*ci, *ti));
+
+        EvalRun {
+            id: uuid::Uuid::new_v4().to_string(),
+            suite: suite.name.clone(),
+            started_at,
+            models: targets.iter().map(|t| t.model.clone()).collect(),
+            results: results.into_iter().map(|(_, _, r)| r).collect(),
+        }
+    }
+
+    async fn run_case(
+        &self,
+        suite: &EvalSuite,
+        case: &EvalCase,
+        target: &EvalTarget,
+    ) -> CaseResult {
+        let request = ChatRequest {
+            messages: case.messages(),
+            model: target.model.clone(),
+            max_tokens: self.config.max_tokens,
+            temperature: self.config.temperature,
+            system_prompt: case
+                .system_prompt
+                .clone()
+                .or_else(|| suite.system_prompt.clone()),
+            tools: None,
+        };
+
+        let start = Instant::now();
+        let response = target.provider.chat(&request).await;
+        let latency_ms = start.elapsed().as_millis() as u64;
+
+        let mut result = CaseResult {
+            case_id: case.id.clone(),
+            model: target.model.clone(),
+            provider: target.provider.provider_type().to_string(),
+            response: String::new(),
+            error: None,
+            latency_ms,
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            cost: 0.0,
+            score: 0.0,
+            passed: false,
+            assertions: Vec::new(),
+        };
+        let response = match response {
+            Ok(response) => response,
+            Err(e) => {
+                debug!(case = %case.id, model = %target.model, "Eval call failed: {e}");
+                result.error = Some(e.to_string());
+                return result;
+            }
+        };
+
+        result.prompt_tokens = response.usage.prompt_tokens;
+        result.completion_tokens = response.usage.completion_tokens;
+        result.cost = calculate_cost(
+            &target.model,
+            response.usage.prompt_tokens as usize,
+            response.usage.completion_tokens as usize,
+        )
+        .total_cost;
+
+        for assertion in &case.assertions {
+            let checked = assertion
+                .check(case, &response.content, self.judge.as_ref())
+                .await;
+            result.assertions.push(checked);
+        }
+        // A case without assertions only has to produce an answer.
+        result.passed = result.assertions.iter().all(|a| a.passed);
+        result.score = if result.assertions.is_empty() {
+            1.0
+        } else {
+            mean(
+                result.assertions.iter().map(|a| a.score),
+                result.assertions.len(),
+            )
+        };
+        result.response = response.content;
+        result
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::mock::MockProvider;
+    use crate::types::ProviderType;
+
+    const SUITE: &str = r#"
+name: mock
+system_prompt: Answer briefly.
+cases:
+  - id: hello
+    prompt: Greet me
+    assertions:
+      - type: regex
+        pattern: "(?i)hello"
+  - id: judged
+    prompt: Explain ownership
+    assertions:
+      - type: judge
+        rubric: Mentions moves.
+        min_score: 0.7
+"#;
+
+    #[tokio::test]
+    async fn replays_every_case_against_every_model() {
+        let suite = EvalSuite::from_yaml(SUITE).unwrap();
+        let good = Arc::new(MockProvider::new("Hello there"));
+        let bad = Arc::new(MockProvider::new("Go away").with_type(ProviderType::Anthropic));
+        let judge = Arc::new(MockProvider::new(r#"{"score": 8, "reason": "fine"}"#));
+
+        let runner = EvalRunner::new(EvalConfig::default())
+            .with_judge(EvalTarget::new(judge.clone(), "judge-model"));
+        let run = runner
+            .run(
+                &suite,
+                &[
+                    EvalTarget::new(good.clone(), "gpt-4o"),
+                    EvalTarget::new(bad, "claude-sonnet-4-20250514"),
+                ],
+            )
+            .await;
+
+        assert_eq!(run.results.len(), 4);
+        let order: Vec<_> = run
+            .results
+            .iter()
+            .map(|r| (r.case_id.as_str(), r.model.as_str()))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                ("hello", "gpt-4o"),
+                ("hello", "claude-sonnet-4-20250514"),
+                ("judged", "gpt-4o"),
+                ("judged", "claude-sonnet-4-20250514"),
+            ]
+        );
+
+        assert!(run.result("hello", "gpt-4o").unwrap().passed);
+        assert!(
+            !run.result("hello", "claude-sonnet-4-20250514")
+                .unwrap()
+                .passed
+        );
+        let judged = run.result("judged", "gpt-4o").unwrap();
+        assert!(judged.passed);
+        assert!((judged.score - 0.8).abs() < 1e-9);
+        assert!(judged.cost > 0.0);
+        assert_eq!(judge.requests().len(), 2);
+
+        let sent = &good.requests()[0];
+        assert_eq!(sent.system_prompt.as_deref(), Some("Answer briefly."));
+        assert_eq!(sent.temperature, Some(0.0));
+
+        let summaries = run.summaries();
+        assert_eq!(summaries[0].model, "gpt-4o");
+        assert_eq!(summaries[0].passed, 2);
+        assert_eq!(summaries[1].passed, 1);
+        assert!(summaries[0].avg_score > summaries[1].avg_score);
+    }
+}
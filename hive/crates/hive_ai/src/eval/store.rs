@@ -0,0 +1,258 @@
+//! SQLite persistence for eval runs.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use hive_core::config::HiveConfig;
+use parking_lot::Mutex;
+use rusqlite::{Connection, OptionalExtension, params};
+
+use super::runner::{CaseResult, EvalRun};
+
+/// Header of a stored run, for listings.
+#[derive(Debug, Clone)]
+pub struct RunInfo {
+    pub id: String,
+    pub suite: String,
+    pub started_at: DateTime<Utc>,
+    pub models: Vec<String>,
+}
+
+/// SQLite-backed store of eval runs and their per-case results.
+pub struct EvalStore {
+    conn: Mutex<Connection>,
+}
+
+impl EvalStore {
+    /// The default database: `~/.hive/eval.db`.
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(HiveConfig::base_dir()?.join("eval.db"))
+    }
+
+    /// Open (or create) the store at `path`.
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open eval store {}", path.display()))?;
+        Self::with_connection(conn)
+    }
+
+    /// Store backed by an in-memory database (for tests).
+    pub fn in_memory() -> Result<Self> {
+        let conn = Connection::open_in_memory().context("Failed to open in-memory eval store")?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS eval_runs (
+                id TEXT PRIMARY KEY,
+                suite TEXT NOT NULL,
+                started_at TEXT NOT NULL,
+                models TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS eval_results (
+                run_id TEXT NOT NULL REFERENCES eval_runs(id) ON DELETE CASCADE,
+                position INTEGER NOT NULL,
+                case_id TEXT NOT NULL,
+                model TEXT NOT NULL,
+                provider TEXT NOT NULL,
+                response TEXT NOT NULL,
+                error TEXT,
+                latency_ms INTEGER NOT NULL,
+                prompt_tokens INTEGER NOT NULL,
+                completion_tokens INTEGER NOT NULL,
+                cost REAL NOT NULL,
+                score REAL NOT NULL,
+                passed INTEGER NOT NULL,
+                assertions TEXT NOT NULL,
+                PRIMARY KEY (run_id, position)
+            );
+            CREATE INDEX IF NOT EXISTS idx_eval_results_model ON eval_results(model);
+            ",
+        )
+        .context("Failed to create eval store schema")?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Persist a completed run.
+    pub fn save_run(&self, run: &EvalRun) -> Result<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO eval_runs (id, suite, started_at, models) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                run.id,
+                run.suite,
+                run.started_at.to_rfc3339(),
+                serde_json::to_string(&run.models)?,
+            ],
+        )
+        .context("Failed to save eval run")?;
+        for (position, r) in run.results.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO eval_results
+                    (run_id, position, case_id, model, provider, response, error, latency_ms,
+                     prompt_tokens, completion_tokens, cost, score, passed, assertions)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                params![
+                    run.id,
+                    position as i64,
+                    r.case_id,
+                    r.model,
+                    r.provider,
+                    r.response,
+                    r.error,
+                    r.latency_ms as i64,
+                    r.prompt_tokens,
+                    r.completion_tokens,
+                    r.cost,
+                    r.score,
+                    r.passed,
+                    serde_json::to_string(&r.assertions)?,
+                ],
+            )
+            .context("Failed to save eval result")?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Load a run by ID.
+    pub fn load_run(&self, id: &str) -> Result<Option<EvalRun>> {
+        let conn = self.conn.lock();
+        let Some(info) = conn
+            .query_row(
+                "SELECT id, suite, started_at, models FROM eval_runs WHERE id = ?1",
+                params![id],
+                row_to_info,
+            )
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        let mut stmt = conn.prepare(
+            "SELECT case_id, model, provider, response, error, latency_ms, prompt_tokens,
+                    completion_tokens, cost, score, passed, assertions
+             FROM eval_results WHERE run_id = ?1 ORDER BY position",
+        )?;
+        let results = stmt
+            .query_map(params![id], |row| {
+                let assertions: String = row.get(11)?;
+                Ok(CaseResult {
+                    case_id: row.get(0)?,
+                    model: row.get(1)?,
+                    provider: row.get(2)?,
+                    response: row.get(3)?,
+                    error: row.get(4)?,
+                    latency_ms: row.get::<_, i64>(5)? as u64,
+                    prompt_tokens: row.get(6)?,
+                    completion_tokens: row.get(7)?,
+                    cost: row.get(8)?,
+                    score: row.get(9)?,
+                    passed: row.get(10)?,
+                    assertions: serde_json::from_str(&assertions).unwrap_or_default(),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(Some(EvalRun {
+            id: info.id,
+            suite: info.suite,
+            started_at: info.started_at,
+            models: info.models,
+            results,
+        }))
+    }
+
+    /// Most recent runs first.
+    pub fn recent_runs(&self, limit: usize) -> Result<Vec<RunInfo>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT id, suite, started_at, models FROM eval_runs
+             ORDER BY started_at DESC LIMIT ?1",
+        )?;
+        let runs = stmt
+            .query_map(params![limit as i64], row_to_info)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(runs)
+    }
+}
+
+fn row_to_info(row: &rusqlite::Row<'_>) -> rusqlite::Result<RunInfo> {
+    let started_at: String = row.get(2)?;
+    let models: String = row.get(3)?;
+    Ok(RunInfo {
+        id: row.get(0)?,
+        suite: row.get(1)?,
+        started_at: DateTime::parse_from_rfc3339(&started_at)
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+        models: serde_json::from_str(&models).unwrap_or_default(),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::assertions::AssertionResult;
+
+    fn result(case_id: &str, model: &str, passed: bool) -> CaseResult {
+        CaseResult {
+            case_id: case_id.into(),
+            model: model.into(),
+            provider: "mock".into(),
+            response: "answer".into(),
+            error: (!passed).then(|| "timeout".to_string()),
+            latency_ms: 420,
+            prompt_tokens: 100,
+            completion_tokens: 20,
+            cost: 0.001,
+            score: if passed { 1.0 } else { 0.0 },
+            passed,
+            assertions: vec![AssertionResult {
+                assertion: "regex /a/".into(),
+                passed,
+                score: if passed { 1.0 } else { 0.0 },
+                detail: "matched".into(),
+            }],
+        }
+    }
+
+    #[test]
+    fn saves_and_loads_runs() {
+        let store = EvalStore::in_memory().unwrap();
+        let run = EvalRun {
+            id: "run-1".into(),
+            suite: "smoke".into(),
+            started_at: Utc::now(),
+            models: vec!["a".into(), "b".into()],
+            results: vec![result("c1", "a", true), result("c1", "b", false)],
+        };
+        store.save_run(&run).unwrap();
+
+        let loaded = store.load_run("run-1").unwrap().unwrap();
+        assert_eq!(loaded.models, ["a", "b"]);
+        assert_eq!(loaded.results.len(), 2);
+        assert_eq!(loaded.results[1].error.as_deref(), Some("timeout"));
+        assert_eq!(loaded.results[0].assertions[0].assertion, "regex /a/");
+        assert_eq!(loaded.summaries(), run.summaries());
+
+        assert!(store.load_run("missing").unwrap().is_none());
+        let runs = store.recent_runs(10).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].suite, "smoke");
+    }
+}
@@ -0,0 +1,271 @@
+//! Eval suites: recorded prompts with the assertions their answers must meet.
+//!
+//! Suites are written by hand as YAML (or JSON) or exported from saved
+//! conversations, in which case each recorded reply becomes the reference
+//! answer for an LLM-as-judge comparison.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use hive_core::conversations::Conversation;
+use serde::{Deserialize, Serialize};
+
+use super::assertions::Assertion;
+use crate::types::{ChatMessage, MessageRole};
+
+/// Rubric for cases exported from conversations.
+pub const REFERENCE_RUBRIC: &str =
+    "The response is at least as correct, complete and helpful as the reference answer.";
+
+/// Minimum judge score (0.0–1.0) for exported cases.
+const REFERENCE_MIN_SCORE: f64 = 0.7;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A named set of eval cases.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalSuite {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// System prompt for cases that do not set their own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    pub cases: Vec<EvalCase>,
+}
+
+/// One prompt to replay and the checks its answer must pass.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalCase {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// Earlier turns replayed before `prompt`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<EvalTurn>,
+    pub prompt: String,
+    /// Known-good answer, shown to the judge.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assertions: Vec<Assertion>,
+}
+
+/// A prior conversation turn.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalTurn {
+    pub role: MessageRole,
+    pub content: String,
+}
+
+// ---------------------------------------------------------------------------
+// Loading and export
+// ---------------------------------------------------------------------------
+
+impl EvalSuite {
+    /// Parse a suite from YAML. JSON is accepted too, as a subset of YAML.
+    pub fn from_yaml(text: &str) -> Result<Self> {
+        let suite: Self = serde_yaml::from_str(text).context("Invalid eval suite")?;
+        suite.validate()?;
+        Ok(suite)
+    }
+
+    /// Load a suite file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::from_yaml(&text).with_context(|| format!("Failed to load {}", path.display()))
+    }
+
+    /// Serialize the suite as YAML.
+    pub fn to_yaml(&self) -> Result<String> {
+        serde_yaml::to_string(self).context("Failed to serialize eval suite")
+    }
+
+    /// Check that case IDs are unique and every assertion is well formed.
+    pub fn validate(&self) -> Result<()> {
+        if self.cases.is_empty() {
+            bail!("Suite '{}' has no cases", self.name);
+        }
+        let mut seen = HashSet::new();
+        for case in &self.cases {
+            if !seen.insert(case.id.as_str()) {
+                bail!("Duplicate case id '{}'", case.id);
+            }
+            if case.prompt.trim().is_empty() {
+                bail!("Case '{}' has an empty prompt", case.id);
+            }
+            for assertion in &case.assertions {
+                assertion
+                    .validate()
+                    .with_context(|| format!("Case '{}'", case.id))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Build a suite from saved conversations: every user prompt on the
+    /// active path that got a reply becomes a case, with the reply as the
+    /// reference answer.
+    pub fn from_conversations(name: impl Into<String>, conversations: &[Conversation]) -> Self {
+        let mut cases = Vec::new();
+        for conversation in conversations {
+            let messages = &conversation.messages;
+            for (i, pair) in messages.windows(2).enumerate() {
+                let (prompt, reply) = (&pair[0], &pair[1]);
+                if prompt.role != "user" || reply.role != "assistant" {
+                    continue;
+                }
+                let history = messages[..i]
+                    .iter()
+                    .filter_map(|m| {
+                        let role = match m.role.as_str() {
+                            "user" => MessageRole::User,
+                            "assistant" => MessageRole::Assistant,
+                            _ => return None,
+                        };
+                        Some(EvalTurn {
+                            role,
+                            content: m.content.clone(),
+                        })
+                    })
+                    .collect();
+                cases.push(EvalCase {
+                    id: format!("{}-{}", conversation.id, i),
+                    system_prompt: None,
+                    history,
+                    prompt: prompt.content.clone(),
+                    reference: Some(reply.content.clone()),
+                    assertions: vec![Assertion::Judge {
+                        rubric: REFERENCE_RUBRIC.to_string(),
+                        min_score: REFERENCE_MIN_SCORE,
+                    }],
+                });
+            }
+        }
+        Self {
+            name: name.into(),
+            description: Some(format!(
+                "Exported from {} conversation(s)",
+                conversations.len()
+            )),
+            system_prompt: None,
+            cases,
+        }
+    }
+}
+
+impl EvalCase {
+    /// The messages sent to a candidate model.
+    pub fn messages(&self) -> Vec<ChatMessage> {
+        self.history
+            .iter()
+            .map(|t| ChatMessage::text(t.role, t.content.clone()))
+            .chain(std::iter::once(ChatMessage::text(
+                MessageRole::User,
+                self.prompt.clone(),
+            )))
+            .collect()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hive_core::conversations::StoredMessage;
+
+    const SUITE: &str = r#"
+name: smoke
+system_prompt: Be terse.
+cases:
+  - id: greet
+    prompt: Say hello
+    assertions:
+      - type: regex
+        pattern: "(?i)hello"
+  - id: json
+    history:
+      - role: user
+        content: We only speak JSON.
+      - role: assistant
+        content: "{}"
+    prompt: Give me a user object
+    assertions:
+      - type: json_schema
+        schema:
+          type: object
+          required: [name]
+      - type: judge
+        rubric: Names are realistic.
+"#;
+
+    fn stored(role: &str, content: &str) -> StoredMessage {
+        StoredMessage {
+            role: role.into(),
+            content: content.into(),
+            timestamp: chrono::Utc::now(),
+            model: None,
+            cost: None,
+            tokens: None,
+            thinking: None,
+            id: None,
+            parent_id: None,
+        }
+    }
+
+    #[test]
+    fn parses_yaml_suite() {
+        let suite = EvalSuite::from_yaml(SUITE).unwrap();
+        assert_eq!(suite.name, "smoke");
+        assert_eq!(suite.cases.len(), 2);
+        assert_eq!(suite.cases[1].assertions.len(), 2);
+        assert!(matches!(
+            suite.cases[1].assertions[1],
+            Assertion::Judge { min_score, .. } if (min_score - 0.7).abs() < f64::EPSILON
+        ));
+
+        let messages = suite.cases[1].messages();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1].role, MessageRole::Assistant);
+        assert_eq!(messages[2].content, "Give me a user object");
+    }
+
+    #[test]
+    fn rejects_duplicate_ids_and_bad_regex() {
+        let dup = "name: s\ncases:\n  - {id: a, prompt: x}\n  - {id: a, prompt: y}\n";
+        assert!(EvalSuite::from_yaml(dup).is_err());
+
+        let bad = "name: s\ncases:\n  - id: a\n    prompt: x\n    assertions:\n      - {type: regex, pattern: \"(\"}\n";
+        assert!(EvalSuite::from_yaml(bad).is_err());
+    }
+
+    #[test]
+    fn exports_conversation_turns_with_references() {
+        let mut conversation = Conversation::new("gpt-4o");
+        for (role, content) in [
+            ("user", "What is 2+2?"),
+            ("assistant", "4"),
+            ("user", "And times 3?"),
+            ("assistant", "12"),
+        ] {
+            conversation.add_message(stored(role, content));
+        }
+
+        let suite = EvalSuite::from_conversations("exported", &[conversation]);
+        assert_eq!(suite.cases.len(), 2);
+        let second = &suite.cases[1];
+        assert_eq!(second.prompt, "And times 3?");
+        assert_eq!(second.reference.as_deref(), Some("12"));
+        assert_eq!(second.history.len(), 2);
+
+        let round_trip = EvalSuite::from_yaml(&suite.to_yaml().unwrap()).unwrap();
+        assert_eq!(round_trip.cases.len(), 2);
+    }
+}
@@ -58,6 +58,17 @@ pub struct ModelPerformance {
     pub last_updated: DateTime<Utc>,
 }
 
+/// Outcome statistics for one model on one kind of task.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskPerformance {
+    pub task_type: String,
+    pub model_id: String,
+    pub total_requests: u64,
+    pub success_count: u64,
+    pub avg_quality_score: f64,
+    pub last_updated: DateTime<Utc>,
+}
+
 /// An insight derived from fleet-wide analysis.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FleetInsight {
@@ -104,6 +115,7 @@ pub struct FleetSummary {
 pub struct FleetLearningService {
     patterns: Vec<LearningPattern>,
     model_performance: HashMap<String, ModelPerformance>,
+    task_performance: HashMap<(String, String), TaskPerformance>,
     insights: Vec<FleetInsight>,
     instances: HashMap<String, InstanceMetrics>,
     db: Option<Mutex<Connection>>,
@@ -115,6 +127,7 @@ impl FleetLearningService {
         Self {
             patterns: Vec::new(),
             model_performance: HashMap::new(),
+            task_performance: HashMap::new(),
             insights: Vec::new(),
             instances: HashMap::new(),
             db: None,
@@ -126,12 +139,12 @@ impl FleetLearningService {
     /// Existing data is loaded from the database on construction. Call
     /// `persist()` to flush in-memory changes back to disk.
     pub fn with_db(path: &str) -> Result<Self, String> {
-        let conn =
-            Connection::open(path).map_err(|e| format!("Failed to open fleet DB: {e}"))?;
+        let conn = Connection::open(path).map_err(|e| format!("Failed to open fleet DB: {e}"))?;
         Self::init_fleet_tables(&conn)?;
         let mut service = Self {
             patterns: Vec::new(),
             model_performance: HashMap::new(),
+            task_performance: HashMap::new(),
             insights: Vec::new(),
             instances: HashMap::new(),
             db: Some(Mutex::new(conn)),
@@ -148,6 +161,7 @@ impl FleetLearningService {
         Ok(Self {
             patterns: Vec::new(),
             model_performance: HashMap::new(),
+            task_performance: HashMap::new(),
             insights: Vec::new(),
             instances: HashMap::new(),
             db: Some(Mutex::new(conn)),
@@ -175,6 +189,15 @@ impl FleetLearningService {
                 avg_quality_score REAL NOT NULL DEFAULT 0.0,
                 last_updated TEXT NOT NULL
             );
+            CREATE TABLE IF NOT EXISTS fleet_task_performance (
+                task_type TEXT NOT NULL,
+                model_id TEXT NOT NULL,
+                total_requests INTEGER NOT NULL DEFAULT 0,
+                success_count INTEGER NOT NULL DEFAULT 0,
+                avg_quality_score REAL NOT NULL DEFAULT 0.0,
+                last_updated TEXT NOT NULL,
+                PRIMARY KEY (task_type, model_id)
+            );
             CREATE TABLE IF NOT EXISTS fleet_insights (
                 id TEXT PRIMARY KEY,
                 insight_type TEXT NOT NULL,
@@ -261,6 +284,32 @@ impl FleetLearningService {
             }
         }
 
+        // Load task performance
+        {
+            let mut stmt = conn_guard
+                .prepare("SELECT task_type, model_id, total_requests, success_count, avg_quality_score, last_updated FROM fleet_task_performance")
+                .map_err(|e| format!("Prepare: {e}"))?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok(TaskPerformance {
+                        task_type: row.get(0)?,
+                        model_id: row.get(1)?,
+                        total_requests: row.get(2)?,
+                        success_count: row.get(3)?,
+                        avg_quality_score: row.get(4)?,
+                        last_updated: row
+                            .get::<_, String>(5)?
+                            .parse::<DateTime<Utc>>()
+                            .unwrap_or_else(|_| Utc::now()),
+                    })
+                })
+                .map_err(|e| format!("Query: {e}"))?;
+            for p in rows.flatten() {
+                self.task_performance
+                    .insert((p.task_type.clone(), p.model_id.clone()), p);
+            }
+        }
+
         // Load insights
         {
             let mut stmt = conn_guard
@@ -370,6 +419,22 @@ impl FleetLearningService {
             .map_err(|e| format!("Persist model_performance: {e}"))?;
         }
 
+        // Persist task performance
+        for perf in self.task_performance.values() {
+            conn.execute(
+                "INSERT OR REPLACE INTO fleet_task_performance (task_type, model_id, total_requests, success_count, avg_quality_score, last_updated) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    perf.task_type,
+                    perf.model_id,
+                    perf.total_requests,
+                    perf.success_count,
+                    perf.avg_quality_score,
+                    perf.last_updated.to_rfc3339(),
+                ],
+            )
+            .map_err(|e| format!("Persist task_performance: {e}"))?;
+        }
+
         // Persist insights
         for insight in &self.insights {
             conn.execute(
@@ -551,6 +616,71 @@ impl FleetLearningService {
         })
     }
 
+    // -- Task Performance --
+
+    /// Record how a model did on a task of `task_type` (e.g. "code_gen").
+    ///
+    /// Unlike patterns, these are plain counters and averages, so they are
+    /// what fleet sharing exchanges with peers.
+    pub fn record_task_performance(
+        &mut self,
+        task_type: &str,
+        model_id: &str,
+        success: bool,
+        quality_score: f64,
+    ) {
+        let now = Utc::now();
+        let perf = self
+            .task_performance
+            .entry((task_type.to_string(), model_id.to_string()))
+            .or_insert_with(|| TaskPerformance {
+                task_type: task_type.to_string(),
+                model_id: model_id.to_string(),
+                total_requests: 0,
+                success_count: 0,
+                avg_quality_score: 0.0,
+                last_updated: now,
+            });
+
+        let n = perf.total_requests as f64;
+        perf.total_requests += 1;
+        if success {
+            perf.success_count += 1;
+        }
+        perf.avg_quality_score =
+            (perf.avg_quality_score * n + quality_score) / perf.total_requests as f64;
+        perf.last_updated = now;
+    }
+
+    /// Get performance of `model_id` on tasks of `task_type`.
+    pub fn get_task_performance(
+        &self,
+        task_type: &str,
+        model_id: &str,
+    ) -> Option<&TaskPerformance> {
+        self.task_performance
+            .get(&(task_type.to_string(), model_id.to_string()))
+    }
+
+    /// All per-task performance records, ordered by task type then model.
+    pub fn task_performance(&self) -> Vec<&TaskPerformance> {
+        let mut all: Vec<&TaskPerformance> = self.task_performance.values().collect();
+        all.sort_by(|a, b| (&a.task_type, &a.model_id).cmp(&(&b.task_type, &b.model_id)));
+        all
+    }
+
+    /// The model with the highest average quality on `task_type`.
+    pub fn best_model_for_task(&self, task_type: &str) -> Option<&TaskPerformance> {
+        self.task_performance
+            .values()
+            .filter(|p| p.task_type == task_type)
+            .max_by(|a, b| {
+                a.avg_quality_score
+                    .partial_cmp(&b.avg_quality_score)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+    }
+
     // -- Insights --
 
     /// Add a fleet insight.
@@ -847,6 +977,41 @@ mod tests {
         assert!(service.best_model_by_cost().is_none());
     }
 
+    // -- Task performance --
+
+    #[test]
+    fn test_record_task_performance() {
+        let mut service = FleetLearningService::new();
+        service.record_task_performance("code_gen", "claude", true, 0.9);
+        service.record_task_performance("code_gen", "claude", false, 0.5);
+        service.record_task_performance("chat", "claude", true, 1.0);
+
+        let perf = service.get_task_performance("code_gen", "claude").unwrap();
+        assert_eq!(perf.total_requests, 2);
+        assert_eq!(perf.success_count, 1);
+        assert!((perf.avg_quality_score - 0.7).abs() < 1e-9);
+        assert_eq!(service.task_performance().len(), 2);
+        assert_eq!(service.task_performance()[0].task_type, "chat");
+    }
+
+    #[test]
+    fn test_best_model_for_task() {
+        let mut service = FleetLearningService::new();
+        service.record_task_performance("code_gen", "small", true, 0.6);
+        service.record_task_performance("code_gen", "large", true, 0.9);
+        service.record_task_performance("chat", "small", true, 0.95);
+
+        assert_eq!(
+            service.best_model_for_task("code_gen").unwrap().model_id,
+            "large"
+        );
+        assert_eq!(
+            service.best_model_for_task("chat").unwrap().model_id,
+            "small"
+        );
+        assert!(service.best_model_for_task("debugging").is_none());
+    }
+
     // -- Insights --
 
     #[test]
@@ -1084,6 +1249,23 @@ mod tests {
         assert!(service.get_instance_metrics("inst-1").is_some());
     }
 
+    #[test]
+    fn test_task_performance_survives_reload() {
+        let path = std::env::temp_dir().join(format!("fleet-{}.db", Uuid::new_v4()));
+        let db = path.to_string_lossy().to_string();
+        {
+            let mut service = FleetLearningService::with_db(&db).unwrap();
+            service.record_task_performance("code_gen", "claude", true, 0.8);
+            service.persist().unwrap();
+        }
+
+        let reloaded = FleetLearningService::with_db(&db).unwrap();
+        let perf = reloaded.get_task_performance("code_gen", "claude").unwrap();
+        assert_eq!(perf.total_requests, 1);
+        assert!((perf.avg_quality_score - 0.8).abs() < 1e-9);
+        let _ = std::fs::remove_file(&path);
+    }
+
     #[test]
     fn test_persist_noop_without_db() {
         let service = FleetLearningService::new();
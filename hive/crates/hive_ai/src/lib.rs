@@ -1,14 +1,18 @@
+pub mod compaction;
 pub mod context_engine;
 pub mod cost;
 pub mod discovery;
+pub mod eval;
 pub mod fleet_learning;
 pub mod model_registry;
 pub mod providers;
 pub mod rag;
+pub mod response_cache;
 pub mod routing;
 pub mod semantic_search;
 pub mod service;
 pub mod speculative;
+pub mod stream_failover;
 pub mod tts;
 pub mod types;
 
@@ -17,17 +21,19 @@ pub use context_engine::{
     ContextBudget, ContextEngine, ContextSource, ContextStats, CuratedContext, RelevanceScore,
     SourceType,
 };
-pub use cost::{BudgetLimits, CostBreakdown, CostTracker};
+pub use cost::{BudgetLimits, CacheStats, CostBreakdown, CostTracker};
 pub use discovery::{DiscoveredProvider, DiscoveryState, LocalDiscovery};
 pub use fleet_learning::{
     FleetInsight, FleetLearningService, InstanceMetrics, LearningPattern, ModelPerformance,
-    PatternType,
+    PatternType, TaskPerformance,
 };
 pub use providers::{AiProvider, ProviderError};
 pub use rag::{DocumentChunk, IndexStats, RagQuery, RagResult, RagService, ScoredChunk};
+pub use response_cache::{CacheHit, CacheHitKind, ResponseCache, ResponseCacheConfig};
 pub use semantic_search::{SearchEntry, SearchQuery, SearchResult, SemanticSearchService};
 pub use service::{AiService, AiServiceConfig};
 pub use speculative::{SpeculativeChunk, SpeculativeConfig, SpeculativeMetrics};
+pub use stream_failover::{FailoverProvider, FailoverTarget, StreamRetryPolicy};
 pub use tts::service::{TtsService, TtsServiceConfig};
 pub use tts::{TtsError, TtsProvider, TtsProviderType};
 pub use types::*;
@@ -529,11 +529,12 @@ impl AiProvider for AnthropicProvider {
             let mut state = SseParseState::new();
             let mut current_event_type = String::new();
 
-            while let Some(chunk_result) = stream.next().await {
+            'read: while let Some(chunk_result) = stream.next().await {
                 let bytes = match chunk_result {
                     Ok(b) => b,
                     Err(e) => {
                         warn!("Anthropic stream read error: {e}");
+                        state.error = Some(format!("connection lost: {e}"));
                         break;
                     }
                 };
@@ -568,11 +569,35 @@ impl AiProvider for AnthropicProvider {
                             }
 
                         current_event_type.clear();
+
+                        if state.error.is_some() {
+                            break 'read;
+                        }
                     }
                 }
             }
 
-            // Stream ended — send a final done chunk if we haven't already.
+            // `message_stop` already sent the final chunk.
+            if state.finished {
+                return;
+            }
+
+            // Stream ended early — send a final done chunk flagged as broken.
+            let error = state
+                .error
+                .take()
+                .unwrap_or_else(|| "stream ended before message_stop".into());
+            // Report tool calls in progress so the caller knows the answer
+            // cannot simply be continued.
+            let mut tool_calls = std::mem::take(&mut state.accumulated_tool_calls);
+            if state.current_block_type == "tool_use" {
+                tool_calls.push(ToolCall {
+                    id: std::mem::take(&mut state.current_tool_id),
+                    name: std::mem::take(&mut state.current_tool_name),
+                    input: serde_json::from_str(&state.current_tool_input_json)
+                        .unwrap_or(serde_json::Value::Object(serde_json::Map::new())),
+                });
+            }
             let _ = tx
                 .send(StreamChunk {
                     content: String::new(),
@@ -583,8 +608,10 @@ impl AiProvider for AnthropicProvider {
                         completion_tokens: state.output_tokens,
                         total_tokens: state.input_tokens + state.output_tokens,
                     }),
-                    tool_calls: None,
+                    tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
                     stop_reason: None,
+                    error: Some(error),
+                    handoff: None,
                 })
                 .await;
         });
@@ -608,6 +635,10 @@ struct SseParseState {
     current_tool_input_json: String,
     accumulated_tool_calls: Vec<ToolCall>,
     stop_reason: Option<String>,
+    /// `message_stop` was received and the final chunk sent.
+    finished: bool,
+    /// The stream broke (read error or `error` event).
+    error: Option<String>,
 }
 
 impl SseParseState {
@@ -621,6 +652,8 @@ impl SseParseState {
             current_tool_input_json: String::new(),
             accumulated_tool_calls: Vec::new(),
             stop_reason: None,
+            finished: false,
+            error: None,
         }
     }
 }
@@ -671,6 +704,8 @@ async fn process_sse_event(
                                     usage: None,
                                     tool_calls: None,
                                     stop_reason: None,
+                                    error: None,
+                                    handoff: None,
                                 };
                                 if tx.send(chunk).await.is_err() {
                                     return Err(true);
@@ -686,6 +721,8 @@ async fn process_sse_event(
                                     usage: None,
                                     tool_calls: None,
                                     stop_reason: None,
+                                    error: None,
+                                    handoff: None,
                                 };
                                 if tx.send(chunk).await.is_err() {
                                     return Err(true);
@@ -755,7 +792,10 @@ async fn process_sse_event(
                 }),
                 tool_calls,
                 stop_reason,
+                error: None,
+                handoff: None,
             };
+            state.finished = true;
             if tx.send(chunk).await.is_err() {
                 return Err(true);
             }
@@ -767,6 +807,7 @@ async fn process_sse_event(
 
         "error" => {
             warn!("Anthropic SSE error event: {data}");
+            state.error = Some(truncate_error(data));
         }
 
         _ => {
@@ -1112,6 +1153,18 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn error_event_marks_stream_broken() {
+        let (tx, _rx) = mpsc::channel(16);
+        let mut state = SseParseState::new();
+
+        let data = r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+        let result = process_sse_event("error", data, &mut state, &tx).await;
+        assert!(result.is_ok());
+        assert_eq!(state.error.as_deref(), Some("Overloaded"));
+        assert!(!state.finished);
+    }
+
     // -- Full SSE stream simulation --
 
     #[tokio::test]
@@ -0,0 +1,132 @@
+//! Scripted provider for unit tests.
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use tokio::sync::mpsc;
+
+use super::{AiProvider, ProviderError};
+use crate::types::{
+    ChatRequest, ChatResponse, FinishReason, ModelInfo, ProviderType, StreamChunk, TokenUsage,
+};
+
+/// Provider that answers every request with a fixed reply and records the
+/// requests it saw.
+pub(crate) struct MockProvider {
+    provider_type: ProviderType,
+    reply: String,
+    requests: Mutex<Vec<ChatRequest>>,
+    /// Calls still to fail before the provider starts answering.
+    failures: Mutex<u32>,
+}
+
+impl MockProvider {
+    pub(crate) fn new(reply: &str) -> Self {
+        Self {
+            provider_type: ProviderType::OpenAI,
+            reply: reply.to_string(),
+            requests: Mutex::new(Vec::new()),
+            failures: Mutex::new(0),
+        }
+    }
+
+    /// Fail the first `count` calls with a network error.
+    pub(crate) fn failing(self, count: u32) -> Self {
+        *self.failures.lock() = count;
+        self
+    }
+
+    /// Report `provider_type` instead of OpenAI.
+    pub(crate) fn with_type(mut self, provider_type: ProviderType) -> Self {
+        self.provider_type = provider_type;
+        self
+    }
+
+    /// Requests received so far, oldest first.
+    pub(crate) fn requests(&self) -> Vec<ChatRequest> {
+        self.requests.lock().clone()
+    }
+
+    fn usage() -> TokenUsage {
+        TokenUsage {
+            prompt_tokens: 100,
+            completion_tokens: 20,
+            total_tokens: 120,
+        }
+    }
+}
+
+#[async_trait]
+impl AiProvider for MockProvider {
+    fn provider_type(&self) -> ProviderType {
+        self.provider_type
+    }
+
+    fn name(&self) -> &str {
+        "Mock"
+    }
+
+    async fn is_available(&self) -> bool {
+        true
+    }
+
+    async fn get_models(&self) -> Vec<ModelInfo> {
+        Vec::new()
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse, ProviderError> {
+        self.requests.lock().push(request.clone());
+        {
+            let mut failures = self.failures.lock();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err(ProviderError::Network("mock failure".into()));
+            }
+        }
+        Ok(ChatResponse {
+            content: self.reply.clone(),
+            model: request.model.clone(),
+            usage: Self::usage(),
+            finish_reason: FinishReason::Stop,
+            thinking: None,
+            tool_calls: None,
+        })
+    }
+
+    async fn stream_chat(
+        &self,
+        request: &ChatRequest,
+    ) -> Result<mpsc::Receiver<StreamChunk>, ProviderError> {
+        self.requests.lock().push(request.clone());
+        let (tx, rx) = mpsc::channel(4);
+        let chunks = [
+            StreamChunk {
+                content: self.reply.clone(),
+                done: false,
+                thinking: None,
+                usage: None,
+                tool_calls: None,
+                stop_reason: None,
+                error: None,
+                handoff: None,
+            },
+            StreamChunk {
+                content: String::new(),
+                done: true,
+                thinking: None,
+                usage: Some(Self::usage()),
+                tool_calls: None,
+                stop_reason: None,
+                error: None,
+                handoff: None,
+            },
+        ];
+        tokio::spawn(async move {
+            for chunk in chunks {
+                if tx.send(chunk).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(rx)
+    }
+}
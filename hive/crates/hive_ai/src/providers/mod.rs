@@ -13,6 +13,8 @@ pub mod huggingface;
 pub mod huggingface_catalog;
 pub mod litellm;
 pub mod lmstudio;
+#[cfg(test)]
+pub(crate) mod mock;
 pub mod ollama;
 pub mod openai;
 pub mod openai_catalog;
@@ -52,6 +54,10 @@ pub enum ProviderError {
     #[error("Budget exceeded")]
     BudgetExceeded,
 
+    /// A routing rule forbids the request on every available model.
+    #[error(transparent)]
+    RouteBlocked(#[from] crate::routing::RouteBlocked),
+
     #[error("Provider error: {0}")]
     Other(String),
 }
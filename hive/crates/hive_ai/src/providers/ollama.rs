@@ -323,6 +323,8 @@ impl AiProvider for OllamaProvider {
                                 usage,
                                 tool_calls: None,
                                 stop_reason: None,
+                                error: None,
+                                handoff: None,
                             };
 
                             if tx.send(chunk).await.is_err() {
@@ -350,6 +352,8 @@ impl AiProvider for OllamaProvider {
                     usage: None,
                     tool_calls: None,
                     stop_reason: None,
+                    error: Some("stream ended before completion".into()),
+                    handoff: None,
                 })
                 .await;
         });
@@ -27,8 +27,17 @@ use crate::types::{StopReason, StreamChunk, TokenUsage, ToolCall};
 pub(crate) struct SseFrame {
     #[allow(dead_code)]
     pub id: Option<String>,
+    #[serde(default)]
     pub choices: Vec<SseChoice>,
     pub usage: Option<SseUsage>,
+    /// Mid-stream error (OpenRouter sends these when the upstream fails
+    /// after the response has started).
+    pub error: Option<SseError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct SseError {
+    pub message: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -118,12 +127,14 @@ pub(crate) async fn drive_sse_stream(resp: reqwest::Response, tx: mpsc::Sender<S
     let mut accumulated_usage: Option<TokenUsage> = None;
     let mut tool_call_accumulators: Vec<ToolCallAccumulator> = Vec::new();
     let mut finish_reason: Option<String> = None;
+    let mut stream_error: Option<String> = None;
 
-    while let Some(chunk_result) = stream.next().await {
+    'read: while let Some(chunk_result) = stream.next().await {
         let bytes = match chunk_result {
             Ok(b) => b,
             Err(e) => {
                 warn!("SSE stream read error: {e}");
+                stream_error = Some(format!("connection lost: {e}"));
                 break;
             }
         };
@@ -175,6 +186,8 @@ pub(crate) async fn drive_sse_stream(resp: reqwest::Response, tx: mpsc::Sender<S
                     usage: accumulated_usage.take(),
                     tool_calls,
                     stop_reason,
+                    error: None,
+                    handoff: None,
                 };
                 let _ = tx.send(chunk).await;
                 return;
@@ -183,6 +196,13 @@ pub(crate) async fn drive_sse_stream(resp: reqwest::Response, tx: mpsc::Sender<S
             // Parse the JSON frame.
             match serde_json::from_str::<SseFrame>(data) {
                 Ok(frame) => {
+                    if let Some(err) = &frame.error {
+                        let message = err.message.as_deref().unwrap_or("unknown error");
+                        warn!("SSE stream error event: {message}");
+                        stream_error = Some(message.to_string());
+                        break 'read;
+                    }
+
                     let choice = frame.choices.first();
 
                     // Track finish_reason.
@@ -246,6 +266,8 @@ pub(crate) async fn drive_sse_stream(resp: reqwest::Response, tx: mpsc::Sender<S
                             usage: None,
                             tool_calls: None,
                             stop_reason: None,
+                            error: None,
+                            handoff: None,
                         };
                         if tx.send(chunk).await.is_err() {
                             return;
@@ -259,7 +281,14 @@ pub(crate) async fn drive_sse_stream(resp: reqwest::Response, tx: mpsc::Sender<S
         }
     }
 
-    // Stream ended without [DONE] — send a final sentinel.
+    // Stream ended without [DONE] — send a final sentinel. Without a finish
+    // reason either, the connection was cut mid-response.
+    if finish_reason.as_deref() == Some("error") && stream_error.is_none() {
+        stream_error = Some("provider reported an error".into());
+    }
+    if finish_reason.is_none() && stream_error.is_none() {
+        stream_error = Some("stream ended before completion".into());
+    }
     let stop_reason = finish_reason.as_deref().map(|r| match r {
         "tool_calls" => StopReason::ToolUse,
         "length" => StopReason::MaxTokens,
@@ -289,6 +318,8 @@ pub(crate) async fn drive_sse_stream(resp: reqwest::Response, tx: mpsc::Sender<S
             usage: accumulated_usage,
             tool_calls,
             stop_reason,
+            error: stream_error,
+            handoff: None,
         })
         .await;
 }
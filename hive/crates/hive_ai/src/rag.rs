@@ -2,8 +2,13 @@
 //!
 //! Provides document chunking, TF-IDF indexing, and context assembly
 //! for feeding relevant code/document snippets into LLM prompts.
+//! PDF, DOCX, XLSX and PPTX files are indexed through `hive_docs`
+//! extraction, and their chunks keep the page, slide or cell range they
+//! came from so answers can cite it.
 
 use anyhow::{Context, Result};
+use hive_core::search::{SearchDocument, SearchSource};
+use hive_docs::extract::{DocumentFormat, ExtractedDocument};
 use hive_fs::is_likely_binary;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
@@ -25,6 +30,21 @@ pub struct DocumentChunk {
     pub start_line: usize,
     pub end_line: usize,
     pub embedding: Option<Vec<f32>>,
+    /// Page, slide or cell range the chunk came from ("page 4",
+    /// "Sheet2!B3:F20"), for chunks of extracted documents.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<String>,
+}
+
+impl DocumentChunk {
+    /// Human-readable position: "lines 3-9", or "page 4, lines 3-9" when
+    /// the chunk has an anchor.
+    pub fn location(&self) -> String {
+        match &self.anchor {
+            Some(anchor) => format!("{anchor}, lines {}-{}", self.start_line, self.end_line),
+            None => format!("lines {}-{}", self.start_line, self.end_line),
+        }
+    }
 }
 
 /// A query against the RAG index.
@@ -63,7 +83,7 @@ pub struct IndexStats {
 // ---------------------------------------------------------------------------
 
 /// Tokenize text into lowercase word tokens, stripping non-alphanumeric chars.
-fn tokenize(text: &str) -> Vec<String> {
+pub(crate) fn tokenize(text: &str) -> Vec<String> {
     text.split(|c: char| !c.is_alphanumeric() && c != '_')
         .filter(|w| !w.is_empty())
         .map(|w| w.to_lowercase())
@@ -71,7 +91,7 @@ fn tokenize(text: &str) -> Vec<String> {
 }
 
 /// Compute term frequency (TF) for a list of tokens.
-fn term_frequency(tokens: &[String]) -> HashMap<String, f32> {
+pub(crate) fn term_frequency(tokens: &[String]) -> HashMap<String, f32> {
     let mut counts: HashMap<String, usize> = HashMap::new();
     for token in tokens {
         *counts.entry(token.clone()).or_insert(0) += 1;
@@ -172,10 +192,53 @@ impl RagService {
         self.rebuild_cache();
     }
 
+    /// Index an extracted document (PDF, DOCX, XLSX, PPTX, ...).
+    ///
+    /// Each page, slide or sheet range is chunked on its own so every chunk
+    /// carries a single anchor. Line numbers refer to the document's
+    /// Markdown rendering (`ExtractedDocument::to_markdown`).
+    pub fn index_document(&mut self, path: &str, document: &ExtractedDocument) {
+        self.add_document_chunks(path, document);
+        self.rebuild_cache();
+    }
+
     /// Add chunks for a file without rebuilding the cache.
     /// Use `rebuild_cache()` after batch additions.
     fn add_file_chunks(&mut self, path: &str, content: &str) {
         let lines: Vec<&str> = content.lines().collect();
+        self.push_chunks(path, &lines, 0, None);
+
+        debug!(
+            "Indexed file '{}': {} lines, {} chunks",
+            path,
+            lines.len(),
+            self.index.iter().filter(|c| c.source_file == path).count()
+        );
+    }
+
+    /// Add chunks for an extracted document without rebuilding the cache.
+    fn add_document_chunks(&mut self, path: &str, document: &ExtractedDocument) {
+        // Sections are joined by a blank line in the Markdown rendering.
+        let mut offset = 0;
+        for section in document.sections.iter().filter(|s| !s.is_empty()) {
+            let markdown = section.to_markdown();
+            let lines: Vec<&str> = markdown.lines().collect();
+            let anchor = section.anchor.as_ref().map(ToString::to_string);
+            self.push_chunks(path, &lines, offset, anchor.as_deref());
+            offset += lines.len() + 1;
+        }
+
+        debug!(
+            "Indexed document '{}': {} sections, {} chunks",
+            path,
+            document.sections.len(),
+            self.index.iter().filter(|c| c.source_file == path).count()
+        );
+    }
+
+    /// Split `lines` into overlapping chunks. `offset` is the number of
+    /// lines in the source before `lines[0]`.
+    fn push_chunks(&mut self, path: &str, lines: &[&str], offset: usize, anchor: Option<&str>) {
         if lines.is_empty() {
             return;
         }
@@ -191,9 +254,10 @@ impl RagService {
                 id: Uuid::new_v4().to_string(),
                 source_file: path.to_string(),
                 content: chunk_content,
-                start_line: start + 1, // 1-based
-                end_line: end,         // inclusive of last line
+                start_line: offset + start + 1, // 1-based
+                end_line: offset + end,         // inclusive of last line
                 embedding: None,
+                anchor: anchor.map(str::to_string),
             });
 
             start += step;
@@ -201,13 +265,6 @@ impl RagService {
                 break;
             }
         }
-
-        debug!(
-            "Indexed file '{}': {} lines, {} chunks",
-            path,
-            lines.len(),
-            self.index.iter().filter(|c| c.source_file == path).count()
-        );
     }
 
     /// Rebuild cached IDF map, document token sets, and TF-IDF vectors.
@@ -250,7 +307,8 @@ impl RagService {
 
     /// Recursively index all text files in a directory.
     ///
-    /// Skips binary files and hidden directories. Returns the number of
+    /// PDF, DOCX, XLSX and PPTX files are extracted first; other binary
+    /// files and hidden directories are skipped. Returns the number of
     /// files successfully indexed.
     pub fn index_directory(&mut self, path: &Path) -> Result<usize> {
         let count = self.index_directory_inner(path)?;
@@ -279,12 +337,22 @@ impl RagService {
             if entry_path.is_dir() {
                 count += self.index_directory_inner(&entry_path)?;
             } else if entry_path.is_file() {
+                let path_str = entry_path.to_string_lossy().to_string();
+                if DocumentFormat::from_path(&entry_path).is_some_and(DocumentFormat::is_binary) {
+                    match hive_docs::extract::extract_file(&entry_path) {
+                        Ok(document) => {
+                            self.add_document_chunks(&path_str, &document);
+                            count += 1;
+                        }
+                        Err(e) => debug!("Skipping '{}': {e:#}", path_str),
+                    }
+                    continue;
+                }
                 // Skip likely binary files
                 if is_likely_binary(&entry_path) {
                     continue;
                 }
                 if let Ok(content) = fs::read_to_string(&entry_path) {
-                    let path_str = entry_path.to_string_lossy().to_string();
                     self.add_file_chunks(&path_str, &content);
                     count += 1;
                 }
@@ -338,8 +406,10 @@ impl RagService {
             .iter()
             .map(|sc| {
                 format!(
-                    "--- {} (lines {}-{}) ---\n{}",
-                    sc.chunk.source_file, sc.chunk.start_line, sc.chunk.end_line, sc.chunk.content
+                    "--- {} ({}) ---\n{}",
+                    sc.chunk.source_file,
+                    sc.chunk.location(),
+                    sc.chunk.content
                 )
             })
             .collect::<Vec<_>>()
@@ -370,8 +440,10 @@ impl RagService {
 
         for sc in &result.chunks {
             let snippet = format!(
-                "--- {} (lines {}-{}) ---\n{}\n\n",
-                sc.chunk.source_file, sc.chunk.start_line, sc.chunk.end_line, sc.chunk.content
+                "--- {} ({}) ---\n{}\n\n",
+                sc.chunk.source_file,
+                sc.chunk.location(),
+                sc.chunk.content
             );
             let snippet_tokens = estimate_tokens(&snippet);
             if tokens_used + snippet_tokens > max_tokens {
@@ -408,6 +480,34 @@ impl RagService {
     pub fn chunks(&self) -> &[DocumentChunk] {
         &self.index
     }
+
+    /// All chunks as documents for the unified search index.
+    pub fn search_documents(&self) -> Vec<SearchDocument> {
+        self.index.iter().map(SearchDocument::from).collect()
+    }
+}
+
+impl From<&DocumentChunk> for SearchDocument {
+    /// Keyed by file and line range, which stay stable across re-indexing.
+    fn from(chunk: &DocumentChunk) -> Self {
+        let file_name = Path::new(&chunk.source_file)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| chunk.source_file.clone());
+        SearchDocument::new(
+            SearchSource::Code,
+            format!(
+                "{}:{}-{}",
+                chunk.source_file, chunk.start_line, chunk.end_line
+            ),
+            match &chunk.anchor {
+                Some(anchor) => format!("{file_name} ({anchor})"),
+                None => format!("{file_name}:{}-{}", chunk.start_line, chunk.end_line),
+            },
+            &chunk.content,
+        )
+        .with_location(&chunk.source_file)
+    }
 }
 
 impl Default for RagService {
@@ -617,6 +717,19 @@ mod tests {
         assert_eq!(ids.len(), service.index.len());
     }
 
+    #[test]
+    fn test_search_documents_have_stable_ids() {
+        let mut service = RagService::new(2, 0);
+        service.index_file("src/lib.rs", "line1\nline2\nline3");
+
+        let docs = service.search_documents();
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[0].id, "src/lib.rs:1-2");
+        assert_eq!(docs[0].title, "lib.rs:1-2");
+        assert_eq!(docs[1].id, "src/lib.rs:3-3");
+        assert_eq!(docs[0].location.as_deref(), Some("src/lib.rs"));
+    }
+
     #[test]
     fn test_idf_computation() {
         let doc1: HashSet<String> = ["hello", "world"].iter().map(|s| s.to_string()).collect();
@@ -653,4 +766,87 @@ mod tests {
         assert_eq!(service.overlap, 10);
         assert!(service.index.is_empty());
     }
+
+    #[test]
+    fn test_index_document_keeps_anchors() {
+        let rows = vec![
+            vec!["Widget".to_string(), "9.99".to_string()],
+            vec!["Gadget".to_string(), "19.50".to_string()],
+        ];
+        let bytes = hive_docs::xlsx::generate_xlsx(&["Product", "Price"], &rows).unwrap();
+        let document = hive_docs::extract::extract(&bytes, DocumentFormat::Xlsx).unwrap();
+
+        let mut service = RagService::new(50, 0);
+        service.index_file("notes.md", "Unrelated notes about cats.");
+        service.index_document("prices.xlsx", &document);
+
+        let chunk = service
+            .chunks()
+            .iter()
+            .find(|c| c.source_file == "prices.xlsx")
+            .unwrap();
+        assert_eq!(chunk.anchor.as_deref(), Some("Sheet1!A1:B3"));
+        assert_eq!(chunk.start_line, 1);
+
+        let result = service
+            .query(&RagQuery {
+                query: "gadget price".to_string(),
+                max_results: 1,
+                min_similarity: 0.0,
+            })
+            .unwrap();
+        assert!(
+            result
+                .context
+                .starts_with("--- prices.xlsx (Sheet1!A1:B3, lines 1-"),
+            "{}",
+            result.context
+        );
+        assert!(result.context.contains("| Gadget | 19.5 |"));
+
+        let doc = SearchDocument::from(chunk);
+        assert_eq!(doc.title, "prices.xlsx (Sheet1!A1:B3)");
+    }
+
+    #[test]
+    fn test_index_directory_extracts_documents() {
+        let dir = std::env::temp_dir().join(format!("hive_rag_docs_{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let pdf = hive_docs::pdf::generate_pdf_document(
+            "Launch Plan",
+            &[("Timeline", "Beta ships in March.")],
+        )
+        .unwrap();
+        fs::write(dir.join("plan.pdf"), pdf).unwrap();
+        fs::write(dir.join("broken.docx"), b"not a zip").unwrap();
+        fs::write(dir.join("readme.txt"), "Plain text file.").unwrap();
+
+        let mut service = RagService::new(50, 0);
+        let count = service.index_directory(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(count, 2);
+        let chunk = service
+            .chunks()
+            .iter()
+            .find(|c| c.source_file.ends_with("plan.pdf"))
+            .unwrap();
+        assert_eq!(chunk.anchor.as_deref(), Some("page 1"));
+        assert!(chunk.content.contains("Beta ships in March."));
+        assert!(
+            service
+                .build_context("beta march", 1000)
+                .contains("plan.pdf (page 1, lines 1-")
+        );
+    }
+
+    #[test]
+    fn test_chunk_without_anchor_deserializes() {
+        let chunk: DocumentChunk = serde_json::from_str(
+            r#"{"id":"1","source_file":"a.rs","content":"x","start_line":1,"end_line":2,"embedding":null}"#,
+        )
+        .unwrap();
+        assert_eq!(chunk.anchor, None);
+        assert_eq!(chunk.location(), "lines 1-2");
+    }
 }
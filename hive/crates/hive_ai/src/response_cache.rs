@@ -0,0 +1,539 @@
+//! Response cache for non-streaming chat requests.
+//!
+//! Identical requests (same model, system prompt, messages, tools and
+//! sampling settings) are answered from a SQLite cache instead of the
+//! provider. An opt-in semantic mode also reuses an answer when everything
+//! but the final user prompt matches and that prompt is a near-duplicate of
+//! a cached one. Requests with a non-zero temperature ask for varied output
+//! and always bypass the cache; requests that leave the temperature unset
+//! run at the provider's (usually non-zero) default and bypass it unless
+//! [`ResponseCacheConfig::cache_default_temperature`] is set.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use parking_lot::Mutex;
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+use crate::rag::{cosine_similarity, term_frequency, tokenize};
+use crate::types::{
+    ChatRequest, ChatResponse, FinishReason, MessageRole, ToolCall, ToolDefinition,
+};
+
+/// Most recent entries compared against a prompt in semantic mode.
+const SEMANTIC_CANDIDATES: usize = 500;
+
+// ---------------------------------------------------------------------------
+// Configuration
+// ---------------------------------------------------------------------------
+
+/// Response cache settings extracted from HiveConfig.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseCacheConfig {
+    /// How long an entry stays valid, in seconds.
+    pub ttl_secs: u64,
+    /// Also reuse answers for near-duplicate prompts.
+    pub semantic: bool,
+    /// Minimum cosine similarity (0.0–1.0) for a semantic hit.
+    pub similarity_threshold: f32,
+    /// Entries kept before the oldest are evicted.
+    pub max_entries: usize,
+    /// Also cache requests that leave the temperature unset, trusting the
+    /// provider's default to be deterministic enough to replay.
+    pub cache_default_temperature: bool,
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: 24 * 60 * 60,
+            semantic: false,
+            similarity_threshold: 0.92,
+            max_entries: 10_000,
+            cache_default_temperature: false,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// How a cached response matched the request.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CacheHitKind {
+    Exact,
+    Semantic { similarity: f32 },
+}
+
+/// A response served from the cache.
+#[derive(Debug, Clone)]
+pub struct CacheHit {
+    pub response: ChatResponse,
+    pub kind: CacheHitKind,
+}
+
+/// The parts of a request that determine its answer. Timestamps are left
+/// out and text is trimmed so re-sent conversations hash the same.
+#[derive(Serialize)]
+struct NormalizedRequest<'a> {
+    model: &'a str,
+    system_prompt: Option<&'a str>,
+    messages: Vec<NormalizedMessage<'a>>,
+    tools: Option<&'a [ToolDefinition]>,
+    temperature: Option<f32>,
+    max_tokens: u32,
+}
+
+#[derive(Serialize)]
+struct NormalizedMessage<'a> {
+    role: MessageRole,
+    content: &'a str,
+    tool_call_id: Option<&'a str>,
+    tool_calls: Option<&'a [ToolCall]>,
+}
+
+impl<'a> NormalizedRequest<'a> {
+    fn new(request: &'a ChatRequest) -> Self {
+        Self {
+            model: &request.model,
+            system_prompt: request.system_prompt.as_deref().map(str::trim),
+            messages: request
+                .messages
+                .iter()
+                .map(|m| NormalizedMessage {
+                    role: m.role,
+                    content: m.content.trim(),
+                    tool_call_id: m.tool_call_id.as_deref(),
+                    tool_calls: m.tool_calls.as_deref(),
+                })
+                .collect(),
+            tools: request.tools.as_deref(),
+            temperature: request.temperature,
+            max_tokens: request.max_tokens,
+        }
+    }
+
+    fn hash(&self) -> String {
+        let json = serde_json::to_string(self).unwrap_or_default();
+        format!("{:x}", Sha256::digest(json.as_bytes()))
+    }
+}
+
+/// Exact-match key for a request.
+pub fn request_key(request: &ChatRequest) -> String {
+    NormalizedRequest::new(request).hash()
+}
+
+/// Semantic lookup key: a hash of the request with the final user prompt
+/// blanked out, plus that prompt. `None` if the request does not end with a
+/// user message.
+fn semantic_key(request: &ChatRequest) -> Option<(String, String)> {
+    let last = request.messages.last()?;
+    if last.role != MessageRole::User {
+        return None;
+    }
+    let mut normalized = NormalizedRequest::new(request);
+    normalized.messages.last_mut()?.content = "";
+    Some((normalized.hash(), last.content.trim().to_string()))
+}
+
+fn prompt_similarity(a: &str, b: &str) -> f32 {
+    cosine_similarity(&term_frequency(&tokenize(a)), &term_frequency(&tokenize(b)))
+}
+
+// ---------------------------------------------------------------------------
+// ResponseCache
+// ---------------------------------------------------------------------------
+
+/// SQLite-backed cache of chat responses.
+pub struct ResponseCache {
+    conn: Mutex<Connection>,
+    config: ResponseCacheConfig,
+}
+
+impl ResponseCache {
+    /// Open (or create) the cache database at `path`.
+    pub fn open(path: &Path, config: ResponseCacheConfig) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open response cache {}", path.display()))?;
+        Self::with_connection(conn, config)
+    }
+
+    /// Cache backed by an in-memory database (for tests).
+    pub fn in_memory(config: ResponseCacheConfig) -> Result<Self> {
+        let conn = Connection::open_in_memory().context("Failed to open in-memory cache")?;
+        Self::with_connection(conn, config)
+    }
+
+    fn with_connection(conn: Connection, config: ResponseCacheConfig) -> Result<Self> {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS response_cache (
+                key TEXT PRIMARY KEY,
+                scope TEXT,
+                prompt TEXT,
+                model TEXT NOT NULL,
+                response TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                expires_at INTEGER NOT NULL,
+                hits INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE INDEX IF NOT EXISTS idx_response_cache_scope
+                ON response_cache(scope, expires_at);
+            ",
+        )
+        .context("Failed to create response cache schema")?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            config,
+        })
+    }
+
+    /// Current settings.
+    pub fn config(&self) -> &ResponseCacheConfig {
+        &self.config
+    }
+
+    /// Whether `request` may be served from or stored in the cache. Requests
+    /// that set a non-zero temperature want varied output and bypass it, as
+    /// do requests without a temperature unless the config opts them in.
+    pub fn is_cacheable(&self, request: &ChatRequest) -> bool {
+        match request.temperature {
+            Some(t) => t.abs() < f32::EPSILON,
+            None => self.config.cache_default_temperature,
+        }
+    }
+
+    /// Look up a cached response. Tries an exact match first, then (in
+    /// semantic mode) the most similar cached prompt above the threshold.
+    /// Errors are logged and treated as a miss.
+    pub fn lookup(&self, request: &ChatRequest) -> Option<CacheHit> {
+        if !self.is_cacheable(request) {
+            return None;
+        }
+        match self.try_lookup(request) {
+            Ok(hit) => hit,
+            Err(e) => {
+                warn!("Response cache lookup failed: {e:#}");
+                None
+            }
+        }
+    }
+
+    fn try_lookup(&self, request: &ChatRequest) -> Result<Option<CacheHit>> {
+        let conn = self.conn.lock();
+        let now = Utc::now().timestamp();
+
+        let key = request_key(request);
+        let exact: Option<String> = conn
+            .query_row(
+                "SELECT response FROM response_cache WHERE key = ?1 AND expires_at > ?2",
+                params![key, now],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(json) = exact {
+            conn.execute(
+                "UPDATE response_cache SET hits = hits + 1 WHERE key = ?1",
+                params![key],
+            )?;
+            return Ok(Some(CacheHit {
+                response: serde_json::from_str(&json)?,
+                kind: CacheHitKind::Exact,
+            }));
+        }
+
+        if !self.config.semantic {
+            return Ok(None);
+        }
+        let Some((scope, prompt)) = semantic_key(request) else {
+            return Ok(None);
+        };
+        let mut stmt = conn.prepare(
+            "SELECT key, prompt, response FROM response_cache
+             WHERE scope = ?1 AND expires_at > ?2
+             ORDER BY created_at DESC LIMIT ?3",
+        )?;
+        let candidates = stmt
+            .query_map(params![scope, now, SEMANTIC_CANDIDATES as i64], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let best = candidates
+            .into_iter()
+            .map(|(key, cached_prompt, json)| {
+                (prompt_similarity(&prompt, &cached_prompt), key, json)
+            })
+            .filter(|(similarity, _, _)| *similarity >= self.config.similarity_threshold)
+            .max_by(|a, b| a.0.total_cmp(&b.0));
+        let Some((similarity, key, json)) = best else {
+            return Ok(None);
+        };
+        conn.execute(
+            "UPDATE response_cache SET hits = hits + 1 WHERE key = ?1",
+            params![key],
+        )?;
+        debug!("Semantic cache hit (similarity {similarity:.3})");
+        Ok(Some(CacheHit {
+            response: serde_json::from_str(&json)?,
+            kind: CacheHitKind::Semantic { similarity },
+        }))
+    }
+
+    /// Store the response to `request`. Uncacheable requests and failed
+    /// responses are ignored.
+    pub fn store(&self, request: &ChatRequest, response: &ChatResponse) -> Result<()> {
+        if !self.is_cacheable(request) || response.finish_reason == FinishReason::Error {
+            return Ok(());
+        }
+        let (scope, prompt) = semantic_key(request).unzip();
+        let now = Utc::now().timestamp();
+        let expires_at = now.saturating_add(self.config.ttl_secs.min(i64::MAX as u64) as i64);
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT OR REPLACE INTO response_cache
+                (key, scope, prompt, model, response, created_at, expires_at, hits)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 0)",
+            params![
+                request_key(request),
+                scope,
+                prompt,
+                request.model,
+                serde_json::to_string(response)?,
+                now,
+                expires_at,
+            ],
+        )?;
+        conn.execute(
+            "DELETE FROM response_cache WHERE key NOT IN (
+                SELECT key FROM response_cache ORDER BY created_at DESC, rowid DESC LIMIT ?1
+             )",
+            params![self.config.max_entries as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Delete expired entries. Returns how many were removed.
+    pub fn purge_expired(&self) -> Result<usize> {
+        let removed = self.conn.lock().execute(
+            "DELETE FROM response_cache WHERE expires_at <= ?1",
+            params![Utc::now().timestamp()],
+        )?;
+        Ok(removed)
+    }
+
+    /// Delete every entry.
+    pub fn clear(&self) -> Result<()> {
+        self.conn.lock().execute("DELETE FROM response_cache", [])?;
+        Ok(())
+    }
+
+    /// Number of stored entries, including expired ones not yet purged.
+    pub fn len(&self) -> usize {
+        self.conn
+            .lock()
+            .query_row("SELECT COUNT(*) FROM response_cache", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .map(|n| n as usize)
+            .unwrap_or(0)
+    }
+
+    /// Whether the cache holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ChatMessage, TokenUsage};
+
+    fn request(prompt: &str) -> ChatRequest {
+        ChatRequest {
+            messages: vec![
+                ChatMessage::text(MessageRole::User, "You review Rust code."),
+                ChatMessage::text(MessageRole::Assistant, "Ready."),
+                ChatMessage::text(MessageRole::User, prompt),
+            ],
+            model: "gpt-4o-mini".into(),
+            max_tokens: 4096,
+            temperature: Some(0.0),
+            system_prompt: Some("Be terse.".into()),
+            tools: None,
+        }
+    }
+
+    fn response(content: &str) -> ChatResponse {
+        ChatResponse {
+            content: content.into(),
+            model: "gpt-4o-mini".into(),
+            usage: TokenUsage {
+                prompt_tokens: 1000,
+                completion_tokens: 500,
+                total_tokens: 1500,
+            },
+            finish_reason: FinishReason::Stop,
+            thinking: None,
+            tool_calls: None,
+        }
+    }
+
+    fn semantic_config() -> ResponseCacheConfig {
+        ResponseCacheConfig {
+            semantic: true,
+            similarity_threshold: 0.8,
+            ..ResponseCacheConfig::default()
+        }
+    }
+
+    #[test]
+    fn key_ignores_timestamps_and_whitespace() {
+        let a = request("Explain lifetimes");
+        let mut b = request("  Explain lifetimes\n");
+        for m in &mut b.messages {
+            m.timestamp = Utc::now() + chrono::Duration::hours(1);
+        }
+        assert_eq!(request_key(&a), request_key(&b));
+
+        let mut c = request("Explain lifetimes");
+        c.model = "gpt-4o".into();
+        assert_ne!(request_key(&a), request_key(&c));
+    }
+
+    #[test]
+    fn exact_hit_after_store() {
+        let cache = ResponseCache::in_memory(ResponseCacheConfig::default()).unwrap();
+        let req = request("Explain lifetimes");
+        assert!(cache.lookup(&req).is_none());
+
+        cache.store(&req, &response("Borrow scopes.")).unwrap();
+        let hit = cache.lookup(&req).unwrap();
+        assert_eq!(hit.kind, CacheHitKind::Exact);
+        assert_eq!(hit.response.content, "Borrow scopes.");
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn nonzero_temperature_bypasses_cache() {
+        let cache = ResponseCache::in_memory(ResponseCacheConfig::default()).unwrap();
+        let mut req = request("Write a poem");
+        req.temperature = Some(0.7);
+
+        cache.store(&req, &response("Roses")).unwrap();
+        assert!(cache.is_empty());
+        assert!(cache.lookup(&req).is_none());
+
+        req.temperature = Some(0.0);
+        assert!(cache.is_cacheable(&req));
+    }
+
+    #[test]
+    fn unset_temperature_is_cached_only_when_opted_in() {
+        let mut req = request("Write a poem");
+        req.temperature = None;
+
+        let cache = ResponseCache::in_memory(ResponseCacheConfig::default()).unwrap();
+        cache.store(&req, &response("Roses")).unwrap();
+        assert!(cache.is_empty());
+        assert!(cache.lookup(&req).is_none());
+
+        let config = ResponseCacheConfig {
+            cache_default_temperature: true,
+            ..ResponseCacheConfig::default()
+        };
+        let cache = ResponseCache::in_memory(config).unwrap();
+        cache.store(&req, &response("Roses")).unwrap();
+        assert_eq!(cache.lookup(&req).unwrap().response.content, "Roses");
+    }
+
+    #[test]
+    fn expired_entries_miss_and_purge() {
+        let config = ResponseCacheConfig {
+            ttl_secs: 0,
+            ..ResponseCacheConfig::default()
+        };
+        let cache = ResponseCache::in_memory(config).unwrap();
+        let req = request("Explain lifetimes");
+        cache.store(&req, &response("Borrow scopes.")).unwrap();
+
+        assert!(cache.lookup(&req).is_none());
+        assert_eq!(cache.purge_expired().unwrap(), 1);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn semantic_hit_for_near_duplicate_prompt() {
+        let cache = ResponseCache::in_memory(semantic_config()).unwrap();
+        cache
+            .store(
+                &request("explain how rust lifetimes work in structs"),
+                &response("Borrow scopes."),
+            )
+            .unwrap();
+
+        let hit = cache
+            .lookup(&request("Explain how Rust lifetimes work in structs?"))
+            .unwrap();
+        assert!(matches!(hit.kind, CacheHitKind::Semantic { similarity } if similarity > 0.99));
+
+        assert!(cache.lookup(&request("Write a SQL migration")).is_none());
+    }
+
+    #[test]
+    fn semantic_mode_requires_same_context() {
+        let cache = ResponseCache::in_memory(semantic_config()).unwrap();
+        cache
+            .store(&request("explain lifetimes in structs"), &response("x"))
+            .unwrap();
+
+        let mut other_model = request("Explain lifetimes in structs!");
+        other_model.model = "gpt-4o".into();
+        assert!(cache.lookup(&other_model).is_none());
+    }
+
+    #[test]
+    fn semantic_mode_is_opt_in() {
+        let cache = ResponseCache::in_memory(ResponseCacheConfig::default()).unwrap();
+        cache
+            .store(&request("explain lifetimes in structs"), &response("x"))
+            .unwrap();
+        assert!(
+            cache
+                .lookup(&request("Explain lifetimes in structs!"))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn evicts_oldest_beyond_max_entries() {
+        let config = ResponseCacheConfig {
+            max_entries: 2,
+            ..ResponseCacheConfig::default()
+        };
+        let cache = ResponseCache::in_memory(config).unwrap();
+        for prompt in ["a", "b", "c"] {
+            cache.store(&request(prompt), &response(prompt)).unwrap();
+        }
+        assert_eq!(cache.len(), 2);
+    }
+}
@@ -893,6 +893,19 @@ impl CapabilityRouter {
         messages: &[ChatMessage],
         available_models: &[ModelInfo],
         tier_preference: Option<ModelTier>,
+    ) -> RoutingRecommendation {
+        self.recommend_weighted(messages, available_models, tier_preference, &|_| 1.0)
+    }
+
+    /// Like [`recommend`](Self::recommend), but each model's static score is
+    /// multiplied by `weight(model)` before ranking (e.g. live telemetry and
+    /// learned quality).
+    pub fn recommend_weighted(
+        &self,
+        messages: &[ChatMessage],
+        available_models: &[ModelInfo],
+        tier_preference: Option<ModelTier>,
+        weight: &dyn Fn(&ModelInfo) -> f32,
     ) -> RoutingRecommendation {
         let task = classify_task(messages);
 
@@ -903,7 +916,15 @@ impl CapabilityRouter {
             return self.default_recommendation(task);
         }
 
-        let ranked = rank_models_for_task(&task, available_models, tier_preference);
+        let mut ranked = rank_models_for_task(&task, available_models, tier_preference);
+        for (model, score) in &mut ranked {
+            *score *= weight(model);
+        }
+        ranked.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.0.id.cmp(&b.0.id))
+        });
 
         // The top entry is our recommendation.
         let (model, score) = ranked
@@ -6,6 +6,7 @@
 
 use once_cell::sync::Lazy;
 use regex::Regex;
+use hive_shield::DataClassification;
 use serde::{Deserialize, Serialize};
 
 use crate::types::{ChatMessage, MessageRole, ModelTier};
@@ -100,6 +101,13 @@ pub struct ComplexityResult {
 pub struct ClassificationContext {
     pub context_size: Option<u32>,
     pub file_count: Option<u32>,
+    /// Paths of the files in context, matched by routing rules.
+    pub file_paths: Vec<String>,
+    /// Shield classification of the outgoing data, matched by routing rules.
+    pub data_classification: Option<DataClassification>,
+    /// The request comes from interactive chat, so latency matters more
+    /// than usual when choosing a model within the tier.
+    pub interactive: bool,
 }
 
 // ---------------------------------------------------------------------------
@@ -752,6 +760,7 @@ mod tests {
             Some(&ClassificationContext {
                 file_count: Some(50),
                 context_size: Some(100_000),
+                ..Default::default()
             }),
         );
         assert!(result.score >= 0.0 && result.score <= 1.0);
@@ -2,18 +2,31 @@
 //!
 //! Orchestrates the complexity classifier and auto-fallback manager to produce
 //! a final routing decision for each request. Supports both explicit model
-//! selection and automatic tier-based routing.
+//! selection and automatic tier-based routing. User-defined
+//! [`RoutingRule`]s are applied on top of the heuristic decision.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use chrono::{Local, NaiveDateTime};
+use hive_core::routing_rules::{RoutingRule, RuleAction, RuleInput};
 use serde::{Deserialize, Serialize};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
+use crate::cost::calculate_cost;
 use crate::types::{ChatMessage, ModelInfo, ModelTier};
 
-use super::auto_fallback::{AutoFallbackManager, FallbackConfig, FallbackReason, ProviderType};
+use super::auto_fallback::{
+    AutoFallbackManager, FallbackChainEntry, FallbackConfig, FallbackReason, ProviderType,
+};
 use super::capability_router::CapabilityRouter;
-use super::complexity_classifier::{ClassificationContext, ComplexityClassifier, ComplexityResult};
+use super::complexity_classifier::{
+    ClassificationContext, ComplexityClassifier, ComplexityResult, TaskType,
+};
+use super::telemetry::{self, ModelTelemetry, QualitySource};
+
+/// Output tokens assumed when estimating a request's cost for rule matching.
+const ESTIMATED_OUTPUT_TOKENS: usize = 1_000;
 
 // ---------------------------------------------------------------------------
 // Tier Adjuster trait (for learning system integration)
@@ -48,6 +61,33 @@ pub struct RoutingDecision {
     pub reasoning: String,
 }
 
+/// A request that matched a routing rule forbidding its route, when no
+/// configured model satisfies every matched rule. The request must not be
+/// sent.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("Routing rule '{rule}' forbids {model} and no allowed model is configured")]
+pub struct RouteBlocked {
+    /// The rule that forbade the heuristic route.
+    pub rule: String,
+    /// The model the heuristics picked.
+    pub model: String,
+}
+
+/// Result of [`ModelRouter::dry_run`]: what the heuristics picked, what the
+/// routing rules turned that into, and why.
+#[derive(Debug, Clone)]
+pub struct RoutingDryRun {
+    /// The decision before routing rules were applied.
+    pub heuristic: RoutingDecision,
+    /// The final decision, or why the request would be refused.
+    pub decision: Result<RoutingDecision, RouteBlocked>,
+    /// Names of the rules that matched, in rule order.
+    pub matched_rules: Vec<String>,
+    pub task_type: TaskType,
+    /// Estimated cost (USD) of the heuristic choice.
+    pub estimated_cost: f64,
+}
+
 /// Known model-to-provider mappings for explicit model resolution.
 struct ModelMapping {
     prefix: &'static str,
@@ -132,6 +172,9 @@ pub struct ModelRouter {
     classifier: ComplexityClassifier,
     fallback_manager: AutoFallbackManager,
     tier_adjuster: Option<Arc<dyn TierAdjuster>>,
+    rules: Vec<RoutingRule>,
+    telemetry: Arc<ModelTelemetry>,
+    quality_source: Option<Arc<dyn QualitySource>>,
 }
 
 impl Default for ModelRouter {
@@ -147,6 +190,9 @@ impl ModelRouter {
             classifier: ComplexityClassifier::new(),
             fallback_manager: AutoFallbackManager::with_defaults(),
             tier_adjuster: None,
+            rules: Vec::new(),
+            telemetry: Arc::new(ModelTelemetry::new()),
+            quality_source: None,
         }
     }
 
@@ -156,6 +202,9 @@ impl ModelRouter {
             classifier: ComplexityClassifier::new(),
             fallback_manager: AutoFallbackManager::new(fallback_config),
             tier_adjuster: None,
+            rules: Vec::new(),
+            telemetry: Arc::new(ModelTelemetry::new()),
+            quality_source: None,
         }
     }
 
@@ -168,17 +217,86 @@ impl ModelRouter {
         self.tier_adjuster = Some(adjuster);
     }
 
+    /// Set a source of learned per-model answer quality.
+    ///
+    /// Quality is blended with live telemetry when choosing between models
+    /// of the same tier.
+    pub fn set_quality_source(&mut self, source: Arc<dyn QualitySource>) {
+        self.quality_source = Some(source);
+    }
+
+    /// Rolling per-model call telemetry used as a routing input.
+    pub fn telemetry(&self) -> &Arc<ModelTelemetry> {
+        &self.telemetry
+    }
+
+    /// Replace the ordered routing rules.
+    pub fn set_rules(&mut self, rules: Vec<RoutingRule>) {
+        self.rules = rules;
+    }
+
+    /// The configured routing rules, in evaluation order.
+    pub fn rules(&self) -> &[RoutingRule] {
+        &self.rules
+    }
+
     /// Route a request to the best available provider and model.
     ///
     /// If `explicit_model` is provided, the router resolves it directly to a
     /// provider (falling back through the chain if that provider is down).
     /// Otherwise it classifies the request complexity and picks the best
-    /// available provider for the determined tier.
+    /// available provider for the determined tier. Routing rules are applied
+    /// to the result; an explicit model is replaced only if a rule forbids it.
+    /// Fails when a rule forbids the route and no allowed model is available.
     pub fn route(
         &self,
         messages: &[ChatMessage],
         explicit_model: Option<&str>,
         context: Option<&ClassificationContext>,
+    ) -> Result<RoutingDecision, RouteBlocked> {
+        let decision = self.route_heuristic(messages, explicit_model, context);
+        self.apply_rules(
+            decision,
+            messages,
+            explicit_model.is_some(),
+            context,
+            Local::now().naive_local(),
+        )
+        .0
+    }
+
+    /// Show how a prompt would be routed without sending anything.
+    ///
+    /// Uses the same path as automatic routing with capabilities, evaluating
+    /// time-based rules at `now` (local time).
+    pub fn dry_run(
+        &self,
+        messages: &[ChatMessage],
+        available_models: &[ModelInfo],
+        context: Option<&ClassificationContext>,
+        now: NaiveDateTime,
+    ) -> RoutingDryRun {
+        let heuristic =
+            self.route_with_capabilities_heuristic(messages, available_models, None, context);
+        let result = self.classify(messages, context);
+        let estimated_cost = estimate_cost(&heuristic.model_id, &result);
+        let (decision, matched_rules) =
+            self.apply_rules(heuristic.clone(), messages, false, context, now);
+        RoutingDryRun {
+            heuristic,
+            decision,
+            matched_rules,
+            task_type: result.factors.task_type,
+            estimated_cost,
+        }
+    }
+
+    /// Heuristic routing only (no rules).
+    fn route_heuristic(
+        &self,
+        messages: &[ChatMessage],
+        explicit_model: Option<&str>,
+        context: Option<&ClassificationContext>,
     ) -> RoutingDecision {
         // --- Explicit model path ---
         if let Some(model_id) = explicit_model {
@@ -237,22 +355,47 @@ impl ModelRouter {
     /// 3. Merges capability insight with tier preference and provider health
     ///
     /// Falls back to standard [`route()`](Self::route) when `available_models`
-    /// is empty or the user explicitly chose a model.
+    /// is empty or the user explicitly chose a model. Routing rules are
+    /// applied to the result, failing as in [`route()`](Self::route).
     pub fn route_with_capabilities(
         &self,
         messages: &[ChatMessage],
         available_models: &[ModelInfo],
         explicit_model: Option<&str>,
         context: Option<&ClassificationContext>,
+    ) -> Result<RoutingDecision, RouteBlocked> {
+        let decision = self.route_with_capabilities_heuristic(
+            messages,
+            available_models,
+            explicit_model,
+            context,
+        );
+        self.apply_rules(
+            decision,
+            messages,
+            explicit_model.is_some(),
+            context,
+            Local::now().naive_local(),
+        )
+        .0
+    }
+
+    /// Capability-aware heuristic routing only (no rules).
+    fn route_with_capabilities_heuristic(
+        &self,
+        messages: &[ChatMessage],
+        available_models: &[ModelInfo],
+        explicit_model: Option<&str>,
+        context: Option<&ClassificationContext>,
     ) -> RoutingDecision {
         // Explicit model — respect the user's choice.
         if explicit_model.is_some() {
-            return self.route(messages, explicit_model, context);
+            return self.route_heuristic(messages, explicit_model, context);
         }
 
         // No models to rank — fall back to standard routing.
         if available_models.is_empty() {
-            return self.route(messages, None, context);
+            return self.route_heuristic(messages, None, context);
         }
 
         // 1. Classify complexity to get the preferred tier.
@@ -260,8 +403,28 @@ impl ModelRouter {
         let tier = self.adjust_tier_if_needed(&result);
 
         // 2. Use CapabilityRouter to rank models for the detected task.
+        //    Live telemetry and learned quality weight the ranking.
         let cap_router = CapabilityRouter::new();
-        let recommendation = cap_router.recommend(messages, available_models, Some(tier));
+        let interactive = context.is_some_and(|c| c.interactive);
+        let ids: Vec<&str> = available_models.iter().map(|m| m.id.as_str()).collect();
+        let recommendation = match telemetry::signal_scores(
+            &ids,
+            &self.telemetry,
+            self.quality_source.as_deref(),
+            interactive,
+        ) {
+            Some(scores) => {
+                let weights: HashMap<&str, f32> = ids
+                    .iter()
+                    .copied()
+                    .zip(scores.iter().map(|s| 0.5 + *s as f32))
+                    .collect();
+                cap_router.recommend_weighted(messages, available_models, Some(tier), &|m| {
+                    weights.get(m.id.as_str()).copied().unwrap_or(1.0)
+                })
+            }
+            None => cap_router.recommend(messages, available_models, Some(tier)),
+        };
 
         // 3. Check provider health before committing.
         if self.fallback_manager.is_available(recommendation.provider) {
@@ -277,7 +440,185 @@ impl ModelRouter {
         }
 
         // Provider unhealthy — fall back to standard routing.
-        self.route(messages, None, context)
+        self.route_heuristic(messages, None, context)
+    }
+
+    // ------------------------------------------------------------------
+    // Routing rules
+    // ------------------------------------------------------------------
+
+    /// Apply the routing rules to a heuristic decision. Returns the final
+    /// decision (or the refusal) and the names of the rules that matched.
+    fn apply_rules(
+        &self,
+        decision: RoutingDecision,
+        messages: &[ChatMessage],
+        explicit: bool,
+        context: Option<&ClassificationContext>,
+        now: NaiveDateTime,
+    ) -> (Result<RoutingDecision, RouteBlocked>, Vec<String>) {
+        if !self.rules.iter().any(|r| r.enabled) {
+            return (Ok(decision), Vec::new());
+        }
+
+        let result = self.classifier.classify(messages, context);
+        let task_type = task_type_key(result.factors.task_type);
+        let input = RuleInput {
+            task_type: &task_type,
+            tier: tier_key(decision.tier),
+            paths: context.map_or(&[], |c| c.file_paths.as_slice()),
+            classification: context.and_then(|c| c.data_classification.as_ref()),
+            estimated_cost: estimate_cost(&decision.model_id, &result),
+            now,
+        };
+        let policy = RulePolicy::collect(&self.rules, &input);
+        if policy.matched.is_empty() {
+            return (Ok(decision), Vec::new());
+        }
+
+        let decision = self.enforce_rules(decision, &policy, explicit);
+        match &decision {
+            Ok(decision) => debug!(
+                rules = ?policy.matched,
+                model = %decision.model_id,
+                "Routing rules applied"
+            ),
+            Err(blocked) => warn!(
+                rule = %blocked.rule,
+                model = %blocked.model,
+                "No model satisfies the routing rules; refusing the request"
+            ),
+        }
+        (decision, policy.matched)
+    }
+
+    /// Whether the routing rules that match this request allow sending it to
+    /// `model` on `provider`. Used to vet failover targets, which never go
+    /// through [`route()`](Self::route).
+    pub fn allows(
+        &self,
+        messages: &[ChatMessage],
+        provider: ProviderType,
+        model: &str,
+        tier: ModelTier,
+        context: Option<&ClassificationContext>,
+    ) -> bool {
+        if !self.rules.iter().any(|r| r.enabled) {
+            return true;
+        }
+
+        let result = self.classifier.classify(messages, context);
+        let task_type = task_type_key(result.factors.task_type);
+        let input = RuleInput {
+            task_type: &task_type,
+            tier: tier_key(tier),
+            paths: context.map_or(&[], |c| c.file_paths.as_slice()),
+            classification: context.and_then(|c| c.data_classification.as_ref()),
+            estimated_cost: estimate_cost(model, &result),
+            now: Local::now().naive_local(),
+        };
+        RulePolicy::collect(&self.rules, &input)
+            .violation(provider, tier)
+            .is_none()
+    }
+
+    /// Turn a decision into one that satisfies every matched rule, or refuse
+    /// it when no available model does.
+    fn enforce_rules(
+        &self,
+        decision: RoutingDecision,
+        policy: &RulePolicy<'_>,
+        explicit: bool,
+    ) -> Result<RoutingDecision, RouteBlocked> {
+        let mut notes = Vec::new();
+
+        // Pins only replace automatic choices, and never override a
+        // restriction from another rule.
+        if !explicit && let Some((rule, model)) = policy.pin {
+            let provider = resolve_provider(model);
+            let tier = infer_tier(model);
+            match policy.violation(provider, tier) {
+                None => {
+                    return Ok(RoutingDecision {
+                        provider,
+                        model_id: model.to_string(),
+                        tier,
+                        reasoning: format!(
+                            "Rule '{rule}': pinned {model} via {provider} | {}",
+                            decision.reasoning
+                        ),
+                    });
+                }
+                Some(blocker) => {
+                    notes.push(format!("pin from '{rule}' conflicts with '{blocker}'"));
+                }
+            }
+        }
+
+        let Some(blocker) = policy.violation(decision.provider, decision.tier) else {
+            notes.insert(0, format!("Rules matched: {}", quoted(&policy.matched)));
+            return Ok(RoutingDecision {
+                reasoning: format!("{} | {}", notes.join("; "), decision.reasoning),
+                ..decision
+            });
+        };
+
+        let target = match policy.cap {
+            Some((cap, _)) if tier_rank(cap) < tier_rank(decision.tier) => cap,
+            _ => decision.tier,
+        };
+        let (provider, model_id, tier) = match self.best_allowed_entry(policy, target) {
+            Some(entry) => (entry.provider, entry.model.clone(), entry.cost_tier),
+            None => {
+                let (model, provider) = default_for_tier(ModelTier::Free);
+                if policy.violation(provider, ModelTier::Free).is_some()
+                    || !self.fallback_manager.is_available(provider)
+                {
+                    return Err(RouteBlocked {
+                        rule: blocker.to_string(),
+                        model: decision.model_id,
+                    });
+                }
+                (provider, model.to_string(), ModelTier::Free)
+            }
+        };
+
+        notes.insert(
+            0,
+            format!(
+                "Rule '{blocker}': rerouted {} ({}) to {model_id} ({provider})",
+                decision.model_id, decision.provider
+            ),
+        );
+        Ok(RoutingDecision {
+            provider,
+            model_id,
+            tier,
+            reasoning: format!("{} | {}", notes.join("; "), decision.reasoning),
+        })
+    }
+
+    /// The fallback-chain entry closest to `target` tier that every matched
+    /// rule allows. Prefers the same tier, then cheaper tiers, then pricier.
+    fn best_allowed_entry(
+        &self,
+        policy: &RulePolicy<'_>,
+        target: ModelTier,
+    ) -> Option<&FallbackChainEntry> {
+        self.fallback_manager
+            .fallback_chain()
+            .iter()
+            .filter(|e| policy.violation(e.provider, e.cost_tier).is_none())
+            .filter(|e| self.fallback_manager.is_available(e.provider))
+            .min_by_key(|e| {
+                let (rank, want) = (tier_rank(e.cost_tier), tier_rank(target));
+                let distance = if rank <= want {
+                    want - rank
+                } else {
+                    4 + rank - want
+                };
+                (distance, e.priority)
+            })
     }
 
     // ------------------------------------------------------------------
@@ -381,6 +722,54 @@ impl ModelRouter {
         }
     }
 
+    /// Pick the best available chain entry in `tier` by live telemetry and
+    /// learned quality. Returns `None` when there is nothing to choose
+    /// between or no data to choose with.
+    fn route_by_signals(
+        &self,
+        tier: ModelTier,
+        interactive: bool,
+        reasoning: &str,
+    ) -> Option<RoutingDecision> {
+        let mut candidates: Vec<&FallbackChainEntry> = self
+            .fallback_manager
+            .fallback_chain()
+            .iter()
+            .filter(|e| e.cost_tier == tier && self.fallback_manager.is_available(e.provider))
+            .collect();
+        if candidates.len() < 2 {
+            return None;
+        }
+        candidates.sort_by_key(|e| e.priority);
+
+        let models: Vec<&str> = candidates.iter().map(|e| e.model.as_str()).collect();
+        let quality = self.quality_source.as_deref();
+        let scores = telemetry::signal_scores(&models, &self.telemetry, quality, interactive)?;
+
+        // Highest score wins; ties keep chain priority order.
+        let (best, _) = scores
+            .iter()
+            .enumerate()
+            .fold((0, f64::MIN), |acc, (i, &s)| if s > acc.1 { (i, s) } else { acc });
+        let entry = candidates[best];
+        let signals = telemetry::describe_signals(
+            self.telemetry.stats(&entry.model).as_ref(),
+            quality.and_then(|q| q.model_quality(&entry.model)),
+        );
+        let goal = if interactive { "latency" } else { "quality" };
+
+        debug!(model = %entry.model, goal, "Auto-routing: ranked by telemetry");
+        Some(RoutingDecision {
+            provider: entry.provider,
+            model_id: entry.model.clone(),
+            tier,
+            reasoning: format!(
+                "Auto-routed: {} | Model: {} ({}) | ranked for {}: {}",
+                reasoning, entry.model, entry.provider, goal, signals
+            ),
+        })
+    }
+
     /// Automatic routing based on complexity classification.
     fn route_auto(
         &self,
@@ -399,6 +788,11 @@ impl ModelRouter {
             "Auto-routing: classified request"
         );
 
+        let interactive = context.is_some_and(|c| c.interactive);
+        if let Some(decision) = self.route_by_signals(tier, interactive, &result.reasoning) {
+            return decision;
+        }
+
         let chain = self.fallback_manager.get_fallback_chain(tier);
 
         // Find the best available entry from the fallback chain that matches
@@ -453,6 +847,183 @@ impl ModelRouter {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Rule policy
+// ---------------------------------------------------------------------------
+
+/// The combined effect of the rules that matched one request.
+struct RulePolicy<'a> {
+    matched: Vec<String>,
+    /// First matching pin: `(rule, model)`.
+    pin: Option<(&'a str, &'a str)>,
+    forbidden: Vec<(ProviderType, &'a str)>,
+    local_only: Option<&'a str>,
+    /// Lowest tier cap: `(tier, rule)`.
+    cap: Option<(ModelTier, &'a str)>,
+}
+
+impl<'a> RulePolicy<'a> {
+    fn collect(rules: &'a [RoutingRule], input: &RuleInput<'_>) -> Self {
+        let mut policy = Self {
+            matched: Vec::new(),
+            pin: None,
+            forbidden: Vec::new(),
+            local_only: None,
+            cap: None,
+        };
+        for rule in rules.iter().filter(|r| r.matches(input)) {
+            let name = rule.name.as_str();
+            policy.matched.push(rule.name.clone());
+            match &rule.action {
+                RuleAction::PinModel { model } => {
+                    policy.pin.get_or_insert((name, model.as_str()));
+                }
+                RuleAction::ForbidProviders { providers } => {
+                    for selector in providers {
+                        let resolved = providers_for_selector(selector);
+                        if resolved.is_empty() {
+                            warn!(rule = name, selector = %selector, "Unknown provider in routing rule");
+                        }
+                        policy
+                            .forbidden
+                            .extend(resolved.into_iter().map(|p| (p, name)));
+                    }
+                }
+                RuleAction::LocalOnly => {
+                    policy.local_only.get_or_insert(name);
+                }
+                RuleAction::CapTier { tier } => match parse_tier(tier) {
+                    Some(tier) => {
+                        if policy
+                            .cap
+                            .is_none_or(|(cap, _)| tier_rank(tier) < tier_rank(cap))
+                        {
+                            policy.cap = Some((tier, name));
+                        }
+                    }
+                    None => warn!(rule = name, tier = %tier, "Unknown tier in routing rule"),
+                },
+            }
+        }
+        policy
+    }
+
+    /// The rule that forbids `provider` at `tier`, if any.
+    fn violation(&self, provider: ProviderType, tier: ModelTier) -> Option<&'a str> {
+        if let Some(rule) = self.local_only
+            && !is_local_provider(provider)
+        {
+            return Some(rule);
+        }
+        if let Some((_, rule)) = self.forbidden.iter().find(|(p, _)| *p == provider) {
+            return Some(rule);
+        }
+        match self.cap {
+            Some((cap, rule)) if tier_rank(tier) > tier_rank(cap) => Some(rule),
+            _ => None,
+        }
+    }
+}
+
+const ALL_PROVIDERS: [ProviderType; 11] = [
+    ProviderType::Anthropic,
+    ProviderType::OpenAI,
+    ProviderType::OpenRouter,
+    ProviderType::Google,
+    ProviderType::Groq,
+    ProviderType::LiteLLM,
+    ProviderType::HuggingFace,
+    ProviderType::Ollama,
+    ProviderType::LMStudio,
+    ProviderType::GenericLocal,
+    ProviderType::XAI,
+];
+
+/// Providers that run on the user's machine.
+fn is_local_provider(provider: ProviderType) -> bool {
+    matches!(
+        provider,
+        ProviderType::Ollama | ProviderType::LMStudio | ProviderType::GenericLocal
+    )
+}
+
+/// Resolve a provider name from a rule (`"openai"`, `"lm-studio"`) or a
+/// group (`"cloud"`, `"local"`).
+fn providers_for_selector(selector: &str) -> Vec<ProviderType> {
+    let key = normalize_key(selector);
+    match key.as_str() {
+        "cloud" => ALL_PROVIDERS
+            .into_iter()
+            .filter(|p| !is_local_provider(*p))
+            .collect(),
+        "local" => ALL_PROVIDERS
+            .into_iter()
+            .filter(|p| is_local_provider(*p))
+            .collect(),
+        _ => ALL_PROVIDERS
+            .into_iter()
+            .filter(|p| normalize_key(&p.to_string()) == key)
+            .collect(),
+    }
+}
+
+fn normalize_key(s: &str) -> String {
+    s.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn parse_tier(tier: &str) -> Option<ModelTier> {
+    match normalize_key(tier).as_str() {
+        "free" => Some(ModelTier::Free),
+        "budget" => Some(ModelTier::Budget),
+        "mid" | "standard" => Some(ModelTier::Mid),
+        "premium" | "enterprise" => Some(ModelTier::Premium),
+        _ => None,
+    }
+}
+
+fn tier_key(tier: ModelTier) -> &'static str {
+    match tier {
+        ModelTier::Free => "free",
+        ModelTier::Budget => "budget",
+        ModelTier::Mid => "mid",
+        ModelTier::Premium => "premium",
+    }
+}
+
+fn tier_rank(tier: ModelTier) -> u8 {
+    match tier {
+        ModelTier::Free => 0,
+        ModelTier::Budget => 1,
+        ModelTier::Mid => 2,
+        ModelTier::Premium => 3,
+    }
+}
+
+/// The task type's config name, e.g. `"code_gen"`.
+fn task_type_key(task_type: TaskType) -> String {
+    serde_json::to_value(task_type)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Rough cost of sending the classified request to `model_id`.
+fn estimate_cost(model_id: &str, result: &ComplexityResult) -> f64 {
+    let input_tokens = result.factors.token_count.max(result.factors.context_size) as usize;
+    calculate_cost(model_id, input_tokens, ESTIMATED_OUTPUT_TOKENS).total_cost
+}
+
+fn quoted(names: &[String]) -> String {
+    names
+        .iter()
+        .map(|n| format!("'{n}'"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 // ---------------------------------------------------------------------------
 // Resolution helpers
 // ---------------------------------------------------------------------------
@@ -548,6 +1119,8 @@ mod tests {
     use super::*;
     use crate::types::{MessageRole, ModelCapabilities, ProviderType as TypesProviderType};
     use chrono::Utc;
+    use hive_core::routing_rules::RuleConditions;
+    use hive_shield::DataClassification;
 
     fn user_msg(content: &str) -> ChatMessage {
         ChatMessage {
@@ -576,7 +1149,9 @@ mod tests {
     #[test]
     fn explicit_model_routes_directly() {
         let router = setup_router();
-        let decision = router.route(&[user_msg("hello")], Some("claude-opus-4-20250514"), None);
+        let decision = router
+            .route(&[user_msg("hello")], Some("claude-opus-4-20250514"), None)
+            .unwrap();
         assert_eq!(decision.provider, ProviderType::Anthropic);
         assert_eq!(decision.model_id, "claude-opus-4-20250514");
     }
@@ -584,27 +1159,33 @@ mod tests {
     #[test]
     fn explicit_openrouter_model() {
         let router = setup_router();
-        let decision = router.route(&[user_msg("hello")], Some("deepseek/deepseek-chat"), None);
+        let decision = router
+            .route(&[user_msg("hello")], Some("deepseek/deepseek-chat"), None)
+            .unwrap();
         assert_eq!(decision.provider, ProviderType::OpenRouter);
     }
 
     #[test]
     fn auto_route_simple_question_budget() {
         let router = setup_router();
-        let decision = router.route(&[user_msg("What is Rust?")], None, None);
+        let decision = router
+            .route(&[user_msg("What is Rust?")], None, None)
+            .unwrap();
         assert_eq!(decision.tier, ModelTier::Budget);
     }
 
     #[test]
     fn auto_route_architecture_premium() {
         let router = setup_router();
-        let decision = router.route(
-            &[user_msg(
-                "Design the system architecture for our new platform",
-            )],
-            None,
-            None,
-        );
+        let decision = router
+            .route(
+                &[user_msg(
+                    "Design the system architecture for our new platform",
+                )],
+                None,
+                None,
+            )
+            .unwrap();
         assert_eq!(decision.tier, ModelTier::Premium);
     }
 
@@ -688,7 +1269,9 @@ mod tests {
         router.set_tier_adjuster(Arc::new(TestAdjuster));
 
         // "What is Rust?" normally classifies as Budget, but our adjuster upgrades it
-        let decision = router.route(&[user_msg("What is Rust?")], None, None);
+        let decision = router
+            .route(&[user_msg("What is Rust?")], None, None)
+            .unwrap();
         assert_eq!(decision.tier, ModelTier::Premium);
     }
 
@@ -704,7 +1287,9 @@ mod tests {
         let mut router = setup_router();
         router.set_tier_adjuster(Arc::new(NoOpAdjuster));
 
-        let decision = router.route(&[user_msg("What is Rust?")], None, None);
+        let decision = router
+            .route(&[user_msg("What is Rust?")], None, None)
+            .unwrap();
         assert_eq!(decision.tier, ModelTier::Budget);
     }
 
@@ -716,7 +1301,9 @@ mod tests {
             .fallback_manager()
             .set_available(ProviderType::Anthropic, false);
 
-        let decision = router.route(&[user_msg("hello")], Some("claude-opus-4-20250514"), None);
+        let decision = router
+            .route(&[user_msg("hello")], Some("claude-opus-4-20250514"), None)
+            .unwrap();
         // Should have fallen back — either via OpenRouter proxy or another provider
         assert_ne!(decision.provider, ProviderType::Anthropic);
     }
@@ -796,7 +1383,7 @@ mod tests {
             &models,
             None,
             None,
-        );
+        ).unwrap();
 
         // Capability-aware routing should include "capability:" in the reasoning
         assert!(
@@ -813,12 +1400,14 @@ mod tests {
         let router = setup_router();
         let models = sample_models();
 
-        let decision = router.route_with_capabilities(
-            &[user_msg("Solve this equation: x^2 + 3x - 10 = 0")],
-            &models,
-            Some("claude-sonnet-4-20250514"),
-            None,
-        );
+        let decision = router
+            .route_with_capabilities(
+                &[user_msg("Solve this equation: x^2 + 3x - 10 = 0")],
+                &models,
+                Some("claude-sonnet-4-20250514"),
+                None,
+            )
+            .unwrap();
 
         // Explicit model should be respected regardless of capability scores.
         assert_eq!(decision.model_id, "claude-sonnet-4-20250514");
@@ -829,15 +1418,298 @@ mod tests {
     fn route_with_capabilities_empty_models_falls_back() {
         let router = setup_router();
 
-        let decision = router.route_with_capabilities(
-            &[user_msg("What is Rust?")],
-            &[],
-            None,
-            None,
-        );
+        let decision = router
+            .route_with_capabilities(&[user_msg("What is Rust?")], &[], None, None)
+            .unwrap();
 
         // With empty models, should fall back to standard route() behaviour
         // which classifies "What is Rust?" as Budget tier.
         assert_eq!(decision.tier, ModelTier::Budget);
     }
+
+    // ------------------------------------------------------------------
+    // Routing rule tests
+    // ------------------------------------------------------------------
+
+    fn rule(name: &str, when: RuleConditions, action: RuleAction) -> RoutingRule {
+        RoutingRule {
+            name: name.to_string(),
+            enabled: true,
+            when,
+            action,
+        }
+    }
+
+    fn wednesday_noon() -> NaiveDateTime {
+        chrono::NaiveDate::from_ymd_opt(2026, 3, 4)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn pin_rule_matches_task_and_paths() {
+        let mut router = setup_router();
+        router.set_rules(vec![rule(
+            "infra-security",
+            RuleConditions {
+                task_types: vec!["security".into()],
+                paths: vec!["infra/".into()],
+                ..Default::default()
+            },
+            RuleAction::PinModel {
+                model: "gpt-4o".into(),
+            },
+        )]);
+        let messages = [user_msg("Audit this terraform for security holes")];
+        let ctx = ClassificationContext {
+            file_paths: vec!["repo/infra/main.tf".into()],
+            ..Default::default()
+        };
+
+        let decision = router.route(&messages, None, Some(&ctx)).unwrap();
+        assert_eq!(decision.model_id, "gpt-4o");
+        assert_eq!(decision.provider, ProviderType::OpenAI);
+        assert!(decision.reasoning.contains("infra-security"));
+
+        // Without the path the rule does not fire.
+        let decision = router.route(&messages, None, None).unwrap();
+        assert_ne!(decision.model_id, "gpt-4o");
+    }
+
+    #[test]
+    fn pin_does_not_override_explicit_model() {
+        let mut router = setup_router();
+        router.set_rules(vec![rule(
+            "always-gpt",
+            RuleConditions::default(),
+            RuleAction::PinModel {
+                model: "gpt-4o".into(),
+            },
+        )]);
+        let decision = router
+            .route(&[user_msg("hello")], Some("claude-sonnet-4-20250514"), None)
+            .unwrap();
+        assert_eq!(decision.model_id, "claude-sonnet-4-20250514");
+    }
+
+    #[test]
+    fn confidential_data_stays_local_even_for_explicit_model() {
+        let mut router = setup_router();
+        router
+            .fallback_manager()
+            .set_available(ProviderType::Ollama, true);
+        router.set_rules(vec![rule(
+            "no-confidential-cloud",
+            RuleConditions {
+                min_classification: Some(DataClassification::Confidential),
+                ..Default::default()
+            },
+            RuleAction::ForbidProviders {
+                providers: vec!["cloud".into()],
+            },
+        )]);
+        let ctx = ClassificationContext {
+            data_classification: Some(DataClassification::Confidential),
+            ..Default::default()
+        };
+
+        let decision = router
+            .route(
+                &[user_msg("hello")],
+                Some("claude-opus-4-20250514"),
+                Some(&ctx),
+            )
+            .unwrap();
+        assert_eq!(decision.provider, ProviderType::Ollama);
+        assert!(decision.reasoning.contains("Rule 'no-confidential-cloud'"));
+
+        // Internal data is unaffected.
+        let ctx = ClassificationContext {
+            data_classification: Some(DataClassification::Internal),
+            ..Default::default()
+        };
+        let decision = router
+            .route(
+                &[user_msg("hello")],
+                Some("claude-opus-4-20250514"),
+                Some(&ctx),
+            )
+            .unwrap();
+        assert_eq!(decision.provider, ProviderType::Anthropic);
+    }
+
+    #[test]
+    fn cap_tier_reroutes_to_cheaper_model() {
+        let mut router = setup_router();
+        router.set_rules(vec![rule(
+            "no-premium",
+            RuleConditions::default(),
+            RuleAction::CapTier { tier: "mid".into() },
+        )]);
+        let decision = router
+            .route(
+                &[user_msg(
+                    "Design the system architecture for our new platform",
+                )],
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(decision.tier, ModelTier::Mid);
+        assert_eq!(decision.model_id, "claude-sonnet-4-20250514");
+        assert!(decision.reasoning.contains("no-premium"));
+    }
+
+    #[test]
+    fn pin_conflicting_with_restriction_is_ignored() {
+        let mut router = setup_router();
+        router.set_rules(vec![
+            rule(
+                "pin-opus",
+                RuleConditions::default(),
+                RuleAction::PinModel {
+                    model: "claude-opus-4-20250514".into(),
+                },
+            ),
+            rule(
+                "no-anthropic",
+                RuleConditions::default(),
+                RuleAction::ForbidProviders {
+                    providers: vec!["Anthropic".into()],
+                },
+            ),
+        ]);
+        let decision = router
+            .route(&[user_msg("What is Rust?")], None, None)
+            .unwrap();
+        assert_ne!(decision.provider, ProviderType::Anthropic);
+        assert!(decision.reasoning.contains("conflicts with 'no-anthropic'"));
+    }
+
+    #[test]
+    fn rule_without_allowed_model_refuses_request() {
+        // No local provider is available, so a local-only rule cannot be met.
+        let mut router = setup_router();
+        router.set_rules(vec![rule(
+            "local-only",
+            RuleConditions::default(),
+            RuleAction::LocalOnly,
+        )]);
+        let blocked = router
+            .route(&[user_msg("hello")], Some("claude-opus-4-20250514"), None)
+            .unwrap_err();
+        assert_eq!(blocked.rule, "local-only");
+        assert_eq!(blocked.model, "claude-opus-4-20250514");
+
+        let dry_run = router.dry_run(&[user_msg("hello")], &[], None, wednesday_noon());
+        assert!(dry_run.decision.is_err());
+    }
+
+    #[test]
+    fn dry_run_reports_matched_rules_and_heuristic() {
+        let mut router = setup_router();
+        router
+            .fallback_manager()
+            .set_available(ProviderType::Ollama, true);
+        router.set_rules(vec![
+            rule(
+                "weekend-debugging",
+                RuleConditions {
+                    task_types: vec!["debugging".into()],
+                    days: vec!["weekend".into()],
+                    ..Default::default()
+                },
+                RuleAction::LocalOnly,
+            ),
+            rule(
+                "disabled",
+                RuleConditions::default(),
+                RuleAction::CapTier {
+                    tier: "free".into(),
+                },
+            ),
+        ]);
+        router.rules[1].enabled = false;
+        let messages = [user_msg("Debug this crash: stack trace attached")];
+
+        let weekday = router.dry_run(&messages, &[], None, wednesday_noon());
+        assert!(weekday.matched_rules.is_empty());
+        assert_eq!(weekday.decision.unwrap().model_id, weekday.heuristic.model_id);
+        assert_eq!(weekday.task_type, TaskType::Debugging);
+
+        let saturday = wednesday_noon() + chrono::Duration::days(3);
+        let weekend = router.dry_run(&messages, &[], None, saturday);
+        assert_eq!(weekend.matched_rules, ["weekend-debugging"]);
+        assert_eq!(weekend.decision.unwrap().provider, ProviderType::Ollama);
+        assert_ne!(weekend.heuristic.provider, ProviderType::Ollama);
+    }
+
+    #[test]
+    fn no_rules_leaves_reasoning_untouched() {
+        let router = setup_router();
+        let decision = router
+            .route(&[user_msg("What is Rust?")], None, None)
+            .unwrap();
+        assert!(!decision.reasoning.contains("Rule"));
+    }
+
+    // -- Telemetry ---------------------------------------------------------
+
+    fn record_calls(router: &ModelRouter, model: &str, ttft_ms: u64, latency_ms: u64) {
+        for _ in 0..telemetry::MIN_SAMPLES {
+            router.telemetry().record(telemetry::CallSample {
+                model_id: model.into(),
+                provider: "test".into(),
+                success: true,
+                latency_ms,
+                ttft_ms: Some(ttft_ms),
+                output_tokens: 200,
+                timestamp: Utc::now(),
+            });
+        }
+    }
+
+    #[test]
+    fn interactive_chat_prefers_fastest_model_in_tier() {
+        let router = setup_router();
+        let messages = [user_msg("What is Rust?")];
+        let interactive = ClassificationContext {
+            interactive: true,
+            ..Default::default()
+        };
+        let before = router.route(&messages, None, Some(&interactive)).unwrap();
+        assert_ne!(before.model_id, "deepseek/deepseek-chat");
+
+        record_calls(&router, "claude-haiku-4-5-20251001", 2_000, 12_000);
+        record_calls(&router, "deepseek/deepseek-chat", 200, 1_200);
+
+        let after = router.route(&messages, None, Some(&interactive)).unwrap();
+        assert_eq!(after.tier, ModelTier::Budget);
+        assert_eq!(after.model_id, "deepseek/deepseek-chat");
+        assert!(after.reasoning.contains("ranked for latency"));
+    }
+
+    #[test]
+    fn unreliable_model_loses_to_steady_one() {
+        let router = setup_router();
+        record_calls(&router, "claude-haiku-4-5-20251001", 400, 2_000);
+        for _ in 0..telemetry::MIN_SAMPLES {
+            router.telemetry().record(telemetry::CallSample {
+                model_id: "deepseek/deepseek-chat".into(),
+                provider: "test".into(),
+                success: false,
+                latency_ms: 100,
+                ttft_ms: None,
+                output_tokens: 0,
+                timestamp: Utc::now(),
+            });
+        }
+
+        let decision = router
+            .route(&[user_msg("What is Rust?")], None, None)
+            .unwrap();
+        assert_eq!(decision.model_id, "claude-haiku-4-5-20251001");
+        assert!(decision.reasoning.contains("ranked for quality"));
+    }
 }
@@ -0,0 +1,571 @@
+//! Live per-model latency and reliability telemetry.
+//!
+//! Every call made through [`AiService`](crate::AiService) is recorded as a
+//! [`CallSample`]: end-to-end latency, time to first token, output tokens and
+//! whether it succeeded. [`ModelTelemetry`] keeps a rolling window per model
+//! and summarises it as [`ModelStats`] (p50/p95 latency, TTFT, tokens/sec,
+//! error rate). The router blends these with learned answer quality from a
+//! [`QualitySource`] to rank models inside the tier it picked.
+//!
+//! Samples are forwarded to an optional [`TelemetrySink`] for persistence
+//! (implemented by `hive_learn`), and can be preloaded on startup.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::providers::{AiProvider, ProviderError};
+use crate::types::{ChatRequest, ChatResponse, ModelInfo, ProviderType, StreamChunk};
+
+/// Samples kept per model.
+pub const TELEMETRY_WINDOW: usize = 200;
+
+/// Samples a model needs before its telemetry influences routing.
+pub const MIN_SAMPLES: usize = 5;
+
+// ---------------------------------------------------------------------------
+// Samples and stats
+// ---------------------------------------------------------------------------
+
+/// One completed (or failed) provider call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallSample {
+    pub model_id: String,
+    pub provider: String,
+    pub success: bool,
+    /// Request start to last byte.
+    pub latency_ms: u64,
+    /// Request start to first streamed token; `None` for non-streaming calls.
+    pub ttft_ms: Option<u64>,
+    pub output_tokens: u32,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl CallSample {
+    /// Generation throughput, measured after the first token when known.
+    pub fn tokens_per_sec(&self) -> Option<f64> {
+        let generation_ms = self.latency_ms.saturating_sub(self.ttft_ms.unwrap_or(0));
+        (self.success && self.output_tokens > 0 && generation_ms > 0)
+            .then(|| f64::from(self.output_tokens) * 1000.0 / generation_ms as f64)
+    }
+}
+
+/// Rolling summary of a model's recent calls.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelStats {
+    pub samples: usize,
+    /// Fraction of calls that failed, 0.0–1.0.
+    pub error_rate: f64,
+    pub latency_p50_ms: u64,
+    pub latency_p95_ms: u64,
+    /// Median time to first token over streaming calls.
+    pub ttft_p50_ms: Option<u64>,
+    /// Median generation throughput over successful calls.
+    pub tokens_per_sec: Option<f64>,
+}
+
+impl ModelStats {
+    fn from_samples<'a>(samples: impl Iterator<Item = &'a CallSample>) -> Option<Self> {
+        let samples: Vec<&CallSample> = samples.collect();
+        if samples.is_empty() {
+            return None;
+        }
+
+        let failures = samples.iter().filter(|s| !s.success).count();
+        let mut latencies: Vec<u64> = samples
+            .iter()
+            .filter(|s| s.success)
+            .map(|s| s.latency_ms)
+            .collect();
+        let mut ttfts: Vec<u64> = samples.iter().filter_map(|s| s.ttft_ms).collect();
+        let mut throughput: Vec<f64> = samples.iter().filter_map(|s| s.tokens_per_sec()).collect();
+        latencies.sort_unstable();
+        ttfts.sort_unstable();
+        throughput.sort_by(f64::total_cmp);
+
+        Some(Self {
+            samples: samples.len(),
+            error_rate: failures as f64 / samples.len() as f64,
+            latency_p50_ms: percentile(&latencies, 0.50).unwrap_or(0),
+            latency_p95_ms: percentile(&latencies, 0.95).unwrap_or(0),
+            ttft_p50_ms: percentile(&ttfts, 0.50),
+            tokens_per_sec: percentile(&throughput, 0.50),
+        })
+    }
+}
+
+/// Nearest-rank percentile of an ascending slice.
+fn percentile<T: Copy>(sorted: &[T], p: f64) -> Option<T> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+// ---------------------------------------------------------------------------
+// Extension points
+// ---------------------------------------------------------------------------
+
+/// Persists call samples (e.g. `hive_learn` storage).
+pub trait TelemetrySink: Send + Sync {
+    fn record_call(&self, sample: &CallSample);
+}
+
+/// Learned answer quality per model (e.g. `OutcomeTracker::model_quality`).
+pub trait QualitySource: Send + Sync {
+    /// Quality in 0.0–1.0, or `None` when there is not enough data.
+    fn model_quality(&self, model_id: &str) -> Option<f64>;
+}
+
+// ---------------------------------------------------------------------------
+// ModelTelemetry
+// ---------------------------------------------------------------------------
+
+/// Thread-safe rolling window of call samples per model.
+#[derive(Default)]
+pub struct ModelTelemetry {
+    samples: RwLock<HashMap<String, VecDeque<CallSample>>>,
+    sink: RwLock<Option<Arc<dyn TelemetrySink>>>,
+}
+
+impl ModelTelemetry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forward every recorded sample to `sink`.
+    pub fn set_sink(&self, sink: Arc<dyn TelemetrySink>) {
+        *self.sink.write() = Some(sink);
+    }
+
+    /// Record a call and forward it to the sink.
+    pub fn record(&self, sample: CallSample) {
+        if let Some(sink) = self.sink.read().as_ref() {
+            sink.record_call(&sample);
+        }
+        self.push(sample);
+    }
+
+    /// Load previously persisted samples (oldest first) without re-sinking.
+    pub fn preload(&self, samples: impl IntoIterator<Item = CallSample>) {
+        for sample in samples {
+            self.push(sample);
+        }
+    }
+
+    fn push(&self, sample: CallSample) {
+        let mut map = self.samples.write();
+        let window = map.entry(sample.model_id.clone()).or_default();
+        if window.len() == TELEMETRY_WINDOW {
+            window.pop_front();
+        }
+        window.push_back(sample);
+    }
+
+    /// Summary of a model's recent calls.
+    pub fn stats(&self, model_id: &str) -> Option<ModelStats> {
+        self.samples
+            .read()
+            .get(model_id)
+            .and_then(|w| ModelStats::from_samples(w.iter()))
+    }
+
+    /// Summaries for every model with samples, sorted by model ID.
+    pub fn all_stats(&self) -> Vec<(String, ModelStats)> {
+        let map = self.samples.read();
+        let mut all: Vec<(String, ModelStats)> = map
+            .iter()
+            .filter_map(|(id, w)| ModelStats::from_samples(w.iter()).map(|s| (id.clone(), s)))
+            .collect();
+        all.sort_by(|a, b| a.0.cmp(&b.0));
+        all
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.read().is_empty()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Ranking
+// ---------------------------------------------------------------------------
+
+/// Score each candidate model in 0.0–1.0 from telemetry and learned quality.
+///
+/// Reliability scales the whole score. The rest blends quality with speed
+/// (throughput and time to first token, relative to the fastest candidate);
+/// `prefer_fast` weights speed more heavily. Models without data get neutral
+/// values. Returns `None` when no candidate has enough data to improve on
+/// the static ordering.
+pub fn signal_scores(
+    models: &[&str],
+    telemetry: &ModelTelemetry,
+    quality: Option<&dyn QualitySource>,
+    prefer_fast: bool,
+) -> Option<Vec<f64>> {
+    let stats: Vec<Option<ModelStats>> = models
+        .iter()
+        .map(|m| telemetry.stats(m).filter(|s| s.samples >= MIN_SAMPLES))
+        .collect();
+    let learned: Vec<Option<f64>> = models
+        .iter()
+        .map(|m| quality.and_then(|q| q.model_quality(m)))
+        .collect();
+    if stats.iter().all(Option::is_none) && learned.iter().all(Option::is_none) {
+        return None;
+    }
+
+    let best_tps = stats
+        .iter()
+        .flatten()
+        .filter_map(|s| s.tokens_per_sec)
+        .fold(0.0, f64::max);
+    let best_ttft = stats
+        .iter()
+        .flatten()
+        .filter_map(|s| s.ttft_p50_ms)
+        .min()
+        .unwrap_or(0);
+    let speed_weight = if prefer_fast { 0.6 } else { 0.2 };
+
+    let scores = stats
+        .iter()
+        .zip(&learned)
+        .map(|(stats, learned)| {
+            let reliability = stats.as_ref().map_or(0.95, |s| 1.0 - s.error_rate);
+            let throughput = stats
+                .as_ref()
+                .and_then(|s| s.tokens_per_sec)
+                .filter(|_| best_tps > 0.0)
+                .map_or(0.5, |tps| tps / best_tps);
+            let responsiveness = stats
+                .as_ref()
+                .and_then(|s| s.ttft_p50_ms)
+                .map_or(0.5, |ttft| {
+                    (best_ttft.max(1) as f64 / ttft.max(1) as f64).min(1.0)
+                });
+            let speed = (throughput + responsiveness) / 2.0;
+            let quality = learned.unwrap_or(0.5).clamp(0.0, 1.0);
+            reliability * ((1.0 - speed_weight) * quality + speed_weight * speed)
+        })
+        .collect();
+    Some(scores)
+}
+
+/// Short human-readable summary for routing reasoning strings.
+pub fn describe_signals(stats: Option<&ModelStats>, quality: Option<f64>) -> String {
+    let mut parts = Vec::new();
+    if let Some(s) = stats {
+        parts.push(format!("p50 {}ms", s.latency_p50_ms));
+        if let Some(ttft) = s.ttft_p50_ms {
+            parts.push(format!("TTFT {ttft}ms"));
+        }
+        if let Some(tps) = s.tokens_per_sec {
+            parts.push(format!("{tps:.0} tok/s"));
+        }
+        if s.error_rate > 0.0 {
+            parts.push(format!("{:.0}% errors", s.error_rate * 100.0));
+        }
+    }
+    if let Some(q) = quality {
+        parts.push(format!("quality {q:.2}"));
+    }
+    if parts.is_empty() {
+        "no telemetry".to_string()
+    } else {
+        parts.join(", ")
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MeteredProvider
+// ---------------------------------------------------------------------------
+
+/// Provider wrapper that records a [`CallSample`] for every call.
+pub struct MeteredProvider {
+    inner: Arc<dyn AiProvider>,
+    telemetry: Arc<ModelTelemetry>,
+}
+
+impl MeteredProvider {
+    pub fn new(inner: Arc<dyn AiProvider>, telemetry: Arc<ModelTelemetry>) -> Self {
+        Self { inner, telemetry }
+    }
+
+    fn sample(&self, model: &str, start: Instant) -> CallSample {
+        CallSample {
+            model_id: model.to_string(),
+            provider: self.inner.provider_type().to_string(),
+            success: false,
+            latency_ms: start.elapsed().as_millis() as u64,
+            ttft_ms: None,
+            output_tokens: 0,
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+#[async_trait]
+impl AiProvider for MeteredProvider {
+    fn provider_type(&self) -> ProviderType {
+        self.inner.provider_type()
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn is_available(&self) -> bool {
+        self.inner.is_available().await
+    }
+
+    async fn get_models(&self) -> Vec<ModelInfo> {
+        self.inner.get_models().await
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse, ProviderError> {
+        let start = Instant::now();
+        let result = self.inner.chat(request).await;
+        let mut sample = self.sample(&request.model, start);
+        if let Ok(response) = &result {
+            sample.success = true;
+            sample.output_tokens = response.usage.completion_tokens;
+        }
+        self.telemetry.record(sample);
+        result
+    }
+
+    async fn stream_chat(
+        &self,
+        request: &ChatRequest,
+    ) -> Result<mpsc::Receiver<StreamChunk>, ProviderError> {
+        let start = Instant::now();
+        let mut rx = match self.inner.stream_chat(request).await {
+            Ok(rx) => rx,
+            Err(e) => {
+                self.telemetry.record(self.sample(&request.model, start));
+                return Err(e);
+            }
+        };
+
+        let (tx, out) = mpsc::channel(64);
+        let telemetry = self.telemetry.clone();
+        let mut sample = self.sample(&request.model, start);
+        tokio::spawn(async move {
+            let mut text = String::new();
+            let mut usage_tokens = None;
+            while let Some(chunk) = rx.recv().await {
+                if sample.ttft_ms.is_none()
+                    && (!chunk.content.is_empty() || chunk.thinking.is_some())
+                {
+                    sample.ttft_ms = Some(start.elapsed().as_millis() as u64);
+                }
+                text.push_str(&chunk.content);
+                if let Some(usage) = &chunk.usage {
+                    usage_tokens = Some(usage.completion_tokens);
+                }
+                let done = chunk.done;
+                if done {
+                    sample.success = chunk.error.is_none();
+                }
+                let sent = tx.send(chunk).await.is_ok();
+                if done {
+                    break;
+                }
+                if !sent {
+                    // The consumer went away; says nothing about the provider.
+                    return;
+                }
+            }
+
+            sample.latency_ms = start.elapsed().as_millis() as u64;
+            sample.output_tokens = usage_tokens
+                .filter(|&t| t > 0)
+                .unwrap_or_else(|| crate::cost::count_tokens(&sample.model_id, &text) as u32);
+            sample.timestamp = Utc::now();
+            telemetry.record(sample);
+        });
+        Ok(out)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::mock::MockProvider;
+    use crate::types::{ChatMessage, MessageRole};
+    use parking_lot::Mutex;
+
+    fn sample(
+        model: &str,
+        latency_ms: u64,
+        ttft_ms: u64,
+        tokens: u32,
+        success: bool,
+    ) -> CallSample {
+        CallSample {
+            model_id: model.into(),
+            provider: "openai".into(),
+            success,
+            latency_ms,
+            ttft_ms: Some(ttft_ms),
+            output_tokens: tokens,
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn record_n(telemetry: &ModelTelemetry, n: usize, s: CallSample) {
+        for _ in 0..n {
+            telemetry.record(s.clone());
+        }
+    }
+
+    struct FixedQuality(Vec<(&'static str, f64)>);
+
+    impl QualitySource for FixedQuality {
+        fn model_quality(&self, model_id: &str) -> Option<f64> {
+            self.0.iter().find(|(m, _)| *m == model_id).map(|(_, q)| *q)
+        }
+    }
+
+    #[test]
+    fn stats_compute_percentiles_and_error_rate() {
+        let telemetry = ModelTelemetry::new();
+        for latency in 1..=20u64 {
+            telemetry.record(sample("m", latency * 100, 50, 100, true));
+        }
+        telemetry.record(sample("m", 9_999, 50, 0, false));
+
+        let stats = telemetry.stats("m").unwrap();
+        assert_eq!(stats.samples, 21);
+        assert!((stats.error_rate - 1.0 / 21.0).abs() < 1e-9);
+        assert_eq!(stats.latency_p50_ms, 1_000);
+        assert_eq!(stats.latency_p95_ms, 1_900);
+        assert_eq!(stats.ttft_p50_ms, Some(50));
+        assert!(stats.tokens_per_sec.unwrap() > 0.0);
+        assert!(telemetry.stats("other").is_none());
+    }
+
+    #[test]
+    fn window_keeps_latest_samples() {
+        let telemetry = ModelTelemetry::new();
+        record_n(
+            &telemetry,
+            TELEMETRY_WINDOW,
+            sample("m", 100, 10, 10, false),
+        );
+        record_n(&telemetry, TELEMETRY_WINDOW, sample("m", 100, 10, 10, true));
+        let stats = telemetry.stats("m").unwrap();
+        assert_eq!(stats.samples, TELEMETRY_WINDOW);
+        assert_eq!(stats.error_rate, 0.0);
+    }
+
+    #[test]
+    fn record_forwards_to_sink_but_preload_does_not() {
+        struct Collect(Mutex<Vec<CallSample>>);
+        impl TelemetrySink for Collect {
+            fn record_call(&self, sample: &CallSample) {
+                self.0.lock().push(sample.clone());
+            }
+        }
+
+        let sink = Arc::new(Collect(Mutex::new(Vec::new())));
+        let telemetry = ModelTelemetry::new();
+        telemetry.set_sink(sink.clone());
+        telemetry.preload(vec![sample("m", 100, 10, 10, true)]);
+        telemetry.record(sample("m", 200, 10, 10, true));
+
+        assert_eq!(sink.0.lock().len(), 1);
+        assert_eq!(telemetry.stats("m").unwrap().samples, 2);
+    }
+
+    #[test]
+    fn interactive_ranking_prefers_speed_otherwise_quality() {
+        let telemetry = ModelTelemetry::new();
+        // "fast": quick first token and high throughput. "smart": slow.
+        record_n(
+            &telemetry,
+            MIN_SAMPLES,
+            sample("fast", 1_100, 100, 1_000, true),
+        );
+        record_n(
+            &telemetry,
+            MIN_SAMPLES,
+            sample("smart", 9_000, 2_000, 700, true),
+        );
+        let quality = FixedQuality(vec![("fast", 0.6), ("smart", 0.95)]);
+        let models = ["fast", "smart"];
+
+        let chat = signal_scores(&models, &telemetry, Some(&quality), true).unwrap();
+        assert!(chat[0] > chat[1]);
+        let batch = signal_scores(&models, &telemetry, Some(&quality), false).unwrap();
+        assert!(batch[1] > batch[0]);
+    }
+
+    #[test]
+    fn unreliable_model_is_penalised() {
+        let telemetry = ModelTelemetry::new();
+        record_n(&telemetry, MIN_SAMPLES, sample("a", 1_000, 100, 500, true));
+        record_n(&telemetry, MIN_SAMPLES, sample("b", 1_000, 100, 500, true));
+        record_n(&telemetry, MIN_SAMPLES, sample("b", 1_000, 100, 0, false));
+
+        let scores = signal_scores(&["a", "b"], &telemetry, None, true).unwrap();
+        assert!(scores[0] > scores[1]);
+    }
+
+    #[test]
+    fn no_signals_without_enough_data() {
+        let telemetry = ModelTelemetry::new();
+        record_n(
+            &telemetry,
+            MIN_SAMPLES - 1,
+            sample("a", 1_000, 100, 500, true),
+        );
+        assert!(signal_scores(&["a", "b"], &telemetry, None, true).is_none());
+    }
+
+    #[tokio::test]
+    async fn metered_provider_records_stream_and_chat_calls() {
+        let telemetry = Arc::new(ModelTelemetry::new());
+        let provider = MeteredProvider::new(Arc::new(MockProvider::new("hi")), telemetry.clone());
+        let request = ChatRequest {
+            messages: vec![ChatMessage::text(MessageRole::User, "hello")],
+            model: "gpt-4o".into(),
+            max_tokens: 64,
+            temperature: None,
+            system_prompt: None,
+            tools: None,
+        };
+
+        let mut rx = provider.stream_chat(&request).await.unwrap();
+        while let Some(chunk) = rx.recv().await {
+            if chunk.done {
+                break;
+            }
+        }
+        provider.chat(&request).await.unwrap();
+
+        // The stream sample is recorded by the forwarding task after `done`.
+        for _ in 0..100 {
+            if telemetry.stats("gpt-4o").is_some_and(|s| s.samples == 2) {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        let stats = telemetry.stats("gpt-4o").unwrap();
+        assert_eq!(stats.samples, 2);
+        assert_eq!(stats.error_rate, 0.0);
+        assert!(stats.ttft_p50_ms.is_some());
+    }
+}
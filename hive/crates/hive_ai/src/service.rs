@@ -2,12 +2,16 @@
 //!
 //! Manages provider instances, routes requests via the [`ModelRouter`],
 //! and exposes a simple `chat` / `stream_chat` API that the UI layer calls.
+//! Non-streaming `chat` calls go through the optional [`ResponseCache`].
 
 use std::collections::HashMap;
 use std::sync::Arc;
 
 use tokio::sync::mpsc;
-use tracing::{debug, info};
+use hive_core::config::HiveConfig;
+use hive_core::routing_rules::RoutingRule;
+use hive_shield::HiveShield;
+use tracing::{debug, info, warn};
 
 use crate::cost::{CostBreakdown, CostTracker, calculate_cost};
 use crate::discovery::LocalDiscovery;
@@ -23,9 +27,14 @@ use crate::providers::ollama::OllamaProvider;
 use crate::providers::openai::OpenAIProvider;
 use crate::providers::openrouter::OpenRouterProvider;
 use crate::providers::{AiProvider, ProviderError};
-use crate::routing::ModelRouter;
+use crate::response_cache::{CacheHitKind, ResponseCache, ResponseCacheConfig};
+use crate::routing::{
+    ClassificationContext, MeteredProvider, ModelRouter, ModelStats, RoutingDryRun,
+};
+use crate::stream_failover::{FailoverProvider, FailoverTarget, StreamRetryPolicy};
 use crate::types::{
-    ChatMessage, ChatRequest, ChatResponse, ProviderType, StreamChunk, ToolDefinition,
+    ChatMessage, ChatRequest, ChatResponse, MessageRole, ProviderType, StreamChunk,
+    ToolDefinition,
 };
 
 // ---------------------------------------------------------------------------
@@ -50,6 +59,41 @@ pub struct AiServiceConfig {
     pub privacy_mode: bool,
     pub default_model: String,
     pub auto_routing: bool,
+    /// Response cache settings; `None` disables the cache.
+    pub response_cache: Option<ResponseCacheConfig>,
+    /// Ordered routing rules applied on top of automatic routing.
+    pub routing_rules: Vec<RoutingRule>,
+}
+
+impl From<&HiveConfig> for AiServiceConfig {
+    /// AI service settings derived from the user's config.
+    fn from(config: &HiveConfig) -> Self {
+        Self {
+            anthropic_api_key: config.anthropic_api_key.clone(),
+            openai_api_key: config.openai_api_key.clone(),
+            openrouter_api_key: config.openrouter_api_key.clone(),
+            google_api_key: config.google_api_key.clone(),
+            groq_api_key: config.groq_api_key.clone(),
+            huggingface_api_key: config.huggingface_api_key.clone(),
+            xai_api_key: config.xai_api_key.clone(),
+            litellm_url: config.litellm_url.clone(),
+            litellm_api_key: config.litellm_api_key.clone(),
+            ollama_url: config.ollama_url.clone(),
+            lmstudio_url: config.lmstudio_url.clone(),
+            local_provider_url: config.local_provider_url.clone(),
+            privacy_mode: config.privacy_mode,
+            default_model: config.default_model.clone(),
+            auto_routing: config.auto_routing,
+            response_cache: config.response_cache_enabled.then(|| ResponseCacheConfig {
+                ttl_secs: u64::from(config.response_cache_ttl_hours) * 3600,
+                semantic: config.response_cache_semantic,
+                similarity_threshold: config.response_cache_similarity,
+                cache_default_temperature: config.response_cache_default_temperature,
+                ..Default::default()
+            }),
+            routing_rules: config.routing_rules.clone(),
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -63,11 +107,83 @@ pub struct AiService {
     cost_tracker: CostTracker,
     config: AiServiceConfig,
     discovery: Option<Arc<LocalDiscovery>>,
+    response_cache: Option<ResponseCache>,
+    stream_retry: StreamRetryPolicy,
+    shield: Option<Arc<HiveShield>>,
 }
 
 impl AiService {
     /// Create a new service from the given configuration.
     pub fn new(config: AiServiceConfig) -> Self {
+        let providers = Self::build_providers(&config);
+
+        let response_cache = config.response_cache.clone().and_then(|cache_config| {
+            let path = HiveConfig::base_dir().ok()?.join("response_cache.db");
+            match ResponseCache::open(&path, cache_config) {
+                Ok(cache) => {
+                    info!("Response cache enabled at {}", path.display());
+                    Some(cache)
+                }
+                Err(e) => {
+                    warn!("Response cache disabled: {e:#}");
+                    None
+                }
+            }
+        });
+
+        // Registered providers are the ones the router may fall back to.
+        let mut router = ModelRouter::new();
+        for pt in providers.keys() {
+            router
+                .fallback_manager()
+                .set_available(map_to_router_provider(*pt), true);
+        }
+        router.set_rules(config.routing_rules.clone());
+
+        Self {
+            providers,
+            router,
+            cost_tracker: CostTracker::new(crate::cost::BudgetLimits::default()),
+            config,
+            discovery: None,
+            response_cache,
+            stream_retry: StreamRetryPolicy::default(),
+            shield: None,
+        }
+    }
+
+    /// Update configuration (e.g. after settings change).
+    pub fn update_config(&mut self, config: AiServiceConfig) {
+        let shield = self.shield.take();
+        *self = Self::new(config);
+        self.shield = shield;
+    }
+
+    /// Rebuild the providers from `config`, e.g. after API keys were
+    /// unlocked or locked away. Unlike [`update_config`](Self::update_config)
+    /// this keeps routing, cost tracking, discovery and the response cache.
+    pub fn reload_providers(&mut self, config: AiServiceConfig) {
+        let providers = Self::build_providers(&config);
+        for pt in self
+            .providers
+            .keys()
+            .filter(|pt| !providers.contains_key(pt))
+        {
+            self.router
+                .fallback_manager()
+                .set_available(map_to_router_provider(*pt), false);
+        }
+        for pt in providers.keys() {
+            self.router
+                .fallback_manager()
+                .set_available(map_to_router_provider(*pt), true);
+        }
+        self.providers = providers;
+        self.config = config;
+    }
+
+    /// Providers for every configured key and endpoint.
+    fn build_providers(config: &AiServiceConfig) -> HashMap<ProviderType, Arc<dyn AiProvider>> {
         let mut providers: HashMap<ProviderType, Arc<dyn AiProvider>> = HashMap::new();
 
         // Register cloud providers (skipped in privacy mode)
@@ -171,18 +287,7 @@ impl AiService {
 
         info!("{} AI provider(s) registered", providers.len());
 
-        Self {
-            providers,
-            router: ModelRouter::new(),
-            cost_tracker: CostTracker::new(crate::cost::BudgetLimits::default()),
-            config,
-            discovery: None,
-        }
-    }
-
-    /// Update configuration (e.g. after settings change).
-    pub fn update_config(&mut self, config: AiServiceConfig) {
-        *self = Self::new(config);
+        providers
     }
 
     /// The currently configured default model.
@@ -205,6 +310,124 @@ impl AiService {
         &mut self.cost_tracker
     }
 
+    /// The response cache, if enabled.
+    pub fn response_cache(&self) -> Option<&ResponseCache> {
+        self.response_cache.as_ref()
+    }
+
+    /// Replace the response cache (`None` disables caching).
+    pub fn set_response_cache(&mut self, cache: Option<ResponseCache>) {
+        self.response_cache = cache;
+    }
+
+    /// Set the shield that classifies requests arriving without a routing
+    /// context (`None` leaves them unclassified).
+    pub fn set_shield(&mut self, shield: Option<Arc<HiveShield>>) {
+        self.shield = shield;
+    }
+
+    /// Routing context for a request that carries only its `messages`: the
+    /// shield's data classification of their text, if a shield is set.
+    pub fn classification_context(&self, messages: &[ChatMessage]) -> ClassificationContext {
+        let data_classification = self.shield.as_ref().map(|shield| {
+            let text = messages
+                .iter()
+                .map(|m| m.content.as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+            shield.classify(&text)
+        });
+        ClassificationContext {
+            data_classification,
+            ..Default::default()
+        }
+    }
+
+    /// Register (or replace) a provider directly. Test-only.
+    #[cfg(test)]
+    pub(crate) fn register_provider(&mut self, pt: ProviderType, provider: Arc<dyn AiProvider>) {
+        self.router
+            .fallback_manager()
+            .set_available(map_to_router_provider(pt), true);
+        self.providers.insert(pt, provider);
+    }
+
+    /// Show how `prompt` would be auto-routed right now, without sending it.
+    pub fn dry_run_route(
+        &self,
+        prompt: &str,
+        context: Option<&ClassificationContext>,
+    ) -> RoutingDryRun {
+        let messages = [ChatMessage::text(MessageRole::User, prompt)];
+        self.router.dry_run(
+            &messages,
+            &self.all_available_models(),
+            context,
+            chrono::Local::now().naive_local(),
+        )
+    }
+
+    /// Replace the retry/backoff policy for broken streams.
+    pub fn set_stream_retry_policy(&mut self, policy: StreamRetryPolicy) {
+        self.stream_retry = policy;
+    }
+
+    /// Wrap `provider` so a failed call or a stream that breaks mid-response
+    /// is retried on the same provider and then on the registered fallback
+    /// chain entries. Entries the routing rules forbid for this request
+    /// (`messages` and `context`) are left out, so failover never sends a
+    /// request somewhere normal routing would not.
+    fn with_failover(
+        &self,
+        provider_type: ProviderType,
+        provider: Arc<dyn AiProvider>,
+        messages: &[ChatMessage],
+        context: Option<&ClassificationContext>,
+    ) -> FailoverProvider {
+        let manager = self.router.fallback_manager();
+        let mut chain = manager.fallback_chain().to_vec();
+        chain.sort_by_key(|e| e.priority);
+
+        let mut seen = vec![provider_type];
+        let mut fallbacks = Vec::new();
+        for entry in chain {
+            let entry_type = map_router_provider(entry.provider);
+            if seen.contains(&entry_type)
+                || !manager.is_available(entry.provider)
+                || !self.router.allows(
+                    messages,
+                    entry.provider,
+                    &entry.model,
+                    entry.cost_tier,
+                    context,
+                )
+            {
+                continue;
+            }
+            if let Some(fallback) = self.providers.get(&entry_type) {
+                seen.push(entry_type);
+                fallbacks.push(FailoverTarget {
+                    provider: self.metered(fallback.clone()),
+                    model: entry.model,
+                });
+            }
+        }
+
+        let primary = self.metered(provider);
+        FailoverProvider::new(primary, fallbacks, self.stream_retry)
+    }
+
+    /// Wrap `provider` so every call feeds the router's live telemetry.
+    fn metered(&self, provider: Arc<dyn AiProvider>) -> Arc<dyn AiProvider> {
+        Arc::new(MeteredProvider::new(provider, self.router.telemetry().clone()))
+    }
+
+    /// Rolling latency/throughput/error stats for `model_id`, if it has been
+    /// called recently.
+    pub fn model_stats(&self, model_id: &str) -> Option<ModelStats> {
+        self.router.telemetry().stats(model_id)
+    }
+
     /// Access the model router (read-only, e.g. for building panel data).
     pub fn router(&self) -> &ModelRouter {
         &self.router
@@ -293,8 +516,9 @@ impl AiService {
 
     /// Resolve a model ID to its provider.
     fn resolve_provider(&self, model_id: &str) -> Option<(ProviderType, Arc<dyn AiProvider>)> {
-        // Use the router to pick the provider
-        let decision = self.router.route(&[], Some(model_id), None);
+        // Use the router to pick the provider; a model the routing rules
+        // forbid does not resolve at all.
+        let decision = self.router.route(&[], Some(model_id), None).ok()?;
         let provider_type = map_router_provider(decision.provider);
         if let Some(provider) = self.providers.get(&provider_type) {
             return Some((provider_type, provider.clone()));
@@ -311,17 +535,29 @@ impl AiService {
     /// When `model` is the configured default (i.e. the user has not explicitly
     /// chosen a model), this method uses the capability router to pick the best
     /// model for the task based on the conversation content. Otherwise it
-    /// delegates to the standard provider resolution.
+    /// delegates to the standard provider resolution. Fails with
+    /// [`ProviderError::RouteBlocked`] when a routing rule forbids every
+    /// available model, and never falls back to a route a rule replaced.
     fn resolve_provider_smart(
         &self,
         messages: &[ChatMessage],
         model: &str,
-    ) -> Option<(ProviderType, Arc<dyn AiProvider>, String)> {
-        // If the user picked a specific model, use standard resolution.
+        context: Option<&ClassificationContext>,
+    ) -> Result<(ProviderType, Arc<dyn AiProvider>, String), ProviderError> {
+        let no_provider = || ProviderError::Other("No providers available".into());
+
+        // If the user picked a specific model, use standard resolution
+        // (routing rules may still replace it).
         let is_auto = model == self.config.default_model || model == "auto";
         if !is_auto || !self.config.auto_routing {
-            let (pt, provider) = self.resolve_provider(model)?;
-            return Some((pt, provider, model.to_string()));
+            let decision = self.router.route(messages, Some(model), context)?;
+            if decision.model_id != model {
+                let provider_type = map_router_provider(decision.provider);
+                let provider = self.providers.get(&provider_type).ok_or_else(no_provider)?;
+                return Ok((provider_type, provider.clone(), decision.model_id));
+            }
+            let (pt, provider) = self.resolve_provider(model).ok_or_else(no_provider)?;
+            return Ok((pt, provider, model.to_string()));
         }
 
         // Capability-aware auto-routing: use discovered models + conversation content.
@@ -330,59 +566,100 @@ impl AiService {
             messages,
             &available,
             None, // no explicit model
-            None, // no classification context
-        );
+            context,
+        )?;
 
         let provider_type = map_router_provider(decision.provider);
         if let Some(provider) = self.providers.get(&provider_type) {
-            return Some((provider_type, provider.clone(), decision.model_id));
+            return Ok((provider_type, provider.clone(), decision.model_id));
         }
 
         // Fallback: try standard resolution with the decided model
-        let (pt, provider) = self.resolve_provider(&decision.model_id)?;
-        Some((pt, provider, decision.model_id))
+        let (pt, provider) = self
+            .resolve_provider(&decision.model_id)
+            .ok_or_else(no_provider)?;
+        Ok((pt, provider, decision.model_id))
     }
 
-    /// Send a non-streaming chat request.
+    /// Send a non-streaming chat request. `context` carries the file paths
+    /// and data classification that routing rules match against.
+    ///
+    /// Deterministic callers should pass a `temperature` of `Some(0.0)`: only
+    /// those requests are served from the response cache by default.
     pub async fn chat(
         &mut self,
         messages: Vec<ChatMessage>,
         model: &str,
+        temperature: Option<f32>,
         tools: Option<Vec<ToolDefinition>>,
+        context: Option<&ClassificationContext>,
     ) -> Result<ChatResponse, ProviderError> {
-        let (provider_type, provider, resolved_model) = self
-            .resolve_provider_smart(&messages, model)
-            .ok_or_else(|| ProviderError::Other("No providers available".into()))?;
+        let (provider_type, provider, resolved_model) =
+            self.resolve_provider_smart(&messages, model, context)?;
 
         let request = ChatRequest {
             messages,
             model: resolved_model.clone(),
             max_tokens: 4096,
-            temperature: None,
+            temperature,
             system_prompt: None,
             tools,
         };
 
+        if let Some(cache) = &self.response_cache {
+            if !cache.is_cacheable(&request) {
+                self.cost_tracker.record_cache_bypass();
+            } else if let Some(hit) = cache.lookup(&request) {
+                self.cost_tracker.record_cache_hit(
+                    &resolved_model,
+                    hit.response.usage.prompt_tokens as usize,
+                    hit.response.usage.completion_tokens as usize,
+                    matches!(hit.kind, CacheHitKind::Semantic { .. }),
+                );
+                info!("Chat response served from cache ({:?})", hit.kind);
+                return Ok(hit.response);
+            } else {
+                self.cost_tracker.record_cache_miss();
+            }
+        }
+
         info!(
             "Sending chat request to {:?} model={}",
             provider_type, resolved_model
         );
-        let response = provider.chat(&request).await?;
+        let (response, answered) = self
+            .with_failover(provider_type, provider, &request.messages, context)
+            .chat_with_target(&request)
+            .await?;
+
+        // A fallback may have answered; bill and cache under its model.
+        let answered_request = ChatRequest {
+            model: answered.model.clone(),
+            ..request
+        };
+        if let Some(cache) = &self.response_cache
+            && let Err(e) = cache.store(&answered_request, &response)
+        {
+            warn!("Failed to cache chat response: {e:#}");
+        }
 
         // Track cost
         let cost = calculate_cost(
-            &resolved_model,
+            &answered.model,
             response.usage.prompt_tokens as usize,
             response.usage.completion_tokens as usize,
         );
         self.cost_tracker.record(
-            &resolved_model,
+            &answered.model,
             response.usage.prompt_tokens as usize,
             response.usage.completion_tokens as usize,
         );
 
-        self.router
-            .record_result(map_to_router_provider(provider_type), true, None);
+        self.router.record_result(
+            map_to_router_provider(answered.provider.provider_type()),
+            true,
+            None,
+        );
 
         info!(
             "Chat response: {} tokens, ${:.6}",
@@ -393,16 +670,17 @@ impl AiService {
     }
 
     /// Send a streaming chat request. Returns a receiver for stream chunks.
+    /// `context` is matched by routing rules as in [`chat`](Self::chat).
     pub async fn stream_chat(
         &self,
         messages: Vec<ChatMessage>,
         model: &str,
         system_prompt: Option<String>,
         tools: Option<Vec<ToolDefinition>>,
+        context: Option<&ClassificationContext>,
     ) -> Result<mpsc::Receiver<StreamChunk>, ProviderError> {
-        let (provider_type, provider, resolved_model) = self
-            .resolve_provider_smart(&messages, model)
-            .ok_or_else(|| ProviderError::Other("No providers available".into()))?;
+        let (provider_type, provider, resolved_model) =
+            self.resolve_provider_smart(&messages, model, context)?;
 
         let request = ChatRequest {
             messages,
@@ -414,13 +692,16 @@ impl AiService {
         };
 
         info!("Starting stream to {:?} model={}", provider_type, resolved_model);
-        provider.stream_chat(&request).await
+        self.with_failover(provider_type, provider, &request.messages, context)
+            .stream_chat(&request)
+            .await
     }
 
     /// Prepare a streaming request without awaiting it.
     ///
-    /// Returns the provider (Arc-cloned) and the request struct so the caller
-    /// can spawn the async work outside of any Global borrow. Typical usage in
+    /// Returns the provider (wrapped for mid-stream failover) and the request
+    /// struct so the caller can spawn the async work outside of any Global
+    /// borrow. Typical usage in
     /// a GPUI workspace:
     ///
     /// ```ignore
@@ -439,8 +720,23 @@ impl AiService {
         system_prompt: Option<String>,
         tools: Option<Vec<ToolDefinition>>,
     ) -> Option<(Arc<dyn AiProvider>, ChatRequest)> {
-        let (_provider_type, provider, resolved_model) =
-            self.resolve_provider_smart(&messages, model)?;
+        self.prepare_stream_with_context(messages, model, system_prompt, tools, None)
+            .ok()
+    }
+
+    /// [`prepare_stream`](Self::prepare_stream) with the file paths and data
+    /// classification that routing rules match against. Fails with
+    /// [`ProviderError::RouteBlocked`] when the rules refuse the request.
+    pub fn prepare_stream_with_context(
+        &self,
+        messages: Vec<ChatMessage>,
+        model: &str,
+        system_prompt: Option<String>,
+        tools: Option<Vec<ToolDefinition>>,
+        context: Option<&ClassificationContext>,
+    ) -> Result<(Arc<dyn AiProvider>, ChatRequest), ProviderError> {
+        let (provider_type, provider, resolved_model) =
+            self.resolve_provider_smart(&messages, model, context)?;
         let request = ChatRequest {
             messages,
             model: resolved_model,
@@ -449,7 +745,8 @@ impl AiService {
             system_prompt,
             tools,
         };
-        Some((provider, request))
+        let provider = self.with_failover(provider_type, provider, &request.messages, context);
+        Ok((Arc::new(provider), request))
     }
 
     /// Prepare a speculative decoding stream.
@@ -458,7 +755,8 @@ impl AiService {
     /// so the caller can spawn `speculative::speculative_stream()`.
     ///
     /// Returns `None` if speculative decoding is not possible (no draft model,
-    /// or providers not available).
+    /// or providers not available), or if routing rules would send either
+    /// model elsewhere; the caller then uses the normal, rule-routed stream.
     pub fn prepare_speculative_stream(
         &self,
         messages: Vec<ChatMessage>,
@@ -466,6 +764,7 @@ impl AiService {
         system_prompt: Option<String>,
         tools: Option<Vec<ToolDefinition>>,
         spec_config: &crate::speculative::SpeculativeConfig,
+        context: Option<&ClassificationContext>,
     ) -> Option<(
         Arc<dyn AiProvider>,
         ChatRequest,
@@ -475,6 +774,14 @@ impl AiService {
         // Select a draft model
         let draft_model = crate::speculative::select_draft_model(model, spec_config)?;
 
+        // Only speculate when the rules leave both models in place.
+        for candidate in [model, draft_model.as_str()] {
+            let decision = self.router.route(&messages, Some(candidate), context).ok()?;
+            if decision.model_id != candidate {
+                return None;
+            }
+        }
+
         // Resolve primary provider
         let (_pt_primary, primary_provider) = self.resolve_provider(model)?;
         // Resolve draft provider
@@ -502,9 +809,54 @@ impl AiService {
         Some((draft_provider, draft_request, primary_provider, primary_request))
     }
 
+    /// Resolve the provider and model that summarize old messages when a
+    /// conversation on `model` is compacted. `context` is the conversation's
+    /// routing context; the summarizer must satisfy the same routing rules.
+    ///
+    /// In privacy mode, or when the summarizer's provider is not configured,
+    /// the conversation's own model is used instead, routed as chat would.
+    pub fn prepare_compaction(
+        &self,
+        model: &str,
+        override_model: Option<&str>,
+        context: Option<&ClassificationContext>,
+    ) -> Option<(Arc<dyn AiProvider>, String)> {
+        if !self.config.privacy_mode {
+            let summarizer = crate::compaction::select_compaction_model(model, override_model);
+            // Skip the summarizer if the routing rules would send it elsewhere.
+            if let Ok(decision) = self.router.route(&[], Some(&summarizer), context)
+                && decision.model_id == summarizer
+                && let Some(provider) = self.providers.get(&map_router_provider(decision.provider))
+            {
+                return Some((provider.clone(), summarizer));
+            }
+        }
+        let (_pt, provider, resolved_model) =
+            self.resolve_provider_smart(&[], model, context).ok()?;
+        Some((provider, resolved_model))
+    }
+
+    /// Resolve `model` to an eval target on the provider the router assigns
+    /// it. Unlike normal resolution there is no fallback to another
+    /// provider, so an eval never silently measures the wrong model, and a
+    /// model the routing rules forbid has no target.
+    pub fn eval_target(&self, model: &str) -> Option<crate::eval::EvalTarget> {
+        let decision = self.router.route(&[], Some(model), None).ok()?;
+        if decision.model_id != model {
+            return None;
+        }
+        let provider = self
+            .providers
+            .get(&map_router_provider(decision.provider))?;
+        Some(crate::eval::EvalTarget::new(
+            self.metered(provider.clone()),
+            model,
+        ))
+    }
+
     /// Estimate the cost of a message before sending.
     pub fn estimate_cost(&self, text: &str, model: &str) -> CostBreakdown {
-        let input_tokens = crate::cost::estimate_tokens(text);
+        let input_tokens = crate::cost::count_tokens(model, text);
         // Assume 2x output tokens for estimation
         let output_tokens = input_tokens * 2;
         calculate_cost(model, input_tokens, output_tokens)
@@ -612,6 +964,8 @@ mod tests {
             privacy_mode: false,
             default_model: "claude-sonnet-4-5".into(),
             auto_routing: true,
+            response_cache: None,
+            routing_rules: Vec::new(),
         }
     }
 
@@ -672,6 +1026,23 @@ mod tests {
         assert!(svc.privacy_mode());
     }
 
+    #[test]
+    fn test_reload_providers_follows_keys_and_keeps_costs() {
+        let mut locked = test_config();
+        locked.anthropic_api_key = None;
+        let mut svc = AiService::new(locked.clone());
+        svc.cost_tracker_mut()
+            .record("claude-sonnet-4-5-20250929", 100, 50);
+        assert!(!svc.available_providers().contains(&ProviderType::Anthropic));
+
+        svc.reload_providers(test_config());
+        assert!(svc.available_providers().contains(&ProviderType::Anthropic));
+        assert!(svc.cost_tracker().total_cost() > 0.0);
+
+        svc.reload_providers(locked);
+        assert!(!svc.available_providers().contains(&ProviderType::Anthropic));
+    }
+
     #[test]
     fn test_no_providers_returns_none() {
         let config = AiServiceConfig {
@@ -730,4 +1101,269 @@ mod tests {
             assert_eq!(pt, back);
         }
     }
+
+    #[tokio::test]
+    async fn test_chat_serves_repeat_request_from_cache() {
+        use crate::providers::mock::MockProvider;
+
+        let config = AiServiceConfig {
+            ollama_url: String::new(),
+            privacy_mode: true,
+            default_model: "gpt-4o-mini".into(),
+            ..Default::default()
+        };
+        let mut svc = AiService::new(config);
+        let provider = Arc::new(MockProvider::new("cached answer"));
+        svc.register_provider(ProviderType::OpenAI, provider.clone());
+        svc.set_response_cache(Some(
+            ResponseCache::in_memory(ResponseCacheConfig {
+                cache_default_temperature: true,
+                ..ResponseCacheConfig::default()
+            })
+            .unwrap(),
+        ));
+
+        let messages = vec![ChatMessage::text(MessageRole::User, "What is 2 + 2?")];
+        let first = svc
+            .chat(messages.clone(), "gpt-4o-mini", None, None, None)
+            .await
+            .unwrap();
+        let second = svc
+            .chat(messages, "gpt-4o-mini", None, None, None)
+            .await
+            .unwrap();
+
+        assert_eq!(first.content, "cached answer");
+        assert_eq!(second.content, "cached answer");
+        assert_eq!(provider.requests().len(), 1);
+        let stats = svc.cost_tracker().cache_stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert!(stats.saved_tokens > 0);
+    }
+
+    #[tokio::test]
+    async fn test_routing_rule_keeps_explicit_model_local() {
+        use crate::providers::mock::MockProvider;
+        use hive_core::routing_rules::{RuleAction, RuleConditions};
+
+        let config = AiServiceConfig {
+            ollama_url: String::new(),
+            default_model: "auto".into(),
+            routing_rules: vec![RoutingRule {
+                name: "local-only".into(),
+                enabled: true,
+                when: RuleConditions::default(),
+                action: RuleAction::LocalOnly,
+            }],
+            ..Default::default()
+        };
+        let mut svc = AiService::new(config);
+        let cloud = Arc::new(MockProvider::new("cloud"));
+        let local = Arc::new(MockProvider::new("local").with_type(ProviderType::Ollama));
+        svc.register_provider(ProviderType::OpenAI, cloud.clone());
+        svc.register_provider(ProviderType::Ollama, local.clone());
+
+        let messages = vec![ChatMessage::text(MessageRole::User, "hello")];
+        let response = svc
+            .chat(messages, "gpt-4o", None, None, None)
+            .await
+            .unwrap();
+
+        assert_eq!(response.content, "local");
+        assert!(cloud.requests().is_empty());
+        assert_eq!(local.requests()[0].model, "llama3.2");
+
+        let dry_run = svc.dry_run_route("hello", None);
+        assert_eq!(dry_run.matched_rules, ["local-only"]);
+        assert!(
+            dry_run
+                .decision
+                .unwrap()
+                .reasoning
+                .contains("Rule 'local-only'")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_refuses_route_forbidden_for_context() {
+        use crate::providers::mock::MockProvider;
+        use hive_core::routing_rules::{RuleAction, RuleConditions};
+        use hive_shield::DataClassification;
+
+        let config = AiServiceConfig {
+            ollama_url: String::new(),
+            default_model: "auto".into(),
+            routing_rules: vec![RoutingRule {
+                name: "confidential-local".into(),
+                enabled: true,
+                when: RuleConditions {
+                    min_classification: Some(DataClassification::Confidential),
+                    ..Default::default()
+                },
+                action: RuleAction::LocalOnly,
+            }],
+            ..Default::default()
+        };
+        let mut svc = AiService::new(config);
+        let cloud = Arc::new(MockProvider::new("cloud"));
+        svc.register_provider(ProviderType::OpenAI, cloud.clone());
+
+        let messages = vec![ChatMessage::text(MessageRole::User, "hello")];
+        let context = ClassificationContext {
+            data_classification: Some(DataClassification::Confidential),
+            ..Default::default()
+        };
+        let err = svc
+            .chat(messages.clone(), "gpt-4o", None, None, Some(&context))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ProviderError::RouteBlocked(_)));
+        assert!(cloud.requests().is_empty());
+
+        let response = svc
+            .chat(messages, "gpt-4o", None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(response.content, "cloud");
+    }
+
+    #[tokio::test]
+    async fn test_failover_respects_routing_rules() {
+        use crate::providers::mock::MockProvider;
+        use hive_core::routing_rules::{RuleAction, RuleConditions};
+        use hive_shield::DataClassification;
+
+        let config = AiServiceConfig {
+            ollama_url: String::new(),
+            default_model: "auto".into(),
+            routing_rules: vec![RoutingRule {
+                name: "confidential-local".into(),
+                enabled: true,
+                when: RuleConditions {
+                    min_classification: Some(DataClassification::Confidential),
+                    ..Default::default()
+                },
+                action: RuleAction::LocalOnly,
+            }],
+            ..Default::default()
+        };
+        let mut svc = AiService::new(config);
+        svc.set_stream_retry_policy(StreamRetryPolicy {
+            max_retries: 0,
+            ..StreamRetryPolicy::default()
+        });
+        let cloud = Arc::new(MockProvider::new("cloud"));
+        let local = Arc::new(
+            MockProvider::new("local")
+                .with_type(ProviderType::Ollama)
+                .failing(2),
+        );
+        svc.register_provider(ProviderType::OpenAI, cloud.clone());
+        svc.register_provider(ProviderType::Ollama, local.clone());
+
+        let messages = vec![ChatMessage::text(MessageRole::User, "hello")];
+        let context = ClassificationContext {
+            data_classification: Some(DataClassification::Confidential),
+            ..Default::default()
+        };
+        let err = svc
+            .chat(messages.clone(), "llama3.2", None, None, Some(&context))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ProviderError::Network(_)));
+        assert!(cloud.requests().is_empty());
+
+        // Without the classification the cloud fallback may answer, and the
+        // call is billed under the fallback's model.
+        let response = svc
+            .chat(messages, "llama3.2", None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(response.content, "cloud");
+        let by_model = svc.cost_tracker().cost_by_model();
+        assert!(by_model.contains_key(&cloud.requests()[0].model));
+        assert!(!by_model.contains_key("llama3.2"));
+    }
+
+    #[test]
+    fn test_compaction_follows_rules_for_classified_conversation() {
+        use crate::providers::mock::MockProvider;
+        use hive_core::routing_rules::{RuleAction, RuleConditions};
+        use hive_shield::{DataClassification, ShieldConfig};
+
+        let config = AiServiceConfig {
+            ollama_url: String::new(),
+            default_model: "auto".into(),
+            routing_rules: vec![RoutingRule {
+                name: "confidential-local".into(),
+                enabled: true,
+                when: RuleConditions {
+                    min_classification: Some(DataClassification::Confidential),
+                    ..Default::default()
+                },
+                action: RuleAction::LocalOnly,
+            }],
+            ..Default::default()
+        };
+        let mut svc = AiService::new(config);
+        svc.register_provider(ProviderType::OpenAI, Arc::new(MockProvider::new("cloud")));
+        svc.register_provider(
+            ProviderType::Ollama,
+            Arc::new(MockProvider::new("local").with_type(ProviderType::Ollama)),
+        );
+        svc.set_shield(Some(Arc::new(HiveShield::new(ShieldConfig::default()))));
+
+        let messages = vec![ChatMessage::text(
+            MessageRole::User,
+            "Contact alice@example.com about this.",
+        )];
+        let context = svc.classification_context(&messages);
+        assert_eq!(
+            context.data_classification,
+            Some(DataClassification::Confidential)
+        );
+
+        let (provider, summarizer) = svc
+            .prepare_compaction("gpt-4o", None, Some(&context))
+            .unwrap();
+        assert_eq!(provider.provider_type(), ProviderType::Ollama);
+        assert_eq!(summarizer, "llama3.2");
+
+        let (provider, _) = svc.prepare_compaction("gpt-4o", None, None).unwrap();
+        assert_eq!(provider.provider_type(), ProviderType::OpenAI);
+    }
+
+    #[tokio::test]
+    async fn test_chat_at_zero_temperature_hits_default_cache() {
+        use crate::providers::mock::MockProvider;
+
+        let config = AiServiceConfig {
+            ollama_url: String::new(),
+            privacy_mode: true,
+            default_model: "gpt-4o-mini".into(),
+            ..Default::default()
+        };
+        let mut svc = AiService::new(config);
+        let provider = Arc::new(MockProvider::new("cached answer"));
+        svc.register_provider(ProviderType::OpenAI, provider.clone());
+        svc.set_response_cache(Some(
+            ResponseCache::in_memory(ResponseCacheConfig::default()).unwrap(),
+        ));
+
+        let messages = vec![ChatMessage::text(MessageRole::User, "What is 2 + 2?")];
+        for _ in 0..2 {
+            svc.chat(messages.clone(), "gpt-4o-mini", Some(0.0), None, None)
+                .await
+                .unwrap();
+        }
+        svc.chat(messages, "gpt-4o-mini", None, None, None)
+            .await
+            .unwrap();
+
+        assert_eq!(provider.requests().len(), 2);
+        assert_eq!(provider.requests()[0].temperature, Some(0.0));
+        let stats = svc.cost_tracker().cache_stats();
+        assert_eq!((stats.hits, stats.misses, stats.bypassed), (1, 1, 1));
+    }
 }
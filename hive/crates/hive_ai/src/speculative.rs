@@ -85,7 +85,7 @@ pub fn select_draft_model(primary_model: &str, config: &SpeculativeConfig) -> Op
 }
 
 /// Infer model tier from the model ID string (mirrors routing logic).
-fn infer_tier_from_model(model_id: &str) -> ModelTier {
+pub(crate) fn infer_tier_from_model(model_id: &str) -> ModelTier {
     let lower = model_id.to_lowercase();
 
     if lower.contains("opus")
@@ -205,6 +205,8 @@ pub async fn speculative_stream(
                             usage: None,
                             tool_calls: None,
                             stop_reason: None,
+                            error: None,
+                            handoff: None,
                         },
                         is_draft: false,
                         metrics: None,
@@ -265,6 +267,8 @@ pub async fn speculative_stream(
                                             usage: None,
                                             tool_calls: None,
                                             stop_reason: None,
+                                            error: None,
+                                            handoff: None,
                                         },
                                         is_draft: false,  // marks transition to primary
                                         metrics: None,
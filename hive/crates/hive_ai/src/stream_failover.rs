@@ -0,0 +1,783 @@
+//! Mid-stream failover for streaming responses.
+//!
+//! The [`AutoFallbackManager`](crate::routing::AutoFallbackManager) only picks
+//! a provider before a request starts. Once an SSE stream is running, a
+//! dropped connection or a mid-stream `overloaded` error used to end the
+//! answer halfway.
+//!
+//! [`FailoverProvider`] wraps the provider a request was routed to. When its
+//! stream breaks, the wrapper retries the same provider with exponential
+//! backoff, then moves down the fallback chain. Each attempt re-issues the
+//! request with the partial answer as an assistant prefill so the new stream
+//! continues where the old one stopped. A chunk carrying a [`StreamHandoff`]
+//! marks the point where another provider took over.
+//!
+//! A stream that broke while the model was emitting tool calls cannot be
+//! continued by prefill, so it is restarted from the original request and
+//! the handoff is flagged [`restarted`](StreamHandoff::restarted).
+//! Non-streaming `chat` calls get the same retry-then-fallback treatment.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::providers::{AiProvider, ProviderError};
+use crate::types::{
+    ChatMessage, ChatRequest, ChatResponse, MessageRole, ModelInfo, ProviderType, StreamChunk,
+    StreamHandoff,
+};
+
+/// Sent after the prefill to providers that cannot continue an assistant turn
+/// directly.
+const CONTINUE_PROMPT: &str = "Your previous reply was cut off. Continue it exactly where it \
+                               stopped, without repeating any of it or adding a preamble.";
+
+// ---------------------------------------------------------------------------
+// Configuration
+// ---------------------------------------------------------------------------
+
+/// How hard to retry a broken stream before handing off.
+#[derive(Debug, Clone, Copy)]
+pub struct StreamRetryPolicy {
+    /// Resume attempts on the same provider before moving to the next one.
+    pub max_retries: u32,
+    /// Delay before the first retry; doubles on each further attempt.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for StreamRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(4),
+        }
+    }
+}
+
+impl StreamRetryPolicy {
+    /// Delay before retry number `attempt` (1-based).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// A provider/model pair to resume a broken stream on.
+#[derive(Clone)]
+pub struct FailoverTarget {
+    pub provider: Arc<dyn AiProvider>,
+    pub model: String,
+}
+
+// ---------------------------------------------------------------------------
+// Provider wrapper
+// ---------------------------------------------------------------------------
+
+/// Provider wrapper that resumes broken streams, first on the wrapped
+/// provider and then on each fallback in order.
+///
+/// `chat` is retried and failed over the same way. Everything else is
+/// delegated to the primary provider.
+pub struct FailoverProvider {
+    primary: Arc<dyn AiProvider>,
+    fallbacks: Vec<FailoverTarget>,
+    policy: StreamRetryPolicy,
+}
+
+impl FailoverProvider {
+    pub fn new(
+        primary: Arc<dyn AiProvider>,
+        fallbacks: Vec<FailoverTarget>,
+        policy: StreamRetryPolicy,
+    ) -> Self {
+        Self {
+            primary,
+            fallbacks,
+            policy,
+        }
+    }
+
+    /// Fallback targets, in the order they are tried.
+    pub fn fallbacks(&self) -> &[FailoverTarget] {
+        &self.fallbacks
+    }
+
+    /// [`chat`](AiProvider::chat), also returning the target that answered,
+    /// so callers can bill and cache under the model that actually ran.
+    pub async fn chat_with_target(
+        &self,
+        request: &ChatRequest,
+    ) -> Result<(ChatResponse, FailoverTarget), ProviderError> {
+        let primary = FailoverTarget {
+            provider: self.primary.clone(),
+            model: request.model.clone(),
+        };
+        let mut error = match self.primary.chat(request).await {
+            Ok(response) => return Ok((response, primary)),
+            Err(e) => e,
+        };
+
+        let retries = (0..self.policy.max_retries).map(|_| primary.clone());
+        for (attempt, target) in retries.chain(self.fallbacks.iter().cloned()).enumerate() {
+            if !should_fail_over(&error) {
+                break;
+            }
+            if attempt < self.policy.max_retries as usize {
+                let delay = self.policy.backoff(attempt as u32 + 1);
+                warn!(
+                    provider = %target.provider.provider_type(),
+                    attempt = attempt + 1,
+                    "Chat failed ({error}); retrying in {delay:?}"
+                );
+                tokio::time::sleep(delay).await;
+            } else {
+                warn!(
+                    to = %target.provider.provider_type(),
+                    model = %target.model,
+                    "Chat failed ({error}); handing off"
+                );
+            }
+            let mut retry = request.clone();
+            retry.model = target.model.clone();
+            match target.provider.chat(&retry).await {
+                Ok(response) => return Ok((response, target)),
+                Err(e) => error = e,
+            }
+        }
+        Err(error)
+    }
+}
+
+#[async_trait]
+impl AiProvider for FailoverProvider {
+    fn provider_type(&self) -> ProviderType {
+        self.primary.provider_type()
+    }
+
+    fn name(&self) -> &str {
+        self.primary.name()
+    }
+
+    async fn is_available(&self) -> bool {
+        self.primary.is_available().await
+    }
+
+    async fn get_models(&self) -> Vec<ModelInfo> {
+        self.primary.get_models().await
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse, ProviderError> {
+        self.chat_with_target(request)
+            .await
+            .map(|(response, _)| response)
+    }
+
+    async fn stream_chat(
+        &self,
+        request: &ChatRequest,
+    ) -> Result<mpsc::Receiver<StreamChunk>, ProviderError> {
+        // Failing to start is reported to the caller as before; only streams
+        // that break after starting are resumed.
+        let rx = self.primary.stream_chat(request).await?;
+        if self.policy.max_retries == 0 && self.fallbacks.is_empty() {
+            return Ok(rx);
+        }
+
+        let (tx, out) = mpsc::channel(64);
+        let primary = FailoverTarget {
+            provider: self.primary.clone(),
+            model: request.model.clone(),
+        };
+        tokio::spawn(supervise(
+            primary,
+            self.fallbacks.clone(),
+            self.policy,
+            request.clone(),
+            rx,
+            tx,
+        ));
+        Ok(out)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Supervision
+// ---------------------------------------------------------------------------
+
+/// How a forwarded stream ended.
+enum Forwarded {
+    /// The stream finished normally, or the consumer went away.
+    Finished,
+    /// The stream broke; carries the reason and whether tool calls were in
+    /// progress (they cannot be continued by prefill).
+    Broken {
+        reason: String,
+        tool_calls_pending: bool,
+    },
+}
+
+/// Forward chunks from `rx` to `tx`, collecting streamed text in `partial`.
+async fn forward(
+    rx: &mut mpsc::Receiver<StreamChunk>,
+    tx: &mpsc::Sender<StreamChunk>,
+    partial: &mut String,
+) -> Forwarded {
+    while let Some(mut chunk) = rx.recv().await {
+        partial.push_str(&chunk.content);
+
+        if let Some(reason) = chunk.error.take() {
+            // The partial tool calls of a broken stream are incomplete and
+            // must not reach the consumer.
+            let tool_calls_pending = chunk.tool_calls.take().is_some_and(|c| !c.is_empty());
+            // Keep whatever the final chunk carried, but not its done flag:
+            // the answer is not finished yet.
+            if !chunk.content.is_empty() || chunk.thinking.is_some() {
+                chunk.done = false;
+                chunk.usage = None;
+                chunk.stop_reason = None;
+                if tx.send(chunk).await.is_err() {
+                    return Forwarded::Finished;
+                }
+            }
+            return Forwarded::Broken {
+                reason,
+                tool_calls_pending,
+            };
+        }
+
+        let done = chunk.done;
+        if tx.send(chunk).await.is_err() || done {
+            return Forwarded::Finished;
+        }
+    }
+    Forwarded::Broken {
+        reason: "stream closed unexpectedly".into(),
+        tool_calls_pending: false,
+    }
+}
+
+/// Drive `rx`, resuming on the same provider and then the fallbacks each
+/// time it breaks.
+async fn supervise(
+    primary: FailoverTarget,
+    fallbacks: Vec<FailoverTarget>,
+    policy: StreamRetryPolicy,
+    request: ChatRequest,
+    mut rx: mpsc::Receiver<StreamChunk>,
+    tx: mpsc::Sender<StreamChunk>,
+) {
+    let mut partial = String::new();
+    let mut next_targets = fallbacks.into_iter();
+    let mut target = primary;
+    // The provider that produced the text streamed so far.
+    let mut streaming_from = target.provider.provider_type();
+    let mut retries = 0;
+
+    loop {
+        let (mut reason, restart) = match forward(&mut rx, &tx, &mut partial).await {
+            Forwarded::Finished => return,
+            Forwarded::Broken {
+                reason,
+                tool_calls_pending,
+            } => (reason, tool_calls_pending),
+        };
+        if restart {
+            // Tool calls cannot be resumed mid-way: start the answer over.
+            partial.clear();
+        }
+
+        // Find a stream to continue on.
+        loop {
+            if retries < policy.max_retries {
+                retries += 1;
+                let delay = policy.backoff(retries);
+                warn!(
+                    provider = %target.provider.provider_type(),
+                    attempt = retries,
+                    "Stream broke ({reason}); retrying in {delay:?}"
+                );
+                tokio::time::sleep(delay).await;
+            } else if let Some(next) = next_targets.next() {
+                warn!(
+                    from = %target.provider.provider_type(),
+                    to = %next.provider.provider_type(),
+                    model = %next.model,
+                    "Stream broke ({reason}); handing off"
+                );
+                target = next;
+                retries = 0;
+            } else {
+                warn!("Stream broke ({reason}); no fallbacks left");
+                let _ = tx
+                    .send(StreamChunk {
+                        content: String::new(),
+                        done: true,
+                        thinking: None,
+                        usage: None,
+                        tool_calls: None,
+                        stop_reason: None,
+                        error: Some(reason),
+                        handoff: None,
+                    })
+                    .await;
+                return;
+            }
+
+            if tx.is_closed() {
+                return;
+            }
+
+            let provider_type = target.provider.provider_type();
+            let resumed = continuation_request(&request, &target.model, provider_type, &partial);
+            match target.provider.stream_chat(&resumed).await {
+                Ok(new_rx) => {
+                    if provider_type != streaming_from || restart {
+                        info!(
+                            from = %streaming_from,
+                            to = %provider_type,
+                            restart,
+                            "Stream handed off"
+                        );
+                        let handoff = StreamHandoff {
+                            from_provider: streaming_from,
+                            to_provider: provider_type,
+                            to_model: target.model.clone(),
+                            reason: reason.clone(),
+                            restarted: restart,
+                        };
+                        if tx.send(handoff_chunk(handoff)).await.is_err() {
+                            return;
+                        }
+                        streaming_from = provider_type;
+                    }
+                    rx = new_rx;
+                    break;
+                }
+                Err(e) => reason = e.to_string(),
+            }
+        }
+    }
+}
+
+/// Whether `error` is worth retrying or handing to another provider.
+/// Budget and routing-rule refusals would fail the same way anywhere.
+fn should_fail_over(error: &ProviderError) -> bool {
+    !matches!(
+        error,
+        ProviderError::BudgetExceeded | ProviderError::RouteBlocked(_)
+    )
+}
+
+fn handoff_chunk(handoff: StreamHandoff) -> StreamChunk {
+    StreamChunk {
+        content: String::new(),
+        done: false,
+        thinking: None,
+        usage: None,
+        tool_calls: None,
+        stop_reason: None,
+        error: None,
+        handoff: Some(handoff),
+    }
+}
+
+/// Build the request that continues `partial` on `provider`/`model`.
+///
+/// Anthropic continues a trailing assistant message natively. Other providers
+/// get the partial answer followed by a short instruction to carry on.
+pub fn continuation_request(
+    request: &ChatRequest,
+    model: &str,
+    provider: ProviderType,
+    partial: &str,
+) -> ChatRequest {
+    let mut resumed = request.clone();
+    resumed.model = model.to_string();
+
+    // Anthropic rejects a final assistant message ending in whitespace.
+    let prefill = partial.trim_end();
+    if prefill.is_empty() {
+        return resumed;
+    }
+
+    resumed
+        .messages
+        .push(ChatMessage::text(MessageRole::Assistant, prefill));
+    if provider != ProviderType::Anthropic {
+        resumed
+            .messages
+            .push(ChatMessage::text(MessageRole::User, CONTINUE_PROMPT));
+    }
+    resumed
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::mock::MockProvider;
+    use crate::providers::openai::OpenAIProvider;
+    use crate::providers::openrouter::OpenRouterProvider;
+    use parking_lot::Mutex;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// What the mock server does on one connection.
+    enum Reply {
+        /// Stream these text deltas, then cut the connection mid-body.
+        Drop(Vec<&'static str>),
+        /// Stream these text deltas and half a tool call, then cut the
+        /// connection mid-body.
+        DropInToolCall(Vec<&'static str>),
+        /// Send an OpenRouter-style mid-stream error frame and end.
+        ErrorFrame(&'static str),
+        /// Stream these text deltas and finish properly.
+        Complete(Vec<&'static str>),
+    }
+
+    /// Minimal OpenAI-compatible SSE server. Answers connections with the
+    /// scripted replies in order and records each request body.
+    struct MockSseServer {
+        base_url: String,
+        bodies: Arc<Mutex<Vec<serde_json::Value>>>,
+    }
+
+    impl MockSseServer {
+        async fn start(replies: Vec<Reply>) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let base_url = format!("http://{}", listener.local_addr().unwrap());
+            let bodies = Arc::new(Mutex::new(Vec::new()));
+            let recorded = bodies.clone();
+            tokio::spawn(async move {
+                for reply in replies {
+                    let Ok((mut socket, _)) = listener.accept().await else {
+                        return;
+                    };
+                    let body = read_request(&mut socket).await;
+                    recorded
+                        .lock()
+                        .push(serde_json::from_slice(&body).unwrap_or_default());
+                    write_reply(&mut socket, reply).await;
+                }
+            });
+            Self { base_url, bodies }
+        }
+
+        /// Messages of the `n`th request as (role, content) pairs.
+        fn messages(&self, n: usize) -> Vec<(String, String)> {
+            self.bodies.lock()[n]["messages"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|m| {
+                    (
+                        m["role"].as_str().unwrap_or_default().to_string(),
+                        m["content"].as_str().unwrap_or_default().to_string(),
+                    )
+                })
+                .collect()
+        }
+
+        fn request_count(&self) -> usize {
+            self.bodies.lock().len()
+        }
+    }
+
+    async fn read_request(socket: &mut TcpStream) -> Vec<u8> {
+        let mut buf = Vec::new();
+        let mut tmp = [0u8; 4096];
+        let header_end = loop {
+            let n = socket.read(&mut tmp).await.unwrap();
+            buf.extend_from_slice(&tmp[..n]);
+            if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+        };
+        let headers = String::from_utf8_lossy(&buf[..header_end]).to_lowercase();
+        let length: usize = headers
+            .lines()
+            .find_map(|l| l.strip_prefix("content-length:"))
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(0);
+        while buf.len() < header_end + length {
+            let n = socket.read(&mut tmp).await.unwrap();
+            buf.extend_from_slice(&tmp[..n]);
+        }
+        buf[header_end..header_end + length].to_vec()
+    }
+
+    async fn write_chunk(socket: &mut TcpStream, data: &str) {
+        let frame = format!("{:x}\r\n{data}\r\n", data.len());
+        socket.write_all(frame.as_bytes()).await.unwrap();
+        socket.flush().await.unwrap();
+    }
+
+    fn delta(text: &str) -> String {
+        let frame = serde_json::json!({
+            "choices": [{ "delta": { "content": text }, "finish_reason": null }]
+        });
+        format!("data: {frame}\n\n")
+    }
+
+    async fn write_reply(socket: &mut TcpStream, reply: Reply) {
+        socket
+            .write_all(
+                b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\
+                  transfer-encoding: chunked\r\n\r\n",
+            )
+            .await
+            .unwrap();
+        match reply {
+            Reply::Drop(texts) => {
+                for text in texts {
+                    write_chunk(socket, &delta(text)).await;
+                }
+                // Give the client time to read, then cut the body short.
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            Reply::DropInToolCall(texts) => {
+                for text in texts {
+                    write_chunk(socket, &delta(text)).await;
+                }
+                let call = serde_json::json!({
+                    "choices": [{ "delta": { "tool_calls": [{
+                        "index": 0,
+                        "id": "call_1",
+                        "function": { "name": "read_file", "arguments": "{\"pa" }
+                    }] }, "finish_reason": null }]
+                });
+                write_chunk(socket, &format!("data: {call}\n\n")).await;
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            Reply::ErrorFrame(message) => {
+                let frame = serde_json::json!({ "error": { "message": message } });
+                write_chunk(socket, &format!("data: {frame}\n\n")).await;
+                write_chunk(socket, "").await;
+            }
+            Reply::Complete(texts) => {
+                for text in texts {
+                    write_chunk(socket, &delta(text)).await;
+                }
+                let stop = serde_json::json!({
+                    "choices": [{ "delta": {}, "finish_reason": "stop" }]
+                });
+                write_chunk(socket, &format!("data: {stop}\n\ndata: [DONE]\n\n")).await;
+                write_chunk(socket, "").await;
+            }
+        }
+    }
+
+    fn fast_policy(max_retries: u32) -> StreamRetryPolicy {
+        StreamRetryPolicy {
+            max_retries,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+        }
+    }
+
+    fn request() -> ChatRequest {
+        ChatRequest {
+            messages: vec![ChatMessage::text(MessageRole::User, "What is the answer?")],
+            model: "anthropic/claude-sonnet-4".into(),
+            max_tokens: 256,
+            temperature: None,
+            system_prompt: None,
+            tools: None,
+        }
+    }
+
+    async fn collect(mut rx: mpsc::Receiver<StreamChunk>) -> Vec<StreamChunk> {
+        let mut chunks = Vec::new();
+        while let Some(chunk) = rx.recv().await {
+            let done = chunk.done;
+            chunks.push(chunk);
+            if done {
+                break;
+            }
+        }
+        chunks
+    }
+
+    fn text(chunks: &[StreamChunk]) -> String {
+        chunks.iter().map(|c| c.content.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn dropped_stream_is_flagged_by_provider() {
+        let server = MockSseServer::start(vec![Reply::Drop(vec!["The answer"])]).await;
+        let provider = OpenRouterProvider::with_base_url("key".into(), server.base_url.clone());
+
+        let chunks = collect(provider.stream_chat(&request()).await.unwrap()).await;
+        assert_eq!(text(&chunks), "The answer");
+        let last = chunks.last().unwrap();
+        assert!(last.done);
+        assert!(last.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn retries_then_hands_off_with_prefill() {
+        let primary = MockSseServer::start(vec![
+            Reply::Drop(vec!["The ", "answer"]),
+            Reply::ErrorFrame("Overloaded"),
+        ])
+        .await;
+        let fallback = MockSseServer::start(vec![Reply::Complete(vec![" is 42."])]).await;
+
+        let provider = FailoverProvider::new(
+            Arc::new(OpenRouterProvider::with_base_url(
+                "key".into(),
+                primary.base_url.clone(),
+            )),
+            vec![FailoverTarget {
+                provider: Arc::new(OpenAIProvider::with_base_url(
+                    "key".into(),
+                    fallback.base_url.clone(),
+                )),
+                model: "gpt-4o-mini".into(),
+            }],
+            fast_policy(1),
+        );
+
+        let chunks = collect(provider.stream_chat(&request()).await.unwrap()).await;
+        assert_eq!(text(&chunks), "The answer is 42.");
+        assert!(chunks.iter().all(|c| c.error.is_none()));
+
+        let handoffs: Vec<_> = chunks.iter().filter_map(|c| c.handoff.as_ref()).collect();
+        assert_eq!(handoffs.len(), 1);
+        assert_eq!(handoffs[0].from_provider, ProviderType::OpenRouter);
+        assert_eq!(handoffs[0].to_provider, ProviderType::OpenAI);
+        assert_eq!(handoffs[0].to_model, "gpt-4o-mini");
+        assert_eq!(handoffs[0].reason, "Overloaded");
+
+        // The retry on the primary already carried the partial answer.
+        assert_eq!(primary.request_count(), 2);
+        let retry = primary.messages(1);
+        assert_eq!(retry[1], ("assistant".into(), "The answer".into()));
+
+        let resumed = fallback.messages(0);
+        assert_eq!(resumed.len(), 3);
+        assert_eq!(resumed[1], ("assistant".into(), "The answer".into()));
+        assert_eq!(resumed[2].0, "user");
+        assert_eq!(fallback.bodies.lock()[0]["model"], "gpt-4o-mini");
+    }
+
+    #[tokio::test]
+    async fn exhausted_failover_keeps_partial_and_reports_error() {
+        let primary =
+            MockSseServer::start(vec![Reply::Drop(vec!["Partial"]), Reply::Drop(vec![])]).await;
+        let provider = FailoverProvider::new(
+            Arc::new(OpenRouterProvider::with_base_url(
+                "key".into(),
+                primary.base_url.clone(),
+            )),
+            Vec::new(),
+            fast_policy(1),
+        );
+
+        let chunks = collect(provider.stream_chat(&request()).await.unwrap()).await;
+        assert_eq!(text(&chunks), "Partial");
+        let last = chunks.last().unwrap();
+        assert!(last.done);
+        assert!(last.error.is_some());
+        assert!(chunks.iter().all(|c| c.handoff.is_none()));
+    }
+
+    #[tokio::test]
+    async fn broken_tool_call_restarts_without_prefill() {
+        let primary =
+            MockSseServer::start(vec![Reply::DropInToolCall(vec!["Let me look. "])]).await;
+        let fallback = MockSseServer::start(vec![Reply::Complete(vec!["Done."])]).await;
+
+        let provider = FailoverProvider::new(
+            Arc::new(OpenRouterProvider::with_base_url(
+                "key".into(),
+                primary.base_url.clone(),
+            )),
+            vec![FailoverTarget {
+                provider: Arc::new(OpenAIProvider::with_base_url(
+                    "key".into(),
+                    fallback.base_url.clone(),
+                )),
+                model: "gpt-4o-mini".into(),
+            }],
+            fast_policy(0),
+        );
+
+        let chunks = collect(provider.stream_chat(&request()).await.unwrap()).await;
+        // No half-finished tool call leaks to the consumer.
+        assert!(chunks.iter().all(|c| c.tool_calls.is_none()));
+        let handoff = chunks.iter().find_map(|c| c.handoff.as_ref()).unwrap();
+        assert!(handoff.restarted);
+        assert!(handoff.marker().contains("Restarted by gpt-4o-mini"));
+
+        // The fallback starts over from the original conversation.
+        let resumed = fallback.messages(0);
+        assert_eq!(resumed.len(), 1);
+        assert_eq!(resumed[0].0, "user");
+        assert!(chunks.last().unwrap().error.is_none());
+    }
+
+    #[tokio::test]
+    async fn chat_retries_then_falls_back() {
+        let primary = Arc::new(MockProvider::new("primary").failing(2));
+        let fallback = Arc::new(MockProvider::new("fallback").with_type(ProviderType::Groq));
+        let provider = FailoverProvider::new(
+            primary.clone(),
+            vec![FailoverTarget {
+                provider: fallback.clone(),
+                model: "llama-3.3-70b".into(),
+            }],
+            fast_policy(1),
+        );
+
+        let response = provider.chat(&request()).await.unwrap();
+        assert_eq!(response.content, "fallback");
+        assert_eq!(primary.requests().len(), 2);
+        assert_eq!(fallback.requests()[0].model, "llama-3.3-70b");
+    }
+
+    #[test]
+    fn budget_errors_are_not_failed_over() {
+        assert!(should_fail_over(&ProviderError::Network("down".into())));
+        assert!(!should_fail_over(&ProviderError::BudgetExceeded));
+    }
+
+    #[test]
+    fn anthropic_continuation_uses_plain_prefill() {
+        let resumed = continuation_request(
+            &request(),
+            "claude-sonnet-4",
+            ProviderType::Anthropic,
+            "The answer  \n",
+        );
+        assert_eq!(resumed.model, "claude-sonnet-4");
+        assert_eq!(resumed.messages.len(), 2);
+        assert_eq!(resumed.messages[1].role, MessageRole::Assistant);
+        assert_eq!(resumed.messages[1].content, "The answer");
+    }
+
+    #[test]
+    fn empty_partial_reissues_original_request() {
+        let resumed = continuation_request(&request(), "gpt-4o", ProviderType::OpenAI, "  ");
+        assert_eq!(resumed.messages.len(), 1);
+        assert_eq!(resumed.model, "gpt-4o");
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_cap() {
+        let policy = StreamRetryPolicy::default();
+        assert_eq!(policy.backoff(1), Duration::from_millis(500));
+        assert_eq!(policy.backoff(2), Duration::from_secs(1));
+        assert_eq!(policy.backoff(10), Duration::from_secs(4));
+    }
+}
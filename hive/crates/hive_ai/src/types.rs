@@ -1,3 +1,4 @@
+use hive_core::tokenizer::TokenizerFamily;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
@@ -143,6 +144,26 @@ pub struct ModelInfo {
     pub release_date: Option<String>,
 }
 
+impl ModelInfo {
+    /// Tokenizer used to count tokens for this model.
+    ///
+    /// Providers that don't publish their tokenizer get the closest public
+    /// one (Anthropic -> `cl100k_base`, Google -> the Gemma vocabulary);
+    /// everything else is resolved from the model ID.
+    pub fn tokenizer_family(&self) -> TokenizerFamily {
+        match self.provider_type {
+            ProviderType::Anthropic => TokenizerFamily::Cl100kBase,
+            ProviderType::Google => TokenizerFamily::Local("gemma".into()),
+            ProviderType::OpenAI => match TokenizerFamily::for_model(&self.id) {
+                // Newer OpenAI models all use o200k.
+                TokenizerFamily::Heuristic => TokenizerFamily::O200kBase,
+                family => family,
+            },
+            _ => TokenizerFamily::for_model(&self.id),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ModelTier {
@@ -284,4 +305,43 @@ pub struct StreamChunk {
     /// Why the model stopped generating.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub stop_reason: Option<StopReason>,
+    /// Set on the final chunk when the stream broke before the model finished
+    /// (dropped connection, mid-stream overload). Content already received is
+    /// still valid.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Set when a broken stream was resumed on a different provider; the
+    /// chunks that follow come from the new provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handoff: Option<StreamHandoff>,
+}
+
+/// Where a broken stream was picked up by a fallback provider.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamHandoff {
+    pub from_provider: ProviderType,
+    pub to_provider: ProviderType,
+    pub to_model: String,
+    /// Why the previous stream failed.
+    pub reason: String,
+    /// The answer was restarted from scratch instead of continued, because
+    /// the broken stream had tool calls in progress. Text received before
+    /// this chunk is superseded and should be discarded.
+    #[serde(default)]
+    pub restarted: bool,
+}
+
+impl StreamHandoff {
+    /// Markdown line inserted into the transcript at the handoff point.
+    pub fn marker(&self) -> String {
+        let verb = if self.restarted {
+            "Restarted"
+        } else {
+            "Continued"
+        };
+        format!(
+            "\n\n> \u{21AA} *{verb} by {} ({}) after {} failed: {}*\n\n",
+            self.to_model, self.to_provider, self.from_provider, self.reason
+        )
+    }
 }
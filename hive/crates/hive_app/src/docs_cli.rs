@@ -0,0 +1,172 @@
+//! `hive docs` — manage the offline documentation sets agents search.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use hive_integrations::docs_indexer::{DocsIndex, DocsIndexer};
+
+const USAGE: &str = "\
+Usage:
+  hive docs list
+  hive docs crawl <name> <url> [--max-pages <n>]
+  hive docs refresh <name> [--max-pages <n>]
+  hive docs import-rustdoc [<target/doc dir | crate.json>]
+  hive docs import-devdocs <dir | archive.tar.gz> [--name <name>]
+  hive docs import-dash <Name.docset | archive.tgz> [--name <name>]
+  hive docs remove <name>
+
+rustdoc JSON comes from:
+  RUSTDOCFLAGS=\"-Z unstable-options --output-format json\" cargo +nightly doc";
+
+const DEFAULT_MAX_PAGES: usize = 500;
+
+/// Entry point for `hive docs <args>`.
+pub fn run(args: &[String]) -> Result<()> {
+    let Some((command, rest)) = args.split_first() else {
+        println!("{USAGE}");
+        return Ok(());
+    };
+    let options = Options::parse(rest)?;
+    let mut indexer = DocsIndexer::open_default()?;
+    match command.as_str() {
+        "list" => list(&indexer),
+        "crawl" => crawl(&mut indexer, &options),
+        "refresh" => refresh(&mut indexer, &options),
+        "import-rustdoc" => import_rustdoc(&mut indexer, &options),
+        "import-devdocs" => {
+            let path = options.one_path("a DevDocs directory or archive")?;
+            report(&indexer.import_devdocs(&path, options.name.as_deref())?);
+            Ok(())
+        }
+        "import-dash" => {
+            let path = options.one_path("a .docset bundle or archive")?;
+            report(&indexer.import_dash_docset(&path, options.name.as_deref())?);
+            Ok(())
+        }
+        "remove" => {
+            let [name] = options.positional.as_slice() else {
+                bail!("Expected one index name\n\n{USAGE}");
+            };
+            if indexer.get_index(name).is_none() {
+                bail!("No docs index named '{name}'");
+            }
+            indexer.remove_index(name);
+            println!("Removed {name}");
+            Ok(())
+        }
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        other => bail!("Unknown docs command '{other}'\n\n{USAGE}"),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Argument parsing
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Default)]
+struct Options {
+    positional: Vec<String>,
+    max_pages: Option<usize>,
+    name: Option<String>,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self> {
+        let mut options = Self::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let mut value = |flag: &str| {
+                iter.next()
+                    .cloned()
+                    .with_context(|| format!("{flag} needs a value"))
+            };
+            match arg.as_str() {
+                "--max-pages" => {
+                    options.max_pages = Some(
+                        value(arg.as_str())?
+                            .parse()
+                            .context("--max-pages must be a number")?,
+                    );
+                }
+                "--name" => options.name = Some(value(arg.as_str())?),
+                flag if flag.starts_with('-') => bail!("Unknown option '{flag}'\n\n{USAGE}"),
+                _ => options.positional.push(arg.clone()),
+            }
+        }
+        Ok(options)
+    }
+
+    fn one_path(&self, what: &str) -> Result<PathBuf> {
+        match self.positional.as_slice() {
+            [path] => Ok(PathBuf::from(path)),
+            _ => bail!("Expected {what}\n\n{USAGE}"),
+        }
+    }
+
+    fn max_pages(&self) -> usize {
+        self.max_pages.unwrap_or(DEFAULT_MAX_PAGES)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+fn list(indexer: &DocsIndexer) -> Result<()> {
+    let mut indexes = indexer.list_indexes();
+    indexes.sort_by(|a, b| a.0.cmp(&b.0));
+    for (name, base_url, pages, indexed_at) in indexes {
+        let origin = indexer
+            .get_index(&name)
+            .map(|index| format!("{:?}", index.origin).to_lowercase())
+            .unwrap_or_default();
+        println!(
+            "{name}  {pages} pages  {origin}  {}  {base_url}",
+            indexed_at.format("%Y-%m-%d %H:%M")
+        );
+    }
+    Ok(())
+}
+
+fn crawl(indexer: &mut DocsIndexer, options: &Options) -> Result<()> {
+    let [name, url] = options.positional.as_slice() else {
+        bail!("Expected a name and a URL\n\n{USAGE}");
+    };
+    let runtime = tokio::runtime::Runtime::new().context("Failed to start async runtime")?;
+    report(&runtime.block_on(indexer.index_site(name, url, options.max_pages()))?);
+    Ok(())
+}
+
+fn refresh(indexer: &mut DocsIndexer, options: &Options) -> Result<()> {
+    let [name] = options.positional.as_slice() else {
+        bail!("Expected one index name\n\n{USAGE}");
+    };
+    let runtime = tokio::runtime::Runtime::new().context("Failed to start async runtime")?;
+    report(&runtime.block_on(indexer.refresh_index(name, options.max_pages()))?);
+    Ok(())
+}
+
+fn import_rustdoc(indexer: &mut DocsIndexer, options: &Options) -> Result<()> {
+    let path = match options.positional.as_slice() {
+        [] => Path::new("target").join("doc"),
+        [path] => PathBuf::from(path),
+        _ => bail!("Expected at most one path\n\n{USAGE}"),
+    };
+    if path.is_dir() {
+        let imported = indexer.import_cargo_doc(&path)?;
+        if imported.is_empty() {
+            bail!("No rustdoc JSON found in {}\n\n{USAGE}", path.display());
+        }
+        imported.iter().for_each(report);
+    } else {
+        report(&indexer.import_rustdoc_json(&path)?);
+    }
+    Ok(())
+}
+
+fn report(index: &DocsIndex) {
+    println!("Indexed {} ({} pages)", index.name, index.pages.len());
+}
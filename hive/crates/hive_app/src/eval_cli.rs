@@ -0,0 +1,213 @@
+//! `hive eval` — replay an eval suite against candidate models from the
+//! command line.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+use hive_ai::eval::report::{self, ReportFormat};
+use hive_ai::eval::{EvalConfig, EvalRunner, EvalStore, EvalSuite, EvalTarget};
+use hive_core::conversations::ConversationStore;
+
+const USAGE: &str = "\
+Usage:
+  hive eval run <suite.yaml> --models <m1,m2,...> [--judge <model>]
+                [--concurrency <n>] [--report <out.md|out.html>] [--db <path>]
+  hive eval export <conversation-id>... --out <suite.yaml> [--name <name>]
+  hive eval runs [--db <path>]
+  hive eval report <run-id> [--report <out.md|out.html>] [--db <path>]";
+
+/// Entry point for `hive eval <args>`.
+pub fn run(args: &[String]) -> Result<()> {
+    let Some((command, rest)) = args.split_first() else {
+        println!("{USAGE}");
+        return Ok(());
+    };
+    let options = Options::parse(rest)?;
+    match command.as_str() {
+        "run" => run_suite(&options),
+        "export" => export(&options),
+        "runs" => list_runs(&options),
+        "report" => show_report(&options),
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        other => bail!("Unknown eval command '{other}'\n\n{USAGE}"),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Argument parsing
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Default)]
+struct Options {
+    positional: Vec<String>,
+    models: Vec<String>,
+    judge: Option<String>,
+    concurrency: Option<usize>,
+    report: Option<PathBuf>,
+    db: Option<PathBuf>,
+    out: Option<PathBuf>,
+    name: Option<String>,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self> {
+        let mut options = Self::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let mut value = |flag: &str| {
+                iter.next()
+                    .cloned()
+                    .with_context(|| format!("{flag} needs a value"))
+            };
+            match arg.as_str() {
+                "--models" | "-m" => {
+                    options.models = value(arg.as_str())?
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|m| !m.is_empty())
+                        .map(String::from)
+                        .collect();
+                }
+                "--judge" => options.judge = Some(value(arg.as_str())?),
+                "--concurrency" | "-j" => {
+                    options.concurrency = Some(
+                        value(arg.as_str())?
+                            .parse()
+                            .context("--concurrency must be a number")?,
+                    );
+                }
+                "--report" => options.report = Some(value(arg.as_str())?.into()),
+                "--db" => options.db = Some(value(arg.as_str())?.into()),
+                "--out" | "-o" => options.out = Some(value(arg.as_str())?.into()),
+                "--name" => options.name = Some(value(arg.as_str())?),
+                flag if flag.starts_with('-') => bail!("Unknown option '{flag}'\n\n{USAGE}"),
+                _ => options.positional.push(arg.clone()),
+            }
+        }
+        Ok(options)
+    }
+
+    fn store(&self) -> Result<EvalStore> {
+        let path = match &self.db {
+            Some(path) => path.clone(),
+            None => EvalStore::default_path()?,
+        };
+        EvalStore::open(&path)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+fn run_suite(options: &Options) -> Result<()> {
+    let [suite_path] = options.positional.as_slice() else {
+        bail!("Expected one suite file\n\n{USAGE}");
+    };
+    if options.models.is_empty() {
+        bail!("--models is required\n\n{USAGE}");
+    }
+    let suite = EvalSuite::load(std::path::Path::new(suite_path))?;
+
+    let runtime = tokio::runtime::Runtime::new().context("Failed to start async runtime")?;
+    let _runtime_guard = runtime.enter();
+    let config_manager = super::open_config_manager()?;
+    let service = hive_ai::AiService::new(super::ai_service_config(&config_manager.get()));
+    let resolve = |model: &str| -> Result<EvalTarget> {
+        service
+            .eval_target(model)
+            .with_context(|| format!("No configured provider serves '{model}'"))
+    };
+    let targets = options
+        .models
+        .iter()
+        .map(|m| resolve(m))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut config = EvalConfig::default();
+    if let Some(concurrency) = options.concurrency {
+        config.concurrency = concurrency;
+    }
+    let mut runner = EvalRunner::new(config);
+    if let Some(judge) = &options.judge {
+        runner = runner.with_judge(resolve(judge)?);
+    }
+
+    println!(
+        "Running '{}': {} case(s) x {} model(s)...",
+        suite.name,
+        suite.cases.len(),
+        targets.len()
+    );
+    let run = runtime.block_on(runner.run(&suite, &targets));
+
+    options.store()?.save_run(&run)?;
+    write_report(&run, options.report.as_ref())?;
+    println!("Saved run {}", run.id);
+    Ok(())
+}
+
+fn export(options: &Options) -> Result<()> {
+    if options.positional.is_empty() {
+        bail!("Expected at least one conversation id\n\n{USAGE}");
+    }
+    let out = options.out.as_ref().context("--out is required")?;
+    let store = ConversationStore::new()?;
+    let conversations = options
+        .positional
+        .iter()
+        .map(|id| store.load(id))
+        .collect::<Result<Vec<_>>>()?;
+
+    let name = options.name.clone().unwrap_or_else(|| {
+        out.file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "exported".into())
+    });
+    let suite = EvalSuite::from_conversations(name, &conversations);
+    std::fs::write(out, suite.to_yaml()?)
+        .with_context(|| format!("Failed to write {}", out.display()))?;
+    println!("Wrote {} case(s) to {}", suite.cases.len(), out.display());
+    Ok(())
+}
+
+fn list_runs(options: &Options) -> Result<()> {
+    for run in options.store()?.recent_runs(20)? {
+        println!(
+            "{}  {}  {}  [{}]",
+            run.id,
+            run.started_at.format("%Y-%m-%d %H:%M"),
+            run.suite,
+            run.models.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn show_report(options: &Options) -> Result<()> {
+    let [id] = options.positional.as_slice() else {
+        bail!("Expected one run id\n\n{USAGE}");
+    };
+    let run = options
+        .store()?
+        .load_run(id)?
+        .with_context(|| format!("No eval run '{id}'"))?;
+    write_report(&run, options.report.as_ref())
+}
+
+/// Write the report to `path`, or print the Markdown report.
+fn write_report(run: &hive_ai::eval::EvalRun, path: Option<&PathBuf>) -> Result<()> {
+    match path {
+        Some(path) => {
+            let text = report::render(run, ReportFormat::from_path(path));
+            std::fs::write(path, text)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            println!("Report written to {}", path.display());
+        }
+        None => println!("{}", report::markdown_report(run)),
+    }
+    Ok(())
+}
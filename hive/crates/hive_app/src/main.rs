@@ -1,5 +1,8 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod docs_cli;
+mod eval_cli;
+mod relay_cli;
 mod tray;
 
 use std::borrow::Cow;
@@ -21,10 +24,11 @@ use hive_core::updater::UpdateService;
 use hive_ui::globals::{
     AppAiService, AppAssistant, AppAutomation, AppAws, AppAzure, AppBitbucket, AppBrowser,
     AppChannels, AppCli, AppCollectiveMemory, AppCompetenceDetector, AppConfig, AppDatabase,
-    AppDocker, AppDocsIndexer, AppFleetLearning, AppGcp, AppGitLab, AppIde, AppIntegrationDb,
-    AppKnowledge, AppKubernetes, AppLearning, AppMarketplace, AppMcpServer, AppMessaging, AppNetwork, AppNotifications, AppPersonas,
+    AppDocker, AppDocsIndexer, AppFleetLearning, AppFleetSharing, AppGcp, AppGitLab, AppIde,
+    AppKanban, AppKnowledge, AppKubernetes, AppLearning, AppMarketplace, AppMcpServer, AppMessaging,
+    AppIntegrationDb, AppNetwork, AppNotifications, AppOrchestration, AppPersonas, AppReplication,
     AppContextEngine, AppProjectManagement, AppRagService, AppRpcConfig, AppScheduler,
-    AppSecurity, AppSemanticSearch, AppShield, AppSkills, AppSpecs, AppStandupService,
+    AppSearch, AppSecurity, AppSemanticSearch, AppShield, AppSkills, AppSpecs, AppStandupService,
     AppTts, AppUpdater, AppWallets,
 };
 use hive_ui::workspace::{
@@ -91,14 +95,43 @@ actions!(hive, [Quit, TogglePrivacy, OpenSettings]);
 // Bootstrap
 // ---------------------------------------------------------------------------
 
-/// Initialize backend services and store them as GPUI globals.
-fn init_services(cx: &mut App) -> anyhow::Result<()> {
+/// Load the config and, when `HIVE_MASTER_PASSPHRASE` is set, unlock the
+/// secure storage that holds the API keys. Otherwise it is unlocked from
+/// Settings → Security.
+fn open_config_manager() -> anyhow::Result<ConfigManager> {
     let config_manager =
         ConfigManager::new().inspect_err(|e| error!("Config manager init failed: {e}"))?;
     info!(
         "Config loaded (privacy_mode={})",
         config_manager.get().privacy_mode
     );
+
+    // Passphrase-protected secure storage starts locked. Headless and scripted
+    // launches can unlock on start via HIVE_MASTER_PASSPHRASE; otherwise keys
+    // stay unavailable until the passphrase is entered in Settings.
+    if config_manager.is_locked() {
+        match std::env::var("HIVE_MASTER_PASSPHRASE") {
+            Ok(passphrase) => {
+                // SAFETY: called during single-threaded startup, before any
+                // service threads that read the environment are spawned.
+                unsafe { std::env::remove_var("HIVE_MASTER_PASSPHRASE") };
+                match config_manager.unlock(&passphrase) {
+                    Ok(()) => info!("Secure storage unlocked on start"),
+                    Err(e) => warn!("Secure storage unlock failed: {e}"),
+                }
+            }
+            Err(_) => warn!(
+                "Secure storage is locked; API keys are unavailable until it is \
+                 unlocked in Settings → Security"
+            ),
+        }
+    }
+    Ok(config_manager)
+}
+
+/// Initialize backend services and store them as GPUI globals.
+fn init_services(cx: &mut App) -> anyhow::Result<()> {
+    let config_manager = open_config_manager()?;
     cx.set_global(AppConfig(config_manager));
 
     cx.set_global(AppSecurity(SecurityGateway::new()));
@@ -107,26 +140,13 @@ fn init_services(cx: &mut App) -> anyhow::Result<()> {
     cx.set_global(AppNotifications(
         hive_core::notifications::NotificationStore::new(),
     ));
+    if cx.global::<AppConfig>().0.is_locked() {
+        notify_storage_locked(cx, "API keys are unavailable.");
+    }
 
     // Build AI service from config (needed before wiring LearnerTierAdjuster).
     let config = cx.global::<AppConfig>().0.get().clone();
-    let ai_config = AiServiceConfig {
-        anthropic_api_key: config.anthropic_api_key.clone(),
-        openai_api_key: config.openai_api_key.clone(),
-        openrouter_api_key: config.openrouter_api_key.clone(),
-        google_api_key: config.google_api_key.clone(),
-        groq_api_key: config.groq_api_key.clone(),
-        huggingface_api_key: config.huggingface_api_key.clone(),
-        xai_api_key: config.xai_api_key.clone(),
-        litellm_url: config.litellm_url.clone(),
-        litellm_api_key: config.litellm_api_key.clone(),
-        ollama_url: config.ollama_url.clone(),
-        lmstudio_url: config.lmstudio_url.clone(),
-        local_provider_url: config.local_provider_url.clone(),
-        privacy_mode: config.privacy_mode,
-        default_model: config.default_model.clone(),
-        auto_routing: config.auto_routing,
-    };
+    let ai_config = AiServiceConfig::from(&config);
     cx.set_global(AppAiService(hive_ai::AiService::new(ai_config)));
     cx.global_mut::<AppAiService>().0.start_discovery();
     info!("AiService initialized");
@@ -192,6 +212,20 @@ fn init_services(cx: &mut App) -> anyhow::Result<()> {
                 .set_tier_adjuster(std::sync::Arc::new(adjuster));
             info!("LearnerTierAdjuster wired into ModelRouter");
 
+            // Persist live model telemetry, warm it from previous sessions,
+            // and blend learned model quality into routing.
+            let learner_telemetry = std::sync::Arc::new(hive_learn::LearnerTelemetry::new(
+                std::sync::Arc::clone(&learning),
+            ));
+            let router = cx.global_mut::<AppAiService>().0.router_mut();
+            match learning.recent_model_calls(5_000) {
+                Ok(calls) => router.telemetry().preload(calls),
+                Err(e) => warn!("Failed to load model telemetry: {e}"),
+            }
+            router.telemetry().set_sink(learner_telemetry.clone());
+            router.set_quality_source(learner_telemetry);
+            info!("LearnerTelemetry wired into ModelRouter");
+
             cx.set_global(AppLearning(learning));
         }
         Err(e) => {
@@ -284,7 +318,12 @@ fn init_services(cx: &mut App) -> anyhow::Result<()> {
     info!("SkillMarketplace initialized");
 
     // Persona registry — built-in agent roles.
-    cx.set_global(AppPersonas(hive_agents::personas::PersonaRegistry::new()));
+    // Learned prompts (and prompt A/B experiments) override the built-ins.
+    let mut personas = hive_agents::personas::PersonaRegistry::new();
+    if cx.has_global::<AppLearning>() {
+        personas.set_prompt_override(cx.global::<AppLearning>().0.clone());
+    }
+    cx.set_global(AppPersonas(personas));
     info!("PersonaRegistry initialized (6 built-in personas)");
 
     // Automation service — workflow engine.
@@ -296,12 +335,27 @@ fn init_services(cx: &mut App) -> anyhow::Result<()> {
             warn!("Workflow load error: {load_error}");
         }
     }
-    cx.set_global(AppAutomation(automation));
+    let automation = std::sync::Arc::new(std::sync::Mutex::new(automation));
+    cx.set_global(AppAutomation(automation.clone()));
     info!(
         "AutomationService initialized (loaded={}, failed={}, skipped={})",
         workflow_report.loaded, workflow_report.failed, workflow_report.skipped
     );
 
+    // Webhook receiver — verified inbound deliveries trigger workflows.
+    if config.webhook_receiver_enabled {
+        match start_webhook_receiver(&config, automation, workspace_root.clone()) {
+            Ok(()) => info!("Webhook receiver starting"),
+            Err(e) => warn!("Webhook receiver disabled: {e:#}"),
+        }
+    }
+
+    // Outbound webhooks — retries and dead letters from the persisted outbox.
+    match start_webhook_outbox() {
+        Ok(()) => info!("Webhook outbox initialized"),
+        Err(e) => warn!("Webhook outbox unavailable: {e:#}"),
+    }
+
     // Built-in MCP tool server — file I/O, command exec, search, git.
     cx.set_global(AppMcpServer(hive_agents::mcp_server::McpServer::new(
         workspace_root,
@@ -368,6 +422,30 @@ fn init_services(cx: &mut App) -> anyhow::Result<()> {
     } else {
         hive_blockchain::wallet_store::WalletStore::new()
     };
+    let wallets = std::sync::Arc::new(std::sync::Mutex::new(wallets));
+    // Wallet keys are encrypted with the master key, so they must move with
+    // it when the user switches between machine, passphrase and keyring mode.
+    let migration_wallets = std::sync::Arc::clone(&wallets);
+    cx.global::<AppConfig>()
+        .0
+        .add_secret_migration(Box::new(move |current, target, staged| {
+            let mut migrated = migration_wallets
+                .lock()
+                .map_err(|_| anyhow::anyhow!("wallet store lock poisoned"))?
+                .clone();
+            let count = migrated.reencrypt_keys(current, target)?;
+            if count > 0 {
+                staged.write(wallet_path.clone(), migrated.to_json()?);
+            }
+            let wallets = std::sync::Arc::clone(&migration_wallets);
+            staged.on_commit(move || {
+                if let Ok(mut store) = wallets.lock() {
+                    *store = migrated;
+                }
+                info!("Re-encrypted {count} wallet keys with the new master key");
+            });
+            Ok(())
+        }));
     cx.set_global(AppWallets(wallets));
     info!("WalletStore initialized");
 
@@ -390,9 +468,51 @@ fn init_services(cx: &mut App) -> anyhow::Result<()> {
 
     // Project management hub — always create, providers added when tokens configured.
     let pm = std::sync::Arc::new(hive_integrations::project_management::ProjectManagementHub::new());
-    cx.set_global(AppProjectManagement(pm));
+    cx.set_global(AppProjectManagement(std::sync::Arc::clone(&pm)));
     info!("ProjectManagementHub initialized");
 
+    // Kanban store shared by the Kanban panel and the coordinator's tracker,
+    // which also pushes card moves to the linked Jira/Linear/Asana issues.
+    match hive_core::kanban::KanbanStore::open_default() {
+        Ok(store) => {
+            match hive_ui::panels::kanban::KanbanData::migrate_store(&store) {
+                Ok(true) => info!("Converted kanban.json to the shared board format"),
+                Ok(false) => {}
+                Err(e) => warn!("Failed to convert kanban.json: {e:#}"),
+            }
+            let mut tracker = hive_agents::KanbanTracker::new(store.clone());
+            match hive_integrations::project_management::SyncState::default_path() {
+                Ok(state_path) => tracker = tracker.with_sync(pm, state_path),
+                Err(e) => warn!("Kanban sync disabled: {e:#}"),
+            }
+            // Agents get their own service so runs on background threads
+            // never touch the chat panel's. The shield classifies their
+            // requests for the routing rules, as it does for chat.
+            let mut agent_ai = hive_ai::AiService::new(AiServiceConfig::from(&config));
+            if config.shield_enabled {
+                agent_ai.set_shield(Some(cx.global::<AppShield>().0.clone()));
+            }
+            cx.set_global(AppOrchestration {
+                executor: std::sync::Arc::new(agent_ai),
+                tracker: std::sync::Arc::new(tracker),
+                remote: None,
+            });
+            cx.set_global(AppKanban(store));
+            info!("Kanban store and coordinator tracker initialized");
+        }
+        Err(e) => warn!("Kanban store unavailable: {e:#}"),
+    }
+
+    // Messaging bot — answers mentions and DMs with the agents' executor.
+    if config.messaging_bot_enabled && cx.has_global::<AppOrchestration>() {
+        let executor = cx.global::<AppOrchestration>().executor.clone();
+        let personas = cx.global::<AppPersonas>().0.clone();
+        match start_messaging_bot(executor, personas) {
+            Ok(()) => info!("Messaging bot starting"),
+            Err(e) => warn!("Messaging bot disabled: {e:#}"),
+        }
+    }
+
     // Knowledge hub — always create, providers added when tokens configured.
     let knowledge = std::sync::Arc::new(hive_integrations::knowledge::KnowledgeHub::new());
     cx.set_global(AppKnowledge(knowledge));
@@ -456,8 +576,8 @@ fn init_services(cx: &mut App) -> anyhow::Result<()> {
     cx.set_global(AppGcp(gcp));
     info!("GcpClient initialized");
 
-    // Docs indexer — workspace-scoped documentation search.
-    match hive_integrations::docs_indexer::DocsIndexer::new() {
+    // Docs indexer — crawled sites and offline doc sets saved under ~/.hive/docs.
+    match hive_integrations::docs_indexer::DocsIndexer::open_default() {
         Ok(indexer) => {
             cx.set_global(AppDocsIndexer(std::sync::Arc::new(indexer)));
             info!("DocsIndexer initialized");
@@ -498,6 +618,39 @@ fn init_services(cx: &mut App) -> anyhow::Result<()> {
         info!("MCP integration tools wired to live services");
     }
 
+    // Unified search index — rebuilt from every knowledge source off the UI
+    // thread; sources keep it fresh afterwards as they change.
+    match hive_core::SearchHub::open_default() {
+        Ok(hub) => {
+            let hub = std::sync::Arc::new(hub);
+            cx.set_global(AppSearch(std::sync::Arc::clone(&hub)));
+
+            let specs: Vec<_> = cx.global::<AppSpecs>().0.specs.values().map(Into::into).collect();
+            let memory = std::sync::Arc::clone(&cx.global::<AppCollectiveMemory>().0);
+            let rag = std::sync::Arc::clone(&cx.global::<AppRagService>().0);
+            let docs = cx.has_global::<AppDocsIndexer>().then(|| cx.global::<AppDocsIndexer>().0.clone());
+            let knowledge = std::sync::Arc::clone(&cx.global::<AppKnowledge>().0);
+            let vault = cx
+                .global::<AppConfig>()
+                .0
+                .get()
+                .obsidian_vault_path
+                .filter(|p| !p.is_empty())
+                .map(std::path::PathBuf::from);
+            let spawned = std::thread::Builder::new()
+                .name("hive-search-index".into())
+                .spawn(move || {
+                    let notes = knowledge_notes(&knowledge, vault);
+                    index_knowledge_sources(&hub, specs, notes, &memory, &rag, docs.as_deref())
+                });
+            if let Err(e) = spawned {
+                warn!("Search indexer thread failed to start: {e}");
+            }
+            info!("SearchHub initialized");
+        }
+        Err(e) => warn!("SearchHub init failed: {e}"),
+    }
+
     // Channel store — AI agent messaging channels.
     let mut channel_store = hive_core::channels::ChannelStore::new();
     channel_store.ensure_default_channels();
@@ -506,16 +659,16 @@ fn init_services(cx: &mut App) -> anyhow::Result<()> {
 
     // P2P network node — federation, peer discovery, WebSocket server.
     //
-    // The node is created with the persisted config and started on a dedicated
-    // background thread with its own tokio runtime.  We store a *query handle*
-    // (a separate HiveNode with matching identity) in the GPUI global for
-    // read-only access to peer info.  The running node lives on the background
-    // thread for the lifetime of the application.
+    // The node is started on a dedicated background thread that owns its
+    // tokio runtime, then handed back so the UI (and the services attached
+    // to it) share the one running instance. The thread parks the runtime so
+    // the node's server, discovery and heartbeat tasks keep running.
     {
-        let network_config_path = HiveConfig::base_dir()
-            .map(|d| d.join("network.json"))
-            .unwrap_or_else(|_| std::path::PathBuf::from("network.json"));
-        let net_config = hive_network::NetworkConfig::load_or_default(&network_config_path);
+        let network_dir =
+            HiveConfig::base_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+        let net_config =
+            hive_network::NetworkConfig::load_or_default(&network_dir.join("network.json"));
+        let trust_path = network_dir.join("trusted_peers.json");
 
         let node_name = std::env::var("HIVE_NODE_NAME").unwrap_or_else(|_| {
             #[cfg(unix)]
@@ -533,15 +686,16 @@ fn init_services(cx: &mut App) -> anyhow::Result<()> {
             }
         });
 
-        // Create the global handle — a separate node instance for read-only queries.
-        let global_node = std::sync::Arc::new(
-            hive_network::HiveNode::with_defaults(&node_name),
+        // The identity (and its signing key) persists so trusted peers keep
+        // recognising this node across restarts.
+        let identity = hive_network::NodeIdentity::load_or_generate(
+            &network_dir.join("identity.json"),
+            &node_name,
         );
-        cx.set_global(AppNetwork(global_node));
 
         let listen_addr = net_config.listen_addr;
+        let (node_tx, node_rx) = mpsc::channel();
 
-        // Start the *real* node on a background thread with its own tokio runtime.
         std::thread::Builder::new()
             .name("hive-p2p".into())
             .spawn(move || {
@@ -550,27 +704,127 @@ fn init_services(cx: &mut App) -> anyhow::Result<()> {
                     .build()
                     .expect("P2P tokio runtime");
                 rt.block_on(async {
-                    let node = hive_network::HiveNode::with_defaults(&node_name);
-                    // Replace the default config with the loaded one.
-                    let identity = node.identity().clone();
-                    let mut node = hive_network::HiveNode::new(identity, net_config);
+                    let mut node = hive_network::HiveNode::new(identity, net_config)
+                        .with_trust_store(&trust_path);
 
                     match node.start().await {
                         Ok(()) => info!("P2P network started — listening on {listen_addr}"),
-                        Err(e) => {
-                            error!("P2P network start failed (non-fatal): {e}");
-                            return;
-                        }
+                        Err(e) => error!("P2P network start failed (non-fatal): {e}"),
                     }
-                    // Park the runtime so spawned tasks (server, discovery,
-                    // heartbeat) keep running for the lifetime of the app.
-                    loop {
-                        tokio::time::sleep(std::time::Duration::from_secs(3600)).await;
+                    let node = std::sync::Arc::new(node);
+                    if node_tx
+                        .send((node, tokio::runtime::Handle::current()))
+                        .is_err()
+                    {
+                        return;
                     }
+                    std::future::pending::<()>().await;
                 });
             })
             .ok();
-        info!("P2P network node initialized (background start in progress)");
+
+        match node_rx.recv() {
+            Ok((node, runtime)) => {
+                cx.set_global(AppNetwork { node, runtime });
+                info!("P2P network node initialized");
+            }
+            Err(_) => error!("P2P network thread exited before the node was created"),
+        }
+    }
+
+    // Remote execution — serves agent work sent by trusted peers and lets the
+    // coordinator and swarms offload theirs. Capability detection probes local
+    // AI providers, so the service joins `AppOrchestration` once it is ready.
+    if config.remote_exec_enabled
+        && cx.has_global::<AppOrchestration>()
+        && cx.has_global::<AppNetwork>()
+    {
+        let node = cx.global::<AppNetwork>().node.clone();
+        let executor = cx.global::<AppOrchestration>().executor.clone();
+        match start_remote_exec(node, executor) {
+            Ok(service_rx) => {
+                cx.spawn(async move |app: &mut AsyncApp| {
+                    loop {
+                        match service_rx.try_recv() {
+                            Ok(service) => {
+                                let _ = app.update(|cx| {
+                                    cx.global_mut::<AppOrchestration>().remote = Some(service);
+                                });
+                                info!("Remote execution attached to the network node");
+                                return;
+                            }
+                            Err(mpsc::TryRecvError::Empty) => {}
+                            Err(mpsc::TryRecvError::Disconnected) => return,
+                        }
+                        app.background_executor()
+                            .timer(Duration::from_millis(250))
+                            .await;
+                    }
+                })
+                .detach();
+            }
+            Err(e) => warn!("Remote execution disabled: {e:#}"),
+        }
+    }
+
+    // Replication — shares channels, specs and kanban boards with trusted
+    // peers. The service attaches on the P2P runtime and joins the app as
+    // `AppReplication` once its saved documents are loaded.
+    if config.replication_enabled && cx.has_global::<AppNetwork>() {
+        let network = cx.global::<AppNetwork>();
+        match start_replication(network.node.clone(), &network.runtime) {
+            Ok(service_rx) => {
+                cx.spawn(async move |app: &mut AsyncApp| {
+                    loop {
+                        match service_rx.try_recv() {
+                            Ok(service) => {
+                                let _ = app.update(|cx| cx.set_global(AppReplication(service)));
+                                info!("Replication attached to the network node");
+                                return;
+                            }
+                            Err(mpsc::TryRecvError::Empty) => {}
+                            Err(mpsc::TryRecvError::Disconnected) => return,
+                        }
+                        app.background_executor()
+                            .timer(Duration::from_millis(250))
+                            .await;
+                    }
+                })
+                .detach();
+            }
+            Err(e) => warn!("Replication disabled: {e:#}"),
+        }
+    }
+
+    // Fleet sharing — trades noised per-task statistics (never raw learning
+    // data) with trusted peers. Joins the app as `AppFleetSharing` once the
+    // handler is attached to the node.
+    if config.fleet_sharing_enabled && cx.has_global::<AppNetwork>() {
+        let network = cx.global::<AppNetwork>();
+        let fleet = std::sync::Arc::clone(&cx.global::<AppFleetLearning>().0);
+        let shield = hive_shield::HiveShield::new(config.shield.clone());
+        match start_fleet_sharing(network.node.clone(), fleet, shield, &network.runtime) {
+            Ok(service_rx) => {
+                cx.spawn(async move |app: &mut AsyncApp| {
+                    loop {
+                        match service_rx.try_recv() {
+                            Ok(service) => {
+                                let _ = app.update(|cx| cx.set_global(AppFleetSharing(service)));
+                                info!("Fleet sharing attached to the network node");
+                                return;
+                            }
+                            Err(mpsc::TryRecvError::Empty) => {}
+                            Err(mpsc::TryRecvError::Disconnected) => return,
+                        }
+                        app.background_executor()
+                            .timer(Duration::from_millis(250))
+                            .await;
+                    }
+                })
+                .detach();
+            }
+            Err(e) => warn!("Fleet sharing disabled: {e:#}"),
+        }
     }
 
     // Auto-update service — checks GitHub releases for newer versions.
@@ -581,7 +835,326 @@ fn init_services(cx: &mut App) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Serve inbound webhooks on a background thread and run the workflows
+/// each verified delivery triggers. Fails on a bad listen address or a
+/// source without a secret.
+fn start_webhook_receiver(
+    config: &HiveConfig,
+    automation: std::sync::Arc<std::sync::Mutex<hive_agents::AutomationService>>,
+    working_dir: std::path::PathBuf,
+) -> anyhow::Result<()> {
+    use anyhow::Context as _;
+    use hive_integrations::webhooks::{
+        DeliveryLog, ReceiverConfig, WebhookEventBus, WebhookReceiver,
+    };
+
+    let mut receiver_config = ReceiverConfig {
+        sources: ReceiverConfig::load_sources(&ReceiverConfig::sources_path()?)?,
+        ..ReceiverConfig::default()
+    };
+    if let Some(addr) = &config.webhook_receiver_addr {
+        receiver_config.bind_addr = addr
+            .parse()
+            .with_context(|| format!("Invalid webhook receiver address {addr:?}"))?;
+    }
+    let log = std::sync::Arc::new(DeliveryLog::open(&DeliveryLog::default_path()?)?);
+    let bus = WebhookEventBus::new();
+    let subscription = bus.subscribe("*");
+    let receiver = WebhookReceiver::new(receiver_config, log, bus)?;
+
+    std::thread::Builder::new()
+        .name("hive-webhooks".into())
+        .spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("webhook tokio runtime");
+            rt.block_on(async move {
+                // The receiver holds the bus, so dispatch runs for the
+                // lifetime of the app.
+                let _handle = match receiver.start().await {
+                    Ok(handle) => handle,
+                    Err(e) => {
+                        error!("Webhook receiver failed to start: {e:#}");
+                        return;
+                    }
+                };
+                hive_agents::dispatch_webhook_workflows(automation, subscription, working_dir)
+                    .await;
+            });
+        })?;
+    Ok(())
+}
+
+/// Connect to the platform in `~/.hive/messaging_bot.json` and answer its
+/// mentions and DMs on a background thread until the event stream ends.
+fn start_messaging_bot(
+    executor: std::sync::Arc<hive_ai::AiService>,
+    personas: hive_agents::personas::PersonaRegistry,
+) -> anyhow::Result<()> {
+    use hive_agents::{BotSettings, MessagingBot};
+
+    let path = BotSettings::default_path()?;
+    let settings = BotSettings::load(&path)?
+        .ok_or_else(|| anyhow::anyhow!("{} does not exist", path.display()))?;
+    let bot =
+        MessagingBot::new(settings.provider()?, executor, settings.bot).with_personas(personas);
+
+    std::thread::Builder::new()
+        .name("hive-messaging-bot".into())
+        .spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("messaging bot tokio runtime");
+            if let Err(e) = rt.block_on(bot.run()) {
+                error!("Messaging bot stopped: {e:#}");
+            }
+        })?;
+    Ok(())
+}
+
+/// Attach remote execution to the network node and run jobs from trusted
+/// peers on a background thread. The service is sent back over the returned
+/// channel once the node's capabilities are detected.
+fn start_remote_exec(
+    node: std::sync::Arc<hive_network::HiveNode>,
+    executor: std::sync::Arc<hive_ai::AiService>,
+) -> anyhow::Result<mpsc::Receiver<std::sync::Arc<hive_agents::RemoteExecService>>> {
+    use hive_agents::skills::SkillsRegistry;
+    use hive_agents::{NodeCapabilities, RemoteExecService};
+
+    let (service_tx, service_rx) = mpsc::channel();
+    std::thread::Builder::new()
+        .name("hive-remote-exec".into())
+        .spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("remote execution tokio runtime");
+            rt.block_on(async {
+                let capabilities = NodeCapabilities::detect(&SkillsRegistry::new()).await;
+                let service =
+                    std::sync::Arc::new(RemoteExecService::attach(node, capabilities).await);
+                if service_tx.send(std::sync::Arc::clone(&service)).is_err() {
+                    return;
+                }
+                if let Err(e) = service.serve(executor).await {
+                    error!("Remote execution stopped: {e:#}");
+                }
+            });
+        })?;
+    Ok(service_rx)
+}
+
+/// Attach CRDT replication to the network node on the P2P runtime, loading
+/// the documents saved under `~/.hive/replicas`, then reconcile with every
+/// connected peer each minute so offline edits merge after reconnecting.
+/// The service is sent back over the returned channel once attached.
+fn start_replication(
+    node: std::sync::Arc<hive_network::HiveNode>,
+    runtime: &tokio::runtime::Handle,
+) -> anyhow::Result<mpsc::Receiver<std::sync::Arc<hive_agents::ReplicationService>>> {
+    use hive_agents::ReplicationService;
+
+    let storage = HiveConfig::base_dir()?.join("replicas");
+    let (service_tx, service_rx) = mpsc::channel();
+    runtime.spawn(async move {
+        let service = match ReplicationService::attach(node, Some(storage)).await {
+            Ok(service) => std::sync::Arc::new(service),
+            Err(e) => {
+                warn!("Replication disabled: {e:#}");
+                return;
+            }
+        };
+        if service_tx.send(std::sync::Arc::clone(&service)).is_err() {
+            return;
+        }
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            if let Err(e) = service.sync_all().await {
+                warn!("Replication sync failed: {e:#}");
+            }
+        }
+    });
+    Ok(service_rx)
+}
+
+/// Attach fleet sharing to the network node on the P2P runtime, resuming the
+/// privacy budget saved next to the fleet learning database, then offer this
+/// node's statistics to connected trusted peers every ten minutes. Peers in
+/// one aggregation window all get the same release. The service is sent back
+/// over the returned channel once attached.
+fn start_fleet_sharing(
+    node: std::sync::Arc<hive_network::HiveNode>,
+    fleet: std::sync::Arc<std::sync::Mutex<hive_ai::FleetLearningService>>,
+    shield: hive_shield::HiveShield,
+    runtime: &tokio::runtime::Handle,
+) -> anyhow::Result<mpsc::Receiver<std::sync::Arc<hive_agents::FleetSharingService>>> {
+    use hive_agents::{FleetSharing, FleetSharingPolicy, FleetSharingService};
+
+    let sharing = FleetSharing::with_shield(FleetSharingPolicy::default(), shield)
+        .with_state_file(HiveConfig::base_dir()?.join("fleet_sharing.json"))?;
+    let (service_tx, service_rx) = mpsc::channel();
+    runtime.spawn(async move {
+        let service = std::sync::Arc::new(FleetSharingService::attach(node, fleet, sharing).await);
+        if service_tx.send(std::sync::Arc::clone(&service)).is_err() {
+            return;
+        }
+        let mut interval = tokio::time::interval(Duration::from_secs(10 * 60));
+        loop {
+            interval.tick().await;
+            match service.share_all().await {
+                Ok(0) => {}
+                Ok(sent) => info!("Shared fleet statistics with {sent} peer(s)"),
+                Err(e) => warn!("Fleet sharing failed: {e:#}"),
+            }
+        }
+    });
+    Ok(service_rx)
+}
+
+/// Load the registered outbound webhooks over the persisted outbox and
+/// retry queued deliveries on a background thread, including those left
+/// over from the last run.
+fn start_webhook_outbox() -> anyhow::Result<()> {
+    use hive_integrations::webhooks::{Outbox, WebhookRegistry};
+
+    let outbox = std::sync::Arc::new(Outbox::open(&Outbox::default_path()?)?);
+    let registry = WebhookRegistry::default_path()
+        .and_then(|path| WebhookRegistry::load(&path, outbox))
+        .map_err(anyhow::Error::msg)?;
+
+    std::thread::Builder::new()
+        .name("hive-webhook-outbox".into())
+        .spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("webhook outbox tokio runtime");
+            rt.block_on(registry.run_retries(Duration::from_secs(60)));
+        })?;
+    Ok(())
+}
+
 /// Register global keyboard shortcuts and action handlers.
+/// Rebuild every source in the unified search index. Runs on a background
+/// thread at startup; each source is replaced wholesale so deleted items drop
+/// out of the index.
+fn index_knowledge_sources(
+    hub: &hive_core::SearchHub,
+    specs: Vec<hive_core::SearchDocument>,
+    notes: Vec<hive_core::SearchDocument>,
+    memory: &std::sync::Mutex<hive_agents::collective_memory::CollectiveMemory>,
+    rag: &std::sync::Mutex<hive_ai::RagService>,
+    docs: Option<&hive_integrations::docs_indexer::DocsIndexer>,
+) {
+    use hive_core::{SearchDocument, SearchSource};
+
+    let conversations: Vec<SearchDocument> = hive_core::ConversationStore::new()
+        .and_then(|store| {
+            let summaries = store.list_summaries()?;
+            Ok(summaries
+                .iter()
+                .filter_map(|summary| store.load(&summary.id).ok())
+                .map(|conversation| (&conversation).into())
+                .collect())
+        })
+        .unwrap_or_else(|e| {
+            warn!("Search: failed to read conversations: {e}");
+            Vec::new()
+        });
+    let memories: Vec<SearchDocument> = memory
+        .lock()
+        .map_err(|e| e.to_string())
+        .and_then(|m| m.recall("", None, None, 100_000))
+        .map(|entries| entries.iter().map(Into::into).collect())
+        .unwrap_or_else(|e| {
+            warn!("Search: failed to read collective memory: {e}");
+            Vec::new()
+        });
+    let code = rag
+        .lock()
+        .map(|r| r.search_documents())
+        .unwrap_or_default();
+    let docs = docs.map(|d| d.search_documents()).unwrap_or_default();
+
+    for (source, documents) in [
+        (SearchSource::Conversation, conversations),
+        (SearchSource::Note, notes),
+        (SearchSource::Spec, specs),
+        (SearchSource::Memory, memories),
+        (SearchSource::Code, code),
+        (SearchSource::Docs, docs),
+    ] {
+        match hub.replace_source(source, &documents) {
+            Ok(changed) => info!("Search: {changed} of {} {source} documents re-indexed", documents.len()),
+            Err(e) => warn!("Search: failed to index {source}: {e}"),
+        }
+    }
+}
+
+/// Pages of the configured Obsidian vault and of every knowledge base
+/// registered with the hub, as search documents.
+fn knowledge_notes(
+    knowledge: &hive_integrations::knowledge::KnowledgeHub,
+    vault: Option<std::path::PathBuf>,
+) -> Vec<hive_core::SearchDocument> {
+    use hive_integrations::knowledge::{KBPlatform, ObsidianProvider};
+
+    /// Upper bound on pages fetched from each remote knowledge base.
+    const MAX_REMOTE_PAGES: usize = 2_000;
+
+    let rt = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(rt) => rt,
+        Err(e) => {
+            warn!("Search: failed to start knowledge runtime: {e}");
+            return Vec::new();
+        }
+    };
+    rt.block_on(async {
+        let mut notes = Vec::new();
+        if let Some(vault) = vault {
+            let mut obsidian = ObsidianProvider::new(vault);
+            match obsidian.index_vault().await {
+                Ok(_) => notes.extend(obsidian.index().search_documents(obsidian.vault_path())),
+                Err(e) => warn!("Search: failed to index Obsidian vault: {e:#}"),
+            }
+        }
+
+        for platform in knowledge.platforms() {
+            if platform == KBPlatform::Obsidian {
+                continue;
+            }
+            let mut pending = vec![None];
+            let mut fetched = 0;
+            while let Some(parent) = pending.pop()
+                && fetched < MAX_REMOTE_PAGES
+            {
+                let summaries = match knowledge.list_pages(platform, parent.as_deref()).await {
+                    Ok(summaries) => summaries,
+                    Err(e) => {
+                        warn!("Search: failed to list {platform} pages: {e:#}");
+                        continue;
+                    }
+                };
+                for summary in summaries.into_iter().take(MAX_REMOTE_PAGES - fetched) {
+                    fetched += 1;
+                    match knowledge.get_page(platform, &summary.id).await {
+                        Ok(page) => notes.push((&page).into()),
+                        Err(e) => warn!("Search: failed to read {platform} page {}: {e:#}", summary.id),
+                    }
+                    if summary.has_children {
+                        pending.push(Some(summary.id));
+                    }
+                }
+            }
+        }
+        notes
+    })
+}
+
 fn register_actions(cx: &mut App) {
     // macOS uses Cmd for shortcuts; all other platforms use Ctrl.
     #[cfg(target_os = "macos")]
@@ -768,6 +1341,19 @@ fn open_main_window(cx: &mut App) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Post a warning pointing at the Settings unlock prompt.
+fn notify_storage_locked(cx: &mut App, message: &str) {
+    if cx.has_global::<AppNotifications>() {
+        cx.global_mut::<AppNotifications>().0.push(
+            AppNotification::new(
+                NotificationType::Warning,
+                format!("{message} Unlock secure storage in Settings → Security."),
+            )
+            .with_title("Secure Storage Locked"),
+        );
+    }
+}
+
 /// Post an error notification into the global store.
 fn notify_error(cx: &mut App, message: impl Into<String>) {
     if cx.has_global::<AppNotifications>() {
@@ -787,6 +1373,30 @@ fn main() {
 
     HiveConfig::ensure_dirs().expect("Failed to create config directories");
 
+    // Headless subcommands run without opening a window.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("eval") {
+        if let Err(e) = eval_cli::run(&args[1..]) {
+            eprintln!("hive eval: {e:#}");
+            std::process::exit(1);
+        }
+        return;
+    }
+    if args.first().map(String::as_str) == Some("docs") {
+        if let Err(e) = docs_cli::run(&args[1..]) {
+            eprintln!("hive docs: {e:#}");
+            std::process::exit(1);
+        }
+        return;
+    }
+    if args.first().map(String::as_str) == Some("relay") {
+        if let Err(e) = relay_cli::run(&args[1..]) {
+            eprintln!("hive relay: {e:#}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
     Application::new().with_assets(Assets).run(|cx| {
         gpui_component::init(cx);
 
@@ -863,6 +1473,33 @@ fn main() {
         // display the app in the dock.
         cx.activate(true);
 
+        // Idle auto-lock for passphrase-protected secure storage. Cheap check,
+        // so a 30s cadence keeps the lock within half a minute of the timeout.
+        if cx.has_global::<AppConfig>() {
+            cx.spawn(async move |app: &mut AsyncApp| {
+                loop {
+                    app.background_executor()
+                        .timer(Duration::from_secs(30))
+                        .await;
+                    let result = app.update(|cx| {
+                        if cx.global::<AppConfig>().0.lock_if_idle() {
+                            info!("Secure storage auto-locked after inactivity");
+                            // Drop providers built with the now-cleared keys.
+                            let config = cx.global::<AppConfig>().0.get();
+                            cx.global_mut::<AppAiService>()
+                                .0
+                                .reload_providers(AiServiceConfig::from(&config));
+                            notify_storage_locked(cx, "Locked after inactivity.");
+                        }
+                    });
+                    if result.is_err() {
+                        break;
+                    }
+                }
+            })
+            .detach();
+        }
+
         // Background update check — runs 5s after startup and every 4 hours.
         // The blocking HTTP call runs on an OS thread; results are polled on the
         // main thread to update the status bar.
@@ -0,0 +1,117 @@
+//! `hive relay` — run a headless relay/rendezvous node.
+//!
+//! Peers behind NAT register with the relay, look each other up through it,
+//! and tunnel end-to-end encrypted connections through it when they cannot
+//! dial each other directly.
+
+use std::net::SocketAddr;
+
+use anyhow::{Context, Result, bail};
+use hive_core::config::HiveConfig;
+use hive_network::{HiveNode, NetworkConfig, NodeIdentity};
+
+const USAGE: &str = "\
+Usage:
+  hive relay [--listen <addr:port>] [--name <name>] [--trusted-only]
+
+Options:
+  --listen <addr:port>  Address to accept peers on (default 0.0.0.0:9470)
+  --name <name>         Name the relay announces (default hive-relay)
+  --trusted-only        Only serve peers in trusted_peers.json
+
+Peers add the printed entry to \"relays\" in their network.json.";
+
+const DEFAULT_NAME: &str = "hive-relay";
+
+/// Entry point for `hive relay <args>`.
+pub fn run(args: &[String]) -> Result<()> {
+    let Some(options) = Options::parse(args)? else {
+        println!("{USAGE}");
+        return Ok(());
+    };
+
+    let base_dir = HiveConfig::base_dir()?;
+    // Separate from the desktop node's identity so both can run on one host.
+    let identity = NodeIdentity::load_or_generate(
+        &base_dir.join("relay_identity.json"),
+        options.name.as_deref().unwrap_or(DEFAULT_NAME),
+    );
+    let mut config = NetworkConfig {
+        relay_mode: true,
+        discovery_enabled: false,
+        allow_untrusted_peers: !options.trusted_only,
+        ..NetworkConfig::default()
+    };
+    if let Some(listen_addr) = options.listen {
+        config.listen_addr = listen_addr;
+    }
+    let listen_addr = config.listen_addr;
+
+    let mut node = HiveNode::new(identity, config);
+    if options.trusted_only {
+        node = node.with_trust_store(&base_dir.join("trusted_peers.json"));
+    }
+
+    let runtime = tokio::runtime::Runtime::new().context("Failed to start async runtime")?;
+    runtime.block_on(async {
+        node.start().await?;
+        println!(
+            "Relay {} listening on {listen_addr}",
+            node.peer_id().short()
+        );
+        println!("Relay entry: {}@{listen_addr}", node.peer_id());
+        if listen_addr.ip().is_unspecified() {
+            println!(
+                "(replace {} with this host's public address)",
+                listen_addr.ip()
+            );
+        }
+
+        tokio::signal::ctrl_c()
+            .await
+            .context("Failed to wait for Ctrl-C")?;
+        println!("Stopping relay");
+        node.stop().await;
+        Ok(())
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Argument parsing
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Default)]
+struct Options {
+    listen: Option<SocketAddr>,
+    name: Option<String>,
+    trusted_only: bool,
+}
+
+impl Options {
+    /// Parse the flags, or `None` when help was requested.
+    fn parse(args: &[String]) -> Result<Option<Self>> {
+        let mut options = Self::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let mut value = |flag: &str| {
+                iter.next()
+                    .cloned()
+                    .with_context(|| format!("{flag} needs a value"))
+            };
+            match arg.as_str() {
+                "--listen" => {
+                    options.listen = Some(
+                        value(arg.as_str())?
+                            .parse()
+                            .context("--listen must be an address like 0.0.0.0:9470")?,
+                    );
+                }
+                "--name" => options.name = Some(value(arg.as_str())?),
+                "--trusted-only" => options.trusted_only = true,
+                "help" | "--help" | "-h" => return Ok(None),
+                other => bail!("Unknown option '{other}'\n\n{USAGE}"),
+            }
+        }
+        Ok(Some(options))
+    }
+}
@@ -3,8 +3,10 @@ use std::sync::{Arc, Mutex};
 use serde::{Deserialize, Serialize};
 use tokio::runtime::Handle;
 
+use hive_ai::routing::ClassificationContext;
 use hive_ai::service::AiService;
 use hive_ai::types::{ChatMessage, MessageRole};
+use hive_shield::{HiveShield, ShieldConfig};
 
 use crate::email::UnifiedEmail;
 
@@ -109,6 +111,15 @@ impl ComposeAgent {
             format!("Instruction: {instruction}")
         };
 
+        // Email bodies often carry personal data; classify them so routing
+        // rules can keep such drafts on allowed providers.
+        let context = ClassificationContext {
+            data_classification: Some(
+                HiveShield::new(ShieldConfig::default()).classify(&user_content),
+            ),
+            ..Default::default()
+        };
+
         let messages = vec![
             ChatMessage::text(MessageRole::System, system_prompt),
             ChatMessage::text(MessageRole::User, &user_content),
@@ -126,7 +137,7 @@ impl ComposeAgent {
             let mut svc = ai_service
                 .lock()
                 .map_err(|e| format!("Lock error: {e}"))?;
-            svc.chat(messages, &model, None)
+            svc.chat(messages, &model, None, None, Some(&context))
                 .await
                 .map_err(|e| format!("AI chat error: {e}"))
         })?;
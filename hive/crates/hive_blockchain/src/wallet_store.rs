@@ -6,6 +6,7 @@ use aes_gcm::aead::{Aead, KeyInit};
 use aes_gcm::{Aes256Gcm, Key, Nonce};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use hive_core::SecureStorage;
 use serde::{Deserialize, Serialize};
 use tracing::info;
 
@@ -51,6 +52,19 @@ impl fmt::Display for Chain {
     }
 }
 
+/// How a wallet's private key is encrypted at rest.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyProtection {
+    /// Legacy scheme: AES-256-GCM with a key derived from a per-wallet
+    /// password and a fixed salt (see [`encrypt_key`]).
+    #[default]
+    Password,
+    /// AES-256-GCM with the [`SecureStorage`] master key, so the wallet is
+    /// covered by the master passphrase / OS keyring.
+    SecureStorage,
+}
+
 /// A stored wallet entry with encrypted private key material.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WalletEntry {
@@ -60,13 +74,16 @@ pub struct WalletEntry {
     pub address: String,
     pub encrypted_key: Vec<u8>,
     pub created_at: DateTime<Utc>,
+    /// Missing in stores written before master-key support, hence `Password`.
+    #[serde(default)]
+    pub protection: KeyProtection,
 }
 
 /// Manages a collection of wallets with encrypted key storage.
 ///
 /// Wallets are indexed by their UUID. The store can be persisted to and
 /// loaded from a JSON file on disk.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WalletStore {
     wallets: HashMap<String, WalletEntry>,
 }
@@ -79,13 +96,47 @@ impl WalletStore {
         }
     }
 
-    /// Add a wallet to the store. Returns the generated wallet ID.
+    /// Add a wallet whose key was encrypted with [`encrypt_key`] (legacy
+    /// password scheme). Returns the generated wallet ID.
     pub fn add_wallet(
         &mut self,
         name: String,
         chain: Chain,
         address: String,
         encrypted_key: Vec<u8>,
+    ) -> String {
+        self.insert_entry(name, chain, address, encrypted_key, KeyProtection::Password)
+    }
+
+    /// Add a wallet, encrypting `private_key` with the SecureStorage master
+    /// key. Returns the generated wallet ID.
+    pub fn add_wallet_secure(
+        &mut self,
+        name: String,
+        chain: Chain,
+        address: String,
+        private_key: &[u8],
+        storage: &SecureStorage,
+    ) -> Result<String> {
+        let encrypted_key = storage
+            .encrypt_bytes(private_key)
+            .context("failed to encrypt wallet key")?;
+        Ok(self.insert_entry(
+            name,
+            chain,
+            address,
+            encrypted_key,
+            KeyProtection::SecureStorage,
+        ))
+    }
+
+    fn insert_entry(
+        &mut self,
+        name: String,
+        chain: Chain,
+        address: String,
+        encrypted_key: Vec<u8>,
+        protection: KeyProtection,
     ) -> String {
         let id = uuid::Uuid::new_v4().to_string();
         let entry = WalletEntry {
@@ -95,6 +146,7 @@ impl WalletStore {
             address,
             encrypted_key,
             created_at: Utc::now(),
+            protection,
         };
         info!(wallet_id = %id, chain = ?chain, "wallet added to store");
         self.wallets.insert(id.clone(), entry);
@@ -120,10 +172,66 @@ impl WalletStore {
         self.wallets.get(id)
     }
 
-    /// Decrypt the private key for a specific wallet entry.
-    pub fn decrypt_wallet_key(&self, id: &str, password: &str) -> Result<Vec<u8>> {
+    /// Decrypt the private key for a specific wallet entry with the
+    /// SecureStorage master key. Fails while the storage is locked, and for
+    /// legacy password-protected entries that have not been migrated yet
+    /// (see [`migrate_wallet_key`](Self::migrate_wallet_key)).
+    pub fn decrypt_wallet_key(&self, id: &str, storage: &SecureStorage) -> Result<Vec<u8>> {
         let wallet = self.get_wallet(id).ok_or_else(|| anyhow::anyhow!("wallet not found: {id}"))?;
-        decrypt_key(&wallet.encrypted_key, password).context("failed to decrypt wallet key")
+        if wallet.protection == KeyProtection::Password {
+            anyhow::bail!("wallet {id} uses a legacy password; migrate it to secure storage first");
+        }
+        storage
+            .decrypt_bytes(&wallet.encrypted_key)
+            .context("failed to decrypt wallet key")
+    }
+
+    /// Re-encrypt a legacy password-protected wallet with the SecureStorage
+    /// master key. No-op for entries that are already migrated.
+    pub fn migrate_wallet_key(
+        &mut self,
+        id: &str,
+        password: &str,
+        storage: &SecureStorage,
+    ) -> Result<()> {
+        let wallet = self
+            .wallets
+            .get_mut(id)
+            .ok_or_else(|| anyhow::anyhow!("wallet not found: {id}"))?;
+        if wallet.protection == KeyProtection::SecureStorage {
+            return Ok(());
+        }
+        let plaintext =
+            decrypt_key(&wallet.encrypted_key, password).context("failed to decrypt wallet key")?;
+        wallet.encrypted_key = storage
+            .encrypt_bytes(&plaintext)
+            .context("failed to encrypt wallet key")?;
+        wallet.protection = KeyProtection::SecureStorage;
+        info!(wallet_id = %id, "wallet key migrated to secure storage");
+        Ok(())
+    }
+
+    /// Re-encrypt every SecureStorage-protected wallet from `from`'s master
+    /// key to `to`'s. Intended as the `reencrypt_extra` hook of
+    /// `ConfigManager::migrate_master_key`. All keys are re-encrypted before
+    /// any entry is modified, so on error the store is unchanged.
+    pub fn reencrypt_keys(&mut self, from: &SecureStorage, to: &SecureStorage) -> Result<usize> {
+        let mut updated = Vec::new();
+        for (id, wallet) in &self.wallets {
+            if wallet.protection == KeyProtection::SecureStorage {
+                let encrypted = from
+                    .reencrypt_bytes(to, &wallet.encrypted_key)
+                    .with_context(|| format!("failed to re-encrypt wallet {id}"))?;
+                updated.push((id.clone(), encrypted));
+            }
+        }
+        let count = updated.len();
+        for (id, encrypted) in updated {
+            if let Some(wallet) = self.wallets.get_mut(&id) {
+                wallet.encrypted_key = encrypted;
+            }
+        }
+        Ok(count)
     }
 
     /// Number of wallets in the store.
@@ -136,10 +244,14 @@ impl WalletStore {
         self.wallets.is_empty()
     }
 
+    /// The wallet store as the JSON written by [`save_to_file`](Self::save_to_file).
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("failed to serialize wallet store")
+    }
+
     /// Persist the wallet store to a JSON file.
     pub fn save_to_file(&self, path: &Path) -> Result<()> {
-        let json =
-            serde_json::to_string_pretty(self).context("failed to serialize wallet store")?;
+        let json = self.to_json()?;
         std::fs::write(path, json).context("failed to write wallet store file")?;
 
         // Restrict file permissions to owner-only on Unix (0o600 = rw-------).
@@ -394,4 +506,73 @@ mod tests {
         let parsed: Chain = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed, Chain::Base);
     }
+
+    fn test_storage(dir: &std::path::Path) -> SecureStorage {
+        SecureStorage::with_salt_path(&dir.join("storage.salt")).unwrap()
+    }
+
+    #[test]
+    fn secure_wallet_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = test_storage(dir.path());
+        let mut store = WalletStore::new();
+        let id = store
+            .add_wallet_secure(
+                "Secure".into(),
+                Chain::Ethereum,
+                "0xabc".into(),
+                b"private-key",
+                &storage,
+            )
+            .unwrap();
+
+        assert_eq!(
+            store.get_wallet(&id).unwrap().protection,
+            KeyProtection::SecureStorage
+        );
+        assert_eq!(store.decrypt_wallet_key(&id, &storage).unwrap(), b"private-key");
+    }
+
+    #[test]
+    fn legacy_wallet_requires_migration() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = test_storage(dir.path());
+        let mut store = WalletStore::new();
+        let encrypted = encrypt_key(b"legacy-key", "pw").unwrap();
+        let id = store.add_wallet("Old".into(), Chain::Base, "0x1".into(), encrypted);
+
+        assert!(store.decrypt_wallet_key(&id, &storage).is_err());
+        assert!(store.migrate_wallet_key(&id, "wrong", &storage).is_err());
+
+        store.migrate_wallet_key(&id, "pw", &storage).unwrap();
+        assert_eq!(store.decrypt_wallet_key(&id, &storage).unwrap(), b"legacy-key");
+    }
+
+    #[test]
+    fn legacy_store_file_defaults_to_password_protection() {
+        let json = r#"{"wallets":{"w1":{"id":"w1","name":"Old","chain":"base",
+            "address":"0x1","encrypted_key":[1,2,3],
+            "created_at":"2025-01-01T00:00:00Z"}}}"#;
+        let store: WalletStore = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            store.get_wallet("w1").unwrap().protection,
+            KeyProtection::Password
+        );
+    }
+
+    #[test]
+    fn reencrypt_keys_moves_wallets_to_new_master_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = test_storage(dir.path());
+        let new = SecureStorage::with_passphrase(&dir.path().join("storage.salt"), "pw").unwrap();
+        let mut store = WalletStore::new();
+        let id = store
+            .add_wallet_secure("W".into(), Chain::Solana, "sol".into(), b"k", &old)
+            .unwrap();
+        store.add_wallet("Legacy".into(), Chain::Base, "0x2".into(), vec![9; 40]);
+
+        assert_eq!(store.reencrypt_keys(&old, &new).unwrap(), 1);
+        assert!(store.decrypt_wallet_key(&id, &old).is_err());
+        assert_eq!(store.decrypt_wallet_key(&id, &new).unwrap(), b"k");
+    }
 }
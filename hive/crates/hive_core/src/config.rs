@@ -3,11 +3,13 @@ use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tracing::{info, warn};
 
-use crate::secure_storage::SecureStorage;
+use crate::keyring::KeyringBackend;
+use crate::routing_rules::RoutingRule;
+use crate::secure_storage::{MasterKeySource, SecureStorage};
 
 // ---------------------------------------------------------------------------
 // Connected accounts
@@ -310,12 +312,29 @@ pub struct HiveConfig {
     pub default_model: String,
     pub auto_routing: bool,
     pub project_models: Vec<String>,
+    /// Ordered policy rules applied on top of automatic routing.
+    pub routing_rules: Vec<RoutingRule>,
 
     // Speculative decoding ("guess and check")
     pub speculative_decoding: bool,
     pub speculative_draft_model: Option<String>,
     pub speculative_show_metrics: bool,
 
+    // Context compaction (summarize old messages instead of dropping them)
+    pub context_compaction: bool,
+    /// Model used to write session-memory summaries. `None` picks a cheap
+    /// model automatically.
+    pub compaction_model: Option<String>,
+
+    // Response cache (replay identical deterministic requests)
+    pub response_cache_enabled: bool,
+    pub response_cache_ttl_hours: u32,
+    /// Also reuse answers to near-duplicate prompts.
+    pub response_cache_semantic: bool,
+    pub response_cache_similarity: f32,
+    /// Also cache requests sent at the provider's default temperature.
+    pub response_cache_default_temperature: bool,
+
     // Budget
     pub daily_budget_usd: f64,
     pub monthly_budget_usd: f64,
@@ -333,6 +352,35 @@ pub struct HiveConfig {
     // Connected accounts
     pub connected_accounts: Vec<ConnectedAccount>,
 
+    // Knowledge
+    /// Local Obsidian vault whose notes are added to the search index.
+    pub obsidian_vault_path: Option<String>,
+
+    // Inbound webhooks
+    /// Serve the webhook receiver. Sources and their secrets are read from
+    /// `~/.hive/webhook_sources.json`.
+    pub webhook_receiver_enabled: bool,
+    /// Listen address override, e.g. `127.0.0.1:8743`.
+    pub webhook_receiver_addr: Option<String>,
+
+    // Messaging bot
+    /// Answer mentions and DMs on the platform configured in
+    /// `~/.hive/messaging_bot.json`.
+    pub messaging_bot_enabled: bool,
+
+    // Remote execution
+    /// Run agent work sent by trusted peers and offload coordinator and swarm
+    /// work to them.
+    pub remote_exec_enabled: bool,
+
+    // Replication
+    /// Share channels, specs and kanban boards with trusted peers.
+    pub replication_enabled: bool,
+
+    // Fleet sharing
+    /// Exchange aggregated, noised learning statistics with trusted peers.
+    pub fleet_sharing_enabled: bool,
+
     // Privacy Shield
     pub shield_enabled: bool,
     #[serde(default)]
@@ -378,9 +426,17 @@ impl Default for HiveConfig {
             default_model: "gpt-4o-mini".into(),
             auto_routing: true,
             project_models: Vec::new(),
+            routing_rules: Vec::new(),
             speculative_decoding: false,
             speculative_draft_model: None,
             speculative_show_metrics: true,
+            context_compaction: true,
+            compaction_model: None,
+            response_cache_enabled: false,
+            response_cache_ttl_hours: 24,
+            response_cache_semantic: false,
+            response_cache_similarity: 0.92,
+            response_cache_default_temperature: false,
             daily_budget_usd: 10.0,
             monthly_budget_usd: 100.0,
             theme: "dark".into(),
@@ -390,6 +446,13 @@ impl Default for HiveConfig {
             log_level: "info".into(),
             close_to_tray_notice_seen: false,
             connected_accounts: Vec::new(),
+            obsidian_vault_path: None,
+            webhook_receiver_enabled: false,
+            webhook_receiver_addr: None,
+            messaging_bot_enabled: false,
+            remote_exec_enabled: false,
+            replication_enabled: false,
+            fleet_sharing_enabled: false,
             google_oauth_client_id: None,
             microsoft_oauth_client_id: None,
             github_oauth_client_id: None,
@@ -425,6 +488,11 @@ impl HiveConfig {
         Ok(Self::base_dir()?.join("logs"))
     }
 
+    /// Returns the local tokenizer directory: `~/.hive/tokenizers/`
+    pub fn tokenizers_dir() -> Result<PathBuf> {
+        Ok(Self::base_dir()?.join("tokenizers"))
+    }
+
     /// Returns the database path: `~/.hive/memory.db`
     pub fn db_path() -> Result<PathBuf> {
         Ok(Self::base_dir()?.join("memory.db"))
@@ -558,6 +626,83 @@ fn migrate_plaintext_keys(raw_json: &str) -> Result<(LegacyKeys, String)> {
     }
 }
 
+/// Re-encrypts secrets kept outside `keys.enc` (such as wallet keys) from
+/// the current master key to a new one. Registered with
+/// [`ConfigManager::add_secret_migration`].
+pub type SecretMigration =
+    Box<dyn Fn(&SecureStorage, &SecureStorage, &mut StagedSecrets) -> Result<()> + Send + Sync>;
+
+/// Files re-encrypted during a master key switch. Nothing is written to its
+/// final path until every secret has been re-encrypted and staged.
+#[derive(Default)]
+pub struct StagedSecrets {
+    files: Vec<(PathBuf, Vec<u8>)>,
+    on_commit: Vec<Box<dyn FnOnce()>>,
+}
+
+impl StagedSecrets {
+    /// Replace `path` with `contents` when the switch commits.
+    pub fn write(&mut self, path: impl Into<PathBuf>, contents: impl Into<Vec<u8>>) {
+        self.files.push((path.into(), contents.into()));
+    }
+
+    /// Run `f` once every staged file is in place, e.g. to swap in-memory
+    /// copies of the migrated secrets.
+    pub fn on_commit(&mut self, f: impl FnOnce() + 'static) {
+        self.on_commit.push(Box::new(f));
+    }
+
+    /// Write every file to a temporary sibling, run `before_rename`, then
+    /// move the files into place. If a write or `before_rename` fails, the
+    /// temporary files are removed and no destination is touched.
+    fn commit(self, before_rename: impl FnOnce() -> Result<()>) -> Result<()> {
+        let mut staged = Vec::with_capacity(self.files.len());
+        let written = self
+            .files
+            .iter()
+            .try_for_each(|(path, contents)| {
+                let tmp = staging_path(path);
+                staged.push((tmp.clone(), path));
+                write_private(&tmp, contents)
+            })
+            .and_then(|()| before_rename());
+        if let Err(e) = written {
+            for (tmp, _) in &staged {
+                let _ = std::fs::remove_file(tmp);
+            }
+            return Err(e);
+        }
+        for (tmp, path) in staged {
+            std::fs::rename(&tmp, path)
+                .with_context(|| format!("Failed to replace {}", path.display()))?;
+        }
+        for f in self.on_commit {
+            f();
+        }
+        Ok(())
+    }
+}
+
+/// Temporary sibling of `path` holding its staged contents.
+fn staging_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".migrating");
+    path.with_file_name(name)
+}
+
+/// Write a file that holds secrets, readable only by the owner on Unix.
+fn write_private(path: &Path, contents: &[u8]) -> Result<()> {
+    std::fs::write(path, contents)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+            .with_context(|| format!("Failed to set permissions on {}", path.display()))?;
+    }
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // ConfigManager
 // ---------------------------------------------------------------------------
@@ -570,6 +715,7 @@ pub struct ConfigManager {
     config: Arc<RwLock<HiveConfig>>,
     secure_storage: Option<SecureStorage>,
     keys_path: PathBuf,
+    secret_migrations: RwLock<Vec<SecretMigration>>,
     _watcher: Option<RecommendedWatcher>,
 }
 
@@ -605,6 +751,7 @@ impl ConfigManager {
             config,
             secure_storage,
             keys_path,
+            secret_migrations: RwLock::new(Vec::new()),
             _watcher: Some(watcher),
         })
     }
@@ -624,7 +771,11 @@ impl ConfigManager {
             // Check for plaintext keys that need migration
             let (legacy, cleaned_json) = migrate_plaintext_keys(&raw_json)?;
 
-            if legacy.has_any() {
+            let storage_locked = secure_storage.as_ref().is_some_and(|ss| ss.is_locked());
+            if legacy.has_any() && storage_locked {
+                // Stripping the keys now would lose them; retry after unlock.
+                warn!("SecureStorage is locked; deferring plaintext key migration");
+            } else if legacy.has_any() {
                 info!("Migrating plaintext API keys from config.json to SecureStorage");
 
                 if let Some(ss) = secure_storage {
@@ -653,7 +804,8 @@ impl ConfigManager {
             }
 
             // Parse the (potentially cleaned) JSON into HiveConfig
-            let mut config: HiveConfig = serde_json::from_str(&cleaned_json)
+            let json = if storage_locked { &raw_json } else { &cleaned_json };
+            let mut config: HiveConfig = serde_json::from_str(json)
                 .with_context(|| "Failed to parse config.json")?;
 
             // Populate API keys from SecureStorage
@@ -698,11 +850,18 @@ impl ConfigManager {
     /// Update the config. The closure receives a mutable reference to the
     /// config. After mutation, non-secret fields are saved to `config.json`
     /// and API keys are saved to SecureStorage.
+    ///
+    /// While secure storage is locked the in-memory keys are blank, so they
+    /// are not written back (doing so would erase the stored keys).
     pub fn update(&self, f: impl FnOnce(&mut HiveConfig)) -> Result<()> {
         let mut config = self.config.write();
         f(&mut config);
         config.save()?;
-        self.save_api_keys(&config)?;
+        if self.is_locked() {
+            warn!("SecureStorage is locked; API keys not persisted");
+        } else {
+            self.save_api_keys(&config)?;
+        }
         Ok(())
     }
 
@@ -754,6 +913,9 @@ impl ConfigManager {
             warn!("SecureStorage unavailable; API keys not persisted");
             anyhow::bail!("SecureStorage unavailable; API keys cannot be saved");
         };
+        if ss.is_locked() {
+            anyhow::bail!("SecureStorage is locked; unlock it before saving API keys");
+        }
         let mut key_map = load_key_map(&self.keys_path);
         set_secure_key(ss, &mut key_map, KEY_ANTHROPIC, &config.anthropic_api_key)?;
         set_secure_key(ss, &mut key_map, KEY_OPENAI, &config.openai_api_key)?;
@@ -773,6 +935,189 @@ impl ConfigManager {
         save_key_map(&self.keys_path, &key_map)
     }
 
+    // -- Master key / lock helpers ------------------------------------------
+
+    /// Whether secure storage is locked (passphrase mode, not yet unlocked or
+    /// auto-locked). API keys and OAuth tokens are unavailable while locked.
+    pub fn is_locked(&self) -> bool {
+        self.secure_storage.as_ref().is_some_and(|ss| ss.is_locked())
+    }
+
+    /// The master key source currently in use, if secure storage is available.
+    pub fn master_key_source(&self) -> Option<MasterKeySource> {
+        self.secure_storage.as_ref().map(|ss| ss.source())
+    }
+
+    /// Unlock passphrase-protected storage and load API keys into memory.
+    pub fn unlock(&self, passphrase: &str) -> Result<()> {
+        let Some(ss) = &self.secure_storage else {
+            anyhow::bail!("SecureStorage unavailable");
+        };
+        ss.unlock(passphrase)?;
+        let mut config = self.config.write();
+        Self::populate_keys_from_storage(&mut config, &self.keys_path, &self.secure_storage);
+        Ok(())
+    }
+
+    /// Lock secure storage and drop decrypted API keys from memory.
+    pub fn lock(&self) {
+        if let Some(ss) = &self.secure_storage {
+            ss.lock();
+        }
+        if self.is_locked() {
+            Self::clear_keys(&mut self.config.write());
+        }
+    }
+
+    /// Lock if the configured idle timeout has elapsed. Intended to be polled
+    /// periodically by the UI. Returns `true` if this call locked storage.
+    pub fn lock_if_idle(&self) -> bool {
+        let locked = self
+            .secure_storage
+            .as_ref()
+            .is_some_and(|ss| ss.lock_if_idle());
+        if locked {
+            Self::clear_keys(&mut self.config.write());
+        }
+        locked
+    }
+
+    /// The idle auto-lock timeout in minutes (`0` means disabled).
+    pub fn idle_lock_minutes(&self) -> u32 {
+        self.secure_storage
+            .as_ref()
+            .map_or(0, |ss| ss.idle_lock_minutes())
+    }
+
+    /// Set the idle auto-lock timeout in minutes (`0` disables) and persist it.
+    pub fn set_idle_lock_minutes(&self, minutes: u32) -> Result<()> {
+        let Some(ss) = &self.secure_storage else {
+            anyhow::bail!("SecureStorage unavailable");
+        };
+        ss.set_idle_lock_minutes(minutes);
+        ss.save_settings()
+    }
+
+    /// Register a hook that re-encrypts secrets stored outside `keys.enc`
+    /// whenever the master key changes. Every owner of such secrets must
+    /// register one, or they become unreadable after a mode switch.
+    pub fn add_secret_migration(&self, migration: SecretMigration) {
+        self.secret_migrations.write().push(migration);
+    }
+
+    /// Protect the master key with `passphrase`, re-encrypting all stored
+    /// secrets. Storage must be unlocked.
+    pub fn enable_passphrase(&self, passphrase: &str) -> Result<()> {
+        self.switch_master_key(self.passphrase_storage(passphrase)?, None)
+    }
+
+    /// Move the master key into an OS keyring, re-encrypting all stored secrets.
+    pub fn enable_keyring(&self, backend: &dyn KeyringBackend) -> Result<()> {
+        self.switch_master_key(self.keyring_storage(backend)?, Some(backend))
+    }
+
+    /// Revert to the machine-derived master key, re-encrypting all stored secrets.
+    pub fn use_machine_key(&self) -> Result<()> {
+        self.switch_master_key(self.machine_storage()?, None)
+    }
+
+    /// [`migrate_master_key`](Self::migrate_master_key) with every
+    /// registered [`SecretMigration`]; the first failure aborts the switch.
+    fn switch_master_key(
+        &self,
+        target: SecureStorage,
+        keyring: Option<&dyn KeyringBackend>,
+    ) -> Result<()> {
+        let migrations = self.secret_migrations.read();
+        self.migrate_master_key(target, keyring, |current, target, staged| {
+            migrations
+                .iter()
+                .try_for_each(|migrate| migrate(current, target, staged))
+        })
+    }
+
+    /// Build a passphrase-keyed storage sharing this manager's salt, for use
+    /// with [`migrate_master_key`](Self::migrate_master_key).
+    pub fn passphrase_storage(&self, passphrase: &str) -> Result<SecureStorage> {
+        SecureStorage::with_passphrase(&self.salt_path(), passphrase)
+    }
+
+    /// Build a keyring-held storage sharing this manager's salt. Its key is
+    /// written to `backend` only when a migration to it commits.
+    pub fn keyring_storage(&self, backend: &dyn KeyringBackend) -> Result<SecureStorage> {
+        SecureStorage::with_keyring(&self.salt_path(), backend)
+    }
+
+    /// Build a machine-keyed storage sharing this manager's salt.
+    pub fn machine_storage(&self) -> Result<SecureStorage> {
+        SecureStorage::with_salt_path(&self.salt_path())
+    }
+
+    /// Re-encrypt `keys.enc` (API keys and OAuth tokens) from the current
+    /// master key to `target`, then switch the shared storage handle over and
+    /// persist the new settings.
+    ///
+    /// `reencrypt_extra` is called with `(current, target, staged)` so secrets
+    /// stored elsewhere (e.g. the wallet store) are staged in the same step.
+    /// Files are replaced only after every secret has been re-encrypted, and
+    /// a keyring-held `target` key is written to `keyring` last, just before
+    /// the replacement. Any error leaves the old key and files in place.
+    pub fn migrate_master_key(
+        &self,
+        target: SecureStorage,
+        keyring: Option<&dyn KeyringBackend>,
+        reencrypt_extra: impl FnOnce(
+            &SecureStorage,
+            &SecureStorage,
+            &mut StagedSecrets,
+        ) -> Result<()>,
+    ) -> Result<()> {
+        let Some(ss) = &self.secure_storage else {
+            anyhow::bail!("SecureStorage unavailable");
+        };
+        if ss.is_locked() {
+            anyhow::bail!("SecureStorage is locked; unlock it before changing the master key");
+        }
+        if target.has_unsaved_key() && keyring.is_none() {
+            anyhow::bail!("A keyring backend is required to store the new master key");
+        }
+        let mut key_map = load_key_map(&self.keys_path);
+        let count = ss.reencrypt(&target, &mut key_map)?;
+        let mut staged = StagedSecrets::default();
+        staged.write(&self.keys_path, serde_json::to_string_pretty(&key_map)?);
+        let (settings_path, settings) = target.settings_file()?;
+        staged.write(settings_path, settings);
+        reencrypt_extra(ss, &target, &mut staged)?;
+        staged.commit(|| keyring.map_or(Ok(()), |backend| target.store_key(backend)))?;
+        ss.adopt(target);
+        info!("Re-encrypted {count} secrets with new master key ({:?})", ss.source());
+        Ok(())
+    }
+
+    /// Salt file shared by every master key source, next to `keys.enc`.
+    fn salt_path(&self) -> PathBuf {
+        self.keys_path.with_file_name("storage.salt")
+    }
+
+    fn clear_keys(config: &mut HiveConfig) {
+        config.anthropic_api_key = None;
+        config.openai_api_key = None;
+        config.openrouter_api_key = None;
+        config.google_api_key = None;
+        config.groq_api_key = None;
+        config.huggingface_api_key = None;
+        config.litellm_api_key = None;
+        config.elevenlabs_api_key = None;
+        config.telnyx_api_key = None;
+        config.xai_api_key = None;
+    }
+
+    /// Shared handle to the secure storage, e.g. for wallet key encryption.
+    /// Lock state is shared with this manager.
+    pub fn secure_storage(&self) -> Option<SecureStorage> {
+        self.secure_storage.as_ref().map(|ss| ss.duplicate())
+    }
+
     // -- Connected accounts helpers ----------------------------------------
 
     /// Get the OAuth token for a connected platform.
@@ -1444,6 +1789,7 @@ mod tests {
             config: Arc::new(RwLock::new(config)),
             secure_storage: Some(ss),
             keys_path: keys_path.clone(),
+            secret_migrations: RwLock::new(Vec::new()),
             _watcher: None,
         }
     }
@@ -1553,4 +1899,175 @@ mod tests {
         let result2 = mgr.import_config(just_magic, "password");
         assert!(result2.is_err());
     }
+
+    // -----------------------------------------------------------------------
+    // 10. Master passphrase, locking and re-encryption
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn enable_passphrase_reencrypts_and_locks() {
+        let (_tmp, config_path, keys_path) = make_temp_config_dir();
+        let mgr = test_config_manager(&config_path, &keys_path);
+        mgr.set_api_key("anthropic", Some("sk-ant-locked".into())).unwrap();
+        mgr.set_oauth_token(
+            AccountPlatform::GitHub,
+            &OAuthTokenData {
+                access_token: "gho_token".into(),
+                refresh_token: None,
+                expires_at: None,
+            },
+        )
+        .unwrap();
+
+        mgr.enable_passphrase("hunter2").unwrap();
+        assert_eq!(mgr.master_key_source(), Some(MasterKeySource::Passphrase));
+
+        // The machine key can no longer read the store.
+        let machine = test_secure_storage(&keys_path);
+        let map = load_key_map(&keys_path);
+        assert!(get_secure_key(&machine, &map, KEY_ANTHROPIC).is_none());
+
+        mgr.lock();
+        assert!(mgr.is_locked());
+        assert!(mgr.get_api_key("anthropic").is_none());
+        assert!(mgr.get_oauth_token(AccountPlatform::GitHub).is_none());
+        assert!(mgr.set_api_key("openai", Some("sk".into())).is_err());
+
+        assert!(mgr.unlock("wrong").is_err());
+        mgr.unlock("hunter2").unwrap();
+        assert_eq!(mgr.get_api_key("anthropic").as_deref(), Some("sk-ant-locked"));
+        assert_eq!(
+            mgr.get_oauth_token(AccountPlatform::GitHub).unwrap().access_token,
+            "gho_token"
+        );
+    }
+
+    #[test]
+    fn use_machine_key_reverts_passphrase() {
+        let (_tmp, config_path, keys_path) = make_temp_config_dir();
+        let mgr = test_config_manager(&config_path, &keys_path);
+        mgr.set_api_key("groq", Some("gsk-1".into())).unwrap();
+
+        mgr.enable_passphrase("pw").unwrap();
+        mgr.use_machine_key().unwrap();
+        assert_eq!(mgr.master_key_source(), Some(MasterKeySource::Machine));
+
+        let machine = test_secure_storage(&keys_path);
+        let map = load_key_map(&keys_path);
+        assert_eq!(get_secure_key(&machine, &map, KEY_GROQ).as_deref(), Some("gsk-1"));
+    }
+
+    #[test]
+    fn master_key_switch_runs_secret_migrations() {
+        let (_tmp, config_path, keys_path) = make_temp_config_dir();
+        let mgr = test_config_manager(&config_path, &keys_path);
+        let wallet_key = mgr.secure_storage().unwrap().encrypt("seed").unwrap();
+        let migrated = Arc::new(parking_lot::Mutex::new(None));
+        let slot = Arc::clone(&migrated);
+        mgr.add_secret_migration(Box::new(move |current, target, _staged| {
+            let plaintext = current.decrypt(&wallet_key)?;
+            *slot.lock() = Some(target.encrypt(&plaintext)?);
+            Ok(())
+        }));
+
+        mgr.enable_passphrase("pw").unwrap();
+        let reencrypted = migrated.lock().clone().unwrap();
+        assert_eq!(mgr.secure_storage().unwrap().decrypt(&reencrypted).unwrap(), "seed");
+    }
+
+    #[test]
+    fn failed_secret_migration_aborts_master_key_switch() {
+        let (_tmp, config_path, keys_path) = make_temp_config_dir();
+        let mgr = test_config_manager(&config_path, &keys_path);
+        mgr.set_api_key("groq", Some("gsk-1".into())).unwrap();
+        mgr.add_secret_migration(Box::new(|_, _, _| anyhow::bail!("wallet store unwritable")));
+
+        assert!(mgr.enable_passphrase("pw").is_err());
+        assert_eq!(mgr.master_key_source(), Some(MasterKeySource::Machine));
+        assert_eq!(mgr.get_api_key("groq").as_deref(), Some("gsk-1"));
+    }
+
+    #[test]
+    fn failed_master_key_switch_leaves_files_and_keyring_untouched() {
+        let (tmp, config_path, keys_path) = make_temp_config_dir();
+        let mgr = test_config_manager(&config_path, &keys_path);
+        mgr.set_api_key("groq", Some("gsk-1".into())).unwrap();
+        let keys_before = std::fs::read(&keys_path).unwrap();
+
+        // The first migration stages a file; the second fails afterwards.
+        let wallet_path = tmp.path().join("wallets.enc");
+        std::fs::write(&wallet_path, "old wallets").unwrap();
+        let staged_path = wallet_path.clone();
+        mgr.add_secret_migration(Box::new(move |_, _, staged| {
+            staged.write(staged_path.clone(), "new wallets");
+            Ok(())
+        }));
+        mgr.add_secret_migration(Box::new(|_, _, _| anyhow::bail!("wallet store unwritable")));
+
+        let backend = crate::keyring::MemoryKeyring::new();
+        assert!(mgr.enable_keyring(&backend).is_err());
+        assert_eq!(backend.get_secret("master-key").unwrap(), None);
+        assert_eq!(std::fs::read(&keys_path).unwrap(), keys_before);
+        assert_eq!(std::fs::read_to_string(&wallet_path).unwrap(), "old wallets");
+        assert!(!keys_path.with_file_name("master_key.json").exists());
+        let leftovers: Vec<_> = std::fs::read_dir(tmp.path())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().ends_with(".migrating"))
+            .collect();
+        assert!(leftovers.is_empty());
+        assert_eq!(mgr.get_api_key("groq").as_deref(), Some("gsk-1"));
+    }
+
+    #[test]
+    fn keyring_switch_commits_staged_files_and_key_together() {
+        let (tmp, config_path, keys_path) = make_temp_config_dir();
+        let mgr = test_config_manager(&config_path, &keys_path);
+        mgr.set_api_key("groq", Some("gsk-1".into())).unwrap();
+        let wallet_path = tmp.path().join("wallets.enc");
+        let committed = Arc::new(parking_lot::Mutex::new(false));
+        let flag = Arc::clone(&committed);
+        let staged_path = wallet_path.clone();
+        mgr.add_secret_migration(Box::new(move |_, _, staged| {
+            staged.write(staged_path.clone(), "new wallets");
+            let flag = Arc::clone(&flag);
+            staged.on_commit(move || *flag.lock() = true);
+            Ok(())
+        }));
+
+        let backend = crate::keyring::MemoryKeyring::new();
+        mgr.enable_keyring(&backend).unwrap();
+        assert!(*committed.lock());
+        assert_eq!(std::fs::read_to_string(&wallet_path).unwrap(), "new wallets");
+        assert!(backend.get_secret("master-key").unwrap().is_some());
+
+        let reopened = SecureStorage::open_with_backend(
+            &keys_path.with_file_name("storage.salt"),
+            &backend,
+        )
+        .unwrap();
+        let map = load_key_map(&keys_path);
+        assert_eq!(get_secure_key(&reopened, &map, KEY_GROQ).as_deref(), Some("gsk-1"));
+    }
+
+    #[test]
+    fn locked_storage_defers_plaintext_migration() {
+        let (_tmp, config_path, keys_path) = make_temp_config_dir();
+        let salt_path = keys_path.with_file_name("storage.salt");
+        SecureStorage::with_passphrase(&salt_path, "pw")
+            .unwrap()
+            .save_settings()
+            .unwrap();
+        let locked = Some(SecureStorage::open(&salt_path).unwrap());
+        assert!(locked.as_ref().unwrap().is_locked());
+
+        let legacy_json = serde_json::json!({ "anthropic_api_key": "sk-ant-legacy" });
+        std::fs::write(&config_path, legacy_json.to_string()).unwrap();
+
+        ConfigManager::load_with_migration(&config_path, &keys_path, &locked).unwrap();
+
+        // Nothing was stripped while the key was unavailable.
+        let raw = std::fs::read_to_string(&config_path).unwrap();
+        assert!(raw.contains("sk-ant-legacy"));
+    }
 }
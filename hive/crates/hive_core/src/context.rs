@@ -1,20 +1,36 @@
-//! Context window management — tracks token usage and prunes messages
-//! to stay within model-specific context limits.
-
+//! Context window management — tracks token usage and keeps messages
+//! within model-specific context limits.
+//!
+//! Long sessions are *compacted*: once usage crosses a threshold, the oldest
+//! unprotected span is summarized (by a cheap model, see
+//! `hive_ai::compaction`) into a structured [`SessionMemory`] message. The
+//! originals are kept so they stay searchable and the compaction can be
+//! undone. Plain pruning remains as the last resort when the hard limit is
+//! exceeded.
+
+use std::collections::HashSet;
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::conversations::Conversation;
+use crate::tokenizer::{TokenizerFamily, TokenizerRegistry};
+
 // ---------------------------------------------------------------------------
 // Token estimation
 // ---------------------------------------------------------------------------
 
 /// Rough token estimate: ~4 characters per token for English text.
-/// This is intentionally conservative (overestimates) to avoid truncation.
+/// Underestimates code and non-English text, so it is only used when no
+/// real tokenizer is available for the model.
 const CHARS_PER_TOKEN: usize = 4;
 
-/// Estimate token count for a string.
+/// Heuristic token count for a string (`chars / 4`).
+///
+/// Prefer [`crate::tokenizer::count_tokens`] when the model is known; this is
+/// the fallback it uses for models without a tokenizer.
 pub fn estimate_tokens(text: &str) -> usize {
-    // Use character count / 4 as a rough approximation.
-    // More accurate would be tiktoken, but this avoids a heavy dependency.
     text.len().div_ceil(CHARS_PER_TOKEN)
 }
 
@@ -29,6 +45,18 @@ pub struct ContextMessage {
     pub content: String,
     pub tokens: usize,
     pub pinned: bool,
+    /// Stable ID (the conversation message ID when there is one). Assigned
+    /// on [`ContextWindow::push`] if absent.
+    #[serde(default)]
+    pub id: Option<String>,
+    /// IDs of earlier messages this one depends on, e.g. the tool result an
+    /// answer was built from. Referenced messages are not compacted while
+    /// the referencing message is still in the window.
+    #[serde(default)]
+    pub references: Vec<String>,
+    /// Insertion order, used to restore the original order on undo.
+    #[serde(skip)]
+    seq: u64,
 }
 
 impl ContextMessage {
@@ -41,6 +69,9 @@ impl ContextMessage {
             content,
             tokens,
             pinned: false,
+            id: None,
+            references: Vec::new(),
+            seq: 0,
         }
     }
 
@@ -49,6 +80,18 @@ impl ContextMessage {
         self.pinned = true;
         self
     }
+
+    /// Sets the message ID.
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Records that this message depends on the message with `id`.
+    pub fn referencing(mut self, id: impl Into<String>) -> Self {
+        self.references.push(id.into());
+        self
+    }
 }
 
 /// Manages the context window for a conversation.
@@ -59,29 +102,107 @@ pub struct ContextWindow {
     messages: Vec<ContextMessage>,
     max_tokens: usize,
     system_prompt_tokens: usize,
+    tokenizer: TokenizerFamily,
+    next_seq: u64,
+    /// Applied compactions, oldest first, with the messages they replaced.
+    compacted: Vec<AppliedCompaction>,
+}
+
+/// A compaction applied to a window, kept for search and undo.
+struct AppliedCompaction {
+    record: CompactionRecord,
+    originals: Vec<ContextMessage>,
 }
 
 impl ContextWindow {
     /// Creates a new context window with the given maximum token budget.
+    /// Tokens are estimated heuristically; use [`for_model`](Self::for_model)
+    /// for exact counts.
     pub fn new(max_tokens: usize) -> Self {
         Self {
             messages: Vec::new(),
             max_tokens,
             system_prompt_tokens: 0,
+            tokenizer: TokenizerFamily::Heuristic,
+            next_seq: 0,
+            compacted: Vec::new(),
         }
     }
 
+    /// Creates a context window sized for `model_id` that counts tokens with
+    /// the model's tokenizer.
+    pub fn for_model(model_id: &str) -> Self {
+        Self::new(model_context_size(model_id)).with_tokenizer(TokenizerFamily::for_model(model_id))
+    }
+
+    /// Count tokens with `tokenizer` instead of the heuristic.
+    pub fn with_tokenizer(mut self, tokenizer: TokenizerFamily) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// Builds the window for a stored conversation's active path, replaying
+    /// its stored compactions.
+    pub fn from_conversation(conversation: &Conversation, model_id: &str) -> Self {
+        let messages = conversation.messages.iter().map(|msg| {
+            let mut message = ContextMessage::new(msg.role.clone(), msg.content.clone());
+            message.id = msg.id.clone();
+            message
+        });
+        Self::from_messages(model_id, messages, &conversation.compactions)
+    }
+
+    /// Builds a window for `model_id` from existing messages and replays
+    /// `compactions` over them. Unlike [`push`](Self::push), nothing is
+    /// pruned, so the caller sees the real usage.
+    pub fn from_messages(
+        model_id: &str,
+        messages: impl IntoIterator<Item = ContextMessage>,
+        compactions: &[CompactionRecord],
+    ) -> Self {
+        let mut window = Self::for_model(model_id);
+        for message in messages {
+            let message = window.prepare(message);
+            window.messages.push(message);
+        }
+        for record in compactions {
+            window.apply_record(record);
+        }
+        window
+    }
+
+    fn count_tokens(&self, text: &str) -> usize {
+        TokenizerRegistry::global().count(&self.tokenizer, text)
+    }
+
     /// Set the system prompt (counts toward the token budget).
     pub fn set_system_prompt(&mut self, prompt: &str) {
-        self.system_prompt_tokens = estimate_tokens(prompt);
+        self.system_prompt_tokens = self.count_tokens(prompt);
     }
 
     /// Add a message to the context. May trigger pruning.
+    ///
+    /// When the window has a model tokenizer, the message's heuristic token
+    /// count is replaced with the exact count.
     pub fn push(&mut self, message: ContextMessage) {
+        let message = self.prepare(message);
         self.messages.push(message);
         self.prune();
     }
 
+    /// Recount tokens and assign an ID and sequence number.
+    fn prepare(&mut self, mut message: ContextMessage) -> ContextMessage {
+        if self.tokenizer != TokenizerFamily::Heuristic {
+            message.tokens = self.count_tokens(&message.content);
+        }
+        if message.id.is_none() {
+            message.id = Some(uuid::Uuid::new_v4().to_string());
+        }
+        message.seq = self.next_seq;
+        self.next_seq += 1;
+        message
+    }
+
     /// Total estimated tokens across all messages + system prompt.
     pub fn total_tokens(&self) -> usize {
         self.system_prompt_tokens + self.messages.iter().map(|m| m.tokens).sum::<usize>()
@@ -133,9 +254,10 @@ impl ContextWindow {
         });
     }
 
-    /// Clear all messages.
+    /// Clear all messages, including compaction history.
     pub fn clear(&mut self) {
         self.messages.clear();
+        self.compacted.clear();
     }
 
     /// Summarize context state for debugging.
@@ -147,6 +269,7 @@ impl ContextWindow {
             available_tokens: self.available_tokens(),
             usage_pct: self.usage_pct(),
             pinned_count: self.messages.iter().filter(|m| m.pinned).count(),
+            compaction_count: self.compacted.len(),
         }
     }
 }
@@ -160,6 +283,336 @@ pub struct ContextSummary {
     pub available_tokens: usize,
     pub usage_pct: f64,
     pub pinned_count: usize,
+    #[serde(default)]
+    pub compaction_count: usize,
+}
+
+// ---------------------------------------------------------------------------
+// Compaction
+// ---------------------------------------------------------------------------
+
+/// When and how aggressively to compact a context window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactionConfig {
+    /// Usage fraction (0.0–1.0) at which compaction should run.
+    pub threshold: f64,
+    /// Usage fraction to bring the window down to.
+    pub target: f64,
+    /// Number of most recent messages that are never compacted.
+    pub keep_recent: usize,
+    /// Smallest span worth a summarization call.
+    pub min_messages: usize,
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 0.8,
+            target: 0.5,
+            keep_recent: 6,
+            min_messages: 4,
+        }
+    }
+}
+
+/// Structured summary that replaces a compacted span of messages.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionMemory {
+    pub summary: String,
+    #[serde(default)]
+    pub decisions: Vec<String>,
+    #[serde(default)]
+    pub facts: Vec<String>,
+    #[serde(default, alias = "open_tasks")]
+    pub open_tasks: Vec<String>,
+    #[serde(default)]
+    pub files: Vec<String>,
+}
+
+impl SessionMemory {
+    /// Parses a summarizer reply. Accepts the requested JSON object, with or
+    /// without a Markdown code fence; anything else becomes a plain summary.
+    pub fn parse(text: &str) -> Self {
+        let trimmed = text.trim();
+        if let (Some(start), Some(end)) = (trimmed.find('{'), trimmed.rfind('}'))
+            && start < end
+            && let Ok(memory) = serde_json::from_str::<SessionMemory>(&trimmed[start..=end])
+        {
+            return memory;
+        }
+        Self {
+            summary: trimmed.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Renders the memory as the content of the message that stands in for
+    /// the compacted span.
+    pub fn to_message_content(&self) -> String {
+        let mut out = String::from("## Session memory\n\n");
+        out.push_str("Summary of earlier messages in this conversation.\n\n");
+        out.push_str(self.summary.trim());
+        out.push('\n');
+        for (heading, items) in [
+            ("Decisions", &self.decisions),
+            ("Facts", &self.facts),
+            ("Open tasks", &self.open_tasks),
+            ("Files", &self.files),
+        ] {
+            if items.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n### {heading}\n"));
+            for item in items {
+                out.push_str(&format!("- {item}\n"));
+            }
+        }
+        out
+    }
+}
+
+/// A compaction stored alongside the conversation. The summarized messages
+/// themselves are untouched in the conversation; `message_ids` says which
+/// ones the memory replaces when the context is rebuilt.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactionRecord {
+    pub id: String,
+    pub memory: SessionMemory,
+    pub message_ids: Vec<String>,
+    pub tokens_before: usize,
+    pub tokens_after: usize,
+    /// Model that wrote the summary.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Messages selected for compaction, produced by
+/// [`ContextWindow::compaction_plan`].
+#[derive(Debug, Clone)]
+pub struct CompactionPlan {
+    /// The messages to summarize, oldest first.
+    pub messages: Vec<ContextMessage>,
+    /// Tokens those messages currently occupy.
+    pub tokens: usize,
+}
+
+/// Instructions sent to the summarizer ahead of the transcript.
+const COMPACTION_INSTRUCTIONS: &str = "\
+Summarize the conversation excerpt below so it can replace the original \
+messages in the context window. Preserve decisions, constraints, facts the \
+user stated, file paths, identifiers and unfinished work; drop pleasantries \
+and superseded ideas. If the excerpt contains an earlier session memory, \
+merge it into yours.
+
+Reply with only a JSON object of this shape:
+{\"summary\": \"...\", \"decisions\": [\"...\"], \"facts\": [\"...\"], \
+\"open_tasks\": [\"...\"], \"files\": [\"...\"]}";
+
+impl CompactionPlan {
+    /// Prompt asking a model to summarize the plan into a [`SessionMemory`].
+    pub fn prompt(&self) -> String {
+        let mut prompt = String::from(COMPACTION_INSTRUCTIONS);
+        prompt.push_str("\n\n--- Conversation excerpt ---\n");
+        for message in &self.messages {
+            prompt.push_str(&format!("\n[{}]\n{}\n", message.role, message.content));
+        }
+        prompt
+    }
+
+    fn ids(&self) -> Vec<String> {
+        self.messages.iter().filter_map(|m| m.id.clone()).collect()
+    }
+}
+
+impl ContextWindow {
+    /// Whether usage has crossed the compaction threshold.
+    pub fn needs_compaction(&self, config: &CompactionConfig) -> bool {
+        self.usage_pct() >= config.threshold
+    }
+
+    /// Pick the oldest messages to summarize so usage drops to
+    /// `config.target`.
+    ///
+    /// Pinned messages, the `keep_recent` newest messages, and messages
+    /// referenced by anything that stays in the window are never selected.
+    /// Returns `None` when there is nothing worth compacting.
+    pub fn compaction_plan(&self, config: &CompactionConfig) -> Option<CompactionPlan> {
+        let target = (self.max_tokens as f64 * config.target) as usize;
+        let to_shed = self.total_tokens().saturating_sub(target);
+        if to_shed == 0 {
+            return None;
+        }
+
+        let eligible = self.messages.len().saturating_sub(config.keep_recent);
+        let mut protected: HashSet<usize> = HashSet::new();
+        let selected = loop {
+            let mut selected = vec![false; self.messages.len()];
+            let mut freed = 0;
+            for (i, message) in self.messages[..eligible].iter().enumerate() {
+                if freed >= to_shed {
+                    break;
+                }
+                if message.pinned || protected.contains(&i) {
+                    continue;
+                }
+                selected[i] = true;
+                freed += message.tokens;
+            }
+
+            // Anything referenced by a message that stays must stay too.
+            // Protecting it changes the selection, so select again.
+            let referenced: HashSet<&str> = self
+                .messages
+                .iter()
+                .zip(&selected)
+                .filter(|(_, selected)| !**selected)
+                .flat_map(|(m, _)| m.references.iter().map(String::as_str))
+                .collect();
+            let before = protected.len();
+            protected.extend(self.messages.iter().enumerate().filter_map(|(i, m)| {
+                let id = m.id.as_deref()?;
+                (selected[i] && referenced.contains(id)).then_some(i)
+            }));
+            if protected.len() == before {
+                break selected;
+            }
+        };
+
+        let messages: Vec<ContextMessage> = self
+            .messages
+            .iter()
+            .zip(&selected)
+            .filter(|(_, selected)| **selected)
+            .map(|(m, _)| m.clone())
+            .collect();
+        if messages.is_empty() || messages.len() < config.min_messages {
+            return None;
+        }
+        let tokens = messages.iter().map(|m| m.tokens).sum();
+        Some(CompactionPlan { messages, tokens })
+    }
+
+    /// Replace the plan's messages with a session-memory message.
+    ///
+    /// Messages are matched by ID, so a plan can be applied to a window
+    /// rebuilt from the same conversation. Fails if any planned message is
+    /// no longer in the window. The originals are kept for
+    /// [`search`](Self::search) and
+    /// [`undo_last_compaction`](Self::undo_last_compaction).
+    pub fn apply_compaction(
+        &mut self,
+        plan: &CompactionPlan,
+        memory: SessionMemory,
+        model: Option<&str>,
+    ) -> Result<CompactionRecord> {
+        let message_ids = plan.ids();
+        let ids: HashSet<String> = message_ids.iter().cloned().collect();
+        let present = self
+            .messages
+            .iter()
+            .filter(|m| m.id.as_ref().is_some_and(|id| ids.contains(id)))
+            .count();
+        if ids.is_empty() || present != ids.len() {
+            bail!("Context changed since the compaction was planned");
+        }
+
+        let record = CompactionRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            memory,
+            message_ids,
+            tokens_before: self.total_tokens(),
+            tokens_after: 0,
+            model: model.map(str::to_string),
+            created_at: Utc::now(),
+        };
+        Ok(self.replace_with_memory(record, |m| m.id.as_ref().is_some_and(|id| ids.contains(id))))
+    }
+
+    /// Replay a stored compaction, e.g. after reloading a conversation.
+    /// Returns `false` if none of its messages are in the window.
+    pub fn apply_record(&mut self, record: &CompactionRecord) -> bool {
+        let ids: HashSet<&str> = record.message_ids.iter().map(String::as_str).collect();
+        let matches = |m: &ContextMessage| m.id.as_deref().is_some_and(|id| ids.contains(id));
+        if !self.messages.iter().any(matches) {
+            return false;
+        }
+        self.replace_with_memory(record.clone(), matches);
+        true
+    }
+
+    fn replace_with_memory(
+        &mut self,
+        mut record: CompactionRecord,
+        mut replaced: impl FnMut(&ContextMessage) -> bool,
+    ) -> CompactionRecord {
+        let first = self
+            .messages
+            .iter()
+            .position(&mut replaced)
+            .unwrap_or(self.messages.len());
+        let mut originals = Vec::new();
+        let mut kept = Vec::with_capacity(self.messages.len());
+        for message in self.messages.drain(..) {
+            if replaced(&message) {
+                originals.push(message);
+            } else {
+                kept.push(message);
+            }
+        }
+        self.messages = kept;
+
+        let content = record.memory.to_message_content();
+        let memory_message = ContextMessage {
+            role: "system".into(),
+            tokens: self.count_tokens(&content),
+            content,
+            pinned: false,
+            id: Some(record.id.clone()),
+            references: Vec::new(),
+            // Sorts where the span started, so undo restores the order.
+            seq: originals.first().map_or(self.next_seq, |m| m.seq),
+        };
+        // Everything before the first replaced message was kept, so its
+        // index is unchanged.
+        self.messages.insert(first, memory_message);
+
+        record.tokens_after = self.total_tokens();
+        self.compacted.push(AppliedCompaction {
+            record: record.clone(),
+            originals,
+        });
+        record
+    }
+
+    /// Undo the most recent compaction, restoring the original messages in
+    /// their original order.
+    pub fn undo_last_compaction(&mut self) -> Option<CompactionRecord> {
+        let applied = self.compacted.pop()?;
+        let id = applied.record.id.as_str();
+        self.messages.retain(|m| m.id.as_deref() != Some(id));
+        self.messages.extend(applied.originals);
+        self.messages.sort_by_key(|m| m.seq);
+        Some(applied.record)
+    }
+
+    /// Compactions applied to this window, oldest first.
+    pub fn compactions(&self) -> impl Iterator<Item = &CompactionRecord> {
+        self.compacted.iter().map(|c| &c.record)
+    }
+
+    /// Case-insensitive search over the window, including messages that
+    /// have been compacted away.
+    pub fn search(&self, query: &str) -> Vec<&ContextMessage> {
+        let query = query.to_lowercase();
+        self.messages
+            .iter()
+            .chain(self.compacted.iter().flat_map(|c| c.originals.iter()))
+            .filter(|m| m.content.to_lowercase().contains(&query))
+            .collect()
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -324,6 +777,16 @@ mod tests {
         assert_eq!(model_context_size("unknown-model"), 8_000);
     }
 
+    #[test]
+    fn context_window_for_model_counts_exactly() {
+        let mut ctx = ContextWindow::for_model("gpt-4o");
+        assert_eq!(ctx.summary().max_tokens, 128_000);
+
+        // 11 chars: heuristic says 3 tokens, o200k says 2.
+        ctx.push(ContextMessage::new("user", "hello world"));
+        assert_eq!(ctx.total_tokens(), 2);
+    }
+
     #[test]
     fn context_window_zero_capacity() {
         let mut ctx = ContextWindow::new(0);
@@ -332,4 +795,229 @@ mod tests {
         // Should prune immediately, but if all messages are needed it stays over
         assert!(ctx.is_over_budget() || ctx.message_count() == 0);
     }
+
+    // -- Compaction ---------------------------------------------------------
+
+    /// A 100-token window holding `n` 10-token messages with IDs `m0..`.
+    fn filled_window(n: usize) -> ContextWindow {
+        let mut ctx = ContextWindow::new(100);
+        for i in 0..n {
+            let role = if i % 2 == 0 { "user" } else { "assistant" };
+            ctx.push(ContextMessage::new(role, format!("{i:0>40}")).with_id(format!("m{i}")));
+        }
+        ctx
+    }
+
+    fn ids(messages: &[ContextMessage]) -> Vec<&str> {
+        messages.iter().filter_map(|m| m.id.as_deref()).collect()
+    }
+
+    fn memory(summary: &str) -> SessionMemory {
+        SessionMemory {
+            summary: summary.into(),
+            decisions: vec!["Use SQLite".into()],
+            ..SessionMemory::default()
+        }
+    }
+
+    #[test]
+    fn compaction_plan_selects_oldest_outside_recent() {
+        let ctx = filled_window(10);
+        assert!(ctx.needs_compaction(&CompactionConfig::default()));
+
+        let plan = ctx.compaction_plan(&CompactionConfig::default()).unwrap();
+        // 100 tokens down to 50, but only the 4 messages before the 6
+        // most recent are eligible.
+        assert_eq!(ids(&plan.messages), ["m0", "m1", "m2", "m3"]);
+        assert_eq!(plan.tokens, 40);
+        assert!(plan.prompt().contains("[assistant]"));
+    }
+
+    #[test]
+    fn compaction_plan_none_below_target_or_min_span() {
+        let ctx = filled_window(4);
+        assert!(!ctx.needs_compaction(&CompactionConfig::default()));
+        assert!(ctx.compaction_plan(&CompactionConfig::default()).is_none());
+
+        let config = CompactionConfig {
+            keep_recent: 7,
+            ..CompactionConfig::default()
+        };
+        assert!(filled_window(10).compaction_plan(&config).is_none());
+    }
+
+    #[test]
+    fn compaction_skips_pinned_and_referenced_messages() {
+        let mut ctx = ContextWindow::new(100);
+        ctx.push(
+            ContextMessage::new("system", "a".repeat(40))
+                .pinned()
+                .with_id("pin"),
+        );
+        ctx.push(ContextMessage::new("tool", "b".repeat(40)).with_id("tool"));
+        for i in 0..3 {
+            ctx.push(ContextMessage::new("user", "c".repeat(40)).with_id(format!("u{i}")));
+        }
+        // A recent answer still relies on the tool output.
+        ctx.push(ContextMessage::new("assistant", "d".repeat(40)).referencing("tool"));
+        let config = CompactionConfig {
+            keep_recent: 1,
+            min_messages: 1,
+            ..CompactionConfig::default()
+        };
+
+        let plan = ctx.compaction_plan(&config).unwrap();
+        assert_eq!(ids(&plan.messages), ["u0"]);
+    }
+
+    #[test]
+    fn compaction_releases_references_compacted_together() {
+        let mut ctx = ContextWindow::new(100);
+        ctx.push(ContextMessage::new("tool", "a".repeat(40)).with_id("tool"));
+        ctx.push(
+            ContextMessage::new("assistant", "b".repeat(40))
+                .with_id("answer")
+                .referencing("tool"),
+        );
+        for _ in 0..7 {
+            ctx.push(ContextMessage::new("user", "c".repeat(40)));
+        }
+        let config = CompactionConfig {
+            min_messages: 1,
+            ..CompactionConfig::default()
+        };
+
+        let plan = ctx.compaction_plan(&config).unwrap();
+        assert_eq!(&ids(&plan.messages)[..2], ["tool", "answer"]);
+    }
+
+    #[test]
+    fn apply_compaction_replaces_span_and_keeps_originals() {
+        let mut ctx = filled_window(10);
+        let plan = ctx.compaction_plan(&CompactionConfig::default()).unwrap();
+        let before = ctx.total_tokens();
+
+        let record = ctx
+            .apply_compaction(
+                &plan,
+                memory("Chose a storage backend"),
+                Some("gpt-4o-mini"),
+            )
+            .unwrap();
+
+        assert_eq!(record.message_ids, ["m0", "m1", "m2", "m3"]);
+        assert_eq!(record.tokens_before, before);
+        assert_eq!(record.tokens_after, ctx.total_tokens());
+        assert_eq!(ctx.message_count(), 7);
+        let first = &ctx.messages()[0];
+        assert_eq!(first.role, "system");
+        assert!(first.content.contains("Chose a storage backend"));
+        assert!(first.content.contains("- Use SQLite"));
+        assert_eq!(ctx.messages()[1].id.as_deref(), Some("m4"));
+
+        // Compacted originals remain searchable.
+        assert_eq!(ctx.search(&"0".repeat(40)).len(), 1);
+        assert_eq!(ctx.summary().compaction_count, 1);
+    }
+
+    #[test]
+    fn apply_compaction_rejects_stale_plan() {
+        let mut ctx = filled_window(10);
+        let plan = ctx.compaction_plan(&CompactionConfig::default()).unwrap();
+        ctx.clear();
+        assert!(ctx.apply_compaction(&plan, memory("x"), None).is_err());
+    }
+
+    #[test]
+    fn undo_compaction_restores_original_order() {
+        let mut ctx = ContextWindow::new(100);
+        for i in 0..10 {
+            let message = ContextMessage::new("user", "a".repeat(40)).with_id(format!("m{i}"));
+            // Pin m1 so the compacted span is not contiguous.
+            ctx.push(if i == 1 { message.pinned() } else { message });
+        }
+        let original: Vec<String> = ctx.messages().iter().filter_map(|m| m.id.clone()).collect();
+        let config = CompactionConfig {
+            min_messages: 2,
+            ..CompactionConfig::default()
+        };
+        let plan = ctx.compaction_plan(&config).unwrap();
+        assert_eq!(ids(&plan.messages), ["m0", "m2", "m3"]);
+        let record = ctx.apply_compaction(&plan, memory("x"), None).unwrap();
+        assert_eq!(ctx.messages()[1].id.as_deref(), Some("m1"));
+
+        let undone = ctx.undo_last_compaction().unwrap();
+        assert_eq!(undone.id, record.id);
+        assert_eq!(ids(ctx.messages()), original);
+        assert!(ctx.undo_last_compaction().is_none());
+    }
+
+    #[test]
+    fn from_conversation_replays_stored_compactions() {
+        let mut conversation = Conversation::new("unknown-model");
+        for i in 0..6 {
+            conversation.add_message(crate::conversations::StoredMessage {
+                role: "user".into(),
+                content: format!("message {i}"),
+                timestamp: Utc::now(),
+                model: None,
+                cost: None,
+                tokens: None,
+                thinking: None,
+                id: None,
+                parent_id: None,
+            });
+        }
+        let compacted: Vec<String> = conversation.messages[..3]
+            .iter()
+            .filter_map(|m| m.id.clone())
+            .collect();
+        conversation.compactions.push(CompactionRecord {
+            id: "c1".into(),
+            memory: memory("first three"),
+            message_ids: compacted,
+            tokens_before: 0,
+            tokens_after: 0,
+            model: None,
+            created_at: Utc::now(),
+        });
+
+        let mut ctx = ContextWindow::from_conversation(&conversation, "unknown-model");
+        assert_eq!(ctx.message_count(), 4);
+        assert_eq!(ctx.messages()[0].id.as_deref(), Some("c1"));
+        assert_eq!(ctx.search("message 0").len(), 1);
+
+        ctx.undo_last_compaction().unwrap();
+        assert_eq!(ctx.message_count(), 6);
+        assert_eq!(ctx.messages()[0].content, "message 0");
+    }
+
+    #[test]
+    fn session_memory_parse_variants() {
+        let fenced = "Here you go:\n```json\n{\"summary\": \"s\", \"open_tasks\": [\"t\"]}\n```";
+        let parsed = SessionMemory::parse(fenced);
+        assert_eq!(parsed.summary, "s");
+        assert_eq!(parsed.open_tasks, ["t"]);
+
+        let plain = SessionMemory::parse("  just prose  ");
+        assert_eq!(plain.summary, "just prose");
+        assert!(plain.decisions.is_empty());
+    }
+
+    #[test]
+    fn compaction_record_round_trips_json() {
+        let record = CompactionRecord {
+            id: "c1".into(),
+            memory: memory("s"),
+            message_ids: vec!["m0".into()],
+            tokens_before: 10,
+            tokens_after: 5,
+            model: Some("gpt-4o-mini".into()),
+            created_at: Utc::now(),
+        };
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(json.contains("messageIds"));
+        let back: CompactionRecord = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, record);
+    }
 }
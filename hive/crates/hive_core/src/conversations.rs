@@ -1,10 +1,12 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::PathBuf;
 use tracing::warn;
 
 use crate::config::HiveConfig;
+use crate::context::CompactionRecord;
 
 // ---------------------------------------------------------------------------
 // Data types — JSON-compatible with the Electron reference (main.ts)
@@ -27,9 +29,21 @@ pub struct StoredMessage {
     pub tokens: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub thinking: Option<String>,
+    /// Stable message ID. Absent in files written before branching existed;
+    /// assigned by [`Conversation::normalize_tree`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// ID of the message this one follows. `None` for the first message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
 }
 
 /// Full conversation persisted as `{id}.json`.
+///
+/// Messages form a tree: editing a prompt or regenerating a reply adds a
+/// sibling instead of overwriting. `messages` always holds the active path
+/// from the root to the current leaf (so readers that predate branching
+/// still see a linear chat), and `branches` holds every other node.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Conversation {
@@ -41,6 +55,14 @@ pub struct Conversation {
     pub total_tokens: u32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Messages that are not on the active path.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub branches: Vec<StoredMessage>,
+    /// Session-memory summaries that stand in for older messages when the
+    /// context is rebuilt, oldest first. The summarized messages stay in
+    /// `messages` for search and undo.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compactions: Vec<CompactionRecord>,
 }
 
 // ---------------------------------------------------------------------------
@@ -66,6 +88,8 @@ struct ConversationMeta {
     id: String,
     title: String,
     messages: Vec<MessageMeta>,
+    #[serde(default)]
+    branches: Vec<MessageMeta>,
     model: String,
     #[serde(default)]
     total_cost: f64,
@@ -207,8 +231,9 @@ impl ConversationStore {
         let path = self.path_for(id)?;
         let content = std::fs::read_to_string(&path)
             .with_context(|| format!("Conversation not found: {}", path.display()))?;
-        let conv: Conversation = serde_json::from_str(&content)
+        let mut conv: Conversation = serde_json::from_str(&content)
             .with_context(|| format!("Failed to parse conversation: {}", path.display()))?;
+        conv.normalize_tree();
         Ok(conv)
     }
 
@@ -275,7 +300,8 @@ impl ConversationStore {
         Ok(summaries)
     }
 
-    /// Case-insensitive search across title and message content.
+    /// Case-insensitive search across title and message content, including
+    /// messages on inactive branches.
     ///
     /// For large conversation stores, prefer `Database::search_conversations`
     /// which uses an FTS5 full-text index.  This file-scanning fallback
@@ -311,6 +337,7 @@ impl ConversationStore {
                 || meta
                     .messages
                     .iter()
+                    .chain(meta.branches.iter())
                     .any(|m| m.content.to_lowercase().contains(&query_lower));
 
             if matched {
@@ -381,11 +408,19 @@ impl Conversation {
             total_tokens: 0,
             created_at: now,
             updated_at: now,
+            branches: Vec::new(),
+            compactions: Vec::new(),
         }
     }
 
-    /// Appends a message and updates the title / timestamps.
-    pub fn add_message(&mut self, msg: StoredMessage) {
+    /// Appends a message to the end of the active path and updates the
+    /// title / timestamps. The message is given an ID if it has none and is
+    /// parented to the current leaf.
+    pub fn add_message(&mut self, mut msg: StoredMessage) {
+        if msg.id.is_none() {
+            msg.id = Some(uuid::Uuid::new_v4().to_string());
+        }
+        msg.parent_id = self.messages.last().and_then(|m| m.id.clone());
         if let Some(cost) = msg.cost {
             self.total_cost += cost;
         }
@@ -398,6 +433,197 @@ impl Conversation {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Branching
+// ---------------------------------------------------------------------------
+
+impl Conversation {
+    /// Gives every message an ID and re-links the active path so each message
+    /// is parented to its predecessor. Needed for files saved before
+    /// branching existed and after callers filter messages out of the path.
+    pub fn normalize_tree(&mut self) {
+        for msg in self.messages.iter_mut().chain(self.branches.iter_mut()) {
+            if msg.id.is_none() {
+                msg.id = Some(uuid::Uuid::new_v4().to_string());
+            }
+        }
+        let mut parent: Option<String> = None;
+        for msg in &mut self.messages {
+            msg.parent_id = parent.take();
+            parent = msg.id.clone();
+        }
+    }
+
+    /// Iterates over every message in the tree, active path first.
+    pub fn all_messages(&self) -> impl Iterator<Item = &StoredMessage> {
+        self.messages.iter().chain(self.branches.iter())
+    }
+
+    /// Looks up a message anywhere in the tree.
+    pub fn find_message(&self, id: &str) -> Option<&StoredMessage> {
+        self.all_messages().find(|m| m.id.as_deref() == Some(id))
+    }
+
+    /// Children of `parent_id` (the root messages when `None`), oldest first.
+    pub fn children(&self, parent_id: Option<&str>) -> Vec<&StoredMessage> {
+        let mut children: Vec<&StoredMessage> = self
+            .all_messages()
+            .filter(|m| m.id.is_some() && m.parent_id.as_deref() == parent_id)
+            .collect();
+        children.sort_by_key(|m| m.timestamp);
+        children
+    }
+
+    /// The message `id` and its alternatives (same parent), oldest first.
+    pub fn siblings(&self, id: &str) -> Vec<&StoredMessage> {
+        match self.find_message(id) {
+            Some(msg) => self.children(msg.parent_id.as_deref()),
+            None => Vec::new(),
+        }
+    }
+
+    /// Zero-based position of `id` among its siblings and the sibling count,
+    /// e.g. `(1, 3)` for the second of three versions.
+    pub fn sibling_position(&self, id: &str) -> Option<(usize, usize)> {
+        let siblings = self.siblings(id);
+        let index = siblings.iter().position(|m| m.id.as_deref() == Some(id))?;
+        Some((index, siblings.len()))
+    }
+
+    /// The chain of messages from the root down to `id`, inclusive.
+    pub fn path_to(&self, id: &str) -> Result<Vec<StoredMessage>> {
+        let limit = self.messages.len() + self.branches.len();
+        let mut path = Vec::new();
+        let mut current = Some(id.to_string());
+        while let Some(cur) = current {
+            if path.len() >= limit {
+                anyhow::bail!("Message tree has a cycle at {cur}");
+            }
+            let msg = self
+                .find_message(&cur)
+                .with_context(|| format!("Message not found: {cur}"))?;
+            current = msg.parent_id.clone();
+            path.push(msg.clone());
+        }
+        path.reverse();
+        Ok(path)
+    }
+
+    /// Truncates the active path so that `id` becomes the leaf, or empties
+    /// it when `None`. Later messages move to `branches` rather than being
+    /// deleted, so the next [`add_message`](Self::add_message) starts a new
+    /// branch.
+    pub fn rewind_to(&mut self, id: Option<&str>) -> Result<()> {
+        let path = match id {
+            Some(id) => self.path_to(id)?,
+            None => Vec::new(),
+        };
+        self.set_active_path(path);
+        Ok(())
+    }
+
+    /// Makes `id` part of the active path, continuing below it through the
+    /// most recent child at each level.
+    pub fn switch_to(&mut self, id: &str) -> Result<()> {
+        let limit = self.messages.len() + self.branches.len();
+        let mut path = self.path_to(id)?;
+        while path.len() < limit {
+            let leaf_id = path.last().and_then(|m| m.id.as_deref());
+            let Some(child) = self.children(leaf_id).last().map(|m| (*m).clone()) else {
+                break;
+            };
+            path.push(child);
+        }
+        self.set_active_path(path);
+        Ok(())
+    }
+
+    /// Records `content` as an edited version of message `id`: a sibling with
+    /// the same role is added under the same parent and becomes the leaf.
+    /// The original and everything after it stay in `branches`. Returns the
+    /// ID of the new message.
+    pub fn edit_message(&mut self, id: &str, content: impl Into<String>) -> Result<String> {
+        let original = self
+            .find_message(id)
+            .with_context(|| format!("Message not found: {id}"))?;
+        let role = original.role.clone();
+        let parent_id = original.parent_id.clone();
+
+        self.rewind_to(parent_id.as_deref())?;
+        let new_id = uuid::Uuid::new_v4().to_string();
+        self.add_message(StoredMessage {
+            role,
+            content: content.into(),
+            timestamp: Utc::now(),
+            model: None,
+            cost: None,
+            tokens: None,
+            thinking: None,
+            id: Some(new_id.clone()),
+            parent_id: None,
+        });
+        Ok(new_id)
+    }
+
+    /// Prepares to regenerate the assistant reply `id` by rewinding to the
+    /// prompt it answered. The new reply, added with
+    /// [`add_message`](Self::add_message), becomes a sibling of `id`.
+    pub fn prepare_regenerate(&mut self, id: &str) -> Result<()> {
+        let reply = self
+            .find_message(id)
+            .with_context(|| format!("Message not found: {id}"))?;
+        if reply.role != "assistant" {
+            anyhow::bail!("Only assistant replies can be regenerated");
+        }
+        let prompt_id = reply
+            .parent_id
+            .clone()
+            .context("Reply has no prompt to regenerate from")?;
+        self.rewind_to(Some(&prompt_id))
+    }
+
+    /// Copies the path from the root to `id` into a new conversation with a
+    /// fresh ID. Other branches are not copied.
+    pub fn fork_at(&self, id: &str) -> Result<Conversation> {
+        let messages = self.path_to(id)?;
+        let on_path: HashSet<&str> = messages.iter().filter_map(|m| m.id.as_deref()).collect();
+        let compactions = self
+            .compactions
+            .iter()
+            .filter(|c| c.message_ids.iter().all(|id| on_path.contains(id.as_str())))
+            .cloned()
+            .collect();
+        let now = Utc::now();
+        Ok(Conversation {
+            id: uuid::Uuid::new_v4().to_string(),
+            title: generate_title(&messages),
+            total_cost: messages.iter().filter_map(|m| m.cost).sum(),
+            total_tokens: messages.iter().filter_map(|m| m.tokens).sum(),
+            messages,
+            model: self.model.clone(),
+            created_at: now,
+            updated_at: now,
+            branches: Vec::new(),
+            compactions,
+        })
+    }
+
+    /// Replaces the active path, moving every other node into `branches`.
+    fn set_active_path(&mut self, path: Vec<StoredMessage>) {
+        let active: HashSet<&str> = path.iter().filter_map(|m| m.id.as_deref()).collect();
+        let branches: Vec<StoredMessage> = self
+            .messages
+            .drain(..)
+            .chain(self.branches.drain(..))
+            .filter(|m| !m.id.as_deref().is_some_and(|id| active.contains(id)))
+            .collect();
+        self.branches = branches;
+        self.messages = path;
+        self.title = generate_title(&self.messages);
+        self.updated_at = Utc::now();
+    }
+}
+
 // ===========================================================================
 // Tests
 // ===========================================================================
@@ -433,12 +659,16 @@ mod tests {
                 cost: Some(0.01),
                 tokens: Some(100),
                 thinking: None,
+                id: None,
+                parent_id: None,
             }],
             model: "test-model".into(),
             total_cost: 0.01,
             total_tokens: 100,
             created_at: Utc::now() - chrono::Duration::hours(1),
             updated_at,
+            branches: Vec::new(),
+            compactions: Vec::new(),
         }
     }
 
@@ -539,12 +769,16 @@ mod tests {
                 cost: None,
                 tokens: None,
                 thinking: None,
+                id: None,
+                parent_id: None,
             }],
             model: "gpt-4".into(),
             total_cost: 0.0,
             total_tokens: 0,
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            branches: Vec::new(),
+            compactions: Vec::new(),
         };
 
         let conv2 = Conversation {
@@ -558,12 +792,16 @@ mod tests {
                 cost: None,
                 tokens: None,
                 thinking: None,
+                id: None,
+                parent_id: None,
             }],
             model: "claude".into(),
             total_cost: 0.0,
             total_tokens: 0,
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            branches: Vec::new(),
+            compactions: Vec::new(),
         };
 
         let conv3 = Conversation {
@@ -577,12 +815,16 @@ mod tests {
                 cost: None,
                 tokens: None,
                 thinking: None,
+                id: None,
+                parent_id: None,
             }],
             model: "claude".into(),
             total_cost: 0.0,
             total_tokens: 0,
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            branches: Vec::new(),
+            compactions: Vec::new(),
         };
 
         store.save(&conv1).unwrap();
@@ -620,6 +862,8 @@ mod tests {
             cost: None,
             tokens: None,
             thinking: None,
+            id: None,
+            parent_id: None,
         }];
         assert_eq!(generate_title(&msgs), "Hello");
     }
@@ -635,6 +879,8 @@ mod tests {
             cost: None,
             tokens: None,
             thinking: None,
+            id: None,
+            parent_id: None,
         }];
         let title = generate_title(&msgs);
         assert!(title.ends_with("..."));
@@ -653,6 +899,8 @@ mod tests {
             cost: None,
             tokens: None,
             thinking: None,
+            id: None,
+            parent_id: None,
         }];
         assert_eq!(generate_title(&msgs), "New Conversation");
     }
@@ -732,6 +980,8 @@ mod tests {
             cost: Some(0.005),
             tokens: Some(50),
             thinking: None,
+            id: None,
+            parent_id: None,
         });
 
         assert_eq!(conv.messages.len(), 1);
@@ -759,12 +1009,16 @@ mod tests {
                 cost: None,
                 tokens: None,
                 thinking: None,
+                id: None,
+                parent_id: None,
             }],
             model: "test".into(),
             total_cost: 0.0,
             total_tokens: 0,
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            branches: Vec::new(),
+            compactions: Vec::new(),
         };
         store.save(&conv).unwrap();
 
@@ -785,4 +1039,148 @@ mod tests {
         assert!(store.load("../../../etc/passwd").is_err());
         assert!(store.load("").is_err());
     }
+
+    // -----------------------------------------------------------------------
+    // Branching
+    // -----------------------------------------------------------------------
+
+    fn msg(role: &str, content: &str) -> StoredMessage {
+        StoredMessage {
+            role: role.into(),
+            content: content.into(),
+            timestamp: Utc::now(),
+            model: None,
+            cost: None,
+            tokens: None,
+            thinking: None,
+            id: None,
+            parent_id: None,
+        }
+    }
+
+    /// user "q1" -> assistant "a1" -> user "q2" -> assistant "a2"
+    fn linear_conversation() -> Conversation {
+        let mut conv = Conversation::new("test");
+        for (role, content) in [
+            ("user", "q1"),
+            ("assistant", "a1"),
+            ("user", "q2"),
+            ("assistant", "a2"),
+        ] {
+            conv.add_message(msg(role, content));
+        }
+        conv
+    }
+
+    fn contents(messages: &[StoredMessage]) -> Vec<&str> {
+        messages.iter().map(|m| m.content.as_str()).collect()
+    }
+
+    #[test]
+    fn test_add_message_links_parents() {
+        let conv = linear_conversation();
+        assert!(conv.messages[0].parent_id.is_none());
+        for pair in conv.messages.windows(2) {
+            assert_eq!(pair[1].parent_id, pair[0].id);
+        }
+    }
+
+    #[test]
+    fn test_edit_message_keeps_original_branch() {
+        let mut conv = linear_conversation();
+        let q2 = conv.messages[2].id.clone().unwrap();
+
+        let edited = conv.edit_message(&q2, "q2 edited").unwrap();
+        conv.add_message(msg("assistant", "a2 for edit"));
+
+        assert_eq!(contents(&conv.messages), ["q1", "a1", "q2 edited", "a2 for edit"]);
+        assert_eq!(contents(&conv.branches), ["q2", "a2"]);
+        assert_eq!(conv.sibling_position(&edited), Some((1, 2)));
+        assert_eq!(conv.sibling_position(&q2), Some((0, 2)));
+    }
+
+    #[test]
+    fn test_regenerate_adds_sibling_reply() {
+        let mut conv = linear_conversation();
+        let a2 = conv.messages[3].id.clone().unwrap();
+
+        conv.prepare_regenerate(&a2).unwrap();
+        assert_eq!(contents(&conv.messages), ["q1", "a1", "q2"]);
+        conv.add_message(msg("assistant", "a2 retry"));
+
+        assert_eq!(conv.siblings(&a2).len(), 2);
+        let q1 = conv.messages[0].id.clone().unwrap();
+        assert!(conv.prepare_regenerate(&q1).is_err());
+    }
+
+    #[test]
+    fn test_switch_to_follows_latest_descendant() {
+        let mut conv = linear_conversation();
+        let q2 = conv.messages[2].id.clone().unwrap();
+        let edited = conv.edit_message(&q2, "q2 edited").unwrap();
+        conv.add_message(msg("assistant", "a2 for edit"));
+
+        conv.switch_to(&q2).unwrap();
+        assert_eq!(contents(&conv.messages), ["q1", "a1", "q2", "a2"]);
+
+        conv.switch_to(&edited).unwrap();
+        assert_eq!(contents(&conv.messages), ["q1", "a1", "q2 edited", "a2 for edit"]);
+        assert_eq!(conv.messages.len() + conv.branches.len(), 6);
+    }
+
+    #[test]
+    fn test_fork_at_copies_path_only() {
+        let mut conv = linear_conversation();
+        let a1 = conv.messages[1].id.clone().unwrap();
+        let q2 = conv.messages[2].id.clone().unwrap();
+        conv.edit_message(&q2, "other").unwrap();
+
+        let fork = conv.fork_at(&a1).unwrap();
+        assert_ne!(fork.id, conv.id);
+        assert_eq!(contents(&fork.messages), ["q1", "a1"]);
+        assert!(fork.branches.is_empty());
+        assert_eq!(fork.title, "q1");
+    }
+
+    #[test]
+    fn test_branches_round_trip_and_are_searchable() {
+        let (store, _tmp) = temp_store();
+        let mut conv = linear_conversation();
+        let q2 = conv.messages[2].id.clone().unwrap();
+        conv.edit_message(&q2, "replacement").unwrap();
+        store.save(&conv).unwrap();
+
+        let loaded = store.load(&conv.id).unwrap();
+        assert_eq!(contents(&loaded.messages), ["q1", "a1", "replacement"]);
+        assert_eq!(contents(&loaded.branches), ["q2", "a2"]);
+
+        // "a2" only exists on the inactive branch.
+        let results = store.search("a2").unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].message_count, 3);
+    }
+
+    #[test]
+    fn test_legacy_file_without_ids_is_normalized() {
+        let (store, tmp) = temp_store();
+        let json = r#"{
+            "id": "legacy",
+            "title": "Old",
+            "messages": [
+                {"role": "user", "content": "hi", "timestamp": "2024-01-01T00:00:00Z"},
+                {"role": "assistant", "content": "hello", "timestamp": "2024-01-01T00:00:01Z"}
+            ],
+            "model": "m",
+            "totalCost": 0.0,
+            "totalTokens": 0,
+            "createdAt": "2024-01-01T00:00:00Z",
+            "updatedAt": "2024-01-01T00:00:01Z"
+        }"#;
+        fs::write(tmp.path().join("legacy.json"), json).unwrap();
+
+        let conv = store.load("legacy").unwrap();
+        assert!(conv.messages.iter().all(|m| m.id.is_some()));
+        assert_eq!(conv.messages[1].parent_id, conv.messages[0].id);
+        assert!(conv.branches.is_empty());
+    }
 }
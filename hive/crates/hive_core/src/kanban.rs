@@ -2,7 +2,8 @@ use anyhow::{Result, bail};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
 use uuid::Uuid;
 
 // ---------------------------------------------------------------------------
@@ -408,6 +409,12 @@ impl KanbanBoard {
         self.tasks.iter().find(|t| t.id == id)
     }
 
+    /// Mutable access to a task, for callers that mirror fields from
+    /// elsewhere. Unlike the other mutators this leaves `updated_at` alone.
+    pub fn get_task_mut(&mut self, id: &str) -> Option<&mut KanbanTask> {
+        self.tasks.iter_mut().find(|t| t.id == id)
+    }
+
     /// Returns a slice of all tasks (unordered).
     pub fn all_tasks(&self) -> &[KanbanTask] {
         &self.tasks
@@ -471,6 +478,74 @@ impl Default for KanbanBoard {
     }
 }
 
+// ---------------------------------------------------------------------------
+// KanbanStore  --  shared, lock-guarded access to the board file
+// ---------------------------------------------------------------------------
+
+/// Shared handle on a board file.
+///
+/// Every writer in the process (the Kanban panel, the coordinator's tracker,
+/// automation actions) goes through a store, and all stores on the same path
+/// share one lock. [`update`](Self::update) reloads the file, applies the
+/// change and saves it while holding that lock, so one writer never
+/// overwrites another's moves with a stale copy.
+#[derive(Clone)]
+pub struct KanbanStore {
+    path: PathBuf,
+    lock: Arc<Mutex<()>>,
+}
+
+impl KanbanStore {
+    /// Store backed by the board file at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        static LOCKS: OnceLock<Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>> = OnceLock::new();
+
+        let path = path.into();
+        let lock = LOCKS
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(path.clone())
+            .or_default()
+            .clone();
+        Self { path, lock }
+    }
+
+    /// Store backed by `~/.hive/kanban.json`.
+    pub fn open_default() -> Result<Self> {
+        Ok(Self::new(
+            crate::config::HiveConfig::base_dir()?.join("kanban.json"),
+        ))
+    }
+
+    /// Path of the board file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read the current board.
+    pub fn load(&self) -> Result<KanbanBoard> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        KanbanBoard::load_from_file(&self.path)
+    }
+
+    /// Replace the board on disk.
+    pub fn save(&self, board: &KanbanBoard) -> Result<()> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        board.save_to_file(&self.path)
+    }
+
+    /// Reload the board, apply `change` and save the result. Nothing is
+    /// written when `change` fails.
+    pub fn update<T>(&self, change: impl FnOnce(&mut KanbanBoard) -> Result<T>) -> Result<T> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut board = KanbanBoard::load_from_file(&self.path)?;
+        let value = change(&mut board)?;
+        board.save_to_file(&self.path)?;
+        Ok(value)
+    }
+}
+
 // ===========================================================================
 // Tests
 // ===========================================================================
@@ -954,6 +1029,55 @@ mod tests {
         let _ = std::fs::remove_file(&path);
     }
 
+    #[test]
+    fn concurrent_store_updates_keep_every_write() {
+        let dir = std::env::temp_dir().join(format!("hive-kanban-store-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = KanbanStore::new(dir.join("kanban.json"));
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                // Separately opened stores on one path serialize too.
+                let store = if i % 2 == 0 {
+                    store.clone()
+                } else {
+                    KanbanStore::new(dir.join("kanban.json"))
+                };
+                std::thread::spawn(move || {
+                    store
+                        .update(|board| {
+                            Ok(board.add_task(format!("Task {i}"), None, Priority::Low))
+                        })
+                        .unwrap()
+                })
+            })
+            .collect();
+        let first = handles
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .next()
+            .unwrap();
+
+        store
+            .update(|board| board.move_task(&first.id, KanbanColumn::Done))
+            .unwrap();
+        let board = store.load().unwrap();
+        assert_eq!(board.all_tasks().len(), 8);
+        assert_eq!(
+            board.get_task(&first.id).unwrap().column,
+            KanbanColumn::Done
+        );
+
+        // A failed change leaves the file untouched.
+        assert!(
+            store
+                .update(|board| board.move_task("missing", KanbanColumn::Done))
+                .is_err()
+        );
+        assert_eq!(store.load().unwrap().all_tasks().len(), 8);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn load_missing_file_returns_empty() {
         let path = std::env::temp_dir().join("nonexistent-hive-kanban.json");
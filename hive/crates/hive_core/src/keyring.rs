@@ -0,0 +1,328 @@
+//! OS keyring backends for holding the SecureStorage master key.
+//!
+//! On Linux the desktop keyrings are reached through their command-line
+//! front-ends (`secret-tool` for the freedesktop Secret Service, and
+//! `kwallet-query` for KWallet), so no D-Bus bindings are needed at build
+//! time. Availability is probed at runtime; callers fall back to the
+//! machine-derived or passphrase-derived key when no backend is present.
+
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Service name under which Hive stores its secrets in the OS keyring.
+pub const KEYRING_SERVICE: &str = "hive";
+
+/// A place where small secrets (such as the master key) can be stored
+/// outside of `~/.hive`.
+pub trait KeyringBackend: Send + Sync {
+    /// Stable identifier persisted in `master_key.json` (e.g. `"secret-service"`).
+    fn name(&self) -> &'static str;
+
+    /// Whether the backend can be used on this machine right now.
+    fn is_available(&self) -> bool;
+
+    /// Read a secret. Returns `Ok(None)` if no entry exists for `account`.
+    fn get_secret(&self, account: &str) -> Result<Option<String>>;
+
+    /// Create or overwrite the secret stored for `account`.
+    fn set_secret(&self, account: &str, secret: &str) -> Result<()>;
+
+    /// Delete the secret stored for `account`. Deleting a missing entry is not an error.
+    fn delete_secret(&self, account: &str) -> Result<()>;
+}
+
+/// Returns `true` if `program` can be spawned (i.e. it is on `PATH`).
+fn command_exists(program: &str) -> bool {
+    Command::new(program)
+        .arg("--help")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok()
+}
+
+/// Run `cmd`, feeding `input` on stdin, and return its stdout on success.
+fn run_with_stdin(mut cmd: Command, input: Option<&str>) -> Result<std::process::Output> {
+    cmd.stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = cmd.spawn().context("Failed to spawn keyring helper")?;
+    // Dropping stdin closes the pipe so the helper sees EOF.
+    if let Some(mut stdin) = child.stdin.take()
+        && let Some(input) = input
+    {
+        stdin
+            .write_all(input.as_bytes())
+            .context("Failed to write secret to keyring helper")?;
+    }
+    child
+        .wait_with_output()
+        .context("Failed to wait for keyring helper")
+}
+
+// ---------------------------------------------------------------------------
+// freedesktop Secret Service (GNOME Keyring, KeePassXC, ...)
+// ---------------------------------------------------------------------------
+
+/// Secret Service backend driven through libsecret's `secret-tool`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SecretServiceBackend;
+
+impl KeyringBackend for SecretServiceBackend {
+    fn name(&self) -> &'static str {
+        "secret-service"
+    }
+
+    fn is_available(&self) -> bool {
+        cfg!(target_os = "linux")
+            && std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_some()
+            && command_exists("secret-tool")
+    }
+
+    fn get_secret(&self, account: &str) -> Result<Option<String>> {
+        let mut cmd = Command::new("secret-tool");
+        cmd.args(["lookup", "service", KEYRING_SERVICE, "account", account]);
+        let output = run_with_stdin(cmd, None)?;
+        // secret-tool exits non-zero when the entry does not exist.
+        if !output.status.success() || output.stdout.is_empty() {
+            return Ok(None);
+        }
+        let secret = String::from_utf8(output.stdout).context("Keyring secret is not UTF-8")?;
+        Ok(Some(secret.trim_end_matches('\n').to_string()))
+    }
+
+    fn set_secret(&self, account: &str, secret: &str) -> Result<()> {
+        let mut cmd = Command::new("secret-tool");
+        cmd.args([
+            "store",
+            "--label",
+            &format!("Hive ({account})"),
+            "service",
+            KEYRING_SERVICE,
+            "account",
+            account,
+        ]);
+        let output = run_with_stdin(cmd, Some(secret))?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "secret-tool store failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+
+    fn delete_secret(&self, account: &str) -> Result<()> {
+        let mut cmd = Command::new("secret-tool");
+        cmd.args(["clear", "service", KEYRING_SERVICE, "account", account]);
+        run_with_stdin(cmd, None)?;
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// KWallet
+// ---------------------------------------------------------------------------
+
+/// KWallet backend driven through `kwallet-query`.
+#[derive(Debug, Clone)]
+pub struct KWalletBackend {
+    /// Wallet to open, `kdewallet` by default.
+    pub wallet: String,
+}
+
+impl Default for KWalletBackend {
+    fn default() -> Self {
+        Self {
+            wallet: "kdewallet".into(),
+        }
+    }
+}
+
+impl KWalletBackend {
+    fn entry_name(account: &str) -> String {
+        format!("{KEYRING_SERVICE}-{account}")
+    }
+}
+
+impl KeyringBackend for KWalletBackend {
+    fn name(&self) -> &'static str {
+        "kwallet"
+    }
+
+    fn is_available(&self) -> bool {
+        cfg!(target_os = "linux") && command_exists("kwallet-query")
+    }
+
+    fn get_secret(&self, account: &str) -> Result<Option<String>> {
+        let mut cmd = Command::new("kwallet-query");
+        cmd.args(["-f", "Hive", "-r", &Self::entry_name(account), &self.wallet]);
+        let output = run_with_stdin(cmd, None)?;
+        if !output.status.success() || output.stdout.is_empty() {
+            return Ok(None);
+        }
+        let secret = String::from_utf8(output.stdout).context("Keyring secret is not UTF-8")?;
+        Ok(Some(secret.trim_end_matches('\n').to_string()))
+    }
+
+    fn set_secret(&self, account: &str, secret: &str) -> Result<()> {
+        let mut cmd = Command::new("kwallet-query");
+        cmd.args(["-f", "Hive", "-w", &Self::entry_name(account), &self.wallet]);
+        let output = run_with_stdin(cmd, Some(secret))?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "kwallet-query write failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+
+    fn delete_secret(&self, account: &str) -> Result<()> {
+        if self.get_secret(account)?.is_none() {
+            return Ok(());
+        }
+        // kwallet-query has no delete verb, so go through kwalletd's D-Bus
+        // interface: open the wallet for a handle, then remove the entry.
+        let entry = Self::entry_name(account);
+        for daemon in ["kwalletd6", "kwalletd5"] {
+            let service = format!("org.kde.{daemon}");
+            let path = format!("/modules/{daemon}");
+            let mut open = Command::new("qdbus");
+            open.args([
+                service.as_str(),
+                path.as_str(),
+                "org.kde.KWallet.open",
+                self.wallet.as_str(),
+                "0",
+                KEYRING_SERVICE,
+            ]);
+            let Ok(output) = run_with_stdin(open, None) else {
+                continue;
+            };
+            let handle = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if !output.status.success() || handle.parse::<i32>().is_err() {
+                continue;
+            }
+
+            let mut remove = Command::new("qdbus");
+            remove.args([
+                service.as_str(),
+                path.as_str(),
+                "org.kde.KWallet.removeEntry",
+                handle.as_str(),
+                "Hive",
+                entry.as_str(),
+                KEYRING_SERVICE,
+            ]);
+            let output = run_with_stdin(remove, None)?;
+            if !output.status.success() || String::from_utf8_lossy(&output.stdout).trim() != "0" {
+                anyhow::bail!(
+                    "KWallet removeEntry failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            return Ok(());
+        }
+        anyhow::bail!("Cannot delete KWallet entry {entry}: kwalletd is not reachable over D-Bus")
+    }
+}
+
+// ---------------------------------------------------------------------------
+// In-memory backend
+// ---------------------------------------------------------------------------
+
+/// Process-local keyring. Secrets are lost when the process exits, so this is
+/// only useful for tests and ephemeral headless runs.
+#[derive(Debug, Default)]
+pub struct MemoryKeyring {
+    entries: Mutex<HashMap<String, String>>,
+}
+
+impl MemoryKeyring {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl KeyringBackend for MemoryKeyring {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn get_secret(&self, account: &str) -> Result<Option<String>> {
+        Ok(self.entries.lock().get(account).cloned())
+    }
+
+    fn set_secret(&self, account: &str, secret: &str) -> Result<()> {
+        self.entries
+            .lock()
+            .insert(account.to_string(), secret.to_string());
+        Ok(())
+    }
+
+    fn delete_secret(&self, account: &str) -> Result<()> {
+        self.entries.lock().remove(account);
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Lookup helpers
+// ---------------------------------------------------------------------------
+
+/// Resolve a persisted backend name to a system backend.
+///
+/// `"memory"` is intentionally not resolvable: an in-memory key cannot be
+/// recovered on the next start.
+pub fn backend_by_name(name: &str) -> Option<Box<dyn KeyringBackend>> {
+    match name {
+        "secret-service" => Some(Box::new(SecretServiceBackend)),
+        "kwallet" => Some(Box::new(KWalletBackend::default())),
+        _ => None,
+    }
+}
+
+/// Return the first system keyring that is usable on this machine, preferring
+/// the Secret Service over KWallet.
+pub fn detect_backend() -> Option<Box<dyn KeyringBackend>> {
+    let candidates: [Box<dyn KeyringBackend>; 2] = [
+        Box::new(SecretServiceBackend),
+        Box::new(KWalletBackend::default()),
+    ];
+    candidates.into_iter().find(|b| b.is_available())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_keyring_roundtrip() {
+        let kr = MemoryKeyring::new();
+        assert!(kr.get_secret("master-key").unwrap().is_none());
+        kr.set_secret("master-key", "abc").unwrap();
+        assert_eq!(kr.get_secret("master-key").unwrap().as_deref(), Some("abc"));
+        kr.delete_secret("master-key").unwrap();
+        assert!(kr.get_secret("master-key").unwrap().is_none());
+    }
+
+    #[test]
+    fn backend_by_name_resolves_system_backends() {
+        assert_eq!(
+            backend_by_name("secret-service").unwrap().name(),
+            "secret-service"
+        );
+        assert_eq!(backend_by_name("kwallet").unwrap().name(), "kwallet");
+        assert!(backend_by_name("memory").is_none());
+        assert!(backend_by_name("bogus").is_none());
+    }
+}
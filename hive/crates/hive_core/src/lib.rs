@@ -21,6 +21,8 @@ pub mod conversations;
 pub mod enterprise;
 /// Error classification, severity levels, and user-friendly error messages.
 pub mod error_handler;
+/// OS keyring backends (Secret Service, KWallet) for the storage master key.
+pub mod keyring;
 /// Kanban board with WIP limits, subtasks, dependencies, and metrics.
 pub mod kanban;
 /// Logging initialization with daily file rotation and console output.
@@ -29,9 +31,15 @@ pub mod logging;
 pub mod notifications;
 /// SQLite-backed persistence for conversations, memory, and cost tracking.
 pub mod persistence;
+/// User-defined routing rules that override automatic model routing.
+pub mod routing_rules;
+/// Unified full-text search index (SQLite FTS5, BM25) across all knowledge
+/// sources, stored under `~/.hive/index/`.
+pub mod search;
 /// Cron-based task scheduler with job lifecycle management.
 pub mod scheduler;
-/// AES-256-GCM encrypted storage for API keys and sensitive data.
+/// AES-256-GCM encrypted storage for API keys and sensitive data, with an
+/// optional passphrase-protected or keyring-held master key.
 pub mod secure_storage;
 /// Security gateway for command, URL, path, and injection validation.
 pub mod security;
@@ -39,6 +47,8 @@ pub mod security;
 pub mod session;
 /// Theme data model, built-in themes, and file management (`~/.hive/themes/`).
 pub mod theme_manager;
+/// Model-aware token counting (tiktoken BPE tables and local tokenizer files).
+pub mod tokenizer;
 /// Auto-update service — checks GitHub for newer releases and installs updates.
 pub mod updater;
 
@@ -50,7 +60,8 @@ pub use code_review::{
 };
 pub use config::HiveConfig;
 pub use context::{
-    ContextMessage, ContextSummary, ContextWindow, estimate_tokens, model_context_size,
+    CompactionConfig, CompactionPlan, CompactionRecord, ContextMessage, ContextSummary,
+    ContextWindow, SessionMemory, estimate_tokens, model_context_size,
 };
 pub use conversations::{Conversation, ConversationStore, ConversationSummary, StoredMessage};
 pub use enterprise::{
@@ -60,14 +71,19 @@ pub use error_handler::{
     ClassifiedCategory, ClassifiedError, ErrorCategory, ErrorSeverity, HiveError, classify_error,
 };
 pub use kanban::{
-    BoardMetrics, KanbanBoard, KanbanColumn, KanbanTask, Priority, Subtask, TaskComment,
+    BoardMetrics, KanbanBoard, KanbanColumn, KanbanStore, KanbanTask, Priority, Subtask,
+    TaskComment,
 };
 pub use notifications::{AppNotification, NotificationStore, NotificationType};
 pub use persistence::{ConversationRow, Database, LogRow, MemoryEntry, MessageRow, ModelCostRow};
+pub use routing_rules::{HourRange, RoutingRule, RuleAction, RuleConditions, RuleInput};
+pub use search::{SearchDocument, SearchHit, SearchHub, SearchQuery, SearchSource};
 pub use scheduler::{CronSchedule, ScheduledJob, Scheduler};
-pub use secure_storage::SecureStorage;
+pub use keyring::{KeyringBackend, detect_backend};
+pub use secure_storage::{MasterKeySettings, MasterKeySource, SecureStorage};
 pub use security::SecurityGateway;
 pub use session::SessionState;
 pub use channels::{AgentChannel, ChannelMessage, ChannelStore, ChannelThread, MessageAuthor};
 pub use theme_manager::{ThemeColors, ThemeDefinition, ThemeFonts, ThemeManager};
+pub use tokenizer::{TokenizerFamily, TokenizerRegistry, count_tokens};
 pub use updater::{UpdateInfo, UpdateService};
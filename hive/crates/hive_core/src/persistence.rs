@@ -1,10 +1,11 @@
 use anyhow::{Context, Result};
 use rusqlite::{Connection, params};
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use tracing::{info, warn};
 
 use crate::config::HiveConfig;
-use crate::conversations::Conversation;
+use crate::conversations::{Conversation, StoredMessage};
 
 // ---------------------------------------------------------------------------
 // Row types
@@ -25,6 +26,9 @@ pub struct ConversationRow {
 #[derive(Debug, Clone)]
 pub struct MessageRow {
     pub id: i64,
+    /// Row ID of the message this one follows; `None` for a root message.
+    /// Edits and regenerations are siblings sharing the same parent.
+    pub parent_id: Option<i64>,
     pub role: String,
     pub content: String,
     pub model: Option<String>,
@@ -62,11 +66,32 @@ pub struct ModelCostRow {
     pub request_count: u64,
 }
 
+/// Maps a row selected as `id, parent_id, role, content, model, cost, tokens,
+/// created_at` to a [`MessageRow`].
+fn message_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<MessageRow> {
+    Ok(MessageRow {
+        id: row.get(0)?,
+        parent_id: row.get(1)?,
+        role: row.get(2)?,
+        content: row.get(3)?,
+        model: row.get(4)?,
+        cost: row.get(5)?,
+        tokens: row.get::<_, Option<i64>>(6)?.map(|v| v as u32),
+        created_at: row.get(7)?,
+    })
+}
+
 // ---------------------------------------------------------------------------
 // Database
 // ---------------------------------------------------------------------------
 
 /// SQLite database for conversations, messages, memory entries, and cost tracking.
+///
+/// For conversations, the JSON files written by
+/// [`ConversationStore`](crate::conversations::ConversationStore) are the
+/// source of truth, including the message tree. This database mirrors them
+/// (see [`backfill_from_json`](Self::backfill_from_json)) for search and
+/// reporting; branches are edited on [`Conversation`], never here.
 pub struct Database {
     conn: Connection,
 }
@@ -113,12 +138,14 @@ impl Database {
                 title TEXT NOT NULL,
                 model TEXT NOT NULL DEFAULT '',
                 created_at TEXT NOT NULL DEFAULT (datetime('now')),
-                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+                updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+                active_leaf_id INTEGER
             );
 
             CREATE TABLE IF NOT EXISTS messages (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+                parent_id INTEGER,
                 role TEXT NOT NULL,
                 content TEXT NOT NULL,
                 model TEXT,
@@ -174,9 +201,84 @@ impl Database {
             );
             ",
         )?;
+        self.migrate_message_tree()?;
         Ok(())
     }
 
+    /// Adds the message-tree columns to databases created before branching
+    /// existed, linking each conversation's existing messages into a single
+    /// chain ending at its newest message.
+    ///
+    /// Runs in one transaction and walks the messages once in
+    /// `(conversation_id, id)` order, so large histories migrate in linear
+    /// time rather than with a correlated subquery per row.
+    fn migrate_message_tree(&self) -> Result<()> {
+        let needs_parent = !self.has_column("messages", "parent_id")?;
+        let needs_leaf = !self.has_column("conversations", "active_leaf_id")?;
+
+        if needs_parent || needs_leaf {
+            let tx = self.conn.unchecked_transaction()?;
+            if needs_parent {
+                tx.execute_batch("ALTER TABLE messages ADD COLUMN parent_id INTEGER;")?;
+            }
+            if needs_leaf {
+                tx.execute_batch("ALTER TABLE conversations ADD COLUMN active_leaf_id INTEGER;")?;
+            }
+            {
+                let mut select = tx.prepare(
+                    "SELECT id, conversation_id FROM messages ORDER BY conversation_id, id",
+                )?;
+                let mut set_parent =
+                    tx.prepare("UPDATE messages SET parent_id = ?2 WHERE id = ?1")?;
+                let mut set_leaf =
+                    tx.prepare("UPDATE conversations SET active_leaf_id = ?2 WHERE id = ?1")?;
+
+                let mut rows = select.query([])?;
+                // (conversation_id, newest message id) of the chain being linked.
+                let mut current: Option<(String, i64)> = None;
+                while let Some(row) = rows.next()? {
+                    let id: i64 = row.get(0)?;
+                    let conversation_id: String = row.get(1)?;
+                    let parent = match &current {
+                        Some((conv, prev)) if *conv == conversation_id => Some(*prev),
+                        Some((conv, prev)) => {
+                            if needs_leaf {
+                                set_leaf.execute(params![conv, prev])?;
+                            }
+                            None
+                        }
+                        None => None,
+                    };
+                    if needs_parent && parent.is_some() {
+                        set_parent.execute(params![id, parent])?;
+                    }
+                    current = Some((conversation_id, id));
+                }
+                if needs_leaf && let Some((conv, prev)) = &current {
+                    set_leaf.execute(params![conv, prev])?;
+                }
+            }
+            tx.commit()?;
+            info!("Migrated messages table to tree layout");
+        }
+        self.conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_messages_parent ON messages(parent_id);",
+        )?;
+        Ok(())
+    }
+
+    /// Returns `true` if `table` has a column named `column`.
+    fn has_column(&self, table: &str, column: &str) -> Result<bool> {
+        let mut stmt = self.conn.prepare(&format!("PRAGMA table_info({table})"))?;
+        let names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+        for name in names {
+            if name? == column {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
     /// Returns a reference to the underlying connection.
     pub fn conn(&self) -> &Connection {
         &self.conn
@@ -323,8 +425,8 @@ impl Database {
     }
 
     /// Rebuild the FTS5 index entry for a single conversation.  Deletes the
-    /// existing entry (if any) and re-inserts with the current title and all
-    /// message content concatenated.
+    /// existing entry (if any) and re-inserts with the current title and the
+    /// content of every message on every branch concatenated.
     fn rebuild_fts_for(&self, conversation_id: &str) -> Result<()> {
         // Remove stale entry.
         self.conn.execute(
@@ -368,7 +470,8 @@ impl Database {
     // Messages
     // -----------------------------------------------------------------------
 
-    /// Saves a message, updates the FTS index, and returns its auto-generated row ID.
+    /// Saves a message under the conversation's active leaf, makes it the new
+    /// leaf, updates the FTS index, and returns its auto-generated row ID.
     pub fn save_message(
         &self,
         conversation_id: &str,
@@ -378,17 +481,14 @@ impl Database {
         cost: Option<f64>,
         tokens: Option<u32>,
     ) -> Result<i64> {
-        // Touch the conversation's updated_at timestamp
-        self.conn.execute(
-            "UPDATE conversations SET updated_at = datetime('now') WHERE id = ?1",
-            params![conversation_id],
-        )?;
+        let parent_id = self.active_leaf(conversation_id)?;
 
         self.conn.execute(
-            "INSERT INTO messages (conversation_id, role, content, model, cost, tokens)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            "INSERT INTO messages (conversation_id, parent_id, role, content, model, cost, tokens)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             params![
                 conversation_id,
+                parent_id,
                 role,
                 content,
                 model,
@@ -396,32 +496,76 @@ impl Database {
                 tokens.map(|t| t as i64)
             ],
         )?;
-
         let row_id = self.conn.last_insert_rowid();
+
+        // Advance the leaf and touch the conversation's updated_at timestamp.
+        self.conn.execute(
+            "UPDATE conversations SET active_leaf_id = ?2, updated_at = datetime('now')
+             WHERE id = ?1",
+            params![conversation_id, row_id],
+        )?;
+
         self.rebuild_fts_for(conversation_id)?;
         Ok(row_id)
     }
 
-    /// Returns all messages for a conversation, ordered chronologically.
+    /// Returns every message for a conversation across all branches, in
+    /// insertion order.
     pub fn get_messages(&self, conversation_id: &str) -> Result<Vec<MessageRow>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, role, content, model, cost, tokens, created_at
+            "SELECT id, parent_id, role, content, model, cost, tokens, created_at
              FROM messages
              WHERE conversation_id = ?1
              ORDER BY id ASC",
         )?;
 
-        let rows = stmt.query_map(params![conversation_id], |row| {
-            Ok(MessageRow {
-                id: row.get(0)?,
-                role: row.get(1)?,
-                content: row.get(2)?,
-                model: row.get(3)?,
-                cost: row.get(4)?,
-                tokens: row.get::<_, Option<i64>>(5)?.map(|v| v as u32),
-                created_at: row.get(6)?,
-            })
-        })?;
+        let rows = stmt.query_map(params![conversation_id], message_from_row)?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row.context("Failed to read message row")?);
+        }
+        Ok(result)
+    }
+
+    /// Returns the active branch of a conversation, from the root message to
+    /// the active leaf.
+    pub fn get_active_path(&self, conversation_id: &str) -> Result<Vec<MessageRow>> {
+        match self.active_leaf(conversation_id)? {
+            Some(leaf) => self.path_ending_at(leaf),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Returns the row ID of the conversation's active leaf message.
+    pub fn active_leaf(&self, conversation_id: &str) -> Result<Option<i64>> {
+        let leaf: Option<Option<i64>> = self
+            .conn
+            .query_row(
+                "SELECT active_leaf_id FROM conversations WHERE id = ?1",
+                params![conversation_id],
+                |row| row.get(0),
+            )
+            .ok();
+        Ok(leaf.flatten())
+    }
+
+    /// Returns the chain of messages from the root down to `message_id`.
+    fn path_ending_at(&self, message_id: i64) -> Result<Vec<MessageRow>> {
+        let mut stmt = self.conn.prepare(
+            "WITH RECURSIVE path(id, depth) AS (
+                 SELECT ?1, 0
+                 UNION ALL
+                 SELECT m.parent_id, path.depth + 1
+                 FROM messages m JOIN path ON m.id = path.id
+                 WHERE m.parent_id IS NOT NULL AND path.depth < 100000
+             )
+             SELECT m.id, m.parent_id, m.role, m.content, m.model, m.cost, m.tokens, m.created_at
+             FROM path JOIN messages m ON m.id = path.id
+             ORDER BY path.depth DESC",
+        )?;
+
+        let rows = stmt.query_map(params![message_id], message_from_row)?;
 
         let mut result = Vec::new();
         for row in rows {
@@ -636,7 +780,7 @@ impl Database {
                 }
             };
 
-            let conv: Conversation = match serde_json::from_str(&content) {
+            let mut conv: Conversation = match serde_json::from_str(&content) {
                 Ok(c) => c,
                 Err(e) => {
                     warn!("Skipping corrupt conversation file {}: {e}", path.display());
@@ -666,24 +810,65 @@ impl Database {
                 params![conv.id, conv.title, conv.model, created, updated],
             )?;
 
-            // Insert all messages preserving their timestamps.
-            for msg in &conv.messages {
-                let msg_created = msg.timestamp.format("%Y-%m-%d %H:%M:%S").to_string();
-                self.conn.execute(
-                    "INSERT INTO messages (conversation_id, role, content, model, cost, tokens, created_at)
-                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-                    params![
-                        conv.id,
-                        msg.role,
-                        msg.content,
-                        msg.model,
-                        msg.cost,
-                        msg.tokens.map(|t| t as i64),
-                        msg_created,
-                    ],
-                )?;
+            // Insert every message on every branch, preserving timestamps and
+            // the tree shape. Parents are inserted before their children.
+            conv.normalize_tree();
+            let mut row_ids: HashMap<&str, i64> = HashMap::new();
+            let known: HashSet<&str> = conv.all_messages().filter_map(|m| m.id.as_deref()).collect();
+            let mut pending: Vec<&StoredMessage> = conv.all_messages().collect();
+            let mut force_roots = false;
+            while !pending.is_empty() {
+                let before = pending.len();
+                let mut deferred = Vec::new();
+                for msg in pending {
+                    let parent = msg.parent_id.as_deref().filter(|p| known.contains(p));
+                    let parent_row = match parent {
+                        None => None,
+                        Some(p) => match row_ids.get(p) {
+                            Some(row) => Some(*row),
+                            None if !force_roots => {
+                                deferred.push(msg);
+                                continue;
+                            }
+                            // The previous pass made no progress (a cycle):
+                            // import the rest as roots.
+                            None => None,
+                        },
+                    };
+                    let msg_created = msg.timestamp.format("%Y-%m-%d %H:%M:%S").to_string();
+                    self.conn.execute(
+                        "INSERT INTO messages
+                            (conversation_id, parent_id, role, content, model, cost, tokens, created_at)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                        params![
+                            conv.id,
+                            parent_row,
+                            msg.role,
+                            msg.content,
+                            msg.model,
+                            msg.cost,
+                            msg.tokens.map(|t| t as i64),
+                            msg_created,
+                        ],
+                    )?;
+                    if let Some(id) = msg.id.as_deref() {
+                        row_ids.insert(id, self.conn.last_insert_rowid());
+                    }
+                }
+                force_roots = deferred.len() == before;
+                pending = deferred;
             }
 
+            let leaf = conv
+                .messages
+                .last()
+                .and_then(|m| m.id.as_deref())
+                .and_then(|id| row_ids.get(id).copied());
+            self.conn.execute(
+                "UPDATE conversations SET active_leaf_id = ?2 WHERE id = ?1",
+                params![conv.id, leaf],
+            )?;
+
             // Build FTS index for the imported conversation.
             self.rebuild_fts_for(&conv.id)?;
             imported += 1;
@@ -1306,6 +1491,8 @@ mod tests {
                     cost: None,
                     tokens: None,
                     thinking: None,
+                    id: None,
+                    parent_id: None,
                 },
                 crate::conversations::StoredMessage {
                     role: "assistant".to_string(),
@@ -1315,6 +1502,8 @@ mod tests {
                     cost: Some(0.002),
                     tokens: Some(42),
                     thinking: None,
+                    id: None,
+                    parent_id: None,
                 },
             ],
             model: "claude-sonnet".to_string(),
@@ -1322,6 +1511,8 @@ mod tests {
             total_tokens: 42,
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            branches: Vec::new(),
+            compactions: Vec::new(),
         }
     }
 
@@ -1396,4 +1587,101 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), 0);
     }
+
+    // -----------------------------------------------------------------------
+    // Message tree
+    // -----------------------------------------------------------------------
+
+    /// Saves q1 -> a1 -> q2 -> a2 and returns their row IDs.
+    fn linear_messages(db: &Database, conv: &str) -> [i64; 4] {
+        db.save_conversation(conv, "Tree", "m").unwrap();
+        [
+            db.save_message(conv, "user", "q1", None, None, None).unwrap(),
+            db.save_message(conv, "assistant", "a1", None, None, None).unwrap(),
+            db.save_message(conv, "user", "q2 original", None, None, None).unwrap(),
+            db.save_message(conv, "assistant", "a2 original", None, None, None).unwrap(),
+        ]
+    }
+
+    fn path_contents(db: &Database, conv: &str) -> Vec<String> {
+        db.get_active_path(conv)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.content)
+            .collect()
+    }
+
+    #[test]
+    fn test_save_message_chains_parents() {
+        let db = test_db();
+        let ids = linear_messages(&db, "t");
+        let msgs = db.get_messages("t").unwrap();
+        assert_eq!(msgs[0].parent_id, None);
+        assert_eq!(msgs[3].parent_id, Some(ids[2]));
+        assert_eq!(db.active_leaf("t").unwrap(), Some(ids[3]));
+    }
+
+    #[test]
+    fn test_migrates_pre_branching_database() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("old.db");
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "
+                CREATE TABLE conversations (
+                    id TEXT PRIMARY KEY,
+                    title TEXT NOT NULL,
+                    model TEXT NOT NULL DEFAULT '',
+                    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+                );
+                CREATE TABLE messages (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+                    role TEXT NOT NULL,
+                    content TEXT NOT NULL,
+                    model TEXT,
+                    cost REAL,
+                    tokens INTEGER,
+                    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+                );
+                INSERT INTO conversations (id, title) VALUES ('a', 'A'), ('b', 'B');
+                INSERT INTO messages (conversation_id, role, content) VALUES
+                    ('a', 'user', 'a1'), ('b', 'user', 'b1'),
+                    ('a', 'assistant', 'a2'), ('b', 'assistant', 'b2');
+                ",
+            )
+            .unwrap();
+        }
+
+        let db = Database::open_at(path).unwrap();
+        assert_eq!(path_contents(&db, "a"), ["a1", "a2"]);
+        assert_eq!(path_contents(&db, "b"), ["b1", "b2"]);
+    }
+
+    #[test]
+    fn test_backfill_imports_branches() {
+        let db = test_db();
+        let tmp = tempfile::tempdir().unwrap();
+
+        let mut conv = sample_conversation("bf-tree", "Branches");
+        conv.normalize_tree();
+        let first = conv.messages[0].id.clone().unwrap();
+        conv.edit_message(&first, "Edited opener").unwrap();
+        write_json_conversation(tmp.path(), &conv);
+
+        db.backfill_from_json(tmp.path()).unwrap();
+
+        assert_eq!(db.get_messages("bf-tree").unwrap().len(), 3);
+        assert_eq!(path_contents(&db, "bf-tree"), ["Edited opener"]);
+        // The original opener and its reply are kept as an inactive branch.
+        let messages = db.get_messages("bf-tree").unwrap();
+        assert_eq!(messages.iter().filter(|m| m.parent_id.is_none()).count(), 2);
+
+        // Inactive branches stay searchable.
+        let results = db.search_conversations("Hello").unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "bf-tree");
+    }
 }
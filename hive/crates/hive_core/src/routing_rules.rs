@@ -0,0 +1,423 @@
+//! User-defined routing rules.
+//!
+//! Rules express hard team policy ("security work goes to model X", "never
+//! send confidential data to cloud providers") that takes precedence over the
+//! heuristic model router. They live in [`HiveConfig`](crate::HiveConfig) as
+//! an ordered list; this module holds the rule data and the predicate
+//! matching. Applying the actions to a routing decision is done by the router
+//! in `hive_ai`, which knows about models and providers.
+
+use chrono::{Datelike, NaiveDateTime, Timelike, Weekday};
+use hive_shield::DataClassification;
+use serde::{Deserialize, Serialize};
+
+// ---------------------------------------------------------------------------
+// Rule data
+// ---------------------------------------------------------------------------
+
+/// One ordered routing rule: when every condition matches, apply the action.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoutingRule {
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub when: RuleConditions,
+    pub action: RuleAction,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Match predicates. Every set predicate must match; list predicates match
+/// when any entry matches. A rule with no predicates always matches.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuleConditions {
+    /// Task types from the complexity classifier, e.g. `"security"`,
+    /// `"debugging"`, `"code_gen"`.
+    pub task_types: Vec<String>,
+    /// Tiers chosen by the heuristic router: `free`, `budget`, `mid`,
+    /// `premium`.
+    pub tiers: Vec<String>,
+    /// Path patterns matched against files in context. A plain pattern such
+    /// as `infra/` matches that directory anywhere in the path; `*` matches
+    /// any run of characters (`*.tf`).
+    pub paths: Vec<String>,
+    /// Match when the shield classified the data at or above this level.
+    pub min_classification: Option<DataClassification>,
+    /// Match when the estimated request cost (USD) is at least this much.
+    pub min_cost_usd: Option<f64>,
+    /// Match when the estimated request cost (USD) is at most this much.
+    pub max_cost_usd: Option<f64>,
+    /// Local weekdays: `mon` … `sun`, `weekdays` or `weekend`.
+    pub days: Vec<String>,
+    /// Local hours of the day.
+    pub hours: Option<HourRange>,
+}
+
+/// Hours of the day, `start` inclusive and `end` exclusive. Wraps past
+/// midnight when `start > end` (e.g. 22–6).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HourRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl HourRange {
+    pub fn contains(&self, hour: u32) -> bool {
+        if self.start <= self.end {
+            hour >= self.start && hour < self.end
+        } else {
+            hour >= self.start || hour < self.end
+        }
+    }
+}
+
+/// What a matching rule does to the routing decision.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Use this model. Applies to automatic routing only; the first matching
+    /// pin wins.
+    PinModel { model: String },
+    /// Never route to these providers. `"cloud"` and `"local"` name the
+    /// provider groups.
+    ForbidProviders { providers: Vec<String> },
+    /// Only local providers (Ollama, LM Studio, generic local) may be used.
+    LocalOnly,
+    /// Never use a model above this tier.
+    CapTier { tier: String },
+}
+
+impl RuleAction {
+    /// Short human-readable form, e.g. `"pin claude-opus-4"`.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::PinModel { model } => format!("pin {model}"),
+            Self::ForbidProviders { providers } => format!("forbid {}", providers.join(", ")),
+            Self::LocalOnly => "local models only".to_string(),
+            Self::CapTier { tier } => format!("cap tier at {tier}"),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Matching
+// ---------------------------------------------------------------------------
+
+/// The facts about a request that rules are matched against.
+#[derive(Debug, Clone)]
+pub struct RuleInput<'a> {
+    pub task_type: &'a str,
+    pub tier: &'a str,
+    pub paths: &'a [String],
+    pub classification: Option<&'a DataClassification>,
+    pub estimated_cost: f64,
+    /// Local wall-clock time.
+    pub now: NaiveDateTime,
+}
+
+impl RoutingRule {
+    /// Whether the rule is enabled and all of its conditions match `input`.
+    pub fn matches(&self, input: &RuleInput<'_>) -> bool {
+        self.enabled && self.when.matches(input)
+    }
+}
+
+impl RuleConditions {
+    pub fn matches(&self, input: &RuleInput<'_>) -> bool {
+        let task_ok = self.task_types.is_empty()
+            || self
+                .task_types
+                .iter()
+                .any(|t| normalize(t) == normalize(input.task_type));
+        let tier_ok = self.tiers.is_empty()
+            || self
+                .tiers
+                .iter()
+                .any(|t| normalize_tier(t) == normalize_tier(input.tier));
+        let path_ok = self.paths.is_empty()
+            || self
+                .paths
+                .iter()
+                .any(|p| input.paths.iter().any(|path| path_matches(p, path)));
+        let class_ok = match &self.min_classification {
+            None => true,
+            Some(min) => input.classification.is_some_and(|c| c >= min),
+        };
+        let cost_ok = self
+            .min_cost_usd
+            .is_none_or(|min| input.estimated_cost >= min)
+            && self
+                .max_cost_usd
+                .is_none_or(|max| input.estimated_cost <= max);
+        let day_ok = self.days.is_empty()
+            || self
+                .days
+                .iter()
+                .any(|d| day_matches(d, input.now.weekday()));
+        let hour_ok = self.hours.is_none_or(|h| h.contains(input.now.hour()));
+
+        task_ok && tier_ok && path_ok && class_ok && cost_ok && day_ok && hour_ok
+    }
+
+    /// Short human-readable form, e.g. `"task: security; paths: infra/"`.
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.task_types.is_empty() {
+            parts.push(format!("task: {}", self.task_types.join(", ")));
+        }
+        if !self.tiers.is_empty() {
+            parts.push(format!("tier: {}", self.tiers.join(", ")));
+        }
+        if !self.paths.is_empty() {
+            parts.push(format!("paths: {}", self.paths.join(", ")));
+        }
+        if let Some(min) = &self.min_classification {
+            parts.push(format!("data >= {min}"));
+        }
+        if let Some(min) = self.min_cost_usd {
+            parts.push(format!("cost >= ${min:.2}"));
+        }
+        if let Some(max) = self.max_cost_usd {
+            parts.push(format!("cost <= ${max:.2}"));
+        }
+        if !self.days.is_empty() {
+            parts.push(format!("days: {}", self.days.join(", ")));
+        }
+        if let Some(h) = self.hours {
+            parts.push(format!("hours: {:02}-{:02}", h.start, h.end));
+        }
+        if parts.is_empty() {
+            "always".to_string()
+        } else {
+            parts.join("; ")
+        }
+    }
+}
+
+/// Lowercase and drop separators so `"Code Gen"`, `"code-gen"` and
+/// `"code_gen"` compare equal.
+fn normalize(s: &str) -> String {
+    s.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn normalize_tier(s: &str) -> String {
+    match normalize(s).as_str() {
+        "standard" => "mid".to_string(),
+        "enterprise" => "premium".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn day_matches(day: &str, weekday: Weekday) -> bool {
+    match normalize(day).as_str() {
+        "weekend" => matches!(weekday, Weekday::Sat | Weekday::Sun),
+        "weekday" | "weekdays" => !matches!(weekday, Weekday::Sat | Weekday::Sun),
+        other => other
+            .get(..3)
+            .is_some_and(|prefix| prefix == weekday.to_string().to_lowercase()),
+    }
+}
+
+/// Match `pattern` against `path` (see [`RuleConditions::paths`]).
+pub fn path_matches(pattern: &str, path: &str) -> bool {
+    let path = path.replace('\\', "/");
+    let pattern = pattern.replace('\\', "/");
+    if pattern.contains('*') {
+        return wildcard_match(&pattern, &path)
+            || path
+                .rsplit('/')
+                .next()
+                .is_some_and(|name| wildcard_match(&pattern, name));
+    }
+    let wanted: Vec<&str> = segments(&pattern).collect();
+    if wanted.is_empty() {
+        return false;
+    }
+    let have: Vec<&str> = segments(&path).collect();
+    have.windows(wanted.len()).any(|w| w == wanted.as_slice())
+}
+
+fn segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|s| !s.is_empty() && *s != ".")
+}
+
+/// `*` matches any run of characters (including `/`).
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let mut rest = text;
+    for (i, part) in parts.iter().enumerate() {
+        if i == 0 {
+            let Some(stripped) = rest.strip_prefix(part) else {
+                return false;
+            };
+            rest = stripped;
+        } else if i == parts.len() - 1 {
+            return rest.ends_with(part);
+        } else if let Some(pos) = rest.find(part) {
+            rest = &rest[pos + part.len()..];
+        } else {
+            return false;
+        }
+    }
+    rest.is_empty()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(y: i32, m: u32, d: u32, h: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, 0, 0)
+            .unwrap()
+    }
+
+    fn input<'a>(paths: &'a [String], now: NaiveDateTime) -> RuleInput<'a> {
+        RuleInput {
+            task_type: "security",
+            tier: "premium",
+            paths,
+            classification: None,
+            estimated_cost: 0.05,
+            now,
+        }
+    }
+
+    #[test]
+    fn empty_conditions_always_match() {
+        let now = at(2026, 3, 4, 12);
+        assert!(RuleConditions::default().matches(&input(&[], now)));
+        assert_eq!(RuleConditions::default().describe(), "always");
+    }
+
+    #[test]
+    fn task_and_tier_names_are_normalized() {
+        let now = at(2026, 3, 4, 12);
+        let conditions = RuleConditions {
+            task_types: vec!["Security".into()],
+            tiers: vec!["enterprise".into()],
+            ..Default::default()
+        };
+        assert!(conditions.matches(&input(&[], now)));
+
+        let other = RuleConditions {
+            task_types: vec!["code_gen".into()],
+            ..Default::default()
+        };
+        assert!(!other.matches(&input(&[], now)));
+    }
+
+    #[test]
+    fn path_patterns() {
+        assert!(path_matches("infra/", "infra/main.tf"));
+        assert!(path_matches(
+            "infra/",
+            "/home/me/repo/infra/k8s/deploy.yaml"
+        ));
+        assert!(path_matches("infra", "repo/infra/main.tf"));
+        assert!(!path_matches("infra/", "repo/infrastructure.md"));
+        assert!(!path_matches("infra", "repo/infrastructure/main.tf"));
+        assert!(path_matches("*.tf", "deploy/main.tf"));
+        assert!(path_matches("src/*/mod.rs", "src/routing/mod.rs"));
+        assert!(!path_matches("*.tf", "main.rs"));
+        assert!(path_matches("infra\\", "C:\\repo\\infra\\main.tf"));
+    }
+
+    #[test]
+    fn classification_must_reach_minimum() {
+        let now = at(2026, 3, 4, 12);
+        let conditions = RuleConditions {
+            min_classification: Some(DataClassification::Confidential),
+            ..Default::default()
+        };
+        let mut req = input(&[], now);
+        assert!(!conditions.matches(&req));
+
+        req.classification = Some(&DataClassification::Internal);
+        assert!(!conditions.matches(&req));
+
+        req.classification = Some(&DataClassification::Restricted);
+        assert!(conditions.matches(&req));
+    }
+
+    #[test]
+    fn cost_bounds() {
+        let now = at(2026, 3, 4, 12);
+        let cheap_only = RuleConditions {
+            max_cost_usd: Some(0.01),
+            ..Default::default()
+        };
+        let expensive = RuleConditions {
+            min_cost_usd: Some(0.01),
+            ..Default::default()
+        };
+        assert!(!cheap_only.matches(&input(&[], now)));
+        assert!(expensive.matches(&input(&[], now)));
+    }
+
+    #[test]
+    fn days_and_hours() {
+        // 2026-03-07 is a Saturday.
+        let saturday = at(2026, 3, 7, 23);
+        let wednesday = at(2026, 3, 4, 10);
+        let weekend = RuleConditions {
+            days: vec!["weekend".into()],
+            ..Default::default()
+        };
+        assert!(weekend.matches(&input(&[], saturday)));
+        assert!(!weekend.matches(&input(&[], wednesday)));
+
+        let named = RuleConditions {
+            days: vec!["Wednesday".into()],
+            ..Default::default()
+        };
+        assert!(named.matches(&input(&[], wednesday)));
+
+        let night = RuleConditions {
+            hours: Some(HourRange { start: 22, end: 6 }),
+            ..Default::default()
+        };
+        assert!(night.matches(&input(&[], saturday)));
+        assert!(!night.matches(&input(&[], wednesday)));
+    }
+
+    #[test]
+    fn disabled_rule_never_matches() {
+        let rule = RoutingRule {
+            name: "off".into(),
+            enabled: false,
+            when: RuleConditions::default(),
+            action: RuleAction::LocalOnly,
+        };
+        assert!(!rule.matches(&input(&[], at(2026, 3, 4, 12))));
+    }
+
+    #[test]
+    fn rule_deserializes_from_config_json() {
+        let json = r#"{
+            "name": "infra to opus",
+            "when": { "paths": ["infra/"], "task_types": ["security"] },
+            "action": { "type": "pin_model", "model": "claude-opus-4-20250514" }
+        }"#;
+        let rule: RoutingRule = serde_json::from_str(json).unwrap();
+        assert!(rule.enabled);
+        assert_eq!(rule.when.describe(), "task: security; paths: infra/");
+        assert_eq!(rule.action.describe(), "pin claude-opus-4-20250514");
+
+        let paths = vec!["infra/main.tf".to_string()];
+        assert!(rule.matches(&input(&paths, at(2026, 3, 4, 12))));
+    }
+}
@@ -0,0 +1,839 @@
+//! Unified local full-text search across Hive knowledge sources.
+//!
+//! Every source (conversations, knowledge-base notes, indexed docs sites,
+//! code chunks, specs, kanban tasks, collective memory) is converted to a
+//! [`SearchDocument`] and written into one SQLite FTS5 index under
+//! `~/.hive/index/`. [`SearchHub::query`] ranks matches with BM25 and
+//! returns highlighted snippets.
+//!
+//! Query syntax:
+//!
+//! - `tokio spawn` — all terms must match (Porter-stemmed)
+//! - `"join handle"` — phrase
+//! - `title:runtime`, `tags:"async io"` — restrict a term to a field
+//!   (`title`, `body`, `tags`)
+//! - `source:docs` — restrict to a source (repeatable)
+//! - `-blocking` — exclude a term
+//! - `spawn*` — prefix match
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use rusqlite::{Connection, OptionalExtension, params, params_from_iter};
+use serde::{Deserialize, Serialize};
+
+use crate::config::HiveConfig;
+use crate::conversations::Conversation;
+use crate::kanban::KanbanTask;
+
+/// Default number of hits returned by a query.
+pub const DEFAULT_LIMIT: usize = 20;
+
+/// Markers placed around matched terms in snippets.
+const HIGHLIGHT: (&str, &str) = ("**", "**");
+
+/// Relative BM25 weights of the `title`, `body`, and `tags` columns.
+const COLUMN_WEIGHTS: (f64, f64, f64) = (4.0, 1.0, 2.0);
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Where an indexed document came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchSource {
+    Conversation,
+    /// Obsidian / Notion knowledge-base pages.
+    Note,
+    /// Pages of indexed documentation sites.
+    Docs,
+    Code,
+    Spec,
+    Task,
+    /// Collective memory entries.
+    Memory,
+}
+
+impl SearchSource {
+    pub const ALL: [SearchSource; 7] = [
+        Self::Conversation,
+        Self::Note,
+        Self::Docs,
+        Self::Code,
+        Self::Spec,
+        Self::Task,
+        Self::Memory,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Conversation => "conversation",
+            Self::Note => "note",
+            Self::Docs => "docs",
+            Self::Code => "code",
+            Self::Spec => "spec",
+            Self::Task => "task",
+            Self::Memory => "memory",
+        }
+    }
+
+    /// Parse a source name, accepting plurals and common aliases.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "conversation" | "conversations" | "chat" | "chats" => Some(Self::Conversation),
+            "note" | "notes" | "knowledge" | "obsidian" | "notion" => Some(Self::Note),
+            "docs" | "doc" | "documentation" => Some(Self::Docs),
+            "code" | "file" | "files" => Some(Self::Code),
+            "spec" | "specs" => Some(Self::Spec),
+            "task" | "tasks" | "kanban" => Some(Self::Task),
+            "memory" | "memories" => Some(Self::Memory),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for SearchSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A unit of searchable content from any source.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchDocument {
+    pub source: SearchSource,
+    /// Identifier, unique within the source.
+    pub id: String,
+    pub title: String,
+    pub body: String,
+    pub tags: Vec<String>,
+    /// Path or URL to open the document, if it has one.
+    pub location: Option<String>,
+    /// Re-indexing a document with the same timestamp is a no-op.
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SearchDocument {
+    pub fn new(
+        source: SearchSource,
+        id: impl Into<String>,
+        title: impl Into<String>,
+        body: impl Into<String>,
+    ) -> Self {
+        Self {
+            source,
+            id: id.into(),
+            title: title.into(),
+            body: body.into(),
+            tags: Vec::new(),
+            location: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    pub fn with_tags(mut self, tags: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.tags = tags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_location(mut self, location: impl Into<String>) -> Self {
+        self.location = Some(location.into());
+        self
+    }
+
+    pub fn updated(mut self, at: DateTime<Utc>) -> Self {
+        self.updated_at = at;
+        self
+    }
+}
+
+impl From<&Conversation> for SearchDocument {
+    fn from(conv: &Conversation) -> Self {
+        let body = conv
+            .messages
+            .iter()
+            .filter(|m| m.role == "user" || m.role == "assistant")
+            .map(|m| m.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        SearchDocument::new(SearchSource::Conversation, &conv.id, &conv.title, body)
+            .with_tags([conv.model.clone()])
+            .updated(conv.updated_at)
+    }
+}
+
+impl From<&KanbanTask> for SearchDocument {
+    fn from(task: &KanbanTask) -> Self {
+        let mut body = task.description.clone().unwrap_or_default();
+        for subtask in &task.subtasks {
+            body.push_str("\n- ");
+            body.push_str(&subtask.title);
+        }
+        for comment in &task.comments {
+            body.push_str(&format!("\n{}: {}", comment.author, comment.content));
+        }
+        SearchDocument::new(SearchSource::Task, &task.id, &task.title, body)
+            .with_tags(task.labels.iter().cloned())
+            .updated(task.updated_at)
+    }
+}
+
+/// A ranked match.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub source: SearchSource,
+    pub id: String,
+    pub title: String,
+    pub location: Option<String>,
+    /// Best-matching excerpt, with matched terms wrapped in `**`.
+    pub snippet: String,
+    /// BM25 relevance; higher is better.
+    pub score: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A search request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchQuery {
+    /// Query text in the syntax described in the module docs.
+    pub text: String,
+    /// Sources to search; empty means all. `source:` terms in `text` add to
+    /// this list.
+    pub sources: Vec<SearchSource>,
+    pub limit: usize,
+}
+
+impl SearchQuery {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            sources: Vec::new(),
+            limit: DEFAULT_LIMIT,
+        }
+    }
+
+    pub fn with_sources(mut self, sources: impl IntoIterator<Item = SearchSource>) -> Self {
+        self.sources.extend(sources);
+        self
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Build a query from a chat message that `@`-mentions sources, e.g.
+    /// `@docs @code how do I spawn a task?`. `@search` (or `@hive`) searches
+    /// everything. Returns `None` when the message mentions no source.
+    pub fn from_mentions(message: &str) -> Option<Self> {
+        let mut sources = Vec::new();
+        let mut mentioned = false;
+        let mut rest = Vec::new();
+        for word in message.split_whitespace() {
+            let name = word
+                .strip_prefix('@')
+                .map(|n| n.trim_end_matches([',', ':', ';', '.']));
+            match name {
+                Some("search" | "hive" | "all") => mentioned = true,
+                Some(name) if SearchSource::parse(name).is_some() => {
+                    mentioned = true;
+                    sources.extend(SearchSource::parse(name));
+                }
+                _ => rest.push(word),
+            }
+        }
+        if !mentioned {
+            return None;
+        }
+        // Chat messages are prose: drop punctuation that FTS5 would reject
+        // and match any of the words instead of requiring all of them.
+        let text = rest
+            .iter()
+            .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()))
+            .filter(|w| !w.is_empty())
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        Some(Self::new(text).with_sources(sources))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Query parsing
+// ---------------------------------------------------------------------------
+
+/// A query compiled to an FTS5 `MATCH` expression plus source filters.
+#[derive(Debug, Default, PartialEq)]
+struct CompiledQuery {
+    expression: Option<String>,
+    sources: Vec<SearchSource>,
+}
+
+/// Split on whitespace, keeping double-quoted phrases together.
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in text.chars() {
+        match c {
+            '"' => {
+                current.push(c);
+                quoted = !quoted;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// Quote a term or phrase as an FTS5 string, keeping a trailing `*` as a
+/// prefix marker.
+fn fts_string(raw: &str) -> Option<String> {
+    let (raw, prefix) = match raw.strip_suffix('*') {
+        Some(stripped) => (stripped, true),
+        None => (raw, false),
+    };
+    let inner = raw.trim_matches('"').replace('"', "");
+    if inner.trim().is_empty() {
+        return None;
+    }
+    let star = if prefix { "*" } else { "" };
+    Some(format!("\"{inner}\"{star}"))
+}
+
+fn compile(text: &str) -> CompiledQuery {
+    let mut compiled = CompiledQuery::default();
+    let mut positive: Vec<String> = Vec::new();
+    let mut negative: Vec<String> = Vec::new();
+    let mut pending_or = false;
+
+    for token in tokenize(text) {
+        if token == "OR" {
+            pending_or = !positive.is_empty();
+            continue;
+        }
+        let (negated, token) = match token.strip_prefix('-') {
+            Some(rest) if !rest.is_empty() => (true, rest.to_string()),
+            _ => (false, token),
+        };
+
+        let term = match token.split_once(':') {
+            Some(("source", name)) => {
+                match SearchSource::parse(name) {
+                    Some(source) => compiled.sources.push(source),
+                    None => tracing::debug!("Ignoring unknown search source '{name}'"),
+                }
+                continue;
+            }
+            Some((field @ ("title" | "body" | "tags"), value)) => {
+                fts_string(value).map(|v| format!("{field}:{v}"))
+            }
+            _ => fts_string(&token),
+        };
+        let Some(term) = term else { continue };
+
+        if negated {
+            negative.push(term);
+        } else if pending_or {
+            let last = positive.pop().unwrap_or_default();
+            positive.push(format!("{last} OR {term}"));
+            pending_or = false;
+        } else {
+            positive.push(term);
+        }
+    }
+
+    if !positive.is_empty() {
+        let mut expression = positive
+            .iter()
+            .map(|t| format!("({t})"))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        for term in negative {
+            expression = format!("({expression}) NOT {term}");
+        }
+        compiled.expression = Some(expression);
+    }
+    compiled
+}
+
+// ---------------------------------------------------------------------------
+// SearchHub
+// ---------------------------------------------------------------------------
+
+/// The embedded full-text index shared by every Hive knowledge source.
+pub struct SearchHub {
+    conn: Mutex<Connection>,
+}
+
+impl SearchHub {
+    /// The default index directory: `~/.hive/index`.
+    pub fn default_dir() -> Result<PathBuf> {
+        Ok(HiveConfig::base_dir()?.join("index"))
+    }
+
+    /// Open (or create) the index in the default directory.
+    pub fn open_default() -> Result<Self> {
+        Self::open(&Self::default_dir()?)
+    }
+
+    /// Open (or create) the index in `dir`.
+    pub fn open(dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join("search.db");
+        let conn = Connection::open(&path)
+            .with_context(|| format!("Failed to open search index {}", path.display()))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        Self::with_connection(conn)
+    }
+
+    /// Index backed by an in-memory database (for tests).
+    pub fn in_memory() -> Result<Self> {
+        let conn = Connection::open_in_memory().context("Failed to open in-memory search index")?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS search_documents (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                source TEXT NOT NULL,
+                doc_id TEXT NOT NULL,
+                title TEXT NOT NULL,
+                location TEXT,
+                updated_at TEXT NOT NULL,
+                UNIQUE (source, doc_id)
+            );
+            CREATE VIRTUAL TABLE IF NOT EXISTS search_fts USING fts5(
+                title, body, tags,
+                tokenize = 'porter unicode61'
+            );
+            ",
+        )
+        .context("Failed to create search index schema")?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Add or update a document. Returns `false` if it was already indexed
+    /// with the same `updated_at`.
+    pub fn index(&self, doc: &SearchDocument) -> Result<bool> {
+        let conn = self.conn.lock();
+        upsert(&conn, doc)
+    }
+
+    /// Add or update many documents in one transaction. Returns how many
+    /// changed.
+    pub fn index_all<'a>(
+        &self,
+        docs: impl IntoIterator<Item = &'a SearchDocument>,
+    ) -> Result<usize> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let mut changed = 0;
+        for doc in docs {
+            changed += upsert(&tx, doc)? as usize;
+        }
+        tx.commit()?;
+        Ok(changed)
+    }
+
+    /// Make `docs` the complete contents of `source`: index them and drop
+    /// anything else previously indexed for it. Returns how many changed.
+    pub fn replace_source(&self, source: SearchSource, docs: &[SearchDocument]) -> Result<usize> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let mut changed = 0;
+        for doc in docs.iter().filter(|d| d.source == source) {
+            changed += upsert(&tx, doc)? as usize;
+        }
+
+        let stale: Vec<(i64, String)> = {
+            let mut stmt =
+                tx.prepare("SELECT id, doc_id FROM search_documents WHERE source = ?1")?;
+            stmt.query_map(params![source.as_str()], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+            .into_iter()
+            .filter(|(_, id)| !docs.iter().any(|d| &d.id == id))
+            .collect()
+        };
+        for (rowid, _) in &stale {
+            delete_row(&tx, *rowid)?;
+        }
+        tx.commit()?;
+        Ok(changed + stale.len())
+    }
+
+    /// Remove one document. Returns whether it was indexed.
+    pub fn remove(&self, source: SearchSource, id: &str) -> Result<bool> {
+        let conn = self.conn.lock();
+        match find_row(&conn, source, id)? {
+            Some((rowid, _)) => {
+                delete_row(&conn, rowid)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Number of indexed documents, optionally for one source.
+    pub fn count(&self, source: Option<SearchSource>) -> Result<usize> {
+        let conn = self.conn.lock();
+        let count: i64 = match source {
+            Some(source) => conn.query_row(
+                "SELECT COUNT(*) FROM search_documents WHERE source = ?1",
+                params![source.as_str()],
+                |row| row.get(0),
+            )?,
+            None => conn.query_row("SELECT COUNT(*) FROM search_documents", [], |row| {
+                row.get(0)
+            })?,
+        };
+        Ok(count as usize)
+    }
+
+    /// Run a query, best matches first.
+    pub fn query(&self, query: &SearchQuery) -> Result<Vec<SearchHit>> {
+        let mut compiled = compile(&query.text);
+        let Some(expression) = compiled.expression.take() else {
+            return Ok(Vec::new());
+        };
+        let mut sources = query.sources.clone();
+        sources.append(&mut compiled.sources);
+        sources.sort_by_key(|s| s.as_str());
+        sources.dedup();
+
+        let (title_w, body_w, tags_w) = COLUMN_WEIGHTS;
+        let rank = format!("bm25(search_fts, {title_w}, {body_w}, {tags_w})");
+        let source_filter = if sources.is_empty() {
+            String::new()
+        } else {
+            let placeholders = (0..sources.len())
+                .map(|i| format!("?{}", i + 3))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("AND d.source IN ({placeholders})")
+        };
+        let sql = format!(
+            "SELECT d.source, d.doc_id, d.title, d.location, d.updated_at,
+                    snippet(search_fts, -1, '{open}', '{close}', '…', 16), {rank}
+             FROM search_fts JOIN search_documents d ON d.id = search_fts.rowid
+             WHERE search_fts MATCH ?1 {source_filter}
+             ORDER BY {rank}
+             LIMIT ?2",
+            open = HIGHLIGHT.0,
+            close = HIGHLIGHT.1,
+        );
+
+        let mut args: Vec<rusqlite::types::Value> =
+            vec![expression.clone().into(), (query.limit as i64).into()];
+        args.extend(sources.iter().map(|s| s.as_str().to_string().into()));
+
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&sql)?;
+        let hits = stmt
+            .query_map(params_from_iter(args), |row| {
+                let source: String = row.get(0)?;
+                let updated_at: String = row.get(4)?;
+                Ok(SearchHit {
+                    source: SearchSource::parse(&source).unwrap_or(SearchSource::Note),
+                    id: row.get(1)?,
+                    title: row.get(2)?,
+                    location: row.get(3)?,
+                    snippet: row.get(5)?,
+                    score: -row.get::<_, f64>(6)?,
+                    updated_at: DateTime::parse_from_rfc3339(&updated_at)
+                        .map(|t| t.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                })
+            })
+            .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+            .with_context(|| format!("Search query failed: {expression}"))?;
+        Ok(hits)
+    }
+}
+
+fn find_row(conn: &Connection, source: SearchSource, id: &str) -> Result<Option<(i64, String)>> {
+    Ok(conn
+        .query_row(
+            "SELECT id, updated_at FROM search_documents WHERE source = ?1 AND doc_id = ?2",
+            params![source.as_str(), id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?)
+}
+
+fn delete_row(conn: &Connection, rowid: i64) -> Result<()> {
+    conn.execute("DELETE FROM search_fts WHERE rowid = ?1", params![rowid])?;
+    conn.execute("DELETE FROM search_documents WHERE id = ?1", params![rowid])?;
+    Ok(())
+}
+
+fn upsert(conn: &Connection, doc: &SearchDocument) -> Result<bool> {
+    let updated_at = doc.updated_at.to_rfc3339();
+    let rowid = match find_row(conn, doc.source, &doc.id)? {
+        Some((_, indexed_at)) if indexed_at == updated_at => return Ok(false),
+        Some((rowid, _)) => {
+            conn.execute("DELETE FROM search_fts WHERE rowid = ?1", params![rowid])?;
+            conn.execute(
+                "UPDATE search_documents SET title = ?1, location = ?2, updated_at = ?3
+                 WHERE id = ?4",
+                params![doc.title, doc.location, updated_at, rowid],
+            )?;
+            rowid
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO search_documents (source, doc_id, title, location, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    doc.source.as_str(),
+                    doc.id,
+                    doc.title,
+                    doc.location,
+                    updated_at
+                ],
+            )?;
+            conn.last_insert_rowid()
+        }
+    };
+    conn.execute(
+        "INSERT INTO search_fts (rowid, title, body, tags) VALUES (?1, ?2, ?3, ?4)",
+        params![rowid, doc.title, doc.body, doc.tags.join(" ")],
+    )
+    .context("Failed to index document")?;
+    Ok(true)
+}
+
+/// Format hits as a context block for a model prompt.
+pub fn render_context(hits: &[SearchHit]) -> String {
+    let mut out = String::new();
+    for hit in hits {
+        out.push_str(&format!("## [{}] {}", hit.source, hit.title));
+        if let Some(location) = &hit.location {
+            out.push_str(&format!(" ({location})"));
+        }
+        out.push('\n');
+        out.push_str(&hit.snippet);
+        out.push_str("\n\n");
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hub() -> SearchHub {
+        let hub = SearchHub::in_memory().unwrap();
+        let docs = [
+            SearchDocument::new(
+                SearchSource::Docs,
+                "tokio/spawn",
+                "tokio::spawn",
+                "Spawns a new asynchronous task, returning a JoinHandle for it.",
+            )
+            .with_location("https://docs.rs/tokio/latest/tokio/fn.spawn.html"),
+            SearchDocument::new(
+                SearchSource::Docs,
+                "tokio/block_on",
+                "Runtime::block_on",
+                "Runs a future to completion on the runtime. This is blocking.",
+            ),
+            SearchDocument::new(
+                SearchSource::Conversation,
+                "c1",
+                "Async debugging",
+                "We spawned tasks and the join handle never resolved.",
+            )
+            .with_tags(["rust"]),
+            SearchDocument::new(
+                SearchSource::Task,
+                "t1",
+                "Write release notes",
+                "Summarise the spawning changes.",
+            )
+            .with_tags(["docs", "release"]),
+        ];
+        hub.index_all(&docs).unwrap();
+        hub
+    }
+
+    fn ids(hits: &[SearchHit]) -> Vec<&str> {
+        hits.iter().map(|h| h.id.as_str()).collect()
+    }
+
+    #[test]
+    fn ranks_with_bm25_and_stems_terms() {
+        let hub = hub();
+        let hits = hub.query(&SearchQuery::new("spawn")).unwrap();
+        // "spawn", "spawned", and "spawning" all stem to the same term; the
+        // title match ranks first.
+        assert_eq!(hits.len(), 3);
+        assert_eq!(hits[0].id, "tokio/spawn");
+        assert!(hits[0].score > hits[1].score);
+        assert!(hits[0].snippet.contains("**"));
+        assert_eq!(
+            hits[0].location.as_deref(),
+            Some("https://docs.rs/tokio/latest/tokio/fn.spawn.html")
+        );
+    }
+
+    #[test]
+    fn supports_phrases_fields_sources_and_exclusions() {
+        let hub = hub();
+        let q = |text: &str| ids(&hub.query(&SearchQuery::new(text)).unwrap()).join(",");
+
+        assert_eq!(q("\"join handle\""), "c1");
+        assert_eq!(q("title:runtime"), "tokio/block_on");
+        assert_eq!(q("tags:release"), "t1");
+        assert_eq!(q("spawn source:conversation"), "c1");
+        let mut either: Vec<String> = q("runtime OR release")
+            .split(',')
+            .map(String::from)
+            .collect();
+        either.sort();
+        assert_eq!(either, ["t1", "tokio/block_on"]);
+        assert!(!q("spawn -join").contains("c1"));
+        assert_eq!(q("run*"), "tokio/block_on");
+        assert_eq!(q(""), "");
+        assert_eq!(q("source:docs"), "");
+    }
+
+    #[test]
+    fn filters_by_requested_sources() {
+        let hub = hub();
+        let hits = hub
+            .query(&SearchQuery::new("spawn").with_sources([SearchSource::Task]))
+            .unwrap();
+        assert_eq!(ids(&hits), ["t1"]);
+    }
+
+    #[test]
+    fn reindexing_updates_and_skips_unchanged() {
+        let hub = hub();
+        let doc = SearchDocument::new(SearchSource::Note, "n1", "Ideas", "pineapple");
+        assert!(hub.index(&doc).unwrap());
+        assert!(!hub.index(&doc).unwrap());
+
+        let edited = SearchDocument {
+            body: "mango".into(),
+            updated_at: doc.updated_at + chrono::Duration::seconds(1),
+            ..doc
+        };
+        assert!(hub.index(&edited).unwrap());
+        assert!(
+            hub.query(&SearchQuery::new("pineapple"))
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(ids(&hub.query(&SearchQuery::new("mango")).unwrap()), ["n1"]);
+
+        assert!(hub.remove(SearchSource::Note, "n1").unwrap());
+        assert!(!hub.remove(SearchSource::Note, "n1").unwrap());
+        assert!(hub.query(&SearchQuery::new("mango")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn replace_source_drops_stale_documents() {
+        let hub = hub();
+        let keep = SearchDocument::new(
+            SearchSource::Docs,
+            "tokio/select",
+            "tokio::select!",
+            "Waits on multiple branches.",
+        );
+        hub.replace_source(SearchSource::Docs, std::slice::from_ref(&keep))
+            .unwrap();
+        assert_eq!(hub.count(Some(SearchSource::Docs)).unwrap(), 1);
+        assert_eq!(hub.count(None).unwrap(), 3);
+        assert!(hub.query(&SearchQuery::new("runtime")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn persists_under_index_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let hub = SearchHub::open(dir.path()).unwrap();
+            hub.index(&SearchDocument::new(
+                SearchSource::Code,
+                "a.rs",
+                "a.rs",
+                "fn main() {}",
+            ))
+            .unwrap();
+        }
+        let hub = SearchHub::open(dir.path()).unwrap();
+        assert!(dir.path().join("search.db").exists());
+        assert_eq!(
+            ids(&hub.query(&SearchQuery::new("main")).unwrap()),
+            ["a.rs"]
+        );
+    }
+
+    #[test]
+    fn parses_mentions() {
+        assert_eq!(SearchQuery::from_mentions("no mentions here"), None);
+        let q = SearchQuery::from_mentions("@docs @tasks, how do I spawn a task?").unwrap();
+        assert_eq!(q.sources, [SearchSource::Docs, SearchSource::Task]);
+        assert_eq!(q.text, "how OR do OR I OR spawn OR a OR task");
+        // Short terms such as language names are kept.
+        let q = SearchQuery::from_mentions("@code go vs C").unwrap();
+        assert_eq!(q.text, "go OR vs OR C");
+        let q = SearchQuery::from_mentions("@search tokio").unwrap();
+        assert!(q.sources.is_empty());
+        // Unknown handles are left in the text.
+        assert_eq!(SearchQuery::from_mentions("ping @alice"), None);
+    }
+
+    #[test]
+    fn mention_queries_run_against_the_index() {
+        let hub = hub();
+        let q = SearchQuery::from_mentions("@docs what does spawn return?").unwrap();
+        let hits = hub.query(&q).unwrap();
+        assert_eq!(hits[0].id, "tokio/spawn");
+        let context = render_context(&hits);
+        assert!(context.starts_with("## [docs] tokio::spawn (https://docs.rs/"));
+    }
+
+    #[test]
+    fn converts_conversations_and_tasks() {
+        let mut conv = Conversation::new("gpt-4o");
+        conv.title = "Borrow checker".into();
+        conv.messages.push(crate::conversations::StoredMessage {
+            role: "user".into(),
+            content: "Why does this move?".into(),
+            timestamp: Utc::now(),
+            model: None,
+            cost: None,
+            tokens: None,
+            thinking: None,
+            id: None,
+            parent_id: None,
+        });
+        let doc = SearchDocument::from(&conv);
+        assert_eq!(doc.source, SearchSource::Conversation);
+        assert_eq!(doc.body, "Why does this move?");
+        assert_eq!(doc.updated_at, conv.updated_at);
+    }
+}
@@ -4,73 +4,380 @@ use aes_gcm::{
 };
 use anyhow::{Context, Result};
 use argon2::{Algorithm, Argon2, Params, Version};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+use crate::keyring::{self, KeyringBackend};
 
 const AES_NONCE_LEN: usize = 12;
 const SALT_LEN: usize = 16;
 const SALT_FILENAME: &str = "storage.salt";
+const SETTINGS_FILENAME: &str = "master_key.json";
+/// Keyring account under which the random master key is stored.
+const KEYRING_ACCOUNT: &str = "master-key";
+/// Known plaintext encrypted with the master key to verify a passphrase.
+const VERIFIER_PLAINTEXT: &str = "hive-master-key-check";
+
+/// Where the SecureStorage master key comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MasterKeySource {
+    /// Derived from username + home directory. Anyone who copies `~/.hive`
+    /// together with the account name can reproduce it.
+    Machine,
+    /// Derived from a user passphrase. Storage starts locked until
+    /// [`SecureStorage::unlock`] is called.
+    Passphrase,
+    /// A random key held in an OS keyring backend (see [`crate::keyring`]).
+    Keyring { backend: String },
+}
+
+/// Master key settings persisted next to the salt as `master_key.json`.
+///
+/// Contains no secret material, only enough to know how to obtain the key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MasterKeySettings {
+    pub source: MasterKeySource,
+    /// Encrypted [`VERIFIER_PLAINTEXT`], used to reject a wrong passphrase.
+    #[serde(default)]
+    pub verifier: Option<String>,
+    /// Lock automatically after this many idle minutes (passphrase only).
+    /// `0` disables auto-lock.
+    #[serde(default)]
+    pub idle_lock_minutes: u32,
+}
+
+impl Default for MasterKeySettings {
+    fn default() -> Self {
+        Self {
+            source: MasterKeySource::Machine,
+            verifier: None,
+            idle_lock_minutes: 0,
+        }
+    }
+}
+
+/// Mutable state shared between duplicated handles.
+struct StorageState {
+    cipher: Option<Aes256Gcm>,
+    settings: MasterKeySettings,
+    last_used: Instant,
+    /// Keyring-held key not yet written to its backend.
+    unsaved_key: Option<[u8; 32]>,
+}
 
 /// Secure storage for API keys and sensitive data.
-/// Uses AES-256-GCM encryption with a key derived via Argon2id from
-/// machine-specific context and a persisted random salt.
+/// Uses AES-256-GCM encryption with a master key that is either derived via
+/// Argon2id (from machine context or a user passphrase, plus a persisted
+/// random salt) or held in an OS keyring.
+///
+/// Passphrase-protected storage can be locked and unlocked at runtime;
+/// while locked, [`encrypt`](Self::encrypt) and [`decrypt`](Self::decrypt)
+/// fail. Handles created with [`duplicate`](Self::duplicate) share the lock
+/// state, so unlocking one unlocks all of them.
 pub struct SecureStorage {
-    cipher: Aes256Gcm,
-    key_material: [u8; 32],
+    state: Arc<RwLock<StorageState>>,
+    salt_path: PathBuf,
 }
 
 impl SecureStorage {
-    /// Create a new SecureStorage with a key derived via Argon2id.
+    /// Open the default storage in `~/.hive/`, honouring `master_key.json`.
     ///
     /// The salt is loaded from (or generated and saved to) `~/.hive/storage.salt`.
+    /// In passphrase mode the returned storage is locked.
     pub fn new() -> Result<Self> {
         let salt_path = Self::default_salt_path()?;
-        let key_material = Self::derive_key(&salt_path)?;
-        Ok(Self::from_key_material(key_material))
+        Self::open(&salt_path)
     }
 
-    /// Create a SecureStorage with a salt file at a custom path.
+    /// Open storage whose salt lives at `salt_path`, reading the master key
+    /// settings from the same directory. Keyring backends are resolved via
+    /// [`keyring::backend_by_name`].
+    pub fn open(salt_path: &Path) -> Result<Self> {
+        let settings = Self::load_settings(salt_path)?;
+        match &settings.source {
+            MasterKeySource::Keyring { backend } => {
+                let backend = keyring::backend_by_name(backend)
+                    .with_context(|| format!("Unknown keyring backend: {backend}"))?;
+                Self::open_with_backend(salt_path, backend.as_ref())
+            }
+            _ => Self::open_with_settings(salt_path, settings, None),
+        }
+    }
+
+    /// Like [`open`](Self::open), but uses `backend` when the settings name a
+    /// keyring. Useful for tests and for backends not known by name.
+    pub fn open_with_backend(salt_path: &Path, backend: &dyn KeyringBackend) -> Result<Self> {
+        let settings = Self::load_settings(salt_path)?;
+        Self::open_with_settings(salt_path, settings, Some(backend))
+    }
+
+    fn open_with_settings(
+        salt_path: &Path,
+        settings: MasterKeySettings,
+        backend: Option<&dyn KeyringBackend>,
+    ) -> Result<Self> {
+        let key_material = match &settings.source {
+            MasterKeySource::Machine => Some(Self::derive_key(salt_path)?),
+            MasterKeySource::Passphrase => None,
+            MasterKeySource::Keyring { backend: name } => {
+                let backend = backend.context("No keyring backend supplied")?;
+                let hex_key = backend
+                    .get_secret(KEYRING_ACCOUNT)?
+                    .with_context(|| format!("Master key missing from keyring '{name}'"))?;
+                Some(Self::parse_key_hex(&hex_key)?)
+            }
+        };
+        Ok(Self::from_parts(salt_path, key_material, settings))
+    }
+
+    /// Create a machine-keyed SecureStorage with a salt file at a custom path.
     /// Useful for testing without touching `~/.hive/`.
     pub fn with_salt_path(salt_path: &Path) -> Result<Self> {
         let key_material = Self::derive_key(salt_path)?;
-        Ok(Self::from_key_material(key_material))
+        Ok(Self::from_parts(
+            salt_path,
+            Some(key_material),
+            MasterKeySettings::default(),
+        ))
+    }
+
+    /// Create an unlocked storage whose key is derived from `passphrase`.
+    ///
+    /// Nothing is written except the salt; call
+    /// [`save_settings`](Self::save_settings) once existing secrets have been
+    /// re-encrypted (see [`reencrypt`](Self::reencrypt)).
+    pub fn with_passphrase(salt_path: &Path, passphrase: &str) -> Result<Self> {
+        if passphrase.is_empty() {
+            anyhow::bail!("Passphrase must not be empty");
+        }
+        let salt = Self::load_or_create_salt(salt_path)?;
+        let key_material = Self::derive_key_raw(passphrase.as_bytes(), &salt)?;
+        let storage = Self::from_parts(
+            salt_path,
+            Some(key_material),
+            MasterKeySettings {
+                source: MasterKeySource::Passphrase,
+                ..MasterKeySettings::default()
+            },
+        );
+        let verifier = storage.encrypt(VERIFIER_PLAINTEXT)?;
+        storage.state.write().settings.verifier = Some(verifier);
+        Ok(storage)
+    }
+
+    /// Create an unlocked storage with a fresh random master key to be held
+    /// in `backend`. The key stays in memory until [`store_key`](Self::store_key)
+    /// writes it, so preparing a migration never overwrites the keyring entry
+    /// that existing secrets depend on.
+    pub fn with_keyring(salt_path: &Path, backend: &dyn KeyringBackend) -> Result<Self> {
+        if !backend.is_available() {
+            anyhow::bail!("Keyring backend '{}' is not available", backend.name());
+        }
+        let key_material: [u8; 32] = rand::random();
+        let storage = Self::from_parts(
+            salt_path,
+            Some(key_material),
+            MasterKeySettings {
+                source: MasterKeySource::Keyring {
+                    backend: backend.name().to_string(),
+                },
+                ..MasterKeySettings::default()
+            },
+        );
+        storage.state.write().unsaved_key = Some(key_material);
+        Ok(storage)
+    }
+
+    /// Whether a key created by [`with_keyring`](Self::with_keyring) still
+    /// has to be written to its backend.
+    pub fn has_unsaved_key(&self) -> bool {
+        self.state.read().unsaved_key.is_some()
+    }
+
+    /// Write a key created by [`with_keyring`](Self::with_keyring) to
+    /// `backend`. No-op if there is nothing to store.
+    pub fn store_key(&self, backend: &dyn KeyringBackend) -> Result<()> {
+        let mut state = self.state.write();
+        if let Some(key_material) = state.unsaved_key {
+            backend.set_secret(KEYRING_ACCOUNT, &hex::encode(key_material))?;
+            state.unsaved_key = None;
+        }
+        Ok(())
     }
 
-    /// Create a duplicate instance reusing the same derived key material.
+    /// Create a duplicate handle sharing the same key material and lock state.
     /// Avoids re-running Argon2 key derivation.
     pub fn duplicate(&self) -> Self {
-        Self::from_key_material(self.key_material)
+        Self {
+            state: Arc::clone(&self.state),
+            salt_path: self.salt_path.clone(),
+        }
     }
 
-    fn from_key_material(key_material: [u8; 32]) -> Self {
-        let key = Key::<Aes256Gcm>::from_slice(&key_material);
-        let cipher = Aes256Gcm::new(key);
+    fn from_parts(
+        salt_path: &Path,
+        key_material: Option<[u8; 32]>,
+        settings: MasterKeySettings,
+    ) -> Self {
         Self {
-            cipher,
-            key_material,
+            state: Arc::new(RwLock::new(StorageState {
+                cipher: key_material.as_ref().map(Self::cipher_for),
+                settings,
+                last_used: Instant::now(),
+                unsaved_key: None,
+            })),
+            salt_path: salt_path.to_path_buf(),
+        }
+    }
+
+    fn cipher_for(key_material: &[u8; 32]) -> Aes256Gcm {
+        let key = Key::<Aes256Gcm>::from_slice(key_material);
+        Aes256Gcm::new(key)
+    }
+
+    fn parse_key_hex(hex_key: &str) -> Result<[u8; 32]> {
+        let bytes = hex::decode(hex_key.trim()).context("Keyring master key is not hex")?;
+        bytes
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Keyring master key has wrong length"))
+    }
+
+    // -- lock state ----------------------------------------------------------
+
+    /// The master key source this storage was opened with.
+    pub fn source(&self) -> MasterKeySource {
+        self.state.read().settings.source.clone()
+    }
+
+    /// Whether the storage is currently locked (no key in memory).
+    pub fn is_locked(&self) -> bool {
+        self.state.read().cipher.is_none()
+    }
+
+    /// Unlock passphrase-protected storage. Fails on a wrong passphrase.
+    /// Unlocking an already unlocked or non-passphrase storage is a no-op.
+    pub fn unlock(&self, passphrase: &str) -> Result<()> {
+        if !self.is_locked() {
+            return Ok(());
         }
+        let salt = Self::load_or_create_salt(&self.salt_path)?;
+        let key_material = Self::derive_key_raw(passphrase.as_bytes(), &salt)?;
+        let cipher = Self::cipher_for(&key_material);
+
+        let mut state = self.state.write();
+        if let Some(verifier) = &state.settings.verifier {
+            let ok = Self::decrypt_with(&cipher, verifier)
+                .map(|v| v == VERIFIER_PLAINTEXT)
+                .unwrap_or(false);
+            if !ok {
+                anyhow::bail!("Incorrect passphrase");
+            }
+        }
+        state.cipher = Some(cipher);
+        state.last_used = Instant::now();
+        info!("Secure storage unlocked");
+        Ok(())
+    }
+
+    /// Drop the key from memory. Only passphrase storage can be locked, since
+    /// the other sources could simply re-derive or re-fetch their key.
+    pub fn lock(&self) {
+        let mut state = self.state.write();
+        if state.settings.source == MasterKeySource::Passphrase && state.cipher.is_some() {
+            state.cipher = None;
+            info!("Secure storage locked");
+        }
+    }
+
+    /// Idle auto-lock timeout in minutes (`0` means disabled).
+    pub fn idle_lock_minutes(&self) -> u32 {
+        self.state.read().settings.idle_lock_minutes
+    }
+
+    /// Configure idle auto-lock in minutes (`0` disables). Takes effect
+    /// immediately and is persisted by the next [`save_settings`](Self::save_settings).
+    pub fn set_idle_lock_minutes(&self, minutes: u32) {
+        self.state.write().settings.idle_lock_minutes = minutes;
     }
 
+    /// Lock the storage if it has been idle for longer than the configured
+    /// timeout. Returns `true` if this call locked it.
+    pub fn lock_if_idle(&self) -> bool {
+        let idle_for = {
+            let state = self.state.read();
+            if state.cipher.is_none() || state.settings.idle_lock_minutes == 0 {
+                return false;
+            }
+            let timeout = Duration::from_secs(u64::from(state.settings.idle_lock_minutes) * 60);
+            if state.last_used.elapsed() < timeout {
+                return false;
+            }
+            state.last_used.elapsed()
+        };
+        info!("Secure storage idle for {}s; locking", idle_for.as_secs());
+        self.lock();
+        self.is_locked()
+    }
+
+    /// Returns the active cipher, refreshing the idle timer.
+    fn active_cipher(&self) -> Result<Aes256Gcm> {
+        self.lock_if_idle();
+        let mut state = self.state.write();
+        let Some(cipher) = state.cipher.clone() else {
+            anyhow::bail!("Secure storage is locked");
+        };
+        state.last_used = Instant::now();
+        Ok(cipher)
+    }
+
+    // -- encryption ----------------------------------------------------------
+
     /// Encrypt a plaintext string, returning hex-encoded ciphertext.
     pub fn encrypt(&self, plaintext: &str) -> Result<String> {
+        Ok(hex::encode(self.encrypt_bytes(plaintext.as_bytes())?))
+    }
+
+    /// Decrypt a hex-encoded ciphertext string.
+    pub fn decrypt(&self, hex_ciphertext: &str) -> Result<String> {
+        let cipher = self.active_cipher()?;
+        Self::decrypt_with(&cipher, hex_ciphertext)
+    }
+
+    /// Encrypt raw bytes, returning `nonce || ciphertext`.
+    pub fn encrypt_bytes(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let cipher = self.active_cipher()?;
         let nonce_bytes: [u8; AES_NONCE_LEN] = rand::random();
         let nonce = Nonce::from_slice(&nonce_bytes);
 
-        let ciphertext = self
-            .cipher
-            .encrypt(nonce, plaintext.as_bytes())
+        let ciphertext = cipher
+            .encrypt(nonce, plaintext)
             .map_err(|e| anyhow::anyhow!("Encryption failed: {e}"))?;
 
         // Prepend nonce to ciphertext
         let mut result = nonce_bytes.to_vec();
         result.extend_from_slice(&ciphertext);
+        Ok(result)
+    }
 
-        Ok(hex::encode(result))
+    /// Decrypt bytes produced by [`encrypt_bytes`](Self::encrypt_bytes).
+    pub fn decrypt_bytes(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let cipher = self.active_cipher()?;
+        Self::decrypt_raw(&cipher, data)
     }
 
-    /// Decrypt a hex-encoded ciphertext string.
-    pub fn decrypt(&self, hex_ciphertext: &str) -> Result<String> {
+    fn decrypt_with(cipher: &Aes256Gcm, hex_ciphertext: &str) -> Result<String> {
         let data = hex::decode(hex_ciphertext).context("Invalid hex")?;
+        let plaintext = Self::decrypt_raw(cipher, &data)?;
+        String::from_utf8(plaintext).context("Decrypted data is not valid UTF-8")
+    }
+
+    fn decrypt_raw(cipher: &Aes256Gcm, data: &[u8]) -> Result<Vec<u8>> {
         if data.len() < AES_NONCE_LEN {
             anyhow::bail!("Ciphertext too short");
         }
@@ -78,14 +385,95 @@ impl SecureStorage {
         let (nonce_bytes, ciphertext) = data.split_at(AES_NONCE_LEN);
         let nonce = Nonce::from_slice(nonce_bytes);
 
-        let plaintext = self
-            .cipher
+        cipher
             .decrypt(nonce, ciphertext)
-            .map_err(|e| anyhow::anyhow!("Decryption failed: {e}"))?;
+            .map_err(|e| anyhow::anyhow!("Decryption failed: {e}"))
+    }
 
-        String::from_utf8(plaintext).context("Decrypted data is not valid UTF-8")
+    // -- migration -----------------------------------------------------------
+
+    /// Re-encrypt hex ciphertexts from this storage's key to `target`'s key.
+    ///
+    /// All values are decrypted before any is replaced, so on error `map` is
+    /// left untouched. Returns the number of re-encrypted entries.
+    pub fn reencrypt(
+        &self,
+        target: &SecureStorage,
+        map: &mut std::collections::HashMap<String, String>,
+    ) -> Result<usize> {
+        let mut plaintexts = Vec::with_capacity(map.len());
+        for (name, ciphertext) in map.iter() {
+            let plaintext = self
+                .decrypt(ciphertext)
+                .with_context(|| format!("Failed to decrypt '{name}' during re-encryption"))?;
+            plaintexts.push((name.clone(), plaintext));
+        }
+        let mut reencrypted = Vec::with_capacity(plaintexts.len());
+        for (name, plaintext) in plaintexts {
+            reencrypted.push((name, target.encrypt(&plaintext)?));
+        }
+        let count = reencrypted.len();
+        map.extend(reencrypted);
+        Ok(count)
     }
 
+    /// Re-encrypt a byte blob from this storage's key to `target`'s key.
+    pub fn reencrypt_bytes(&self, target: &SecureStorage, data: &[u8]) -> Result<Vec<u8>> {
+        target.encrypt_bytes(&self.decrypt_bytes(data)?)
+    }
+
+    /// Switch this handle (and every duplicate of it) to `other`'s key and
+    /// settings. Used after [`reencrypt`](Self::reencrypt) to complete a
+    /// master key migration without rebuilding shared handles.
+    pub fn adopt(&self, other: SecureStorage) {
+        let other_state = other.state.read();
+        let mut state = self.state.write();
+        state.cipher = other_state.cipher.clone();
+        state.settings = other_state.settings.clone();
+        state.last_used = Instant::now();
+    }
+
+    // -- settings ------------------------------------------------------------
+
+    /// Path of `master_key.json` for a given salt path.
+    fn settings_path(salt_path: &Path) -> PathBuf {
+        salt_path.with_file_name(SETTINGS_FILENAME)
+    }
+
+    /// Read `master_key.json`; a missing file means legacy machine mode.
+    /// Any other read error is returned rather than guessed at, so an
+    /// unreadable passphrase or keyring setting never falls back to the
+    /// machine key.
+    fn load_settings(salt_path: &Path) -> Result<MasterKeySettings> {
+        let path = Self::settings_path(salt_path);
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(MasterKeySettings::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    /// Path and contents of `master_key.json` for the current settings.
+    pub fn settings_file(&self) -> Result<(PathBuf, String)> {
+        let content = serde_json::to_string_pretty(&self.state.read().settings)?;
+        Ok((Self::settings_path(&self.salt_path), content))
+    }
+
+    /// Persist the current master key settings to `master_key.json`.
+    pub fn save_settings(&self) -> Result<()> {
+        let (path, content) = self.settings_file()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+        }
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(())
+    }
+
+    // -- key derivation ------------------------------------------------------
+
     /// Returns the default salt file path: `~/.hive/storage.salt`.
     fn default_salt_path() -> Result<PathBuf> {
         let home = dirs::home_dir().context("Could not determine home directory")?;
@@ -131,22 +519,11 @@ impl SecureStorage {
             .unwrap_or_default();
 
         let password = format!("hive-secure-storage-v2:{username}:{home}");
-
-        let params = Params::new(19_456, 2, 1, Some(32))
-            .map_err(|e| anyhow::anyhow!("Invalid Argon2 params: {e}"))?;
-        let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
-
-        let mut key = [0u8; 32];
-        argon2
-            .hash_password_into(password.as_bytes(), &salt, &mut key)
-            .map_err(|e| anyhow::anyhow!("Argon2 key derivation failed: {e}"))?;
-
-        Ok(key)
+        Self::derive_key_raw(password.as_bytes(), &salt)
     }
 
-    /// Derive a key from an explicit salt and password. Used in tests to verify
-    /// determinism and independence without touching the filesystem.
-    #[cfg(test)]
+    /// Derive a key from an explicit salt and password (Argon2id, same
+    /// parameters as [`derive_key`](Self::derive_key)).
     fn derive_key_raw(password: &[u8], salt: &[u8; SALT_LEN]) -> Result<[u8; 32]> {
         let params = Params::new(19_456, 2, 1, Some(32))
             .map_err(|e| anyhow::anyhow!("Invalid Argon2 params: {e}"))?;
@@ -400,4 +777,156 @@ mod tests {
             "Different salts must prevent cross-decryption"
         );
     }
+
+    // ---- passphrase mode, locking and keyring backends ----
+
+    #[test]
+    fn passphrase_storage_starts_locked_after_reopen() {
+        let tmp = TempDir::new().unwrap();
+        let salt_path = tmp.path().join(SALT_FILENAME);
+        let storage = SecureStorage::with_passphrase(&salt_path, "correct horse").unwrap();
+        let encrypted = storage.encrypt("wallet seed").unwrap();
+        storage.save_settings().unwrap();
+
+        let reopened = SecureStorage::open(&salt_path).unwrap();
+        assert_eq!(reopened.source(), MasterKeySource::Passphrase);
+        assert!(reopened.is_locked());
+        assert!(reopened.decrypt(&encrypted).is_err());
+
+        assert!(reopened.unlock("wrong horse").is_err());
+        assert!(reopened.is_locked());
+
+        reopened.unlock("correct horse").unwrap();
+        assert_eq!(reopened.decrypt(&encrypted).unwrap(), "wallet seed");
+    }
+
+    #[test]
+    fn unreadable_settings_do_not_fall_back_to_machine_key() {
+        let tmp = TempDir::new().unwrap();
+        let salt_path = tmp.path().join(SALT_FILENAME);
+        // A directory in place of the settings file makes the read fail with
+        // something other than NotFound.
+        fs::create_dir(tmp.path().join(SETTINGS_FILENAME)).unwrap();
+        assert!(SecureStorage::open(&salt_path).is_err());
+    }
+
+    #[test]
+    fn lock_applies_to_duplicates() {
+        let tmp = TempDir::new().unwrap();
+        let salt_path = tmp.path().join(SALT_FILENAME);
+        let storage = SecureStorage::with_passphrase(&salt_path, "pw").unwrap();
+        let dup = storage.duplicate();
+
+        storage.lock();
+        assert!(dup.is_locked());
+        assert!(dup.encrypt("x").is_err());
+
+        dup.unlock("pw").unwrap();
+        assert!(!storage.is_locked());
+    }
+
+    #[test]
+    fn machine_storage_cannot_be_locked() {
+        let tmp = TempDir::new().unwrap();
+        let storage = storage_in(tmp.path());
+        storage.lock();
+        assert!(!storage.is_locked());
+    }
+
+    #[test]
+    fn idle_lock_only_after_timeout() {
+        let tmp = TempDir::new().unwrap();
+        let salt_path = tmp.path().join(SALT_FILENAME);
+        let storage = SecureStorage::with_passphrase(&salt_path, "pw").unwrap();
+
+        // Disabled by default.
+        assert!(!storage.lock_if_idle());
+
+        storage.set_idle_lock_minutes(5);
+        assert!(!storage.lock_if_idle());
+
+        storage.state.write().last_used = Instant::now() - Duration::from_secs(6 * 60);
+        assert!(storage.lock_if_idle());
+        assert!(storage.is_locked());
+    }
+
+    #[test]
+    fn keyring_storage_reopens_with_same_key() {
+        let tmp = TempDir::new().unwrap();
+        let salt_path = tmp.path().join(SALT_FILENAME);
+        let backend = crate::keyring::MemoryKeyring::new();
+
+        let storage = SecureStorage::with_keyring(&salt_path, &backend).unwrap();
+        assert_eq!(backend.get_secret(KEYRING_ACCOUNT).unwrap(), None);
+        storage.store_key(&backend).unwrap();
+        assert!(!storage.has_unsaved_key());
+        let encrypted = storage.encrypt("api key").unwrap();
+        storage.save_settings().unwrap();
+
+        let reopened = SecureStorage::open_with_backend(&salt_path, &backend).unwrap();
+        assert_eq!(
+            reopened.source(),
+            MasterKeySource::Keyring {
+                backend: "memory".into()
+            }
+        );
+        assert_eq!(reopened.decrypt(&encrypted).unwrap(), "api key");
+
+        // Without the keyring entry the key cannot be recovered.
+        backend.delete_secret(KEYRING_ACCOUNT).unwrap();
+        assert!(SecureStorage::open_with_backend(&salt_path, &backend).is_err());
+    }
+
+    #[test]
+    fn reencrypt_migrates_machine_secrets_to_passphrase() {
+        let tmp = TempDir::new().unwrap();
+        let salt_path = tmp.path().join(SALT_FILENAME);
+        let machine = SecureStorage::with_salt_path(&salt_path).unwrap();
+
+        let mut map = std::collections::HashMap::new();
+        map.insert("a".to_string(), machine.encrypt("alpha").unwrap());
+        map.insert("b".to_string(), machine.encrypt("beta").unwrap());
+        let blob = machine.encrypt_bytes(b"raw wallet key").unwrap();
+
+        let protected = SecureStorage::with_passphrase(&salt_path, "pw").unwrap();
+        assert_eq!(machine.reencrypt(&protected, &mut map).unwrap(), 2);
+        let blob = machine.reencrypt_bytes(&protected, &blob).unwrap();
+
+        let handle = machine.duplicate();
+        machine.adopt(protected);
+        assert_eq!(handle.source(), MasterKeySource::Passphrase);
+        assert_eq!(handle.decrypt(&map["a"]).unwrap(), "alpha");
+        assert_eq!(handle.decrypt(&map["b"]).unwrap(), "beta");
+        assert_eq!(handle.decrypt_bytes(&blob).unwrap(), b"raw wallet key");
+    }
+
+    #[test]
+    fn reencrypt_leaves_map_untouched_on_error() {
+        let tmp = TempDir::new().unwrap();
+        let machine = storage_in(tmp.path());
+        let mut map = std::collections::HashMap::new();
+        map.insert("good".to_string(), machine.encrypt("ok").unwrap());
+        map.insert("bad".to_string(), "deadbeef".to_string());
+        let before = map.clone();
+
+        let target = SecureStorage::with_passphrase(&tmp.path().join("other.salt"), "pw").unwrap();
+        assert!(machine.reencrypt(&target, &mut map).is_err());
+        assert_eq!(map, before);
+    }
+
+    #[test]
+    fn missing_settings_file_means_machine_mode() {
+        let tmp = TempDir::new().unwrap();
+        let salt_path = tmp.path().join(SALT_FILENAME);
+        let storage = SecureStorage::open(&salt_path).unwrap();
+        assert_eq!(storage.source(), MasterKeySource::Machine);
+        assert!(!storage.is_locked());
+    }
+
+    #[test]
+    fn empty_passphrase_rejected() {
+        let tmp = TempDir::new().unwrap();
+        let salt_path = tmp.path().join(SALT_FILENAME);
+        assert!(SecureStorage::with_passphrase(&salt_path, "").is_err());
+    }
 }
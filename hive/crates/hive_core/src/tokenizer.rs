@@ -0,0 +1,282 @@
+//! Model-aware token counting.
+//!
+//! OpenAI models are counted with the tiktoken BPE tables (`o200k_base`,
+//! `cl100k_base`) bundled in the binary. Open-weight families (Llama,
+//! Mistral, Qwen, ...) are counted with their SentencePiece/BPE vocabulary
+//! loaded from a local Hugging Face `tokenizer.json`, found at
+//! `~/.hive/tokenizers/<family>/tokenizer.json`. When no tokenizer is
+//! available the `chars / 4` heuristic from [`crate::context`] is used.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+use crate::config::HiveConfig;
+
+/// File name expected inside each local tokenizer directory.
+const TOKENIZER_FILENAME: &str = "tokenizer.json";
+
+/// Tokenizer used to count tokens for a model.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenizerFamily {
+    /// tiktoken `o200k_base` (GPT-4o, GPT-4.1, o-series, GPT-5).
+    O200kBase,
+    /// tiktoken `cl100k_base` (GPT-4, GPT-3.5). Also used as the closest
+    /// public approximation for Anthropic models, whose tokenizer is not
+    /// published.
+    Cl100kBase,
+    /// A local `tokenizer.json` under `<tokenizers_dir>/<name>/`.
+    Local(String),
+    /// `chars / 4` estimate.
+    Heuristic,
+}
+
+impl TokenizerFamily {
+    /// Pick a tokenizer from a model ID alone. Provider-qualified IDs such as
+    /// `meta-llama/llama-3.1-70b-instruct` or `mistral:latest` are handled.
+    pub fn for_model(model_id: &str) -> Self {
+        let id = model_id.to_lowercase();
+        let name = id.rsplit('/').next().unwrap_or(&id);
+
+        if name.starts_with("gpt-4o")
+            || name.starts_with("gpt-4.1")
+            || name.starts_with("gpt-4.5")
+            || name.starts_with("gpt-5")
+            || name.starts_with("chatgpt-4o")
+            || name.starts_with("o1")
+            || name.starts_with("o3")
+            || name.starts_with("o4")
+        {
+            return Self::O200kBase;
+        }
+        if name.starts_with("gpt-4") || name.starts_with("gpt-3.5") || name.starts_with("claude")
+        {
+            return Self::Cl100kBase;
+        }
+
+        const LOCAL_FAMILIES: &[(&str, &str)] = &[
+            ("llama", "llama"),
+            ("codellama", "llama"),
+            ("mistral", "mistral"),
+            ("mixtral", "mistral"),
+            ("codestral", "mistral"),
+            ("ministral", "mistral"),
+            ("qwen", "qwen"),
+            ("gemma", "gemma"),
+            ("gemini", "gemma"),
+            ("deepseek", "deepseek"),
+            ("phi", "phi"),
+        ];
+        LOCAL_FAMILIES
+            .iter()
+            .find(|(prefix, _)| name.starts_with(prefix))
+            .map(|(_, family)| Self::Local((*family).to_string()))
+            .unwrap_or(Self::Heuristic)
+    }
+}
+
+/// Counts tokens per [`TokenizerFamily`], caching loaded local tokenizers.
+pub struct TokenizerRegistry {
+    dir: Option<PathBuf>,
+    /// `None` entries record a failed load so the file is not retried on
+    /// every call.
+    local: RwLock<HashMap<String, Option<Arc<tokenizers::Tokenizer>>>>,
+}
+
+static GLOBAL_REGISTRY: Lazy<TokenizerRegistry> =
+    Lazy::new(|| TokenizerRegistry::new(HiveConfig::tokenizers_dir().ok()));
+
+impl TokenizerRegistry {
+    /// Create a registry that loads local tokenizers from `dir`.
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        Self {
+            dir,
+            local: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The process-wide registry reading from `~/.hive/tokenizers/`.
+    pub fn global() -> &'static TokenizerRegistry {
+        &GLOBAL_REGISTRY
+    }
+
+    /// Count tokens in `text` for `family`, falling back to the heuristic
+    /// when the family's tokenizer is unavailable.
+    pub fn count(&self, family: &TokenizerFamily, text: &str) -> usize {
+        if text.is_empty() {
+            return 0;
+        }
+        match family {
+            TokenizerFamily::O200kBase => tiktoken_rs::o200k_base_singleton()
+                .encode_ordinary(text)
+                .len(),
+            TokenizerFamily::Cl100kBase => tiktoken_rs::cl100k_base_singleton()
+                .encode_ordinary(text)
+                .len(),
+            TokenizerFamily::Local(name) => match self.local_tokenizer(name) {
+                Some(tokenizer) => match tokenizer.encode(text, false) {
+                    Ok(encoding) => encoding.len(),
+                    Err(e) => {
+                        warn!("Tokenizer '{name}' failed to encode: {e}");
+                        crate::context::estimate_tokens(text)
+                    }
+                },
+                None => crate::context::estimate_tokens(text),
+            },
+            TokenizerFamily::Heuristic => crate::context::estimate_tokens(text),
+        }
+    }
+
+    /// Count tokens in `text` for a model ID.
+    pub fn count_for_model(&self, model_id: &str, text: &str) -> usize {
+        self.count(&TokenizerFamily::for_model(model_id), text)
+    }
+
+    /// Whether `family` will be counted exactly (not with the heuristic).
+    pub fn is_exact(&self, family: &TokenizerFamily) -> bool {
+        match family {
+            TokenizerFamily::O200kBase | TokenizerFamily::Cl100kBase => true,
+            TokenizerFamily::Local(name) => self.local_tokenizer(name).is_some(),
+            TokenizerFamily::Heuristic => false,
+        }
+    }
+
+    fn local_tokenizer(&self, name: &str) -> Option<Arc<tokenizers::Tokenizer>> {
+        if let Some(cached) = self.local.read().get(name) {
+            return cached.clone();
+        }
+        let loaded = self
+            .dir
+            .as_deref()
+            .and_then(|dir| Self::load_local(dir, name));
+        self.local.write().insert(name.to_string(), loaded.clone());
+        loaded
+    }
+
+    fn load_local(dir: &Path, name: &str) -> Option<Arc<tokenizers::Tokenizer>> {
+        let path = dir.join(name).join(TOKENIZER_FILENAME);
+        if !path.exists() {
+            debug!("No local tokenizer for '{name}' at {}", path.display());
+            return None;
+        }
+        match tokenizers::Tokenizer::from_file(&path) {
+            Ok(tokenizer) => {
+                debug!("Loaded tokenizer '{name}' from {}", path.display());
+                Some(Arc::new(tokenizer))
+            }
+            Err(e) => {
+                warn!("Failed to load tokenizer {}: {e}", path.display());
+                None
+            }
+        }
+    }
+}
+
+/// Count tokens in `text` for `model_id` using the global registry.
+pub fn count_tokens(model_id: &str, text: &str) -> usize {
+    TokenizerRegistry::global().count_for_model(model_id, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn family_for_openai_models() {
+        assert_eq!(TokenizerFamily::for_model("gpt-4o-mini"), TokenizerFamily::O200kBase);
+        assert_eq!(TokenizerFamily::for_model("o3-mini"), TokenizerFamily::O200kBase);
+        assert_eq!(
+            TokenizerFamily::for_model("openai/gpt-4.1"),
+            TokenizerFamily::O200kBase
+        );
+        assert_eq!(TokenizerFamily::for_model("gpt-4-turbo"), TokenizerFamily::Cl100kBase);
+        assert_eq!(
+            TokenizerFamily::for_model("gpt-3.5-turbo"),
+            TokenizerFamily::Cl100kBase
+        );
+    }
+
+    #[test]
+    fn family_for_open_weight_models() {
+        assert_eq!(
+            TokenizerFamily::for_model("meta-llama/llama-3.1-70b-instruct"),
+            TokenizerFamily::Local("llama".into())
+        );
+        assert_eq!(
+            TokenizerFamily::for_model("mistral:latest"),
+            TokenizerFamily::Local("mistral".into())
+        );
+        assert_eq!(
+            TokenizerFamily::for_model("Mixtral-8x7B"),
+            TokenizerFamily::Local("mistral".into())
+        );
+        assert_eq!(
+            TokenizerFamily::for_model("totally-unknown"),
+            TokenizerFamily::Heuristic
+        );
+    }
+
+    #[test]
+    fn tiktoken_counts_differ_from_heuristic() {
+        let registry = TokenizerRegistry::new(None);
+        // "hello world" is two tokens in both BPE tables.
+        assert_eq!(registry.count(&TokenizerFamily::O200kBase, "hello world"), 2);
+        assert_eq!(registry.count(&TokenizerFamily::Cl100kBase, "hello world"), 2);
+        assert_eq!(registry.count(&TokenizerFamily::O200kBase, ""), 0);
+        assert!(registry.is_exact(&TokenizerFamily::O200kBase));
+    }
+
+    #[test]
+    fn non_english_text_counts_more_than_heuristic() {
+        let registry = TokenizerRegistry::new(None);
+        let text = "これは日本語のテキストです。トークン数は文字数に近くなります。";
+        let exact = registry.count(&TokenizerFamily::Cl100kBase, text);
+        let heuristic = crate::context::estimate_tokens(text);
+        assert!(exact > 0);
+        assert_ne!(exact, heuristic);
+    }
+
+    #[test]
+    fn missing_local_tokenizer_falls_back_to_heuristic() {
+        let tmp = tempfile::tempdir().unwrap();
+        let registry = TokenizerRegistry::new(Some(tmp.path().to_path_buf()));
+        let family = TokenizerFamily::Local("llama".into());
+        let text = "a".repeat(100);
+        assert_eq!(registry.count(&family, &text), 25);
+        assert!(!registry.is_exact(&family));
+    }
+
+    #[test]
+    fn local_tokenizer_json_is_loaded() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("tiny");
+        std::fs::create_dir_all(&dir).unwrap();
+        // Minimal word-level tokenizer.json with whitespace pre-tokenization.
+        let json = serde_json::json!({
+            "version": "1.0",
+            "truncation": null,
+            "padding": null,
+            "added_tokens": [],
+            "normalizer": null,
+            "pre_tokenizer": { "type": "Whitespace" },
+            "post_processor": null,
+            "decoder": null,
+            "model": {
+                "type": "WordLevel",
+                "vocab": { "[UNK]": 0, "hello": 1, "world": 2 },
+                "unk_token": "[UNK]"
+            }
+        });
+        std::fs::write(dir.join(TOKENIZER_FILENAME), json.to_string()).unwrap();
+
+        let registry = TokenizerRegistry::new(Some(tmp.path().to_path_buf()));
+        let family = TokenizerFamily::Local("tiny".into());
+        assert!(registry.is_exact(&family));
+        assert_eq!(registry.count(&family, "hello world hello"), 3);
+    }
+}
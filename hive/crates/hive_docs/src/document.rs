@@ -0,0 +1,638 @@
+//! Document model shared by the generators.
+//!
+//! Writers render a [`Document`] — a tree of block [`Node`]s holding styled
+//! [`Inline`] content — rather than flat `(heading, body)` pairs, so lists,
+//! emphasis, code, links, images and nested headings survive into every
+//! format. [`crate::markdown::parse_markdown`] builds one from model output;
+//! the `render_*` functions in each format module write it back out.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Largest image [`Document::load_images`] will read (20 MiB).
+const MAX_IMAGE_SIZE: u64 = 20 * 1024 * 1024;
+
+/// A document: an optional title followed by blocks.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Document {
+    pub title: Option<String>,
+    pub blocks: Vec<Node>,
+}
+
+/// Block-level content.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Node {
+    Heading {
+        /// 1 to 6.
+        level: u8,
+        content: Vec<Inline>,
+    },
+    Paragraph {
+        content: Vec<Inline>,
+    },
+    /// A bulleted list, or a numbered one counting from `start`.
+    List {
+        start: Option<u64>,
+        items: Vec<ListItem>,
+    },
+    CodeBlock {
+        language: Option<String>,
+        code: String,
+    },
+    Quote {
+        blocks: Vec<Node>,
+    },
+    Table {
+        header: Vec<Cell>,
+        rows: Vec<Vec<Cell>>,
+        /// Per-column alignment; may be shorter than the header.
+        alignments: Vec<Alignment>,
+    },
+    Rule,
+}
+
+/// One item of a [`Node::List`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ListItem {
+    /// Task-list state; `None` for ordinary items.
+    pub checked: Option<bool>,
+    pub blocks: Vec<Node>,
+}
+
+/// The content of a table cell.
+pub type Cell = Vec<Inline>;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Alignment {
+    #[default]
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+/// Inline content within a block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum Inline {
+    Text(String),
+    Strong(Vec<Inline>),
+    Emphasis(Vec<Inline>),
+    Strikethrough(Vec<Inline>),
+    Code(String),
+    Link { url: String, content: Vec<Inline> },
+    Image(Image),
+    LineBreak,
+}
+
+/// An image reference, with its bytes once loaded.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Image {
+    /// URL, file path or `data:` URI, as written in the source.
+    pub src: String,
+    pub alt: String,
+    /// Encoded PNG or JPEG data. Writers that embed images need this; the
+    /// others fall back to `src`, and to `alt` when nothing can be shown.
+    #[serde(skip)]
+    pub data: Option<Vec<u8>>,
+}
+
+impl Document {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn titled(title: impl Into<String>) -> Self {
+        Self {
+            title: Some(title.into()),
+            blocks: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, node: Node) {
+        self.blocks.push(node);
+    }
+
+    /// The explicit title, else the text of the first heading.
+    pub fn display_title(&self) -> Option<String> {
+        if let Some(title) = &self.title {
+            return Some(title.clone());
+        }
+        self.blocks.iter().find_map(|node| match node {
+            Node::Heading { content, .. } => Some(plain_text(content)),
+            _ => None,
+        })
+    }
+
+    /// Fill in [`Image::data`] for `data:` URIs and local files, resolving
+    /// relative paths against `base_dir`. Remote URLs are left alone, as are
+    /// files that aren't PNG or JPEG.
+    pub fn load_images(&mut self, base_dir: &Path) {
+        for node in &mut self.blocks {
+            node.visit_images(&mut |image| {
+                if image.data.is_none() {
+                    image.data = load_image(&image.src, base_dir);
+                }
+            });
+        }
+    }
+}
+
+impl Node {
+    pub fn heading(level: u8, text: impl Into<String>) -> Self {
+        Node::Heading {
+            level: level.clamp(1, 6),
+            content: vec![Inline::Text(text.into())],
+        }
+    }
+
+    pub fn paragraph(text: impl Into<String>) -> Self {
+        Node::Paragraph {
+            content: vec![Inline::Text(text.into())],
+        }
+    }
+
+    /// A paragraph keeping each line of `text` on a line of its own.
+    pub fn lines(text: &str) -> Self {
+        let mut content = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            if i > 0 {
+                content.push(Inline::LineBreak);
+            }
+            content.push(Inline::Text(line.to_string()));
+        }
+        Node::Paragraph { content }
+    }
+
+    /// A table of plain-text cells.
+    pub fn table(headers: &[&str], rows: &[Vec<String>]) -> Self {
+        Node::Table {
+            header: headers
+                .iter()
+                .map(|h| vec![Inline::Text((*h).to_string())])
+                .collect(),
+            rows: rows
+                .iter()
+                .map(|row| row.iter().map(|c| vec![Inline::Text(c.clone())]).collect())
+                .collect(),
+            alignments: Vec::new(),
+        }
+    }
+
+    fn visit_images(&mut self, f: &mut dyn FnMut(&mut Image)) {
+        match self {
+            Node::Heading { content, .. } | Node::Paragraph { content } => {
+                visit_inline_images(content, f)
+            }
+            Node::List { items, .. } => {
+                for item in items {
+                    for node in &mut item.blocks {
+                        node.visit_images(f);
+                    }
+                }
+            }
+            Node::Quote { blocks } => {
+                for node in blocks {
+                    node.visit_images(f);
+                }
+            }
+            Node::Table { header, rows, .. } => {
+                for cell in header.iter_mut().chain(rows.iter_mut().flatten()) {
+                    visit_inline_images(cell, f);
+                }
+            }
+            Node::CodeBlock { .. } | Node::Rule => {}
+        }
+    }
+}
+
+fn visit_inline_images(content: &mut [Inline], f: &mut dyn FnMut(&mut Image)) {
+    for inline in content {
+        match inline {
+            Inline::Image(image) => f(image),
+            Inline::Strong(inner)
+            | Inline::Emphasis(inner)
+            | Inline::Strikethrough(inner)
+            | Inline::Link { content: inner, .. } => visit_inline_images(inner, f),
+            Inline::Text(_) | Inline::Code(_) | Inline::LineBreak => {}
+        }
+    }
+}
+
+/// Inline content as plain text: images become their alt text and line
+/// breaks become newlines.
+pub fn plain_text(content: &[Inline]) -> String {
+    let mut out = String::new();
+    for inline in content {
+        match inline {
+            Inline::Text(text) | Inline::Code(text) => out.push_str(text),
+            Inline::Strong(inner)
+            | Inline::Emphasis(inner)
+            | Inline::Strikethrough(inner)
+            | Inline::Link { content: inner, .. } => out.push_str(&plain_text(inner)),
+            Inline::Image(image) => out.push_str(&image.alt),
+            Inline::LineBreak => out.push('\n'),
+        }
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Flattened runs
+// ---------------------------------------------------------------------------
+
+/// Character styling accumulated from enclosing inlines.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub strike: bool,
+    /// Monospace, from inline code.
+    pub code: bool,
+}
+
+/// Inline content flattened into uniformly styled pieces, the shape the
+/// run-based formats (PDF, DOCX, PPTX) write.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Span<'a> {
+    Text {
+        text: &'a str,
+        style: TextStyle,
+        link: Option<&'a str>,
+    },
+    Image(&'a Image),
+    LineBreak,
+}
+
+pub fn spans(content: &[Inline]) -> Vec<Span<'_>> {
+    let mut out = Vec::new();
+    collect_spans(content, TextStyle::default(), None, &mut out);
+    out
+}
+
+fn collect_spans<'a>(
+    content: &'a [Inline],
+    style: TextStyle,
+    link: Option<&'a str>,
+    out: &mut Vec<Span<'a>>,
+) {
+    for inline in content {
+        match inline {
+            Inline::Text(text) => out.push(Span::Text { text, style, link }),
+            Inline::Code(text) => out.push(Span::Text {
+                text,
+                style: TextStyle {
+                    code: true,
+                    ..style
+                },
+                link,
+            }),
+            Inline::Strong(inner) => {
+                let style = TextStyle {
+                    bold: true,
+                    ..style
+                };
+                collect_spans(inner, style, link, out);
+            }
+            Inline::Emphasis(inner) => {
+                let style = TextStyle {
+                    italic: true,
+                    ..style
+                };
+                collect_spans(inner, style, link, out);
+            }
+            Inline::Strikethrough(inner) => {
+                let style = TextStyle {
+                    strike: true,
+                    ..style
+                };
+                collect_spans(inner, style, link, out);
+            }
+            Inline::Link { url, content } => collect_spans(content, style, Some(url), out),
+            Inline::Image(image) => out.push(Span::Image(image)),
+            Inline::LineBreak => out.push(Span::LineBreak),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Images
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+}
+
+impl ImageFormat {
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "image/png",
+            ImageFormat::Jpeg => "image/jpeg",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg => "jpeg",
+        }
+    }
+}
+
+/// Format and pixel size read from an image's header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageInfo {
+    pub format: ImageFormat,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Identify PNG and JPEG data and read its dimensions.
+pub fn image_info(data: &[u8]) -> Option<ImageInfo> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        // The IHDR chunk always comes first.
+        let ihdr = data.get(16..24)?;
+        let width = u32::from_be_bytes(ihdr[0..4].try_into().ok()?);
+        let height = u32::from_be_bytes(ihdr[4..8].try_into().ok()?);
+        return (width > 0 && height > 0).then_some(ImageInfo {
+            format: ImageFormat::Png,
+            width,
+            height,
+        });
+    }
+    if data.starts_with(&[0xFF, 0xD8]) {
+        let (width, height) = jpeg_dimensions(data)?;
+        return Some(ImageInfo {
+            format: ImageFormat::Jpeg,
+            width,
+            height,
+        });
+    }
+    None
+}
+
+/// Walk JPEG markers to the first start-of-frame segment.
+fn jpeg_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let mut i = 2;
+    while i + 4 <= data.len() {
+        if data[i] != 0xFF {
+            return None;
+        }
+        let marker = data[i + 1];
+        if marker == 0xFF {
+            i += 1;
+            continue;
+        }
+        let length = usize::from(u16::from_be_bytes([data[i + 2], data[i + 3]]));
+        // SOF0–SOF15, except DHT (C4), JPG (C8) and DAC (CC).
+        if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            let segment = data.get(i + 4..i + 2 + length)?;
+            let height = u32::from(u16::from_be_bytes([*segment.get(1)?, *segment.get(2)?]));
+            let width = u32::from(u16::from_be_bytes([*segment.get(3)?, *segment.get(4)?]));
+            return (width > 0 && height > 0).then_some((width, height));
+        }
+        i += 2 + length;
+    }
+    None
+}
+
+fn load_image(src: &str, base_dir: &Path) -> Option<Vec<u8>> {
+    let data = if let Some(uri) = src.strip_prefix("data:") {
+        let (meta, payload) = uri.split_once(',')?;
+        if !meta.ends_with(";base64") {
+            return None;
+        }
+        base64_decode(payload)?
+    } else if src.contains("://") && !src.starts_with("file://") {
+        return None;
+    } else {
+        let path = src.strip_prefix("file://").unwrap_or(src);
+        let path = base_dir.join(path);
+        let metadata = std::fs::metadata(&path).ok()?;
+        if !metadata.is_file() || metadata.len() > MAX_IMAGE_SIZE {
+            return None;
+        }
+        std::fs::read(&path).ok()?
+    };
+    image_info(&data).is_some().then_some(data)
+}
+
+const BASE64_TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Decode standard base64, ignoring whitespace and padding.
+pub(crate) fn base64_decode(input: &str) -> Option<Vec<u8>> {
+    let mut lookup = [255u8; 256];
+    for (i, &b) in BASE64_TABLE.iter().enumerate() {
+        lookup[b as usize] = i as u8;
+    }
+
+    let mut output = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for b in input.bytes() {
+        if b == b'=' || b.is_ascii_whitespace() {
+            continue;
+        }
+        let value = lookup[b as usize];
+        if value == 255 {
+            return None;
+        }
+        buffer = buffer << 6 | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+    Some(output)
+}
+
+/// Encode bytes as standard, padded base64.
+pub(crate) fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, &b)| acc | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_TABLE[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// A 2×2 RGBA PNG: red, green, blue and a transparent pixel.
+#[cfg(test)]
+pub(crate) fn test_png() -> Vec<u8> {
+    use flate2::{Compression, Crc, write::ZlibEncoder};
+    use std::io::Write;
+
+    fn chunk(png: &mut Vec<u8>, kind: &[u8], data: &[u8]) {
+        png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        png.extend_from_slice(kind);
+        png.extend_from_slice(data);
+        let mut crc = Crc::new();
+        crc.update(kind);
+        crc.update(data);
+        png.extend_from_slice(&crc.sum().to_be_bytes());
+    }
+
+    let mut ihdr = Vec::new();
+    ihdr.extend_from_slice(&2u32.to_be_bytes());
+    ihdr.extend_from_slice(&2u32.to_be_bytes());
+    ihdr.extend_from_slice(&[8, 6, 0, 0, 0]);
+    let scanlines = [
+        0, 255, 0, 0, 255, 0, 255, 0, 255, // filter None
+        0, 0, 0, 255, 255, 0, 0, 0, 0,
+    ];
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&scanlines).unwrap();
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    chunk(&mut png, b"IHDR", &ihdr);
+    chunk(&mut png, b"IDAT", &encoder.finish().unwrap());
+    chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spans_accumulate_styles_and_links() {
+        let content = vec![
+            Inline::Text("a ".into()),
+            Inline::Strong(vec![
+                Inline::Text("b ".into()),
+                Inline::Emphasis(vec![Inline::Code("c".into())]),
+            ]),
+            Inline::Link {
+                url: "https://example.com".into(),
+                content: vec![Inline::Text("d".into())],
+            },
+            Inline::LineBreak,
+        ];
+        let spans = spans(&content);
+
+        assert_eq!(spans.len(), 5);
+        assert_eq!(
+            spans[2],
+            Span::Text {
+                text: "c",
+                style: TextStyle {
+                    bold: true,
+                    italic: true,
+                    code: true,
+                    strike: false,
+                },
+                link: None,
+            }
+        );
+        assert!(matches!(
+            spans[3],
+            Span::Text {
+                link: Some("https://example.com"),
+                ..
+            }
+        ));
+        assert_eq!(spans[4], Span::LineBreak);
+        assert_eq!(plain_text(&content), "a b cd\n");
+    }
+
+    #[test]
+    fn test_display_title_falls_back_to_first_heading() {
+        let mut doc = Document::new();
+        doc.push(Node::paragraph("intro"));
+        doc.push(Node::heading(2, "Findings"));
+        assert_eq!(doc.display_title().as_deref(), Some("Findings"));
+        assert_eq!(
+            Document::titled("Report").display_title().as_deref(),
+            Some("Report")
+        );
+    }
+
+    #[test]
+    fn test_image_info() {
+        assert_eq!(
+            image_info(&test_png()),
+            Some(ImageInfo {
+                format: ImageFormat::Png,
+                width: 2,
+                height: 2
+            })
+        );
+        let jpeg = [
+            0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, 0xFF, 0xC0, 0x00, 0x0B, 0x08, 0x00,
+            0x20, 0x00, 0x40, 0x01, 0x01, 0x11, 0x00,
+        ];
+        assert_eq!(
+            image_info(&jpeg),
+            Some(ImageInfo {
+                format: ImageFormat::Jpeg,
+                width: 64,
+                height: 32
+            })
+        );
+        assert_eq!(image_info(b"GIF89a"), None);
+    }
+
+    #[test]
+    fn test_load_images_from_data_uri_and_file() {
+        let dir = std::env::temp_dir().join(format!("hive_docs_images_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("pixel.png"), test_png()).unwrap();
+
+        let image = |src: &str| {
+            Inline::Image(Image {
+                src: src.into(),
+                alt: "pixel".into(),
+                data: None,
+            })
+        };
+        let mut doc = Document::new();
+        doc.push(Node::Paragraph {
+            content: vec![
+                image(&format!(
+                    "data:image/png;base64,{}",
+                    base64_encode(&test_png())
+                )),
+                image("pixel.png"),
+                image("missing.png"),
+                image("https://example.com/pixel.png"),
+            ],
+        });
+        doc.load_images(&dir);
+        std::fs::remove_dir_all(&dir).ok();
+
+        let Node::Paragraph { content } = &doc.blocks[0] else {
+            panic!("expected a paragraph");
+        };
+        let loaded: Vec<bool> = content
+            .iter()
+            .map(|inline| matches!(inline, Inline::Image(Image { data: Some(_), .. })))
+            .collect();
+        assert_eq!(loaded, vec![true, true, false, false]);
+    }
+
+    #[test]
+    fn test_base64_roundtrip() {
+        let png = test_png();
+        for input in [&b""[..], b"f", b"fo", b"foo", b"foobar", &png] {
+            let encoded = base64_encode(input);
+            assert_eq!(base64_decode(&encoded).unwrap(), input);
+        }
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert!(base64_decode("not*base64").is_none());
+    }
+}
@@ -1,42 +1,29 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use docx_rs::*;
+use std::collections::HashMap;
 use std::io::Cursor;
 
+use crate::document::{
+    Alignment, Cell, Document, Image, Inline, ListItem, Node, Span, TextStyle, image_info, spans,
+};
+use crate::extract::{
+    Anchor, Block, DocumentFormat, ExtractedDocument, Package, Section, attr, child,
+    find_relationship, is, parse_xml,
+};
+
 /// Generate a DOCX document with a title and a series of sections.
 ///
-/// The title is rendered as a large, bold heading. Each section gets a bold
-/// heading followed by its body text (split on newlines into separate paragraphs).
+/// The title uses the "Title" style and each section a "Heading 2"
+/// followed by its body text (split on newlines into separate paragraphs).
 pub fn generate_docx_document(title: &str, sections: &[(&str, &str)]) -> Result<Vec<u8>> {
-    let mut docx = Docx::new();
-
-    // Title paragraph -- large bold text
-    let title_run = Run::new().add_text(title).bold().size(48); // size is in half-points, so 48 = 24pt
-    docx = docx.add_paragraph(Paragraph::new().add_run(title_run));
-
-    // Spacer paragraph
-    docx = docx.add_paragraph(Paragraph::new());
-
+    let mut document = Document::titled(title);
     for (heading, body) in sections {
-        // Section heading -- bold, medium size
-        let heading_run = Run::new().add_text(*heading).bold().size(32); // 16pt
-        docx = docx.add_paragraph(Paragraph::new().add_run(heading_run));
-
-        // Body text -- split by newlines into separate paragraphs
+        document.push(Node::heading(2, *heading));
         for line in body.lines() {
-            let body_run = Run::new().add_text(line).size(22); // 11pt
-            docx = docx.add_paragraph(Paragraph::new().add_run(body_run));
+            document.push(Node::paragraph(line));
         }
-
-        // Spacer after each section
-        docx = docx.add_paragraph(Paragraph::new());
     }
-
-    let mut buf = Cursor::new(Vec::new());
-    docx.build()
-        .pack(&mut buf)
-        .map_err(|e| anyhow::anyhow!("Failed to pack DOCX: {}", e))?;
-
-    Ok(buf.into_inner())
+    render_docx(&document)
 }
 
 /// Generate a DOCX document containing a table with headers and rows.
@@ -44,40 +31,68 @@ pub fn generate_docx_document(title: &str, sections: &[(&str, &str)]) -> Result<
 /// Headers are rendered in bold. The table spans the full width with evenly
 /// distributed columns.
 pub fn generate_docx_table(title: &str, headers: &[&str], rows: &[Vec<String>]) -> Result<Vec<u8>> {
-    let mut docx = Docx::new();
-
-    // Title
-    let title_run = Run::new().add_text(title).bold().size(36); // 18pt
-    docx = docx.add_paragraph(Paragraph::new().add_run(title_run));
-    docx = docx.add_paragraph(Paragraph::new());
-
-    // Build table rows
-    let mut table_rows = Vec::new();
-
-    // Header row
-    let header_cells: Vec<TableCell> = headers
-        .iter()
-        .map(|h| {
-            let run = Run::new().add_text(*h).bold().size(22);
-            TableCell::new().add_paragraph(Paragraph::new().add_run(run))
-        })
-        .collect();
-    table_rows.push(TableRow::new(header_cells));
+    let mut document = Document::titled(title);
+    document.push(Node::table(headers, rows));
+    render_docx(&document)
+}
+
+/// Render a [`Document`] to DOCX.
+///
+/// Headings use Word's "Title" and "Heading N" styles so they appear in the
+/// navigation pane, lists are real numbered or bulleted lists, links are
+/// hyperlinks and loaded images are embedded. Pages are numbered in the
+/// footer.
+pub fn render_docx(document: &Document) -> Result<Vec<u8>> {
+    let mut writer = DocxWriter::default();
+    if let Some(title) = &document.title {
+        writer.parts.push(DocxPart::Paragraph(Box::new(
+            Paragraph::new()
+                .style("Title")
+                .add_run(Run::new().add_text(title)),
+        )));
+    }
+    writer.blocks(&document.blocks, Scope::default());
 
-    // Data rows
-    for row in rows {
-        let cells: Vec<TableCell> = row
-            .iter()
-            .map(|cell_text| {
-                let run = Run::new().add_text(cell_text).size(22);
-                TableCell::new().add_paragraph(Paragraph::new().add_run(run))
-            })
-            .collect();
-        table_rows.push(TableRow::new(cells));
+    let mut docx = Docx::new().default_size(BODY_SIZE).add_style(
+        Style::new("Title", StyleType::Paragraph)
+            .name("Title")
+            .size(48)
+            .bold(),
+    );
+    for (level, size) in (1..=6).zip(HEADING_SIZES) {
+        docx = docx.add_style(
+            Style::new(format!("Heading{level}"), StyleType::Paragraph)
+                .name(format!("heading {level}"))
+                .size(size)
+                .bold(),
+        );
+    }
+    for (abstract_numbering, numbering) in writer.numberings {
+        docx = docx
+            .add_abstract_numbering(abstract_numbering)
+            .add_numbering(numbering);
+    }
+    for part in writer.parts {
+        docx = match part {
+            DocxPart::Paragraph(paragraph) => docx.add_paragraph(*paragraph),
+            DocxPart::Table(table) => docx.add_table(table),
+        };
     }
 
-    let table = Table::new(table_rows);
-    docx = docx.add_table(table);
+    let page_number = Run::new()
+        .size(18)
+        .add_field_char(FieldCharType::Begin, false)
+        .add_instr_text(InstrText::PAGE(InstrPAGE::new()))
+        .add_field_char(FieldCharType::Separate, false)
+        .add_text("1")
+        .add_field_char(FieldCharType::End, false);
+    docx = docx.footer(
+        Footer::new().add_paragraph(
+            Paragraph::new()
+                .align(AlignmentType::Center)
+                .add_run(page_number),
+        ),
+    );
 
     let mut buf = Cursor::new(Vec::new());
     docx.build()
@@ -87,9 +102,562 @@ pub fn generate_docx_table(title: &str, headers: &[&str], rows: &[Vec<String>])
     Ok(buf.into_inner())
 }
 
+/// Body text size, in half-points.
+const BODY_SIZE: usize = 22;
+/// "Heading 1" to "Heading 6" sizes, in half-points.
+const HEADING_SIZES: [usize; 6] = [40, 32, 28, 24, 22, 22];
+const CODE_SIZE: usize = 19;
+const MONOSPACE: &str = "Courier New";
+const LINK_COLOR: &str = "0563C1";
+/// Widest an image may be, in pixels at 96 dpi: the text column of a
+/// default page.
+const MAX_IMAGE_WIDTH: f64 = 576.0;
+const EMU_PER_PIXEL: f64 = 9525.0;
+
+/// Body content in order. Paragraphs are boxed: a `Paragraph` is several
+/// times the size of a `Table`.
+enum DocxPart {
+    Paragraph(Box<Paragraph>),
+    Table(Table),
+}
+
+/// Where a block sits: how deeply nested in lists, and whether in a quote.
+#[derive(Clone, Copy, Default)]
+struct Scope {
+    list_depth: usize,
+    quote: bool,
+}
+
+#[derive(Default)]
+struct DocxWriter {
+    parts: Vec<DocxPart>,
+    numberings: Vec<(AbstractNumbering, Numbering)>,
+}
+
+impl DocxWriter {
+    fn blocks(&mut self, nodes: &[Node], scope: Scope) {
+        for node in nodes {
+            self.block(node, scope);
+        }
+    }
+
+    fn block(&mut self, node: &Node, scope: Scope) {
+        match node {
+            Node::Heading { level, content } => {
+                let style = format!("Heading{}", (*level).clamp(1, 6));
+                let paragraph = inline_paragraph(Paragraph::new().style(&style), content, scope);
+                self.parts.push(DocxPart::Paragraph(Box::new(paragraph)));
+            }
+            Node::Paragraph { content } => {
+                let paragraph = inline_paragraph(base_paragraph(scope), content, scope);
+                self.parts.push(DocxPart::Paragraph(Box::new(paragraph)));
+            }
+            Node::List { start, items } => self.list(*start, items, scope),
+            Node::CodeBlock { code, .. } => {
+                let code = code.strip_suffix('\n').unwrap_or(code);
+                for line in code.split('\n') {
+                    let mut paragraph = base_paragraph(scope);
+                    if !line.is_empty() {
+                        let run = Run::new()
+                            .add_text(line.replace('\t', "    "))
+                            .size(CODE_SIZE)
+                            .fonts(RunFonts::new().ascii(MONOSPACE).hi_ansi(MONOSPACE));
+                        paragraph = paragraph.add_run(run);
+                    }
+                    self.parts.push(DocxPart::Paragraph(Box::new(paragraph)));
+                }
+            }
+            Node::Quote { blocks } => self.blocks(
+                blocks,
+                Scope {
+                    quote: true,
+                    ..scope
+                },
+            ),
+            Node::Table {
+                header,
+                rows,
+                alignments,
+            } => {
+                let align = |i: usize| alignments.get(i).copied().unwrap_or_default();
+                let row = |cells: &[Cell], bold: bool| {
+                    let cells = cells
+                        .iter()
+                        .enumerate()
+                        .map(|(i, cell)| {
+                            let mut paragraph = Paragraph::new();
+                            if let Some(alignment) = alignment_type(align(i)) {
+                                paragraph = paragraph.align(alignment);
+                            }
+                            let scope = Scope::default();
+                            let paragraph = if bold {
+                                let strong = [Inline::Strong(cell.clone())];
+                                inline_paragraph(paragraph, &strong, scope)
+                            } else {
+                                inline_paragraph(paragraph, cell, scope)
+                            };
+                            TableCell::new().add_paragraph(paragraph)
+                        })
+                        .collect();
+                    TableRow::new(cells)
+                };
+                let mut table_rows = vec![row(header, true)];
+                table_rows.extend(rows.iter().map(|cells| row(cells, false)));
+                self.parts.push(DocxPart::Table(Table::new(table_rows)));
+            }
+            Node::Rule => {
+                let paragraph = Paragraph::new()
+                    .align(AlignmentType::Center)
+                    .add_run(Run::new().add_text("* * *"));
+                self.parts.push(DocxPart::Paragraph(Box::new(paragraph)));
+            }
+        }
+    }
+
+    /// Each list gets its own numbering instance, so ordered lists count
+    /// from their own start.
+    fn list(&mut self, start: Option<u64>, items: &[ListItem], scope: Scope) {
+        let id = self.numberings.len() + 2;
+        let first = start.map_or(1, |start| start.min(u64::from(u32::MAX)) as usize);
+        let mut abstract_numbering = AbstractNumbering::new(id);
+        for level in 0..9 {
+            let (format, text) = match start {
+                Some(_) => ("decimal", format!("%{}.", level + 1)),
+                None => ("bullet", ["•", "◦", "▪"][level % 3].to_string()),
+            };
+            abstract_numbering = abstract_numbering.add_level(
+                Level::new(
+                    level,
+                    Start::new(if level == 0 { first } else { 1 }),
+                    NumberFormat::new(format),
+                    LevelText::new(text),
+                    LevelJc::new("left"),
+                )
+                .indent(
+                    Some(720 * (level as i32 + 1)),
+                    Some(SpecialIndentType::Hanging(360)),
+                    None,
+                    None,
+                ),
+            );
+        }
+        self.numberings
+            .push((abstract_numbering, Numbering::new(id, id)));
+
+        let depth = scope.list_depth.min(8);
+        let nested = Scope {
+            list_depth: scope.list_depth + 1,
+            ..scope
+        };
+        for item in items {
+            let mut numbered = false;
+            for node in &item.blocks {
+                match node {
+                    // The item's first paragraph carries the number; the
+                    // rest of the item follows without one.
+                    Node::Paragraph { content } if !numbered => {
+                        let mut content = content.clone();
+                        if let Some(checked) = item.checked {
+                            let mark = if checked { "☒ " } else { "☐ " };
+                            content.insert(0, Inline::Text(mark.to_string()));
+                        }
+                        let paragraph = base_paragraph(scope)
+                            .numbering(NumberingId::new(id), IndentLevel::new(depth));
+                        let paragraph = inline_paragraph(paragraph, &content, scope);
+                        self.parts.push(DocxPart::Paragraph(Box::new(paragraph)));
+                        numbered = true;
+                    }
+                    other => self.block(other, nested),
+                }
+            }
+        }
+    }
+}
+
+fn base_paragraph(scope: Scope) -> Paragraph {
+    if scope.quote {
+        Paragraph::new().indent(Some(720), None, None, None)
+    } else {
+        Paragraph::new()
+    }
+}
+
+/// Add inline content to a paragraph as runs, hyperlinks and pictures.
+fn inline_paragraph(mut paragraph: Paragraph, content: &[Inline], scope: Scope) -> Paragraph {
+    let spans = spans(content);
+    let mut i = 0;
+    while i < spans.len() {
+        match &spans[i] {
+            Span::Text {
+                link: Some(url), ..
+            } => {
+                // Consecutive spans with the same target share a hyperlink.
+                let mut hyperlink = Hyperlink::new(*url, HyperlinkType::External);
+                while let Some(Span::Text {
+                    text,
+                    style,
+                    link: Some(next),
+                }) = spans.get(i)
+                {
+                    if next != url {
+                        break;
+                    }
+                    let run = styled_run(text, *style, scope)
+                        .color(LINK_COLOR)
+                        .underline("single");
+                    hyperlink = hyperlink.add_run(run);
+                    i += 1;
+                }
+                paragraph = paragraph.add_hyperlink(hyperlink);
+                continue;
+            }
+            Span::Text { text, style, .. } => {
+                paragraph = paragraph.add_run(styled_run(text, *style, scope));
+            }
+            Span::Image(image) => {
+                paragraph = paragraph.add_run(match picture(image) {
+                    Some(pic) => Run::new().add_image(pic),
+                    // Nothing embeddable: show the alt text instead.
+                    None => Run::new()
+                        .add_text(format!("[{}]", image_label(image)))
+                        .italic(),
+                });
+            }
+            Span::LineBreak => {
+                paragraph = paragraph.add_run(Run::new().add_break(BreakType::TextWrapping));
+            }
+        }
+        i += 1;
+    }
+    paragraph
+}
+
+fn styled_run(text: &str, style: TextStyle, scope: Scope) -> Run {
+    let mut run = Run::new().add_text(text);
+    if style.bold {
+        run = run.bold();
+    }
+    if style.italic || scope.quote {
+        run = run.italic();
+    }
+    if style.strike {
+        run = run.strike();
+    }
+    if style.code {
+        run = run.fonts(RunFonts::new().ascii(MONOSPACE).hi_ansi(MONOSPACE));
+    }
+    if scope.quote {
+        run = run.color("595959");
+    }
+    run
+}
+
+/// An embedded picture at 96 dpi, shrunk to fit the text column.
+fn picture(image: &Image) -> Option<Pic> {
+    let data = image.data.as_ref()?;
+    let info = image_info(data)?;
+    let (width, height) = (f64::from(info.width), f64::from(info.height));
+    let scale = (MAX_IMAGE_WIDTH / width).min(1.0);
+    let emu = |pixels: f64| (pixels * scale * EMU_PER_PIXEL).round() as u32;
+    Some(
+        Pic::new_with_dimensions(data.clone(), info.width, info.height)
+            .size(emu(width), emu(height)),
+    )
+}
+
+fn image_label(image: &Image) -> &str {
+    if image.alt.is_empty() {
+        &image.src
+    } else {
+        &image.alt
+    }
+}
+
+fn alignment_type(alignment: Alignment) -> Option<AlignmentType> {
+    match alignment {
+        Alignment::None => None,
+        Alignment::Left => Some(AlignmentType::Left),
+        Alignment::Center => Some(AlignmentType::Center),
+        Alignment::Right => Some(AlignmentType::Right),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Extraction
+// ---------------------------------------------------------------------------
+
+/// Extract headings, paragraphs, lists and tables from a DOCX file.
+///
+/// Heading levels come from paragraph styles ("Heading 1", "Title", …),
+/// falling back to large bold runs for documents styled by hand. When the
+/// file records page breaks — explicit ones, or the markers Word saves at
+/// each rendered page boundary — sections are split and anchored per page.
+pub fn extract_docx(bytes: &[u8]) -> Result<ExtractedDocument> {
+    let mut package = Package::open(bytes)?;
+    let main = package.main_part()?;
+    let rels = package.relationships(&main)?;
+    let xml = package
+        .read(&main)?
+        .with_context(|| format!("Missing document part {main}"))?;
+
+    let heading_styles = match find_relationship(&rels, "styles") {
+        Some(styles) => match package.read(&styles.target)? {
+            Some(styles_xml) => heading_styles(&styles_xml)?,
+            None => HashMap::new(),
+        },
+        None => HashMap::new(),
+    };
+
+    let doc = parse_xml(&xml)?;
+    let body = doc
+        .descendants()
+        .find(|n| is(*n, "body"))
+        .context("Document has no body")?;
+
+    let mut walker = DocxWalker {
+        heading_styles,
+        pages: vec![Section::new(Some(Anchor::Page { number: 1 }))],
+        saw_page_break: false,
+        in_list: false,
+    };
+    walker.walk(body);
+
+    let mut document = ExtractedDocument::new(DocumentFormat::Docx);
+    let paginated = walker.saw_page_break;
+    for mut page in walker.pages {
+        if page.blocks.is_empty() {
+            continue;
+        }
+        if !paginated {
+            page.anchor = None;
+        }
+        document.sections.push(page);
+    }
+    Ok(document)
+}
+
+/// Map style IDs to heading levels using the style names in `styles.xml`.
+fn heading_styles(xml: &str) -> Result<HashMap<String, u8>> {
+    let doc = parse_xml(xml)?;
+    let mut levels = HashMap::new();
+    for style in doc.descendants().filter(|n| is(*n, "style")) {
+        let (Some(id), Some(name)) = (
+            attr(style, "styleId"),
+            child(style, "name").and_then(|n| attr(n, "val")),
+        ) else {
+            continue;
+        };
+        if let Some(level) = heading_level_for_name(name) {
+            levels.insert(id.to_string(), level);
+        }
+    }
+    Ok(levels)
+}
+
+fn heading_level_for_name(name: &str) -> Option<u8> {
+    let name = name.to_ascii_lowercase();
+    match name.as_str() {
+        "title" => Some(1),
+        "subtitle" => Some(2),
+        _ => name
+            .strip_prefix("heading")
+            .map(str::trim)
+            .and_then(|level| level.parse::<u8>().ok())
+            .map(|level| level.clamp(1, 6)),
+    }
+}
+
+struct DocxWalker {
+    heading_styles: HashMap<String, u8>,
+    pages: Vec<Section>,
+    saw_page_break: bool,
+    /// Whether the last block pushed was a list paragraph.
+    in_list: bool,
+}
+
+impl DocxWalker {
+    fn walk(&mut self, node: roxmltree::Node<'_, '_>) {
+        for child in node.children().filter(|n| n.is_element()) {
+            match child.tag_name().name() {
+                "p" => self.paragraph(child),
+                "tbl" => {
+                    let rows = table_rows(child);
+                    if !rows.is_empty() {
+                        self.push(Block::Table { rows });
+                    }
+                }
+                "sdt" | "sdtContent" | "customXml" | "smartTag" => self.walk(child),
+                _ => {}
+            }
+        }
+    }
+
+    fn paragraph(&mut self, p: roxmltree::Node<'_, '_>) {
+        let props = child(p, "pPr");
+        if props.is_some_and(|props| child(props, "pageBreakBefore").is_some_and(is_on)) {
+            self.new_page();
+        }
+
+        // Text between page breaks; every segment after the first starts a page.
+        let mut segments = vec![String::new()];
+        for node in p.descendants().filter(|n| n.is_element()) {
+            let text = segments.last_mut().expect("segments is never empty");
+            match node.tag_name().name() {
+                "t" => text.push_str(node.text().unwrap_or_default()),
+                "tab" if !node.ancestors().any(|a| is(a, "pPr")) => text.push('\t'),
+                "br" | "cr" if attr(node, "type") == Some("page") => segments.push(String::new()),
+                "br" | "cr" => text.push('\n'),
+                "lastRenderedPageBreak" => segments.push(String::new()),
+                _ => {}
+            }
+        }
+
+        let style_level = props
+            .and_then(|props| child(props, "pStyle"))
+            .and_then(|style| attr(style, "val"))
+            .and_then(|id| {
+                self.heading_styles
+                    .get(id)
+                    .copied()
+                    .or_else(|| heading_level_for_name(id))
+            });
+        let outline_level = props
+            .and_then(|props| child(props, "outlineLvl"))
+            .and_then(|lvl| attr(lvl, "val"))
+            .and_then(|lvl| lvl.parse::<u8>().ok())
+            .filter(|&lvl| lvl < 9)
+            .map(|lvl| (lvl + 1).min(6));
+        let list_depth = props.and_then(|props| child(props, "numPr")).map(|num| {
+            child(num, "ilvl")
+                .and_then(|lvl| attr(lvl, "val"))
+                .and_then(|lvl| lvl.parse::<usize>().ok())
+                .unwrap_or(0)
+        });
+
+        for (i, segment) in segments.into_iter().enumerate() {
+            if i > 0 {
+                self.new_page();
+            }
+            let text = segment.trim();
+            if text.is_empty() {
+                continue;
+            }
+            let level = style_level
+                .or(outline_level)
+                .or_else(|| run_heading_level(p, text));
+            match (level, list_depth) {
+                (Some(level), _) => self.push(Block::Heading {
+                    level,
+                    text: text.to_string(),
+                }),
+                (None, Some(depth)) => self.push_list_item(depth, text),
+                (None, None) => self.push(Block::Paragraph {
+                    text: text.to_string(),
+                }),
+            }
+        }
+    }
+
+    fn push(&mut self, block: Block) {
+        self.in_list = false;
+        self.current().blocks.push(block);
+    }
+
+    /// Consecutive list items share one paragraph block.
+    fn push_list_item(&mut self, depth: usize, text: &str) {
+        let item = format!("{}- {text}", "  ".repeat(depth));
+        let in_list = self.in_list;
+        let blocks = &mut self.current().blocks;
+        match blocks.last_mut() {
+            Some(Block::Paragraph { text }) if in_list => {
+                text.push('\n');
+                text.push_str(&item);
+            }
+            _ => blocks.push(Block::Paragraph { text: item }),
+        }
+        self.in_list = true;
+    }
+
+    fn new_page(&mut self) {
+        self.saw_page_break = true;
+        self.in_list = false;
+        // A break before any content (e.g. at the top of the document)
+        // doesn't open a page of its own.
+        if self.current().blocks.is_empty() {
+            return;
+        }
+        let number = self.pages.len() + 1;
+        self.pages.push(Section::new(Some(Anchor::Page { number })));
+    }
+
+    fn current(&mut self) -> &mut Section {
+        self.pages.last_mut().expect("pages is never empty")
+    }
+}
+
+/// Heading level for a paragraph formatted by hand: every run bold and set
+/// at 14pt or more (sizes are in half-points).
+fn run_heading_level(p: roxmltree::Node<'_, '_>, text: &str) -> Option<u8> {
+    if text.chars().count() > 200 {
+        return None;
+    }
+    let mut min_size: Option<u32> = None;
+    for run in p.descendants().filter(|n| is(*n, "r")) {
+        if !run.descendants().any(|n| is(n, "t")) {
+            continue;
+        }
+        let props = child(run, "rPr")?;
+        if !child(props, "b").is_some_and(is_on) {
+            return None;
+        }
+        let size = child(props, "sz")
+            .and_then(|sz| attr(sz, "val"))
+            .and_then(|sz| sz.parse::<u32>().ok())?;
+        min_size = Some(min_size.map_or(size, |min| min.min(size)));
+    }
+    match min_size? {
+        36.. => Some(1),
+        28.. => Some(2),
+        _ => None,
+    }
+}
+
+/// A toggle property (`<w:b/>`, `<w:pageBreakBefore w:val="1"/>`) is on
+/// unless its value says otherwise.
+fn is_on(node: roxmltree::Node<'_, '_>) -> bool {
+    !matches!(attr(node, "val"), Some("0" | "false" | "off"))
+}
+
+fn table_rows(table: roxmltree::Node<'_, '_>) -> Vec<Vec<String>> {
+    table
+        .children()
+        .filter(|n| is(*n, "tr"))
+        .map(|row| {
+            row.children()
+                .filter(|n| is(*n, "tc"))
+                .map(|cell| {
+                    cell.descendants()
+                        .filter(|n| is(*n, "p"))
+                        .map(|p| {
+                            p.descendants()
+                                .filter(|n| is(*n, "t"))
+                                .filter_map(|t| t.text())
+                                .collect::<String>()
+                        })
+                        .filter(|text| !text.trim().is_empty())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect::<Vec<String>>()
+        })
+        .filter(|row| !row.is_empty())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::markdown::parse_markdown;
 
     #[test]
     fn test_generate_docx_document_basic() {
@@ -169,4 +737,171 @@ mod tests {
         let bytes = generate_docx_table("Wide Table", headers, &rows).unwrap();
         assert_eq!(&bytes[0..2], b"PK");
     }
+
+    #[test]
+    fn test_extract_docx_roundtrip() {
+        let sections = vec![("Overview", "First line.\nSecond line.")];
+        let bytes = generate_docx_document("Quarterly Report", &sections).unwrap();
+        let doc = extract_docx(&bytes).unwrap();
+
+        assert_eq!(doc.sections.len(), 1);
+        assert_eq!(doc.sections[0].anchor, None);
+        assert_eq!(
+            doc.sections[0].blocks,
+            vec![
+                Block::Heading {
+                    level: 1,
+                    text: "Quarterly Report".into()
+                },
+                Block::Heading {
+                    level: 2,
+                    text: "Overview".into()
+                },
+                Block::Paragraph {
+                    text: "First line.".into()
+                },
+                Block::Paragraph {
+                    text: "Second line.".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_docx_from_markdown_roundtrip() {
+        let document = parse_markdown(
+            "# Plan\n\nSee the [site](https://example.com) for **details**.\n\n\
+             ## Steps\n\n1. Design\n2. Build\n   - Review\n\n> Quoted\n\n\
+             | Name | Age |\n| --- | ---: |\n| Alice | 30 |\n",
+        );
+        let bytes = render_docx(&document).unwrap();
+        let doc = extract_docx(&bytes).unwrap();
+
+        assert_eq!(
+            doc.sections[0].blocks,
+            vec![
+                Block::Heading {
+                    level: 1,
+                    text: "Plan".into()
+                },
+                Block::Paragraph {
+                    text: "See the site for details.".into()
+                },
+                Block::Heading {
+                    level: 2,
+                    text: "Steps".into()
+                },
+                Block::Paragraph {
+                    text: "- Design\n- Build\n  - Review".into()
+                },
+                Block::Paragraph {
+                    text: "Quoted".into()
+                },
+                Block::Table {
+                    rows: vec![
+                        vec!["Name".into(), "Age".into()],
+                        vec!["Alice".into(), "30".into()],
+                    ]
+                },
+            ]
+        );
+
+        let mut package = Package::open(&bytes).unwrap();
+        let xml = package.read("word/document.xml").unwrap().unwrap();
+        assert!(xml.contains("w:hyperlink"));
+        assert!(xml.contains("w:numPr"));
+    }
+
+    #[test]
+    fn test_extract_docx_table() {
+        let rows = vec![vec!["Alice".into(), "30".into()]];
+        let bytes = generate_docx_table("People", &["Name", "Age"], &rows).unwrap();
+        let doc = extract_docx(&bytes).unwrap();
+
+        assert!(doc.sections[0].blocks.contains(&Block::Table {
+            rows: vec![
+                vec!["Name".into(), "Age".into()],
+                vec!["Alice".into(), "30".into()],
+            ]
+        }));
+        assert!(
+            doc.to_markdown()
+                .contains("| Name | Age |\n| --- | --- |\n| Alice | 30 |")
+        );
+    }
+
+    #[test]
+    fn test_extract_docx_styles_lists_and_pages() {
+        let mut buf = Cursor::new(Vec::new());
+        Docx::new()
+            .add_style(Style::new("Heading1", StyleType::Paragraph).name("Heading 1"))
+            .add_style(Style::new("Heading2", StyleType::Paragraph).name("heading 2"))
+            .add_paragraph(
+                Paragraph::new()
+                    .style("Heading1")
+                    .add_run(Run::new().add_text("Plan")),
+            )
+            .add_paragraph(
+                Paragraph::new()
+                    .numbering(NumberingId::new(1), IndentLevel::new(0))
+                    .add_run(Run::new().add_text("Design")),
+            )
+            .add_paragraph(
+                Paragraph::new()
+                    .numbering(NumberingId::new(1), IndentLevel::new(1))
+                    .add_run(Run::new().add_text("Review")),
+            )
+            .add_paragraph(
+                Paragraph::new()
+                    .add_run(Run::new().add_text("Before"))
+                    .add_run(Run::new().add_break(BreakType::Page))
+                    .add_run(Run::new().add_text("After")),
+            )
+            .add_paragraph(
+                Paragraph::new()
+                    .style("Heading2")
+                    .add_run(Run::new().add_text("Appendix")),
+            )
+            .build()
+            .pack(&mut buf)
+            .unwrap();
+
+        let doc = extract_docx(&buf.into_inner()).unwrap();
+        assert_eq!(doc.sections.len(), 2);
+        assert_eq!(doc.sections[0].anchor, Some(Anchor::Page { number: 1 }));
+        assert_eq!(
+            doc.sections[0].blocks,
+            vec![
+                Block::Heading {
+                    level: 1,
+                    text: "Plan".into()
+                },
+                Block::Paragraph {
+                    text: "- Design\n  - Review".into()
+                },
+                Block::Paragraph {
+                    text: "Before".into()
+                },
+            ]
+        );
+        assert_eq!(doc.sections[1].anchor, Some(Anchor::Page { number: 2 }));
+        assert_eq!(
+            doc.sections[1].blocks,
+            vec![
+                Block::Paragraph {
+                    text: "After".into()
+                },
+                Block::Heading {
+                    level: 2,
+                    text: "Appendix".into()
+                },
+            ]
+        );
+        assert!(doc.to_markdown().starts_with("[page 1]\n\n# Plan"));
+    }
+
+    #[test]
+    fn test_extract_docx_rejects_non_zip() {
+        assert!(extract_docx(b"not a docx").is_err());
+    }
 }
@@ -0,0 +1,883 @@
+//! Text extraction — read documents back into structured text.
+//!
+//! The generators in this crate only write files; the extractors here go the
+//! other way. PDF, DOCX, XLSX and PPTX (from any producer, not just ours),
+//! plus CSV, HTML, Markdown and plain text, become an [`ExtractedDocument`]:
+//! sections of headings, paragraphs, tables and speaker notes. Every section
+//! keeps an [`Anchor`] to where it came from — "page 4", "slide 2",
+//! "Sheet2!B3:F20" — so chunks built from it can cite their source.
+
+use std::fmt;
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::markdown::generate_markdown_table;
+
+/// Largest archive member the OOXML readers will inflate (64 MiB).
+const MAX_PART_SIZE: u64 = 64 * 1024 * 1024;
+
+// ---------------------------------------------------------------------------
+// Document model
+// ---------------------------------------------------------------------------
+
+/// File formats the extractors understand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DocumentFormat {
+    Pdf,
+    Docx,
+    Xlsx,
+    Pptx,
+    Csv,
+    Html,
+    Markdown,
+    Text,
+}
+
+impl DocumentFormat {
+    /// Format for a file extension (case-insensitive, without the dot).
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "pdf" => Some(Self::Pdf),
+            "docx" | "docm" => Some(Self::Docx),
+            "xlsx" | "xlsm" => Some(Self::Xlsx),
+            "pptx" | "pptm" => Some(Self::Pptx),
+            "csv" => Some(Self::Csv),
+            "html" | "htm" => Some(Self::Html),
+            "md" | "markdown" => Some(Self::Markdown),
+            "txt" | "text" => Some(Self::Text),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Self::from_extension)
+    }
+
+    /// Whether files of this format have to be extracted to be read as text.
+    pub fn is_binary(self) -> bool {
+        matches!(self, Self::Pdf | Self::Docx | Self::Xlsx | Self::Pptx)
+    }
+}
+
+/// Where a section sits in its source document.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Anchor {
+    /// 1-based page number.
+    Page { number: usize },
+    /// 1-based slide number.
+    Slide { number: usize },
+    /// A cell range on a named sheet, e.g. `B3:F20`.
+    Range { sheet: String, range: String },
+}
+
+impl fmt::Display for Anchor {
+    /// Renders as "page 4", "slide 2" or "Sheet2!B3:F20"; sheet names that
+    /// are not plain identifiers are quoted the way spreadsheets do.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Anchor::Page { number } => write!(f, "page {number}"),
+            Anchor::Slide { number } => write!(f, "slide {number}"),
+            Anchor::Range { sheet, range } => {
+                let plain = !sheet.is_empty()
+                    && sheet
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || c == '_' || c == '.');
+                if plain {
+                    write!(f, "{sheet}!{range}")
+                } else {
+                    write!(f, "'{}'!{range}", sheet.replace('\'', "''"))
+                }
+            }
+        }
+    }
+}
+
+/// A unit of extracted content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Block {
+    Heading {
+        level: u8,
+        text: String,
+    },
+    Paragraph {
+        text: String,
+    },
+    /// Rows of cells; the first row is the header.
+    Table {
+        rows: Vec<Vec<String>>,
+    },
+    /// Speaker notes attached to a slide.
+    Notes {
+        text: String,
+    },
+}
+
+impl Block {
+    pub fn to_markdown(&self) -> String {
+        match self {
+            Block::Heading { level, text } => {
+                format!("{} {text}", "#".repeat((*level).clamp(1, 6) as usize))
+            }
+            Block::Paragraph { text } => text.clone(),
+            Block::Table { rows } => markdown_table(rows),
+            Block::Notes { text } => {
+                let mut quoted = String::from("> Notes:");
+                for line in text.lines() {
+                    quoted.push_str("\n> ");
+                    quoted.push_str(line);
+                }
+                quoted
+            }
+        }
+    }
+}
+
+/// A page, slide or sheet range and the content found there.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Section {
+    pub anchor: Option<Anchor>,
+    /// Slide title or sheet name.
+    pub title: Option<String>,
+    pub blocks: Vec<Block>,
+}
+
+impl Section {
+    pub fn new(anchor: Option<Anchor>) -> Self {
+        Self {
+            anchor,
+            title: None,
+            blocks: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.blocks.is_empty()
+    }
+
+    /// Markdown for the section, led by its anchor in brackets (`[page 4]`)
+    /// so the location survives chunking.
+    pub fn to_markdown(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(anchor) = &self.anchor {
+            parts.push(format!("[{anchor}]"));
+        }
+        if let Some(title) = &self.title {
+            parts.push(format!("## {title}"));
+        }
+        parts.extend(self.blocks.iter().map(Block::to_markdown));
+        parts.join("\n\n")
+    }
+}
+
+/// The structured text of a document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtractedDocument {
+    pub format: DocumentFormat,
+    pub sections: Vec<Section>,
+}
+
+impl ExtractedDocument {
+    pub fn new(format: DocumentFormat) -> Self {
+        Self {
+            format,
+            sections: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sections.iter().all(|s| s.blocks.is_empty())
+    }
+
+    pub fn to_markdown(&self) -> String {
+        self.sections
+            .iter()
+            .filter(|s| !s.is_empty())
+            .map(Section::to_markdown)
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Entry points
+// ---------------------------------------------------------------------------
+
+/// Extract structured text from the raw bytes of a document.
+pub fn extract(bytes: &[u8], format: DocumentFormat) -> Result<ExtractedDocument> {
+    match format {
+        DocumentFormat::Pdf => crate::pdf::extract_pdf(bytes),
+        DocumentFormat::Docx => crate::docx::extract_docx(bytes),
+        DocumentFormat::Xlsx => crate::xlsx::extract_xlsx(bytes),
+        DocumentFormat::Pptx => crate::pptx::extract_pptx(bytes),
+        DocumentFormat::Csv => extract_csv(&String::from_utf8_lossy(bytes)),
+        DocumentFormat::Html => Ok(extract_html(&String::from_utf8_lossy(bytes))),
+        DocumentFormat::Markdown => Ok(extract_markdown(&String::from_utf8_lossy(bytes))),
+        DocumentFormat::Text => Ok(extract_text(&String::from_utf8_lossy(bytes))),
+    }
+}
+
+/// Extract structured text from a file, picking the format by extension.
+pub fn extract_file(path: &Path) -> Result<ExtractedDocument> {
+    let format = DocumentFormat::from_path(path)
+        .with_context(|| format!("Unsupported document type: {}", path.display()))?;
+    let bytes =
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    extract(&bytes, format).with_context(|| format!("Failed to extract {}", path.display()))
+}
+
+/// Render a file as a chat attachment: a `### name` header followed by its
+/// extracted Markdown, truncated to roughly `max_chars`.
+///
+/// Files with unknown extensions are accepted when they are valid UTF-8.
+pub fn attachment_markdown(path: &Path, max_chars: usize) -> Result<String> {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string());
+
+    let body = match DocumentFormat::from_path(path) {
+        Some(_) => extract_file(path)?.to_markdown(),
+        None => {
+            let bytes = std::fs::read(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            String::from_utf8(bytes)
+                .with_context(|| format!("{name} is not a supported document or text file"))?
+        }
+    };
+
+    let mut body = body.trim().to_string();
+    if body.chars().count() > max_chars {
+        body = body.chars().take(max_chars).collect();
+        body.push_str("\n\n[… truncated]");
+    }
+    Ok(format!("### {name}\n\n{body}"))
+}
+
+/// Render rows as a Markdown table, padding ragged rows to the widest one.
+pub fn markdown_table(rows: &[Vec<String>]) -> String {
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let Some((header, body)) = rows.split_first() else {
+        return String::new();
+    };
+    let clean = |cell: &str| cell.replace(['\r', '\n'], " ");
+    let pad = |row: &Vec<String>| {
+        let mut cells: Vec<String> = row.iter().map(|c| clean(c)).collect();
+        cells.resize(width, String::new());
+        cells
+    };
+
+    let headers = pad(header);
+    let headers: Vec<&str> = headers.iter().map(String::as_str).collect();
+    let body: Vec<Vec<String>> = body.iter().map(pad).collect();
+    generate_markdown_table(&headers, &body)
+}
+
+// ---------------------------------------------------------------------------
+// Text formats
+// ---------------------------------------------------------------------------
+
+fn extract_csv(text: &str) -> Result<ExtractedDocument> {
+    let mut reader = ::csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.context("Failed to parse CSV record")?;
+        rows.push(record.iter().map(str::to_string).collect::<Vec<_>>());
+    }
+
+    let mut document = ExtractedDocument::new(DocumentFormat::Csv);
+    if !rows.is_empty() {
+        let mut section = Section::new(None);
+        section.blocks.push(Block::Table { rows });
+        document.sections.push(section);
+    }
+    Ok(document)
+}
+
+fn extract_markdown(text: &str) -> ExtractedDocument {
+    let mut section = Section::new(None);
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut in_fence = false;
+
+    let flush = |paragraph: &mut Vec<&str>, section: &mut Section| {
+        if !paragraph.is_empty() {
+            section.blocks.push(Block::Paragraph {
+                text: paragraph.join("\n"),
+            });
+            paragraph.clear();
+        }
+    };
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            paragraph.push(line);
+            continue;
+        }
+        if in_fence {
+            paragraph.push(line);
+            continue;
+        }
+        let hashes = trimmed.chars().take_while(|&c| c == '#').count();
+        if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+            flush(&mut paragraph, &mut section);
+            section.blocks.push(Block::Heading {
+                level: hashes as u8,
+                text: trimmed[hashes..]
+                    .trim()
+                    .trim_end_matches('#')
+                    .trim()
+                    .to_string(),
+            });
+        } else if trimmed.is_empty() {
+            flush(&mut paragraph, &mut section);
+        } else {
+            paragraph.push(line);
+        }
+    }
+    flush(&mut paragraph, &mut section);
+
+    single_section(DocumentFormat::Markdown, section)
+}
+
+fn extract_text(text: &str) -> ExtractedDocument {
+    let mut section = Section::new(None);
+    section.blocks = text
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| Block::Paragraph {
+            text: p.replace("\r\n", "\n"),
+        })
+        .collect();
+    single_section(DocumentFormat::Text, section)
+}
+
+/// Pull headings, paragraphs, list items and tables out of HTML.
+///
+/// A forgiving tag scanner rather than a full parser: scripts and styles
+/// are dropped, and any markup it does not recognise is treated as inline.
+fn extract_html(html: &str) -> ExtractedDocument {
+    let mut section = Section::new(None);
+    let mut text = String::new();
+    let mut heading: Option<u8> = None;
+    let mut table: Option<Vec<Vec<String>>> = None;
+    let mut rest = html;
+
+    while let Some(open) = rest.find('<') {
+        text.push_str(&decode_entities(&rest[..open]));
+        rest = &rest[open..];
+        let Some(close) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[1..close];
+        rest = &rest[close + 1..];
+
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        match name.as_str() {
+            "script" | "style" if !closing => {
+                let end = format!("</{name}");
+                let lower = rest.to_ascii_lowercase();
+                rest = match lower.find(&end) {
+                    Some(at) => &rest[at..],
+                    None => "",
+                };
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                flush_html_text(&mut text, heading.take(), &mut section);
+                if !closing {
+                    heading = name[1..].parse().ok();
+                }
+            }
+            "table" => {
+                flush_html_text(&mut text, heading.take(), &mut section);
+                if closing {
+                    if let Some(rows) = table.take().filter(|rows| !rows.is_empty()) {
+                        section.blocks.push(Block::Table { rows });
+                    }
+                } else {
+                    table = Some(Vec::new());
+                }
+            }
+            "tr" if !closing => {
+                if let Some(rows) = table.as_mut() {
+                    rows.push(Vec::new());
+                }
+            }
+            "td" | "th" if closing => {
+                if let Some(row) = table.as_mut().and_then(|rows| rows.last_mut()) {
+                    row.push(collapse_whitespace(&text));
+                    text.clear();
+                }
+            }
+            "li" if !closing => {
+                flush_html_text(&mut text, heading.take(), &mut section);
+                text.push_str("- ");
+            }
+            "br" => text.push('\n'),
+            "p" | "div" | "li" | "ul" | "ol" | "pre" | "blockquote" | "section" | "article"
+            | "header" | "footer" | "title" | "body"
+                if table.is_none() =>
+            {
+                flush_html_text(&mut text, heading.take(), &mut section);
+            }
+            _ => {}
+        }
+    }
+    text.push_str(&decode_entities(rest));
+    flush_html_text(&mut text, heading, &mut section);
+
+    // Merge consecutive list items into a single paragraph.
+    let mut blocks: Vec<Block> = Vec::new();
+    for block in section.blocks {
+        if let (Some(Block::Paragraph { text: prev }), Block::Paragraph { text }) =
+            (blocks.last_mut(), &block)
+            && prev.starts_with("- ")
+            && text.starts_with("- ")
+        {
+            prev.push('\n');
+            prev.push_str(text);
+            continue;
+        }
+        blocks.push(block);
+    }
+    section.blocks = blocks;
+
+    single_section(DocumentFormat::Html, section)
+}
+
+fn flush_html_text(text: &mut String, heading: Option<u8>, section: &mut Section) {
+    let collapsed = text
+        .split('\n')
+        .map(collapse_whitespace)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    text.clear();
+    if collapsed.is_empty() || collapsed == "-" {
+        return;
+    }
+    section.blocks.push(match heading {
+        Some(level) => Block::Heading {
+            level,
+            text: collapsed,
+        },
+        None => Block::Paragraph { text: collapsed },
+    });
+}
+
+fn collapse_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn decode_entities(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(at) = rest.find('&') {
+        out.push_str(&rest[..at]);
+        rest = &rest[at..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .map(|end| &rest[1..end + 1]);
+        let decoded = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => {
+                let code = entity.strip_prefix('#')?;
+                let value = match code.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => code.parse().ok()?,
+                };
+                char::from_u32(value)
+            }
+        });
+        match (entity, decoded) {
+            (Some(entity), Some(c)) => {
+                out.push(c);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn single_section(format: DocumentFormat, section: Section) -> ExtractedDocument {
+    let mut document = ExtractedDocument::new(format);
+    if !section.is_empty() {
+        document.sections.push(section);
+    }
+    document
+}
+
+// ---------------------------------------------------------------------------
+// OOXML package helpers (shared by the DOCX, XLSX and PPTX readers)
+// ---------------------------------------------------------------------------
+
+/// An opened OOXML zip package.
+pub(crate) struct Package<'a> {
+    archive: zip::ZipArchive<Cursor<&'a [u8]>>,
+}
+
+/// A resolved entry from a `.rels` part.
+pub(crate) struct Relationship {
+    pub id: String,
+    pub rel_type: String,
+    /// Absolute part name, without a leading slash.
+    pub target: String,
+}
+
+impl<'a> Package<'a> {
+    pub fn open(bytes: &'a [u8]) -> Result<Self> {
+        let archive =
+            zip::ZipArchive::new(Cursor::new(bytes)).context("Not a valid OOXML (zip) file")?;
+        Ok(Self { archive })
+    }
+
+    /// Read a part as text, or `None` when the package does not have it.
+    pub fn read(&mut self, name: &str) -> Result<Option<String>> {
+        let mut file = match self.archive.by_name(name) {
+            Ok(file) => file,
+            Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("Failed to open part {name}")),
+        };
+        if file.size() > MAX_PART_SIZE {
+            bail!(
+                "Part {name} is too large to extract ({} bytes)",
+                file.size()
+            );
+        }
+        let mut text = String::new();
+        file.by_ref()
+            .take(MAX_PART_SIZE)
+            .read_to_string(&mut text)
+            .with_context(|| format!("Failed to read part {name}"))?;
+        Ok(Some(text))
+    }
+
+    /// Relationships declared for `part` (empty when it has none).
+    pub fn relationships(&mut self, part: &str) -> Result<Vec<Relationship>> {
+        let (dir, file) = split_part(part);
+        let rels_name = if dir.is_empty() {
+            format!("_rels/{file}.rels")
+        } else {
+            format!("{dir}/_rels/{file}.rels")
+        };
+        let Some(xml) = self.read(&rels_name)? else {
+            return Ok(Vec::new());
+        };
+        let doc = parse_xml(&xml)?;
+        Ok(doc
+            .descendants()
+            .filter(|n| is(*n, "Relationship"))
+            .filter(|n| attr(*n, "TargetMode") != Some("External"))
+            .filter_map(|n| {
+                Some(Relationship {
+                    id: attr(n, "Id")?.to_string(),
+                    rel_type: attr(n, "Type")?.to_string(),
+                    target: resolve_target(dir, attr(n, "Target")?),
+                })
+            })
+            .collect())
+    }
+
+    /// The package's main part (`word/document.xml`, `xl/workbook.xml`, …).
+    pub fn main_part(&mut self) -> Result<String> {
+        self.relationships("")?
+            .into_iter()
+            .find(|r| r.rel_type.ends_with("/officeDocument"))
+            .map(|r| r.target)
+            .context("Package has no main document part")
+    }
+}
+
+/// Find the first relationship whose type ends with `/kind`.
+pub(crate) fn find_relationship<'r>(
+    rels: &'r [Relationship],
+    kind: &str,
+) -> Option<&'r Relationship> {
+    rels.iter()
+        .find(|r| r.rel_type.rsplit('/').next() == Some(kind))
+}
+
+fn split_part(part: &str) -> (&str, &str) {
+    let part = part.trim_start_matches('/');
+    match part.rfind('/') {
+        Some(at) => (&part[..at], &part[at + 1..]),
+        None => ("", part),
+    }
+}
+
+fn resolve_target(base_dir: &str, target: &str) -> String {
+    let mut segments: Vec<&str> = if target.starts_with('/') {
+        Vec::new()
+    } else {
+        base_dir.split('/').filter(|s| !s.is_empty()).collect()
+    };
+    for segment in target.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            other => segments.push(other),
+        }
+    }
+    segments.join("/")
+}
+
+pub(crate) fn parse_xml(xml: &str) -> Result<roxmltree::Document<'_>> {
+    roxmltree::Document::parse_with_options(
+        xml,
+        roxmltree::ParsingOptions {
+            allow_dtd: true,
+            ..Default::default()
+        },
+    )
+    .context("Malformed XML part")
+}
+
+/// Whether `node` is an element with the given local name.
+pub(crate) fn is(node: roxmltree::Node<'_, '_>, local: &str) -> bool {
+    node.is_element() && node.tag_name().name() == local
+}
+
+/// Attribute by local name, ignoring its namespace prefix.
+pub(crate) fn attr<'a>(node: roxmltree::Node<'a, '_>, local: &str) -> Option<&'a str> {
+    node.attributes()
+        .find(|a| a.name() == local)
+        .map(|a| a.value())
+}
+
+/// The `r:id` relationship reference on an element.
+pub(crate) fn rel_id<'a>(node: roxmltree::Node<'a, '_>) -> Option<&'a str> {
+    node.attributes()
+        .find(|a| {
+            a.name() == "id"
+                && a.namespace()
+                    .is_some_and(|ns| ns.ends_with("relationships"))
+        })
+        .map(|a| a.value())
+}
+
+pub(crate) fn child<'a, 'i>(
+    node: roxmltree::Node<'a, 'i>,
+    local: &str,
+) -> Option<roxmltree::Node<'a, 'i>> {
+    node.children().find(|n| is(*n, local))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            DocumentFormat::from_path(Path::new("Report.PDF")),
+            Some(DocumentFormat::Pdf)
+        );
+        assert_eq!(
+            DocumentFormat::from_path(Path::new("a/b/deck.pptx")),
+            Some(DocumentFormat::Pptx)
+        );
+        assert_eq!(DocumentFormat::from_path(Path::new("image.png")), None);
+        assert!(DocumentFormat::Xlsx.is_binary());
+        assert!(!DocumentFormat::Markdown.is_binary());
+    }
+
+    #[test]
+    fn test_anchor_display() {
+        assert_eq!(Anchor::Page { number: 4 }.to_string(), "page 4");
+        assert_eq!(Anchor::Slide { number: 2 }.to_string(), "slide 2");
+        let range = |sheet: &str| Anchor::Range {
+            sheet: sheet.into(),
+            range: "B3:F20".into(),
+        };
+        assert_eq!(range("Sheet2").to_string(), "Sheet2!B3:F20");
+        assert_eq!(range("Q1 Sales").to_string(), "'Q1 Sales'!B3:F20");
+        assert_eq!(range("Bob's").to_string(), "'Bob''s'!B3:F20");
+    }
+
+    #[test]
+    fn test_section_markdown_keeps_anchor() {
+        let section = Section {
+            anchor: Some(Anchor::Slide { number: 3 }),
+            title: Some("Roadmap".into()),
+            blocks: vec![
+                Block::Paragraph {
+                    text: "- Ship it".into(),
+                },
+                Block::Notes {
+                    text: "Mention dates".into(),
+                },
+            ],
+        };
+        assert_eq!(
+            section.to_markdown(),
+            "[slide 3]\n\n## Roadmap\n\n- Ship it\n\n> Notes:\n> Mention dates"
+        );
+    }
+
+    #[test]
+    fn test_markdown_table_pads_ragged_rows() {
+        let rows = vec![
+            vec!["a".to_string(), "b".to_string()],
+            vec!["1".to_string()],
+            vec!["x|y".to_string(), "two\nlines".to_string()],
+        ];
+        assert_eq!(
+            markdown_table(&rows),
+            "| a | b |\n| --- | --- |\n| 1 |  |\n| x\\|y | two lines |"
+        );
+    }
+
+    #[test]
+    fn test_extract_markdown_headings_and_code() {
+        let doc = extract(
+            b"# Title\n\nIntro line\nsecond line\n\n## Part ##\n\n```\n# not a heading\n```\n",
+            DocumentFormat::Markdown,
+        )
+        .unwrap();
+        let blocks = &doc.sections[0].blocks;
+        assert_eq!(
+            blocks[0],
+            Block::Heading {
+                level: 1,
+                text: "Title".into()
+            }
+        );
+        assert_eq!(
+            blocks[1],
+            Block::Paragraph {
+                text: "Intro line\nsecond line".into()
+            }
+        );
+        assert_eq!(
+            blocks[2],
+            Block::Heading {
+                level: 2,
+                text: "Part".into()
+            }
+        );
+        assert!(
+            matches!(&blocks[3], Block::Paragraph { text } if text.contains("# not a heading"))
+        );
+    }
+
+    #[test]
+    fn test_extract_html_structure() {
+        let html = crate::html::generate_html(
+            "Doc",
+            &format!(
+                "<h1>Hello &amp; welcome</h1><p>First <b>para</b>.</p>\
+                 <ul><li>one</li><li>two</li></ul>{}\
+                 <script>var x = '<p>hidden</p>';</script>",
+                crate::html::generate_html_table(
+                    &["Name", "Age"],
+                    &[vec!["Ann".into(), "7".into()]]
+                )
+            ),
+        );
+        let doc = extract(html.as_bytes(), DocumentFormat::Html).unwrap();
+        let blocks = &doc.sections[0].blocks;
+        assert!(blocks.contains(&Block::Heading {
+            level: 1,
+            text: "Hello & welcome".into()
+        }));
+        assert!(blocks.contains(&Block::Paragraph {
+            text: "First para.".into()
+        }));
+        assert!(blocks.contains(&Block::Paragraph {
+            text: "- one\n- two".into()
+        }));
+        assert!(blocks.contains(&Block::Table {
+            rows: vec![
+                vec!["Name".into(), "Age".into()],
+                vec!["Ann".into(), "7".into()]
+            ]
+        }));
+        assert!(!doc.to_markdown().contains("hidden"));
+    }
+
+    #[test]
+    fn test_extract_csv_flexible() {
+        let doc = extract(b"a,b\n1,2,3\n", DocumentFormat::Csv).unwrap();
+        assert_eq!(
+            doc.to_markdown(),
+            "| a | b |  |\n| --- | --- | --- |\n| 1 | 2 | 3 |"
+        );
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(
+            decode_entities("a &lt;b&gt; &#65;&#x42; & c"),
+            "a <b> AB & c"
+        );
+        assert_eq!(decode_entities("&unknown; &"), "&unknown; &");
+    }
+
+    #[test]
+    fn test_resolve_target() {
+        assert_eq!(
+            resolve_target("ppt/slides", "../notesSlides/n1.xml"),
+            "ppt/notesSlides/n1.xml"
+        );
+        assert_eq!(
+            resolve_target("xl", "worksheets/sheet1.xml"),
+            "xl/worksheets/sheet1.xml"
+        );
+        assert_eq!(
+            resolve_target("xl", "/xl/sharedStrings.xml"),
+            "xl/sharedStrings.xml"
+        );
+        assert_eq!(resolve_target("", "word/document.xml"), "word/document.xml");
+    }
+
+    #[test]
+    fn test_attachment_markdown_truncates() {
+        let dir = std::env::temp_dir().join(format!("hive_docs_attach_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("notes.log");
+        std::fs::write(&path, "x".repeat(50)).unwrap();
+
+        let text = attachment_markdown(&path, 10).unwrap();
+        assert!(text.starts_with("### notes.log\n\nxxxxxxxxxx\n"));
+        assert!(text.ends_with("[… truncated]"));
+
+        std::fs::write(&path, [0xff, 0xfe, 0x00]).unwrap();
+        assert!(attachment_markdown(&path, 10).is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
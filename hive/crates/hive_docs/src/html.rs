@@ -1,3 +1,5 @@
+use crate::document::{Alignment, Document, Inline, Node, base64_encode, image_info};
+
 /// Generate a complete HTML document with the given title and body HTML content.
 pub fn generate_html(title: &str, body_html: &str) -> String {
     format!(
@@ -13,6 +15,10 @@ pub fn generate_html(title: &str, body_html: &str) -> String {
         th, td {{ border: 1px solid #ddd; padding: 8px; text-align: left; }}
         th {{ background-color: #f4f4f4; font-weight: 600; }}
         tr:nth-child(even) {{ background-color: #fafafa; }}
+        pre {{ background-color: #f6f8fa; padding: 12px; overflow-x: auto; }}
+        code {{ font-family: "SFMono-Regular", Consolas, "Liberation Mono", monospace; font-size: 0.9em; }}
+        blockquote {{ border-left: 4px solid #ddd; margin-left: 0; padding-left: 1rem; color: #555; }}
+        img {{ max-width: 100%; }}
     </style>
 </head>
 <body>
@@ -47,7 +53,193 @@ pub fn generate_html_table(headers: &[&str], rows: &[Vec<String>]) -> String {
     html
 }
 
-fn escape_html(s: &str) -> String {
+/// Render a [`Document`] as a complete HTML page.
+///
+/// Images with loaded data are inlined as `data:` URIs so the page stands
+/// on its own; links with scripting schemes are rendered as plain text.
+pub fn render_html(document: &Document) -> String {
+    let mut body = String::new();
+    if let Some(title) = &document.title {
+        body.push_str(&format!("<h1>{}</h1>\n", escape_html(title)));
+    }
+    body.push_str(&render_html_blocks(&document.blocks));
+    let title = document
+        .display_title()
+        .unwrap_or_else(|| "Document".into());
+    generate_html(&title, &body)
+}
+
+/// Render blocks as an HTML fragment.
+pub fn render_html_blocks(blocks: &[Node]) -> String {
+    let mut html = String::new();
+    for node in blocks {
+        render_node(node, &mut html);
+    }
+    html
+}
+
+fn render_node(node: &Node, html: &mut String) {
+    match node {
+        Node::Heading { level, content } => {
+            let level = (*level).clamp(1, 6);
+            html.push_str(&format!(
+                "<h{level}>{}</h{level}>\n",
+                render_inlines(content)
+            ));
+        }
+        Node::Paragraph { content } => {
+            html.push_str(&format!("<p>{}</p>\n", render_inlines(content)));
+        }
+        Node::List { start, items } => {
+            match start {
+                Some(1) => html.push_str("<ol>\n"),
+                Some(start) => html.push_str(&format!("<ol start=\"{start}\">\n")),
+                None => html.push_str("<ul>\n"),
+            }
+            for item in items {
+                html.push_str("<li>");
+                if let Some(checked) = item.checked {
+                    let checked = if checked { " checked" } else { "" };
+                    html.push_str(&format!("<input type=\"checkbox\" disabled{checked}> "));
+                }
+                // A lone paragraph renders inline, as in a tight list.
+                match item.blocks.as_slice() {
+                    [Node::Paragraph { content }] => html.push_str(&render_inlines(content)),
+                    blocks => {
+                        html.push('\n');
+                        html.push_str(&render_html_blocks(blocks));
+                    }
+                }
+                html.push_str("</li>\n");
+            }
+            html.push_str(if start.is_some() {
+                "</ol>\n"
+            } else {
+                "</ul>\n"
+            });
+        }
+        Node::CodeBlock { language, code } => {
+            let class = language
+                .as_deref()
+                .map(|lang| format!(" class=\"language-{}\"", escape_html(lang)))
+                .unwrap_or_default();
+            html.push_str(&format!(
+                "<pre><code{class}>{}</code></pre>\n",
+                escape_html(code)
+            ));
+        }
+        Node::Quote { blocks } => {
+            html.push_str("<blockquote>\n");
+            html.push_str(&render_html_blocks(blocks));
+            html.push_str("</blockquote>\n");
+        }
+        Node::Table {
+            header,
+            rows,
+            alignments,
+        } => {
+            let align = |i: usize| match alignments.get(i).copied().unwrap_or_default() {
+                Alignment::None => "",
+                Alignment::Left => " style=\"text-align: left\"",
+                Alignment::Center => " style=\"text-align: center\"",
+                Alignment::Right => " style=\"text-align: right\"",
+            };
+            html.push_str("<table>\n<thead>\n<tr>\n");
+            for (i, cell) in header.iter().enumerate() {
+                html.push_str(&format!(
+                    "    <th{}>{}</th>\n",
+                    align(i),
+                    render_inlines(cell)
+                ));
+            }
+            html.push_str("</tr>\n</thead>\n<tbody>\n");
+            for row in rows {
+                html.push_str("<tr>\n");
+                for (i, cell) in row.iter().enumerate() {
+                    html.push_str(&format!(
+                        "    <td{}>{}</td>\n",
+                        align(i),
+                        render_inlines(cell)
+                    ));
+                }
+                html.push_str("</tr>\n");
+            }
+            html.push_str("</tbody>\n</table>\n");
+        }
+        Node::Rule => html.push_str("<hr>\n"),
+    }
+}
+
+fn render_inlines(content: &[Inline]) -> String {
+    let mut html = String::new();
+    for inline in content {
+        match inline {
+            Inline::Text(text) => html.push_str(&escape_html(text)),
+            Inline::Strong(inner) => {
+                html.push_str(&format!("<strong>{}</strong>", render_inlines(inner)))
+            }
+            Inline::Emphasis(inner) => {
+                html.push_str(&format!("<em>{}</em>", render_inlines(inner)))
+            }
+            Inline::Strikethrough(inner) => {
+                html.push_str(&format!("<del>{}</del>", render_inlines(inner)))
+            }
+            Inline::Code(code) => html.push_str(&format!("<code>{}</code>", escape_html(code))),
+            Inline::Link { url, content } => {
+                if is_safe_url(url) {
+                    html.push_str(&format!(
+                        "<a href=\"{}\">{}</a>",
+                        escape_html(url),
+                        render_inlines(content)
+                    ));
+                } else {
+                    html.push_str(&render_inlines(content));
+                }
+            }
+            Inline::Image(image) => {
+                let src = match image
+                    .data
+                    .as_deref()
+                    .and_then(|data| image_info(data).map(|info| (info, data)))
+                {
+                    Some((info, data)) => format!(
+                        "data:{};base64,{}",
+                        info.format.mime_type(),
+                        base64_encode(data)
+                    ),
+                    None if is_safe_url(&image.src) => image.src.clone(),
+                    None => {
+                        html.push_str(&escape_html(&image.alt));
+                        continue;
+                    }
+                };
+                html.push_str(&format!(
+                    "<img src=\"{}\" alt=\"{}\">",
+                    escape_html(&src),
+                    escape_html(&image.alt)
+                ));
+            }
+            Inline::LineBreak => html.push_str("<br>\n"),
+        }
+    }
+    html
+}
+
+/// Whether a link target is safe to emit: anything but `javascript:`,
+/// `vbscript:` and non-image `data:` URLs.
+fn is_safe_url(url: &str) -> bool {
+    let lower: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_ascii_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    !(lower.starts_with("javascript:")
+        || lower.starts_with("vbscript:")
+        || (lower.starts_with("data:") && !lower.starts_with("data:image/")))
+}
+
+/// Escape text for use in HTML content or attribute values.
+pub fn escape_html(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
         .replace('>', "&gt;")
@@ -58,6 +250,9 @@ fn escape_html(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::document::{Image, test_png};
+    use crate::extract::Block;
+    use crate::markdown::parse_markdown;
 
     #[test]
     fn test_generate_html_structure() {
@@ -103,4 +298,70 @@ mod tests {
         assert!(table.contains("<th>Col1</th>"));
         assert!(table.contains("<tbody>\n</tbody>"));
     }
+
+    #[test]
+    fn test_render_html_document() {
+        let mut doc = parse_markdown(
+            "Intro with **bold**, *em*, `code` and [a link](https://example.com?a=1&b=2).\n\n\
+             3. third\n4. fourth\n\n- [x] done\n\n> quoted\n\n\
+             ```sh\necho <hi>\n```\n\n| A | B |\n| :-: | --: |\n| 1 | 2 |\n\n\
+             [bad](javascript:alert(1)) ![pixel](pixel.png)",
+        );
+        doc.title = Some("Report <draft>".into());
+        let html = render_html(&doc);
+
+        assert!(html.contains("<title>Report &lt;draft&gt;</title>"));
+        assert!(html.contains("<h1>Report &lt;draft&gt;</h1>"));
+        assert!(html.contains("<strong>bold</strong>, <em>em</em>, <code>code</code>"));
+        assert!(html.contains("<a href=\"https://example.com?a=1&amp;b=2\">a link</a>"));
+        assert!(html.contains("<ol start=\"3\">\n<li>third</li>"));
+        assert!(html.contains("<input type=\"checkbox\" disabled checked> done"));
+        assert!(html.contains("<blockquote>\n<p>quoted</p>"));
+        assert!(html.contains("<pre><code class=\"language-sh\">echo &lt;hi&gt;\n</code></pre>"));
+        assert!(html.contains("<th style=\"text-align: center\">A</th>"));
+        assert!(html.contains("<td style=\"text-align: right\">2</td>"));
+        assert!(!html.contains("javascript:"));
+        assert!(html.contains("<img src=\"pixel.png\" alt=\"pixel\">"));
+    }
+
+    #[test]
+    fn test_render_html_inlines_loaded_images() {
+        let mut doc = Document::new();
+        doc.push(Node::Paragraph {
+            content: vec![Inline::Image(Image {
+                src: "chart.png".into(),
+                alt: "chart".into(),
+                data: Some(test_png()),
+            })],
+        });
+        let html = render_html(&doc);
+        assert!(html.contains("<img src=\"data:image/png;base64,iVBORw0KGgo"));
+    }
+
+    #[test]
+    fn test_render_html_roundtrip_through_extraction() {
+        let doc = parse_markdown(
+            "# Plan\n\nShip **it**.\n\n- one\n- two\n\n| K | V |\n| --- | --- |\n| a | 1 |",
+        );
+        let extracted = crate::extract::extract(
+            render_html(&doc).as_bytes(),
+            crate::extract::DocumentFormat::Html,
+        )
+        .unwrap();
+        let blocks = &extracted.sections[0].blocks;
+
+        assert!(blocks.contains(&Block::Heading {
+            level: 1,
+            text: "Plan".into()
+        }));
+        assert!(blocks.contains(&Block::Paragraph {
+            text: "Ship it.".into()
+        }));
+        assert!(blocks.contains(&Block::Paragraph {
+            text: "- one\n- two".into()
+        }));
+        assert!(blocks.contains(&Block::Table {
+            rows: vec![vec!["K".into(), "V".into()], vec!["a".into(), "1".into()]]
+        }));
+    }
 }
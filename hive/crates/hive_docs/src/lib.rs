@@ -1,7 +1,10 @@
 // Phase 5: Document generation (CSV, PDF, DOCX, XLSX, HTML, Markdown, PPTX)
+// and text extraction back out of the same formats.
 
 pub mod csv;
+pub mod document;
 pub mod docx;
+pub mod extract;
 pub mod html;
 pub mod markdown;
 pub mod pdf;
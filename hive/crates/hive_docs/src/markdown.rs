@@ -1,3 +1,7 @@
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+
+use crate::document::{Alignment, Cell, Document, Image, Inline, ListItem, Node, plain_text};
+
 /// Generate a Markdown table from headers and rows.
 ///
 /// Pipes in cell content are escaped to prevent breaking the table structure.
@@ -45,6 +49,623 @@ fn escape_pipe(s: &str) -> String {
     s.replace('|', "\\|")
 }
 
+// ---------------------------------------------------------------------------
+// Document model
+// ---------------------------------------------------------------------------
+
+/// Parse Markdown (CommonMark with GFM tables, strikethrough and task
+/// lists) into a [`Document`].
+///
+/// Soft line breaks become spaces, as CommonMark renders them. Raw HTML is
+/// kept as literal text, except comments, which are dropped.
+pub fn parse_markdown(text: &str) -> Document {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut builder = TreeBuilder {
+        stack: vec![Frame::Container {
+            blocks: Vec::new(),
+            inline: Vec::new(),
+            checked: None,
+        }],
+    };
+    for event in Parser::new_ext(text, options) {
+        builder.event(event);
+    }
+    builder.finish()
+}
+
+/// Render a [`Document`] as Markdown that [`parse_markdown`] reads back
+/// into the same tree. The title, when set, becomes a leading `#` heading.
+pub fn render_markdown(document: &Document) -> String {
+    let mut parts = Vec::new();
+    if let Some(title) = &document.title {
+        parts.push(format!("# {}", escape_inline_text(title, true)));
+    }
+    parts.push(render_blocks(&document.blocks));
+    parts.retain(|part| !part.is_empty());
+    parts.join("\n\n")
+}
+
+/// An open element while building the tree.
+enum Frame {
+    /// The document root, a block quote or a list item. Tight list items
+    /// hold their text directly, so it collects in `inline` until a block
+    /// starts or the item ends.
+    Container {
+        blocks: Vec<Node>,
+        inline: Vec<Inline>,
+        checked: Option<bool>,
+    },
+    List {
+        start: Option<u64>,
+        items: Vec<ListItem>,
+    },
+    Inline {
+        kind: InlineKind,
+        content: Vec<Inline>,
+    },
+    Table {
+        alignments: Vec<Alignment>,
+        header: Vec<Cell>,
+        rows: Vec<Vec<Cell>>,
+        row: Vec<Cell>,
+    },
+    Code {
+        language: Option<String>,
+        code: String,
+    },
+    Html {
+        text: String,
+    },
+}
+
+enum InlineKind {
+    Paragraph,
+    Heading(u8),
+    Strong,
+    Emphasis,
+    Strikethrough,
+    Link(String),
+    Image(String),
+    Cell,
+}
+
+struct TreeBuilder {
+    stack: Vec<Frame>,
+}
+
+impl TreeBuilder {
+    fn event(&mut self, event: Event<'_>) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => match self.stack.last_mut() {
+                Some(Frame::Code { code, .. }) => code.push_str(&text),
+                Some(Frame::Html { text: html }) => html.push_str(&text),
+                _ => self.push_inline(Inline::Text(text.into_string())),
+            },
+            Event::Code(code) => self.push_inline(Inline::Code(code.into_string())),
+            Event::InlineMath(math) | Event::DisplayMath(math) => {
+                self.push_inline(Inline::Code(math.into_string()))
+            }
+            Event::Html(html) => match self.stack.last_mut() {
+                Some(Frame::Html { text }) => text.push_str(&html),
+                _ => self.push_inline(Inline::Text(html.into_string())),
+            },
+            Event::InlineHtml(html) if is_html_comment(&html) => {}
+            Event::InlineHtml(html) => self.push_inline(Inline::Text(html.into_string())),
+            Event::SoftBreak => self.push_inline(Inline::Text(" ".into())),
+            Event::HardBreak => self.push_inline(Inline::LineBreak),
+            Event::Rule => self.push_block(Node::Rule),
+            Event::TaskListMarker(done) => {
+                if let Some(Frame::Container { checked, .. }) = self.stack.last_mut() {
+                    *checked = Some(done);
+                }
+            }
+            Event::FootnoteReference(label) => {
+                self.push_inline(Inline::Text(format!("[^{label}]")))
+            }
+        }
+    }
+
+    fn start(&mut self, tag: Tag<'_>) {
+        let frame = match tag {
+            Tag::Paragraph => Frame::Inline {
+                kind: InlineKind::Paragraph,
+                content: Vec::new(),
+            },
+            Tag::Heading { level, .. } => Frame::Inline {
+                kind: InlineKind::Heading(level as u8),
+                content: Vec::new(),
+            },
+            Tag::BlockQuote(_) | Tag::Item => Frame::Container {
+                blocks: Vec::new(),
+                inline: Vec::new(),
+                checked: None,
+            },
+            Tag::CodeBlock(kind) => Frame::Code {
+                language: match kind {
+                    CodeBlockKind::Fenced(info) => {
+                        info.split_whitespace().next().map(str::to_string)
+                    }
+                    CodeBlockKind::Indented => None,
+                },
+                code: String::new(),
+            },
+            Tag::HtmlBlock => Frame::Html {
+                text: String::new(),
+            },
+            Tag::List(start) => Frame::List {
+                start,
+                items: Vec::new(),
+            },
+            Tag::Table(alignments) => Frame::Table {
+                alignments: alignments
+                    .into_iter()
+                    .map(|a| match a {
+                        pulldown_cmark::Alignment::None => Alignment::None,
+                        pulldown_cmark::Alignment::Left => Alignment::Left,
+                        pulldown_cmark::Alignment::Center => Alignment::Center,
+                        pulldown_cmark::Alignment::Right => Alignment::Right,
+                    })
+                    .collect(),
+                header: Vec::new(),
+                rows: Vec::new(),
+                row: Vec::new(),
+            },
+            Tag::TableCell => Frame::Inline {
+                kind: InlineKind::Cell,
+                content: Vec::new(),
+            },
+            Tag::Emphasis => Frame::Inline {
+                kind: InlineKind::Emphasis,
+                content: Vec::new(),
+            },
+            Tag::Strong => Frame::Inline {
+                kind: InlineKind::Strong,
+                content: Vec::new(),
+            },
+            Tag::Strikethrough => Frame::Inline {
+                kind: InlineKind::Strikethrough,
+                content: Vec::new(),
+            },
+            Tag::Link { dest_url, .. } => Frame::Inline {
+                kind: InlineKind::Link(dest_url.into_string()),
+                content: Vec::new(),
+            },
+            Tag::Image { dest_url, .. } => Frame::Inline {
+                kind: InlineKind::Image(dest_url.into_string()),
+                content: Vec::new(),
+            },
+            // Table heads and rows are tracked inside the table frame;
+            // footnotes, definition lists and metadata are not modelled.
+            _ => return,
+        };
+        if matches!(
+            frame,
+            Frame::Inline {
+                kind: InlineKind::Paragraph | InlineKind::Heading(_),
+                ..
+            } | Frame::Container { .. }
+                | Frame::Code { .. }
+                | Frame::Html { .. }
+                | Frame::List { .. }
+                | Frame::Table { .. }
+        ) {
+            self.flush_loose_text();
+        }
+        self.stack.push(frame);
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::TableHead => {
+                if let Some(Frame::Table { header, row, .. }) = self.stack.last_mut() {
+                    *header = std::mem::take(row);
+                }
+                return;
+            }
+            TagEnd::TableRow => {
+                if let Some(Frame::Table { rows, row, .. }) = self.stack.last_mut() {
+                    rows.push(std::mem::take(row));
+                }
+                return;
+            }
+            TagEnd::Paragraph
+            | TagEnd::Heading(_)
+            | TagEnd::BlockQuote(_)
+            | TagEnd::Item
+            | TagEnd::CodeBlock
+            | TagEnd::HtmlBlock
+            | TagEnd::List(_)
+            | TagEnd::Table
+            | TagEnd::TableCell
+            | TagEnd::Emphasis
+            | TagEnd::Strong
+            | TagEnd::Strikethrough
+            | TagEnd::Link
+            | TagEnd::Image => {}
+            _ => return,
+        }
+        // The root never closes.
+        if self.stack.len() < 2 {
+            return;
+        }
+        let Some(frame) = self.stack.pop() else {
+            return;
+        };
+        match frame {
+            Frame::Container {
+                mut blocks,
+                inline,
+                checked,
+            } => {
+                let inline = normalize(inline);
+                if !inline.is_empty() {
+                    blocks.push(Node::Paragraph { content: inline });
+                }
+                match self.stack.last_mut() {
+                    Some(Frame::List { items, .. }) => items.push(ListItem { checked, blocks }),
+                    _ => self.push_block(Node::Quote { blocks }),
+                }
+            }
+            Frame::List { start, items } => self.push_block(Node::List { start, items }),
+            Frame::Inline { kind, content } => {
+                let content = normalize(content);
+                match kind {
+                    InlineKind::Paragraph => {
+                        if !content.is_empty() {
+                            self.push_block(Node::Paragraph { content });
+                        }
+                    }
+                    InlineKind::Heading(level) => self.push_block(Node::Heading { level, content }),
+                    InlineKind::Strong => self.push_inline(Inline::Strong(content)),
+                    InlineKind::Emphasis => self.push_inline(Inline::Emphasis(content)),
+                    InlineKind::Strikethrough => self.push_inline(Inline::Strikethrough(content)),
+                    InlineKind::Link(url) => self.push_inline(Inline::Link { url, content }),
+                    InlineKind::Image(src) => self.push_inline(Inline::Image(Image {
+                        src,
+                        alt: plain_text(&content),
+                        data: None,
+                    })),
+                    InlineKind::Cell => {
+                        if let Some(Frame::Table { row, .. }) = self.stack.last_mut() {
+                            row.push(content);
+                        }
+                    }
+                }
+            }
+            Frame::Table {
+                alignments,
+                header,
+                rows,
+                ..
+            } => self.push_block(Node::Table {
+                header,
+                rows,
+                alignments,
+            }),
+            Frame::Code { language, code } => self.push_block(Node::CodeBlock { language, code }),
+            Frame::Html { text } => {
+                let text = text.trim_end();
+                if !text.is_empty() && !is_html_comment(text) {
+                    self.push_block(Node::Paragraph {
+                        content: vec![Inline::Text(text.to_string())],
+                    });
+                }
+            }
+        }
+    }
+
+    fn push_inline(&mut self, inline: Inline) {
+        match self.stack.last_mut() {
+            Some(Frame::Inline { content, .. }) => content.push(inline),
+            Some(Frame::Container { inline: loose, .. }) => loose.push(inline),
+            Some(Frame::Code { code, .. }) => code.push_str(&plain_text(&[inline])),
+            Some(Frame::Html { text }) => text.push_str(&plain_text(&[inline])),
+            // Stray text between table cells or list items has nowhere to go.
+            Some(Frame::List { .. } | Frame::Table { .. }) | None => {}
+        }
+    }
+
+    fn push_block(&mut self, node: Node) {
+        self.flush_loose_text();
+        match self.stack.last_mut() {
+            Some(Frame::Container { blocks, .. }) => blocks.push(node),
+            Some(Frame::List { items, .. }) => items.push(ListItem {
+                checked: None,
+                blocks: vec![node],
+            }),
+            // Blocks never nest inside inline or leaf frames in CommonMark.
+            _ => {}
+        }
+    }
+
+    /// Wrap a tight list item's text in a paragraph before a block follows it.
+    fn flush_loose_text(&mut self) {
+        if let Some(Frame::Container { blocks, inline, .. }) = self.stack.last_mut() {
+            let content = normalize(std::mem::take(inline));
+            if !content.is_empty() {
+                blocks.push(Node::Paragraph { content });
+            }
+        }
+    }
+
+    fn finish(mut self) -> Document {
+        while self.stack.len() > 1 {
+            self.end(TagEnd::Item);
+        }
+        self.flush_loose_text();
+        let blocks = match self.stack.pop() {
+            Some(Frame::Container { blocks, .. }) => blocks,
+            _ => Vec::new(),
+        };
+        Document {
+            title: None,
+            blocks,
+        }
+    }
+}
+
+fn is_html_comment(html: &str) -> bool {
+    let html = html.trim();
+    html.starts_with("<!--") && html.ends_with("-->")
+}
+
+/// Merge adjacent text and trim whitespace around line breaks and at the
+/// ends, so equivalent Markdown builds identical trees.
+fn normalize(content: Vec<Inline>) -> Vec<Inline> {
+    let mut out: Vec<Inline> = Vec::with_capacity(content.len());
+    for inline in content {
+        match (out.last_mut(), inline) {
+            (Some(Inline::Text(last)), Inline::Text(text)) => last.push_str(&text),
+            (_, inline) => out.push(inline),
+        }
+    }
+    for i in 0..out.len() {
+        let before_break = matches!(out.get(i + 1), Some(Inline::LineBreak)) || i + 1 == out.len();
+        let after_break = i == 0 || matches!(out[i - 1], Inline::LineBreak);
+        if let Inline::Text(text) = &mut out[i] {
+            if before_break {
+                text.truncate(text.trim_end().len());
+            }
+            if after_break {
+                *text = text.trim_start().to_string();
+            }
+        }
+    }
+    out.retain(|inline| !matches!(inline, Inline::Text(text) if text.is_empty()));
+    while matches!(out.last(), Some(Inline::LineBreak)) {
+        out.pop();
+    }
+    out
+}
+
+fn render_blocks(blocks: &[Node]) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for (i, node) in blocks.iter().enumerate() {
+        // Two lists in a row would merge into one; an HTML comment keeps
+        // them apart.
+        if i > 0 && matches!(node, Node::List { .. }) && matches!(blocks[i - 1], Node::List { .. })
+        {
+            parts.push("<!-- -->".to_string());
+        }
+        parts.push(render_block(node));
+    }
+    parts.join("\n\n")
+}
+
+fn render_block(node: &Node) -> String {
+    match node {
+        Node::Heading { level, content } => format!(
+            "{} {}",
+            "#".repeat((*level).clamp(1, 6) as usize),
+            render_inlines(content, true)
+        ),
+        Node::Paragraph { content } => render_inlines(content, true),
+        Node::List { start, items } => {
+            let mut lines = Vec::new();
+            for (i, item) in items.iter().enumerate() {
+                let marker = match start {
+                    Some(start) => format!("{}. ", start + i as u64),
+                    None => "- ".to_string(),
+                };
+                let task = match item.checked {
+                    Some(true) => "[x] ",
+                    Some(false) => "[ ] ",
+                    None => "",
+                };
+                // A nested list may follow its item's text directly; other
+                // blocks need a blank line between them.
+                let mut body = String::new();
+                for (j, block) in item.blocks.iter().enumerate() {
+                    if j > 0 {
+                        let tight = matches!(block, Node::List { .. })
+                            && !matches!(item.blocks[j - 1], Node::List { .. });
+                        body.push_str(if tight { "\n" } else { "\n\n" });
+                    }
+                    body.push_str(&render_block(block));
+                }
+                let indent = " ".repeat(marker.len());
+                let mut body_lines = body.lines();
+                let first = body_lines.next().unwrap_or_default();
+                lines.push(format!("{marker}{task}{first}").trim_end().to_string());
+                for line in body_lines {
+                    if line.is_empty() {
+                        lines.push(String::new());
+                    } else {
+                        lines.push(format!("{indent}{line}"));
+                    }
+                }
+            }
+            lines.join("\n")
+        }
+        Node::CodeBlock { language, code } => {
+            let longest = longest_run(code, '`');
+            let fence = "`".repeat(longest.max(2) + 1);
+            let code = code.strip_suffix('\n').unwrap_or(code);
+            format!(
+                "{fence}{}\n{code}\n{fence}",
+                language.as_deref().unwrap_or_default()
+            )
+        }
+        Node::Quote { blocks } => render_blocks(blocks)
+            .lines()
+            .map(|line| {
+                if line.is_empty() {
+                    ">".to_string()
+                } else {
+                    format!("> {line}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Node::Table {
+            header,
+            rows,
+            alignments,
+        } => {
+            let cell = |cell: &Cell| {
+                render_inlines(cell, false)
+                    .replace('|', "\\|")
+                    .replace("\\\n", " ")
+                    .replace('\n', " ")
+            };
+            let headers: Vec<String> = header.iter().map(cell).collect();
+            let mut lines = vec![format!("| {} |", headers.join(" | "))];
+            let separators: Vec<&str> = (0..header.len())
+                .map(|i| match alignments.get(i).copied().unwrap_or_default() {
+                    Alignment::None => "---",
+                    Alignment::Left => ":---",
+                    Alignment::Center => ":---:",
+                    Alignment::Right => "---:",
+                })
+                .collect();
+            lines.push(format!("| {} |", separators.join(" | ")));
+            for row in rows {
+                let cells: Vec<String> = row.iter().map(cell).collect();
+                lines.push(format!("| {} |", cells.join(" | ")));
+            }
+            lines.join("\n")
+        }
+        Node::Rule => "---".to_string(),
+    }
+}
+
+/// Render inline content; `block_start` marks content that opens a block,
+/// where characters like `#` and `-` would change its meaning.
+fn render_inlines(content: &[Inline], block_start: bool) -> String {
+    let mut out = String::new();
+    for (i, inline) in content.iter().enumerate() {
+        let at_start = (block_start && i == 0)
+            || matches!(content.get(i.wrapping_sub(1)), Some(Inline::LineBreak));
+        match inline {
+            Inline::Text(text) => out.push_str(&escape_inline_text(text, at_start)),
+            Inline::Strong(inner) => delimit(&mut out, &render_inlines(inner, false), "**"),
+            Inline::Emphasis(inner) => delimit(&mut out, &render_inlines(inner, false), "*"),
+            Inline::Strikethrough(inner) => delimit(&mut out, &render_inlines(inner, false), "~~"),
+            Inline::Code(code) => {
+                let ticks = "`".repeat(longest_run(code, '`') + 1);
+                let pad = code.starts_with('`')
+                    || code.ends_with('`')
+                    || (code.starts_with(' ') && code.ends_with(' ') && !code.trim().is_empty());
+                let pad = if pad { " " } else { "" };
+                out.push_str(&format!("{ticks}{pad}{code}{pad}{ticks}"));
+            }
+            Inline::Link { url, content } => {
+                out.push('[');
+                out.push_str(&render_inlines(content, false));
+                out.push_str("](");
+                out.push_str(&link_destination(url));
+                out.push(')');
+            }
+            Inline::Image(image) => {
+                out.push_str("![");
+                out.push_str(&escape_inline_text(&image.alt, false));
+                out.push_str("](");
+                out.push_str(&link_destination(&image.src));
+                out.push(')');
+            }
+            Inline::LineBreak => out.push_str("\\\n"),
+        }
+    }
+    out
+}
+
+/// Wrap `inner` in emphasis delimiters, keeping its outer spaces outside
+/// them: `** a**` would not parse as emphasis.
+fn delimit(out: &mut String, inner: &str, delimiter: &str) {
+    let trimmed = inner.trim_matches(' ');
+    if trimmed.is_empty() {
+        out.push_str(inner);
+        return;
+    }
+    let leading = inner.len() - inner.trim_start_matches(' ').len();
+    let trailing = inner.len() - inner.trim_end_matches(' ').len();
+    out.push_str(&inner[..leading]);
+    out.push_str(delimiter);
+    out.push_str(trimmed);
+    out.push_str(delimiter);
+    out.push_str(&inner[inner.len() - trailing..]);
+}
+
+fn link_destination(url: &str) -> String {
+    if url.is_empty() || url.contains([' ', '(', ')', '<', '>']) {
+        format!("<{}>", url.replace('<', "%3C").replace('>', "%3E"))
+    } else {
+        url.to_string()
+    }
+}
+
+/// Backslash-escape the characters that would otherwise start Markdown
+/// syntax, leaving ordinary punctuation readable.
+fn escape_inline_text(text: &str, at_block_start: bool) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    for (i, &c) in chars.iter().enumerate() {
+        let next = chars.get(i + 1).copied();
+        let line_start = (i == 0 && at_block_start) || (i > 0 && chars[i - 1] == '\n');
+        let escape = match c {
+            '\\' | '*' | '_' | '`' | '[' | ']' | '~' => true,
+            '<' => next.is_some_and(|n| n.is_ascii_alphabetic() || matches!(n, '/' | '!' | '?')),
+            '&' => next.is_some_and(|n| n.is_ascii_alphanumeric() || n == '#'),
+            '#' | '>' | '+' | '=' | '|' => line_start,
+            '-' => line_start,
+            '.' | ')' => {
+                // "1." or "1)" at the start of a line would open a list.
+                let digits = chars[..i]
+                    .iter()
+                    .rev()
+                    .take_while(|c| c.is_ascii_digit())
+                    .count();
+                digits > 0
+                    && (i == digits && at_block_start
+                        || i > digits && chars[i - digits - 1] == '\n')
+            }
+            _ => false,
+        };
+        if escape {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn longest_run(text: &str, c: char) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    for ch in text.chars() {
+        if ch == c {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    longest
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -96,6 +717,157 @@ mod tests {
         assert!(doc.contains("Here are the results."));
     }
 
+    const SAMPLE: &str = "# Release notes
+
+Version **2.1** adds *faster* sync, ~~legacy~~ removal and `hive sync --all`.
+See [the docs](https://example.com/docs \"Docs\") or ![logo](logo.png).
+
+## Changes
+
+1. Parser
+2. Writers
+   - PDF
+   - DOCX
+
+- [x] Shipped
+- [ ] Pending
+
+> Quoted *advice*
+>
+> Second line
+
+```rust
+fn main() {}
+```
+
+| Model | Cost |
+| :--- | ---: |
+| a\\|b | 1.5 |
+
+---
+
+Line one\\
+Line two
+";
+
+    #[test]
+    fn test_parse_markdown_structure() {
+        let doc = parse_markdown(SAMPLE);
+        assert_eq!(doc.title, None);
+        assert_eq!(doc.display_title().as_deref(), Some("Release notes"));
+
+        assert_eq!(doc.blocks[0], Node::heading(1, "Release notes"));
+        let Node::Paragraph { content } = &doc.blocks[1] else {
+            panic!("expected a paragraph, got {:?}", doc.blocks[1]);
+        };
+        assert_eq!(content[0], Inline::Text("Version ".into()));
+        assert_eq!(content[1], Inline::Strong(vec![Inline::Text("2.1".into())]));
+        assert!(content.contains(&Inline::Code("hive sync --all".into())));
+        assert!(content.contains(&Inline::Link {
+            url: "https://example.com/docs".into(),
+            content: vec![Inline::Text("the docs".into())],
+        }));
+        assert!(content.contains(&Inline::Image(Image {
+            src: "logo.png".into(),
+            alt: "logo".into(),
+            data: None,
+        })));
+        // The soft break became a space.
+        assert_eq!(
+            plain_text(content),
+            "Version 2.1 adds faster sync, legacy removal and hive sync --all. See the docs or logo."
+        );
+
+        let Node::List { start, items } = &doc.blocks[3] else {
+            panic!("expected a list, got {:?}", doc.blocks[3]);
+        };
+        assert_eq!(*start, Some(1));
+        assert_eq!(items[1].blocks.len(), 2);
+        assert!(
+            matches!(&items[1].blocks[1], Node::List { start: None, items } if items.len() == 2)
+        );
+
+        let Node::List { items, .. } = &doc.blocks[4] else {
+            panic!("expected a task list");
+        };
+        assert_eq!(items[0].checked, Some(true));
+        assert_eq!(items[1].checked, Some(false));
+        assert_eq!(items[0].blocks, vec![Node::paragraph("Shipped")]);
+
+        assert!(matches!(&doc.blocks[5], Node::Quote { blocks } if blocks.len() == 2));
+        assert_eq!(
+            doc.blocks[6],
+            Node::CodeBlock {
+                language: Some("rust".into()),
+                code: "fn main() {}\n".into(),
+            }
+        );
+        let Node::Table {
+            header,
+            rows,
+            alignments,
+        } = &doc.blocks[7]
+        else {
+            panic!("expected a table");
+        };
+        assert_eq!(header.len(), 2);
+        assert_eq!(rows[0][0], vec![Inline::Text("a|b".into())]);
+        assert_eq!(alignments, &vec![Alignment::Left, Alignment::Right]);
+        assert_eq!(doc.blocks[8], Node::Rule);
+        assert_eq!(doc.blocks[9], Node::lines("Line one\nLine two"));
+    }
+
+    #[test]
+    fn test_render_markdown_roundtrip() {
+        let doc = parse_markdown(SAMPLE);
+        let rendered = render_markdown(&doc);
+        assert_eq!(parse_markdown(&rendered), doc, "{rendered}");
+        assert!(rendered.contains("| :--- | ---: |"));
+        assert!(rendered.contains("- [x] Shipped"));
+    }
+
+    #[test]
+    fn test_render_markdown_escapes_literal_syntax() {
+        let mut doc = Document::titled("# Not a heading");
+        doc.push(Node::paragraph("1. not a list"));
+        doc.push(Node::paragraph("- nor this, *nor* _this_ or [this](x) <b>"));
+        doc.push(Node::Paragraph {
+            content: vec![
+                Inline::Strong(vec![Inline::Text("bold ".into())]),
+                Inline::Text("then text".into()),
+                Inline::Code("a ` tick".into()),
+            ],
+        });
+        doc.push(Node::List {
+            start: None,
+            items: vec![ListItem {
+                checked: None,
+                blocks: vec![Node::paragraph("one")],
+            }],
+        });
+        doc.push(Node::List {
+            start: Some(3),
+            items: vec![ListItem {
+                checked: None,
+                blocks: vec![Node::paragraph("three")],
+            }],
+        });
+
+        let rendered = render_markdown(&doc);
+        let parsed = parse_markdown(&rendered);
+        assert_eq!(parsed.blocks[0], Node::heading(1, "# Not a heading"));
+        assert_eq!(&parsed.blocks[1..3], &doc.blocks[..2], "{rendered}");
+        assert_eq!(&parsed.blocks[4..], &doc.blocks[3..], "{rendered}");
+        // The space inside the bold run moves out of the delimiters.
+        assert!(rendered.contains("**bold** then text``a ` tick``"));
+        let (Node::Paragraph { content: parsed }, Node::Paragraph { content: original }) =
+            (&parsed.blocks[3], &doc.blocks[2])
+        else {
+            panic!("expected paragraphs");
+        };
+        assert_eq!(plain_text(parsed), plain_text(original));
+    }
+
     #[test]
     fn test_generate_markdown_document_no_sections() {
         let doc = generate_markdown_document("Title Only", &[]);
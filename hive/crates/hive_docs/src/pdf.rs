@@ -1,230 +1,3041 @@
-//! PDF document generation.
+//! PDF document generation and text extraction.
 //!
-//! Generates minimal but valid PDF files using raw PDF format construction.
-//! Supports document generation with titled sections and table generation.
-//! Uses built-in Helvetica font — no external font files required.
+//! Generates PDF files by constructing the PDF format directly. Documents
+//! are laid out from the shared [`Document`] model: wrapped paragraphs,
+//! multi-level headings, lists, code blocks, quotes, tables, images,
+//! clickable links and page numbers, paginated onto A4. Text is set in the
+//! built-in Helvetica family (Latin-1 only) unless a [`FontSet`] of TrueType
+//! fonts is supplied, in which case those are embedded and any character
+//! they cover can be used. The `generate_pdf_*` helpers stay on Helvetica
+//! and embed an installed system family only for text it can't encode.
+//!
+//! [`extract_pdf`] reads text back out of PDFs from any producer.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+use anyhow::{Result, anyhow, bail};
+
+use crate::document::{
+    Alignment, Document, Image, ImageFormat, Inline, Node, Span, TextStyle, image_info, plain_text,
+    spans,
+};
+use crate::extract::{Anchor, Block, DocumentFormat, ExtractedDocument, Section};
+
+/// Generate a PDF document with a title and a series of sections.
+///
+/// Each section has a heading (bold) and body text, one line per line.
+/// Text outside Latin-1 is set in an installed system font; see
+/// [`PdfOptions::for_document`].
+pub fn generate_pdf_document(title: &str, sections: &[(&str, &str)]) -> Result<Vec<u8>> {
+    let mut document = Document::titled(title);
+    for (heading, body) in sections {
+        document.push(Node::heading(2, *heading));
+        if !body.is_empty() {
+            document.push(Node::lines(body));
+        }
+    }
+    render_pdf(&document, &PdfOptions::for_document(&document))
+}
+
+/// Generate a PDF document containing a table with headers and rows.
+///
+/// Headers are rendered in bold; columns are sized to their content. Fonts
+/// are chosen as for [`generate_pdf_document`].
+pub fn generate_pdf_table(title: &str, headers: &[&str], rows: &[Vec<String>]) -> Result<Vec<u8>> {
+    let mut document = Document::titled(title);
+    document.push(Node::table(headers, rows));
+    render_pdf(&document, &PdfOptions::for_document(&document))
+}
+
+/// Page geometry and fonts for [`render_pdf`].
+#[derive(Debug, Clone)]
+pub struct PdfOptions {
+    /// Page width and height in points.
+    pub page_size: (f64, f64),
+    /// Margin on every side, in points.
+    pub margin: f64,
+    /// TrueType fonts to embed; the standard Helvetica family when `None`.
+    pub fonts: Option<FontSet>,
+    /// Number the pages of documents longer than one page.
+    pub page_numbers: bool,
+}
+
+impl Default for PdfOptions {
+    fn default() -> Self {
+        Self {
+            page_size: (595.0, 842.0),
+            margin: 72.0,
+            fonts: None,
+            page_numbers: true,
+        }
+    }
+}
+
+impl PdfOptions {
+    /// Default options for `document`: the standard Helvetica family when
+    /// all of its text is in WinAnsiEncoding, otherwise [`FontSet::system`]
+    /// so the rest survives. Latin-1 documents stay small and identical
+    /// across machines.
+    pub fn for_document(document: &Document) -> Self {
+        let win_ansi = document.title.as_deref().is_none_or(is_win_ansi)
+            && document.blocks.iter().all(node_is_win_ansi);
+        Self {
+            fonts: if win_ansi { None } else { FontSet::system() },
+            ..Self::default()
+        }
+    }
+}
+
+/// Whether the standard fonts can set every character of `text`.
+fn is_win_ansi(text: &str) -> bool {
+    text.chars().all(|c| c == '?' || win_ansi_byte(c) != b'?')
+}
+
+fn node_is_win_ansi(node: &Node) -> bool {
+    match node {
+        Node::Heading { content, .. } | Node::Paragraph { content } => {
+            is_win_ansi(&plain_text(content))
+        }
+        Node::List { items, .. } => items
+            .iter()
+            .flat_map(|item| &item.blocks)
+            .all(node_is_win_ansi),
+        Node::Quote { blocks } => blocks.iter().all(node_is_win_ansi),
+        Node::CodeBlock { code, .. } => is_win_ansi(code),
+        Node::Table { header, rows, .. } => header
+            .iter()
+            .chain(rows.iter().flatten())
+            .all(|cell| is_win_ansi(&plain_text(cell))),
+        Node::Rule => true,
+    }
+}
+
+/// Render a [`Document`] to PDF.
+pub fn render_pdf(document: &Document, options: &PdfOptions) -> Result<Vec<u8>> {
+    let (width, height) = options.page_size;
+    if width <= options.margin * 2.0 + 72.0 || height <= options.margin * 2.0 + 72.0 {
+        bail!("Page size {width}x{height} leaves no room inside the margins");
+    }
+
+    let mut layout = Layout::new(options);
+    if let Some(title) = &document.title {
+        layout.heading(&[Inline::Text(title.clone())], TITLE_SIZE, 0.0);
+    }
+    for node in &document.blocks {
+        layout.block(node, 0.0);
+    }
+    layout.number_pages();
+
+    let title = document.display_title().unwrap_or_default();
+    Ok(layout.finish(&title))
+}
+
+/// Escape special characters for PDF string literals.
+fn pdf_escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('(', "\\(")
+        .replace(')', "\\)")
+}
+
+// ---------------------------------------------------------------------------
+// Fonts
+// ---------------------------------------------------------------------------
+
+/// TrueType fonts to embed: a regular face plus optional bold, italic,
+/// bold-italic and monospace faces. Missing faces fall back to the nearest
+/// one present. Fonts are embedded whole, and only when used.
+#[derive(Clone)]
+pub struct FontSet {
+    regular: Arc<TrueTypeFont>,
+    bold: Option<Arc<TrueTypeFont>>,
+    italic: Option<Arc<TrueTypeFont>>,
+    bold_italic: Option<Arc<TrueTypeFont>>,
+    monospace: Option<Arc<TrueTypeFont>>,
+}
+
+impl fmt::Debug for FontSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = |font: &Option<Arc<TrueTypeFont>>| font.as_ref().map(|font| font.name.clone());
+        f.debug_struct("FontSet")
+            .field("regular", &self.regular.name)
+            .field("bold", &name(&self.bold))
+            .field("italic", &name(&self.italic))
+            .field("bold_italic", &name(&self.bold_italic))
+            .field("monospace", &name(&self.monospace))
+            .finish()
+    }
+}
+
+/// Regular, bold, italic, bold-italic and monospace files of font families
+/// commonly installed on Linux, Windows and macOS.
+const SYSTEM_FONTS: &[[&str; 5]] = &[
+    [
+        "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+        "/usr/share/fonts/truetype/dejavu/DejaVuSans-Bold.ttf",
+        "/usr/share/fonts/truetype/dejavu/DejaVuSans-Oblique.ttf",
+        "/usr/share/fonts/truetype/dejavu/DejaVuSans-BoldOblique.ttf",
+        "/usr/share/fonts/truetype/dejavu/DejaVuSansMono.ttf",
+    ],
+    [
+        "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+        "/usr/share/fonts/dejavu/DejaVuSans-Bold.ttf",
+        "/usr/share/fonts/dejavu/DejaVuSans-Oblique.ttf",
+        "/usr/share/fonts/dejavu/DejaVuSans-BoldOblique.ttf",
+        "/usr/share/fonts/dejavu/DejaVuSansMono.ttf",
+    ],
+    [
+        "/usr/share/fonts/TTF/DejaVuSans.ttf",
+        "/usr/share/fonts/TTF/DejaVuSans-Bold.ttf",
+        "/usr/share/fonts/TTF/DejaVuSans-Oblique.ttf",
+        "/usr/share/fonts/TTF/DejaVuSans-BoldOblique.ttf",
+        "/usr/share/fonts/TTF/DejaVuSansMono.ttf",
+    ],
+    [
+        "C:\\Windows\\Fonts\\arial.ttf",
+        "C:\\Windows\\Fonts\\arialbd.ttf",
+        "C:\\Windows\\Fonts\\ariali.ttf",
+        "C:\\Windows\\Fonts\\arialbi.ttf",
+        "C:\\Windows\\Fonts\\consola.ttf",
+    ],
+    [
+        "/System/Library/Fonts/Supplemental/Arial.ttf",
+        "/System/Library/Fonts/Supplemental/Arial Bold.ttf",
+        "/System/Library/Fonts/Supplemental/Arial Italic.ttf",
+        "/System/Library/Fonts/Supplemental/Arial Bold Italic.ttf",
+        "/System/Library/Fonts/Supplemental/Courier New.ttf",
+    ],
+];
+
+/// One of the `FontSet::with_*` methods.
+type AddFace = fn(FontSet, Vec<u8>) -> Result<FontSet>;
+
+impl FontSet {
+    /// A font set from the bytes of a regular-weight TrueType font.
+    pub fn new(regular: Vec<u8>) -> Result<Self> {
+        Ok(Self {
+            regular: Arc::new(TrueTypeFont::parse(regular)?),
+            bold: None,
+            italic: None,
+            bold_italic: None,
+            monospace: None,
+        })
+    }
+
+    pub fn with_bold(mut self, data: Vec<u8>) -> Result<Self> {
+        self.bold = Some(Arc::new(TrueTypeFont::parse(data)?));
+        Ok(self)
+    }
+
+    pub fn with_italic(mut self, data: Vec<u8>) -> Result<Self> {
+        self.italic = Some(Arc::new(TrueTypeFont::parse(data)?));
+        Ok(self)
+    }
+
+    pub fn with_bold_italic(mut self, data: Vec<u8>) -> Result<Self> {
+        self.bold_italic = Some(Arc::new(TrueTypeFont::parse(data)?));
+        Ok(self)
+    }
+
+    pub fn with_monospace(mut self, data: Vec<u8>) -> Result<Self> {
+        self.monospace = Some(Arc::new(TrueTypeFont::parse(data)?));
+        Ok(self)
+    }
+
+    /// The first installed family from a list of common system fonts
+    /// (DejaVu, Arial), or `None` when none is found. The fonts are read
+    /// once per process.
+    pub fn system() -> Option<Self> {
+        static SYSTEM: OnceLock<Option<FontSet>> = OnceLock::new();
+        SYSTEM.get_or_init(Self::load_system).clone()
+    }
+
+    fn load_system() -> Option<Self> {
+        SYSTEM_FONTS.iter().find_map(|paths| {
+            let read = |path: &str| std::fs::read(Path::new(path)).ok();
+            let mut set = Self::new(read(paths[0])?).ok()?;
+            let faces: [AddFace; 4] = [
+                Self::with_bold,
+                Self::with_italic,
+                Self::with_bold_italic,
+                Self::with_monospace,
+            ];
+            for (path, with) in paths[1..].iter().zip(faces) {
+                if let Some(data) = read(path) {
+                    set = match with(set.clone(), data) {
+                        Ok(updated) => updated,
+                        Err(_) => set,
+                    };
+                }
+            }
+            Some(set)
+        })
+    }
+
+    fn face(&self, face: Face) -> &Arc<TrueTypeFont> {
+        let chosen = match face {
+            Face::Regular => None,
+            Face::Bold => self.bold.as_ref(),
+            Face::Italic => self.italic.as_ref(),
+            Face::BoldItalic => self.bold_italic.as_ref().or(self.bold.as_ref()),
+            Face::Mono => self.monospace.as_ref(),
+        };
+        chosen.unwrap_or(&self.regular)
+    }
+}
+
+/// A parsed TrueType font and the metrics needed to set and embed it.
+struct TrueTypeFont {
+    data: Vec<u8>,
+    /// PostScript name, restricted to characters valid in a PDF name.
+    name: String,
+    glyphs: HashMap<char, u16>,
+    /// Advance width per glyph ID, in font units.
+    advances: Vec<u16>,
+    units_per_em: f64,
+    ascent: i16,
+    descent: i16,
+    cap_height: i16,
+    bbox: [i16; 4],
+    italic_angle: f32,
+    monospaced: bool,
+}
+
+impl TrueTypeFont {
+    fn parse(data: Vec<u8>) -> Result<Self> {
+        let face =
+            ttf_parser::Face::parse(&data, 0).map_err(|e| anyhow!("Invalid font file: {e}"))?;
+        if face.tables().glyf.is_none() {
+            bail!("Only fonts with TrueType outlines can be embedded");
+        }
+        if face.permissions() == Some(ttf_parser::Permissions::Restricted) {
+            bail!("The font's license does not allow embedding");
+        }
+
+        let mut glyphs = HashMap::new();
+        if let Some(cmap) = face.tables().cmap {
+            for subtable in cmap.subtables {
+                if !subtable.is_unicode() {
+                    continue;
+                }
+                subtable.codepoints(|code| {
+                    if let Some(c) = char::from_u32(code)
+                        && let Some(glyph) = subtable.glyph_index(code)
+                    {
+                        glyphs.entry(c).or_insert(glyph.0);
+                    }
+                });
+            }
+        }
+        if glyphs.is_empty() {
+            bail!("Font has no Unicode character map");
+        }
+
+        let name = face
+            .names()
+            .into_iter()
+            .filter(|name| name.name_id == ttf_parser::name_id::POST_SCRIPT_NAME)
+            .find_map(|name| name.to_string())
+            .map(|name| {
+                name.chars()
+                    .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+                    .collect::<String>()
+            })
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "EmbeddedFont".to_string());
+        let advances = (0..face.number_of_glyphs())
+            .map(|glyph| {
+                face.glyph_hor_advance(ttf_parser::GlyphId(glyph))
+                    .unwrap_or(0)
+            })
+            .collect();
+        let bbox = face.global_bounding_box();
+
+        let font = Self {
+            name,
+            glyphs,
+            advances,
+            units_per_em: f64::from(face.units_per_em().max(1)),
+            ascent: face.ascender(),
+            descent: face.descender(),
+            cap_height: face.capital_height().unwrap_or(face.ascender()),
+            bbox: [bbox.x_min, bbox.y_min, bbox.x_max, bbox.y_max],
+            italic_angle: face.italic_angle(),
+            monospaced: face.is_monospaced(),
+            data: Vec::new(),
+        };
+        Ok(Self { data, ..font })
+    }
+
+    fn glyph(&self, c: char) -> u16 {
+        self.glyphs.get(&c).copied().unwrap_or(0)
+    }
+
+    /// Width of a glyph in thousandths of an em.
+    fn width(&self, glyph: u16) -> f64 {
+        let advance = self.advances.get(glyph as usize).copied().unwrap_or(0);
+        f64::from(advance) * 1000.0 / self.units_per_em
+    }
+
+    fn scaled(&self, value: i16) -> i64 {
+        (f64::from(value) * 1000.0 / self.units_per_em).round() as i64
+    }
+}
+
+/// The five faces text is set in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Face {
+    Regular,
+    Bold,
+    Italic,
+    BoldItalic,
+    Mono,
+}
+
+const FACES: [Face; 5] = [
+    Face::Regular,
+    Face::Bold,
+    Face::Italic,
+    Face::BoldItalic,
+    Face::Mono,
+];
+
+impl Face {
+    fn for_style(style: TextStyle) -> Self {
+        match (style.code, style.bold, style.italic) {
+            (true, _, _) => Face::Mono,
+            (false, true, true) => Face::BoldItalic,
+            (false, true, false) => Face::Bold,
+            (false, false, true) => Face::Italic,
+            (false, false, false) => Face::Regular,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn resource(self) -> String {
+        format!("F{}", self.index() + 1)
+    }
+
+    fn standard_font(self) -> &'static str {
+        match self {
+            Face::Regular => "Helvetica",
+            Face::Bold => "Helvetica-Bold",
+            Face::Italic => "Helvetica-Oblique",
+            Face::BoldItalic => "Helvetica-BoldOblique",
+            Face::Mono => "Courier",
+        }
+    }
+}
+
+/// Helvetica advance widths for ASCII 32–126, in thousandths of an em.
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Helvetica-Bold advance widths for ASCII 32–126.
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, 975, 722, 722, 722, 722, 667,
+    611, 778, 722, 278, 556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 333, 278, 333, 584, 556, 333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556,
+    278, 889, 611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+/// Measures and encodes text, tracking which glyphs each face used so
+/// embedded fonts can describe them.
+struct Fonts<'a> {
+    set: Option<&'a FontSet>,
+    used: [BTreeMap<u16, char>; 5],
+}
+
+impl<'a> Fonts<'a> {
+    fn new(set: Option<&'a FontSet>) -> Self {
+        Self {
+            set,
+            used: Default::default(),
+        }
+    }
+
+    /// Width of `text` at `size` points.
+    fn width(&self, face: Face, text: &str, size: f64) -> f64 {
+        let units: f64 = match self.set {
+            Some(set) => {
+                let font = set.face(face);
+                text.chars().map(|c| font.width(font.glyph(c))).sum()
+            }
+            None => text.chars().map(|c| standard_width(face, c)).sum(),
+        };
+        units * size / 1000.0
+    }
+
+    /// `text` as a PDF string operand for the face's font.
+    fn encode(&mut self, face: Face, text: &str) -> String {
+        match self.set {
+            Some(set) => {
+                let font = set.face(face);
+                let used = &mut self.used[face.index()];
+                let mut hex = String::from("<");
+                for c in text.chars() {
+                    let glyph = font.glyph(c);
+                    used.entry(glyph).or_insert(c);
+                    hex.push_str(&format!("{glyph:04X}"));
+                }
+                hex.push('>');
+                hex
+            }
+            None => {
+                let mut literal = String::from("(");
+                for c in text.chars() {
+                    match win_ansi_byte(c) {
+                        b'(' | b')' | b'\\' => {
+                            literal.push('\\');
+                            literal.push(c);
+                        }
+                        byte @ 0x20..=0x7E => literal.push(byte as char),
+                        byte => literal.push_str(&format!("\\{byte:03o}")),
+                    }
+                }
+                literal.push(')');
+                literal
+            }
+        }
+    }
+}
+
+fn standard_width(face: Face, c: char) -> f64 {
+    if face == Face::Mono {
+        return 600.0;
+    }
+    let table = match face {
+        Face::Bold | Face::BoldItalic => &HELVETICA_BOLD_WIDTHS,
+        _ => &HELVETICA_WIDTHS,
+    };
+    let width = match win_ansi_byte(c) {
+        byte @ 0x20..=0x7E => table[(byte - 0x20) as usize],
+        0x95 => 350,
+        0x85 | 0x97 => 1000,
+        0x99 => 1000,
+        _ => 556,
+    };
+    f64::from(width)
+}
+
+/// The WinAnsiEncoding byte for a character, or `?` when it has none.
+fn win_ansi_byte(c: char) -> u8 {
+    match c {
+        '\t' | '\n' | '\r' => b' ',
+        ' '..='~' | '\u{A0}'..='\u{FF}' => c as u8,
+        '€' => 0x80,
+        '…' => 0x85,
+        '‘' => 0x91,
+        '’' => 0x92,
+        '“' => 0x93,
+        '”' => 0x94,
+        '•' => 0x95,
+        '–' => 0x96,
+        '—' => 0x97,
+        '™' => 0x99,
+        _ => b'?',
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Layout
+// ---------------------------------------------------------------------------
+
+const BODY_SIZE: f64 = 11.0;
+const TITLE_SIZE: f64 = 24.0;
+const HEADING_SIZES: [f64; 6] = [20.0, 16.0, 13.5, 12.0, 11.0, 11.0];
+const CODE_SIZE: f64 = 9.5;
+const TABLE_SIZE: f64 = 10.0;
+const LEADING: f64 = 1.4;
+const PARAGRAPH_GAP: f64 = 8.0;
+const LIST_INDENT: f64 = 18.0;
+const QUOTE_INDENT: f64 = 14.0;
+const CELL_PADDING: f64 = 4.0;
+const LINK_COLOR: &str = "0.02 0.39 0.76";
+
+/// A run of text in one face on one line.
+#[derive(Debug, Clone)]
+struct Piece {
+    text: String,
+    face: Face,
+    size: f64,
+    width: f64,
+    link: Option<String>,
+    strike: bool,
+}
+
+impl Piece {
+    fn same_format(&self, other: &Piece) -> bool {
+        self.face == other.face
+            && self.size == other.size
+            && self.link == other.link
+            && self.strike == other.strike
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Line {
+    pieces: Vec<Piece>,
+    width: f64,
+}
+
+impl Line {
+    fn size(&self, default: f64) -> f64 {
+        self.pieces
+            .iter()
+            .map(|p| p.size)
+            .fold(0.0, f64::max)
+            .max(default * 0.5)
+    }
+
+    fn push(&mut self, piece: Piece) {
+        self.width += piece.width;
+        match self.pieces.last_mut() {
+            Some(last) if last.same_format(&piece) => {
+                last.text.push_str(&piece.text);
+                last.width += piece.width;
+            }
+            _ => self.pieces.push(piece),
+        }
+    }
+
+    /// Drop trailing spaces, which would otherwise count toward alignment.
+    fn trim_end(&mut self, fonts: &Fonts<'_>) {
+        while let Some(last) = self.pieces.last_mut() {
+            let trimmed = last.text.trim_end().len();
+            if trimmed == last.text.len() {
+                break;
+            }
+            let removed: String = last.text[trimmed..].to_string();
+            last.text.truncate(trimmed);
+            let width = fonts.width(last.face, &removed, last.size);
+            last.width -= width;
+            self.width -= width;
+            if last.text.is_empty() {
+                self.pieces.pop();
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+/// Inline content broken into lines, with images standing on their own.
+enum Flow<'a> {
+    Line(Line),
+    Image(&'a Image),
+}
+
+/// An image XObject ready to write.
+struct PdfImage {
+    width: u32,
+    height: u32,
+    dict: String,
+    data: Vec<u8>,
+    /// Compressed 8-bit alpha channel.
+    soft_mask: Option<Vec<u8>>,
+}
+
+#[derive(Default)]
+struct PageContent {
+    content: String,
+    links: Vec<([f64; 4], String)>,
+    images: Vec<usize>,
+}
+
+struct Layout<'a> {
+    options: &'a PdfOptions,
+    fonts: Fonts<'a>,
+    pages: Vec<PageContent>,
+    /// Top of the free space on the current page.
+    y: f64,
+    images: Vec<PdfImage>,
+    /// Marker ("•", "3.") to draw beside the next line laid out.
+    marker: Option<(String, f64)>,
+    /// X positions of the bars of the enclosing block quotes.
+    quote_bars: Vec<f64>,
+    /// Gray level for text inside quotes.
+    gray: f64,
+}
+
+impl<'a> Layout<'a> {
+    fn new(options: &'a PdfOptions) -> Self {
+        Self {
+            options,
+            fonts: Fonts::new(options.fonts.as_ref()),
+            pages: vec![PageContent::default()],
+            y: options.page_size.1 - options.margin,
+            images: Vec::new(),
+            marker: None,
+            quote_bars: Vec::new(),
+            gray: 0.0,
+        }
+    }
+
+    fn left(&self) -> f64 {
+        self.options.margin
+    }
+
+    fn text_width(&self) -> f64 {
+        self.options.page_size.0 - self.options.margin * 2.0
+    }
+
+    fn bottom(&self) -> f64 {
+        self.options.margin
+    }
+
+    fn top(&self) -> f64 {
+        self.options.page_size.1 - self.options.margin
+    }
+
+    fn at_page_top(&self) -> bool {
+        self.y >= self.top()
+    }
+
+    fn page(&mut self) -> &mut PageContent {
+        self.pages.last_mut().expect("pages is never empty")
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(PageContent::default());
+        self.y = self.top();
+    }
+
+    /// Start a new page unless `height` fits in the space left.
+    fn ensure(&mut self, height: f64) {
+        if self.y - height < self.bottom() && !self.at_page_top() {
+            self.new_page();
+        }
+    }
+
+    fn gap(&mut self, height: f64) {
+        if !self.at_page_top() {
+            self.y -= height;
+        }
+    }
+
+    fn block(&mut self, node: &Node, indent: f64) {
+        match node {
+            Node::Heading { level, content } => {
+                let level = (*level).clamp(1, 6) as usize;
+                self.heading(content, HEADING_SIZES[level - 1], indent);
+            }
+            Node::Paragraph { content } => {
+                self.paragraph(content, TextStyle::default(), BODY_SIZE, indent);
+                self.gap(PARAGRAPH_GAP);
+            }
+            Node::List { start, items } => self.list(*start, items, indent),
+            Node::CodeBlock { code, .. } => {
+                self.code_block(code, indent);
+                self.gap(PARAGRAPH_GAP);
+            }
+            Node::Quote { blocks } => {
+                let x = self.left() + indent + 2.0;
+                self.quote_bars.push(x);
+                let gray = self.gray;
+                self.gray = 0.35;
+                for node in blocks {
+                    self.block(node, indent + QUOTE_INDENT);
+                }
+                self.gray = gray;
+                self.quote_bars.pop();
+            }
+            Node::Table {
+                header,
+                rows,
+                alignments,
+            } => {
+                self.table(header, rows, alignments, indent);
+                self.gap(PARAGRAPH_GAP);
+            }
+            Node::Rule => {
+                self.ensure(12.0);
+                let y = self.y - 6.0;
+                let (x, width) = (self.left() + indent, self.text_width() - indent);
+                self.page().content.push_str(&format!(
+                    "0.75 G 0.75 w {x:.2} {y:.2} m {:.2} {y:.2} l S 0 G\n",
+                    x + width
+                ));
+                self.y -= 12.0;
+                self.gap(PARAGRAPH_GAP);
+            }
+        }
+    }
+
+    fn heading(&mut self, content: &[Inline], size: f64, indent: f64) {
+        self.gap(size * 0.6);
+        // Keep the heading with at least two lines of what follows.
+        self.ensure(size * LEADING + BODY_SIZE * LEADING * 2.0);
+        let style = TextStyle {
+            bold: true,
+            ..TextStyle::default()
+        };
+        self.paragraph(content, style, size, indent);
+        self.gap(size * 0.3);
+    }
+
+    fn paragraph(&mut self, content: &[Inline], style: TextStyle, size: f64, indent: f64) {
+        let width = self.text_width() - indent;
+        let flow = self.flow(&spans(content), style, size, width);
+        for item in flow {
+            match item {
+                Flow::Line(line) => self.text_line(&line, size, indent, width, Alignment::None),
+                Flow::Image(image) => self.image(image, indent),
+            }
+        }
+    }
+
+    /// Break spans into lines no wider than `width`.
+    fn flow<'s>(
+        &self,
+        spans: &[Span<'s>],
+        base: TextStyle,
+        size: f64,
+        width: f64,
+    ) -> Vec<Flow<'s>> {
+        let mut out = Vec::new();
+        let mut line = Line::default();
+        let mut pending_space: Option<Piece> = None;
+
+        let finish = |line: &mut Line, out: &mut Vec<Flow<'s>>| {
+            line.trim_end(&self.fonts);
+            out.push(Flow::Line(std::mem::take(line)));
+        };
+
+        for span in spans {
+            let (text, style, link) = match span {
+                Span::Text { text, style, link } => (*text, *style, *link),
+                Span::LineBreak => {
+                    pending_space = None;
+                    finish(&mut line, &mut out);
+                    continue;
+                }
+                Span::Image(image) => {
+                    if !line.pieces.is_empty() {
+                        finish(&mut line, &mut out);
+                    }
+                    pending_space = None;
+                    out.push(Flow::Image(image));
+                    continue;
+                }
+            };
+            let style = TextStyle {
+                bold: style.bold || base.bold,
+                italic: style.italic || base.italic,
+                ..style
+            };
+            let face = Face::for_style(style);
+            let piece = |text: &str| Piece {
+                text: text.to_string(),
+                face,
+                size,
+                width: self.fonts.width(face, text, size),
+                link: link.map(str::to_string),
+                strike: style.strike,
+            };
+
+            for token in tokens(text) {
+                if token.trim().is_empty() {
+                    if !line.pieces.is_empty() {
+                        pending_space = Some(piece(" "));
+                    }
+                    continue;
+                }
+                let word = piece(token);
+                let space = pending_space.as_ref().map_or(0.0, |s| s.width);
+                if !line.pieces.is_empty() && line.width + space + word.width > width {
+                    finish(&mut line, &mut out);
+                    pending_space = None;
+                }
+                if let Some(space) = pending_space.take() {
+                    line.push(space);
+                }
+                if word.width <= width || !line.pieces.is_empty() {
+                    line.push(word);
+                    continue;
+                }
+                // A word wider than the line is broken between characters.
+                for c in token.chars() {
+                    let part = piece(&c.to_string());
+                    if !line.pieces.is_empty() && line.width + part.width > width {
+                        finish(&mut line, &mut out);
+                    }
+                    line.push(part);
+                }
+            }
+        }
+        if !line.pieces.is_empty() {
+            finish(&mut line, &mut out);
+        }
+        out
+    }
+
+    /// Lay out one line of text at the cursor and advance past it.
+    fn text_line(&mut self, line: &Line, size: f64, indent: f64, width: f64, align: Alignment) {
+        let size = line.size(size);
+        let height = size * LEADING;
+        self.ensure(height);
+        let baseline = self.y - size;
+        let x = self.left()
+            + indent
+            + match align {
+                Alignment::Right => width - line.width,
+                Alignment::Center => (width - line.width) / 2.0,
+                Alignment::None | Alignment::Left => 0.0,
+            };
+        self.decorate_line(height);
+        if let Some((marker, marker_x)) = self.marker.take() {
+            let mut marker_line = Line::default();
+            marker_line.push(Piece {
+                width: self.fonts.width(Face::Regular, &marker, BODY_SIZE),
+                text: marker,
+                face: Face::Regular,
+                size: BODY_SIZE,
+                link: None,
+                strike: false,
+            });
+            self.draw_line(&marker_line, marker_x, baseline);
+        }
+        self.draw_line(line, x, baseline);
+        self.y -= height;
+    }
+
+    /// Quote bars beside a line of `height` at the cursor.
+    fn decorate_line(&mut self, height: f64) {
+        let y = self.y - height;
+        let bars: Vec<String> = self
+            .quote_bars
+            .iter()
+            .map(|x| format!("0.8 g {x:.2} {y:.2} 2 {height:.2} re f 0 g\n"))
+            .collect();
+        for bar in bars {
+            self.page().content.push_str(&bar);
+        }
+    }
+
+    /// Emit the text of a line with its baseline at `y`, plus link
+    /// underlines, strike-through rules and link annotations.
+    fn draw_line(&mut self, line: &Line, x: f64, y: f64) {
+        if line.pieces.is_empty() {
+            return;
+        }
+        let gray = self.gray;
+        let mut text = format!("BT\n{x:.2} {y:.2} Td\n");
+        let mut rules = String::new();
+        let mut links = Vec::new();
+        let mut cursor = x;
+        let mut current: Option<(Face, f64)> = None;
+        for piece in &line.pieces {
+            if current != Some((piece.face, piece.size)) {
+                text.push_str(&format!(
+                    "/{} {} Tf\n",
+                    piece.face.resource(),
+                    fmt_num(piece.size)
+                ));
+                current = Some((piece.face, piece.size));
+            }
+            let color = match &piece.link {
+                Some(_) => LINK_COLOR.to_string(),
+                None => format!("{gray} {gray} {gray}"),
+            };
+            let encoded = self.fonts.encode(piece.face, &piece.text);
+            text.push_str(&format!("{color} rg {encoded} Tj\n"));
+
+            let thickness = piece.size / 18.0;
+            if let Some(url) = &piece.link {
+                rules.push_str(&format!(
+                    "{LINK_COLOR} rg {cursor:.2} {:.2} {:.2} {thickness:.2} re f\n",
+                    y - piece.size * 0.12,
+                    piece.width
+                ));
+                links.push((
+                    [
+                        cursor,
+                        y - piece.size * 0.25,
+                        cursor + piece.width,
+                        y + piece.size * 0.85,
+                    ],
+                    url.clone(),
+                ));
+            }
+            if piece.strike {
+                rules.push_str(&format!(
+                    "{color} rg {cursor:.2} {:.2} {:.2} {thickness:.2} re f\n",
+                    y + piece.size * 0.3,
+                    piece.width
+                ));
+            }
+            cursor += piece.width;
+        }
+        text.push_str("ET\n");
+        if !rules.is_empty() {
+            rules.push_str("0 g\n");
+        }
+
+        let page = self.page();
+        page.content.push_str(&text);
+        page.content.push_str(&rules);
+        page.links.extend(links);
+    }
+
+    fn list(&mut self, start: Option<u64>, items: &[crate::document::ListItem], indent: f64) {
+        let depth = (indent / LIST_INDENT) as usize;
+        for (i, item) in items.iter().enumerate() {
+            let mut marker = match start {
+                Some(start) => format!("{}.", start + i as u64),
+                None if depth.is_multiple_of(2) => "•".to_string(),
+                None => "–".to_string(),
+            };
+            if let Some(checked) = item.checked {
+                marker = if checked { "[x]" } else { "[ ]" }.to_string();
+            }
+            let marker_width = self.fonts.width(Face::Regular, &marker, BODY_SIZE);
+            let item_indent = indent + LIST_INDENT.max(marker_width + 6.0);
+            self.marker = Some((marker, self.left() + item_indent - marker_width - 6.0));
+
+            for (j, node) in item.blocks.iter().enumerate() {
+                match node {
+                    // List text stays tight; the gap goes after the item.
+                    Node::Paragraph { content } => {
+                        if j > 0 {
+                            self.gap(PARAGRAPH_GAP / 2.0);
+                        }
+                        self.paragraph(content, TextStyle::default(), BODY_SIZE, item_indent)
+                    }
+                    Node::List { start, items } => self.list(*start, items, item_indent),
+                    other => self.block(other, item_indent),
+                }
+            }
+            // An empty item still shows its marker.
+            if self.marker.is_some() {
+                self.text_line(
+                    &Line::default(),
+                    BODY_SIZE,
+                    item_indent,
+                    0.0,
+                    Alignment::None,
+                );
+            }
+            self.gap(PARAGRAPH_GAP / 2.0);
+        }
+        if depth == 0 {
+            self.gap(PARAGRAPH_GAP / 2.0);
+        }
+    }
+
+    fn code_block(&mut self, code: &str, indent: f64) {
+        let pad = 6.0;
+        let x = self.left() + indent;
+        let width = self.text_width() - indent;
+        let height = CODE_SIZE * 1.3;
+        let code = code.strip_suffix('\n').unwrap_or(code);
+
+        self.code_background(x, width, pad);
+        for source_line in code.split('\n') {
+            let source_line = source_line.replace('\t', "    ");
+            // Break long lines between characters; code has no word breaks
+            // worth preferring.
+            let mut lines = vec![Line::default()];
+            for c in source_line.chars() {
+                let piece = Piece {
+                    text: c.to_string(),
+                    face: Face::Mono,
+                    size: CODE_SIZE,
+                    width: self.fonts.width(Face::Mono, &c.to_string(), CODE_SIZE),
+                    link: None,
+                    strike: false,
+                };
+                let line = lines.last_mut().expect("lines is never empty");
+                if line.width + piece.width > width - pad * 2.0 && !line.pieces.is_empty() {
+                    lines.push(Line::default());
+                }
+                lines.last_mut().expect("lines is never empty").push(piece);
+            }
+            for line in lines {
+                if self.y - height < self.bottom() {
+                    self.new_page();
+                }
+                self.code_background(x, width, height);
+                let baseline = self.y - CODE_SIZE;
+                self.decorate_line(height);
+                self.draw_line(&line, x + pad, baseline);
+                self.y -= height;
+            }
+        }
+        self.code_background(x, width, pad);
+    }
+
+    /// A strip of code-block background `height` tall at the cursor; padding
+    /// strips also advance the cursor.
+    fn code_background(&mut self, x: f64, width: f64, height: f64) {
+        let y = self.y - height;
+        self.page().content.push_str(&format!(
+            "0.95 g {x:.2} {y:.2} {width:.2} {height:.2} re f 0 g\n"
+        ));
+        if height < CODE_SIZE {
+            self.y -= height;
+        }
+    }
+
+    fn table(
+        &mut self,
+        header: &[crate::document::Cell],
+        rows: &[Vec<crate::document::Cell>],
+        alignments: &[Alignment],
+        indent: f64,
+    ) {
+        let columns = header
+            .len()
+            .max(rows.iter().map(Vec::len).max().unwrap_or(0));
+        if columns == 0 {
+            return;
+        }
+        let available = self.text_width() - indent;
+        let bold = TextStyle {
+            bold: true,
+            ..TextStyle::default()
+        };
+
+        // Size columns to their widest unwrapped cell, then fit the total
+        // to the available width.
+        let mut natural = vec![24.0_f64; columns];
+        for (row, style) in std::iter::once((header, bold))
+            .chain(rows.iter().map(|r| (r.as_slice(), TextStyle::default())))
+        {
+            for (i, cell) in row.iter().enumerate() {
+                let width = self
+                    .flow(&spans(cell), style, TABLE_SIZE, f64::INFINITY)
+                    .iter()
+                    .map(|flow| match flow {
+                        Flow::Line(line) => line.width,
+                        Flow::Image(_) => 0.0,
+                    })
+                    .fold(0.0, f64::max);
+                natural[i] = natural[i].max(width + CELL_PADDING * 2.0);
+            }
+        }
+        let total: f64 = natural.iter().sum();
+        let widths: Vec<f64> = if total <= available {
+            let extra = (available - total) / columns as f64;
+            natural.iter().map(|w| w + extra).collect()
+        } else {
+            natural.iter().map(|w| w * available / total).collect()
+        };
+        let align = |i: usize| alignments.get(i).copied().unwrap_or_default();
+
+        let header_lines = self.cell_lines(header, bold, &widths);
+        self.table_row(&header_lines, &widths, align, indent, Some(0.9));
+        for (i, row) in rows.iter().enumerate() {
+            let lines = self.cell_lines(row, TextStyle::default(), &widths);
+            let height = row_height(&lines);
+            if self.y - height < self.bottom() {
+                self.new_page();
+                self.table_row(&header_lines, &widths, align, indent, Some(0.9));
+            }
+            let fill = (i % 2 == 0).then_some(0.96);
+            self.table_row(&lines, &widths, align, indent, fill);
+        }
+    }
+
+    fn cell_lines(
+        &self,
+        row: &[crate::document::Cell],
+        style: TextStyle,
+        widths: &[f64],
+    ) -> Vec<Vec<Line>> {
+        widths
+            .iter()
+            .enumerate()
+            .map(|(i, width)| {
+                let Some(cell) = row.get(i) else {
+                    return Vec::new();
+                };
+                // Images in cells show as their alt text.
+                let content: Vec<Inline> = cell
+                    .iter()
+                    .map(|inline| match inline {
+                        Inline::Image(image) => Inline::Text(image.alt.clone()),
+                        other => other.clone(),
+                    })
+                    .collect();
+                self.flow(
+                    &spans(&content),
+                    style,
+                    TABLE_SIZE,
+                    width - CELL_PADDING * 2.0,
+                )
+                .into_iter()
+                .filter_map(|flow| match flow {
+                    Flow::Line(line) => Some(line),
+                    Flow::Image(_) => None,
+                })
+                .collect()
+            })
+            .collect()
+    }
+
+    fn table_row(
+        &mut self,
+        cells: &[Vec<Line>],
+        widths: &[f64],
+        align: impl Fn(usize) -> Alignment,
+        indent: f64,
+        fill: Option<f64>,
+    ) {
+        let height = row_height(cells);
+        if self.y - height < self.bottom() && !self.at_page_top() {
+            self.new_page();
+        }
+        let x0 = self.left() + indent;
+        let total: f64 = widths.iter().sum();
+        let top = self.y;
+        let bottom = top - height;
+
+        let mut content = String::new();
+        if let Some(gray) = fill {
+            content.push_str(&format!(
+                "{gray} g {x0:.2} {bottom:.2} {total:.2} {height:.2} re f 0 g\n"
+            ));
+        }
+        self.page().content.push_str(&content);
+
+        // Line by line across the row, so text reads in order.
+        let line_height = TABLE_SIZE * 1.2;
+        let depth = cells.iter().map(Vec::len).max().unwrap_or(0);
+        for n in 0..depth {
+            let baseline = top - CELL_PADDING - TABLE_SIZE * 0.95 - n as f64 * line_height;
+            let mut x = x0;
+            for (i, width) in widths.iter().enumerate() {
+                if let Some(line) = cells.get(i).and_then(|lines| lines.get(n)) {
+                    let inner = width - CELL_PADDING * 2.0;
+                    let offset = match align(i) {
+                        Alignment::Right => inner - line.width,
+                        Alignment::Center => (inner - line.width) / 2.0,
+                        Alignment::None | Alignment::Left => 0.0,
+                    };
+                    self.draw_line(line, x + CELL_PADDING + offset.max(0.0), baseline);
+                }
+                x += width;
+            }
+        }
+
+        let mut borders = format!("0.6 G 0.5 w {x0:.2} {bottom:.2} {total:.2} {height:.2} re S\n");
+        let mut x = x0;
+        for width in &widths[..widths.len() - 1] {
+            x += width;
+            borders.push_str(&format!("{x:.2} {bottom:.2} m {x:.2} {top:.2} l S\n"));
+        }
+        borders.push_str("0 G\n");
+        self.page().content.push_str(&borders);
+        self.y = bottom;
+    }
+
+    fn image(&mut self, image: &Image, indent: f64) {
+        let Some(pdf_image) = image.data.as_deref().and_then(pdf_image) else {
+            // Nothing embeddable: show the alt text instead.
+            let label = if image.alt.is_empty() {
+                image.src.clone()
+            } else {
+                image.alt.clone()
+            };
+            let italic = TextStyle {
+                italic: true,
+                ..TextStyle::default()
+            };
+            self.paragraph(
+                &[Inline::Text(format!("[{label}]"))],
+                italic,
+                BODY_SIZE,
+                indent,
+            );
+            return;
+        };
+
+        // Pixels at 96 dpi, shrunk to fit the text column and at most
+        // two thirds of the page height.
+        let max_width = self.text_width() - indent;
+        let max_height = (self.top() - self.bottom()) * 2.0 / 3.0;
+        let mut width = f64::from(pdf_image.width) * 0.75;
+        let mut height = f64::from(pdf_image.height) * 0.75;
+        let scale = (max_width / width).min(max_height / height).min(1.0);
+        width *= scale;
+        height *= scale;
+
+        self.ensure(height + 4.0);
+        let x = self.left() + indent;
+        let y = self.y - height;
+        let index = self.images.len();
+        self.images.push(pdf_image);
+        let page = self.page();
+        page.images.push(index);
+        page.content.push_str(&format!(
+            "q {width:.2} 0 0 {height:.2} {x:.2} {y:.2} cm /Im{} Do Q\n",
+            index + 1
+        ));
+        self.y = y - 4.0;
+    }
+
+    /// "n / N" at the foot of every page, when there is more than one.
+    fn number_pages(&mut self) {
+        let count = self.pages.len();
+        if !self.options.page_numbers || count < 2 {
+            return;
+        }
+        let size = 9.0;
+        let y = self.options.margin / 2.0;
+        for i in 0..count {
+            let label = format!("{} / {count}", i + 1);
+            let width = self.fonts.width(Face::Regular, &label, size);
+            let x = (self.options.page_size.0 - width) / 2.0;
+            let encoded = self.fonts.encode(Face::Regular, &label);
+            self.pages[i].content.push_str(&format!(
+                "BT\n/{} {size} Tf\n0.4 0.4 0.4 rg\n{x:.2} {y:.2} Td\n{encoded} Tj\nET\n0 g\n",
+                Face::Regular.resource()
+            ));
+        }
+    }
+
+    /// Assemble the laid-out pages into a PDF file.
+    fn finish(self, title: &str) -> Vec<u8> {
+        let mut pdf = PdfWriter::new();
+        let catalog = pdf.reserve();
+        let pages_id = pdf.reserve();
+
+        // Fonts: one object per face used, embedded TrueType fonts shared
+        // between faces that fall back to the same file.
+        let mut font_refs: Vec<(Face, u32)> = Vec::new();
+        match self.fonts.set {
+            None => {
+                let used: HashSet<Face> = FACES
+                    .into_iter()
+                    .filter(|face| {
+                        self.pages
+                            .iter()
+                            .any(|page| page.content.contains(&format!("/{} ", face.resource())))
+                    })
+                    .collect();
+                for face in FACES.into_iter().filter(|face| used.contains(face)) {
+                    let id = pdf.add(format!(
+                        "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+                        face.standard_font()
+                    ));
+                    font_refs.push((face, id));
+                }
+            }
+            Some(set) => {
+                let mut embedded: Vec<(*const TrueTypeFont, BTreeMap<u16, char>, Vec<Face>)> =
+                    Vec::new();
+                for face in FACES {
+                    let used = &self.fonts.used[face.index()];
+                    if used.is_empty() {
+                        continue;
+                    }
+                    let font = Arc::as_ptr(set.face(face));
+                    match embedded.iter_mut().find(|(ptr, _, _)| *ptr == font) {
+                        Some((_, glyphs, faces)) => {
+                            glyphs.extend(used.iter().map(|(g, c)| (*g, *c)));
+                            faces.push(face);
+                        }
+                        None => embedded.push((font, used.clone(), vec![face])),
+                    }
+                }
+                for (_, glyphs, faces) in embedded {
+                    let font = set.face(faces[0]);
+                    let id = embed_truetype(&mut pdf, font, &glyphs);
+                    font_refs.extend(faces.into_iter().map(|face| (face, id)));
+                }
+            }
+        }
+        let font_dict: String = font_refs
+            .iter()
+            .map(|(face, id)| format!("/{} {id} 0 R ", face.resource()))
+            .collect();
+
+        let image_ids: Vec<u32> = self
+            .images
+            .iter()
+            .map(|image| {
+                let smask = image.soft_mask.as_ref().map(|mask| {
+                    pdf.add_stream(
+                        &format!(
+                            "/Type /XObject /Subtype /Image /Width {} /Height {} \
+                             /ColorSpace /DeviceGray /BitsPerComponent 8 /Filter /FlateDecode",
+                            image.width, image.height
+                        ),
+                        mask,
+                    )
+                });
+                let smask = smask
+                    .map(|id| format!(" /SMask {id} 0 R"))
+                    .unwrap_or_default();
+                pdf.add_stream(
+                    &format!(
+                        "/Type /XObject /Subtype /Image /Width {} /Height {} {}{smask}",
+                        image.width, image.height, image.dict
+                    ),
+                    &image.data,
+                )
+            })
+            .collect();
+
+        let (width, height) = self.options.page_size;
+        let mut kids = Vec::new();
+        for page in &self.pages {
+            let content = pdf.add_stream("", page.content.as_bytes());
+            let annots: Vec<u32> = page
+                .links
+                .iter()
+                .map(|([x1, y1, x2, y2], url)| {
+                    pdf.add(format!(
+                        "<< /Type /Annot /Subtype /Link /Rect [{x1:.2} {y1:.2} {x2:.2} {y2:.2}] \
+                         /Border [0 0 0] /A << /Type /Action /S /URI /URI {} >> >>",
+                        pdf_text_string(url)
+                    ))
+                })
+                .collect();
+            let annots = if annots.is_empty() {
+                String::new()
+            } else {
+                let refs: Vec<String> = annots.iter().map(|id| format!("{id} 0 R")).collect();
+                format!("/Annots [{}] ", refs.join(" "))
+            };
+            let xobjects = if page.images.is_empty() {
+                String::new()
+            } else {
+                let refs: String = page
+                    .images
+                    .iter()
+                    .map(|&i| format!("/Im{} {} 0 R ", i + 1, image_ids[i]))
+                    .collect();
+                format!("/XObject << {refs}>> ")
+            };
+            kids.push(pdf.add(format!(
+                "<< /Type /Page /Parent {pages_id} 0 R /MediaBox [0 0 {} {}] \
+                 /Contents {content} 0 R {annots}/Resources << /Font << {font_dict}>> {xobjects}>> >>",
+                fmt_num(width),
+                fmt_num(height)
+            )));
+        }
+
+        let kids: Vec<String> = kids.iter().map(|id| format!("{id} 0 R")).collect();
+        pdf.set(
+            pages_id,
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                kids.len()
+            ),
+        );
+        pdf.set(
+            catalog,
+            format!("<< /Type /Catalog /Pages {pages_id} 0 R >>"),
+        );
+        let info = pdf.add(format!(
+            "<< /Title {} /Producer (Hive) >>",
+            pdf_text_string(title)
+        ));
+        pdf.finish(catalog, info)
+    }
+}
+
+fn row_height(cells: &[Vec<Line>]) -> f64 {
+    let depth = cells.iter().map(Vec::len).max().unwrap_or(0).max(1);
+    depth as f64 * TABLE_SIZE * 1.2 + CELL_PADDING * 2.0 - 2.0
+}
+
+/// Split text into alternating runs of whitespace and non-whitespace.
+fn tokens(text: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    let mut in_space: Option<bool> = None;
+    for (i, c) in text.char_indices() {
+        let space = c.is_whitespace();
+        if in_space.is_some_and(|s| s != space) {
+            out.push(&text[start..i]);
+            start = i;
+        }
+        in_space = Some(space);
+    }
+    if start < text.len() {
+        out.push(&text[start..]);
+    }
+    out
+}
+
+/// A number without trailing zeros.
+fn fmt_num(value: f64) -> String {
+    let text = format!("{value:.2}");
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// A PDF text string: a literal when ASCII, UTF-16BE hex otherwise.
+fn pdf_text_string(s: &str) -> String {
+    if s.chars().all(|c| (' '..='~').contains(&c)) {
+        return format!("({})", pdf_escape(s));
+    }
+    let mut hex = String::from("<FEFF");
+    for unit in s.encode_utf16() {
+        hex.push_str(&format!("{unit:04X}"));
+    }
+    hex.push('>');
+    hex
+}
+
+/// Write a TrueType font as a Type0 font with Identity-H encoding, so
+/// character codes are glyph IDs, and return the font's object ID.
+fn embed_truetype(pdf: &mut PdfWriter, font: &TrueTypeFont, glyphs: &BTreeMap<u16, char>) -> u32 {
+    let file = pdf.add_stream(
+        &format!("/Length1 {} /Filter /FlateDecode", font.data.len()),
+        &deflate(&font.data),
+    );
+
+    let mut flags = 32; // Nonsymbolic
+    if font.monospaced {
+        flags |= 1;
+    }
+    if font.italic_angle != 0.0 {
+        flags |= 64;
+    }
+    let [x_min, y_min, x_max, y_max] = font.bbox;
+    let descriptor = pdf.add(format!(
+        "<< /Type /FontDescriptor /FontName /{} /Flags {flags} /FontBBox [{} {} {} {}] \
+         /ItalicAngle {} /Ascent {} /Descent {} /CapHeight {} /StemV 80 /FontFile2 {file} 0 R >>",
+        font.name,
+        font.scaled(x_min),
+        font.scaled(y_min),
+        font.scaled(x_max),
+        font.scaled(y_max),
+        fmt_num(f64::from(font.italic_angle)),
+        font.scaled(font.ascent),
+        font.scaled(font.descent),
+        font.scaled(font.cap_height),
+    ));
+
+    let widths: Vec<String> = glyphs
+        .keys()
+        .map(|&glyph| format!("{glyph} [{}]", font.width(glyph).round()))
+        .collect();
+    let cid_font = pdf.add(format!(
+        "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /{} \
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+         /FontDescriptor {descriptor} 0 R /DW 1000 /W [{}] /CIDToGIDMap /Identity >>",
+        font.name,
+        widths.join(" ")
+    ));
+
+    let mut cmap = String::from(
+        "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+         /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+         1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+    );
+    let entries: Vec<(&u16, &char)> = glyphs.iter().filter(|(glyph, _)| **glyph != 0).collect();
+    for chunk in entries.chunks(100) {
+        cmap.push_str(&format!("{} beginbfchar\n", chunk.len()));
+        for (glyph, c) in chunk {
+            let utf16: String = c
+                .encode_utf16(&mut [0; 2])
+                .iter()
+                .map(|unit| format!("{unit:04X}"))
+                .collect();
+            cmap.push_str(&format!("<{glyph:04X}> <{utf16}>\n"));
+        }
+        cmap.push_str("endbfchar\n");
+    }
+    cmap.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+    let to_unicode = pdf.add_stream("", cmap.as_bytes());
+
+    pdf.add(format!(
+        "<< /Type /Font /Subtype /Type0 /BaseFont /{} /Encoding /Identity-H \
+         /DescendantFonts [{cid_font} 0 R] /ToUnicode {to_unicode} 0 R >>",
+        font.name
+    ))
+}
+
+fn deflate(data: &[u8]) -> Vec<u8> {
+    use flate2::{Compression, write::ZlibEncoder};
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    // Writing to a Vec cannot fail.
+    encoder.write_all(data).expect("in-memory write");
+    encoder.finish().expect("in-memory write")
+}
+
+/// Convert PNG or JPEG data into an image XObject. JPEGs pass through
+/// as-is; PNGs are re-encoded, with any alpha channel split into a soft
+/// mask. Interlaced and 16-bit PNGs with alpha are not supported.
+fn pdf_image(data: &[u8]) -> Option<PdfImage> {
+    let info = image_info(data)?;
+    match info.format {
+        ImageFormat::Jpeg => {
+            let components = jpeg_components(data)?;
+            let color_space = match components {
+                1 => "/DeviceGray",
+                3 => "/DeviceRGB",
+                // Adobe CMYK JPEGs store inverted values.
+                4 => "/DeviceCMYK /Decode [1 0 1 0 1 0 1 0]",
+                _ => return None,
+            };
+            Some(PdfImage {
+                width: info.width,
+                height: info.height,
+                dict: format!("/ColorSpace {color_space} /BitsPerComponent 8 /Filter /DCTDecode"),
+                data: data.to_vec(),
+                soft_mask: None,
+            })
+        }
+        ImageFormat::Png => png_image(data),
+    }
+}
+
+fn jpeg_components(data: &[u8]) -> Option<u8> {
+    let mut i = 2;
+    while i + 4 <= data.len() {
+        let marker = data[i + 1];
+        let length = usize::from(u16::from_be_bytes([data[i + 2], data[i + 3]]));
+        if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            return data.get(i + 9).copied();
+        }
+        i += 2 + length;
+    }
+    None
+}
+
+fn png_image(data: &[u8]) -> Option<PdfImage> {
+    let mut pos = 8;
+    let mut header = None;
+    let mut palette = Vec::new();
+    let mut idat = Vec::new();
+    while pos + 8 <= data.len() {
+        let length = u32::from_be_bytes(data[pos..pos + 4].try_into().ok()?) as usize;
+        let kind = &data[pos + 4..pos + 8];
+        let body = data.get(pos + 8..pos + 8 + length)?;
+        match kind {
+            b"IHDR" if length >= 13 => header = Some((body[8], body[9], body[12])),
+            b"PLTE" => palette = body.to_vec(),
+            b"IDAT" => idat.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+        pos += 12 + length;
+    }
+    let (depth, color_type, interlace) = header?;
+    if interlace != 0 {
+        return None;
+    }
+    let info = image_info(data)?;
+    let (width, height) = (info.width, info.height);
+
+    let (colors, color_space) = match color_type {
+        0 | 4 => (1, "/DeviceGray".to_string()),
+        2 | 6 => (3, "/DeviceRGB".to_string()),
+        3 => {
+            let hex: String = palette.iter().map(|b| format!("{b:02X}")).collect();
+            let entries = (palette.len() / 3).max(1);
+            (1, format!("[/Indexed /DeviceRGB {} <{hex}>]", entries - 1))
+        }
+        _ => return None,
+    };
+
+    if matches!(color_type, 0 | 2 | 3) {
+        // PDF understands PNG row filters, so the compressed data can be
+        // used directly.
+        return Some(PdfImage {
+            width,
+            height,
+            dict: format!(
+                "/ColorSpace {color_space} /BitsPerComponent {depth} /Filter /FlateDecode \
+                 /DecodeParms << /Predictor 15 /Colors {colors} /BitsPerComponent {depth} \
+                 /Columns {width} >>"
+            ),
+            data: idat,
+            soft_mask: None,
+        });
+    }
+
+    if depth != 8 {
+        return None;
+    }
+    let mut raw = Vec::new();
+    flate2::read::ZlibDecoder::new(idat.as_slice())
+        .take(MAX_STREAM_SIZE)
+        .read_to_end(&mut raw)
+        .ok()?;
+    let channels = colors + 1;
+    let pixels = unfilter_png(&raw, width as usize, height as usize, channels)?;
+    let mut color = Vec::with_capacity(pixels.len() / channels * colors);
+    let mut alpha = Vec::with_capacity(pixels.len() / channels);
+    for pixel in pixels.chunks(channels) {
+        color.extend_from_slice(&pixel[..colors]);
+        alpha.push(pixel[colors]);
+    }
+    Some(PdfImage {
+        width,
+        height,
+        dict: format!("/ColorSpace {color_space} /BitsPerComponent 8 /Filter /FlateDecode"),
+        data: deflate(&color),
+        soft_mask: Some(deflate(&alpha)),
+    })
+}
+
+/// Undo PNG row filters for 8-bit samples.
+fn unfilter_png(raw: &[u8], width: usize, height: usize, channels: usize) -> Option<Vec<u8>> {
+    let stride = width * channels;
+    let mut out = vec![0u8; stride * height];
+    for row in 0..height {
+        let line = raw.get(row * (stride + 1)..(row + 1) * (stride + 1))?;
+        let (filter, line) = (line[0], &line[1..]);
+        for i in 0..stride {
+            let left = if i >= channels {
+                out[row * stride + i - channels]
+            } else {
+                0
+            };
+            let up = if row > 0 {
+                out[(row - 1) * stride + i]
+            } else {
+                0
+            };
+            let up_left = if row > 0 && i >= channels {
+                out[(row - 1) * stride + i - channels]
+            } else {
+                0
+            };
+            let predicted = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((u16::from(left) + u16::from(up)) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => return None,
+            };
+            out[row * stride + i] = line[i].wrapping_add(predicted);
+        }
+    }
+    Some(out)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = i16::from(a) + i16::from(b) - i16::from(c);
+    let (pa, pb, pc) = (
+        (p - i16::from(a)).abs(),
+        (p - i16::from(b)).abs(),
+        (p - i16::from(c)).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Collects numbered objects and writes them out with a cross-reference
+/// table.
+struct PdfWriter {
+    objects: Vec<Option<Vec<u8>>>,
+}
+
+impl PdfWriter {
+    fn new() -> Self {
+        Self {
+            objects: Vec::new(),
+        }
+    }
+
+    /// Allocate an object number to fill in later with [`Self::set`].
+    fn reserve(&mut self) -> u32 {
+        self.objects.push(None);
+        self.objects.len() as u32
+    }
+
+    fn set(&mut self, id: u32, body: String) {
+        self.objects[id as usize - 1] = Some(body.into_bytes());
+    }
+
+    fn add(&mut self, body: String) -> u32 {
+        self.objects.push(Some(body.into_bytes()));
+        self.objects.len() as u32
+    }
+
+    fn add_stream(&mut self, dict: &str, data: &[u8]) -> u32 {
+        let separator = if dict.is_empty() { "" } else { " " };
+        let mut body =
+            format!("<< /Length {}{separator}{dict} >>\nstream\n", data.len()).into_bytes();
+        body.extend_from_slice(data);
+        body.extend_from_slice(b"\nendstream");
+        self.objects.push(Some(body));
+        self.objects.len() as u32
+    }
+
+    fn finish(self, root: u32, info: u32) -> Vec<u8> {
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::with_capacity(self.objects.len());
+        for (i, body) in self.objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+            pdf.extend_from_slice(body.as_deref().unwrap_or(b"null"));
+            pdf.extend_from_slice(b"\nendobj\n");
+        }
+
+        let xref_offset = pdf.len();
+        let size = offsets.len() + 1;
+        let mut xref = format!("xref\n0 {size}\n0000000000 65535 f \n");
+        for offset in offsets {
+            xref.push_str(&format!("{offset:010} 00000 n \n"));
+        }
+        xref.push_str(&format!(
+            "trailer\n<< /Size {size} /Root {root} 0 R /Info {info} 0 R >>\nstartxref\n{xref_offset}\n%%EOF\n"
+        ));
+        pdf.extend_from_slice(xref.as_bytes());
+        pdf
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Extraction
+// ---------------------------------------------------------------------------
+
+/// Largest decoded stream the reader will produce (64 MiB).
+const MAX_STREAM_SIZE: u64 = 64 * 1024 * 1024;
+
+/// How deeply page trees, references and form XObjects may nest.
+const MAX_DEPTH: usize = 32;
+
+/// How deeply arrays and dictionaries may nest inside one object. Deeper
+/// input is cut off rather than recursing until the stack overflows.
+const MAX_NESTING: usize = 64;
+
+/// Extract text from a PDF, one section per page anchored as "page N".
+///
+/// Reads plain and Flate-compressed content streams, object streams and
+/// form XObjects. Text is decoded through each font's ToUnicode map, or as
+/// WinAnsi when a font has none. Lines set noticeably larger than the
+/// page's body text become headings; runs of closely spaced lines become
+/// paragraphs. Encrypted files are rejected, and scanned pages without a
+/// text layer come out empty.
+pub fn extract_pdf(bytes: &[u8]) -> Result<ExtractedDocument> {
+    if !bytes.starts_with(b"%PDF") {
+        bail!("Not a PDF file");
+    }
+    let file = PdfFile::parse(bytes)?;
+    if file.encrypted {
+        bail!("Encrypted PDFs are not supported");
+    }
+
+    let mut document = ExtractedDocument::new(DocumentFormat::Pdf);
+    for (i, page) in file.pages().iter().enumerate() {
+        let mut section = Section::new(Some(Anchor::Page { number: i + 1 }));
+        let lines = file.page_lines(page);
+        section.blocks = lines_to_blocks(&lines);
+        document.sections.push(section);
+    }
+    Ok(document)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Object {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Real(f64),
+    Name(String),
+    Str(Vec<u8>),
+    Array(Vec<Object>),
+    Dict(Dict),
+    Ref(u32),
+    /// A stream dictionary and its still-encoded data.
+    Stream(Dict, Vec<u8>),
+}
+
+type Dict = HashMap<String, Object>;
+
+static NULL: Object = Object::Null;
+
+impl Object {
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Object::Int(i) => Some(*i as f64),
+            Object::Real(r) => Some(*r),
+            _ => None,
+        }
+    }
+
+    fn as_name(&self) -> Option<&str> {
+        match self {
+            Object::Name(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    fn dict(&self) -> Option<&Dict> {
+        match self {
+            Object::Dict(dict) | Object::Stream(dict, _) => Some(dict),
+            _ => None,
+        }
+    }
+}
+
+/// A token from a content stream or object body.
+#[derive(Debug)]
+enum Token {
+    Object(Object),
+    Keyword(String),
+    ArrayEnd,
+    DictEnd,
+}
+
+/// Tokenizer over PDF syntax.
+struct Lexer<'a> {
+    data: &'a [u8],
+    pos: usize,
+    /// Arrays and dictionaries currently open.
+    depth: usize,
+}
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b'\0' | b'\t' | b'\n' | b'\x0c' | b'\r' | b' ')
+}
+
+fn is_delimiter(b: u8) -> bool {
+    matches!(
+        b,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+impl<'a> Lexer<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        Self {
+            data,
+            pos,
+            depth: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b) = self.peek() {
+            if is_whitespace(b) {
+                self.pos += 1;
+            } else if b == b'%' {
+                while let Some(b) = self.peek() {
+                    if b == b'\n' || b == b'\r' {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn regular_run(&mut self) -> &'a [u8] {
+        let start = self.pos;
+        while let Some(b) = self.peek() {
+            if is_whitespace(b) || is_delimiter(b) {
+                break;
+            }
+            self.pos += 1;
+        }
+        &self.data[start..self.pos]
+    }
+
+    /// Next token, or `None` at end of input or when arrays and dictionaries
+    /// nest deeper than [`MAX_NESTING`]. `refs` enables `N G R` references,
+    /// which only occur outside content streams.
+    fn token(&mut self, refs: bool) -> Option<Token> {
+        self.skip_whitespace();
+        let b = self.peek()?;
+        let token = match b {
+            b'/' => {
+                self.pos += 1;
+                Token::Object(Object::Name(decode_name(self.regular_run())))
+            }
+            b'(' => Token::Object(Object::Str(self.literal_string())),
+            b'<' if self.data.get(self.pos + 1) == Some(&b'<') => {
+                if self.depth >= MAX_NESTING {
+                    return None;
+                }
+                self.pos += 2;
+                self.depth += 1;
+                let dict = self.dict_body(refs);
+                self.depth -= 1;
+                Token::Object(dict)
+            }
+            b'<' => Token::Object(Object::Str(self.hex_string())),
+            b'>' if self.data.get(self.pos + 1) == Some(&b'>') => {
+                self.pos += 2;
+                Token::DictEnd
+            }
+            b'[' => {
+                if self.depth >= MAX_NESTING {
+                    return None;
+                }
+                self.pos += 1;
+                self.depth += 1;
+                let mut items = Vec::new();
+                while let Some(token) = self.token(refs) {
+                    match token {
+                        Token::Object(obj) => items.push(obj),
+                        Token::ArrayEnd => break,
+                        // Stray keywords and closers inside arrays are skipped.
+                        Token::Keyword(_) | Token::DictEnd => {}
+                    }
+                }
+                self.depth -= 1;
+                Token::Object(Object::Array(items))
+            }
+            b']' => {
+                self.pos += 1;
+                Token::ArrayEnd
+            }
+            b'{' | b'}' | b')' | b'>' => {
+                self.pos += 1;
+                Token::Keyword((b as char).to_string())
+            }
+            _ => {
+                let word = self.regular_run();
+                if word.is_empty() {
+                    // Unreachable for well-formed input; never stall on it.
+                    self.pos += 1;
+                    return Some(Token::Keyword(String::new()));
+                }
+                match parse_number(word) {
+                    Some(number) => {
+                        let reference = match number {
+                            Object::Int(n) if refs => {
+                                u32::try_from(n).ok().and_then(|n| self.reference(n))
+                            }
+                            _ => None,
+                        };
+                        Token::Object(reference.unwrap_or(number))
+                    }
+                    None => match word {
+                        b"true" => Token::Object(Object::Bool(true)),
+                        b"false" => Token::Object(Object::Bool(false)),
+                        b"null" => Token::Object(Object::Null),
+                        _ => Token::Keyword(String::from_utf8_lossy(word).into_owned()),
+                    },
+                }
+            }
+        };
+        Some(token)
+    }
 
-use anyhow::Result;
+    /// After an integer, try to read `G R`; rewinds when it isn't a reference.
+    fn reference(&mut self, num: u32) -> Option<Object> {
+        let save = self.pos;
+        self.skip_whitespace();
+        let generation = self.regular_run();
+        if !generation.is_empty() && generation.iter().all(u8::is_ascii_digit) {
+            self.skip_whitespace();
+            if self.regular_run() == b"R" {
+                return Some(Object::Ref(num));
+            }
+        }
+        self.pos = save;
+        None
+    }
 
-/// Generate a PDF document with a title and a series of sections.
-///
-/// Each section has a heading (bold) and body text.
-pub fn generate_pdf_document(title: &str, sections: &[(&str, &str)]) -> Result<Vec<u8>> {
-    let mut builder = PdfBuilder::new();
+    fn object(&mut self, refs: bool) -> Option<Object> {
+        match self.token(refs)? {
+            Token::Object(obj) => Some(obj),
+            _ => None,
+        }
+    }
 
-    // Build content stream
-    let mut content = String::new();
-    let mut y = 770.0_f64; // start near top of A4 (842pt height)
+    fn dict_body(&mut self, refs: bool) -> Object {
+        let mut dict = Dict::new();
+        loop {
+            match self.token(refs) {
+                Some(Token::Object(Object::Name(key))) => {
+                    let Some(value) = self.object(refs) else {
+                        break;
+                    };
+                    dict.insert(key, value);
+                }
+                Some(Token::DictEnd) | None => break,
+                Some(_) => {}
+            }
+        }
+        Object::Dict(dict)
+    }
 
-    // Title
-    content.push_str("BT\n");
-    content.push_str("/F1 22 Tf\n");
-    content.push_str(&format!("72 {y:.0} Td\n"));
-    content.push_str(&format!("({}) Tj\n", pdf_escape(title)));
-    content.push_str("ET\n");
-    y -= 36.0;
+    fn literal_string(&mut self) -> Vec<u8> {
+        self.pos += 1;
+        let mut out = Vec::new();
+        let mut depth = 1;
+        while let Some(b) = self.peek() {
+            self.pos += 1;
+            match b {
+                b'\\' => {
+                    let Some(escaped) = self.peek() else {
+                        break;
+                    };
+                    self.pos += 1;
+                    match escaped {
+                        b'n' => out.push(b'\n'),
+                        b'r' => out.push(b'\r'),
+                        b't' => out.push(b'\t'),
+                        b'b' => out.push(0x08),
+                        b'f' => out.push(0x0c),
+                        b'0'..=b'7' => {
+                            let mut value = u32::from(escaped - b'0');
+                            for _ in 0..2 {
+                                match self.peek() {
+                                    Some(d @ b'0'..=b'7') => {
+                                        value = value * 8 + u32::from(d - b'0');
+                                        self.pos += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            out.push(value as u8);
+                        }
+                        // Line continuation.
+                        b'\r' => {
+                            if self.peek() == Some(b'\n') {
+                                self.pos += 1;
+                            }
+                        }
+                        b'\n' => {}
+                        other => out.push(other),
+                    }
+                }
+                b'(' => {
+                    depth += 1;
+                    out.push(b);
+                }
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                    out.push(b);
+                }
+                _ => out.push(b),
+            }
+        }
+        out
+    }
 
-    // Sections
-    for (heading, body) in sections {
-        y -= 16.0;
+    fn hex_string(&mut self) -> Vec<u8> {
+        self.pos += 1;
+        let start = self.pos;
+        let end = find(self.data, b">", start).unwrap_or(self.data.len());
+        self.pos = (end + 1).min(self.data.len());
+        hex_bytes(&self.data[start..end])
+    }
+}
 
-        // Heading
-        content.push_str("BT\n");
-        content.push_str("/F1 14 Tf\n");
-        content.push_str(&format!("72 {y:.0} Td\n"));
-        content.push_str(&format!("({}) Tj\n", pdf_escape(heading)));
-        content.push_str("ET\n");
-        y -= 20.0;
+/// Decode hex digits (whitespace ignored) up to an optional `>` marker; an
+/// odd final digit is padded with zero.
+fn hex_bytes(data: &[u8]) -> Vec<u8> {
+    let mut digits: Vec<u8> = data
+        .iter()
+        .take_while(|&&b| b != b'>')
+        .filter_map(|&b| (b as char).to_digit(16))
+        .map(|d| d as u8)
+        .collect();
+    if digits.len() % 2 == 1 {
+        digits.push(0);
+    }
+    digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair[1])
+        .collect()
+}
 
-        // Body lines
-        content.push_str("BT\n");
-        content.push_str("/F2 11 Tf\n");
-        content.push_str(&format!("72 {y:.0} Td\n"));
-        content.push_str("0 -15.4 Td\n"); // set leading for TL
+fn parse_number(word: &[u8]) -> Option<Object> {
+    let first = *word.first()?;
+    if !(first.is_ascii_digit() || matches!(first, b'+' | b'-' | b'.')) {
+        return None;
+    }
+    let text = std::str::from_utf8(word).ok()?;
+    if let Ok(int) = text.parse::<i64>() {
+        return Some(Object::Int(int));
+    }
+    text.parse::<f64>().ok().map(Object::Real)
+}
 
-        for line in body.lines() {
-            content.push_str(&format!("({}) Tj\n", pdf_escape(line)));
-            content.push_str("0 -15.4 Td\n");
-            y -= 15.4;
+fn decode_name(raw: &[u8]) -> String {
+    let mut out = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        if raw[i] == b'#'
+            && i + 3 <= raw.len()
+            && let Ok(hex) = std::str::from_utf8(&raw[i + 1..i + 3])
+            && let Ok(byte) = u8::from_str_radix(hex, 16)
+        {
+            out.push(byte);
+            i += 3;
+        } else {
+            out.push(raw[i]);
+            i += 1;
         }
-        content.push_str("ET\n");
     }
+    String::from_utf8_lossy(&out).into_owned()
+}
 
-    builder.set_content(&content);
-    Ok(builder.build(title))
+/// All objects in a PDF file, found by scanning for `N G obj` rather than
+/// trusting the cross-reference table, which is often damaged.
+struct PdfFile {
+    objects: HashMap<u32, Object>,
+    root: Option<u32>,
+    encrypted: bool,
 }
 
-/// Generate a PDF document containing a table with headers and rows.
-///
-/// Headers are rendered in bold; columns are evenly distributed.
-pub fn generate_pdf_table(title: &str, headers: &[&str], rows: &[Vec<String>]) -> Result<Vec<u8>> {
-    let num_cols = headers.len().max(1);
-    let usable_width = 468.0_f64; // 612 - 72*2 margins
-    let col_width = usable_width / num_cols as f64;
-
-    let mut content = String::new();
-    let mut y = 770.0_f64;
-
-    // Title
-    content.push_str("BT\n");
-    content.push_str("/F1 18 Tf\n");
-    content.push_str(&format!("72 {y:.0} Td\n"));
-    content.push_str(&format!("({}) Tj\n", pdf_escape(title)));
-    content.push_str("ET\n");
-    y -= 30.0;
-
-    let row_height = 18.0_f64;
-
-    // Header background (light gray)
-    content.push_str("0.9 0.9 0.9 rg\n"); // fill color
-    content.push_str(&format!(
-        "72 {:.0} {usable_width:.0} {row_height:.0} re f\n",
-        y - row_height
-    ));
+impl PdfFile {
+    fn parse(data: &[u8]) -> Result<Self> {
+        let mut file = Self {
+            objects: HashMap::new(),
+            root: None,
+            encrypted: false,
+        };
 
-    // Header text
-    content.push_str("0 0 0 rg\n"); // black text
-    for (i, header) in headers.iter().enumerate() {
-        let x = 72.0 + i as f64 * col_width + 4.0;
-        content.push_str("BT\n");
-        content.push_str("/F1 10 Tf\n");
-        content.push_str(&format!("{x:.0} {:.0} Td\n", y - row_height + 5.0));
-        content.push_str(&format!("({}) Tj\n", pdf_escape(header)));
-        content.push_str("ET\n");
-    }
-    y -= row_height;
-
-    // Data rows
-    for (row_idx, row) in rows.iter().enumerate() {
-        // Alternating background
-        if row_idx % 2 == 0 {
-            content.push_str("0.96 0.96 0.96 rg\n");
-            content.push_str(&format!(
-                "72 {:.0} {usable_width:.0} {row_height:.0} re f\n",
-                y - row_height
-            ));
+        let mut at = 0;
+        while let Some(offset) = find(data, b"obj", at) {
+            at = offset + 3;
+            if data
+                .get(at)
+                .is_some_and(|&b| !is_whitespace(b) && !is_delimiter(b))
+            {
+                continue;
+            }
+            let Some(num) = object_number_before(data, offset) else {
+                continue;
+            };
+            let mut lexer = Lexer::new(data, at);
+            let Some(mut obj) = lexer.object(true) else {
+                continue;
+            };
+            if let Object::Dict(dict) = &obj {
+                lexer.skip_whitespace();
+                if data[lexer.pos..].starts_with(b"stream") {
+                    let stream = stream_data(data, lexer.pos + 6, dict);
+                    at = at.max(stream.1);
+                    obj = Object::Stream(dict.clone(), stream.0.to_vec());
+                }
+            }
+            file.note_trailer_keys(&obj);
+            // Later definitions win: incremental updates append new versions.
+            file.objects.insert(num, obj);
+        }
+
+        let mut at = 0;
+        while let Some(offset) = find(data, b"trailer", at) {
+            at = offset + 7;
+            if let Some(trailer) = Lexer::new(data, at).object(true) {
+                file.note_trailer_keys(&trailer);
+            }
         }
 
-        content.push_str("0 0 0 rg\n");
-        for (col_idx, cell) in row.iter().enumerate() {
-            let x = 72.0 + col_idx as f64 * col_width + 4.0;
-            content.push_str("BT\n");
-            content.push_str("/F2 10 Tf\n");
-            content.push_str(&format!("{x:.0} {:.0} Td\n", y - row_height + 5.0));
-            content.push_str(&format!("({}) Tj\n", pdf_escape(cell)));
-            content.push_str("ET\n");
+        file.unpack_object_streams();
+        if file.objects.is_empty() {
+            bail!("No objects found in PDF");
         }
-        y -= row_height;
+        Ok(file)
     }
 
-    // Table border
-    content.push_str("0.6 0.6 0.6 RG\n"); // stroke color
-    content.push_str("0.5 w\n"); // line width
-    let table_top = 770.0 - 30.0;
-    let table_height = table_top - y;
-    content.push_str(&format!(
-        "72 {y:.0} {usable_width:.0} {table_height:.0} re S\n"
-    ));
+    /// Record `/Root` and `/Encrypt` from a trailer or cross-reference stream.
+    fn note_trailer_keys(&mut self, obj: &Object) {
+        let Some(dict) = obj.dict() else {
+            return;
+        };
+        let is_trailer = (matches!(obj, Object::Dict(_)) && dict.contains_key("Size"))
+            || dict.get("Type").and_then(Object::as_name) == Some("XRef");
+        if !is_trailer {
+            return;
+        }
+        if dict.contains_key("Encrypt") {
+            self.encrypted = true;
+        }
+        if let Some(Object::Ref(root)) = dict.get("Root") {
+            self.root = Some(*root);
+        }
+    }
+
+    fn unpack_object_streams(&mut self) {
+        let streams: Vec<(Dict, Vec<u8>)> = self
+            .objects
+            .values()
+            .filter_map(|obj| match obj {
+                Object::Stream(dict, data)
+                    if dict.get("Type").and_then(Object::as_name) == Some("ObjStm") =>
+                {
+                    Some((dict.clone(), data.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+
+        for (dict, raw) in streams {
+            let Some(data) = self.decode_stream(&dict, &raw) else {
+                continue;
+            };
+            let int = |key| match self.get(&dict, key) {
+                Some(Object::Int(n)) => usize::try_from(*n).ok(),
+                _ => None,
+            };
+            let (Some(count), Some(first)) = (int("N"), int("First")) else {
+                continue;
+            };
+            let mut header = Lexer::new(&data, 0);
+            let mut entries = Vec::new();
+            for _ in 0..count {
+                let (Some(Object::Int(num)), Some(Object::Int(offset))) =
+                    (header.object(false), header.object(false))
+                else {
+                    break;
+                };
+                // Skip entries whose number or offset is out of range.
+                if let Ok(num) = u32::try_from(num)
+                    && let Some(pos) = usize::try_from(offset)
+                        .ok()
+                        .and_then(|offset| first.checked_add(offset))
+                        .filter(|&pos| pos < data.len())
+                {
+                    entries.push((num, pos));
+                }
+            }
+            for (num, pos) in entries {
+                if let Some(obj) = Lexer::new(&data, pos).object(true) {
+                    self.objects.entry(num).or_insert(obj);
+                }
+            }
+        }
+    }
+
+    /// Follow references to the object they point at.
+    fn resolve<'a>(&'a self, mut obj: &'a Object) -> &'a Object {
+        for _ in 0..MAX_DEPTH {
+            match obj {
+                Object::Ref(num) => match self.objects.get(num) {
+                    Some(target) => obj = target,
+                    None => return &NULL,
+                },
+                _ => return obj,
+            }
+        }
+        &NULL
+    }
+
+    fn get<'a>(&'a self, dict: &'a Dict, key: &str) -> Option<&'a Object> {
+        dict.get(key).map(|obj| self.resolve(obj))
+    }
+
+    fn decode_stream(&self, dict: &Dict, raw: &[u8]) -> Option<Vec<u8>> {
+        let filters: Vec<&str> = match self.get(dict, "Filter") {
+            None | Some(Object::Null) => Vec::new(),
+            Some(Object::Name(name)) => vec![name.as_str()],
+            Some(Object::Array(items)) => items
+                .iter()
+                .filter_map(|f| self.resolve(f).as_name())
+                .collect(),
+            Some(_) => return None,
+        };
+
+        let mut data = raw.to_vec();
+        for filter in filters {
+            data = match filter {
+                "FlateDecode" | "Fl" => inflate(&data)?,
+                "ASCIIHexDecode" | "AHx" => hex_bytes(&data),
+                // Image and other filters carry no text.
+                _ => return None,
+            };
+        }
+        Some(data)
+    }
+
+    /// Page dictionaries in document order, each with its inherited resources.
+    fn pages(&self) -> Vec<Page> {
+        let mut pages = Vec::new();
+        let root = self
+            .root
+            .and_then(|root| self.objects.get(&root))
+            .or_else(|| {
+                self.objects.values().find(|obj| {
+                    obj.dict()
+                        .and_then(|d| d.get("Type"))
+                        .and_then(Object::as_name)
+                        == Some("Catalog")
+                })
+            });
+        if let Some(tree) = root
+            .and_then(Object::dict)
+            .and_then(|catalog| self.get(catalog, "Pages"))
+        {
+            let mut visited = HashSet::new();
+            self.collect_pages(tree, None, &mut visited, &mut pages, 0);
+        }
+
+        if pages.is_empty() {
+            // No usable page tree: fall back to every page object, in object order.
+            let mut numbers: Vec<&u32> = self.objects.keys().collect();
+            numbers.sort();
+            for num in numbers {
+                let obj = &self.objects[num];
+                if let Some(dict) = obj.dict()
+                    && dict.get("Type").and_then(Object::as_name) == Some("Page")
+                {
+                    pages.push(Page {
+                        dict: dict.clone(),
+                        resources: self.get(dict, "Resources").and_then(Object::dict).cloned(),
+                    });
+                }
+            }
+        }
+        pages
+    }
+
+    fn collect_pages(
+        &self,
+        node: &Object,
+        inherited: Option<&Dict>,
+        visited: &mut HashSet<*const Object>,
+        pages: &mut Vec<Page>,
+        depth: usize,
+    ) {
+        if depth > MAX_DEPTH || !visited.insert(node as *const Object) {
+            return;
+        }
+        let Some(dict) = node.dict() else {
+            return;
+        };
+        let resources = self
+            .get(dict, "Resources")
+            .and_then(Object::dict)
+            .or(inherited);
+
+        match self.get(dict, "Kids") {
+            Some(Object::Array(kids)) => {
+                for kid in kids {
+                    self.collect_pages(self.resolve(kid), resources, visited, pages, depth + 1);
+                }
+            }
+            _ => pages.push(Page {
+                dict: dict.clone(),
+                resources: resources.cloned(),
+            }),
+        }
+    }
+
+    /// Decoded content of a page, with multiple content streams joined.
+    fn page_content(&self, page: &Page) -> Vec<u8> {
+        let mut content = Vec::new();
+        let streams: Vec<&Object> = match self.get(&page.dict, "Contents") {
+            Some(Object::Array(items)) => items.iter().map(|item| self.resolve(item)).collect(),
+            Some(obj) => vec![obj],
+            None => Vec::new(),
+        };
+        for stream in streams {
+            if let Object::Stream(dict, raw) = stream
+                && let Some(data) = self.decode_stream(dict, raw)
+            {
+                content.extend_from_slice(&data);
+                content.push(b'\n');
+            }
+        }
+        content
+    }
+
+    fn page_lines(&self, page: &Page) -> Vec<TextLine> {
+        let content = self.page_content(page);
+        let mut state = TextState::default();
+        let empty = Dict::new();
+        self.run_content(
+            &content,
+            page.resources.as_ref().unwrap_or(&empty),
+            &mut state,
+            0,
+        );
+        state.finish()
+    }
+
+    /// Interpret the text operators of a content stream.
+    fn run_content(&self, content: &[u8], resources: &Dict, state: &mut TextState, depth: usize) {
+        let mut fonts: HashMap<String, Font> = HashMap::new();
+        let mut lexer = Lexer::new(content, 0);
+        let mut operands: Vec<Object> = Vec::new();
 
-    let mut builder = PdfBuilder::new();
-    builder.set_content(&content);
-    Ok(builder.build(title))
+        while let Some(token) = lexer.token(false) {
+            let op = match token {
+                Token::Object(obj) => {
+                    operands.push(obj);
+                    continue;
+                }
+                Token::Keyword(op) => op,
+                Token::ArrayEnd | Token::DictEnd => continue,
+            };
+            let num = |i: usize| operands.get(i).and_then(Object::as_f64).unwrap_or(0.0);
+
+            match op.as_str() {
+                "BT" => state.begin_text(),
+                "Tf" => {
+                    if let Some(name) = operands.first().and_then(Object::as_name) {
+                        if !fonts.contains_key(name) {
+                            let font = self.load_font(resources, name);
+                            fonts.insert(name.to_string(), font);
+                        }
+                        state.font = name.to_string();
+                    }
+                    state.font_size = num(1);
+                }
+                "Tm" => state.set_matrix([num(0), num(1), num(2), num(3), num(4), num(5)]),
+                "Td" => state.move_line(num(0), num(1)),
+                "TD" => {
+                    state.leading = -num(1);
+                    state.move_line(num(0), num(1));
+                }
+                "TL" => state.leading = num(0),
+                "T*" => state.next_line(),
+                "Tj" | "'" | "\"" => {
+                    if op != "Tj" {
+                        state.next_line();
+                    }
+                    if let Some(Object::Str(bytes)) = operands.last() {
+                        let text = decode_text(fonts.get(&state.font), bytes);
+                        state.show(&text);
+                    }
+                }
+                "TJ" => {
+                    if let Some(Object::Array(items)) = operands.last() {
+                        for item in items {
+                            match item {
+                                Object::Str(bytes) => {
+                                    let text = decode_text(fonts.get(&state.font), bytes);
+                                    state.show(&text);
+                                }
+                                other => state.kern(other.as_f64().unwrap_or(0.0)),
+                            }
+                        }
+                    }
+                }
+                "Do" if depth < 8 => {
+                    if let Some(name) = operands.first().and_then(Object::as_name)
+                        && let Some(Object::Stream(dict, raw)) = self
+                            .get(resources, "XObject")
+                            .and_then(Object::dict)
+                            .and_then(|xobjects| self.get(xobjects, name))
+                        && dict.get("Subtype").and_then(Object::as_name) == Some("Form")
+                        && let Some(data) = self.decode_stream(dict, raw)
+                    {
+                        let form_resources = self
+                            .get(dict, "Resources")
+                            .and_then(Object::dict)
+                            .unwrap_or(resources);
+                        let saved = state.save_font();
+                        self.run_content(&data, form_resources, state, depth + 1);
+                        state.restore_font(saved);
+                    }
+                }
+                "BI" => skip_inline_image(&mut lexer),
+                _ => {}
+            }
+            operands.clear();
+        }
+    }
+
+    fn load_font(&self, resources: &Dict, name: &str) -> Font {
+        let Some(font) = self
+            .get(resources, "Font")
+            .and_then(Object::dict)
+            .and_then(|fonts| self.get(fonts, name))
+            .and_then(Object::dict)
+        else {
+            return Font::default();
+        };
+
+        let two_byte = font.get("Subtype").and_then(Object::as_name) == Some("Type0");
+        let mut result = Font {
+            code_width: if two_byte { 2 } else { 1 },
+            map: HashMap::new(),
+        };
+        if let Some(Object::Stream(dict, raw)) = self.get(font, "ToUnicode")
+            && let Some(cmap) = self.decode_stream(dict, raw)
+        {
+            parse_cmap(&cmap, &mut result);
+        }
+        result
+    }
 }
 
-/// Escape special characters for PDF string literals.
-fn pdf_escape(s: &str) -> String {
-    s.replace('\\', "\\\\")
-        .replace('(', "\\(")
-        .replace(')', "\\)")
+struct Page {
+    dict: Dict,
+    resources: Option<Dict>,
 }
 
-/// Minimal PDF file builder. Constructs valid PDF 1.4 files.
-struct PdfBuilder {
-    content: String,
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|at| at + from)
 }
 
-impl PdfBuilder {
-    fn new() -> Self {
+/// Parse the `N G` in front of an `obj` keyword at `offset`.
+fn object_number_before(data: &[u8], offset: usize) -> Option<u32> {
+    let mut i = offset;
+    let skip = |i: &mut usize, pred: fn(u8) -> bool| -> usize {
+        let end = *i;
+        while *i > 0 && pred(data[*i - 1]) {
+            *i -= 1;
+        }
+        end - *i
+    };
+    if skip(&mut i, is_whitespace) == 0 {
+        return None;
+    }
+    if skip(&mut i, |b| b.is_ascii_digit()) == 0 {
+        return None;
+    }
+    if skip(&mut i, is_whitespace) == 0 {
+        return None;
+    }
+    let end = i;
+    if skip(&mut i, |b| b.is_ascii_digit()) == 0 {
+        return None;
+    }
+    if i > 0 && !is_whitespace(data[i - 1]) && !is_delimiter(data[i - 1]) {
+        return None;
+    }
+    std::str::from_utf8(&data[i..end]).ok()?.parse().ok()
+}
+
+/// The bytes of a stream starting right after the `stream` keyword, and
+/// where scanning should resume.
+fn stream_data<'a>(data: &'a [u8], mut start: usize, dict: &Dict) -> (&'a [u8], usize) {
+    if data.get(start) == Some(&b'\r') {
+        start += 1;
+    }
+    if data.get(start) == Some(&b'\n') {
+        start += 1;
+    }
+    if let Some(Object::Int(len)) = dict.get("Length")
+        && let Ok(len) = usize::try_from(*len)
+        && let Some(end) = start.checked_add(len)
+        && end <= data.len()
+    {
+        let mut after = Lexer::new(data, end);
+        after.skip_whitespace();
+        if data[after.pos..].starts_with(b"endstream") {
+            return (&data[start..end], after.pos + 9);
+        }
+    }
+
+    // Indirect or wrong /Length: fall back to the endstream marker.
+    let end = find(data, b"endstream", start).unwrap_or(data.len());
+    let mut trimmed = end;
+    if trimmed > start && data[trimmed - 1] == b'\n' {
+        trimmed -= 1;
+    }
+    if trimmed > start && data[trimmed - 1] == b'\r' {
+        trimmed -= 1;
+    }
+    (&data[start..trimmed], end)
+}
+
+fn inflate(data: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    let zlib = flate2::read::ZlibDecoder::new(data)
+        .take(MAX_STREAM_SIZE)
+        .read_to_end(&mut out);
+    if zlib.is_ok() || !out.is_empty() {
+        return Some(out);
+    }
+    // Some producers write raw deflate data without the zlib header.
+    out.clear();
+    flate2::read::DeflateDecoder::new(data)
+        .take(MAX_STREAM_SIZE)
+        .read_to_end(&mut out)
+        .ok()?;
+    Some(out)
+}
+
+/// Skip inline image data: everything up to `EI` after the `ID` keyword.
+fn skip_inline_image(lexer: &mut Lexer<'_>) {
+    while let Some(token) = lexer.token(false) {
+        if matches!(&token, Token::Keyword(k) if k == "ID") {
+            break;
+        }
+    }
+    let data = lexer.data;
+    let mut i = lexer.pos + 1;
+    while i + 2 <= data.len() {
+        if &data[i..i + 2] == b"EI"
+            && is_whitespace(data[i - 1])
+            && data.get(i + 2).is_none_or(|&b| is_whitespace(b))
+        {
+            lexer.pos = i + 2;
+            return;
+        }
+        i += 1;
+    }
+    lexer.pos = data.len();
+}
+
+// ---------------------------------------------------------------------------
+// Fonts and text decoding
+// ---------------------------------------------------------------------------
+
+#[derive(Debug)]
+struct Font {
+    /// Bytes per character code.
+    code_width: usize,
+    /// ToUnicode mappings; empty when the font has none.
+    map: HashMap<u32, String>,
+}
+
+impl Default for Font {
+    fn default() -> Self {
         Self {
-            content: String::new(),
+            code_width: 1,
+            map: HashMap::new(),
         }
     }
+}
 
-    fn set_content(&mut self, content: &str) {
-        self.content = content.to_string();
+/// Read `bfchar` and `bfrange` mappings from a ToUnicode CMap.
+fn parse_cmap(data: &[u8], font: &mut Font) {
+    let mut lexer = Lexer::new(data, 0);
+    let mut operands: Vec<Object> = Vec::new();
+    let mut mode = "";
+
+    while let Some(token) = lexer.token(false) {
+        match token {
+            Token::Object(obj) => {
+                operands.push(obj);
+                match (mode, operands.as_slice()) {
+                    ("codespace", [Object::Str(lo), Object::Str(_)]) => {
+                        font.code_width = lo.len().clamp(1, 4);
+                        operands.clear();
+                    }
+                    ("bfchar", [Object::Str(src), Object::Str(dst)]) => {
+                        font.map.insert(code_value(src), utf16_text(dst));
+                        operands.clear();
+                    }
+                    ("bfrange", [Object::Str(lo), Object::Str(hi), dst]) => {
+                        let (lo, hi) = (code_value(lo), code_value(hi));
+                        // Guard against absurd ranges in broken files.
+                        for (i, code) in (lo..=hi).take(0x10000).enumerate() {
+                            let text = match dst {
+                                Object::Str(base) => offset_utf16(base, i as u32),
+                                Object::Array(items) => match items.get(i) {
+                                    Some(Object::Str(item)) => utf16_text(item),
+                                    _ => continue,
+                                },
+                                _ => continue,
+                            };
+                            font.map.insert(code, text);
+                        }
+                        operands.clear();
+                    }
+                    _ => {}
+                }
+            }
+            Token::Keyword(keyword) => {
+                mode = match keyword.as_str() {
+                    "begincodespacerange" => "codespace",
+                    "beginbfchar" => "bfchar",
+                    "beginbfrange" => "bfrange",
+                    _ => "",
+                };
+                operands.clear();
+            }
+            Token::ArrayEnd | Token::DictEnd => {}
+        }
     }
+}
 
-    /// Build the complete PDF file as bytes.
-    fn build(&self, title: &str) -> Vec<u8> {
-        let mut pdf = String::new();
-        let mut offsets: Vec<usize> = Vec::new();
+fn code_value(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .take(4)
+        .fold(0, |acc, &b| acc << 8 | u32::from(b))
+}
 
-        // Header
-        pdf.push_str("%PDF-1.4\n");
+fn utf16_text(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks(2)
+        .map(|pair| u16::from(pair[0]) << 8 | u16::from(*pair.get(1).unwrap_or(&0)))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
 
-        // Obj 1: Catalog
-        offsets.push(pdf.len());
-        pdf.push_str("1 0 obj\n<< /Type /Catalog /Pages 2 0 R >>\nendobj\n");
+/// `base` with its last UTF-16 unit advanced by `offset` (bfrange).
+fn offset_utf16(base: &[u8], offset: u32) -> String {
+    let mut units: Vec<u16> = base
+        .chunks(2)
+        .map(|pair| u16::from(pair[0]) << 8 | u16::from(*pair.get(1).unwrap_or(&0)))
+        .collect();
+    if let Some(last) = units.last_mut() {
+        *last = last.wrapping_add(offset as u16);
+    }
+    String::from_utf16_lossy(&units)
+}
 
-        // Obj 2: Pages
-        offsets.push(pdf.len());
-        pdf.push_str("2 0 obj\n<< /Type /Pages /Kids [3 0 R] /Count 1 >>\nendobj\n");
+fn decode_text(font: Option<&Font>, bytes: &[u8]) -> String {
+    let default = Font::default();
+    let font = font.unwrap_or(&default);
+    if font.map.is_empty() {
+        if font.code_width == 2 {
+            // Identity-encoded CIDs without a ToUnicode map can't be read.
+            return String::new();
+        }
+        return bytes.iter().map(|&b| win_ansi(b)).collect();
+    }
+    bytes
+        .chunks(font.code_width)
+        .map(|code| {
+            let code = code_value(code);
+            match font.map.get(&code) {
+                Some(text) => text.clone(),
+                None if font.code_width == 1 => win_ansi(code as u8).to_string(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
 
-        // Obj 3: Page
-        offsets.push(pdf.len());
-        pdf.push_str("3 0 obj\n<< /Type /Page /Parent 2 0 R ");
-        pdf.push_str("/MediaBox [0 0 612 842] "); // A4 in points
-        pdf.push_str("/Contents 4 0 R /Resources << /Font << ");
-        pdf.push_str("/F1 5 0 R /F2 6 0 R >> >> >>\nendobj\n");
+/// WinAnsiEncoding: Latin-1 plus typographic characters in 0x80–0x9F.
+fn win_ansi(b: u8) -> char {
+    match b {
+        0x80 => '€',
+        0x85 => '…',
+        0x91 => '‘',
+        0x92 => '’',
+        0x93 => '“',
+        0x94 => '”',
+        0x95 => '•',
+        0x96 => '–',
+        0x97 => '—',
+        0x99 => '™',
+        _ => b as char,
+    }
+}
 
-        // Obj 4: Content stream
-        offsets.push(pdf.len());
-        let stream = &self.content;
-        pdf.push_str(&format!(
-            "4 0 obj\n<< /Length {} >>\nstream\n{}\nendstream\nendobj\n",
-            stream.len(),
-            stream
-        ));
+// ---------------------------------------------------------------------------
+// Text layout
+// ---------------------------------------------------------------------------
 
-        // Obj 5: Font (Helvetica-Bold)
-        offsets.push(pdf.len());
-        pdf.push_str(
-            "5 0 obj\n<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold >>\nendobj\n",
-        );
+const IDENTITY: [f64; 6] = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
 
-        // Obj 6: Font (Helvetica)
-        offsets.push(pdf.len());
-        pdf.push_str("6 0 obj\n<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>\nendobj\n");
+#[derive(Debug, Clone, PartialEq)]
+struct TextLine {
+    /// Baseline in text space; larger is higher on the page.
+    y: f64,
+    size: f64,
+    text: String,
+}
 
-        // Obj 7: Info (title)
-        offsets.push(pdf.len());
-        pdf.push_str(&format!(
-            "7 0 obj\n<< /Title ({}) /Producer (Hive) >>\nendobj\n",
-            pdf_escape(title)
-        ));
+/// Text state while running a content stream. Positions are tracked in
+/// text space only (the CTM is ignored), which is enough to tell lines
+/// and word gaps apart.
+#[derive(Debug)]
+struct TextState {
+    font: String,
+    font_size: f64,
+    leading: f64,
+    /// Text matrix `[a b c d e f]`; advances as text is shown.
+    text_matrix: [f64; 6],
+    /// Matrix at the start of the current line, which `Td` and `T*` move.
+    line_matrix: [f64; 6],
+    /// Estimated end of the last shown text on the current line.
+    pen_x: f64,
+    lines: Vec<TextLine>,
+    current: Option<TextLine>,
+}
 
-        // Cross-reference table
-        let xref_offset = pdf.len();
-        let num_objects = offsets.len() + 1; // +1 for free entry
-        pdf.push_str(&format!("xref\n0 {num_objects}\n"));
-        pdf.push_str("0000000000 65535 f \n");
-        for offset in &offsets {
-            pdf.push_str(&format!("{:010} 00000 n \n", offset));
+impl Default for TextState {
+    fn default() -> Self {
+        Self {
+            font: String::new(),
+            font_size: 12.0,
+            leading: 0.0,
+            text_matrix: IDENTITY,
+            line_matrix: IDENTITY,
+            pen_x: 0.0,
+            lines: Vec::new(),
+            current: None,
+        }
+    }
+}
+
+impl TextState {
+    fn begin_text(&mut self) {
+        self.set_matrix(IDENTITY);
+    }
+
+    fn set_matrix(&mut self, matrix: [f64; 6]) {
+        self.text_matrix = matrix;
+        self.line_matrix = matrix;
+    }
+
+    fn move_line(&mut self, tx: f64, ty: f64) {
+        let [a, b, c, d, e, f] = self.line_matrix;
+        self.set_matrix([a, b, c, d, e + tx * a + ty * c, f + tx * b + ty * d]);
+    }
+
+    fn next_line(&mut self) {
+        self.move_line(0.0, -self.leading);
+    }
+
+    /// Effective font size, including the text matrix scale.
+    fn size(&self) -> f64 {
+        let [_, b, _, d, _, _] = self.text_matrix;
+        (self.font_size * b.hypot(d)).abs().max(0.1)
+    }
+
+    fn show(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
         }
+        let size = self.size();
+        let [_, _, _, _, x, y] = self.text_matrix;
 
-        // Trailer
-        pdf.push_str(&format!(
-            "trailer\n<< /Size {num_objects} /Root 1 0 R /Info 7 0 R >>\n"
-        ));
-        pdf.push_str(&format!("startxref\n{xref_offset}\n%%EOF\n"));
+        let same_line = self
+            .current
+            .as_ref()
+            .is_some_and(|line| (line.y - y).abs() < size * 0.5);
+        if same_line {
+            let line = self.current.as_mut().expect("checked above");
+            // A visible jump from where the last text ended is a word gap.
+            if (x - self.pen_x).abs() > size * 0.2
+                && !line.text.ends_with(' ')
+                && !text.starts_with(' ')
+            {
+                line.text.push(' ');
+            }
+            line.text.push_str(text);
+            line.size = line.size.max(size);
+        } else {
+            self.end_line();
+            self.current = Some(TextLine {
+                y,
+                size,
+                text: text.to_string(),
+            });
+        }
+
+        // Glyph widths aren't read; half an em per character is close
+        // enough to spot gaps.
+        let advance = text.chars().count() as f64 * size * 0.5;
+        self.text_matrix[4] = x + advance;
+        self.pen_x = x + advance;
+    }
+
+    /// A `TJ` adjustment, in thousandths of an em; large negative values
+    /// are word gaps.
+    fn kern(&mut self, amount: f64) {
+        let size = self.size();
+        if amount < -200.0
+            && let Some(line) = self.current.as_mut()
+            && !line.text.ends_with(' ')
+        {
+            line.text.push(' ');
+        }
+        self.text_matrix[4] -= amount / 1000.0 * size;
+        self.pen_x = self.text_matrix[4];
+    }
+
+    fn save_font(&self) -> (String, f64) {
+        (self.font.clone(), self.font_size)
+    }
+
+    fn restore_font(&mut self, (font, size): (String, f64)) {
+        self.font = font;
+        self.font_size = size;
+    }
+
+    fn end_line(&mut self) {
+        if let Some(mut line) = self.current.take() {
+            line.text = line.text.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !line.text.is_empty() {
+                self.lines.push(line);
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<TextLine> {
+        self.end_line();
+        self.lines
+    }
+}
+
+/// Group lines into headings and paragraphs.
+///
+/// The body size is the size most text on the page is set in; shorter lines
+/// at least 20% larger are headings. A vertical gap of more than about one
+/// and a half lines, or a jump back up the page, starts a new paragraph.
+fn lines_to_blocks(lines: &[TextLine]) -> Vec<Block> {
+    let mut weight: HashMap<i64, usize> = HashMap::new();
+    for line in lines {
+        *weight.entry((line.size * 2.0).round() as i64).or_default() += line.text.len();
+    }
+    let body_size = weight
+        .into_iter()
+        .max_by_key(|&(size, chars)| (chars, -size))
+        .map(|(size, _)| size as f64 / 2.0)
+        .unwrap_or(12.0);
+
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut previous: Option<&TextLine> = None;
+
+    for line in lines {
+        let heading = line.size >= body_size * 1.2 && line.text.chars().count() <= 120;
+        let gap = previous.map(|prev| prev.y - line.y);
+        let breaks =
+            heading || gap.is_none_or(|gap| gap < 0.0 || gap > line.size.max(body_size) * 1.6);
+        if breaks && !paragraph.is_empty() {
+            blocks.push(Block::Paragraph {
+                text: paragraph.join("\n"),
+            });
+            paragraph.clear();
+        }
 
-        pdf.into_bytes()
+        if heading {
+            let level = if line.size >= body_size * 1.6 { 1 } else { 2 };
+            blocks.push(Block::Heading {
+                level,
+                text: line.text.clone(),
+            });
+        } else {
+            paragraph.push(&line.text);
+        }
+        previous = Some(line);
+    }
+    if !paragraph.is_empty() {
+        blocks.push(Block::Paragraph {
+            text: paragraph.join("\n"),
+        });
     }
+    blocks
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::document::test_png;
+    use crate::markdown::parse_markdown;
 
     #[test]
     fn test_generate_pdf_document_basic() {
@@ -284,15 +3095,372 @@ mod tests {
         let sections = vec![("Symbols & Signs", "Price: $100 @ 10% off (sale)")];
         let bytes = generate_pdf_document("Special Chars", &sections).unwrap();
         assert!(bytes.starts_with(b"%PDF"));
-        // Parentheses should be escaped
+        let markdown = extract_pdf(&bytes).unwrap().to_markdown();
+        assert!(
+            markdown.contains("Price: $100 @ 10% off (sale)"),
+            "{markdown}"
+        );
+
+        // Parentheses in Helvetica string literals should be escaped
+        let bytes = render_pdf(&Document::titled("(sale)"), &PdfOptions::default()).unwrap();
         let content = String::from_utf8_lossy(&bytes);
         assert!(content.contains("\\(sale\\)"));
     }
 
+    #[test]
+    fn test_render_pdf_from_markdown_roundtrip() {
+        let document = parse_markdown(
+            "# Plan\n\nSome **bold** and *italic* text with a [link](https://example.com).\n\n\
+             ## Steps\n\n1. Design\n2. Build\n\n```\nlet x = 1;\n```\n",
+        );
+        let bytes = render_pdf(&document, &PdfOptions::default()).unwrap();
+        let content = String::from_utf8_lossy(&bytes);
+        assert!(content.contains("/BaseFont /Helvetica-Bold"));
+        assert!(content.contains("/BaseFont /Helvetica-Oblique"));
+        assert!(content.contains("/BaseFont /Courier"));
+        assert!(content.contains("/URI (https://example.com)"));
+
+        let doc = extract_pdf(&bytes).unwrap();
+        let markdown = doc.to_markdown();
+        assert!(markdown.contains("# Plan"), "{markdown}");
+        assert!(markdown.contains("## Steps"), "{markdown}");
+        assert!(
+            markdown.contains("Some bold and italic text with a link."),
+            "{markdown}"
+        );
+        assert!(markdown.contains("1. Design"), "{markdown}");
+        assert!(markdown.contains("let x = 1;"), "{markdown}");
+    }
+
+    #[test]
+    fn test_render_pdf_numbers_pages() {
+        let mut document = Document::titled("Long");
+        for i in 0..120 {
+            document.push(Node::paragraph(format!("Paragraph {i}")));
+        }
+        let bytes = render_pdf(&document, &PdfOptions::default()).unwrap();
+        let doc = extract_pdf(&bytes).unwrap();
+        let pages = doc.sections.len();
+        assert!(pages > 1);
+        assert!(doc.to_markdown().contains(&format!("2 / {pages}")));
+
+        let short = render_pdf(&Document::titled("Short"), &PdfOptions::default()).unwrap();
+        assert!(!extract_pdf(&short).unwrap().to_markdown().contains("1 / 1"));
+    }
+
+    #[test]
+    fn test_render_pdf_embeds_png_image() {
+        let mut document = Document::new();
+        document.push(Node::Paragraph {
+            content: vec![Inline::Image(Image {
+                src: "chart.png".into(),
+                alt: "Chart".into(),
+                data: Some(test_png()),
+            })],
+        });
+        let bytes = render_pdf(&document, &PdfOptions::default()).unwrap();
+        let content = String::from_utf8_lossy(&bytes);
+        assert!(content.contains("/Subtype /Image"));
+        assert!(content.contains("/Im1 Do"));
+
+        // Without data the alt text stands in.
+        document.blocks[0] = Node::Paragraph {
+            content: vec![Inline::Image(Image {
+                src: "missing.png".into(),
+                alt: "Chart".into(),
+                data: None,
+            })],
+        };
+        let bytes = render_pdf(&document, &PdfOptions::default()).unwrap();
+        assert!(
+            extract_pdf(&bytes)
+                .unwrap()
+                .to_markdown()
+                .contains("[Chart]")
+        );
+    }
+
+    #[test]
+    fn test_render_pdf_embedded_font_keeps_unicode() {
+        // Only runs where a system font is installed.
+        let Some(fonts) = FontSet::system() else {
+            return;
+        };
+        let options = PdfOptions {
+            fonts: Some(fonts),
+            ..PdfOptions::default()
+        };
+        let document = parse_markdown("# Überblick\n\nΑλφα — Ωμέγα, Привет\n");
+        let bytes = render_pdf(&document, &options).unwrap();
+        let content = String::from_utf8_lossy(&bytes);
+        assert!(content.contains("/Subtype /Type0"));
+        assert!(content.contains("/FontFile2"));
+        assert!(!content.contains("/BaseFont /Helvetica"));
+
+        let markdown = extract_pdf(&bytes).unwrap().to_markdown();
+        assert!(markdown.contains("# Überblick"), "{markdown}");
+        assert!(markdown.contains("Αλφα — Ωμέγα, Привет"), "{markdown}");
+    }
+
+    #[test]
+    fn test_generate_pdf_keeps_helvetica_for_latin1() {
+        let bytes = generate_pdf_document("Hello", &[("Café", "“Hello” — world")]).unwrap();
+        let content = String::from_utf8_lossy(&bytes);
+        assert!(content.contains("/BaseFont /Helvetica"));
+        assert!(!content.contains("/FontFile2"));
+        assert!(bytes.len() < 10_000, "{} bytes", bytes.len());
+
+        let mut document = Document::titled("Plain");
+        document.push(Node::table(&["Name"], &[vec!["Zoë".into()]]));
+        assert!(PdfOptions::for_document(&document).fonts.is_none());
+        document.push(Node::CodeBlock {
+            language: None,
+            code: "let π = 3.14;".into(),
+        });
+        assert_eq!(
+            PdfOptions::for_document(&document).fonts.is_some(),
+            FontSet::system().is_some()
+        );
+    }
+
+    #[test]
+    fn test_generate_pdf_embeds_system_fonts() {
+        let bytes = generate_pdf_document("Überblick", &[("Intro", "Αλφα — Привет")]).unwrap();
+        let content = String::from_utf8_lossy(&bytes);
+        if FontSet::system().is_none() {
+            assert!(content.contains("/BaseFont /Helvetica"));
+            return;
+        }
+        assert!(content.contains("/FontFile2"));
+        let markdown = extract_pdf(&bytes).unwrap().to_markdown();
+        assert!(markdown.contains("Αλφα — Привет"), "{markdown}");
+
+        let table = generate_pdf_table("Ω", &["Name"], &[vec!["Дмитрий".into()]]).unwrap();
+        let markdown = extract_pdf(&table).unwrap().to_markdown();
+        assert!(markdown.contains("Дмитрий"), "{markdown}");
+    }
+
+    #[test]
+    fn test_font_set_rejects_invalid_data() {
+        assert!(FontSet::new(b"not a font".to_vec()).is_err());
+    }
+
     #[test]
     fn test_pdf_escape() {
         assert_eq!(pdf_escape("hello"), "hello");
         assert_eq!(pdf_escape("(test)"), "\\(test\\)");
         assert_eq!(pdf_escape("a\\b"), "a\\\\b");
     }
+
+    #[test]
+    fn test_extract_pdf_document_roundtrip() {
+        let sections = vec![
+            ("Introduction", "This is the introduction (draft)."),
+            ("Details", "Line one\nLine two\nLine three"),
+        ];
+        let bytes = generate_pdf_document("Test Report", &sections).unwrap();
+        let doc = extract_pdf(&bytes).unwrap();
+
+        assert_eq!(doc.sections.len(), 1);
+        assert_eq!(doc.sections[0].anchor, Some(Anchor::Page { number: 1 }));
+        assert_eq!(
+            doc.sections[0].blocks,
+            vec![
+                Block::Heading {
+                    level: 1,
+                    text: "Test Report".into()
+                },
+                Block::Heading {
+                    level: 2,
+                    text: "Introduction".into()
+                },
+                Block::Paragraph {
+                    text: "This is the introduction (draft).".into()
+                },
+                Block::Heading {
+                    level: 2,
+                    text: "Details".into()
+                },
+                Block::Paragraph {
+                    text: "Line one\nLine two\nLine three".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_pdf_table_cells_on_one_line() {
+        let rows = vec![vec!["Alice".into(), "30".into(), "New York".into()]];
+        let bytes = generate_pdf_table("People", &["Name", "Age", "City"], &rows).unwrap();
+        let markdown = extract_pdf(&bytes).unwrap().to_markdown();
+        assert!(
+            markdown.contains("\n\nName Age City\n\nAlice 30 New York"),
+            "{markdown}"
+        );
+    }
+
+    /// A two-page PDF with a compressed content stream, a ToUnicode map and
+    /// a TJ array.
+    fn compressed_pdf() -> Vec<u8> {
+        use flate2::{Compression, write::ZlibEncoder};
+        use std::io::Write;
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(b"BT /F1 12 Tf 72 700 Td [(\x01\x02)-400(\x03)] TJ ET")
+            .unwrap();
+        let page_one = encoder.finish().unwrap();
+        let cmap = b"/CIDInit /ProcSet findresource begin\n\
+            begincmap\n1 begincodespacerange <00> <FF> endcodespacerange\n\
+            2 beginbfchar <01> <0048> <02> <0069> endbfchar\n\
+            1 beginbfrange <03> <03> <00E9> endbfrange\nendcmap";
+
+        let mut pdf = b"%PDF-1.5\n".to_vec();
+        pdf.extend_from_slice(b"1 0 obj << /Type /Catalog /Pages 2 0 R >> endobj\n");
+        pdf.extend_from_slice(
+            b"2 0 obj << /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 \
+              /Resources << /Font << /F1 7 0 R >> >> >> endobj\n",
+        );
+        pdf.extend_from_slice(b"3 0 obj << /Type /Page /Parent 2 0 R /Contents 5 0 R >> endobj\n");
+        pdf.extend_from_slice(b"4 0 obj << /Type /Page /Parent 2 0 R /Contents 6 0 R >> endobj\n");
+        pdf.extend_from_slice(
+            format!(
+                "5 0 obj << /Length {} /Filter /FlateDecode >> stream\n",
+                page_one.len()
+            )
+            .as_bytes(),
+        );
+        pdf.extend_from_slice(&page_one);
+        pdf.extend_from_slice(b"\nendstream endobj\n");
+        pdf.extend_from_slice(
+            b"6 0 obj << /Length 99 >> stream\nBT /F1 12 Tf 14 TL 72 700 Td (\x01) Tj T* (\x02) Tj ET\nendstream endobj\n",
+        );
+        pdf.extend_from_slice(
+            b"7 0 obj << /Type /Font /Subtype /Type1 /BaseFont /Helvetica /ToUnicode 8 0 R >> endobj\n",
+        );
+        pdf.extend_from_slice(format!("8 0 obj << /Length {} >> stream\n", cmap.len()).as_bytes());
+        pdf.extend_from_slice(cmap);
+        pdf.extend_from_slice(b"\nendstream endobj\ntrailer << /Size 9 /Root 1 0 R >>\n%%EOF\n");
+        pdf
+    }
+
+    #[test]
+    fn test_extract_pdf_compressed_with_tounicode() {
+        let doc = extract_pdf(&compressed_pdf()).unwrap();
+
+        assert_eq!(doc.sections.len(), 2);
+        assert_eq!(
+            doc.sections[0].blocks,
+            vec![Block::Paragraph {
+                text: "Hi é".into()
+            }]
+        );
+        assert_eq!(doc.sections[1].anchor, Some(Anchor::Page { number: 2 }));
+        assert_eq!(
+            doc.sections[1].blocks,
+            vec![Block::Paragraph {
+                text: "H\ni".into()
+            }]
+        );
+        assert!(doc.to_markdown().contains("[page 2]\n\nH\ni"));
+    }
+
+    #[test]
+    fn test_extract_pdf_rejects_encrypted_and_garbage() {
+        let mut pdf = render_pdf(&Document::titled("Secret"), &PdfOptions::default()).unwrap();
+        let text = String::from_utf8(pdf).unwrap().replace(
+            "/Root 1 0 R",
+            "/Root 1 0 R /Encrypt << /Filter /Standard /V 2 >>",
+        );
+        pdf = text.into_bytes();
+        let err = extract_pdf(&pdf).unwrap_err();
+        assert!(err.to_string().contains("Encrypted"));
+
+        assert!(extract_pdf(b"hello").is_err());
+    }
+
+    #[test]
+    fn test_object_streams_skip_malformed_entries() {
+        let body = b"<< /Type /Catalog >> (ok)";
+        let header = format!(
+            "-1 0 4294967296 0 1 {} 2 -5 3 9223372036854775807 9 0 ",
+            body.len() - 4
+        );
+        let mut pdf = b"%PDF-1.5\n".to_vec();
+        pdf.extend_from_slice(
+            format!(
+                "5 0 obj << /Type /ObjStm /N 6 /First {} /Length {} >> stream\n",
+                header.len(),
+                header.len() + body.len()
+            )
+            .as_bytes(),
+        );
+        pdf.extend_from_slice(header.as_bytes());
+        pdf.extend_from_slice(body);
+        pdf.extend_from_slice(b"\nendstream endobj\n%%EOF\n");
+
+        let file = PdfFile::parse(&pdf).unwrap();
+        assert_eq!(file.objects[&1], Object::Str(b"ok".to_vec()));
+        assert!(matches!(file.objects[&9], Object::Dict(_)));
+        assert!(!file.objects.contains_key(&2));
+        assert!(!file.objects.contains_key(&3));
+
+        // A negative /First leaves the stream unpacked.
+        let negative =
+            String::from_utf8_lossy(&pdf).replace(&format!("/First {}", header.len()), "/First -1");
+        let file = PdfFile::parse(negative.as_bytes()).unwrap();
+        assert_eq!(file.objects.len(), 1);
+    }
+
+    #[test]
+    fn test_deeply_nested_objects_do_not_overflow_the_stack() {
+        for open in ["[", "<< /X "] {
+            let mut pdf = b"%PDF-1.4\n1 0 obj << /X ".to_vec();
+            pdf.extend(open.repeat(200_000).into_bytes());
+            pdf.extend_from_slice(b"\nendobj\n%%EOF\n");
+            // Returns, with or without a document, instead of aborting.
+            let _ = extract_pdf(&pdf);
+        }
+
+        let mut lexer = Lexer::new(b"[[[1]]]", 0);
+        assert_eq!(
+            lexer.object(false),
+            Some(Object::Array(vec![Object::Array(vec![Object::Array(
+                vec![Object::Int(1)]
+            )])]))
+        );
+        let deep = "[".repeat(MAX_NESTING + 1);
+        let mut lexer = Lexer::new(deep.as_bytes(), 0);
+        let mut obj = lexer.object(false).unwrap();
+        let mut depth = 1;
+        while let Object::Array(mut items) = obj {
+            let Some(inner) = items.pop() else { break };
+            obj = inner;
+            depth += 1;
+        }
+        assert_eq!(depth, MAX_NESTING);
+    }
+
+    #[test]
+    fn test_lexer_objects() {
+        let mut lexer = Lexer::new(
+            b"<< /A 1 0 R /B [1 -2.5 .5 (a\\(b\\)\\101) <48 69>] /C#20D true >>",
+            0,
+        );
+        let Some(Object::Dict(dict)) = lexer.object(true) else {
+            panic!("expected a dictionary");
+        };
+        assert_eq!(dict["A"], Object::Ref(1));
+        assert_eq!(
+            dict["B"],
+            Object::Array(vec![
+                Object::Int(1),
+                Object::Real(-2.5),
+                Object::Real(0.5),
+                Object::Str(b"a(b)A".to_vec()),
+                Object::Str(b"Hi".to_vec()),
+            ])
+        );
+        assert_eq!(dict["C D"], Object::Bool(true));
+    }
 }
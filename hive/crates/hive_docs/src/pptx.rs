@@ -4,6 +4,14 @@ use zip::CompressionMethod;
 use zip::ZipWriter;
 use zip::write::SimpleFileOptions;
 
+use crate::document::{
+    Alignment, Cell, Document, Image, Inline, ListItem, Node, Span, TextStyle, image_info, spans,
+};
+use crate::extract::{
+    Anchor, Block, DocumentFormat, ExtractedDocument, Package, Section, attr, child,
+    find_relationship, is, parse_xml, rel_id,
+};
+
 /// A single slide in a PPTX presentation.
 pub struct PptxSlide {
     pub title: String,
@@ -12,9 +20,94 @@ pub struct PptxSlide {
 
 /// Generate a PPTX (PowerPoint) file from a list of slides.
 ///
-/// Each slide has a title and body content. The output is a valid OOXML
-/// presentation file constructed using the `zip` crate.
+/// Each slide has a title and body content, one bullet per line. The output
+/// is a valid OOXML presentation file constructed using the `zip` crate.
 pub fn generate_pptx(slides: &[PptxSlide]) -> Result<Vec<u8>> {
+    let slides: Vec<Slide> = slides
+        .iter()
+        .map(|slide| {
+            let items: Vec<ListItem> = slide
+                .content
+                .lines()
+                .map(|line| ListItem {
+                    checked: None,
+                    blocks: vec![Node::paragraph(line)],
+                })
+                .collect();
+            Slide {
+                title: vec![Inline::Text(slide.title.clone())],
+                opening: false,
+                blocks: if items.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![Node::List { start: None, items }]
+                },
+            }
+        })
+        .collect();
+    write_pptx(&slides)
+}
+
+/// Render a [`Document`] to PPTX.
+///
+/// The document title gets an opening slide, and each top-level heading
+/// starts a slide titled with it. Lists keep their bullets and numbering,
+/// links are clickable, tables become native tables and loaded images are
+/// placed as pictures. Text that overflows a slide is shrunk to fit.
+pub fn render_pptx(document: &Document) -> Result<Vec<u8>> {
+    write_pptx(&split_slides(document))
+}
+
+/// A slide to write: its title and the blocks below it.
+struct Slide {
+    title: Vec<Inline>,
+    /// The opening slide, with a centred title and no slide number.
+    opening: bool,
+    blocks: Vec<Node>,
+}
+
+/// Split a document into slides at its highest heading level.
+fn split_slides(document: &Document) -> Vec<Slide> {
+    let split_level = document
+        .blocks
+        .iter()
+        .filter_map(|node| match node {
+            Node::Heading { level, .. } => Some(*level),
+            _ => None,
+        })
+        .min();
+
+    let mut slides = Vec::new();
+    let mut current = document.title.as_ref().map(|title| Slide {
+        title: vec![Inline::Text(title.clone())],
+        opening: true,
+        blocks: Vec::new(),
+    });
+    for node in &document.blocks {
+        match node {
+            Node::Heading { level, content } if Some(*level) == split_level => {
+                slides.extend(current.take());
+                current = Some(Slide {
+                    title: content.clone(),
+                    opening: false,
+                    blocks: Vec::new(),
+                });
+            }
+            other => current
+                .get_or_insert_with(|| Slide {
+                    title: Vec::new(),
+                    opening: false,
+                    blocks: Vec::new(),
+                })
+                .blocks
+                .push(other.clone()),
+        }
+    }
+    slides.extend(current);
+    slides
+}
+
+fn write_pptx(slides: &[Slide]) -> Result<Vec<u8>> {
     let buf = Cursor::new(Vec::new());
     let mut zip = ZipWriter::new(buf);
     let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
@@ -76,26 +169,667 @@ pub fn generate_pptx(slides: &[PptxSlide]) -> Result<Vec<u8>> {
     // Individual slides
     for (i, slide) in slides.iter().enumerate() {
         let slide_num = i + 1;
+        let part = SlideBuilder::new(slide_num).build(slide);
 
         // ppt/slides/slideN.xml
         let slide_path = format!("ppt/slides/slide{slide_num}.xml");
         zip.start_file(&slide_path, options)
             .with_context(|| format!("Failed to create {slide_path}"))?;
-        zip.write_all(slide_xml(&slide.title, &slide.content).as_bytes())
+        zip.write_all(part.xml.as_bytes())
             .with_context(|| format!("Failed to write {slide_path}"))?;
 
         // ppt/slides/_rels/slideN.xml.rels
         let slide_rels_path = format!("ppt/slides/_rels/slide{slide_num}.xml.rels");
         zip.start_file(&slide_rels_path, options)
             .with_context(|| format!("Failed to create {slide_rels_path}"))?;
-        zip.write_all(slide_rels_xml().as_bytes())
+        zip.write_all(slide_rels_xml(&part.rels).as_bytes())
             .with_context(|| format!("Failed to write {slide_rels_path}"))?;
+
+        // ppt/media/*, pictures placed on the slide
+        for (name, data) in &part.media {
+            let media_path = format!("ppt/media/{name}");
+            zip.start_file(&media_path, options)
+                .with_context(|| format!("Failed to create {media_path}"))?;
+            zip.write_all(data)
+                .with_context(|| format!("Failed to write {media_path}"))?;
+        }
     }
 
     let cursor = zip.finish().context("Failed to finalize PPTX zip")?;
     Ok(cursor.into_inner())
 }
 
+// ---------------------------------------------------------------------------
+// Slide content
+// ---------------------------------------------------------------------------
+
+const EMU_PER_POINT: f64 = 12700.0;
+const EMU_PER_PIXEL: f64 = 9525.0;
+/// Left edge and width of the content area.
+const CONTENT_X: i64 = 457200;
+const CONTENT_WIDTH: i64 = 8229600;
+/// Top and bottom of the content area below a slide title.
+const CONTENT_TOP: i64 = 1600200;
+const CONTENT_BOTTOM: i64 = 6126163;
+/// Top of the content area on the opening slide, below its title.
+const OPENING_TOP: i64 = 3886200;
+/// Text sizes, in hundredths of a point.
+const TITLE_SIZE: u32 = 3600;
+const OPENING_TITLE_SIZE: u32 = 4400;
+const BODY_SIZE: u32 = 1800;
+const SUBHEADING_SIZE: u32 = 2000;
+const CODE_SIZE: u32 = 1400;
+const TABLE_SIZE: u32 = 1400;
+const TABLE_ROW_HEIGHT: i64 = 370840;
+/// Indent per list level, in EMUs.
+const LIST_INDENT: i64 = 342900;
+const MONOSPACE: &str = "Courier New";
+
+/// A finished slide part with its relationships and media.
+struct SlidePart {
+    xml: String,
+    /// Relationship elements after rId1, the slide layout.
+    rels: Vec<String>,
+    /// File names under `ppt/media/` and their data.
+    media: Vec<(String, Vec<u8>)>,
+}
+
+/// Character formatting for the runs of a paragraph.
+#[derive(Clone, Copy)]
+struct RunFormat {
+    size: u32,
+    bold: bool,
+    italic: bool,
+    mono: bool,
+    /// Gray text, for quotes.
+    muted: bool,
+}
+
+impl RunFormat {
+    fn sized(size: u32) -> Self {
+        Self {
+            size,
+            bold: false,
+            italic: false,
+            mono: false,
+            muted: false,
+        }
+    }
+}
+
+/// A paragraph of a text box and its estimated height.
+struct SlideParagraph<'a> {
+    xml: String,
+    height: i64,
+    /// Embeddable images found in the paragraph, placed after its text box.
+    images: Vec<&'a Image>,
+    has_text: bool,
+}
+
+/// Slide content in reading order: text boxes, tables and pictures.
+enum Item<'a> {
+    Text(Vec<SlideParagraph<'a>>),
+    Table {
+        header: &'a [Cell],
+        rows: &'a [Vec<Cell>],
+        alignments: &'a [Alignment],
+    },
+    Picture(&'a Image),
+}
+
+struct SlideBuilder {
+    number: usize,
+    shapes: String,
+    next_shape_id: u32,
+    rels: Vec<String>,
+    media: Vec<(String, Vec<u8>)>,
+}
+
+impl SlideBuilder {
+    fn new(number: usize) -> Self {
+        Self {
+            number,
+            shapes: String::new(),
+            next_shape_id: 1,
+            rels: Vec::new(),
+            media: Vec::new(),
+        }
+    }
+
+    fn shape_id(&mut self) -> u32 {
+        self.next_shape_id += 1;
+        self.next_shape_id
+    }
+
+    /// Add a relationship from the slide and return its ID.
+    fn relationship(&mut self, kind: &str, target: &str, external: bool) -> String {
+        let id = format!("rId{}", self.rels.len() + 2);
+        let mode = if external {
+            r#" TargetMode="External""#
+        } else {
+            ""
+        };
+        self.rels.push(format!(
+            r#"  <Relationship Id="{id}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/{kind}" Target="{}"{mode}/>"#,
+            xml_escape(target)
+        ));
+        id
+    }
+
+    fn build(mut self, slide: &Slide) -> SlidePart {
+        let (title_size, top) = if slide.opening {
+            (OPENING_TITLE_SIZE, OPENING_TOP)
+        } else {
+            (TITLE_SIZE, CONTENT_TOP)
+        };
+        let title_format = RunFormat {
+            bold: true,
+            ..RunFormat::sized(title_size)
+        };
+        let align = if slide.opening { r#" algn="ctr""# } else { "" };
+        let title = self.paragraph(&format!("<a:pPr{align}/>"), &slide.title, title_format);
+        let (kind, y, height) = if slide.opening {
+            ("ctrTitle", 2130425, 1470025)
+        } else {
+            ("title", 274638, 1143000)
+        };
+        let id = self.shape_id();
+        self.shapes.push_str(&format!(
+            r#"      <p:sp>
+        <p:nvSpPr>
+          <p:cNvPr id="{id}" name="Title"/>
+          <p:cNvSpPr><a:spLocks noGrp="1"/></p:cNvSpPr>
+          <p:nvPr><p:ph type="{kind}"/></p:nvPr>
+        </p:nvSpPr>
+        <p:spPr>
+          <a:xfrm>
+            <a:off x="{CONTENT_X}" y="{y}"/>
+            <a:ext cx="{CONTENT_WIDTH}" cy="{height}"/>
+          </a:xfrm>
+        </p:spPr>
+        <p:txBody>
+          <a:bodyPr><a:normAutofit/></a:bodyPr>
+          <a:lstStyle/>
+{}
+        </p:txBody>
+      </p:sp>
+"#,
+            title.xml
+        ));
+
+        let mut items = Vec::new();
+        let mut text = Vec::new();
+        for node in &slide.blocks {
+            self.collect(node, 0, &mut items, &mut text);
+        }
+        if !text.is_empty() {
+            items.push(Item::Text(text));
+        }
+        self.place(&items, top);
+
+        if !slide.opening {
+            self.slide_number();
+        }
+        SlidePart {
+            xml: slide_xml(&self.shapes),
+            rels: self.rels,
+            media: self.media,
+        }
+    }
+
+    /// Turn a block into text paragraphs, starting a new item for tables
+    /// and pictures.
+    fn collect<'a>(
+        &mut self,
+        node: &'a Node,
+        depth: usize,
+        items: &mut Vec<Item<'a>>,
+        text: &mut Vec<SlideParagraph<'a>>,
+    ) {
+        let plain = r#"<a:pPr marL="0" indent="0"><a:buNone/></a:pPr>"#;
+        match node {
+            Node::Heading { content, .. } => {
+                let format = RunFormat {
+                    bold: true,
+                    ..RunFormat::sized(SUBHEADING_SIZE)
+                };
+                let paragraph = self.paragraph(plain, content, format);
+                self.push_paragraph(paragraph, items, text);
+            }
+            Node::Paragraph { content } => {
+                let paragraph = self.paragraph(plain, content, RunFormat::sized(BODY_SIZE));
+                self.push_paragraph(paragraph, items, text);
+            }
+            Node::List { start, items: list } => {
+                for (i, item) in list.iter().enumerate() {
+                    let level = depth.min(8);
+                    let margin = LIST_INDENT * (level as i64 + 1);
+                    let bullet = match (start, item.checked) {
+                        (_, Some(true)) => r#"<a:buChar char="☒"/>"#.to_string(),
+                        (_, Some(false)) => r#"<a:buChar char="☐"/>"#.to_string(),
+                        (Some(start), None) => format!(
+                            r#"<a:buAutoNum type="arabicPeriod" startAt="{}"/>"#,
+                            (start + i as u64).min(32767)
+                        ),
+                        (None, None) => {
+                            format!(r#"<a:buChar char="{}"/>"#, ["•", "–", "▪"][level % 3])
+                        }
+                    };
+                    let props = format!(
+                        r#"<a:pPr marL="{margin}" lvl="{level}" indent="-{LIST_INDENT}">{bullet}</a:pPr>"#
+                    );
+                    let mut bulleted = false;
+                    for block in &item.blocks {
+                        match block {
+                            Node::Paragraph { content } if !bulleted => {
+                                let paragraph =
+                                    self.paragraph(&props, content, RunFormat::sized(BODY_SIZE));
+                                self.push_paragraph(paragraph, items, text);
+                                bulleted = true;
+                            }
+                            other => self.collect(other, depth + 1, items, text),
+                        }
+                    }
+                }
+            }
+            Node::CodeBlock { code, .. } => {
+                let format = RunFormat {
+                    mono: true,
+                    ..RunFormat::sized(CODE_SIZE)
+                };
+                let code = code.strip_suffix('\n').unwrap_or(code);
+                for line in code.split('\n') {
+                    let content = [Inline::Text(line.replace('\t', "    "))];
+                    let paragraph = self.paragraph(plain, &content, format);
+                    text.push(SlideParagraph {
+                        xml: paragraph.xml,
+                        height: paragraph.height,
+                        images: Vec::new(),
+                        has_text: paragraph.has_text,
+                    });
+                }
+            }
+            Node::Quote { blocks } => {
+                let props =
+                    format!(r#"<a:pPr marL="{LIST_INDENT}" indent="0"><a:buNone/></a:pPr>"#);
+                let format = RunFormat {
+                    italic: true,
+                    muted: true,
+                    ..RunFormat::sized(BODY_SIZE)
+                };
+                for block in blocks {
+                    match block {
+                        Node::Paragraph { content } => {
+                            let paragraph = self.paragraph(&props, content, format);
+                            self.push_paragraph(paragraph, items, text);
+                        }
+                        other => self.collect(other, depth, items, text),
+                    }
+                }
+            }
+            Node::Table {
+                header,
+                rows,
+                alignments,
+            } => {
+                if !text.is_empty() {
+                    items.push(Item::Text(std::mem::take(text)));
+                }
+                items.push(Item::Table {
+                    header,
+                    rows,
+                    alignments,
+                });
+            }
+            Node::Rule => {}
+        }
+    }
+
+    fn push_paragraph<'a>(
+        &mut self,
+        paragraph: SlideParagraph<'a>,
+        items: &mut Vec<Item<'a>>,
+        text: &mut Vec<SlideParagraph<'a>>,
+    ) {
+        let images = paragraph.images.clone();
+        if paragraph.has_text || images.is_empty() {
+            text.push(paragraph);
+        }
+        if !images.is_empty() {
+            if !text.is_empty() {
+                items.push(Item::Text(std::mem::take(text)));
+            }
+            items.extend(images.into_iter().map(Item::Picture));
+        }
+    }
+
+    /// An `a:p` element: paragraph properties, then runs for the content.
+    /// Embeddable images are set aside for the caller to place.
+    fn paragraph<'a>(
+        &mut self,
+        props: &str,
+        content: &'a [Inline],
+        format: RunFormat,
+    ) -> SlideParagraph<'a> {
+        let mut xml = format!("          <a:p>{props}");
+        let mut images = Vec::new();
+        let mut chars = 0;
+        let mut lines = 1;
+        for span in spans(content) {
+            match span {
+                Span::Text { text, style, link } => {
+                    let hyperlink = link.map(|url| self.relationship("hyperlink", url, true));
+                    xml.push_str(&self.run(text, style, link.is_some(), hyperlink, format));
+                    chars += text.chars().count();
+                }
+                Span::LineBreak => {
+                    xml.push_str(&format!(
+                        r#"<a:br><a:rPr lang="en-US" sz="{}"/></a:br>"#,
+                        format.size
+                    ));
+                    lines += 1;
+                }
+                Span::Image(image) => {
+                    if image.data.as_deref().and_then(image_info).is_some() {
+                        images.push(image);
+                    } else {
+                        // Nothing embeddable: show the alt text instead.
+                        let label = format!("[{}]", image_label(image));
+                        chars += label.chars().count();
+                        let style = TextStyle {
+                            italic: true,
+                            ..TextStyle::default()
+                        };
+                        xml.push_str(&self.run(&label, style, false, None, format));
+                    }
+                }
+            }
+        }
+        xml.push_str(&format!(
+            r#"<a:endParaRPr lang="en-US" sz="{}" dirty="0"/></a:p>"#,
+            format.size
+        ));
+
+        // Roughly half an em per character.
+        let size = f64::from(format.size) / 100.0;
+        let per_line = (CONTENT_WIDTH as f64 / (size * 0.5 * EMU_PER_POINT)).max(1.0);
+        lines += (chars as f64 / per_line) as i64;
+        SlideParagraph {
+            xml,
+            height: (lines as f64 * size * 1.2 * EMU_PER_POINT) as i64,
+            images,
+            has_text: chars > 0,
+        }
+    }
+
+    fn run(
+        &self,
+        text: &str,
+        style: TextStyle,
+        linked: bool,
+        hyperlink: Option<String>,
+        format: RunFormat,
+    ) -> String {
+        let mut attrs = format!(r#" lang="en-US" sz="{}""#, format.size);
+        if style.bold || format.bold {
+            attrs.push_str(r#" b="1""#);
+        }
+        if style.italic || format.italic {
+            attrs.push_str(r#" i="1""#);
+        }
+        if style.strike {
+            attrs.push_str(r#" strike="sngStrike""#);
+        }
+        if linked {
+            attrs.push_str(r#" u="sng""#);
+        }
+        let mut props = String::new();
+        if format.muted {
+            props.push_str(r#"<a:solidFill><a:srgbClr val="595959"/></a:solidFill>"#);
+        }
+        if style.code || format.mono {
+            props.push_str(&format!(r#"<a:latin typeface="{MONOSPACE}"/>"#));
+        }
+        if let Some(id) = hyperlink {
+            props.push_str(&format!(r#"<a:hlinkClick r:id="{id}"/>"#));
+        }
+        format!(
+            r#"<a:r><a:rPr{attrs} dirty="0">{props}</a:rPr><a:t>{}</a:t></a:r>"#,
+            xml_escape(text)
+        )
+    }
+
+    /// Stack the items down the content area. Text boxes and tables take
+    /// their estimated height; pictures share what is left.
+    fn place(&mut self, items: &[Item<'_>], top: i64) {
+        let available = CONTENT_BOTTOM - top;
+        if let [Item::Text(paragraphs)] = items {
+            self.text_box(paragraphs, top, available, 1);
+            return;
+        }
+
+        let fixed: i64 = items
+            .iter()
+            .map(|item| match item {
+                Item::Text(paragraphs) => text_height(paragraphs),
+                Item::Table { rows, .. } => TABLE_ROW_HEIGHT * (rows.len() as i64 + 1),
+                Item::Picture(_) => 0,
+            })
+            .sum();
+        let pictures = items
+            .iter()
+            .filter(|item| matches!(item, Item::Picture(_)))
+            .count()
+            .max(1) as i64;
+        let picture_height = ((available - fixed) / pictures).max(available / 4);
+
+        let mut y = top;
+        let mut text_boxes = 0;
+        for item in items {
+            y += match item {
+                Item::Text(paragraphs) => {
+                    text_boxes += 1;
+                    let height = text_height(paragraphs);
+                    self.text_box(paragraphs, y, height, text_boxes);
+                    height
+                }
+                Item::Table {
+                    header,
+                    rows,
+                    alignments,
+                } => self.table(header, rows, alignments, y),
+                Item::Picture(image) => self.picture(image, y, picture_height),
+            };
+        }
+    }
+
+    fn text_box(&mut self, paragraphs: &[SlideParagraph<'_>], y: i64, height: i64, index: usize) {
+        let id = self.shape_id();
+        let body: Vec<&str> = paragraphs.iter().map(|p| p.xml.as_str()).collect();
+        self.shapes.push_str(&format!(
+            r#"      <p:sp>
+        <p:nvSpPr>
+          <p:cNvPr id="{id}" name="Content {index}"/>
+          <p:cNvSpPr><a:spLocks noGrp="1"/></p:cNvSpPr>
+          <p:nvPr><p:ph idx="{index}"/></p:nvPr>
+        </p:nvSpPr>
+        <p:spPr>
+          <a:xfrm>
+            <a:off x="{CONTENT_X}" y="{y}"/>
+            <a:ext cx="{CONTENT_WIDTH}" cy="{height}"/>
+          </a:xfrm>
+        </p:spPr>
+        <p:txBody>
+          <a:bodyPr><a:normAutofit/></a:bodyPr>
+          <a:lstStyle/>
+{}
+        </p:txBody>
+      </p:sp>
+"#,
+            body.join("\n")
+        ));
+    }
+
+    /// A native table with a bold header row; returns its height.
+    fn table(
+        &mut self,
+        header: &[Cell],
+        rows: &[Vec<Cell>],
+        alignments: &[Alignment],
+        y: i64,
+    ) -> i64 {
+        let columns = header
+            .len()
+            .max(rows.iter().map(Vec::len).max().unwrap_or(0))
+            .max(1);
+        let column_width = CONTENT_WIDTH / columns as i64;
+        let grid: String = (0..columns)
+            .map(|_| format!(r#"<a:gridCol w="{column_width}"/>"#))
+            .collect();
+
+        let mut body = String::new();
+        for (i, row) in std::iter::once(header)
+            .chain(rows.iter().map(Vec::as_slice))
+            .enumerate()
+        {
+            body.push_str(&format!(r#"<a:tr h="{TABLE_ROW_HEIGHT}">"#));
+            for column in 0..columns {
+                let align = match alignments.get(column).copied().unwrap_or_default() {
+                    Alignment::Center => r#" algn="ctr""#,
+                    Alignment::Right => r#" algn="r""#,
+                    Alignment::None | Alignment::Left => "",
+                };
+                let format = RunFormat {
+                    bold: i == 0,
+                    ..RunFormat::sized(TABLE_SIZE)
+                };
+                let empty = Vec::new();
+                let cell = row.get(column).unwrap_or(&empty);
+                let paragraph = self.paragraph(&format!("<a:pPr{align}/>"), cell, format);
+                let fill = if i == 0 {
+                    "D9E2F3"
+                } else if i % 2 == 0 {
+                    "F2F2F2"
+                } else {
+                    "FFFFFF"
+                };
+                body.push_str(&format!(
+                    r#"<a:tc><a:txBody><a:bodyPr/><a:lstStyle/>{}</a:txBody><a:tcPr><a:solidFill><a:srgbClr val="{fill}"/></a:solidFill></a:tcPr></a:tc>"#,
+                    paragraph.xml.trim_start()
+                ));
+            }
+            body.push_str("</a:tr>");
+        }
+
+        let height = TABLE_ROW_HEIGHT * (rows.len() as i64 + 1);
+        let id = self.shape_id();
+        self.shapes.push_str(&format!(
+            r#"      <p:graphicFrame>
+        <p:nvGraphicFramePr>
+          <p:cNvPr id="{id}" name="Table {id}"/>
+          <p:cNvGraphicFramePr><a:graphicFrameLocks noGrp="1"/></p:cNvGraphicFramePr>
+          <p:nvPr/>
+        </p:nvGraphicFramePr>
+        <p:xfrm>
+          <a:off x="{CONTENT_X}" y="{y}"/>
+          <a:ext cx="{CONTENT_WIDTH}" cy="{height}"/>
+        </p:xfrm>
+        <a:graphic>
+          <a:graphicData uri="http://schemas.openxmlformats.org/drawingml/2006/table">
+            <a:tbl><a:tblPr firstRow="1" bandRow="1"/><a:tblGrid>{grid}</a:tblGrid>{body}</a:tbl>
+          </a:graphicData>
+        </a:graphic>
+      </p:graphicFrame>
+"#
+        ));
+        height
+    }
+
+    /// A picture at 96 dpi, shrunk to the content width and `max_height`
+    /// and centred; returns its height.
+    fn picture(&mut self, image: &Image, y: i64, max_height: i64) -> i64 {
+        let Some((data, info)) = image
+            .data
+            .as_ref()
+            .and_then(|data| Some((data, image_info(data)?)))
+        else {
+            return 0;
+        };
+        let width = f64::from(info.width) * EMU_PER_PIXEL;
+        let height = f64::from(info.height) * EMU_PER_PIXEL;
+        let scale = (CONTENT_WIDTH as f64 / width)
+            .min(max_height as f64 / height)
+            .min(1.0);
+        let (cx, cy) = ((width * scale) as i64, (height * scale) as i64);
+        let x = CONTENT_X + (CONTENT_WIDTH - cx) / 2;
+
+        let name = format!(
+            "image{}-{}.{}",
+            self.number,
+            self.media.len() + 1,
+            info.format.extension()
+        );
+        let rel = self.relationship("image", &format!("../media/{name}"), false);
+        self.media.push((name, data.clone()));
+        let id = self.shape_id();
+        self.shapes.push_str(&format!(
+            r#"      <p:pic>
+        <p:nvPicPr>
+          <p:cNvPr id="{id}" name="Picture {id}" descr="{}"/>
+          <p:cNvPicPr><a:picLocks noChangeAspect="1"/></p:cNvPicPr>
+          <p:nvPr/>
+        </p:nvPicPr>
+        <p:blipFill><a:blip r:embed="{rel}"/><a:stretch><a:fillRect/></a:stretch></p:blipFill>
+        <p:spPr>
+          <a:xfrm><a:off x="{x}" y="{y}"/><a:ext cx="{cx}" cy="{cy}"/></a:xfrm>
+          <a:prstGeom prst="rect"><a:avLst/></a:prstGeom>
+        </p:spPr>
+      </p:pic>
+"#,
+            xml_escape(&image.alt)
+        ));
+        cy
+    }
+
+    fn slide_number(&mut self) {
+        let id = self.shape_id();
+        let number = self.number;
+        self.shapes.push_str(&format!(
+            r#"      <p:sp>
+        <p:nvSpPr>
+          <p:cNvPr id="{id}" name="Slide Number"/>
+          <p:cNvSpPr><a:spLocks noGrp="1"/></p:cNvSpPr>
+          <p:nvPr><p:ph type="sldNum" sz="quarter" idx="12"/></p:nvPr>
+        </p:nvSpPr>
+        <p:spPr>
+          <a:xfrm>
+            <a:off x="6553200" y="6356350"/>
+            <a:ext cx="2133600" cy="365125"/>
+          </a:xfrm>
+        </p:spPr>
+        <p:txBody>
+          <a:bodyPr/>
+          <a:lstStyle/>
+          <a:p><a:pPr algn="r"/><a:fld id="{{B6F15528-21DE-4FAA-801E-634DDDAF4B2B}}" type="slidenum"><a:rPr lang="en-US" sz="1200"/><a:t>{number}</a:t></a:fld><a:endParaRPr lang="en-US" sz="1200"/></a:p>
+        </p:txBody>
+      </p:sp>
+"#
+        ));
+    }
+}
+
+fn text_height(paragraphs: &[SlideParagraph<'_>]) -> i64 {
+    paragraphs.iter().map(|p| p.height).sum::<i64>() + 91440
+}
+
+fn image_label(image: &Image) -> &str {
+    if image.alt.is_empty() {
+        &image.src
+    } else {
+        &image.alt
+    }
+}
+
 // ---------------------------------------------------------------------------
 // XML template functions
 // ---------------------------------------------------------------------------
@@ -121,6 +855,8 @@ fn content_types_xml(slide_count: usize) -> String {
 <Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
   <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
   <Default Extension="xml" ContentType="application/xml"/>
+  <Default Extension="png" ContentType="image/png"/>
+  <Default Extension="jpeg" ContentType="image/jpeg"/>
   <Override PartName="/ppt/presentation.xml" ContentType="application/vnd.openxmlformats-officedocument.presentationml.presentation.main+xml"/>
   <Override PartName="/ppt/slideMasters/slideMaster1.xml" ContentType="application/vnd.openxmlformats-officedocument.presentationml.slideMaster+xml"/>
   <Override PartName="/ppt/slideLayouts/slideLayout1.xml" ContentType="application/vnd.openxmlformats-officedocument.presentationml.slideLayout+xml"/>
@@ -298,28 +1034,7 @@ fn theme_xml() -> String {
         .to_string()
 }
 
-fn slide_xml(title: &str, content: &str) -> String {
-    let escaped_title = xml_escape(title);
-    let escaped_content = xml_escape(content);
-
-    // Build content paragraphs -- each line becomes a separate <a:p> element
-    let mut content_paragraphs = String::new();
-    for line in escaped_content.lines() {
-        content_paragraphs.push_str(&format!(
-            r#"              <a:p>
-                <a:r>
-                  <a:rPr lang="en-US" sz="1800" dirty="0"/>
-                  <a:t>{line}</a:t>
-                </a:r>
-              </a:p>"#
-        ));
-        content_paragraphs.push('\n');
-    }
-    // If content is empty, add an empty paragraph
-    if content.is_empty() {
-        content_paragraphs.push_str("              <a:p><a:endParaRPr lang=\"en-US\"/></a:p>\n");
-    }
-
+fn slide_xml(shapes: &str) -> String {
     format!(
         r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <p:sld xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main"
@@ -333,63 +1048,217 @@ fn slide_xml(title: &str, content: &str) -> String {
         <p:nvPr/>
       </p:nvGrpSpPr>
       <p:grpSpPr/>
-      <p:sp>
-        <p:nvSpPr>
-          <p:cNvPr id="2" name="Title"/>
-          <p:cNvSpPr><a:spLocks noGrp="1"/></p:cNvSpPr>
-          <p:nvPr><p:ph type="title"/></p:nvPr>
-        </p:nvSpPr>
-        <p:spPr>
-          <a:xfrm>
-            <a:off x="457200" y="274638"/>
-            <a:ext cx="8229600" cy="1143000"/>
-          </a:xfrm>
-        </p:spPr>
-        <p:txBody>
-          <a:bodyPr/>
-          <a:lstStyle/>
-          <a:p>
-            <a:r>
-              <a:rPr lang="en-US" sz="3600" b="1" dirty="0"/>
-              <a:t>{escaped_title}</a:t>
-            </a:r>
-          </a:p>
-        </p:txBody>
-      </p:sp>
-      <p:sp>
-        <p:nvSpPr>
-          <p:cNvPr id="3" name="Content"/>
-          <p:cNvSpPr><a:spLocks noGrp="1"/></p:cNvSpPr>
-          <p:nvPr><p:ph idx="1"/></p:nvPr>
-        </p:nvSpPr>
-        <p:spPr>
-          <a:xfrm>
-            <a:off x="457200" y="1600200"/>
-            <a:ext cx="8229600" cy="4525963"/>
-          </a:xfrm>
-        </p:spPr>
-        <p:txBody>
-          <a:bodyPr/>
-          <a:lstStyle/>
-{content_paragraphs}        </p:txBody>
-      </p:sp>
-    </p:spTree>
+{shapes}    </p:spTree>
   </p:cSld>
 </p:sld>"#
     )
 }
 
-fn slide_rels_xml() -> String {
-    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+fn slide_rels_xml(rels: &[String]) -> String {
+    let mut extra = String::new();
+    for rel in rels {
+        extra.push_str(rel);
+        extra.push('\n');
+    }
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
   <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slideLayout" Target="../slideLayouts/slideLayout1.xml"/>
-</Relationships>"#
-        .to_string()
+{extra}</Relationships>"#
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Extraction
+// ---------------------------------------------------------------------------
+
+/// Extract slide titles, body text, tables and speaker notes from a PPTX
+/// file, one section per slide in presentation order.
+pub fn extract_pptx(bytes: &[u8]) -> Result<ExtractedDocument> {
+    let mut package = Package::open(bytes)?;
+    let presentation = package.main_part()?;
+    let rels = package.relationships(&presentation)?;
+    let xml = package
+        .read(&presentation)?
+        .with_context(|| format!("Missing presentation part {presentation}"))?;
+
+    let doc = parse_xml(&xml)?;
+    let slide_parts: Vec<String> = doc
+        .descendants()
+        .filter(|n| is(*n, "sldId"))
+        .filter_map(rel_id)
+        .filter_map(|id| rels.iter().find(|r| r.id == id))
+        .map(|r| r.target.clone())
+        .collect();
+
+    let mut document = ExtractedDocument::new(DocumentFormat::Pptx);
+    for (i, part) in slide_parts.iter().enumerate() {
+        let Some(slide_xml) = package.read(part)? else {
+            continue;
+        };
+        let mut section = Section::new(Some(Anchor::Slide { number: i + 1 }));
+        let slide = parse_xml(&slide_xml)?;
+        if let Some(tree) = slide.descendants().find(|n| is(*n, "spTree")) {
+            read_shapes(tree, &mut section);
+        }
+
+        let slide_rels = package.relationships(part)?;
+        if let Some(notes) = find_relationship(&slide_rels, "notesSlide")
+            && let Some(notes_xml) = package.read(&notes.target)?
+        {
+            let text = notes_text(&notes_xml)?;
+            if !text.is_empty() {
+                section.blocks.push(Block::Notes { text });
+            }
+        }
+        document.sections.push(section);
+    }
+    Ok(document)
+}
+
+fn read_shapes(tree: roxmltree::Node<'_, '_>, section: &mut Section) {
+    for shape in tree.children().filter(|n| n.is_element()) {
+        match shape.tag_name().name() {
+            "sp" => {
+                let Some(body) = child(shape, "txBody") else {
+                    continue;
+                };
+                let paragraphs = text_paragraphs(body);
+                if paragraphs.is_empty() {
+                    continue;
+                }
+                match placeholder_type(shape) {
+                    Some("title" | "ctrTitle") if section.title.is_none() => {
+                        let title: Vec<&str> = paragraphs.iter().map(|p| p.text.as_str()).collect();
+                        section.title = Some(title.join(" "));
+                    }
+                    // Slide numbers, dates and footers repeat on every slide.
+                    Some("sldNum" | "dt" | "ftr" | "hdr") => {}
+                    Some("title" | "ctrTitle" | "subTitle") | None => {
+                        section.blocks.push(Block::Paragraph {
+                            text: paragraphs
+                                .iter()
+                                .map(|p| p.text.as_str())
+                                .collect::<Vec<_>>()
+                                .join("\n"),
+                        });
+                    }
+                    Some(_) => {
+                        section.blocks.push(Block::Paragraph {
+                            text: paragraphs
+                                .iter()
+                                .map(TextParagraph::as_bullet)
+                                .collect::<Vec<_>>()
+                                .join("\n"),
+                        });
+                    }
+                }
+            }
+            "graphicFrame" => {
+                let Some(table) = shape.descendants().find(|n| is(*n, "tbl")) else {
+                    continue;
+                };
+                let rows: Vec<Vec<String>> = table
+                    .children()
+                    .filter(|n| is(*n, "tr"))
+                    .map(|row| {
+                        row.children()
+                            .filter(|n| is(*n, "tc"))
+                            .map(|cell| {
+                                text_paragraphs(cell)
+                                    .into_iter()
+                                    .map(|p| p.text)
+                                    .collect::<Vec<_>>()
+                                    .join(" ")
+                            })
+                            .collect()
+                    })
+                    .collect();
+                if !rows.is_empty() {
+                    section.blocks.push(Block::Table { rows });
+                }
+            }
+            "grpSp" => read_shapes(shape, section),
+            _ => {}
+        }
+    }
+}
+
+/// The shape's placeholder type; untyped placeholders are body content.
+fn placeholder_type<'a>(shape: roxmltree::Node<'a, '_>) -> Option<&'a str> {
+    let ph = shape
+        .children()
+        .find(|n| n.tag_name().name().starts_with("nv"))?
+        .descendants()
+        .find(|n| is(*n, "ph"))?;
+    Some(attr(ph, "type").unwrap_or("body"))
+}
+
+struct TextParagraph {
+    level: usize,
+    bulleted: bool,
+    text: String,
+}
+
+impl TextParagraph {
+    fn as_bullet(&self) -> String {
+        if self.bulleted {
+            format!("{}- {}", "  ".repeat(self.level), self.text)
+        } else {
+            self.text.clone()
+        }
+    }
+}
+
+/// Non-empty `a:p` paragraphs directly under a text body or table cell.
+fn text_paragraphs(body: roxmltree::Node<'_, '_>) -> Vec<TextParagraph> {
+    body.descendants()
+        .filter(|n| is(*n, "p") && n.parent().is_some_and(|p| is(p, "txBody")))
+        .filter_map(|p| {
+            let mut text = String::new();
+            for node in p.descendants().filter(|n| n.is_element()) {
+                match node.tag_name().name() {
+                    "t" => text.push_str(node.text().unwrap_or_default()),
+                    "br" => text.push('\n'),
+                    _ => {}
+                }
+            }
+            let text = text.trim().to_string();
+            if text.is_empty() {
+                return None;
+            }
+            let props = child(p, "pPr");
+            Some(TextParagraph {
+                level: props
+                    .and_then(|props| attr(props, "lvl"))
+                    .and_then(|lvl| lvl.parse().ok())
+                    .unwrap_or(0),
+                bulleted: props.is_none_or(|props| child(props, "buNone").is_none()),
+                text,
+            })
+        })
+        .collect()
+}
+
+/// Text of the body placeholder on a notes slide.
+fn notes_text(xml: &str) -> Result<String> {
+    let doc = parse_xml(xml)?;
+    let text = doc
+        .descendants()
+        .filter(|n| is(*n, "sp") && placeholder_type(*n) == Some("body"))
+        .filter_map(|shape| child(shape, "txBody"))
+        .flat_map(text_paragraphs)
+        .map(|p| p.text)
+        .collect::<Vec<_>>()
+        .join("\n");
+    Ok(text)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::document::test_png;
+    use crate::markdown::parse_markdown;
 
     #[test]
     fn test_generate_pptx_single_slide() {
@@ -485,4 +1354,135 @@ mod tests {
         assert!(names.contains(&"ppt/slideLayouts/slideLayout1.xml"));
         assert!(names.contains(&"ppt/theme/theme1.xml"));
     }
+
+    #[test]
+    fn test_extract_pptx_roundtrip() {
+        let slides = vec![
+            PptxSlide {
+                title: "Roadmap".to_string(),
+                content: "Design & build\nShip".to_string(),
+            },
+            PptxSlide {
+                title: "Title Only".to_string(),
+                content: String::new(),
+            },
+        ];
+        let doc = extract_pptx(&generate_pptx(&slides).unwrap()).unwrap();
+
+        assert_eq!(doc.sections.len(), 2);
+        assert_eq!(doc.sections[0].anchor, Some(Anchor::Slide { number: 1 }));
+        assert_eq!(doc.sections[0].title.as_deref(), Some("Roadmap"));
+        assert_eq!(
+            doc.sections[0].blocks,
+            vec![Block::Paragraph {
+                text: "- Design & build\n- Ship".to_string()
+            }]
+        );
+        assert_eq!(doc.sections[1].title.as_deref(), Some("Title Only"));
+        assert!(doc.sections[1].blocks.is_empty());
+        assert!(doc.to_markdown().contains("[slide 2]\n\n## Title Only"));
+    }
+
+    #[test]
+    fn test_render_pptx_from_markdown_roundtrip() {
+        let mut document = parse_markdown(
+            "# Deck\n\nIntro with **bold** text.\n\n- One\n  - Two\n\n\
+             | A | B |\n| --- | --- |\n| 1 | 2 |\n\n\
+             # Next\n\nSee [docs](https://example.com/docs).\n\n![Logo](logo.png)\n",
+        );
+        document.title = Some("Quarterly Review".into());
+        if let Node::Paragraph { content } = document.blocks.last_mut().unwrap()
+            && let Inline::Image(image) = &mut content[0]
+        {
+            image.data = Some(test_png());
+        }
+        let bytes = render_pptx(&document).unwrap();
+        let doc = extract_pptx(&bytes).unwrap();
+
+        assert_eq!(doc.sections.len(), 3);
+        assert_eq!(doc.sections[0].title.as_deref(), Some("Quarterly Review"));
+        assert_eq!(doc.sections[1].title.as_deref(), Some("Deck"));
+        assert_eq!(
+            doc.sections[1].blocks,
+            vec![
+                Block::Paragraph {
+                    text: "Intro with bold text.\n- One\n  - Two".to_string()
+                },
+                Block::Table {
+                    rows: vec![
+                        vec!["A".to_string(), "B".to_string()],
+                        vec!["1".to_string(), "2".to_string()],
+                    ]
+                },
+            ]
+        );
+        assert_eq!(doc.sections[2].title.as_deref(), Some("Next"));
+        assert_eq!(
+            doc.sections[2].blocks,
+            vec![Block::Paragraph {
+                text: "See docs.".to_string()
+            }]
+        );
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(&bytes)).unwrap();
+        assert!(archive.by_name("ppt/media/image3-1.png").is_ok());
+        let mut rels = String::new();
+        std::io::Read::read_to_string(
+            &mut archive.by_name("ppt/slides/_rels/slide3.xml.rels").unwrap(),
+            &mut rels,
+        )
+        .unwrap();
+        assert!(rels.contains(r#"Target="https://example.com/docs" TargetMode="External""#));
+        assert!(rels.contains(r#"Target="../media/image3-1.png""#));
+    }
+
+    #[test]
+    fn test_extract_pptx_speaker_notes() {
+        let slides = vec![PptxSlide {
+            title: "Intro".to_string(),
+            content: "Agenda".to_string(),
+        }];
+        let bytes = generate_pptx(&slides).unwrap();
+
+        // Copy the deck, linking a notes slide to slide 1.
+        let mut archive = zip::ZipArchive::new(Cursor::new(&bytes)).unwrap();
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default();
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i).unwrap();
+            let name = file.name().to_string();
+            let mut data = Vec::new();
+            std::io::Read::read_to_end(&mut file, &mut data).unwrap();
+            if name == "ppt/slides/_rels/slide1.xml.rels" {
+                data = String::from_utf8(data)
+                    .unwrap()
+                    .replace(
+                        "</Relationships>",
+                        r#"<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/notesSlide" Target="../notesSlides/notesSlide1.xml"/></Relationships>"#,
+                    )
+                    .into_bytes();
+            }
+            zip.start_file(name, options).unwrap();
+            zip.write_all(&data).unwrap();
+        }
+        zip.start_file("ppt/notesSlides/notesSlide1.xml", options)
+            .unwrap();
+        zip.write_all(
+            br#"<p:notes xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main"><p:cSld><p:spTree>
+<p:sp><p:nvSpPr><p:cNvPr id="2" name="Image"/><p:cNvSpPr/><p:nvPr><p:ph type="sldImg"/></p:nvPr></p:nvSpPr></p:sp>
+<p:sp><p:nvSpPr><p:cNvPr id="3" name="Notes"/><p:cNvSpPr/><p:nvPr><p:ph type="body" idx="1"/></p:nvPr></p:nvSpPr>
+<p:txBody><a:p><a:r><a:t>Welcome everyone.</a:t></a:r></a:p><a:p><a:r><a:t>Keep it short.</a:t></a:r></a:p></p:txBody></p:sp>
+</p:spTree></p:cSld></p:notes>"#,
+        )
+        .unwrap();
+        let bytes = zip.finish().unwrap().into_inner();
+
+        let doc = extract_pptx(&bytes).unwrap();
+        assert_eq!(
+            doc.sections[0].blocks.last(),
+            Some(&Block::Notes {
+                text: "Welcome everyone.\nKeep it short.".to_string()
+            })
+        );
+    }
 }
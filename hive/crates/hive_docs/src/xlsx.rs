@@ -1,98 +1,872 @@
 use anyhow::{Context, Result};
-use rust_xlsxwriter::{Format, Workbook};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use rust_xlsxwriter::{
+    Chart, ChartType, Color, ConditionalFormat2ColorScale, ConditionalFormatCell,
+    ConditionalFormatCellRule, ConditionalFormatDataBar, ExcelDateTime, Format, Formula, Workbook,
+    Worksheet,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::extract::{
+    Anchor, Block, DocumentFormat, ExtractedDocument, Package, Section, attr, child,
+    find_relationship, is, parse_xml, rel_id,
+};
 
 /// A single sheet definition: (name, headers, rows).
 type SheetDef<'a> = (&'a str, &'a [&'a str], &'a [Vec<String>]);
 
+/// Number format applied to date cells whose column sets none.
+const DATE_FORMAT: &str = "yyyy-mm-dd";
+/// Number format applied to date-time cells whose column sets none.
+const DATETIME_FORMAT: &str = "yyyy-mm-dd hh:mm";
+
+/// Rows between the tops of stacked charts (the default chart is 15 rows tall).
+const CHART_ROW_SPACING: u32 = 16;
+
+/// Most digits an inferred number may have; every 15-digit decimal survives
+/// the round trip through `f64`.
+const MAX_INFERRED_DIGITS: usize = 15;
+
+/// A typed cell value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CellValue {
+    Empty,
+    Text(String),
+    Number(f64),
+    Bool(bool),
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+    /// A formula (without the leading `=`) and, optionally, its cached
+    /// result so readers that don't recalculate still see a value.
+    Formula {
+        formula: String,
+        result: Option<String>,
+    },
+}
+
+impl CellValue {
+    /// A number when the text is a plain decimal such as `42`, `-3.5` or
+    /// `0.25`, otherwise text -- how untyped string rows are written.
+    ///
+    /// Leading zeros, `+`, exponents, `inf`/`nan` and values with more
+    /// digits than an `f64` keeps stay text, so zip codes, phone numbers and
+    /// long ids are not mangled.
+    pub fn infer(text: &str) -> Self {
+        if is_plain_decimal(text)
+            && let Ok(num) = text.parse::<f64>()
+        {
+            return Self::Number(num);
+        }
+        Self::Text(text.to_string())
+    }
+
+    /// A formula such as `SUM(B2:B10)`; a leading `=` is accepted.
+    pub fn formula(formula: impl Into<String>) -> Self {
+        let formula = formula.into();
+        Self::Formula {
+            formula: formula.strip_prefix('=').unwrap_or(&formula).to_string(),
+            result: None,
+        }
+    }
+
+    /// Attach a cached result to a formula. Other values are unchanged.
+    pub fn with_result(self, value: impl ToString) -> Self {
+        match self {
+            Self::Formula { formula, .. } => Self::Formula {
+                formula,
+                result: Some(value.to_string()),
+            },
+            other => other,
+        }
+    }
+}
+
+/// Whether `text` is an optional minus, an integer part without leading
+/// zeros and an optional fraction, in at most [`MAX_INFERRED_DIGITS`] digits.
+fn is_plain_decimal(text: &str) -> bool {
+    let unsigned = text.strip_prefix('-').unwrap_or(text);
+    let (int, frac) = match unsigned.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (unsigned, None),
+    };
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    digits(int)
+        && (int == "0" || !int.starts_with('0'))
+        && frac.is_none_or(digits)
+        && int.len() + frac.map_or(0, str::len) <= MAX_INFERRED_DIGITS
+}
+
+impl From<&str> for CellValue {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+impl From<String> for CellValue {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<f64> for CellValue {
+    fn from(num: f64) -> Self {
+        Self::Number(num)
+    }
+}
+
+impl From<i64> for CellValue {
+    fn from(num: i64) -> Self {
+        Self::Number(num as f64)
+    }
+}
+
+impl From<u64> for CellValue {
+    fn from(num: u64) -> Self {
+        Self::Number(num as f64)
+    }
+}
+
+impl From<usize> for CellValue {
+    fn from(num: usize) -> Self {
+        Self::Number(num as f64)
+    }
+}
+
+impl From<bool> for CellValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<NaiveDate> for CellValue {
+    fn from(date: NaiveDate) -> Self {
+        Self::Date(date)
+    }
+}
+
+impl From<NaiveDateTime> for CellValue {
+    fn from(datetime: NaiveDateTime) -> Self {
+        Self::DateTime(datetime)
+    }
+}
+
+impl<T: Into<CellValue>> From<Option<T>> for CellValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Empty, Into::into)
+    }
+}
+
+/// A sheet column: its header plus optional width and number format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    pub header: String,
+    /// Width in characters; autofit when unset.
+    pub width: Option<f64>,
+    /// Excel number format code, e.g. `"$#,##0.00"` or `"0.0%"`.
+    pub number_format: Option<String>,
+}
+
+impl Column {
+    pub fn new(header: impl Into<String>) -> Self {
+        Self {
+            header: header.into(),
+            width: None,
+            number_format: None,
+        }
+    }
+
+    pub fn with_width(mut self, width: f64) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    pub fn with_number_format(mut self, format: impl Into<String>) -> Self {
+        self.number_format = Some(format.into());
+        self
+    }
+}
+
+/// Conditional formatting applied to a column's data cells. Colors are
+/// `0xRRGGBB`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConditionalRule {
+    /// Fill cells greater than `value`.
+    GreaterThan { value: f64, color: u32 },
+    /// Fill cells less than `value`.
+    LessThan { value: f64, color: u32 },
+    /// In-cell bars proportional to each value.
+    DataBar { color: u32 },
+    /// Two-color gradient from the lowest to the highest value.
+    ColorScale { min_color: u32, max_color: u32 },
+}
+
+/// Native chart types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartKind {
+    /// Vertical bars.
+    Column,
+    /// Horizontal bars.
+    Bar,
+    Line,
+}
+
+/// A chart over a sheet's data rows, placed to the right of the data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartSpec {
+    pub kind: ChartKind,
+    pub title: String,
+    /// Column holding the category (x-axis) labels.
+    pub category_column: usize,
+    /// Columns plotted as series, each named by its header.
+    pub value_columns: Vec<usize>,
+}
+
+impl ChartSpec {
+    pub fn new(
+        kind: ChartKind,
+        title: impl Into<String>,
+        category_column: usize,
+        value_columns: impl Into<Vec<usize>>,
+    ) -> Self {
+        Self {
+            kind,
+            title: title.into(),
+            category_column,
+            value_columns: value_columns.into(),
+        }
+    }
+}
+
+/// A worksheet of typed rows under a bold header row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sheet {
+    pub name: String,
+    pub columns: Vec<Column>,
+    pub rows: Vec<Vec<CellValue>>,
+    /// Bold rows after the data (totals, rates), outside the ranges charts
+    /// and conditional formats cover.
+    pub footer: Vec<Vec<CellValue>>,
+    /// Keep the header row visible while scrolling.
+    pub freeze_header: bool,
+    /// Rules keyed by column index.
+    pub conditional_formats: Vec<(usize, ConditionalRule)>,
+    pub charts: Vec<ChartSpec>,
+}
+
+impl Sheet {
+    pub fn new(name: impl Into<String>, columns: Vec<Column>) -> Self {
+        Self {
+            name: name.into(),
+            columns,
+            rows: Vec::new(),
+            footer: Vec::new(),
+            freeze_header: false,
+            conditional_formats: Vec::new(),
+            charts: Vec::new(),
+        }
+    }
+
+    pub fn push_row<I>(&mut self, cells: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<CellValue>,
+    {
+        self.rows.push(cells.into_iter().map(Into::into).collect());
+    }
+
+    pub fn push_footer_row<I>(&mut self, cells: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<CellValue>,
+    {
+        self.footer
+            .push(cells.into_iter().map(Into::into).collect());
+    }
+
+    pub fn with_frozen_header(mut self) -> Self {
+        self.freeze_header = true;
+        self
+    }
+
+    pub fn with_conditional_format(mut self, column: usize, rule: ConditionalRule) -> Self {
+        self.conditional_formats.push((column, rule));
+        self
+    }
+
+    pub fn with_chart(mut self, chart: ChartSpec) -> Self {
+        self.charts.push(chart);
+        self
+    }
+
+    /// Spreadsheet range of a column's data cells (excluding the footer),
+    /// e.g. `B2:B11`, for use in formulas. `None` when the sheet has no rows.
+    pub fn data_range(&self, column: usize) -> Option<String> {
+        if self.rows.is_empty() {
+            return None;
+        }
+        let name = column_name(column as u32);
+        Some(format!("{name}2:{name}{}", self.rows.len() + 1))
+    }
+}
+
+impl From<SheetDef<'_>> for Sheet {
+    fn from((name, headers, rows): SheetDef<'_>) -> Self {
+        let mut sheet = Sheet::new(name, headers.iter().copied().map(Column::new).collect());
+        sheet.rows = rows
+            .iter()
+            .map(|row| row.iter().map(|cell| CellValue::infer(cell)).collect())
+            .collect();
+        sheet
+    }
+}
+
 /// Generate an XLSX file from headers and rows.
 ///
-/// Returns the raw bytes of the xlsx file (can be written to disk or sent as download).
+/// Cells holding plain decimals are written as numbers, everything else as
+/// text (see [`CellValue::infer`]). Returns the raw bytes of the xlsx file
+/// (can be written to disk or sent as download).
 pub fn generate_xlsx(headers: &[&str], rows: &[Vec<String>]) -> Result<Vec<u8>> {
+    generate_xlsx_multi_sheet(&[Sheet::from(("Sheet1", headers, rows))])
+}
+
+/// Generate an XLSX file with multiple named sheets.
+///
+/// Accepts typed [`Sheet`]s or `(name, headers, rows)` string tuples.
+pub fn generate_xlsx_multi_sheet<S>(sheets: &[S]) -> Result<Vec<u8>>
+where
+    S: Clone + Into<Sheet>,
+{
     let mut workbook = Workbook::new();
-    let worksheet = workbook.add_worksheet();
+    for sheet in sheets {
+        let sheet: Sheet = sheet.clone().into();
+        let worksheet = workbook.add_worksheet();
+        write_sheet(worksheet, &sheet)
+            .with_context(|| format!("Failed to write sheet: {}", sheet.name))?;
+    }
 
-    let header_format = Format::new().set_bold();
+    let bytes = workbook
+        .save_to_buffer()
+        .context("Failed to save workbook to buffer")?;
+
+    Ok(bytes)
+}
 
-    // Write header row
-    for (col, header) in headers.iter().enumerate() {
+fn write_sheet(worksheet: &mut Worksheet, sheet: &Sheet) -> Result<()> {
+    worksheet
+        .set_name(&sheet.name)
+        .with_context(|| format!("Failed to set sheet name: {}", sheet.name))?;
+
+    let header_format = Format::new().set_bold();
+    for (col, column) in sheet.columns.iter().enumerate() {
         worksheet
-            .write_string_with_format(0, col as u16, *header, &header_format)
+            .write_string_with_format(0, col as u16, &column.header, &header_format)
             .with_context(|| format!("Failed to write header at column {col}"))?;
     }
 
-    // Write data rows
-    for (row_idx, row) in rows.iter().enumerate() {
+    let body = sheet.rows.iter().map(|row| (row, false));
+    let footer = sheet.footer.iter().map(|row| (row, true));
+    for (row_idx, (row, bold)) in body.chain(footer).enumerate() {
         let excel_row = (row_idx + 1) as u32;
         for (col_idx, cell) in row.iter().enumerate() {
-            // Try to parse as number, otherwise write as string
-            if let Ok(num) = cell.parse::<f64>() {
-                worksheet
-                    .write_number(excel_row, col_idx as u16, num)
-                    .with_context(|| {
-                        format!("Failed to write number at ({excel_row}, {col_idx})")
-                    })?;
-            } else {
-                worksheet
-                    .write_string(excel_row, col_idx as u16, cell)
-                    .with_context(|| {
-                        format!("Failed to write string at ({excel_row}, {col_idx})")
-                    })?;
-            }
+            let number_format = sheet
+                .columns
+                .get(col_idx)
+                .and_then(|c| c.number_format.as_deref());
+            write_cell(
+                worksheet,
+                excel_row,
+                col_idx as u16,
+                cell,
+                number_format,
+                bold,
+            )
+            .with_context(|| format!("Failed to write cell at ({excel_row}, {col_idx})"))?;
         }
     }
 
-    // Auto-fit columns for readability
+    // Auto-fit for readability, then honour explicit widths.
     worksheet.autofit();
+    for (col, column) in sheet.columns.iter().enumerate() {
+        if let Some(width) = column.width {
+            worksheet.set_column_width(col as u16, width)?;
+        }
+    }
 
-    let bytes = workbook
-        .save_to_buffer()
-        .context("Failed to save workbook to buffer")?;
+    if sheet.freeze_header {
+        worksheet.set_freeze_panes(1, 0)?;
+    }
 
-    Ok(bytes)
-}
+    if sheet.rows.is_empty() {
+        return Ok(());
+    }
+    let last_row = sheet.rows.len() as u32;
 
-/// Generate an XLSX file with multiple named sheets.
-pub fn generate_xlsx_multi_sheet(sheets: &[SheetDef<'_>]) -> Result<Vec<u8>> {
-    let mut workbook = Workbook::new();
-    let header_format = Format::new().set_bold();
+    for (col, rule) in &sheet.conditional_formats {
+        let col = *col as u16;
+        match rule {
+            ConditionalRule::GreaterThan { value, color } => {
+                let format = ConditionalFormatCell::new()
+                    .set_rule(ConditionalFormatCellRule::GreaterThan(*value))
+                    .set_format(Format::new().set_background_color(Color::RGB(*color)));
+                worksheet.add_conditional_format(1, col, last_row, col, &format)?;
+            }
+            ConditionalRule::LessThan { value, color } => {
+                let format = ConditionalFormatCell::new()
+                    .set_rule(ConditionalFormatCellRule::LessThan(*value))
+                    .set_format(Format::new().set_background_color(Color::RGB(*color)));
+                worksheet.add_conditional_format(1, col, last_row, col, &format)?;
+            }
+            ConditionalRule::DataBar { color } => {
+                let format = ConditionalFormatDataBar::new().set_fill_color(Color::RGB(*color));
+                worksheet.add_conditional_format(1, col, last_row, col, &format)?;
+            }
+            ConditionalRule::ColorScale {
+                min_color,
+                max_color,
+            } => {
+                let format = ConditionalFormat2ColorScale::new()
+                    .set_minimum_color(Color::RGB(*min_color))
+                    .set_maximum_color(Color::RGB(*max_color));
+                worksheet.add_conditional_format(1, col, last_row, col, &format)?;
+            }
+        }
+    }
 
-    for (sheet_name, headers, rows) in sheets {
-        let worksheet = workbook.add_worksheet();
+    let chart_col = sheet.columns.len() as u16 + 1;
+    for (i, spec) in sheet.charts.iter().enumerate() {
+        let chart = build_chart(spec, &sheet.name, last_row);
         worksheet
-            .set_name(*sheet_name)
-            .with_context(|| format!("Failed to set sheet name: {sheet_name}"))?;
-
-        for (col, header) in headers.iter().enumerate() {
-            worksheet
-                .write_string_with_format(0, col as u16, *header, &header_format)
-                .with_context(|| format!("Failed to write header at column {col}"))?;
-        }
-
-        for (row_idx, row) in rows.iter().enumerate() {
-            let excel_row = (row_idx + 1) as u32;
-            for (col_idx, cell) in row.iter().enumerate() {
-                if let Ok(num) = cell.parse::<f64>() {
-                    worksheet.write_number(excel_row, col_idx as u16, num)?;
-                } else {
-                    worksheet.write_string(excel_row, col_idx as u16, cell)?;
-                }
+            .insert_chart(1 + i as u32 * CHART_ROW_SPACING, chart_col, &chart)
+            .with_context(|| format!("Failed to insert chart: {}", spec.title))?;
+    }
+    Ok(())
+}
+
+fn write_cell(
+    worksheet: &mut Worksheet,
+    row: u32,
+    col: u16,
+    cell: &CellValue,
+    number_format: Option<&str>,
+    bold: bool,
+) -> Result<()> {
+    let format = |default: Option<&str>| {
+        let mut format = Format::new();
+        if let Some(code) = number_format.or(default) {
+            format = format.set_num_format(code);
+        }
+        if bold {
+            format = format.set_bold();
+        }
+        format
+    };
+    match cell {
+        CellValue::Empty => {}
+        CellValue::Text(text) => {
+            worksheet.write_string_with_format(row, col, text, &format(None))?;
+        }
+        CellValue::Number(num) => {
+            worksheet.write_number_with_format(row, col, *num, &format(None))?;
+        }
+        CellValue::Bool(value) => {
+            worksheet.write_boolean_with_format(row, col, *value, &format(None))?;
+        }
+        CellValue::Date(date) => {
+            let value = excel_date(*date)?;
+            worksheet.write_datetime_with_format(row, col, &value, &format(Some(DATE_FORMAT)))?;
+        }
+        CellValue::DateTime(datetime) => {
+            let time = datetime.time();
+            let value = excel_date(datetime.date())?.and_hms(
+                time.hour() as u16,
+                time.minute() as u8,
+                time.second(),
+            )?;
+            worksheet.write_datetime_with_format(
+                row,
+                col,
+                &value,
+                &format(Some(DATETIME_FORMAT)),
+            )?;
+        }
+        CellValue::Formula { formula, result } => {
+            let mut formula = Formula::new(formula);
+            if let Some(result) = result {
+                formula = formula.set_result(result);
             }
+            worksheet.write_formula_with_format(row, col, formula, &format(None))?;
         }
+    }
+    Ok(())
+}
+
+fn excel_date(date: NaiveDate) -> Result<ExcelDateTime> {
+    let year = u16::try_from(date.year()).with_context(|| format!("Date out of range: {date}"))?;
+    Ok(ExcelDateTime::from_ymd(
+        year,
+        date.month() as u8,
+        date.day() as u8,
+    )?)
+}
 
-        worksheet.autofit();
+fn build_chart(spec: &ChartSpec, sheet: &str, last_row: u32) -> Chart {
+    let mut chart = Chart::new(match spec.kind {
+        ChartKind::Column => ChartType::Column,
+        ChartKind::Bar => ChartType::Bar,
+        ChartKind::Line => ChartType::Line,
+    });
+    chart.title().set_name(&spec.title);
+    let categories = spec.category_column as u16;
+    for &col in &spec.value_columns {
+        let col = col as u16;
+        chart
+            .add_series()
+            .set_name((sheet, 0, col))
+            .set_categories((sheet, 1, categories, last_row, categories))
+            .set_values((sheet, 1, col, last_row, col));
+    }
+    if spec.value_columns.len() < 2 {
+        chart.legend().set_hidden();
     }
+    chart
+}
 
-    let bytes = workbook
-        .save_to_buffer()
-        .context("Failed to save workbook to buffer")?;
+// ---------------------------------------------------------------------------
+// Extraction
+// ---------------------------------------------------------------------------
 
-    Ok(bytes)
+/// Rows per extracted section. Large sheets are split so each chunk can be
+/// cited by its own cell range.
+const ROWS_PER_SECTION: u32 = 50;
+
+/// Extract every worksheet as Markdown tables anchored by cell range.
+///
+/// Each section covers up to [`ROWS_PER_SECTION`] rows of a sheet's used
+/// range and is anchored as e.g. `Sheet2!B3:F20`; sections after the first
+/// repeat the sheet's header row so they read on their own. Cells show
+/// their cached values, or the formula when no value was saved; values in
+/// date-formatted cells are shown as ISO dates and times.
+pub fn extract_xlsx(bytes: &[u8]) -> Result<ExtractedDocument> {
+    let mut package = Package::open(bytes)?;
+    let workbook = package.main_part()?;
+    let rels = package.relationships(&workbook)?;
+    let xml = package
+        .read(&workbook)?
+        .with_context(|| format!("Missing workbook part {workbook}"))?;
+
+    let shared = match find_relationship(&rels, "sharedStrings") {
+        Some(strings) => match package.read(&strings.target)? {
+            Some(strings_xml) => shared_strings(&strings_xml)?,
+            None => Vec::new(),
+        },
+        None => Vec::new(),
+    };
+    let date_styles = match find_relationship(&rels, "styles") {
+        Some(styles) => match package.read(&styles.target)? {
+            Some(styles_xml) => date_styles(&styles_xml)?,
+            None => Vec::new(),
+        },
+        None => Vec::new(),
+    };
+
+    let doc = parse_xml(&xml)?;
+    let mut document = ExtractedDocument::new(DocumentFormat::Xlsx);
+    for sheet in doc.descendants().filter(|n| is(*n, "sheet")) {
+        let name = attr(sheet, "name").unwrap_or("Sheet");
+        let Some(part) = rel_id(sheet).and_then(|id| rels.iter().find(|r| r.id == id)) else {
+            continue;
+        };
+        let Some(sheet_xml) = package.read(&part.target)? else {
+            continue;
+        };
+        let cells = sheet_cells(&sheet_xml, &shared, &date_styles)?;
+        document.sections.extend(sheet_sections(name, &cells));
+    }
+    Ok(document)
+}
+
+fn shared_strings(xml: &str) -> Result<Vec<String>> {
+    let doc = parse_xml(xml)?;
+    Ok(doc
+        .root_element()
+        .children()
+        .filter(|n| is(*n, "si"))
+        .map(rich_text)
+        .collect())
+}
+
+/// How a date-formatted cell's serial number should be shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateStyle {
+    Date,
+    Time,
+    DateTime,
+}
+
+/// The date style of each cell format (`cellXfs` entry), by index.
+fn date_styles(xml: &str) -> Result<Vec<Option<DateStyle>>> {
+    let doc = parse_xml(xml)?;
+    let custom: BTreeMap<u32, &str> = doc
+        .descendants()
+        .filter(|n| is(*n, "numFmt"))
+        .filter_map(|n| {
+            let id = attr(n, "numFmtId")?.parse().ok()?;
+            Some((id, attr(n, "formatCode")?))
+        })
+        .collect();
+    let Some(xfs) = doc.descendants().find(|n| is(*n, "cellXfs")) else {
+        return Ok(Vec::new());
+    };
+    Ok(xfs
+        .children()
+        .filter(|n| is(*n, "xf"))
+        .map(|xf| {
+            let id = attr(xf, "numFmtId")?.parse::<u32>().ok()?;
+            match custom.get(&id) {
+                Some(code) => format_date_style(code),
+                None => builtin_date_style(id),
+            }
+        })
+        .collect())
+}
+
+fn builtin_date_style(id: u32) -> Option<DateStyle> {
+    match id {
+        14..=17 => Some(DateStyle::Date),
+        18..=21 | 45..=47 => Some(DateStyle::Time),
+        22 => Some(DateStyle::DateTime),
+        _ => None,
+    }
+}
+
+/// Classify a custom number format code by its date and time tokens,
+/// ignoring quoted literals, escapes and bracketed sections such as colors.
+fn format_date_style(code: &str) -> Option<DateStyle> {
+    let mut tokens = String::new();
+    let mut chars = code.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => chars.by_ref().take_while(|&c| c != '"').for_each(drop),
+            '[' => chars.by_ref().take_while(|&c| c != ']').for_each(drop),
+            '\\' | '_' | '*' => {
+                chars.next();
+            }
+            c => tokens.push(c.to_ascii_lowercase()),
+        }
+    }
+    let date = tokens.contains(['y', 'd']);
+    let time = tokens.contains(['h', 's']);
+    match (date, time) {
+        (true, true) => Some(DateStyle::DateTime),
+        (true, false) => Some(DateStyle::Date),
+        (false, true) => Some(DateStyle::Time),
+        // A lone month (`mmm`) is still a date; `m` otherwise never appears
+        // in numeric formats.
+        (false, false) if tokens.contains('m') => Some(DateStyle::Date),
+        (false, false) => None,
+    }
+}
+
+/// Render a spreadsheet serial date (days since 1899-12-30) in ISO form.
+fn serial_date(serial: f64, style: DateStyle) -> Option<String> {
+    let epoch = NaiveDate::from_ymd_opt(1899, 12, 30)?.and_time(NaiveTime::MIN);
+    let millis = (serial * 86_400_000.0).round() as i64;
+    let datetime = epoch.checked_add_signed(Duration::try_milliseconds(millis)?)?;
+    let format = match style {
+        DateStyle::Date => "%Y-%m-%d",
+        DateStyle::Time => "%H:%M:%S",
+        DateStyle::DateTime => "%Y-%m-%d %H:%M:%S",
+    };
+    Some(datetime.format(format).to_string())
+}
+
+/// Concatenated text runs, skipping phonetic guides.
+fn rich_text(node: roxmltree::Node<'_, '_>) -> String {
+    node.descendants()
+        .filter(|n| is(*n, "t") && !n.ancestors().any(|a| is(a, "rPh")))
+        .filter_map(|t| t.text())
+        .collect()
+}
+
+/// Non-empty cells keyed by zero-based (row, column).
+fn sheet_cells(
+    xml: &str,
+    shared: &[String],
+    date_styles: &[Option<DateStyle>],
+) -> Result<BTreeMap<(u32, u32), String>> {
+    let doc = parse_xml(xml)?;
+    let mut cells = BTreeMap::new();
+    let Some(data) = doc.descendants().find(|n| is(*n, "sheetData")) else {
+        return Ok(cells);
+    };
+
+    let mut next_row = 0;
+    for row in data.children().filter(|n| is(*n, "row")) {
+        let row_idx = attr(row, "r")
+            .and_then(|r| r.parse::<u32>().ok())
+            .map(|r| r.saturating_sub(1))
+            .unwrap_or(next_row);
+        next_row = row_idx + 1;
+
+        let mut next_col = 0;
+        for cell in row.children().filter(|n| is(*n, "c")) {
+            let (r, c) = attr(cell, "r")
+                .and_then(parse_cell_ref)
+                .unwrap_or((row_idx, next_col));
+            next_col = c + 1;
+            if let Some(value) = cell_value(cell, shared, date_styles).filter(|v| !v.is_empty()) {
+                cells.insert((r, c), value);
+            }
+        }
+    }
+    Ok(cells)
+}
+
+fn cell_value(
+    cell: roxmltree::Node<'_, '_>,
+    shared: &[String],
+    date_styles: &[Option<DateStyle>],
+) -> Option<String> {
+    let value = child(cell, "v").and_then(|v| v.text());
+    let date_style = attr(cell, "s")
+        .and_then(|s| s.parse::<usize>().ok())
+        .and_then(|s| date_styles.get(s).copied().flatten());
+    match attr(cell, "t") {
+        Some("s") => shared.get(value?.trim().parse::<usize>().ok()?).cloned(),
+        Some("inlineStr") => Some(rich_text(child(cell, "is")?)),
+        Some("b") => Some(if value? == "1" { "TRUE" } else { "FALSE" }.to_string()),
+        _ => value
+            .map(|v| {
+                date_style
+                    .and_then(|style| serial_date(v.trim().parse().ok()?, style))
+                    .unwrap_or_else(|| v.to_string())
+            })
+            .or_else(|| {
+                child(cell, "f")
+                    .and_then(|f| f.text())
+                    .map(|formula| format!("={formula}"))
+            }),
+    }
+}
+
+fn sheet_sections(sheet: &str, cells: &BTreeMap<(u32, u32), String>) -> Vec<Section> {
+    let (Some(&(first_row, _)), Some(&(last_row, _))) =
+        (cells.keys().next(), cells.keys().next_back())
+    else {
+        return Vec::new();
+    };
+    let first_col = cells.keys().map(|&(_, c)| c).min().unwrap_or(0);
+    let last_col = cells.keys().map(|&(_, c)| c).max().unwrap_or(0);
+    let row_cells = |row: u32| -> Vec<String> {
+        (first_col..=last_col)
+            .map(|col| cells.get(&(row, col)).cloned().unwrap_or_default())
+            .collect()
+    };
+    let header = row_cells(first_row);
+
+    let mut sections = Vec::new();
+    let mut start = first_row;
+    while start <= last_row {
+        let end = (start + ROWS_PER_SECTION - 1).min(last_row);
+        let mut rows = Vec::new();
+        if start != first_row {
+            rows.push(header.clone());
+        }
+        rows.extend(
+            cells
+                .range((start, 0)..=(end, u32::MAX))
+                .map(|(&(row, _), _)| row)
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .map(row_cells),
+        );
+
+        let mut section = Section::new(Some(Anchor::Range {
+            sheet: sheet.to_string(),
+            range: format!(
+                "{}{}:{}{}",
+                column_name(first_col),
+                start + 1,
+                column_name(last_col),
+                end + 1
+            ),
+        }));
+        section.title = Some(sheet.to_string());
+        section.blocks.push(Block::Table { rows });
+        sections.push(section);
+        start = end + 1;
+    }
+    sections
+}
+
+/// Parse an A1-style reference (`$` markers allowed) into zero-based
+/// (row, column).
+fn parse_cell_ref(reference: &str) -> Option<(u32, u32)> {
+    let reference = reference.replace('$', "");
+    let letters = reference
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .count();
+    let (col, row) = reference.split_at(letters);
+    if col.is_empty() || col.len() > 3 {
+        return None;
+    }
+    let col = col.chars().fold(0u32, |acc, c| {
+        acc * 26 + (c.to_ascii_uppercase() as u32 - 'A' as u32 + 1)
+    });
+    let row = row.parse::<u32>().ok().filter(|&row| row > 0)?;
+    Some((row - 1, col - 1))
+}
+
+/// Spreadsheet column letters for a zero-based column index.
+fn column_name(col: u32) -> String {
+    let mut n = col + 1;
+    let mut name = Vec::new();
+    while n > 0 {
+        let rem = (n - 1) % 26;
+        name.push(b'A' + rem as u8);
+        n = (n - 1) / 26;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_infer_only_plain_decimals() {
+        for (text, num) in [("0", 0.0), ("42", 42.0), ("-3.5", -3.5), ("0.25", 0.25)] {
+            assert_eq!(CellValue::infer(text), CellValue::Number(num), "{text}");
+        }
+        for text in [
+            "007",
+            "00.5",
+            "+15551234567",
+            "1e5",
+            "1E5",
+            "inf",
+            "-inf",
+            "NaN",
+            "infinity",
+            ".5",
+            "5.",
+            "-",
+            "",
+            " 1",
+            "1,000",
+            "1234567890123456",
+            "12345678901234567890",
+        ] {
+            assert_eq!(
+                CellValue::infer(text),
+                CellValue::Text(text.into()),
+                "{text}"
+            );
+        }
+    }
+
     #[test]
     fn test_generate_xlsx_basic() {
         let headers = &["Name", "Age", "City"];
@@ -146,7 +920,7 @@ mod tests {
         let sheet2_headers = &["Product", "Price"];
         let sheet2_rows = vec![vec!["Widget".into(), "9.99".into()]];
 
-        let sheets: Vec<(&str, &[&str], &[Vec<String>])> = vec![
+        let sheets: Vec<SheetDef<'_>> = vec![
             ("Scores", sheet1_headers, &sheet1_rows),
             ("Products", sheet2_headers, &sheet2_rows),
         ];
@@ -173,4 +947,212 @@ mod tests {
         let bytes = generate_xlsx(headers, &rows).unwrap();
         assert!(!bytes.is_empty());
     }
+
+    #[test]
+    fn test_extract_xlsx_multi_sheet() {
+        let scores = vec![
+            vec!["Alice".into(), "95".into()],
+            vec!["Bob".into(), "87.5".into()],
+        ];
+        let products = vec![vec!["Widget".into(), "9.99".into()]];
+        let sheets: Vec<SheetDef<'_>> = vec![
+            ("Scores", &["Name", "Score"], &scores),
+            ("Q1 Products", &["Product", "Price"], &products),
+        ];
+        let bytes = generate_xlsx_multi_sheet(&sheets).unwrap();
+        let doc = extract_xlsx(&bytes).unwrap();
+
+        assert_eq!(doc.sections.len(), 2);
+        assert_eq!(doc.sections[0].title.as_deref(), Some("Scores"));
+        assert_eq!(
+            doc.sections[0].anchor.as_ref().unwrap().to_string(),
+            "Scores!A1:B3"
+        );
+        assert_eq!(
+            doc.sections[0].blocks,
+            vec![Block::Table {
+                rows: vec![
+                    vec!["Name".into(), "Score".into()],
+                    vec!["Alice".into(), "95".into()],
+                    vec!["Bob".into(), "87.5".into()],
+                ]
+            }]
+        );
+        assert_eq!(
+            doc.sections[1].anchor.as_ref().unwrap().to_string(),
+            "'Q1 Products'!A1:B2"
+        );
+    }
+
+    #[test]
+    fn test_extract_xlsx_splits_large_sheets() {
+        let rows: Vec<Vec<String>> = (1..=120).map(|i| vec![i.to_string(), "x".into()]).collect();
+        let bytes = generate_xlsx(&["ID", "Value"], &rows).unwrap();
+        let doc = extract_xlsx(&bytes).unwrap();
+
+        let anchors: Vec<String> = doc
+            .sections
+            .iter()
+            .map(|s| s.anchor.as_ref().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            anchors,
+            ["Sheet1!A1:B50", "Sheet1!A51:B100", "Sheet1!A101:B121"]
+        );
+
+        // Later sections repeat the header row.
+        let Block::Table { rows } = &doc.sections[1].blocks[0] else {
+            panic!("expected a table");
+        };
+        assert_eq!(rows[0], vec!["ID".to_string(), "Value".to_string()]);
+        assert_eq!(rows[1], vec!["50".to_string(), "x".to_string()]);
+        assert_eq!(rows.len(), 51);
+    }
+
+    /// The XML of a package part, e.g. `xl/worksheets/sheet1.xml`.
+    fn part_xml(bytes: &[u8], part: &str) -> String {
+        let mut package = Package::open(bytes).unwrap();
+        package.read(part).unwrap().unwrap()
+    }
+
+    fn sample_sheet() -> Sheet {
+        let mut sheet = Sheet::new(
+            "Costs",
+            vec![
+                Column::new("Day").with_width(14.0),
+                Column::new("Cost").with_number_format("$#,##0.00"),
+                Column::new("Over budget"),
+            ],
+        );
+        for (day, cost) in [(1, 1.25), (2, 7.5), (3, 3.0)] {
+            sheet.push_row([
+                CellValue::Date(NaiveDate::from_ymd_opt(2026, 3, day).unwrap()),
+                cost.into(),
+                (cost > 5.0).into(),
+            ]);
+        }
+        let total = sheet.data_range(1).unwrap();
+        sheet.push_footer_row([
+            "Total".into(),
+            CellValue::formula(format!("SUM({total})")).with_result(11.75),
+            CellValue::Empty,
+        ]);
+        sheet
+    }
+
+    #[test]
+    fn test_typed_cells_roundtrip() {
+        let mut sheet = sample_sheet();
+        sheet.push_row([
+            CellValue::DateTime(
+                NaiveDate::from_ymd_opt(2026, 3, 4)
+                    .unwrap()
+                    .and_hms_opt(9, 30, 0)
+                    .unwrap(),
+            ),
+            CellValue::formula("=B2*2"),
+            CellValue::Empty,
+        ]);
+        let bytes = generate_xlsx_multi_sheet(&[sheet]).unwrap();
+        let doc = extract_xlsx(&bytes).unwrap();
+
+        let Block::Table { rows } = &doc.sections[0].blocks[0] else {
+            panic!("expected a table");
+        };
+        assert_eq!(rows[0], ["Day", "Cost", "Over budget"]);
+        assert_eq!(rows[1], ["2026-03-01", "1.25", "FALSE"]);
+        assert_eq!(rows[2], ["2026-03-02", "7.5", "TRUE"]);
+        assert_eq!(rows[4][0], "2026-03-04 09:30:00");
+        // Footer rows follow the data; cached formula results are shown in
+        // place of the formula.
+        assert_eq!(rows[5], ["Total", "11.75", ""]);
+
+        let xml = part_xml(&bytes, "xl/worksheets/sheet1.xml");
+        assert!(xml.contains("<f>SUM(B2:B4)</f>"));
+        assert!(xml.contains("<f>B2*2</f>"));
+        assert!(xml.contains(r#"t="b""#));
+    }
+
+    #[test]
+    fn test_sheet_layout_features() {
+        let sheet = sample_sheet()
+            .with_frozen_header()
+            .with_conditional_format(
+                1,
+                ConditionalRule::GreaterThan {
+                    value: 5.0,
+                    color: 0xFFC7CE,
+                },
+            )
+            .with_conditional_format(1, ConditionalRule::DataBar { color: 0x638EC6 })
+            .with_chart(ChartSpec::new(ChartKind::Line, "Daily cost", 0, [1]))
+            .with_chart(ChartSpec::new(ChartKind::Column, "Cost by day", 0, [1]));
+        let bytes = generate_xlsx_multi_sheet(&[sheet]).unwrap();
+
+        let xml = part_xml(&bytes, "xl/worksheets/sheet1.xml");
+        assert!(xml.contains(r#"<pane ySplit="1" topLeftCell="A2""#));
+        assert!(xml.contains(r#"<col min="1" max="1" width="14.7109375" customWidth="1"/>"#));
+        // Charts and conditional formats stop before the footer row.
+        assert!(xml.contains(r#"<conditionalFormatting sqref="B2:B4">"#));
+        assert!(xml.contains(r#"operator="greaterThan""#));
+        assert!(xml.contains("<dataBar>"));
+        assert!(xml.contains("<drawing "));
+
+        let line = part_xml(&bytes, "xl/charts/chart1.xml");
+        assert!(line.contains("<c:lineChart>"));
+        assert!(line.contains("<c:f>Costs!$B$2:$B$4</c:f>"));
+        let column = part_xml(&bytes, "xl/charts/chart2.xml");
+        assert!(column.contains("<c:barChart>"));
+        assert!(column.contains(r#"<c:barDir val="col"/>"#));
+
+        let styles = part_xml(&bytes, "xl/styles.xml");
+        assert!(styles.contains(r#"formatCode="$#,##0.00""#));
+    }
+
+    #[test]
+    fn test_sheet_from_string_rows() {
+        let rows = [vec!["Widget".to_string(), "9.99".to_string()]];
+        let sheet = Sheet::from(("Products", &["Product", "Price"][..], &rows[..]));
+        assert_eq!(sheet.columns[1], Column::new("Price"));
+        assert_eq!(
+            sheet.rows[0],
+            [CellValue::Text("Widget".into()), CellValue::Number(9.99)]
+        );
+        assert_eq!(sheet.data_range(1).as_deref(), Some("B2:B2"));
+        assert_eq!(Sheet::new("Empty", Vec::new()).data_range(0), None);
+    }
+
+    #[test]
+    fn test_date_styles() {
+        assert_eq!(builtin_date_style(14), Some(DateStyle::Date));
+        assert_eq!(builtin_date_style(22), Some(DateStyle::DateTime));
+        assert_eq!(builtin_date_style(2), None);
+        assert_eq!(format_date_style("yyyy-mm-dd"), Some(DateStyle::Date));
+        assert_eq!(format_date_style("mmm"), Some(DateStyle::Date));
+        assert_eq!(format_date_style("[h]:mm:ss"), Some(DateStyle::Time));
+        assert_eq!(
+            format_date_style("yyyy-mm-dd hh:mm"),
+            Some(DateStyle::DateTime)
+        );
+        assert_eq!(format_date_style("$#,##0.00"), None);
+        assert_eq!(format_date_style("[Red]0.0%"), None);
+        assert_eq!(format_date_style(r#"0 "days""#), None);
+        assert_eq!(
+            serial_date(46082.5, DateStyle::DateTime).as_deref(),
+            Some("2026-03-01 12:00:00")
+        );
+    }
+
+    #[test]
+    fn test_cell_refs() {
+        assert_eq!(parse_cell_ref("A1"), Some((0, 0)));
+        assert_eq!(parse_cell_ref("$AB$12"), Some((11, 27)));
+        assert_eq!(parse_cell_ref("1A"), None);
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(27), "AB");
+        assert_eq!(column_name(701), "ZZ");
+        assert_eq!(column_name(702), "AAA");
+    }
 }
@@ -0,0 +1,355 @@
+//! Importers for DevDocs doc sets and Dash docsets.
+//!
+//! DevDocs publishes each doc set as an `index.json` (entry names, paths
+//! and types) plus a `db.json` mapping page paths to HTML fragments. A Dash
+//! docset is a `.docset` bundle whose `Contents/Resources/docSet.dsidx`
+//! SQLite index points at HTML files under `Contents/Resources/Documents`.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use regex::Regex;
+use rusqlite::{Connection, OpenFlags};
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::debug;
+
+use super::{DocPage, DocsIndex, DocsOrigin, archive_stem, parse_html_page};
+
+// ── DevDocs ────────────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+struct DevDocsIndex {
+    entries: Vec<DevDocsEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DevDocsEntry {
+    name: String,
+    path: String,
+}
+
+/// Convert a DevDocs doc set into a [`DocsIndex`] named `slug`, with one
+/// page per `db.json` document. Entry names pointing into a page become
+/// its headings, so API names are searchable even when the HTML fragment
+/// only mentions them in code.
+pub fn parse_devdocs(slug: &str, index_json: &str, db_json: &str) -> Result<DocsIndex> {
+    let index: DevDocsIndex =
+        serde_json::from_str(index_json).context("invalid DevDocs index.json")?;
+    let db: BTreeMap<String, String> =
+        serde_json::from_str(db_json).context("invalid DevDocs db.json")?;
+
+    let mut entries: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for entry in &index.entries {
+        let page = entry.path.split('#').next().unwrap_or_default();
+        entries.entry(page).or_default().push(&entry.name);
+    }
+
+    let base_url = format!("https://devdocs.io/{slug}");
+    let pages = db
+        .iter()
+        .map(|(path, html)| {
+            let names = entries.get(path.as_str()).map_or(&[][..], Vec::as_slice);
+            page_with_entries(
+                parse_html_page(&format!("{base_url}/{path}"), html),
+                names,
+                path,
+            )
+        })
+        .collect();
+
+    Ok(DocsIndex {
+        name: slug.to_string(),
+        base_url,
+        pages,
+        indexed_at: Utc::now(),
+        origin: DocsOrigin::DevDocs,
+        // `python~3.12` style slugs carry the version.
+        version: slug.split_once('~').map(|(_, v)| v.to_string()),
+    })
+}
+
+/// The release (or display version) recorded in a DevDocs `meta.json`.
+pub(super) fn devdocs_version(meta_json: &str) -> Option<String> {
+    let meta: Value = serde_json::from_str(meta_json).ok()?;
+    ["release", "version"]
+        .iter()
+        .filter_map(|key| meta.get(key).and_then(Value::as_str))
+        .find(|v| !v.is_empty())
+        .map(String::from)
+}
+
+/// `dir` itself or its first subdirectory holding `index.json` + `db.json`.
+pub(super) fn find_devdocs_root(dir: &Path) -> Option<PathBuf> {
+    let is_root = |d: &Path| d.join("index.json").is_file() && d.join("db.json").is_file();
+    if is_root(dir) {
+        return Some(dir.to_path_buf());
+    }
+    subdirectories(dir).into_iter().find(|d| is_root(d))
+}
+
+// ── Dash ───────────────────────────────────────────────────────────
+
+/// `dir` itself if it is a `.docset` bundle, else the first bundle in it.
+pub(super) fn find_docset_bundle(dir: &Path) -> Option<PathBuf> {
+    let is_bundle = |d: &Path| d.extension().is_some_and(|ext| ext == "docset");
+    if is_bundle(dir) {
+        return Some(dir.to_path_buf());
+    }
+    subdirectories(dir).into_iter().find(|d| is_bundle(d))
+}
+
+/// Read a `.docset` bundle into a [`DocsIndex`], one page per document
+/// referenced from the docset's search index.
+pub(super) fn read_dash_docset(bundle: &Path) -> Result<DocsIndex> {
+    let resources = bundle.join("Contents").join("Resources");
+    let documents = resources.join("Documents");
+    let plist =
+        std::fs::read_to_string(bundle.join("Contents").join("Info.plist")).unwrap_or_default();
+    let name = plist_string(&plist, "CFBundleName").unwrap_or_else(|| archive_stem(bundle));
+    let version = plist_string(&plist, "CFBundleShortVersionString")
+        .or_else(|| plist_string(&plist, "CFBundleVersion"));
+
+    let dsidx = resources.join("docSet.dsidx");
+    let conn = Connection::open_with_flags(&dsidx, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("failed to open {}", dsidx.display()))?;
+    let entries = dash_entries(&conn)?;
+
+    let mut files: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (entry_name, path) in entries {
+        files
+            .entry(dash_file(&path).to_string())
+            .or_default()
+            .push(entry_name);
+    }
+
+    // Entry paths come from the docset; never read outside `Documents/`.
+    let documents = documents
+        .canonicalize()
+        .with_context(|| format!("missing docset documents in {}", bundle.display()))?;
+    let mut pages = Vec::new();
+    for (file, names) in &files {
+        let Some(path) = contained_path(&documents, file) else {
+            debug!(file = %file, "docset document missing or outside the bundle, skipping");
+            continue;
+        };
+        let Ok(html) = std::fs::read_to_string(&path) else {
+            debug!(path = %path.display(), "docset document unreadable, skipping");
+            continue;
+        };
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let page = parse_html_page(&format!("file://{}", path.display()), &html);
+        pages.push(page_with_entries(page, &names, file));
+    }
+
+    Ok(DocsIndex {
+        name,
+        base_url: format!("file://{}", documents.display()),
+        pages,
+        indexed_at: Utc::now(),
+        origin: DocsOrigin::Dash,
+        version,
+    })
+}
+
+/// `root.join(relative)` resolved through symlinks, if it exists and stays
+/// inside the canonical directory `root`.
+fn contained_path(root: &Path, relative: &str) -> Option<PathBuf> {
+    let path = root.join(relative).canonicalize().ok()?;
+    path.starts_with(root).then_some(path)
+}
+
+/// `(name, path)` for every entry, from either index layout Dash uses:
+/// the plain `searchIndex` table or the Core Data `ZTOKEN` tables.
+fn dash_entries(conn: &Connection) -> Result<Vec<(String, String)>> {
+    let has_search_index: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'searchIndex'",
+        [],
+        |row| row.get(0),
+    )?;
+    let sql = if has_search_index {
+        "SELECT name, path FROM searchIndex"
+    } else {
+        "SELECT ZTOKEN.ZTOKENNAME,
+                ZFILEPATH.ZPATH || COALESCE('#' || ZTOKENMETAINFORMATION.ZANCHOR, '')
+         FROM ZTOKEN
+         JOIN ZTOKENMETAINFORMATION ON ZTOKEN.ZMETAINFORMATION = ZTOKENMETAINFORMATION.Z_PK
+         JOIN ZFILEPATH ON ZTOKENMETAINFORMATION.ZFILE = ZFILEPATH.Z_PK"
+    };
+    let mut stmt = conn
+        .prepare(sql)
+        .context("unrecognised docset index layout")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+/// The document file of a Dash entry path, without the `<dash_entry_…>`
+/// metadata prefix or `#anchor`.
+fn dash_file(path: &str) -> &str {
+    let path = if path.starts_with('<') {
+        path.rsplit_once('>').map_or(path, |(_, rest)| rest)
+    } else {
+        path
+    };
+    path.split('#').next().unwrap_or(path)
+}
+
+/// The `<string>` value following `<key>key</key>` in an XML plist.
+fn plist_string(plist: &str, key: &str) -> Option<String> {
+    let re = Regex::new(&format!(
+        r"<key>{}</key>\s*<string>(.*?)</string>",
+        regex::escape(key)
+    ))
+    .ok()?;
+    re.captures(plist)
+        .and_then(|cap| cap.get(1))
+        .map(|m| m.as_str().trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+// ── Shared helpers ─────────────────────────────────────────────────
+
+/// Fold a doc set's entry names into a parsed page: the first name titles
+/// untitled fragments, and all of them are kept as headings.
+fn page_with_entries(mut page: DocPage, names: &[&str], path: &str) -> DocPage {
+    if page.title.is_empty() {
+        page.title = names
+            .first()
+            .map(|n| n.to_string())
+            .or_else(|| page.headings.first().cloned())
+            .unwrap_or_else(|| path.to_string());
+    }
+    for name in names {
+        if !page.headings.iter().any(|h| h == name) {
+            page.headings.push(name.to_string());
+        }
+    }
+    page
+}
+
+fn subdirectories(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.is_dir())
+                .collect()
+        })
+        .unwrap_or_default();
+    dirs.sort();
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tempdir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hive_docset_test_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_parse_devdocs() {
+        let index_json = r#"{"entries": [
+            {"name": "Vec", "path": "std/vec/struct.vec", "type": "std::vec"},
+            {"name": "Vec::push", "path": "std/vec/struct.vec#method.push", "type": "std::vec"}
+        ], "types": []}"#;
+        let db_json = r#"{
+            "std/vec/struct.vec": "<h1>Struct Vec</h1><p>A contiguous growable array.</p><pre>let v = vec![1];</pre>",
+            "index": "<p>Standard library overview.</p>"
+        }"#;
+
+        let index = parse_devdocs("rust~1.80", index_json, db_json).unwrap();
+        assert_eq!(index.name, "rust~1.80");
+        assert_eq!(index.version.as_deref(), Some("1.80"));
+        assert_eq!(index.origin, DocsOrigin::DevDocs);
+        assert_eq!(index.pages.len(), 2);
+
+        let vec_page = index
+            .pages
+            .iter()
+            .find(|p| p.url == "https://devdocs.io/rust~1.80/std/vec/struct.vec")
+            .unwrap();
+        assert_eq!(vec_page.title, "Vec");
+        assert_eq!(vec_page.headings, ["Struct Vec", "Vec", "Vec::push"]);
+        assert_eq!(vec_page.code_blocks, ["let v = vec![1];"]);
+
+        let overview = index
+            .pages
+            .iter()
+            .find(|p| p.url.ends_with("/index"))
+            .unwrap();
+        assert_eq!(overview.title, "index");
+    }
+
+    #[test]
+    fn test_devdocs_version() {
+        assert_eq!(
+            devdocs_version(r#"{"version": "3.12", "release": "3.12.4"}"#).as_deref(),
+            Some("3.12.4")
+        );
+        assert_eq!(
+            devdocs_version(r#"{"version": "5", "release": ""}"#).as_deref(),
+            Some("5")
+        );
+        assert!(devdocs_version("{}").is_none());
+    }
+
+    #[test]
+    fn test_dash_file() {
+        assert_eq!(dash_file("std/index.html#section"), "std/index.html");
+        assert_eq!(
+            dash_file("<dash_entry_name=Vec><dash_entry_originalName=std::Vec>std/vec.html#x"),
+            "std/vec.html"
+        );
+        assert_eq!(dash_file("plain.html"), "plain.html");
+    }
+
+    #[test]
+    fn test_read_dash_docset() {
+        let tmp = tempdir();
+        let bundle = tmp.join("Demo.docset");
+        let resources = bundle.join("Contents").join("Resources");
+        std::fs::create_dir_all(resources.join("Documents")).unwrap();
+        std::fs::write(
+            bundle.join("Contents").join("Info.plist"),
+            "<plist><dict><key>CFBundleName</key><string>Demo Lib</string>\
+             <key>CFBundleShortVersionString</key><string>2.0</string></dict></plist>",
+        )
+        .unwrap();
+        std::fs::write(
+            resources.join("Documents").join("widget.html"),
+            "<title>Widget</title><p>Widgets render things.</p>",
+        )
+        .unwrap();
+
+        let conn = Connection::open(resources.join("docSet.dsidx")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE searchIndex(id INTEGER PRIMARY KEY, name TEXT, type TEXT, path TEXT);
+             INSERT INTO searchIndex(name, type, path) VALUES
+                 ('Widget', 'Class', 'widget.html'),
+                 ('Widget.render', 'Method', 'widget.html#render'),
+                 ('Gone', 'Class', 'missing.html'),
+                 ('Escape', 'Class', '../../../../secret.html');",
+        )
+        .unwrap();
+        drop(conn);
+        std::fs::write(tmp.join("secret.html"), "<title>Secret</title>").unwrap();
+
+        assert_eq!(find_docset_bundle(&tmp), Some(bundle.clone()));
+        let index = read_dash_docset(&bundle).unwrap();
+        assert_eq!(index.name, "Demo Lib");
+        assert_eq!(index.version.as_deref(), Some("2.0"));
+        assert_eq!(index.origin, DocsOrigin::Dash);
+        assert_eq!(index.pages.len(), 1);
+        assert_eq!(index.pages[0].title, "Widget");
+        assert_eq!(index.pages[0].headings, ["Widget", "Widget.render"]);
+        assert!(index.pages[0].content.contains("render things"));
+
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+}
@@ -0,0 +1,1808 @@
+//! Documentation indexer: crawled sites plus offline doc sets (rustdoc
+//! JSON, DevDocs, Dash docsets), persisted under `~/.hive/docs`.
+
+mod docset;
+mod rustdoc;
+
+pub use docset::parse_devdocs;
+pub use rustdoc::parse_rustdoc_json;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use hive_core::config::HiveConfig;
+use hive_core::search::{SearchDocument, SearchSource};
+use regex::Regex;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+// ── Types ──────────────────────────────────────────────────────────
+
+/// A single indexed documentation page.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DocPage {
+    pub url: String,
+    pub title: String,
+    pub content: String,
+    pub headings: Vec<String>,
+    pub code_blocks: Vec<String>,
+    /// Validators from the last fetch, sent back on re-crawl so unchanged
+    /// pages come back as `304 Not Modified`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+/// Where a documentation set came from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocsOrigin {
+    /// Crawled over HTTP; refreshable with conditional requests.
+    #[default]
+    Crawl,
+    /// Imported from `rustdoc --output-format json`.
+    Rustdoc,
+    /// Imported from a DevDocs `index.json` + `db.json` pair.
+    DevDocs,
+    /// Imported from a Dash `.docset` bundle.
+    Dash,
+}
+
+/// A complete index of a documentation site.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocsIndex {
+    pub name: String,
+    pub base_url: String,
+    pub pages: Vec<DocPage>,
+    pub indexed_at: DateTime<Utc>,
+    #[serde(default)]
+    pub origin: DocsOrigin,
+    /// Version of the documented package, when the source records one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+impl DocsIndex {
+    /// Pages as documents for the unified search index, tagged with the
+    /// index name.
+    pub fn search_documents(&self) -> Vec<SearchDocument> {
+        self.pages
+            .iter()
+            .map(|page| {
+                let body = format!("{}\n\n{}", page.content, page.code_blocks.join("\n\n"));
+                SearchDocument::new(SearchSource::Docs, &page.url, &page.title, body)
+                    .with_tags(std::iter::once(self.name.clone()).chain(page.headings.clone()))
+                    .with_location(&page.url)
+                    .updated(self.indexed_at)
+            })
+            .collect()
+    }
+}
+
+/// A single search result from querying an index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocSearchResult {
+    pub page_url: String,
+    pub title: String,
+    pub snippet: String,
+    pub relevance_score: f64,
+}
+
+/// Parsed rules from a robots.txt file.
+#[derive(Debug, Clone, Default)]
+struct RobotsTxt {
+    disallowed: Vec<String>,
+    sitemaps: Vec<String>,
+}
+
+/// Outcome of a (possibly conditional) page fetch.
+enum Fetched {
+    NotModified,
+    Page {
+        html: String,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    },
+}
+
+/// Upper bound on nested sitemaps followed from a sitemap index.
+const MAX_SITEMAPS: usize = 20;
+
+// ── DocsIndexer ────────────────────────────────────────────────────
+
+/// Documentation site indexer that can crawl, index, and search any
+/// documentation website.
+///
+/// Uses simple HTML parsing (regex-based) to extract text, headings,
+/// and code blocks from pages. Provides TF-IDF-like full-text search
+/// across indexed pages and respects robots.txt.
+///
+/// Indexers opened with [`DocsIndexer::open`] write every index to a JSON
+/// file in their storage directory and load them back on startup.
+pub struct DocsIndexer {
+    indexes: HashMap<String, DocsIndex>,
+    client: Client,
+    storage_dir: Option<PathBuf>,
+}
+
+impl DocsIndexer {
+    /// Create a new empty indexer.
+    pub fn new() -> Result<Self> {
+        let client = Client::builder()
+            .user_agent("Hive-DocsIndexer/1.0")
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .context("failed to build HTTP client for docs indexer")?;
+
+        Ok(Self {
+            indexes: HashMap::new(),
+            client,
+            storage_dir: None,
+        })
+    }
+
+    /// The default storage directory: `~/.hive/docs`.
+    pub fn default_dir() -> Result<PathBuf> {
+        Ok(HiveConfig::base_dir()?.join("docs"))
+    }
+
+    /// Open the persistent indexer in the default storage directory.
+    pub fn open_default() -> Result<Self> {
+        Self::open(&Self::default_dir()?)
+    }
+
+    /// Open a persistent indexer backed by `dir`, loading every saved index.
+    ///
+    /// Unreadable index files are skipped with a warning rather than failing
+    /// the whole load.
+    pub fn open(dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+        let mut indexer = Self::new()?;
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let loaded = std::fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|json| Ok(serde_json::from_str::<DocsIndex>(&json)?));
+            match loaded {
+                Ok(index) => {
+                    indexer.indexes.insert(index.name.clone(), index);
+                }
+                Err(e) => {
+                    warn!(path = %path.display(), error = %e, "skipping unreadable docs index")
+                }
+            }
+        }
+        debug!(dir = %dir.display(), count = indexer.indexes.len(), "loaded docs indexes");
+        indexer.storage_dir = Some(dir.to_path_buf());
+        Ok(indexer)
+    }
+
+    /// Create a minimal indexer with no indexes and a default HTTP client.
+    ///
+    /// Used as a fallback when `new()` fails, so that downstream code
+    /// that holds an `Arc<DocsIndexer>` can still call `search()` (which
+    /// returns empty results).
+    pub fn empty() -> Self {
+        let client = Client::builder()
+            .user_agent("Hive-DocsIndexer/1.0")
+            .build()
+            .unwrap_or_else(|_| Client::new());
+        Self {
+            indexes: HashMap::new(),
+            client,
+            storage_dir: None,
+        }
+    }
+
+    /// Create a new indexer with a custom HTTP client (useful for testing).
+    pub fn with_client(client: Client) -> Self {
+        Self {
+            indexes: HashMap::new(),
+            client,
+            storage_dir: None,
+        }
+    }
+
+    // ── Indexing ───────────────────────────────────────────────────
+
+    /// Crawl a documentation site and build an index.
+    ///
+    /// Seeds the crawl from `base_url`, the site's sitemaps (from robots.txt,
+    /// else `/sitemap.xml`), and the pages of any previous index with the
+    /// same name, then follows internal links up to `max_pages` pages.
+    /// Previously indexed pages are revalidated with their ETag /
+    /// Last-Modified and reused when the server answers `304`. Respects
+    /// robots.txt disallow rules.
+    pub async fn index_site(
+        &mut self,
+        name: &str,
+        base_url: &str,
+        max_pages: usize,
+    ) -> Result<DocsIndex> {
+        let base_url = base_url.trim_end_matches('/').to_string();
+        debug!(name = %name, base_url = %base_url, max_pages = max_pages, "starting site index");
+
+        let robots = self.fetch_robots_txt(&base_url).await;
+        let previous: HashMap<String, DocPage> = self
+            .indexes
+            .get(name)
+            .filter(|index| index.base_url == base_url)
+            .map(|index| {
+                index
+                    .pages
+                    .iter()
+                    .map(|page| (page.url.clone(), page.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut seeds = vec![base_url.clone()];
+        seeds.extend(self.fetch_sitemap_urls(&base_url, &robots).await);
+        seeds.extend(previous.keys().cloned());
+        let mut queue: Vec<String> = Vec::new();
+        for url in seeds.into_iter().rev() {
+            if !queue.contains(&url) {
+                queue.push(url);
+            }
+        }
+
+        let mut visited: Vec<String> = Vec::new();
+        let mut pages: Vec<DocPage> = Vec::new();
+        let mut revalidated = 0usize;
+
+        while let Some(url) = queue.pop() {
+            if visited.len() >= max_pages {
+                break;
+            }
+            if visited.contains(&url) {
+                continue;
+            }
+            if !in_scope(&url, &base_url) {
+                continue;
+            }
+
+            let path = url.strip_prefix(&base_url).unwrap_or("/");
+            if robots.is_disallowed(path) {
+                debug!(url = %url, "skipping disallowed URL");
+                continue;
+            }
+
+            debug!(url = %url, visited = visited.len(), "fetching page");
+            visited.push(url.clone());
+
+            let cached = previous.get(&url);
+            match self.fetch_page(&url, cached).await {
+                Ok(Fetched::NotModified) => {
+                    if let Some(page) = cached {
+                        revalidated += 1;
+                        pages.push(page.clone());
+                    }
+                }
+                Ok(Fetched::Page {
+                    html,
+                    etag,
+                    last_modified,
+                }) => {
+                    let mut page = parse_html_page(&url, &html);
+                    page.etag = etag;
+                    page.last_modified = last_modified;
+                    let links = extract_links(&html, &base_url);
+                    for link in links {
+                        if !visited.contains(&link) && !queue.contains(&link) {
+                            queue.push(link);
+                        }
+                    }
+                    pages.push(page);
+                }
+                Err(e) => {
+                    warn!(url = %url, error = %e, "failed to fetch page, skipping");
+                }
+            }
+        }
+
+        debug!(
+            name = %name,
+            page_count = pages.len(),
+            revalidated = revalidated,
+            "indexing complete"
+        );
+
+        let index = DocsIndex {
+            name: name.to_string(),
+            base_url: base_url.clone(),
+            pages,
+            indexed_at: Utc::now(),
+            origin: DocsOrigin::Crawl,
+            version: None,
+        };
+
+        self.insert_index(index.clone())?;
+        Ok(index)
+    }
+
+    /// Re-crawl a previously crawled index, revalidating unchanged pages.
+    ///
+    /// Imported doc sets have nothing to revalidate against; re-import them
+    /// instead.
+    pub async fn refresh_index(&mut self, name: &str, max_pages: usize) -> Result<DocsIndex> {
+        let index = self
+            .indexes
+            .get(name)
+            .with_context(|| format!("no docs index named '{name}'"))?;
+        if index.origin != DocsOrigin::Crawl {
+            anyhow::bail!(
+                "'{name}' was imported from {:?} docs; re-import it instead",
+                index.origin
+            );
+        }
+        let base_url = index.base_url.clone();
+        self.index_site(name, &base_url, max_pages).await
+    }
+
+    // ── Offline doc sets ──────────────────────────────────────────
+
+    /// Import a rustdoc JSON file (`target/doc/<crate>.json`).
+    ///
+    /// Produce these with
+    /// `RUSTDOCFLAGS="-Z unstable-options --output-format json" cargo +nightly doc`,
+    /// which writes one file per crate in the dependency graph, at the exact
+    /// versions in `Cargo.lock`.
+    pub fn import_rustdoc_json(&mut self, path: &Path) -> Result<DocsIndex> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let index = parse_rustdoc_json(&json)
+            .with_context(|| format!("failed to parse rustdoc JSON {}", path.display()))?;
+        info!(name = %index.name, pages = index.pages.len(), "imported rustdoc JSON");
+        self.insert_index(index.clone())?;
+        Ok(index)
+    }
+
+    /// Import every rustdoc JSON file in a `target/doc` directory.
+    ///
+    /// Files that fail to parse (e.g. search indexes or an unsupported
+    /// format version) are skipped with a warning.
+    pub fn import_cargo_doc(&mut self, doc_dir: &Path) -> Result<Vec<DocsIndex>> {
+        let mut imported = Vec::new();
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(doc_dir)
+            .with_context(|| format!("failed to read {}", doc_dir.display()))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            match self.import_rustdoc_json(&path) {
+                Ok(index) => imported.push(index),
+                Err(e) => warn!(path = %path.display(), error = %e, "skipping rustdoc JSON"),
+            }
+        }
+        Ok(imported)
+    }
+
+    /// Import a DevDocs doc set: a directory (or `.tar.gz` archive) holding
+    /// the `index.json` and `db.json` DevDocs serves for each doc slug.
+    pub fn import_devdocs(&mut self, path: &Path, name: Option<&str>) -> Result<DocsIndex> {
+        let dir = self.unpack(path)?;
+        let dir = docset::find_devdocs_root(&dir)
+            .with_context(|| format!("no index.json/db.json found in {}", path.display()))?;
+        let slug = match name {
+            Some(name) => name.to_string(),
+            None => archive_stem(&dir),
+        };
+        let index_json = std::fs::read_to_string(dir.join("index.json"))?;
+        let db_json = std::fs::read_to_string(dir.join("db.json"))?;
+        let mut index = parse_devdocs(&slug, &index_json, &db_json)?;
+        if let Ok(meta) = std::fs::read_to_string(dir.join("meta.json")) {
+            index.version = docset::devdocs_version(&meta);
+        }
+        info!(name = %index.name, pages = index.pages.len(), "imported DevDocs doc set");
+        self.insert_index(index.clone())?;
+        Ok(index)
+    }
+
+    /// Import a Dash docset: a `.docset` bundle (or `.tgz` archive of one).
+    pub fn import_dash_docset(&mut self, path: &Path, name: Option<&str>) -> Result<DocsIndex> {
+        let unpacked = self.unpack(path)?;
+        let bundle = docset::find_docset_bundle(&unpacked)
+            .with_context(|| format!("no .docset bundle found in {}", path.display()))?;
+        let mut index = docset::read_dash_docset(&bundle)?;
+        if let Some(name) = name {
+            index.name = name.to_string();
+        }
+        info!(name = %index.name, pages = index.pages.len(), "imported Dash docset");
+        self.insert_index(index.clone())?;
+        Ok(index)
+    }
+
+    /// Directories pass through; `.tar.gz`/`.tgz` archives are extracted
+    /// next to the saved indexes (or into a temp dir) and that path returned.
+    fn unpack(&self, path: &Path) -> Result<PathBuf> {
+        if path.is_dir() {
+            return Ok(path.to_path_buf());
+        }
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        if !(file_name.ends_with(".tgz") || file_name.ends_with(".tar.gz")) {
+            anyhow::bail!("expected a directory or .tgz archive: {}", path.display());
+        }
+        let target = self
+            .storage_dir
+            .clone()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("archives")
+            .join(archive_stem(path));
+        std::fs::create_dir_all(&target)
+            .with_context(|| format!("failed to create {}", target.display()))?;
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        // `unpack` refuses entries that would land outside `target`.
+        tar::Archive::new(flate2::read::GzDecoder::new(file))
+            .unpack(&target)
+            .with_context(|| format!("failed to extract {}", path.display()))?;
+        Ok(target)
+    }
+
+    // ── Search ────────────────────────────────────────────────────
+
+    /// Search an index by name using TF-IDF-like scoring.
+    ///
+    /// Returns up to `limit` results ranked by relevance.
+    pub fn search(&self, name: &str, query: &str, limit: usize) -> Vec<DocSearchResult> {
+        let Some(index) = self.indexes.get(name) else {
+            return Vec::new();
+        };
+
+        let query_terms = tokenize(query);
+        if query_terms.is_empty() {
+            return Vec::new();
+        }
+
+        let total_docs = index.pages.len() as f64;
+        let mut doc_freq: HashMap<String, usize> = HashMap::new();
+        let mut doc_tokens: Vec<Vec<String>> = Vec::new();
+
+        // Compute document frequencies.
+        for page in &index.pages {
+            let tokens = tokenize(&format!("{} {} {}", page.title, page.content, page.headings.join(" ")));
+            for term in &query_terms {
+                if tokens.contains(term) {
+                    *doc_freq.entry(term.clone()).or_insert(0) += 1;
+                }
+            }
+            doc_tokens.push(tokens);
+        }
+
+        // Score each document.
+        let mut results: Vec<DocSearchResult> = Vec::new();
+        for (i, page) in index.pages.iter().enumerate() {
+            let tokens = &doc_tokens[i];
+            let token_count = tokens.len() as f64;
+            if token_count == 0.0 {
+                continue;
+            }
+
+            let mut score = 0.0;
+            for term in &query_terms {
+                let tf = tokens.iter().filter(|t| t == &term).count() as f64 / token_count;
+                let df = *doc_freq.get(term).unwrap_or(&0) as f64;
+                let idf = if df > 0.0 {
+                    (total_docs / df).ln() + 1.0
+                } else {
+                    0.0
+                };
+                score += tf * idf;
+            }
+
+            // Boost title matches.
+            let title_lower = page.title.to_lowercase();
+            for term in &query_terms {
+                if title_lower.contains(term) {
+                    score *= 1.5;
+                }
+            }
+
+            if score > 0.0 {
+                let snippet = generate_snippet(&page.content, &query_terms, 200);
+                results.push(DocSearchResult {
+                    page_url: page.url.clone(),
+                    title: page.title.clone(),
+                    snippet,
+                    relevance_score: score,
+                });
+            }
+        }
+
+        // Sort by relevance, descending.
+        results.sort_by(|a, b| {
+            b.relevance_score
+                .partial_cmp(&a.relevance_score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        results.truncate(limit);
+        results
+    }
+
+    /// Search every index and merge the results by relevance.
+    pub fn search_all(&self, query: &str, limit: usize) -> Vec<DocSearchResult> {
+        let mut results: Vec<DocSearchResult> = self
+            .indexes
+            .keys()
+            .flat_map(|name| self.search(name, query, limit))
+            .collect();
+        results.sort_by(|a, b| {
+            b.relevance_score
+                .partial_cmp(&a.relevance_score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        results.truncate(limit);
+        results
+    }
+
+    /// Get relevant documentation context formatted for AI prompting.
+    ///
+    /// Searches the named index and formats matching sections into a
+    /// single context string suitable for including in an LLM prompt.
+    pub fn get_context(&self, name: &str, query: &str) -> String {
+        let results = self.search(name, query, 5);
+        if results.is_empty() {
+            return format!("No documentation found for '{}' in index '{}'.", query, name);
+        }
+
+        let mut context = format!("## Documentation context for: {}\n\n", query);
+        for result in &results {
+            context.push_str(&format!("### {} ({})\n", result.title, result.page_url));
+            context.push_str(&result.snippet);
+            context.push_str("\n\n");
+        }
+        context
+    }
+
+    // ── Index management ──────────────────────────────────────────
+
+    /// List all indexes with their metadata.
+    ///
+    /// Returns tuples of (name, base_url, page_count, indexed_at).
+    pub fn list_indexes(&self) -> Vec<(String, String, usize, DateTime<Utc>)> {
+        self.indexes
+            .values()
+            .map(|idx| {
+                (
+                    idx.name.clone(),
+                    idx.base_url.clone(),
+                    idx.pages.len(),
+                    idx.indexed_at,
+                )
+            })
+            .collect()
+    }
+
+    /// Remove an index by name, deleting its saved file if persistent.
+    pub fn remove_index(&mut self, name: &str) {
+        debug!(name = %name, "removing docs index");
+        self.indexes.remove(name);
+        if let Some(dir) = &self.storage_dir {
+            let path = dir.join(index_file_name(name));
+            if path.exists()
+                && let Err(e) = std::fs::remove_file(&path)
+            {
+                warn!(path = %path.display(), error = %e, "failed to delete docs index file");
+            }
+        }
+    }
+
+    /// Add or replace an index, saving it when the indexer is persistent.
+    pub fn insert_index(&mut self, index: DocsIndex) -> Result<()> {
+        if let Some(dir) = &self.storage_dir {
+            let path = dir.join(index_file_name(&index.name));
+            let tmp = path.with_extension("json.tmp");
+            let json = serde_json::to_string(&index).context("failed to serialize docs index")?;
+            std::fs::write(&tmp, json)
+                .with_context(|| format!("failed to write {}", tmp.display()))?;
+            std::fs::rename(&tmp, &path)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+        self.indexes.insert(index.name.clone(), index);
+        Ok(())
+    }
+
+    /// Get a reference to a specific index.
+    pub fn get_index(&self, name: &str) -> Option<&DocsIndex> {
+        self.indexes.get(name)
+    }
+
+    /// Every indexed page, as documents for the unified search index.
+    pub fn search_documents(&self) -> Vec<SearchDocument> {
+        self.indexes
+            .values()
+            .flat_map(DocsIndex::search_documents)
+            .collect()
+    }
+
+    // ── Internal helpers ──────────────────────────────────────────
+
+    /// Fetch the HTML content of a single page, conditionally on the
+    /// validators of `cached` when there is one.
+    async fn fetch_page(&self, url: &str, cached: Option<&DocPage>) -> Result<Fetched> {
+        let mut request = self.client.get(url);
+        if let Some(page) = cached {
+            if let Some(etag) = &page.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &page.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let response = request.send().await.context("HTTP request failed")?;
+
+        let status = response.status();
+        if status == StatusCode::NOT_MODIFIED {
+            return Ok(Fetched::NotModified);
+        }
+        if !status.is_success() {
+            anyhow::bail!("HTTP {} for {}", status, url);
+        }
+
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from)
+        };
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+        let content_type = header(reqwest::header::CONTENT_TYPE).unwrap_or_default();
+
+        if !content_type.contains("text/html") && !content_type.is_empty() {
+            anyhow::bail!("non-HTML content type: {}", content_type);
+        }
+
+        let html = response
+            .text()
+            .await
+            .context("failed to read response body")?;
+        Ok(Fetched::Page {
+            html,
+            etag,
+            last_modified,
+        })
+    }
+
+    /// Collect page URLs under `base_url` from the site's sitemaps.
+    ///
+    /// Uses the `Sitemap:` entries from robots.txt, falling back to
+    /// `/sitemap.xml`, and follows sitemap indexes up to [`MAX_SITEMAPS`]
+    /// files. A missing sitemap just yields no URLs.
+    async fn fetch_sitemap_urls(&self, base_url: &str, robots: &RobotsTxt) -> Vec<String> {
+        let mut pending = if robots.sitemaps.is_empty() {
+            vec![format!("{}/sitemap.xml", base_url)]
+        } else {
+            robots.sitemaps.clone()
+        };
+        let mut fetched = 0usize;
+        let mut urls = Vec::new();
+
+        while let Some(sitemap_url) = pending.pop() {
+            if fetched >= MAX_SITEMAPS {
+                break;
+            }
+            fetched += 1;
+            debug!(url = %sitemap_url, "fetching sitemap");
+            let body = match self.client.get(&sitemap_url).send().await {
+                Ok(response) if response.status().is_success() => response.text().await.ok(),
+                _ => None,
+            };
+            let Some(body) = body else {
+                continue;
+            };
+            let sitemap = parse_sitemap(&body);
+            pending.extend(sitemap.sitemaps);
+            urls.extend(
+                sitemap
+                    .urls
+                    .into_iter()
+                    .filter(|url| in_scope(url, base_url)),
+            );
+        }
+
+        debug!(count = urls.len(), "sitemap URLs discovered");
+        urls
+    }
+
+    /// Fetch and parse robots.txt for the given base URL.
+    async fn fetch_robots_txt(&self, base_url: &str) -> RobotsTxt {
+        let robots_url = format!("{}/robots.txt", base_url);
+        debug!(url = %robots_url, "fetching robots.txt");
+
+        match self.client.get(&robots_url).send().await {
+            Ok(response) if response.status().is_success() => {
+                match response.text().await {
+                    Ok(body) => parse_robots_txt(&body),
+                    Err(_) => RobotsTxt::default(),
+                }
+            }
+            _ => RobotsTxt::default(),
+        }
+    }
+}
+
+impl Default for DocsIndexer {
+    fn default() -> Self {
+        Self::new().expect("failed to create default DocsIndexer")
+    }
+}
+
+// ── robots.txt ─────────────────────────────────────────────────────
+
+impl RobotsTxt {
+    /// Check whether a path is disallowed by the robots.txt rules.
+    fn is_disallowed(&self, path: &str) -> bool {
+        let path = if path.is_empty() { "/" } else { path };
+        self.disallowed.iter().any(|rule| path.starts_with(rule))
+    }
+}
+
+/// Parse robots.txt content and extract Disallow rules for all user agents.
+fn parse_robots_txt(content: &str) -> RobotsTxt {
+    let mut disallowed = Vec::new();
+    let mut sitemaps = Vec::new();
+    let mut applies_to_us = false;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let lower = line.to_lowercase();
+        if lower.starts_with("sitemap:") {
+            // Sitemap lines apply regardless of user-agent group.
+            if let Some((_, url)) = line.split_once(':') {
+                sitemaps.push(url.trim().to_string());
+            }
+        } else if lower.starts_with("user-agent:") {
+            let agent = lower.strip_prefix("user-agent:").unwrap_or("").trim();
+            applies_to_us = agent == "*" || agent.contains("hive");
+        } else if applies_to_us && lower.starts_with("disallow:") {
+            let path = line
+                .split_once(':')
+                .map(|(_, v)| v.trim())
+                .unwrap_or("");
+            if !path.is_empty() {
+                disallowed.push(path.to_string());
+            }
+        }
+    }
+
+    RobotsTxt {
+        disallowed,
+        sitemaps,
+    }
+}
+
+// ── sitemap.xml ────────────────────────────────────────────────────
+
+/// `<loc>` entries of a sitemap, split into page URLs and nested sitemaps
+/// (when the document is a `<sitemapindex>`).
+#[derive(Debug, Default)]
+struct Sitemap {
+    urls: Vec<String>,
+    sitemaps: Vec<String>,
+}
+
+/// Parse a sitemap or sitemap index document.
+fn parse_sitemap(xml: &str) -> Sitemap {
+    let re = Regex::new(r"(?is)<loc>\s*(.*?)\s*</loc>").unwrap();
+    let locs = re
+        .captures_iter(xml)
+        .filter_map(|cap| cap.get(1))
+        .map(|m| decode_html_entities(m.as_str()))
+        .filter(|loc| !loc.is_empty());
+    if xml.contains("<sitemapindex") {
+        Sitemap {
+            urls: Vec::new(),
+            sitemaps: locs.collect(),
+        }
+    } else {
+        Sitemap {
+            urls: locs.collect(),
+            sitemaps: Vec::new(),
+        }
+    }
+}
+
+/// File name an index is saved under: the name with path-unsafe characters
+/// replaced.
+fn index_file_name(name: &str) -> String {
+    let safe: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '@') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{safe}.json")
+}
+
+/// Archive or directory name without `.tar.gz` / `.tgz` / `.docset`.
+fn archive_stem(path: &Path) -> String {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("docs");
+    [".tar.gz", ".tgz", ".docset"]
+        .iter()
+        .find_map(|ext| name.strip_suffix(ext))
+        .unwrap_or(name)
+        .to_string()
+}
+
+// ── HTML parsing ───────────────────────────────────────────────────
+
+/// Parse an HTML page and extract structured content.
+fn parse_html_page(url: &str, html: &str) -> DocPage {
+    let title = extract_title(html);
+    let headings = extract_headings(html);
+    let code_blocks = extract_code_blocks(html);
+    let content = strip_html_tags(html);
+
+    DocPage {
+        url: url.to_string(),
+        title,
+        content,
+        headings,
+        code_blocks,
+        ..Default::default()
+    }
+}
+
+/// Extract the <title> tag content.
+fn extract_title(html: &str) -> String {
+    let re = Regex::new(r"(?is)<title[^>]*>(.*?)</title>").unwrap();
+    re.captures(html)
+        .and_then(|cap| cap.get(1))
+        .map(|m| strip_html_tags(m.as_str()).trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Extract all heading tags (h1-h6) content.
+fn extract_headings(html: &str) -> Vec<String> {
+    let re = Regex::new(r"(?is)<h[1-6][^>]*>(.*?)</h[1-6]>").unwrap();
+    re.captures_iter(html)
+        .filter_map(|cap| cap.get(1))
+        .map(|m| strip_html_tags(m.as_str()).trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Extract code blocks from <pre> and <code> tags.
+fn extract_code_blocks(html: &str) -> Vec<String> {
+    let re = Regex::new(r"(?is)<(?:pre|code)[^>]*>(.*?)</(?:pre|code)>").unwrap();
+    re.captures_iter(html)
+        .filter_map(|cap| cap.get(1))
+        .map(|m| decode_html_entities(m.as_str()).trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Extract internal links from HTML.
+fn extract_links(html: &str, base_url: &str) -> Vec<String> {
+    let re = Regex::new(r#"(?i)href\s*=\s*["']([^"'#]+)"#).unwrap();
+    let mut links = Vec::new();
+
+    for cap in re.captures_iter(html) {
+        if let Some(href) = cap.get(1) {
+            let href = href.as_str().trim();
+            let full_url = resolve_url(href, base_url);
+            if let Some(url) = full_url {
+                if in_scope(&url, base_url) {
+                    links.push(url);
+                }
+            }
+        }
+    }
+
+    links
+}
+
+/// Whether `url` lies under `base_url`: same scheme, host and port, and the
+/// base's path segments are a prefix of the URL's. A string prefix would
+/// also admit `https://docs.example.com.evil` or `/guide-old` for `/guide`.
+fn in_scope(url: &str, base_url: &str) -> bool {
+    let (Ok(url), Ok(base)) = (url::Url::parse(url), url::Url::parse(base_url)) else {
+        return false;
+    };
+    if url.scheme() != base.scheme()
+        || url.host_str() != base.host_str()
+        || url.port_or_known_default() != base.port_or_known_default()
+    {
+        return false;
+    }
+    let segments = |u: &url::Url| -> Vec<String> {
+        u.path_segments()
+            .map(|s| s.filter(|s| !s.is_empty()).map(String::from).collect())
+            .unwrap_or_default()
+    };
+    segments(&url).starts_with(&segments(&base))
+}
+
+/// Resolve a potentially relative URL against a base URL.
+fn resolve_url(href: &str, base_url: &str) -> Option<String> {
+    if href.starts_with("http://") || href.starts_with("https://") {
+        Some(href.to_string())
+    } else if href.starts_with('/') {
+        // Absolute path: join with origin.
+        if let Some(origin) = extract_origin(base_url) {
+            Some(format!("{}{}", origin, href))
+        } else {
+            None
+        }
+    } else if href.starts_with("mailto:")
+        || href.starts_with("javascript:")
+        || href.starts_with("tel:")
+    {
+        None
+    } else {
+        // Relative path.
+        Some(format!("{}/{}", base_url, href))
+    }
+}
+
+/// Extract the origin (scheme + host) from a URL.
+fn extract_origin(url: &str) -> Option<String> {
+    let re = Regex::new(r"^(https?://[^/]+)").unwrap();
+    re.captures(url)
+        .and_then(|cap| cap.get(1))
+        .map(|m| m.as_str().to_string())
+}
+
+/// Strip all HTML tags, leaving only text content.
+fn strip_html_tags(html: &str) -> String {
+    // Remove script and style blocks entirely.
+    let re_script = Regex::new(r"(?is)<script[^>]*>.*?</script>").unwrap();
+    let cleaned = re_script.replace_all(html, " ");
+    let re_style = Regex::new(r"(?is)<style[^>]*>.*?</style>").unwrap();
+    let cleaned = re_style.replace_all(&cleaned, " ");
+
+    // Replace block-level tags with newlines.
+    let re_block = Regex::new(r"(?i)</?(?:p|div|br|li|tr|h[1-6])[^>]*>").unwrap();
+    let cleaned = re_block.replace_all(&cleaned, "\n");
+
+    // Remove all remaining tags.
+    let re_tags = Regex::new(r"<[^>]+>").unwrap();
+    let cleaned = re_tags.replace_all(&cleaned, " ");
+
+    // Decode common HTML entities.
+    let text = decode_html_entities(&cleaned);
+
+    // Collapse whitespace.
+    let re_ws = Regex::new(r"[ \t]+").unwrap();
+    let text = re_ws.replace_all(&text, " ");
+
+    // Collapse multiple newlines.
+    let re_nl = Regex::new(r"\n{3,}").unwrap();
+    let text = re_nl.replace_all(&text, "\n\n");
+
+    text.trim().to_string()
+}
+
+/// Decode common HTML entities.
+fn decode_html_entities(text: &str) -> String {
+    text.replace("&amp;", "&")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+}
+
+// ── TF-IDF helpers ─────────────────────────────────────────────────
+
+/// Tokenize text into lowercase words for search.
+fn tokenize(text: &str) -> Vec<String> {
+    let re = Regex::new(r"[a-zA-Z0-9_]+").unwrap();
+    re.find_iter(&text.to_lowercase())
+        .map(|m| m.as_str().to_string())
+        .filter(|w| w.len() >= 2)
+        .collect()
+}
+
+/// Generate a text snippet around the best matching region.
+fn generate_snippet(content: &str, query_terms: &[String], max_len: usize) -> String {
+    let lower = content.to_lowercase();
+    let mut best_pos = 0;
+    let mut best_count = 0;
+
+    // Slide a window through the content and find the region with the
+    // most query term matches.
+    let chars: Vec<char> = lower.chars().collect();
+    let window_size = max_len.min(chars.len());
+
+    if chars.is_empty() {
+        return String::new();
+    }
+
+    let step = 50.max(1);
+    let mut pos = 0;
+    while pos + window_size <= chars.len() {
+        let window: String = chars[pos..pos + window_size].iter().collect();
+        let count: usize = query_terms.iter().filter(|t| window.contains(t.as_str())).count();
+        if count > best_count {
+            best_count = count;
+            best_pos = pos;
+        }
+        pos += step;
+    }
+
+    let content_chars: Vec<char> = content.chars().collect();
+    let end = (best_pos + max_len).min(content_chars.len());
+    let snippet: String = content_chars[best_pos..end].iter().collect();
+
+    let snippet = snippet.trim().to_string();
+    if best_pos > 0 {
+        format!("...{}", snippet)
+    } else if end < content_chars.len() {
+        format!("{}...", snippet)
+    } else {
+        snippet
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── HTML parsing ──────────────────────────────────────────────
+
+    #[test]
+    fn test_extract_title() {
+        let html = "<html><head><title>My Docs</title></head><body></body></html>";
+        assert_eq!(extract_title(html), "My Docs");
+    }
+
+    #[test]
+    fn test_extract_title_missing() {
+        let html = "<html><body>no title</body></html>";
+        assert_eq!(extract_title(html), "");
+    }
+
+    #[test]
+    fn test_extract_headings() {
+        let html = "<h1>Intro</h1><p>text</p><h2>Details</h2><h3>Sub</h3>";
+        let headings = extract_headings(html);
+        assert_eq!(headings, vec!["Intro", "Details", "Sub"]);
+    }
+
+    #[test]
+    fn test_extract_headings_empty() {
+        let html = "<p>no headings here</p>";
+        let headings = extract_headings(html);
+        assert!(headings.is_empty());
+    }
+
+    #[test]
+    fn test_extract_code_blocks() {
+        let html = "<p>intro</p><pre>fn main() {}</pre><code>let x = 1;</code>";
+        let blocks = extract_code_blocks(html);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0], "fn main() {}");
+        assert_eq!(blocks[1], "let x = 1;");
+    }
+
+    #[test]
+    fn test_strip_html_tags() {
+        let html = "<p>Hello <b>world</b></p><script>alert('x')</script>";
+        let text = strip_html_tags(html);
+        assert!(text.contains("Hello"));
+        assert!(text.contains("world"));
+        assert!(!text.contains("alert"));
+        assert!(!text.contains("<p>"));
+    }
+
+    #[test]
+    fn test_decode_html_entities() {
+        assert_eq!(decode_html_entities("&amp; &lt; &gt;"), "& < >");
+        assert_eq!(decode_html_entities("&quot;hello&quot;"), "\"hello\"");
+        assert_eq!(decode_html_entities("it&#39;s"), "it's");
+    }
+
+    // ── Link extraction ───────────────────────────────────────────
+
+    #[test]
+    fn test_extract_links_absolute() {
+        let html = r#"<a href="https://docs.example.com/guide">Guide</a>"#;
+        let links = extract_links(html, "https://docs.example.com");
+        assert_eq!(links, vec!["https://docs.example.com/guide"]);
+    }
+
+    #[test]
+    fn test_extract_links_relative() {
+        let html = r#"<a href="/api/reference">API</a>"#;
+        let links = extract_links(html, "https://docs.example.com");
+        assert_eq!(links, vec!["https://docs.example.com/api/reference"]);
+    }
+
+    #[test]
+    fn test_extract_links_external_filtered() {
+        let html = r#"<a href="https://other.com/page">External</a>"#;
+        let links = extract_links(html, "https://docs.example.com");
+        assert!(links.is_empty());
+    }
+
+    #[test]
+    fn test_extract_links_mailto_filtered() {
+        let html = r#"<a href="mailto:test@example.com">Email</a>"#;
+        let links = extract_links(html, "https://docs.example.com");
+        assert!(links.is_empty());
+    }
+
+    #[test]
+    fn test_in_scope_compares_host_and_segments() {
+        let base = "https://docs.example.com/guide";
+        assert!(in_scope("https://docs.example.com/guide", base));
+        assert!(in_scope("https://DOCS.example.com/guide/intro?x=1", base));
+        assert!(!in_scope("https://docs.example.com/guide-old/intro", base));
+        assert!(!in_scope("https://docs.example.com.evil.net/guide/intro", base));
+        assert!(!in_scope("https://docs.example.com@evil.net/guide", base));
+        assert!(!in_scope("http://docs.example.com/guide", base));
+        assert!(!in_scope("https://docs.example.com:8443/guide", base));
+        assert!(in_scope("https://docs.example.com/", "https://docs.example.com"));
+    }
+
+    // ── URL resolution ────────────────────────────────────────────
+
+    #[test]
+    fn test_resolve_url_absolute() {
+        let url = resolve_url("https://docs.example.com/page", "https://docs.example.com");
+        assert_eq!(url, Some("https://docs.example.com/page".to_string()));
+    }
+
+    #[test]
+    fn test_resolve_url_relative_path() {
+        let url = resolve_url("/guide/intro", "https://docs.example.com");
+        assert_eq!(url, Some("https://docs.example.com/guide/intro".to_string()));
+    }
+
+    #[test]
+    fn test_resolve_url_relative_segment() {
+        let url = resolve_url("page2", "https://docs.example.com/guide");
+        assert_eq!(url, Some("https://docs.example.com/guide/page2".to_string()));
+    }
+
+    #[test]
+    fn test_extract_origin() {
+        assert_eq!(
+            extract_origin("https://docs.example.com/path/to/page"),
+            Some("https://docs.example.com".to_string())
+        );
+    }
+
+    // ── robots.txt ────────────────────────────────────────────────
+
+    #[test]
+    fn test_parse_robots_txt_disallow() {
+        let content = "User-agent: *\nDisallow: /admin\nDisallow: /private/\n";
+        let robots = parse_robots_txt(content);
+        assert!(robots.is_disallowed("/admin"));
+        assert!(robots.is_disallowed("/admin/page"));
+        assert!(robots.is_disallowed("/private/secret"));
+        assert!(!robots.is_disallowed("/public"));
+    }
+
+    #[test]
+    fn test_parse_robots_txt_empty() {
+        let robots = parse_robots_txt("");
+        assert!(!robots.is_disallowed("/anything"));
+    }
+
+    #[test]
+    fn test_parse_robots_txt_only_other_agent() {
+        let content = "User-agent: Googlebot\nDisallow: /secret\n";
+        let robots = parse_robots_txt(content);
+        assert!(!robots.is_disallowed("/secret"));
+    }
+
+    #[test]
+    fn test_parse_robots_txt_sitemaps() {
+        let content = "Sitemap: https://docs.example.com/sitemap.xml\nUser-agent: Googlebot\nDisallow: /x\nSitemap: https://docs.example.com/api.xml\n";
+        let robots = parse_robots_txt(content);
+        assert_eq!(
+            robots.sitemaps,
+            [
+                "https://docs.example.com/sitemap.xml",
+                "https://docs.example.com/api.xml"
+            ]
+        );
+    }
+
+    // ── sitemap.xml ───────────────────────────────────────────────
+
+    #[test]
+    fn test_parse_sitemap_urlset() {
+        let xml = r#"<?xml version="1.0"?>
+            <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+              <url><loc>https://docs.example.com/guide</loc><lastmod>2024-01-01</lastmod></url>
+              <url><loc> https://docs.example.com/api?a=1&amp;b=2 </loc></url>
+            </urlset>"#;
+        let sitemap = parse_sitemap(xml);
+        assert_eq!(
+            sitemap.urls,
+            [
+                "https://docs.example.com/guide",
+                "https://docs.example.com/api?a=1&b=2"
+            ]
+        );
+        assert!(sitemap.sitemaps.is_empty());
+    }
+
+    #[test]
+    fn test_parse_sitemap_index() {
+        let xml = r#"<sitemapindex><sitemap><loc>https://docs.example.com/s1.xml</loc></sitemap></sitemapindex>"#;
+        let sitemap = parse_sitemap(xml);
+        assert!(sitemap.urls.is_empty());
+        assert_eq!(sitemap.sitemaps, ["https://docs.example.com/s1.xml"]);
+    }
+
+    // ── Tokenizer ─────────────────────────────────────────────────
+
+    #[test]
+    fn test_tokenize() {
+        let tokens = tokenize("Hello, World! This is a test.");
+        assert!(tokens.contains(&"hello".to_string()));
+        assert!(tokens.contains(&"world".to_string()));
+        assert!(tokens.contains(&"this".to_string()));
+        assert!(tokens.contains(&"test".to_string()));
+        // Single-character words filtered out.
+        assert!(!tokens.contains(&"a".to_string()));
+    }
+
+    #[test]
+    fn test_tokenize_empty() {
+        let tokens = tokenize("");
+        assert!(tokens.is_empty());
+    }
+
+    // ── Snippet generation ────────────────────────────────────────
+
+    #[test]
+    fn test_generate_snippet() {
+        let content = "This is a document about Rust programming. Rust is fast and safe.";
+        let snippet = generate_snippet(content, &["rust".to_string()], 50);
+        assert!(!snippet.is_empty());
+    }
+
+    #[test]
+    fn test_generate_snippet_empty_content() {
+        let snippet = generate_snippet("", &["rust".to_string()], 100);
+        assert!(snippet.is_empty());
+    }
+
+    // ── TF-IDF search ─────────────────────────────────────────────
+
+    #[test]
+    fn test_search_nonexistent_index() {
+        let indexer = DocsIndexer::new().unwrap();
+        let results = indexer.search("nonexistent", "rust", 10);
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_search_empty_query() {
+        let mut indexer = DocsIndexer::new().unwrap();
+        indexer.indexes.insert(
+            "test".to_string(),
+            DocsIndex {
+                name: "test".to_string(),
+                base_url: "https://example.com".to_string(),
+                pages: vec![DocPage {
+                    url: "https://example.com/page".to_string(),
+                    title: "Test Page".to_string(),
+                    content: "Some content here".to_string(),
+                    headings: vec![],
+                    code_blocks: vec![],
+                    ..Default::default()
+                }],
+                indexed_at: Utc::now(),
+                origin: DocsOrigin::Crawl,
+                version: None,
+            },
+        );
+        let results = indexer.search("test", "", 10);
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_search_returns_results() {
+        let mut indexer = DocsIndexer::new().unwrap();
+        indexer.indexes.insert(
+            "rust".to_string(),
+            DocsIndex {
+                name: "rust".to_string(),
+                base_url: "https://doc.rust-lang.org".to_string(),
+                pages: vec![
+                    DocPage {
+                        url: "https://doc.rust-lang.org/ownership".to_string(),
+                        title: "Ownership".to_string(),
+                        content: "Rust ownership is a key concept. Ownership rules govern memory.".to_string(),
+                        headings: vec!["Ownership".to_string()],
+                        code_blocks: vec![],
+                        ..Default::default()
+                    },
+                    DocPage {
+                        url: "https://doc.rust-lang.org/borrowing".to_string(),
+                        title: "Borrowing".to_string(),
+                        content: "Borrowing lets you reference data without ownership.".to_string(),
+                        headings: vec!["Borrowing".to_string()],
+                        code_blocks: vec![],
+                        ..Default::default()
+                    },
+                    DocPage {
+                        url: "https://doc.rust-lang.org/types".to_string(),
+                        title: "Types".to_string(),
+                        content: "Rust has a strong type system with generics and traits.".to_string(),
+                        headings: vec!["Types".to_string()],
+                        code_blocks: vec![],
+                        ..Default::default()
+                    },
+                ],
+                indexed_at: Utc::now(),
+                origin: DocsOrigin::Crawl,
+                version: None,
+            },
+        );
+
+        let results = indexer.search("rust", "ownership", 10);
+        assert!(!results.is_empty());
+        // The ownership page should rank highest.
+        assert_eq!(results[0].page_url, "https://doc.rust-lang.org/ownership");
+        assert!(results[0].relevance_score > 0.0);
+    }
+
+    #[test]
+    fn test_search_respects_limit() {
+        let mut indexer = DocsIndexer::new().unwrap();
+        let pages: Vec<DocPage> = (0..20)
+            .map(|i| DocPage {
+                url: format!("https://example.com/page{}", i),
+                title: format!("Page {}", i),
+                content: "rust programming language".to_string(),
+                headings: vec![],
+                code_blocks: vec![],
+                ..Default::default()
+            })
+            .collect();
+        indexer.indexes.insert(
+            "many".to_string(),
+            DocsIndex {
+                name: "many".to_string(),
+                base_url: "https://example.com".to_string(),
+                pages,
+                indexed_at: Utc::now(),
+                origin: DocsOrigin::Crawl,
+                version: None,
+            },
+        );
+
+        let results = indexer.search("many", "rust", 5);
+        assert!(results.len() <= 5);
+    }
+
+    // ── Context generation ────────────────────────────────────────
+
+    #[test]
+    fn test_get_context_no_index() {
+        let indexer = DocsIndexer::new().unwrap();
+        let ctx = indexer.get_context("missing", "query");
+        assert!(ctx.contains("No documentation found"));
+    }
+
+    #[test]
+    fn test_get_context_with_results() {
+        let mut indexer = DocsIndexer::new().unwrap();
+        indexer.indexes.insert(
+            "test".to_string(),
+            DocsIndex {
+                name: "test".to_string(),
+                base_url: "https://example.com".to_string(),
+                pages: vec![DocPage {
+                    url: "https://example.com/setup".to_string(),
+                    title: "Setup Guide".to_string(),
+                    content: "Follow this setup guide to install the application.".to_string(),
+                    headings: vec!["Setup".to_string()],
+                    code_blocks: vec![],
+                    ..Default::default()
+                }],
+                indexed_at: Utc::now(),
+                origin: DocsOrigin::Crawl,
+                version: None,
+            },
+        );
+
+        let ctx = indexer.get_context("test", "setup");
+        assert!(ctx.contains("Documentation context for"));
+        assert!(ctx.contains("Setup Guide"));
+    }
+
+    // ── Index management ──────────────────────────────────────────
+
+    #[test]
+    fn test_list_indexes_empty() {
+        let indexer = DocsIndexer::new().unwrap();
+        assert!(indexer.list_indexes().is_empty());
+    }
+
+    #[test]
+    fn test_list_and_remove_index() {
+        let mut indexer = DocsIndexer::new().unwrap();
+        indexer.indexes.insert(
+            "docs".to_string(),
+            DocsIndex {
+                name: "docs".to_string(),
+                base_url: "https://docs.example.com".to_string(),
+                pages: vec![],
+                indexed_at: Utc::now(),
+                origin: DocsOrigin::Crawl,
+                version: None,
+            },
+        );
+
+        let list = indexer.list_indexes();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].0, "docs");
+        assert_eq!(list[0].1, "https://docs.example.com");
+        assert_eq!(list[0].2, 0);
+
+        indexer.remove_index("docs");
+        assert!(indexer.list_indexes().is_empty());
+    }
+
+    #[test]
+    fn test_get_index() {
+        let mut indexer = DocsIndexer::new().unwrap();
+        indexer.indexes.insert(
+            "test".to_string(),
+            DocsIndex {
+                name: "test".to_string(),
+                base_url: "https://example.com".to_string(),
+                pages: vec![],
+                indexed_at: Utc::now(),
+                origin: DocsOrigin::Crawl,
+                version: None,
+            },
+        );
+
+        assert!(indexer.get_index("test").is_some());
+        assert!(indexer.get_index("missing").is_none());
+    }
+
+    #[test]
+    fn test_search_documents() {
+        let mut indexer = DocsIndexer::new().unwrap();
+        let page = parse_html_page(
+            "https://example.com/api",
+            "<title>API</title><h2>Auth</h2><p>Use bearer tokens.</p><pre>curl -H auth</pre>",
+        );
+        indexer.indexes.insert(
+            "example".to_string(),
+            DocsIndex {
+                name: "example".to_string(),
+                base_url: "https://example.com".to_string(),
+                pages: vec![page],
+                indexed_at: Utc::now(),
+                origin: DocsOrigin::Crawl,
+                version: None,
+            },
+        );
+
+        let docs = indexer.search_documents();
+        assert_eq!(docs.len(), 1);
+        assert_eq!(docs[0].source, SearchSource::Docs);
+        assert_eq!(docs[0].location.as_deref(), Some("https://example.com/api"));
+        assert_eq!(docs[0].tags, ["example", "Auth"]);
+        assert!(docs[0].body.contains("bearer tokens"));
+        assert!(docs[0].body.contains("curl -H auth"));
+    }
+
+    // ── Page parsing integration ──────────────────────────────────
+
+    #[test]
+    fn test_parse_html_page() {
+        let html = r#"
+            <html>
+            <head><title>API Reference</title></head>
+            <body>
+                <h1>API Reference</h1>
+                <p>This is the API documentation.</p>
+                <h2>Authentication</h2>
+                <p>Use bearer tokens.</p>
+                <pre>Authorization: Bearer token123</pre>
+            </body>
+            </html>
+        "#;
+
+        let page = parse_html_page("https://example.com/api", html);
+        assert_eq!(page.url, "https://example.com/api");
+        assert_eq!(page.title, "API Reference");
+        assert!(page.headings.contains(&"API Reference".to_string()));
+        assert!(page.headings.contains(&"Authentication".to_string()));
+        assert!(!page.code_blocks.is_empty());
+        assert!(page.content.contains("API documentation"));
+    }
+
+    // ── Persistence ───────────────────────────────────────────────
+
+    fn tempdir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hive_docs_test_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_index_file_name() {
+        assert_eq!(index_file_name("tokio@1.40.0"), "tokio@1.40.0.json");
+        assert_eq!(index_file_name("../etc/passwd"), ".._etc_passwd.json");
+        assert_eq!(index_file_name("python~3.12"), "python_3.12.json");
+    }
+
+    #[test]
+    fn test_archive_stem() {
+        assert_eq!(archive_stem(Path::new("/tmp/Rust.tgz")), "Rust");
+        assert_eq!(
+            archive_stem(Path::new("/tmp/rust~1.80.tar.gz")),
+            "rust~1.80"
+        );
+        assert_eq!(archive_stem(Path::new("/tmp/Go.docset")), "Go");
+    }
+
+    #[test]
+    fn test_persistent_indexes_survive_reopen() {
+        let dir = tempdir();
+        let mut indexer = DocsIndexer::open(&dir).unwrap();
+        indexer
+            .insert_index(DocsIndex {
+                name: "serde@1.0.210".to_string(),
+                base_url: "https://docs.rs/serde/1.0.210".to_string(),
+                pages: vec![DocPage {
+                    url: "https://docs.rs/serde/1.0.210/serde/?search=serde%3A%3ASerialize"
+                        .to_string(),
+                    title: "serde::Serialize".to_string(),
+                    content: "A data structure that can be serialized.".to_string(),
+                    ..Default::default()
+                }],
+                indexed_at: Utc::now(),
+                origin: DocsOrigin::Rustdoc,
+                version: Some("1.0.210".to_string()),
+            })
+            .unwrap();
+        std::fs::write(dir.join("broken.json"), "{").unwrap();
+
+        let mut reopened = DocsIndexer::open(&dir).unwrap();
+        let index = reopened.get_index("serde@1.0.210").unwrap();
+        assert_eq!(index.origin, DocsOrigin::Rustdoc);
+        assert_eq!(index.version.as_deref(), Some("1.0.210"));
+        assert_eq!(reopened.search_all("serialized", 5).len(), 1);
+
+        reopened.remove_index("serde@1.0.210");
+        assert!(!dir.join("serde@1.0.210.json").exists());
+        assert!(DocsIndexer::open(&dir).unwrap().list_indexes().is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_import_devdocs_archive() {
+        let dir = tempdir();
+        let archive = dir.join("rust~1.80.tgz");
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            std::fs::File::create(&archive).unwrap(),
+            flate2::Compression::default(),
+        ));
+        for (name, body) in [
+            ("rust~1.80/index.json", r#"{"entries": [], "types": []}"#),
+            ("rust~1.80/db.json", r#"{"index": "<p>Overview.</p>"}"#),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(body.len() as u64);
+            header.set_mode(0o644);
+            builder
+                .append_data(&mut header, name, body.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let mut indexer = DocsIndexer::open(&dir.join("store")).unwrap();
+        let index = indexer.import_devdocs(&archive, None).unwrap();
+        assert_eq!(index.name, "rust~1.80");
+        assert_eq!(index.pages.len(), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_legacy_index_json_loads_as_crawl() {
+        let json = r#"{"name":"old","base_url":"https://example.com","pages":[
+            {"url":"https://example.com","title":"Home","content":"hi","headings":[],"code_blocks":[]}
+        ],"indexed_at":"2024-01-01T00:00:00Z"}"#;
+        let index: DocsIndex = serde_json::from_str(json).unwrap();
+        assert_eq!(index.origin, DocsOrigin::Crawl);
+        assert!(index.version.is_none());
+        assert!(index.pages[0].etag.is_none());
+    }
+
+    #[test]
+    fn test_search_all_merges_indexes() {
+        let mut indexer = DocsIndexer::new().unwrap();
+        for (name, content) in [
+            ("a", "tokio runtime"),
+            ("b", "tokio tokio tokio"),
+            ("c", "serde"),
+        ] {
+            indexer
+                .insert_index(DocsIndex {
+                    name: name.to_string(),
+                    base_url: format!("https://{name}.example.com"),
+                    pages: vec![DocPage {
+                        url: format!("https://{name}.example.com/"),
+                        title: name.to_string(),
+                        content: content.to_string(),
+                        ..Default::default()
+                    }],
+                    indexed_at: Utc::now(),
+                    origin: DocsOrigin::Crawl,
+                    version: None,
+                })
+                .unwrap();
+        }
+        let results = indexer.search_all("tokio", 10);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].page_url, "https://b.example.com/");
+        assert_eq!(indexer.search_all("tokio", 1).len(), 1);
+    }
+
+    #[test]
+    fn test_import_cargo_doc_skips_non_rustdoc_json() {
+        let dir = tempdir();
+        std::fs::write(
+            dir.join("demo.json"),
+            r#"{"root":0,"crate_version":"0.1.0","index":{"0":{"crate_id":0,"name":"demo","docs":"Demo.","inner":{"module":{"items":[]}}}},"paths":{"0":{"crate_id":0,"path":["demo"],"kind":"module"}}}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("search-index.json"), "[]").unwrap();
+        std::fs::write(dir.join("readme.txt"), "ignored").unwrap();
+
+        let mut indexer = DocsIndexer::new().unwrap();
+        let imported = indexer.import_cargo_doc(&dir).unwrap();
+        assert_eq!(imported.len(), 1);
+        assert!(indexer.get_index("demo@0.1.0").is_some());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_rejects_imported_index() {
+        let mut indexer = DocsIndexer::new().unwrap();
+        indexer
+            .insert_index(DocsIndex {
+                name: "rust".to_string(),
+                base_url: "https://devdocs.io/rust".to_string(),
+                pages: vec![],
+                indexed_at: Utc::now(),
+                origin: DocsOrigin::DevDocs,
+                version: None,
+            })
+            .unwrap();
+        let err = indexer.refresh_index("rust", 10).await.unwrap_err();
+        assert!(err.to_string().contains("re-import"));
+        assert!(indexer.refresh_index("missing", 10).await.is_err());
+    }
+
+    // ── Crawling ──────────────────────────────────────────────────
+
+    /// Serve a tiny site on localhost: `/` links to `/a`, `/orphan` is only
+    /// listed in the sitemap. Pages carry an ETag and answer `304` when it
+    /// is sent back; the returned counter tracks those `304`s.
+    fn serve_site() -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use std::io::{BufRead, BufReader, Write};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let not_modified = std::sync::Arc::new(AtomicUsize::new(0));
+        let counter = not_modified.clone();
+        let sitemap = format!("<urlset><url><loc>{base}/orphan</loc></url></urlset>");
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let path = request_line
+                    .split_whitespace()
+                    .nth(1)
+                    .unwrap_or("/")
+                    .to_string();
+                let mut if_none_match = None;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':')
+                        && name.eq_ignore_ascii_case("if-none-match")
+                    {
+                        if_none_match = Some(value.trim().to_string());
+                    }
+                }
+                let etag = format!("\"{path}-v1\"");
+                let (status, content_type, body) = match path.as_str() {
+                    "/sitemap.xml" => ("200 OK", "application/xml", sitemap.clone()),
+                    "/" => (
+                        "200 OK",
+                        "text/html",
+                        r#"<title>Home</title><a href="/a">A</a>"#.to_string(),
+                    ),
+                    "/a" => ("200 OK", "text/html", "<title>Page A</title>".to_string()),
+                    "/orphan" => ("200 OK", "text/html", "<title>Orphan</title>".to_string()),
+                    _ => ("404 Not Found", "text/plain", String::new()),
+                };
+                let response = if status.starts_with("200")
+                    && content_type == "text/html"
+                    && if_none_match.as_deref() == Some(etag.as_str())
+                {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    format!(
+                        "HTTP/1.1 304 Not Modified\r\nETag: {etag}\r\nConnection: close\r\n\r\n"
+                    )
+                } else {
+                    format!(
+                        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nETag: {etag}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    )
+                };
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        (base, not_modified)
+    }
+
+    #[tokio::test]
+    async fn test_index_site_uses_sitemap_and_revalidates() {
+        use std::sync::atomic::Ordering;
+
+        let (base, not_modified) = serve_site();
+        let dir = tempdir();
+        let mut indexer = DocsIndexer::open(&dir).unwrap();
+
+        let first = indexer.index_site("local", &base, 10).await.unwrap();
+        let mut titles: Vec<&str> = first.pages.iter().map(|p| p.title.as_str()).collect();
+        titles.sort();
+        assert_eq!(titles, ["Home", "Orphan", "Page A"]);
+        assert!(first.pages.iter().all(|p| p.etag.is_some()));
+        assert_eq!(not_modified.load(Ordering::SeqCst), 0);
+
+        let mut reopened = DocsIndexer::open(&dir).unwrap();
+        let refreshed = reopened.refresh_index("local", 10).await.unwrap();
+        assert_eq!(refreshed.pages.len(), 3);
+        assert_eq!(not_modified.load(Ordering::SeqCst), 3);
+        assert!(refreshed.pages.iter().any(|p| p.title == "Orphan"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_default_indexer() {
+        let indexer = DocsIndexer::default();
+        assert!(indexer.list_indexes().is_empty());
+    }
+}
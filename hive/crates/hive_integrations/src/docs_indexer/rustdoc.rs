@@ -0,0 +1,268 @@
+//! Importer for rustdoc's JSON output (`--output-format json`).
+//!
+//! Only the fields that have been stable across format versions are read:
+//! `root`, `crate_version`, `index` (items with `name`, `docs`, `crate_id`
+//! and a single-key `inner`) and `paths`. Ids may be strings (older
+//! formats) or integers.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde_json::{Map, Value};
+
+use super::{DocPage, DocsIndex, DocsOrigin};
+
+/// Item kinds that never get a page of their own.
+const SKIPPED_KINDS: &[&str] = &["impl", "use", "import", "extern_crate", "struct_field"];
+
+/// Convert a rustdoc JSON document into a [`DocsIndex`] named
+/// `<crate>@<version>`, with one page per documented item of the crate.
+///
+/// Associated items (methods, associated consts/types) are titled after
+/// the type or trait that owns them, e.g. `tokio::runtime::Runtime::new`.
+pub fn parse_rustdoc_json(json: &str) -> Result<DocsIndex> {
+    let doc: Value = serde_json::from_str(json)?;
+    let index = doc
+        .get("index")
+        .and_then(Value::as_object)
+        .context("missing `index`")?;
+    let paths = doc
+        .get("paths")
+        .and_then(Value::as_object)
+        .context("missing `paths`")?;
+    let root = doc.get("root").and_then(id_key).context("missing `root`")?;
+    let crate_name = index
+        .get(&root)
+        .and_then(|item| item.get("name"))
+        .and_then(Value::as_str)
+        .context("root item has no name")?
+        .to_string();
+    let version = doc
+        .get("crate_version")
+        .and_then(Value::as_str)
+        .map(String::from);
+
+    // Associated items have no `paths` entry; map them to their owner.
+    let mut owners: HashMap<String, String> = HashMap::new();
+    for (id, item) in index {
+        let Some((kind, inner)) = item_inner(item) else {
+            continue;
+        };
+        let owner = match kind {
+            "impl" => inner
+                .get("for")
+                .and_then(|ty| ty.get("resolved_path"))
+                .and_then(|resolved| {
+                    resolved
+                        .get("id")
+                        .and_then(id_key)
+                        .and_then(|id| item_path(paths, &id))
+                        .or_else(|| {
+                            resolved
+                                .get("path")
+                                .or_else(|| resolved.get("name"))
+                                .and_then(Value::as_str)
+                                .map(String::from)
+                        })
+                }),
+            "trait" => item_path(paths, id),
+            _ => None,
+        };
+        let (Some(owner), Some(children)) = (owner, inner.get("items").and_then(Value::as_array))
+        else {
+            continue;
+        };
+        for child in children.iter().filter_map(id_key) {
+            owners.entry(child).or_insert_with(|| owner.clone());
+        }
+    }
+
+    let mut pages = Vec::new();
+    for (id, item) in index {
+        if item.get("crate_id").and_then(Value::as_u64) != Some(0) {
+            continue;
+        }
+        let Some((kind, _)) = item_inner(item) else {
+            continue;
+        };
+        let docs = item
+            .get("docs")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .trim();
+        if docs.is_empty() || SKIPPED_KINDS.contains(&kind) {
+            continue;
+        }
+        let path = item_path(paths, id).or_else(|| {
+            let name = item.get("name").and_then(Value::as_str)?;
+            Some(match owners.get(id) {
+                Some(owner) => format!("{owner}::{name}"),
+                None => name.to_string(),
+            })
+        });
+        let Some(path) = path else {
+            continue;
+        };
+        pages.push(DocPage {
+            url: docs_rs_url(&crate_name, version.as_deref(), &path),
+            title: path,
+            content: docs.to_string(),
+            headings: vec![kind.to_string()],
+            code_blocks: fenced_code_blocks(docs),
+            ..Default::default()
+        });
+    }
+    pages.sort_by(|a, b| a.title.cmp(&b.title));
+
+    Ok(DocsIndex {
+        name: match &version {
+            Some(version) => format!("{crate_name}@{version}"),
+            None => crate_name.clone(),
+        },
+        base_url: format!(
+            "https://docs.rs/{crate_name}/{}",
+            version.as_deref().unwrap_or("latest")
+        ),
+        pages,
+        indexed_at: Utc::now(),
+        origin: DocsOrigin::Rustdoc,
+        version,
+    })
+}
+
+/// Ids are strings in older format versions and integers in newer ones.
+fn id_key(id: &Value) -> Option<String> {
+    match id {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// `(kind, inner)` for an item: newer formats nest the payload under a
+/// single key of `inner`, older ones carry a separate `kind` field.
+fn item_inner(item: &Value) -> Option<(&str, &Value)> {
+    let inner = item.get("inner")?;
+    if let Some(kind) = item.get("kind").and_then(Value::as_str) {
+        return Some((kind, inner));
+    }
+    let (kind, payload) = inner.as_object()?.iter().next()?;
+    Some((kind.as_str(), payload))
+}
+
+/// The `::`-joined path of an item from the `paths` table.
+fn item_path(paths: &Map<String, Value>, id: &str) -> Option<String> {
+    let segments = paths.get(id)?.get("path")?.as_array()?;
+    let segments: Vec<&str> = segments.iter().filter_map(Value::as_str).collect();
+    (!segments.is_empty()).then(|| segments.join("::"))
+}
+
+/// A docs.rs search link for an item; rustdoc search resolves full paths.
+fn docs_rs_url(crate_name: &str, version: Option<&str>, path: &str) -> String {
+    let query: String = url::form_urlencoded::byte_serialize(path.as_bytes()).collect();
+    format!(
+        "https://docs.rs/{crate_name}/{}/{crate_name}/?search={query}",
+        version.unwrap_or("latest")
+    )
+}
+
+/// Contents of the ``` fenced blocks in a Markdown doc comment.
+fn fenced_code_blocks(markdown: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current: Option<Vec<&str>> = None;
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            match current.take() {
+                Some(lines) => blocks.push(lines.join("\n")),
+                None => current = Some(Vec::new()),
+            }
+        } else if let Some(lines) = current.as_mut() {
+            lines.push(line);
+        }
+    }
+    blocks.retain(|block| !block.trim().is_empty());
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"{
+        "root": 0,
+        "crate_version": "1.2.3",
+        "format_version": 39,
+        "index": {
+            "0": {"id": 0, "crate_id": 0, "name": "demo", "docs": "Demo crate.",
+                  "inner": {"module": {"items": [1, 2]}}},
+            "1": {"id": 1, "crate_id": 0, "name": "Widget",
+                  "docs": "A widget.\n\n```\nlet w = Widget::new();\n```",
+                  "inner": {"struct": {"impls": [3]}}},
+            "2": {"id": 2, "crate_id": 0, "name": "helper", "docs": null,
+                  "inner": {"function": {}}},
+            "3": {"id": 3, "crate_id": 0, "name": null, "docs": "Ignored impl docs.",
+                  "inner": {"impl": {"for": {"resolved_path": {"path": "Widget", "id": 1}},
+                                     "items": [4]}}},
+            "4": {"id": 4, "crate_id": 0, "name": "new", "docs": "Creates a widget.",
+                  "inner": {"function": {}}},
+            "9": {"id": 9, "crate_id": 1, "name": "Vec", "docs": "External.",
+                  "inner": {"struct": {}}}
+        },
+        "paths": {
+            "0": {"crate_id": 0, "path": ["demo"], "kind": "module"},
+            "1": {"crate_id": 0, "path": ["demo", "Widget"], "kind": "struct"},
+            "9": {"crate_id": 1, "path": ["alloc", "vec", "Vec"], "kind": "struct"}
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_rustdoc_json() {
+        let index = parse_rustdoc_json(SAMPLE).unwrap();
+        assert_eq!(index.name, "demo@1.2.3");
+        assert_eq!(index.version.as_deref(), Some("1.2.3"));
+        assert_eq!(index.origin, DocsOrigin::Rustdoc);
+        assert_eq!(index.base_url, "https://docs.rs/demo/1.2.3");
+
+        let titles: Vec<&str> = index.pages.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, ["demo", "demo::Widget", "demo::Widget::new"]);
+
+        let widget = &index.pages[1];
+        assert_eq!(widget.headings, ["struct"]);
+        assert_eq!(widget.code_blocks, ["let w = Widget::new();"]);
+        assert_eq!(
+            widget.url,
+            "https://docs.rs/demo/1.2.3/demo/?search=demo%3A%3AWidget"
+        );
+        assert_eq!(index.pages[2].content, "Creates a widget.");
+    }
+
+    #[test]
+    fn test_parse_rustdoc_json_string_ids() {
+        let json = r#"{
+            "root": "0:0",
+            "index": {
+                "0:0": {"crate_id": 0, "name": "old", "docs": "Old format.",
+                        "kind": "module", "inner": {"items": []}}
+            },
+            "paths": {"0:0": {"crate_id": 0, "path": ["old"], "kind": "module"}}
+        }"#;
+        let index = parse_rustdoc_json(json).unwrap();
+        assert_eq!(index.name, "old");
+        assert!(index.version.is_none());
+        assert_eq!(index.pages.len(), 1);
+        assert_eq!(index.pages[0].headings, ["module"]);
+    }
+
+    #[test]
+    fn test_parse_rustdoc_json_rejects_other_json() {
+        assert!(parse_rustdoc_json(r#"{"items": []}"#).is_err());
+        assert!(parse_rustdoc_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_fenced_code_blocks() {
+        let md = "Intro\n```rust\nfn a() {}\n```\ntext\n```\n\n```\n```\nb()\n```";
+        assert_eq!(fenced_code_blocks(md), ["fn a() {}", "b()"]);
+    }
+}
@@ -18,6 +18,7 @@ use std::fmt;
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use hive_core::search::{SearchDocument, SearchSource};
 use serde::{Deserialize, Serialize};
 use tracing::debug;
 
@@ -57,6 +58,20 @@ pub struct KBPage {
     pub tags: Vec<String>,
 }
 
+impl From<&KBPage> for SearchDocument {
+    fn from(page: &KBPage) -> Self {
+        let mut doc = SearchDocument::new(SearchSource::Note, &page.id, &page.title, &page.content)
+            .with_tags(page.tags.iter().cloned());
+        if let Some(url) = &page.url {
+            doc = doc.with_location(url);
+        }
+        if let Some(updated_at) = page.updated_at.or(page.created_at) {
+            doc = doc.updated(updated_at);
+        }
+        doc
+    }
+}
+
 /// A lightweight page summary without content.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
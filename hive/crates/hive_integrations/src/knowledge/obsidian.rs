@@ -1,8 +1,10 @@
 //! Obsidian vault knowledge base provider.
 //!
 //! Reads a local Obsidian vault directory, parsing Markdown files with
-//! YAML front matter, `[[wiki links]]`, and `#tags`. Provides full-text
-//! search using a TF-IDF scoring algorithm over an in-memory index.
+//! YAML front matter, `[[wiki links]]`, and `#tags`. PDF, DOCX, XLSX and
+//! PPTX attachments in the vault are indexed too, through their extracted
+//! text. Provides full-text search using a TF-IDF scoring algorithm over an
+//! in-memory index.
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -10,6 +12,8 @@ use std::path::{Path, PathBuf};
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use hive_core::search::{SearchDocument, SearchSource};
+use hive_docs::extract::DocumentFormat;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use tracing::debug;
@@ -27,9 +31,11 @@ use super::{
 pub struct ObsidianPage {
     /// Relative path from vault root (e.g. "folder/note.md").
     pub path: String,
-    /// Title derived from file name or front matter.
+    /// Title derived from file name or front matter; attachments use their
+    /// file name.
     pub title: String,
-    /// Raw Markdown content (excluding front matter).
+    /// Raw Markdown content (excluding front matter), or the extracted
+    /// text of an attachment.
     pub content: String,
     /// Parsed YAML front matter key-value pairs.
     #[serde(default)]
@@ -62,6 +68,18 @@ impl ObsidianIndex {
             last_indexed: Utc::now(),
         }
     }
+    /// Pages as documents for the unified search index.
+    pub fn search_documents(&self, vault: &Path) -> Vec<SearchDocument> {
+        self.pages
+            .values()
+            .map(|page| {
+                SearchDocument::new(SearchSource::Note, &page.path, &page.title, &page.content)
+                    .with_tags(page.tags.iter().cloned())
+                    .with_location(vault.join(&page.path).display().to_string())
+                    .updated(self.last_indexed)
+            })
+            .collect()
+    }
 }
 
 // -- Provider ---------------------------------------------------------------
@@ -70,7 +88,7 @@ impl ObsidianIndex {
 ///
 /// Reads `.md` files from the configured vault path, parses YAML front
 /// matter, extracts wiki links and tags, and provides TF-IDF search
-/// across the vault contents.
+/// across the vault contents, including document attachments.
 pub struct ObsidianProvider {
     vault_path: PathBuf,
     index: ObsidianIndex,
@@ -99,7 +117,7 @@ impl ObsidianProvider {
     }
 
     /// Build (or rebuild) the in-memory index by scanning all `.md` files
-    /// in the vault directory recursively.
+    /// and document attachments in the vault directory recursively.
     pub async fn index_vault(&mut self) -> Result<usize> {
         debug!(vault = %self.vault_path.display(), "indexing Obsidian vault");
 
@@ -136,7 +154,8 @@ impl ObsidianProvider {
         Ok(count)
     }
 
-    /// Recursively scan a directory for `.md` files and parse them.
+    /// Recursively scan a directory for `.md` files and attachments and
+    /// parse them.
     fn scan_directory<'a>(
         base: &'a Path,
         dir: &'a Path,
@@ -159,7 +178,9 @@ impl ObsidianProvider {
 
                 if path.is_dir() {
                     Self::scan_directory(base, &path, pages).await?;
-                } else if path.extension().and_then(|e| e.to_str()) == Some("md") {
+                } else if path.extension().and_then(|e| e.to_str()) == Some("md")
+                    || is_attachment(&path)
+                {
                     match Self::parse_file(base, &path).await {
                         Ok(page) => {
                             pages.insert(page.path.clone(), page);
@@ -179,18 +200,30 @@ impl ObsidianProvider {
         })
     }
 
-    /// Parse a single `.md` file into an [`ObsidianPage`].
+    /// Parse a single `.md` file or attachment into an [`ObsidianPage`].
     async fn parse_file(base: &Path, path: &Path) -> Result<ObsidianPage> {
-        let raw = tokio::fs::read_to_string(path)
-            .await
-            .with_context(|| format!("failed to read file: {}", path.display()))?;
-
         let relative = path
             .strip_prefix(base)
             .unwrap_or(path)
             .to_string_lossy()
             .to_string();
 
+        if is_attachment(path) {
+            return Ok(ObsidianPage {
+                path: relative,
+                title: attachment_title(path),
+                content: read_attachment(path).await?,
+                front_matter: HashMap::new(),
+                tags: vec![],
+                backlinks: vec![],
+                outlinks: vec![],
+            });
+        }
+
+        let raw = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("failed to read file: {}", path.display()))?;
+
         let title = path
             .file_stem()
             .and_then(|s| s.to_str())
@@ -223,7 +256,12 @@ impl ObsidianProvider {
     /// Obsidian wiki links can be bare filenames ("Note") or contain
     /// relative paths ("folder/Note"). We match against file stems.
     fn resolve_link(link: &str, pages: &HashMap<String, ObsidianPage>) -> Option<String> {
-        // First try exact path match with .md extension.
+        // Attachments are linked with their extension ("report.pdf").
+        if pages.contains_key(link) {
+            return Some(link.to_string());
+        }
+
+        // Then try exact path match with .md extension.
         let with_ext = if link.ends_with(".md") {
             link.to_string()
         } else {
@@ -237,11 +275,18 @@ impl ObsidianProvider {
         // Try matching by file stem (last component).
         let link_stem = link.rsplit('/').next().unwrap_or(link);
         for (path, page) in pages {
-            let page_stem = Path::new(&page.path)
+            let page_path = Path::new(&page.path);
+            let page_stem = page_path
                 .file_stem()
                 .and_then(|s| s.to_str())
                 .unwrap_or("");
-            if page_stem.eq_ignore_ascii_case(link_stem) {
+            let page_name = page_path
+                .file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or("");
+            if page_stem.eq_ignore_ascii_case(link_stem)
+                || page_name.eq_ignore_ascii_case(link_stem)
+            {
                 return Some(path.clone());
             }
         }
@@ -363,23 +408,31 @@ impl KnowledgeBaseProvider for ObsidianProvider {
         // page_id is the relative path within the vault.
         let full_path = self.vault_path.join(page_id);
 
-        let raw = tokio::fs::read_to_string(&full_path)
-            .await
-            .with_context(|| format!("failed to read Obsidian page: {}", full_path.display()))?;
-
-        let (front_matter, content) = parse_front_matter(&raw);
-        let tags = extract_tags(&content, &front_matter);
+        let (title, content, tags) = if is_attachment(&full_path) {
+            let content = read_attachment(&full_path).await?;
+            (attachment_title(&full_path), content, vec![])
+        } else {
+            let raw = tokio::fs::read_to_string(&full_path)
+                .await
+                .with_context(|| {
+                    format!("failed to read Obsidian page: {}", full_path.display())
+                })?;
 
-        let title = front_matter
-            .get("title")
-            .cloned()
-            .unwrap_or_else(|| {
-                full_path
-                    .file_stem()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("Untitled")
-                    .to_string()
-            });
+            let (front_matter, content) = parse_front_matter(&raw);
+            let tags = extract_tags(&content, &front_matter);
+
+            let title = front_matter
+                .get("title")
+                .cloned()
+                .unwrap_or_else(|| {
+                    full_path
+                        .file_stem()
+                        .and_then(|s| s.to_str())
+                        .unwrap_or("Untitled")
+                        .to_string()
+                });
+            (title, content, tags)
+        };
 
         // Extract parent directory as parent_id.
         let parent_id = Path::new(page_id)
@@ -453,17 +506,22 @@ impl KnowledgeBaseProvider for ObsidianProvider {
                     parent_id: parent_id.map(String::from),
                     has_children: true,
                 });
-            } else if path.extension().and_then(|e| e.to_str()) == Some("md") {
+            } else if path.extension().and_then(|e| e.to_str()) == Some("md")
+                || is_attachment(&path)
+            {
                 let relative = path
                     .strip_prefix(&self.vault_path)
                     .unwrap_or(&path)
                     .to_string_lossy()
                     .to_string();
-                let title = path
-                    .file_stem()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("Untitled")
-                    .to_string();
+                let title = if is_attachment(&path) {
+                    attachment_title(&path)
+                } else {
+                    path.file_stem()
+                        .and_then(|s| s.to_str())
+                        .unwrap_or("Untitled")
+                        .to_string()
+                };
 
                 summaries.push(KBPageSummary {
                     id: relative,
@@ -601,8 +659,8 @@ impl KnowledgeBaseProvider for ObsidianProvider {
                 Ok(page) => {
                     context.push_str(&format!("## {} ({})\n\n", page.title, result.page_id));
                     let max_chars = 2000;
-                    if page.content.len() > max_chars {
-                        context.push_str(&page.content[..max_chars]);
+                    if let Some((cut, _)) = page.content.char_indices().nth(max_chars) {
+                        context.push_str(&page.content[..cut]);
                         context.push_str("\n...(truncated)\n\n");
                     } else {
                         context.push_str(&page.content);
@@ -625,6 +683,30 @@ impl KnowledgeBaseProvider for ObsidianProvider {
 
 // -- Parsing helpers --------------------------------------------------------
 
+/// Whether `path` is a document attachment (PDF, DOCX, XLSX, PPTX) whose
+/// text can be extracted.
+fn is_attachment(path: &Path) -> bool {
+    DocumentFormat::from_path(path).is_some_and(DocumentFormat::is_binary)
+}
+
+/// Attachments are titled by file name, extension included, the way
+/// Obsidian links to them.
+fn attachment_title(path: &Path) -> String {
+    path.file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Untitled")
+        .to_string()
+}
+
+/// Extract an attachment's text as Markdown, off the async runtime.
+async fn read_attachment(path: &Path) -> Result<String> {
+    let owned = path.to_path_buf();
+    let document = tokio::task::spawn_blocking(move || hive_docs::extract::extract_file(&owned))
+        .await
+        .context("attachment extraction task failed")??;
+    Ok(document.to_markdown())
+}
+
 /// Parse YAML front matter delimited by `---` and return (front_matter, content).
 fn parse_front_matter(raw: &str) -> (HashMap<String, String>, String) {
     let trimmed = raw.trim_start();
@@ -1362,6 +1444,46 @@ mod tests {
         cleanup_tempdir(&tmp);
     }
 
+    #[tokio::test]
+    async fn test_index_vault_with_attachments() {
+        let tmp = tempdir();
+        tokio::fs::create_dir(tmp.join("files")).await.unwrap();
+        tokio::fs::write(tmp.join("plan.md"), "See [[roadmap.pptx]] for dates.")
+            .await
+            .unwrap();
+        let deck = hive_docs::pptx::generate_pptx(&[hive_docs::pptx::PptxSlide {
+            title: "Roadmap".to_string(),
+            content: "Beta in March\nLaunch in June".to_string(),
+        }])
+        .unwrap();
+        tokio::fs::write(tmp.join("files/roadmap.pptx"), deck)
+            .await
+            .unwrap();
+        tokio::fs::write(tmp.join("files/broken.pdf"), "not a pdf")
+            .await
+            .unwrap();
+
+        let mut provider = ObsidianProvider::new(&tmp);
+        let count = provider.index_vault().await.unwrap();
+        assert_eq!(count, 2);
+
+        let deck = provider.index.pages.get("files/roadmap.pptx").unwrap();
+        assert_eq!(deck.title, "roadmap.pptx");
+        assert!(deck.content.contains("[slide 1]"));
+        assert!(deck.backlinks.contains(&"plan.md".to_string()));
+
+        let results = provider.search("launch june", 10).await.unwrap();
+        assert_eq!(results[0].page_id, "files/roadmap.pptx");
+
+        let page = provider.get_page("files/roadmap.pptx").await.unwrap();
+        assert!(page.content.contains("- Launch in June"));
+
+        let listed = provider.list_pages(Some("files")).await.unwrap();
+        assert!(listed.iter().any(|p| p.title == "roadmap.pptx"));
+
+        cleanup_tempdir(&tmp);
+    }
+
     // -- Test helpers -------------------------------------------------------
 
     /// Create a temporary directory for tests.
@@ -67,5 +67,8 @@ pub use kubernetes::{
     Namespace as K8sNamespace, Pod,
 };
 pub use smart_home::PhilipsHueClient;
-pub use webhooks::{Webhook, WebhookRegistry};
+pub use webhooks::{
+    ReceiverConfig, SourceConfig, Webhook, WebhookEvent, WebhookEventBus, WebhookReceiver,
+    WebhookRegistry, WebhookSource,
+};
 pub use clawdtalk::{ClawdTalkClient, ClawdTalkConfig, BridgeState, InboundMessage, OutboundMessage};
@@ -1,22 +1,36 @@
 //! Discord messaging provider.
 //!
 //! Wraps the Discord REST API v10 at `https://discord.com/api/v10` using
-//! `reqwest` for HTTP and bot-token authentication.
+//! `reqwest` for HTTP and bot-token authentication. Real-time events
+//! arrive over the Gateway WebSocket.
+
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use futures::{SinkExt, StreamExt};
 use reqwest::Client;
 use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue};
+use reqwest::multipart::{Form, Part};
 use serde::{Deserialize, Serialize};
-use tracing::debug;
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, warn};
 
 use super::provider::{
-    Attachment, Channel, IncomingMessage, MessagingProvider, Platform, SentMessage,
+    Attachment, Channel, FileUpload, IncomingMessage, MessageEvent, MessageStream,
+    MessagingProvider, Platform, RECONNECT_DELAY, SentMessage,
 };
+use super::rich_text::RichText;
 
 const DEFAULT_BASE_URL: &str = "https://discord.com/api/v10";
 
+/// Gateway intents: `GUILD_MESSAGES | DIRECT_MESSAGES | MESSAGE_CONTENT`.
+const GATEWAY_INTENTS: u64 = (1 << 9) | (1 << 12) | (1 << 15);
+
 // ── Discord API response types ─────────────────────────────────────
 
 /// A Discord guild (server).
@@ -51,9 +65,32 @@ struct DiscordMessage {
 
 #[derive(Debug, Clone, Deserialize)]
 struct DiscordAuthor {
-    #[serde(rename = "id")]
-    _id: String,
+    id: String,
     username: String,
+    #[serde(default)]
+    bot: bool,
+}
+
+/// A `MESSAGE_CREATE` dispatch from the Gateway.
+#[derive(Debug, Clone, Deserialize)]
+struct GatewayMessage {
+    #[serde(flatten)]
+    message: DiscordMessage,
+    guild_id: Option<String>,
+    #[serde(default)]
+    mentions: Vec<DiscordAuthor>,
+    referenced_message: Option<ReferencedMessage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ReferencedMessage {
+    author: DiscordAuthor,
+}
+
+/// Response from `GET /gateway/bot`.
+#[derive(Debug, Clone, Deserialize)]
+struct GatewayInfo {
+    url: String,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -75,6 +112,7 @@ struct DiscordSentMessage {
 // ── Client ─────────────────────────────────────────────────────────
 
 /// Discord messaging provider using the Discord REST API v10.
+#[derive(Clone)]
 pub struct DiscordProvider {
     base_url: String,
     token: String,
@@ -178,26 +216,40 @@ impl DiscordProvider {
             platform: Platform::Discord,
         }
     }
-}
 
-#[async_trait]
-impl MessagingProvider for DiscordProvider {
-    fn platform(&self) -> Platform {
-        Platform::Discord
+    /// Create a message in a channel from a full request payload.
+    async fn create_message(&self, channel: &str, payload: &Value) -> Result<SentMessage> {
+        self.create_message_with_file(channel, payload, None).await
     }
 
-    async fn send_message(&self, channel: &str, text: &str) -> Result<SentMessage> {
+    /// Create a message, attaching `file` as `files[0]` when given.
+    async fn create_message_with_file(
+        &self,
+        channel: &str,
+        payload: &Value,
+        file: Option<&FileUpload>,
+    ) -> Result<SentMessage> {
         let url = format!("{}/channels/{}/messages", self.base_url, channel);
-        let payload = serde_json::json!({
-            "content": text,
-        });
 
         debug!(url = %url, channel = %channel, "sending Discord message");
 
-        let resp = self
-            .client
-            .post(&url)
-            .json(&payload)
+        let request = self.client.post(&url);
+        let request = match file {
+            Some(file) => {
+                let mut payload = payload.clone();
+                payload["attachments"] = serde_json::json!([{ "id": 0, "filename": file.name }]);
+                let part = Part::bytes(file.data.clone())
+                    .file_name(file.name.clone())
+                    .mime_str(&file.mime_type)
+                    .context("invalid MIME type for Discord upload")?;
+                let form = Form::new()
+                    .text("payload_json", payload.to_string())
+                    .part("files[0]", part);
+                request.multipart(form)
+            }
+            None => request.json(payload),
+        };
+        let resp = request
             .send()
             .await
             .context("Discord send message request failed")?;
@@ -225,6 +277,201 @@ impl MessagingProvider for DiscordProvider {
         })
     }
 
+    // ── Gateway ────────────────────────────────────────────────────
+
+    /// The Gateway WebSocket URL for this bot.
+    async fn gateway_url(&self) -> Result<String> {
+        let url = format!("{}/gateway/bot", self.base_url);
+        let resp = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("Discord gateway request failed")?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Discord API error ({}): {}", status, body);
+        }
+
+        let info: GatewayInfo = resp
+            .json()
+            .await
+            .context("failed to parse Discord gateway response")?;
+        Ok(format!(
+            "{}/?v=10&encoding=json",
+            info.url.trim_end_matches('/')
+        ))
+    }
+
+    /// Keep a Gateway session open, identifying afresh after every
+    /// disconnect, until the stream is dropped.
+    async fn run_gateway(self, tx: mpsc::Sender<MessageEvent>) {
+        while !tx.is_closed() {
+            match self.gateway_session(&tx).await {
+                Ok(()) => debug!("Discord Gateway connection closed, reconnecting"),
+                Err(e) => {
+                    warn!("Discord Gateway connection failed: {e:#}");
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            }
+        }
+    }
+
+    /// One Gateway session: Hello, Identify, then heartbeat while
+    /// forwarding `MESSAGE_CREATE` dispatches. Returns `Ok` when Discord
+    /// asks for a reconnect.
+    async fn gateway_session(&self, tx: &mpsc::Sender<MessageEvent>) -> Result<()> {
+        let url = self.gateway_url().await?;
+        let (ws, _) = connect_async(url.as_str())
+            .await
+            .context("Discord Gateway connect failed")?;
+        let (mut sink, mut source) = ws.split();
+
+        let hello = loop {
+            match source.next().await {
+                Some(Ok(Message::Text(text))) => break serde_json::from_str::<Value>(&text)?,
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => return Err(e).context("Discord Gateway read failed"),
+                None => anyhow::bail!("Discord Gateway closed before Hello"),
+            }
+        };
+        let interval = hello["d"]["heartbeat_interval"]
+            .as_u64()
+            .filter(|_| hello["op"] == 10)
+            .context("Discord Gateway did not send Hello")?;
+
+        let identify = serde_json::json!({
+            "op": 2,
+            "d": {
+                "token": self.token,
+                "intents": GATEWAY_INTENTS,
+                "properties": { "os": std::env::consts::OS, "browser": "hive", "device": "hive" },
+            },
+        });
+        sink.send(Message::Text(identify.to_string().into()))
+            .await
+            .context("Discord Identify failed")?;
+
+        let mut heartbeat = tokio::time::interval(Duration::from_millis(interval));
+        heartbeat.tick().await;
+        let mut seq: Option<u64> = None;
+        let mut bot_user_id: Option<String> = None;
+
+        loop {
+            let frame = tokio::select! {
+                _ = heartbeat.tick() => {
+                    let beat = serde_json::json!({ "op": 1, "d": seq });
+                    sink.send(Message::Text(beat.to_string().into()))
+                        .await
+                        .context("Discord heartbeat failed")?;
+                    continue;
+                }
+                frame = source.next() => frame,
+            };
+            let text = match frame {
+                None => return Ok(()),
+                Some(frame) => match frame.context("Discord Gateway read failed")? {
+                    Message::Text(text) => text,
+                    Message::Close(Some(close)) if u16::from(close.code) >= 4000 => {
+                        anyhow::bail!(
+                            "Discord closed the Gateway ({}): {}",
+                            u16::from(close.code),
+                            close.reason
+                        )
+                    }
+                    Message::Close(_) => return Ok(()),
+                    _ => continue,
+                },
+            };
+            let payload: Value = match serde_json::from_str(&text) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    warn!("Ignoring malformed Discord Gateway frame: {e}");
+                    continue;
+                }
+            };
+            if let Some(s) = payload["s"].as_u64() {
+                seq = Some(s);
+            }
+            match payload["op"].as_u64() {
+                Some(0) => match payload["t"].as_str() {
+                    Some("READY") => {
+                        bot_user_id = payload["d"]["user"]["id"].as_str().map(String::from);
+                        debug!("Discord Gateway ready");
+                    }
+                    Some("MESSAGE_CREATE") => {
+                        if let Some(event) =
+                            self.gateway_event(&payload["d"], bot_user_id.as_deref())
+                            && tx.send(event).await.is_err()
+                        {
+                            return Ok(());
+                        }
+                    }
+                    _ => {}
+                },
+                Some(1) => {
+                    let beat = serde_json::json!({ "op": 1, "d": seq });
+                    sink.send(Message::Text(beat.to_string().into()))
+                        .await
+                        .context("Discord heartbeat failed")?;
+                }
+                Some(7) => return Ok(()),
+                Some(9) => anyhow::bail!("Discord invalidated the Gateway session"),
+                _ => {}
+            }
+        }
+    }
+
+    /// Convert a `MESSAGE_CREATE` payload into a [`MessageEvent`]. Messages
+    /// from bots, from this bot, and from other guilds are skipped.
+    fn gateway_event(&self, data: &Value, bot_user_id: Option<&str>) -> Option<MessageEvent> {
+        let msg: GatewayMessage = serde_json::from_value(data.clone()).ok()?;
+        let author = &msg.message.author;
+        if author.bot || Some(author.id.as_str()) == bot_user_id {
+            return None;
+        }
+        if msg.guild_id.as_ref().is_some_and(|g| *g != self.guild_id) {
+            return None;
+        }
+
+        let mut text = msg.message.content.clone();
+        let mut mentions_bot = false;
+        if let Some(bot) = bot_user_id {
+            mentions_bot = msg.mentions.iter().any(|m| m.id == bot)
+                || msg
+                    .referenced_message
+                    .as_ref()
+                    .is_some_and(|r| r.author.id == bot);
+            text = text
+                .replace(&format!("<@{bot}>"), "")
+                .replace(&format!("<@!{bot}>"), "");
+        }
+
+        Some(MessageEvent {
+            message: self.convert_message(&msg.message),
+            thread_id: Some(msg.message.id.clone()),
+            is_direct: msg.guild_id.is_none(),
+            mentions_bot,
+            text: text.trim().to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl MessagingProvider for DiscordProvider {
+    fn platform(&self) -> Platform {
+        Platform::Discord
+    }
+
+    async fn send_message(&self, channel: &str, text: &str) -> Result<SentMessage> {
+        let payload = serde_json::json!({
+            "content": text,
+        });
+        self.create_message(channel, &payload).await
+    }
+
     async fn list_channels(&self) -> Result<Vec<Channel>> {
         let url = format!("{}/guilds/{}/channels", self.base_url, self.guild_id);
 
@@ -362,6 +609,103 @@ impl MessagingProvider for DiscordProvider {
 
         Ok(results)
     }
+
+    async fn event_stream(&self) -> Result<MessageStream> {
+        let provider = self.clone();
+        Ok(MessageStream::spawn(move |tx| provider.run_gateway(tx)))
+    }
+
+    /// Replies reference `thread_id`, the message being answered.
+    async fn reply_in_thread(
+        &self,
+        channel: &str,
+        thread_id: &str,
+        text: &str,
+    ) -> Result<SentMessage> {
+        let payload = serde_json::json!({
+            "content": text,
+            "message_reference": { "message_id": thread_id },
+        });
+        self.create_message(channel, &payload).await
+    }
+
+    async fn send_rich(
+        &self,
+        channel: &str,
+        thread_id: Option<&str>,
+        text: &RichText,
+    ) -> Result<SentMessage> {
+        let mut payload = serde_json::json!({ "content": text.to_markdown() });
+        if let Some(thread_id) = thread_id {
+            payload["message_reference"] = serde_json::json!({ "message_id": thread_id });
+        }
+        self.create_message(channel, &payload).await
+    }
+
+    async fn upload_file(
+        &self,
+        channel: &str,
+        thread_id: Option<&str>,
+        file: &FileUpload,
+    ) -> Result<SentMessage> {
+        let mut payload = serde_json::json!({});
+        if let Some(caption) = &file.caption {
+            payload["content"] = caption.as_str().into();
+        }
+        if let Some(thread_id) = thread_id {
+            payload["message_reference"] = serde_json::json!({ "message_id": thread_id });
+        }
+        self.create_message_with_file(channel, &payload, Some(file))
+            .await
+    }
+
+    async fn edit_message(&self, channel: &str, message_id: &str, text: &RichText) -> Result<()> {
+        let url = format!(
+            "{}/channels/{}/messages/{}",
+            self.base_url, channel, message_id
+        );
+        let payload = serde_json::json!({ "content": text.to_markdown() });
+
+        debug!(url = %url, message_id = %message_id, "editing Discord message");
+
+        let resp = self
+            .client
+            .patch(&url)
+            .json(&payload)
+            .send()
+            .await
+            .context("Discord edit message request failed")?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Discord API error ({}): {}", status, body);
+        }
+        Ok(())
+    }
+
+    async fn delete_message(&self, channel: &str, message_id: &str) -> Result<()> {
+        let url = format!(
+            "{}/channels/{}/messages/{}",
+            self.base_url, channel, message_id
+        );
+
+        debug!(url = %url, message_id = %message_id, "deleting Discord message");
+
+        let resp = self
+            .client
+            .delete(&url)
+            .send()
+            .await
+            .context("Discord delete message request failed")?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Discord API error ({}): {}", status, body);
+        }
+        Ok(())
+    }
 }
 
 /// Minimal percent-encoding for URL path/query segments.
@@ -570,8 +914,9 @@ mod tests {
             id: "msg-99".into(),
             channel_id: "ch-5".into(),
             author: DiscordAuthor {
-                _id: "u1".into(),
+                id: "u1".into(),
                 username: "charlie".into(),
+                bot: false,
             },
             content: "Test message".into(),
             timestamp: "2025-06-15T10:30:00Z".into(),
@@ -633,4 +978,203 @@ mod tests {
         assert_eq!(filtered[0].name.as_deref(), Some("general"));
         assert_eq!(filtered[1].name.as_deref(), Some("announcements"));
     }
+
+    fn gateway_message(author: &str, guild: Option<&str>, content: &str) -> Value {
+        serde_json::json!({
+            "id": "msg-1",
+            "channel_id": "ch-1",
+            "guild_id": guild,
+            "author": {"id": author, "username": author},
+            "content": content,
+            "timestamp": "2025-06-15T10:30:00Z",
+            "mentions": if content.contains("<@BOT>") {
+                serde_json::json!([{"id": "BOT", "username": "hive"}])
+            } else {
+                serde_json::json!([])
+            },
+        })
+    }
+
+    #[test]
+    fn test_gateway_event_mentions_and_direct_messages() {
+        let provider = make_provider();
+        let mention = provider
+            .gateway_event(
+                &gateway_message("u1", Some("guild-456"), "<@BOT> build it"),
+                Some("BOT"),
+            )
+            .unwrap();
+        assert!(mention.mentions_bot);
+        assert!(!mention.is_direct);
+        assert_eq!(mention.text, "build it");
+        assert_eq!(mention.thread_id.as_deref(), Some("msg-1"));
+
+        let dm = provider
+            .gateway_event(&gateway_message("u1", None, "hi"), Some("BOT"))
+            .unwrap();
+        assert!(dm.is_direct);
+        assert!(!dm.mentions_bot);
+
+        let mut reply = gateway_message("u1", Some("guild-456"), "thanks");
+        reply["referenced_message"] =
+            serde_json::json!({"author": {"id": "BOT", "username": "hive"}});
+        assert!(
+            provider
+                .gateway_event(&reply, Some("BOT"))
+                .unwrap()
+                .mentions_bot
+        );
+    }
+
+    #[test]
+    fn test_gateway_event_skips_bots_self_and_other_guilds() {
+        let provider = make_provider();
+        let own = gateway_message("BOT", Some("guild-456"), "echo");
+        assert!(provider.gateway_event(&own, Some("BOT")).is_none());
+
+        let mut other_bot = gateway_message("b2", Some("guild-456"), "beep");
+        other_bot["author"]["bot"] = true.into();
+        assert!(provider.gateway_event(&other_bot, Some("BOT")).is_none());
+
+        let elsewhere = gateway_message("u1", Some("guild-999"), "hi");
+        assert!(provider.gateway_event(&elsewhere, Some("BOT")).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_gateway_stream_identifies_heartbeats_and_replies() {
+        use super::super::test_support::{http_stub, ws_stub};
+
+        let (ws_url, mut sockets) = ws_stub().await;
+        let (base_url, requests) = http_stub(move |req| {
+            let body = if req.path == "/gateway/bot" {
+                format!(r#"{{"url":"{ws_url}"}}"#)
+            } else {
+                r#"{"id":"sent-1","channel_id":"ch-1","timestamp":"2025-01-01T00:00:00Z"}"#
+                    .to_string()
+            };
+            (200, body)
+        })
+        .await;
+
+        let provider = DiscordProvider::with_base_url("bot-tok", "guild-456", &base_url).unwrap();
+        let mut stream = provider.event_stream().await.unwrap();
+        let mut socket = sockets.recv().await.unwrap();
+
+        let send = |payload: Value| Message::Text(payload.to_string().into());
+        socket
+            .send(send(
+                serde_json::json!({"op": 10, "d": {"heartbeat_interval": 20}}),
+            ))
+            .await
+            .unwrap();
+        let mut identified = false;
+        let mut heartbeats = 0;
+        while !identified || heartbeats == 0 {
+            let Some(Ok(Message::Text(text))) = socket.next().await else {
+                continue;
+            };
+            let frame: Value = serde_json::from_str(&text).unwrap();
+            match frame["op"].as_u64() {
+                Some(2) => {
+                    assert_eq!(frame["d"]["token"], "bot-tok");
+                    assert_eq!(frame["d"]["intents"], GATEWAY_INTENTS);
+                    identified = true;
+                }
+                Some(1) => heartbeats += 1,
+                _ => {}
+            }
+        }
+
+        socket
+            .send(send(serde_json::json!({
+                "op": 0, "s": 1, "t": "READY", "d": {"user": {"id": "BOT"}},
+            })))
+            .await
+            .unwrap();
+        for (author, content) in [("BOT", "my own echo"), ("u1", "<@BOT> status?")] {
+            socket
+                .send(send(serde_json::json!({
+                    "op": 0, "s": 2, "t": "MESSAGE_CREATE",
+                    "d": gateway_message(author, Some("guild-456"), content),
+                })))
+                .await
+                .unwrap();
+        }
+
+        let event = stream.next().await.unwrap();
+        assert_eq!(event.text, "status?");
+        assert!(event.mentions_bot);
+
+        provider
+            .reply_in_thread("ch-1", "msg-1", "all green")
+            .await
+            .unwrap();
+        let requests = requests.lock().unwrap();
+        let post = requests
+            .iter()
+            .find(|r| r.path == "/channels/ch-1/messages")
+            .unwrap();
+        assert_eq!(post.json()["message_reference"]["message_id"], "msg-1");
+        assert_eq!(post.json()["content"], "all green");
+    }
+
+    #[tokio::test]
+    async fn test_rich_text_upload_edit_and_delete() {
+        use super::super::rich_text::Span;
+        use super::super::test_support::http_stub;
+
+        let (base_url, requests) = http_stub(|_| {
+            (
+                200,
+                r#"{"id":"msg-9","channel_id":"ch-1","timestamp":"2024-01-01T00:00:00Z"}"#
+                    .to_string(),
+            )
+        })
+        .await;
+        let provider = DiscordProvider::with_base_url("bot-token", "guild-456", &base_url).unwrap();
+
+        let text = RichText::new().paragraph(vec![Span::bold("Done"), Span::text(" in 3s")]);
+        provider
+            .send_rich("ch-1", Some("msg-1"), &text)
+            .await
+            .unwrap();
+        let file = FileUpload::new("report.pdf", "application/pdf", b"%PDF-1.7".to_vec())
+            .with_caption("Weekly report");
+        let sent = provider.upload_file("ch-1", None, &file).await.unwrap();
+        assert_eq!(sent.id, "msg-9");
+        provider
+            .edit_message("ch-1", "msg-9", &RichText::plain("Updated"))
+            .await
+            .unwrap();
+        provider.delete_message("ch-1", "msg-9").await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].json()["content"], "**Done** in 3s");
+        assert_eq!(
+            requests[0].json()["message_reference"]["message_id"],
+            "msg-1"
+        );
+
+        let upload = &requests[1];
+        assert!(
+            upload
+                .header("content-type")
+                .unwrap()
+                .starts_with("multipart/form-data")
+        );
+        assert!(
+            upload
+                .body
+                .contains("name=\"files[0]\"; filename=\"report.pdf\"")
+        );
+        assert!(upload.body.contains("%PDF-1.7"));
+        assert!(upload.body.contains(r#""content":"Weekly report""#));
+        assert!(upload.body.contains(r#""filename":"report.pdf""#));
+
+        assert_eq!(requests[2].method, "PATCH");
+        assert_eq!(requests[2].path, "/channels/ch-1/messages/msg-9");
+        assert_eq!(requests[2].json()["content"], "Updated");
+        assert_eq!(requests[3].method, "DELETE");
+        assert_eq!(requests[3].path, "/channels/ch-1/messages/msg-9");
+    }
 }
@@ -9,9 +9,13 @@ use chrono::{DateTime, Utc};
 use reqwest::Client;
 use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue};
 use serde::Deserialize;
+use serde_json::Value;
 use tracing::debug;
 
-use super::provider::{Channel, IncomingMessage, MessagingProvider, Platform, SentMessage};
+use super::provider::{
+    Channel, FileUpload, IncomingMessage, MessagingProvider, Platform, SentMessage,
+};
+use super::rich_text::RichText;
 
 const DEFAULT_BASE_URL: &str = "https://chat.googleapis.com/v1";
 
@@ -58,6 +62,13 @@ struct ListMessagesResponse {
     messages: Option<Vec<GoogleChatMessage>>,
 }
 
+/// Response from the media upload endpoint.
+#[derive(Debug, Deserialize)]
+struct UploadAttachmentResponse {
+    #[serde(rename = "attachmentDataRef")]
+    attachment_data_ref: Value,
+}
+
 /// Error from Google Chat API.
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
@@ -150,20 +161,41 @@ impl GoogleChatProvider {
             format!("spaces/{space}")
         }
     }
-}
 
-#[async_trait]
-impl MessagingProvider for GoogleChatProvider {
-    fn platform(&self) -> Platform {
-        Platform::GoogleChat
+    /// Build a thread resource name within `space` unless `thread` is
+    /// already fully qualified.
+    fn thread_name(space: &str, thread: &str) -> String {
+        if thread.starts_with("spaces/") {
+            thread.to_string()
+        } else {
+            format!("{}/threads/{thread}", Self::space_name(space))
+        }
     }
 
-    async fn send_message(&self, channel: &str, text: &str) -> Result<SentMessage> {
+    /// Build a message resource name within `space`.
+    fn message_name(space: &str, message_id: &str) -> String {
+        if message_id.starts_with("spaces/") {
+            message_id.to_string()
+        } else {
+            format!("{}/messages/{message_id}", Self::space_name(space))
+        }
+    }
+
+    /// Create a message in `channel`, joining `thread_id` when given (or
+    /// starting a new thread if it no longer exists).
+    async fn create_message(
+        &self,
+        channel: &str,
+        thread_id: Option<&str>,
+        mut payload: Value,
+    ) -> Result<SentMessage> {
         let space = Self::space_name(channel);
-        let url = format!("{}/{}/messages", self.base_url, space);
-        let payload = serde_json::json!({
-            "text": text,
-        });
+        let mut url = format!("{}/{}/messages", self.base_url, space);
+        if let Some(thread_id) = thread_id {
+            payload["thread"] =
+                serde_json::json!({ "name": Self::thread_name(channel, thread_id) });
+            url.push_str("?messageReplyOption=REPLY_MESSAGE_FALLBACK_TO_NEW_THREAD");
+        }
 
         debug!(url = %url, channel = %channel, "sending Google Chat message");
 
@@ -205,6 +237,86 @@ impl MessagingProvider for GoogleChatProvider {
         })
     }
 
+    /// Upload a file to `space` as a `multipart/related` media upload,
+    /// returning the attachment data reference to put on a message.
+    async fn upload_attachment(&self, channel: &str, file: &FileUpload) -> Result<Value> {
+        let upload_base = self.base_url.replacen("/v1", "/upload/v1", 1);
+        let url = format!(
+            "{}/{}/attachments:upload?uploadType=multipart",
+            upload_base,
+            Self::space_name(channel)
+        );
+
+        let boundary = format!("hive-{}", uuid::Uuid::new_v4().simple());
+        let metadata = serde_json::json!({ "filename": file.name });
+        let mut body = Vec::with_capacity(file.data.len() + 256);
+        body.extend_from_slice(
+            format!(
+                "--{boundary}\r\nContent-Type: application/json; charset=UTF-8\r\n\r\n{metadata}\r\n\
+                 --{boundary}\r\nContent-Type: {}\r\n\r\n",
+                file.mime_type
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(&file.data);
+        body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+
+        debug!(url = %url, file = %file.name, "uploading Google Chat attachment");
+
+        let resp = self
+            .client
+            .post(&url)
+            .header(
+                CONTENT_TYPE,
+                format!("multipart/related; boundary={boundary}"),
+            )
+            .body(body)
+            .send()
+            .await
+            .context("Google Chat attachment upload request failed")?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Google Chat API HTTP error ({}): {}", status, body);
+        }
+
+        let uploaded: UploadAttachmentResponse = resp
+            .json()
+            .await
+            .context("failed to parse Google Chat upload response")?;
+        Ok(uploaded.attachment_data_ref)
+    }
+
+    /// Send a request that returns no useful body.
+    async fn send_empty(&self, req: reqwest::RequestBuilder, what: &str) -> Result<()> {
+        let resp = req
+            .send()
+            .await
+            .with_context(|| format!("Google Chat {what} request failed"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Google Chat API HTTP error ({}): {}", status, body);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MessagingProvider for GoogleChatProvider {
+    fn platform(&self) -> Platform {
+        Platform::GoogleChat
+    }
+
+    async fn send_message(&self, channel: &str, text: &str) -> Result<SentMessage> {
+        let payload = serde_json::json!({
+            "text": text,
+        });
+        self.create_message(channel, None, payload).await
+    }
+
     async fn list_channels(&self) -> Result<Vec<Channel>> {
         let url = format!("{}/spaces", self.base_url);
 
@@ -370,6 +482,73 @@ impl MessagingProvider for GoogleChatProvider {
 
         Ok(results)
     }
+
+    async fn reply_in_thread(
+        &self,
+        channel: &str,
+        thread_id: &str,
+        text: &str,
+    ) -> Result<SentMessage> {
+        let payload = serde_json::json!({
+            "text": text,
+        });
+        self.create_message(channel, Some(thread_id), payload).await
+    }
+
+    async fn send_rich(
+        &self,
+        channel: &str,
+        thread_id: Option<&str>,
+        text: &RichText,
+    ) -> Result<SentMessage> {
+        let payload = serde_json::json!({
+            "text": text.to_mrkdwn(),
+        });
+        self.create_message(channel, thread_id, payload).await
+    }
+
+    async fn upload_file(
+        &self,
+        channel: &str,
+        thread_id: Option<&str>,
+        file: &FileUpload,
+    ) -> Result<SentMessage> {
+        let data_ref = self.upload_attachment(channel, file).await?;
+        let payload = serde_json::json!({
+            "text": file.caption.as_deref().unwrap_or_default(),
+            "attachment": [{ "attachmentDataRef": data_ref }],
+        });
+        self.create_message(channel, thread_id, payload).await
+    }
+
+    async fn edit_message(&self, channel: &str, message_id: &str, text: &RichText) -> Result<()> {
+        let url = format!(
+            "{}/{}?updateMask=text",
+            self.base_url,
+            Self::message_name(channel, message_id)
+        );
+        let payload = serde_json::json!({
+            "text": text.to_mrkdwn(),
+        });
+
+        debug!(url = %url, message_id = %message_id, "editing Google Chat message");
+
+        self.send_empty(self.client.patch(&url).json(&payload), "edit message")
+            .await
+    }
+
+    async fn delete_message(&self, channel: &str, message_id: &str) -> Result<()> {
+        let url = format!(
+            "{}/{}",
+            self.base_url,
+            Self::message_name(channel, message_id)
+        );
+
+        debug!(url = %url, message_id = %message_id, "deleting Google Chat message");
+
+        self.send_empty(self.client.delete(&url), "delete message")
+            .await
+    }
 }
 
 #[cfg(test)]
@@ -524,4 +703,79 @@ mod tests {
         assert_eq!(detail.message, "Not found");
         assert_eq!(detail.code, Some(404));
     }
+
+    #[tokio::test]
+    async fn test_threads_rich_text_upload_edit_and_delete() {
+        use super::super::test_support::http_stub;
+        use crate::messaging::rich_text::Span;
+
+        let (stub, requests) = http_stub(|req| {
+            let body = if req.path.starts_with("/upload/") {
+                serde_json::json!({"attachmentDataRef": {"attachmentUploadToken": "tok-1"}})
+            } else {
+                serde_json::json!({"name": "spaces/AAA/messages/M1"})
+            };
+            (200, body.to_string())
+        })
+        .await;
+        let provider = GoogleChatProvider::with_base_url("oauth", &format!("{stub}/v1")).unwrap();
+
+        provider
+            .reply_in_thread("AAA", "T1", "on it")
+            .await
+            .unwrap();
+
+        let rich = RichText::new().paragraph(vec![Span::text("build "), Span::bold("green")]);
+        provider.send_rich("AAA", None, &rich).await.unwrap();
+
+        let file = FileUpload::new("notes.txt", "text/plain", b"hello".to_vec());
+        let sent = provider.upload_file("AAA", None, &file).await.unwrap();
+        assert_eq!(sent.id, "M1");
+
+        provider.edit_message("AAA", "M1", &rich).await.unwrap();
+        provider.delete_message("AAA", "M1").await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 6);
+
+        assert_eq!(
+            requests[0].path,
+            "/v1/spaces/AAA/messages?messageReplyOption=REPLY_MESSAGE_FALLBACK_TO_NEW_THREAD"
+        );
+        assert_eq!(
+            requests[0].json()["thread"]["name"],
+            "spaces/AAA/threads/T1"
+        );
+        assert_eq!(requests[1].json()["text"], "build *green*");
+
+        let upload = &requests[2];
+        assert_eq!(
+            upload.path,
+            "/upload/v1/spaces/AAA/attachments:upload?uploadType=multipart"
+        );
+        assert!(
+            upload
+                .header("content-type")
+                .unwrap()
+                .starts_with("multipart/related; boundary=")
+        );
+        assert!(upload.body.contains(r#"{"filename":"notes.txt"}"#));
+        assert!(
+            upload
+                .body
+                .contains("Content-Type: text/plain\r\n\r\nhello\r\n")
+        );
+        assert_eq!(
+            requests[3].json()["attachment"][0]["attachmentDataRef"]["attachmentUploadToken"],
+            "tok-1"
+        );
+
+        assert_eq!(requests[4].method, "PATCH");
+        assert_eq!(
+            requests[4].path,
+            "/v1/spaces/AAA/messages/M1?updateMask=text"
+        );
+        assert_eq!(requests[5].method, "DELETE");
+        assert_eq!(requests[5].path, "/v1/spaces/AAA/messages/M1");
+    }
 }
@@ -9,7 +9,10 @@ use std::collections::HashMap;
 use anyhow::{Context, Result};
 use tracing::debug;
 
-use super::provider::{Channel, IncomingMessage, MessagingProvider, Platform, SentMessage};
+use super::provider::{
+    Channel, FileUpload, IncomingMessage, MessagingProvider, Platform, SentMessage,
+};
+use super::rich_text::RichText;
 
 /// Central hub that manages and dispatches to messaging providers.
 pub struct MessagingHub {
@@ -62,6 +65,41 @@ impl MessagingHub {
         provider.send_message(channel, text).await
     }
 
+    /// Send rich text, rendered in the platform's own markup, optionally
+    /// as a reply in a thread.
+    pub async fn send_rich(
+        &self,
+        platform: Platform,
+        channel: &str,
+        thread_id: Option<&str>,
+        text: &RichText,
+    ) -> Result<SentMessage> {
+        let provider = self
+            .providers
+            .get(&platform)
+            .context(format!("no provider registered for {platform}"))?;
+
+        debug!(platform = %platform, channel = %channel, "sending rich message via hub");
+        provider.send_rich(channel, thread_id, text).await
+    }
+
+    /// Upload a file to a channel on the given platform.
+    pub async fn upload_file(
+        &self,
+        platform: Platform,
+        channel: &str,
+        thread_id: Option<&str>,
+        file: &FileUpload,
+    ) -> Result<SentMessage> {
+        let provider = self
+            .providers
+            .get(&platform)
+            .context(format!("no provider registered for {platform}"))?;
+
+        debug!(platform = %platform, channel = %channel, file = %file.name, "uploading file via hub");
+        provider.upload_file(channel, thread_id, file).await
+    }
+
     /// List channels visible on the given platform.
     pub async fn list_channels(&self, platform: Platform) -> Result<Vec<Channel>> {
         let provider = self
@@ -2,7 +2,10 @@
 //!
 //! Wraps the Matrix Client-Server API v3 at
 //! `https://matrix.example.com/_matrix/client/v3` using `reqwest` for HTTP
-//! and access-token authentication.
+//! and access-token authentication. Real-time events come from
+//! long-polling `/sync`.
+
+use std::collections::{HashMap, HashSet};
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
@@ -10,14 +13,21 @@ use chrono::{DateTime, TimeZone, Utc};
 use reqwest::Client;
 use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue};
 use serde::Deserialize;
-use tracing::debug;
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
 
 use super::provider::{
-    Attachment, Channel, IncomingMessage, MessagingProvider, Platform, SentMessage,
+    Attachment, Channel, FileUpload, IncomingMessage, MessageEvent, MessageStream,
+    MessagingProvider, Platform, RECONNECT_DELAY, SentMessage,
 };
+use super::rich_text::RichText;
 
 const DEFAULT_BASE_URL: &str = "https://matrix.org/_matrix/client/v3";
 
+/// How long each `/sync` call waits for new events, in milliseconds.
+const SYNC_TIMEOUT_MS: u64 = 30_000;
+
 // ── Matrix API response types ────────────────────────────────────
 
 /// Response from sending an event.
@@ -66,7 +76,7 @@ struct MatrixEventContent {
     msgtype: Option<String>,
     url: Option<String>,
     #[serde(rename = "m.relates_to")]
-    _relates_to: Option<serde_json::Value>,
+    relates_to: Option<serde_json::Value>,
     info: Option<MatrixFileInfo>,
 }
 
@@ -87,7 +97,66 @@ struct MatrixError {
 
 // ── Client ─────────────────────────────────────────────────────────
 
+/// Response from the media repository's `upload`.
+#[derive(Debug, Deserialize)]
+struct MatrixUploadResponse {
+    content_uri: String,
+}
+
+/// Response from `account/whoami`.
+#[derive(Debug, Deserialize)]
+struct MatrixWhoAmI {
+    user_id: String,
+}
+
+/// What the `/sync` loop remembers between calls.
+#[derive(Debug, Default)]
+struct SyncState {
+    user_id: String,
+    next_batch: String,
+    /// Rooms flagged as direct chats in the `m.direct` account data.
+    direct_rooms: HashSet<String>,
+    /// Latest joined-member count per room, from room summaries.
+    member_counts: HashMap<String, u64>,
+}
+
+impl SyncState {
+    /// Fold a `/sync` response's batch token, account data and room
+    /// summaries into the state.
+    fn absorb(&mut self, sync: &Value) {
+        if let Some(batch) = sync["next_batch"].as_str() {
+            self.next_batch = batch.to_string();
+        }
+        let account_data = sync["account_data"]["events"].as_array();
+        for event in account_data.into_iter().flatten() {
+            if event["type"] == "m.direct"
+                && let Some(users) = event["content"].as_object()
+            {
+                self.direct_rooms = users
+                    .values()
+                    .filter_map(Value::as_array)
+                    .flatten()
+                    .filter_map(Value::as_str)
+                    .map(String::from)
+                    .collect();
+            }
+        }
+        if let Some(rooms) = sync["rooms"]["join"].as_object() {
+            for (room_id, room) in rooms {
+                if let Some(count) = room["summary"]["m.joined_member_count"].as_u64() {
+                    self.member_counts.insert(room_id.clone(), count);
+                }
+            }
+        }
+    }
+
+    fn is_direct(&self, room_id: &str) -> bool {
+        self.direct_rooms.contains(room_id) || self.member_counts.get(room_id) == Some(&2)
+    }
+}
+
 /// Matrix messaging provider using the Matrix Client-Server API v3.
+#[derive(Clone)]
 pub struct MatrixProvider {
     base_url: String,
     token: String,
@@ -196,32 +265,22 @@ impl MatrixProvider {
             platform: Platform::Matrix,
         }
     }
-}
-
-#[async_trait]
-impl MessagingProvider for MatrixProvider {
-    fn platform(&self) -> Platform {
-        Platform::Matrix
-    }
 
-    async fn send_message(&self, channel: &str, text: &str) -> Result<SentMessage> {
-        let txn_id = format!("hive-{}", Utc::now().timestamp_millis());
+    /// Send an `m.room.message` event with the given content.
+    async fn send_event(&self, channel: &str, payload: &Value) -> Result<SentMessage> {
+        let txn_id = format!("hive-{}", uuid::Uuid::new_v4());
         let encoded_room = urlencod(channel);
         let url = format!(
             "{}/rooms/{}/send/m.room.message/{}",
             self.base_url, encoded_room, txn_id
         );
-        let payload = serde_json::json!({
-            "msgtype": "m.text",
-            "body": text,
-        });
 
         debug!(url = %url, channel = %channel, "sending Matrix message");
 
         let resp = self
             .client
             .put(&url)
-            .json(&payload)
+            .json(payload)
             .send()
             .await
             .context("Matrix send message request failed")?;
@@ -244,6 +303,191 @@ impl MessagingProvider for MatrixProvider {
         })
     }
 
+    /// Media repository URL, derived from the client API URL
+    /// (`…/_matrix/client/v3` → `…/_matrix/media/v3`).
+    fn media_url(&self) -> String {
+        match self.base_url.strip_suffix("/client/v3") {
+            Some(root) => format!("{root}/media/v3"),
+            None => self.base_url.clone(),
+        }
+    }
+
+    /// Upload bytes to the media repository, returning the `mxc://` URI.
+    async fn upload_media(&self, file: &FileUpload) -> Result<String> {
+        let url = format!(
+            "{}/upload?filename={}",
+            self.media_url(),
+            urlencod(&file.name)
+        );
+
+        debug!(url = %url, file = %file.name, "uploading Matrix media");
+
+        let resp = self
+            .client
+            .post(&url)
+            .header(CONTENT_TYPE, file.mime_type.as_str())
+            .body(file.data.clone())
+            .send()
+            .await
+            .context("Matrix media upload request failed")?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Matrix API HTTP error ({}): {}", status, body);
+        }
+
+        let upload: MatrixUploadResponse = resp
+            .json()
+            .await
+            .context("failed to parse Matrix upload response")?;
+        Ok(upload.content_uri)
+    }
+
+    /// GET a Client-Server API path and decode the JSON response.
+    async fn get_json<T: for<'de> Deserialize<'de>>(&self, path: &str) -> Result<T> {
+        let url = format!("{}{}", self.base_url, path);
+        let resp = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .with_context(|| format!("Matrix {path} request failed"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Matrix API HTTP error ({}): {}", status, body);
+        }
+
+        resp.json()
+            .await
+            .with_context(|| format!("failed to parse Matrix {path} response"))
+    }
+
+    // ── Sync ───────────────────────────────────────────────────────
+
+    /// Long-poll `/sync` until the stream is dropped. Messages that arrived
+    /// before the stream opened are skipped.
+    async fn run_sync(self, tx: mpsc::Sender<MessageEvent>) {
+        let mut state = loop {
+            match self.initial_sync().await {
+                Ok(state) => break state,
+                Err(e) => warn!("Matrix initial sync failed: {e:#}"),
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+            if tx.is_closed() {
+                return;
+            }
+        };
+
+        while !tx.is_closed() {
+            match self.sync_once(&mut state).await {
+                Ok(events) => {
+                    for event in events {
+                        if tx.send(event).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!("Matrix sync failed: {e:#}");
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            }
+        }
+    }
+
+    /// Learn who we are and where the timeline currently ends.
+    async fn initial_sync(&self) -> Result<SyncState> {
+        let me: MatrixWhoAmI = self.get_json("/account/whoami").await?;
+        let filter = urlencod(r#"{"room":{"timeline":{"limit":0}}}"#);
+        let sync: Value = self
+            .get_json(&format!("/sync?timeout=0&filter={filter}"))
+            .await?;
+        let mut state = SyncState {
+            user_id: me.user_id,
+            ..SyncState::default()
+        };
+        state.absorb(&sync);
+        Ok(state)
+    }
+
+    /// One `/sync` round: new messages in joined rooms since the last batch.
+    async fn sync_once(&self, state: &mut SyncState) -> Result<Vec<MessageEvent>> {
+        let sync: Value = self
+            .get_json(&format!(
+                "/sync?since={}&timeout={}",
+                urlencod(&state.next_batch),
+                SYNC_TIMEOUT_MS
+            ))
+            .await?;
+        state.absorb(&sync);
+
+        let mut events = Vec::new();
+        if let Some(rooms) = sync["rooms"]["join"].as_object() {
+            for (room_id, room) in rooms {
+                let timeline = room["timeline"]["events"].as_array();
+                events.extend(
+                    timeline
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|event| self.sync_event(room_id, event, state)),
+                );
+            }
+        }
+        Ok(events)
+    }
+
+    /// Convert a timeline event into a [`MessageEvent`]. Only messages from
+    /// other users are kept; replies go into the event's thread.
+    fn sync_event(&self, room_id: &str, raw: &Value, state: &SyncState) -> Option<MessageEvent> {
+        let event: MatrixEvent = serde_json::from_value(raw.clone()).ok()?;
+        if event.event_type != "m.room.message" || event.sender == state.user_id {
+            return None;
+        }
+        let content = event.content.as_ref()?;
+        let body = content.body.clone().unwrap_or_default();
+
+        let mentions_bot = body.contains(&state.user_id)
+            || raw["content"]["m.mentions"]["user_ids"]
+                .as_array()
+                .is_some_and(|ids| ids.iter().any(|id| *id == *state.user_id));
+        let text = body.replace(&state.user_id, "");
+        let text = text.trim().trim_start_matches([':', ',']).trim();
+
+        let thread_id = content
+            .relates_to
+            .as_ref()
+            .filter(|r| r["rel_type"] == "m.thread")
+            .and_then(|r| r["event_id"].as_str())
+            .unwrap_or(&event.event_id)
+            .to_string();
+
+        Some(MessageEvent {
+            message: self.convert_event(&event, room_id),
+            thread_id: Some(thread_id),
+            is_direct: state.is_direct(room_id),
+            mentions_bot,
+            text: text.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl MessagingProvider for MatrixProvider {
+    fn platform(&self) -> Platform {
+        Platform::Matrix
+    }
+
+    async fn send_message(&self, channel: &str, text: &str) -> Result<SentMessage> {
+        let payload = serde_json::json!({
+            "msgtype": "m.text",
+            "body": text,
+        });
+        self.send_event(channel, &payload).await
+    }
+
     async fn list_channels(&self) -> Result<Vec<Channel>> {
         let url = format!("{}/joined_rooms", self.base_url);
 
@@ -416,6 +660,125 @@ impl MessagingProvider for MatrixProvider {
 
         Ok(results)
     }
+
+    async fn event_stream(&self) -> Result<MessageStream> {
+        let provider = self.clone();
+        Ok(MessageStream::spawn(move |tx| provider.run_sync(tx)))
+    }
+
+    async fn reply_in_thread(
+        &self,
+        channel: &str,
+        thread_id: &str,
+        text: &str,
+    ) -> Result<SentMessage> {
+        let payload = serde_json::json!({
+            "msgtype": "m.text",
+            "body": text,
+            "m.relates_to": thread_relation(thread_id),
+        });
+        self.send_event(channel, &payload).await
+    }
+
+    async fn send_rich(
+        &self,
+        channel: &str,
+        thread_id: Option<&str>,
+        text: &RichText,
+    ) -> Result<SentMessage> {
+        let mut payload = formatted_content(text);
+        if let Some(thread_id) = thread_id {
+            payload["m.relates_to"] = thread_relation(thread_id);
+        }
+        self.send_event(channel, &payload).await
+    }
+
+    async fn upload_file(
+        &self,
+        channel: &str,
+        thread_id: Option<&str>,
+        file: &FileUpload,
+    ) -> Result<SentMessage> {
+        let content_uri = self.upload_media(file).await?;
+        let msgtype = if file.is_image() { "m.image" } else { "m.file" };
+        let mut payload = serde_json::json!({
+            "msgtype": msgtype,
+            "body": file.caption.as_deref().unwrap_or(&file.name),
+            "filename": file.name,
+            "url": content_uri,
+            "info": {
+                "mimetype": file.mime_type,
+                "size": file.data.len(),
+            },
+        });
+        if let Some(thread_id) = thread_id {
+            payload["m.relates_to"] = thread_relation(thread_id);
+        }
+        self.send_event(channel, &payload).await
+    }
+
+    /// Edits are `m.replace` events; the outer body is the fallback shown
+    /// by clients that don't support edits.
+    async fn edit_message(&self, channel: &str, message_id: &str, text: &RichText) -> Result<()> {
+        let new_content = formatted_content(text);
+        let mut payload = formatted_content(text);
+        payload["body"] = format!("* {}", text.to_plain()).into();
+        payload["m.new_content"] = new_content;
+        payload["m.relates_to"] = serde_json::json!({
+            "rel_type": "m.replace",
+            "event_id": message_id,
+        });
+        self.send_event(channel, &payload).await?;
+        Ok(())
+    }
+
+    async fn delete_message(&self, channel: &str, message_id: &str) -> Result<()> {
+        let url = format!(
+            "{}/rooms/{}/redact/{}/hive-{}",
+            self.base_url,
+            urlencod(channel),
+            urlencod(message_id),
+            uuid::Uuid::new_v4()
+        );
+
+        debug!(url = %url, message_id = %message_id, "redacting Matrix event");
+
+        let resp = self
+            .client
+            .put(&url)
+            .json(&serde_json::json!({}))
+            .send()
+            .await
+            .context("Matrix redact request failed")?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Matrix API HTTP error ({}): {}", status, body);
+        }
+        Ok(())
+    }
+}
+
+/// `m.text` content with an HTML `formatted_body`.
+fn formatted_content(text: &RichText) -> serde_json::Value {
+    serde_json::json!({
+        "msgtype": "m.text",
+        "body": text.to_plain(),
+        "format": "org.matrix.custom.html",
+        "formatted_body": text.to_html(),
+    })
+}
+
+/// Relation placing an event in the thread rooted at `thread_id`, falling
+/// back to a plain reply for clients without thread support.
+fn thread_relation(thread_id: &str) -> serde_json::Value {
+    serde_json::json!({
+        "rel_type": "m.thread",
+        "event_id": thread_id,
+        "is_falling_back": true,
+        "m.in_reply_to": { "event_id": thread_id },
+    })
 }
 
 /// Minimal percent-encoding for URL path/query segments.
@@ -439,6 +802,7 @@ fn urlencod(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::messaging::rich_text::Span;
 
     /// Build the full URL for a given API path.
     fn build_url(base: &str, path: &str) -> String {
@@ -680,7 +1044,7 @@ mod tests {
                 body: Some("Test message".into()),
                 msgtype: Some("m.text".into()),
                 url: None,
-                _relates_to: None,
+                relates_to: None,
                 info: None,
             }),
             room_id: Some("!room5:matrix.org".into()),
@@ -707,7 +1071,7 @@ mod tests {
                 body: Some("photo.jpg".into()),
                 msgtype: Some("m.image".into()),
                 url: Some("mxc://matrix.org/xyz789".into()),
-                _relates_to: None,
+                relates_to: None,
                 info: Some(MatrixFileInfo {
                     mimetype: Some("image/jpeg".into()),
                     size: Some(8192),
@@ -731,4 +1095,192 @@ mod tests {
         assert_eq!(urlencod("!room:matrix.org"), "%21room%3Amatrix.org");
         assert_eq!(urlencod("safe_name.txt"), "safe_name.txt");
     }
+
+    fn room_message(event_id: &str, sender: &str, content: Value) -> Value {
+        serde_json::json!({
+            "event_id": event_id,
+            "sender": sender,
+            "origin_server_ts": 1609459200000_i64,
+            "type": "m.room.message",
+            "content": content,
+        })
+    }
+
+    #[test]
+    fn test_sync_event_mentions_threads_and_direct_rooms() {
+        let provider = make_provider();
+        let mut state = SyncState {
+            user_id: "@hive:example.com".into(),
+            ..SyncState::default()
+        };
+        state.absorb(&serde_json::json!({
+            "next_batch": "s1",
+            "account_data": {"events": [
+                {"type": "m.direct", "content": {"@alice:example.com": ["!dm:example.com"]}},
+            ]},
+        }));
+        assert_eq!(state.next_batch, "s1");
+
+        let mention = room_message(
+            "$e1",
+            "@alice:example.com",
+            serde_json::json!({
+                "msgtype": "m.text",
+                "body": "@hive:example.com: deploy?",
+                "m.relates_to": {"rel_type": "m.thread", "event_id": "$root"},
+            }),
+        );
+        let ev = provider
+            .sync_event("!room:example.com", &mention, &state)
+            .unwrap();
+        assert!(ev.mentions_bot);
+        assert!(!ev.is_direct);
+        assert_eq!(ev.text, "deploy?");
+        assert_eq!(ev.thread_id.as_deref(), Some("$root"));
+
+        let dm = room_message(
+            "$e2",
+            "@alice:example.com",
+            serde_json::json!({"msgtype": "m.text", "body": "hi"}),
+        );
+        let ev = provider.sync_event("!dm:example.com", &dm, &state).unwrap();
+        assert!(ev.is_direct);
+        assert_eq!(ev.thread_id.as_deref(), Some("$e2"));
+
+        let own = room_message(
+            "$e3",
+            "@hive:example.com",
+            serde_json::json!({"msgtype": "m.text", "body": "echo"}),
+        );
+        assert!(
+            provider
+                .sync_event("!dm:example.com", &own, &state)
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_stream_skips_backlog_and_replies_in_thread() {
+        use super::super::test_support::http_stub;
+
+        let (base_url, requests) = http_stub(|req| {
+            let body = if req.path == "/account/whoami" {
+                serde_json::json!({"user_id": "@hive:example.com"})
+            } else if req.path.starts_with("/sync?timeout=0") {
+                serde_json::json!({"next_batch": "s0", "rooms": {"join": {"!room:example.com": {
+                    "timeline": {"events": [room_message(
+                        "$old", "@alice:example.com",
+                        serde_json::json!({"msgtype": "m.text", "body": "backlog"}),
+                    )]},
+                }}}})
+            } else if req.path.starts_with("/sync?since=s0") {
+                serde_json::json!({"next_batch": "s1", "rooms": {"join": {"!room:example.com": {
+                    "summary": {"m.joined_member_count": 2},
+                    "timeline": {"events": [room_message(
+                        "$new", "@alice:example.com",
+                        serde_json::json!({
+                            "msgtype": "m.text",
+                            "body": "status?",
+                            "m.mentions": {"user_ids": ["@hive:example.com"]},
+                        }),
+                    )]},
+                }}}})
+            } else if req.path.starts_with("/sync") {
+                serde_json::json!({"next_batch": "s1"})
+            } else {
+                serde_json::json!({"event_id": "$reply"})
+            };
+            (200, body.to_string())
+        })
+        .await;
+
+        let provider = MatrixProvider::with_base_url("syt_token", &base_url).unwrap();
+        let mut stream = provider.event_stream().await.unwrap();
+
+        let ev = stream.next().await.unwrap();
+        assert_eq!(ev.message.id, "$new");
+        assert!(ev.mentions_bot);
+        assert!(ev.is_direct);
+
+        let sent = provider
+            .reply_in_thread("!room:example.com", "$new", "green")
+            .await
+            .unwrap();
+        assert_eq!(sent.id, "$reply");
+        drop(stream);
+
+        let requests = requests.lock().unwrap();
+        let reply = requests.iter().find(|r| r.method == "PUT").unwrap();
+        assert!(
+            reply
+                .path
+                .starts_with("/rooms/%21room%3Aexample.com/send/m.room.message/")
+        );
+        let relation = &reply.json()["m.relates_to"];
+        assert_eq!(relation["rel_type"], "m.thread");
+        assert_eq!(relation["event_id"], "$new");
+    }
+
+    #[tokio::test]
+    async fn test_rich_text_upload_edit_and_delete() {
+        use super::super::test_support::http_stub;
+
+        let (stub, requests) = http_stub(|req| {
+            let body = if req.path.starts_with("/_matrix/media/v3/upload") {
+                serde_json::json!({"content_uri": "mxc://example.com/abc"})
+            } else {
+                serde_json::json!({"event_id": "$event"})
+            };
+            (200, body.to_string())
+        })
+        .await;
+        let provider =
+            MatrixProvider::with_base_url("syt_token", &format!("{stub}/_matrix/client/v3"))
+                .unwrap();
+        let room = "!room:example.com";
+
+        let rich = RichText::new().paragraph(vec![Span::text("build "), Span::bold("green")]);
+        provider
+            .send_rich(room, Some("$root"), &rich)
+            .await
+            .unwrap();
+
+        let file = FileUpload::new("chart.png", "image/png", vec![1, 2, 3]);
+        let sent = provider.upload_file(room, None, &file).await.unwrap();
+        assert_eq!(sent.id, "$event");
+
+        provider.edit_message(room, "$event", &rich).await.unwrap();
+        provider.delete_message(room, "$event").await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 5);
+
+        let formatted = requests[0].json();
+        assert_eq!(formatted["format"], "org.matrix.custom.html");
+        assert_eq!(
+            formatted["formatted_body"],
+            "<p>build <strong>green</strong></p>"
+        );
+        assert_eq!(formatted["m.relates_to"]["event_id"], "$root");
+
+        assert_eq!(requests[1].method, "POST");
+        assert!(requests[1].path.ends_with("/upload?filename=chart.png"));
+        assert_eq!(requests[1].header("content-type"), Some("image/png"));
+        let image = requests[2].json();
+        assert_eq!(image["msgtype"], "m.image");
+        assert_eq!(image["url"], "mxc://example.com/abc");
+        assert_eq!(image["info"]["size"], 3);
+
+        let edit = requests[3].json();
+        assert_eq!(edit["body"], "* build green");
+        assert_eq!(edit["m.relates_to"]["rel_type"], "m.replace");
+        assert_eq!(edit["m.new_content"]["body"], "build green");
+
+        assert_eq!(requests[4].method, "PUT");
+        assert!(
+            requests[4]
+                .path
+                .starts_with("/_matrix/client/v3/rooms/%21room%3Aexample.com/redact/%24event/")
+        );
+    }
 }
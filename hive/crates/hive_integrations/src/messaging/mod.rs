@@ -6,10 +6,13 @@ pub mod hub;
 pub mod imessage;
 pub mod matrix;
 pub mod provider;
+pub mod rich_text;
 pub mod signal;
 pub mod slack;
 pub mod teams;
 pub mod telegram;
+#[cfg(test)]
+mod test_support;
 pub mod webchat;
 pub mod whatsapp;
 
@@ -21,8 +24,10 @@ pub use hub::MessagingHub;
 pub use imessage::IMessageProvider;
 pub use matrix::MatrixProvider;
 pub use provider::{
-    Attachment, Channel, IncomingMessage, MessagingProvider, Platform, SentMessage,
+    Attachment, Channel, FileUpload, IncomingMessage, MessageEvent, MessageStream,
+    MessagingProvider, Platform, SentMessage,
 };
+pub use rich_text::{Block, RichText, Span};
 pub use signal::SignalProvider;
 pub use slack::SlackProvider;
 pub use teams::TeamsProvider;
@@ -4,11 +4,25 @@
 //! implementations (Slack, Discord, etc.) must satisfy, along with the
 //! common data types exchanged across providers.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::future::Future;
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use super::rich_text::RichText;
+
+/// Events buffered between a live connection and its consumer.
+const STREAM_BUFFER: usize = 256;
+
+/// Pause before reconnecting a dropped real-time connection.
+pub(crate) const RECONNECT_DELAY: Duration = Duration::from_secs(5);
 
 // ── Platform enum ──────────────────────────────────────────────────
 
@@ -79,6 +93,73 @@ pub struct IncomingMessage {
     pub platform: Platform,
 }
 
+/// A file to post with [`MessagingProvider::upload_file`].
+#[derive(Debug, Clone)]
+pub struct FileUpload {
+    pub name: String,
+    pub mime_type: String,
+    pub data: Vec<u8>,
+    /// Text posted alongside the file.
+    pub caption: Option<String>,
+}
+
+impl FileUpload {
+    pub fn new(name: impl Into<String>, mime_type: impl Into<String>, data: Vec<u8>) -> Self {
+        Self {
+            name: name.into(),
+            mime_type: mime_type.into(),
+            data,
+            caption: None,
+        }
+    }
+
+    /// Read a file from disk, guessing its MIME type from the extension.
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let data =
+            std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "file".into());
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        Ok(Self::new(name, mime_for_extension(&extension), data))
+    }
+
+    pub fn with_caption(mut self, caption: impl Into<String>) -> Self {
+        self.caption = Some(caption.into());
+        self
+    }
+
+    /// Whether platforms should display the file inline as an image.
+    pub fn is_image(&self) -> bool {
+        self.mime_type.starts_with("image/")
+    }
+}
+
+/// MIME type for the file types Hive generates and commonly shares.
+fn mime_for_extension(extension: &str) -> &'static str {
+    match extension {
+        "pdf" => "application/pdf",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "csv" => "text/csv",
+        "md" => "text/markdown",
+        "txt" | "log" => "text/plain",
+        "html" | "htm" => "text/html",
+        "json" => "application/json",
+        "zip" => "application/zip",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
 /// Confirmation of a successfully sent message.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -88,6 +169,96 @@ pub struct SentMessage {
     pub timestamp: DateTime<Utc>,
 }
 
+// ── Real-time events ───────────────────────────────────────────────
+
+/// A message delivered as it arrives by [`MessagingProvider::event_stream`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageEvent {
+    pub message: IncomingMessage,
+    /// Where a reply belongs: the thread the message is in, or the message
+    /// itself on platforms where replying to it starts a thread.
+    pub thread_id: Option<String>,
+    /// Sent in a one-to-one conversation with the bot.
+    pub is_direct: bool,
+    /// The bot was mentioned or replied to.
+    pub mentions_bot: bool,
+    /// The message text with the bot's own mention removed.
+    pub text: String,
+}
+
+/// A live feed of [`MessageEvent`]s. Dropping the stream closes the
+/// underlying connection.
+pub struct MessageStream {
+    rx: mpsc::Receiver<MessageEvent>,
+    task: JoinHandle<()>,
+}
+
+impl MessageStream {
+    /// Run `producer` in the background, feeding the events it sends into
+    /// the stream. The producer should return once the sender is closed.
+    pub fn spawn<F, Fut>(producer: F) -> Self
+    where
+        F: FnOnce(mpsc::Sender<MessageEvent>) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        let task = tokio::spawn(producer(tx));
+        Self { rx, task }
+    }
+
+    /// Wait for the next event. Returns `None` once the producer has stopped.
+    pub async fn next(&mut self) -> Option<MessageEvent> {
+        self.rx.recv().await
+    }
+}
+
+impl Drop for MessageStream {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Fan-out for providers whose events arrive as inbound webhooks: the host
+/// HTTP layer publishes each delivery, and every open [`MessageStream`]
+/// receives it.
+#[derive(Clone)]
+pub(crate) struct EventFeed {
+    tx: broadcast::Sender<MessageEvent>,
+}
+
+impl EventFeed {
+    pub(crate) fn new() -> Self {
+        let (tx, _) = broadcast::channel(STREAM_BUFFER);
+        Self { tx }
+    }
+
+    /// Deliver an event to every open stream. Dropped if nobody listens.
+    pub(crate) fn publish(&self, event: MessageEvent) {
+        let _ = self.tx.send(event);
+    }
+
+    /// Open a stream of events published from now on.
+    pub(crate) fn stream(&self) -> MessageStream {
+        let mut rx = self.tx.subscribe();
+        MessageStream::spawn(move |tx| async move {
+            loop {
+                match rx.recv().await {
+                    Ok(event) => {
+                        if tx.send(event).await.is_err() {
+                            return;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!(skipped, "Message stream fell behind, skipping events");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                }
+            }
+        })
+    }
+}
+
 // ── Provider trait ─────────────────────────────────────────────────
 
 /// Trait that every messaging platform integration must implement.
@@ -110,6 +281,66 @@ pub trait MessagingProvider: Send + Sync {
 
     /// Search messages across channels.
     async fn search_messages(&self, query: &str, limit: u32) -> Result<Vec<IncomingMessage>>;
+
+    /// Open a live feed of incoming messages, for running as a bot.
+    ///
+    /// Providers without a real-time mechanism return an error.
+    async fn event_stream(&self) -> Result<MessageStream> {
+        anyhow::bail!("{} does not support real-time events", self.platform())
+    }
+
+    /// Reply inside the thread identified by `thread_id` (see
+    /// [`MessageEvent::thread_id`]). Platforms without threads post to the
+    /// channel instead.
+    async fn reply_in_thread(
+        &self,
+        channel: &str,
+        thread_id: &str,
+        text: &str,
+    ) -> Result<SentMessage> {
+        let _ = thread_id;
+        self.send_message(channel, text).await
+    }
+
+    /// Send formatted text, as a reply in `thread_id` when given. The
+    /// default posts the plain-text rendering.
+    async fn send_rich(
+        &self,
+        channel: &str,
+        thread_id: Option<&str>,
+        text: &RichText,
+    ) -> Result<SentMessage> {
+        match thread_id {
+            Some(thread_id) => {
+                self.reply_in_thread(channel, thread_id, &text.to_plain())
+                    .await
+            }
+            None => self.send_message(channel, &text.to_plain()).await,
+        }
+    }
+
+    /// Upload a file to a channel, as a reply in `thread_id` when given.
+    async fn upload_file(
+        &self,
+        channel: &str,
+        thread_id: Option<&str>,
+        file: &FileUpload,
+    ) -> Result<SentMessage> {
+        let _ = (channel, thread_id, file);
+        anyhow::bail!("{} does not support file uploads", self.platform())
+    }
+
+    /// Replace the content of a message the bot sent.
+    async fn edit_message(&self, channel: &str, message_id: &str, text: &RichText) -> Result<()> {
+        let _ = (channel, message_id, text);
+        anyhow::bail!("{} does not support editing messages", self.platform())
+    }
+
+    /// Delete a message the bot sent.
+    async fn delete_message(&self, channel: &str, message_id: &str) -> Result<()> {
+        let _ = (channel, message_id);
+        anyhow::bail!("{} does not support deleting messages", self.platform())
+    }
 }
 
 #[cfg(test)]
@@ -251,6 +482,30 @@ mod tests {
         assert_ne!(Platform::Slack, Platform::Discord);
     }
 
+    #[tokio::test]
+    async fn test_message_stream_ends_with_producer() {
+        let mut stream = MessageStream::spawn(|tx| async move {
+            let event = MessageEvent {
+                message: IncomingMessage {
+                    id: "m-1".into(),
+                    channel_id: "C1".into(),
+                    author: "alice".into(),
+                    content: "hi".into(),
+                    timestamp: Utc::now(),
+                    attachments: vec![],
+                    platform: Platform::WebChat,
+                },
+                thread_id: None,
+                is_direct: true,
+                mentions_bot: false,
+                text: "hi".into(),
+            };
+            let _ = tx.send(event).await;
+        });
+        assert_eq!(stream.next().await.unwrap().text, "hi");
+        assert!(stream.next().await.is_none());
+    }
+
     #[test]
     fn test_platform_hash_used_as_key() {
         use std::collections::HashMap;
@@ -0,0 +1,556 @@
+//! Portable rich text for outgoing messages.
+//!
+//! A [`RichText`] is a list of blocks (paragraphs, headings, code blocks,
+//! quotes, lists) made of styled [`Span`]s. Each provider renders it to
+//! its own markup: Slack and Google Chat use mrkdwn, Discord uses Markdown,
+//! Telegram uses its HTML subset, and Matrix and Teams use HTML. Every
+//! renderer escapes the text it is given, so agent output can't inject
+//! markup.
+
+use serde::{Deserialize, Serialize};
+
+// ── Model ──────────────────────────────────────────────────────────
+
+/// A run of text with one set of styles.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Span {
+    pub text: String,
+    #[serde(default)]
+    pub bold: bool,
+    #[serde(default)]
+    pub italic: bool,
+    #[serde(default)]
+    pub strike: bool,
+    /// Inline code. Other styles are ignored on code spans.
+    #[serde(default)]
+    pub code: bool,
+    /// Link target.
+    #[serde(default)]
+    pub link: Option<String>,
+}
+
+impl Span {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn bold(text: impl Into<String>) -> Self {
+        Self {
+            bold: true,
+            ..Self::text(text)
+        }
+    }
+
+    pub fn italic(text: impl Into<String>) -> Self {
+        Self {
+            italic: true,
+            ..Self::text(text)
+        }
+    }
+
+    pub fn strike(text: impl Into<String>) -> Self {
+        Self {
+            strike: true,
+            ..Self::text(text)
+        }
+    }
+
+    pub fn code(text: impl Into<String>) -> Self {
+        Self {
+            code: true,
+            ..Self::text(text)
+        }
+    }
+
+    pub fn link(text: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            link: Some(url.into()),
+            ..Self::text(text)
+        }
+    }
+}
+
+/// A block-level element.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Block {
+    Paragraph {
+        spans: Vec<Span>,
+    },
+    /// Level 1–3. Platforms without headings render it bold.
+    Heading {
+        level: u8,
+        spans: Vec<Span>,
+    },
+    CodeBlock {
+        language: Option<String>,
+        code: String,
+    },
+    Quote {
+        spans: Vec<Span>,
+    },
+    List {
+        ordered: bool,
+        items: Vec<Vec<Span>>,
+    },
+}
+
+/// A formatted message body.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RichText {
+    pub blocks: Vec<Block>,
+}
+
+impl RichText {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A single unstyled paragraph.
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self::new().paragraph(vec![Span::text(text)])
+    }
+
+    pub fn paragraph(mut self, spans: Vec<Span>) -> Self {
+        self.blocks.push(Block::Paragraph { spans });
+        self
+    }
+
+    pub fn heading(mut self, level: u8, spans: Vec<Span>) -> Self {
+        self.blocks.push(Block::Heading {
+            level: level.clamp(1, 3),
+            spans,
+        });
+        self
+    }
+
+    pub fn code_block(mut self, language: Option<&str>, code: impl Into<String>) -> Self {
+        self.blocks.push(Block::CodeBlock {
+            language: language.map(String::from),
+            code: code.into(),
+        });
+        self
+    }
+
+    pub fn quote(mut self, spans: Vec<Span>) -> Self {
+        self.blocks.push(Block::Quote { spans });
+        self
+    }
+
+    pub fn list(mut self, ordered: bool, items: Vec<Vec<Span>>) -> Self {
+        self.blocks.push(Block::List { ordered, items });
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    // ── Renderers ──────────────────────────────────────────────────
+
+    /// Unformatted text, for notifications and platforms without markup.
+    pub fn to_plain(&self) -> String {
+        self.render(&Plain)
+    }
+
+    /// Markdown as Discord renders it.
+    pub fn to_markdown(&self) -> String {
+        self.render(&Markdown)
+    }
+
+    /// Slack / Google Chat mrkdwn.
+    pub fn to_mrkdwn(&self) -> String {
+        self.render(&Mrkdwn)
+    }
+
+    /// HTML for Matrix `formatted_body` and Teams message bodies.
+    pub fn to_html(&self) -> String {
+        self.render(&Html)
+    }
+
+    /// The HTML subset accepted by Telegram's `parse_mode: HTML`.
+    pub fn to_telegram_html(&self) -> String {
+        self.render(&TelegramHtml)
+    }
+
+    fn render(&self, markup: &dyn Markup) -> String {
+        let blocks: Vec<String> = self.blocks.iter().map(|b| markup.block(b)).collect();
+        blocks.join(markup.block_separator())
+    }
+}
+
+impl From<&str> for RichText {
+    fn from(text: &str) -> Self {
+        Self::plain(text)
+    }
+}
+
+impl From<String> for RichText {
+    fn from(text: String) -> Self {
+        Self::plain(text)
+    }
+}
+
+// ── Markup dialects ────────────────────────────────────────────────
+
+/// One platform's markup. Text-based dialects share the block layout in
+/// [`text_block`]; HTML dialects override [`Markup::block`].
+trait Markup {
+    fn span(&self, span: &Span) -> String;
+
+    fn spans(&self, spans: &[Span]) -> String {
+        spans.iter().map(|s| self.span(s)).collect()
+    }
+
+    fn block(&self, block: &Block) -> String {
+        text_block(self, block)
+    }
+
+    fn heading(&self, _level: u8, content: String) -> String {
+        content
+    }
+
+    fn code_block(&self, language: Option<&str>, code: &str) -> String {
+        format!("```{}\n{}\n```", language.unwrap_or_default(), code)
+    }
+
+    fn block_separator(&self) -> &'static str {
+        "\n\n"
+    }
+}
+
+/// Lay out a block as lines of text: `> ` quotes and `•`/`1.` list items.
+fn text_block<M: Markup + ?Sized>(markup: &M, block: &Block) -> String {
+    match block {
+        Block::Paragraph { spans } => markup.spans(spans),
+        Block::Heading { level, spans } => markup.heading(*level, markup.spans(spans)),
+        Block::CodeBlock { language, code } => markup.code_block(language.as_deref(), code),
+        Block::Quote { spans } => markup
+            .spans(spans)
+            .lines()
+            .map(|line| format!("> {line}"))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Block::List { ordered, items } => items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let marker = if *ordered {
+                    format!("{}.", i + 1)
+                } else {
+                    "•".to_string()
+                };
+                format!("{marker} {}", markup.spans(item))
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+/// Wrap `text` in a symmetric delimiter, keeping surrounding whitespace
+/// outside it (`* bold*` doesn't render on most platforms).
+fn wrap(text: &str, delimiter: &str) -> String {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return text.to_string();
+    }
+    let start = text.len() - text.trim_start().len();
+    let end = start + trimmed.len();
+    format!(
+        "{}{delimiter}{trimmed}{delimiter}{}",
+        &text[..start],
+        &text[end..]
+    )
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+struct Plain;
+
+impl Markup for Plain {
+    fn span(&self, span: &Span) -> String {
+        match &span.link {
+            Some(url) if *url != span.text => format!("{} ({url})", span.text),
+            _ => span.text.clone(),
+        }
+    }
+
+    fn code_block(&self, _language: Option<&str>, code: &str) -> String {
+        code.to_string()
+    }
+}
+
+struct Markdown;
+
+impl Markdown {
+    fn escape(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        for c in text.chars() {
+            if matches!(
+                c,
+                '\\' | '*' | '_' | '~' | '`' | '|' | '[' | ']' | '>' | '#'
+            ) {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+        out
+    }
+}
+
+impl Markup for Markdown {
+    fn span(&self, span: &Span) -> String {
+        if span.code {
+            return wrap(&span.text.replace('`', "'"), "`");
+        }
+        let mut text = self.escape(&span.text);
+        if span.bold {
+            text = wrap(&text, "**");
+        }
+        if span.italic {
+            text = wrap(&text, "*");
+        }
+        if span.strike {
+            text = wrap(&text, "~~");
+        }
+        match &span.link {
+            Some(url) => format!("[{text}]({})", url.replace(')', "%29")),
+            None => text,
+        }
+    }
+
+    fn heading(&self, level: u8, content: String) -> String {
+        format!("{} {content}", "#".repeat(level.clamp(1, 3) as usize))
+    }
+}
+
+struct Mrkdwn;
+
+impl Mrkdwn {
+    fn escape(&self, text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    }
+}
+
+impl Markup for Mrkdwn {
+    fn span(&self, span: &Span) -> String {
+        if span.code {
+            return wrap(&self.escape(&span.text.replace('`', "'")), "`");
+        }
+        let mut text = self.escape(&span.text);
+        if span.bold {
+            text = wrap(&text, "*");
+        }
+        if span.italic {
+            text = wrap(&text, "_");
+        }
+        if span.strike {
+            text = wrap(&text, "~");
+        }
+        match &span.link {
+            Some(url) => format!("<{}|{text}>", self.escape(url).replace('|', "%7C")),
+            None => text,
+        }
+    }
+
+    fn heading(&self, _level: u8, content: String) -> String {
+        wrap(&content, "*")
+    }
+
+    fn code_block(&self, _language: Option<&str>, code: &str) -> String {
+        format!("```\n{}\n```", self.escape(code))
+    }
+}
+
+/// Inline HTML shared by the Matrix/Teams and Telegram dialects.
+fn html_span(span: &Span, bold: &str, italic: &str, strike: &str) -> String {
+    if span.code {
+        return format!("<code>{}</code>", html_escape(&span.text));
+    }
+    let mut text = html_escape(&span.text);
+    if span.bold {
+        text = format!("<{bold}>{text}</{bold}>");
+    }
+    if span.italic {
+        text = format!("<{italic}>{text}</{italic}>");
+    }
+    if span.strike {
+        text = format!("<{strike}>{text}</{strike}>");
+    }
+    match &span.link {
+        Some(url) => format!("<a href=\"{}\">{text}</a>", html_escape(url)),
+        None => text,
+    }
+}
+
+fn html_code_block(language: Option<&str>, code: &str) -> String {
+    match language {
+        Some(lang) => format!(
+            "<pre><code class=\"language-{}\">{}</code></pre>",
+            html_escape(lang),
+            html_escape(code)
+        ),
+        None => format!("<pre><code>{}</code></pre>", html_escape(code)),
+    }
+}
+
+struct Html;
+
+impl Markup for Html {
+    fn span(&self, span: &Span) -> String {
+        html_span(span, "strong", "em", "del")
+    }
+
+    fn block(&self, block: &Block) -> String {
+        match block {
+            Block::Paragraph { spans } => format!("<p>{}</p>", self.spans(spans)),
+            Block::Heading { level, spans } => {
+                let level = level.clamp(&1, &3);
+                format!("<h{level}>{}</h{level}>", self.spans(spans))
+            }
+            Block::CodeBlock { language, code } => html_code_block(language.as_deref(), code),
+            Block::Quote { spans } => format!("<blockquote>{}</blockquote>", self.spans(spans)),
+            Block::List { ordered, items } => {
+                let tag = if *ordered { "ol" } else { "ul" };
+                let items: String = items
+                    .iter()
+                    .map(|item| format!("<li>{}</li>", self.spans(item)))
+                    .collect();
+                format!("<{tag}>{items}</{tag}>")
+            }
+        }
+    }
+
+    fn block_separator(&self) -> &'static str {
+        ""
+    }
+}
+
+struct TelegramHtml;
+
+impl Markup for TelegramHtml {
+    fn span(&self, span: &Span) -> String {
+        html_span(span, "b", "i", "s")
+    }
+
+    fn block(&self, block: &Block) -> String {
+        match block {
+            Block::Quote { spans } => format!("<blockquote>{}</blockquote>", self.spans(spans)),
+            _ => text_block(self, block),
+        }
+    }
+
+    fn heading(&self, _level: u8, content: String) -> String {
+        format!("<b>{content}</b>")
+    }
+
+    fn code_block(&self, language: Option<&str>, code: &str) -> String {
+        html_code_block(language, code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> RichText {
+        RichText::new()
+            .heading(2, vec![Span::text("Build report")])
+            .paragraph(vec![
+                Span::text("Status: "),
+                Span::bold("passed"),
+                Span::text(" on "),
+                Span::code("main"),
+                Span::text(", see "),
+                Span::link("CI", "https://ci.example.com/1"),
+            ])
+            .code_block(Some("rust"), "fn main() {}")
+            .list(
+                false,
+                vec![vec![Span::italic("fast")], vec![Span::strike("slow")]],
+            )
+    }
+
+    #[test]
+    fn test_plain_drops_markup_and_keeps_links() {
+        assert_eq!(
+            sample().to_plain(),
+            "Build report\n\nStatus: passed on main, see CI (https://ci.example.com/1)\n\n\
+             fn main() {}\n\n• fast\n• slow"
+        );
+    }
+
+    #[test]
+    fn test_markdown_for_discord() {
+        assert_eq!(
+            sample().to_markdown(),
+            "## Build report\n\nStatus: **passed** on `main`, see [CI](https://ci.example.com/1)\n\n\
+             ```rust\nfn main() {}\n```\n\n• *fast*\n• ~~slow~~"
+        );
+        assert_eq!(
+            RichText::plain("2*3 = _x_").to_markdown(),
+            "2\\*3 = \\_x\\_"
+        );
+    }
+
+    #[test]
+    fn test_mrkdwn_for_slack_and_google_chat() {
+        assert_eq!(
+            sample().to_mrkdwn(),
+            "*Build report*\n\nStatus: *passed* on `main`, see <https://ci.example.com/1|CI>\n\n\
+             ```\nfn main() {}\n```\n\n• _fast_\n• ~slow~"
+        );
+        assert_eq!(RichText::plain("a < b & c").to_mrkdwn(), "a &lt; b &amp; c");
+    }
+
+    #[test]
+    fn test_html_for_matrix_and_teams() {
+        assert_eq!(
+            sample().to_html(),
+            "<h2>Build report</h2>\
+             <p>Status: <strong>passed</strong> on <code>main</code>, see \
+             <a href=\"https://ci.example.com/1\">CI</a></p>\
+             <pre><code class=\"language-rust\">fn main() {}</code></pre>\
+             <ul><li><em>fast</em></li><li><del>slow</del></li></ul>"
+        );
+        let quoted = RichText::new().quote(vec![Span::text("<script>")]);
+        assert_eq!(quoted.to_html(), "<blockquote>&lt;script&gt;</blockquote>");
+    }
+
+    #[test]
+    fn test_telegram_html() {
+        assert_eq!(
+            sample().to_telegram_html(),
+            "<b>Build report</b>\n\nStatus: <b>passed</b> on <code>main</code>, see \
+             <a href=\"https://ci.example.com/1\">CI</a>\n\n\
+             <pre><code class=\"language-rust\">fn main() {}</code></pre>\n\n\
+             • <i>fast</i>\n• <s>slow</s>"
+        );
+    }
+
+    #[test]
+    fn test_wrap_keeps_whitespace_outside_delimiters() {
+        let text = RichText::new().paragraph(vec![Span::text("a"), Span::bold(" b ")]);
+        assert_eq!(text.to_markdown(), "a **b** ");
+        assert_eq!(text.to_mrkdwn(), "a *b* ");
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let json = serde_json::to_string(&sample()).unwrap();
+        assert!(json.contains("\"type\":\"codeBlock\""));
+        let back: RichText = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, sample());
+    }
+}
@@ -1,22 +1,36 @@
 //! Slack messaging provider.
 //!
 //! Wraps the Slack Web API at `https://slack.com/api` using
-//! `reqwest` for HTTP and bot-token authentication.
+//! `reqwest` for HTTP and bot-token authentication. Real-time events
+//! arrive over Socket Mode, which needs an app-level token.
+
+use std::collections::{HashSet, VecDeque};
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, TimeZone, Utc};
+use futures::{SinkExt, StreamExt};
 use reqwest::Client;
 use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue};
 use serde::Deserialize;
-use tracing::debug;
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, warn};
 
 use super::provider::{
-    Attachment, Channel, IncomingMessage, MessagingProvider, Platform, SentMessage,
+    Attachment, Channel, FileUpload, IncomingMessage, MessageEvent, MessageStream,
+    MessagingProvider, Platform, RECONNECT_DELAY, SentMessage,
 };
+use super::rich_text::RichText;
 
 const DEFAULT_BASE_URL: &str = "https://slack.com/api";
 
+/// Message ids remembered to drop the duplicate `message` + `app_mention`
+/// pair Slack sends for a mention.
+const SEEN_CAPACITY: usize = 512;
+
 // ── Slack API response types ───────────────────────────────────────
 
 /// Envelope returned by most Slack Web API methods.
@@ -42,6 +56,25 @@ struct ConversationsListData {
     channels: Vec<SlackChannel>,
 }
 
+/// Data portion of `files.getUploadURLExternal` response.
+#[derive(Debug, Deserialize)]
+struct UploadUrlData {
+    upload_url: String,
+    file_id: String,
+}
+
+/// Data portion of `apps.connections.open` response.
+#[derive(Debug, Deserialize)]
+struct ConnectionsOpenData {
+    url: Option<String>,
+}
+
+/// Data portion of `auth.test` response.
+#[derive(Debug, Deserialize)]
+struct AuthTestData {
+    user_id: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct SlackChannel {
     id: String,
@@ -96,9 +129,11 @@ struct SlackFile {
 // ── Client ─────────────────────────────────────────────────────────
 
 /// Slack messaging provider using the Slack Web API.
+#[derive(Clone)]
 pub struct SlackProvider {
     base_url: String,
     token: String,
+    app_token: Option<String>,
     client: Client,
 }
 
@@ -129,10 +164,18 @@ impl SlackProvider {
         Ok(Self {
             base_url,
             token: bot_token.to_string(),
+            app_token: None,
             client,
         })
     }
 
+    /// Set the app-level token (`xapp-…`, scope `connections:write`) that
+    /// Socket Mode connects with. Required for [`MessagingProvider::event_stream`].
+    pub fn with_app_token(mut self, app_token: &str) -> Self {
+        self.app_token = Some(app_token.to_string());
+        self
+    }
+
     /// Return the configured base URL.
     pub fn base_url(&self) -> &str {
         &self.base_url
@@ -180,20 +223,22 @@ impl SlackProvider {
             platform: Platform::Slack,
         }
     }
-}
 
-#[async_trait]
-impl MessagingProvider for SlackProvider {
-    fn platform(&self) -> Platform {
-        Platform::Slack
-    }
-
-    async fn send_message(&self, channel: &str, text: &str) -> Result<SentMessage> {
+    /// Post a message, optionally as a reply in the thread rooted at `thread_ts`.
+    async fn post_message(
+        &self,
+        channel: &str,
+        text: &str,
+        thread_ts: Option<&str>,
+    ) -> Result<SentMessage> {
         let url = format!("{}/chat.postMessage", self.base_url);
-        let payload = serde_json::json!({
+        let mut payload = serde_json::json!({
             "channel": channel,
             "text": text,
         });
+        if let Some(thread_ts) = thread_ts {
+            payload["thread_ts"] = thread_ts.into();
+        }
 
         debug!(url = %url, channel = %channel, "sending Slack message");
 
@@ -235,6 +280,336 @@ impl MessagingProvider for SlackProvider {
         })
     }
 
+    /// POST a JSON payload to a Web API method with the bot token.
+    async fn call_json<T: for<'de> Deserialize<'de>>(
+        &self,
+        method: &str,
+        payload: &Value,
+    ) -> Result<T> {
+        let url = format!("{}/{}", self.base_url, method);
+        debug!(url = %url, "calling Slack API");
+
+        let resp = self
+            .client
+            .post(&url)
+            .json(payload)
+            .send()
+            .await
+            .with_context(|| format!("Slack {method} request failed"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Slack API HTTP error ({}): {}", status, body);
+        }
+
+        let envelope: SlackResponse<T> = resp
+            .json()
+            .await
+            .with_context(|| format!("failed to parse Slack {method} response"))?;
+
+        if !envelope.ok {
+            anyhow::bail!(
+                "Slack API error: {}",
+                envelope.error.unwrap_or_else(|| "unknown".into())
+            );
+        }
+        envelope
+            .data
+            .with_context(|| format!("Slack {method} returned no data"))
+    }
+
+    // ── Files ──────────────────────────────────────────────────────
+
+    /// Upload a file with Slack's external upload flow: reserve an upload
+    /// URL, send the bytes there, then share the file to the channel.
+    ///
+    /// Slack posts the file message asynchronously, so the returned id is
+    /// the file id rather than a message timestamp.
+    async fn upload(
+        &self,
+        channel: &str,
+        thread_ts: Option<&str>,
+        file: &FileUpload,
+    ) -> Result<SentMessage> {
+        let url = format!("{}/files.getUploadURLExternal", self.base_url);
+        let length = file.data.len().to_string();
+        let resp = self
+            .client
+            .post(&url)
+            .form(&[("filename", file.name.as_str()), ("length", length.as_str())])
+            .send()
+            .await
+            .context("Slack files.getUploadURLExternal request failed")?;
+        let envelope: SlackResponse<UploadUrlData> = resp
+            .json()
+            .await
+            .context("failed to parse Slack files.getUploadURLExternal response")?;
+        if !envelope.ok {
+            anyhow::bail!(
+                "Slack API error: {}",
+                envelope.error.unwrap_or_else(|| "unknown".into())
+            );
+        }
+        let target = envelope
+            .data
+            .context("Slack files.getUploadURLExternal returned no upload URL")?;
+
+        debug!(file = %file.name, bytes = file.data.len(), "uploading file to Slack");
+
+        let resp = self
+            .client
+            .post(&target.upload_url)
+            .header(CONTENT_TYPE, file.mime_type.as_str())
+            .body(file.data.clone())
+            .send()
+            .await
+            .context("Slack file upload failed")?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Slack file upload HTTP error ({}): {}", status, body);
+        }
+
+        let mut payload = serde_json::json!({
+            "files": [{ "id": target.file_id, "title": file.name }],
+            "channel_id": channel,
+        });
+        if let Some(thread_ts) = thread_ts {
+            payload["thread_ts"] = thread_ts.into();
+        }
+        if let Some(caption) = &file.caption {
+            payload["initial_comment"] = caption.as_str().into();
+        }
+        let _: Value = self
+            .call_json("files.completeUploadExternal", &payload)
+            .await?;
+
+        Ok(SentMessage {
+            id: target.file_id,
+            channel_id: channel.to_string(),
+            timestamp: Utc::now(),
+        })
+    }
+
+    // ── Socket Mode ────────────────────────────────────────────────
+
+    /// Call a Web API method with a specific token (the app-level token
+    /// for Socket Mode).
+    async fn call_with_token<T: for<'de> Deserialize<'de>>(
+        &self,
+        method: &str,
+        app_token: &str,
+    ) -> Result<T> {
+        let url = format!("{}/{}", self.base_url, method);
+        let resp = self
+            .client
+            .post(&url)
+            .bearer_auth(app_token)
+            .send()
+            .await
+            .with_context(|| format!("Slack {method} request failed"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Slack API HTTP error ({}): {}", status, body);
+        }
+
+        let envelope: SlackResponse<T> = resp
+            .json()
+            .await
+            .with_context(|| format!("failed to parse Slack {method} response"))?;
+
+        if !envelope.ok {
+            anyhow::bail!(
+                "Slack API error: {}",
+                envelope.error.unwrap_or_else(|| "unknown".into())
+            );
+        }
+        envelope
+            .data
+            .with_context(|| format!("Slack {method} returned no data"))
+    }
+
+    /// The bot's own user id, used to spot mentions.
+    async fn bot_user_id(&self) -> Result<String> {
+        let data: AuthTestData = self.call_with_token("auth.test", &self.token).await?;
+        data.user_id.context("Slack auth.test returned no user id")
+    }
+
+    /// Keep a Socket Mode connection open, reconnecting when Slack asks to
+    /// or the connection drops, until the stream is dropped.
+    async fn run_socket(self, app_token: String, tx: mpsc::Sender<MessageEvent>) {
+        let bot_user_id = match self.bot_user_id().await {
+            Ok(id) => Some(id),
+            Err(e) => {
+                warn!("Slack auth.test failed, relying on event authorizations: {e:#}");
+                None
+            }
+        };
+        let mut seen = SeenMessages::default();
+        while !tx.is_closed() {
+            match self
+                .socket_session(&app_token, bot_user_id.as_deref(), &mut seen, &tx)
+                .await
+            {
+                Ok(()) => debug!("Slack Socket Mode connection closed, reconnecting"),
+                Err(e) => {
+                    warn!("Slack Socket Mode connection failed: {e:#}");
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            }
+        }
+    }
+
+    /// One Socket Mode connection: acknowledge every envelope and forward
+    /// message events. Returns `Ok` when Slack asks for a reconnect.
+    async fn socket_session(
+        &self,
+        app_token: &str,
+        bot_user_id: Option<&str>,
+        seen: &mut SeenMessages,
+        tx: &mpsc::Sender<MessageEvent>,
+    ) -> Result<()> {
+        let open: ConnectionsOpenData = self
+            .call_with_token("apps.connections.open", app_token)
+            .await?;
+        let url = open
+            .url
+            .context("Slack apps.connections.open returned no URL")?;
+        let (ws, _) = connect_async(url.as_str())
+            .await
+            .context("Slack Socket Mode connect failed")?;
+        let (mut sink, mut source) = ws.split();
+
+        while let Some(frame) = source.next().await {
+            let text = match frame.context("Slack Socket Mode read failed")? {
+                Message::Text(text) => text,
+                Message::Close(_) => break,
+                _ => continue,
+            };
+            let envelope: Value = match serde_json::from_str(&text) {
+                Ok(envelope) => envelope,
+                Err(e) => {
+                    warn!("Ignoring malformed Slack Socket Mode frame: {e}");
+                    continue;
+                }
+            };
+            if let Some(envelope_id) = envelope["envelope_id"].as_str() {
+                let ack = serde_json::json!({ "envelope_id": envelope_id }).to_string();
+                sink.send(Message::Text(ack.into()))
+                    .await
+                    .context("Slack Socket Mode ack failed")?;
+            }
+            match envelope["type"].as_str() {
+                Some("hello") => debug!("Slack Socket Mode connected"),
+                Some("disconnect") => {
+                    debug!(reason = ?envelope["reason"].as_str(), "Slack requested a reconnect");
+                    break;
+                }
+                Some("events_api") => {
+                    let payload = &envelope["payload"];
+                    let bot = payload["authorizations"][0]["user_id"]
+                        .as_str()
+                        .or(bot_user_id);
+                    if let Some(event) = self.socket_event(&payload["event"], bot)
+                        && seen.insert(&event.message.channel_id, &event.message.id)
+                        && tx.send(event).await.is_err()
+                    {
+                        return Ok(());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Convert an Events API event into a [`MessageEvent`]. Edits, joins,
+    /// other bots and the bot's own messages are skipped.
+    fn socket_event(&self, event: &Value, bot_user_id: Option<&str>) -> Option<MessageEvent> {
+        let kind = event["type"].as_str()?;
+        if !matches!(kind, "message" | "app_mention")
+            || event.get("bot_id").is_some()
+            || !matches!(
+                event["subtype"].as_str(),
+                None | Some("file_share") | Some("thread_broadcast")
+            )
+        {
+            return None;
+        }
+        let msg: SlackMessage = serde_json::from_value(event.clone()).ok()?;
+        if msg.user.is_empty() || Some(msg.user.as_str()) == bot_user_id {
+            return None;
+        }
+        let channel = msg.channel.clone()?;
+
+        let mention = bot_user_id.map(|id| format!("<@{id}>"));
+        let mentions_bot =
+            kind == "app_mention" || mention.as_ref().is_some_and(|m| msg.text.contains(m));
+        let text = match &mention {
+            Some(mention) => msg.text.replace(mention, ""),
+            None if kind == "app_mention" => strip_leading_mention(&msg.text).to_string(),
+            None => msg.text.clone(),
+        };
+        let thread_ts = event["thread_ts"].as_str().unwrap_or(&msg.ts).to_string();
+
+        Some(MessageEvent {
+            message: self.convert_message(&msg, &channel),
+            thread_id: Some(thread_ts),
+            is_direct: event["channel_type"].as_str() == Some("im"),
+            mentions_bot,
+            text: text.trim().to_string(),
+        })
+    }
+}
+
+/// Drop a leading `<@U…>` mention token.
+fn strip_leading_mention(text: &str) -> &str {
+    let trimmed = text.trim_start();
+    if trimmed.starts_with("<@")
+        && let Some(end) = trimmed.find('>')
+    {
+        return &trimmed[end + 1..];
+    }
+    text
+}
+
+/// Bounded set of recently forwarded `(channel, ts)` pairs.
+#[derive(Default)]
+struct SeenMessages {
+    order: VecDeque<String>,
+    ids: HashSet<String>,
+}
+
+impl SeenMessages {
+    /// Record a message. Returns `false` if it was already seen.
+    fn insert(&mut self, channel: &str, ts: &str) -> bool {
+        let key = format!("{channel}:{ts}");
+        if !self.ids.insert(key.clone()) {
+            return false;
+        }
+        self.order.push_back(key);
+        if self.order.len() > SEEN_CAPACITY
+            && let Some(oldest) = self.order.pop_front()
+        {
+            self.ids.remove(&oldest);
+        }
+        true
+    }
+}
+
+#[async_trait]
+impl MessagingProvider for SlackProvider {
+    fn platform(&self) -> Platform {
+        Platform::Slack
+    }
+
+    async fn send_message(&self, channel: &str, text: &str) -> Result<SentMessage> {
+        self.post_message(channel, text, None).await
+    }
+
     async fn list_channels(&self) -> Result<Vec<Channel>> {
         let url = format!(
             "{}/conversations.list?types=public_channel,private_channel&limit=200",
@@ -410,6 +785,64 @@ impl MessagingProvider for SlackProvider {
             .map(|m| self.convert_message(m, ""))
             .collect())
     }
+
+    async fn event_stream(&self) -> Result<MessageStream> {
+        let app_token = self
+            .app_token
+            .clone()
+            .context("Slack Socket Mode needs an app-level token (see with_app_token)")?;
+        let provider = self.clone();
+        Ok(MessageStream::spawn(move |tx| {
+            provider.run_socket(app_token, tx)
+        }))
+    }
+
+    async fn reply_in_thread(
+        &self,
+        channel: &str,
+        thread_id: &str,
+        text: &str,
+    ) -> Result<SentMessage> {
+        self.post_message(channel, text, Some(thread_id)).await
+    }
+
+    async fn send_rich(
+        &self,
+        channel: &str,
+        thread_id: Option<&str>,
+        text: &RichText,
+    ) -> Result<SentMessage> {
+        self.post_message(channel, &text.to_mrkdwn(), thread_id)
+            .await
+    }
+
+    async fn upload_file(
+        &self,
+        channel: &str,
+        thread_id: Option<&str>,
+        file: &FileUpload,
+    ) -> Result<SentMessage> {
+        self.upload(channel, thread_id, file).await
+    }
+
+    async fn edit_message(&self, channel: &str, message_id: &str, text: &RichText) -> Result<()> {
+        let payload = serde_json::json!({
+            "channel": channel,
+            "ts": message_id,
+            "text": text.to_mrkdwn(),
+        });
+        let _: Value = self.call_json("chat.update", &payload).await?;
+        Ok(())
+    }
+
+    async fn delete_message(&self, channel: &str, message_id: &str) -> Result<()> {
+        let payload = serde_json::json!({
+            "channel": channel,
+            "ts": message_id,
+        });
+        let _: Value = self.call_json("chat.delete", &payload).await?;
+        Ok(())
+    }
 }
 
 /// Minimal percent-encoding for query parameter values.
@@ -625,4 +1058,246 @@ mod tests {
         assert_eq!(msg.attachments.len(), 1);
         assert_eq!(msg.attachments[0].name, "doc.pdf");
     }
+
+    fn event(json: serde_json::Value) -> Option<MessageEvent> {
+        SlackProvider::new("xoxb-tok")
+            .unwrap()
+            .socket_event(&json, Some("UBOT"))
+    }
+
+    #[test]
+    fn test_socket_event_mention_in_channel() {
+        let ev = event(serde_json::json!({
+            "type": "app_mention",
+            "user": "U01",
+            "text": "<@UBOT> deploy status?",
+            "ts": "1609459200.000100",
+            "channel": "C01",
+            "channel_type": "channel",
+        }))
+        .unwrap();
+        assert!(ev.mentions_bot);
+        assert!(!ev.is_direct);
+        assert_eq!(ev.text, "deploy status?");
+        assert_eq!(ev.thread_id.as_deref(), Some("1609459200.000100"));
+        assert_eq!(ev.message.channel_id, "C01");
+    }
+
+    #[test]
+    fn test_socket_event_direct_message_in_thread() {
+        let ev = event(serde_json::json!({
+            "type": "message",
+            "user": "U01",
+            "text": "hello",
+            "ts": "1609459300.000200",
+            "thread_ts": "1609459200.000100",
+            "channel": "D01",
+            "channel_type": "im",
+        }))
+        .unwrap();
+        assert!(ev.is_direct);
+        assert!(!ev.mentions_bot);
+        assert_eq!(ev.thread_id.as_deref(), Some("1609459200.000100"));
+    }
+
+    #[test]
+    fn test_socket_event_skips_edits_bots_and_self() {
+        let base = serde_json::json!({
+            "type": "message", "user": "U01", "text": "x", "ts": "1.0", "channel": "C01",
+        });
+        let mut edit = base.clone();
+        edit["subtype"] = "message_changed".into();
+        assert!(event(edit).is_none());
+        let mut bot = base.clone();
+        bot["bot_id"] = "B01".into();
+        assert!(event(bot).is_none());
+        let mut own = base.clone();
+        own["user"] = "UBOT".into();
+        assert!(event(own).is_none());
+        let mut reaction = base;
+        reaction["type"] = "reaction_added".into();
+        assert!(event(reaction).is_none());
+    }
+
+    #[test]
+    fn test_seen_messages_deduplicates_and_is_bounded() {
+        let mut seen = SeenMessages::default();
+        assert!(seen.insert("C1", "1.0"));
+        assert!(!seen.insert("C1", "1.0"));
+        assert!(seen.insert("C2", "1.0"));
+        for i in 0..SEEN_CAPACITY {
+            seen.insert("C3", &i.to_string());
+        }
+        assert!(seen.insert("C1", "1.0"));
+    }
+
+    #[tokio::test]
+    async fn test_event_stream_requires_app_token() {
+        let provider = SlackProvider::new("xoxb-tok").unwrap();
+        let err = provider.event_stream().await.err().unwrap();
+        assert!(err.to_string().contains("app-level token"));
+    }
+
+    #[tokio::test]
+    async fn test_socket_mode_stream_acks_and_deduplicates() {
+        use super::super::test_support::{http_stub, ws_stub};
+
+        let (ws_url, mut sockets) = ws_stub().await;
+        let (base_url, requests) = http_stub(move |req| {
+            let body = match req.path.as_str() {
+                "/auth.test" => r#"{"ok":true,"user_id":"UBOT"}"#.to_string(),
+                "/apps.connections.open" => format!(r#"{{"ok":true,"url":"{ws_url}"}}"#),
+                _ => r#"{"ok":true,"ts":"1609459400.000300","channel":"C01"}"#.to_string(),
+            };
+            (200, body)
+        })
+        .await;
+
+        let provider = SlackProvider::with_base_url("xoxb-tok", &base_url)
+            .unwrap()
+            .with_app_token("xapp-tok");
+        let mut stream = provider.event_stream().await.unwrap();
+        let mut socket = sockets.recv().await.unwrap();
+
+        let envelope = |id: &str, kind: &str, ts: &str| {
+            serde_json::json!({
+                "envelope_id": id,
+                "type": "events_api",
+                "payload": {"event": {
+                    "type": kind, "user": "U01", "text": "<@UBOT> ping",
+                    "ts": ts, "channel": "C01", "channel_type": "channel",
+                }},
+            })
+            .to_string()
+        };
+        socket
+            .send(Message::Text(r#"{"type":"hello"}"#.into()))
+            .await
+            .unwrap();
+        for (id, kind, ts) in [
+            ("e1", "message", "1.0"),
+            ("e2", "app_mention", "1.0"),
+            ("e3", "app_mention", "2.0"),
+        ] {
+            socket
+                .send(Message::Text(envelope(id, kind, ts).into()))
+                .await
+                .unwrap();
+        }
+
+        let first = stream.next().await.unwrap();
+        assert_eq!(first.message.id, "1.0");
+        assert_eq!(first.text, "ping");
+        assert!(first.mentions_bot);
+        assert_eq!(stream.next().await.unwrap().message.id, "2.0");
+
+        let mut acks = Vec::new();
+        while acks.len() < 3 {
+            if let Some(Ok(Message::Text(text))) = socket.next().await {
+                let ack: Value = serde_json::from_str(&text).unwrap();
+                acks.push(ack["envelope_id"].as_str().unwrap().to_string());
+            }
+        }
+        assert_eq!(acks, ["e1", "e2", "e3"]);
+
+        provider
+            .reply_in_thread("C01", "1.0", "pong")
+            .await
+            .unwrap();
+        let requests = requests.lock().unwrap();
+        let post = requests
+            .iter()
+            .find(|r| r.path == "/chat.postMessage")
+            .unwrap();
+        assert_eq!(post.method, "POST");
+        assert_eq!(post.json()["thread_ts"], "1.0");
+        assert_eq!(post.json()["text"], "pong");
+    }
+
+    #[tokio::test]
+    async fn test_rich_text_edit_and_delete() {
+        use super::super::rich_text::Span;
+        use super::super::test_support::http_stub;
+
+        let (base_url, requests) = http_stub(|_| {
+            (
+                200,
+                r#"{"ok":true,"ts":"1.5","channel":"C01"}"#.to_string(),
+            )
+        })
+        .await;
+        let provider = SlackProvider::with_base_url("xoxb-tok", &base_url).unwrap();
+        let text = RichText::new()
+            .paragraph(vec![Span::text("Build "), Span::bold("passed")])
+            .code_block(Some("sh"), "cargo test");
+
+        let sent = provider
+            .send_rich("C01", Some("1.0"), &text)
+            .await
+            .unwrap();
+        assert_eq!(sent.id, "1.5");
+        provider
+            .edit_message("C01", "1.5", &RichText::plain("Build failed"))
+            .await
+            .unwrap();
+        provider.delete_message("C01", "1.5").await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].path, "/chat.postMessage");
+        assert_eq!(
+            requests[0].json()["text"],
+            "Build *passed*\n\n```\ncargo test\n```"
+        );
+        assert_eq!(requests[0].json()["thread_ts"], "1.0");
+        assert_eq!(requests[1].path, "/chat.update");
+        assert_eq!(requests[1].json()["ts"], "1.5");
+        assert_eq!(requests[1].json()["text"], "Build failed");
+        assert_eq!(requests[2].path, "/chat.delete");
+        assert_eq!(requests[2].json()["channel"], "C01");
+    }
+
+    #[tokio::test]
+    async fn test_upload_file_uses_external_upload_flow() {
+        use super::super::test_support::http_stub;
+        use std::sync::{Arc, Mutex};
+
+        let base = Arc::new(Mutex::new(String::new()));
+        let stub_base = Arc::clone(&base);
+        let (base_url, requests) = http_stub(move |req| {
+            let body = if req.path == "/files.getUploadURLExternal" {
+                format!(
+                    r#"{{"ok":true,"upload_url":"{}/upload/F01","file_id":"F01"}}"#,
+                    stub_base.lock().unwrap()
+                )
+            } else if req.path == "/upload/F01" {
+                "OK".to_string()
+            } else {
+                r#"{"ok":true,"files":[{"id":"F01"}]}"#.to_string()
+            };
+            (200, body)
+        })
+        .await;
+        *base.lock().unwrap() = base_url.clone();
+
+        let provider = SlackProvider::with_base_url("xoxb-tok", &base_url).unwrap();
+        let file = FileUpload::new("report.csv", "text/csv", b"a,b\n1,2\n".to_vec())
+            .with_caption("Weekly report");
+        let sent = provider
+            .upload_file("C01", Some("1.0"), &file)
+            .await
+            .unwrap();
+        assert_eq!(sent.id, "F01");
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].body, "filename=report.csv&length=8");
+        assert_eq!(requests[1].path, "/upload/F01");
+        assert_eq!(requests[1].header("content-type"), Some("text/csv"));
+        assert_eq!(requests[1].body, "a,b\n1,2\n");
+        let complete = requests[2].json();
+        assert_eq!(requests[2].path, "/files.completeUploadExternal");
+        assert_eq!(complete["files"][0]["id"], "F01");
+        assert_eq!(complete["channel_id"], "C01");
+        assert_eq!(complete["thread_ts"], "1.0");
+        assert_eq!(complete["initial_comment"], "Weekly report");
+    }
 }
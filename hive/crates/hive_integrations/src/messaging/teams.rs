@@ -2,7 +2,9 @@
 //!
 //! Wraps the Microsoft Graph API at `https://graph.microsoft.com/v1.0`
 //! for Teams bot messaging using `reqwest` for HTTP and bearer-token
-//! authentication.
+//! authentication. Real-time events arrive as Bot Framework activities
+//! posted to the bot's messaging endpoint and handed to
+//! [`TeamsProvider::receive_activity`].
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
@@ -10,11 +12,14 @@ use chrono::{DateTime, Utc};
 use reqwest::Client;
 use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue};
 use serde::Deserialize;
+use serde_json::Value;
 use tracing::debug;
 
 use super::provider::{
-    Attachment, Channel, IncomingMessage, MessagingProvider, Platform, SentMessage,
+    Attachment, Channel, EventFeed, FileUpload, IncomingMessage, MessageEvent, MessageStream,
+    MessagingProvider, Platform, SentMessage,
 };
+use super::rich_text::RichText;
 
 const DEFAULT_BASE_URL: &str = "https://graph.microsoft.com/v1.0";
 
@@ -86,6 +91,26 @@ struct TeamsSentMessage {
     created_date_time: Option<String>,
 }
 
+/// A SharePoint drive item (channel files folder or an uploaded file).
+#[derive(Debug, Deserialize)]
+struct DriveItem {
+    id: String,
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "webUrl", default)]
+    web_url: String,
+    #[serde(rename = "eTag", default)]
+    e_tag: String,
+    #[serde(rename = "parentReference")]
+    parent_reference: Option<DriveReference>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DriveReference {
+    #[serde(rename = "driveId")]
+    drive_id: String,
+}
+
 // ── Client ─────────────────────────────────────────────────────────
 
 /// Microsoft Teams messaging provider using the Microsoft Graph API.
@@ -94,6 +119,7 @@ pub struct TeamsProvider {
     token: String,
     team_id: String,
     client: Client,
+    events: EventFeed,
 }
 
 impl TeamsProvider {
@@ -122,6 +148,7 @@ impl TeamsProvider {
             token: access_token.to_string(),
             team_id: team_id.to_string(),
             client,
+            events: EventFeed::new(),
         })
     }
 
@@ -184,31 +211,102 @@ impl TeamsProvider {
             platform: Platform::Teams,
         }
     }
-}
 
-#[async_trait]
-impl MessagingProvider for TeamsProvider {
-    fn platform(&self) -> Platform {
-        Platform::Teams
+    /// Hand over a Bot Framework activity received on the bot's messaging
+    /// endpoint. Message activities are delivered to open event streams;
+    /// returns whether the activity was one.
+    pub fn receive_activity(&self, activity: &Value) -> bool {
+        match self.activity_event(activity) {
+            Some(event) => {
+                self.events.publish(event);
+                true
+            }
+            None => false,
+        }
     }
 
-    async fn send_message(&self, channel: &str, text: &str) -> Result<SentMessage> {
+    /// Convert a `message` activity into a [`MessageEvent`]. The activity's
+    /// `recipient` is the bot, so mentions are matched against its id.
+    fn activity_event(&self, activity: &Value) -> Option<MessageEvent> {
+        if activity["type"] != "message" {
+            return None;
+        }
+        let id = activity["id"].as_str()?.to_string();
+        let conversation = activity["conversation"]["id"].as_str()?;
+        let bot_id = activity["recipient"]["id"].as_str();
+        if activity["from"]["id"].as_str() == bot_id {
+            return None;
+        }
+        let channel_id = activity["channelData"]["channel"]["id"]
+            .as_str()
+            .unwrap_or_else(|| conversation.split(';').next().unwrap_or(conversation))
+            .to_string();
+
+        let mut text = activity["text"].as_str().unwrap_or_default().to_string();
+        let mut mentions_bot = false;
+        let entities = activity["entities"].as_array();
+        for entity in entities.into_iter().flatten() {
+            if entity["type"] == "mention" && entity["mentioned"]["id"].as_str() == bot_id {
+                mentions_bot = true;
+                if let Some(tag) = entity["text"].as_str() {
+                    text = text.replace(tag, "");
+                }
+            }
+        }
+
+        // Channel conversations name their thread root after `;messageid=`;
+        // anything else starts a new thread at this message.
+        let thread_id = conversation
+            .split_once(";messageid=")
+            .map_or(id.as_str(), |(_, root)| root)
+            .to_string();
+
+        let content = activity["text"].as_str().unwrap_or_default().to_string();
+        let timestamp = activity["timestamp"]
+            .as_str()
+            .and_then(|s| s.parse::<DateTime<Utc>>().ok())
+            .unwrap_or_else(Utc::now);
+
+        Some(MessageEvent {
+            message: IncomingMessage {
+                id,
+                channel_id,
+                author: activity["from"]["name"]
+                    .as_str()
+                    .unwrap_or("unknown")
+                    .to_string(),
+                content,
+                timestamp,
+                attachments: Vec::new(),
+                platform: Platform::Teams,
+            },
+            thread_id: Some(thread_id),
+            is_direct: activity["conversation"]["conversationType"] == "personal",
+            mentions_bot,
+            text: text.trim().to_string(),
+        })
+    }
+
+    /// Messages collection for a channel, or for the replies of `thread_id`.
+    fn messages_url(&self, channel: &str, thread_id: Option<&str>) -> String {
         let url = format!(
             "{}/teams/{}/channels/{}/messages",
             self.base_url, self.team_id, channel
         );
-        let payload = serde_json::json!({
-            "body": {
-                "content": text,
-            },
-        });
+        match thread_id {
+            Some(thread_id) => format!("{url}/{thread_id}/replies"),
+            None => url,
+        }
+    }
 
+    /// Post a message payload to a Graph messages collection URL.
+    async fn post_message(&self, url: &str, channel: &str, payload: &Value) -> Result<SentMessage> {
         debug!(url = %url, channel = %channel, "sending Teams message");
 
         let resp = self
             .client
-            .post(&url)
-            .json(&payload)
+            .post(url)
+            .json(payload)
             .send()
             .await
             .context("Teams send message request failed")?;
@@ -237,6 +335,118 @@ impl MessagingProvider for TeamsProvider {
         })
     }
 
+    /// Upload a file into the channel's SharePoint files folder.
+    async fn upload_to_files_folder(&self, channel: &str, file: &FileUpload) -> Result<DriveItem> {
+        let url = format!(
+            "{}/teams/{}/channels/{}/filesFolder",
+            self.base_url, self.team_id, channel
+        );
+        let folder: DriveItem = self.get_json(&url, "files folder").await?;
+        let drive_id = folder
+            .parent_reference
+            .map(|r| r.drive_id)
+            .context("Teams files folder has no drive reference")?;
+
+        let url = format!(
+            "{}/drives/{}/items/{}:/{}:/content",
+            self.base_url,
+            drive_id,
+            folder.id,
+            urlencod(&file.name)
+        );
+
+        debug!(url = %url, file = %file.name, "uploading Teams channel file");
+
+        let resp = self
+            .client
+            .put(&url)
+            .header(CONTENT_TYPE, file.mime_type.as_str())
+            .body(file.data.clone())
+            .send()
+            .await
+            .context("Teams file upload request failed")?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Teams API HTTP error ({}): {}", status, body);
+        }
+
+        resp.json()
+            .await
+            .context("failed to parse Teams file upload response")
+    }
+
+    /// GET a Graph URL and decode the JSON response.
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str, what: &str) -> Result<T> {
+        debug!(url = %url, "fetching Teams {what}");
+
+        let resp = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .with_context(|| format!("Teams {what} request failed"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Teams API HTTP error ({}): {}", status, body);
+        }
+
+        resp.json()
+            .await
+            .with_context(|| format!("failed to parse Teams {what} response"))
+    }
+
+    /// Send a request that returns no useful body.
+    async fn send_empty(&self, req: reqwest::RequestBuilder, what: &str) -> Result<()> {
+        let resp = req
+            .send()
+            .await
+            .with_context(|| format!("Teams {what} request failed"))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Teams API HTTP error ({}): {}", status, body);
+        }
+        Ok(())
+    }
+}
+
+/// Message payload with an HTML body.
+fn html_payload(content: String) -> Value {
+    serde_json::json!({
+        "body": {
+            "contentType": "html",
+            "content": content,
+        },
+    })
+}
+
+/// Attachment id Teams expects for a file reference: the GUID inside the
+/// drive item's eTag (`"{GUID},1"`).
+fn reference_id(item: &DriveItem) -> String {
+    item.e_tag
+        .split(['{', '}'])
+        .nth(1)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+}
+
+#[async_trait]
+impl MessagingProvider for TeamsProvider {
+    fn platform(&self) -> Platform {
+        Platform::Teams
+    }
+
+    async fn send_message(&self, channel: &str, text: &str) -> Result<SentMessage> {
+        let url = self.messages_url(channel, None);
+        self.post_message(&url, channel, &text_payload(text)).await
+    }
+
     async fn list_channels(&self) -> Result<Vec<Channel>> {
         let url = format!("{}/teams/{}/channels", self.base_url, self.team_id);
 
@@ -409,6 +619,88 @@ impl MessagingProvider for TeamsProvider {
 
         Ok(results)
     }
+
+    async fn event_stream(&self) -> Result<MessageStream> {
+        Ok(self.events.stream())
+    }
+
+    async fn reply_in_thread(
+        &self,
+        channel: &str,
+        thread_id: &str,
+        text: &str,
+    ) -> Result<SentMessage> {
+        let url = self.messages_url(channel, Some(thread_id));
+        self.post_message(&url, channel, &text_payload(text)).await
+    }
+
+    async fn send_rich(
+        &self,
+        channel: &str,
+        thread_id: Option<&str>,
+        text: &RichText,
+    ) -> Result<SentMessage> {
+        let url = self.messages_url(channel, thread_id);
+        self.post_message(&url, channel, &html_payload(text.to_html()))
+            .await
+    }
+
+    /// Files are stored in the channel's SharePoint folder and posted as a
+    /// reference attachment.
+    async fn upload_file(
+        &self,
+        channel: &str,
+        thread_id: Option<&str>,
+        file: &FileUpload,
+    ) -> Result<SentMessage> {
+        let item = self.upload_to_files_folder(channel, file).await?;
+        let attachment_id = reference_id(&item);
+
+        let caption = file
+            .caption
+            .as_deref()
+            .map(|c| RichText::plain(c).to_html())
+            .unwrap_or_default();
+        let mut payload = html_payload(format!(
+            "{caption}<attachment id=\"{attachment_id}\"></attachment>"
+        ));
+        payload["attachments"] = serde_json::json!([{
+            "id": attachment_id,
+            "contentType": "reference",
+            "contentUrl": item.web_url,
+            "name": if item.name.is_empty() { &file.name } else { &item.name },
+        }]);
+
+        let url = self.messages_url(channel, thread_id);
+        self.post_message(&url, channel, &payload).await
+    }
+
+    async fn edit_message(&self, channel: &str, message_id: &str, text: &RichText) -> Result<()> {
+        let url = format!("{}/{}", self.messages_url(channel, None), message_id);
+        debug!(url = %url, message_id = %message_id, "editing Teams message");
+        let req = self.client.patch(&url).json(&html_payload(text.to_html()));
+        self.send_empty(req, "edit message").await
+    }
+
+    async fn delete_message(&self, channel: &str, message_id: &str) -> Result<()> {
+        let url = format!(
+            "{}/{}/softDelete",
+            self.messages_url(channel, None),
+            message_id
+        );
+        debug!(url = %url, message_id = %message_id, "deleting Teams message");
+        self.send_empty(self.client.post(&url), "delete message")
+            .await
+    }
+}
+
+/// Message payload with a plain-text body.
+fn text_payload(text: &str) -> Value {
+    serde_json::json!({
+        "body": {
+            "content": text,
+        },
+    })
 }
 
 /// Minimal percent-encoding for query parameter values.
@@ -654,4 +946,180 @@ mod tests {
         assert_eq!(urlencod("a+b=c"), "a%2Bb%3Dc");
         assert_eq!(urlencod("safe_name.txt"), "safe_name.txt");
     }
+
+    fn activity(conversation: Value, text: &str, entities: Value) -> Value {
+        serde_json::json!({
+            "type": "message",
+            "id": "1700000000002",
+            "timestamp": "2024-11-14T22:13:20Z",
+            "text": text,
+            "from": {"id": "29:alice", "name": "Alice"},
+            "recipient": {"id": "28:hive-bot", "name": "Hive"},
+            "conversation": conversation,
+            "entities": entities,
+        })
+    }
+
+    #[test]
+    fn test_activity_event_channel_mention_in_thread() {
+        let provider = make_provider();
+        let ev = provider
+            .activity_event(&activity(
+                serde_json::json!({
+                    "id": "19:ch-5@thread.tacv2;messageid=1700000000001",
+                    "conversationType": "channel",
+                }),
+                "<at>Hive</at> how is the build?",
+                serde_json::json!([{
+                    "type": "mention",
+                    "text": "<at>Hive</at>",
+                    "mentioned": {"id": "28:hive-bot", "name": "Hive"},
+                }]),
+            ))
+            .unwrap();
+        assert!(ev.mentions_bot);
+        assert!(!ev.is_direct);
+        assert_eq!(ev.text, "how is the build?");
+        assert_eq!(ev.thread_id.as_deref(), Some("1700000000001"));
+        assert_eq!(ev.message.channel_id, "19:ch-5@thread.tacv2");
+        assert_eq!(ev.message.author, "Alice");
+    }
+
+    #[test]
+    fn test_activity_event_personal_and_ignored_activities() {
+        let provider = make_provider();
+        let personal = activity(
+            serde_json::json!({"id": "a:1xyz", "conversationType": "personal"}),
+            "hello",
+            serde_json::json!([]),
+        );
+        let ev = provider.activity_event(&personal).unwrap();
+        assert!(ev.is_direct);
+        assert!(!ev.mentions_bot);
+        assert_eq!(ev.thread_id.as_deref(), Some("1700000000002"));
+
+        let mut typing = personal.clone();
+        typing["type"] = "typing".into();
+        assert!(provider.activity_event(&typing).is_none());
+        let mut own = personal;
+        own["from"]["id"] = "28:hive-bot".into();
+        assert!(!provider.receive_activity(&own));
+    }
+
+    #[tokio::test]
+    async fn test_event_stream_and_threaded_reply() {
+        use super::super::test_support::http_stub;
+
+        let (base_url, requests) =
+            http_stub(|_| (201, r#"{"id":"1700000000003"}"#.to_string())).await;
+        let provider =
+            TeamsProvider::with_base_url("eyJ0eXAi.access-token", "team-123", &base_url).unwrap();
+        let mut stream = provider.event_stream().await.unwrap();
+
+        assert!(provider.receive_activity(&activity(
+            serde_json::json!({"id": "a:1xyz", "conversationType": "personal"}),
+            "status?",
+            serde_json::json!([]),
+        )));
+        let ev = stream.next().await.unwrap();
+        assert_eq!(ev.text, "status?");
+
+        let sent = provider
+            .reply_in_thread("ch-5", "1700000000001", "green")
+            .await
+            .unwrap();
+        assert_eq!(sent.id, "1700000000003");
+        let requests = requests.lock().unwrap();
+        assert_eq!(
+            requests[0].path,
+            "/teams/team-123/channels/ch-5/messages/1700000000001/replies"
+        );
+        assert_eq!(requests[0].json()["body"]["content"], "green");
+    }
+
+    #[tokio::test]
+    async fn test_rich_text_upload_edit_and_delete() {
+        use super::super::test_support::http_stub;
+        use crate::messaging::rich_text::Span;
+
+        let (base_url, requests) = http_stub(|req| {
+            let body = if req.path.ends_with("/filesFolder") {
+                serde_json::json!({"id": "folder-1", "parentReference": {"driveId": "drive-9"}})
+            } else if req.path.starts_with("/drives/") {
+                serde_json::json!({
+                    "id": "item-1",
+                    "name": "report.pdf",
+                    "webUrl": "https://contoso.sharepoint.com/report.pdf",
+                    "eTag": "\"{6A3C1E2B-0000-4C9D-8F00-AB12CD34EF56},1\"",
+                })
+            } else {
+                serde_json::json!({"id": "1700000000009"})
+            };
+            (200, body.to_string())
+        })
+        .await;
+        let provider =
+            TeamsProvider::with_base_url("eyJ0eXAi.access-token", "team-123", &base_url).unwrap();
+
+        let rich = RichText::new().paragraph(vec![Span::text("build "), Span::bold("green")]);
+        provider
+            .send_rich("ch-5", Some("1700000000001"), &rich)
+            .await
+            .unwrap();
+
+        let file = FileUpload::new("report.pdf", "application/pdf", b"%PDF".to_vec())
+            .with_caption("weekly report");
+        let sent = provider.upload_file("ch-5", None, &file).await.unwrap();
+        assert_eq!(sent.id, "1700000000009");
+
+        provider
+            .edit_message("ch-5", "1700000000009", &rich)
+            .await
+            .unwrap();
+        provider
+            .delete_message("ch-5", "1700000000009")
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 6);
+
+        assert_eq!(
+            requests[0].path,
+            "/teams/team-123/channels/ch-5/messages/1700000000001/replies"
+        );
+        let body = &requests[0].json()["body"];
+        assert_eq!(body["contentType"], "html");
+        assert_eq!(body["content"], "<p>build <strong>green</strong></p>");
+
+        assert_eq!(
+            requests[1].path,
+            "/teams/team-123/channels/ch-5/filesFolder"
+        );
+        assert_eq!(requests[2].method, "PUT");
+        assert_eq!(
+            requests[2].path,
+            "/drives/drive-9/items/folder-1:/report.pdf:/content"
+        );
+        assert_eq!(requests[2].body, "%PDF");
+        let message = requests[3].json();
+        let attachment = &message["attachments"][0];
+        assert_eq!(attachment["id"], "6A3C1E2B-0000-4C9D-8F00-AB12CD34EF56");
+        assert_eq!(attachment["contentType"], "reference");
+        assert_eq!(
+            message["body"]["content"],
+            "<p>weekly report</p><attachment id=\"6A3C1E2B-0000-4C9D-8F00-AB12CD34EF56\"></attachment>"
+        );
+
+        assert_eq!(requests[4].method, "PATCH");
+        assert_eq!(
+            requests[4].path,
+            "/teams/team-123/channels/ch-5/messages/1700000000009"
+        );
+        assert_eq!(requests[5].method, "POST");
+        assert_eq!(
+            requests[5].path,
+            "/teams/team-123/channels/ch-5/messages/1700000000009/softDelete"
+        );
+    }
 }
@@ -1,22 +1,32 @@
 //! Telegram messaging provider.
 //!
 //! Wraps the Telegram Bot API at `https://api.telegram.org/bot{token}/`
-//! using `reqwest` for HTTP and bot-token authentication.
+//! using `reqwest` for HTTP and bot-token authentication. Real-time events
+//! come from long-polling `getUpdates`.
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, TimeZone, Utc};
 use reqwest::Client;
+use reqwest::Response;
 use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
+use reqwest::multipart::{Form, Part};
 use serde::Deserialize;
-use tracing::debug;
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
 
 use super::provider::{
-    Attachment, Channel, IncomingMessage, MessagingProvider, Platform, SentMessage,
+    Attachment, Channel, FileUpload, IncomingMessage, MessageEvent, MessageStream,
+    MessagingProvider, Platform, RECONNECT_DELAY, SentMessage,
 };
+use super::rich_text::RichText;
 
 const DEFAULT_BASE_URL: &str = "https://api.telegram.org";
 
+/// Seconds each `getUpdates` call waits for new updates.
+const LONG_POLL_SECS: u64 = 30;
+
 // ── Telegram API response types ──────────────────────────────────
 
 /// Envelope returned by Telegram Bot API methods.
@@ -33,7 +43,6 @@ struct TelegramChat {
     id: i64,
     title: Option<String>,
     #[serde(rename = "type")]
-    #[allow(dead_code)]
     chat_type: String,
     username: Option<String>,
 }
@@ -41,7 +50,6 @@ struct TelegramChat {
 /// A Telegram user object.
 #[derive(Debug, Deserialize)]
 struct TelegramUser {
-    #[allow(dead_code)]
     id: i64,
     first_name: String,
     username: Option<String>,
@@ -58,6 +66,8 @@ struct TelegramMessage {
     text: Option<String>,
     #[serde(default)]
     document: Option<TelegramDocument>,
+    #[serde(default)]
+    reply_to_message: Option<Box<TelegramMessage>>,
 }
 
 /// A Telegram document attachment.
@@ -72,14 +82,14 @@ struct TelegramDocument {
 /// An update from `getUpdates`.
 #[derive(Debug, Deserialize)]
 struct TelegramUpdate {
-    #[serde(rename = "update_id")]
-    _update_id: i64,
+    update_id: i64,
     message: Option<TelegramMessage>,
 }
 
 // ── Client ─────────────────────────────────────────────────────────
 
 /// Telegram messaging provider using the Telegram Bot API.
+#[derive(Clone)]
 pub struct TelegramProvider {
     base_url: String,
     token: String,
@@ -126,6 +136,20 @@ impl TelegramProvider {
         format!("{}/bot{}/{}", self.base_url, self.token, method)
     }
 
+    /// Parse a message id passed as a string.
+    fn message_id(id: &str) -> Result<i64> {
+        id.parse()
+            .with_context(|| format!("invalid Telegram message id: {id}"))
+    }
+
+    fn sent(msg: &TelegramMessage) -> SentMessage {
+        SentMessage {
+            id: msg.message_id.to_string(),
+            channel_id: msg.chat.id.to_string(),
+            timestamp: Self::parse_unix_ts(msg.date),
+        }
+    }
+
     /// Parse a Unix timestamp into a `DateTime<Utc>`.
     fn parse_unix_ts(ts: i64) -> DateTime<Utc> {
         Utc.timestamp_opt(ts, 0)
@@ -167,6 +191,147 @@ impl TelegramProvider {
             platform: Platform::Telegram,
         }
     }
+
+    /// Call a Bot API method and unwrap its `result`.
+    async fn call<T: for<'de> Deserialize<'de>>(
+        &self,
+        method: &str,
+        payload: &serde_json::Value,
+    ) -> Result<T> {
+        let url = self.method_url(method);
+        let resp = self
+            .client
+            .post(&url)
+            .json(payload)
+            .send()
+            .await
+            .with_context(|| format!("Telegram {method} request failed"))?;
+        Self::read_result(resp, method).await
+    }
+
+    /// Call a Bot API method with a multipart body (file uploads).
+    async fn call_multipart<T: for<'de> Deserialize<'de>>(
+        &self,
+        method: &str,
+        form: Form,
+    ) -> Result<T> {
+        let url = self.method_url(method);
+        let resp = self
+            .client
+            .post(&url)
+            .multipart(form)
+            .send()
+            .await
+            .with_context(|| format!("Telegram {method} request failed"))?;
+        Self::read_result(resp, method).await
+    }
+
+    /// Check a Bot API response and unwrap its `result`.
+    async fn read_result<T: for<'de> Deserialize<'de>>(resp: Response, method: &str) -> Result<T> {
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Telegram API HTTP error ({}): {}", status, body);
+        }
+
+        let envelope: TelegramResponse<T> = resp
+            .json()
+            .await
+            .with_context(|| format!("failed to parse Telegram {method} response"))?;
+
+        if !envelope.ok {
+            anyhow::bail!(
+                "Telegram API error: {}",
+                envelope.description.unwrap_or_else(|| "unknown".into())
+            );
+        }
+        envelope
+            .result
+            .with_context(|| format!("Telegram {method} returned no result"))
+    }
+
+    // ── Long polling ───────────────────────────────────────────────
+
+    /// Long-poll `getUpdates`, acknowledging each batch through the next
+    /// call's offset, until the stream is dropped.
+    async fn run_long_poll(self, tx: mpsc::Sender<MessageEvent>) {
+        let me = loop {
+            match self
+                .call::<TelegramUser>("getMe", &serde_json::json!({}))
+                .await
+            {
+                Ok(me) => break me,
+                Err(e) => warn!("Telegram getMe failed: {e:#}"),
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+            if tx.is_closed() {
+                return;
+            }
+        };
+
+        let mut offset = 0;
+        while !tx.is_closed() {
+            let payload = serde_json::json!({
+                "offset": offset,
+                "timeout": LONG_POLL_SECS,
+                "allowed_updates": ["message"],
+            });
+            match self
+                .call::<Vec<TelegramUpdate>>("getUpdates", &payload)
+                .await
+            {
+                Ok(updates) => {
+                    for update in updates {
+                        offset = offset.max(update.update_id + 1);
+                        if let Some(event) = update
+                            .message
+                            .as_ref()
+                            .and_then(|m| self.update_event(m, &me))
+                            && tx.send(event).await.is_err()
+                        {
+                            return;
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!("Telegram getUpdates failed: {e:#}");
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            }
+        }
+    }
+
+    /// Convert an update's message into a [`MessageEvent`]. The bot is
+    /// mentioned by `@username` or by replying to one of its messages.
+    fn update_event(&self, msg: &TelegramMessage, me: &TelegramUser) -> Option<MessageEvent> {
+        let from = msg.from.as_ref()?;
+        if from.id == me.id {
+            return None;
+        }
+        let content = msg.text.clone().unwrap_or_default();
+
+        let mut text = content.clone();
+        let mut mentions_bot = msg
+            .reply_to_message
+            .as_ref()
+            .and_then(|r| r.from.as_ref())
+            .is_some_and(|u| u.id == me.id);
+        if let Some(username) = &me.username {
+            let handle = format!("@{}", username.to_lowercase());
+            if let Some(pos) = content.to_lowercase().find(&handle) {
+                mentions_bot = true;
+                text.replace_range(pos..pos + handle.len(), "");
+            }
+        }
+
+        Some(MessageEvent {
+            message: self.convert_message(msg),
+            thread_id: Some(msg.message_id.to_string()),
+            is_direct: msg.chat.chat_type == "private",
+            mentions_bot,
+            text: text.trim().to_string(),
+        })
+    }
 }
 
 #[async_trait]
@@ -410,6 +575,100 @@ impl MessagingProvider for TelegramProvider {
             .map(|m| self.convert_message(m))
             .collect())
     }
+
+    async fn event_stream(&self) -> Result<MessageStream> {
+        let provider = self.clone();
+        Ok(MessageStream::spawn(move |tx| provider.run_long_poll(tx)))
+    }
+
+    /// Replies quote `thread_id`, the message being answered.
+    async fn reply_in_thread(
+        &self,
+        channel: &str,
+        thread_id: &str,
+        text: &str,
+    ) -> Result<SentMessage> {
+        let payload = serde_json::json!({
+            "chat_id": channel,
+            "text": text,
+            "reply_parameters": { "message_id": Self::message_id(thread_id)? },
+        });
+        let msg: TelegramMessage = self.call("sendMessage", &payload).await?;
+        Ok(Self::sent(&msg))
+    }
+
+    async fn send_rich(
+        &self,
+        channel: &str,
+        thread_id: Option<&str>,
+        text: &RichText,
+    ) -> Result<SentMessage> {
+        let mut payload = serde_json::json!({
+            "chat_id": channel,
+            "text": text.to_telegram_html(),
+            "parse_mode": "HTML",
+        });
+        if let Some(thread_id) = thread_id {
+            payload["reply_parameters"] =
+                serde_json::json!({ "message_id": Self::message_id(thread_id)? });
+        }
+        let msg: TelegramMessage = self.call("sendMessage", &payload).await?;
+        Ok(Self::sent(&msg))
+    }
+
+    /// Images go out with `sendPhoto` so they display inline; everything
+    /// else with `sendDocument`.
+    async fn upload_file(
+        &self,
+        channel: &str,
+        thread_id: Option<&str>,
+        file: &FileUpload,
+    ) -> Result<SentMessage> {
+        let (method, field) = if file.is_image() {
+            ("sendPhoto", "photo")
+        } else {
+            ("sendDocument", "document")
+        };
+        let part = Part::bytes(file.data.clone())
+            .file_name(file.name.clone())
+            .mime_str(&file.mime_type)
+            .context("invalid MIME type for Telegram upload")?;
+        let mut form = Form::new()
+            .text("chat_id", channel.to_string())
+            .part(field, part);
+        if let Some(caption) = &file.caption {
+            form = form.text("caption", caption.clone());
+        }
+        if let Some(thread_id) = thread_id {
+            let reply = serde_json::json!({ "message_id": Self::message_id(thread_id)? });
+            form = form.text("reply_parameters", reply.to_string());
+        }
+
+        debug!(method, channel = %channel, file = %file.name, "uploading Telegram file");
+
+        let msg: TelegramMessage = self.call_multipart(method, form).await?;
+        Ok(Self::sent(&msg))
+    }
+
+    async fn edit_message(&self, channel: &str, message_id: &str, text: &RichText) -> Result<()> {
+        let payload = serde_json::json!({
+            "chat_id": channel,
+            "message_id": Self::message_id(message_id)?,
+            "text": text.to_telegram_html(),
+            "parse_mode": "HTML",
+        });
+        let _: Value = self.call("editMessageText", &payload).await?;
+        Ok(())
+    }
+
+    async fn delete_message(&self, channel: &str, message_id: &str) -> Result<()> {
+        let payload = serde_json::json!({
+            "chat_id": channel,
+            "message_id": Self::message_id(message_id)?,
+        });
+        let _: bool = self.call("deleteMessage", &payload).await?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -597,7 +856,7 @@ mod tests {
             }
         }"#;
         let update: TelegramUpdate = serde_json::from_str(json).unwrap();
-        assert_eq!(update._update_id, 999);
+        assert_eq!(update.update_id, 999);
         assert!(update.message.is_some());
         assert_eq!(update.message.unwrap().message_id, 42);
     }
@@ -621,6 +880,7 @@ mod tests {
             date: 1609459200,
             text: Some("Hello there".into()),
             document: None,
+            reply_to_message: None,
         };
 
         let msg = provider.convert_message(&tg_msg);
@@ -656,6 +916,7 @@ mod tests {
                 mime_type: Some("application/pdf".into()),
                 file_size: Some(2048),
             }),
+            reply_to_message: None,
         };
 
         let msg = provider.convert_message(&tg_msg);
@@ -664,4 +925,169 @@ mod tests {
         assert_eq!(msg.attachments[0].mime_type, "application/pdf");
         assert_eq!(msg.attachments[0].size, 2048);
     }
+
+    fn bot() -> TelegramUser {
+        TelegramUser {
+            id: 7,
+            first_name: "Hive".into(),
+            username: Some("hive_bot".into()),
+        }
+    }
+
+    fn update_message(chat_type: &str, text: &str) -> TelegramMessage {
+        serde_json::from_value(serde_json::json!({
+            "message_id": 42,
+            "chat": {"id": 100, "type": chat_type},
+            "from": {"id": 1, "first_name": "Alice", "username": "alice"},
+            "date": 1609459200,
+            "text": text,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_update_event_mentions_and_private_chats() {
+        let provider = make_provider();
+        let mention = provider
+            .update_event(&update_message("group", "@Hive_Bot what's up?"), &bot())
+            .unwrap();
+        assert!(mention.mentions_bot);
+        assert!(!mention.is_direct);
+        assert_eq!(mention.text, "what's up?");
+        assert_eq!(mention.thread_id.as_deref(), Some("42"));
+
+        let private = provider
+            .update_event(&update_message("private", "hello"), &bot())
+            .unwrap();
+        assert!(private.is_direct);
+        assert!(!private.mentions_bot);
+
+        let mut reply = update_message("supergroup", "thanks");
+        reply.reply_to_message = Some(Box::new(TelegramMessage {
+            from: Some(bot()),
+            ..update_message("supergroup", "done")
+        }));
+        assert!(provider.update_event(&reply, &bot()).unwrap().mentions_bot);
+
+        let own = TelegramMessage {
+            from: Some(bot()),
+            ..update_message("group", "echo")
+        };
+        assert!(provider.update_event(&own, &bot()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_long_poll_stream_advances_offset_and_replies() {
+        use super::super::test_support::http_stub;
+
+        let (base_url, requests) = http_stub(|req| {
+            let body = if req.path.ends_with("/getMe") {
+                r#"{"ok":true,"result":{"id":7,"first_name":"Hive","username":"hive_bot"}}"#
+                    .to_string()
+            } else if req.path.ends_with("/getUpdates") && req.json()["offset"] == 0 {
+                serde_json::json!({"ok": true, "result": [
+                    {"update_id": 10, "message": {
+                        "message_id": 5, "chat": {"id": -100, "type": "group"},
+                        "from": {"id": 1, "first_name": "Alice"}, "date": 1609459200,
+                        "text": "@hive_bot status",
+                    }},
+                    {"update_id": 11, "message": {
+                        "message_id": 6, "chat": {"id": 1, "type": "private"},
+                        "from": {"id": 1, "first_name": "Alice"}, "date": 1609459201,
+                        "text": "hi",
+                    }},
+                ]})
+                .to_string()
+            } else if req.path.ends_with("/getUpdates") {
+                r#"{"ok":true,"result":[]}"#.to_string()
+            } else {
+                r#"{"ok":true,"result":{"message_id":9,"chat":{"id":-100,"type":"group"},"date":1609459300}}"#
+                    .to_string()
+            };
+            (200, body)
+        })
+        .await;
+
+        let provider = TelegramProvider::with_base_url("123:ABC", &base_url).unwrap();
+        let mut stream = provider.event_stream().await.unwrap();
+
+        let mention = stream.next().await.unwrap();
+        assert_eq!(mention.text, "status");
+        assert!(mention.mentions_bot);
+        let dm = stream.next().await.unwrap();
+        assert!(dm.is_direct);
+        assert_eq!(dm.message.channel_id, "1");
+
+        let sent = provider.reply_in_thread("-100", "5", "ok").await.unwrap();
+        assert_eq!(sent.id, "9");
+        drop(stream);
+
+        let requests = requests.lock().unwrap();
+        let polls: Vec<_> = requests
+            .iter()
+            .filter(|r| r.path.ends_with("/getUpdates"))
+            .map(|r| r.json()["offset"].as_i64().unwrap())
+            .collect();
+        assert_eq!(polls[0], 0);
+        assert!(polls[1..].iter().all(|offset| *offset == 12));
+        let reply = requests
+            .iter()
+            .find(|r| r.path.ends_with("/sendMessage"))
+            .unwrap();
+        assert_eq!(reply.method, "POST");
+        assert_eq!(reply.json()["reply_parameters"]["message_id"], 5);
+    }
+
+    #[tokio::test]
+    async fn test_rich_text_upload_edit_and_delete() {
+        use super::super::rich_text::Span;
+        use super::super::test_support::http_stub;
+
+        let (base_url, requests) = http_stub(|req| {
+            let body = if req.path.ends_with("/deleteMessage") {
+                r#"{"ok":true,"result":true}"#
+            } else {
+                r#"{"ok":true,"result":{"message_id":9,"chat":{"id":-100,"type":"group"},"date":1609459300}}"#
+            };
+            (200, body.to_string())
+        })
+        .await;
+        let provider = TelegramProvider::with_base_url("123:ABC", &base_url).unwrap();
+
+        let text = RichText::new()
+            .paragraph(vec![Span::bold("1 < 2"), Span::text(" holds")])
+            .code_block(None, "x = 1");
+        provider.send_rich("-100", Some("5"), &text).await.unwrap();
+        let chart = FileUpload::new("chart.png", "image/png", b"PNGDATA".to_vec());
+        provider.upload_file("-100", Some("5"), &chart).await.unwrap();
+        let report = FileUpload::new("report.xlsx", "application/octet-stream", b"PK".to_vec())
+            .with_caption("Q3");
+        let sent = provider.upload_file("-100", None, &report).await.unwrap();
+        assert_eq!(sent.id, "9");
+        provider
+            .edit_message("-100", "9", &RichText::plain("edited"))
+            .await
+            .unwrap();
+        provider.delete_message("-100", "9").await.unwrap();
+        assert!(provider.delete_message("-100", "not-a-number").await.is_err());
+
+        let requests = requests.lock().unwrap();
+        let rich = requests[0].json();
+        assert_eq!(rich["parse_mode"], "HTML");
+        assert_eq!(
+            rich["text"],
+            "<b>1 &lt; 2</b> holds\n\n<pre><code>x = 1</code></pre>"
+        );
+        assert_eq!(rich["reply_parameters"]["message_id"], 5);
+
+        assert!(requests[1].path.ends_with("/sendPhoto"));
+        assert!(requests[1].body.contains("name=\"photo\"; filename=\"chart.png\""));
+        assert!(requests[1].body.contains(r#"{"message_id":5}"#));
+        assert!(requests[2].path.ends_with("/sendDocument"));
+        assert!(requests[2].body.contains("name=\"caption\""));
+
+        assert!(requests[3].path.ends_with("/editMessageText"));
+        assert_eq!(requests[3].json()["message_id"], 9);
+        assert!(requests[4].path.ends_with("/deleteMessage"));
+    }
 }
@@ -0,0 +1,135 @@
+//! Local HTTP and WebSocket stand-ins for exercising providers in tests.
+
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_tungstenite::{WebSocketStream, accept_async};
+
+/// A request received by [`http_stub`].
+#[derive(Debug, Clone)]
+pub struct StubRequest {
+    pub method: String,
+    /// Path including the query string.
+    pub path: String,
+    /// Header names are lowercased.
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl StubRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_str(&self.body).unwrap_or_default()
+    }
+}
+
+pub type Requests = Arc<Mutex<Vec<StubRequest>>>;
+
+/// Serve HTTP on a local port, answering each request with
+/// `respond(&request) -> (status, json body)`. Returns the base URL and a
+/// log of every request received.
+pub async fn http_stub<F>(respond: F) -> (String, Requests)
+where
+    F: Fn(&StubRequest) -> (u16, String) + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let requests: Requests = Arc::default();
+    let log = requests.clone();
+    let respond = Arc::new(respond);
+    tokio::spawn(async move {
+        loop {
+            let Ok((socket, _)) = listener.accept().await else {
+                return;
+            };
+            let log = log.clone();
+            let respond = respond.clone();
+            tokio::spawn(async move {
+                let _ = serve_one(socket, &log, &*respond).await;
+            });
+        }
+    });
+    (base_url, requests)
+}
+
+async fn serve_one<F>(mut socket: TcpStream, log: &Requests, respond: &F) -> Option<()>
+where
+    F: Fn(&StubRequest) -> (u16, String),
+{
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        let n = socket.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&buf[..head_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name == "content-length")
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    while buf.len() < head_end + content_length {
+        let n = socket.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let body = String::from_utf8_lossy(&buf[head_end..]).to_string();
+
+    let request = StubRequest {
+        method,
+        path,
+        headers,
+        body,
+    };
+    let (status, body) = respond(&request);
+    log.lock().unwrap().push(request);
+
+    let response = format!(
+        "HTTP/1.1 {status} OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    socket.write_all(response.as_bytes()).await.ok()?;
+    socket.shutdown().await.ok()
+}
+
+/// Accept WebSocket connections on a local port. Returns the `ws://` URL
+/// and a channel yielding each accepted connection for the test to script.
+pub async fn ws_stub() -> (String, mpsc::UnboundedReceiver<WebSocketStream<TcpStream>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            if let Ok(ws) = accept_async(socket).await
+                && tx.send(ws).is_err()
+            {
+                return;
+            }
+        }
+    });
+    (url, rx)
+}
@@ -2,7 +2,8 @@
 //!
 //! A simple HTTP webhook-based chat provider. Sends outgoing messages
 //! via POST to a configurable webhook URL and stores received messages
-//! in-memory for retrieval.
+//! in-memory for retrieval. Received messages are also delivered live to
+//! any open [`MessagingProvider::event_stream`].
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
@@ -14,7 +15,8 @@ use std::sync::{Arc, Mutex};
 use tracing::debug;
 
 use super::provider::{
-    Attachment, Channel, IncomingMessage, MessagingProvider, Platform, SentMessage,
+    Attachment, Channel, EventFeed, IncomingMessage, MessageEvent, MessageStream,
+    MessagingProvider, Platform, SentMessage,
 };
 
 const DEFAULT_BASE_URL: &str = "https://webhooks.example.com";
@@ -72,6 +74,7 @@ pub struct WebChatProvider {
     client: Client,
     /// In-memory store for received messages.
     inbox: Arc<Mutex<Vec<IncomingMessage>>>,
+    events: EventFeed,
 }
 
 impl WebChatProvider {
@@ -100,6 +103,7 @@ impl WebChatProvider {
             token: token.to_string(),
             client,
             inbox: Arc::new(Mutex::new(Vec::new())),
+            events: EventFeed::new(),
         })
     }
 
@@ -113,8 +117,19 @@ impl WebChatProvider {
         &self.token
     }
 
-    /// Push an externally received message into the in-memory inbox.
+    /// Push an externally received message into the in-memory inbox and
+    /// any open event streams.
+    ///
+    /// A WebChat visitor is always talking to Hive directly, so every
+    /// message is delivered as a direct message.
     pub fn receive_message(&self, msg: IncomingMessage) {
+        self.events.publish(MessageEvent {
+            text: msg.content.clone(),
+            message: msg.clone(),
+            thread_id: None,
+            is_direct: true,
+            mentions_bot: false,
+        });
         let mut inbox = self.inbox.lock().unwrap_or_else(|e| e.into_inner());
         inbox.push(msg);
     }
@@ -298,6 +313,10 @@ impl MessagingProvider for WebChatProvider {
 
         Ok(messages)
     }
+
+    async fn event_stream(&self) -> Result<MessageStream> {
+        Ok(self.events.stream())
+    }
 }
 
 #[cfg(test)]
@@ -467,6 +486,47 @@ mod tests {
         assert_eq!(provider.inbox_count(), 1);
     }
 
+    #[tokio::test]
+    async fn test_event_stream_delivers_webhooks_and_replies() {
+        use super::super::test_support::http_stub;
+
+        let (base_url, requests) =
+            http_stub(|_| (200, r#"{"id":"out-1","timestamp":null}"#.to_string())).await;
+        let provider = WebChatProvider::with_base_url("wc-token-123", &base_url).unwrap();
+
+        provider.receive_webhook(&WebChatIncoming {
+            id: "before".into(),
+            channel: "lobby".into(),
+            author: "bob".into(),
+            text: "too early".into(),
+            timestamp: "2025-01-01T00:00:00Z".into(),
+            attachments: vec![],
+        });
+        let mut stream = provider.event_stream().await.unwrap();
+        provider.receive_webhook(&WebChatIncoming {
+            id: "msg-3".into(),
+            channel: "lobby".into(),
+            author: "bob".into(),
+            text: "any news?".into(),
+            timestamp: "2025-01-01T00:00:00Z".into(),
+            attachments: vec![],
+        });
+
+        let ev = stream.next().await.unwrap();
+        assert_eq!(ev.message.id, "msg-3");
+        assert!(ev.is_direct);
+        assert_eq!(ev.text, "any news?");
+
+        let sent = provider
+            .reply_in_thread("lobby", "msg-3", "not yet")
+            .await
+            .unwrap();
+        assert_eq!(sent.id, "out-1");
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].path, "/send");
+        assert_eq!(requests[0].json()["text"], "not yet");
+    }
+
     #[test]
     fn test_clear_inbox() {
         let provider = make_provider();
@@ -5,7 +5,7 @@
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SecondsFormat, Utc};
 use reqwest::Client;
 use reqwest::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue};
 use serde::Deserialize;
@@ -362,6 +362,19 @@ impl AsanaClient {
         }
     }
 
+    /// Convert an Asana comment story to our common Comment type.
+    fn convert_story(story: AsanaStory) -> Comment {
+        Comment {
+            id: story.gid,
+            author: story.created_by.and_then(|u| u.name),
+            body: story.text.unwrap_or_default(),
+            created_at: story
+                .created_at
+                .as_deref()
+                .and_then(Self::parse_datetime),
+        }
+    }
+
     /// Map an Asana task's status from its section name and completed flag.
     fn map_status(task: &AsanaTask) -> IssueStatus {
         if task.completed.unwrap_or(false) {
@@ -540,6 +553,13 @@ impl AsanaClient {
             params.push(("assignee".into(), assignee.clone()));
         }
 
+        if let Some(since) = filters.updated_since {
+            params.push((
+                "modified_since".into(),
+                since.to_rfc3339_opts(SecondsFormat::Millis, true),
+            ));
+        }
+
         params
     }
 }
@@ -577,10 +597,18 @@ impl ProjectManagementProvider for AsanaClient {
         filters: &IssueFilters,
     ) -> Result<Vec<Issue>> {
         let opt_fields = Self::task_opt_fields();
-        let mut url = format!(
-            "{}/projects/{}/tasks?opt_fields={}&limit=100",
-            self.base_url, project_id, opt_fields,
-        );
+        // Only the workspace-level task list understands `modified_since`.
+        let mut url = if filters.updated_since.is_some() {
+            format!(
+                "{}/tasks?project={}&opt_fields={}&limit=100",
+                self.base_url, project_id, opt_fields,
+            )
+        } else {
+            format!(
+                "{}/projects/{}/tasks?opt_fields={}&limit=100",
+                self.base_url, project_id, opt_fields,
+            )
+        };
 
         let extra_params = Self::build_task_filter_params(filters);
         for (key, value) in &extra_params {
@@ -816,16 +844,23 @@ impl ProjectManagementProvider for AsanaClient {
         });
 
         let story: AsanaStory = self.post_one(&url, &payload).await?;
+        Ok(Self::convert_story(story))
+    }
 
-        Ok(Comment {
-            id: story.gid,
-            author: story.created_by.and_then(|u| u.name),
-            body: story.text.unwrap_or_default(),
-            created_at: story
-                .created_at
-                .as_deref()
-                .and_then(Self::parse_datetime),
-        })
+    async fn list_comments(&self, issue_id: &str) -> Result<Vec<Comment>> {
+        let url = format!(
+            "{}/tasks/{}/stories?opt_fields=text,type,created_at,created_by.name&limit=100",
+            self.base_url, issue_id,
+        );
+        let stories: Vec<AsanaStory> = self.get_list(&url).await?;
+
+        // Stories also record system activity (moves, assignments); keep
+        // only what people wrote.
+        Ok(stories
+            .into_iter()
+            .filter(|s| s.story_type.as_deref() == Some("comment"))
+            .map(Self::convert_story)
+            .collect())
     }
 
     async fn transition_issue(&self, issue_id: &str, status: IssueStatus) -> Result<Issue> {
@@ -1287,6 +1322,22 @@ mod tests {
         assert_eq!(params[0].0, "completed_since");
     }
 
+    #[test]
+    fn test_build_task_filter_params_with_updated_since() {
+        let since = DateTime::parse_from_rfc3339("2026-03-04T05:06:07Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let filters = IssueFilters {
+            updated_since: Some(since),
+            ..Default::default()
+        };
+        let params = AsanaClient::build_task_filter_params(&filters);
+        assert_eq!(
+            params,
+            [("modified_since".to_string(), "2026-03-04T05:06:07.000Z".to_string())]
+        );
+    }
+
     #[test]
     fn test_task_opt_fields_not_empty() {
         let fields = AsanaClient::task_opt_fields();
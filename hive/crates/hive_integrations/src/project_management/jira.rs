@@ -36,10 +36,12 @@ struct JiraIssue {
     key: String,
     #[serde(rename = "self")]
     self_url: Option<String>,
+    /// Absent from the minimal `{id, key, self}` body returned on create.
+    #[serde(default)]
     fields: JiraIssueFields,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 struct JiraIssueFields {
     summary: Option<String>,
     description: Option<serde_json::Value>,
@@ -109,6 +111,12 @@ struct JiraComment {
     created: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct JiraCommentsResponse {
+    #[serde(default)]
+    comments: Vec<JiraComment>,
+}
+
 #[derive(Debug, Deserialize)]
 struct JiraTransitionsResponse {
     #[serde(default)]
@@ -364,6 +372,20 @@ impl JiraClient {
         }
     }
 
+    /// Convert a Jira comment to our common Comment type.
+    fn convert_comment(jira: JiraComment) -> Comment {
+        Comment {
+            id: jira.id,
+            author: jira.author.and_then(|a| a.display_name),
+            body: jira
+                .body
+                .as_ref()
+                .map(Self::extract_text_from_adf)
+                .unwrap_or_default(),
+            created_at: jira.created.as_deref().and_then(Self::parse_datetime),
+        }
+    }
+
     /// Map a Jira status to our IssueStatus enum.
     ///
     /// Uses the status category key first (more reliable), then falls back
@@ -518,6 +540,11 @@ impl JiraClient {
             clauses.push(format!("text ~ \"{}\"", query));
         }
 
+        if let Some(since) = filters.updated_since {
+            // JQL only has minute precision; the caller filters the overlap.
+            clauses.push(format!("updated >= \"{}\"", since.format("%Y-%m-%d %H:%M")));
+        }
+
         clauses.join(" AND ") + " ORDER BY updated DESC"
     }
 }
@@ -657,22 +684,13 @@ impl ProjectManagementProvider for JiraClient {
         });
 
         let jira_comment: JiraComment = self.post(&url, &payload).await?;
+        Ok(Self::convert_comment(jira_comment))
+    }
 
-        Ok(Comment {
-            id: jira_comment.id,
-            author: jira_comment
-                .author
-                .and_then(|a| a.display_name),
-            body: jira_comment
-                .body
-                .as_ref()
-                .map(Self::extract_text_from_adf)
-                .unwrap_or_default(),
-            created_at: jira_comment
-                .created
-                .as_deref()
-                .and_then(Self::parse_datetime),
-        })
+    async fn list_comments(&self, issue_id: &str) -> Result<Vec<Comment>> {
+        let url = format!("{}/issue/{}/comment?maxResults=100", self.base_url, issue_id);
+        let resp: JiraCommentsResponse = self.get(&url).await?;
+        Ok(resp.comments.into_iter().map(Self::convert_comment).collect())
     }
 
     async fn transition_issue(&self, issue_id: &str, status: IssueStatus) -> Result<Issue> {
@@ -1063,6 +1081,21 @@ mod tests {
         assert!(jql.contains("text ~ \"login error\""));
     }
 
+    #[test]
+    fn test_build_jql_with_updated_since() {
+        let client = make_client();
+        let since = DateTime::parse_from_rfc3339("2026-03-04T05:06:07Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let filters = IssueFilters {
+            updated_since: Some(since),
+            ..Default::default()
+        };
+        let jql = client.build_jql("PROJ", &filters);
+        assert!(jql.contains("updated >= \"2026-03-04 05:06\""));
+        assert!(jql.ends_with(" ORDER BY updated DESC"));
+    }
+
     #[test]
     fn test_urlencod() {
         assert_eq!(urlencod("hello world"), "hello%20world");
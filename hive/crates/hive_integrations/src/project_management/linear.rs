@@ -109,6 +109,16 @@ struct CommentCreatePayload {
     comment: Option<LinearComment>,
 }
 
+#[derive(Debug, Deserialize)]
+struct IssueCommentsData {
+    issue: IssueCommentsInner,
+}
+
+#[derive(Debug, Deserialize)]
+struct IssueCommentsInner {
+    comments: LinearConnection<LinearComment>,
+}
+
 #[derive(Debug, Deserialize)]
 struct IssueSearchData {
     #[serde(rename = "issueSearch")]
@@ -330,6 +340,19 @@ impl LinearClient {
         }
     }
 
+    /// Convert a Linear comment to our common Comment type.
+    fn convert_comment(comment: LinearComment) -> Comment {
+        Comment {
+            id: comment.id,
+            author: comment.user.map(|u| u.display_name.unwrap_or(u.name)),
+            body: comment.body,
+            created_at: comment
+                .created_at
+                .as_deref()
+                .and_then(Self::parse_datetime),
+        }
+    }
+
     /// Map a Linear workflow state to our IssueStatus enum.
     ///
     /// Linear states have a type field: backlog, unstarted, started, completed, cancelled.
@@ -449,6 +472,13 @@ impl LinearClient {
             );
         }
 
+        if let Some(since) = filters.updated_since {
+            filter.insert(
+                "updatedAt".into(),
+                serde_json::json!({ "gte": since.to_rfc3339() }),
+            );
+        }
+
         serde_json::Value::Object(filter)
     }
 
@@ -780,17 +810,39 @@ impl ProjectManagementProvider for LinearClient {
             .comment
             .context("Linear returned success but no comment data")?;
 
-        Ok(Comment {
-            id: comment.id,
-            author: comment
-                .user
-                .map(|u| u.display_name.unwrap_or(u.name)),
-            body: comment.body,
-            created_at: comment
-                .created_at
-                .as_deref()
-                .and_then(Self::parse_datetime),
-        })
+        Ok(Self::convert_comment(comment))
+    }
+
+    async fn list_comments(&self, issue_id: &str) -> Result<Vec<Comment>> {
+        let query = r#"
+            query IssueComments($issueId: String!) {
+                issue(id: $issueId) {
+                    comments(first: 100) {
+                        nodes {
+                            id
+                            body
+                            createdAt
+                            user {
+                                id
+                                name
+                                displayName
+                            }
+                        }
+                    }
+                }
+            }
+        "#;
+
+        let vars = serde_json::json!({ "issueId": issue_id });
+        let data: IssueCommentsData = self.graphql(query, Some(vars)).await?;
+
+        Ok(data
+            .issue
+            .comments
+            .nodes
+            .into_iter()
+            .map(Self::convert_comment)
+            .collect())
     }
 
     async fn transition_issue(&self, issue_id: &str, status: IssueStatus) -> Result<Issue> {
@@ -1162,6 +1214,19 @@ mod tests {
         assert!(json.get("priority").is_some());
     }
 
+    #[test]
+    fn test_build_filter_json_with_updated_since() {
+        let since = DateTime::parse_from_rfc3339("2026-03-04T05:06:07Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let filters = IssueFilters {
+            updated_since: Some(since),
+            ..Default::default()
+        };
+        let json = LinearClient::build_filter_json(&filters);
+        assert_eq!(json["updatedAt"]["gte"], "2026-03-04T05:06:07+00:00");
+    }
+
     #[test]
     fn test_parse_datetime_valid() {
         let dt = LinearClient::parse_datetime("2024-01-15T10:30:00.000Z");
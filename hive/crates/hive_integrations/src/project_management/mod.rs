@@ -8,10 +8,14 @@
 pub mod asana;
 pub mod jira;
 pub mod linear;
+pub mod sync;
 
 pub use asana::AsanaClient;
 pub use jira::JiraClient;
 pub use linear::LinearClient;
+pub use sync::{
+    BoardLink, ConflictPolicy, KanbanSync, SyncConflict, SyncField, SyncReport, SyncState, TaskLink,
+};
 
 use std::collections::HashMap;
 use std::fmt;
@@ -141,6 +145,9 @@ pub struct IssueFilters {
     pub labels: Vec<String>,
     pub sprint_id: Option<String>,
     pub search_query: Option<String>,
+    /// Only issues updated at or after this instant (incremental pulls).
+    #[serde(default)]
+    pub updated_since: Option<DateTime<Utc>>,
 }
 
 /// Request to create a new issue.
@@ -215,6 +222,9 @@ pub trait ProjectManagementProvider: Send + Sync {
     /// Add a comment to an issue.
     async fn add_comment(&self, issue_id: &str, body: &str) -> Result<Comment>;
 
+    /// List the comments on an issue, oldest first.
+    async fn list_comments(&self, issue_id: &str) -> Result<Vec<Comment>>;
+
     /// Transition an issue to a new status.
     async fn transition_issue(&self, issue_id: &str, status: IssueStatus) -> Result<Issue>;
 
@@ -331,6 +341,17 @@ impl ProjectManagementHub {
         provider.add_comment(issue_id, body).await
     }
 
+    /// List the comments on an issue on a specific platform.
+    pub async fn list_comments(
+        &self,
+        platform: PMPlatform,
+        issue_id: &str,
+    ) -> Result<Vec<Comment>> {
+        let provider = self.provider(platform)?;
+        debug!(platform = %platform, issue_id = %issue_id, "listing comments via hub");
+        provider.list_comments(issue_id).await
+    }
+
     /// Transition an issue to a new status on a specific platform.
     pub async fn transition_issue(
         &self,
@@ -550,6 +571,7 @@ mod tests {
         assert!(filters.labels.is_empty());
         assert!(filters.sprint_id.is_none());
         assert!(filters.search_query.is_none());
+        assert!(filters.updated_since.is_none());
     }
 
     #[test]
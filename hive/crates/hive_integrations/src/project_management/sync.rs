@@ -0,0 +1,1468 @@
+//! Two-way sync between the local Kanban board and a remote tracker.
+//!
+//! A [`SyncState`] links a [`KanbanBoard`] to a project on any platform
+//! registered with the [`ProjectManagementHub`]. For every card it records
+//! the issue it mirrors and a snapshot of the synced fields as both sides
+//! agreed at the last run. [`KanbanSync::sync`] then:
+//!
+//! 1. pulls the issues updated since the previous pull,
+//! 2. diffs each side against the snapshot, applying remote edits to the
+//!    card and pushing local edits to the issue,
+//! 3. imports new issues as cards and creates issues for new cards,
+//! 4. mirrors comments in both directions.
+//!
+//! `updated_at` on each side decides whether that side changed at all; a
+//! field changed on both sides to different values is a conflict, handled
+//! per [`ConflictPolicy`].
+//!
+//! Subtasks travel as a Markdown checklist under a `Subtasks:` line at the
+//! end of the issue description. Trackers have no "blocked" state, so a
+//! blocked card is in progress remotely and stays blocked when the issue
+//! reports in progress.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use hive_core::config::HiveConfig;
+use hive_core::kanban::{KanbanBoard, KanbanColumn, KanbanTask, Priority, Subtask, TaskComment};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use super::{
+    CreateIssueRequest, Issue, IssueFilters, IssuePriority, IssueStatus, IssueUpdate, PMPlatform,
+    ProjectManagementHub,
+};
+
+/// Line that introduces the subtask checklist in an issue description.
+const SUBTASKS_HEADING: &str = "Subtasks:";
+
+// ── Persisted state ────────────────────────────────────────────────
+
+/// The remote project a board is linked to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardLink {
+    pub platform: PMPlatform,
+    pub project_id: String,
+}
+
+/// A field kept in sync between a card and its issue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncField {
+    Title,
+    Description,
+    Status,
+    Priority,
+    Assignee,
+    Subtasks,
+}
+
+impl SyncField {
+    const ALL: [SyncField; 6] = [
+        SyncField::Title,
+        SyncField::Description,
+        SyncField::Status,
+        SyncField::Priority,
+        SyncField::Assignee,
+        SyncField::Subtasks,
+    ];
+}
+
+/// One checklist line of an issue description.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct ChecklistItem {
+    title: String,
+    done: bool,
+}
+
+/// The synced fields of a card/issue pair, in board terms.
+///
+/// `column` and `status` are both kept because the mapping between them is
+/// lossy: the local side is compared by column, the remote side by status.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Snapshot {
+    title: String,
+    description: String,
+    column: KanbanColumn,
+    status: IssueStatus,
+    priority: Priority,
+    assignee: Option<String>,
+    subtasks: Vec<ChecklistItem>,
+}
+
+impl Snapshot {
+    fn of_task(task: &KanbanTask) -> Self {
+        Self {
+            title: task.title.clone(),
+            description: task
+                .description
+                .as_deref()
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+            column: task.column,
+            status: column_to_status(task.column),
+            priority: task.priority,
+            assignee: task.assignee.clone(),
+            subtasks: task
+                .subtasks
+                .iter()
+                .map(|s| ChecklistItem {
+                    title: s.title.clone(),
+                    done: s.completed,
+                })
+                .collect(),
+        }
+    }
+
+    /// View of an issue; `current` is the card's column, see [`status_to_column`].
+    fn of_issue(issue: &Issue, current: KanbanColumn) -> Self {
+        let (description, subtasks) =
+            split_checklist(issue.description.as_deref().unwrap_or_default());
+        Self {
+            title: issue.title.clone(),
+            description,
+            column: status_to_column(issue.status, current),
+            status: issue.status,
+            priority: priority_from_remote(issue.priority),
+            assignee: issue.assignee.clone(),
+            subtasks,
+        }
+    }
+
+    /// Whether two views disagree on `field`, comparing status by column.
+    fn differs(&self, other: &Self, field: SyncField) -> bool {
+        match field {
+            SyncField::Title => self.title != other.title,
+            SyncField::Description => self.description != other.description,
+            SyncField::Status => self.column != other.column,
+            SyncField::Priority => self.priority != other.priority,
+            SyncField::Assignee => self.assignee != other.assignee,
+            SyncField::Subtasks => self.subtasks != other.subtasks,
+        }
+    }
+
+    /// Whether the remote side moved away from this snapshot on `field`.
+    fn remote_differs(&self, remote: &Self, field: SyncField) -> bool {
+        match field {
+            SyncField::Status => self.status != remote.status,
+            _ => self.differs(remote, field),
+        }
+    }
+
+    fn take(&mut self, from: &Self, field: SyncField) {
+        match field {
+            SyncField::Title => self.title = from.title.clone(),
+            SyncField::Description => self.description = from.description.clone(),
+            SyncField::Status => {
+                self.column = from.column;
+                self.status = from.status;
+            }
+            SyncField::Priority => self.priority = from.priority,
+            SyncField::Assignee => self.assignee = from.assignee.clone(),
+            SyncField::Subtasks => self.subtasks = from.subtasks.clone(),
+        }
+    }
+}
+
+/// A card linked to a remote issue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskLink {
+    pub task_id: String,
+    pub remote_id: String,
+    pub remote_key: Option<String>,
+    /// The card's `updated_at` when it was last in sync.
+    pub local_updated_at: DateTime<Utc>,
+    /// The issue's `updated_at` when it was last in sync.
+    pub remote_updated_at: Option<DateTime<Utc>>,
+    snapshot: Snapshot,
+    /// Local comment id → remote comment id.
+    #[serde(default)]
+    comments: HashMap<String, String>,
+    #[serde(default)]
+    conflicts: Vec<SyncField>,
+}
+
+impl TaskLink {
+    /// Fields left unresolved by [`ConflictPolicy::Report`].
+    pub fn conflicts(&self) -> &[SyncField] {
+        &self.conflicts
+    }
+}
+
+/// The mapping table between a board and its remote project.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncState {
+    pub link: BoardLink,
+    /// Latest remote `updated_at` seen; the next pull starts there.
+    pub last_pulled_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    tasks: Vec<TaskLink>,
+    /// Issues whose card was deleted locally; they are not imported again.
+    #[serde(default)]
+    ignored: Vec<String>,
+}
+
+impl SyncState {
+    /// Start an empty mapping for `link`.
+    pub fn new(link: BoardLink) -> Self {
+        Self {
+            link,
+            last_pulled_at: None,
+            tasks: Vec::new(),
+            ignored: Vec::new(),
+        }
+    }
+
+    /// `~/.hive/kanban_sync.json`, next to the board itself.
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(HiveConfig::base_dir()?.join("kanban_sync.json"))
+    }
+
+    /// Load a mapping table saved with [`SyncState::save`].
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    /// Persist the mapping table, replacing the file atomically.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("failed to serialize sync state")?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json).with_context(|| format!("failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, path).with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// All card ↔ issue links.
+    pub fn links(&self) -> &[TaskLink] {
+        &self.tasks
+    }
+
+    /// The link for a card, if it has been synced.
+    pub fn link_for_task(&self, task_id: &str) -> Option<&TaskLink> {
+        self.tasks.iter().find(|l| l.task_id == task_id)
+    }
+}
+
+// ── Sync engine ────────────────────────────────────────────────────
+
+/// How to resolve a field changed on both sides since the last sync.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// Leave both sides untouched and report the conflict on every run
+    /// until one side is changed to match the other.
+    #[default]
+    Report,
+    PreferLocal,
+    PreferRemote,
+}
+
+/// A card and issue whose fields were changed on both sides.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncConflict {
+    pub task_id: String,
+    pub remote_id: String,
+    pub remote_key: Option<String>,
+    pub fields: Vec<SyncField>,
+}
+
+/// What one sync run did.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReport {
+    /// Cards updated from their issue.
+    pub pulled: usize,
+    /// Issues updated from their card.
+    pub pushed: usize,
+    pub created_local: usize,
+    pub created_remote: usize,
+    pub comments_pulled: usize,
+    pub comments_pushed: usize,
+    /// Links dropped because their card was deleted.
+    pub unlinked: usize,
+    pub conflicts: Vec<SyncConflict>,
+    /// Per-card failures; the rest of the board still syncs.
+    pub errors: Vec<String>,
+}
+
+/// Syncs a [`KanbanBoard`] with the project named in a [`SyncState`].
+pub struct KanbanSync<'a> {
+    hub: &'a ProjectManagementHub,
+    policy: ConflictPolicy,
+}
+
+impl<'a> KanbanSync<'a> {
+    /// Sync through `hub`, which must have a provider for the linked platform.
+    pub fn new(hub: &'a ProjectManagementHub) -> Self {
+        Self {
+            hub,
+            policy: ConflictPolicy::default(),
+        }
+    }
+
+    /// Set how conflicting edits are resolved.
+    pub fn with_policy(mut self, policy: ConflictPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Run a full two-way sync. The caller saves `board` and `state`
+    /// afterwards; both are updated even when some cards fail.
+    pub async fn sync(&self, board: &mut KanbanBoard, state: &mut SyncState) -> Result<SyncReport> {
+        let mut report = SyncReport::default();
+        let link = state.link.clone();
+
+        let filters = IssueFilters {
+            updated_since: state.last_pulled_at,
+            ..Default::default()
+        };
+        let mut pulled: HashMap<String, Issue> = self
+            .hub
+            .list_issues(link.platform, &link.project_id, &filters)
+            .await?
+            .into_iter()
+            .map(|issue| (issue.id.clone(), issue))
+            .collect();
+        debug!(platform = %link.platform, pulled = pulled.len(), "pulled issues for kanban sync");
+        if let Some(latest) = pulled.values().filter_map(|i| i.updated_at).max() {
+            state.last_pulled_at =
+                Some(state.last_pulled_at.map_or(latest, |prev| prev.max(latest)));
+        }
+
+        let (kept, deleted): (Vec<TaskLink>, Vec<TaskLink>) = std::mem::take(&mut state.tasks)
+            .into_iter()
+            .partition(|l| board.get_task(&l.task_id).is_some());
+        state.tasks = kept;
+        for gone in deleted {
+            debug!(remote_id = %gone.remote_id, "card deleted locally, unlinking issue");
+            state.ignored.push(gone.remote_id);
+            report.unlinked += 1;
+        }
+
+        for task_link in &mut state.tasks {
+            let mut issue = pulled.remove(&task_link.remote_id);
+            if issue.is_none() && !task_link.conflicts.is_empty() {
+                // An open conflict needs the current remote values even when
+                // the issue has not changed since the last pull.
+                match self
+                    .hub
+                    .get_issue(link.platform, &task_link.remote_id)
+                    .await
+                {
+                    Ok(fetched) => issue = Some(fetched),
+                    Err(e) => {
+                        record_error(&mut report, &task_link.task_id, &e);
+                        continue;
+                    }
+                }
+            }
+            if let Err(e) = self
+                .reconcile(board, link.platform, task_link, issue.as_ref(), &mut report)
+                .await
+            {
+                record_error(&mut report, &task_link.task_id, &e);
+            }
+        }
+
+        let mut new_issues: Vec<Issue> = pulled
+            .into_values()
+            .filter(|issue| !state.ignored.contains(&issue.id))
+            .collect();
+        new_issues.sort_by_key(|issue| issue.created_at);
+        for issue in new_issues {
+            let task_link = self
+                .import_issue(board, link.platform, &issue, &mut report)
+                .await;
+            state.tasks.push(task_link);
+        }
+
+        let linked: HashSet<String> = state.tasks.iter().map(|l| l.task_id.clone()).collect();
+        let unlinked: Vec<String> = board
+            .all_tasks()
+            .iter()
+            .filter(|task| !linked.contains(&task.id))
+            .map(|task| task.id.clone())
+            .collect();
+        for task_id in unlinked {
+            match self.export_task(board, &link, &task_id, &mut report).await {
+                Ok(task_link) => state.tasks.push(task_link),
+                Err(e) => record_error(&mut report, &task_id, &e),
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Push one card right away, creating its issue if it has none yet.
+    ///
+    /// The issue is fetched first so remote edits since the last sync are
+    /// merged (or reported as conflicts) rather than overwritten.
+    pub async fn push_task(
+        &self,
+        board: &mut KanbanBoard,
+        state: &mut SyncState,
+        task_id: &str,
+    ) -> Result<SyncReport> {
+        let mut report = SyncReport::default();
+        let link = state.link.clone();
+        match state.tasks.iter_mut().find(|l| l.task_id == task_id) {
+            Some(task_link) => {
+                let issue = self
+                    .hub
+                    .get_issue(link.platform, &task_link.remote_id)
+                    .await?;
+                self.reconcile(board, link.platform, task_link, Some(&issue), &mut report)
+                    .await?;
+            }
+            None => {
+                let task_link = self.export_task(board, &link, task_id, &mut report).await?;
+                state.tasks.push(task_link);
+            }
+        }
+        Ok(report)
+    }
+
+    /// Move a card and push the move to its issue, for agents working the
+    /// board.
+    pub async fn move_card(
+        &self,
+        board: &mut KanbanBoard,
+        state: &mut SyncState,
+        task_id: &str,
+        column: KanbanColumn,
+    ) -> Result<SyncReport> {
+        board.move_task(task_id, column)?;
+        self.push_task(board, state, task_id).await
+    }
+
+    /// Merge one linked card with its issue. `issue` is `None` when the
+    /// issue has not changed since the last pull.
+    async fn reconcile(
+        &self,
+        board: &mut KanbanBoard,
+        platform: PMPlatform,
+        link: &mut TaskLink,
+        issue: Option<&Issue>,
+        report: &mut SyncReport,
+    ) -> Result<()> {
+        let task = board
+            .get_task(&link.task_id)
+            .context("card no longer exists")?;
+        let local = Snapshot::of_task(task);
+        let remote = issue.map(|i| Snapshot::of_issue(i, task.column));
+        let local_changed = task.updated_at > link.local_updated_at;
+        let remote_changed = issue.is_some_and(|i| match (i.updated_at, link.remote_updated_at) {
+            (Some(now), Some(seen)) => now > seen,
+            _ => true,
+        });
+        if !local_changed && !remote_changed {
+            return Ok(());
+        }
+
+        let base = &link.snapshot;
+        let mut merged = base.clone();
+        let mut to_push = Vec::new();
+        let mut to_apply = Vec::new();
+        let mut conflicts = Vec::new();
+        for field in SyncField::ALL {
+            let local_edit = local_changed && local.differs(base, field);
+            let remote_edit = remote_changed
+                && remote
+                    .as_ref()
+                    .is_some_and(|r| base.remote_differs(r, field));
+            let Some(remote) = remote.as_ref().filter(|_| remote_edit) else {
+                if local_edit {
+                    merged.take(&local, field);
+                    to_push.push(field);
+                }
+                continue;
+            };
+            if !local_edit {
+                merged.take(remote, field);
+                to_apply.push(field);
+            } else if !local.differs(remote, field) {
+                // Same edit on both sides.
+                merged.take(remote, field);
+            } else {
+                match self.policy {
+                    ConflictPolicy::Report => conflicts.push(field),
+                    ConflictPolicy::PreferLocal => {
+                        merged.take(&local, field);
+                        to_push.push(field);
+                    }
+                    ConflictPolicy::PreferRemote => {
+                        merged.take(remote, field);
+                        to_apply.push(field);
+                    }
+                }
+            }
+        }
+
+        if !to_apply.is_empty() {
+            let task = board
+                .get_task_mut(&link.task_id)
+                .context("card no longer exists")?;
+            apply_to_task(task, &merged, &to_apply);
+            report.pulled += 1;
+        }
+
+        let mut remote_updated_at = issue.and_then(|i| i.updated_at);
+        if !to_push.is_empty() {
+            // Unresolved fields keep their remote value in the description.
+            let mut outgoing = merged.clone();
+            for field in &conflicts {
+                outgoing.take(remote.as_ref().unwrap_or(base), *field);
+            }
+            let update = build_update(&outgoing, &to_push);
+            let updated = self
+                .hub
+                .update_issue(platform, &link.remote_id, &update)
+                .await?;
+            if to_push.contains(&SyncField::Status) {
+                // Trackers may coarsen the status (e.g. review → in progress).
+                merged.status = updated.status;
+            }
+            remote_updated_at = updated.updated_at.or(remote_updated_at);
+            report.pushed += 1;
+        }
+        link.snapshot = merged;
+
+        if remote_changed {
+            self.pull_comments(board, platform, link, report).await?;
+        }
+        if local_changed {
+            self.push_comments(board, platform, link, report).await?;
+        }
+
+        if conflicts.is_empty() {
+            link.local_updated_at = board
+                .get_task(&link.task_id)
+                .map_or(link.local_updated_at, |t| t.updated_at);
+            link.remote_updated_at = remote_updated_at.or(link.remote_updated_at);
+        } else {
+            // Timestamps stay put so both sides still count as changed.
+            report.conflicts.push(SyncConflict {
+                task_id: link.task_id.clone(),
+                remote_id: link.remote_id.clone(),
+                remote_key: link.remote_key.clone(),
+                fields: conflicts.clone(),
+            });
+        }
+        link.conflicts = conflicts;
+        Ok(())
+    }
+
+    /// Create a card for an issue first seen on the tracker.
+    async fn import_issue(
+        &self,
+        board: &mut KanbanBoard,
+        platform: PMPlatform,
+        issue: &Issue,
+        report: &mut SyncReport,
+    ) -> TaskLink {
+        let values = Snapshot::of_issue(issue, KanbanColumn::Todo);
+        let task_id = board
+            .add_task(issue.title.clone(), None, values.priority)
+            .id;
+        let mut link = TaskLink {
+            task_id: task_id.clone(),
+            remote_id: issue.id.clone(),
+            remote_key: issue.key.clone(),
+            local_updated_at: Utc::now(),
+            remote_updated_at: issue.updated_at,
+            snapshot: values.clone(),
+            comments: HashMap::new(),
+            conflicts: Vec::new(),
+        };
+        if let Some(task) = board.get_task_mut(&task_id) {
+            apply_to_task(task, &values, &SyncField::ALL);
+            task.labels = issue.labels.clone();
+            link.local_updated_at = task.updated_at;
+        }
+        report.created_local += 1;
+
+        if let Err(e) = self.pull_comments(board, platform, &mut link, report).await {
+            record_error(report, &task_id, &e);
+        }
+        link
+    }
+
+    /// Create an issue for a card that has none yet.
+    async fn export_task(
+        &self,
+        board: &mut KanbanBoard,
+        link: &BoardLink,
+        task_id: &str,
+        report: &mut SyncReport,
+    ) -> Result<TaskLink> {
+        let task = board.get_task(task_id).context("card no longer exists")?;
+        let values = Snapshot::of_task(task);
+        let description = join_checklist(&values.description, &values.subtasks);
+        let request = CreateIssueRequest {
+            project_id: link.project_id.clone(),
+            title: values.title.clone(),
+            description: (!description.is_empty()).then_some(description),
+            priority: Some(priority_to_remote(values.priority)),
+            assignee: values.assignee.clone(),
+            labels: task.labels.clone(),
+        };
+        let column = task.column;
+        let issue = self.hub.create_issue(link.platform, &request).await?;
+        report.created_remote += 1;
+
+        // Creation only carries the basics. Treat everything else (status,
+        // comments) as a local edit against the fresh issue and push it.
+        let mut task_link = TaskLink {
+            task_id: task_id.to_string(),
+            remote_id: issue.id.clone(),
+            remote_key: issue.key.clone(),
+            local_updated_at: DateTime::<Utc>::MIN_UTC,
+            remote_updated_at: issue.updated_at,
+            snapshot: Snapshot::of_issue(&issue, column),
+            comments: HashMap::new(),
+            conflicts: Vec::new(),
+        };
+        if let Err(e) = self
+            .reconcile(board, link.platform, &mut task_link, None, report)
+            .await
+        {
+            record_error(report, task_id, &e);
+        }
+        Ok(task_link)
+    }
+
+    async fn pull_comments(
+        &self,
+        board: &mut KanbanBoard,
+        platform: PMPlatform,
+        link: &mut TaskLink,
+        report: &mut SyncReport,
+    ) -> Result<()> {
+        let comments = self.hub.list_comments(platform, &link.remote_id).await?;
+        let known: HashSet<String> = link.comments.values().cloned().collect();
+        let task = board
+            .get_task_mut(&link.task_id)
+            .context("card no longer exists")?;
+        for comment in comments.into_iter().filter(|c| !known.contains(&c.id)) {
+            let local_id = Uuid::new_v4().to_string();
+            task.comments.push(TaskComment {
+                id: local_id.clone(),
+                author: comment.author.unwrap_or_else(|| platform.to_string()),
+                content: comment.body,
+                created_at: comment.created_at.unwrap_or_else(Utc::now),
+            });
+            link.comments.insert(local_id, comment.id);
+            report.comments_pulled += 1;
+        }
+        Ok(())
+    }
+
+    async fn push_comments(
+        &self,
+        board: &KanbanBoard,
+        platform: PMPlatform,
+        link: &mut TaskLink,
+        report: &mut SyncReport,
+    ) -> Result<()> {
+        let task = board
+            .get_task(&link.task_id)
+            .context("card no longer exists")?;
+        let pending: Vec<&TaskComment> = task
+            .comments
+            .iter()
+            .filter(|c| !link.comments.contains_key(&c.id))
+            .collect();
+        for comment in pending {
+            // The tracker attributes the comment to the API user.
+            let body = format!("{}: {}", comment.author, comment.content);
+            let remote = self
+                .hub
+                .add_comment(platform, &link.remote_id, &body)
+                .await?;
+            link.comments.insert(comment.id.clone(), remote.id);
+            report.comments_pushed += 1;
+        }
+        Ok(())
+    }
+}
+
+fn record_error(report: &mut SyncReport, task_id: &str, error: &anyhow::Error) {
+    warn!(task_id = %task_id, error = %format!("{error:#}"), "kanban sync failed for card");
+    report.errors.push(format!("{task_id}: {error:#}"));
+}
+
+// ── Field mapping ──────────────────────────────────────────────────
+
+fn column_to_status(column: KanbanColumn) -> IssueStatus {
+    match column {
+        KanbanColumn::Todo => IssueStatus::Todo,
+        KanbanColumn::InProgress | KanbanColumn::Blocked => IssueStatus::InProgress,
+        KanbanColumn::Review => IssueStatus::InReview,
+        KanbanColumn::Done => IssueStatus::Done,
+    }
+}
+
+/// Column for a remote status. A card that is blocked or in review stays
+/// there while the issue is in progress, since not every tracker can tell
+/// those states apart.
+fn status_to_column(status: IssueStatus, current: KanbanColumn) -> KanbanColumn {
+    match status {
+        IssueStatus::Backlog | IssueStatus::Todo => KanbanColumn::Todo,
+        IssueStatus::InProgress
+            if matches!(current, KanbanColumn::Blocked | KanbanColumn::Review) =>
+        {
+            current
+        }
+        IssueStatus::InProgress => KanbanColumn::InProgress,
+        IssueStatus::InReview => KanbanColumn::Review,
+        IssueStatus::Done | IssueStatus::Cancelled => KanbanColumn::Done,
+    }
+}
+
+fn priority_to_remote(priority: Priority) -> IssuePriority {
+    match priority {
+        Priority::Low => IssuePriority::Low,
+        Priority::Medium => IssuePriority::Medium,
+        Priority::High => IssuePriority::High,
+        Priority::Critical => IssuePriority::Critical,
+    }
+}
+
+fn priority_from_remote(priority: IssuePriority) -> Priority {
+    match priority {
+        IssuePriority::Low => Priority::Low,
+        IssuePriority::Medium | IssuePriority::None => Priority::Medium,
+        IssuePriority::High => Priority::High,
+        IssuePriority::Critical => Priority::Critical,
+    }
+}
+
+/// Split a trailing `Subtasks:` checklist off an issue description.
+fn split_checklist(text: &str) -> (String, Vec<ChecklistItem>) {
+    let lines: Vec<&str> = text.lines().collect();
+    if let Some(pos) = lines.iter().rposition(|l| l.trim() == SUBTASKS_HEADING) {
+        let items: Option<Vec<ChecklistItem>> = lines[pos + 1..]
+            .iter()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| parse_checklist_item(l))
+            .collect();
+        if let Some(items) = items {
+            return (lines[..pos].join("\n").trim().to_string(), items);
+        }
+    }
+    (text.trim().to_string(), Vec::new())
+}
+
+fn parse_checklist_item(line: &str) -> Option<ChecklistItem> {
+    let (mark, title) = line.trim().strip_prefix("- [")?.split_once("] ")?;
+    let done = match mark {
+        " " => false,
+        "x" | "X" => true,
+        _ => return None,
+    };
+    Some(ChecklistItem {
+        title: title.trim().to_string(),
+        done,
+    })
+}
+
+fn join_checklist(description: &str, subtasks: &[ChecklistItem]) -> String {
+    if subtasks.is_empty() {
+        return description.to_string();
+    }
+    let mut text = description.to_string();
+    if !text.is_empty() {
+        text.push_str("\n\n");
+    }
+    text.push_str(SUBTASKS_HEADING);
+    for item in subtasks {
+        let mark = if item.done { 'x' } else { ' ' };
+        text.push_str(&format!("\n- [{mark}] {}", item.title));
+    }
+    text
+}
+
+fn apply_to_task(task: &mut KanbanTask, values: &Snapshot, fields: &[SyncField]) {
+    for field in fields {
+        match field {
+            SyncField::Title => task.title = values.title.clone(),
+            SyncField::Description => {
+                task.description =
+                    (!values.description.is_empty()).then(|| values.description.clone());
+            }
+            SyncField::Status => task.column = values.column,
+            SyncField::Priority => task.priority = values.priority,
+            SyncField::Assignee => task.assignee = values.assignee.clone(),
+            SyncField::Subtasks => {
+                // Subtasks that survive by title keep their ids.
+                let mut previous = std::mem::take(&mut task.subtasks);
+                task.subtasks = values
+                    .subtasks
+                    .iter()
+                    .map(|item| {
+                        let id = previous
+                            .iter()
+                            .position(|s| s.title == item.title)
+                            .map(|i| previous.remove(i).id)
+                            .unwrap_or_else(|| Uuid::new_v4().to_string());
+                        Subtask {
+                            id,
+                            title: item.title.clone(),
+                            completed: item.done,
+                        }
+                    })
+                    .collect();
+            }
+        }
+    }
+    task.updated_at = Utc::now();
+}
+
+fn build_update(values: &Snapshot, fields: &[SyncField]) -> IssueUpdate {
+    let mut update = IssueUpdate::default();
+    for field in fields {
+        match field {
+            SyncField::Title => update.title = Some(values.title.clone()),
+            SyncField::Description | SyncField::Subtasks => {
+                update.description = Some(join_checklist(&values.description, &values.subtasks));
+            }
+            SyncField::Status => update.status = Some(column_to_status(values.column)),
+            SyncField::Priority => update.priority = Some(priority_to_remote(values.priority)),
+            // Providers cannot clear an assignee, so only set ones are pushed.
+            SyncField::Assignee => update.assignee = values.assignee.clone(),
+        }
+    }
+    update
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_management::JiraClient;
+    use serde_json::{Value, json};
+    use std::sync::{Arc, Mutex};
+
+    // ── Mock Jira ──────────────────────────────────────────────────
+
+    /// In-memory Jira project. Every write advances the clock by a minute,
+    /// so `updated >= "..."` in JQL selects exactly the later writes.
+    #[derive(Default)]
+    struct MockJira {
+        issues: Vec<Value>,
+        comments: HashMap<String, Vec<Value>>,
+        clock: i64,
+        jql: Vec<String>,
+    }
+
+    impl MockJira {
+        fn tick(&mut self) -> String {
+            self.clock += 1;
+            let at = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z").unwrap()
+                + chrono::Duration::minutes(self.clock);
+            at.with_timezone(&Utc).to_rfc3339()
+        }
+
+        fn seed(
+            &mut self,
+            summary: &str,
+            description: &str,
+            category: &str,
+            priority: &str,
+        ) -> String {
+            let n = self.issues.len() + 1;
+            let now = self.tick();
+            self.issues.push(json!({
+                "id": format!("{}", 10000 + n),
+                "key": format!("HIVE-{n}"),
+                "fields": {
+                    "summary": summary,
+                    "description": description,
+                    "status": status_json(category),
+                    "priority": {"name": priority},
+                    "assignee": null,
+                    "labels": [],
+                    "created": now,
+                    "updated": now,
+                }
+            }));
+            format!("{}", 10000 + n)
+        }
+
+        fn issue_mut(&mut self, id: &str) -> Option<&mut Value> {
+            self.issues
+                .iter_mut()
+                .find(|i| i["id"] == id || i["key"] == id)
+        }
+
+        fn edit(&mut self, id: &str, field: &str, value: Value) {
+            let now = self.tick();
+            let issue = self.issue_mut(id).unwrap();
+            issue["fields"][field] = value;
+            issue["fields"]["updated"] = json!(now);
+        }
+
+        fn field(&mut self, id: &str, field: &str) -> Value {
+            self.issue_mut(id).unwrap()["fields"][field].clone()
+        }
+
+        fn handle(&mut self, method: &str, path: &str, query: &str, body: Value) -> (u16, Value) {
+            let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+            match (method, segments.as_slice()) {
+                ("GET", ["search"]) => {
+                    let jql = url::form_urlencoded::parse(query.as_bytes())
+                        .find(|(k, _)| k == "jql")
+                        .map(|(_, v)| v.into_owned())
+                        .unwrap_or_default();
+                    let since = jql.split("updated >= \"").nth(1).and_then(|rest| {
+                        let stamp = rest.split('"').next()?;
+                        chrono::NaiveDateTime::parse_from_str(stamp, "%Y-%m-%d %H:%M").ok()
+                    });
+                    self.jql.push(jql);
+                    let issues: Vec<Value> = self
+                        .issues
+                        .iter()
+                        .filter(|i| {
+                            since.is_none_or(|since| {
+                                let updated = i["fields"]["updated"].as_str().unwrap();
+                                DateTime::parse_from_rfc3339(updated).unwrap().naive_utc() >= since
+                            })
+                        })
+                        .cloned()
+                        .collect();
+                    (200, json!({"issues": issues, "total": issues.len()}))
+                }
+                ("POST", ["issue"]) => {
+                    let fields = &body["fields"];
+                    let id = self.seed(
+                        fields["summary"].as_str().unwrap(),
+                        "",
+                        "new",
+                        fields["priority"]["name"].as_str().unwrap_or("Medium"),
+                    );
+                    let issue = self.issue_mut(&id).unwrap();
+                    issue["fields"]["description"] = fields["description"].clone();
+                    if let Some(account) = fields["assignee"]["accountId"].as_str() {
+                        issue["fields"]["assignee"] = json!({"displayName": account});
+                    }
+                    let key = issue["key"].clone();
+                    (201, json!({"id": id, "key": key}))
+                }
+                ("GET", ["issue", id]) => match self.issue_mut(id) {
+                    Some(issue) => (200, issue.clone()),
+                    None => (404, json!({"errorMessages": ["Issue does not exist"]})),
+                },
+                ("PUT", ["issue", id]) => {
+                    for (name, value) in body["fields"].as_object().unwrap() {
+                        let value = match name.as_str() {
+                            "assignee" => json!({"displayName": value["accountId"]}),
+                            _ => value.clone(),
+                        };
+                        self.edit(id, name, value);
+                    }
+                    (204, Value::Null)
+                }
+                ("GET", ["issue", _, "transitions"]) => (
+                    200,
+                    json!({"transitions": [
+                        {"id": "11", "name": "To Do", "to": status_json("new")},
+                        {"id": "21", "name": "In Progress", "to": status_json("indeterminate")},
+                        {"id": "31", "name": "Done", "to": status_json("done")},
+                    ]}),
+                ),
+                ("POST", ["issue", id, "transitions"]) => {
+                    let category = match body["transition"]["id"].as_str() {
+                        Some("11") => "new",
+                        Some("21") => "indeterminate",
+                        _ => "done",
+                    };
+                    self.edit(id, "status", status_json(category));
+                    (204, Value::Null)
+                }
+                ("GET", ["issue", id, "comment"]) => {
+                    let comments = self.comments.get(*id).cloned().unwrap_or_default();
+                    (200, json!({"comments": comments}))
+                }
+                ("POST", ["issue", id, "comment"]) => {
+                    let comment = self.add_comment(id, "Hive Bot", body["body"].clone());
+                    (201, comment)
+                }
+                _ => (404, json!({})),
+            }
+        }
+
+        fn add_comment(&mut self, id: &str, author: &str, body: Value) -> Value {
+            let now = self.tick();
+            let comment_id = format!("c{}", self.clock);
+            let comment = json!({
+                "id": comment_id,
+                "author": {"displayName": author},
+                "body": body,
+                "created": now,
+            });
+            self.comments
+                .entry(id.to_string())
+                .or_default()
+                .push(comment.clone());
+            self.issue_mut(id).unwrap()["fields"]["updated"] = json!(now);
+            comment
+        }
+    }
+
+    /// Text of the single-paragraph ADF documents the Jira client writes.
+    fn adf_text(doc: &Value) -> &str {
+        doc["content"][0]["content"][0]["text"]
+            .as_str()
+            .unwrap_or_default()
+    }
+
+    fn status_json(category: &str) -> Value {
+        let name = match category {
+            "new" => "To Do",
+            "indeterminate" => "In Progress",
+            _ => "Done",
+        };
+        json!({"name": name, "statusCategory": {"key": category}})
+    }
+
+    /// Serve `mock` over HTTP on a local port and return its base URL.
+    fn serve(mock: Arc<Mutex<MockJira>>) -> String {
+        use std::io::{BufRead, BufReader, Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap_or_default().to_string();
+                let target = parts.next().unwrap_or("/").to_string();
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        content_length = value.trim().parse().unwrap_or(0);
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let body = serde_json::from_slice(&body).unwrap_or(Value::Null);
+                let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+                let (status, payload) = mock.lock().unwrap().handle(&method, path, query, body);
+                let payload = if payload.is_null() {
+                    String::new()
+                } else {
+                    payload.to_string()
+                };
+                let response = format!(
+                    "HTTP/1.1 {status} OK\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{payload}",
+                    payload.len()
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        base
+    }
+
+    fn jira_hub(mock: &Arc<Mutex<MockJira>>) -> ProjectManagementHub {
+        let base = serve(mock.clone());
+        let client = JiraClient::with_base_url("hive", "bot@example.com", "token", &base).unwrap();
+        let mut hub = ProjectManagementHub::new();
+        hub.register_provider(Box::new(client));
+        hub
+    }
+
+    fn new_state() -> SyncState {
+        SyncState::new(BoardLink {
+            platform: PMPlatform::Jira,
+            project_id: "HIVE".into(),
+        })
+    }
+
+    /// A mock with one in-progress issue (two subtasks, one comment) and a
+    /// board with one card in review, synced once.
+    async fn synced_pair() -> (
+        Arc<Mutex<MockJira>>,
+        ProjectManagementHub,
+        KanbanBoard,
+        SyncState,
+    ) {
+        let mock = Arc::new(Mutex::new(MockJira::default()));
+        {
+            let mut jira = mock.lock().unwrap();
+            let id = jira.seed(
+                "Fix login",
+                "Users get logged out.\n\nSubtasks:\n- [x] Reproduce\n- [ ] Patch",
+                "indeterminate",
+                "High",
+            );
+            jira.add_comment(&id, "Ada", json!("Seen on prod"));
+        }
+        let hub = jira_hub(&mock);
+
+        let mut board = KanbanBoard::new();
+        let card = board.add_task(
+            "Write docs",
+            Some("For the sync engine".into()),
+            Priority::Low,
+        );
+        board.add_subtask(&card.id, "Outline").unwrap();
+        board
+            .add_comment(&card.id, "Coordinator", "Started")
+            .unwrap();
+        board.move_task(&card.id, KanbanColumn::Review).unwrap();
+
+        let mut state = new_state();
+        let report = KanbanSync::new(&hub)
+            .sync(&mut board, &mut state)
+            .await
+            .unwrap();
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        (mock, hub, board, state)
+    }
+
+    fn card_titled<'b>(board: &'b KanbanBoard, title: &str) -> &'b KanbanTask {
+        board.all_tasks().iter().find(|t| t.title == title).unwrap()
+    }
+
+    // ── Engine ─────────────────────────────────────────────────────
+
+    #[tokio::test]
+    async fn test_first_sync_links_both_sides() {
+        let (mock, _hub, board, state) = synced_pair().await;
+        assert_eq!(state.links().len(), 2);
+        assert!(state.last_pulled_at.is_some());
+
+        // The issue became a card.
+        let imported = card_titled(&board, "Fix login");
+        assert_eq!(imported.column, KanbanColumn::InProgress);
+        assert_eq!(imported.priority, Priority::High);
+        assert_eq!(
+            imported.description.as_deref(),
+            Some("Users get logged out.")
+        );
+        let subtasks: Vec<(&str, bool)> = imported
+            .subtasks
+            .iter()
+            .map(|s| (s.title.as_str(), s.completed))
+            .collect();
+        assert_eq!(subtasks, [("Reproduce", true), ("Patch", false)]);
+        assert_eq!(imported.comments.len(), 1);
+        assert_eq!(imported.comments[0].author, "Ada");
+        assert_eq!(imported.comments[0].content, "Seen on prod");
+
+        // The card became an issue, moved to review and commented on.
+        let card = card_titled(&board, "Write docs");
+        let link = state.link_for_task(&card.id).unwrap();
+        assert_eq!(link.remote_key.as_deref(), Some("HIVE-2"));
+        let mut jira = mock.lock().unwrap();
+        assert_eq!(
+            jira.field("HIVE-2", "status")["statusCategory"]["key"],
+            "indeterminate"
+        );
+        assert_eq!(jira.field("HIVE-2", "priority")["name"], "Low");
+        let description = jira.field("HIVE-2", "description");
+        assert_eq!(
+            adf_text(&description),
+            "For the sync engine\n\nSubtasks:\n- [ ] Outline"
+        );
+        let comments = &jira.comments[&link.remote_id];
+        assert_eq!(adf_text(&comments[0]["body"]), "Coordinator: Started");
+    }
+
+    #[tokio::test]
+    async fn test_second_sync_without_changes_is_a_no_op() {
+        let (mock, hub, mut board, mut state) = synced_pair().await;
+        let report = KanbanSync::new(&hub)
+            .sync(&mut board, &mut state)
+            .await
+            .unwrap();
+        assert_eq!(report.pulled, 0);
+        assert_eq!(report.pushed, 0);
+        assert_eq!(report.created_local + report.created_remote, 0);
+        assert_eq!(report.comments_pulled + report.comments_pushed, 0);
+        assert!(report.conflicts.is_empty());
+
+        // The card in review reads back from Jira as "in progress" but stays put.
+        assert_eq!(
+            card_titled(&board, "Write docs").column,
+            KanbanColumn::Review
+        );
+        let jira = mock.lock().unwrap();
+        assert!(
+            jira.jql[1].contains("updated >= \"2026-01-01"),
+            "{}",
+            jira.jql[1]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_incremental_pull_and_push() {
+        let (mock, hub, mut board, mut state) = synced_pair().await;
+        let remote_id = state
+            .link_for_task(&card_titled(&board, "Fix login").id)
+            .unwrap()
+            .remote_id
+            .clone();
+        {
+            let mut jira = mock.lock().unwrap();
+            jira.edit(&remote_id, "summary", json!("Fix login redirect"));
+            jira.add_comment(&remote_id, "Grace", json!("Patched in staging"));
+        }
+        let docs_id = card_titled(&board, "Write docs").id.clone();
+        board.move_task(&docs_id, KanbanColumn::Done).unwrap();
+        board.add_comment(&docs_id, "Alice", "Shipped").unwrap();
+
+        let report = KanbanSync::new(&hub)
+            .sync(&mut board, &mut state)
+            .await
+            .unwrap();
+        assert_eq!(report.pulled, 1);
+        assert_eq!(report.pushed, 1);
+        assert_eq!(report.comments_pulled, 1);
+        assert_eq!(report.comments_pushed, 1);
+
+        let pulled = card_titled(&board, "Fix login redirect");
+        assert_eq!(
+            pulled.comments.last().unwrap().content,
+            "Patched in staging"
+        );
+        let docs_remote = state.link_for_task(&docs_id).unwrap().remote_id.clone();
+        let mut jira = mock.lock().unwrap();
+        assert_eq!(
+            jira.field(&docs_remote, "status")["statusCategory"]["key"],
+            "done"
+        );
+        assert_eq!(jira.comments[&docs_remote].len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_conflict_is_reported_until_resolved() {
+        let (mock, hub, mut board, mut state) = synced_pair().await;
+        let card_id = card_titled(&board, "Fix login").id.clone();
+        let remote_id = state.link_for_task(&card_id).unwrap().remote_id.clone();
+        mock.lock()
+            .unwrap()
+            .edit(&remote_id, "summary", json!("Remote title"));
+        board
+            .update_task(
+                &card_id,
+                Some("Local title".into()),
+                None,
+                Some(Priority::Critical),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+
+        // Priority only changed locally and is pushed; the title is a conflict.
+        let report = KanbanSync::new(&hub)
+            .sync(&mut board, &mut state)
+            .await
+            .unwrap();
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].fields, [SyncField::Title]);
+        assert_eq!(board.get_task(&card_id).unwrap().title, "Local title");
+        assert_eq!(
+            mock.lock().unwrap().field(&remote_id, "summary"),
+            "Remote title"
+        );
+        assert_eq!(
+            mock.lock().unwrap().field(&remote_id, "priority")["name"],
+            "Highest"
+        );
+
+        // Still reported although the issue no longer shows up in the pull.
+        let report = KanbanSync::new(&hub)
+            .sync(&mut board, &mut state)
+            .await
+            .unwrap();
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(
+            state.link_for_task(&card_id).unwrap().conflicts(),
+            [SyncField::Title]
+        );
+
+        let report = KanbanSync::new(&hub)
+            .with_policy(ConflictPolicy::PreferRemote)
+            .sync(&mut board, &mut state)
+            .await
+            .unwrap();
+        assert!(report.conflicts.is_empty());
+        assert_eq!(board.get_task(&card_id).unwrap().title, "Remote title");
+        assert!(
+            state
+                .link_for_task(&card_id)
+                .unwrap()
+                .conflicts()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_blocked_card_survives_remote_in_progress() {
+        let (mock, hub, mut board, mut state) = synced_pair().await;
+        let card_id = card_titled(&board, "Fix login").id.clone();
+        let remote_id = state.link_for_task(&card_id).unwrap().remote_id.clone();
+        board.move_task(&card_id, KanbanColumn::Blocked).unwrap();
+        mock.lock()
+            .unwrap()
+            .edit(&remote_id, "priority", json!({"name": "Lowest"}));
+
+        let report = KanbanSync::new(&hub)
+            .sync(&mut board, &mut state)
+            .await
+            .unwrap();
+        assert!(report.conflicts.is_empty());
+        let card = board.get_task(&card_id).unwrap();
+        assert_eq!(card.column, KanbanColumn::Blocked);
+        assert_eq!(card.priority, Priority::Medium);
+    }
+
+    #[tokio::test]
+    async fn test_move_card_pushes_immediately() {
+        let (mock, hub, mut board, mut state) = synced_pair().await;
+        let card_id = card_titled(&board, "Fix login").id.clone();
+        let remote_id = state.link_for_task(&card_id).unwrap().remote_id.clone();
+
+        let report = KanbanSync::new(&hub)
+            .move_card(&mut board, &mut state, &card_id, KanbanColumn::Done)
+            .await
+            .unwrap();
+        assert_eq!(report.pushed, 1);
+        assert_eq!(
+            mock.lock().unwrap().field(&remote_id, "status")["statusCategory"]["key"],
+            "done"
+        );
+
+        // A brand-new card is created on the tracker by the same call.
+        let fresh = board.add_task("Follow-up", None, Priority::Medium).id;
+        let report = KanbanSync::new(&hub)
+            .move_card(&mut board, &mut state, &fresh, KanbanColumn::InProgress)
+            .await
+            .unwrap();
+        assert_eq!(report.created_remote, 1);
+        assert!(state.link_for_task(&fresh).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_deleted_card_is_not_reimported() {
+        let (mock, hub, mut board, mut state) = synced_pair().await;
+        let card_id = card_titled(&board, "Fix login").id.clone();
+        let remote_id = state.link_for_task(&card_id).unwrap().remote_id.clone();
+        board.delete_task(&card_id).unwrap();
+        mock.lock()
+            .unwrap()
+            .edit(&remote_id, "summary", json!("Still open"));
+
+        let report = KanbanSync::new(&hub)
+            .sync(&mut board, &mut state)
+            .await
+            .unwrap();
+        assert_eq!(report.unlinked, 1);
+        assert_eq!(report.created_local, 0);
+        assert_eq!(board.all_tasks().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_state_persists() {
+        let (_mock, _hub, _board, state) = synced_pair().await;
+        let dir = std::env::temp_dir().join(format!("hive_kanban_sync_test_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("kanban_sync.json");
+        state.save(&path).unwrap();
+
+        let loaded = SyncState::load(&path).unwrap();
+        assert_eq!(loaded.link, state.link);
+        assert_eq!(loaded.last_pulled_at, state.last_pulled_at);
+        assert_eq!(loaded.links().len(), 2);
+        assert_eq!(loaded.links()[0].snapshot, state.links()[0].snapshot);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // ── Mapping ────────────────────────────────────────────────────
+
+    #[test]
+    fn test_checklist_roundtrip() {
+        let items = vec![
+            ChecklistItem {
+                title: "One".into(),
+                done: true,
+            },
+            ChecklistItem {
+                title: "Two".into(),
+                done: false,
+            },
+        ];
+        let text = join_checklist("Body", &items);
+        assert_eq!(text, "Body\n\nSubtasks:\n- [x] One\n- [ ] Two");
+        assert_eq!(split_checklist(&text), ("Body".to_string(), items.clone()));
+        assert_eq!(
+            split_checklist(&join_checklist("", &items)),
+            (String::new(), items)
+        );
+        assert_eq!(join_checklist("Body", &[]), "Body");
+    }
+
+    #[test]
+    fn test_split_checklist_ignores_prose() {
+        let text = "Subtasks:\nwe have none yet";
+        assert_eq!(split_checklist(text), (text.to_string(), Vec::new()));
+    }
+
+    #[test]
+    fn test_status_mapping_keeps_local_refinements() {
+        assert_eq!(
+            column_to_status(KanbanColumn::Blocked),
+            IssueStatus::InProgress
+        );
+        assert_eq!(
+            status_to_column(IssueStatus::InProgress, KanbanColumn::Blocked),
+            KanbanColumn::Blocked
+        );
+        assert_eq!(
+            status_to_column(IssueStatus::InProgress, KanbanColumn::Todo),
+            KanbanColumn::InProgress
+        );
+        assert_eq!(
+            status_to_column(IssueStatus::Cancelled, KanbanColumn::Todo),
+            KanbanColumn::Done
+        );
+        assert_eq!(
+            status_to_column(IssueStatus::Backlog, KanbanColumn::Done),
+            KanbanColumn::Todo
+        );
+    }
+
+    #[test]
+    fn test_priority_mapping() {
+        for priority in [
+            Priority::Low,
+            Priority::Medium,
+            Priority::High,
+            Priority::Critical,
+        ] {
+            assert_eq!(priority_from_remote(priority_to_remote(priority)), priority);
+        }
+        assert_eq!(priority_from_remote(IssuePriority::None), Priority::Medium);
+    }
+}
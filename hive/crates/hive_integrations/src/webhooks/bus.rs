@@ -0,0 +1,217 @@
+//! In-process event bus for verified inbound webhooks.
+//!
+//! The receiver publishes every accepted delivery as a [`WebhookEvent`];
+//! automations and agents subscribe with a topic pattern and receive the
+//! events that match. Slow subscribers skip what they missed rather than
+//! holding up the receiver.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
+
+use super::inbound::WebhookSource;
+
+/// Events buffered per subscriber before the oldest are dropped.
+const BUS_CAPACITY: usize = 256;
+
+/// A verified, normalized inbound webhook.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    /// Local id assigned on receipt.
+    pub id: String,
+    pub source: WebhookSource,
+    /// Sender-specific event name, e.g. `push` or `pull_request.opened`.
+    pub event: String,
+    /// The sender's delivery id, or the body digest when it sends none.
+    pub delivery_id: String,
+    /// Digest of the signed bytes, used for deduplication.
+    #[serde(default)]
+    pub fingerprint: String,
+    /// Who caused the event, when the sender says.
+    pub actor: Option<String>,
+    /// What the event is about: a repository, issue key, channel, or object id.
+    pub subject: Option<String>,
+    pub payload: Value,
+    pub received_at: DateTime<Utc>,
+}
+
+impl WebhookEvent {
+    /// `source.event`, e.g. `github.pull_request.opened`.
+    pub fn topic(&self) -> String {
+        format!("{}.{}", self.source, self.event)
+    }
+
+    /// Whether this event matches a subscription pattern.
+    ///
+    /// Patterns are dot-separated topics where `*` matches one segment and
+    /// a trailing `*` matches the rest: `github.*`, `*.push`,
+    /// `jira.issue_updated`. A pattern without a source (`push`) matches
+    /// that event from any source.
+    pub fn matches(&self, pattern: &str) -> bool {
+        let topic = self.topic();
+        topic_matches(pattern, &topic) || topic_matches(pattern, &self.event)
+    }
+}
+
+fn topic_matches(pattern: &str, topic: &str) -> bool {
+    let mut pattern_parts = pattern.split('.');
+    let mut topic_parts = topic.split('.');
+    loop {
+        match (pattern_parts.next(), topic_parts.next()) {
+            (Some("*"), Some(_)) if pattern_parts.clone().next().is_none() => return true,
+            (Some("*"), Some(_)) => {}
+            (Some(p), Some(t)) if p == t => {}
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// Fan-out channel for [`WebhookEvent`]s. Cheap to clone.
+#[derive(Clone)]
+pub struct WebhookEventBus {
+    tx: broadcast::Sender<Arc<WebhookEvent>>,
+}
+
+impl WebhookEventBus {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(BUS_CAPACITY);
+        Self { tx }
+    }
+
+    /// Publish an event. Returns how many subscribers were listening.
+    pub fn publish(&self, event: WebhookEvent) -> usize {
+        let topic = event.topic();
+        let receivers = self.tx.send(Arc::new(event)).unwrap_or(0);
+        debug!(topic = %topic, receivers, "published webhook event");
+        receivers
+    }
+
+    /// Subscribe to events whose topic matches `pattern` (see
+    /// [`WebhookEvent::matches`]). Use `*` for everything.
+    pub fn subscribe(&self, pattern: impl Into<String>) -> WebhookSubscription {
+        WebhookSubscription {
+            rx: self.tx.subscribe(),
+            pattern: pattern.into(),
+        }
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+}
+
+impl Default for WebhookEventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A filtered view of the bus.
+pub struct WebhookSubscription {
+    rx: broadcast::Receiver<Arc<WebhookEvent>>,
+    pattern: String,
+}
+
+impl WebhookSubscription {
+    /// Wait for the next matching event. Returns `None` once every bus
+    /// handle has been dropped.
+    pub async fn recv(&mut self) -> Option<Arc<WebhookEvent>> {
+        loop {
+            match self.rx.recv().await {
+                Ok(event) if event.matches(&self.pattern) => return Some(event),
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(pattern = %self.pattern, skipped, "webhook subscriber lagged");
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Return the next matching event that is already queued, if any.
+    pub fn try_recv(&mut self) -> Option<Arc<WebhookEvent>> {
+        loop {
+            match self.rx.try_recv() {
+                Ok(event) if event.matches(&self.pattern) => return Some(event),
+                Ok(_) => {}
+                Err(broadcast::error::TryRecvError::Lagged(skipped)) => {
+                    warn!(pattern = %self.pattern, skipped, "webhook subscriber lagged");
+                }
+                Err(_) => return None,
+            }
+        }
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(source: WebhookSource, name: &str) -> WebhookEvent {
+        WebhookEvent {
+            id: "local-1".into(),
+            source,
+            event: name.into(),
+            delivery_id: "d-1".into(),
+            fingerprint: "f-1".into(),
+            actor: None,
+            subject: None,
+            payload: json!({}),
+            received_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_pattern_matching() {
+        let pr = event(WebhookSource::GitHub, "pull_request.opened");
+        assert!(pr.matches("*"));
+        assert!(pr.matches("github.*"));
+        assert!(pr.matches("github.pull_request.*"));
+        assert!(pr.matches("github.pull_request.opened"));
+        assert!(pr.matches("*.pull_request.opened"));
+        assert!(pr.matches("pull_request.opened"));
+        assert!(!pr.matches("github.pull_request"));
+        assert!(!pr.matches("gitlab.*"));
+        assert!(!pr.matches("github.push"));
+
+        let push = event(WebhookSource::GitLab, "push");
+        assert!(push.matches("push"));
+        assert!(push.matches("*.push"));
+        assert!(!push.matches("github.push"));
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_only_see_matching_events() {
+        let bus = WebhookEventBus::new();
+        let mut github = bus.subscribe("github.*");
+        let mut everything = bus.subscribe("*");
+        assert_eq!(bus.subscriber_count(), 2);
+
+        assert_eq!(bus.publish(event(WebhookSource::Stripe, "invoice.paid")), 2);
+        assert_eq!(bus.publish(event(WebhookSource::GitHub, "push")), 2);
+
+        assert_eq!(github.recv().await.unwrap().topic(), "github.push");
+        assert!(github.try_recv().is_none());
+        assert_eq!(
+            everything.try_recv().unwrap().topic(),
+            "stripe.invoice.paid"
+        );
+        assert_eq!(everything.try_recv().unwrap().topic(), "github.push");
+    }
+
+    #[test]
+    fn test_publish_without_subscribers() {
+        let bus = WebhookEventBus::new();
+        assert_eq!(bus.publish(event(WebhookSource::Slack, "message")), 0);
+    }
+}
@@ -0,0 +1,389 @@
+//! Persistent log of inbound webhook deliveries.
+//!
+//! Every request the receiver handles is recorded, whether it was accepted,
+//! refused, or a repeat. Accepted deliveries are unique per
+//! `(source, fingerprint)`, a digest of the signed bytes, which is what
+//! makes a replayed delivery detectable across restarts even when its
+//! unsigned delivery-id header was changed.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use hive_core::config::HiveConfig;
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+
+use super::bus::WebhookEvent;
+use super::inbound::WebhookSource;
+
+/// Outcome of one delivery attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    /// Verified and published to the event bus.
+    Accepted,
+    /// Verified, but the same signed body was already accepted.
+    Duplicate,
+    /// Refused: bad signature, stale timestamp, or malformed body.
+    Rejected,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Accepted => "accepted",
+            Self::Duplicate => "duplicate",
+            Self::Rejected => "rejected",
+        }
+    }
+
+    pub fn parse_str(s: &str) -> Self {
+        match s {
+            "accepted" => Self::Accepted,
+            "duplicate" => Self::Duplicate,
+            _ => Self::Rejected,
+        }
+    }
+}
+
+/// One row of the delivery log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryRecord {
+    pub source: WebhookSource,
+    pub delivery_id: Option<String>,
+    pub event: Option<String>,
+    pub status: DeliveryStatus,
+    pub error: Option<String>,
+    pub received_at: DateTime<Utc>,
+}
+
+/// SQLite-backed delivery log.
+pub struct DeliveryLog {
+    conn: Mutex<Connection>,
+}
+
+impl DeliveryLog {
+    /// Open (or create) the log at `path`.
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open delivery log {}", path.display()))?;
+        Self::init_tables(&conn)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// An in-memory log (useful for testing).
+    pub fn in_memory() -> Result<Self> {
+        let conn = Connection::open_in_memory().context("Failed to open in-memory log")?;
+        Self::init_tables(&conn)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// `~/.hive/webhooks.db`
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(HiveConfig::base_dir()?.join("webhooks.db"))
+    }
+
+    fn init_tables(conn: &Connection) -> Result<()> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS deliveries (
+                id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                source      TEXT NOT NULL,
+                delivery_id TEXT,
+                event       TEXT,
+                status      TEXT NOT NULL,
+                error       TEXT,
+                payload     TEXT,
+                received_at TEXT NOT NULL,
+                fingerprint TEXT
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_deliveries_received
+                ON deliveries(received_at DESC);",
+        )
+        .context("Failed to initialise delivery log")?;
+
+        // Logs written before fingerprints keyed acceptance on the
+        // unsigned delivery id; move the uniqueness onto the fingerprint.
+        let has_fingerprint = conn
+            .prepare("SELECT 1 FROM pragma_table_info('deliveries') WHERE name = 'fingerprint'")?
+            .exists([])?;
+        if !has_fingerprint {
+            conn.execute_batch("ALTER TABLE deliveries ADD COLUMN fingerprint TEXT;")
+                .context("Failed to migrate delivery log")?;
+        }
+        conn.execute_batch(
+            "DROP INDEX IF EXISTS idx_deliveries_accepted;
+
+            CREATE UNIQUE INDEX IF NOT EXISTS idx_deliveries_fingerprint
+                ON deliveries(source, fingerprint) WHERE status = 'accepted';",
+        )
+        .context("Failed to index delivery log")
+    }
+
+    /// Record a verified event. Returns `false` (and records a duplicate)
+    /// when an event with the same fingerprint was already accepted.
+    pub fn record_accepted(&self, event: &WebhookEvent) -> Result<bool> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+        let payload = serde_json::to_string(&event.payload)?;
+        let inserted = conn
+            .execute(
+                "INSERT OR IGNORE INTO deliveries
+                    (source, delivery_id, event, status, payload, received_at, fingerprint)
+                 VALUES (?1, ?2, ?3, 'accepted', ?4, ?5, ?6)",
+                params![
+                    event.source.as_str(),
+                    event.delivery_id,
+                    event.event,
+                    payload,
+                    event.received_at.to_rfc3339(),
+                    event.fingerprint,
+                ],
+            )
+            .context("Failed to record delivery")?;
+        if inserted == 0 {
+            conn.execute(
+                "INSERT INTO deliveries
+                    (source, delivery_id, event, status, received_at, fingerprint)
+                 VALUES (?1, ?2, ?3, 'duplicate', ?4, ?5)",
+                params![
+                    event.source.as_str(),
+                    event.delivery_id,
+                    event.event,
+                    event.received_at.to_rfc3339(),
+                    event.fingerprint,
+                ],
+            )
+            .context("Failed to record duplicate delivery")?;
+        }
+        Ok(inserted > 0)
+    }
+
+    /// Record a refused delivery.
+    pub fn record_rejected(
+        &self,
+        source: WebhookSource,
+        delivery_id: Option<&str>,
+        error: &str,
+    ) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+        conn.execute(
+            "INSERT INTO deliveries (source, delivery_id, status, error, received_at)
+             VALUES (?1, ?2, 'rejected', ?3, ?4)",
+            params![source.as_str(), delivery_id, error, Utc::now().to_rfc3339()],
+        )
+        .context("Failed to record rejected delivery")?;
+        Ok(())
+    }
+
+    /// The most recent deliveries, newest first.
+    pub fn recent(&self, limit: usize) -> Result<Vec<DeliveryRecord>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+        let mut stmt = conn.prepare(
+            "SELECT source, delivery_id, event, status, error, received_at
+             FROM deliveries ORDER BY id DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit as i64], |row| {
+            let source: String = row.get(0)?;
+            let status: String = row.get(3)?;
+            let received_at: String = row.get(5)?;
+            Ok((
+                source,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                status,
+                row.get::<_, Option<String>>(4)?,
+                received_at,
+            ))
+        })?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            let (source, delivery_id, event, status, error, received_at) = row?;
+            let Some(source) = WebhookSource::parse_str(&source) else {
+                continue;
+            };
+            records.push(DeliveryRecord {
+                source,
+                delivery_id,
+                event,
+                status: DeliveryStatus::parse_str(&status),
+                error,
+                received_at: DateTime::parse_from_rfc3339(&received_at)
+                    .map(|t| t.with_timezone(&Utc))
+                    .unwrap_or_default(),
+            });
+        }
+        Ok(records)
+    }
+
+    /// The stored payload of an accepted delivery, for replaying it by hand.
+    pub fn payload(
+        &self,
+        source: WebhookSource,
+        delivery_id: &str,
+    ) -> Result<Option<serde_json::Value>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+        let payload: Option<String> = conn
+            .query_row(
+                "SELECT payload FROM deliveries
+                 WHERE source = ?1 AND delivery_id = ?2 AND status = 'accepted'
+                 ORDER BY id DESC LIMIT 1",
+                params![source.as_str(), delivery_id],
+                |row| row.get(0),
+            )
+            .ok();
+        payload
+            .map(|json| serde_json::from_str(&json).context("Corrupt stored payload"))
+            .transpose()
+    }
+
+    /// Delete entries received before `cutoff`. Returns how many were removed.
+    ///
+    /// Pruning accepted rows also forgets their fingerprints, so keep the
+    /// cutoff well beyond any sender's retry horizon.
+    pub fn prune_before(&self, cutoff: DateTime<Utc>) -> Result<usize> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+        let removed = conn
+            .execute(
+                "DELETE FROM deliveries WHERE received_at < ?1",
+                params![cutoff.to_rfc3339()],
+            )
+            .context("Failed to prune delivery log")?;
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(delivery_id: &str) -> WebhookEvent {
+        WebhookEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            source: WebhookSource::GitHub,
+            event: "push".into(),
+            delivery_id: delivery_id.into(),
+            fingerprint: format!("f-{delivery_id}"),
+            actor: None,
+            subject: None,
+            payload: json!({"ref": "refs/heads/main"}),
+            received_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_duplicate_delivery_detected() {
+        let log = DeliveryLog::in_memory().unwrap();
+        assert!(log.record_accepted(&event("d-1")).unwrap());
+        assert!(!log.record_accepted(&event("d-1")).unwrap());
+        assert!(log.record_accepted(&event("d-2")).unwrap());
+
+        let statuses: Vec<_> = log.recent(10).unwrap().iter().map(|r| r.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                DeliveryStatus::Accepted,
+                DeliveryStatus::Duplicate,
+                DeliveryStatus::Accepted
+            ]
+        );
+        let payload = log.payload(WebhookSource::GitHub, "d-1").unwrap().unwrap();
+        assert_eq!(payload["ref"], "refs/heads/main");
+    }
+
+    #[test]
+    fn test_duplicate_detected_by_fingerprint_not_delivery_id() {
+        let log = DeliveryLog::in_memory().unwrap();
+        let original = event("d-1");
+        let mut replay = event("forged-id");
+        replay.fingerprint = original.fingerprint.clone();
+        assert!(log.record_accepted(&original).unwrap());
+        assert!(!log.record_accepted(&replay).unwrap());
+    }
+
+    #[test]
+    fn test_legacy_log_is_migrated() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE deliveries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, source TEXT NOT NULL,
+                delivery_id TEXT, event TEXT, status TEXT NOT NULL, error TEXT,
+                payload TEXT, received_at TEXT NOT NULL
+            );
+            CREATE UNIQUE INDEX idx_deliveries_accepted
+                ON deliveries(source, delivery_id) WHERE status = 'accepted';
+            INSERT INTO deliveries (source, delivery_id, status, received_at)
+                VALUES ('github', 'd-1', 'accepted', '2026-01-01T00:00:00Z');",
+        )
+        .unwrap();
+        DeliveryLog::init_tables(&conn).unwrap();
+        let log = DeliveryLog {
+            conn: Mutex::new(conn),
+        };
+        // Same delivery id, different body: no longer collides.
+        assert!(log.record_accepted(&event("d-1")).unwrap());
+        assert!(!log.record_accepted(&event("d-1")).unwrap());
+    }
+
+    #[test]
+    fn test_rejection_does_not_block_later_acceptance() {
+        let log = DeliveryLog::in_memory().unwrap();
+        log.record_rejected(WebhookSource::GitHub, Some("d-1"), "signature mismatch")
+            .unwrap();
+        assert!(log.record_accepted(&event("d-1")).unwrap());
+
+        let recent = log.recent(10).unwrap();
+        assert_eq!(recent[1].status, DeliveryStatus::Rejected);
+        assert_eq!(recent[1].error.as_deref(), Some("signature mismatch"));
+    }
+
+    #[test]
+    fn test_log_survives_reopen() {
+        let dir = std::env::temp_dir().join(format!("hive_webhooks_test_{}", uuid::Uuid::new_v4()));
+        let path = dir.join("webhooks.db");
+        {
+            let log = DeliveryLog::open(&path).unwrap();
+            assert!(log.record_accepted(&event("d-1")).unwrap());
+        }
+        let log = DeliveryLog::open(&path).unwrap();
+        assert!(!log.record_accepted(&event("d-1")).unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_prune_before() {
+        let log = DeliveryLog::in_memory().unwrap();
+        log.record_accepted(&event("d-1")).unwrap();
+        let removed = log
+            .prune_before(Utc::now() + chrono::Duration::seconds(1))
+            .unwrap();
+        assert_eq!(removed, 1);
+        assert!(log.recent(10).unwrap().is_empty());
+    }
+}
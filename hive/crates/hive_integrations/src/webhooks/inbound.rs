@@ -0,0 +1,643 @@
+//! Inbound webhook sources: signature verification and event normalization.
+//!
+//! Every supported sender signs its deliveries differently. [`verify`]
+//! checks the signature (and, where the sender signs one, the timestamp)
+//! against the shared secret, and [`normalize`] turns the verified JSON body
+//! into a [`WebhookEvent`] with a uniform `source.event` topic.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use super::bus::WebhookEvent;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// ── Sources ──────────────────────────────────────────────────────────
+
+/// A service that can deliver webhooks to the receiver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookSource {
+    GitHub,
+    GitLab,
+    Bitbucket,
+    Linear,
+    Jira,
+    Stripe,
+    Slack,
+}
+
+impl WebhookSource {
+    pub const ALL: [WebhookSource; 7] = [
+        Self::GitHub,
+        Self::GitLab,
+        Self::Bitbucket,
+        Self::Linear,
+        Self::Jira,
+        Self::Stripe,
+        Self::Slack,
+    ];
+
+    /// Lowercase identifier used in topics, routes, and the delivery log.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::GitHub => "github",
+            Self::GitLab => "gitlab",
+            Self::Bitbucket => "bitbucket",
+            Self::Linear => "linear",
+            Self::Jira => "jira",
+            Self::Stripe => "stripe",
+            Self::Slack => "slack",
+        }
+    }
+
+    pub fn parse_str(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|source| source.as_str() == s)
+    }
+
+    /// Route the receiver serves this source on unless configured otherwise.
+    pub fn default_path(&self) -> String {
+        format!("/webhooks/{}", self.as_str())
+    }
+
+    /// Whether the sender signs a timestamp that bounds how old a delivery
+    /// may be. Other senders rely on body-digest deduplication alone.
+    pub fn signs_timestamp(&self) -> bool {
+        matches!(self, Self::Linear | Self::Stripe | Self::Slack)
+    }
+}
+
+impl fmt::Display for WebhookSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Shared secret and route for one inbound source.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceConfig {
+    pub source: WebhookSource,
+    /// HMAC key (GitHub, Bitbucket, Linear, Jira, Stripe, Slack) or the
+    /// plain token GitLab echoes back in `X-Gitlab-Token`.
+    pub secret: String,
+    /// Override for [`WebhookSource::default_path`].
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+impl SourceConfig {
+    /// Refuse configurations that would accept forged deliveries.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.secret.trim().is_empty() {
+            return Err(format!(
+                "webhook source {} has an empty secret",
+                self.source
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn new(source: WebhookSource, secret: impl Into<String>) -> Self {
+        Self {
+            source,
+            secret: secret.into(),
+            path: None,
+        }
+    }
+
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// The route this source is served on.
+    pub fn route(&self) -> String {
+        self.path
+            .clone()
+            .unwrap_or_else(|| self.source.default_path())
+    }
+}
+
+// ── Verification ─────────────────────────────────────────────────────
+
+/// Why a delivery was refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    MissingHeader(&'static str),
+    BadSignature,
+    /// The signed timestamp is outside the replay window.
+    StaleTimestamp {
+        age_secs: i64,
+    },
+    Malformed(String),
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingHeader(name) => write!(f, "missing {name} header"),
+            Self::BadSignature => f.write_str("signature mismatch"),
+            Self::StaleTimestamp { age_secs } => {
+                write!(f, "timestamp is {age_secs}s outside the replay window")
+            }
+            Self::Malformed(reason) => write!(f, "malformed delivery: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// Check that `body` was signed by the holder of `config.secret`.
+///
+/// `headers` must use lowercase names. For senders that sign a timestamp,
+/// deliveries more than `tolerance` away from `now` are refused so a
+/// captured request cannot be replayed later.
+pub fn verify(
+    config: &SourceConfig,
+    headers: &HashMap<String, String>,
+    body: &[u8],
+    now: DateTime<Utc>,
+    tolerance: Duration,
+) -> Result<(), VerifyError> {
+    let secret = config.secret.as_bytes();
+    match config.source {
+        WebhookSource::GitHub => {
+            let signature = header(headers, "x-hub-signature-256")?;
+            check_prefixed_hmac(secret, body, signature)
+        }
+        WebhookSource::Bitbucket | WebhookSource::Jira => {
+            let signature = header(headers, "x-hub-signature")?;
+            check_prefixed_hmac(secret, body, signature)
+        }
+        WebhookSource::GitLab => {
+            let token = header(headers, "x-gitlab-token")?;
+            if constant_time_eq(token.as_bytes(), secret) {
+                Ok(())
+            } else {
+                Err(VerifyError::BadSignature)
+            }
+        }
+        WebhookSource::Linear => {
+            let signature = header(headers, "linear-signature")?;
+            check_hmac(secret, &[body], signature)?;
+            // The signed body carries the send time in milliseconds.
+            let payload: Value =
+                serde_json::from_slice(body).map_err(|e| VerifyError::Malformed(e.to_string()))?;
+            let millis = payload
+                .get("webhookTimestamp")
+                .and_then(Value::as_i64)
+                .ok_or_else(|| VerifyError::Malformed("missing webhookTimestamp".into()))?;
+            let sent = Utc
+                .timestamp_millis_opt(millis)
+                .single()
+                .ok_or_else(|| VerifyError::Malformed("invalid webhookTimestamp".into()))?;
+            check_freshness(sent, now, tolerance)
+        }
+        WebhookSource::Stripe => {
+            let header_value = header(headers, "stripe-signature")?;
+            let mut timestamp = None;
+            let mut signatures = Vec::new();
+            for part in header_value.split(',') {
+                match part.trim().split_once('=') {
+                    Some(("t", value)) => timestamp = Some(value),
+                    Some(("v1", value)) => signatures.push(value),
+                    _ => {}
+                }
+            }
+            let timestamp = timestamp
+                .ok_or_else(|| VerifyError::Malformed("Stripe-Signature has no t=".into()))?;
+            let signed: &[&[u8]] = &[timestamp.as_bytes(), b".", body];
+            if !signatures
+                .iter()
+                .any(|sig| check_hmac(secret, signed, sig).is_ok())
+            {
+                return Err(VerifyError::BadSignature);
+            }
+            check_freshness(parse_unix_seconds(timestamp)?, now, tolerance)
+        }
+        WebhookSource::Slack => {
+            let timestamp = header(headers, "x-slack-request-timestamp")?;
+            let signature = header(headers, "x-slack-signature")?;
+            let signature = signature
+                .strip_prefix("v0=")
+                .ok_or(VerifyError::BadSignature)?;
+            check_hmac(
+                secret,
+                &[b"v0:", timestamp.as_bytes(), b":", body],
+                signature,
+            )?;
+            check_freshness(parse_unix_seconds(timestamp)?, now, tolerance)
+        }
+    }
+}
+
+fn header<'a>(
+    headers: &'a HashMap<String, String>,
+    name: &'static str,
+) -> Result<&'a str, VerifyError> {
+    headers
+        .get(name)
+        .map(|value| value.trim())
+        .ok_or(VerifyError::MissingHeader(name))
+}
+
+/// `sha256=<hex>` as sent by GitHub, Bitbucket, and Jira.
+fn check_prefixed_hmac(secret: &[u8], body: &[u8], signature: &str) -> Result<(), VerifyError> {
+    let hex = signature
+        .strip_prefix("sha256=")
+        .ok_or(VerifyError::BadSignature)?;
+    check_hmac(secret, &[body], hex)
+}
+
+fn check_hmac(secret: &[u8], parts: &[&[u8]], hex_signature: &str) -> Result<(), VerifyError> {
+    let expected = hmac_hex(secret, parts);
+    if constant_time_eq(
+        expected.as_bytes(),
+        hex_signature.to_ascii_lowercase().as_bytes(),
+    ) {
+        Ok(())
+    } else {
+        Err(VerifyError::BadSignature)
+    }
+}
+
+/// Lowercase hex HMAC-SHA256 of the concatenated `parts`.
+pub fn hmac_hex(secret: &[u8], parts: &[&[u8]]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    for part in parts {
+        mac.update(part);
+    }
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn parse_unix_seconds(value: &str) -> Result<DateTime<Utc>, VerifyError> {
+    value
+        .parse::<i64>()
+        .ok()
+        .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+        .ok_or_else(|| VerifyError::Malformed(format!("invalid timestamp {value:?}")))
+}
+
+fn check_freshness(
+    sent: DateTime<Utc>,
+    now: DateTime<Utc>,
+    tolerance: Duration,
+) -> Result<(), VerifyError> {
+    let age_secs = (now - sent).num_seconds();
+    if age_secs.unsigned_abs() > tolerance.as_secs() {
+        Err(VerifyError::StaleTimestamp { age_secs })
+    } else {
+        Ok(())
+    }
+}
+
+// ── Normalization ────────────────────────────────────────────────────
+
+/// Turn a verified delivery into a [`WebhookEvent`].
+///
+/// The event name follows each sender's own vocabulary, joined with the
+/// action where there is one (`pull_request.opened`, `issue.create`,
+/// `invoice.paid`). The delivery id comes from the sender's idempotency
+/// header or body field; when there is none, the SHA-256 of the body
+/// stands in. Deduplication uses [`fingerprint`], which only covers signed
+/// bytes.
+pub fn normalize(
+    source: WebhookSource,
+    headers: &HashMap<String, String>,
+    body: &[u8],
+    payload: Value,
+    received_at: DateTime<Utc>,
+) -> WebhookEvent {
+    let header = |name: &str| headers.get(name).map(|v| v.trim().to_string());
+    let text = |pointer: &str| {
+        payload
+            .pointer(pointer)
+            .and_then(Value::as_str)
+            .map(String::from)
+    };
+    let with_action = |kind: String, action: Option<String>| match action {
+        Some(action) if !action.is_empty() => format!("{kind}.{action}"),
+        _ => kind,
+    };
+
+    let (event, delivery_id, actor, subject) = match source {
+        WebhookSource::GitHub => (
+            with_action(
+                header("x-github-event").unwrap_or_else(|| "unknown".into()),
+                text("/action"),
+            ),
+            header("x-github-delivery"),
+            text("/sender/login"),
+            text("/repository/full_name"),
+        ),
+        WebhookSource::GitLab => (
+            with_action(
+                text("/object_kind").unwrap_or_else(|| "unknown".into()),
+                text("/object_attributes/action"),
+            ),
+            header("x-gitlab-event-uuid").or_else(|| header("idempotency-key")),
+            text("/user_username").or_else(|| text("/user/username")),
+            text("/project/path_with_namespace"),
+        ),
+        WebhookSource::Bitbucket => (
+            header("x-event-key")
+                .map(|key| key.replace(':', "."))
+                .unwrap_or_else(|| "unknown".into()),
+            header("x-request-uuid").or_else(|| header("x-hook-uuid")),
+            text("/actor/display_name"),
+            text("/repository/full_name"),
+        ),
+        WebhookSource::Linear => (
+            with_action(
+                text("/type")
+                    .map(|kind| kind.to_lowercase())
+                    .unwrap_or_else(|| "unknown".into()),
+                text("/action"),
+            ),
+            header("linear-delivery"),
+            text("/actor/name"),
+            text("/data/identifier").or_else(|| text("/data/id")),
+        ),
+        WebhookSource::Jira => (
+            text("/webhookEvent")
+                .map(|event| event.trim_start_matches("jira:").to_string())
+                .unwrap_or_else(|| "unknown".into()),
+            header("x-atlassian-webhook-identifier"),
+            text("/user/displayName"),
+            text("/issue/key"),
+        ),
+        WebhookSource::Stripe => (
+            text("/type").unwrap_or_else(|| "unknown".into()),
+            text("/id"),
+            None,
+            text("/data/object/id"),
+        ),
+        WebhookSource::Slack => (
+            text("/event/type")
+                .or_else(|| text("/type"))
+                .unwrap_or_else(|| "unknown".into()),
+            text("/event_id"),
+            text("/event/user"),
+            text("/event/channel"),
+        ),
+    };
+
+    WebhookEvent {
+        id: Uuid::new_v4().to_string(),
+        source,
+        event,
+        delivery_id: delivery_id.unwrap_or_else(|| format!("{:x}", Sha256::digest(body))),
+        fingerprint: fingerprint(source, headers, body),
+        actor,
+        subject,
+        payload,
+        received_at,
+    }
+}
+
+/// SHA-256 over what the sender signed: the body, prefixed with the signed
+/// timestamp for Stripe and Slack.
+///
+/// Unsigned headers such as delivery ids can be rewritten by whoever
+/// replays a captured request, so they must not decide what is a repeat.
+pub fn fingerprint(
+    source: WebhookSource,
+    headers: &HashMap<String, String>,
+    body: &[u8],
+) -> String {
+    let timestamp = match source {
+        WebhookSource::Stripe => headers.get("stripe-signature").and_then(|value| {
+            value
+                .split(',')
+                .find_map(|part| part.trim().strip_prefix("t="))
+                .map(str::to_string)
+        }),
+        WebhookSource::Slack => headers
+            .get("x-slack-request-timestamp")
+            .map(|value| value.trim().to_string()),
+        _ => None,
+    };
+    let mut hasher = Sha256::new();
+    if let Some(timestamp) = timestamp {
+        hasher.update(timestamp.as_bytes());
+        hasher.update(b":");
+    }
+    hasher.update(body);
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SECRET: &str = "It's a Secret to Everybody";
+    const WINDOW: Duration = Duration::from_secs(300);
+
+    fn headers(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.timestamp_opt(1_700_000_000, 0).unwrap()
+    }
+
+    #[test]
+    fn test_github_signature_matches_documented_example() {
+        // Test vector from GitHub's "Validating webhook deliveries" guide.
+        let body = b"Hello, World!";
+        let signature = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+        let config = SourceConfig::new(WebhookSource::GitHub, SECRET);
+        let ok = headers(&[("x-hub-signature-256", signature)]);
+        assert_eq!(verify(&config, &ok, body, now(), WINDOW), Ok(()));
+
+        let tampered = verify(&config, &ok, b"Hello, World?", now(), WINDOW);
+        assert_eq!(tampered, Err(VerifyError::BadSignature));
+        let missing = verify(&config, &HashMap::new(), body, now(), WINDOW);
+        assert_eq!(
+            missing,
+            Err(VerifyError::MissingHeader("x-hub-signature-256"))
+        );
+    }
+
+    #[test]
+    fn test_gitlab_token() {
+        let config = SourceConfig::new(WebhookSource::GitLab, SECRET);
+        let ok = headers(&[("x-gitlab-token", SECRET)]);
+        assert!(verify(&config, &ok, b"{}", now(), WINDOW).is_ok());
+        let bad = headers(&[("x-gitlab-token", "guess")]);
+        assert_eq!(
+            verify(&config, &bad, b"{}", now(), WINDOW),
+            Err(VerifyError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn test_slack_signature_and_replay_window() {
+        let config = SourceConfig::new(WebhookSource::Slack, SECRET);
+        let body = br#"{"type":"event_callback"}"#;
+        let ts = now().timestamp().to_string();
+        let signature = format!(
+            "v0={}",
+            hmac_hex(SECRET.as_bytes(), &[b"v0:", ts.as_bytes(), b":", body])
+        );
+        let signed = headers(&[
+            ("x-slack-request-timestamp", &ts),
+            ("x-slack-signature", &signature),
+        ]);
+        assert!(verify(&config, &signed, body, now(), WINDOW).is_ok());
+
+        let later = now() + chrono::Duration::minutes(10);
+        assert_eq!(
+            verify(&config, &signed, body, later, WINDOW),
+            Err(VerifyError::StaleTimestamp { age_secs: 600 })
+        );
+    }
+
+    #[test]
+    fn test_stripe_accepts_any_matching_v1() {
+        let config = SourceConfig::new(WebhookSource::Stripe, SECRET);
+        let body = br#"{"id":"evt_1","type":"invoice.paid"}"#;
+        let ts = now().timestamp().to_string();
+        let good = hmac_hex(SECRET.as_bytes(), &[ts.as_bytes(), b".", body]);
+        let header_value = format!("t={ts},v1={},v1={good}", "0".repeat(64));
+        let signed = headers(&[("stripe-signature", &header_value)]);
+        assert!(verify(&config, &signed, body, now(), WINDOW).is_ok());
+
+        let old = (now().timestamp() - 3600).to_string();
+        let stale_sig = hmac_hex(SECRET.as_bytes(), &[old.as_bytes(), b".", body]);
+        let stale_value = format!("t={old},v1={stale_sig}");
+        let stale = headers(&[("stripe-signature", &stale_value)]);
+        assert!(matches!(
+            verify(&config, &stale, body, now(), WINDOW),
+            Err(VerifyError::StaleTimestamp { .. })
+        ));
+    }
+
+    #[test]
+    fn test_linear_checks_body_timestamp() {
+        let config = SourceConfig::new(WebhookSource::Linear, SECRET);
+        let body =
+            json!({"type": "Issue", "webhookTimestamp": now().timestamp_millis()}).to_string();
+        let sig = hmac_hex(SECRET.as_bytes(), &[body.as_bytes()]);
+        let signed = headers(&[("linear-signature", &sig)]);
+        assert!(verify(&config, &signed, body.as_bytes(), now(), WINDOW).is_ok());
+    }
+
+    #[test]
+    fn test_normalize_github_pull_request() {
+        let hdrs = headers(&[
+            ("x-github-event", "pull_request"),
+            ("x-github-delivery", "d-1"),
+        ]);
+        let payload = json!({
+            "action": "opened",
+            "sender": {"login": "octocat"},
+            "repository": {"full_name": "hive/hive"},
+        });
+        let event = normalize(WebhookSource::GitHub, &hdrs, b"{}", payload, now());
+        assert_eq!(event.topic(), "github.pull_request.opened");
+        assert_eq!(event.delivery_id, "d-1");
+        assert_eq!(event.actor.as_deref(), Some("octocat"));
+        assert_eq!(event.subject.as_deref(), Some("hive/hive"));
+    }
+
+    #[test]
+    fn test_normalize_other_sources() {
+        let none = HashMap::new();
+        let jira = json!({"webhookEvent": "jira:issue_updated", "issue": {"key": "HIVE-7"}});
+        let event = normalize(WebhookSource::Jira, &none, b"a", jira, now());
+        assert_eq!(event.topic(), "jira.issue_updated");
+        assert_eq!(event.subject.as_deref(), Some("HIVE-7"));
+
+        let linear = json!({"type": "Issue", "action": "create", "data": {"identifier": "ENG-1"}});
+        let event = normalize(WebhookSource::Linear, &none, b"b", linear, now());
+        assert_eq!(event.topic(), "linear.issue.create");
+
+        let bitbucket = headers(&[("x-event-key", "repo:push")]);
+        let event = normalize(WebhookSource::Bitbucket, &bitbucket, b"c", json!({}), now());
+        assert_eq!(event.topic(), "bitbucket.repo.push");
+
+        let gitlab =
+            json!({"object_kind": "merge_request", "object_attributes": {"action": "merge"}});
+        let event = normalize(WebhookSource::GitLab, &none, b"d", gitlab, now());
+        assert_eq!(event.topic(), "gitlab.merge_request.merge");
+    }
+
+    #[test]
+    fn test_missing_delivery_id_falls_back_to_body_digest() {
+        let none = HashMap::new();
+        let a = normalize(WebhookSource::GitLab, &none, b"same", json!({}), now());
+        let b = normalize(WebhookSource::GitLab, &none, b"same", json!({}), now());
+        assert_eq!(a.delivery_id, b.delivery_id);
+        assert_eq!(a.delivery_id.len(), 64);
+        assert_ne!(a.id, b.id);
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_unsigned_headers() {
+        let body = br#"{"ref":"main"}"#;
+        let a = normalize(
+            WebhookSource::GitHub,
+            &headers(&[("x-github-delivery", "d-1")]),
+            body,
+            json!({}),
+            now(),
+        );
+        let b = normalize(
+            WebhookSource::GitHub,
+            &headers(&[("x-github-delivery", "d-2")]),
+            body,
+            json!({}),
+            now(),
+        );
+        assert_ne!(a.delivery_id, b.delivery_id);
+        assert_eq!(a.fingerprint, b.fingerprint);
+
+        let first = headers(&[("x-slack-request-timestamp", "1700000000")]);
+        let resent = headers(&[("x-slack-request-timestamp", "1700000060")]);
+        assert_ne!(
+            fingerprint(WebhookSource::Slack, &first, body),
+            fingerprint(WebhookSource::Slack, &resent, body)
+        );
+    }
+
+    #[test]
+    fn test_empty_secret_is_invalid() {
+        assert!(
+            SourceConfig::new(WebhookSource::GitHub, "  ")
+                .validate()
+                .is_err()
+        );
+        assert!(
+            SourceConfig::new(WebhookSource::GitHub, SECRET)
+                .validate()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_source_routes() {
+        assert_eq!(WebhookSource::GitHub.default_path(), "/webhooks/github");
+        let config = SourceConfig::new(WebhookSource::Stripe, "s").with_path("/hooks/pay");
+        assert_eq!(config.route(), "/hooks/pay");
+        for source in WebhookSource::ALL {
+            assert_eq!(WebhookSource::parse_str(source.as_str()), Some(source));
+        }
+    }
+}
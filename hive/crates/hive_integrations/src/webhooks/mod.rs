@@ -0,0 +1,957 @@
+pub mod bus;
+pub mod delivery_log;
+pub mod inbound;
+pub mod outbox;
+pub mod receiver;
+
+pub use bus::{WebhookEvent, WebhookEventBus, WebhookSubscription};
+pub use delivery_log::{DeliveryLog, DeliveryRecord, DeliveryStatus};
+pub use inbound::{SourceConfig, VerifyError, WebhookSource};
+pub use outbox::{DeliveryAttempt, NextStep, Outbox, OutboxEntry, OutboxStatus};
+pub use receiver::{ReceiverConfig, ReceiverHandle, WebhookReceiver, WebhookResponse};
+
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+use url::Url;
+use uuid::Uuid;
+
+/// A registered webhook that receives event notifications.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub events: Vec<String>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    /// Key for the `X-Hive-Signature-256` header. Share it with the receiver.
+    /// Missing from webhooks saved before signing existed until
+    /// [`WebhookRegistry::load`] generates and saves one.
+    #[serde(default)]
+    pub secret: String,
+}
+
+impl Webhook {
+    /// Create a new webhook with a generated UUID and signing secret.
+    pub fn new(name: impl Into<String>, url: impl Into<String>, events: Vec<String>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name: name.into(),
+            url: url.into(),
+            events,
+            active: true,
+            created_at: Utc::now(),
+            secret: generate_secret(),
+        }
+    }
+
+    /// Check whether this webhook is subscribed to the given event.
+    pub fn is_subscribed_to(&self, event: &str) -> bool {
+        self.active && self.events.iter().any(|e| e == event)
+    }
+}
+
+fn generate_secret() -> String {
+    let bytes: [u8; 32] = rand::random();
+    let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    format!("whsec_{hex}")
+}
+
+/// `sha256=<hex>` signature of `{timestamp}.{body}`, as sent in
+/// `X-Hive-Signature-256`. Receivers recompute it with the webhook secret
+/// and should reject timestamps that are too old.
+pub fn sign_payload(secret: &str, timestamp: i64, body: &str) -> String {
+    let ts = timestamp.to_string();
+    let mac = inbound::hmac_hex(secret.as_bytes(), &[ts.as_bytes(), b".", body.as_bytes()]);
+    format!("sha256={mac}")
+}
+
+/// Validate that a webhook URL is safe to deliver to.
+///
+/// Enforces HTTPS and blocks private/internal IP addresses to prevent SSRF.
+fn validate_webhook_url(raw: &str) -> Result<(), String> {
+    let parsed = Url::parse(raw).map_err(|e| format!("Invalid URL: {e}"))?;
+
+    if parsed.scheme() != "https" {
+        return Err("Webhook URLs must use HTTPS".into());
+    }
+
+    if let Some(host) = parsed.host_str() {
+        let blocked_hosts = ["localhost", "127.0.0.1", "0.0.0.0", "[::1]"];
+        if blocked_hosts.contains(&host) {
+            return Err(format!("Webhook URL blocked: {host} is a local address"));
+        }
+
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            match ip {
+                IpAddr::V4(v4)
+                    if v4.is_private()
+                        || v4.is_loopback()
+                        || v4.is_link_local()
+                        || v4.is_broadcast()
+                        || v4.is_unspecified() =>
+                {
+                    return Err(format!(
+                        "Webhook URL blocked: {ip} is a private/reserved address"
+                    ));
+                }
+                IpAddr::V6(v6) if v6.is_loopback() || v6.is_unspecified() => {
+                    return Err(format!(
+                        "Webhook URL blocked: {ip} is a private/reserved address"
+                    ));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Timeouts and retry policy for outbound deliveries.
+#[derive(Debug, Clone)]
+pub struct DeliveryConfig {
+    /// Per-request timeout.
+    pub timeout: Duration,
+    /// Attempts before an entry is dead-lettered.
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubles on each further failure.
+    pub base_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for DeliveryConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            max_attempts: 8,
+            base_backoff: Duration::from_secs(5),
+            max_backoff: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+impl DeliveryConfig {
+    /// Delay after the `attempt`-th failure: exponential, capped, with
+    /// jitter over the upper half so retries from many webhooks spread out.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff);
+        let ceiling = exp.as_millis() as u64;
+        if ceiling == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_millis(rand::random_range(ceiling / 2..=ceiling))
+    }
+
+    /// How long a claimed outbox entry is reserved for one attempt: the
+    /// request timeout plus time to record the outcome.
+    pub fn lease(&self) -> Duration {
+        self.timeout + Duration::from_secs(30)
+    }
+}
+
+/// Registry that manages webhook subscriptions and dispatches events.
+///
+/// Every delivery goes through the [`Outbox`]: it is attempted straight
+/// away, retried with backoff by [`process_due`](Self::process_due), and
+/// dead-lettered once it runs out of attempts.
+pub struct WebhookRegistry {
+    webhooks: Vec<Webhook>,
+    client: Client,
+    config: DeliveryConfig,
+    outbox: Arc<Outbox>,
+    /// Where registrations are saved, when loaded from a file.
+    path: Option<PathBuf>,
+}
+
+impl WebhookRegistry {
+    /// Create a new empty registry with an in-memory outbox.
+    pub fn new() -> Self {
+        let outbox = Outbox::in_memory().expect("in-memory SQLite is always available");
+        Self::with_outbox(Arc::new(outbox))
+    }
+
+    /// Create a registry whose deliveries persist in `outbox`.
+    pub fn with_outbox(outbox: Arc<Outbox>) -> Self {
+        Self {
+            webhooks: Vec::new(),
+            client: Client::new(),
+            config: DeliveryConfig::default(),
+            outbox,
+            path: None,
+        }
+    }
+
+    /// `~/.hive/webhooks.json`
+    pub fn default_path() -> Result<PathBuf, String> {
+        hive_core::config::HiveConfig::base_dir()
+            .map(|dir| dir.join("webhooks.json"))
+            .map_err(|e| format!("{e:#}"))
+    }
+
+    /// Load the webhooks saved at `path` (none if the file is missing) and
+    /// save later registrations there.
+    ///
+    /// Webhooks saved before signing secrets existed get one here, and the
+    /// file is rewritten straight away so the secret is the same on every
+    /// later load.
+    pub fn load(path: &Path, outbox: Arc<Outbox>) -> Result<Self, String> {
+        let mut registry = Self::with_outbox(outbox);
+        registry.path = Some(path.to_path_buf());
+        if !path.exists() {
+            return Ok(registry);
+        }
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        registry.webhooks = serde_json::from_str(&raw)
+            .map_err(|e| format!("Failed to parse {}: {e}", path.display()))?;
+
+        let mut migrated = 0;
+        for webhook in registry.webhooks.iter_mut().filter(|w| w.secret.is_empty()) {
+            webhook.secret = generate_secret();
+            migrated += 1;
+        }
+        if migrated > 0 {
+            registry.save()?;
+            debug!(count = migrated, "generated secrets for legacy webhooks");
+        }
+        Ok(registry)
+    }
+
+    /// Write the registrations to the file given to [`load`](Self::load).
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+        }
+        let json = serde_json::to_string_pretty(&self.webhooks).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+    }
+
+    /// Replace the delivery policy.
+    pub fn with_config(mut self, config: DeliveryConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn outbox(&self) -> &Arc<Outbox> {
+        &self.outbox
+    }
+
+    /// Register a new webhook and return its ID.
+    ///
+    /// Returns an error if the webhook URL fails validation (non-HTTPS or
+    /// targets a private/local address).
+    pub fn register(&mut self, mut webhook: Webhook) -> Result<String, String> {
+        validate_webhook_url(&webhook.url)?;
+        if webhook.secret.is_empty() {
+            webhook.secret = generate_secret();
+        }
+        let id = webhook.id.clone();
+        debug!(id = %id, name = %webhook.name, "registering webhook");
+        self.webhooks.push(webhook);
+        if let Err(err) = self.save() {
+            self.webhooks.pop();
+            return Err(err);
+        }
+        Ok(id)
+    }
+
+    /// Unregister a webhook by ID. Returns `true` if it was found and removed.
+    pub fn unregister(&mut self, id: &str) -> bool {
+        let before = self.webhooks.len();
+        self.webhooks.retain(|w| w.id != id);
+        let removed = self.webhooks.len() < before;
+        if removed {
+            debug!(id = %id, "unregistered webhook");
+            if let Err(err) = self.save() {
+                warn!("failed to save webhooks: {err}");
+            }
+        } else {
+            warn!(id = %id, "webhook not found for unregister");
+        }
+        removed
+    }
+
+    /// List all registered webhooks.
+    pub fn list(&self) -> &[Webhook] {
+        &self.webhooks
+    }
+
+    /// Return the number of registered webhooks.
+    pub fn len(&self) -> usize {
+        self.webhooks.len()
+    }
+
+    /// Return whether the registry is empty.
+    pub fn is_empty(&self) -> bool {
+        self.webhooks.is_empty()
+    }
+
+    /// Find a webhook by ID.
+    pub fn get(&self, id: &str) -> Option<&Webhook> {
+        self.webhooks.iter().find(|w| w.id == id)
+    }
+
+    /// Trigger an event for all subscribed active webhooks.
+    ///
+    /// Each delivery is queued in the outbox and attempted once right away.
+    /// Returns the number of webhooks that were successfully notified;
+    /// failed deliveries stay queued for [`process_due`](Self::process_due).
+    pub async fn trigger(&self, event: &str, payload: &Value) -> usize {
+        let subscribers: Vec<&Webhook> = self
+            .webhooks
+            .iter()
+            .filter(|w| w.is_subscribed_to(event))
+            .collect();
+
+        if subscribers.is_empty() {
+            debug!(event = %event, "no subscribers for event");
+            return 0;
+        }
+
+        let body = serde_json::json!({
+            "event": event,
+            "payload": payload,
+            "timestamp": Utc::now().to_rfc3339(),
+        })
+        .to_string();
+
+        let mut success_count = 0;
+        for webhook in subscribers {
+            let claimed = self
+                .outbox
+                .enqueue(&webhook.id, event, &body)
+                .and_then(|entry| {
+                    self.outbox
+                        .claim(&entry.id, Utc::now(), self.config.lease())
+                });
+            match claimed {
+                Ok(Some(entry)) => {
+                    if self.deliver(&entry).await {
+                        success_count += 1;
+                    }
+                }
+                // Already picked up by process_due; it will report the outcome.
+                Ok(None) => {}
+                Err(err) => warn!(id = %webhook.id, "failed to queue webhook delivery: {err:#}"),
+            }
+        }
+        success_count
+    }
+
+    /// Attempt every queued delivery whose retry is due. Call this
+    /// periodically (and once at startup to flush deliveries queued while
+    /// the app was offline). Returns how many were delivered.
+    pub async fn process_due(&self) -> usize {
+        let due = match self.outbox.claim_due(Utc::now(), self.config.lease(), 100) {
+            Ok(due) => due,
+            Err(err) => {
+                warn!("failed to read webhook outbox: {err:#}");
+                return 0;
+            }
+        };
+        let mut delivered = 0;
+        for entry in &due {
+            if self.deliver(entry).await {
+                delivered += 1;
+            }
+        }
+        delivered
+    }
+
+    /// How long until the next queued retry is due, if any.
+    pub fn next_retry_in(&self) -> Option<Duration> {
+        let next = self.outbox.next_due_at().ok()??;
+        Some((next - Utc::now()).to_std().unwrap_or(Duration::ZERO))
+    }
+
+    /// Attempt queued deliveries as their retries fall due, forever.
+    /// Sleeps at most `idle` between checks so deliveries queued by other
+    /// processes sharing the outbox are picked up too.
+    pub async fn run_retries(&self, idle: Duration) {
+        loop {
+            self.process_due().await;
+            let wait = self
+                .next_retry_in()
+                .map_or(idle, |next| next.min(idle))
+                .max(Duration::from_secs(1));
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Deliveries that ran out of attempts, newest first.
+    pub fn dead_letters(&self, limit: usize) -> Result<Vec<OutboxEntry>, String> {
+        self.outbox
+            .dead_letters(limit)
+            .map_err(|e| format!("{e:#}"))
+    }
+
+    /// Give a dead letter a fresh attempt budget and try it immediately.
+    /// Returns whether this attempt succeeded.
+    pub async fn redeliver(&self, outbox_id: &str) -> Result<bool, String> {
+        if !self
+            .outbox
+            .requeue(outbox_id)
+            .map_err(|e| format!("{e:#}"))?
+        {
+            return Err(format!("No dead letter with id {outbox_id}"));
+        }
+        let entry = self
+            .outbox
+            .claim(outbox_id, Utc::now(), self.config.lease())
+            .map_err(|e| format!("{e:#}"))?
+            .ok_or_else(|| format!("Delivery {outbox_id} is already being retried"))?;
+        Ok(self.deliver(&entry).await)
+    }
+
+    /// Recent delivery attempts for a webhook, newest first.
+    pub fn history(&self, webhook_id: &str, limit: usize) -> Result<Vec<DeliveryAttempt>, String> {
+        self.outbox
+            .history(webhook_id, limit)
+            .map_err(|e| format!("{e:#}"))
+    }
+
+    /// Make one signed attempt at a claimed `entry` and record the outcome.
+    async fn deliver(&self, entry: &OutboxEntry) -> bool {
+        let Some(webhook) = self.get(&entry.webhook_id).filter(|w| w.active) else {
+            warn!(id = %entry.webhook_id, "dropping delivery for missing or inactive webhook");
+            if let Err(err) = self
+                .outbox
+                .mark_dead(&entry.id, "webhook is no longer active")
+            {
+                warn!("failed to dead-letter webhook delivery: {err:#}");
+            }
+            return false;
+        };
+
+        debug!(
+            id = %webhook.id,
+            name = %webhook.name,
+            url = %webhook.url,
+            event = %entry.event,
+            attempt = entry.attempts + 1,
+            "delivering webhook"
+        );
+
+        let timestamp = Utc::now().timestamp();
+        let started = Instant::now();
+        let result = self
+            .client
+            .post(&webhook.url)
+            .timeout(self.config.timeout)
+            .header("Content-Type", "application/json")
+            .header("X-Hive-Event", &entry.event)
+            .header("X-Hive-Delivery", &entry.id)
+            .header("X-Hive-Timestamp", timestamp.to_string())
+            .header(
+                "X-Hive-Signature-256",
+                sign_payload(&webhook.secret, timestamp, &entry.body),
+            )
+            .body(entry.body.clone())
+            .send()
+            .await;
+
+        let (status_code, error) = match result {
+            Ok(response) => (Some(response.status().as_u16()), None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+        let attempt = DeliveryAttempt {
+            outbox_id: entry.id.clone(),
+            webhook_id: webhook.id.clone(),
+            event: entry.event.clone(),
+            attempt: entry.attempts + 1,
+            status_code,
+            error,
+            duration_ms: started.elapsed().as_millis() as u64,
+            attempted_at: Utc::now(),
+        };
+
+        let delivered = attempt.succeeded();
+        let next = if delivered {
+            NextStep::Delivered
+        } else if attempt.attempt >= self.config.max_attempts {
+            warn!(id = %webhook.id, delivery = %entry.id, "webhook delivery dead-lettered");
+            NextStep::Dead
+        } else {
+            let delay = self.config.backoff(attempt.attempt);
+            warn!(
+                id = %webhook.id,
+                status = ?attempt.status_code,
+                error = ?attempt.error,
+                retry_in_ms = delay.as_millis() as u64,
+                "webhook delivery failed"
+            );
+            NextStep::RetryAt(attempt.attempted_at + delay)
+        };
+        if let Err(err) = self.outbox.record_attempt(&attempt, next) {
+            warn!("failed to record webhook attempt: {err:#}");
+        }
+        delivered
+    }
+}
+
+impl Default for WebhookRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_webhook(name: &str, events: Vec<&str>) -> Webhook {
+        Webhook::new(
+            name,
+            format!("https://example.com/hooks/{name}"),
+            events.into_iter().map(String::from).collect(),
+        )
+    }
+
+    #[test]
+    fn test_webhook_creation() {
+        let wh = Webhook::new("test", "https://example.com/hook", vec!["push".into()]);
+        assert!(!wh.id.is_empty());
+        assert_eq!(wh.name, "test");
+        assert_eq!(wh.url, "https://example.com/hook");
+        assert!(wh.active);
+        assert_eq!(wh.events, vec!["push"]);
+    }
+
+    #[test]
+    fn test_is_subscribed_to() {
+        let wh = sample_webhook("ci", vec!["push", "pr"]);
+        assert!(wh.is_subscribed_to("push"));
+        assert!(wh.is_subscribed_to("pr"));
+        assert!(!wh.is_subscribed_to("issue"));
+    }
+
+    #[test]
+    fn test_inactive_webhook_not_subscribed() {
+        let mut wh = sample_webhook("ci", vec!["push"]);
+        wh.active = false;
+        assert!(!wh.is_subscribed_to("push"));
+    }
+
+    #[test]
+    fn test_register_and_list() {
+        let mut registry = WebhookRegistry::new();
+        assert!(registry.is_empty());
+
+        let wh = sample_webhook("deploy", vec!["deploy"]);
+        let id = registry.register(wh).unwrap();
+
+        assert_eq!(registry.len(), 1);
+        assert!(!registry.is_empty());
+
+        let listed = registry.list();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, id);
+    }
+
+    #[test]
+    fn test_unregister() {
+        let mut registry = WebhookRegistry::new();
+        let wh = sample_webhook("temp", vec!["test"]);
+        let id = registry.register(wh).unwrap();
+        assert_eq!(registry.len(), 1);
+
+        assert!(registry.unregister(&id));
+        assert_eq!(registry.len(), 0);
+
+        // Unregistering again returns false.
+        assert!(!registry.unregister(&id));
+    }
+
+    #[test]
+    fn test_get_by_id() {
+        let mut registry = WebhookRegistry::new();
+        let wh = sample_webhook("finder", vec!["event"]);
+        let id = registry.register(wh).unwrap();
+
+        let found = registry.get(&id);
+        assert!(found.is_some());
+        assert_eq!(found.unwrap().name, "finder");
+
+        assert!(registry.get("nonexistent").is_none());
+    }
+
+    #[test]
+    fn test_webhook_serialization_roundtrip() {
+        let wh = sample_webhook("serial", vec!["push", "tag"]);
+        let json = serde_json::to_string(&wh).unwrap();
+        let deserialized: Webhook = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(deserialized.id, wh.id);
+        assert_eq!(deserialized.name, wh.name);
+        assert_eq!(deserialized.url, wh.url);
+        assert_eq!(deserialized.events, wh.events);
+        assert_eq!(deserialized.active, wh.active);
+    }
+
+    #[test]
+    fn test_multiple_webhooks_different_events() {
+        let mut registry = WebhookRegistry::new();
+        registry
+            .register(sample_webhook("a", vec!["push"]))
+            .unwrap();
+        registry
+            .register(sample_webhook("b", vec!["deploy"]))
+            .unwrap();
+        registry
+            .register(sample_webhook("c", vec!["push", "deploy"]))
+            .unwrap();
+
+        assert_eq!(registry.len(), 3);
+
+        let push_subscribers: Vec<_> = registry
+            .list()
+            .iter()
+            .filter(|w| w.is_subscribed_to("push"))
+            .collect();
+        assert_eq!(push_subscribers.len(), 2);
+
+        let deploy_subscribers: Vec<_> = registry
+            .list()
+            .iter()
+            .filter(|w| w.is_subscribed_to("deploy"))
+            .collect();
+        assert_eq!(deploy_subscribers.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_trigger_no_subscribers_returns_zero() {
+        let registry = WebhookRegistry::new();
+        let count = registry
+            .trigger("unknown_event", &serde_json::json!({}))
+            .await;
+        assert_eq!(count, 0);
+    }
+
+    // -----------------------------------------------------------------------
+    // Delivery tests (local HTTP stub)
+    // -----------------------------------------------------------------------
+
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    struct StubRequest {
+        headers: HashMap<String, String>,
+        body: String,
+    }
+
+    type Requests = Arc<Mutex<Vec<StubRequest>>>;
+
+    /// Local endpoint that answers with `statuses` in turn (200 once they
+    /// run out) and records every request it receives.
+    async fn stub_server(statuses: Vec<u16>) -> (String, Requests) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let requests: Requests = Arc::default();
+        let seen = Arc::clone(&requests);
+        tokio::spawn(async move {
+            let mut statuses = statuses.into_iter();
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = Vec::new();
+                let mut chunk = [0u8; 4096];
+                let (head_len, headers) = loop {
+                    let n = stream.read(&mut chunk).await.unwrap();
+                    buf.extend_from_slice(&chunk[..n]);
+                    if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                        let head = String::from_utf8_lossy(&buf[..pos]).to_string();
+                        let headers: HashMap<String, String> = head
+                            .lines()
+                            .skip(1)
+                            .filter_map(|l| l.split_once(':'))
+                            .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim().to_string()))
+                            .collect();
+                        break (pos + 4, headers);
+                    }
+                };
+                let length: usize = headers["content-length"].parse().unwrap();
+                while buf.len() < head_len + length {
+                    let n = stream.read(&mut chunk).await.unwrap();
+                    buf.extend_from_slice(&chunk[..n]);
+                }
+                let body = String::from_utf8_lossy(&buf[head_len..head_len + length]).to_string();
+                seen.lock().unwrap().push(StubRequest { headers, body });
+
+                let status = statuses.next().unwrap_or(200);
+                let response = format!(
+                    "HTTP/1.1 {status} Stub\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    fn fast_config(max_attempts: u32) -> DeliveryConfig {
+        DeliveryConfig {
+            timeout: Duration::from_secs(5),
+            max_attempts,
+            base_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+        }
+    }
+
+    /// Register without URL validation so the local stub is reachable.
+    fn register_local(registry: &mut WebhookRegistry, url: &str) -> Webhook {
+        let webhook = Webhook::new("local", url, vec!["deploy".into()]);
+        registry.webhooks.push(webhook.clone());
+        webhook
+    }
+
+    #[tokio::test]
+    async fn test_delivery_is_signed() {
+        let (url, requests) = stub_server(vec![]).await;
+        let mut registry = WebhookRegistry::new().with_config(fast_config(3));
+        let webhook = register_local(&mut registry, &url);
+
+        let count = registry
+            .trigger("deploy", &serde_json::json!({"version": "1.2.3"}))
+            .await;
+        assert_eq!(count, 1);
+
+        let requests = requests.lock().unwrap();
+        let request = &requests[0];
+        let timestamp: i64 = request.headers["x-hive-timestamp"].parse().unwrap();
+        assert_eq!(
+            request.headers["x-hive-signature-256"],
+            sign_payload(&webhook.secret, timestamp, &request.body)
+        );
+        assert_eq!(request.headers["x-hive-event"], "deploy");
+        let body: Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(body["payload"]["version"], "1.2.3");
+    }
+
+    #[tokio::test]
+    async fn test_intermittent_failures_are_retried() {
+        let (url, requests) = stub_server(vec![500, 503]).await;
+        let mut registry = WebhookRegistry::new().with_config(fast_config(5));
+        let webhook = register_local(&mut registry, &url);
+
+        assert_eq!(registry.trigger("deploy", &serde_json::json!({})).await, 0);
+        assert_eq!(registry.next_retry_in(), Some(Duration::ZERO));
+        assert_eq!(registry.process_due().await, 0);
+        assert_eq!(registry.process_due().await, 1);
+        assert_eq!(registry.process_due().await, 0);
+        assert_eq!(registry.next_retry_in(), None);
+
+        // Retries reuse the delivery id so receivers can deduplicate.
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        let delivery = &requests[0].headers["x-hive-delivery"];
+        assert!(
+            requests
+                .iter()
+                .all(|r| &r.headers["x-hive-delivery"] == delivery)
+        );
+
+        let history = registry.history(&webhook.id, 10).unwrap();
+        let codes: Vec<_> = history.iter().map(|a| a.status_code).collect();
+        assert_eq!(codes, [Some(200), Some(503), Some(500)]);
+        assert_eq!(history[0].attempt, 3);
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_and_manual_redelivery() {
+        let (url, _requests) = stub_server(vec![500, 502]).await;
+        let mut registry = WebhookRegistry::new().with_config(fast_config(2));
+        register_local(&mut registry, &url);
+
+        assert_eq!(registry.trigger("deploy", &serde_json::json!({})).await, 0);
+        assert_eq!(registry.process_due().await, 0);
+        let dead = registry.dead_letters(10).unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].attempts, 2);
+        assert_eq!(dead[0].last_error.as_deref(), Some("HTTP 502"));
+
+        assert!(registry.redeliver(&dead[0].id).await.unwrap());
+        assert!(registry.dead_letters(10).unwrap().is_empty());
+        assert!(registry.redeliver(&dead[0].id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_outbox_survives_restart() {
+        let dir = std::env::temp_dir().join(format!("hive_webhooks_test_{}", Uuid::new_v4()));
+        let path = dir.join("webhook_outbox.db");
+
+        // Nothing is listening yet: the delivery stays queued.
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let offline_url = format!("http://{}/hook", closed.local_addr().unwrap());
+        drop(closed);
+        let webhook = {
+            let outbox = Arc::new(Outbox::open(&path).unwrap());
+            let mut registry = WebhookRegistry::with_outbox(outbox).with_config(fast_config(5));
+            let webhook = register_local(&mut registry, &offline_url);
+            assert_eq!(registry.trigger("deploy", &serde_json::json!({})).await, 0);
+            webhook
+        };
+
+        let (url, requests) = stub_server(vec![]).await;
+        let outbox = Arc::new(Outbox::open(&path).unwrap());
+        let mut registry = WebhookRegistry::with_outbox(outbox).with_config(fast_config(5));
+        registry.webhooks.push(Webhook { url, ..webhook });
+        assert_eq!(registry.process_due().await, 1);
+        assert_eq!(requests.lock().unwrap().len(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unregistered_webhook_is_dead_lettered() {
+        let (url, requests) = stub_server(vec![500]).await;
+        let mut registry = WebhookRegistry::new().with_config(fast_config(5));
+        let webhook = register_local(&mut registry, &url);
+        registry.trigger("deploy", &serde_json::json!({})).await;
+        registry.unregister(&webhook.id);
+
+        assert_eq!(registry.process_due().await, 0);
+        assert_eq!(registry.dead_letters(10).unwrap().len(), 1);
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_backoff_grows_with_jitter_and_cap() {
+        let config = DeliveryConfig {
+            base_backoff: Duration::from_secs(2),
+            max_backoff: Duration::from_secs(30),
+            ..DeliveryConfig::default()
+        };
+        for _ in 0..20 {
+            let first = config.backoff(1);
+            assert!(first >= Duration::from_secs(1) && first <= Duration::from_secs(2));
+            let third = config.backoff(3);
+            assert!(third >= Duration::from_secs(4) && third <= Duration::from_secs(8));
+            assert!(config.backoff(30) <= Duration::from_secs(30));
+        }
+    }
+
+    #[test]
+    fn test_secrets_are_unique_and_survive_old_json() {
+        let a = sample_webhook("a", vec!["push"]);
+        let b = sample_webhook("b", vec!["push"]);
+        assert!(a.secret.starts_with("whsec_"));
+        assert_ne!(a.secret, b.secret);
+
+        let dir = std::env::temp_dir().join(format!("hive_webhooks_test_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("webhooks.json");
+        let mut legacy = serde_json::to_value(&a).unwrap();
+        legacy.as_object_mut().unwrap().remove("secret");
+        std::fs::write(&path, Value::Array(vec![legacy]).to_string()).unwrap();
+
+        let outbox = || Arc::new(Outbox::in_memory().unwrap());
+        let loaded = WebhookRegistry::load(&path, outbox()).unwrap();
+        let secret = loaded.get(&a.id).unwrap().secret.clone();
+        assert!(secret.starts_with("whsec_"));
+        // Generated once and saved, not regenerated on every load.
+        let reloaded = WebhookRegistry::load(&path, outbox()).unwrap();
+        assert_eq!(reloaded.get(&a.id).unwrap().secret, secret);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_registrations_are_saved() {
+        let dir = std::env::temp_dir().join(format!("hive_webhooks_test_{}", Uuid::new_v4()));
+        let path = dir.join("webhooks.json");
+        let outbox = || Arc::new(Outbox::in_memory().unwrap());
+
+        let mut registry = WebhookRegistry::load(&path, outbox()).unwrap();
+        let id = registry
+            .register(sample_webhook("saved", vec!["push"]))
+            .unwrap();
+        let removed = registry
+            .register(sample_webhook("removed", vec!["push"]))
+            .unwrap();
+        assert!(registry.unregister(&removed));
+
+        let reloaded = WebhookRegistry::load(&path, outbox()).unwrap();
+        assert_eq!(reloaded.len(), 1);
+        assert_eq!(reloaded.get(&id).unwrap().name, "saved");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // -----------------------------------------------------------------------
+    // URL validation tests
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn test_validate_rejects_http() {
+        let wh = Webhook::new("bad", "http://example.com/hook", vec!["push".into()]);
+        let mut registry = WebhookRegistry::new();
+        let err = registry.register(wh).unwrap_err();
+        assert!(err.contains("HTTPS"), "expected HTTPS error, got: {err}");
+    }
+
+    #[test]
+    fn test_validate_rejects_localhost() {
+        let wh = Webhook::new("bad", "https://localhost/hook", vec!["push".into()]);
+        let mut registry = WebhookRegistry::new();
+        let err = registry.register(wh).unwrap_err();
+        assert!(
+            err.contains("local address"),
+            "expected local address error, got: {err}"
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_private_ip() {
+        for url in [
+            "https://127.0.0.1/hook",
+            "https://10.0.0.1/hook",
+            "https://192.168.1.1/hook",
+            "https://172.16.0.1/hook",
+        ] {
+            let wh = Webhook::new("bad", url, vec!["push".into()]);
+            let mut registry = WebhookRegistry::new();
+            let result = registry.register(wh);
+            assert!(result.is_err(), "expected rejection for {url}");
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_link_local() {
+        let wh = Webhook::new("bad", "https://169.254.169.254/hook", vec!["push".into()]);
+        let mut registry = WebhookRegistry::new();
+        assert!(registry.register(wh).is_err());
+    }
+
+    #[test]
+    fn test_validate_accepts_valid_https() {
+        let wh = Webhook::new(
+            "good",
+            "https://hooks.example.com/event",
+            vec!["push".into()],
+        );
+        let mut registry = WebhookRegistry::new();
+        assert!(registry.register(wh).is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_url() {
+        let wh = Webhook::new("bad", "not a url", vec!["push".into()]);
+        let mut registry = WebhookRegistry::new();
+        let err = registry.register(wh).unwrap_err();
+        assert!(
+            err.contains("Invalid URL"),
+            "expected Invalid URL error, got: {err}"
+        );
+    }
+}
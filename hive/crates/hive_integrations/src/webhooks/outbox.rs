@@ -0,0 +1,576 @@
+//! Persistent outbox for outbound webhook deliveries.
+//!
+//! Each (webhook, event) pair becomes one outbox entry that stays `pending`
+//! until it is delivered or runs out of attempts, at which point it moves
+//! to the dead-letter list. Every attempt is kept as delivery history.
+//! Because the outbox lives in SQLite, deliveries queued while the app was
+//! offline (or while a receiver was down) are picked up after a restart.
+//!
+//! A sender claims an entry before attempting it, which leases the entry
+//! until `in_flight_until`. Claimed entries are skipped by other senders,
+//! and a lease left behind by a crashed sender simply expires.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use hive_core::config::HiveConfig;
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Where an outbox entry is in its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutboxStatus {
+    /// Waiting for its first or next attempt.
+    Pending,
+    Delivered,
+    /// Out of attempts; only redelivered by hand.
+    Dead,
+}
+
+impl OutboxStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Delivered => "delivered",
+            Self::Dead => "dead",
+        }
+    }
+
+    pub fn parse_str(s: &str) -> Self {
+        match s {
+            "delivered" => Self::Delivered,
+            "dead" => Self::Dead,
+            _ => Self::Pending,
+        }
+    }
+}
+
+/// One queued delivery of an event to a webhook.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    /// Stable across retries; sent as `X-Hive-Delivery`.
+    pub id: String,
+    pub webhook_id: String,
+    pub event: String,
+    /// The exact JSON body that is signed and sent.
+    pub body: String,
+    pub attempts: u32,
+    pub status: OutboxStatus,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Set while a sender holds the entry; see [`Outbox::claim`].
+    #[serde(default)]
+    pub in_flight_until: Option<DateTime<Utc>>,
+}
+
+/// The result of one HTTP attempt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryAttempt {
+    pub outbox_id: String,
+    pub webhook_id: String,
+    pub event: String,
+    /// 1-based attempt number.
+    pub attempt: u32,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    pub attempted_at: DateTime<Utc>,
+}
+
+impl DeliveryAttempt {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+            && self
+                .status_code
+                .is_some_and(|code| (200..300).contains(&code))
+    }
+}
+
+/// What happens to an entry after an attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NextStep {
+    Delivered,
+    RetryAt(DateTime<Utc>),
+    Dead,
+}
+
+/// SQLite-backed outbox and delivery history.
+pub struct Outbox {
+    conn: Mutex<Connection>,
+}
+
+impl Outbox {
+    /// Open (or create) the outbox at `path`.
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open webhook outbox {}", path.display()))?;
+        Self::init_tables(&conn)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// An in-memory outbox (nothing survives the process).
+    pub fn in_memory() -> Result<Self> {
+        let conn = Connection::open_in_memory().context("Failed to open in-memory outbox")?;
+        Self::init_tables(&conn)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// `~/.hive/webhook_outbox.db`
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(HiveConfig::base_dir()?.join("webhook_outbox.db"))
+    }
+
+    fn init_tables(conn: &Connection) -> Result<()> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS outbox (
+                id              TEXT PRIMARY KEY,
+                webhook_id      TEXT NOT NULL,
+                event           TEXT NOT NULL,
+                body            TEXT NOT NULL,
+                attempts        INTEGER NOT NULL DEFAULT 0,
+                status          TEXT NOT NULL,
+                next_attempt_at TEXT NOT NULL,
+                last_error      TEXT,
+                created_at      TEXT NOT NULL,
+                updated_at      TEXT NOT NULL,
+                in_flight_until TEXT
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_outbox_due
+                ON outbox(status, next_attempt_at);
+
+            CREATE TABLE IF NOT EXISTS delivery_attempts (
+                id           INTEGER PRIMARY KEY AUTOINCREMENT,
+                outbox_id    TEXT NOT NULL,
+                webhook_id   TEXT NOT NULL,
+                event        TEXT NOT NULL,
+                attempt      INTEGER NOT NULL,
+                status_code  INTEGER,
+                error        TEXT,
+                duration_ms  INTEGER NOT NULL,
+                attempted_at TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_attempts_webhook
+                ON delivery_attempts(webhook_id, id DESC);",
+        )
+        .context("Failed to initialise webhook outbox")?;
+
+        // Outboxes created before leases have no in_flight_until column.
+        let has_lease = conn
+            .prepare("SELECT 1 FROM pragma_table_info('outbox') WHERE name = 'in_flight_until'")?
+            .exists([])?;
+        if !has_lease {
+            conn.execute_batch("ALTER TABLE outbox ADD COLUMN in_flight_until TEXT;")
+                .context("Failed to migrate webhook outbox")?;
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Connection>> {
+        self.conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))
+    }
+
+    /// Queue `body` for delivery to `webhook_id`, due immediately.
+    pub fn enqueue(&self, webhook_id: &str, event: &str, body: &str) -> Result<OutboxEntry> {
+        let now = Utc::now();
+        let entry = OutboxEntry {
+            id: Uuid::new_v4().to_string(),
+            webhook_id: webhook_id.to_string(),
+            event: event.to_string(),
+            body: body.to_string(),
+            attempts: 0,
+            status: OutboxStatus::Pending,
+            next_attempt_at: now,
+            last_error: None,
+            created_at: now,
+            updated_at: now,
+            in_flight_until: None,
+        };
+        self.lock()?
+            .execute(
+                "INSERT INTO outbox (id, webhook_id, event, body, attempts, status,
+                                     next_attempt_at, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, 0, 'pending', ?5, ?5, ?5)",
+                params![entry.id, entry.webhook_id, entry.event, entry.body, ts(now)],
+            )
+            .context("Failed to enqueue webhook delivery")?;
+        Ok(entry)
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<OutboxEntry>> {
+        let conn = self.lock()?;
+        conn.query_row(
+            &format!("SELECT {ENTRY_COLUMNS} FROM outbox WHERE id = ?1"),
+            params![id],
+            row_to_entry,
+        )
+        .optional()
+        .context("Failed to read outbox entry")
+    }
+
+    /// Pending, unclaimed entries whose next attempt is due at `now`,
+    /// oldest first.
+    pub fn due(&self, now: DateTime<Utc>, limit: usize) -> Result<Vec<OutboxEntry>> {
+        self.query_entries(
+            &format!("{CLAIMABLE} ORDER BY next_attempt_at LIMIT ?2"),
+            params![ts(now), limit as i64],
+        )
+    }
+
+    /// Lease entry `id` for one attempt until `now + lease`.
+    ///
+    /// Returns the entry only if it is pending, due, and not already held
+    /// by another sender, so the same delivery is never in flight twice.
+    pub fn claim(
+        &self,
+        id: &str,
+        now: DateTime<Utc>,
+        lease: Duration,
+    ) -> Result<Option<OutboxEntry>> {
+        let until = now + chrono::Duration::from_std(lease)?;
+        self.lock()?
+            .query_row(
+                &format!(
+                    "UPDATE outbox SET in_flight_until = ?3
+                     WHERE id = ?2 AND {CLAIMABLE}
+                     RETURNING {ENTRY_COLUMNS}"
+                ),
+                params![ts(now), id, ts(until)],
+                row_to_entry,
+            )
+            .optional()
+            .context("Failed to claim webhook delivery")
+    }
+
+    /// Lease up to `limit` due entries at once, oldest first. See
+    /// [`claim`](Self::claim).
+    pub fn claim_due(
+        &self,
+        now: DateTime<Utc>,
+        lease: Duration,
+        limit: usize,
+    ) -> Result<Vec<OutboxEntry>> {
+        let until = now + chrono::Duration::from_std(lease)?;
+        let conn = self.lock()?;
+        let mut stmt = conn.prepare(&format!(
+            "UPDATE outbox SET in_flight_until = ?3
+             WHERE id IN (SELECT id FROM outbox WHERE {CLAIMABLE}
+                          ORDER BY next_attempt_at LIMIT ?2)
+             RETURNING {ENTRY_COLUMNS}"
+        ))?;
+        let mut entries = stmt
+            .query_map(params![ts(now), limit as i64, ts(until)], row_to_entry)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("Failed to claim webhook deliveries")?;
+        entries.sort_by_key(|entry| entry.next_attempt_at);
+        Ok(entries)
+    }
+
+    /// When the earliest pending entry becomes due, or its lease runs out
+    /// if a sender holds it.
+    pub fn next_due_at(&self) -> Result<Option<DateTime<Utc>>> {
+        let conn = self.lock()?;
+        let next: Option<String> = conn
+            .query_row(
+                "SELECT MIN(MAX(next_attempt_at, COALESCE(in_flight_until, next_attempt_at)))
+                 FROM outbox WHERE status = 'pending'",
+                [],
+                |row| row.get(0),
+            )
+            .context("Failed to read outbox")?;
+        Ok(next.map(|t| parse_ts(&t)))
+    }
+
+    /// Entries that ran out of attempts, newest first.
+    pub fn dead_letters(&self, limit: usize) -> Result<Vec<OutboxEntry>> {
+        self.query_entries(
+            "status = 'dead' ORDER BY updated_at DESC LIMIT ?1",
+            params![limit as i64],
+        )
+    }
+
+    pub fn pending_count(&self) -> Result<usize> {
+        let conn = self.lock()?;
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM outbox WHERE status = 'pending'",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    /// Store an attempt and move its entry on to `next`.
+    pub fn record_attempt(&self, attempt: &DeliveryAttempt, next: NextStep) -> Result<()> {
+        let mut conn = self.lock()?;
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO delivery_attempts (outbox_id, webhook_id, event, attempt, status_code,
+                                            error, duration_ms, attempted_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                attempt.outbox_id,
+                attempt.webhook_id,
+                attempt.event,
+                attempt.attempt,
+                attempt.status_code,
+                attempt.error,
+                attempt.duration_ms as i64,
+                ts(attempt.attempted_at),
+            ],
+        )?;
+        let (status, next_at) = match next {
+            NextStep::Delivered => (OutboxStatus::Delivered, attempt.attempted_at),
+            NextStep::RetryAt(at) => (OutboxStatus::Pending, at),
+            NextStep::Dead => (OutboxStatus::Dead, attempt.attempted_at),
+        };
+        let last_error = attempt.error.clone().or_else(|| {
+            attempt
+                .status_code
+                .filter(|_| !attempt.succeeded())
+                .map(|code| format!("HTTP {code}"))
+        });
+        tx.execute(
+            "UPDATE outbox SET attempts = ?2, status = ?3, next_attempt_at = ?4,
+                               last_error = ?5, updated_at = ?6, in_flight_until = NULL
+             WHERE id = ?1",
+            params![
+                attempt.outbox_id,
+                attempt.attempt,
+                status.as_str(),
+                ts(next_at),
+                last_error,
+                ts(Utc::now()),
+            ],
+        )?;
+        tx.commit().context("Failed to record webhook attempt")
+    }
+
+    /// Give up on an entry without attempting it (e.g. its webhook is gone).
+    pub fn mark_dead(&self, id: &str, reason: &str) -> Result<()> {
+        self.lock()?
+            .execute(
+                "UPDATE outbox SET status = 'dead', last_error = ?2, updated_at = ?3,
+                                   in_flight_until = NULL
+                 WHERE id = ?1",
+                params![id, reason, ts(Utc::now())],
+            )
+            .context("Failed to dead-letter webhook delivery")?;
+        Ok(())
+    }
+
+    /// Move a dead letter back to pending with a fresh attempt budget.
+    /// Returns `false` if `id` is not a dead letter.
+    pub fn requeue(&self, id: &str) -> Result<bool> {
+        let now = ts(Utc::now());
+        let updated = self
+            .lock()?
+            .execute(
+                "UPDATE outbox SET status = 'pending', attempts = 0, next_attempt_at = ?2,
+                                   updated_at = ?2, in_flight_until = NULL
+                 WHERE id = ?1 AND status = 'dead'",
+                params![id, now],
+            )
+            .context("Failed to requeue webhook delivery")?;
+        Ok(updated > 0)
+    }
+
+    /// Attempts made for a webhook, newest first.
+    pub fn history(&self, webhook_id: &str, limit: usize) -> Result<Vec<DeliveryAttempt>> {
+        let conn = self.lock()?;
+        let mut stmt = conn.prepare(
+            "SELECT outbox_id, webhook_id, event, attempt, status_code, error, duration_ms,
+                    attempted_at
+             FROM delivery_attempts WHERE webhook_id = ?1 ORDER BY id DESC LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![webhook_id, limit as i64], |row| {
+            Ok(DeliveryAttempt {
+                outbox_id: row.get(0)?,
+                webhook_id: row.get(1)?,
+                event: row.get(2)?,
+                attempt: row.get(3)?,
+                status_code: row.get(4)?,
+                error: row.get(5)?,
+                duration_ms: row.get::<_, i64>(6)? as u64,
+                attempted_at: parse_ts(&row.get::<_, String>(7)?),
+            })
+        })?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .context("Failed to read delivery history")
+    }
+
+    fn query_entries(
+        &self,
+        clause: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<OutboxEntry>> {
+        let conn = self.lock()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {ENTRY_COLUMNS} FROM outbox WHERE {clause}"
+        ))?;
+        let rows = stmt.query_map(params, row_to_entry)?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .context("Failed to read outbox")
+    }
+}
+
+const ENTRY_COLUMNS: &str = "id, webhook_id, event, body, attempts, status, next_attempt_at, \
+                             last_error, created_at, updated_at, in_flight_until";
+
+/// Due, pending, and not leased at `?1`.
+const CLAIMABLE: &str = "status = 'pending' AND next_attempt_at <= ?1 \
+                         AND (in_flight_until IS NULL OR in_flight_until <= ?1)";
+
+fn row_to_entry(row: &rusqlite::Row<'_>) -> rusqlite::Result<OutboxEntry> {
+    Ok(OutboxEntry {
+        id: row.get(0)?,
+        webhook_id: row.get(1)?,
+        event: row.get(2)?,
+        body: row.get(3)?,
+        attempts: row.get(4)?,
+        status: OutboxStatus::parse_str(&row.get::<_, String>(5)?),
+        next_attempt_at: parse_ts(&row.get::<_, String>(6)?),
+        last_error: row.get(7)?,
+        created_at: parse_ts(&row.get::<_, String>(8)?),
+        updated_at: parse_ts(&row.get::<_, String>(9)?),
+        in_flight_until: row.get::<_, Option<String>>(10)?.map(|t| parse_ts(&t)),
+    })
+}
+
+/// Fixed-width UTC timestamps so SQLite can compare them as text.
+fn ts(t: DateTime<Utc>) -> String {
+    t.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+fn parse_ts(s: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attempt(entry: &OutboxEntry, n: u32, code: u16) -> DeliveryAttempt {
+        DeliveryAttempt {
+            outbox_id: entry.id.clone(),
+            webhook_id: entry.webhook_id.clone(),
+            event: entry.event.clone(),
+            attempt: n,
+            status_code: Some(code),
+            error: None,
+            duration_ms: 3,
+            attempted_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_entry_lifecycle() {
+        let outbox = Outbox::in_memory().unwrap();
+        let entry = outbox.enqueue("wh-1", "deploy", r#"{"ok":true}"#).unwrap();
+        assert_eq!(outbox.pending_count().unwrap(), 1);
+        assert_eq!(outbox.due(Utc::now(), 10).unwrap().len(), 1);
+
+        let later = Utc::now() + chrono::Duration::minutes(5);
+        outbox
+            .record_attempt(&attempt(&entry, 1, 503), NextStep::RetryAt(later))
+            .unwrap();
+        assert!(outbox.due(Utc::now(), 10).unwrap().is_empty());
+        assert_eq!(outbox.due(later, 10).unwrap().len(), 1);
+        let next_due = outbox.next_due_at().unwrap().unwrap();
+        assert!((next_due - later).num_milliseconds().abs() < 1);
+
+        let stored = outbox.get(&entry.id).unwrap().unwrap();
+        assert_eq!(stored.attempts, 1);
+        assert_eq!(stored.last_error.as_deref(), Some("HTTP 503"));
+
+        outbox
+            .record_attempt(&attempt(&entry, 2, 200), NextStep::Delivered)
+            .unwrap();
+        let stored = outbox.get(&entry.id).unwrap().unwrap();
+        assert_eq!(stored.status, OutboxStatus::Delivered);
+        assert_eq!(stored.last_error, None);
+        assert_eq!(outbox.pending_count().unwrap(), 0);
+
+        let history = outbox.history("wh-1", 10).unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(history[0].succeeded());
+        assert!(!history[1].succeeded());
+    }
+
+    #[test]
+    fn test_dead_letters_and_requeue() {
+        let outbox = Outbox::in_memory().unwrap();
+        let entry = outbox.enqueue("wh-1", "deploy", "{}").unwrap();
+        outbox
+            .record_attempt(&attempt(&entry, 1, 500), NextStep::Dead)
+            .unwrap();
+        let dead = outbox.dead_letters(10).unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].id, entry.id);
+
+        assert!(outbox.requeue(&entry.id).unwrap());
+        assert!(!outbox.requeue(&entry.id).unwrap());
+        assert!(outbox.dead_letters(10).unwrap().is_empty());
+        let requeued = outbox.get(&entry.id).unwrap().unwrap();
+        assert_eq!(requeued.attempts, 0);
+        assert_eq!(requeued.status, OutboxStatus::Pending);
+    }
+
+    #[test]
+    fn test_claimed_entry_is_not_claimed_twice() {
+        let outbox = Outbox::in_memory().unwrap();
+        let entry = outbox.enqueue("wh-1", "deploy", "{}").unwrap();
+        let lease = Duration::from_secs(30);
+        let now = Utc::now();
+
+        assert!(outbox.claim(&entry.id, now, lease).unwrap().is_some());
+        assert!(outbox.claim(&entry.id, now, lease).unwrap().is_none());
+        assert!(outbox.claim_due(now, lease, 10).unwrap().is_empty());
+        assert!(outbox.due(now, 10).unwrap().is_empty());
+
+        // A sender that died mid-attempt leaves a lease that runs out.
+        let expired = now + chrono::Duration::seconds(31);
+        let reclaimed = outbox.claim_due(expired, lease, 10).unwrap();
+        assert_eq!(reclaimed.len(), 1);
+        assert!(reclaimed[0].in_flight_until.is_some());
+
+        outbox
+            .record_attempt(&attempt(&entry, 1, 503), NextStep::RetryAt(now))
+            .unwrap();
+        let stored = outbox.get(&entry.id).unwrap().unwrap();
+        assert_eq!(stored.in_flight_until, None);
+        assert!(outbox.claim(&entry.id, expired, lease).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_outbox_survives_reopen() {
+        let dir = std::env::temp_dir().join(format!("hive_outbox_test_{}", Uuid::new_v4()));
+        let path = dir.join("webhook_outbox.db");
+        let id = {
+            let outbox = Outbox::open(&path).unwrap();
+            outbox.enqueue("wh-1", "deploy", "{}").unwrap().id
+        };
+        let outbox = Outbox::open(&path).unwrap();
+        assert_eq!(outbox.due(Utc::now(), 10).unwrap()[0].id, id);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
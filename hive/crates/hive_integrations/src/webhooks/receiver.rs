@@ -0,0 +1,571 @@
+//! Embedded HTTP listener for inbound webhooks.
+//!
+//! Each configured source is served on its own route (`/webhooks/github`,
+//! `/webhooks/stripe`, ...). A delivery is verified against the source's
+//! secret, checked against the replay window and the delivery log, and then
+//! published on the [`WebhookEventBus`]. Like the OAuth callback server, the
+//! HTTP/1.1 request is parsed directly from the socket; only `POST` with a
+//! `Content-Length` body is accepted.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use hive_core::config::HiveConfig;
+use serde_json::Value;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use super::bus::WebhookEventBus;
+use super::delivery_log::DeliveryLog;
+use super::inbound::{self, SourceConfig, VerifyError};
+
+// ── Constants ────────────────────────────────────────────────────────
+
+const DEFAULT_BIND_ADDR: &str = "127.0.0.1:8743";
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+// ── Configuration ────────────────────────────────────────────────────
+
+/// Settings for [`WebhookReceiver`].
+#[derive(Debug, Clone)]
+pub struct ReceiverConfig {
+    /// Where to listen. Put a tunnel or reverse proxy in front to expose it.
+    pub bind_addr: SocketAddr,
+    pub sources: Vec<SourceConfig>,
+    /// How far a signed timestamp may drift from now.
+    pub replay_window: Duration,
+    pub max_body_bytes: usize,
+}
+
+impl Default for ReceiverConfig {
+    fn default() -> Self {
+        Self {
+            bind_addr: DEFAULT_BIND_ADDR.parse().expect("valid default address"),
+            sources: Vec::new(),
+            replay_window: Duration::from_secs(300),
+            max_body_bytes: 5 * 1024 * 1024,
+        }
+    }
+}
+
+impl ReceiverConfig {
+    /// `~/.hive/webhook_sources.json`
+    pub fn sources_path() -> Result<PathBuf> {
+        Ok(HiveConfig::base_dir()?.join("webhook_sources.json"))
+    }
+
+    /// Read the sources (routes and secrets) from a JSON array at `path`.
+    /// A missing file means no sources; an empty secret is an error.
+    pub fn load_sources(path: &Path) -> Result<Vec<SourceConfig>> {
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let sources: Vec<SourceConfig> = serde_json::from_str(&raw)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        for source in &sources {
+            source.validate().map_err(anyhow::Error::msg)?;
+        }
+        Ok(sources)
+    }
+}
+
+/// Status and plain-text body sent back to the sender.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl WebhookResponse {
+    fn new(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: body.into(),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            411 => "Length Required",
+            413 => "Payload Too Large",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+// ── Receiver ─────────────────────────────────────────────────────────
+
+/// Verifies inbound deliveries and publishes them as events.
+pub struct WebhookReceiver {
+    bind_addr: SocketAddr,
+    routes: HashMap<String, SourceConfig>,
+    replay_window: Duration,
+    max_body_bytes: usize,
+    log: Arc<DeliveryLog>,
+    bus: WebhookEventBus,
+}
+
+impl WebhookReceiver {
+    /// Fails if any source has an empty secret, which would let anyone
+    /// forge deliveries for it.
+    pub fn new(
+        config: ReceiverConfig,
+        log: Arc<DeliveryLog>,
+        bus: WebhookEventBus,
+    ) -> Result<Self> {
+        for source in &config.sources {
+            source.validate().map_err(anyhow::Error::msg)?;
+        }
+        let routes = config
+            .sources
+            .into_iter()
+            .map(|source| (source.route(), source))
+            .collect();
+        Ok(Self {
+            bind_addr: config.bind_addr,
+            routes,
+            replay_window: config.replay_window,
+            max_body_bytes: config.max_body_bytes,
+            log,
+            bus,
+        })
+    }
+
+    /// The bus verified events are published on.
+    pub fn bus(&self) -> &WebhookEventBus {
+        &self.bus
+    }
+
+    /// Routes currently served, sorted.
+    pub fn routes(&self) -> Vec<String> {
+        let mut routes: Vec<String> = self.routes.keys().cloned().collect();
+        routes.sort();
+        routes
+    }
+
+    /// Handle one request. `headers` must use lowercase names.
+    pub fn handle(
+        &self,
+        method: &str,
+        path: &str,
+        headers: &HashMap<String, String>,
+        body: &[u8],
+    ) -> WebhookResponse {
+        self.handle_at(method, path, headers, body, Utc::now())
+    }
+
+    fn handle_at(
+        &self,
+        method: &str,
+        path: &str,
+        headers: &HashMap<String, String>,
+        body: &[u8],
+        now: DateTime<Utc>,
+    ) -> WebhookResponse {
+        let path = path.split('?').next().unwrap_or(path);
+        let Some(config) = self.routes.get(path) else {
+            return WebhookResponse::new(404, "unknown webhook route");
+        };
+        if method != "POST" {
+            return WebhookResponse::new(405, "webhooks must be POSTed");
+        }
+        let source = config.source;
+
+        let verified =
+            inbound::verify(config, headers, body, now, self.replay_window).and_then(|()| {
+                serde_json::from_slice::<Value>(body)
+                    .map_err(|e| VerifyError::Malformed(e.to_string()))
+            });
+        let payload = match verified {
+            Ok(payload) => payload,
+            Err(err) => {
+                warn!(source = %source, "rejected webhook: {err}");
+                if let Err(e) = self.log.record_rejected(source, None, &err.to_string()) {
+                    warn!("Failed to log rejected webhook: {e:#}");
+                }
+                let status = if matches!(err, VerifyError::Malformed(_)) {
+                    400
+                } else {
+                    401
+                };
+                return WebhookResponse::new(status, err.to_string());
+            }
+        };
+
+        // Slack confirms the endpoint by asking for its challenge back.
+        if payload.get("type").and_then(Value::as_str) == Some("url_verification") {
+            let challenge = payload.get("challenge").and_then(Value::as_str);
+            return WebhookResponse::new(200, challenge.unwrap_or_default());
+        }
+
+        let event = inbound::normalize(source, headers, body, payload, now);
+        match self.log.record_accepted(&event) {
+            Ok(true) => {
+                info!(topic = %event.topic(), delivery = %event.delivery_id, "webhook received");
+                self.bus.publish(event);
+                WebhookResponse::new(200, "accepted")
+            }
+            Ok(false) => {
+                debug!(topic = %event.topic(), delivery = %event.delivery_id, "duplicate webhook");
+                WebhookResponse::new(200, "duplicate")
+            }
+            Err(e) => {
+                warn!("Failed to log webhook delivery: {e:#}");
+                WebhookResponse::new(500, "delivery log unavailable")
+            }
+        }
+    }
+
+    /// Bind the listener and serve in the background until the returned
+    /// handle is shut down.
+    pub async fn start(self) -> Result<ReceiverHandle> {
+        let listener = TcpListener::bind(self.bind_addr)
+            .await
+            .with_context(|| format!("Failed to bind webhook receiver on {}", self.bind_addr))?;
+        let local_addr = listener.local_addr()?;
+        info!(routes = ?self.routes(), "Webhook receiver listening on http://{local_addr}");
+
+        let (shutdown_tx, mut shutdown) = broadcast::channel(1);
+        let receiver = Arc::new(self);
+        let task = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    accepted = listener.accept() => match accepted {
+                        Ok((stream, peer)) => {
+                            let receiver = Arc::clone(&receiver);
+                            tokio::spawn(async move {
+                                if let Err(e) = receiver.serve_connection(stream).await {
+                                    debug!("Webhook connection from {peer} failed: {e:#}");
+                                }
+                            });
+                        }
+                        Err(e) => warn!("Webhook receiver accept failed: {e}"),
+                    },
+                    _ = shutdown.recv() => break,
+                }
+            }
+            info!("Webhook receiver stopped");
+        });
+
+        Ok(ReceiverHandle {
+            local_addr,
+            shutdown_tx,
+            task,
+        })
+    }
+
+    async fn serve_connection(&self, mut stream: TcpStream) -> Result<()> {
+        let response =
+            match tokio::time::timeout(READ_TIMEOUT, self.read_request(&mut stream)).await {
+                Ok(Ok(Ok(request))) => self.handle(
+                    &request.method,
+                    &request.path,
+                    &request.headers,
+                    &request.body,
+                ),
+                Ok(Ok(Err(response))) => response,
+                Ok(Err(e)) => return Err(e),
+                Err(_) => WebhookResponse::new(400, "request timed out"),
+            };
+        let head = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: text/plain; charset=utf-8\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n",
+            response.status,
+            response.reason(),
+            response.body.len()
+        );
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(response.body.as_bytes()).await?;
+        stream.shutdown().await?;
+        Ok(())
+    }
+
+    /// Read one request. The inner `Err` is a response to send instead of
+    /// handling the request.
+    async fn read_request(
+        &self,
+        stream: &mut TcpStream,
+    ) -> Result<std::result::Result<RawRequest, WebhookResponse>> {
+        let mut buf = Vec::with_capacity(4096);
+        let mut chunk = [0u8; 4096];
+        let header_end = loop {
+            if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+            if buf.len() > MAX_HEADER_BYTES {
+                return Ok(Err(WebhookResponse::new(400, "headers too large")));
+            }
+            let n = stream.read(&mut chunk).await?;
+            if n == 0 {
+                anyhow::bail!("connection closed before headers were complete");
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        };
+
+        let head = String::from_utf8_lossy(&buf[..header_end]);
+        let mut lines = head.split("\r\n");
+        let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+        let method = request_line.next().unwrap_or_default().to_string();
+        let path = request_line.next().unwrap_or("/").to_string();
+        let headers: HashMap<String, String> = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+            .collect();
+
+        let Some(length) = headers
+            .get("content-length")
+            .and_then(|v| v.parse::<usize>().ok())
+        else {
+            return Ok(Err(WebhookResponse::new(411, "Content-Length required")));
+        };
+        if length > self.max_body_bytes {
+            return Ok(Err(WebhookResponse::new(413, "payload too large")));
+        }
+
+        let mut body = buf.split_off(header_end);
+        while body.len() < length {
+            let n = stream.read(&mut chunk).await?;
+            if n == 0 {
+                anyhow::bail!("connection closed before body was complete");
+            }
+            body.extend_from_slice(&chunk[..n]);
+        }
+        body.truncate(length);
+
+        Ok(Ok(RawRequest {
+            method,
+            path,
+            headers,
+            body,
+        }))
+    }
+}
+
+struct RawRequest {
+    method: String,
+    path: String,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+/// A running receiver.
+pub struct ReceiverHandle {
+    local_addr: SocketAddr,
+    shutdown_tx: broadcast::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl ReceiverHandle {
+    /// The address actually bound (useful when binding port 0).
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Stop accepting connections and wait for the listener to close.
+    pub async fn shutdown(self) {
+        let _ = self.shutdown_tx.send(());
+        let _ = self.task.await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webhooks::delivery_log::DeliveryStatus;
+    use crate::webhooks::inbound::{WebhookSource, hmac_hex};
+    use serde_json::json;
+
+    const SECRET: &str = "shh";
+
+    fn receiver() -> WebhookReceiver {
+        let config = ReceiverConfig {
+            bind_addr: "127.0.0.1:0".parse().unwrap(),
+            sources: vec![
+                SourceConfig::new(WebhookSource::GitHub, SECRET),
+                SourceConfig::new(WebhookSource::Slack, SECRET).with_path("/slack/events"),
+            ],
+            ..ReceiverConfig::default()
+        };
+        WebhookReceiver::new(
+            config,
+            Arc::new(DeliveryLog::in_memory().unwrap()),
+            WebhookEventBus::new(),
+        )
+        .unwrap()
+    }
+
+    fn github_headers(body: &[u8], delivery: &str) -> HashMap<String, String> {
+        HashMap::from([
+            ("x-github-event".to_string(), "push".to_string()),
+            ("x-github-delivery".to_string(), delivery.to_string()),
+            (
+                "x-hub-signature-256".to_string(),
+                format!("sha256={}", hmac_hex(SECRET.as_bytes(), &[body])),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_verified_delivery_is_published_once() {
+        let receiver = receiver();
+        let mut sub = receiver.bus().subscribe("github.push");
+        let body = br#"{"ref":"refs/heads/main"}"#;
+        let headers = github_headers(body, "d-1");
+
+        let first = receiver.handle("POST", "/webhooks/github", &headers, body);
+        assert_eq!(first, WebhookResponse::new(200, "accepted"));
+        let replay = receiver.handle("POST", "/webhooks/github", &headers, body);
+        assert_eq!(replay, WebhookResponse::new(200, "duplicate"));
+
+        let event = sub.try_recv().unwrap();
+        assert_eq!(event.payload["ref"], "refs/heads/main");
+        assert!(sub.try_recv().is_none());
+    }
+
+    #[test]
+    fn test_replay_with_new_delivery_id_is_duplicate() {
+        let receiver = receiver();
+        let mut sub = receiver.bus().subscribe("github.push");
+        let body = br#"{"ref":"refs/heads/main"}"#;
+
+        let first = receiver.handle(
+            "POST",
+            "/webhooks/github",
+            &github_headers(body, "d-1"),
+            body,
+        );
+        assert_eq!(first, WebhookResponse::new(200, "accepted"));
+        let replay = receiver.handle(
+            "POST",
+            "/webhooks/github",
+            &github_headers(body, "d-9"),
+            body,
+        );
+        assert_eq!(replay, WebhookResponse::new(200, "duplicate"));
+
+        assert!(sub.try_recv().is_some());
+        assert!(sub.try_recv().is_none());
+    }
+
+    #[test]
+    fn test_empty_secret_is_refused() {
+        let config = ReceiverConfig {
+            sources: vec![SourceConfig::new(WebhookSource::GitHub, "")],
+            ..ReceiverConfig::default()
+        };
+        let receiver = WebhookReceiver::new(
+            config,
+            Arc::new(DeliveryLog::in_memory().unwrap()),
+            WebhookEventBus::new(),
+        );
+        assert!(receiver.is_err());
+    }
+
+    #[test]
+    fn test_load_sources_refuses_empty_secret() {
+        let dir = std::env::temp_dir().join(format!("hive_receiver_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("webhook_sources.json");
+        assert!(ReceiverConfig::load_sources(&path).unwrap().is_empty());
+
+        std::fs::write(&path, r#"[{"source": "github", "secret": "s3cret"}]"#).unwrap();
+        let sources = ReceiverConfig::load_sources(&path).unwrap();
+        assert_eq!(sources[0].route(), "/webhooks/github");
+
+        std::fs::write(&path, r#"[{"source": "stripe", "secret": ""}]"#).unwrap();
+        assert!(ReceiverConfig::load_sources(&path).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_bad_requests_are_refused_and_logged() {
+        let receiver = receiver();
+        let body = br#"{"ref":"x"}"#;
+        let mut forged = github_headers(body, "d-2");
+        forged.insert("x-hub-signature-256".into(), "sha256=00".into());
+
+        let resp = receiver.handle("POST", "/webhooks/github", &forged, body);
+        assert_eq!(resp.status, 401);
+        assert_eq!(receiver.handle("POST", "/nope", &forged, body).status, 404);
+        assert_eq!(
+            receiver
+                .handle("GET", "/webhooks/github", &forged, body)
+                .status,
+            405
+        );
+
+        let not_json = b"not json";
+        let headers = github_headers(not_json, "d-3");
+        let resp = receiver.handle("POST", "/webhooks/github", &headers, not_json);
+        assert_eq!(resp.status, 400);
+
+        let log = receiver.log.recent(10).unwrap();
+        assert_eq!(log.len(), 2);
+        assert!(log.iter().all(|r| r.status == DeliveryStatus::Rejected));
+    }
+
+    #[test]
+    fn test_slack_url_verification_echoes_challenge() {
+        let receiver = receiver();
+        let now = Utc::now();
+        let body = json!({"type": "url_verification", "challenge": "abc123"}).to_string();
+        let ts = now.timestamp().to_string();
+        let sig = hmac_hex(
+            SECRET.as_bytes(),
+            &[b"v0:", ts.as_bytes(), b":", body.as_bytes()],
+        );
+        let headers = HashMap::from([
+            ("x-slack-request-timestamp".to_string(), ts),
+            ("x-slack-signature".to_string(), format!("v0={sig}")),
+        ]);
+        let resp = receiver.handle_at("POST", "/slack/events", &headers, body.as_bytes(), now);
+        assert_eq!(resp, WebhookResponse::new(200, "abc123"));
+    }
+
+    #[tokio::test]
+    async fn test_receiver_serves_http() {
+        let receiver = receiver();
+        let mut sub = receiver.bus().subscribe("*");
+        let handle = receiver.start().await.unwrap();
+
+        let body = br#"{"ref":"refs/heads/dev"}"#;
+        let mut request = format!(
+            "POST /webhooks/github?via=test HTTP/1.1\r\nHost: localhost\r\n\
+             Content-Type: application/json\r\nContent-Length: {}\r\n",
+            body.len()
+        );
+        for (name, value) in github_headers(body, "d-http") {
+            request.push_str(&format!("{name}: {value}\r\n"));
+        }
+        request.push_str("\r\n");
+
+        let mut stream = TcpStream::connect(handle.local_addr()).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+        stream.write_all(body).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+        assert!(response.ends_with("accepted"));
+
+        let event = sub.recv().await.unwrap();
+        assert_eq!(event.topic(), "github.push");
+        assert_eq!(event.delivery_id, "d-http");
+        handle.shutdown().await;
+    }
+}
@@ -8,9 +8,13 @@ pub mod self_evaluator;
 pub mod storage;
 pub mod types;
 
-use std::sync::Arc;
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
-use tracing::info;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use hive_ai::routing::{CallSample, QualitySource, TelemetrySink};
 
 use outcome_tracker::OutcomeTracker;
 use pattern_library::PatternLibrary;
@@ -71,7 +75,9 @@ impl LearningService {
     /// This is the main entry point for recording interaction results. It:
     /// 1. Records the outcome via the outcome tracker
     /// 2. Records routing history for the routing learner
-    /// 3. Updates prompt quality scores if a persona is associated
+    /// 3. Updates prompt quality scores if a persona is associated, attributing
+    ///    them to the experiment arm `serve_prompt` served to the conversation
+    ///    while an A/B test runs
     /// 4. Triggers periodic routing analysis (every 50 interactions)
     /// 5. Triggers periodic self-evaluation (every 200 interactions)
     pub fn on_outcome(&self, record: &OutcomeRecord) -> Result<(), String> {
@@ -90,10 +96,14 @@ impl LearningService {
         })?;
 
         // 3. Update prompt performance
-        if let Some(ref persona) = record.persona {
-            let _ = self
-                .prompt_evolver
-                .record_quality(persona, record.quality_score);
+        if let Some(ref persona) = record.persona
+            && let Err(e) = self.prompt_evolver.record_outcome(
+                persona,
+                &record.conversation_id,
+                record.quality_score,
+            )
+        {
+            warn!("Failed to record prompt quality for '{persona}': {e}");
         }
 
         // 4. Increment interaction count
@@ -127,6 +137,38 @@ impl LearningService {
         self.prompt_evolver.apply_refinement(persona, prompt)
     }
 
+    /// User control: A/B test a refinement against the active prompt instead
+    /// of switching to it outright. The winner is promoted automatically.
+    pub fn propose_prompt_refinement(
+        &self,
+        persona: &str,
+        prompt: &str,
+        candidate_share: f64,
+    ) -> Result<PromptExperiment, String> {
+        self.prompt_evolver
+            .propose_refinement(persona, prompt, candidate_share)
+    }
+
+    /// User control: stop a persona's running prompt experiment.
+    pub fn stop_prompt_experiment(&self, persona: &str) -> Result<(), String> {
+        self.prompt_evolver.stop_experiment(persona)
+    }
+
+    /// The prompt version to serve for `persona` in a conversation. During
+    /// an A/B test the served arm is recorded for outcome attribution.
+    pub fn serve_prompt(
+        &self,
+        persona: &str,
+        conversation_id: &str,
+    ) -> Result<Option<PromptVersion>, String> {
+        self.prompt_evolver.serve_prompt(persona, conversation_id)
+    }
+
+    /// Recent prompt experiments with per-arm quality, newest first.
+    pub fn prompt_experiments(&self, limit: usize) -> Result<Vec<ExperimentReport>, String> {
+        self.prompt_evolver.experiment_reports(limit)
+    }
+
     /// User control: rollback a prompt to a previous version.
     pub fn rollback_prompt(&self, persona: &str, version: u32) -> Result<(), String> {
         self.prompt_evolver.rollback(persona, version)
@@ -146,6 +188,12 @@ impl LearningService {
     pub fn all_preferences(&self) -> Result<Vec<(String, String, f64)>, String> {
         self.preference_model.all()
     }
+
+    /// The most recent persisted model calls, oldest first, for warming the
+    /// router's telemetry at startup.
+    pub fn recent_model_calls(&self, limit: usize) -> Result<Vec<CallSample>, String> {
+        self.storage.recent_model_calls(limit)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -175,6 +223,76 @@ impl hive_ai::routing::TierAdjuster for LearnerTierAdjuster {
     }
 }
 
+// ---------------------------------------------------------------------------
+// LearnerTelemetry — persists call telemetry and supplies learned quality
+// ---------------------------------------------------------------------------
+
+/// Outcomes a model needs within the window before its quality is trusted.
+const MIN_QUALITY_OUTCOMES: u64 = 3;
+/// Days of outcomes averaged into a model's quality.
+const QUALITY_WINDOW_DAYS: u32 = 30;
+/// How long a looked-up quality is reused before querying storage again.
+const QUALITY_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Adapter that implements `hive_ai::routing::TelemetrySink` (persisting
+/// every model call) and `hive_ai::routing::QualitySource` (delegating to
+/// `OutcomeTracker::model_quality()`).
+///
+/// Register it with `ModelTelemetry::set_sink()` and
+/// `ModelRouter::set_quality_source()`.
+pub struct LearnerTelemetry {
+    learning: Arc<LearningService>,
+    quality_cache: Mutex<HashMap<String, (Instant, Option<f64>)>>,
+}
+
+impl LearnerTelemetry {
+    pub fn new(learning: Arc<LearningService>) -> Self {
+        Self {
+            learning,
+            quality_cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn lookup_quality(&self, model_id: &str) -> Option<f64> {
+        let count = self
+            .learning
+            .storage
+            .model_outcome_count(model_id, QUALITY_WINDOW_DAYS)
+            .ok()?;
+        if count < MIN_QUALITY_OUTCOMES {
+            return None;
+        }
+        self.learning
+            .outcome_tracker
+            .model_quality(model_id, QUALITY_WINDOW_DAYS)
+            .ok()
+    }
+}
+
+impl TelemetrySink for LearnerTelemetry {
+    fn record_call(&self, sample: &CallSample) {
+        if let Err(e) = self.learning.storage.record_model_call(sample) {
+            warn!("Failed to persist model call telemetry: {e}");
+        }
+    }
+}
+
+impl QualitySource for LearnerTelemetry {
+    fn model_quality(&self, model_id: &str) -> Option<f64> {
+        let Ok(mut cache) = self.quality_cache.lock() else {
+            return self.lookup_quality(model_id);
+        };
+        if let Some((at, quality)) = cache.get(model_id)
+            && at.elapsed() < QUALITY_CACHE_TTL
+        {
+            return *quality;
+        }
+        let quality = self.lookup_quality(model_id);
+        cache.insert(model_id.to_string(), (Instant::now(), quality));
+        quality
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -243,6 +361,36 @@ mod tests {
         assert_eq!(prompt, "You are a coder.");
     }
 
+    #[test]
+    fn test_on_outcome_attributes_experiment_arm_by_conversation() {
+        let service = LearningService::in_memory().unwrap();
+        service
+            .prompt_evolver
+            .apply_refinement("coder", "Control.")
+            .unwrap();
+        let exp = service
+            .propose_prompt_refinement("coder", "Candidate.", 0.5)
+            .unwrap();
+
+        let record = make_outcome_record("gpt-4o", 0.9, Outcome::Accepted);
+        let arm = exp.arm_for(&record.conversation_id).unwrap();
+        let served = service
+            .serve_prompt("coder", &record.conversation_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(served.version, arm);
+        service.on_outcome(&record).unwrap();
+
+        let report = &service.prompt_experiments(1).unwrap()[0];
+        let (served_arm, other_arm) = if arm == exp.candidate_version {
+            (report.candidate, report.control)
+        } else {
+            (report.control, report.candidate)
+        };
+        assert_eq!(served_arm.samples, 1);
+        assert_eq!(other_arm.samples, 0);
+    }
+
     #[test]
     fn test_on_outcome_no_persona_is_ok() {
         let service = LearningService::in_memory().unwrap();
@@ -428,4 +576,37 @@ mod tests {
         // 7. Verify interaction count
         assert_eq!(service.interaction_count(), 5);
     }
+
+    #[test]
+    fn test_learner_telemetry_persists_calls_and_gates_quality() {
+        let service = Arc::new(LearningService::in_memory().unwrap());
+        let telemetry = LearnerTelemetry::new(Arc::clone(&service));
+
+        telemetry.record_call(&CallSample {
+            model_id: "gpt-4o".into(),
+            provider: "openai".into(),
+            success: true,
+            latency_ms: 900,
+            ttft_ms: Some(120),
+            output_tokens: 80,
+            timestamp: chrono::Utc::now(),
+        });
+        let calls = service.recent_model_calls(10).unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].ttft_ms, Some(120));
+
+        // Too few outcomes: no opinion yet.
+        service
+            .on_outcome(&make_outcome_record("claude", 0.9, Outcome::Accepted))
+            .unwrap();
+        assert_eq!(telemetry.model_quality("claude"), None);
+
+        for _ in 0..MIN_QUALITY_OUTCOMES {
+            service
+                .on_outcome(&make_outcome_record("gpt-4o", 0.8, Outcome::Accepted))
+                .unwrap();
+        }
+        let quality = telemetry.model_quality("gpt-4o").unwrap();
+        assert!((quality - 0.8).abs() < 1e-9);
+    }
 }
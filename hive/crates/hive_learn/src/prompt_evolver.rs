@@ -2,6 +2,20 @@ use crate::storage::LearningStorage;
 use crate::types::*;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use tracing::info;
+
+/// Samples each arm needs before an experiment can conclude.
+pub const MIN_ARM_SAMPLES: u32 = 30;
+
+/// Samples per arm after which an undecided experiment ends inconclusive.
+pub const MAX_ARM_SAMPLES: u32 = 1_000;
+
+/// False-promotion rate the sequential test guards against.
+pub const EXPERIMENT_ALPHA: f64 = 0.05;
+
+/// Standard deviation of the mixing prior over quality differences. Tuned to
+/// the size of improvement worth promoting on a 0–1 quality scale.
+const MIXTURE_TAU: f64 = 0.1;
 
 /// Gradual, user-approved prompt refinement system.
 ///
@@ -107,6 +121,7 @@ impl PromptEvolver {
 
         self.storage.save_prompt_version(&pv)?;
         self.storage.activate_prompt_version(persona, new_version)?;
+        self.supersede_experiment(persona)?;
 
         self.storage.log_learning(&LearningLogEntry {
             id: 0,
@@ -139,6 +154,7 @@ impl PromptEvolver {
         }
 
         self.storage.activate_prompt_version(persona, to_version)?;
+        self.supersede_experiment(persona)?;
 
         self.storage.log_learning(&LearningLogEntry {
             id: 0,
@@ -220,6 +236,348 @@ impl PromptEvolver {
     }
 }
 
+// ---------------------------------------------------------------------------
+// A/B experiments
+// ---------------------------------------------------------------------------
+
+impl PromptEvolver {
+    /// Save `new_prompt` as a new version without activating it, and start
+    /// an experiment serving it to `candidate_share` of conversations.
+    ///
+    /// The candidate replaces the active prompt only if it wins the
+    /// experiment; use `apply_refinement` to switch immediately.
+    pub fn propose_refinement(
+        &self,
+        persona: &str,
+        new_prompt: &str,
+        candidate_share: f64,
+    ) -> Result<PromptExperiment, String> {
+        if self.storage.get_active_prompt(persona)?.is_none() {
+            return Err(format!(
+                "Persona '{persona}' has no active prompt to test against"
+            ));
+        }
+        if self.storage.running_experiment(persona)?.is_some() {
+            return Err(format!(
+                "Persona '{persona}' already has a running experiment"
+            ));
+        }
+
+        let version = self.storage.max_prompt_version(persona)? + 1;
+        self.storage.save_prompt_version(&PromptVersion {
+            persona: persona.to_string(),
+            version,
+            prompt_text: new_prompt.to_string(),
+            avg_quality: 0.0,
+            sample_count: 0,
+            is_active: false,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        })?;
+        self.start_experiment(persona, version, candidate_share)
+    }
+
+    /// Start an experiment between the active version of `persona` and an
+    /// existing `candidate_version`.
+    pub fn start_experiment(
+        &self,
+        persona: &str,
+        candidate_version: u32,
+        candidate_share: f64,
+    ) -> Result<PromptExperiment, String> {
+        if !(candidate_share > 0.0 && candidate_share < 1.0) {
+            return Err(format!(
+                "Candidate share must be between 0 and 1, got {candidate_share}"
+            ));
+        }
+        let control = self
+            .storage
+            .get_active_prompt(persona)?
+            .ok_or_else(|| format!("Persona '{persona}' has no active prompt to test against"))?;
+        if control.version == candidate_version {
+            return Err(format!(
+                "Version {candidate_version} is already active for persona '{persona}'"
+            ));
+        }
+        if self
+            .storage
+            .get_prompt_version(persona, candidate_version)?
+            .is_none()
+        {
+            return Err(format!(
+                "Version {candidate_version} does not exist for persona '{persona}'"
+            ));
+        }
+        if self.storage.running_experiment(persona)?.is_some() {
+            return Err(format!(
+                "Persona '{persona}' already has a running experiment"
+            ));
+        }
+
+        let mut experiment = PromptExperiment {
+            id: 0,
+            persona: persona.to_string(),
+            control_version: control.version,
+            candidate_version,
+            candidate_share,
+            status: ExperimentStatus::Running,
+            winner: None,
+            started_at: chrono::Utc::now().to_rfc3339(),
+            ended_at: None,
+        };
+        experiment.id = self.storage.create_experiment(&experiment)?;
+
+        self.storage.log_learning(&LearningLogEntry {
+            id: 0,
+            event_type: "prompt_experiment_started".into(),
+            description: format!(
+                "Testing persona '{persona}' version {candidate_version} against version {} \
+                 ({:.0}% of conversations)",
+                control.version,
+                candidate_share * 100.0
+            ),
+            details: serde_json::to_string(&experiment).unwrap_or_default(),
+            reversible: false,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })?;
+
+        Ok(experiment)
+    }
+
+    /// The prompt version to serve for `persona` in the conversation
+    /// identified by `subject`.
+    ///
+    /// While an experiment runs, each subject is pinned to one arm, so a
+    /// conversation sees a consistent prompt, and the served arm is recorded
+    /// so its outcomes can be attributed. Otherwise this is the active
+    /// version.
+    pub fn serve_prompt(
+        &self,
+        persona: &str,
+        subject: &str,
+    ) -> Result<Option<PromptVersion>, String> {
+        if let Some(exp) = self.storage.running_experiment(persona)?
+            && let Some(version) = exp.arm_for(subject)
+        {
+            self.storage
+                .record_experiment_assignment(exp.id, subject, version)?;
+            return self.storage.get_prompt_version(persona, version);
+        }
+        self.storage.get_active_prompt(persona)
+    }
+
+    /// Record the quality of a response served to `subject` for `persona`.
+    ///
+    /// During an experiment the score is attributed to the arm recorded by
+    /// `serve_prompt` for the subject and the experiment is re-evaluated.
+    /// Otherwise, or when the subject was never served a variant, it updates
+    /// the active version as `record_quality` does.
+    pub fn record_outcome(
+        &self,
+        persona: &str,
+        subject: &str,
+        quality_score: f64,
+    ) -> Result<(), String> {
+        let Some(exp) = self.storage.running_experiment(persona)? else {
+            return self.record_quality(persona, quality_score);
+        };
+        let Some(version) = self.storage.experiment_assignment(exp.id, subject)? else {
+            // Unattributable; the control was served.
+            return self.record_quality(persona, quality_score);
+        };
+
+        self.storage
+            .record_experiment_sample(exp.id, version, quality_score)?;
+        if version == exp.control_version {
+            self.record_quality(persona, quality_score)?;
+        }
+        self.evaluate_experiment(&exp)?;
+        Ok(())
+    }
+
+    /// Current figures for an experiment.
+    pub fn experiment_report(&self, exp: &PromptExperiment) -> Result<ExperimentReport, String> {
+        let control = self
+            .storage
+            .experiment_arm_stats(exp.id, exp.control_version)?;
+        let candidate = self
+            .storage
+            .experiment_arm_stats(exp.id, exp.candidate_version)?;
+        Ok(ExperimentReport {
+            experiment: exp.clone(),
+            control,
+            candidate,
+            likelihood_ratio: mixture_likelihood_ratio(&control, &candidate),
+        })
+    }
+
+    /// Run the sequential test and conclude the experiment if it is decided.
+    ///
+    /// The winner is activated only once both arms have `MIN_ARM_SAMPLES`
+    /// and the mixture likelihood ratio reaches `1 / EXPERIMENT_ALPHA`. The
+    /// test stays valid however often it is checked. Past `MAX_ARM_SAMPLES`
+    /// without a decision, the control is kept.
+    pub fn evaluate_experiment(&self, exp: &PromptExperiment) -> Result<ExperimentStatus, String> {
+        if exp.status != ExperimentStatus::Running {
+            return Ok(exp.status);
+        }
+        let report = self.experiment_report(exp)?;
+        let (control, candidate) = (report.control, report.candidate);
+        let min_samples = control.samples.min(candidate.samples);
+        if min_samples < MIN_ARM_SAMPLES {
+            return Ok(ExperimentStatus::Running);
+        }
+
+        if report.likelihood_ratio >= 1.0 / EXPERIMENT_ALPHA {
+            let winner = if candidate.mean > control.mean {
+                candidate
+            } else {
+                control
+            };
+            if winner.version == exp.candidate_version {
+                self.storage
+                    .activate_prompt_version(&exp.persona, winner.version)?;
+                self.storage
+                    .update_prompt_quality(&exp.persona, winner.mean, winner.samples)?;
+            }
+            self.storage.finish_experiment(
+                exp.id,
+                ExperimentStatus::Concluded,
+                Some(winner.version),
+            )?;
+            info!(
+                persona = %exp.persona,
+                winner = winner.version,
+                "Prompt experiment concluded"
+            );
+            self.storage.log_learning(&LearningLogEntry {
+                id: 0,
+                event_type: "prompt_experiment_concluded".into(),
+                description: format!(
+                    "Persona '{}': version {} wins ({:.2} vs {:.2} over {} + {} samples)",
+                    exp.persona,
+                    winner.version,
+                    candidate.mean.max(control.mean),
+                    candidate.mean.min(control.mean),
+                    control.samples,
+                    candidate.samples
+                ),
+                details: serde_json::to_string(&report).unwrap_or_default(),
+                reversible: true,
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            })?;
+            return Ok(ExperimentStatus::Concluded);
+        }
+
+        if min_samples >= MAX_ARM_SAMPLES {
+            self.storage
+                .finish_experiment(exp.id, ExperimentStatus::Inconclusive, None)?;
+            self.storage.log_learning(&LearningLogEntry {
+                id: 0,
+                event_type: "prompt_experiment_inconclusive".into(),
+                description: format!(
+                    "Persona '{}': no significant difference between versions {} and {}; \
+                     keeping version {}",
+                    exp.persona, exp.control_version, exp.candidate_version, exp.control_version
+                ),
+                details: serde_json::to_string(&report).unwrap_or_default(),
+                reversible: false,
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            })?;
+            return Ok(ExperimentStatus::Inconclusive);
+        }
+
+        Ok(ExperimentStatus::Running)
+    }
+
+    /// The running experiment for a persona, if any.
+    pub fn running_experiment(&self, persona: &str) -> Result<Option<PromptExperiment>, String> {
+        self.storage.running_experiment(persona)
+    }
+
+    /// Reports for the most recent experiments, newest first.
+    pub fn experiment_reports(&self, limit: usize) -> Result<Vec<ExperimentReport>, String> {
+        self.storage
+            .recent_experiments(limit)?
+            .iter()
+            .map(|exp| self.experiment_report(exp))
+            .collect()
+    }
+
+    /// Stop the running experiment for a persona, keeping the control.
+    pub fn stop_experiment(&self, persona: &str) -> Result<(), String> {
+        let exp = self
+            .storage
+            .running_experiment(persona)?
+            .ok_or_else(|| format!("Persona '{persona}' has no running experiment"))?;
+        self.storage
+            .finish_experiment(exp.id, ExperimentStatus::Stopped, None)?;
+        self.storage.log_learning(&LearningLogEntry {
+            id: 0,
+            event_type: "prompt_experiment_stopped".into(),
+            description: format!(
+                "Stopped experiment for persona '{persona}'; keeping version {}",
+                exp.control_version
+            ),
+            details: serde_json::to_string(&exp).unwrap_or_default(),
+            reversible: false,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })?;
+        Ok(())
+    }
+
+    /// A manual activation ends any experiment whose control it replaced.
+    fn supersede_experiment(&self, persona: &str) -> Result<(), String> {
+        if let Some(exp) = self.storage.running_experiment(persona)? {
+            self.storage
+                .finish_experiment(exp.id, ExperimentStatus::Stopped, None)?;
+        }
+        Ok(())
+    }
+}
+
+impl PromptExperiment {
+    /// The version served to `subject`, or `None` for an empty subject,
+    /// which cannot be pinned to an arm.
+    ///
+    /// Assignment hashes the experiment ID with the subject, so it is stable
+    /// across restarts and independent between experiments.
+    pub fn arm_for(&self, subject: &str) -> Option<u32> {
+        if subject.is_empty() {
+            return None;
+        }
+        let bucket = fnv1a(format!("{}:{subject}", self.id).as_bytes()) % 10_000;
+        if (bucket as f64) < self.candidate_share * 10_000.0 {
+            Some(self.candidate_version)
+        } else {
+            Some(self.control_version)
+        }
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Mixture sequential probability ratio (mSPRT) for the difference of two
+/// arm means under a normal approximation, with a `N(0, MIXTURE_TAU²)` prior
+/// on the difference.
+///
+/// Rejecting "no difference" once this reaches `1 / alpha` bounds the false
+/// positive rate by `alpha` no matter how often it is checked.
+pub fn mixture_likelihood_ratio(control: &ArmStats, candidate: &ArmStats) -> f64 {
+    if control.samples < 2 || candidate.samples < 2 {
+        return 0.0;
+    }
+    let v = (control.variance / control.samples as f64
+        + candidate.variance / candidate.samples as f64)
+        .max(1e-9);
+    let tau2 = MIXTURE_TAU * MIXTURE_TAU;
+    let delta = candidate.mean - control.mean;
+    (v / (v + tau2)).sqrt() * (tau2 * delta * delta / (2.0 * v * (v + tau2))).exp()
+}
+
 /// A request to send to an AI provider for intelligent prompt refinement.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AiRefinementRequest {
@@ -645,4 +1003,234 @@ mod tests {
             Some("Writer prompt.".to_string())
         );
     }
+
+    // ── A/B experiments ──────────────────────────────────────────────
+
+    /// Feed alternating-noise scores around `mean` to subjects on `arm`.
+    fn feed_arm(evolver: &PromptEvolver, exp: &PromptExperiment, arm: u32, mean: f64, n: u32) {
+        let mut fed = 0;
+        let mut i = 0;
+        while fed < n {
+            let subject = format!("conv-{arm}-{i}");
+            i += 1;
+            if exp.arm_for(&subject) != Some(arm) {
+                continue;
+            }
+            let noise = if fed % 2 == 0 { 0.05 } else { -0.05 };
+            evolver.serve_prompt("coder", &subject).unwrap();
+            evolver
+                .record_outcome("coder", &subject, mean + noise)
+                .unwrap();
+            fed += 1;
+        }
+    }
+
+    #[test]
+    fn test_propose_refinement_keeps_control_active() {
+        let (evolver, storage) = make_evolver_with_storage();
+        evolver.apply_refinement("coder", "Control.").unwrap();
+        let exp = evolver
+            .propose_refinement("coder", "Candidate.", 0.5)
+            .unwrap();
+
+        assert_eq!(exp.control_version, 1);
+        assert_eq!(exp.candidate_version, 2);
+        assert_eq!(
+            evolver.get_prompt("coder").unwrap().as_deref(),
+            Some("Control.")
+        );
+        assert!(evolver.running_experiment("coder").unwrap().is_some());
+        assert!(
+            evolver
+                .propose_refinement("coder", "Another.", 0.5)
+                .is_err()
+        );
+        assert!(
+            storage
+                .get_learning_log(10)
+                .unwrap()
+                .iter()
+                .any(|e| e.event_type == "prompt_experiment_started")
+        );
+    }
+
+    #[test]
+    fn test_start_experiment_validates_input() {
+        let evolver = make_evolver();
+        assert!(
+            evolver
+                .propose_refinement("coder", "No control.", 0.5)
+                .is_err()
+        );
+        evolver.apply_refinement("coder", "Control.").unwrap();
+        assert!(evolver.start_experiment("coder", 1, 0.5).is_err());
+        assert!(evolver.start_experiment("coder", 7, 0.5).is_err());
+        assert!(
+            evolver
+                .propose_refinement("coder", "Candidate.", 1.0)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_arm_assignment_is_stable_and_split() {
+        let (evolver, _) = make_evolver_with_storage();
+        evolver.apply_refinement("coder", "Control.").unwrap();
+        let exp = evolver
+            .propose_refinement("coder", "Candidate.", 0.2)
+            .unwrap();
+
+        assert_eq!(exp.arm_for(""), None);
+        assert_eq!(exp.arm_for("conv-42"), exp.arm_for("conv-42"));
+        let candidates = (0..2000)
+            .filter(|i| exp.arm_for(&format!("conv-{i}")) == Some(2))
+            .count();
+        assert!((300..500).contains(&candidates), "got {candidates}");
+
+        let served = evolver.serve_prompt("coder", "conv-42").unwrap().unwrap();
+        assert_eq!(Some(served.version), exp.arm_for("conv-42"));
+        let unpinned = evolver.serve_prompt("coder", "").unwrap().unwrap();
+        assert_eq!(unpinned.version, 1);
+    }
+
+    #[test]
+    fn test_outcomes_are_attributed_per_arm() {
+        let (evolver, _) = make_evolver_with_storage();
+        evolver.apply_refinement("coder", "Control.").unwrap();
+        let exp = evolver
+            .propose_refinement("coder", "Candidate.", 0.5)
+            .unwrap();
+        feed_arm(&evolver, &exp, 1, 0.5, 5);
+        feed_arm(&evolver, &exp, 2, 0.8, 3);
+        // Without a subject the score updates the control's running average
+        // but is not an experiment sample.
+        evolver.record_outcome("coder", "", 0.1).unwrap();
+
+        let report = evolver.experiment_report(&exp).unwrap();
+        assert_eq!(report.control.samples, 5);
+        assert_eq!(report.candidate.samples, 3);
+        assert!((report.candidate.mean - 0.8).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_unserved_conversation_is_not_attributed() {
+        let (evolver, _) = make_evolver_with_storage();
+        evolver.apply_refinement("coder", "Control.").unwrap();
+        let exp = evolver
+            .propose_refinement("coder", "Candidate.", 0.5)
+            .unwrap();
+
+        // The subject hashes to an arm, but no prompt was served to it.
+        let subject = (0..)
+            .map(|i| format!("conv-{i}"))
+            .find(|s| exp.arm_for(s) == Some(exp.candidate_version))
+            .unwrap();
+        evolver.record_outcome("coder", &subject, 0.9).unwrap();
+        let report = evolver.experiment_report(&exp).unwrap();
+        assert_eq!(report.candidate.samples, 0);
+        assert_eq!(report.control.samples, 0);
+
+        evolver.serve_prompt("coder", &subject).unwrap();
+        evolver.record_outcome("coder", &subject, 0.9).unwrap();
+        let report = evolver.experiment_report(&exp).unwrap();
+        assert_eq!(report.candidate.samples, 1);
+    }
+
+    #[test]
+    fn test_better_candidate_is_promoted_after_significance() {
+        let (evolver, storage) = make_evolver_with_storage();
+        evolver.apply_refinement("coder", "Control.").unwrap();
+        let exp = evolver
+            .propose_refinement("coder", "Candidate.", 0.5)
+            .unwrap();
+
+        // Large gap, but not enough samples yet.
+        feed_arm(&evolver, &exp, 1, 0.4, MIN_ARM_SAMPLES - 1);
+        feed_arm(&evolver, &exp, 2, 0.8, MIN_ARM_SAMPLES - 1);
+        assert!(evolver.running_experiment("coder").unwrap().is_some());
+        assert_eq!(
+            evolver.get_prompt("coder").unwrap().as_deref(),
+            Some("Control.")
+        );
+
+        feed_arm(&evolver, &exp, 1, 0.4, 1);
+        feed_arm(&evolver, &exp, 2, 0.8, 1);
+        assert!(evolver.running_experiment("coder").unwrap().is_none());
+        assert_eq!(
+            evolver.get_prompt("coder").unwrap().as_deref(),
+            Some("Candidate.")
+        );
+
+        let report = &evolver.experiment_reports(1).unwrap()[0];
+        assert_eq!(report.experiment.status, ExperimentStatus::Concluded);
+        assert_eq!(report.experiment.winner, Some(2));
+        let active = storage.get_active_prompt("coder").unwrap().unwrap();
+        assert_eq!(active.sample_count, MIN_ARM_SAMPLES);
+        assert!(
+            storage
+                .get_learning_log(10)
+                .unwrap()
+                .iter()
+                .any(|e| e.event_type == "prompt_experiment_concluded")
+        );
+    }
+
+    #[test]
+    fn test_equal_arms_do_not_promote() {
+        let (evolver, _) = make_evolver_with_storage();
+        evolver.apply_refinement("coder", "Control.").unwrap();
+        let exp = evolver
+            .propose_refinement("coder", "Candidate.", 0.5)
+            .unwrap();
+        feed_arm(&evolver, &exp, 1, 0.7, 200);
+        feed_arm(&evolver, &exp, 2, 0.7, 200);
+
+        assert!(evolver.running_experiment("coder").unwrap().is_some());
+        assert_eq!(
+            evolver.get_prompt("coder").unwrap().as_deref(),
+            Some("Control.")
+        );
+    }
+
+    #[test]
+    fn test_mixture_likelihood_ratio() {
+        let arm = |version, mean| ArmStats {
+            version,
+            samples: 50,
+            mean,
+            variance: 0.04,
+        };
+        assert!(mixture_likelihood_ratio(&arm(1, 0.6), &arm(2, 0.6)) < 1.0);
+        assert!(mixture_likelihood_ratio(&arm(1, 0.6), &arm(2, 0.65)) < 20.0);
+        assert!(mixture_likelihood_ratio(&arm(1, 0.5), &arm(2, 0.7)) > 20.0);
+        let single = ArmStats {
+            samples: 1,
+            ..arm(2, 0.9)
+        };
+        assert_eq!(mixture_likelihood_ratio(&arm(1, 0.1), &single), 0.0);
+    }
+
+    #[test]
+    fn test_manual_change_and_stop_end_experiment() {
+        let (evolver, _) = make_evolver_with_storage();
+        evolver.apply_refinement("coder", "Control.").unwrap();
+        evolver
+            .propose_refinement("coder", "Candidate.", 0.5)
+            .unwrap();
+        evolver.stop_experiment("coder").unwrap();
+        assert!(evolver.running_experiment("coder").unwrap().is_none());
+        assert!(evolver.stop_experiment("coder").is_err());
+
+        evolver
+            .propose_refinement("coder", "Candidate 2.", 0.5)
+            .unwrap();
+        evolver.rollback("coder", 2).unwrap();
+        let reports = evolver.experiment_reports(10).unwrap();
+        assert_eq!(reports.len(), 2);
+        assert!(
+            reports
+                .iter()
+                .all(|r| r.experiment.status == ExperimentStatus::Stopped)
+        );
+    }
 }
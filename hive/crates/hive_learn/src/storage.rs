@@ -1,11 +1,15 @@
-use rusqlite::{Connection, params};
+use hive_ai::routing::CallSample;
+use rusqlite::{Connection, OptionalExtension, params};
 use std::sync::Mutex;
 
 use crate::types::{
-    CodePattern, LearningLogEntry, OutcomeRecord, PromptVersion, RoutingHistoryEntry,
-    UserPreference,
+    ArmStats, CodePattern, ExperimentStatus, LearningLogEntry, OutcomeRecord, PromptExperiment,
+    PromptVersion, RoutingHistoryEntry, UserPreference,
 };
 
+/// Model call telemetry rows kept; older rows are pruned on insert.
+const MODEL_CALL_RETENTION: i64 = 20_000;
+
 /// SQLite-backed persistence for all learning data.
 pub struct LearningStorage {
     conn: Mutex<Connection>,
@@ -100,6 +104,47 @@ impl LearningStorage {
                 reversible INTEGER NOT NULL DEFAULT 0,
                 timestamp TEXT NOT NULL
             );
+
+            CREATE TABLE IF NOT EXISTS model_calls (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                model_id TEXT NOT NULL,
+                provider TEXT NOT NULL,
+                success INTEGER NOT NULL,
+                latency_ms INTEGER NOT NULL,
+                ttft_ms INTEGER,
+                output_tokens INTEGER NOT NULL DEFAULT 0,
+                timestamp TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS prompt_experiments (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                persona TEXT NOT NULL,
+                control_version INTEGER NOT NULL,
+                candidate_version INTEGER NOT NULL,
+                candidate_share REAL NOT NULL,
+                status TEXT NOT NULL,
+                winner INTEGER,
+                started_at TEXT NOT NULL,
+                ended_at TEXT
+            );
+
+            CREATE TABLE IF NOT EXISTS prompt_experiment_samples (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                experiment_id INTEGER NOT NULL,
+                version INTEGER NOT NULL,
+                quality_score REAL NOT NULL,
+                timestamp TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_experiment_samples
+                ON prompt_experiment_samples(experiment_id, version);
+
+            CREATE TABLE IF NOT EXISTS prompt_experiment_assignments (
+                experiment_id INTEGER NOT NULL,
+                subject TEXT NOT NULL,
+                version INTEGER NOT NULL,
+                served_at TEXT NOT NULL,
+                PRIMARY KEY (experiment_id, subject)
+            );
             ",
         )
         .map_err(|e| format!("Failed to initialize tables: {e}"))?;
@@ -527,6 +572,225 @@ impl LearningStorage {
         Ok(())
     }
 
+    /// Get one prompt version of a persona.
+    pub fn get_prompt_version(
+        &self,
+        persona: &str,
+        version: u32,
+    ) -> Result<Option<PromptVersion>, String> {
+        Ok(self
+            .get_prompt_versions(persona)?
+            .into_iter()
+            .find(|pv| pv.version == version))
+    }
+
+    /// Create a prompt experiment and return its ID.
+    pub fn create_experiment(&self, exp: &PromptExperiment) -> Result<i64, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| format!("Lock poisoned: {e}"))?;
+        conn.execute(
+            "INSERT INTO prompt_experiments
+                (persona, control_version, candidate_version, candidate_share, status, winner,
+                 started_at, ended_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                exp.persona,
+                exp.control_version,
+                exp.candidate_version,
+                exp.candidate_share,
+                exp.status.as_str(),
+                exp.winner,
+                exp.started_at,
+                exp.ended_at,
+            ],
+        )
+        .map_err(|e| format!("Failed to create experiment: {e}"))?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// The running experiment for a persona, if any.
+    pub fn running_experiment(&self, persona: &str) -> Result<Option<PromptExperiment>, String> {
+        Ok(self
+            .query_experiments(
+                "WHERE persona = ?1 AND status = 'running' ORDER BY id DESC LIMIT 1",
+                params![persona],
+            )?
+            .into_iter()
+            .next())
+    }
+
+    /// Most recent experiments first.
+    pub fn recent_experiments(&self, limit: usize) -> Result<Vec<PromptExperiment>, String> {
+        self.query_experiments("ORDER BY id DESC LIMIT ?1", params![limit as i64])
+    }
+
+    fn query_experiments(
+        &self,
+        clause: &str,
+        args: impl rusqlite::Params,
+    ) -> Result<Vec<PromptExperiment>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| format!("Lock poisoned: {e}"))?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT id, persona, control_version, candidate_version, candidate_share, status,
+                        winner, started_at, ended_at
+                 FROM prompt_experiments {clause}"
+            ))
+            .map_err(|e| format!("Failed to prepare query: {e}"))?;
+
+        let rows = stmt
+            .query_map(args, |row| {
+                let status: String = row.get(5)?;
+                Ok(PromptExperiment {
+                    id: row.get(0)?,
+                    persona: row.get(1)?,
+                    control_version: row.get(2)?,
+                    candidate_version: row.get(3)?,
+                    candidate_share: row.get(4)?,
+                    status: ExperimentStatus::parse(&status),
+                    winner: row.get(6)?,
+                    started_at: row.get(7)?,
+                    ended_at: row.get(8)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query experiments: {e}"))?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row.map_err(|e| format!("Failed to read experiment row: {e}"))?);
+        }
+        Ok(results)
+    }
+
+    /// Close an experiment with its final status and winner.
+    pub fn finish_experiment(
+        &self,
+        id: i64,
+        status: ExperimentStatus,
+        winner: Option<u32>,
+    ) -> Result<(), String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| format!("Lock poisoned: {e}"))?;
+        conn.execute(
+            "UPDATE prompt_experiments SET status = ?1, winner = ?2, ended_at = ?3 WHERE id = ?4",
+            params![status.as_str(), winner, chrono::Utc::now().to_rfc3339(), id],
+        )
+        .map_err(|e| format!("Failed to finish experiment: {e}"))?;
+        Ok(())
+    }
+
+    /// Attribute one quality score to an experiment arm.
+    pub fn record_experiment_sample(
+        &self,
+        experiment_id: i64,
+        version: u32,
+        quality_score: f64,
+    ) -> Result<(), String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| format!("Lock poisoned: {e}"))?;
+        conn.execute(
+            "INSERT INTO prompt_experiment_samples (experiment_id, version, quality_score, timestamp)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                experiment_id,
+                version,
+                quality_score,
+                chrono::Utc::now().to_rfc3339()
+            ],
+        )
+        .map_err(|e| format!("Failed to record experiment sample: {e}"))?;
+        Ok(())
+    }
+
+    /// Remember that `subject` was served `version` during an experiment.
+    /// The first assignment wins.
+    pub fn record_experiment_assignment(
+        &self,
+        experiment_id: i64,
+        subject: &str,
+        version: u32,
+    ) -> Result<(), String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| format!("Lock poisoned: {e}"))?;
+        conn.execute(
+            "INSERT OR IGNORE INTO prompt_experiment_assignments
+                (experiment_id, subject, version, served_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                experiment_id,
+                subject,
+                version,
+                chrono::Utc::now().to_rfc3339()
+            ],
+        )
+        .map_err(|e| format!("Failed to record experiment assignment: {e}"))?;
+        Ok(())
+    }
+
+    /// The version served to `subject` during an experiment, if any.
+    pub fn experiment_assignment(
+        &self,
+        experiment_id: i64,
+        subject: &str,
+    ) -> Result<Option<u32>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| format!("Lock poisoned: {e}"))?;
+        conn.query_row(
+            "SELECT version FROM prompt_experiment_assignments
+             WHERE experiment_id = ?1 AND subject = ?2",
+            params![experiment_id, subject],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read experiment assignment: {e}"))
+    }
+
+    /// Sample count, mean, and variance of one experiment arm.
+    pub fn experiment_arm_stats(
+        &self,
+        experiment_id: i64,
+        version: u32,
+    ) -> Result<ArmStats, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| format!("Lock poisoned: {e}"))?;
+        let (samples, mean, mean_sq): (i64, f64, f64) = conn
+            .query_row(
+                "SELECT COUNT(*), COALESCE(AVG(quality_score), 0.0),
+                        COALESCE(AVG(quality_score * quality_score), 0.0)
+                 FROM prompt_experiment_samples WHERE experiment_id = ?1 AND version = ?2",
+                params![experiment_id, version],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .map_err(|e| format!("Failed to compute arm stats: {e}"))?;
+        let n = samples as f64;
+        let variance = if samples > 1 {
+            ((mean_sq - mean * mean) * n / (n - 1.0)).max(0.0)
+        } else {
+            0.0
+        };
+        Ok(ArmStats {
+            version,
+            samples: samples as u32,
+            mean,
+            variance,
+        })
+    }
+
     /// Save a code pattern.
     pub fn save_pattern(&self, pattern: &CodePattern) -> Result<i64, String> {
         let conn = self
@@ -1136,6 +1400,95 @@ impl LearningStorage {
             .map_err(|e| format!("Failed to count outcomes: {e}"))?;
         Ok(count as u64)
     }
+
+    /// Count outcomes recorded for a model over the last N days.
+    pub fn model_outcome_count(&self, model_id: &str, days: u32) -> Result<u64, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| format!("Lock poisoned: {e}"))?;
+        let cutoff = chrono::Utc::now()
+            .checked_sub_signed(chrono::Duration::days(i64::from(days)))
+            .unwrap_or_else(chrono::Utc::now)
+            .to_rfc3339();
+
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM learning_outcomes WHERE model_id = ?1 AND timestamp >= ?2",
+                params![model_id, cutoff],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to count model outcomes: {e}"))?;
+        Ok(count as u64)
+    }
+
+    /// Record one model call's latency/throughput telemetry.
+    pub fn record_model_call(&self, sample: &CallSample) -> Result<i64, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| format!("Lock poisoned: {e}"))?;
+        conn.execute(
+            "INSERT INTO model_calls
+                (model_id, provider, success, latency_ms, ttft_ms, output_tokens, timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                sample.model_id,
+                sample.provider,
+                sample.success,
+                sample.latency_ms as i64,
+                sample.ttft_ms.map(|t| t as i64),
+                sample.output_tokens,
+                sample.timestamp.to_rfc3339(),
+            ],
+        )
+        .map_err(|e| format!("Failed to record model call: {e}"))?;
+        let id = conn.last_insert_rowid();
+        conn.execute(
+            "DELETE FROM model_calls WHERE id <= ?1",
+            params![id - MODEL_CALL_RETENTION],
+        )
+        .map_err(|e| format!("Failed to prune model calls: {e}"))?;
+        Ok(id)
+    }
+
+    /// The most recent model calls, oldest first.
+    pub fn recent_model_calls(&self, limit: usize) -> Result<Vec<CallSample>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| format!("Lock poisoned: {e}"))?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT model_id, provider, success, latency_ms, ttft_ms, output_tokens, timestamp
+                 FROM (SELECT * FROM model_calls ORDER BY id DESC LIMIT ?1)
+                 ORDER BY id ASC",
+            )
+            .map_err(|e| format!("Failed to prepare query: {e}"))?;
+
+        let rows = stmt
+            .query_map(params![limit as i64], |row| {
+                let timestamp: String = row.get(6)?;
+                Ok(CallSample {
+                    model_id: row.get(0)?,
+                    provider: row.get(1)?,
+                    success: row.get(2)?,
+                    latency_ms: row.get::<_, i64>(3)? as u64,
+                    ttft_ms: row.get::<_, Option<i64>>(4)?.map(|t| t as u64),
+                    output_tokens: row.get(5)?,
+                    timestamp: chrono::DateTime::parse_from_rfc3339(&timestamp)
+                        .map(|t| t.with_timezone(&chrono::Utc))
+                        .unwrap_or_else(|_| chrono::Utc::now()),
+                })
+            })
+            .map_err(|e| format!("Failed to query model calls: {e}"))?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row.map_err(|e| format!("Failed to read model call row: {e}"))?);
+        }
+        Ok(results)
+    }
 }
 
 #[cfg(test)]
@@ -1670,4 +2023,45 @@ mod tests {
         // Zero outcome count
         assert_eq!(storage.outcome_count().unwrap(), 0);
     }
+
+    #[test]
+    fn test_model_calls_round_trip_oldest_first() {
+        let storage = LearningStorage::in_memory().unwrap();
+        for (i, model) in ["a", "b", "c"].iter().enumerate() {
+            storage
+                .record_model_call(&CallSample {
+                    model_id: model.to_string(),
+                    provider: "openai".into(),
+                    success: i != 1,
+                    latency_ms: 1_000 + i as u64,
+                    ttft_ms: (i == 0).then_some(150),
+                    output_tokens: 42,
+                    timestamp: chrono::Utc::now(),
+                })
+                .unwrap();
+        }
+
+        let recent = storage.recent_model_calls(2).unwrap();
+        let models: Vec<_> = recent.iter().map(|c| c.model_id.as_str()).collect();
+        assert_eq!(models, ["b", "c"]);
+        assert!(!recent[0].success);
+        assert_eq!(recent[1].latency_ms, 1_002);
+        assert_eq!(recent[1].ttft_ms, None);
+
+        let all = storage.recent_model_calls(10).unwrap();
+        assert_eq!(all[0].ttft_ms, Some(150));
+    }
+
+    #[test]
+    fn test_model_outcome_count() {
+        let storage = LearningStorage::in_memory().unwrap();
+        storage
+            .record_outcome(&make_outcome("gpt-4o", 0.9, Outcome::Accepted))
+            .unwrap();
+        storage
+            .record_outcome(&make_outcome("gpt-4o", 0.7, Outcome::Accepted))
+            .unwrap();
+        assert_eq!(storage.model_outcome_count("gpt-4o", 30).unwrap(), 2);
+        assert_eq!(storage.model_outcome_count("other", 30).unwrap(), 0);
+    }
 }
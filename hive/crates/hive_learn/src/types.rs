@@ -94,6 +94,79 @@ pub struct PromptRefinement {
     pub reason: String,
 }
 
+/// Lifecycle of a prompt A/B experiment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExperimentStatus {
+    /// Both arms are being served and scored.
+    Running,
+    /// A significant winner was found and activated.
+    Concluded,
+    /// The sample cap was reached without a significant difference; the
+    /// control stays active.
+    Inconclusive,
+    /// Ended by the user or superseded by a manual prompt change.
+    Stopped,
+}
+
+impl ExperimentStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Concluded => "concluded",
+            Self::Inconclusive => "inconclusive",
+            Self::Stopped => "stopped",
+        }
+    }
+
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "running" => Self::Running,
+            "concluded" => Self::Concluded,
+            "inconclusive" => Self::Inconclusive,
+            _ => Self::Stopped,
+        }
+    }
+}
+
+/// An A/B experiment between the active prompt version of a persona (the
+/// control) and a candidate version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptExperiment {
+    pub id: i64,
+    pub persona: String,
+    pub control_version: u32,
+    pub candidate_version: u32,
+    /// Fraction of subjects (conversations) served the candidate, 0.0–1.0.
+    pub candidate_share: f64,
+    pub status: ExperimentStatus,
+    /// Version that won, once concluded.
+    pub winner: Option<u32>,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+}
+
+/// Quality statistics for one arm of an experiment.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ArmStats {
+    pub version: u32,
+    pub samples: u32,
+    pub mean: f64,
+    /// Unbiased sample variance of the quality scores.
+    pub variance: f64,
+}
+
+/// An experiment together with its current per-arm figures.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentReport {
+    pub experiment: PromptExperiment,
+    pub control: ArmStats,
+    pub candidate: ArmStats,
+    /// Mixture sequential probability ratio; the experiment is significant
+    /// once this reaches `1 / alpha`.
+    pub likelihood_ratio: f64,
+}
+
 /// A reusable code pattern.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodePattern {
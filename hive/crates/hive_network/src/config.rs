@@ -16,6 +16,11 @@ pub struct NetworkConfig {
     /// Whether LAN discovery (UDP broadcast) is enabled.
     pub discovery_enabled: bool,
 
+    /// Advertise and browse for peers over mDNS (`_hive._tcp`) instead of,
+    /// or alongside, UDP broadcast.
+    #[serde(default)]
+    pub mdns_enabled: bool,
+
     /// UDP port used for LAN discovery announcements.
     pub discovery_port: u16,
 
@@ -32,6 +37,21 @@ pub struct NetworkConfig {
 
     /// List of bootstrap peer addresses to connect to on startup.
     pub known_peers: Vec<String>,
+
+    /// Admit authenticated peers that are not on the trust list. Off by
+    /// default: unknown peers must pair first.
+    #[serde(default)]
+    pub allow_untrusted_peers: bool,
+
+    /// Run as a relay/rendezvous node: accept peer registrations, answer
+    /// lookups and splice relayed connections between registered peers.
+    #[serde(default)]
+    pub relay_mode: bool,
+
+    /// Relays to register with, as `peer_id@host:port` (pinning the relay's
+    /// key) or `host:port` for a relay already on the trust list.
+    #[serde(default)]
+    pub relays: Vec<String>,
 }
 
 impl Default for NetworkConfig {
@@ -39,11 +59,15 @@ impl Default for NetworkConfig {
         Self {
             listen_addr: "0.0.0.0:9470".parse().expect("valid default listen address"),
             discovery_enabled: true,
+            mdns_enabled: false,
             discovery_port: 9471,
             max_peers: 32,
             heartbeat_interval: Duration::from_secs(30),
             connection_timeout: Duration::from_secs(10),
             known_peers: Vec::new(),
+            allow_untrusted_peers: false,
+            relay_mode: false,
+            relays: Vec::new(),
         }
     }
 }
@@ -128,6 +152,10 @@ mod tests {
         assert_eq!(config.max_peers, 32);
         assert_eq!(config.heartbeat_interval, Duration::from_secs(30));
         assert!(config.known_peers.is_empty());
+        assert!(!config.allow_untrusted_peers);
+        assert!(!config.mdns_enabled);
+        assert!(!config.relay_mode);
+        assert!(config.relays.is_empty());
     }
 
     #[test]
@@ -140,6 +168,16 @@ mod tests {
         assert_eq!(deserialized.discovery_port, config.discovery_port);
     }
 
+    #[test]
+    fn test_config_without_trust_fields_rejects_untrusted() {
+        let json = r#"{"listen_addr":"0.0.0.0:9470","discovery_enabled":true,"discovery_port":9471,
+            "max_peers":32,"heartbeat_interval":30,"connection_timeout":10,"known_peers":[]}"#;
+        let config: NetworkConfig = serde_json::from_str(json).unwrap();
+        assert!(!config.allow_untrusted_peers);
+        assert!(!config.relay_mode);
+        assert!(config.relays.is_empty());
+    }
+
     #[test]
     fn test_config_save_load() {
         let dir = std::env::temp_dir().join("hive_network_test_config");
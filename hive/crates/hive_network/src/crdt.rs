@@ -0,0 +1,852 @@
+//! Replicated documents — conflict-free state shared between peers.
+//!
+//! A [`CrdtDoc`] is a map from string keys to JSON values that every peer
+//! edits locally, online or offline. Each edit becomes an [`Op`] stamped with
+//! the author's replica id, a per-replica sequence number and a Lamport
+//! counter. Peers exchange ops they have not seen, as decided by comparing
+//! [`VectorClock`]s, and apply them in any order:
+//!
+//! - the value of a key is the one written by the op with the highest
+//!   [`Stamp`] (Lamport counter, then replica id), so concurrent edits resolve
+//!   the same way everywhere;
+//! - deletes are ops too (tombstones), so they replicate like writes;
+//! - wall-clock time is never consulted, so clock skew cannot reorder edits.
+//!
+//! Two documents that have applied the same set of ops hold the same entries,
+//! regardless of delivery order or duplication.
+//!
+//! Ops are signed by the node key their replica id names, so a peer can relay
+//! another replica's edits but cannot forge them. Once every peer has
+//! acknowledged an op it can be [compacted](CrdtDoc::compact) out of the log;
+//! peers whose clock predates the compacted log catch up from a
+//! [`Snapshot`] of the current entries instead.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::identity::{NodeKeypair, PeerId, from_hex, to_hex};
+
+// ---------------------------------------------------------------------------
+// Vector clock
+// ---------------------------------------------------------------------------
+
+/// How two vector clocks relate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockOrdering {
+    Equal,
+    /// The left clock has seen a strict subset of the right's ops.
+    Before,
+    /// The left clock has seen a strict superset of the right's ops.
+    After,
+    /// Each side has seen ops the other has not.
+    Concurrent,
+}
+
+/// Highest contiguous sequence number seen from each replica.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorClock(BTreeMap<String, u64>);
+
+impl VectorClock {
+    /// Create an empty clock.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sequence number seen from `replica` (0 if none).
+    pub fn get(&self, replica: &str) -> u64 {
+        self.0.get(replica).copied().unwrap_or(0)
+    }
+
+    /// Record that ops up to `seq` from `replica` have been seen.
+    pub fn observe(&mut self, replica: &str, seq: u64) {
+        let entry = self.0.entry(replica.to_string()).or_insert(0);
+        *entry = (*entry).max(seq);
+    }
+
+    /// Component-wise minimum with `other`: the ops both clocks have seen.
+    pub fn meet(&self, other: &VectorClock) -> VectorClock {
+        VectorClock(
+            self.0
+                .iter()
+                .map(|(replica, &seq)| (replica.clone(), seq.min(other.get(replica))))
+                .filter(|&(_, seq)| seq > 0)
+                .collect(),
+        )
+    }
+
+    /// Take the component-wise maximum with `other`.
+    pub fn merge(&mut self, other: &VectorClock) {
+        for (replica, &seq) in &other.0 {
+            self.observe(replica, seq);
+        }
+    }
+
+    /// Whether this clock has seen every op `other` has.
+    pub fn dominates(&self, other: &VectorClock) -> bool {
+        other.0.iter().all(|(replica, &seq)| self.get(replica) >= seq)
+    }
+
+    /// Compare two clocks.
+    pub fn compare(&self, other: &VectorClock) -> ClockOrdering {
+        match (self.dominates(other), other.dominates(self)) {
+            (true, true) => ClockOrdering::Equal,
+            (true, false) => ClockOrdering::After,
+            (false, true) => ClockOrdering::Before,
+            (false, false) => ClockOrdering::Concurrent,
+        }
+    }
+
+    /// Replicas and their sequence numbers.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.0.iter().map(|(r, &s)| (r.as_str(), s))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Ops
+// ---------------------------------------------------------------------------
+
+/// Total order on writes: Lamport counter, then replica id.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Stamp {
+    pub counter: u64,
+    pub replica: String,
+}
+
+/// A single edit to a document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Op {
+    /// Replica that made the edit.
+    pub replica: String,
+    /// Position in that replica's edit sequence, starting at 1.
+    pub seq: u64,
+    /// Lamport counter at the time of the edit.
+    pub counter: u64,
+    pub key: String,
+    /// New value, or `None` to delete the key.
+    pub value: Option<serde_json::Value>,
+    /// Hex Ed25519 signature by the key `replica` names, over the op and the
+    /// id of its document.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+impl Op {
+    /// The stamp used to order this op against concurrent writes.
+    pub fn stamp(&self) -> Stamp {
+        Stamp {
+            counter: self.counter,
+            replica: self.replica.clone(),
+        }
+    }
+
+    /// Canonical bytes the signature covers.
+    fn signing_bytes(&self, doc_id: &str) -> Vec<u8> {
+        serde_json::to_vec(&(
+            doc_id,
+            &self.replica,
+            self.seq,
+            self.counter,
+            &self.key,
+            &self.value,
+        ))
+        .unwrap_or_default()
+    }
+
+    /// Sign the op as part of document `doc_id`.
+    pub fn sign(&mut self, doc_id: &str, keypair: &NodeKeypair) {
+        self.signature = Some(to_hex(&keypair.sign(&self.signing_bytes(doc_id))));
+    }
+
+    /// Whether the op carries a valid signature for document `doc_id` from
+    /// the key its replica id names.
+    pub fn verify(&self, doc_id: &str) -> bool {
+        let Some(signature) = self.signature.as_deref().and_then(from_hex) else {
+            return false;
+        };
+        PeerId::from_string(self.replica.as_str()).verify(&self.signing_bytes(doc_id), &signature)
+    }
+}
+
+/// Current state of one key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    value: Option<serde_json::Value>,
+    stamp: Stamp,
+    /// Earliest write that gave the key a value; orders collections causally.
+    first: Option<Stamp>,
+    /// Sequence number and signature of the winning write, so it can be
+    /// sent in a snapshot after the op itself is compacted away.
+    #[serde(default)]
+    seq: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+}
+
+impl Entry {
+    fn empty() -> Self {
+        Self {
+            value: None,
+            stamp: Stamp {
+                counter: 0,
+                replica: String::new(),
+            },
+            first: None,
+            seq: 0,
+            signature: None,
+        }
+    }
+
+    /// The write that gave the entry its current state.
+    fn winner(&self, key: &str) -> Op {
+        Op {
+            replica: self.stamp.replica.clone(),
+            seq: self.seq,
+            counter: self.stamp.counter,
+            key: key.to_string(),
+            value: self.value.clone(),
+            signature: self.signature.clone(),
+        }
+    }
+}
+
+/// The entries of a document at `clock`, for peers whose clock predates the
+/// compacted op log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Ops reflected in the entries.
+    pub clock: VectorClock,
+    /// Per key, the signed write of its current state and the stamp of the
+    /// first write that gave it a value.
+    pub entries: Vec<(Op, Option<Stamp>)>,
+}
+
+// ---------------------------------------------------------------------------
+// Document
+// ---------------------------------------------------------------------------
+
+/// A replicated last-writer-wins map.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrdtDoc {
+    id: String,
+    replica: String,
+    clock: VectorClock,
+    counter: u64,
+    entries: BTreeMap<String, Entry>,
+    /// Ops applied above `floor`, for sending deltas to peers that lack them.
+    log: Vec<Op>,
+    /// Ops received ahead of a gap in their replica's sequence.
+    #[serde(default)]
+    pending: Vec<Op>,
+    /// Ops at or below this clock have been compacted out of `log`.
+    #[serde(default)]
+    floor: VectorClock,
+    /// Key local ops are signed with.
+    #[serde(skip)]
+    signer: Option<NodeKeypair>,
+}
+
+impl CrdtDoc {
+    /// Create an empty document edited as `replica`.
+    pub fn new(id: impl Into<String>, replica: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            replica: replica.into(),
+            clock: VectorClock::new(),
+            counter: 0,
+            entries: BTreeMap::new(),
+            log: Vec::new(),
+            pending: Vec::new(),
+            floor: VectorClock::new(),
+            signer: None,
+        }
+    }
+
+    /// Create an empty document edited as the node holding `signer`, whose
+    /// local ops are signed with it.
+    pub fn signed(id: impl Into<String>, signer: NodeKeypair) -> Self {
+        let mut doc = Self::new(id, signer.peer_id().to_string());
+        doc.signer = Some(signer);
+        doc
+    }
+
+    /// Sign local ops with `signer`, which must be the key the replica id
+    /// names. Unsigned local ops already recorded, such as those of a
+    /// document loaded from disk, are signed too.
+    pub fn set_signer(&mut self, signer: NodeKeypair) {
+        debug_assert_eq!(signer.peer_id().as_str(), self.replica);
+        for op in self.log.iter_mut().chain(self.pending.iter_mut()) {
+            if op.replica == self.replica && op.signature.is_none() {
+                op.sign(&self.id, &signer);
+            }
+        }
+        for (key, entry) in &mut self.entries {
+            if entry.stamp.replica == self.replica && entry.signature.is_none() {
+                let mut op = entry.winner(key);
+                op.sign(&self.id, &signer);
+                entry.signature = op.signature;
+            }
+        }
+        self.signer = Some(signer);
+    }
+
+    /// The document id shared by all replicas.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The replica local edits are attributed to.
+    pub fn replica(&self) -> &str {
+        &self.replica
+    }
+
+    /// Ops seen so far, per replica.
+    pub fn clock(&self) -> &VectorClock {
+        &self.clock
+    }
+
+    /// Set `key` to `value`.
+    pub fn set(&mut self, key: impl Into<String>, value: serde_json::Value) -> Op {
+        self.local_op(key.into(), Some(value))
+    }
+
+    /// Delete `key`. Deleting a missing key still records a tombstone.
+    pub fn delete(&mut self, key: impl Into<String>) -> Op {
+        self.local_op(key.into(), None)
+    }
+
+    fn local_op(&mut self, key: String, value: Option<serde_json::Value>) -> Op {
+        let mut op = Op {
+            replica: self.replica.clone(),
+            seq: self.clock.get(&self.replica) + 1,
+            counter: self.counter + 1,
+            key,
+            value,
+            signature: None,
+        };
+        if let Some(signer) = &self.signer {
+            op.sign(&self.id, signer);
+        }
+        self.apply(op.clone());
+        op
+    }
+
+    /// Current value of `key`.
+    pub fn get(&self, key: &str) -> Option<&serde_json::Value> {
+        self.entries.get(key).and_then(|e| e.value.as_ref())
+    }
+
+    /// All live entries, in key order.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &serde_json::Value)> {
+        self.entries
+            .iter()
+            .filter_map(|(k, e)| e.value.as_ref().map(|v| (k.as_str(), v)))
+    }
+
+    /// Live entries whose key starts with `prefix`, in key order.
+    pub fn scan<'a>(
+        &'a self,
+        prefix: &'a str,
+    ) -> impl Iterator<Item = (&'a str, &'a serde_json::Value)> + 'a {
+        self.entries
+            .range(prefix.to_string()..)
+            .take_while(move |(k, _)| k.starts_with(prefix))
+            .filter_map(|(k, e)| e.value.as_ref().map(|v| (k.as_str(), v)))
+    }
+
+    /// Distinct key segments directly under `prefix` (which should end in
+    /// `/`), ordered by when each was first written. Concurrent additions
+    /// are ordered by replica id, identically on every peer.
+    pub fn children(&self, prefix: &str) -> Vec<String> {
+        let mut firsts: HashMap<&str, &Stamp> = HashMap::new();
+        for (key, entry) in self
+            .entries
+            .range(prefix.to_string()..)
+            .take_while(|(k, _)| k.starts_with(prefix))
+        {
+            let (Some(first), Some(_)) = (&entry.first, &entry.value) else {
+                continue;
+            };
+            let rest = &key[prefix.len()..];
+            let child = rest.split('/').next().unwrap_or(rest);
+            firsts
+                .entry(child)
+                .and_modify(|s| *s = (*s).min(first))
+                .or_insert(first);
+        }
+
+        let mut children: Vec<(&str, &Stamp)> = firsts.into_iter().collect();
+        children.sort_by(|a, b| a.1.cmp(b.1).then_with(|| a.0.cmp(b.0)));
+        children.into_iter().map(|(c, _)| c.to_string()).collect()
+    }
+
+    /// Apply an op from any replica. Returns `true` if it was new.
+    ///
+    /// Duplicates are ignored; an op that arrives ahead of a gap in its
+    /// replica's sequence is held until the missing ops arrive.
+    pub fn apply(&mut self, op: Op) -> bool {
+        let seen = self.clock.get(&op.replica);
+        if op.seq <= seen || self.pending.contains(&op) {
+            return false;
+        }
+        if op.seq > seen + 1 {
+            self.pending.push(op);
+            return false;
+        }
+
+        self.integrate(op);
+        self.drain_pending();
+        true
+    }
+
+    /// Apply an op received from a peer. Ops not signed by the key their
+    /// replica id names are rejected, so peers cannot forge each other's
+    /// edits.
+    pub fn apply_remote(&mut self, op: Op) -> bool {
+        op.verify(&self.id) && self.apply(op)
+    }
+
+    /// Apply several ops, returning how many were new.
+    pub fn apply_all(&mut self, ops: impl IntoIterator<Item = Op>) -> usize {
+        ops.into_iter().filter(|op| self.apply(op.clone())).count()
+    }
+
+    /// Apply several ops from a peer, returning how many were new.
+    pub fn apply_all_remote(&mut self, ops: impl IntoIterator<Item = Op>) -> usize {
+        ops.into_iter()
+            .filter(|op| self.apply_remote(op.clone()))
+            .count()
+    }
+
+    /// Integrate pending ops whose gap has closed and drop those now seen.
+    fn drain_pending(&mut self) {
+        while let Some(i) = self
+            .pending
+            .iter()
+            .position(|p| p.seq == self.clock.get(&p.replica) + 1)
+        {
+            let next = self.pending.swap_remove(i);
+            self.integrate(next);
+        }
+        self.pending.retain(|p| p.seq > self.clock.get(&p.replica));
+    }
+
+    fn integrate(&mut self, op: Op) {
+        self.clock.observe(&op.replica, op.seq);
+        let first = op.value.is_some().then(|| op.stamp());
+        self.merge_entry(op.clone(), first);
+        self.log.push(op);
+    }
+
+    /// Merge one write into its key's entry. Returns `true` if the entry
+    /// changed.
+    fn merge_entry(&mut self, op: Op, first: Option<Stamp>) -> bool {
+        self.counter = self.counter.max(op.counter);
+        let stamp = op.stamp();
+        let entry = self.entries.entry(op.key).or_insert_with(Entry::empty);
+        let mut changed = false;
+        if let Some(first) = first
+            && entry.first.as_ref().is_none_or(|f| &first < f)
+        {
+            entry.first = Some(first);
+            changed = true;
+        }
+        if stamp.cmp(&entry.stamp) == Ordering::Greater {
+            entry.value = op.value;
+            entry.stamp = stamp;
+            entry.seq = op.seq;
+            entry.signature = op.signature;
+            changed = true;
+        }
+        changed
+    }
+
+    /// Whether the log still holds every op a peer with `clock` lacks. If
+    /// not, the peer needs a [`snapshot`](Self::snapshot).
+    pub fn has_ops_since(&self, clock: &VectorClock) -> bool {
+        clock.dominates(&self.floor)
+    }
+
+    /// Ops this document has that a peer with `clock` has not seen, as far
+    /// as the log still holds them.
+    pub fn delta_since(&self, clock: &VectorClock) -> Vec<Op> {
+        self.log
+            .iter()
+            .filter(|op| op.seq > clock.get(&op.replica))
+            .cloned()
+            .collect()
+    }
+
+    /// The current entries, for a peer the compacted log cannot catch up.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            clock: self.clock.clone(),
+            entries: self
+                .entries
+                .iter()
+                .map(|(key, entry)| (entry.winner(key), entry.first.clone()))
+                .collect(),
+        }
+    }
+
+    /// Merge a peer's snapshot. Entries not signed by the replica that wrote
+    /// them are skipped; the clock is taken on the sending peer's word.
+    /// Returns `true` if any entry changed.
+    pub fn apply_snapshot(&mut self, snapshot: Snapshot) -> bool {
+        let mut changed = false;
+        for (op, first) in snapshot.entries {
+            if op.verify(&self.id) {
+                changed |= self.merge_entry(op, first);
+            }
+        }
+        // The log lacks the ops the snapshot covered beyond our own clock.
+        for (replica, seq) in snapshot.clock.iter() {
+            if seq > self.clock.get(replica) {
+                self.floor.observe(replica, seq);
+            }
+        }
+        self.clock.merge(&snapshot.clock);
+        self.drain_pending();
+        changed
+    }
+
+    /// Drop ops every peer has acknowledged from the log, and pending ops
+    /// peers can send again. `acked` is the meet of the peers' clocks.
+    /// Returns how many ops were dropped.
+    pub fn compact(&mut self, acked: &VectorClock) -> usize {
+        let stable = acked.meet(&self.clock);
+        let before = self.log.len() + self.pending.len();
+        self.log.retain(|op| op.seq > stable.get(&op.replica));
+        self.pending.retain(|op| op.seq > acked.get(&op.replica));
+        self.floor.merge(&stable);
+        before - self.log.len() - self.pending.len()
+    }
+
+    /// Pull every op from `other` that this document lacks.
+    pub fn merge(&mut self, other: &CrdtDoc) -> usize {
+        self.apply_all(other.delta_since(&self.clock))
+    }
+
+    /// Whether two documents hold the same live entries.
+    pub fn same_state(&self, other: &CrdtDoc) -> bool {
+        self.entries().eq(other.entries())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Keys
+// ---------------------------------------------------------------------------
+
+/// Escape a key segment so it can contain `/`.
+pub fn escape_segment(segment: &str) -> String {
+    segment.replace('%', "%25").replace('/', "%2F")
+}
+
+/// Reverse [`escape_segment`].
+pub fn unescape_segment(segment: &str) -> String {
+    segment.replace("%2F", "/").replace("%25", "%")
+}
+
+/// Join escaped segments into a key.
+pub fn key(segments: &[&str]) -> String {
+    segments
+        .iter()
+        .map(|s| escape_segment(s))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_vector_clock_ordering() {
+        let mut a = VectorClock::new();
+        let mut b = VectorClock::new();
+        assert_eq!(a.compare(&b), ClockOrdering::Equal);
+
+        a.observe("a", 2);
+        assert_eq!(a.compare(&b), ClockOrdering::After);
+        assert_eq!(b.compare(&a), ClockOrdering::Before);
+
+        b.observe("b", 1);
+        assert_eq!(a.compare(&b), ClockOrdering::Concurrent);
+
+        a.merge(&b);
+        assert!(a.dominates(&b));
+        assert_eq!(a.get("a"), 2);
+        assert_eq!(a.get("b"), 1);
+    }
+
+    #[test]
+    fn test_concurrent_writes_resolve_identically() {
+        let mut a = CrdtDoc::new("doc", "a");
+        let mut b = CrdtDoc::new("doc", "b");
+
+        a.set("title", json!("from a"));
+        b.set("title", json!("from b"));
+        b.set("owner", json!("bob"));
+
+        a.merge(&b);
+        b.merge(&a);
+        assert!(a.same_state(&b));
+        // Equal Lamport counters fall back to replica id.
+        assert_eq!(a.get("title"), Some(&json!("from b")));
+        assert_eq!(a.get("owner"), Some(&json!("bob")));
+    }
+
+    #[test]
+    fn test_causal_writes_win_over_earlier_ones() {
+        let mut a = CrdtDoc::new("doc", "z-replica");
+        let mut b = CrdtDoc::new("doc", "a-replica");
+
+        a.set("status", json!("draft"));
+        b.merge(&a);
+        // b has seen a's write, so its edit is later no matter the ids.
+        b.set("status", json!("active"));
+        a.merge(&b);
+        assert_eq!(a.get("status"), Some(&json!("active")));
+    }
+
+    #[test]
+    fn test_deletes_replicate_and_duplicates_are_ignored() {
+        let mut a = CrdtDoc::new("doc", "a");
+        let mut b = CrdtDoc::new("doc", "b");
+        a.set("x", json!(1));
+        b.merge(&a);
+
+        let del = b.delete("x");
+        assert!(a.apply(del.clone()));
+        assert!(!a.apply(del));
+        assert_eq!(a.get("x"), None);
+        assert!(a.same_state(&b));
+    }
+
+    #[test]
+    fn test_out_of_order_ops_wait_for_gaps() {
+        let mut a = CrdtDoc::new("doc", "a");
+        let first = a.set("k", json!(1));
+        let second = a.set("k", json!(2));
+
+        let mut b = CrdtDoc::new("doc", "b");
+        assert!(!b.apply(second));
+        assert_eq!(b.get("k"), None);
+        assert_eq!(b.clock().get("a"), 0);
+
+        assert!(b.apply(first));
+        assert_eq!(b.get("k"), Some(&json!(2)));
+        assert_eq!(b.clock().get("a"), 2);
+    }
+
+    #[test]
+    fn test_children_follow_causal_order() {
+        let mut a = CrdtDoc::new("doc", "b");
+        a.set(key(&["messages", "m-2", "text"]), json!("first"));
+        a.set(key(&["messages", "m-1", "text"]), json!("second"));
+        a.set(key(&["messages", "m-2", "edited"]), json!(true));
+        a.set(key(&["pinned", "src/lib.rs"]), json!(true));
+
+        assert_eq!(a.children("messages/"), vec!["m-2", "m-1"]);
+        let pinned = a.children("pinned/");
+        assert_eq!(unescape_segment(&pinned[0]), "src/lib.rs");
+        assert_eq!(a.scan("messages/m-2/").count(), 2);
+    }
+
+    #[test]
+    fn test_doc_serde_roundtrip_keeps_history() {
+        let mut a = CrdtDoc::new("doc", "a");
+        a.set("k", json!("v"));
+        let restored: CrdtDoc = serde_json::from_str(&serde_json::to_string(&a).unwrap()).unwrap();
+        assert!(restored.same_state(&a));
+        assert_eq!(restored.delta_since(&VectorClock::new()).len(), 1);
+    }
+
+    #[test]
+    fn test_remote_ops_must_be_signed_by_their_replica() {
+        let mut a = CrdtDoc::signed("doc", NodeKeypair::generate());
+        let mut b = CrdtDoc::signed("doc", NodeKeypair::generate());
+        let op = a.set("k", json!(1));
+        assert!(op.verify("doc"));
+        assert!(!op.verify("other-doc"));
+
+        // A relayed op keeps its author's signature.
+        assert!(b.apply_remote(op.clone()));
+        assert_eq!(b.get("k"), Some(&json!(1)));
+
+        // Tampering with the value or claiming another replica's id fails.
+        let mut tampered = a.set("k", json!(2));
+        tampered.value = Some(json!("evil"));
+        assert!(!b.apply_remote(tampered));
+        let mut forged = b.set("k", json!(3));
+        forged.replica = a.replica().to_string();
+        forged.seq = 2;
+        let mut c = CrdtDoc::signed("doc", NodeKeypair::generate());
+        assert!(!c.apply_remote(forged));
+        assert!(!c.apply_remote(Op {
+            signature: None,
+            ..op
+        }));
+    }
+
+    #[test]
+    fn test_set_signer_signs_existing_local_ops() {
+        let keypair = NodeKeypair::generate();
+        let mut a = CrdtDoc::new("doc", keypair.peer_id().to_string());
+        a.set("k", json!(1));
+        a.set_signer(keypair);
+        let mut b = CrdtDoc::new("doc", "b");
+        assert_eq!(b.apply_all_remote(a.delta_since(b.clock())), 1);
+        assert!(b.same_state(&a));
+        let mut c = CrdtDoc::new("doc", "c");
+        assert!(c.apply_snapshot(a.snapshot()));
+        assert!(c.same_state(&a));
+    }
+
+    #[test]
+    fn test_compaction_falls_back_to_snapshots() {
+        let mut a = CrdtDoc::signed("doc", NodeKeypair::generate());
+        let mut b = CrdtDoc::signed("doc", NodeKeypair::generate());
+        a.set("x", json!(1));
+        a.set("y", json!(2));
+        b.apply_all_remote(a.delta_since(b.clock()));
+
+        // Both peers have everything, so a drops its log.
+        assert_eq!(a.compact(b.clock()), 2);
+        assert!(a.delta_since(&VectorClock::new()).is_empty());
+        assert!(a.has_ops_since(b.clock()));
+
+        // A newcomer predates the compacted log and needs a snapshot.
+        let mut c = CrdtDoc::signed("doc", NodeKeypair::generate());
+        assert!(!a.has_ops_since(c.clock()));
+        a.delete("x");
+        c.apply_all_remote(a.delta_since(c.clock()));
+        assert!(c.apply_snapshot(a.snapshot()));
+        assert!(c.same_state(&a));
+        assert_eq!(c.clock(), a.clock());
+        assert_eq!(c.get("y"), Some(&json!(2)));
+
+        // c never held a's delete as an op, so b catches up from c's snapshot.
+        c.set("z", json!(3));
+        assert!(!c.has_ops_since(b.clock()));
+        b.apply_all_remote(c.delta_since(b.clock()));
+        assert!(b.apply_snapshot(c.snapshot()));
+        assert!(b.same_state(&c));
+        assert_eq!(b.clock(), c.clock());
+    }
+
+    #[test]
+    fn test_forged_snapshot_entries_are_skipped() {
+        let mut a = CrdtDoc::signed("doc", NodeKeypair::generate());
+        a.set("k", json!("real"));
+        let mut snapshot = a.snapshot();
+        snapshot.entries[0].0.value = Some(json!("forged"));
+        let mut b = CrdtDoc::signed("doc", NodeKeypair::generate());
+        assert!(!b.apply_snapshot(snapshot));
+        assert_eq!(b.get("k"), None);
+    }
+
+    // -----------------------------------------------------------------------
+    // Convergence properties
+    // -----------------------------------------------------------------------
+
+    /// A step in a random multi-replica session.
+    #[derive(Debug, Clone)]
+    enum Step {
+        Set { replica: usize, key: u8, value: u8 },
+        Delete { replica: usize, key: u8 },
+        /// One-way delta sync from one replica to another.
+        Sync { from: usize, to: usize },
+    }
+
+    fn step(replicas: usize) -> impl Strategy<Value = Step> {
+        prop_oneof![
+            (0..replicas, 0u8..6, any::<u8>())
+                .prop_map(|(replica, key, value)| Step::Set { replica, key, value }),
+            (0..replicas, 0u8..6).prop_map(|(replica, key)| Step::Delete { replica, key }),
+            (0..replicas, 0..replicas).prop_map(|(from, to)| Step::Sync { from, to }),
+        ]
+    }
+
+    fn run(replicas: usize, steps: &[Step]) -> Vec<CrdtDoc> {
+        let mut docs: Vec<CrdtDoc> = (0..replicas)
+            .map(|i| CrdtDoc::new("doc", format!("replica-{i}")))
+            .collect();
+        for step in steps {
+            match *step {
+                Step::Set { replica, key, value } => {
+                    docs[replica].set(format!("k{key}"), json!(value));
+                }
+                Step::Delete { replica, key } => {
+                    docs[replica].delete(format!("k{key}"));
+                }
+                Step::Sync { from, to } if from != to => {
+                    let delta = docs[from].delta_since(docs[to].clock());
+                    docs[to].apply_all(delta);
+                }
+                Step::Sync { .. } => {}
+            }
+        }
+        docs
+    }
+
+    proptest! {
+        /// After everyone exchanges deltas, all replicas hold the same state.
+        #[test]
+        fn prop_replicas_converge(steps in prop::collection::vec(step(3), 0..60)) {
+            let mut docs = run(3, &steps);
+            for _ in 0..2 {
+                for from in 0..docs.len() {
+                    for to in 0..docs.len() {
+                        if from != to {
+                            let delta = docs[from].delta_since(docs[to].clock());
+                            docs[to].apply_all(delta);
+                        }
+                    }
+                }
+            }
+            for doc in &docs[1..] {
+                prop_assert!(doc.same_state(&docs[0]));
+                prop_assert_eq!(doc.clock(), docs[0].clock());
+            }
+        }
+
+        /// Delivery order and duplication do not affect the outcome.
+        #[test]
+        fn prop_apply_order_is_irrelevant(
+            steps in prop::collection::vec(step(2), 0..40),
+            seed in any::<u64>(),
+        ) {
+            let docs = run(2, &steps);
+            let mut ops: Vec<Op> = docs
+                .iter()
+                .flat_map(|d| d.delta_since(&VectorClock::new()))
+                .collect();
+            ops.extend(ops.clone());
+
+            // Deterministic shuffle driven by the seed.
+            let mut state = seed | 1;
+            for i in (1..ops.len()).rev() {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                ops.swap(i, (state % (i as u64 + 1)) as usize);
+            }
+
+            let mut forward = CrdtDoc::new("doc", "observer-1");
+            forward.apply_all(ops.clone());
+            ops.reverse();
+            let mut backward = CrdtDoc::new("doc", "observer-2");
+            backward.apply_all(ops);
+            prop_assert!(forward.same_state(&backward));
+        }
+    }
+}
@@ -25,6 +25,22 @@ pub enum NetworkError {
     #[error("Connection refused by {0}")]
     ConnectionRefused(String),
 
+    /// The secure handshake with a peer failed.
+    #[error("Handshake failed: {0}")]
+    Handshake(String),
+
+    /// The peer authenticated but is not on the trust list.
+    #[error("Untrusted peer: {0}")]
+    Untrusted(String),
+
+    /// A relay refused a request or could not reach the target peer.
+    #[error("Relay error: {0}")]
+    Relay(String),
+
+    /// A pairing invite was malformed, expired or did not match.
+    #[error("Pairing error: {0}")]
+    Pairing(String),
+
     /// An operation timed out.
     #[error("Timeout after {0:?}")]
     Timeout(Duration),
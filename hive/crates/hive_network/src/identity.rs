@@ -1,18 +1,28 @@
 //! Peer identity — unique node identification and persistence.
+//!
+//! Every node owns an Ed25519 keypair. Its [`PeerId`] is the hex-encoded
+//! public key, so any peer can check a signature or a handshake against the
+//! id alone without a separate key lookup.
 
 use std::fmt;
 use std::path::Path;
 
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use serde::{Deserialize, Serialize};
 
-/// A unique identifier for a peer node.
+/// A unique identifier for a peer node, derived from its public key.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct PeerId(pub String);
 
 impl PeerId {
-    /// Generate a new random peer ID (UUID v4).
+    /// Generate the PeerId of a fresh random keypair.
     pub fn generate() -> Self {
-        Self(uuid::Uuid::new_v4().to_string())
+        NodeKeypair::generate().peer_id()
+    }
+
+    /// Derive the PeerId for an Ed25519 public key.
+    pub fn from_public_key(key: &VerifyingKey) -> Self {
+        Self(to_hex(key.as_bytes()))
     }
 
     /// Create a PeerId from an existing string.
@@ -24,6 +34,28 @@ impl PeerId {
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// The public key this id encodes, if it is a key-derived id.
+    pub fn public_key(&self) -> Option<VerifyingKey> {
+        let bytes: [u8; 32] = from_hex(&self.0)?.try_into().ok()?;
+        VerifyingKey::from_bytes(&bytes).ok()
+    }
+
+    /// Short, human-comparable form (first 8 hex digits) for logs and UIs.
+    pub fn short(&self) -> &str {
+        self.0.get(..8).unwrap_or(&self.0)
+    }
+
+    /// Verify a signature made by this peer over `message`.
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        let Some(key) = self.public_key() else {
+            return false;
+        };
+        let Ok(signature) = Signature::from_slice(signature) else {
+            return false;
+        };
+        key.verify(message, &signature).is_ok()
+    }
 }
 
 impl fmt::Display for PeerId {
@@ -32,7 +64,58 @@ impl fmt::Display for PeerId {
     }
 }
 
+/// An Ed25519 signing keypair identifying this node.
+#[derive(Clone)]
+pub struct NodeKeypair {
+    signing: SigningKey,
+}
+
+impl NodeKeypair {
+    /// Generate a new random keypair.
+    pub fn generate() -> Self {
+        Self::from_secret_bytes(rand::random())
+    }
+
+    /// Rebuild a keypair from its 32-byte secret.
+    pub fn from_secret_bytes(secret: [u8; 32]) -> Self {
+        Self {
+            signing: SigningKey::from_bytes(&secret),
+        }
+    }
+
+    /// The 32-byte secret, for persistence.
+    pub fn secret_bytes(&self) -> [u8; 32] {
+        self.signing.to_bytes()
+    }
+
+    /// The public half of the keypair.
+    pub fn public_key(&self) -> VerifyingKey {
+        self.signing.verifying_key()
+    }
+
+    /// The PeerId derived from the public key.
+    pub fn peer_id(&self) -> PeerId {
+        PeerId::from_public_key(&self.public_key())
+    }
+
+    /// Sign `message`, returning the 64-byte signature.
+    pub fn sign(&self, message: &[u8]) -> Vec<u8> {
+        self.signing.sign(message).to_bytes().to_vec()
+    }
+}
+
+impl fmt::Debug for NodeKeypair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeKeypair")
+            .field("peer_id", &self.peer_id())
+            .finish_non_exhaustive()
+    }
+}
+
 /// The full identity of a Hive node on the network.
+///
+/// The keypair is only present on the local node's own identity; identities
+/// learned from peers carry the public fields alone.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NodeIdentity {
     /// Unique peer identifier.
@@ -43,13 +126,29 @@ pub struct NodeIdentity {
     pub version: String,
     /// Capabilities advertised by this node.
     pub capabilities: Vec<String>,
+    /// Signing keypair (local node only, never serialized to peers).
+    #[serde(skip)]
+    keypair: Option<NodeKeypair>,
+}
+
+/// On-disk form of the local identity, including the secret key.
+#[derive(Serialize, Deserialize)]
+struct StoredIdentity {
+    #[serde(flatten)]
+    identity: NodeIdentity,
+    secret_key: String,
 }
 
 impl NodeIdentity {
-    /// Create a new identity with a fresh PeerId.
+    /// Create a new identity with a fresh keypair.
     pub fn generate(name: impl Into<String>) -> Self {
+        Self::from_keypair(NodeKeypair::generate(), name)
+    }
+
+    /// Create an identity for an existing keypair.
+    pub fn from_keypair(keypair: NodeKeypair, name: impl Into<String>) -> Self {
         Self {
-            peer_id: PeerId::generate(),
+            peer_id: keypair.peer_id(),
             name: name.into(),
             version: env!("CARGO_PKG_VERSION").to_string(),
             capabilities: vec![
@@ -57,29 +156,67 @@ impl NodeIdentity {
                 "channel_sync".to_string(),
                 "fleet_learn".to_string(),
             ],
+            keypair: Some(keypair),
         }
     }
 
-    /// Save the identity to a JSON file.
+    /// The public identity of a remote peer.
+    pub fn remote(
+        peer_id: PeerId,
+        name: impl Into<String>,
+        version: impl Into<String>,
+        capabilities: Vec<String>,
+    ) -> Self {
+        Self {
+            peer_id,
+            name: name.into(),
+            version: version.into(),
+            capabilities,
+            keypair: None,
+        }
+    }
+
+    /// The node's signing keypair, if this is the local identity.
+    pub fn keypair(&self) -> Option<&NodeKeypair> {
+        self.keypair.as_ref()
+    }
+
+    /// A copy of this identity without the keypair, safe to share.
+    pub fn public(&self) -> Self {
+        Self {
+            keypair: None,
+            ..self.clone()
+        }
+    }
+
+    /// Save the identity (including its secret key) to a JSON file.
     pub fn save_to_file(&self, path: &Path) -> Result<(), String> {
+        let keypair = self
+            .keypair
+            .as_ref()
+            .ok_or_else(|| "Identity has no keypair to save".to_string())?;
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)
                 .map_err(|e| format!("Failed to create directory: {e}"))?;
         }
-        let json = serde_json::to_string_pretty(self)
+        let stored = StoredIdentity {
+            identity: self.public(),
+            secret_key: to_hex(&keypair.secret_bytes()),
+        };
+        let json = serde_json::to_string_pretty(&stored)
             .map_err(|e| format!("Failed to serialize identity: {e}"))?;
         std::fs::write(path, json).map_err(|e| format!("Failed to write identity file: {e}"))
     }
 
     /// Load an identity from a JSON file, or generate a new one if the file
-    /// does not exist.
+    /// does not exist, is corrupt, or predates node keypairs.
     pub fn load_or_generate(path: &Path, name: impl Into<String>) -> Self {
         if path.exists() {
             match std::fs::read_to_string(path) {
-                Ok(data) => match serde_json::from_str::<NodeIdentity>(&data) {
+                Ok(data) => match Self::from_stored(&data) {
                     Ok(identity) => return identity,
                     Err(e) => {
-                        tracing::warn!("Corrupt identity file, generating new: {e}");
+                        tracing::warn!("Unusable identity file, generating new: {e}");
                     }
                 },
                 Err(e) => {
@@ -94,6 +231,38 @@ impl NodeIdentity {
         }
         identity
     }
+
+    /// Parse a stored identity and check its key matches its PeerId.
+    fn from_stored(data: &str) -> Result<Self, String> {
+        let stored: StoredIdentity = serde_json::from_str(data).map_err(|e| e.to_string())?;
+        let secret: [u8; 32] = from_hex(&stored.secret_key)
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| "invalid secret key".to_string())?;
+        let keypair = NodeKeypair::from_secret_bytes(secret);
+        if keypair.peer_id() != stored.identity.peer_id {
+            return Err("secret key does not match peer id".to_string());
+        }
+        Ok(Self {
+            keypair: Some(keypair),
+            ..stored.identity
+        })
+    }
+}
+
+/// Lowercase hex encoding.
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Decode lowercase or uppercase hex; `None` if malformed.
+pub(crate) fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
 }
 
 // ---------------------------------------------------------------------------
@@ -119,12 +288,27 @@ mod tests {
         assert_eq!(format!("{id}"), "test-peer-123");
     }
 
+    #[test]
+    fn test_peer_id_derived_from_public_key() {
+        let keypair = NodeKeypair::generate();
+        let id = keypair.peer_id();
+        assert_eq!(id.as_str().len(), 64);
+        assert_eq!(id.public_key(), Some(keypair.public_key()));
+        assert_eq!(id.short(), &id.as_str()[..8]);
+
+        let sig = keypair.sign(b"hello");
+        assert!(id.verify(b"hello", &sig));
+        assert!(!id.verify(b"tampered", &sig));
+        assert!(!PeerId::from_string("not-a-key").verify(b"hello", &sig));
+    }
+
     #[test]
     fn test_identity_generate() {
         let identity = NodeIdentity::generate("test-node");
         assert_eq!(identity.name, "test-node");
         assert!(!identity.peer_id.as_str().is_empty());
         assert!(!identity.capabilities.is_empty());
+        assert_eq!(identity.keypair().unwrap().peer_id(), identity.peer_id);
     }
 
     #[test]
@@ -135,6 +319,9 @@ mod tests {
         assert_eq!(deserialized.peer_id, identity.peer_id);
         assert_eq!(deserialized.name, identity.name);
         assert_eq!(deserialized.capabilities, identity.capabilities);
+        // The keypair never leaves the node through serialization.
+        assert!(!json.contains("secret"));
+        assert!(deserialized.keypair().is_none());
     }
 
     #[test]
@@ -150,6 +337,10 @@ mod tests {
         let loaded = NodeIdentity::load_or_generate(&path, "fallback-name");
         assert_eq!(loaded.peer_id, original.peer_id);
         assert_eq!(loaded.name, "persist-test");
+        assert_eq!(
+            loaded.keypair().unwrap().secret_bytes(),
+            original.keypair().unwrap().secret_bytes()
+        );
 
         let _ = std::fs::remove_dir_all(&dir);
     }
@@ -165,4 +356,26 @@ mod tests {
 
         let _ = std::fs::remove_file(&path);
     }
+
+    #[test]
+    fn test_identity_load_legacy_file_regenerates() {
+        let dir = std::env::temp_dir().join("hive_network_test_legacy_identity");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("identity.json");
+        let legacy = serde_json::json!({
+            "peer_id": "0b9d9a4e-7c1f-4c59-9d0e-0e1f3a6c2b11",
+            "name": "legacy",
+            "version": "0.3.0",
+            "capabilities": [],
+        });
+        std::fs::write(&path, legacy.to_string()).unwrap();
+
+        let identity = NodeIdentity::load_or_generate(&path, "upgraded");
+        assert_eq!(identity.name, "upgraded");
+        assert!(identity.peer_id.public_key().is_some());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -8,9 +8,18 @@
 //!
 //! - **Transport**: WebSocket-based (via `tokio-tungstenite`) bidirectional
 //!   connections between peers.
-//! - **Discovery**: UDP broadcast on the LAN for automatic peer discovery.
+//! - **Security**: Ed25519 node keys, a Noise XX handshake encrypting every
+//!   connection, signed envelopes, and a trust list that unknown peers join
+//!   by pairing.
+//! - **Discovery**: UDP broadcast or mDNS (`_hive._tcp`) on the LAN for
+//!   automatic peer discovery.
+//! - **Relay**: a self-hostable rendezvous node that peers register with,
+//!   used to find each other's addresses and to tunnel end-to-end encrypted
+//!   connections when direct dialing fails behind NAT.
 //! - **Protocol**: Envelope-based typed messaging with JSON payloads.
 //! - **Routing**: Handler-based dispatch for incoming messages.
+//! - **Replication**: CRDT documents with vector clocks and delta sync, so
+//!   offline edits merge deterministically on reconnect.
 //!
 //! # Quick start
 //!
@@ -30,21 +39,31 @@
 //! ```
 
 pub mod config;
+pub mod crdt;
 pub mod discovery;
 pub mod error;
 pub mod identity;
+pub mod mdns;
 pub mod message;
 pub mod node;
 pub mod peer;
+pub mod relay;
 pub mod router;
+pub mod secure;
 pub mod sync;
 pub mod transport;
+pub mod trust;
 
 // ── Re-exports for convenience ──────────────────────────────────────────
 
 pub use config::NetworkConfig;
+pub use crdt::{ClockOrdering, CrdtDoc, Op, Snapshot, Stamp, VectorClock};
 pub use error::NetworkError;
-pub use identity::{NodeIdentity, PeerId};
+pub use identity::{NodeIdentity, NodeKeypair, PeerId};
 pub use message::{Envelope, MessageKind};
 pub use node::HiveNode;
 pub use peer::{PeerInfo, PeerRegistry, PeerState};
+pub use relay::{PeerRoute, RelayAddr, RelayMessage};
+pub use secure::SecurityContext;
+pub use sync::{CrdtDelta, CrdtSyncHelper, CrdtSyncPayload};
+pub use trust::{PairingInvite, TrustStore, TrustedPeer};
@@ -0,0 +1,199 @@
+//! LAN peer discovery via mDNS / DNS-SD (`_hive._tcp`).
+//!
+//! An alternative to the raw UDP broadcast in [`crate::discovery`]: it works
+//! on networks that filter broadcast but pass multicast DNS, and nodes show
+//! up in standard service browsers. Resolved services are reported as
+//! [`DiscoveredPeer`]s, so the node handles them exactly like broadcast
+//! discoveries — only peers it would admit are dialed, and the handshake,
+//! not the advertisement, decides who a peer is.
+
+use std::net::{IpAddr, SocketAddr};
+
+use mdns_sd::{IfKind, ServiceDaemon, ServiceEvent, ServiceInfo};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, info, warn};
+
+use crate::discovery::{Announcement, DiscoveredPeer};
+use crate::error::NetworkError;
+use crate::identity::PeerId;
+
+/// DNS-SD service type Hive nodes advertise.
+pub const SERVICE_TYPE: &str = "_hive._tcp.local.";
+
+/// LAN discovery service using mDNS.
+pub struct MdnsDiscovery;
+
+impl MdnsDiscovery {
+    /// Advertise `announcement` and browse for other nodes until shutdown.
+    ///
+    /// A node listening on a loopback address also advertises on loopback,
+    /// which lets several nodes on one machine find each other.
+    pub fn start(
+        announcement: Announcement,
+        listen_addr: SocketAddr,
+        discovered_tx: mpsc::Sender<DiscoveredPeer>,
+        mut shutdown: broadcast::Receiver<()>,
+    ) -> Result<(), NetworkError> {
+        let daemon = ServiceDaemon::new().map_err(mdns_error)?;
+        if listen_addr.ip().is_loopback() {
+            daemon
+                .enable_interface(IfKind::LoopbackV4)
+                .map_err(mdns_error)?;
+        }
+
+        let service = service_info(&announcement, listen_addr)?;
+        daemon.register(service).map_err(mdns_error)?;
+        let browser = daemon.browse(SERVICE_TYPE).map_err(mdns_error)?;
+        info!(
+            "mDNS discovery advertising {SERVICE_TYPE} on port {}",
+            listen_addr.port()
+        );
+
+        let our_peer_id = announcement.peer_id;
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    event = browser.recv_async() => match event {
+                        Ok(ServiceEvent::ServiceResolved(service)) => {
+                            let Some(peer) = discovered_peer(&service) else {
+                                debug!("Ignoring malformed mDNS service {}", service.get_fullname());
+                                continue;
+                            };
+                            if peer.announcement.peer_id == our_peer_id {
+                                continue;
+                            }
+                            debug!(
+                                "mDNS discovered '{}' at {}",
+                                peer.announcement.name, peer.announcement.listen_addr
+                            );
+                            let _ = discovered_tx.send(peer).await;
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            warn!("mDNS browser stopped: {e}");
+                            break;
+                        }
+                    },
+                    _ = shutdown.recv() => {
+                        debug!("mDNS discovery shutting down");
+                        break;
+                    }
+                }
+            }
+            let _ = daemon.shutdown();
+        });
+
+        Ok(())
+    }
+}
+
+/// The service record advertising `announcement`.
+fn service_info(
+    announcement: &Announcement,
+    listen_addr: SocketAddr,
+) -> Result<ServiceInfo, NetworkError> {
+    let instance: String = format!("{}-{}", announcement.name, announcement.peer_id.short())
+        .chars()
+        .map(|c| if c == '.' { '-' } else { c })
+        .take(63)
+        .collect();
+    let host = format!("hive-{}.local.", announcement.peer_id.short());
+    let properties = [
+        ("peer_id", announcement.peer_id.as_str()),
+        ("name", announcement.name.as_str()),
+        ("version", announcement.version.as_str()),
+    ];
+
+    let auto_addr = listen_addr.ip().is_unspecified();
+    let ip = if auto_addr {
+        String::new()
+    } else {
+        listen_addr.ip().to_string()
+    };
+    let service = ServiceInfo::new(
+        SERVICE_TYPE,
+        &instance,
+        &host,
+        ip,
+        listen_addr.port(),
+        &properties[..],
+    )
+    .map_err(mdns_error)?;
+    Ok(if auto_addr {
+        service.enable_addr_auto()
+    } else {
+        service
+    })
+}
+
+/// Read a resolved service back into a discovery, preferring IPv4.
+fn discovered_peer(service: &ServiceInfo) -> Option<DiscoveredPeer> {
+    let peer_id = PeerId::from_string(service.get_property_val_str("peer_id")?);
+    let addresses = service.get_addresses();
+    let ip: IpAddr = addresses
+        .iter()
+        .find(|ip| ip.is_ipv4())
+        .or_else(|| addresses.iter().next())
+        .copied()?;
+    let listen_addr = SocketAddr::new(ip, service.get_port());
+
+    Some(DiscoveredPeer {
+        announcement: Announcement {
+            peer_id,
+            listen_addr: listen_addr.to_string(),
+            name: service
+                .get_property_val_str("name")
+                .unwrap_or_default()
+                .to_string(),
+            version: service
+                .get_property_val_str("version")
+                .unwrap_or_default()
+                .to_string(),
+        },
+        source_addr: listen_addr,
+    })
+}
+
+fn mdns_error(e: mdns_sd::Error) -> NetworkError {
+    NetworkError::Discovery(format!("mDNS: {e}"))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn announcement(name: &str) -> Announcement {
+        Announcement {
+            peer_id: PeerId::generate(),
+            listen_addr: "127.0.0.1:9470".to_string(),
+            name: name.to_string(),
+            version: "0.3.10".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_service_info_roundtrip() {
+        let ours = announcement("dev.box");
+        let service = service_info(&ours, "192.168.1.20:9470".parse().unwrap()).unwrap();
+        assert_eq!(service.get_type(), SERVICE_TYPE);
+        assert!(!service.get_fullname().starts_with("dev.box"));
+
+        let peer = discovered_peer(&service).unwrap();
+        assert_eq!(peer.announcement.peer_id, ours.peer_id);
+        assert_eq!(peer.announcement.name, "dev.box");
+        assert_eq!(peer.announcement.version, "0.3.10");
+        assert_eq!(peer.announcement.listen_addr, "192.168.1.20:9470");
+    }
+
+    #[test]
+    fn test_unspecified_listen_addr_advertises_interfaces() {
+        let service = service_info(&announcement("node"), "0.0.0.0:9470".parse().unwrap()).unwrap();
+        assert!(service.is_addr_auto());
+        // Without resolved addresses there is nothing to dial yet.
+        assert!(discovered_peer(&service).is_none());
+    }
+}
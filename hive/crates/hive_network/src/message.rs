@@ -3,7 +3,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::identity::PeerId;
+use crate::identity::{NodeKeypair, PeerId, from_hex, to_hex};
 
 /// The kind of message carried in an [`Envelope`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -20,6 +20,9 @@ pub enum MessageKind {
     Heartbeat,
     /// Keep-alive pong (response to Heartbeat).
     HeartbeatAck,
+    /// Relay and rendezvous control (registration, lookup, relayed
+    /// connections).
+    Relay,
 
     // ── Agent relay ─────────────────────────────────────────────────
     /// Forward a task to a remote swarm for execution.
@@ -36,6 +39,8 @@ pub enum MessageKind {
     FleetLearn,
     /// Generic state synchronization payload.
     StateSync,
+    /// Delta exchange for replicated (CRDT) documents.
+    CrdtSync,
 
     // ── Extensible ──────────────────────────────────────────────────
     /// User-defined message type for extensions.
@@ -51,12 +56,14 @@ impl MessageKind {
             Self::Goodbye => "goodbye".to_string(),
             Self::Heartbeat => "heartbeat".to_string(),
             Self::HeartbeatAck => "heartbeat_ack".to_string(),
+            Self::Relay => "relay".to_string(),
             Self::TaskRequest => "task_request".to_string(),
             Self::TaskResult => "task_result".to_string(),
             Self::AgentRelay => "agent_relay".to_string(),
             Self::ChannelSync => "channel_sync".to_string(),
             Self::FleetLearn => "fleet_learn".to_string(),
             Self::StateSync => "state_sync".to_string(),
+            Self::CrdtSync => "crdt_sync".to_string(),
             Self::Custom(name) => format!("custom:{name}"),
         }
     }
@@ -77,6 +84,20 @@ pub struct Envelope {
     pub payload: serde_json::Value,
     /// When the message was created.
     pub timestamp: DateTime<Utc>,
+    /// Hex Ed25519 signature by `from` over every other field.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+/// The fields of an [`Envelope`] covered by its signature.
+#[derive(Serialize)]
+struct SignedFields<'a> {
+    id: &'a str,
+    from: &'a PeerId,
+    to: &'a Option<PeerId>,
+    kind: &'a MessageKind,
+    payload: &'a serde_json::Value,
+    timestamp: &'a DateTime<Utc>,
 }
 
 impl Envelope {
@@ -89,6 +110,7 @@ impl Envelope {
             kind,
             payload,
             timestamp: Utc::now(),
+            signature: None,
         }
     }
 
@@ -97,6 +119,32 @@ impl Envelope {
         Self::new(from, None, kind, payload)
     }
 
+    /// Canonical bytes the signature covers.
+    fn signing_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(&SignedFields {
+            id: &self.id,
+            from: &self.from,
+            to: &self.to,
+            kind: &self.kind,
+            payload: &self.payload,
+            timestamp: &self.timestamp,
+        })
+        .unwrap_or_default()
+    }
+
+    /// Sign the envelope with the sender's keypair.
+    pub fn sign(&mut self, keypair: &NodeKeypair) {
+        self.signature = Some(to_hex(&keypair.sign(&self.signing_bytes())));
+    }
+
+    /// Whether the envelope carries a valid signature from `from`.
+    pub fn verify_signature(&self) -> bool {
+        let Some(signature) = self.signature.as_deref().and_then(from_hex) else {
+            return false;
+        };
+        self.from.verify(&self.signing_bytes(), &signature)
+    }
+
     /// Serialize the envelope to a JSON string for transmission.
     pub fn to_json(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string(self)
@@ -166,12 +214,14 @@ mod tests {
             MessageKind::Goodbye,
             MessageKind::Heartbeat,
             MessageKind::HeartbeatAck,
+            MessageKind::Relay,
             MessageKind::TaskRequest,
             MessageKind::TaskResult,
             MessageKind::AgentRelay,
             MessageKind::ChannelSync,
             MessageKind::FleetLearn,
             MessageKind::StateSync,
+            MessageKind::CrdtSync,
             MessageKind::Custom("my_extension".to_string()),
         ];
 
@@ -191,4 +241,47 @@ mod tests {
             "custom:foo"
         );
     }
+
+    #[test]
+    fn test_envelope_signature_roundtrip() {
+        let keypair = NodeKeypair::generate();
+        let mut env = Envelope::new(
+            keypair.peer_id(),
+            None,
+            MessageKind::TaskRequest,
+            serde_json::json!({"task": "build", "budget": 1.5, "tags": ["a", "b"]}),
+        );
+        assert!(!env.verify_signature());
+
+        env.sign(&keypair);
+        assert!(env.verify_signature());
+
+        // Signatures survive the wire format.
+        let received = Envelope::from_json(&env.to_json().unwrap()).unwrap();
+        assert!(received.verify_signature());
+
+        // Including floats that only survive with exact parsing.
+        let mut costed = Envelope::new(
+            keypair.peer_id(),
+            None,
+            MessageKind::TaskResult,
+            serde_json::json!({"cost": 0.1 + 0.2, "ratio": 1.0 / 3.0, "tiny": 2.225e-308}),
+        );
+        costed.sign(&keypair);
+        assert!(
+            Envelope::from_json(&costed.to_json().unwrap())
+                .unwrap()
+                .verify_signature()
+        );
+
+        // Any change to a signed field invalidates the signature.
+        let mut tampered = received.clone();
+        tampered.payload["budget"] = serde_json::json!(100.0);
+        assert!(!tampered.verify_signature());
+
+        // So does claiming to be someone else.
+        let mut spoofed = received;
+        spoofed.from = NodeKeypair::generate().peer_id();
+        assert!(!spoofed.verify_signature());
+    }
 }
@@ -3,12 +3,17 @@
 //! [`HiveNode`] is the primary public API for hive_network. It manages:
 //! - WebSocket server (accept incoming connections)
 //! - Outbound connections (connect to known peers)
-//! - LAN discovery (find peers on the local network)
+//! - LAN discovery (find peers on the local network, via UDP broadcast or
+//!   mDNS)
+//! - Relays (rendezvous and relayed connections across networks, or acting
+//!   as a relay for others)
 //! - Heartbeat loop (keep connections alive)
 //! - Message routing (dispatch envelopes to handlers)
+//! - Peer security (authenticated handshakes, trust list, pairing)
 
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::path::Path;
 use std::sync::Arc;
 
 use tokio::sync::{RwLock, broadcast, mpsc};
@@ -18,10 +23,14 @@ use crate::config::NetworkConfig;
 use crate::discovery::{Announcement, DiscoveryConfig, DiscoveryService, DiscoveredPeer};
 use crate::error::NetworkError;
 use crate::identity::{NodeIdentity, PeerId};
+use crate::mdns::MdnsDiscovery;
 use crate::message::{Envelope, MessageKind};
 use crate::peer::{PeerInfo, PeerRegistry, PeerState};
+use crate::relay::{PeerRoute, RelayAddr, RelayClient, RelayHub, relay_handler};
 use crate::router::{MessageRouter, hello_handler, heartbeat_handler, goodbye_handler};
+use crate::secure::SecurityContext;
 use crate::transport::{self, PeerConnection, TransportEvent};
+use crate::trust::{PairingInvite, TrustStore, TrustedPeer};
 
 /// The top-level Hive network node.
 ///
@@ -38,6 +47,14 @@ pub struct HiveNode {
     router: Arc<RwLock<MessageRouter>>,
     /// Active WebSocket connections keyed by peer address.
     connections: Arc<RwLock<HashMap<String, PeerConnection>>>,
+    /// Keys, trust list and pairing state for peer handshakes.
+    security: Arc<SecurityContext>,
+    /// Registrations and tunnels, while running in relay mode.
+    relay_hub: Option<Arc<RelayHub>>,
+    /// Relay links and peer dialing, while running.
+    relay: Option<Arc<RelayClient>>,
+    /// Sender for transport events while running (used by manual connects).
+    event_tx: Option<mpsc::Sender<TransportEvent>>,
     /// Shutdown signal broadcaster.
     shutdown_tx: Option<broadcast::Sender<()>>,
     /// Whether the node is currently running.
@@ -46,7 +63,25 @@ pub struct HiveNode {
 
 impl HiveNode {
     /// Create a new node with the given identity and config.
+    ///
+    /// The identity must carry its keypair; an identity without one (e.g.
+    /// deserialized from a peer) is replaced by a freshly generated one.
+    /// The trust list starts empty; see [`with_trust_store`](Self::with_trust_store).
     pub fn new(identity: NodeIdentity, config: NetworkConfig) -> Self {
+        let identity = if identity.keypair().is_some() {
+            identity
+        } else {
+            warn!(
+                "Identity for '{}' has no keypair, generating a new one",
+                identity.name
+            );
+            NodeIdentity::generate(identity.name)
+        };
+        let security = Arc::new(
+            SecurityContext::new(&identity, TrustStore::new(), config.allow_untrusted_peers)
+                .expect("identity has a keypair"),
+        );
+
         let mut router = MessageRouter::new();
 
         // Register built-in protocol handlers.
@@ -66,11 +101,27 @@ impl HiveNode {
             peers: Arc::new(RwLock::new(PeerRegistry::new())),
             router: Arc::new(RwLock::new(router)),
             connections: Arc::new(RwLock::new(HashMap::new())),
+            security,
+            relay_hub: None,
+            relay: None,
+            event_tx: None,
             shutdown_tx: None,
             running: false,
         }
     }
 
+    /// Load the trust list from `path` and persist every change back to it.
+    /// Call before [`start()`](HiveNode::start).
+    pub fn with_trust_store(mut self, path: &Path) -> Self {
+        let store = TrustStore::load_or_default(path);
+        self.security = Arc::new(
+            SecurityContext::new(&self.identity, store, self.config.allow_untrusted_peers)
+                .expect("identity has a keypair")
+                .with_trust_path(path.to_path_buf()),
+        );
+        self
+    }
+
     /// Create a node with default config.
     pub fn with_defaults(name: impl Into<String>) -> Self {
         let identity = NodeIdentity::generate(name);
@@ -109,6 +160,52 @@ impl HiveNode {
         registry.list_connected().into_iter().cloned().collect()
     }
 
+    /// The node's security context (keys, trust list, pairing).
+    pub fn security(&self) -> &Arc<SecurityContext> {
+        &self.security
+    }
+
+    /// Peers this node accepts connections from and connects to.
+    pub fn trusted_peers(&self) -> Vec<TrustedPeer> {
+        self.security.trusted_peers()
+    }
+
+    /// Add a peer to the trust list directly (e.g. after an out-of-band key
+    /// exchange).
+    pub fn trust_peer(&self, peer_id: PeerId, name: &str) {
+        self.security.trust(peer_id, name);
+    }
+
+    /// Remove a peer from the trust list. Existing connections stay open
+    /// until they drop; new ones are refused.
+    pub fn revoke_peer(&self, peer_id: &PeerId) -> bool {
+        self.security.revoke(peer_id)
+    }
+
+    /// Issue a single-use pairing invite valid for `ttl`. Show its
+    /// [`display_code`](PairingInvite::display_code) or render its
+    /// [`to_uri`](PairingInvite::to_uri) as a QR code for the joining node.
+    pub fn create_pairing_invite(&self, ttl: chrono::Duration) -> PairingInvite {
+        self.security
+            .create_invite(Some(self.config.listen_addr.to_string()), ttl)
+    }
+
+    /// Pair with the node that issued `invite`, connecting to `addr` (or the
+    /// invite's own address). Both nodes trust each other on success.
+    pub async fn pair_with(
+        &self,
+        invite: &PairingInvite,
+        addr: Option<&str>,
+    ) -> Result<PeerId, NetworkError> {
+        if invite.is_expired() {
+            return Err(NetworkError::Pairing("invite expired".into()));
+        }
+        let addr = addr
+            .or(invite.addr.as_deref())
+            .ok_or_else(|| NetworkError::Pairing("invite has no address".into()))?;
+        self.connect_with(addr, Some(invite)).await
+    }
+
     /// Register a custom message handler for a specific message kind.
     pub async fn on_message(
         &self,
@@ -132,66 +229,134 @@ impl HiveNode {
         let (event_tx, event_rx) = mpsc::channel(256);
         let (conn_tx, conn_rx) = mpsc::channel(64);
 
+        self.event_tx = Some(event_tx.clone());
+        let handshake_timeout = self.config.connection_timeout;
+
+        // Relay links, and the relay hub when serving as a relay.
+        let relay = Arc::new(RelayClient::new(
+            Arc::clone(&self.security),
+            self.config.listen_addr,
+            Arc::clone(&self.peers),
+            Arc::clone(&self.connections),
+            event_tx.clone(),
+            handshake_timeout,
+        ));
+        self.relay_hub = self.config.relay_mode.then(|| {
+            Arc::new(RelayHub::new(
+                Arc::clone(&self.peers),
+                Arc::clone(&self.connections),
+                handshake_timeout,
+            ))
+        });
+        self.relay = Some(Arc::clone(&relay));
+        self.router.write().await.register(
+            MessageKind::Relay,
+            relay_handler(self.relay_hub.clone(), Arc::clone(&relay)),
+        );
+
         // Start WebSocket server.
         let server_addr = self.config.listen_addr;
         let server_shutdown = shutdown_tx.subscribe();
         let server_event_tx = event_tx.clone();
+        let server_security = Arc::clone(&self.security);
+        let server_relay = self.relay_hub.clone();
         tokio::spawn(async move {
-            if let Err(e) =
-                transport::start_server(server_addr, server_event_tx, conn_tx, server_shutdown)
-                    .await
+            if let Err(e) = transport::start_server(
+                server_addr,
+                server_security,
+                server_relay,
+                handshake_timeout,
+                server_event_tx,
+                conn_tx,
+                server_shutdown,
+            )
+            .await
             {
                 error!("WebSocket server error: {e}");
             }
         });
 
-        // Start LAN discovery if enabled.
-        if self.config.discovery_enabled {
+        // Start LAN discovery (broadcast and/or mDNS) if enabled.
+        if self.config.discovery_enabled || self.config.mdns_enabled {
             let (discovered_tx, discovered_rx) = mpsc::channel(64);
-            let discovery_config = DiscoveryConfig {
-                port: self.config.discovery_port,
-                interval: std::time::Duration::from_secs(5),
-                announcement: Announcement {
-                    peer_id: self.identity.peer_id.clone(),
-                    listen_addr: self.config.listen_addr.to_string(),
-                    name: self.identity.name.clone(),
-                    version: self.identity.version.clone(),
-                },
+            let announcement = Announcement {
+                peer_id: self.identity.peer_id.clone(),
+                listen_addr: self.config.listen_addr.to_string(),
+                name: self.identity.name.clone(),
+                version: self.identity.version.clone(),
             };
-            let discovery_shutdown = shutdown_tx.subscribe();
-            if let Err(e) =
-                DiscoveryService::start(discovery_config, discovered_tx, discovery_shutdown).await
+            if self.config.discovery_enabled {
+                let discovery_config = DiscoveryConfig {
+                    port: self.config.discovery_port,
+                    interval: std::time::Duration::from_secs(5),
+                    announcement: announcement.clone(),
+                };
+                let discovery_shutdown = shutdown_tx.subscribe();
+                if let Err(e) = DiscoveryService::start(
+                    discovery_config,
+                    discovered_tx.clone(),
+                    discovery_shutdown,
+                )
+                .await
+                {
+                    warn!("Discovery start failed (non-fatal): {e}");
+                }
+            }
+            if self.config.mdns_enabled
+                && let Err(e) = MdnsDiscovery::start(
+                    announcement,
+                    self.config.listen_addr,
+                    discovered_tx,
+                    shutdown_tx.subscribe(),
+                )
             {
-                warn!("Discovery start failed (non-fatal): {e}");
+                warn!("mDNS discovery start failed (non-fatal): {e}");
             }
 
             // Spawn task to handle discovered peers.
             let peers = Arc::clone(&self.peers);
             let event_tx_disc = event_tx.clone();
-            let our_peer_id = self.identity.peer_id.clone();
             let connections = Arc::clone(&self.connections);
+            let security = Arc::clone(&self.security);
             tokio::spawn(async move {
                 Self::handle_discoveries(
                     discovered_rx,
                     peers,
                     connections,
                     event_tx_disc,
-                    our_peer_id,
+                    security,
+                    handshake_timeout,
                 )
                 .await;
             });
         }
 
-        // Connect to known (bootstrap) peers.
-        for addr in &self.config.known_peers {
+        // Connect to known (bootstrap) peers. Entries that are peer ids
+        // rather than addresses are reached through the relays.
+        let (peer_ids, addrs): (Vec<&String>, Vec<&String>) = self
+            .config
+            .known_peers
+            .iter()
+            .partition(|entry| PeerId::from_string(entry.as_str()).public_key().is_some());
+        for addr in addrs {
             let addr = addr.clone();
             let event_tx = event_tx.clone();
             let connections = Arc::clone(&self.connections);
+            let peers = Arc::clone(&self.peers);
+            let security = Arc::clone(&self.security);
             tokio::spawn(async move {
-                match transport::connect_to_peer(&addr, event_tx).await {
+                match transport::connect_to_peer(
+                    &addr,
+                    &security,
+                    None,
+                    handshake_timeout,
+                    event_tx,
+                )
+                .await
+                {
                     Ok(conn) => {
-                        let key = addr.clone();
-                        connections.write().await.insert(key, conn);
+                        Self::register_outbound(&peers, &conn, &addr).await;
+                        connections.write().await.insert(addr.clone(), conn);
                         info!("Connected to bootstrap peer {addr}");
                     }
                     Err(e) => {
@@ -201,6 +366,32 @@ impl HiveNode {
             });
         }
 
+        // Register with relays and keep reaching peers known by id.
+        let relays: Vec<RelayAddr> = self
+            .config
+            .relays
+            .iter()
+            .filter_map(|entry| {
+                RelayAddr::parse(entry)
+                    .inspect_err(|e| warn!("Ignoring relay: {e}"))
+                    .ok()
+            })
+            .collect();
+        let peer_ids: Vec<PeerId> = peer_ids
+            .into_iter()
+            .map(|id| PeerId::from_string(id.as_str()))
+            .collect();
+        if !relays.is_empty() || !peer_ids.is_empty() {
+            let maintain_relay = Arc::clone(&relay);
+            let interval = self.config.heartbeat_interval;
+            let relay_shutdown = shutdown_tx.subscribe();
+            tokio::spawn(async move {
+                maintain_relay
+                    .maintain(relays, peer_ids, interval, relay_shutdown)
+                    .await;
+            });
+        }
+
         // Spawn the main event loop.
         let router = Arc::clone(&self.router);
         let connections = Arc::clone(&self.connections);
@@ -213,6 +404,7 @@ impl HiveNode {
                 router,
                 connections,
                 peers,
+                relay,
                 event_shutdown,
             )
             .await;
@@ -254,6 +446,11 @@ impl HiveNode {
             let _ = conn.close().await;
         }
         conns.clear();
+        drop(conns);
+        if let Some(relay) = self.relay.take() {
+            relay.close_links().await;
+        }
+        self.relay_hub = None;
 
         // Mark all peers as disconnected.
         let mut registry = self.peers.write().await;
@@ -266,20 +463,38 @@ impl HiveNode {
             registry.update_state(&pid, PeerState::Disconnected);
         }
 
+        self.event_tx = None;
         self.running = false;
         info!("HiveNode '{}' stopped", self.identity.name);
     }
 
-    /// Connect to a specific peer by address.
+    /// Connect to a specific peer by address. The peer must be trusted
+    /// unless the node allows untrusted peers.
     pub async fn connect_to(&self, addr: &str) -> Result<PeerId, NetworkError> {
-        if !self.running {
-            return Err(NetworkError::NotRunning);
-        }
+        self.connect_with(addr, None).await
+    }
 
-        let (event_tx, _) = mpsc::channel(64);
-        let conn = transport::connect_to_peer(addr, event_tx).await?;
+    async fn connect_with(
+        &self,
+        addr: &str,
+        invite: Option<&PairingInvite>,
+    ) -> Result<PeerId, NetworkError> {
+        let event_tx = match (&self.event_tx, self.running) {
+            (Some(tx), true) => tx.clone(),
+            _ => return Err(NetworkError::NotRunning),
+        };
+
+        let conn = transport::connect_to_peer(
+            addr,
+            &self.security,
+            invite,
+            self.config.connection_timeout,
+            event_tx,
+        )
+        .await?;
         let peer_id = conn.peer_id().clone();
 
+        Self::register_outbound(&self.peers, &conn, addr).await;
         self.connections
             .write()
             .await
@@ -303,6 +518,24 @@ impl HiveNode {
         }
     }
 
+    /// Send an envelope to a connected peer by id, whichever side opened the
+    /// connection.
+    pub async fn send_to_peer(
+        &self,
+        peer_id: &PeerId,
+        envelope: &Envelope,
+    ) -> Result<(), NetworkError> {
+        if !self.running {
+            return Err(NetworkError::NotRunning);
+        }
+
+        let mut conns = self.connections.write().await;
+        match conns.values_mut().find(|conn| conn.peer_id() == peer_id) {
+            Some(conn) => conn.send(envelope).await,
+            None => Err(NetworkError::PeerNotFound(peer_id.to_string())),
+        }
+    }
+
     /// Broadcast an envelope to all connected peers. Returns the number of
     /// peers the message was sent to.
     pub async fn broadcast(&self, envelope: &Envelope) -> Result<usize, NetworkError> {
@@ -332,10 +565,64 @@ impl HiveNode {
         Ok(sent)
     }
 
+    fn relay_client(&self) -> Result<&Arc<RelayClient>, NetworkError> {
+        match (&self.relay, self.running) {
+            (Some(relay), true) => Ok(relay),
+            _ => Err(NetworkError::NotRunning),
+        }
+    }
+
+    /// Relays this node is currently registered with.
+    pub async fn connected_relays(&self) -> Vec<PeerId> {
+        match &self.relay {
+            Some(relay) => relay.relays().await,
+            None => Vec::new(),
+        }
+    }
+
+    /// Peers registered with this node, when it runs in relay mode.
+    pub fn relay_registrations(&self) -> Vec<PeerId> {
+        self.relay_hub
+            .as_ref()
+            .map(|hub| hub.registered())
+            .unwrap_or_default()
+    }
+
+    /// Ask the relays where `peer_id` can be dialed.
+    pub async fn locate_peer(&self, peer_id: &PeerId) -> Result<Vec<String>, NetworkError> {
+        Ok(self.relay_client()?.locate(peer_id).await)
+    }
+
+    /// Connect to a peer by id: directly at an address the relays know for
+    /// it, or through a relay when direct dialing fails. The peer must be
+    /// trusted unless the node allows untrusted peers.
+    pub async fn dial_peer(&self, peer_id: &PeerId) -> Result<PeerRoute, NetworkError> {
+        self.relay_client()?.dial_peer(peer_id).await
+    }
+
+    /// Connect to a peer through a relay without trying a direct dial, e.g.
+    /// when the peer is known to be behind a NAT.
+    pub async fn connect_relayed(&self, peer_id: &PeerId) -> Result<PeerRoute, NetworkError> {
+        self.relay_client()?.connect_relayed(peer_id).await
+    }
+
     // -----------------------------------------------------------------------
     // Internal tasks
     // -----------------------------------------------------------------------
 
+    /// Record an outbound connection's authenticated peer in the registry.
+    async fn register_outbound(peers: &RwLock<PeerRegistry>, conn: &PeerConnection, addr: &str) {
+        let addr = addr
+            .trim_start_matches("ws://")
+            .trim_start_matches("wss://")
+            .parse()
+            .unwrap_or_else(|_| SocketAddr::from(([0, 0, 0, 0], 0)));
+        peers
+            .write()
+            .await
+            .mark_connected(conn.remote_identity().clone(), addr);
+    }
+
     /// Main event loop — processes transport events and incoming connection handles.
     async fn event_loop(
         mut event_rx: mpsc::Receiver<TransportEvent>,
@@ -343,10 +630,15 @@ impl HiveNode {
         router: Arc<RwLock<MessageRouter>>,
         connections: Arc<RwLock<HashMap<String, PeerConnection>>>,
         peers: Arc<RwLock<PeerRegistry>>,
+        relay: Arc<RelayClient>,
         mut shutdown: broadcast::Receiver<()>,
     ) {
         loop {
             tokio::select! {
+                // Store connection handles before handling the events they
+                // precede, so replies to a first message find the connection.
+                biased;
+
                 // New server-side connection handle.
                 Some((addr, conn)) = conn_rx.recv() => {
                     let key = addr.to_string();
@@ -357,10 +649,19 @@ impl HiveNode {
                 // Transport event (message, connect, disconnect).
                 Some(event) = event_rx.recv() => {
                     match event {
-                        TransportEvent::InboundConnection { addr, peer_id: _ } => {
-                            debug!("Inbound connection from {addr}");
+                        TransportEvent::InboundConnection { addr, identity } => {
+                            debug!("Inbound connection from {} at {addr}", identity.peer_id.short());
+                            peers.write().await.mark_connected(identity, addr);
                         }
                         TransportEvent::Message { from_addr, envelope } => {
+                            // Relay links carry relay control messages only.
+                            if envelope.kind != MessageKind::Relay
+                                && relay.is_link(&from_addr).await
+                            {
+                                debug!("Ignoring {:?} from relay link {from_addr}", envelope.kind);
+                                continue;
+                            }
+
                             // Update peer last-seen.
                             {
                                 let mut reg = peers.write().await;
@@ -371,7 +672,7 @@ impl HiveNode {
                             let router_guard = router.read().await;
                             if let Some(response) = router_guard.dispatch(envelope).await {
                                 // Send response back.
-                                let key = from_addr.to_string();
+                                let key = from_addr;
                                 let mut conns = connections.write().await;
                                 if let Some(conn) = conns.get_mut(&key)
                                     && let Err(e) = conn.send(&response).await {
@@ -381,7 +682,8 @@ impl HiveNode {
                         }
                         TransportEvent::Disconnected { addr } => {
                             debug!("Peer at {addr} disconnected");
-                            connections.write().await.remove(&addr.to_string());
+                            connections.write().await.remove(&addr);
+                            relay.drop_link(&addr).await;
                         }
                     }
                 }
@@ -400,13 +702,24 @@ impl HiveNode {
         peers: Arc<RwLock<PeerRegistry>>,
         connections: Arc<RwLock<HashMap<String, PeerConnection>>>,
         event_tx: mpsc::Sender<TransportEvent>,
-        our_peer_id: PeerId,
+        security: Arc<SecurityContext>,
+        handshake_timeout: std::time::Duration,
     ) {
         while let Some(discovered) = discovered_rx.recv().await {
             let ann = &discovered.announcement;
 
             // Skip ourselves.
-            if ann.peer_id == our_peer_id {
+            if &ann.peer_id == security.peer_id() {
+                continue;
+            }
+
+            // Only dial peers we would admit; unknown peers must pair first.
+            if !security.admits(&ann.peer_id) {
+                debug!(
+                    "Ignoring untrusted peer '{}' ({})",
+                    ann.name,
+                    ann.peer_id.short()
+                );
                 continue;
             }
 
@@ -432,12 +745,12 @@ impl HiveNode {
 
             let peer_info = PeerInfo {
                 id: ann.peer_id.clone(),
-                identity: NodeIdentity {
-                    peer_id: ann.peer_id.clone(),
-                    name: ann.name.clone(),
-                    version: ann.version.clone(),
-                    capabilities: Vec::new(),
-                },
+                identity: NodeIdentity::remote(
+                    ann.peer_id.clone(),
+                    ann.name.clone(),
+                    ann.version.clone(),
+                    Vec::new(),
+                ),
                 addr,
                 state: PeerState::Discovered,
                 connected_at: None,
@@ -453,14 +766,32 @@ impl HiveNode {
 
             // Attempt to connect.
             let addr_str = ann.listen_addr.clone();
-            match transport::connect_to_peer(&addr_str, event_tx.clone()).await {
+            match transport::connect_to_peer(
+                &addr_str,
+                &security,
+                None,
+                handshake_timeout,
+                event_tx.clone(),
+            )
+            .await
+            {
+                Ok(mut conn) if conn.peer_id() != &ann.peer_id => {
+                    // Announcements are unauthenticated; trust only the handshake.
+                    warn!(
+                        "Peer at {addr_str} announced {} but authenticated as {}",
+                        ann.peer_id.short(),
+                        conn.peer_id().short()
+                    );
+                    let _ = conn.close().await;
+                    let mut registry = peers.write().await;
+                    registry.update_state(&ann.peer_id, PeerState::Disconnected);
+                }
                 Ok(conn) => {
+                    Self::register_outbound(&peers, &conn, &addr_str).await;
                     connections
                         .write()
                         .await
                         .insert(addr_str.clone(), conn);
-                    let mut registry = peers.write().await;
-                    registry.update_state(&ann.peer_id, PeerState::Connected);
                     info!("Connected to discovered peer '{}' at {addr_str}", ann.name);
                 }
                 Err(e) => {
@@ -653,4 +984,86 @@ mod tests {
         assert!(router.has_handler(&MessageKind::Heartbeat));
         assert!(router.has_handler(&MessageKind::Goodbye));
     }
+
+    fn local_config() -> NetworkConfig {
+        NetworkConfig {
+            listen_addr: "127.0.0.1:0".parse().unwrap(),
+            discovery_enabled: false,
+            ..NetworkConfig::default()
+        }
+    }
+
+    fn free_addr() -> SocketAddr {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap()
+    }
+
+    #[test]
+    fn test_identity_without_keypair_is_replaced() {
+        let remote = NodeIdentity::generate("keyless").public();
+        let node = HiveNode::new(remote.clone(), NetworkConfig::default());
+        assert_eq!(node.identity().name, "keyless");
+        assert_ne!(node.peer_id(), &remote.peer_id);
+        assert!(node.identity().keypair().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_untrusted_connect_refused_then_pairing_succeeds() {
+        let mut config_a = local_config();
+        config_a.listen_addr = free_addr();
+        let addr_a = config_a.listen_addr.to_string();
+        let mut node_a = HiveNode::new(NodeIdentity::generate("node-a"), config_a);
+        node_a.start().await.unwrap();
+
+        let mut node_b = HiveNode::new(NodeIdentity::generate("node-b"), local_config());
+        node_b.start().await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        // Strangers are turned away by default.
+        node_b.trust_peer(node_a.peer_id().clone(), "node-a");
+        let refused = node_b.connect_to(&addr_a).await;
+        assert!(matches!(refused, Err(NetworkError::ConnectionRefused(_))));
+        node_b.revoke_peer(node_a.peer_id());
+
+        // Pairing via a scanned invite makes both sides trust each other.
+        let invite = PairingInvite::from_uri(
+            &node_a
+                .create_pairing_invite(chrono::Duration::minutes(5))
+                .to_uri(),
+        )
+        .unwrap();
+        let paired = node_b.pair_with(&invite, Some(&addr_a)).await.unwrap();
+        assert_eq!(&paired, node_a.peer_id());
+        assert!(node_a.security().is_trusted(node_b.peer_id()));
+        assert!(node_b.security().is_trusted(node_a.peer_id()));
+
+        let connected = node_b.connected_peers().await;
+        assert_eq!(connected.len(), 1);
+        assert_eq!(connected[0].identity.name, "node-a");
+
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let connected = node_a.connected_peers().await;
+        assert_eq!(connected.len(), 1);
+        assert_eq!(&connected[0].id, node_b.peer_id());
+
+        node_a.stop().await;
+        node_b.stop().await;
+    }
+
+    #[test]
+    fn test_trust_store_persists_through_node() {
+        let dir = std::env::temp_dir().join("hive_network_test_node_trust");
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("trusted_peers.json");
+
+        let peer = PeerId::generate();
+        let node = HiveNode::with_defaults("trusting").with_trust_store(&path);
+        node.trust_peer(peer.clone(), "friend");
+
+        let reloaded = HiveNode::with_defaults("reloaded").with_trust_store(&path);
+        assert_eq!(reloaded.trusted_peers().len(), 1);
+        assert!(reloaded.security().is_trusted(&peer));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
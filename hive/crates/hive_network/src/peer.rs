@@ -115,6 +115,30 @@ impl PeerRegistry {
         }
     }
 
+    /// Record an authenticated connection, adding the peer if it is new.
+    pub fn mark_connected(&mut self, identity: NodeIdentity, addr: SocketAddr) {
+        let now = Utc::now();
+        let peer = self
+            .peers
+            .entry(identity.peer_id.as_str().to_string())
+            .or_insert_with(|| PeerInfo {
+                id: identity.peer_id.clone(),
+                identity: identity.clone(),
+                addr,
+                state: PeerState::Discovered,
+                connected_at: None,
+                last_seen: now,
+                latency_ms: None,
+            });
+        peer.identity = identity;
+        peer.addr = addr;
+        peer.last_seen = now;
+        if peer.state != PeerState::Connected {
+            peer.connected_at = Some(now);
+            peer.state = PeerState::Connected;
+        }
+    }
+
     /// Update the last-seen timestamp for a peer.
     pub fn update_last_seen(&mut self, peer_id: &PeerId) {
         if let Some(peer) = self.peers.get_mut(peer_id.as_str()) {
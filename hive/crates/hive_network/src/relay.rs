@@ -0,0 +1,1133 @@
+//! Relay and rendezvous — reaching peers on other networks.
+//!
+//! LAN discovery only finds peers on the same broadcast domain, and a
+//! `host:port` is of little use when the peer sits behind NAT. A node
+//! running with [`relay_mode`](crate::NetworkConfig::relay_mode) on a
+//! reachable host covers both cases:
+//!
+//! - **Rendezvous**: peers keep a control connection to every relay in
+//!   [`relays`](crate::NetworkConfig::relays) and register the port they
+//!   listen on. A lookup returns the addresses a peer can be dialed at,
+//!   including the public address the relay saw it connect from.
+//! - **Relaying**: when no address can be dialed directly, the relay
+//!   splices two WebSocket connections together at `/relay/{session}`. The
+//!   peers run the usual Noise handshake through the splice, so the relay
+//!   forwards only ciphertext and trust is checked end to end exactly as
+//!   for a direct connection. Neither side has to trust the relay itself.
+//!
+//! Relay control connections are kept apart from peer connections: they
+//! never receive broadcasts, and only [`MessageKind::Relay`] envelopes are
+//! accepted on them.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::stream::{SplitSink, SplitStream};
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpStream;
+use tokio::sync::{RwLock, broadcast, mpsc, oneshot};
+use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
+
+use crate::error::NetworkError;
+use crate::identity::PeerId;
+use crate::message::{Envelope, MessageKind};
+use crate::peer::PeerRegistry;
+use crate::router::MessageHandler;
+use crate::secure::{Expect, SecurityContext};
+use crate::transport::{self, Connections, Handshake, PeerConnection, TransportEvent};
+
+/// Path prefix under which a relay accepts tunnel legs.
+pub const RELAY_PATH: &str = "/relay/";
+
+/// Longest a direct dial may take before falling back to a relay.
+const DIRECT_DIAL_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Relay control messages, carried as [`MessageKind::Relay`] payloads.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RelayMessage {
+    /// Peer → relay: register for rendezvous and relayed connections.
+    Register { listen_addr: String },
+    /// Relay → peer: registration accepted.
+    Registered { observed_addr: String },
+    /// Peer → relay: where can `peer_id` be dialed?
+    Lookup { request_id: String, peer_id: PeerId },
+    /// Relay → peer: the addresses `peer_id` registered, and whether it is
+    /// connected to the relay right now.
+    Located {
+        request_id: String,
+        peer_id: PeerId,
+        addrs: Vec<String>,
+        online: bool,
+    },
+    /// Peer → relay: open a relayed connection to `target`.
+    Connect { request_id: String, target: PeerId },
+    /// Relay → requester: dial `/relay/{session}` and initiate the handshake.
+    Tunnel { request_id: String, session: String },
+    /// Relay → target: `from` wants to connect; dial `/relay/{session}` and
+    /// answer the handshake.
+    Incoming { session: String, from: PeerId },
+    /// Relay → peer: the request failed.
+    Error {
+        #[serde(default)]
+        request_id: Option<String>,
+        message: String,
+    },
+}
+
+impl RelayMessage {
+    /// The id of the request this message answers, if any.
+    pub fn request_id(&self) -> Option<&str> {
+        match self {
+            Self::Lookup { request_id, .. }
+            | Self::Located { request_id, .. }
+            | Self::Connect { request_id, .. }
+            | Self::Tunnel { request_id, .. } => Some(request_id),
+            Self::Error { request_id, .. } => request_id.as_deref(),
+            Self::Register { .. } | Self::Registered { .. } | Self::Incoming { .. } => None,
+        }
+    }
+
+    /// Wrap the message in an envelope addressed to `to`.
+    pub fn to_envelope(&self, from: PeerId, to: PeerId) -> Envelope {
+        Envelope::new(
+            from,
+            Some(to),
+            MessageKind::Relay,
+            serde_json::to_value(self).unwrap_or_default(),
+        )
+    }
+}
+
+/// A configured relay: `peer_id@host:port` pins the relay's key, a bare
+/// `host:port` accepts it only if it is on the trust list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayAddr {
+    pub peer_id: Option<PeerId>,
+    pub addr: String,
+}
+
+impl RelayAddr {
+    /// Parse a [`relays`](crate::NetworkConfig::relays) entry.
+    pub fn parse(entry: &str) -> Result<Self, NetworkError> {
+        let entry = entry.trim();
+        match entry.split_once('@') {
+            Some((peer_id, addr)) => {
+                let peer_id = PeerId::from_string(peer_id);
+                if peer_id.public_key().is_none() || addr.is_empty() {
+                    return Err(NetworkError::Relay(format!(
+                        "invalid relay entry '{entry}'"
+                    )));
+                }
+                Ok(Self {
+                    peer_id: Some(peer_id),
+                    addr: addr.to_string(),
+                })
+            }
+            None if !entry.is_empty() => Ok(Self {
+                peer_id: None,
+                addr: entry.to_string(),
+            }),
+            None => Err(NetworkError::Relay("empty relay entry".into())),
+        }
+    }
+}
+
+/// How [`HiveNode::dial_peer`](crate::HiveNode::dial_peer) reached a peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PeerRoute {
+    /// A connection to the peer was already open.
+    AlreadyConnected,
+    /// Dialed directly at this address.
+    Direct(String),
+    /// Tunnelled through the relay at this address.
+    Relayed(String),
+}
+
+/// Connection key of a tunnel through `relay`.
+fn tunnel_key(relay: &str, session: &str) -> String {
+    format!("relay://{relay}/{session}")
+}
+
+/// WebSocket URL of a tunnel leg.
+fn tunnel_url(relay: &str, session: &str) -> String {
+    format!("{}{RELAY_PATH}{session}", transport::ws_url(relay))
+}
+
+/// Addresses a registered peer can be dialed at: its listen address if it
+/// is routable, and its listen port on the address the relay observed.
+fn candidate_addrs(listen_addr: &str, observed: Option<SocketAddr>) -> Vec<String> {
+    let mut addrs = Vec::new();
+    let listen: Option<SocketAddr> = listen_addr.parse().ok();
+    if let Some(listen) = listen
+        && !listen.ip().is_unspecified()
+    {
+        addrs.push(listen.to_string());
+    }
+    if let (Some(listen), Some(observed)) = (listen, observed) {
+        let public = SocketAddr::new(observed.ip(), listen.port()).to_string();
+        if !addrs.contains(&public) {
+            addrs.push(public);
+        }
+    }
+    addrs
+}
+
+async fn is_connected(connections: &Connections, peer_id: &PeerId) -> bool {
+    connections
+        .read()
+        .await
+        .values()
+        .any(|conn| conn.peer_id() == peer_id)
+}
+
+// ---------------------------------------------------------------------------
+// Relay side
+// ---------------------------------------------------------------------------
+
+type Leg = WebSocketStream<TcpStream>;
+
+/// A tunnel created by a connect request, waiting for both legs.
+struct PendingTunnel {
+    expires: Instant,
+    leg: Option<Leg>,
+}
+
+/// Relay-side state: peer registrations and tunnels being set up.
+pub struct RelayHub {
+    peers: Arc<RwLock<PeerRegistry>>,
+    connections: Arc<Connections>,
+    registrations: Mutex<HashMap<PeerId, Vec<String>>>,
+    tunnels: Mutex<HashMap<String, PendingTunnel>>,
+    leg_timeout: Duration,
+}
+
+impl RelayHub {
+    pub(crate) fn new(
+        peers: Arc<RwLock<PeerRegistry>>,
+        connections: Arc<Connections>,
+        leg_timeout: Duration,
+    ) -> Self {
+        Self {
+            peers,
+            connections,
+            registrations: Mutex::new(HashMap::new()),
+            tunnels: Mutex::new(HashMap::new()),
+            leg_timeout,
+        }
+    }
+
+    /// Peers registered with this relay.
+    pub fn registered(&self) -> Vec<PeerId> {
+        self.registrations.lock().unwrap().keys().cloned().collect()
+    }
+
+    /// Answer a request from the connected peer `from`.
+    async fn handle(&self, from: &PeerId, local: &PeerId, message: RelayMessage) -> RelayMessage {
+        match message {
+            RelayMessage::Register { listen_addr } => {
+                let observed = self.peers.read().await.get_peer(from).map(|p| p.addr);
+                let addrs = candidate_addrs(&listen_addr, observed);
+                info!("Registered {} at {addrs:?}", from.short());
+                self.registrations
+                    .lock()
+                    .unwrap()
+                    .insert(from.clone(), addrs);
+                RelayMessage::Registered {
+                    observed_addr: observed.map(|a| a.to_string()).unwrap_or_default(),
+                }
+            }
+            RelayMessage::Lookup {
+                request_id,
+                peer_id,
+            } => {
+                let addrs = self.registrations.lock().unwrap().get(&peer_id).cloned();
+                match addrs {
+                    Some(addrs) => RelayMessage::Located {
+                        online: is_connected(&self.connections, &peer_id).await,
+                        request_id,
+                        peer_id,
+                        addrs,
+                    },
+                    None => RelayMessage::Error {
+                        request_id: Some(request_id),
+                        message: format!("{} is not registered", peer_id.short()),
+                    },
+                }
+            }
+            RelayMessage::Connect { request_id, target } => {
+                match self.open_tunnel(from, local, &target).await {
+                    Ok(session) => RelayMessage::Tunnel {
+                        request_id,
+                        session,
+                    },
+                    Err(message) => RelayMessage::Error {
+                        request_id: Some(request_id),
+                        message,
+                    },
+                }
+            }
+            other => RelayMessage::Error {
+                request_id: other.request_id().map(str::to_string),
+                message: "unexpected relay message".into(),
+            },
+        }
+    }
+
+    /// Create a tunnel session and tell `target` to join it.
+    async fn open_tunnel(
+        &self,
+        from: &PeerId,
+        local: &PeerId,
+        target: &PeerId,
+    ) -> Result<String, String> {
+        if !self.registrations.lock().unwrap().contains_key(target) {
+            return Err(format!("{} is not registered", target.short()));
+        }
+
+        let session = uuid::Uuid::new_v4().to_string();
+        {
+            let now = Instant::now();
+            let mut tunnels = self.tunnels.lock().unwrap();
+            tunnels.retain(|_, tunnel| tunnel.expires > now);
+            tunnels.insert(
+                session.clone(),
+                PendingTunnel {
+                    expires: now + self.leg_timeout,
+                    leg: None,
+                },
+            );
+        }
+
+        let incoming = RelayMessage::Incoming {
+            session: session.clone(),
+            from: from.clone(),
+        }
+        .to_envelope(local.clone(), target.clone());
+        let sent = {
+            let mut conns = self.connections.write().await;
+            match conns.values_mut().find(|conn| conn.peer_id() == target) {
+                Some(conn) => conn.send(&incoming).await.map_err(|e| e.to_string()),
+                None => Err(format!("{} is offline", target.short())),
+            }
+        };
+        if let Err(e) = sent {
+            self.tunnels.lock().unwrap().remove(&session);
+            return Err(e);
+        }
+
+        debug!(
+            "Opened tunnel {session} from {} to {}",
+            from.short(),
+            target.short()
+        );
+        Ok(session)
+    }
+
+    /// Accept one leg of a tunnel. The first leg waits for its partner; the
+    /// second splices the two together until either side closes.
+    pub(crate) async fn join(&self, session: &str, mut leg: Leg) {
+        let partner = {
+            let now = Instant::now();
+            let mut tunnels = self.tunnels.lock().unwrap();
+            tunnels.retain(|_, tunnel| tunnel.expires > now);
+            match tunnels.get_mut(session) {
+                None => None,
+                Some(tunnel) => match tunnel.leg.take() {
+                    Some(partner) => {
+                        tunnels.remove(session);
+                        Some(partner)
+                    }
+                    None => {
+                        tunnel.leg = Some(leg);
+                        return;
+                    }
+                },
+            }
+        };
+
+        match partner {
+            Some(partner) => {
+                debug!("Splicing tunnel {session}");
+                splice(partner, leg).await;
+                debug!("Tunnel {session} closed");
+            }
+            None => {
+                debug!("Refusing leg for unknown tunnel {session}");
+                let _ = leg.close(None).await;
+            }
+        }
+    }
+}
+
+/// Forward frames between two legs until either closes.
+async fn splice(a: Leg, b: Leg) {
+    let (a_tx, a_rx) = a.split();
+    let (b_tx, b_rx) = b.split();
+    tokio::select! {
+        _ = forward(a_rx, b_tx) => {}
+        _ = forward(b_rx, a_tx) => {}
+    }
+}
+
+async fn forward(mut rx: SplitStream<Leg>, mut tx: SplitSink<Leg, Message>) {
+    while let Some(Ok(msg)) = rx.next().await {
+        if let Message::Close(frame) = msg {
+            let _ = tx.send(Message::Close(frame)).await;
+            return;
+        }
+        if msg.is_binary() && tx.send(msg).await.is_err() {
+            return;
+        }
+    }
+    let _ = tx.send(Message::Close(None)).await;
+}
+
+// ---------------------------------------------------------------------------
+// Peer side
+// ---------------------------------------------------------------------------
+
+/// Peer-side relay state: control links to relays, outstanding requests,
+/// and what is needed to dial peers directly or through a relay.
+pub(crate) struct RelayClient {
+    local: PeerId,
+    listen_addr: String,
+    security: Arc<SecurityContext>,
+    peers: Arc<RwLock<PeerRegistry>>,
+    connections: Arc<Connections>,
+    event_tx: mpsc::Sender<TransportEvent>,
+    timeout: Duration,
+    links: RwLock<HashMap<String, PeerConnection>>,
+    waiters: Mutex<HashMap<String, oneshot::Sender<RelayMessage>>>,
+}
+
+impl RelayClient {
+    pub(crate) fn new(
+        security: Arc<SecurityContext>,
+        listen_addr: SocketAddr,
+        peers: Arc<RwLock<PeerRegistry>>,
+        connections: Arc<Connections>,
+        event_tx: mpsc::Sender<TransportEvent>,
+        timeout: Duration,
+    ) -> Self {
+        Self {
+            local: security.peer_id().clone(),
+            listen_addr: listen_addr.to_string(),
+            security,
+            peers,
+            connections,
+            event_tx,
+            timeout,
+            links: RwLock::new(HashMap::new()),
+            waiters: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether `key` is a relay control connection.
+    pub(crate) async fn is_link(&self, key: &str) -> bool {
+        self.links.read().await.contains_key(key)
+    }
+
+    /// Forget a relay control connection that closed.
+    pub(crate) async fn drop_link(&self, key: &str) -> bool {
+        let dropped = self.links.write().await.remove(key).is_some();
+        if dropped {
+            warn!("Lost connection to relay {key}");
+        }
+        dropped
+    }
+
+    /// Close every relay control connection.
+    pub(crate) async fn close_links(&self) {
+        let mut links = self.links.write().await;
+        for conn in links.values_mut() {
+            let _ = conn.close().await;
+        }
+        links.clear();
+    }
+
+    /// Peer ids of the relays we are registered with.
+    pub(crate) async fn relays(&self) -> Vec<PeerId> {
+        let links = self.links.read().await;
+        links.values().map(|conn| conn.peer_id().clone()).collect()
+    }
+
+    /// Connect and register with a relay.
+    pub(crate) async fn connect_relay(&self, relay: &RelayAddr) -> Result<PeerId, NetworkError> {
+        let expect = match &relay.peer_id {
+            Some(peer_id) => Expect::Pinned(peer_id),
+            None => Expect::Admitted,
+        };
+        let mut conn = transport::dial(
+            &transport::ws_url(&relay.addr),
+            &relay.addr,
+            &self.security,
+            expect,
+            Handshake::Initiate,
+            self.timeout,
+            self.event_tx.clone(),
+        )
+        .await?;
+
+        let relay_id = conn.peer_id().clone();
+        let register = RelayMessage::Register {
+            listen_addr: self.listen_addr.clone(),
+        };
+        conn.send(&register.to_envelope(self.local.clone(), relay_id.clone()))
+            .await?;
+        self.links.write().await.insert(relay.addr.clone(), conn);
+        Ok(relay_id)
+    }
+
+    /// Keep registered with `relays` and connected to `peers` (reached by
+    /// id through the relays), retrying every `interval` until shutdown.
+    pub(crate) async fn maintain(
+        self: Arc<Self>,
+        relays: Vec<RelayAddr>,
+        peers: Vec<PeerId>,
+        interval: Duration,
+        mut shutdown: broadcast::Receiver<()>,
+    ) {
+        loop {
+            for relay in &relays {
+                if self.is_link(&relay.addr).await {
+                    continue;
+                }
+                match self.connect_relay(relay).await {
+                    Ok(relay_id) => info!(
+                        "Registered with relay {} at {}",
+                        relay_id.short(),
+                        relay.addr
+                    ),
+                    Err(e) => warn!("Failed to register with relay {}: {e}", relay.addr),
+                }
+            }
+            for peer_id in &peers {
+                match self.dial_peer(peer_id).await {
+                    Ok(PeerRoute::AlreadyConnected) => {}
+                    Ok(route) => info!("Reached {} via {route:?}", peer_id.short()),
+                    Err(e) => debug!("Cannot reach {} yet: {e}", peer_id.short()),
+                }
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = shutdown.recv() => {
+                    debug!("Relay maintenance shutting down");
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Send `message` to the relay at `relay` and wait for its answer.
+    async fn request(
+        &self,
+        relay: &str,
+        message: RelayMessage,
+    ) -> Result<RelayMessage, NetworkError> {
+        let request_id = message
+            .request_id()
+            .expect("relay requests carry an id")
+            .to_string();
+        let (tx, rx) = oneshot::channel();
+        self.waiters.lock().unwrap().insert(request_id.clone(), tx);
+
+        let sent = {
+            let mut links = self.links.write().await;
+            match links.get_mut(relay) {
+                Some(link) => {
+                    let envelope = message.to_envelope(self.local.clone(), link.peer_id().clone());
+                    link.send(&envelope).await
+                }
+                None => Err(NetworkError::PeerNotFound(relay.to_string())),
+            }
+        };
+        let reply = match sent {
+            Ok(()) => match tokio::time::timeout(self.timeout, rx).await {
+                Ok(Ok(reply)) => Ok(reply),
+                Ok(Err(_)) => Err(NetworkError::Relay(format!("relay {relay} went away"))),
+                Err(_) => Err(NetworkError::Timeout(self.timeout)),
+            },
+            Err(e) => Err(e),
+        };
+        self.waiters.lock().unwrap().remove(&request_id);
+
+        match reply? {
+            RelayMessage::Error { message, .. } => Err(NetworkError::Relay(message)),
+            reply => Ok(reply),
+        }
+    }
+
+    /// Addresses the relays know `peer_id` by.
+    pub(crate) async fn locate(&self, peer_id: &PeerId) -> Vec<String> {
+        let relays: Vec<String> = self.links.read().await.keys().cloned().collect();
+        let mut addrs = Vec::new();
+        for relay in relays {
+            let lookup = RelayMessage::Lookup {
+                request_id: uuid::Uuid::new_v4().to_string(),
+                peer_id: peer_id.clone(),
+            };
+            match self.request(&relay, lookup).await {
+                Ok(RelayMessage::Located { addrs: found, .. }) => {
+                    for addr in found {
+                        if !addrs.contains(&addr) {
+                            addrs.push(addr);
+                        }
+                    }
+                }
+                Ok(other) => debug!("Unexpected lookup answer from {relay}: {other:?}"),
+                Err(e) => debug!("Lookup of {} via {relay} failed: {e}", peer_id.short()),
+            }
+        }
+        addrs
+    }
+
+    /// Reach `peer_id`: directly at an address the relays know, otherwise
+    /// through the first relay that can splice a connection.
+    pub(crate) async fn dial_peer(&self, peer_id: &PeerId) -> Result<PeerRoute, NetworkError> {
+        if is_connected(&self.connections, peer_id).await {
+            return Ok(PeerRoute::AlreadyConnected);
+        }
+
+        let mut last_error = None;
+        let direct_timeout = self.timeout.min(DIRECT_DIAL_TIMEOUT);
+        for addr in self.locate(peer_id).await {
+            let dialed = transport::connect_to_peer(
+                &addr,
+                &self.security,
+                None,
+                direct_timeout,
+                self.event_tx.clone(),
+            )
+            .await;
+            match dialed {
+                Ok(mut conn) if conn.peer_id() != peer_id => {
+                    let _ = conn.close().await;
+                    last_error = Some(NetworkError::Handshake(format!(
+                        "{addr} is {}, not {}",
+                        conn.peer_id().short(),
+                        peer_id.short()
+                    )));
+                }
+                Ok(conn) => {
+                    self.add_connection(addr.clone(), &addr, conn).await;
+                    return Ok(PeerRoute::Direct(addr));
+                }
+                Err(e) => {
+                    debug!("Direct dial of {} at {addr} failed: {e}", peer_id.short());
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        match (self.connect_relayed(peer_id).await, last_error) {
+            (Ok(route), _) => Ok(route),
+            (Err(e), None) => Err(e),
+            (Err(e), Some(direct)) => Err(NetworkError::Relay(format!(
+                "direct dial failed ({direct}), relay failed ({e})"
+            ))),
+        }
+    }
+
+    /// Reach `peer_id` through a relay without trying to dial it directly.
+    pub(crate) async fn connect_relayed(
+        &self,
+        peer_id: &PeerId,
+    ) -> Result<PeerRoute, NetworkError> {
+        if is_connected(&self.connections, peer_id).await {
+            return Ok(PeerRoute::AlreadyConnected);
+        }
+
+        let relays: Vec<String> = self.links.read().await.keys().cloned().collect();
+        let mut last_error = NetworkError::Relay("not registered with any relay".into());
+        for relay in relays {
+            match self.tunnel_to(&relay, peer_id).await {
+                Ok(()) => return Ok(PeerRoute::Relayed(relay)),
+                Err(e) => {
+                    debug!("Relaying to {} via {relay} failed: {e}", peer_id.short());
+                    last_error = e;
+                }
+            }
+        }
+        Err(last_error)
+    }
+
+    async fn tunnel_to(&self, relay: &str, peer_id: &PeerId) -> Result<(), NetworkError> {
+        let connect = RelayMessage::Connect {
+            request_id: uuid::Uuid::new_v4().to_string(),
+            target: peer_id.clone(),
+        };
+        let session = match self.request(relay, connect).await? {
+            RelayMessage::Tunnel { session, .. } => session,
+            other => {
+                return Err(NetworkError::Relay(format!(
+                    "unexpected answer from {relay}: {other:?}"
+                )));
+            }
+        };
+
+        let key = tunnel_key(relay, &session);
+        let mut conn = transport::dial(
+            &tunnel_url(relay, &session),
+            &key,
+            &self.security,
+            Expect::Admitted,
+            Handshake::Initiate,
+            self.timeout,
+            self.event_tx.clone(),
+        )
+        .await?;
+        if conn.peer_id() != peer_id {
+            let _ = conn.close().await;
+            return Err(NetworkError::Handshake(format!(
+                "relay {relay} connected us to {} instead of {}",
+                conn.peer_id().short(),
+                peer_id.short()
+            )));
+        }
+        self.add_connection(key, relay, conn).await;
+        Ok(())
+    }
+
+    /// Join a tunnel another peer opened to us.
+    async fn accept_tunnel(&self, relay: String, session: String, from: PeerId) {
+        let key = tunnel_key(&relay, &session);
+        let joined = transport::dial(
+            &tunnel_url(&relay, &session),
+            &key,
+            &self.security,
+            Expect::Admitted,
+            Handshake::Respond,
+            self.timeout,
+            self.event_tx.clone(),
+        )
+        .await;
+        match joined {
+            Ok(mut conn) if conn.peer_id() != &from => {
+                warn!(
+                    "Tunnel {session} announced {} but authenticated {}",
+                    from.short(),
+                    conn.peer_id().short()
+                );
+                let _ = conn.close().await;
+            }
+            Ok(conn) => {
+                info!(
+                    "Accepted relayed connection from {} via {relay}",
+                    from.short()
+                );
+                self.add_connection(key, &relay, conn).await;
+            }
+            Err(e) => warn!("Relayed connection from {} failed: {e}", from.short()),
+        }
+    }
+
+    /// Track a new peer connection; `addr` is recorded in the registry.
+    async fn add_connection(&self, key: String, addr: &str, conn: PeerConnection) {
+        let addr = addr
+            .trim_start_matches("ws://")
+            .trim_start_matches("wss://")
+            .parse()
+            .unwrap_or_else(|_| SocketAddr::from(([0, 0, 0, 0], 0)));
+        self.peers
+            .write()
+            .await
+            .mark_connected(conn.remote_identity().clone(), addr);
+        self.connections.write().await.insert(key, conn);
+    }
+
+    /// Handle a message a relay sent us.
+    async fn on_message(self: &Arc<Self>, relay_id: &PeerId, message: RelayMessage) {
+        match message {
+            RelayMessage::Registered { observed_addr } => {
+                debug!("Relay {} sees us at {observed_addr}", relay_id.short());
+            }
+            RelayMessage::Incoming { session, from } => {
+                if !self.security.admits(&from) {
+                    debug!(
+                        "Ignoring relayed connection from untrusted {}",
+                        from.short()
+                    );
+                    return;
+                }
+                let relay = {
+                    let links = self.links.read().await;
+                    links
+                        .iter()
+                        .find(|(_, conn)| conn.peer_id() == relay_id)
+                        .map(|(addr, _)| addr.clone())
+                };
+                let Some(relay) = relay else {
+                    warn!(
+                        "Tunnel offer from {}, which is not our relay",
+                        relay_id.short()
+                    );
+                    return;
+                };
+                let client = Arc::clone(self);
+                tokio::spawn(async move { client.accept_tunnel(relay, session, from).await });
+            }
+            reply => {
+                let waiter = reply
+                    .request_id()
+                    .and_then(|id| self.waiters.lock().unwrap().remove(id));
+                match waiter {
+                    Some(waiter) => {
+                        let _ = waiter.send(reply);
+                    }
+                    None => debug!("Unsolicited relay message: {reply:?}"),
+                }
+            }
+        }
+    }
+}
+
+/// Router handler for [`MessageKind::Relay`]: requests are answered by the
+/// hub (if this node is a relay), everything else goes to the client.
+pub(crate) fn relay_handler(
+    hub: Option<Arc<RelayHub>>,
+    client: Arc<RelayClient>,
+) -> MessageHandler {
+    Arc::new(move |envelope: Envelope| {
+        let hub = hub.clone();
+        let client = Arc::clone(&client);
+        Box::pin(async move {
+            let message: RelayMessage = match serde_json::from_value(envelope.payload) {
+                Ok(message) => message,
+                Err(e) => {
+                    warn!("Bad relay message from {}: {e}", envelope.from.short());
+                    return None;
+                }
+            };
+            match message {
+                RelayMessage::Register { .. }
+                | RelayMessage::Lookup { .. }
+                | RelayMessage::Connect { .. } => {
+                    let reply = match &hub {
+                        Some(hub) => hub.handle(&envelope.from, &client.local, message).await,
+                        None => RelayMessage::Error {
+                            request_id: message.request_id().map(str::to_string),
+                            message: "this node is not a relay".into(),
+                        },
+                    };
+                    Some(reply.to_envelope(client.local.clone(), envelope.from))
+                }
+                other => {
+                    client.on_message(&envelope.from, other).await;
+                    None
+                }
+            }
+        })
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NetworkConfig;
+    use crate::identity::NodeIdentity;
+    use crate::node::HiveNode;
+
+    #[test]
+    fn test_relay_message_roundtrip() {
+        let message = RelayMessage::Located {
+            request_id: "r1".into(),
+            peer_id: PeerId::generate(),
+            addrs: vec!["203.0.113.7:9470".into()],
+            online: true,
+        };
+        let json = serde_json::to_value(&message).unwrap();
+        assert_eq!(json["type"], "located");
+        let back: RelayMessage = serde_json::from_value(json).unwrap();
+        assert_eq!(back, message);
+        assert_eq!(back.request_id(), Some("r1"));
+
+        let error: RelayMessage =
+            serde_json::from_str(r#"{"type":"error","message":"nope"}"#).unwrap();
+        assert_eq!(error.request_id(), None);
+    }
+
+    #[test]
+    fn test_parse_relay_entries() {
+        let relay_id = PeerId::generate();
+        let pinned = RelayAddr::parse(&format!("{relay_id}@relay.example.com:9470")).unwrap();
+        assert_eq!(pinned.peer_id.as_ref(), Some(&relay_id));
+        assert_eq!(pinned.addr, "relay.example.com:9470");
+
+        let bare = RelayAddr::parse(" 10.0.0.1:9470 ").unwrap();
+        assert_eq!(bare.peer_id, None);
+        assert_eq!(bare.addr, "10.0.0.1:9470");
+
+        assert!(RelayAddr::parse("not-a-key@10.0.0.1:9470").is_err());
+        assert!(RelayAddr::parse("").is_err());
+    }
+
+    #[test]
+    fn test_candidate_addrs() {
+        let observed: SocketAddr = "203.0.113.7:51234".parse().unwrap();
+        assert_eq!(
+            candidate_addrs("0.0.0.0:9470", Some(observed)),
+            vec!["203.0.113.7:9470"]
+        );
+        assert_eq!(
+            candidate_addrs("192.168.1.5:9470", Some(observed)),
+            vec!["192.168.1.5:9470", "203.0.113.7:9470"]
+        );
+        assert_eq!(
+            candidate_addrs("127.0.0.1:9470", Some("127.0.0.1:40000".parse().unwrap())),
+            vec!["127.0.0.1:9470"]
+        );
+        assert!(candidate_addrs("garbage", Some(observed)).is_empty());
+    }
+
+    #[test]
+    fn test_tunnel_urls() {
+        assert_eq!(
+            tunnel_url("10.0.0.1:9470", "abc"),
+            "ws://10.0.0.1:9470/relay/abc"
+        );
+        assert_eq!(tunnel_url("ws://relay:1", "abc"), "ws://relay:1/relay/abc");
+        assert_eq!(tunnel_key("relay:1", "abc"), "relay://relay:1/abc");
+    }
+
+    fn free_addr() -> SocketAddr {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap()
+    }
+
+    fn config(listen_addr: SocketAddr) -> NetworkConfig {
+        NetworkConfig {
+            listen_addr,
+            discovery_enabled: false,
+            heartbeat_interval: Duration::from_millis(300),
+            connection_timeout: Duration::from_secs(2),
+            ..NetworkConfig::default()
+        }
+    }
+
+    async fn start_relay() -> (HiveNode, String) {
+        let addr = free_addr();
+        let mut relay = HiveNode::new(
+            NodeIdentity::generate("relay"),
+            NetworkConfig {
+                relay_mode: true,
+                allow_untrusted_peers: true,
+                ..config(addr)
+            },
+        );
+        relay.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let entry = format!("{}@{addr}", relay.peer_id());
+        (relay, entry)
+    }
+
+    fn peer(name: &str, listen_addr: SocketAddr, relay: &str) -> HiveNode {
+        HiveNode::new(
+            NodeIdentity::generate(name),
+            NetworkConfig {
+                relays: vec![relay.to_string()],
+                ..config(listen_addr)
+            },
+        )
+    }
+
+    fn trust_both(a: &HiveNode, b: &HiveNode) {
+        a.trust_peer(b.peer_id().clone(), "b");
+        b.trust_peer(a.peer_id().clone(), "a");
+    }
+
+    async fn wait_until<F, Fut>(mut check: F)
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = bool>,
+    {
+        for _ in 0..100 {
+            if check().await {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("condition not reached");
+    }
+
+    async fn wait_registered(relay: &HiveNode, count: usize) {
+        wait_until(|| async { relay.relay_registrations().len() == count }).await;
+    }
+
+    #[tokio::test]
+    async fn test_rendezvous_then_direct_dial() {
+        let (mut relay, entry) = start_relay().await;
+        let addr_b = free_addr();
+        let mut a = peer("a", free_addr(), &entry);
+        let mut b = peer("b", addr_b, &entry);
+        trust_both(&a, &b);
+        a.start().await.unwrap();
+        b.start().await.unwrap();
+        wait_registered(&relay, 2).await;
+
+        // The relay is pinned, not trusted, and never sees broadcasts.
+        assert_eq!(a.connected_relays().await, vec![relay.peer_id().clone()]);
+        assert!(!a.security().is_trusted(relay.peer_id()));
+        let hello = Envelope::broadcast(
+            a.peer_id().clone(),
+            MessageKind::Custom("hello".into()),
+            serde_json::json!({}),
+        );
+        assert_eq!(a.broadcast(&hello).await.unwrap(), 0);
+
+        assert_eq!(
+            a.locate_peer(b.peer_id()).await.unwrap(),
+            vec![addr_b.to_string()]
+        );
+        assert_eq!(
+            a.dial_peer(b.peer_id()).await.unwrap(),
+            PeerRoute::Direct(addr_b.to_string())
+        );
+        assert_eq!(
+            a.dial_peer(b.peer_id()).await.unwrap(),
+            PeerRoute::AlreadyConnected
+        );
+        assert_eq!(a.broadcast(&hello).await.unwrap(), 1);
+
+        a.stop().await;
+        b.stop().await;
+        relay.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_relayed_connection_when_direct_dial_fails() {
+        let (mut relay, entry) = start_relay().await;
+        let relay_addr = entry.split_once('@').unwrap().1.to_string();
+
+        // b registers a port nothing listens on, as if behind a NAT.
+        let blocker = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut b = peer("b", blocker.local_addr().unwrap(), &entry);
+        let mut a = peer("a", free_addr(), &entry);
+        trust_both(&a, &b);
+        b.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        drop(blocker);
+        a.start().await.unwrap();
+        wait_registered(&relay, 2).await;
+
+        let (pong_tx, mut pong_rx) = mpsc::channel(1);
+        b.on_message(
+            MessageKind::Custom("ping".into()),
+            Arc::new(|envelope: Envelope| {
+                Box::pin(async move {
+                    Some(Envelope::new(
+                        envelope.to.clone().unwrap(),
+                        Some(envelope.from),
+                        MessageKind::Custom("pong".into()),
+                        envelope.payload,
+                    ))
+                })
+            }),
+        )
+        .await;
+        a.on_message(
+            MessageKind::Custom("pong".into()),
+            Arc::new(move |envelope: Envelope| {
+                let pong_tx = pong_tx.clone();
+                Box::pin(async move {
+                    let _ = pong_tx.send(envelope.payload).await;
+                    None
+                })
+            }),
+        )
+        .await;
+
+        assert_eq!(
+            a.dial_peer(b.peer_id()).await.unwrap(),
+            PeerRoute::Relayed(relay_addr)
+        );
+        wait_until(|| async {
+            b.connected_peers()
+                .await
+                .iter()
+                .any(|p| &p.id == a.peer_id())
+        })
+        .await;
+
+        let ping = Envelope::new(
+            a.peer_id().clone(),
+            Some(b.peer_id().clone()),
+            MessageKind::Custom("ping".into()),
+            serde_json::json!({"n": 7}),
+        );
+        a.send_to_peer(b.peer_id(), &ping).await.unwrap();
+        let pong = tokio::time::timeout(Duration::from_secs(5), pong_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(pong["n"], 7);
+
+        a.stop().await;
+        b.stop().await;
+        relay.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_relay_does_not_bypass_trust() {
+        let (mut relay, entry) = start_relay().await;
+        let mut b = peer("b", free_addr(), &entry);
+        let mut stranger = peer("stranger", free_addr(), &entry);
+        stranger.trust_peer(b.peer_id().clone(), "b");
+        b.start().await.unwrap();
+        stranger.start().await.unwrap();
+        wait_registered(&relay, 2).await;
+
+        assert!(stranger.connect_relayed(b.peer_id()).await.is_err());
+        assert!(b.connected_peers().await.is_empty());
+
+        // Asking a node that is not a relay fails cleanly.
+        let unknown = PeerId::generate();
+        assert!(matches!(
+            stranger.connect_relayed(&unknown).await,
+            Err(NetworkError::Relay(_))
+        ));
+
+        b.stop().await;
+        stranger.stop().await;
+        relay.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_known_peer_ids_are_reached_through_relays() {
+        let (mut relay, entry) = start_relay().await;
+        let mut b = peer("b", free_addr(), &entry);
+        let mut a = HiveNode::new(
+            NodeIdentity::generate("a"),
+            NetworkConfig {
+                relays: vec![entry.clone()],
+                known_peers: vec![b.peer_id().to_string()],
+                ..config(free_addr())
+            },
+        );
+        trust_both(&a, &b);
+        b.start().await.unwrap();
+        a.start().await.unwrap();
+
+        wait_until(|| async {
+            a.connected_peers()
+                .await
+                .iter()
+                .any(|p| &p.id == b.peer_id())
+        })
+        .await;
+
+        a.stop().await;
+        b.stop().await;
+        relay.stop().await;
+    }
+}
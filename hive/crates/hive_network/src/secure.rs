@@ -0,0 +1,699 @@
+//! Secure sessions — mutually-authenticated, encrypted peer channels.
+//!
+//! Every connection runs a Noise XX handshake
+//! (`Noise_XX_25519_ChaChaPoly_SHA256`) over WebSocket binary frames before
+//! any envelope is exchanged. Noise static keys are per-process X25519 keys;
+//! each side binds its static key to its Ed25519 node identity by signing it
+//! inside the encrypted handshake payload, so a completed handshake proves
+//! the remote holds the key behind the [`PeerId`] it claims.
+//!
+//! The responder then decides whether to admit the initiator: trusted peers
+//! are accepted, unknown peers only with a valid pairing proof (see
+//! [`crate::trust`]) or when the node explicitly allows untrusted peers. It
+//! confirms acceptance with a first encrypted frame, so a rejected initiator
+//! sees a refused connection rather than a silently dead one.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use snow::StatelessTransportState;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tracing::{info, warn};
+
+use crate::error::NetworkError;
+use crate::identity::{NodeIdentity, NodeKeypair, PeerId, from_hex, to_hex};
+use crate::message::Envelope;
+use crate::trust::{PairingInvite, TrustStore, TrustedPeer, pairing_proof, verify_pairing_proof};
+
+/// Noise protocol used for every peer session.
+const NOISE_PARAMS: &str = "Noise_XX_25519_ChaChaPoly_SHA256";
+
+/// Domain separator for the signature binding a Noise static key to a node.
+const STATIC_KEY_CONTEXT: &[u8] = b"hive-noise-static:";
+
+/// Largest Noise message (ciphertext) allowed by the spec.
+const MAX_NOISE_MESSAGE: usize = 65_535;
+
+/// Poly1305 tag appended to every transport message.
+const TAG_LEN: usize = 16;
+
+/// Plaintext bytes per frame: one continuation flag byte plus data.
+const MAX_CHUNK: usize = MAX_NOISE_MESSAGE - TAG_LEN - 1;
+
+/// Largest reassembled message a peer may send. Frames past this drop the
+/// connection rather than growing the reassembly buffer without bound.
+const MAX_MESSAGE: usize = 16 * 1024 * 1024;
+
+/// Plaintext of the responder's acceptance frame.
+const ACCEPTED: &[u8] = b"accepted";
+
+/// Keys, trust list and pending pairing invites shared by all connections
+/// of one node.
+pub struct SecurityContext {
+    identity: NodeIdentity,
+    keypair: NodeKeypair,
+    noise_private: Vec<u8>,
+    noise_public: Vec<u8>,
+    trust: RwLock<TrustStore>,
+    trust_path: Option<PathBuf>,
+    invites: Mutex<Vec<PairingInvite>>,
+    allow_untrusted: bool,
+}
+
+/// Encrypted handshake payload identifying the sender.
+#[derive(Serialize, Deserialize)]
+struct HandshakePayload {
+    identity: NodeIdentity,
+    /// Ed25519 signature over [`STATIC_KEY_CONTEXT`] ‖ Noise static key.
+    static_signature: String,
+    /// HMAC of the handshake hash keyed by a pairing code.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pairing_proof: Option<String>,
+}
+
+impl SecurityContext {
+    /// Create a context for `identity`, which must carry its keypair.
+    pub fn new(
+        identity: &NodeIdentity,
+        trust: TrustStore,
+        allow_untrusted: bool,
+    ) -> Result<Self, NetworkError> {
+        let keypair = identity
+            .keypair()
+            .cloned()
+            .ok_or_else(|| NetworkError::Handshake("local identity has no keypair".into()))?;
+        let noise = noise_builder()
+            .generate_keypair()
+            .map_err(|e| NetworkError::Handshake(format!("Noise keygen failed: {e}")))?;
+        Ok(Self {
+            identity: identity.public(),
+            keypair,
+            noise_private: noise.private,
+            noise_public: noise.public,
+            trust: RwLock::new(trust),
+            trust_path: None,
+            invites: Mutex::new(Vec::new()),
+            allow_untrusted,
+        })
+    }
+
+    /// Persist trust list changes to `path`.
+    pub fn with_trust_path(mut self, path: PathBuf) -> Self {
+        self.trust_path = Some(path);
+        self
+    }
+
+    /// Our public identity.
+    pub fn identity(&self) -> &NodeIdentity {
+        &self.identity
+    }
+
+    /// Our peer id.
+    pub fn peer_id(&self) -> &PeerId {
+        &self.identity.peer_id
+    }
+
+    /// Whether peers off the trust list are admitted.
+    pub fn allows_untrusted(&self) -> bool {
+        self.allow_untrusted
+    }
+
+    /// Whether `peer_id` is on the trust list.
+    pub fn is_trusted(&self, peer_id: &PeerId) -> bool {
+        self.trust
+            .read()
+            .expect("trust lock poisoned")
+            .is_trusted(peer_id)
+    }
+
+    /// Whether a connection with `peer_id` would be admitted.
+    pub fn admits(&self, peer_id: &PeerId) -> bool {
+        self.allow_untrusted || self.is_trusted(peer_id)
+    }
+
+    /// Add a peer to the trust list (and persist it, if configured).
+    pub fn trust(&self, peer_id: PeerId, name: &str) {
+        let mut store = self.trust.write().expect("trust lock poisoned");
+        if store.trust(peer_id.clone(), name) {
+            info!("Trusting peer '{name}' ({})", peer_id.short());
+            self.persist(&store);
+        }
+    }
+
+    /// Remove a peer from the trust list. Returns `true` if it was present.
+    pub fn revoke(&self, peer_id: &PeerId) -> bool {
+        let mut store = self.trust.write().expect("trust lock poisoned");
+        let removed = store.revoke(peer_id);
+        if removed {
+            self.persist(&store);
+        }
+        removed
+    }
+
+    /// Snapshot of the trust list.
+    pub fn trusted_peers(&self) -> Vec<TrustedPeer> {
+        self.trust
+            .read()
+            .expect("trust lock poisoned")
+            .list()
+            .to_vec()
+    }
+
+    /// Issue a pairing invite that lets one unknown peer join within `ttl`.
+    pub fn create_invite(&self, addr: Option<String>, ttl: chrono::Duration) -> PairingInvite {
+        let invite = PairingInvite::new(self.identity.peer_id.clone(), addr, ttl);
+        let mut invites = self.invites.lock().expect("invite lock poisoned");
+        invites.retain(|i| !i.is_expired());
+        invites.push(invite.clone());
+        invite
+    }
+
+    /// Sign an envelope we originate; envelopes from other peers or already
+    /// signed ones pass through unchanged.
+    pub fn sign(&self, envelope: &Envelope) -> Envelope {
+        let mut envelope = envelope.clone();
+        if envelope.signature.is_none() && envelope.from == self.identity.peer_id {
+            envelope.sign(&self.keypair);
+        }
+        envelope
+    }
+
+    /// Consume the pending invite whose code produced `proof`, if any.
+    fn redeem_invite(&self, handshake_hash: &[u8], proof: &[u8]) -> bool {
+        let mut invites = self.invites.lock().expect("invite lock poisoned");
+        invites.retain(|i| !i.is_expired());
+        match invites
+            .iter()
+            .position(|i| verify_pairing_proof(&i.code, handshake_hash, proof))
+        {
+            Some(pos) => {
+                invites.remove(pos);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn persist(&self, store: &TrustStore) {
+        if let Some(path) = &self.trust_path
+            && let Err(e) = store.save_to_file(path)
+        {
+            warn!("Failed to persist trust store: {e}");
+        }
+    }
+
+    /// Our handshake payload, optionally carrying a pairing proof.
+    fn payload(&self, pairing_proof: Option<Vec<u8>>) -> Result<Vec<u8>, NetworkError> {
+        let mut signed = STATIC_KEY_CONTEXT.to_vec();
+        signed.extend_from_slice(&self.noise_public);
+        let payload = HandshakePayload {
+            identity: self.identity.clone(),
+            static_signature: to_hex(&self.keypair.sign(&signed)),
+            pairing_proof: pairing_proof.map(|p| to_hex(&p)),
+        };
+        Ok(serde_json::to_vec(&payload)?)
+    }
+}
+
+/// An authenticated, encrypted session with a peer.
+pub(crate) struct Session {
+    /// The remote node's verified identity.
+    pub remote: NodeIdentity,
+    pub sealer: FrameSealer,
+    pub opener: FrameOpener,
+}
+
+/// Encrypts outgoing plaintext into Noise transport frames.
+pub(crate) struct FrameSealer {
+    transport: Arc<StatelessTransportState>,
+    nonce: u64,
+}
+
+impl FrameSealer {
+    /// Encrypt `plaintext`, splitting it across as many frames as needed.
+    pub fn seal(&mut self, plaintext: &[u8]) -> Result<Vec<Vec<u8>>, NetworkError> {
+        let mut frames = Vec::new();
+        let mut chunks = plaintext.chunks(MAX_CHUNK).peekable();
+        if chunks.peek().is_none() {
+            return Ok(vec![self.seal_chunk(&[0])?]);
+        }
+        while let Some(chunk) = chunks.next() {
+            let mut buf = Vec::with_capacity(chunk.len() + 1);
+            buf.push(u8::from(chunks.peek().is_some()));
+            buf.extend_from_slice(chunk);
+            frames.push(self.seal_chunk(&buf)?);
+        }
+        Ok(frames)
+    }
+
+    fn seal_chunk(&mut self, chunk: &[u8]) -> Result<Vec<u8>, NetworkError> {
+        let mut out = vec![0u8; chunk.len() + TAG_LEN];
+        let len = self
+            .transport
+            .write_message(self.nonce, chunk, &mut out)
+            .map_err(|e| NetworkError::Transport(format!("Encrypt error: {e}")))?;
+        self.nonce += 1;
+        out.truncate(len);
+        Ok(out)
+    }
+}
+
+/// Decrypts incoming Noise transport frames and reassembles messages.
+pub(crate) struct FrameOpener {
+    transport: Arc<StatelessTransportState>,
+    nonce: u64,
+    partial: Vec<u8>,
+}
+
+impl FrameOpener {
+    /// Decrypt one frame. Returns the full message once its last frame
+    /// arrives.
+    pub fn open(&mut self, frame: &[u8]) -> Result<Option<Vec<u8>>, NetworkError> {
+        let mut buf = vec![0u8; frame.len()];
+        let len = self
+            .transport
+            .read_message(self.nonce, frame, &mut buf)
+            .map_err(|e| NetworkError::Transport(format!("Decrypt error: {e}")))?;
+        self.nonce += 1;
+        let (&more, data) = buf[..len]
+            .split_first()
+            .ok_or_else(|| NetworkError::Transport("empty frame".into()))?;
+        if self.partial.len() + data.len() > MAX_MESSAGE {
+            self.partial = Vec::new();
+            return Err(NetworkError::Transport(format!(
+                "message exceeds {MAX_MESSAGE} bytes"
+            )));
+        }
+        self.partial.extend_from_slice(data);
+        if more == 0 {
+            Ok(Some(std::mem::take(&mut self.partial)))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Which remote an outbound handshake accepts.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Expect<'a> {
+    /// Any peer the node admits.
+    Admitted,
+    /// The issuer of a pairing invite, which becomes trusted on success.
+    Invite(&'a PairingInvite),
+    /// Exactly this peer, trusted or not. Used for relays, which carry only
+    /// end-to-end encrypted traffic and are never added to the trust list.
+    Pinned(&'a PeerId),
+}
+
+impl<'a> Expect<'a> {
+    /// Accept the invite's issuer if given, otherwise any admitted peer.
+    pub fn from_invite(invite: Option<&'a PairingInvite>) -> Self {
+        invite.map_or(Self::Admitted, Self::Invite)
+    }
+}
+
+/// Run the initiator side of the handshake, accepting only the remote
+/// described by `expect`.
+pub(crate) async fn initiate<S>(
+    ws: &mut WebSocketStream<S>,
+    ctx: &SecurityContext,
+    expect: Expect<'_>,
+) -> Result<Session, NetworkError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut hs = noise_builder()
+        .local_private_key(&ctx.noise_private)
+        .build_initiator()
+        .map_err(noise_error)?;
+    let mut buf = vec![0u8; MAX_NOISE_MESSAGE];
+
+    // -> e
+    let len = hs.write_message(&[], &mut buf).map_err(noise_error)?;
+    send_binary(ws, &buf[..len]).await?;
+
+    // <- e, ee, s, es
+    let msg = recv_binary(ws).await?;
+    let mut payload = vec![0u8; MAX_NOISE_MESSAGE];
+    let len = hs.read_message(&msg, &mut payload).map_err(noise_error)?;
+    let remote_static = hs.get_remote_static().map(<[u8]>::to_vec);
+    let (remote, _) = verify_payload(&payload[..len], remote_static.as_deref())?;
+    let handshake_hash = hs.get_handshake_hash().to_vec();
+
+    let invite = match expect {
+        Expect::Invite(invite) if remote.peer_id != invite.peer_id => {
+            return Err(NetworkError::Pairing(format!(
+                "peer {} did not issue this invite",
+                remote.peer_id.short()
+            )));
+        }
+        Expect::Invite(invite) if invite.is_expired() => {
+            return Err(NetworkError::Pairing("invite expired".into()));
+        }
+        Expect::Invite(invite) => Some(invite),
+        Expect::Pinned(expected) if &remote.peer_id != expected => {
+            let _ = ws.close(Some(untrusted_close())).await;
+            return Err(NetworkError::Untrusted(format!(
+                "{} is not the expected peer {}",
+                remote.peer_id.short(),
+                expected.short()
+            )));
+        }
+        Expect::Admitted if !ctx.admits(&remote.peer_id) => {
+            let _ = ws.close(Some(untrusted_close())).await;
+            return Err(NetworkError::Untrusted(remote.peer_id.to_string()));
+        }
+        Expect::Pinned(_) | Expect::Admitted => None,
+    };
+
+    // -> s, se
+    let proof = invite.map(|i| pairing_proof(&i.code, &handshake_hash));
+    let len = hs
+        .write_message(&ctx.payload(proof)?, &mut buf)
+        .map_err(noise_error)?;
+    send_binary(ws, &buf[..len]).await?;
+
+    let transport = Arc::new(hs.into_stateless_transport_mode().map_err(noise_error)?);
+    let mut opener = FrameOpener {
+        transport: Arc::clone(&transport),
+        nonce: 0,
+        partial: Vec::new(),
+    };
+    let confirmation = recv_binary(ws).await?;
+    if opener.open(&confirmation)?.as_deref() != Some(ACCEPTED) {
+        return Err(NetworkError::Handshake("missing acceptance".into()));
+    }
+
+    if invite.is_some() {
+        ctx.trust(remote.peer_id.clone(), &remote.name);
+    }
+
+    Ok(Session {
+        remote,
+        sealer: FrameSealer {
+            transport,
+            nonce: 0,
+        },
+        opener,
+    })
+}
+
+/// Run the responder side of the handshake, admitting the initiator only if
+/// it is trusted, presents a valid pairing proof, or untrusted peers are
+/// allowed.
+pub(crate) async fn respond<S>(
+    ws: &mut WebSocketStream<S>,
+    ctx: &SecurityContext,
+) -> Result<Session, NetworkError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut hs = noise_builder()
+        .local_private_key(&ctx.noise_private)
+        .build_responder()
+        .map_err(noise_error)?;
+    let mut buf = vec![0u8; MAX_NOISE_MESSAGE];
+    let mut payload = vec![0u8; MAX_NOISE_MESSAGE];
+
+    // -> e
+    let msg = recv_binary(ws).await?;
+    hs.read_message(&msg, &mut payload).map_err(noise_error)?;
+
+    // <- e, ee, s, es
+    let len = hs
+        .write_message(&ctx.payload(None)?, &mut buf)
+        .map_err(noise_error)?;
+    send_binary(ws, &buf[..len]).await?;
+    let handshake_hash = hs.get_handshake_hash().to_vec();
+
+    // -> s, se
+    let msg = recv_binary(ws).await?;
+    let len = hs.read_message(&msg, &mut payload).map_err(noise_error)?;
+    let remote_static = hs.get_remote_static().map(<[u8]>::to_vec);
+    let (remote, proof) = verify_payload(&payload[..len], remote_static.as_deref())?;
+
+    let trusted = ctx.is_trusted(&remote.peer_id);
+    let paired = !trusted && proof.is_some_and(|p| ctx.redeem_invite(&handshake_hash, &p));
+    if paired {
+        ctx.trust(remote.peer_id.clone(), &remote.name);
+    }
+    if !(trusted || paired || ctx.allow_untrusted) {
+        let _ = ws.close(Some(untrusted_close())).await;
+        return Err(NetworkError::Untrusted(remote.peer_id.to_string()));
+    }
+
+    let transport = Arc::new(hs.into_stateless_transport_mode().map_err(noise_error)?);
+    let mut sealer = FrameSealer {
+        transport: Arc::clone(&transport),
+        nonce: 0,
+    };
+    for frame in sealer.seal(ACCEPTED)? {
+        send_binary(ws, &frame).await?;
+    }
+
+    Ok(Session {
+        remote,
+        sealer,
+        opener: FrameOpener {
+            transport,
+            nonce: 0,
+            partial: Vec::new(),
+        },
+    })
+}
+
+/// Check the remote's payload: its static key must be signed by the
+/// Ed25519 key behind its claimed peer id.
+fn verify_payload(
+    bytes: &[u8],
+    remote_static: Option<&[u8]>,
+) -> Result<(NodeIdentity, Option<Vec<u8>>), NetworkError> {
+    let payload: HandshakePayload = serde_json::from_slice(bytes)
+        .map_err(|e| NetworkError::Handshake(format!("bad payload: {e}")))?;
+    let remote_static =
+        remote_static.ok_or_else(|| NetworkError::Handshake("no remote static key".into()))?;
+    let signature = from_hex(&payload.static_signature)
+        .ok_or_else(|| NetworkError::Handshake("malformed static key signature".into()))?;
+
+    let mut signed = STATIC_KEY_CONTEXT.to_vec();
+    signed.extend_from_slice(remote_static);
+    if !payload.identity.peer_id.verify(&signed, &signature) {
+        return Err(NetworkError::Handshake(format!(
+            "static key not signed by claimed peer {}",
+            payload.identity.peer_id.short()
+        )));
+    }
+
+    let proof = payload.pairing_proof.as_deref().and_then(from_hex);
+    Ok((payload.identity.public(), proof))
+}
+
+fn noise_builder() -> snow::Builder<'static> {
+    snow::Builder::new(NOISE_PARAMS.parse().expect("valid Noise parameters"))
+}
+
+fn noise_error(e: snow::Error) -> NetworkError {
+    NetworkError::Handshake(e.to_string())
+}
+
+fn untrusted_close() -> CloseFrame {
+    CloseFrame {
+        code: CloseCode::Policy,
+        reason: "untrusted peer".into(),
+    }
+}
+
+async fn send_binary<S>(ws: &mut WebSocketStream<S>, data: &[u8]) -> Result<(), NetworkError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    ws.send(Message::Binary(data.to_vec().into()))
+        .await
+        .map_err(|e| NetworkError::Transport(format!("Send error: {e}")))
+}
+
+/// Receive the next binary frame, treating a close as a refusal.
+async fn recv_binary<S>(ws: &mut WebSocketStream<S>) -> Result<Vec<u8>, NetworkError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    while let Some(msg) = ws.next().await {
+        match msg.map_err(|e| NetworkError::Transport(format!("Read error: {e}")))? {
+            Message::Binary(data) => return Ok(data.to_vec()),
+            Message::Close(frame) => {
+                let reason = frame
+                    .map(|f| f.reason.to_string())
+                    .unwrap_or_else(|| "closed during handshake".into());
+                return Err(NetworkError::ConnectionRefused(reason));
+            }
+            Message::Ping(_) | Message::Pong(_) => {}
+            _ => return Err(NetworkError::Handshake("unexpected text frame".into())),
+        }
+    }
+    Err(NetworkError::Transport(
+        "connection closed during handshake".into(),
+    ))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_tungstenite::{MaybeTlsStream, accept_async, connect_async};
+
+    fn context(name: &str, allow_untrusted: bool) -> SecurityContext {
+        SecurityContext::new(
+            &NodeIdentity::generate(name),
+            TrustStore::new(),
+            allow_untrusted,
+        )
+        .unwrap()
+    }
+
+    /// Run both handshake sides over a loopback WebSocket.
+    async fn handshake(
+        client: Arc<SecurityContext>,
+        server: Arc<SecurityContext>,
+        invite: Option<PairingInvite>,
+    ) -> (
+        Result<(Session, WebSocketStream<MaybeTlsStream<TcpStream>>), NetworkError>,
+        Result<Session, NetworkError>,
+    ) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server_task = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(stream).await.unwrap();
+            let session = respond(&mut ws, &server).await;
+            // Keep the socket open until the client has read the outcome.
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            session
+        });
+
+        let (mut ws, _) = connect_async(format!("ws://{addr}")).await.unwrap();
+        let client_result = initiate(&mut ws, &client, Expect::from_invite(invite.as_ref()))
+            .await
+            .map(|s| (s, ws));
+        (client_result, server_task.await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_trusted_peers_exchange_encrypted_frames() {
+        let a = Arc::new(context("a", false));
+        let b = Arc::new(context("b", false));
+        a.trust(b.peer_id().clone(), "b");
+        b.trust(a.peer_id().clone(), "a");
+
+        let (client, server) = handshake(a.clone(), b.clone(), None).await;
+        let (mut client, _ws) = client.unwrap();
+        let mut server = server.unwrap();
+        assert_eq!(&client.remote.peer_id, b.peer_id());
+        assert_eq!(&server.remote.peer_id, a.peer_id());
+
+        // Large messages span several frames and reassemble in order.
+        let big = vec![7u8; MAX_CHUNK * 2 + 10];
+        let frames = client.sealer.seal(&big).unwrap();
+        assert_eq!(frames.len(), 3);
+        assert!(frames.iter().all(|f| f.len() <= MAX_NOISE_MESSAGE));
+        assert_eq!(server.opener.open(&frames[0]).unwrap(), None);
+        assert_eq!(server.opener.open(&frames[1]).unwrap(), None);
+        assert_eq!(server.opener.open(&frames[2]).unwrap(), Some(big));
+
+        let reply = server.sealer.seal(b"hi").unwrap();
+        assert_eq!(
+            client.opener.open(&reply[0]).unwrap().as_deref(),
+            Some(&b"hi"[..])
+        );
+
+        // Replayed frames fail to decrypt.
+        assert!(client.opener.open(&reply[0]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_oversized_message_rejected() {
+        let a = Arc::new(context("a", false));
+        let b = Arc::new(context("b", false));
+        a.trust(b.peer_id().clone(), "b");
+        b.trust(a.peer_id().clone(), "a");
+
+        let (client, server) = handshake(a, b, None).await;
+        let (mut client, _ws) = client.unwrap();
+        let mut server = server.unwrap();
+
+        let frames = client.sealer.seal(&vec![1u8; MAX_MESSAGE + 1]).unwrap();
+        let (last, rest) = frames.split_last().unwrap();
+        for frame in rest {
+            assert_eq!(server.opener.open(frame).unwrap(), None);
+        }
+        assert!(server.opener.open(last).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_peer_rejected_by_default() {
+        let a = Arc::new(context("a", true));
+        let b = Arc::new(context("b", false));
+
+        let (client, server) = handshake(a, b, None).await;
+        assert!(matches!(client, Err(NetworkError::ConnectionRefused(_))));
+        assert!(matches!(server, Err(NetworkError::Untrusted(_))));
+    }
+
+    #[tokio::test]
+    async fn test_pairing_invite_trusts_both_sides() {
+        let joiner = Arc::new(context("joiner", false));
+        let host = Arc::new(context("host", false));
+        let invite = host.create_invite(None, chrono::Duration::minutes(5));
+
+        let (client, server) = handshake(joiner.clone(), host.clone(), Some(invite.clone())).await;
+        assert!(client.is_ok());
+        assert!(server.is_ok());
+        assert!(joiner.is_trusted(host.peer_id()));
+        assert!(host.is_trusted(joiner.peer_id()));
+
+        // Invites are single-use.
+        let stranger = Arc::new(context("stranger", false));
+        let (client, server) = handshake(stranger, host, Some(invite)).await;
+        assert!(client.is_err());
+        assert!(matches!(server, Err(NetworkError::Untrusted(_))));
+    }
+
+    #[tokio::test]
+    async fn test_invite_pins_issuer() {
+        let joiner = Arc::new(context("joiner", false));
+        let host = Arc::new(context("host", false));
+        let impostor = Arc::new(context("impostor", true));
+        let invite = host.create_invite(None, chrono::Duration::minutes(5));
+
+        let (client, _) = handshake(joiner.clone(), impostor, Some(invite)).await;
+        assert!(matches!(client, Err(NetworkError::Pairing(_))));
+        assert!(joiner.trusted_peers().is_empty());
+    }
+
+    #[test]
+    fn test_sign_only_our_unsigned_envelopes() {
+        let ctx = context("a", false);
+        let ours = Envelope::broadcast(
+            ctx.peer_id().clone(),
+            crate::message::MessageKind::Heartbeat,
+            serde_json::json!({}),
+        );
+        assert!(ctx.sign(&ours).verify_signature());
+
+        let theirs = Envelope::broadcast(
+            PeerId::generate(),
+            crate::message::MessageKind::Heartbeat,
+            serde_json::json!({}),
+        );
+        assert!(ctx.sign(&theirs).signature.is_none());
+    }
+}
@@ -3,10 +3,15 @@
 //! Provides helper types that create typed envelopes for specific sync
 //! operations. These are thin wrappers making it easy for other crates
 //! to build the right message payloads.
+//!
+//! Channel and state sync compare wall-clock timestamps and revisions, so
+//! they lose edits under clock skew or concurrent changes. Shared documents
+//! should replicate through [`CrdtSyncHelper`] instead.
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::crdt::{Op, Snapshot, VectorClock};
 use crate::identity::PeerId;
 use crate::message::{Envelope, MessageKind};
 
@@ -113,6 +118,9 @@ pub struct FleetLearnPayload {
 }
 
 /// Helper for building fleet learning envelopes.
+///
+/// Payloads are sent exactly as given. Raw patterns can contain prompt text
+/// and project details, so share scrubbed, aggregated statistics instead.
 pub struct FleetSyncHelper;
 
 impl FleetSyncHelper {
@@ -219,6 +227,57 @@ impl StateSyncHelper {
     }
 }
 
+// ---------------------------------------------------------------------------
+// CRDT sync
+// ---------------------------------------------------------------------------
+
+/// Ops for one replicated document, plus the sender's clock for it.
+///
+/// The receiver applies `ops`, then answers with whatever the sender's
+/// `clock` shows it lacks. A delta with no ops is a digest: "this is what I
+/// have, send me the rest". When the sender has compacted ops the receiver
+/// lacks, it sends a `snapshot` of the document instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrdtDelta {
+    pub doc_id: String,
+    pub clock: VectorClock,
+    #[serde(default)]
+    pub ops: Vec<Op>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<Snapshot>,
+}
+
+/// Payload of a [`MessageKind::CrdtSync`] envelope.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CrdtSyncPayload {
+    pub docs: Vec<CrdtDelta>,
+    /// The sender listed every document it holds, so the receiver should
+    /// also send documents missing from the list.
+    #[serde(default)]
+    pub complete: bool,
+}
+
+/// Helper for building CRDT sync envelopes.
+pub struct CrdtSyncHelper;
+
+impl CrdtSyncHelper {
+    /// Create a sync envelope for a peer, or for every peer if `to` is `None`.
+    pub fn sync(from: PeerId, to: Option<PeerId>, payload: &CrdtSyncPayload) -> Envelope {
+        Envelope::new(
+            from,
+            to,
+            MessageKind::CrdtSync,
+            serde_json::to_value(payload).unwrap_or_default(),
+        )
+    }
+
+    /// Parse a CRDT sync payload from an envelope.
+    pub fn parse_payload(envelope: &Envelope) -> Result<CrdtSyncPayload, String> {
+        serde_json::from_value(envelope.payload.clone())
+            .map_err(|e| format!("Invalid CrdtSync payload: {e}"))
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -337,4 +396,30 @@ mod tests {
         assert_eq!(deserialized.outcome_type, "test");
         assert!((deserialized.confidence - 0.85).abs() < f64::EPSILON);
     }
+
+    #[test]
+    fn test_crdt_sync_roundtrip() {
+        let mut doc = crate::crdt::CrdtDoc::new("spec:auth", "peer-a");
+        doc.set("title", serde_json::json!("Auth"));
+
+        let envelope = CrdtSyncHelper::sync(
+            PeerId::from_string("peer-a"),
+            None,
+            &CrdtSyncPayload {
+                docs: vec![CrdtDelta {
+                    doc_id: doc.id().to_string(),
+                    clock: doc.clock().clone(),
+                    ops: doc.delta_since(&VectorClock::new()),
+                    snapshot: None,
+                }],
+                complete: false,
+            },
+        );
+        assert_eq!(envelope.kind, MessageKind::CrdtSync);
+
+        let payload = CrdtSyncHelper::parse_payload(&envelope).unwrap();
+        let mut replica = crate::crdt::CrdtDoc::new("spec:auth", "peer-b");
+        replica.apply_all(payload.docs[0].ops.clone());
+        assert!(replica.same_state(&doc));
+    }
 }
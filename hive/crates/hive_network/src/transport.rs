@@ -1,23 +1,38 @@
 //! WebSocket transport — server and client connections.
 //!
 //! Provides the low-level WebSocket plumbing for peer-to-peer communication.
-//! The server accepts incoming connections and forwards received envelopes
-//! into an mpsc channel. The client connects to a remote peer and returns a
-//! [`PeerConnection`] handle.
+//! Every connection first completes a secure handshake (see
+//! [`crate::secure`]); afterwards envelopes travel as encrypted binary
+//! frames. The server accepts incoming connections and forwards received
+//! envelopes into an mpsc channel. The client connects to a remote peer and
+//! returns a [`PeerConnection`] handle.
+//!
+//! Connections are keyed by a string: the socket address for inbound
+//! connections, the dialed address for outbound ones, and
+//! `relay://{relay}/{session}` for connections tunnelled through a relay
+//! (see [`crate::relay`]).
 
+use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
 
-use futures::stream::SplitSink;
+use futures::stream::{SplitSink, SplitStream};
 use futures::{SinkExt, StreamExt};
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::mpsc;
+use tokio::sync::{RwLock, mpsc};
 use tokio_tungstenite::tungstenite::Message;
-use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, accept_async, connect_async};
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, accept_hdr_async, connect_async};
 use tracing::{debug, error, info, warn};
 
 use crate::error::NetworkError;
-use crate::identity::PeerId;
+use crate::identity::{NodeIdentity, PeerId};
 use crate::message::Envelope;
+use crate::relay::{RELAY_PATH, RelayHub};
+use crate::secure::{self, Expect, FrameOpener, FrameSealer, SecurityContext};
+use crate::trust::PairingInvite;
 
 /// Type alias for the write half of a server-side WebSocket.
 type ServerWsSink = SplitSink<WebSocketStream<TcpStream>, Message>;
@@ -25,14 +40,20 @@ type ServerWsSink = SplitSink<WebSocketStream<TcpStream>, Message>;
 /// Type alias for the write half of a client-side WebSocket.
 type ClientWsSink = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
 
-/// A handle to an active WebSocket connection with a peer.
+/// Active connections keyed by connection key.
+pub(crate) type Connections = RwLock<HashMap<String, PeerConnection>>;
+
+/// A handle to an active, authenticated connection with a peer.
 ///
 /// Wraps the write-half of the WebSocket stream, providing a simple
-/// `send(envelope)` API. The read-half is consumed by a background task
-/// that forwards incoming envelopes to the node's central channel.
+/// `send(envelope)` API that signs and encrypts each envelope. The read-half
+/// is consumed by a background task that forwards incoming envelopes to the
+/// node's central channel.
 pub struct PeerConnection {
-    peer_id: PeerId,
+    remote: NodeIdentity,
     sink: PeerSink,
+    sealer: FrameSealer,
+    security: Arc<SecurityContext>,
 }
 
 /// The write side can be either a server-accepted or client-initiated socket.
@@ -42,43 +63,36 @@ enum PeerSink {
 }
 
 impl PeerConnection {
-    /// Create a connection wrapping a server-accepted WebSocket sink.
-    pub fn from_server(peer_id: PeerId, sink: ServerWsSink) -> Self {
-        Self {
-            peer_id,
-            sink: PeerSink::Server(sink),
-        }
-    }
-
-    /// Create a connection wrapping a client-initiated WebSocket sink.
-    pub fn from_client(peer_id: PeerId, sink: ClientWsSink) -> Self {
-        Self {
-            peer_id,
-            sink: PeerSink::Client(sink),
-        }
+    /// The authenticated peer ID this connection is associated with.
+    pub fn peer_id(&self) -> &PeerId {
+        &self.remote.peer_id
     }
 
-    /// The peer ID this connection is associated with.
-    pub fn peer_id(&self) -> &PeerId {
-        &self.peer_id
+    /// The remote node's verified identity.
+    pub fn remote_identity(&self) -> &NodeIdentity {
+        &self.remote
     }
 
-    /// Send an envelope over the WebSocket connection.
+    /// Sign (if we originated it) and send an envelope over the connection.
     pub async fn send(&mut self, envelope: &Envelope) -> Result<(), NetworkError> {
-        let json = envelope
+        let json = self
+            .security
+            .sign(envelope)
             .to_json()
             .map_err(|e| NetworkError::Transport(format!("Serialize error: {e}")))?;
 
-        let msg = Message::Text(json.into());
-        match &mut self.sink {
-            PeerSink::Server(sink) => sink
-                .send(msg)
-                .await
-                .map_err(|e| NetworkError::Transport(format!("Send error: {e}")))?,
-            PeerSink::Client(sink) => sink
-                .send(msg)
-                .await
-                .map_err(|e| NetworkError::Transport(format!("Send error: {e}")))?,
+        for frame in self.sealer.seal(json.as_bytes())? {
+            let msg = Message::Binary(frame.into());
+            match &mut self.sink {
+                PeerSink::Server(sink) => sink
+                    .send(msg)
+                    .await
+                    .map_err(|e| NetworkError::Transport(format!("Send error: {e}")))?,
+                PeerSink::Client(sink) => sink
+                    .send(msg)
+                    .await
+                    .map_err(|e| NetworkError::Transport(format!("Send error: {e}")))?,
+            }
         }
         Ok(())
     }
@@ -101,33 +115,40 @@ impl PeerConnection {
 /// An incoming event from the transport layer.
 #[derive(Debug)]
 pub enum TransportEvent {
-    /// A new inbound connection was accepted from the given address.
+    /// A new inbound connection was accepted and authenticated.
     InboundConnection {
         addr: SocketAddr,
-        peer_id: PeerId,
+        identity: NodeIdentity,
     },
-    /// An envelope was received from a peer.
+    /// An envelope was received from a peer. `from_addr` is the key of the
+    /// connection it arrived on.
     Message {
-        from_addr: SocketAddr,
+        from_addr: String,
         envelope: Envelope,
     },
-    /// A peer disconnected.
+    /// The connection with key `addr` closed.
     Disconnected {
-        addr: SocketAddr,
+        addr: String,
     },
 }
 
 /// Start the WebSocket server on the given address.
 ///
-/// Accepted connections spawn a read-loop task that forwards received
-/// envelopes (and connection/disconnection events) into the provided
-/// `event_tx` channel. The returned `JoinHandle` can be used to await
-/// or abort the server.
+/// Each accepted connection must complete the secure handshake within
+/// `handshake_timeout`; peers the node does not admit are closed. Admitted
+/// connections spawn a read-loop task that forwards received envelopes (and
+/// connection/disconnection events) into the provided `event_tx` channel.
 ///
 /// Server-side `PeerConnection` handles (write sinks) are sent through
 /// `conn_tx` so the node can track and write to them.
+///
+/// With `relay`, connections to `/relay/{session}` skip the handshake and
+/// are handed to the relay as tunnel legs; without it they are refused.
 pub async fn start_server(
     addr: SocketAddr,
+    security: Arc<SecurityContext>,
+    relay: Option<Arc<RelayHub>>,
+    handshake_timeout: Duration,
     event_tx: mpsc::Sender<TransportEvent>,
     conn_tx: mpsc::Sender<(SocketAddr, PeerConnection)>,
     mut shutdown: tokio::sync::broadcast::Receiver<()>,
@@ -142,64 +163,80 @@ pub async fn start_server(
                     Ok((stream, peer_addr)) => {
                         let event_tx = event_tx.clone();
                         let conn_tx = conn_tx.clone();
+                        let security = Arc::clone(&security);
+                        let relay = relay.clone();
                         tokio::spawn(async move {
-                            match accept_async(stream).await {
-                                Ok(ws_stream) => {
-                                    let (sink, mut stream) = ws_stream.split();
-                                    let temp_peer_id = PeerId::from_string(format!("pending-{peer_addr}"));
-
-                                    // Send the connection handle to the node.
-                                    let conn = PeerConnection::from_server(
-                                        temp_peer_id.clone(),
-                                        sink,
-                                    );
-                                    let _ = conn_tx.send((peer_addr, conn)).await;
-
-                                    let _ = event_tx
-                                        .send(TransportEvent::InboundConnection {
-                                            addr: peer_addr,
-                                            peer_id: temp_peer_id,
-                                        })
-                                        .await;
-
-                                    // Read loop.
-                                    while let Some(msg) = stream.next().await {
-                                        match msg {
-                                            Ok(Message::Text(text)) => {
-                                                match Envelope::from_json(&text) {
-                                                    Ok(envelope) => {
-                                                        let _ = event_tx
-                                                            .send(TransportEvent::Message {
-                                                                from_addr: peer_addr,
-                                                                envelope,
-                                                            })
-                                                            .await;
-                                                    }
-                                                    Err(e) => {
-                                                        warn!("Bad envelope from {peer_addr}: {e}");
-                                                    }
-                                                }
-                                            }
-                                            Ok(Message::Close(_)) => {
-                                                debug!("Peer {peer_addr} sent close");
-                                                break;
-                                            }
-                                            Ok(_) => {} // Ignore binary/ping/pong
-                                            Err(e) => {
-                                                debug!("Read error from {peer_addr}: {e}");
-                                                break;
-                                            }
-                                        }
-                                    }
-
-                                    let _ = event_tx
-                                        .send(TransportEvent::Disconnected { addr: peer_addr })
-                                        .await;
-                                }
+                            let mut path = String::new();
+                            // The callback signature is fixed by tungstenite.
+                            #[allow(clippy::result_large_err)]
+                            let record_path = |request: &Request, response: Response| {
+                                path = request.uri().path().to_string();
+                                Ok(response)
+                            };
+                            let mut ws_stream = match accept_hdr_async(stream, record_path).await {
+                                Ok(ws_stream) => ws_stream,
                                 Err(e) => {
                                     error!("WebSocket accept failed for {peer_addr}: {e}");
+                                    return;
                                 }
+                            };
+
+                            if let Some(session) = path.strip_prefix(RELAY_PATH) {
+                                match relay {
+                                    Some(hub) => hub.join(session, ws_stream).await,
+                                    None => {
+                                        debug!("Refusing relay leg from {peer_addr}: not a relay");
+                                        let _ = ws_stream.close(None).await;
+                                    }
+                                }
+                                return;
                             }
+
+                            let session = match tokio::time::timeout(
+                                handshake_timeout,
+                                secure::respond(&mut ws_stream, &security),
+                            )
+                            .await
+                            {
+                                Ok(Ok(session)) => session,
+                                Ok(Err(e)) => {
+                                    warn!("Rejected connection from {peer_addr}: {e}");
+                                    return;
+                                }
+                                Err(_) => {
+                                    warn!("Handshake with {peer_addr} timed out");
+                                    return;
+                                }
+                            };
+
+                            let (sink, stream) = ws_stream.split();
+                            let remote = session.remote;
+                            debug!("Authenticated {} at {peer_addr}", remote.peer_id.short());
+
+                            // Send the connection handle to the node.
+                            let conn = PeerConnection {
+                                remote: remote.clone(),
+                                sink: PeerSink::Server(sink),
+                                sealer: session.sealer,
+                                security,
+                            };
+                            let _ = conn_tx.send((peer_addr, conn)).await;
+
+                            let _ = event_tx
+                                .send(TransportEvent::InboundConnection {
+                                    addr: peer_addr,
+                                    identity: remote.clone(),
+                                })
+                                .await;
+
+                            read_loop(
+                                stream,
+                                session.opener,
+                                remote.peer_id,
+                                peer_addr.to_string(),
+                                event_tx,
+                            )
+                            .await;
                         });
                     }
                     Err(e) => {
@@ -219,70 +256,166 @@ pub async fn start_server(
 
 /// Connect to a remote peer as a client.
 ///
-/// Returns a `PeerConnection` (write handle) and spawns a read-loop task
-/// that forwards incoming envelopes to `event_tx`.
+/// The remote must be admitted by `security` (trusted, or untrusted peers
+/// allowed). With `invite`, the remote must instead be the invite's issuer,
+/// and both sides trust each other afterwards. Returns a `PeerConnection`
+/// (write handle) and spawns a read-loop task that forwards incoming
+/// envelopes to `event_tx`.
 pub async fn connect_to_peer(
     addr: &str,
+    security: &Arc<SecurityContext>,
+    invite: Option<&PairingInvite>,
+    handshake_timeout: Duration,
     event_tx: mpsc::Sender<TransportEvent>,
 ) -> Result<PeerConnection, NetworkError> {
-    let url = if addr.starts_with("ws://") || addr.starts_with("wss://") {
-        addr.to_string()
-    } else {
-        format!("ws://{addr}")
-    };
+    dial(
+        &ws_url(addr),
+        addr,
+        security,
+        Expect::from_invite(invite),
+        Handshake::Initiate,
+        handshake_timeout,
+        event_tx,
+    )
+    .await
+}
+
+/// Which side of the secure handshake a dialing node runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Handshake {
+    Initiate,
+    /// Used by the target of a relayed connection, which dials the relay
+    /// but answers the handshake.
+    Respond,
+}
 
-    let (ws_stream, _) = connect_async(&url)
+/// Dial `url`, run the handshake and spawn the read loop, reporting
+/// envelopes under connection key `key`.
+pub(crate) async fn dial(
+    url: &str,
+    key: &str,
+    security: &Arc<SecurityContext>,
+    expect: Expect<'_>,
+    role: Handshake,
+    handshake_timeout: Duration,
+    event_tx: mpsc::Sender<TransportEvent>,
+) -> Result<PeerConnection, NetworkError> {
+    let (mut ws_stream, _) = tokio::time::timeout(handshake_timeout, connect_async(url))
         .await
-        .map_err(|e| NetworkError::Transport(format!("Connect to {addr} failed: {e}")))?;
+        .map_err(|_| NetworkError::Timeout(handshake_timeout))?
+        .map_err(|e| NetworkError::Transport(format!("Connect to {key} failed: {e}")))?;
 
-    let peer_addr: SocketAddr = addr
-        .trim_start_matches("ws://")
-        .trim_start_matches("wss://")
-        .parse()
-        .unwrap_or_else(|_| "0.0.0.0:0".parse().expect("valid fallback address"));
+    let handshake = async {
+        match role {
+            Handshake::Initiate => secure::initiate(&mut ws_stream, security, expect).await,
+            Handshake::Respond => secure::respond(&mut ws_stream, security).await,
+        }
+    };
+    let session = tokio::time::timeout(handshake_timeout, handshake)
+        .await
+        .map_err(|_| NetworkError::Timeout(handshake_timeout))??;
 
-    let (sink, mut stream) = ws_stream.split();
-    let temp_peer_id = PeerId::from_string(format!("outbound-{addr}"));
+    let (sink, stream) = ws_stream.split();
+    let remote = session.remote;
+    debug!("Authenticated {} at {key}", remote.peer_id.short());
 
-    let conn = PeerConnection::from_client(temp_peer_id, sink);
+    let conn = PeerConnection {
+        remote: remote.clone(),
+        sink: PeerSink::Client(sink),
+        sealer: session.sealer,
+        security: Arc::clone(security),
+    };
 
     // Spawn read loop.
-    tokio::spawn(async move {
-        while let Some(msg) = stream.next().await {
-            match msg {
-                Ok(Message::Text(text)) => {
-                    match Envelope::from_json(&text) {
-                        Ok(envelope) => {
-                            let _ = event_tx
-                                .send(TransportEvent::Message {
-                                    from_addr: peer_addr,
-                                    envelope,
-                                })
-                                .await;
-                        }
-                        Err(e) => {
-                            warn!("Bad envelope from {peer_addr}: {e}");
-                        }
+    tokio::spawn(read_loop(
+        stream,
+        session.opener,
+        remote.peer_id,
+        key.to_string(),
+        event_tx,
+    ));
+
+    Ok(conn)
+}
+
+/// WebSocket URL for a `host:port` or `ws://` address.
+pub(crate) fn ws_url(addr: &str) -> String {
+    if addr.starts_with("ws://") || addr.starts_with("wss://") {
+        addr.to_string()
+    } else {
+        format!("ws://{addr}")
+    }
+}
+
+/// Decrypt frames from an authenticated peer and forward its envelopes.
+///
+/// Envelopes must come from the authenticated peer and carry its valid
+/// signature; anything else is dropped.
+async fn read_loop<S>(
+    mut stream: SplitStream<WebSocketStream<S>>,
+    mut opener: FrameOpener,
+    remote: PeerId,
+    peer_addr: String,
+    event_tx: mpsc::Sender<TransportEvent>,
+) where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    while let Some(msg) = stream.next().await {
+        match msg {
+            Ok(Message::Binary(frame)) => {
+                let plaintext = match opener.open(&frame) {
+                    Ok(Some(plaintext)) => plaintext,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        warn!("Dropping connection to {peer_addr}: {e}");
+                        break;
+                    }
+                };
+                match serde_json::from_slice::<Envelope>(&plaintext) {
+                    Ok(envelope) if envelope.from != remote => {
+                        warn!(
+                            "Envelope from {peer_addr} claims sender {}, expected {}",
+                            envelope.from.short(),
+                            remote.short()
+                        );
+                    }
+                    Ok(envelope) if !envelope.verify_signature() => {
+                        warn!(
+                            "Envelope {} from {peer_addr} has a bad signature",
+                            envelope.id
+                        );
+                    }
+                    Ok(envelope) => {
+                        let _ = event_tx
+                            .send(TransportEvent::Message {
+                                from_addr: peer_addr.clone(),
+                                envelope,
+                            })
+                            .await;
+                    }
+                    Err(e) => {
+                        warn!("Bad envelope from {peer_addr}: {e}");
                     }
                 }
-                Ok(Message::Close(_)) => {
-                    debug!("Remote {peer_addr} sent close");
-                    break;
-                }
-                Ok(_) => {}
-                Err(e) => {
-                    debug!("Read error from {peer_addr}: {e}");
-                    break;
-                }
+            }
+            Ok(Message::Close(_)) => {
+                debug!("Peer {peer_addr} sent close");
+                break;
+            }
+            Ok(Message::Text(_)) => {
+                warn!("Ignoring unencrypted text frame from {peer_addr}");
+            }
+            Ok(_) => {} // Ignore ping/pong
+            Err(e) => {
+                debug!("Read error from {peer_addr}: {e}");
+                break;
             }
         }
+    }
 
-        let _ = event_tx
-            .send(TransportEvent::Disconnected { addr: peer_addr })
-            .await;
-    });
-
-    Ok(conn)
+    let _ = event_tx
+        .send(TransportEvent::Disconnected { addr: peer_addr })
+        .await;
 }
 
 // ---------------------------------------------------------------------------
@@ -310,11 +443,28 @@ mod tests {
         drop(listener);
     }
 
+    fn security(name: &str) -> Arc<SecurityContext> {
+        Arc::new(
+            SecurityContext::new(
+                &NodeIdentity::generate(name),
+                crate::trust::TrustStore::new(),
+                false,
+            )
+            .unwrap(),
+        )
+    }
+
     #[tokio::test]
     async fn test_connect_to_server() {
         let (event_tx, mut event_rx) = mpsc::channel(32);
         let (conn_tx, mut conn_rx) = mpsc::channel(32);
         let (shutdown_tx, shutdown_rx) = tokio::sync::broadcast::channel(1);
+        let timeout = Duration::from_secs(2);
+
+        let client_security = security("client");
+        let server_security = security("server");
+        client_security.trust(server_security.peer_id().clone(), "server");
+        server_security.trust(client_security.peer_id().clone(), "client");
 
         // Bind server to a random port.
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -323,43 +473,75 @@ mod tests {
 
         // Start server.
         let event_tx_clone = event_tx.clone();
+        let server_sec = Arc::clone(&server_security);
         let server_handle = tokio::spawn(async move {
-            let _ = start_server(server_addr, event_tx_clone, conn_tx, shutdown_rx).await;
+            let _ = start_server(
+                server_addr,
+                server_sec,
+                None,
+                timeout,
+                event_tx_clone,
+                conn_tx,
+                shutdown_rx,
+            )
+            .await;
         });
 
         // Give server time to start.
-        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
 
         // Connect as client.
-        let client_result = connect_to_peer(&server_addr.to_string(), event_tx).await;
+        let client_result = connect_to_peer(
+            &server_addr.to_string(),
+            &client_security,
+            None,
+            timeout,
+            event_tx,
+        )
+        .await;
         assert!(client_result.is_ok());
 
         let mut client_conn = client_result.unwrap();
+        assert_eq!(client_conn.peer_id(), server_security.peer_id());
 
         // Send a message from client.
         let envelope = Envelope::new(
-            PeerId::from_string("client-peer"),
+            client_security.peer_id().clone(),
             None,
             crate::message::MessageKind::Hello,
             serde_json::json!({"name": "test-client"}),
         );
         client_conn.send(&envelope).await.unwrap();
 
-        // Server should receive the connection and message.
-        // Wait for inbound connection event.
-        let event = tokio::time::timeout(
-            std::time::Duration::from_secs(2),
-            event_rx.recv(),
-        )
-        .await;
-        assert!(event.is_ok());
+        // Server should receive the authenticated connection.
+        let event = tokio::time::timeout(timeout, event_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        match event {
+            TransportEvent::InboundConnection { identity, .. } => {
+                assert_eq!(&identity.peer_id, client_security.peer_id());
+            }
+            other => panic!("Expected InboundConnection, got {other:?}"),
+        }
+
+        // ...and then the signed message.
+        let event = tokio::time::timeout(timeout, event_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        match event {
+            TransportEvent::Message {
+                envelope: received, ..
+            } => {
+                assert_eq!(received.id, envelope.id);
+                assert!(received.verify_signature());
+            }
+            other => panic!("Expected Message, got {other:?}"),
+        }
 
         // Wait for server-side connection handle.
-        let server_conn = tokio::time::timeout(
-            std::time::Duration::from_secs(2),
-            conn_rx.recv(),
-        )
-        .await;
+        let server_conn = tokio::time::timeout(timeout, conn_rx.recv()).await;
         assert!(server_conn.is_ok());
 
         // Cleanup.
@@ -367,4 +549,50 @@ mod tests {
         let _ = shutdown_tx.send(());
         let _ = server_handle.await;
     }
+
+    #[tokio::test]
+    async fn test_untrusted_client_refused() {
+        let (event_tx, mut event_rx) = mpsc::channel(32);
+        let (conn_tx, _conn_rx) = mpsc::channel(32);
+        let (shutdown_tx, shutdown_rx) = tokio::sync::broadcast::channel(1);
+        let timeout = Duration::from_secs(2);
+
+        let client_security = security("intruder");
+        let server_security = security("server");
+        // The client is willing, but the server has never heard of it.
+        client_security.trust(server_security.peer_id().clone(), "server");
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server_addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let server_handle = tokio::spawn(start_server(
+            server_addr,
+            server_security,
+            None,
+            timeout,
+            event_tx.clone(),
+            conn_tx,
+            shutdown_rx,
+        ));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let result = connect_to_peer(
+            &server_addr.to_string(),
+            &client_security,
+            None,
+            timeout,
+            event_tx,
+        )
+        .await;
+        assert!(matches!(result, Err(NetworkError::ConnectionRefused(_))));
+        assert!(
+            tokio::time::timeout(Duration::from_millis(200), event_rx.recv())
+                .await
+                .is_err()
+        );
+
+        let _ = shutdown_tx.send(());
+        let _ = server_handle.await;
+    }
 }
@@ -0,0 +1,320 @@
+//! Trust list and pairing — which peers this node will talk to.
+//!
+//! Connections from peers that are not on the [`TrustStore`] are rejected by
+//! default. A peer joins the list by pairing: one node issues a
+//! [`PairingInvite`] (shown as a short code or encoded in a QR code as a
+//! `hive-pair://` URI), and the other presents it during the handshake. The
+//! code itself never crosses the wire; the joiner sends an HMAC of the
+//! handshake transcript keyed by the code, which also binds the proof to that
+//! one encrypted session.
+
+use std::path::Path;
+
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::error::NetworkError;
+use crate::identity::PeerId;
+
+/// Characters used in pairing codes (no 0/O, 1/I/L or U to avoid misreads).
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTVWXYZ23456789";
+
+/// Length of a pairing code, excluding the display separator.
+const CODE_LEN: usize = 8;
+
+/// URI scheme for pairing invites encoded in QR codes.
+const PAIR_SCHEME: &str = "hive-pair://";
+
+/// A peer this node has agreed to talk to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrustedPeer {
+    /// The peer's key-derived id.
+    pub peer_id: PeerId,
+    /// Name the peer announced when it was trusted.
+    pub name: String,
+    /// When the peer was added.
+    pub added_at: DateTime<Utc>,
+}
+
+/// Persisted list of trusted peers.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrustStore {
+    peers: Vec<TrustedPeer>,
+}
+
+impl TrustStore {
+    /// Create an empty trust store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `peer_id` is trusted.
+    pub fn is_trusted(&self, peer_id: &PeerId) -> bool {
+        self.peers.iter().any(|p| &p.peer_id == peer_id)
+    }
+
+    /// Trust a peer. Returns `false` if it was already trusted.
+    pub fn trust(&mut self, peer_id: PeerId, name: impl Into<String>) -> bool {
+        if self.is_trusted(&peer_id) {
+            return false;
+        }
+        self.peers.push(TrustedPeer {
+            peer_id,
+            name: name.into(),
+            added_at: Utc::now(),
+        });
+        true
+    }
+
+    /// Remove a peer from the trust list. Returns `true` if it was present.
+    pub fn revoke(&mut self, peer_id: &PeerId) -> bool {
+        let before = self.peers.len();
+        self.peers.retain(|p| &p.peer_id != peer_id);
+        self.peers.len() != before
+    }
+
+    /// All trusted peers, in the order they were added.
+    pub fn list(&self) -> &[TrustedPeer] {
+        &self.peers
+    }
+
+    /// Number of trusted peers.
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    /// Whether no peers are trusted.
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+
+    /// Save the trust list to a JSON file.
+    pub fn save_to_file(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory: {e}"))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize trust store: {e}"))?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write trust store: {e}"))
+    }
+
+    /// Load the trust list from a JSON file, or return an empty list if the
+    /// file is missing.
+    pub fn load_or_default(path: &Path) -> Self {
+        if path.exists() {
+            match std::fs::read_to_string(path) {
+                Ok(data) => match serde_json::from_str::<TrustStore>(&data) {
+                    Ok(store) => return store,
+                    Err(e) => {
+                        tracing::warn!("Corrupt trust store, starting empty: {e}");
+                    }
+                },
+                Err(e) => {
+                    tracing::warn!("Cannot read trust store, starting empty: {e}");
+                }
+            }
+        }
+        Self::default()
+    }
+}
+
+/// A one-time invitation for another node to pair with this one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairingInvite {
+    /// The inviting node's id, pinned by the joiner.
+    pub peer_id: PeerId,
+    /// Address the inviting node listens on, if known.
+    pub addr: Option<String>,
+    /// Normalized pairing code (uppercase, no separators).
+    pub code: String,
+    /// When the invite stops being accepted.
+    pub expires_at: DateTime<Utc>,
+}
+
+impl PairingInvite {
+    /// Create an invite with a fresh random code valid for `ttl`.
+    pub fn new(peer_id: PeerId, addr: Option<String>, ttl: Duration) -> Self {
+        let code = (0..CODE_LEN)
+            .map(|_| {
+                let i = rand::random_range(0..CODE_ALPHABET.len());
+                CODE_ALPHABET[i] as char
+            })
+            .collect();
+        Self {
+            peer_id,
+            addr,
+            code,
+            expires_at: Utc::now() + ttl,
+        }
+    }
+
+    /// The code as shown to users, e.g. `ABCD-EFGH`.
+    pub fn display_code(&self) -> String {
+        let (a, b) = self.code.split_at(self.code.len() / 2);
+        format!("{a}-{b}")
+    }
+
+    /// Whether the invite has expired.
+    pub fn is_expired(&self) -> bool {
+        Utc::now() >= self.expires_at
+    }
+
+    /// Encode the invite as a `hive-pair://` URI for a QR code.
+    pub fn to_uri(&self) -> String {
+        let mut uri = format!(
+            "{PAIR_SCHEME}{}?code={}&expires={}",
+            self.peer_id,
+            self.code,
+            self.expires_at.timestamp()
+        );
+        if let Some(addr) = &self.addr {
+            uri.push_str("&addr=");
+            uri.push_str(addr);
+        }
+        uri
+    }
+
+    /// Parse an invite from a `hive-pair://` URI.
+    pub fn from_uri(uri: &str) -> Result<Self, NetworkError> {
+        let rest = uri
+            .strip_prefix(PAIR_SCHEME)
+            .ok_or_else(|| NetworkError::Pairing(format!("not a pairing URI: {uri}")))?;
+        let (peer, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+        let peer_id = PeerId::from_string(peer);
+        if peer_id.public_key().is_none() {
+            return Err(NetworkError::Pairing(format!("invalid peer id: {peer}")));
+        }
+
+        let mut code = None;
+        let mut addr = None;
+        let mut expires_at = None;
+        for pair in query.split('&') {
+            match pair.split_once('=') {
+                Some(("code", v)) => code = Some(normalize_code(v)),
+                Some(("addr", v)) if !v.is_empty() => addr = Some(v.to_string()),
+                Some(("expires", v)) => {
+                    expires_at = v
+                        .parse::<i64>()
+                        .ok()
+                        .and_then(|ts| DateTime::from_timestamp(ts, 0));
+                }
+                _ => {}
+            }
+        }
+
+        let code = code
+            .filter(|c| c.len() == CODE_LEN)
+            .ok_or_else(|| NetworkError::Pairing("missing or malformed code".into()))?;
+        let expires_at =
+            expires_at.ok_or_else(|| NetworkError::Pairing("missing expiry".into()))?;
+
+        Ok(Self {
+            peer_id,
+            addr,
+            code,
+            expires_at,
+        })
+    }
+}
+
+/// Normalize a user-typed pairing code: uppercase, separators removed.
+pub fn normalize_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Prove knowledge of `code` for the session identified by `handshake_hash`.
+pub(crate) fn pairing_proof(code: &str, handshake_hash: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(normalize_code(code).as_bytes())
+        .expect("HMAC accepts any key length");
+    mac.update(handshake_hash);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Constant-time check of a [`pairing_proof`].
+pub(crate) fn verify_pairing_proof(code: &str, handshake_hash: &[u8], proof: &[u8]) -> bool {
+    let mut mac = Hmac::<Sha256>::new_from_slice(normalize_code(code).as_bytes())
+        .expect("HMAC accepts any key length");
+    mac.update(handshake_hash);
+    mac.verify_slice(proof).is_ok()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trust_and_revoke() {
+        let mut store = TrustStore::new();
+        let peer = PeerId::generate();
+
+        assert!(!store.is_trusted(&peer));
+        assert!(store.trust(peer.clone(), "laptop"));
+        assert!(!store.trust(peer.clone(), "laptop"));
+        assert!(store.is_trusted(&peer));
+        assert_eq!(store.len(), 1);
+
+        assert!(store.revoke(&peer));
+        assert!(!store.revoke(&peer));
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_trust_store_save_load() {
+        let dir = std::env::temp_dir().join("hive_network_test_trust");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let path = dir.join("trusted_peers.json");
+        let mut store = TrustStore::new();
+        let peer = PeerId::generate();
+        store.trust(peer.clone(), "desktop");
+        store.save_to_file(&path).unwrap();
+
+        let loaded = TrustStore::load_or_default(&path);
+        assert!(loaded.is_trusted(&peer));
+        assert_eq!(loaded.list()[0].name, "desktop");
+
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(TrustStore::load_or_default(&path).is_empty());
+    }
+
+    #[test]
+    fn test_invite_codes_and_uri_roundtrip() {
+        let invite = PairingInvite::new(
+            PeerId::generate(),
+            Some("192.168.1.20:9470".into()),
+            Duration::minutes(5),
+        );
+        assert_eq!(invite.code.len(), CODE_LEN);
+        assert!(invite.code.bytes().all(|b| CODE_ALPHABET.contains(&b)));
+        assert_eq!(invite.display_code().len(), CODE_LEN + 1);
+        assert!(!invite.is_expired());
+
+        let parsed = PairingInvite::from_uri(&invite.to_uri()).unwrap();
+        assert_eq!(parsed.peer_id, invite.peer_id);
+        assert_eq!(parsed.code, invite.code);
+        assert_eq!(parsed.addr, invite.addr);
+        assert_eq!(parsed.expires_at.timestamp(), invite.expires_at.timestamp());
+
+        assert!(PairingInvite::from_uri("https://example.com").is_err());
+        assert!(PairingInvite::from_uri("hive-pair://not-a-key?code=ABCDEFGH&expires=0").is_err());
+    }
+
+    #[test]
+    fn test_pairing_proof_binds_code_and_session() {
+        let proof = pairing_proof("abcd-efgh", b"session-1");
+        assert!(verify_pairing_proof("ABCDEFGH", b"session-1", &proof));
+        assert!(!verify_pairing_proof("ABCDEFGJ", b"session-1", &proof));
+        assert!(!verify_pairing_proof("ABCDEFGH", b"session-2", &proof));
+    }
+}
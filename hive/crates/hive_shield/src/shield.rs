@@ -263,6 +263,21 @@ impl HiveShield {
         }
     }
 
+    /// Classify outgoing text by what the shield finds in it: credentials are
+    /// `Restricted`, PII is `Confidential`, anything else `Internal`.
+    ///
+    /// Unlike [`process_outgoing`](Self::process_outgoing) this has no side
+    /// effects on the runtime counters.
+    pub fn classify(&self, text: &str) -> DataClassification {
+        if self.config.enable_secret_scan && !self.secret_scanner.scan_text(text).is_empty() {
+            DataClassification::Restricted
+        } else if self.config.enable_pii_detection && !self.pii_detector.detect(text).is_empty() {
+            DataClassification::Confidential
+        } else {
+            DataClassification::Internal
+        }
+    }
+
     /// Run the shield pipeline on an incoming AI response. Checks for leaked
     /// data and injection attempts hidden in the response.
     pub fn process_incoming(&self, response: &str) -> ShieldResult {
@@ -513,4 +528,20 @@ mod tests {
         assert!(config.enable_pii_detection);
         assert!(config.user_rules.is_empty());
     }
+
+    #[test]
+    fn classify_by_findings() {
+        let shield = HiveShield::new(test_config());
+        let fake_key = format!("AKIA{}", "IOSFODNN7EXAMPLE");
+        assert_eq!(
+            shield.classify(&format!("key = {fake_key}")),
+            DataClassification::Restricted
+        );
+        assert_eq!(
+            shield.classify("Contact alice@example.com about this."),
+            DataClassification::Confidential
+        );
+        assert_eq!(shield.classify("What is Rust?"), DataClassification::Internal);
+        assert_eq!(shield.pii_detection_count(), 0);
+    }
 }
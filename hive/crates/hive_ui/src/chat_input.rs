@@ -12,7 +12,8 @@ use hive_ui_core::{AppTheme, HiveTheme};
 
 /// Event emitted when the user submits a chat message via Enter or the send
 /// button. The workspace subscribes to this and feeds the text into the AI
-/// streaming flow.
+/// streaming flow. Attached files arrive as extracted text appended to the
+/// message.
 #[derive(Debug, Clone)]
 pub struct SubmitMessage(pub String);
 
@@ -83,6 +84,13 @@ impl ChatInputView {
         });
     }
 
+    /// Replace the input text, e.g. to load a message for editing.
+    pub fn set_text(&self, text: &str, window: &mut Window, cx: &mut Context<Self>) {
+        self.input_state.update(cx, |state, cx| {
+            state.replace(text, window, cx);
+        });
+    }
+
     /// Toggle the sending/disabled state and update the placeholder text.
     pub fn set_sending(&mut self, sending: bool, window: &mut Window, cx: &mut Context<Self>) {
         self.is_sending = sending;
@@ -143,6 +151,9 @@ impl ChatInputView {
     }
 
     /// Read text, trim, emit `SubmitMessage`, and clear the input.
+    ///
+    /// With attachments, their text is extracted on the background executor
+    /// and the message is emitted once that finishes.
     fn submit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         if self.is_sending {
             return;
@@ -157,8 +168,20 @@ impl ChatInputView {
         }
 
         self.clear(window, cx);
-        self.attachments.clear();
-        cx.emit(SubmitMessage(text));
+        let attachments = std::mem::take(&mut self.attachments);
+        if attachments.is_empty() {
+            cx.emit(SubmitMessage(text));
+            return;
+        }
+
+        let extraction = cx
+            .background_executor()
+            .spawn(async move { with_attachments(text, &attachments) });
+        cx.spawn(async move |this, app: &mut AsyncApp| {
+            let message = extraction.await;
+            let _ = this.update(app, |_this, cx| cx.emit(SubmitMessage(message)));
+        })
+        .detach();
     }
 
     /// Open a native file picker and add selected files as attachments.
@@ -182,6 +205,28 @@ impl ChatInputView {
     }
 }
 
+/// Largest amount of extracted text sent per attachment, in characters.
+const MAX_ATTACHMENT_CHARS: usize = 20_000;
+
+/// Append each attachment's extracted text to the message. Files that can't
+/// be read are noted in the message rather than dropped.
+fn with_attachments(text: String, attachments: &[PathBuf]) -> String {
+    let mut parts = Vec::new();
+    if !text.is_empty() {
+        parts.push(text);
+    }
+    for path in attachments {
+        match hive_docs::extract::attachment_markdown(path, MAX_ATTACHMENT_CHARS) {
+            Ok(markdown) => parts.push(markdown),
+            Err(e) => parts.push(format!(
+                "### {}\n\n[Could not read attachment: {e:#}]",
+                path.display()
+            )),
+        }
+    }
+    parts.join("\n\n")
+}
+
 impl Render for ChatInputView {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let theme = &self.theme;
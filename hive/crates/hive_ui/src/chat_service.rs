@@ -4,9 +4,20 @@
 //! drives streaming responses from [`hive_ai::AiService`]. It keeps its own
 //! message list, streaming buffer, and error state so the UI can render
 //! reactively via `cx.notify()`.
+//!
+//! The conversation is a tree: `messages` is the active branch and
+//! `branches` holds the alternatives left behind by "edit & resend" and
+//! "regenerate". Tree operations go through
+//! [`hive_core::conversations::Conversation`].
+//!
+//! Long conversations are compacted: older messages are summarized into a
+//! session memory that replaces them in what is sent to the model, while the
+//! originals stay in `messages` for display, search and undo.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use anyhow::Context as _;
 use chrono::{DateTime, Utc};
 use gpui::{AsyncApp, Context, EventEmitter, Task, WeakEntity};
 use tokio::sync::mpsc;
@@ -18,6 +29,10 @@ use hive_ai::types::{
     ChatMessage as AiChatMessage, ChatRequest, MessageRole as AiMessageRole, StopReason,
     StreamChunk, TokenUsage, ToolCall as AiToolCall,
 };
+use hive_core::context::{
+    CompactionConfig, CompactionPlan, CompactionRecord, ContextMessage, ContextWindow,
+    SessionMemory,
+};
 use hive_core::conversations::{
     Conversation, ConversationStore, ConversationSummary, StoredMessage, generate_title,
 };
@@ -85,6 +100,8 @@ pub struct ChatMessage {
     pub tool_calls: Option<Vec<AiToolCall>>,
     /// For tool result messages: the ID of the tool call this responds to.
     pub tool_call_id: Option<String>,
+    /// ID of the message this one follows in the conversation tree.
+    pub parent_id: Option<String>,
 }
 
 impl ChatMessage {
@@ -99,6 +116,7 @@ impl ChatMessage {
             tokens: None,
             tool_calls: None,
             tool_call_id: None,
+            parent_id: None,
         }
     }
 
@@ -128,6 +146,8 @@ impl ChatMessage {
             cost: self.cost,
             tokens: total_tokens,
             thinking: None,
+            id: Some(self.id.clone()),
+            parent_id: self.parent_id.clone(),
         }
     }
 
@@ -138,7 +158,10 @@ impl ChatMessage {
         let tokens = stored.tokens.map(|t| (0usize, t as usize));
 
         Self {
-            id: Uuid::new_v4().to_string(),
+            id: stored
+                .id
+                .clone()
+                .unwrap_or_else(|| Uuid::new_v4().to_string()),
             role: MessageRole::from_stored(&stored.role),
             content: stored.content.clone(),
             model: stored.model.clone(),
@@ -147,6 +170,7 @@ impl ChatMessage {
             tokens,
             tool_calls: None,
             tool_call_id: None,
+            parent_id: stored.parent_id.clone(),
         }
     }
 }
@@ -160,7 +184,12 @@ impl ChatMessage {
 /// Owns the conversation message list, drives streaming, and exposes
 /// read-only accessors for the renderer.
 pub struct ChatService {
+    /// The active branch, from the first message to the current leaf.
     pub messages: Vec<ChatMessage>,
+    /// Messages on inactive branches (earlier edits and regenerations).
+    branches: Vec<StoredMessage>,
+    /// Session memories standing in for older messages, oldest first.
+    compactions: Vec<CompactionRecord>,
     pub streaming_content: String,
     pub is_streaming: bool,
     current_model: String,
@@ -184,6 +213,8 @@ impl ChatService {
     pub fn new(default_model: String) -> Self {
         Self {
             messages: Vec::new(),
+            branches: Vec::new(),
+            compactions: Vec::new(),
             streaming_content: String::new(),
             is_streaming: false,
             current_model: default_model,
@@ -236,6 +267,8 @@ impl ChatService {
 
     pub fn clear(&mut self) {
         self.messages.clear();
+        self.branches.clear();
+        self.compactions.clear();
         self.streaming_content.clear();
         self.is_streaming = false;
         self.error = None;
@@ -275,6 +308,8 @@ impl ChatService {
 
     /// Save the current conversation to an arbitrary [`ConversationStore`].
     /// Useful for tests that provide a temp-dir-backed store.
+    ///
+    /// Inactive branches are saved alongside the active path.
     pub fn save_to_store(&self, store: &ConversationStore, id: &str) -> anyhow::Result<()> {
         // Convert ChatMessages -> StoredMessages, skipping errors and empty
         // placeholders (same filter as build_ai_messages).
@@ -302,7 +337,7 @@ impl ChatService {
             .map(|existing| existing.created_at)
             .unwrap_or(now);
 
-        let conversation = Conversation {
+        let mut conversation = Conversation {
             id: id.to_string(),
             title,
             messages: stored_messages,
@@ -311,7 +346,11 @@ impl ChatService {
             total_tokens,
             created_at,
             updated_at: now,
+            branches: self.branches.clone(),
+            compactions: self.compactions.clone(),
         };
+        // Skipped error messages may have been parents on the active path.
+        conversation.normalize_tree();
 
         store.save(&conversation)
     }
@@ -339,6 +378,8 @@ impl ChatService {
             .collect();
 
         self.messages = messages;
+        self.branches = conversation.branches;
+        self.compactions = conversation.compactions;
         self.conversation_id = Some(conversation.id);
         self.current_model = conversation.model;
         self.streaming_content.clear();
@@ -390,29 +431,210 @@ impl ChatService {
         self.error = None;
 
         // 1. Record the user message.
-        let user_msg = ChatMessage::user(&content);
-        self.messages.push(user_msg);
+        self.push_message(ChatMessage::user(&content));
+
+        // 2-3. Prepare streaming state and the assistant placeholder.
+        self.begin_response(model, cx);
+
+        info!(
+            "ChatService: user message queued, awaiting stream attachment (model={})",
+            model
+        );
+    }
 
-        // 2. Prepare streaming state.
+    /// Enter streaming state with an empty assistant placeholder at the end
+    /// of the active branch, ready for [`attach_stream`](Self::attach_stream).
+    fn begin_response(&mut self, model: &str, cx: &mut Context<Self>) {
         self.is_streaming = true;
         self.streaming_content.clear();
         self.current_model = model.to_string();
 
-        // 3. Add a placeholder assistant message that will be finalized later.
-        let placeholder = ChatMessage::assistant_placeholder();
-        self.messages.push(placeholder);
+        // Placeholder assistant message that will be finalized later.
+        self.push_message(ChatMessage::assistant_placeholder());
 
         self.generation += 1;
 
-        info!(
-            "ChatService: user message queued, awaiting stream attachment (model={})",
-            model
-        );
-
         // Notify the UI so the user message renders immediately.
         cx.notify();
     }
 
+    /// Append a message to the active branch, parented to the current leaf.
+    fn push_message(&mut self, mut msg: ChatMessage) {
+        msg.parent_id = self.messages.last().map(|m| m.id.clone());
+        self.messages.push(msg);
+    }
+
+    // -- Branching ----------------------------------------------------------
+
+    /// Replace the user message `message_id` with `content` as a new sibling
+    /// branch and begin streaming a fresh reply. The original message and
+    /// everything after it stay reachable through [`switch_branch`](Self::switch_branch).
+    pub fn edit_and_resend(
+        &mut self,
+        message_id: &str,
+        content: String,
+        model: &str,
+        cx: &mut Context<Self>,
+    ) -> anyhow::Result<()> {
+        self.ensure_idle()?;
+        let mut tree = self.tree();
+        let original = tree
+            .find_message(message_id)
+            .context("Message not found")?;
+        if original.role != MessageRole::User.to_stored() {
+            anyhow::bail!("Only your own messages can be edited");
+        }
+        tree.edit_message(message_id, content)?;
+        self.apply_tree(tree);
+        self.error = None;
+        self.begin_response(model, cx);
+        info!("ChatService: edited message {message_id}, awaiting stream attachment");
+        Ok(())
+    }
+
+    /// Rewind to the prompt answered by assistant message `message_id` and
+    /// begin streaming a new reply as its sibling. Returns the prompt text so
+    /// the caller can rebuild retrieval context.
+    pub fn regenerate(
+        &mut self,
+        message_id: &str,
+        model: &str,
+        cx: &mut Context<Self>,
+    ) -> anyhow::Result<String> {
+        self.ensure_idle()?;
+        let mut tree = self.tree();
+        tree.prepare_regenerate(message_id)?;
+        self.apply_tree(tree);
+        let prompt = self
+            .messages
+            .last()
+            .map(|m| m.content.clone())
+            .unwrap_or_default();
+        self.error = None;
+        self.begin_response(model, cx);
+        info!("ChatService: regenerating reply {message_id}, awaiting stream attachment");
+        Ok(prompt)
+    }
+
+    /// Move from message `message_id` to the sibling `offset` steps away
+    /// (clamped), making that branch active down to its most recent leaf.
+    pub fn switch_branch(&mut self, message_id: &str, offset: isize) -> anyhow::Result<()> {
+        self.ensure_idle()?;
+        let mut tree = self.tree();
+        let target = {
+            let siblings = tree.siblings(message_id);
+            let index = siblings
+                .iter()
+                .position(|m| m.id.as_deref() == Some(message_id))
+                .context("Message not found")?;
+            let target = index
+                .saturating_add_signed(offset)
+                .min(siblings.len() - 1);
+            siblings[target].id.clone().unwrap_or_default()
+        };
+        tree.switch_to(&target)?;
+        self.apply_tree(tree);
+        Ok(())
+    }
+
+    /// Start a new conversation containing the active branch up to and
+    /// including `message_id`, save it, and switch to it. Returns the new
+    /// conversation ID.
+    pub fn fork_from(&mut self, message_id: &str) -> anyhow::Result<String> {
+        let store = ConversationStore::new()?;
+        self.fork_into_store(&store, message_id)
+    }
+
+    /// Like [`fork_from`](Self::fork_from) but saves into an arbitrary
+    /// [`ConversationStore`]. Useful for tests.
+    pub fn fork_into_store(
+        &mut self,
+        store: &ConversationStore,
+        message_id: &str,
+    ) -> anyhow::Result<String> {
+        self.ensure_idle()?;
+        let fork = self.tree().fork_at(message_id)?;
+        let id = fork.id.clone();
+        self.apply_tree(fork);
+        self.conversation_id = Some(id.clone());
+        self.error = None;
+        self.save_to_store(store, &id)?;
+        info!("ChatService: forked conversation at {message_id} into {id}");
+        Ok(id)
+    }
+
+    /// For each message on the active branch, its position among its
+    /// siblings and the sibling count, or `None` if it has no alternatives.
+    pub fn branch_positions(&self) -> Vec<Option<(usize, usize)>> {
+        let tree = self.tree();
+        self.messages
+            .iter()
+            .map(|m| {
+                tree.sibling_position(&m.id)
+                    .filter(|(_, count)| *count > 1)
+            })
+            .collect()
+    }
+
+    /// Messages on inactive branches.
+    pub fn branches(&self) -> &[StoredMessage] {
+        &self.branches
+    }
+
+    fn ensure_idle(&self) -> anyhow::Result<()> {
+        if self.is_streaming {
+            anyhow::bail!("Wait for the current response to finish");
+        }
+        Ok(())
+    }
+
+    /// Snapshot of the in-memory message tree for use with the tree
+    /// operations on [`Conversation`].
+    fn tree(&self) -> Conversation {
+        let mut tree = Conversation::new(self.current_model.clone());
+        tree.id = self.conversation_id.clone().unwrap_or_default();
+        tree.messages = self.messages.iter().map(ChatMessage::to_stored).collect();
+        tree.branches = self.branches.clone();
+        tree.compactions = self.compactions.clone();
+        tree.normalize_tree();
+        tree
+    }
+
+    /// Replace the message list with the active path of `tree`. Messages that
+    /// stay on the active path keep their in-memory metadata (tool calls).
+    fn apply_tree(&mut self, tree: Conversation) {
+        let mut existing: HashMap<String, ChatMessage> = self
+            .messages
+            .drain(..)
+            .map(|m| (m.id.clone(), m))
+            .collect();
+        self.messages = tree
+            .messages
+            .iter()
+            .map(|stored| {
+                match stored.id.as_ref().and_then(|id| existing.remove(id)) {
+                    Some(mut msg) => {
+                        msg.parent_id = stored.parent_id.clone();
+                        msg
+                    }
+                    None => ChatMessage::from_stored(stored),
+                }
+            })
+            .collect();
+        // Errors and unfinished placeholders are transient; don't keep them
+        // around on inactive branches.
+        self.branches = tree
+            .branches
+            .into_iter()
+            .filter(|m| {
+                m.role != MessageRole::Error.to_stored()
+                    && !(m.role == MessageRole::Assistant.to_stored() && m.content.is_empty())
+            })
+            .collect();
+        self.compactions = tree.compactions;
+        self.generation += 1;
+    }
+
     /// Attach a stream receiver from `AiService::stream_chat` and begin
     /// consuming chunks.
     ///
@@ -422,7 +644,7 @@ impl ChatService {
     ///
     /// ```ignore
     /// chat_service.update(cx, |svc, cx| svc.send_message(text, model, cx));
-    /// let rx = ai_service.stream_chat(messages, model, None).await?;
+    /// let rx = ai_service.stream_chat(messages, model, None, None, Some(&context)).await?;
     /// chat_service.update(cx, |svc, cx| svc.attach_stream(rx, model, cx));
     /// ```
     pub fn attach_stream(
@@ -438,6 +660,7 @@ impl ChatService {
             async move |this: WeakEntity<ChatService>, app: &mut AsyncApp| {
                 let mut accumulated = String::new();
                 let mut final_usage: Option<TokenUsage> = None;
+                let mut stream_error: Option<String> = None;
 
                 loop {
                     // Receive the next chunk. We poll via a small async block
@@ -446,11 +669,21 @@ impl ChatService {
 
                     match chunk {
                         Some(chunk) => {
+                            if let Some(handoff) = &chunk.handoff {
+                                // A restarted answer replaces what came before.
+                                if handoff.restarted {
+                                    accumulated.clear();
+                                }
+                                accumulated.push_str(&handoff.marker());
+                            }
                             accumulated.push_str(&chunk.content);
 
                             if let Some(usage) = &chunk.usage {
                                 final_usage = Some(usage.clone());
                             }
+                            if chunk.error.is_some() {
+                                stream_error = chunk.error.clone();
+                            }
 
                             let is_done = chunk.done;
 
@@ -487,6 +720,9 @@ impl ChatService {
                 let _ = this.update(app, |this: &mut ChatService, cx| {
                     this.finalize_stream(assistant_idx, &accumulated, &model_clone, usage.as_ref());
                     this.emit_stream_completed(&model_clone, cx);
+                    if let Some(e) = stream_error {
+                        this.fail_stream(&model_clone, format!("Response interrupted: {e}"), cx);
+                    }
                     cx.notify();
                 });
             },
@@ -511,6 +747,9 @@ impl ChatService {
     ) {
         let assistant_idx = self.messages.len().saturating_sub(1);
         let model_clone = model.clone();
+        let search_hub = cx
+            .has_global::<crate::AppSearch>()
+            .then(|| cx.global::<crate::AppSearch>().0.clone());
 
         let task = cx.spawn(
             async move |this: WeakEntity<ChatService>, app: &mut AsyncApp| {
@@ -526,9 +765,22 @@ impl ChatService {
                     let mut final_tool_calls: Vec<AiToolCall> = Vec::new();
                     let mut final_usage: Option<TokenUsage> = None;
                     let mut final_stop_reason: Option<StopReason> = None;
+                    let mut stream_error: Option<String> = None;
 
                     while let Some(chunk) = current_rx.recv().await {
+                        // Mark where a fallback provider took over a broken stream.
+                        if let Some(handoff) = &chunk.handoff {
+                            // A restarted answer replaces what came before.
+                            if handoff.restarted {
+                                accumulated.clear();
+                                final_tool_calls.clear();
+                            }
+                            accumulated.push_str(&handoff.marker());
+                        }
                         accumulated.push_str(&chunk.content);
+                        if chunk.error.is_some() {
+                            stream_error = chunk.error.clone();
+                        }
 
                         if let Some(ref u) = chunk.usage {
                             final_usage = Some(u.clone());
@@ -573,6 +825,9 @@ impl ChatService {
                                 final_usage.as_ref(),
                             );
                             svc.emit_stream_completed(&m, cx);
+                            if let Some(e) = stream_error {
+                                svc.fail_stream(&m, format!("Response interrupted: {e}"), cx);
+                            }
                             cx.notify();
                         });
                         break;
@@ -585,7 +840,12 @@ impl ChatService {
                         final_tool_calls.len()
                     );
 
-                    let registry = hive_agents::tool_use::builtin_registry();
+                    let mut registry = hive_agents::tool_use::builtin_registry();
+                    if let Some(hub) = &search_hub {
+                        registry.register_tool(Box::new(
+                            hive_agents::tool_use::SearchKnowledgeTool::new(hub.clone()),
+                        ));
+                    }
                     let agent_calls: Vec<hive_agents::tool_use::ToolCall> = final_tool_calls
                         .iter()
                         .map(|tc| hive_agents::tool_use::ToolCall {
@@ -621,11 +881,11 @@ impl ChatService {
                         for result in &results {
                             let mut tool_msg = ChatMessage::new(MessageRole::Tool, &result.content);
                             tool_msg.tool_call_id = Some(result.tool_use_id.clone());
-                            svc.messages.push(tool_msg);
+                            svc.push_message(tool_msg);
                         }
 
                         // New placeholder for the next assistant response.
-                        svc.messages.push(ChatMessage::assistant_placeholder());
+                        svc.push_message(ChatMessage::assistant_placeholder());
 
                         svc.streaming_content.clear();
                         svc.generation += 1;
@@ -665,7 +925,7 @@ impl ChatService {
                         Err(e) => {
                             error!("Tool re-send failed: {e}");
                             let _ = this.update(app, |svc: &mut ChatService, cx| {
-                                svc.set_error(format!("Tool re-send failed: {e}"), cx);
+                                svc.fail_stream(&m, format!("Tool re-send failed: {e}"), cx);
                             });
                             break;
                         }
@@ -696,28 +956,108 @@ impl ChatService {
 
     /// Build the AI wire-format message history for the current conversation.
     ///
-    /// Skips placeholder (empty assistant) and error messages. Useful for
-    /// the caller to construct the `AiService::stream_chat` request.
+    /// Skips placeholder (empty assistant) and error messages, and replaces
+    /// compacted messages with their session memory. Useful for the caller
+    /// to construct the `AiService::stream_chat` request.
     pub fn build_ai_messages(&self) -> Vec<AiChatMessage> {
-        self.messages
+        let to_ai = |m: &ChatMessage| AiChatMessage {
+            role: m.role.to_ai_role(),
+            content: m.content.clone(),
+            timestamp: m.timestamp,
+            tool_call_id: m.tool_call_id.clone(),
+            tool_calls: m.tool_calls.clone(),
+        };
+        if self.compactions.is_empty() {
+            return self.messages.iter().filter(|m| is_sendable(m)).map(to_ai).collect();
+        }
+
+        let by_id: HashMap<&str, &ChatMessage> =
+            self.messages.iter().map(|m| (m.id.as_str(), m)).collect();
+        self.context_window(&self.current_model)
+            .messages()
             .iter()
-            .filter(|m| {
-                m.role != MessageRole::Error
-                    // Skip empty assistant placeholders, but keep messages with tool_calls.
-                    && !(m.role == MessageRole::Assistant
-                        && m.content.is_empty()
-                        && m.tool_calls.is_none())
-            })
-            .map(|m| AiChatMessage {
-                role: m.role.to_ai_role(),
-                content: m.content.clone(),
-                timestamp: m.timestamp,
-                tool_call_id: m.tool_call_id.clone(),
-                tool_calls: m.tool_calls.clone(),
+            .map(|cm| match cm.id.as_deref().and_then(|id| by_id.get(id)) {
+                Some(m) => to_ai(m),
+                // Session memory message.
+                None => AiChatMessage::text(AiMessageRole::System, cm.content.clone()),
             })
             .collect()
     }
 
+    // -- Compaction ---------------------------------------------------------
+
+    /// The context `model` would see: the sendable messages of the active
+    /// branch with stored compactions applied.
+    ///
+    /// Tool results are linked to the call before them and to the answer
+    /// after them, so a call and its result are only compacted together.
+    pub fn context_window(&self, model: &str) -> ContextWindow {
+        let mut messages: Vec<ContextMessage> = Vec::with_capacity(self.messages.len());
+        let mut previous: Option<&ChatMessage> = None;
+        for m in self.messages.iter().filter(|m| is_sendable(m)) {
+            let mut message =
+                ContextMessage::new(m.role.to_stored(), m.content.clone()).with_id(m.id.clone());
+            if let Some(prev) = previous
+                && (m.role == MessageRole::Tool || prev.role == MessageRole::Tool)
+            {
+                message = message.referencing(prev.id.clone());
+            }
+            messages.push(message);
+            previous = Some(m);
+        }
+        ContextWindow::from_messages(model, messages, &self.compactions)
+    }
+
+    /// Messages to summarize if the context for `model` has crossed the
+    /// compaction threshold. `None` while streaming or when nothing needs
+    /// compacting.
+    pub fn compaction_plan(&self, model: &str, config: &CompactionConfig) -> Option<CompactionPlan> {
+        if self.is_streaming {
+            return None;
+        }
+        let window = self.context_window(model);
+        if !window.needs_compaction(config) {
+            return None;
+        }
+        window.compaction_plan(config)
+    }
+
+    /// Record a session memory for `plan`, written by `summarizer`. Fails if
+    /// the planned messages have left the active branch in the meantime.
+    pub fn apply_compaction(
+        &mut self,
+        model: &str,
+        plan: &CompactionPlan,
+        memory: SessionMemory,
+        summarizer: &str,
+    ) -> anyhow::Result<CompactionRecord> {
+        let record = self
+            .context_window(model)
+            .apply_compaction(plan, memory, Some(summarizer))?;
+        self.compactions.push(record.clone());
+        self.generation += 1;
+        info!(
+            "ChatService: compacted {} messages ({} -> {} tokens)",
+            record.message_ids.len(),
+            record.tokens_before,
+            record.tokens_after
+        );
+        Ok(record)
+    }
+
+    /// Drop the most recent compaction so its messages are sent in full
+    /// again.
+    pub fn undo_compaction(&mut self) -> Option<CompactionRecord> {
+        let record = self.compactions.pop()?;
+        self.generation += 1;
+        Some(record)
+    }
+
+    /// Compactions recorded for this conversation, oldest first.
+    pub fn compactions(&self) -> &[CompactionRecord] {
+        &self.compactions
+    }
+
     // -- Internal -----------------------------------------------------------
 
     /// Replace the placeholder assistant message with the final content and
@@ -787,6 +1127,19 @@ impl ChatService {
             }
         }
 
+        // Keep the unified search index current with the saved conversation.
+        if let Some(id) = &self.conversation_id
+            && cx.has_global::<crate::AppSearch>()
+        {
+            let hub = cx.global::<crate::AppSearch>().0.clone();
+            let indexed = ConversationStore::new()
+                .and_then(|store| store.load(id))
+                .and_then(|conversation| hub.index(&(&conversation).into()));
+            if let Err(e) = indexed {
+                warn!("ChatService: search indexing failed: {e}");
+            }
+        }
+
         cx.emit(StreamCompleted {
             model: model.to_string(),
             message_count: self.messages.len(),
@@ -810,10 +1163,26 @@ impl ChatService {
         }
 
         // Push an error message so the user sees what happened.
-        self.messages.push(ChatMessage::error(msg));
+        self.push_message(ChatMessage::error(msg));
         self.generation += 1;
         cx.notify();
     }
+
+    /// Record a provider failure for `model`: shows the error like
+    /// [`set_error`](Self::set_error) and emits [`StreamFailed`].
+    pub fn fail_stream(&mut self, model: &str, message: impl Into<String>, cx: &mut Context<Self>) {
+        self.set_error(message, cx);
+        cx.emit(StreamFailed {
+            model: model.to_string(),
+        });
+    }
+}
+
+/// Whether a message is sent to the model: errors and empty assistant
+/// placeholders are not, but tool-call messages with no text are.
+fn is_sendable(m: &ChatMessage) -> bool {
+    m.role != MessageRole::Error
+        && !(m.role == MessageRole::Assistant && m.content.is_empty() && m.tool_calls.is_none())
 }
 
 // ---------------------------------------------------------------------------
@@ -832,3 +1201,13 @@ pub struct StreamCompleted {
 }
 
 impl EventEmitter<StreamCompleted> for ChatService {}
+
+/// Emitted when the provider fails a request or interrupts a response.
+///
+/// The workspace subscribes to this to count the failure against the model.
+#[derive(Debug, Clone)]
+pub struct StreamFailed {
+    pub model: String,
+}
+
+impl EventEmitter<StreamFailed> for ChatService {}
@@ -2,28 +2,31 @@ use gpui::prelude::FluentBuilder;
 use gpui::*;
 use gpui_component::{Icon, IconName};
 use gpui_component::scroll::ScrollableElement;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tracing::{error, info, warn};
 
-use hive_ai::providers::AiProvider;
+use hive_ai::providers::{AiProvider, ProviderError};
 use hive_ai::speculative::{self, SpeculativeConfig};
 use hive_ai::types::{ChatRequest, ToolDefinition as AiToolDefinition};
 use hive_core::config::HiveConfig;
+use hive_core::context::CompactionConfig;
 use hive_core::notifications::{AppNotification, NotificationType};
 use hive_core::session::SessionState;
 use hive_core::theme_manager::ThemeManager;
 use hive_assistant::ReminderTrigger;
+use hive_learn::outcome_tracker::OutcomeTracker;
 
 use crate::chat_input::{ChatInputView, SubmitMessage};
-use crate::chat_service::{ChatService, StreamCompleted};
+use crate::chat_service::{ChatService, MessageRole, StreamCompleted, StreamFailed};
 use chrono::Utc;
 use hive_ui_core::{
     // Globals
-    AppAiService, AppAssistant, AppAutomation, AppChannels, AppConfig, AppDatabase, AppLearning,
-    AppMarketplace, AppNetwork, AppNotifications, AppPersonas, AppRagService, AppContextEngine,
-    AppSecurity, AppShield, AppSpecs, AppTheme, AppTts, AppUpdater,
+    AppAiService, AppAssistant, AppAutomation, AppChannels, AppConfig, AppDatabase,
+    AppFleetLearning, AppFleetSharing, AppKanban, AppLearning, AppMarketplace, AppNetwork, AppNotifications, AppOrchestration, AppPersonas,
+    AppRagService, AppContextEngine,
+    AppSearch, AppSecurity, AppShield, AppSpecs, AppTheme, AppTts, AppUpdater,
     // Types
     HiveTheme, Panel, Sidebar,
 };
@@ -38,9 +41,10 @@ pub use hive_ui_core::{
     FilesNavigateBack, FilesRefresh, FilesNewFile, FilesNewFolder,
     FilesNavigateTo, FilesOpenEntry, FilesDeleteEntry,
     HistoryRefresh, HistoryLoadConversation, HistoryDeleteConversation,
+    ChatEditMessage, ChatForkConversation, ChatRegenerateMessage, ChatSwitchBranch,
     HistoryClearAll, HistoryClearAllConfirm, HistoryClearAllCancel,
-    KanbanAddTask, LogsClear, LogsToggleAutoScroll, LogsSetFilter,
-    CostsExportCsv, CostsResetToday, CostsClearHistory,
+    KanbanAddTask, KanbanExportMetrics, LogsClear, LogsToggleAutoScroll, LogsSetFilter,
+    CostsExportCsv, CostsExportXlsx, CostsResetToday, CostsClearHistory,
     ReviewStageAll, ReviewUnstageAll, ReviewCommit, ReviewDiscardAll,
     ReviewAiCommitMessage, ReviewBranchCreate, ReviewBranchDeleteNamed, ReviewBranchRefresh,
     ReviewBranchSetName, ReviewBranchSwitch, ReviewCommitWithMessage,
@@ -54,7 +58,9 @@ pub use hive_ui_core::{
     SkillsSetCategory,
     RoutingAddRule, TokenLaunchDeploy, TokenLaunchSetStep, TokenLaunchSelectChain,
     SettingsSave, ExportConfig, ImportConfig,
-    MonitorRefresh, NetworkRefresh, AgentsReloadWorkflows, AgentsRunWorkflow,
+    MonitorRefresh, MonitorExportReport, NetworkRefresh, NetworkCreateInvite,
+    NetworkPairFromClipboard, AgentsReloadWorkflows, AgentsRunWorkflow,
+    SpecsExecute,
     SwitchToWorkflows, SwitchToChannels,
     WorkflowBuilderSave, WorkflowBuilderRun, WorkflowBuilderDeleteNode,
     WorkflowBuilderLoadWorkflow, ChannelSelect,
@@ -75,10 +81,10 @@ use hive_ui_panels::panels::{
     logs::{LogsData, LogsPanel},
     models_browser::{ModelsBrowserView, ProjectModelsChanged},
     monitor::{MonitorData, MonitorPanel, SystemResources},
-    network::{NetworkPanel, NetworkPeerData},
+    network::{FleetPeerDisplay, NetworkPanel, NetworkPeerData},
     review::{AiCommitState, BranchEntry, GitOpsTab, LfsFileEntry, PrForm, PrSummary, ReviewData, ReviewPanel},
-    routing::{RoutingData, RoutingPanel},
-    settings::{SettingsSaved, SettingsView},
+    routing::{DryRunResult, RoutingData, RoutingDryRunRequested, RoutingPanel, RoutingTesterView},
+    settings::{SecureStorageChanged, SettingsSaved, SettingsView},
     shield::{ShieldConfigChanged, ShieldPanelData, ShieldView},
     skills::{SkillsData, SkillsPanel},
     specs::{SpecPanelData, SpecsPanel},
@@ -135,6 +141,7 @@ pub struct HiveWorkspace {
     settings_view: Entity<SettingsView>,
     shield_view: Entity<ShieldView>,
     models_browser_view: Entity<ModelsBrowserView>,
+    routing_tester_view: Entity<RoutingTesterView>,
     workflow_builder_view: Entity<WorkflowBuilderView>,
     channels_view: Entity<ChannelsView>,
     /// Focus handle for the workspace root div. Ensures that `dispatch_action`
@@ -170,6 +177,14 @@ pub struct HiveWorkspace {
     /// `ChatService` generation counter changes, avoiding per-frame string
     /// cloning and enabling markdown parse caching.
     cached_chat_data: CachedChatData,
+    /// User message being edited via "edit & resend". The next submit from
+    /// the chat input replaces it with a new sibling branch.
+    editing_message_id: Option<String>,
+    /// Latest assistant reply, scored once the user's next action shows
+    /// whether it was accepted, corrected or regenerated.
+    pending_outcome: Option<PendingOutcome>,
+    /// Agent replies per channel, scored by the user's next message there.
+    channel_outcomes: HashMap<String, Vec<PendingOutcome>>,
     /// Timestamp of the last discovery scan (for 30s cadence).
     last_discovery_scan: Option<std::time::Instant>,
     /// Whether a discovery scan is currently in-flight.
@@ -203,29 +218,31 @@ impl HiveWorkspace {
         })
         .detach();
 
-        // Subscribe to stream completion events for learning instrumentation.
-        cx.subscribe(&chat_service, |_this, _svc, event: &StreamCompleted, cx| {
-            if cx.has_global::<AppLearning>() {
-                let learning = &cx.global::<AppLearning>().0;
-                let record = hive_learn::OutcomeRecord {
-                    conversation_id: String::new(),
-                    message_id: uuid::Uuid::new_v4().to_string(),
-                    model_id: event.model.clone(),
-                    task_type: "chat".into(),
-                    tier: "standard".into(),
+        // Subscribe to stream completion events for learning instrumentation
+        // and context compaction. The reply is only scored once the user's
+        // next action shows how it landed.
+        cx.subscribe(&chat_service, |this, svc, event: &StreamCompleted, cx| {
+            this.maybe_compact_context(&event.model, cx);
+            let svc = svc.read(cx);
+            this.pending_outcome = svc
+                .messages()
+                .last()
+                .filter(|m| m.role == MessageRole::Assistant && !m.content.is_empty())
+                .map(|m| PendingOutcome {
+                    conversation_id: svc.conversation_id().unwrap_or_default().to_string(),
+                    message_id: m.id.clone(),
+                    model: event.model.clone(),
                     persona: None,
-                    outcome: hive_learn::Outcome::Accepted,
-                    edit_distance: None,
-                    follow_up_count: 0,
-                    quality_score: 0.8, // default; refined by future edits/regeneration
+                    content: m.content.clone(),
                     cost: event.cost.unwrap_or(0.0),
-                    latency_ms: 0,
-                    timestamp: chrono::Utc::now().to_rfc3339(),
-                };
-                if let Err(e) = learning.on_outcome(&record) {
-                    tracing::warn!("Learning: failed to record outcome: {e}");
-                }
-            }
+                });
+        })
+        .detach();
+
+        // A failed or interrupted response counts against its model.
+        cx.subscribe(&chat_service, |this, _svc, event: &StreamFailed, cx| {
+            this.pending_outcome = None;
+            record_fleet_task(cx, "chat", &event.model, false, 0.0);
         })
         .detach();
 
@@ -310,6 +327,17 @@ impl HiveWorkspace {
         )
         .detach();
 
+        // Unlocking, locking, or re-keying secure storage changes which API
+        // keys are available, so rebuild the providers.
+        cx.subscribe_in(
+            &settings_view,
+            window,
+            |this, _view, _event: &SecureStorageChanged, _window, cx| {
+                this.handle_secure_storage_changed(cx);
+            },
+        )
+        .detach();
+
         // Create the interactive shield view entity.
         let shield_view = cx.new(|cx| ShieldView::new(window, cx));
 
@@ -386,6 +414,17 @@ impl HiveWorkspace {
         )
         .detach();
 
+        // Create the routing dry-run tester and answer its requests.
+        let routing_tester_view = cx.new(|cx| RoutingTesterView::new(window, cx));
+        cx.subscribe_in(
+            &routing_tester_view,
+            window,
+            |this, _view, event: &RoutingDryRunRequested, _window, cx| {
+                this.handle_routing_dry_run(&event.0, cx);
+            },
+        )
+        .detach();
+
         // Create the workflow builder view entity.
         let workflow_builder_view = cx.new(|cx| WorkflowBuilderView::new(window, cx));
         cx.subscribe_in(
@@ -415,6 +454,13 @@ impl HiveWorkspace {
             |this, _view, event: &ChannelMessageSent, _window, cx| {
                 info!("Channel message sent in {}: {}", event.channel_id, event.content);
 
+                // The user's reply scores the agents' previous answers.
+                let answered = this.channel_outcomes.remove(&event.channel_id);
+                for pending in answered.unwrap_or_default() {
+                    let outcome = OutcomeTracker::detect_outcome(&pending.content, &event.content);
+                    record_chat_outcome(cx, pending, outcome);
+                }
+
                 // Persist user message to the channel store.
                 if cx.has_global::<AppChannels>() {
                     let user_msg = hive_core::channels::ChannelMessage {
@@ -514,6 +560,7 @@ impl HiveWorkspace {
             settings_view,
             shield_view,
             models_browser_view,
+            routing_tester_view,
             workflow_builder_view,
             channels_view,
             focus_handle,
@@ -537,6 +584,9 @@ impl HiveWorkspace {
             session_dirty: false,
             last_saved_conversation_id: session.active_conversation_id.clone(),
             cached_chat_data: CachedChatData::new(),
+            editing_message_id: None,
+            pending_outcome: None,
+            channel_outcomes: HashMap::new(),
             last_discovery_scan: None,
             discovery_scan_pending: false,
             discovery_done_flag: None,
@@ -765,6 +815,27 @@ impl HiveWorkspace {
             })
             .collect();
 
+        let experiments = learning
+            .prompt_experiments(5)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|report| {
+                let arm = |stats: hive_learn::ArmStats| ExperimentArmDisplay {
+                    version: stats.version,
+                    samples: stats.samples,
+                    quality: stats.mean,
+                };
+                ExperimentDisplay {
+                    persona: report.experiment.persona,
+                    status: format!("{:?}", report.experiment.status),
+                    control: arm(report.control),
+                    candidate: arm(report.candidate),
+                    candidate_share: report.experiment.candidate_share,
+                    winner: report.experiment.winner,
+                }
+            })
+            .collect();
+
         let eval = learning.self_evaluator.evaluate().ok();
 
         self.learning_data = LearningPanelData {
@@ -781,6 +852,7 @@ impl HiveWorkspace {
             log_entries,
             preferences,
             prompt_suggestions: Vec::new(),
+            experiments,
             routing_insights,
             weak_areas: eval.as_ref().map_or(Vec::new(), |e| e.weak_areas.clone()),
             best_model: eval.as_ref().and_then(|e| e.best_model.clone()),
@@ -944,49 +1016,45 @@ impl HiveWorkspace {
         self.logs_data.add_entry(level, source, message);
     }
 
-    /// Load Kanban board state from `~/.hive/kanban.json`.  If the file does
-    /// not exist or cannot be parsed the board starts empty.
-    fn refresh_kanban_data(&mut self) {
-        let path = match hive_core::config::HiveConfig::base_dir() {
-            Ok(d) => d.join("kanban.json"),
-            Err(_) => return,
-        };
-        if !path.exists() {
+    /// Load the Kanban board from the shared store, where the coordinator's
+    /// tracker also moves cards.
+    fn refresh_kanban_data(&mut self, cx: &App) {
+        if !cx.has_global::<AppKanban>() {
             return;
         }
-        match std::fs::read_to_string(&path) {
-            Ok(json) => match serde_json::from_str::<hive_ui_panels::panels::kanban::KanbanData>(&json) {
-                Ok(data) => {
-                    self.kanban_data = data;
-                }
-                Err(e) => {
-                    warn!("Failed to parse kanban.json: {e}");
-                }
-            },
-            Err(e) => {
-                warn!("Failed to read kanban.json: {e}");
-            }
+        match cx.global::<AppKanban>().0.load() {
+            Ok(board) => self.kanban_data = KanbanData::from_board(&board),
+            Err(e) => warn!("Failed to load kanban board: {e}"),
         }
     }
 
-    /// Persist Kanban board state to `~/.hive/kanban.json`.
-    fn save_kanban_data(&self) {
-        let path = match hive_core::config::HiveConfig::base_dir() {
-            Ok(d) => d.join("kanban.json"),
-            Err(e) => {
-                warn!("Cannot save kanban: {e}");
-                return;
-            }
-        };
-        match serde_json::to_string_pretty(&self.kanban_data) {
-            Ok(json) => {
-                if let Err(e) = std::fs::write(&path, json) {
-                    warn!("Failed to write kanban.json: {e}");
-                }
-            }
-            Err(e) => {
-                warn!("Failed to serialize kanban data: {e}");
-            }
+    /// Mirror the board into the unified search index as `task` documents.
+    fn index_kanban_tasks(&self, cx: &App) {
+        if !cx.has_global::<AppSearch>() {
+            return;
+        }
+        let docs: Vec<hive_core::SearchDocument> = self
+            .kanban_data
+            .columns
+            .iter()
+            .flat_map(|column| {
+                column.tasks.iter().map(|task| {
+                    hive_core::SearchDocument::new(
+                        hive_core::SearchSource::Task,
+                        task.card_id.clone(),
+                        &task.title,
+                        &task.description,
+                    )
+                    .with_tags([column.status.label(), task.priority.label()])
+                })
+            })
+            .collect();
+        if let Err(e) = cx
+            .global::<AppSearch>()
+            .0
+            .replace_source(hive_core::SearchSource::Task, &docs)
+        {
+            warn!("Failed to index kanban tasks: {e}");
         }
     }
 
@@ -1138,7 +1206,11 @@ impl HiveWorkspace {
         }
 
         if cx.has_global::<AppAutomation>() {
-            let automation = &cx.global::<AppAutomation>().0;
+            let automation = cx
+                .global::<AppAutomation>()
+                .0
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
 
             self.agents_data.workflows = automation
                 .list_workflows()
@@ -1585,9 +1657,12 @@ impl HiveWorkspace {
         use hive_ui_panels::panels::workflow_builder::WorkflowListEntry;
 
         if cx.has_global::<AppAutomation>() {
-            let automation = &cx.global::<AppAutomation>().0;
-            let workflows = automation.list_workflows();
-            let entries: Vec<WorkflowListEntry> = workflows
+            let entries: Vec<WorkflowListEntry> = cx
+                .global::<AppAutomation>()
+                .0
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .list_workflows()
                 .iter()
                 .map(|wf| WorkflowListEntry {
                     id: wf.id.clone(),
@@ -1730,14 +1805,52 @@ impl HiveWorkspace {
         // Save the user text for RAG query before it is consumed by send_message.
         let user_query_text = send_text.clone();
 
-        // 1. Record user message + create placeholder assistant message.
-        self.chat_service.update(cx, |svc, cx| {
-            svc.send_message(send_text, &model, cx);
+        // 1. Record user message + create placeholder assistant message. An
+        //    edited prompt becomes a sibling branch of the original.
+        let editing = self.editing_message_id.take();
+        let chat = self.chat_service.read(cx);
+        if let Some(pending) = self.pending_outcome.take()
+            && chat.conversation_id() == Some(pending.conversation_id.as_str())
+        {
+            let outcome = if editing.is_some() {
+                hive_learn::Outcome::Corrected
+            } else {
+                OutcomeTracker::detect_outcome(&pending.content, &user_query_text)
+            };
+            record_chat_outcome(cx, pending, outcome);
+        }
+        let recorded = self.chat_service.update(cx, |svc, cx| match editing {
+            Some(id) => svc.edit_and_resend(&id, send_text, &model, cx),
+            None => {
+                svc.send_message(send_text, &model, cx);
+                Ok(())
+            }
         });
+        if let Err(e) = recorded {
+            warn!("Chat: edit & resend failed: {e}");
+            return;
+        }
 
+        self.stream_response(model, user_query_text, window, cx);
+    }
+
+    /// Stream an AI response into the placeholder assistant message at the
+    /// end of the active branch. `user_query_text` seeds retrieval context.
+    ///
+    /// Shared by normal sends, "edit & resend" and "regenerate".
+    fn stream_response(
+        &mut self,
+        model: String,
+        user_query_text: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
         // 2. Build the AI wire-format messages.
         let ai_messages = self.chat_service.read(cx).build_ai_messages();
 
+        // Files pulled into context; routing rules can match on their paths.
+        let mut context_paths: Vec<String> = Vec::new();
+
         // 2b. Query RAG and SemanticSearch for relevant context and compile via ContextEngine
         let ai_messages = {
             let mut all_context = String::new();
@@ -1775,17 +1888,34 @@ impl HiveWorkspace {
                         max_tokens: 4000,
                         max_sources: 10,
                         reserved_tokens: 0,
+                        model: Some(model.clone()),
                     };
                     let curated = ctx_engine.curate(&user_query_text, &budget);
                     
                     all_context.clear();
                     for source in curated.sources {
+                        if source.path != "rag_results.txt" {
+                            context_paths.push(source.path.clone());
+                        }
                         all_context.push_str(&source.content);
                         all_context.push_str("\n\n");
                     }
                 }
             }
 
+            // @docs / @tasks / @search … pull matches from the unified index.
+            if cx.has_global::<AppSearch>()
+                && let Some(query) = hive_core::SearchQuery::from_mentions(&user_query_text)
+            {
+                match cx.global::<AppSearch>().0.query(&query) {
+                    Ok(hits) if !hits.is_empty() => {
+                        all_context.push_str(&hive_core::search::render_context(&hits));
+                    }
+                    Ok(_) => {}
+                    Err(e) => warn!("Knowledge search failed: {e}"),
+                }
+            }
+
             if !all_context.trim().is_empty() {
                 let mut augmented = ai_messages.clone();
                 let insert_idx = augmented
@@ -1809,7 +1939,17 @@ impl HiveWorkspace {
         };
 
         // 3. Build tool definitions from the built-in tool registry.
-        let agent_defs = hive_agents::tool_use::builtin_tool_definitions();
+        let mut agent_defs = hive_agents::tool_use::builtin_tool_definitions();
+        if cx.has_global::<AppSearch>() {
+            use hive_agents::tool_use::ToolHandler as _;
+            let tool =
+                hive_agents::tool_use::SearchKnowledgeTool::new(cx.global::<AppSearch>().0.clone());
+            agent_defs.push(hive_agents::tool_use::ToolDefinition {
+                name: tool.name().to_string(),
+                description: tool.description().to_string(),
+                input_schema: tool.parameters_schema(),
+            });
+        }
         let tool_defs: Vec<AiToolDefinition> = agent_defs
             .into_iter()
             .map(|d| AiToolDefinition {
@@ -1847,6 +1987,9 @@ impl HiveWorkspace {
 
         // 4c. Extract provider + request from the global (sync — no await).
         //     If speculative decoding is enabled, also prepare the draft stream.
+        let mut routing_context = Self::routing_context(&user_query_text, context_paths, cx);
+        routing_context.interactive = true;
+
         let use_speculative = spec_config.enabled
             && cx.has_global::<AppAiService>()
             && cx.global::<AppAiService>().0.prepare_speculative_stream(
@@ -1855,26 +1998,37 @@ impl HiveWorkspace {
                 system_prompt.clone(),
                 Some(tool_defs.clone()),
                 &spec_config,
+                Some(&routing_context),
             ).is_some();
 
-        let stream_setup: Option<(Arc<dyn AiProvider>, ChatRequest)> = if cx
+        let stream_setup: Result<(Arc<dyn AiProvider>, ChatRequest), ProviderError> = if cx
             .has_global::<AppAiService>()
         {
-            cx.global::<AppAiService>()
-                .0
-                .prepare_stream(ai_messages.clone(), &model, system_prompt.clone(), Some(tool_defs.clone()))
+            cx.global::<AppAiService>().0.prepare_stream_with_context(
+                ai_messages.clone(),
+                &model,
+                system_prompt.clone(),
+                Some(tool_defs.clone()),
+                Some(&routing_context),
+            )
         } else {
-            None
+            Err(ProviderError::Other("No providers available".into()))
         };
 
-        let Some((provider, request)) = stream_setup else {
-            self.chat_service.update(cx, |svc, cx| {
-                svc.set_error(
-                    "No AI providers configured. Check Settings \u{2192} API Keys.",
-                    cx,
-                );
-            });
-            return;
+        let (provider, request) = match stream_setup {
+            Ok(setup) => setup,
+            Err(e) => {
+                let message = match e {
+                    // A routing rule refused the request; say which one.
+                    ProviderError::RouteBlocked(blocked) => blocked.to_string(),
+                    _ => "No AI providers configured. Check Settings \u{2192} API Keys."
+                        .to_string(),
+                };
+                self.chat_service.update(cx, |svc, cx| {
+                    svc.set_error(message, cx);
+                });
+                return;
+            }
         };
 
         // 5. Spawn async: call provider.stream_chat, then attach with tool loop.
@@ -1891,6 +2045,7 @@ impl HiveWorkspace {
                 system_prompt,
                 Some(tool_defs),
                 &spec_config,
+                Some(&routing_context),
             );
 
             if let Some((draft_provider, draft_request, primary_provider, primary_request)) = speculative_setup {
@@ -1927,6 +2082,8 @@ impl HiveWorkspace {
                                                 usage: None,
                                                 tool_calls: None,
                                                 stop_reason: None,
+                                                error: None,
+                                                handoff: None,
                                             }).await;
                                             in_draft_phase = false;
                                         }
@@ -1972,7 +2129,7 @@ impl HiveWorkspace {
                                 }
                                 Err(e2) => {
                                     let _ = chat_svc.update(app, |svc, cx| {
-                                        svc.set_error(format!("AI request failed: {e2}"), cx);
+                                        svc.fail_stream(&model_for_attach, format!("AI request failed: {e2}"), cx);
                                     });
                                 }
                             }
@@ -1991,7 +2148,7 @@ impl HiveWorkspace {
                         Err(e) => {
                             error!("Stream error: {e}");
                             let _ = chat_svc.update(app, |svc, cx| {
-                                svc.set_error(format!("AI request failed: {e}"), cx);
+                                svc.fail_stream(&model_for_attach, format!("AI request failed: {e}"), cx);
                             });
                         }
                     }
@@ -2015,7 +2172,11 @@ impl HiveWorkspace {
                     Err(e) => {
                         error!("Stream error: {e}");
                         let _ = chat_svc.update(app, |svc, cx| {
-                            svc.set_error(format!("AI request failed: {e}"), cx);
+                            svc.fail_stream(
+                                &model_for_attach,
+                                format!("AI request failed: {e}"),
+                                cx,
+                            );
                         });
                     }
                 }
@@ -2031,6 +2192,71 @@ impl HiveWorkspace {
         cx.notify();
     }
 
+    /// Summarize the oldest messages into a session memory when the
+    /// conversation's context has crossed the compaction threshold. Runs in
+    /// the background; the memory is used from the next request on.
+    fn maybe_compact_context(&mut self, model: &str, cx: &mut Context<Self>) {
+        if !cx.has_global::<AppAiService>() {
+            return;
+        }
+        let override_model = if cx.has_global::<AppConfig>() {
+            let cfg = cx.global::<AppConfig>().0.get();
+            if !cfg.context_compaction {
+                return;
+            }
+            cfg.compaction_model.clone()
+        } else {
+            None
+        };
+
+        let config = CompactionConfig::default();
+        let Some(plan) = self.chat_service.read(cx).compaction_plan(model, &config) else {
+            return;
+        };
+        // The summarizer sees the excerpt, so it is routed by its content.
+        let excerpt = plan
+            .messages
+            .iter()
+            .map(|m| m.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let routing_context = Self::routing_context(&excerpt, Vec::new(), cx);
+        let Some((provider, summarizer)) = cx
+            .global::<AppAiService>()
+            .0
+            .prepare_compaction(model, override_model.as_deref(), Some(&routing_context))
+        else {
+            return;
+        };
+
+        let chat_svc = self.chat_service.downgrade();
+        let model = model.to_string();
+        cx.spawn(async move |_this, app: &mut AsyncApp| {
+            let memory =
+                match hive_ai::compaction::summarize(provider.as_ref(), &summarizer, &plan).await {
+                    Ok(memory) => memory,
+                    Err(e) => {
+                        warn!("Compaction: summarization with {summarizer} failed: {e}");
+                        return;
+                    }
+                };
+            let _ = chat_svc.update(app, |svc, cx| {
+                match svc.apply_compaction(&model, &plan, memory, &summarizer) {
+                    Ok(_) if !svc.is_streaming() => {
+                        if let Err(e) = svc.save_conversation() {
+                            warn!("Compaction: failed to save conversation: {e}");
+                        }
+                    }
+                    // The save after the in-flight response picks it up.
+                    Ok(_) => {}
+                    Err(e) => warn!("Compaction: discarded summary: {e}"),
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
     /// Sync status bar with current chat service state.
     /// NOTE: This runs on every render frame — must be cheap. No file I/O here.
     fn sync_status_bar(&mut self, window: &mut Window, cx: &mut Context<Self>) {
@@ -2182,7 +2408,10 @@ impl HiveWorkspace {
             Panel::Costs => CostsPanel::render(&self.cost_data, theme).into_any_element(),
             Panel::Review => ReviewPanel::render(&self.review_data, theme).into_any_element(),
             Panel::Skills => SkillsPanel::render(&self.skills_data, theme).into_any_element(),
-            Panel::Routing => RoutingPanel::render(&self.routing_data, theme).into_any_element(),
+            Panel::Routing => {
+                RoutingPanel::render(&self.routing_data, &self.routing_tester_view, theme)
+                    .into_any_element()
+            }
             Panel::Workflows => self.workflow_builder_view.clone().into_any_element(),
             Panel::Channels => self.channels_view.clone().into_any_element(),
             Panel::Models => self.models_browser_view.clone().into_any_element(),
@@ -2240,6 +2469,7 @@ impl HiveWorkspace {
         self.chat_service.update(cx, |svc, _cx| {
             svc.new_conversation();
         });
+        self.editing_message_id = None;
         self.cached_chat_data.markdown_cache.clear();
         self.refresh_history();
         self.sidebar.active_panel = Panel::Chat;
@@ -2257,10 +2487,102 @@ impl HiveWorkspace {
         self.chat_service.update(cx, |svc, _cx| {
             svc.clear();
         });
+        self.editing_message_id = None;
         self.cached_chat_data.markdown_cache.clear();
         cx.notify();
     }
 
+    // -- Chat branching handlers ---------------------------------------------
+
+    fn handle_chat_edit_message(
+        &mut self,
+        action: &ChatEditMessage,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let content = self
+            .chat_service
+            .read(cx)
+            .messages()
+            .iter()
+            .find(|m| m.id == action.message_id)
+            .map(|m| m.content.clone());
+        let Some(content) = content else {
+            return;
+        };
+        info!("Chat: editing message {}", action.message_id);
+        self.editing_message_id = Some(action.message_id.clone());
+        self.chat_input.update(cx, |input, cx| {
+            input.set_text(&content, window, cx);
+        });
+        let fh = self.chat_input.read(cx).input_focus_handle();
+        window.focus(&fh);
+        cx.notify();
+    }
+
+    fn handle_chat_regenerate(
+        &mut self,
+        action: &ChatRegenerateMessage,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(pending) = self.pending_outcome.take()
+            && pending.message_id == action.message_id
+        {
+            record_chat_outcome(cx, pending, hive_learn::Outcome::Regenerated);
+        }
+        let model = self.chat_service.read(cx).current_model().to_string();
+        let result = self
+            .chat_service
+            .update(cx, |svc, cx| svc.regenerate(&action.message_id, &model, cx));
+        match result {
+            Ok(prompt) => {
+                info!("Chat: regenerating reply {}", action.message_id);
+                self.stream_response(model, prompt, window, cx);
+            }
+            Err(e) => warn!("Chat: regenerate failed: {e}"),
+        }
+    }
+
+    fn handle_chat_fork(
+        &mut self,
+        action: &ChatForkConversation,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let result = self
+            .chat_service
+            .update(cx, |svc, _cx| svc.fork_from(&action.message_id));
+        match result {
+            Ok(id) => {
+                info!("Chat: forked conversation into {id}");
+                self.editing_message_id = None;
+                self.cached_chat_data.markdown_cache.clear();
+                self.refresh_history();
+                self.session_dirty = true;
+            }
+            Err(e) => warn!("Chat: fork failed: {e}"),
+        }
+        cx.notify();
+    }
+
+    fn handle_chat_switch_branch(
+        &mut self,
+        action: &ChatSwitchBranch,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let result = self.chat_service.update(cx, |svc, _cx| {
+            svc.switch_branch(&action.message_id, action.offset as isize)?;
+            // Persist the active branch so it is restored on reload.
+            svc.save_conversation()
+        });
+        if let Err(e) = result {
+            warn!("Chat: failed to switch branch: {e}");
+        }
+        cx.notify();
+    }
+
     fn switch_to_panel(&mut self, panel: Panel, cx: &mut Context<Self>) {
         info!("SwitchToPanel action: {:?}", panel);
         self.sidebar.active_panel = panel;
@@ -2323,7 +2645,8 @@ impl HiveWorkspace {
                 self.refresh_logs_data(cx);
             }
             Panel::Kanban => {
-                self.refresh_kanban_data();
+                self.refresh_kanban_data(cx);
+                self.index_kanban_tasks(cx);
             }
             _ => {}
         }
@@ -2578,11 +2901,159 @@ impl HiveWorkspace {
         if !cx.has_global::<AppNetwork>() {
             return;
         }
-        let node = &cx.global::<AppNetwork>().0;
+        let node = &cx.global::<AppNetwork>().node;
         self.network_peer_data.our_peer_id = node.peer_id().to_string();
         // HiveNode::peers() is async; peer list populated via future async
         // wiring. For now the panel shows the peer ID and empty-state.
         self.network_peer_data.peers.clear();
+
+        if !cx.has_global::<AppFleetSharing>() {
+            self.network_peer_data.fleet = None;
+            return;
+        }
+        let fleet = &cx.global::<AppFleetSharing>().0;
+        let (spent, budget) = fleet.budget();
+        self.network_peer_data.fleet_budget = match budget {
+            Some(budget) => format!("{spent:.1} of {budget:.1}"),
+            None => format!("{spent:.1} (unbounded)"),
+        };
+        let mut views = fleet.peer_views();
+        views.sort_by(|a, b| a.peer_id.to_string().cmp(&b.peer_id.to_string()));
+        self.network_peer_data.fleet = Some(
+            views
+                .into_iter()
+                .map(|view| {
+                    let last = view
+                        .shared
+                        .iter()
+                        .chain(&view.received)
+                        .map(|exchange| exchange.at)
+                        .max();
+                    FleetPeerDisplay {
+                        peer: view.peer_id.short().to_string(),
+                        shared: view.shared.len(),
+                        received: view.received.len(),
+                        rejected: view.rejected,
+                        withheld: view.shared.last().map_or(0, |e| e.withheld),
+                        last_exchange: last.map_or_else(
+                            || "--".to_string(),
+                            |at| {
+                                at.with_timezone(&chrono::Local)
+                                    .format("%b %d %H:%M")
+                                    .to_string()
+                            },
+                        ),
+                    }
+                })
+                .collect(),
+        );
+    }
+
+    fn handle_network_create_invite(
+        &mut self,
+        _action: &NetworkCreateInvite,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !cx.has_global::<AppNetwork>() {
+            return;
+        }
+        let invite = cx
+            .global::<AppNetwork>()
+            .node
+            .create_pairing_invite(chrono::Duration::minutes(10));
+        let code = invite.display_code();
+        cx.write_to_clipboard(ClipboardItem::new_string(invite.to_uri()));
+        info!("Network: created pairing invite {code}");
+
+        self.network_peer_data.pairing_code = Some(code.clone());
+        if cx.has_global::<AppNotifications>() {
+            cx.global_mut::<AppNotifications>().0.push(
+                AppNotification::new(
+                    NotificationType::Info,
+                    format!("Invite {code} copied to the clipboard. It expires in 10 minutes."),
+                )
+                .with_title("Pairing Invite"),
+            );
+        }
+        cx.notify();
+    }
+
+    fn handle_network_pair_from_clipboard(
+        &mut self,
+        _action: &NetworkPairFromClipboard,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !cx.has_global::<AppNetwork>() {
+            return;
+        }
+        let uri = cx
+            .read_from_clipboard()
+            .and_then(|item| item.text())
+            .unwrap_or_default();
+        let invite = match hive_network::trust::PairingInvite::from_uri(uri.trim()) {
+            Ok(invite) => invite,
+            Err(e) => {
+                warn!("Network: clipboard does not hold a pairing invite: {e}");
+                if cx.has_global::<AppNotifications>() {
+                    cx.global_mut::<AppNotifications>().0.push(
+                        AppNotification::new(
+                            NotificationType::Error,
+                            "Copy a hive-pair:// invite from the other node first.",
+                        )
+                        .with_title("Pairing"),
+                    );
+                }
+                return;
+            }
+        };
+
+        let network = cx.global::<AppNetwork>();
+        let node = Arc::clone(&network.node);
+        let code = invite.display_code();
+        info!("Network: pairing with invite {code}");
+
+        let pair_result = Arc::new(std::sync::Mutex::new(None));
+        let pair_result_for_task = Arc::clone(&pair_result);
+        network.runtime.spawn(async move {
+            let result = node.pair_with(&invite, None).await;
+            *pair_result_for_task
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()) = Some(result);
+        });
+
+        cx.spawn(async move |this, app: &mut AsyncApp| {
+            loop {
+                if let Some(result) = pair_result.lock().unwrap_or_else(|e| e.into_inner()).take() {
+                    let _ = this.update(app, |this, cx| {
+                        let (notif_type, msg) = match result {
+                            Ok(peer) => (
+                                NotificationType::Success,
+                                format!("Paired with {}", peer.short()),
+                            ),
+                            Err(e) => {
+                                warn!("Network: pairing with invite {code} failed: {e}");
+                                (NotificationType::Error, format!("Pairing failed: {e}"))
+                            }
+                        };
+                        if cx.has_global::<AppNotifications>() {
+                            cx.global_mut::<AppNotifications>()
+                                .0
+                                .push(AppNotification::new(notif_type, msg).with_title("Pairing"));
+                        }
+                        this.refresh_network_peer_data(cx);
+                        cx.notify();
+                    });
+                    break;
+                }
+
+                app.background_executor()
+                    .timer(std::time::Duration::from_millis(250))
+                    .await;
+            }
+        })
+        .detach();
     }
 
     // -- Agents panel handlers -----------------------------------------------
@@ -2599,7 +3070,11 @@ impl HiveWorkspace {
 
         let workspace_root = std::env::current_dir().unwrap_or_default();
         let report = {
-            let automation = &mut cx.global_mut::<AppAutomation>().0;
+            let mut automation = cx
+                .global::<AppAutomation>()
+                .0
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
             automation.ensure_builtin_workflows();
             automation.reload_user_workflows(&workspace_root)
         };
@@ -2695,12 +3170,17 @@ impl HiveWorkspace {
                     let _ = this.update(app, |this, cx| {
                         match result {
                             Ok(run) => {
-                                let _ = cx.global_mut::<AppAutomation>().0.record_run(
-                                    &run.workflow_id,
-                                    run.success,
-                                    run.steps_completed,
-                                    run.error.clone(),
-                                );
+                                let _ = cx
+                                    .global::<AppAutomation>()
+                                    .0
+                                    .lock()
+                                    .unwrap_or_else(|e| e.into_inner())
+                                    .record_run(
+                                        &run.workflow_id,
+                                        run.success,
+                                        run.steps_completed,
+                                        run.error.clone(),
+                                    );
 
                                 if cx.has_global::<AppNotifications>() {
                                     let notif_type = if run.success {
@@ -2758,6 +3238,103 @@ impl HiveWorkspace {
         .detach();
     }
 
+    /// Plan the spec into tasks and run them with the coordinator on a
+    /// background thread. The tracker moves a Kanban card per task (and the
+    /// linked tracker issue, when the board is synced).
+    fn handle_specs_execute(
+        &mut self,
+        action: &SpecsExecute,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !cx.has_global::<AppSpecs>() || !cx.has_global::<AppOrchestration>() {
+            return;
+        }
+        let Some(spec) = cx.global::<AppSpecs>().0.specs.get(&action.spec_id).cloned() else {
+            warn!("Specs: no spec with id '{}'", action.spec_id);
+            return;
+        };
+        let orchestration = cx.global::<AppOrchestration>();
+        let executor = Arc::clone(&orchestration.executor);
+        let tracker = Arc::clone(&orchestration.tracker);
+        let remote = orchestration.remote.clone();
+        let title = spec.title.clone();
+
+        info!("Specs: executing '{title}' with the coordinator");
+        if cx.has_global::<AppNotifications>() {
+            cx.global_mut::<AppNotifications>().0.push(AppNotification::new(
+                NotificationType::Info,
+                format!("Executing spec '{title}'; its tasks will appear on the Kanban board"),
+            ));
+        }
+
+        let run_result = Arc::new(std::sync::Mutex::new(None));
+        let run_result_for_thread = Arc::clone(&run_result);
+        std::thread::spawn(move || {
+            let result = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(|e| format!("Failed to create runtime for spec execution: {e}"))
+                .and_then(|rt| {
+                    let mut coordinator = hive_agents::Coordinator::new(
+                        hive_agents::CoordinatorConfig::default(),
+                        executor,
+                    );
+                    coordinator.set_tracker(tracker);
+                    if let Some(remote) = remote {
+                        coordinator.set_remote(remote);
+                    }
+                    rt.block_on(coordinator.execute_spec(&spec))
+                });
+            *run_result_for_thread.lock().unwrap_or_else(|e| e.into_inner()) = Some(result);
+        });
+
+        cx.spawn(async move |this, app: &mut AsyncApp| {
+            loop {
+                if let Some(result) = run_result.lock().unwrap_or_else(|e| e.into_inner()).take() {
+                    let _ = this.update(app, |this, cx| {
+                        let (notif_type, msg) = match result {
+                            Ok(run) if run.failed_tasks() == 0 => (
+                                NotificationType::Success,
+                                format!(
+                                    "Spec '{title}' completed ({} task(s))",
+                                    run.successful_tasks()
+                                ),
+                            ),
+                            Ok(run) => (
+                                NotificationType::Warning,
+                                format!(
+                                    "Spec '{title}' finished with {} failed task(s)",
+                                    run.failed_tasks()
+                                ),
+                            ),
+                            Err(e) => {
+                                warn!("Specs: execution of '{title}' failed: {e}");
+                                (
+                                    NotificationType::Error,
+                                    format!("Spec '{title}' failed: {e}"),
+                                )
+                            }
+                        };
+                        if cx.has_global::<AppNotifications>() {
+                            cx.global_mut::<AppNotifications>()
+                                .0
+                                .push(AppNotification::new(notif_type, msg).with_title("Spec Execution"));
+                        }
+                        this.refresh_kanban_data(cx);
+                        cx.notify();
+                    });
+                    break;
+                }
+
+                app.background_executor()
+                    .timer(std::time::Duration::from_millis(250))
+                    .await;
+            }
+        })
+        .detach();
+    }
+
     fn make_workflow_for_run(
         &self,
         action: &AgentsRunWorkflow,
@@ -2773,11 +3350,17 @@ impl HiveWorkspace {
             action.workflow_id.clone()
         };
 
-        let automation = &cx.global::<AppAutomation>().0;
-        let workflow = automation
-            .clone_workflow(&requested_id)
-            .or_else(|| automation.clone_workflow(hive_agents::automation::BUILTIN_DOGFOOD_WORKFLOW_ID))
-            .or_else(|| Some(Self::fallback_workflow(&requested_id)));
+        let workflow = {
+            let automation = cx
+                .global::<AppAutomation>()
+                .0
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            automation
+                .clone_workflow(&requested_id)
+                .or_else(|| automation.clone_workflow(hive_agents::automation::BUILTIN_DOGFOOD_WORKFLOW_ID))
+                .or_else(|| Some(Self::fallback_workflow(&requested_id)))
+        };
 
         let Some(mut workflow) = workflow else {
             warn!(
@@ -2956,24 +3539,21 @@ impl HiveWorkspace {
         }
 
         if source == "kanban-task" && !source_id.is_empty() {
-            let task_id: u64 = source_id.parse().unwrap_or(0);
-            if task_id > 0 {
-                for col in &self.kanban_data.columns {
-                    if let Some(task) = col.tasks.iter().find(|task| task.id == task_id) {
-                        let title = task.title.to_lowercase();
-                        let desc = task.description.to_lowercase();
-                        if title.contains("build") || desc.contains("build") {
-                            commands.push("cargo check --quiet".to_string());
-                        }
-                        if title.contains("test") || desc.contains("test") {
-                            commands.push("cargo test --quiet -p hive_app".to_string());
-                        }
-                        if title.contains("lint") || desc.contains("lint") {
-                            commands.push("cargo fmt --check".to_string());
-                            commands.push("cargo clippy --all-targets -- -D warnings".to_string());
-                        }
-                        break;
+            for col in &self.kanban_data.columns {
+                if let Some(task) = col.tasks.iter().find(|task| task.card_id == source_id) {
+                    let title = task.title.to_lowercase();
+                    let desc = task.description.to_lowercase();
+                    if title.contains("build") || desc.contains("build") {
+                        commands.push("cargo check --quiet".to_string());
+                    }
+                    if title.contains("test") || desc.contains("test") {
+                        commands.push("cargo test --quiet -p hive_app".to_string());
+                    }
+                    if title.contains("lint") || desc.contains("lint") {
+                        commands.push("cargo fmt --check".to_string());
+                        commands.push("cargo clippy --all-targets -- -D warnings".to_string());
                     }
+                    break;
                 }
             }
         }
@@ -3303,6 +3883,7 @@ impl HiveWorkspace {
         });
         match result {
             Ok(()) => {
+                self.editing_message_id = None;
                 self.cached_chat_data.markdown_cache.clear();
                 self.sidebar.active_panel = Panel::Chat;
                 self.session_dirty = true;
@@ -3403,27 +3984,34 @@ impl HiveWorkspace {
         _window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        use hive_ui_panels::panels::kanban::{KanbanTask, Priority};
         info!("Kanban: add task");
-        let task = KanbanTask {
-            id: self
-                .kanban_data
-                .columns
-                .iter()
-                .map(|c| c.tasks.len() as u64)
-                .sum::<u64>()
-                + 1,
-            title: "New Task".to_string(),
-            description: String::new(),
-            priority: Priority::Medium,
-            created_at: chrono::Utc::now().format("%Y-%m-%d %H:%M").to_string(),
-            assigned_model: None,
-        };
-        self.kanban_data.columns[0].tasks.push(task);
-        self.save_kanban_data();
+        if !cx.has_global::<AppKanban>() {
+            return;
+        }
+        let added = cx.global::<AppKanban>().0.update(|board| {
+            board.add_task("New Task", None, hive_core::kanban::Priority::Medium);
+            Ok(())
+        });
+        if let Err(e) = added {
+            warn!("Failed to add kanban task: {e}");
+            return;
+        }
+        self.refresh_kanban_data(cx);
+        self.index_kanban_tasks(cx);
         cx.notify();
     }
 
+    fn handle_kanban_export_metrics(
+        &mut self,
+        _action: &KanbanExportMetrics,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        info!("Kanban: export metrics");
+        let report = self.kanban_data.export_xlsx();
+        self.save_export(cx, "Kanban Export", "kanban-metrics", "xlsx", report);
+    }
+
     // -- Logs panel handlers -------------------------------------------------
 
     fn push_notification(
@@ -3523,15 +4111,53 @@ impl HiveWorkspace {
             return;
         };
 
+        self.save_export(cx, "Cost Export", "costs", "csv", Ok(csv.into_bytes()));
+    }
+
+    fn handle_costs_export_xlsx(
+        &mut self,
+        _action: &CostsExportXlsx,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        info!("Costs: export XLSX");
+        let Some(report) = cx
+            .has_global::<AppAiService>()
+            .then(|| cx.global::<AppAiService>().0.cost_tracker().export_xlsx())
+        else {
+            self.push_notification(
+                cx,
+                NotificationType::Warning,
+                "Cost Export",
+                "No cost tracker available.",
+            );
+            return;
+        };
+
+        self.save_export(cx, "Cost Export", "costs", "xlsx", report);
+    }
+
+    /// Write an export to `~/.hive/exports/{stem}-{timestamp}.{extension}`
+    /// and report the outcome as a notification.
+    fn save_export(
+        &mut self,
+        cx: &mut Context<Self>,
+        title: &str,
+        stem: &str,
+        extension: &str,
+        contents: anyhow::Result<Vec<u8>>,
+    ) {
         let timestamp = chrono::Utc::now().format("%Y%m%d-%H%M%S");
         let export_dir = HiveConfig::base_dir()
             .map(|d| d.join("exports"))
             .unwrap_or_else(|_| PathBuf::from(".hive/exports"));
-        let export_path = export_dir.join(format!("costs-{timestamp}.csv"));
+        let export_path = export_dir.join(format!("{stem}-{timestamp}.{extension}"));
+        let kind = extension.to_uppercase();
 
         let result = (|| -> anyhow::Result<()> {
+            let contents = contents?;
             std::fs::create_dir_all(&export_dir)?;
-            std::fs::write(&export_path, csv)?;
+            std::fs::write(&export_path, contents)?;
             Ok(())
         })();
 
@@ -3540,17 +4166,17 @@ impl HiveWorkspace {
                 self.push_notification(
                     cx,
                     NotificationType::Success,
-                    "Cost Export",
-                    format!("Exported CSV to {}", export_path.display()),
+                    title,
+                    format!("Exported {kind} to {}", export_path.display()),
                 );
             }
             Err(e) => {
-                error!("Costs: failed to export CSV: {e}");
+                error!("{title}: failed to export {kind}: {e}");
                 self.push_notification(
                     cx,
                     NotificationType::Error,
-                    "Cost Export",
-                    format!("Failed to export CSV: {e}"),
+                    title,
+                    format!("Failed to export {kind}: {e}"),
                 );
             }
         }
@@ -3792,10 +4418,11 @@ impl HiveWorkspace {
 
         let model = self.status_bar.current_model.clone();
 
+        let routing_context = Self::routing_context(&truncated_diff, Vec::new(), cx);
         let stream_setup = if cx.has_global::<AppAiService>() {
-            cx.global::<AppAiService>().0.prepare_stream(
-                messages, &model, Some(system_prompt), None,
-            )
+            cx.global::<AppAiService>().0.prepare_stream_with_context(
+                messages, &model, Some(system_prompt), None, Some(&routing_context),
+            ).ok()
         } else {
             None
         };
@@ -4135,8 +4762,18 @@ impl HiveWorkspace {
         }];
 
         let model = self.status_bar.current_model.clone();
+        let routing_context = Self::routing_context(&truncated_diff, Vec::new(), cx);
         let stream_setup = if cx.has_global::<AppAiService>() {
-            cx.global::<AppAiService>().0.prepare_stream(messages, &model, Some(system_prompt), None)
+            cx.global::<AppAiService>()
+                .0
+                .prepare_stream_with_context(
+                    messages,
+                    &model,
+                    Some(system_prompt),
+                    None,
+                    Some(&routing_context),
+                )
+                .ok()
         } else {
             None
         };
@@ -5208,17 +5845,81 @@ impl HiveWorkspace {
         _window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        use hive_ui_panels::panels::routing::RoutingRule;
+        use hive_core::routing_rules::{RoutingRule, RuleAction, RuleConditions};
         info!("Routing: add rule");
-        self.routing_data.custom_rules.push(RoutingRule {
-            name: "New Rule".to_string(),
-            condition: "task_type == \"code\"".to_string(),
-            target_model: "auto".to_string(),
-            enabled: true,
-        });
+
+        // New rules start disabled so they never change routing until the
+        // user has edited them in ~/.hive/config.json.
+        let rule = RoutingRule {
+            name: format!("Rule {}", self.routing_data.custom_rules.len() + 1),
+            enabled: false,
+            when: RuleConditions {
+                task_types: vec!["code_gen".to_string()],
+                ..Default::default()
+            },
+            action: RuleAction::LocalOnly,
+        };
+
+        let rules = if cx.has_global::<AppConfig>() {
+            let config = &cx.global::<AppConfig>().0;
+            if let Err(e) = config.update(|cfg| cfg.routing_rules.push(rule.clone())) {
+                warn!("Routing: failed to persist rule: {e}");
+            }
+            config.get().routing_rules.clone()
+        } else {
+            vec![rule]
+        };
+
+        if cx.has_global::<AppAiService>() {
+            cx.global_mut::<AppAiService>().0.router_mut().set_rules(rules);
+        }
+        self.refresh_routing_data(cx);
         cx.notify();
     }
 
+    /// Route `prompt` without sending it and show the result in the tester.
+    fn handle_routing_dry_run(&mut self, prompt: &str, cx: &mut Context<Self>) {
+        if !cx.has_global::<AppAiService>() {
+            return;
+        }
+
+        let mut context = Self::routing_context(prompt, Vec::new(), cx);
+        context.interactive = true;
+
+        let run = cx
+            .global::<AppAiService>()
+            .0
+            .dry_run_route(prompt, Some(&context));
+        let result = DryRunResult::from_dry_run(&run);
+        self.routing_tester_view.update(cx, |view, cx| {
+            view.set_result(result, cx);
+        });
+    }
+
+    /// Routing context for an AI request carrying `text`, which routing rules
+    /// match against: its Shield data classification (when the Shield is
+    /// enabled) and the files the request pulls in. Those are
+    /// `context_paths` plus every file named by a diff header in `text`.
+    fn routing_context(
+        text: &str,
+        mut context_paths: Vec<String>,
+        cx: &App,
+    ) -> hive_ai::routing::ClassificationContext {
+        let shield_enabled = cx.has_global::<AppConfig>()
+            && cx.global::<AppConfig>().0.get().shield_enabled;
+        for path in diff_paths(text) {
+            if !context_paths.contains(&path) {
+                context_paths.push(path);
+            }
+        }
+        hive_ai::routing::ClassificationContext {
+            file_paths: context_paths,
+            data_classification: (shield_enabled && cx.has_global::<AppShield>())
+                .then(|| cx.global::<AppShield>().0.classify(text)),
+            ..Default::default()
+        }
+    }
+
     // -- Token Launch panel handlers -----------------------------------------
 
     fn handle_token_launch_set_step(
@@ -5358,6 +6059,9 @@ impl HiveWorkspace {
         self.models_browser_view.update(cx, |view, cx| {
             view.set_theme(new_theme.clone(), cx);
         });
+        self.routing_tester_view.update(cx, |view, cx| {
+            view.set_theme(new_theme.clone(), cx);
+        });
         self.channels_view.update(cx, |view, cx| {
             view.set_theme(new_theme.clone(), cx);
         });
@@ -5650,6 +6354,20 @@ impl HiveWorkspace {
         cx.notify();
     }
 
+    /// Rebuild AI providers from the keys currently readable in
+    /// `AppConfig` after secure storage was unlocked, locked, or re-keyed.
+    fn handle_secure_storage_changed(&mut self, cx: &mut Context<Self>) {
+        if !cx.has_global::<AppConfig>() || !cx.has_global::<AppAiService>() {
+            return;
+        }
+        let config = cx.global::<AppConfig>().0.get();
+        cx.global_mut::<AppAiService>()
+            .0
+            .reload_providers(hive_ai::AiServiceConfig::from(&config));
+        self.push_keys_to_models_browser(cx);
+        cx.notify();
+    }
+
     fn handle_settings_save_from_view(&mut self, cx: &mut Context<Self>) {
         info!("Settings: persisting from SettingsView");
 
@@ -5745,6 +6463,18 @@ impl HiveWorkspace {
         cx.notify();
     }
 
+    fn handle_monitor_export_report(
+        &mut self,
+        _action: &MonitorExportReport,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        info!("Monitor: export report");
+        self.refresh_monitor_data(cx);
+        let report = self.monitor_data.export_xlsx();
+        self.save_export(cx, "Monitor Export", "monitor", "xlsx", report);
+    }
+
     // NOTE: refresh_monitor_data is defined earlier in this impl block (near
     // line 705) with the full real-metrics implementation.  The handler above
     // at `handle_monitor_refresh` calls it via `self.refresh_monitor_data(cx)`.
@@ -5888,12 +6618,17 @@ impl HiveWorkspace {
                         match result {
                             Ok(run) => {
                                 if cx.has_global::<AppAutomation>() {
-                                    let _ = cx.global_mut::<AppAutomation>().0.record_run(
-                                        &run.workflow_id,
-                                        run.success,
-                                        run.steps_completed,
-                                        run.error.clone(),
-                                    );
+                                    let _ = cx
+                                        .global::<AppAutomation>()
+                                        .0
+                                        .lock()
+                                        .unwrap_or_else(|e| e.into_inner())
+                                        .record_run(
+                                            &run.workflow_id,
+                                            run.success,
+                                            run.steps_completed,
+                                            run.error.clone(),
+                                        );
                                 }
 
                                 if cx.has_global::<AppNotifications>() {
@@ -6004,13 +6739,27 @@ impl HiveWorkspace {
             });
         }
 
+        // Classify the channel history once for the routing rules.
+        let channel_text = context_messages
+            .iter()
+            .map(|m| m.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let routing_context = Self::routing_context(&channel_text, Vec::new(), cx);
+
         // For each assigned agent, spawn a streaming task
         for agent_name in assigned_agents {
+            // Serve the persona per channel so a running prompt experiment
+            // pins the channel to one variant.
             let persona = if cx.has_global::<AppPersonas>() {
-                cx.global::<AppPersonas>().0.find_by_name(&agent_name).cloned()
+                let registry = &cx.global::<AppPersonas>().0;
+                registry
+                    .find_by_name(&agent_name)
+                    .and_then(|p| registry.serve(&p.kind, &channel_id))
             } else {
                 None
             };
+            let persona_key = persona.as_ref().map(|p| p.kind.to_string());
 
             let system_prompt = persona
                 .as_ref()
@@ -6022,12 +6771,16 @@ impl HiveWorkspace {
             // Prepare the stream setup
             let stream_setup: Option<(Arc<dyn AiProvider>, ChatRequest)> =
                 if cx.has_global::<AppAiService>() {
-                    cx.global::<AppAiService>().0.prepare_stream(
-                        context_messages.clone(),
-                        &model,
-                        system_prompt,
-                        None,
-                    )
+                    cx.global::<AppAiService>()
+                        .0
+                        .prepare_stream_with_context(
+                            context_messages.clone(),
+                            &model,
+                            system_prompt,
+                            None,
+                            Some(&routing_context),
+                        )
+                        .ok()
                 } else {
                     None
                 };
@@ -6042,7 +6795,7 @@ impl HiveWorkspace {
             let agent_name_clone = agent_name.clone();
             let model_clone = model.clone();
 
-            cx.spawn(async move |_this, app: &mut AsyncApp| {
+            cx.spawn(async move |this, app: &mut AsyncApp| {
                 match provider.stream_chat(&request).await {
                     Ok(mut rx) => {
                         let mut accumulated = String::new();
@@ -6066,12 +6819,13 @@ impl HiveWorkspace {
                         let agent = agent_name_clone.clone();
                         let ch_id = channel_id_clone.clone();
                         let model_str = model_clone.clone();
+                        let message_id = uuid::Uuid::new_v4().to_string();
 
                         let _ = app.update(|cx| {
                             // Add to channel store
                             if cx.has_global::<AppChannels>() {
                                 let msg = hive_core::channels::ChannelMessage {
-                                    id: uuid::Uuid::new_v4().to_string(),
+                                    id: message_id.clone(),
                                     author: hive_core::channels::MessageAuthor::Agent {
                                         persona: agent.clone(),
                                     },
@@ -6090,12 +6844,31 @@ impl HiveWorkspace {
                                 });
                             }
                         });
+
+                        // Score the reply, crediting the prompt served to
+                        // this channel, once the user answers.
+                        let _ = this.update(app, |this, _cx| {
+                            this.channel_outcomes
+                                .entry(channel_id_clone)
+                                .or_default()
+                                .push(PendingOutcome {
+                                    conversation_id: ch_id,
+                                    message_id,
+                                    model: model_clone,
+                                    persona: persona_key,
+                                    content: final_content,
+                                    cost: 0.0,
+                                });
+                        });
                     }
                     Err(e) => {
                         error!("Channels: stream error for agent '{}': {e}", agent_name_clone);
                         let _ = channels_view.update(app, |view, cx| {
                             view.finish_streaming(cx);
                         });
+                        let _ = app.update(|cx| {
+                            record_fleet_task(cx, "chat", &model_clone, false, 0.0);
+                        });
                     }
                 }
             })
@@ -6608,6 +7381,8 @@ impl Render for HiveWorkspace {
             .on_action(cx.listener(Self::handle_switch_to_help))
             .on_action(cx.listener(Self::handle_switch_to_network))
             .on_action(cx.listener(Self::handle_network_refresh))
+            .on_action(cx.listener(Self::handle_network_create_invite))
+            .on_action(cx.listener(Self::handle_network_pair_from_clipboard))
             .on_action(cx.listener(Self::handle_open_workspace_directory))
             // -- Panel action handlers -----------------------------------
             // Files
@@ -6625,14 +7400,21 @@ impl Render for HiveWorkspace {
             .on_action(cx.listener(Self::handle_history_clear_all))
             .on_action(cx.listener(Self::handle_history_clear_all_confirm))
             .on_action(cx.listener(Self::handle_history_clear_all_cancel))
+            // Chat branching
+            .on_action(cx.listener(Self::handle_chat_edit_message))
+            .on_action(cx.listener(Self::handle_chat_regenerate))
+            .on_action(cx.listener(Self::handle_chat_fork))
+            .on_action(cx.listener(Self::handle_chat_switch_branch))
             // Kanban
             .on_action(cx.listener(Self::handle_kanban_add_task))
+            .on_action(cx.listener(Self::handle_kanban_export_metrics))
             // Logs
             .on_action(cx.listener(Self::handle_logs_clear))
             .on_action(cx.listener(Self::handle_logs_set_filter))
             .on_action(cx.listener(Self::handle_logs_toggle_auto_scroll))
             // Costs
             .on_action(cx.listener(Self::handle_costs_export_csv))
+            .on_action(cx.listener(Self::handle_costs_export_xlsx))
             .on_action(cx.listener(Self::handle_costs_reset_today))
             .on_action(cx.listener(Self::handle_costs_clear_history))
             // Review
@@ -6694,9 +7476,11 @@ impl Render for HiveWorkspace {
             .on_action(cx.listener(Self::handle_theme_changed))
             // Monitor
             .on_action(cx.listener(Self::handle_monitor_refresh))
+            .on_action(cx.listener(Self::handle_monitor_export_report))
             // Agents
             .on_action(cx.listener(Self::handle_agents_reload_workflows))
             .on_action(cx.listener(Self::handle_agents_run_workflow))
+            .on_action(cx.listener(Self::handle_specs_execute))
             // Connected Accounts
             .on_action(cx.listener(Self::handle_account_connect_platform))
             // Auto-update
@@ -6959,6 +7743,70 @@ impl EventEmitter<SwitchPanel> for HiveWorkspace {}
 // Chat cache sync (bridges ChatService → CachedChatData across crate boundary)
 // ---------------------------------------------------------------------------
 
+/// An assistant reply awaiting the user's reaction.
+struct PendingOutcome {
+    conversation_id: String,
+    message_id: String,
+    model: String,
+    persona: Option<String>,
+    content: String,
+    cost: f64,
+}
+
+/// Score a reply from the user's reaction to it and feed both the learning
+/// service and this node's fleet statistics.
+fn record_chat_outcome(cx: &App, pending: PendingOutcome, outcome: hive_learn::Outcome) {
+    let quality =
+        OutcomeTracker::compute_quality_score(outcome, 0, None, pending.content.len(), None);
+    record_fleet_task(
+        cx,
+        "chat",
+        &pending.model,
+        outcome != hive_learn::Outcome::Regenerated,
+        quality,
+    );
+    if !cx.has_global::<AppLearning>() {
+        return;
+    }
+    let record = hive_learn::OutcomeRecord {
+        conversation_id: pending.conversation_id,
+        message_id: pending.message_id,
+        model_id: pending.model,
+        task_type: "chat".into(),
+        tier: "standard".into(),
+        persona: pending.persona,
+        outcome,
+        edit_distance: None,
+        follow_up_count: 0,
+        quality_score: quality,
+        cost: pending.cost,
+        latency_ms: 0,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    };
+    if let Err(e) = cx.global::<AppLearning>().0.on_outcome(&record) {
+        warn!("Learning: failed to record outcome: {e}");
+    }
+}
+
+/// Count a finished task towards this node's per-task model statistics, the
+/// only learning data fleet sharing releases to peers. Recording and
+/// persisting happen on a background thread to keep disk I/O off the UI.
+fn record_fleet_task(cx: &App, task_type: &str, model_id: &str, success: bool, quality: f64) {
+    if model_id.is_empty() || !cx.has_global::<AppFleetLearning>() {
+        return;
+    }
+    let fleet = cx.global::<AppFleetLearning>().0.clone();
+    let task_type = task_type.to_string();
+    let model_id = model_id.to_string();
+    std::thread::spawn(move || {
+        let mut fleet = fleet.lock().unwrap_or_else(|e| e.into_inner());
+        fleet.record_task_performance(&task_type, &model_id, success, quality);
+        if let Err(e) = fleet.persist() {
+            warn!("Fleet learning: failed to persist task performance: {e}");
+        }
+    });
+}
+
 fn sync_chat_cache(cache: &mut CachedChatData, svc: &ChatService) {
     let svc_gen = svc.generation();
     if svc_gen == cache.generation {
@@ -6969,7 +7817,8 @@ fn sync_chat_cache(cache: &mut CachedChatData, svc: &ChatService) {
     cache.total_cost = 0.0;
     cache.total_tokens = 0;
 
-    for msg in svc.messages() {
+    let branch_positions = svc.branch_positions();
+    for (msg, branch) in svc.messages().iter().zip(branch_positions) {
         if msg.role == crate::chat_service::MessageRole::Assistant && msg.content.is_empty() {
             continue;
         }
@@ -7005,6 +7854,8 @@ fn sync_chat_cache(cache: &mut CachedChatData, svc: &ChatService) {
             show_thinking: false,
             tool_calls,
             tool_call_id: msg.tool_call_id.clone(),
+            message_id: msg.id.clone(),
+            branch,
         };
         if let Some(c) = display_msg.cost {
             cache.total_cost += c;
@@ -7043,6 +7894,26 @@ fn parse_etime(s: &str) -> u64 {
     days * 86400 + hours * 3600 + minutes * 60 + seconds
 }
 
+/// Files named by the `diff --git a/<old> b/<new>` headers in `text`, in
+/// order of appearance; a rename yields both paths.
+fn diff_paths(text: &str) -> Vec<String> {
+    let mut paths = Vec::new();
+    for line in text.lines() {
+        let Some((old, new)) = line
+            .strip_prefix("diff --git a/")
+            .and_then(|rest| rest.rsplit_once(" b/"))
+        else {
+            continue;
+        };
+        for path in [old, new] {
+            if !paths.iter().any(|p| p == path) {
+                paths.push(path.to_string());
+            }
+        }
+    }
+    paths
+}
+
 fn parse_github_owner_repo(url: &str) -> Option<(String, String)> {
     // HTTPS: https://github.com/owner/repo.git
     if let Some(rest) = url.strip_prefix("https://github.com/") {
@@ -2,6 +2,7 @@ use chrono::Utc;
 use uuid::Uuid;
 
 use hive_ai::types::{MessageRole as AiMessageRole, TokenUsage};
+use hive_core::context::{CompactionConfig, SessionMemory};
 use hive_core::conversations::{ConversationStore, StoredMessage};
 use hive_ui::chat_service::*;
 
@@ -420,6 +421,8 @@ fn test_chat_message_from_stored_conversion() {
         cost: Some(0.01),
         tokens: Some(50),
         thinking: None,
+        id: None,
+        parent_id: None,
     };
 
     let msg = ChatMessage::from_stored(&stored);
@@ -467,3 +470,198 @@ fn test_save_empty_conversation() {
     assert!((loaded.total_cost - 0.0).abs() < f64::EPSILON);
     assert_eq!(loaded.total_tokens, 0);
 }
+
+// -- Branching tests ----------------------------------------------------
+
+/// Helper: a saved conversation where the second prompt was edited, leaving
+/// the original prompt and its reply on an inactive branch.
+fn branched_service(store: &ConversationStore) -> ChatService {
+    let mut conv = hive_core::conversations::Conversation::new("model-a");
+    for (role, content) in [
+        ("user", "q1"),
+        ("assistant", "a1"),
+        ("user", "q2"),
+        ("assistant", "a2"),
+    ] {
+        conv.add_message(StoredMessage {
+            role: role.into(),
+            content: content.into(),
+            timestamp: Utc::now(),
+            model: None,
+            cost: None,
+            tokens: None,
+            thinking: None,
+            id: None,
+            parent_id: None,
+        });
+    }
+    let q2 = conv.messages[2].id.clone().unwrap();
+    conv.edit_message(&q2, "q2 edited").unwrap();
+    store.save(&conv).unwrap();
+
+    let mut svc = ChatService::new("model-a".into());
+    svc.load_from_store(store, &conv.id).unwrap();
+    svc
+}
+
+fn message_contents(svc: &ChatService) -> Vec<String> {
+    svc.messages().iter().map(|m| m.content.clone()).collect()
+}
+
+#[test]
+fn test_load_keeps_inactive_branches() {
+    let (store, _tmp) = temp_store();
+    let svc = branched_service(&store);
+
+    assert_eq!(message_contents(&svc), ["q1", "a1", "q2 edited"]);
+    assert_eq!(svc.branches().len(), 2);
+    assert_eq!(svc.branch_positions(), [None, None, Some((1, 2))]);
+}
+
+#[test]
+fn test_switch_branch_restores_original() {
+    let (store, _tmp) = temp_store();
+    let mut svc = branched_service(&store);
+    let edited = svc.messages()[2].id.clone();
+
+    svc.switch_branch(&edited, -1).unwrap();
+    assert_eq!(message_contents(&svc), ["q1", "a1", "q2", "a2"]);
+    assert_eq!(svc.branches().len(), 1);
+
+    // Offsets are clamped to the available siblings.
+    let original = svc.messages()[2].id.clone();
+    svc.switch_branch(&original, 5).unwrap();
+    assert_eq!(message_contents(&svc), ["q1", "a1", "q2 edited"]);
+}
+
+#[test]
+fn test_save_round_trips_branches() {
+    let (store, _tmp) = temp_store();
+    let svc = branched_service(&store);
+    let id = svc.conversation_id().unwrap().to_string();
+
+    svc.save_to_store(&store, &id).unwrap();
+    let mut reloaded = ChatService::new("default".into());
+    reloaded.load_from_store(&store, &id).unwrap();
+    assert_eq!(reloaded.branches().len(), 2);
+    assert_eq!(reloaded.branch_positions(), svc.branch_positions());
+}
+
+#[test]
+fn test_fork_into_store_copies_active_path_prefix() {
+    let (store, _tmp) = temp_store();
+    let mut svc = branched_service(&store);
+    let source_id = svc.conversation_id().unwrap().to_string();
+    let a1 = svc.messages()[1].id.clone();
+
+    let fork_id = svc.fork_into_store(&store, &a1).unwrap();
+
+    assert_ne!(fork_id, source_id);
+    assert_eq!(svc.conversation_id(), Some(fork_id.as_str()));
+    assert_eq!(message_contents(&svc), ["q1", "a1"]);
+    assert!(svc.branches().is_empty());
+    // The source conversation is left as it was.
+    assert_eq!(store.load(&source_id).unwrap().messages.len(), 3);
+    assert_eq!(store.load(&fork_id).unwrap().messages.len(), 2);
+}
+
+#[test]
+fn test_tree_ops_link_messages_pushed_directly() {
+    let (store, _tmp) = temp_store();
+    let mut svc = ChatService::new("model-a".into());
+    svc.messages.push(ChatMessage::user("hello"));
+    svc.messages.push(ChatMessage::new(MessageRole::Assistant, "hi"));
+    let reply = svc.messages()[1].id.clone();
+
+    svc.fork_into_store(&store, &reply).unwrap();
+    assert_eq!(svc.messages()[1].parent_id.as_deref(), Some(svc.messages()[0].id.as_str()));
+}
+
+// -- Compaction -------------------------------------------------------------
+
+/// Helper: a conversation of ten ~750-token messages, enough to cross the
+/// compaction threshold of an 8k-token window.
+fn long_service() -> ChatService {
+    let mut svc = ChatService::new("model-a".into());
+    for i in 0..10 {
+        let role = if i % 2 == 0 { MessageRole::User } else { MessageRole::Assistant };
+        svc.messages.push(ChatMessage::new(role, format!("{i}{}", "x".repeat(3000))));
+    }
+    svc
+}
+
+fn session_memory(summary: &str) -> SessionMemory {
+    SessionMemory {
+        summary: summary.into(),
+        ..SessionMemory::default()
+    }
+}
+
+#[test]
+fn test_compaction_replaces_old_messages_in_ai_history() {
+    let mut svc = long_service();
+    let plan = svc
+        .compaction_plan("model-a", &CompactionConfig::default())
+        .expect("context should need compaction");
+    let compacted = plan.messages.len();
+
+    svc.apply_compaction("model-a", &plan, session_memory("Earlier work"), "gpt-4o-mini")
+        .unwrap();
+
+    let ai_msgs = svc.build_ai_messages();
+    assert_eq!(ai_msgs.len(), 10 - compacted + 1);
+    assert_eq!(ai_msgs[0].role, AiMessageRole::System);
+    assert!(ai_msgs[0].content.contains("Earlier work"));
+    // The UI still shows every original message.
+    assert_eq!(svc.messages().len(), 10);
+
+    svc.undo_compaction().unwrap();
+    assert_eq!(svc.build_ai_messages().len(), 10);
+}
+
+#[test]
+fn test_compaction_keeps_tool_call_and_result_together() {
+    let mut svc = long_service();
+    let mut call = ChatMessage::new(MessageRole::Assistant, "");
+    call.tool_calls = Some(Vec::new());
+    // Insert a call/result pair right at the compaction boundary.
+    svc.messages.insert(3, call);
+    svc.messages.insert(4, ChatMessage::new(MessageRole::Tool, "tool output"));
+
+    let plan = svc
+        .compaction_plan("model-a", &CompactionConfig::default())
+        .unwrap();
+    let planned: Vec<&str> = plan.messages.iter().filter_map(|m| m.id.as_deref()).collect();
+    let call_in = planned.contains(&svc.messages()[3].id.as_str());
+    let result_in = planned.contains(&svc.messages()[4].id.as_str());
+    assert_eq!(call_in, result_in);
+}
+
+#[test]
+fn test_compactions_round_trip_through_store() {
+    let (store, _tmp) = temp_store();
+    let mut svc = long_service();
+    let plan = svc
+        .compaction_plan("model-a", &CompactionConfig::default())
+        .unwrap();
+    let record = svc
+        .apply_compaction("model-a", &plan, session_memory("Earlier work"), "gpt-4o-mini")
+        .unwrap();
+    svc.save_to_store(&store, "conv-1").unwrap();
+
+    let mut loaded = ChatService::new("model-a".into());
+    loaded.load_from_store(&store, "conv-1").unwrap();
+
+    assert_eq!(loaded.compactions(), [record]);
+    assert_eq!(loaded.messages().len(), 10);
+    assert_eq!(loaded.build_ai_messages().len(), svc.build_ai_messages().len());
+}
+
+#[test]
+fn test_no_compaction_plan_for_short_conversation() {
+    let mut svc = ChatService::new("model-a".into());
+    svc.messages.push(ChatMessage::user("hello"));
+    assert!(svc
+        .compaction_plan("model-a", &CompactionConfig::default())
+        .is_none());
+}
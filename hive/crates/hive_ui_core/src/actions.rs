@@ -43,11 +43,13 @@ actions!(
         HistoryClearAllCancel,
         // Kanban panel
         KanbanAddTask,
+        KanbanExportMetrics,
         // Logs panel
         LogsClear,
         LogsToggleAutoScroll,
         // Costs panel
         CostsExportCsv,
+        CostsExportXlsx,
         CostsResetToday,
         CostsClearHistory,
         // Review panel
@@ -84,8 +86,11 @@ actions!(
         ImportConfig,
         // Monitor panel
         MonitorRefresh,
+        MonitorExportReport,
         // Network panel
         NetworkRefresh,
+        NetworkCreateInvite,
+        NetworkPairFromClipboard,
         // Agents panel
         AgentsReloadWorkflows,
         // Panel switch — new panels
@@ -140,6 +145,37 @@ pub struct HistoryDeleteConversation {
     pub conversation_id: String,
 }
 
+/// Load a user message back into the chat input for "edit & resend". The
+/// next submit replaces it with a new sibling branch.
+#[derive(Clone, PartialEq, gpui::Action)]
+#[action(namespace = hive_workspace, no_json)]
+pub struct ChatEditMessage {
+    pub message_id: String,
+}
+
+/// Regenerate an assistant reply as a new sibling branch.
+#[derive(Clone, PartialEq, gpui::Action)]
+#[action(namespace = hive_workspace, no_json)]
+pub struct ChatRegenerateMessage {
+    pub message_id: String,
+}
+
+/// Start a new conversation from the history up to and including a message.
+#[derive(Clone, PartialEq, gpui::Action)]
+#[action(namespace = hive_workspace, no_json)]
+pub struct ChatForkConversation {
+    pub message_id: String,
+}
+
+/// Show the previous (`offset = -1`) or next (`offset = 1`) sibling branch
+/// of a message.
+#[derive(Clone, PartialEq, gpui::Action)]
+#[action(namespace = hive_workspace, no_json)]
+pub struct ChatSwitchBranch {
+    pub message_id: String,
+    pub offset: i32,
+}
+
 /// Set log filter level.
 #[derive(Clone, PartialEq, gpui::Action)]
 #[action(namespace = hive_workspace, no_json)]
@@ -203,6 +239,14 @@ pub struct AgentsRunWorkflow {
     pub source_id: String,
 }
 
+/// Plan a spec into tasks and run them with the coordinator. Each task gets a
+/// card on the Kanban board that moves as the task progresses.
+#[derive(Clone, PartialEq, gpui::Action)]
+#[action(namespace = hive_workspace, no_json)]
+pub struct SpecsExecute {
+    pub spec_id: String,
+}
+
 /// Switch to a specific tab within the Git Ops panel.
 #[derive(Clone, PartialEq, gpui::Action)]
 #[action(namespace = hive_workspace, no_json)]
@@ -9,7 +9,11 @@ use std::sync::{Arc, Mutex};
 use gpui::Global;
 
 use hive_agents::automation::AutomationService;
+use hive_agents::kanban_tracker::KanbanTracker;
 use hive_agents::mcp_server::McpServer;
+use hive_agents::remote_exec::RemoteExecService;
+use hive_agents::replication::ReplicationService;
+use hive_agents::fleet_sharing::FleetSharingService;
 use hive_agents::personas::PersonaRegistry;
 use hive_agents::skill_marketplace::SkillMarketplace;
 use hive_agents::skills::SkillsRegistry;
@@ -27,7 +31,9 @@ use hive_assistant::AssistantService;
 use hive_blockchain::rpc_config::RpcConfigStore;
 use hive_blockchain::wallet_store::WalletStore;
 use hive_core::channels::ChannelStore;
+use hive_core::kanban::KanbanStore;
 use hive_core::scheduler::Scheduler;
+use hive_core::search::SearchHub;
 use hive_network::HiveNode;
 use hive_core::config::ConfigManager;
 use hive_core::notifications::NotificationStore;
@@ -99,7 +105,10 @@ pub struct AppPersonas(pub PersonaRegistry);
 impl Global for AppPersonas {}
 
 /// Global wrapper for the automation service (workflow engine).
-pub struct AppAutomation(pub AutomationService);
+///
+/// Wrapped in `Arc<Mutex<_>>` so the webhook dispatcher can run and record
+/// workflows from its own thread.
+pub struct AppAutomation(pub Arc<Mutex<AutomationService>>);
 impl Global for AppAutomation {}
 
 /// Global wrapper for the spec manager (project specifications).
@@ -123,7 +132,10 @@ pub struct AppScheduler(pub Arc<Mutex<Scheduler>>);
 impl Global for AppScheduler {}
 
 /// Global wrapper for the wallet store (blockchain accounts).
-pub struct AppWallets(pub WalletStore);
+///
+/// Shared with the master-key migration hook registered on the config
+/// manager, which re-encrypts wallet keys when the master key changes.
+pub struct AppWallets(pub Arc<Mutex<WalletStore>>);
 impl Global for AppWallets {}
 
 /// Global wrapper for blockchain RPC endpoint configuration.
@@ -140,16 +152,46 @@ impl Global for AppChannels {}
 
 /// Global wrapper for the P2P network node (federation, peer discovery).
 ///
-/// The node is initialized at startup and begins listening for connections
-/// and discovering LAN peers in the background.  Use `AppNetwork::0` to
-/// access the `HiveNode` handle for peer queries and message sending.
-pub struct AppNetwork(pub Arc<HiveNode>);
+/// `node` is the running node itself, started at launch on the background
+/// P2P runtime. Async node calls (pairing, sending) must be spawned onto
+/// `runtime`, which keeps the node's server and discovery tasks alive.
+pub struct AppNetwork {
+    pub node: Arc<HiveNode>,
+    pub runtime: tokio::runtime::Handle,
+}
 impl Global for AppNetwork {}
 
+/// Global wrapper for CRDT replication of channels, specs and kanban boards
+/// with trusted peers. Its async calls run on [`AppNetwork::runtime`].
+pub struct AppReplication(pub Arc<ReplicationService>);
+impl Global for AppReplication {}
+
+/// Global wrapper for exchanging aggregated, noised learning statistics
+/// with trusted peers. Its async calls run on [`AppNetwork::runtime`].
+pub struct AppFleetSharing(pub Arc<FleetSharingService>);
+impl Global for AppFleetSharing {}
+
 /// Global wrapper for the messaging hub (Slack, Discord, Teams, etc.).
 pub struct AppMessaging(pub Arc<MessagingHub>);
 impl Global for AppMessaging {}
 
+/// Global wrapper for the shared Kanban board store. The Kanban panel and the
+/// coordinator's tracker both write through this one store.
+pub struct AppKanban(pub KanbanStore);
+impl Global for AppKanban {}
+
+/// Global wrapper for the background agent runtime: the executor coordinator
+/// and swarm runs send requests through, the tracker that mirrors their
+/// tasks onto the Kanban board (and its linked Jira/Linear/Asana issues), and
+/// the remote execution service that offloads them to trusted peers once it
+/// is attached to the network node.
+pub struct AppOrchestration {
+    pub executor: Arc<AiService>,
+    pub tracker: Arc<KanbanTracker>,
+    pub remote: Option<Arc<RemoteExecService>>,
+}
+impl Global for AppOrchestration {}
+
 /// Global wrapper for project management (Jira, Linear, Asana).
 pub struct AppProjectManagement(pub Arc<ProjectManagementHub>);
 impl Global for AppProjectManagement {}
@@ -217,6 +259,10 @@ impl Global for AppTheme {}
 pub struct AppRagService(pub Arc<Mutex<RagService>>);
 impl Global for AppRagService {}
 
+/// Global wrapper for the unified full-text index over all knowledge sources.
+pub struct AppSearch(pub Arc<SearchHub>);
+impl Global for AppSearch {}
+
 /// Global wrapper for semantic search service.
 pub struct AppSemanticSearch(pub Arc<Mutex<SemanticSearchService>>);
 impl Global for AppSemanticSearch {}
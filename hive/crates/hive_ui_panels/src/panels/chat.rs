@@ -6,6 +6,9 @@ use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
 
 use hive_ui_core::HiveTheme;
 use hive_ui_core::WelcomeScreen;
+use hive_ui_core::{
+    ChatEditMessage, ChatForkConversation, ChatRegenerateMessage, ChatSwitchBranch,
+};
 use hive_ai::MessageRole;
 
 // ---------------------------------------------------------------------------
@@ -325,6 +328,12 @@ pub struct DisplayMessage {
     pub tool_calls: Vec<ToolCallDisplay>,
     /// For tool result messages: the ID of the tool call this responds to.
     pub tool_call_id: Option<String>,
+    /// ID of the underlying `ChatService` message. Empty for messages that
+    /// are not part of a conversation tree (no edit/regenerate/fork actions).
+    pub message_id: String,
+    /// Position among sibling branches and the sibling count, when the
+    /// message has been edited or regenerated.
+    pub branch: Option<(usize, usize)>,
 }
 
 impl DisplayMessage {
@@ -340,6 +349,8 @@ impl DisplayMessage {
             show_thinking: false,
             tool_calls: Vec::new(),
             tool_call_id: None,
+            message_id: String::new(),
+            branch: None,
         }
     }
 
@@ -355,6 +366,8 @@ impl DisplayMessage {
             show_thinking: false,
             tool_calls: Vec::new(),
             tool_call_id: None,
+            message_id: String::new(),
+            branch: None,
         }
     }
 
@@ -370,6 +383,8 @@ impl DisplayMessage {
             show_thinking: false,
             tool_calls: Vec::new(),
             tool_call_id: None,
+            message_id: String::new(),
+            branch: None,
         }
     }
 }
@@ -705,6 +720,11 @@ fn render_message_bubble(msg: &DisplayMessage, theme: &HiveTheme) -> AnyElement
         bubble = bubble.child(render_tool_calls(&msg.tool_calls, theme));
     }
 
+    // Branch switcher + edit / regenerate / fork
+    if let Some(actions) = render_message_actions(msg, theme) {
+        bubble = bubble.child(actions);
+    }
+
     // Row alignment: user right-aligned, others left-aligned
     let row = div().flex().w_full();
     let row = if is_user {
@@ -810,6 +830,11 @@ fn render_message_bubble_cached(
         bubble = bubble.child(render_tool_calls(&msg.tool_calls, theme));
     }
 
+    // Branch switcher + edit / regenerate / fork
+    if let Some(actions) = render_message_actions(msg, theme) {
+        bubble = bubble.child(actions);
+    }
+
     let row = div().flex().w_full();
     let row = if is_user {
         row.flex_row_reverse()
@@ -1014,6 +1039,103 @@ fn render_cost_badge(cost: f64, tokens: Option<u32>, theme: &HiveTheme) -> AnyEl
         .into_any_element()
 }
 
+// ---------------------------------------------------------------------------
+// Message actions (branching)
+// ---------------------------------------------------------------------------
+
+/// Footer row with the "< 2 / 3 >" branch switcher and the edit, regenerate
+/// and fork buttons. `None` for messages outside the conversation tree.
+fn render_message_actions(msg: &DisplayMessage, theme: &HiveTheme) -> Option<AnyElement> {
+    if msg.message_id.is_empty()
+        || !matches!(msg.role, MessageRole::User | MessageRole::Assistant)
+    {
+        return None;
+    }
+    let id = msg.message_id.clone();
+
+    let mut row = div()
+        .flex()
+        .items_center()
+        .gap(theme.space_2)
+        .mt(theme.space_1)
+        .text_size(theme.font_size_xs)
+        .text_color(theme.text_muted);
+
+    if let Some((index, count)) = msg.branch {
+        row = row
+            .child(render_action_button(
+                "\u{2039}",
+                Box::new(ChatSwitchBranch {
+                    message_id: id.clone(),
+                    offset: -1,
+                }),
+                index > 0,
+                theme,
+            ))
+            .child(format!("{} / {}", index + 1, count))
+            .child(render_action_button(
+                "\u{203A}",
+                Box::new(ChatSwitchBranch {
+                    message_id: id.clone(),
+                    offset: 1,
+                }),
+                index + 1 < count,
+                theme,
+            ));
+    }
+
+    row = if msg.role == MessageRole::User {
+        row.child(render_action_button(
+            "Edit",
+            Box::new(ChatEditMessage {
+                message_id: id.clone(),
+            }),
+            true,
+            theme,
+        ))
+    } else {
+        row.child(render_action_button(
+            "Regenerate",
+            Box::new(ChatRegenerateMessage {
+                message_id: id.clone(),
+            }),
+            true,
+            theme,
+        ))
+    };
+
+    row = row.child(render_action_button(
+        "Fork",
+        Box::new(ChatForkConversation { message_id: id }),
+        true,
+        theme,
+    ));
+
+    Some(row.into_any_element())
+}
+
+fn render_action_button(
+    label: &'static str,
+    action: Box<dyn Action>,
+    enabled: bool,
+    theme: &HiveTheme,
+) -> AnyElement {
+    let button = div()
+        .px(theme.space_1)
+        .rounded(theme.radius_sm)
+        .child(label);
+    if !enabled {
+        return button.opacity(0.4).into_any_element();
+    }
+    button
+        .cursor_pointer()
+        .hover(|style: StyleRefinement| style.bg(theme.bg_tertiary).text_color(theme.text_primary))
+        .on_mouse_down(MouseButton::Left, move |_event, window, cx| {
+            window.dispatch_action(action.boxed_clone(), cx);
+        })
+        .into_any_element()
+}
+
 // ---------------------------------------------------------------------------
 // Session totals
 // ---------------------------------------------------------------------------
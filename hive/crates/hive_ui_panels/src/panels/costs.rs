@@ -1,10 +1,10 @@
 use gpui::*;
 use std::collections::HashMap;
 
-use hive_ai::CostTracker;
+use hive_ai::{CacheStats, CostTracker};
 
 use hive_ui_core::HiveTheme;
-use hive_ui_core::{CostsClearHistory, CostsExportCsv, CostsResetToday};
+use hive_ui_core::{CostsClearHistory, CostsExportCsv, CostsExportXlsx, CostsResetToday};
 
 // ---------------------------------------------------------------------------
 // Data types
@@ -38,6 +38,8 @@ pub struct CostData {
     pub total_output_tokens: usize,
     /// Per-model breakdown, sorted descending by cost.
     pub by_model: Vec<ModelCostEntry>,
+    /// Response cache hits, misses and savings.
+    pub cache: CacheStats,
 }
 
 impl CostData {
@@ -82,6 +84,7 @@ impl CostData {
             total_input_tokens: tracker.total_input_tokens(),
             total_output_tokens: tracker.total_output_tokens(),
             by_model,
+            cache: tracker.cache_stats().clone(),
         }
     }
 
@@ -94,6 +97,7 @@ impl CostData {
             total_input_tokens: 0,
             total_output_tokens: 0,
             by_model: Vec::new(),
+            cache: CacheStats::default(),
         }
     }
 }
@@ -119,6 +123,9 @@ impl CostsPanel {
             .child(Self::header(theme))
             .child(Self::summary_cards(data, theme))
             .child(Self::model_table(data, theme))
+            .when(data.cache.hits + data.cache.misses > 0, |el| {
+                el.child(Self::cache_cards(&data.cache, theme))
+            })
             .child(Self::action_buttons(theme))
     }
 
@@ -197,6 +204,42 @@ impl CostsPanel {
             ))
     }
 
+    // ------------------------------------------------------------------
+    // Response cache
+    // ------------------------------------------------------------------
+
+    fn cache_cards(cache: &CacheStats, theme: &HiveTheme) -> impl IntoElement {
+        div()
+            .flex()
+            .flex_row()
+            .gap(theme.space_3)
+            .child(Self::card(
+                "Cache Hit Rate",
+                &format!("{:.0}%", cache.hit_rate() * 100.0),
+                &format!(
+                    "{} hits / {} misses",
+                    Self::fmt_number(cache.hits),
+                    Self::fmt_number(cache.misses),
+                ),
+                theme.accent_aqua,
+                theme,
+            ))
+            .child(Self::card(
+                "Saved",
+                &format!("${:.2}", cache.saved_cost),
+                &format!("{} tokens", Self::fmt_compact(cache.saved_tokens)),
+                theme.accent_green,
+                theme,
+            ))
+            .child(Self::card(
+                "Semantic Hits",
+                &Self::fmt_number(cache.semantic_hits),
+                &format!("{} bypassed", Self::fmt_number(cache.bypassed)),
+                theme.accent_powder,
+                theme,
+            ))
+    }
+
     /// A single summary card with label, big value, subtitle, and accent color.
     fn card(
         label: &str,
@@ -439,6 +482,12 @@ impl CostsPanel {
                         cx.dispatch_action(&CostsExportCsv);
                     }),
             )
+            .child(
+                Self::action_btn("Export XLSX", "costs-export-xlsx", theme.accent_cyan, theme)
+                    .on_mouse_down(MouseButton::Left, |_event, _window, cx| {
+                        cx.dispatch_action(&CostsExportXlsx);
+                    }),
+            )
             .child(
                 Self::action_btn(
                     "Reset Today",
@@ -1,8 +1,11 @@
 use gpui::*;
 use gpui_component::{Icon, IconName};
 
+use hive_docs::xlsx::{
+    CellValue, ChartKind, ChartSpec, Column, ConditionalRule, Sheet, generate_xlsx_multi_sheet,
+};
 use hive_ui_core::HiveTheme;
-use hive_ui_core::KanbanAddTask;
+use hive_ui_core::{KanbanAddTask, KanbanExportMetrics};
 use hive_ui_core::AgentsRunWorkflow;
 
 // ---------------------------------------------------------------------------
@@ -55,10 +58,35 @@ impl Priority {
     }
 }
 
+impl From<hive_core::kanban::Priority> for Priority {
+    fn from(priority: hive_core::kanban::Priority) -> Self {
+        match priority {
+            hive_core::kanban::Priority::Low => Self::Low,
+            hive_core::kanban::Priority::Medium => Self::Medium,
+            hive_core::kanban::Priority::High => Self::High,
+            hive_core::kanban::Priority::Critical => Self::Critical,
+        }
+    }
+}
+
+impl From<Priority> for hive_core::kanban::Priority {
+    fn from(priority: Priority) -> Self {
+        match priority {
+            Priority::Low => Self::Low,
+            Priority::Medium => Self::Medium,
+            Priority::High => Self::High,
+            Priority::Critical => Self::Critical,
+        }
+    }
+}
+
 /// A single task on the Kanban board.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct KanbanTask {
     pub id: u64,
+    /// Id of the card in the shared Kanban store.
+    #[serde(default)]
+    pub card_id: String,
     pub title: String,
     pub description: String,
     pub priority: Priority,
@@ -179,6 +207,70 @@ impl KanbanData {
         data
     }
 
+    /// View of the shared Kanban board. The panel has no Blocked column, so
+    /// blocked cards are listed under To Do. Task ids are positional; use
+    /// `card_id` to address the card in the store.
+    pub fn from_board(board: &hive_core::kanban::KanbanBoard) -> Self {
+        use hive_core::kanban::KanbanColumn as BoardColumn;
+
+        let mut data = Self::default();
+        for card in board.all_tasks() {
+            let column_idx = match card.column {
+                BoardColumn::Todo | BoardColumn::Blocked => 0,
+                BoardColumn::InProgress => 1,
+                BoardColumn::Review => 2,
+                BoardColumn::Done => 3,
+            };
+            let id = data.next_id;
+            data.next_id += 1;
+            data.columns[column_idx].tasks.push(KanbanTask {
+                id,
+                card_id: card.id.clone(),
+                title: card.title.clone(),
+                description: card.description.clone().unwrap_or_default(),
+                priority: card.priority.into(),
+                created_at: card.created_at.format("%Y-%m-%d %H:%M").to_string(),
+                assigned_model: card.assignee.clone(),
+            });
+        }
+        data
+    }
+
+    /// Convert a `kanban.json` the panel saved in its own format, before it
+    /// moved onto the shared store. Returns whether the file was converted.
+    pub fn migrate_store(store: &hive_core::kanban::KanbanStore) -> anyhow::Result<bool> {
+        if store.load().is_ok() {
+            return Ok(false);
+        }
+        let json = std::fs::read_to_string(store.path())?;
+        let legacy: Self = serde_json::from_str(&json)?;
+        store.save(&legacy.into_board())?;
+        Ok(true)
+    }
+
+    fn into_board(self) -> hive_core::kanban::KanbanBoard {
+        use hive_core::kanban::{KanbanBoard, KanbanColumn as BoardColumn};
+
+        let mut board = KanbanBoard::new();
+        for column in self.columns {
+            let target = match column.status {
+                TaskStatus::Todo => BoardColumn::Todo,
+                TaskStatus::InProgress => BoardColumn::InProgress,
+                TaskStatus::Review => BoardColumn::Review,
+                TaskStatus::Done => BoardColumn::Done,
+            };
+            for task in column.tasks {
+                let description = (!task.description.is_empty()).then_some(task.description);
+                let card = board.add_task(task.title, description, task.priority.into());
+                if let Some(card) = board.get_task_mut(&card.id) {
+                    card.column = target;
+                    card.assignee = task.assigned_model;
+                }
+            }
+        }
+        board
+    }
+
     /// Adds a new task to the column at `column_idx`.
     ///
     /// Returns the assigned task ID, or `None` if `column_idx` is out of range.
@@ -195,6 +287,7 @@ impl KanbanData {
 
         column.tasks.push(KanbanTask {
             id,
+            card_id: String::new(),
             title: title.to_string(),
             description: description.to_string(),
             priority,
@@ -272,6 +365,108 @@ impl KanbanData {
             .filter(|t| t.priority == priority)
             .collect()
     }
+
+    /// Export board metrics as a spreadsheet: tasks and share per column
+    /// (with the completion rate), tasks per priority, and the full task
+    /// list. Shares are live formulas over the counts.
+    pub fn export_xlsx(&self) -> anyhow::Result<Vec<u8>> {
+        let total = self.task_count();
+        let share = |count: usize| {
+            if total == 0 {
+                0.0
+            } else {
+                count as f64 / total as f64
+            }
+        };
+
+        let mut by_column = Sheet::new(
+            "By Column",
+            vec![
+                Column::new("Column"),
+                Column::new("Tasks"),
+                Column::new("Share").with_number_format("0.0%"),
+            ],
+        )
+        .with_conditional_format(2, ConditionalRule::DataBar { color: 0x63BE7B })
+        .with_chart(ChartSpec::new(
+            ChartKind::Column,
+            "Tasks per column",
+            0,
+            [1],
+        ));
+        let counts = format!("B2:B{}", self.columns.len() + 1);
+        let mut done_row = None;
+        for (idx, column) in self.columns.iter().enumerate() {
+            let count = column.tasks.len();
+            let row = idx + 2;
+            if column.status == TaskStatus::Done {
+                done_row = Some((row, count));
+            }
+            by_column.push_row([
+                CellValue::from(column.title.clone()),
+                count.into(),
+                CellValue::formula(format!("IF(SUM({counts})=0,0,B{row}/SUM({counts}))"))
+                    .with_result(share(count)),
+            ]);
+        }
+        by_column.push_footer_row([
+            CellValue::from("Total"),
+            CellValue::formula(format!("SUM({counts})")).with_result(total),
+            CellValue::Empty,
+        ]);
+        if let Some((row, done)) = done_row {
+            by_column.push_footer_row([
+                CellValue::from("Completion rate"),
+                CellValue::Empty,
+                CellValue::formula(format!("IF(SUM({counts})=0,0,B{row}/SUM({counts}))"))
+                    .with_result(share(done)),
+            ]);
+        }
+
+        let mut by_priority = Sheet::new(
+            "By Priority",
+            vec![Column::new("Priority"), Column::new("Tasks")],
+        )
+        .with_chart(ChartSpec::new(ChartKind::Bar, "Tasks per priority", 0, [1]));
+        for priority in [
+            Priority::Critical,
+            Priority::High,
+            Priority::Medium,
+            Priority::Low,
+        ] {
+            by_priority.push_row([
+                CellValue::from(priority.label()),
+                self.tasks_with_priority(priority).len().into(),
+            ]);
+        }
+
+        let mut tasks = Sheet::new(
+            "Tasks",
+            vec![
+                Column::new("ID"),
+                Column::new("Title").with_width(40.0),
+                Column::new("Status"),
+                Column::new("Priority"),
+                Column::new("Model"),
+                Column::new("Created"),
+            ],
+        )
+        .with_frozen_header();
+        for column in &self.columns {
+            for task in &column.tasks {
+                tasks.push_row([
+                    CellValue::from(task.id),
+                    task.title.clone().into(),
+                    column.status.label().into(),
+                    task.priority.label().into(),
+                    task.assigned_model.clone().into(),
+                    task.created_at.clone().into(),
+                ]);
+            }
+        }
+
+        generate_xlsx_multi_sheet(&[by_column, by_priority, tasks])
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -338,6 +533,24 @@ impl KanbanPanel {
                 theme.accent_red,
                 theme,
             ))
+            // Export metrics
+            .child(
+                div()
+                    .id("kanban-export-metrics")
+                    .px(theme.space_3)
+                    .py(theme.space_1)
+                    .rounded(theme.radius_sm)
+                    .bg(theme.bg_surface)
+                    .border_1()
+                    .border_color(theme.border)
+                    .text_size(theme.font_size_sm)
+                    .text_color(theme.text_secondary)
+                    .cursor_pointer()
+                    .on_mouse_down(MouseButton::Left, move |_event, window, cx| {
+                        window.dispatch_action(Box::new(KanbanExportMetrics), cx);
+                    })
+                    .child("Export XLSX".to_string()),
+            )
             // Add task
             .child(
                 div()
@@ -558,7 +771,7 @@ impl KanbanPanel {
                 .child(task.created_at.clone()),
         );
 
-        let task_id = task.id;
+        let card_id = task.card_id.clone();
         let title = task.title.clone();
         let description = task.description.clone();
 
@@ -581,7 +794,7 @@ impl KanbanPanel {
                             workflow_id: "builtin:hive-dogfood-v1".into(),
                             instruction,
                             source: "kanban-task".into(),
-                            source_id: task_id.to_string(),
+                            source_id: card_id.clone(),
                         }),
                         cx,
                     );
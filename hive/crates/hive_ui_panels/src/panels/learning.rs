@@ -1,7 +1,8 @@
 //! Learning panel — Continuous self-improvement dashboard.
 //!
 //! Displays performance metrics, learning log, preferences, prompt suggestions,
-//! pattern library, routing insights, and self-evaluation reports.
+//! prompt experiments, pattern library, routing insights, and self-evaluation
+//! reports.
 
 use gpui::*;
 use gpui_component::{Icon, IconName};
@@ -45,6 +46,26 @@ pub struct RoutingInsightDisplay {
     pub confidence: f64,
 }
 
+/// Display data for one arm of a prompt experiment.
+#[derive(Debug, Clone)]
+pub struct ExperimentArmDisplay {
+    pub version: u32,
+    pub samples: u32,
+    pub quality: f64,
+}
+
+/// Display data for a prompt A/B experiment.
+#[derive(Debug, Clone)]
+pub struct ExperimentDisplay {
+    pub persona: String,
+    /// "Running", "Concluded", "Inconclusive", or "Stopped".
+    pub status: String,
+    pub control: ExperimentArmDisplay,
+    pub candidate: ExperimentArmDisplay,
+    pub candidate_share: f64,
+    pub winner: Option<u32>,
+}
+
 /// Display data for quality metrics.
 #[derive(Debug, Clone)]
 pub struct QualityMetrics {
@@ -76,6 +97,7 @@ pub struct LearningPanelData {
     pub log_entries: Vec<LogEntryDisplay>,
     pub preferences: Vec<PreferenceDisplay>,
     pub prompt_suggestions: Vec<PromptSuggestionDisplay>,
+    pub experiments: Vec<ExperimentDisplay>,
     pub routing_insights: Vec<RoutingInsightDisplay>,
     pub weak_areas: Vec<String>,
     pub best_model: Option<String>,
@@ -89,6 +111,7 @@ impl LearningPanelData {
             log_entries: Vec::new(),
             preferences: Vec::new(),
             prompt_suggestions: Vec::new(),
+            experiments: Vec::new(),
             routing_insights: Vec::new(),
             weak_areas: Vec::new(),
             best_model: None,
@@ -139,6 +162,22 @@ impl LearningPanelData {
                 },
             ],
             prompt_suggestions: Vec::new(),
+            experiments: vec![ExperimentDisplay {
+                persona: "implement".into(),
+                status: "Running".into(),
+                control: ExperimentArmDisplay {
+                    version: 3,
+                    samples: 41,
+                    quality: 0.71,
+                },
+                candidate: ExperimentArmDisplay {
+                    version: 4,
+                    samples: 12,
+                    quality: 0.79,
+                },
+                candidate_share: 0.2,
+                winner: None,
+            }],
             routing_insights: vec![RoutingInsightDisplay {
                 task_type: "debugging".into(),
                 from_tier: "Budget".into(),
@@ -177,6 +216,7 @@ impl LearningPanel {
                 theme,
             ))
             .child(render_preferences_section(&data.preferences, theme))
+            .child(render_experiments_section(&data.experiments, theme))
             .child(render_routing_section(&data.routing_insights, theme))
             .child(render_log_section(&data.log_entries, theme))
     }
@@ -437,6 +477,95 @@ fn render_preference_row(pref: &PreferenceDisplay, theme: &HiveTheme) -> AnyElem
         .into_any_element()
 }
 
+// ---------------------------------------------------------------------------
+// Prompt experiments
+// ---------------------------------------------------------------------------
+
+fn render_experiments_section(experiments: &[ExperimentDisplay], theme: &HiveTheme) -> AnyElement {
+    let mut section = div()
+        .flex()
+        .flex_col()
+        .gap(theme.space_2)
+        .child(section_title("Prompt Experiments", theme));
+
+    if experiments.is_empty() {
+        section = section.child(empty_state("No prompt experiments yet", theme));
+    } else {
+        for exp in experiments {
+            section = section.child(render_experiment_row(exp, theme));
+        }
+    }
+
+    section.into_any_element()
+}
+
+fn render_experiment_row(exp: &ExperimentDisplay, theme: &HiveTheme) -> AnyElement {
+    let status_color = match exp.status.as_str() {
+        "Running" => theme.accent_cyan,
+        "Concluded" => theme.accent_green,
+        "Inconclusive" => theme.accent_yellow,
+        _ => theme.text_muted,
+    };
+    let status = match exp.winner {
+        Some(v) => format!("{} (v{v} won)", exp.status),
+        None => exp.status.clone(),
+    };
+
+    div()
+        .flex()
+        .flex_col()
+        .gap(theme.space_1)
+        .p(theme.space_2)
+        .rounded(theme.radius_sm)
+        .bg(theme.bg_surface)
+        .border_1()
+        .border_color(theme.border)
+        .child(
+            div()
+                .flex()
+                .flex_row()
+                .items_center()
+                .gap(theme.space_2)
+                .child(
+                    div()
+                        .text_size(theme.font_size_sm)
+                        .text_color(theme.text_primary)
+                        .font_weight(FontWeight::MEDIUM)
+                        .child(exp.persona.clone()),
+                )
+                .child(
+                    div()
+                        .text_size(theme.font_size_xs)
+                        .text_color(theme.text_muted)
+                        .child(format!("{:.0}% candidate", exp.candidate_share * 100.0)),
+                )
+                .child(div().flex_1())
+                .child(
+                    div()
+                        .text_size(theme.font_size_xs)
+                        .text_color(status_color)
+                        .child(status),
+                ),
+        )
+        .child(experiment_arm_row("Control", &exp.control, theme))
+        .child(experiment_arm_row("Candidate", &exp.candidate, theme))
+        .into_any_element()
+}
+
+fn experiment_arm_row(label: &str, arm: &ExperimentArmDisplay, theme: &HiveTheme) -> Div {
+    insight_row(
+        label,
+        &format!(
+            "v{}: {:.0}% quality over {} samples",
+            arm.version,
+            arm.quality * 100.0,
+            arm.samples
+        ),
+        theme.text_secondary,
+        theme,
+    )
+}
+
 // ---------------------------------------------------------------------------
 // Routing insights
 // ---------------------------------------------------------------------------
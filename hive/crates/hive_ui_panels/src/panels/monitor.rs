@@ -2,8 +2,11 @@ use chrono::{DateTime, Utc};
 use gpui::*;
 use gpui_component::{Icon, IconName};
 
+use hive_docs::xlsx::{
+    CellValue, ChartKind, ChartSpec, Column, ConditionalRule, Sheet, generate_xlsx_multi_sheet,
+};
 use hive_ui_core::HiveTheme;
-use hive_ui_core::MonitorRefresh;
+use hive_ui_core::{MonitorExportReport, MonitorRefresh};
 
 // ---------------------------------------------------------------------------
 // Data types
@@ -405,6 +408,153 @@ impl MonitorData {
     pub fn online_provider_count(&self) -> usize {
         self.providers.iter().filter(|p| p.online).count()
     }
+
+    /// Export a spreadsheet report of the current snapshot: runtime summary,
+    /// resource usage, provider latency, active agents and run history, with
+    /// charts for latency and run cost.
+    pub fn export_xlsx(&self) -> anyhow::Result<Vec<u8>> {
+        const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+        const WARN: u32 = 0xFFC7CE;
+
+        let mut summary = Sheet::new("Summary", vec![Column::new("Metric"), Column::new("Value")]);
+        summary.push_row([CellValue::from("Generated"), Utc::now().naive_utc().into()]);
+        summary.push_row(["Status", self.status.label()]);
+        summary.push_row([
+            CellValue::from("Uptime"),
+            MonitorPanel::fmt_uptime(self.uptime_secs).into(),
+        ]);
+        summary.push_row([
+            CellValue::from("Completed tasks"),
+            self.completed_tasks.into(),
+        ]);
+        summary.push_row([CellValue::from("Total runs"), self.total_runs.into()]);
+        summary.push_row([
+            CellValue::from("Current run"),
+            self.current_run_id.clone().into(),
+        ]);
+        summary.push_row([
+            CellValue::from("Request queue"),
+            self.request_queue_length.into(),
+        ]);
+        summary.push_row([
+            CellValue::from("Active streams"),
+            self.active_streams.into(),
+        ]);
+        summary.push_row([
+            CellValue::from("Providers online"),
+            self.online_provider_count().into(),
+        ]);
+
+        let r = &self.resources;
+        let mut resources = Sheet::new(
+            "Resources",
+            vec![
+                Column::new("Resource"),
+                Column::new("Used (GiB)").with_number_format("0.0"),
+                Column::new("Total (GiB)").with_number_format("0.0"),
+                Column::new("Used").with_number_format("0.0%"),
+            ],
+        )
+        .with_conditional_format(3, ConditionalRule::DataBar { color: 0x638EC6 })
+        .with_conditional_format(
+            3,
+            ConditionalRule::GreaterThan {
+                value: 0.9,
+                color: WARN,
+            },
+        );
+        resources.push_row([
+            CellValue::from("CPU"),
+            CellValue::Empty,
+            CellValue::Empty,
+            (r.cpu_percent / 100.0).into(),
+        ]);
+        for (name, used, total, percent) in [
+            ("Memory", r.memory_used, r.memory_total, r.memory_percent()),
+            ("Disk", r.disk_used, r.disk_total, r.disk_percent()),
+        ] {
+            resources.push_row([
+                CellValue::from(name),
+                (used as f64 / GIB).into(),
+                (total as f64 / GIB).into(),
+                (percent / 100.0).into(),
+            ]);
+        }
+
+        let mut providers = Sheet::new(
+            "Providers",
+            vec![
+                Column::new("Provider"),
+                Column::new("Online"),
+                Column::new("Latency (ms)"),
+            ],
+        )
+        .with_frozen_header()
+        .with_conditional_format(
+            2,
+            ConditionalRule::ColorScale {
+                min_color: 0x63BE7B,
+                max_color: 0xF8696B,
+            },
+        )
+        .with_chart(ChartSpec::new(ChartKind::Bar, "Provider latency", 0, [2]));
+        for p in &self.providers {
+            providers.push_row([
+                CellValue::from(p.name.clone()),
+                p.online.into(),
+                p.latency_ms.map(|ms| ms as f64).into(),
+            ]);
+        }
+
+        let mut agents = Sheet::new(
+            "Agents",
+            vec![
+                Column::new("Role"),
+                Column::new("Status"),
+                Column::new("Phase"),
+                Column::new("Model"),
+                Column::new("Started").with_width(18.0),
+            ],
+        )
+        .with_frozen_header();
+        for a in &self.active_agents {
+            agents.push_row([
+                CellValue::from(a.role.clone()),
+                a.status.label().into(),
+                a.phase.clone().into(),
+                a.model.clone().into(),
+                a.started_at.naive_utc().into(),
+            ]);
+        }
+
+        let mut runs = Sheet::new(
+            "Runs",
+            vec![
+                Column::new("Run"),
+                Column::new("Task").with_width(48.0),
+                Column::new("Agents"),
+                Column::new("Status"),
+                Column::new("Cost").with_number_format("$#,##0.00"),
+                Column::new("Duration (s)"),
+                Column::new("Started"),
+            ],
+        )
+        .with_frozen_header()
+        .with_chart(ChartSpec::new(ChartKind::Column, "Cost per run", 0, [4]));
+        for run in &self.run_history {
+            runs.push_row([
+                CellValue::from(run.id.clone()),
+                run.task_summary.clone().into(),
+                run.agents_used.into(),
+                run.status.label().into(),
+                run.cost.into(),
+                run.duration_secs.into(),
+                run.started_at.clone().into(),
+            ]);
+        }
+
+        generate_xlsx_multi_sheet(&[summary, resources, providers, agents, runs])
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -456,6 +606,7 @@ impl MonitorPanel {
             .child(Self::header_title(theme))
             .child(div().flex_1())
             .child(Self::uptime_label(data, theme))
+            .child(Self::export_btn(theme))
             .child(Self::refresh_btn(theme))
             .child(Self::status_badge(badge_label, badge_color, theme))
     }
@@ -487,6 +638,25 @@ impl MonitorPanel {
             .child("Refresh".to_string())
     }
 
+    /// Export button -- dispatches `MonitorExportReport`.
+    fn export_btn(theme: &HiveTheme) -> impl IntoElement {
+        div()
+            .id("monitor-export-btn")
+            .px(theme.space_2)
+            .py(theme.space_1)
+            .rounded(theme.radius_sm)
+            .bg(theme.bg_surface)
+            .border_1()
+            .border_color(theme.border)
+            .text_size(theme.font_size_xs)
+            .text_color(theme.text_secondary)
+            .cursor_pointer()
+            .on_mouse_down(MouseButton::Left, |_event, _window, cx| {
+                cx.dispatch_action(&MonitorExportReport);
+            })
+            .child("Export XLSX".to_string())
+    }
+
     fn uptime_label(data: &MonitorData, theme: &HiveTheme) -> Div {
         div()
             .text_size(theme.font_size_xs)
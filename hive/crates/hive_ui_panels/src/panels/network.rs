@@ -2,7 +2,7 @@ use gpui::*;
 use gpui_component::{Icon, IconName};
 
 use hive_ui_core::HiveTheme;
-use hive_ui_core::NetworkRefresh;
+use hive_ui_core::{NetworkCreateInvite, NetworkPairFromClipboard, NetworkRefresh};
 
 // ---------------------------------------------------------------------------
 // Data types
@@ -18,11 +18,29 @@ pub struct PeerDisplayInfo {
     pub last_seen: String,
 }
 
+/// Fleet statistics exchanged with one peer.
+#[derive(Debug, Clone)]
+pub struct FleetPeerDisplay {
+    pub peer: String,
+    pub shared: usize,
+    pub received: usize,
+    pub rejected: usize,
+    /// Pairs withheld from the last release sent to this peer.
+    pub withheld: usize,
+    pub last_exchange: String,
+}
+
 /// All data needed to render the Network panel.
 #[derive(Debug, Clone, Default)]
 pub struct NetworkPeerData {
     pub our_peer_id: String,
     pub peers: Vec<PeerDisplayInfo>,
+    /// Code of the pairing invite we last issued, shown until it is replaced.
+    pub pairing_code: Option<String>,
+    /// Per-peer fleet sharing ledger; `None` when fleet sharing is off.
+    pub fleet: Option<Vec<FleetPeerDisplay>>,
+    /// Privacy budget spent on fleet releases, e.g. `"3.0 of 30.0"`.
+    pub fleet_budget: String,
 }
 
 impl NetworkPeerData {
@@ -66,6 +84,9 @@ impl NetworkPeerData {
                     last_seen: "5 min ago".into(),
                 },
             ],
+            pairing_code: None,
+            fleet: None,
+            fleet_budget: String::new(),
         }
     }
 }
@@ -93,6 +114,11 @@ impl NetworkPanel {
             .child(Self::identity_section(data, theme))
             .child(Self::stats_row(data, theme))
             .child(Self::peers_section(data, theme))
+            .children(
+                data.fleet
+                    .as_ref()
+                    .map(|fleet| Self::fleet_section(fleet, &data.fleet_budget, theme)),
+            )
     }
 
     // ------------------------------------------------------------------
@@ -124,14 +150,36 @@ impl NetworkPanel {
                     .child("P2P Network".to_string()),
             )
             .child(div().flex_1())
-            .child(Self::refresh_btn(theme))
+            .child(Self::header_btn(
+                "network-invite-btn",
+                "Invite",
+                Box::new(NetworkCreateInvite),
+                theme,
+            ))
+            .child(Self::header_btn(
+                "network-pair-btn",
+                "Pair from Clipboard",
+                Box::new(NetworkPairFromClipboard),
+                theme,
+            ))
+            .child(Self::header_btn(
+                "network-refresh-btn",
+                "Refresh",
+                Box::new(NetworkRefresh),
+                theme,
+            ))
             .child(Self::status_badge(badge_label, badge_color, theme))
     }
 
-    /// Refresh button — dispatches `NetworkRefresh`.
-    fn refresh_btn(theme: &HiveTheme) -> impl IntoElement {
+    /// Header button that dispatches `action` when clicked.
+    fn header_btn(
+        id: &'static str,
+        label: &'static str,
+        action: Box<dyn Action>,
+        theme: &HiveTheme,
+    ) -> impl IntoElement {
         div()
-            .id("network-refresh-btn")
+            .id(id)
             .px(theme.space_2)
             .py(theme.space_1)
             .rounded(theme.radius_sm)
@@ -141,10 +189,10 @@ impl NetworkPanel {
             .text_size(theme.font_size_xs)
             .text_color(theme.text_secondary)
             .cursor_pointer()
-            .on_mouse_down(MouseButton::Left, |_event, _window, cx| {
-                cx.dispatch_action(&NetworkRefresh);
+            .on_mouse_down(MouseButton::Left, move |_event, window, cx| {
+                window.dispatch_action(action.boxed_clone(), cx);
             })
-            .child("Refresh".to_string())
+            .child(label)
     }
 
     fn status_badge(label: &str, color: Hsla, theme: &HiveTheme) -> Div {
@@ -219,7 +267,29 @@ impl NetworkPanel {
                                     data.our_peer_id.clone()
                                 }),
                         ),
-                ),
+                )
+                .child(div().flex_1())
+                .children(data.pairing_code.as_ref().map(|code| {
+                    div()
+                        .flex()
+                        .flex_col()
+                        .items_end()
+                        .gap(px(2.0))
+                        .child(
+                            div()
+                                .text_size(theme.font_size_xs)
+                                .text_color(theme.text_muted)
+                                .child("Pairing code (invite copied)"),
+                        )
+                        .child(
+                            div()
+                                .text_size(theme.font_size_sm)
+                                .text_color(theme.accent_cyan)
+                                .font_weight(FontWeight::BOLD)
+                                .font_family(theme.font_mono.clone())
+                                .child(code.clone()),
+                        )
+                })),
         )
     }
 
@@ -415,6 +485,100 @@ impl NetworkPanel {
             )
     }
 
+    // ------------------------------------------------------------------
+    // Fleet Sharing
+    // ------------------------------------------------------------------
+
+    fn fleet_section(
+        fleet: &[FleetPeerDisplay],
+        budget: &str,
+        theme: &HiveTheme,
+    ) -> impl IntoElement {
+        let mut container = Self::section("Fleet Sharing", theme)
+            .child(
+                div()
+                    .text_size(theme.font_size_xs)
+                    .text_color(theme.text_muted)
+                    .child(format!(
+                        "Only noised per-task statistics are shared. Privacy budget spent: {budget}"
+                    )),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .gap(theme.space_2)
+                    .pb(theme.space_1)
+                    .border_b_1()
+                    .border_color(theme.border)
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_size(theme.font_size_xs)
+                            .text_color(theme.text_muted)
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .child("Peer".to_string()),
+                    )
+                    .child(Self::col_hdr("Shared", px(70.0), theme))
+                    .child(Self::col_hdr("Withheld", px(70.0), theme))
+                    .child(Self::col_hdr("Received", px(70.0), theme))
+                    .child(Self::col_hdr("Rejected", px(70.0), theme))
+                    .child(Self::col_hdr("Last Exchange", px(120.0), theme)),
+            );
+
+        if fleet.is_empty() {
+            container = container.child(Self::empty_state(
+                "Nothing exchanged yet. Statistics are offered to trusted peers every few minutes.",
+                theme,
+            ));
+        }
+        for peer in fleet {
+            let cell = |value: String, color: Hsla| {
+                div()
+                    .w(px(70.0))
+                    .text_size(theme.font_size_sm)
+                    .text_color(color)
+                    .child(value)
+            };
+            container = container.child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .gap(theme.space_2)
+                    .py(theme.space_1)
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_size(theme.font_size_sm)
+                            .text_color(theme.text_primary)
+                            .font_family(theme.font_mono.clone())
+                            .child(peer.peer.clone()),
+                    )
+                    .child(cell(peer.shared.to_string(), theme.text_secondary))
+                    .child(cell(peer.withheld.to_string(), theme.text_muted))
+                    .child(cell(peer.received.to_string(), theme.text_secondary))
+                    .child(cell(
+                        peer.rejected.to_string(),
+                        if peer.rejected > 0 {
+                            theme.accent_red
+                        } else {
+                            theme.text_muted
+                        },
+                    ))
+                    .child(
+                        div()
+                            .w(px(120.0))
+                            .text_size(theme.font_size_sm)
+                            .text_color(theme.text_muted)
+                            .child(peer.last_exchange.clone()),
+                    ),
+            );
+        }
+        container
+    }
+
     // ------------------------------------------------------------------
     // Shared helpers
     // ------------------------------------------------------------------
@@ -1,9 +1,10 @@
 use gpui::*;
+use gpui_component::input::{Input, InputEvent, InputState};
 use hive_ai::ModelTier;
-use hive_ai::routing::{AutoFallbackManager, ModelRouter, ProviderType};
+use hive_ai::routing::{AutoFallbackManager, ModelRouter, ProviderType, RoutingDryRun};
 
-use hive_ui_core::HiveTheme;
 use hive_ui_core::RoutingAddRule;
+use hive_ui_core::{AppTheme, HiveTheme};
 
 // ---------------------------------------------------------------------------
 // Data types
@@ -45,6 +46,69 @@ pub struct RoutingRule {
     pub enabled: bool,
 }
 
+impl RoutingRule {
+    /// Display form of a configured rule.
+    pub fn from_config(rule: &hive_core::RoutingRule) -> Self {
+        Self {
+            name: rule.name.clone(),
+            condition: rule.when.describe(),
+            target_model: rule.action.describe(),
+            enabled: rule.enabled,
+        }
+    }
+}
+
+/// Outcome of a routing dry run, for display in the tester.
+#[derive(Debug, Clone)]
+pub struct DryRunResult {
+    pub task_type: String,
+    pub heuristic_model: String,
+    pub model_id: String,
+    pub provider: String,
+    pub tier: String,
+    pub matched_rules: Vec<String>,
+    pub estimated_cost: f64,
+    pub reasoning: String,
+    /// The rules forbid every available model, so the request would be
+    /// refused.
+    pub blocked: bool,
+}
+
+impl DryRunResult {
+    pub fn from_dry_run(run: &RoutingDryRun) -> Self {
+        let (model_id, provider, tier, reasoning) = match &run.decision {
+            Ok(decision) => (
+                decision.model_id.clone(),
+                decision.provider.to_string(),
+                tier_label(decision.tier).to_string(),
+                decision.reasoning.clone(),
+            ),
+            Err(blocked) => (
+                "Refused".to_string(),
+                "none".to_string(),
+                "-".to_string(),
+                blocked.to_string(),
+            ),
+        };
+        Self {
+            task_type: run.task_type.to_string(),
+            heuristic_model: run.heuristic.model_id.clone(),
+            model_id,
+            provider,
+            tier,
+            matched_rules: run.matched_rules.clone(),
+            estimated_cost: run.estimated_cost,
+            reasoning,
+            blocked: run.decision.is_err(),
+        }
+    }
+
+    /// Whether the rules changed the heuristic choice.
+    pub fn overridden(&self) -> bool {
+        self.model_id != self.heuristic_model
+    }
+}
+
 /// Provider status entry for the performance tracking table.
 #[derive(Debug, Clone)]
 pub struct ProviderStatusEntry {
@@ -140,12 +204,15 @@ impl RoutingData {
         let task_mappings = build_task_mappings_from_router(router);
         let provider_status = build_provider_status(router.fallback_manager());
 
-        // Custom rules are user-configured; the ModelRouter does not store
-        // them, so we start with an empty list. The caller can populate this
-        // from persisted config.
+        let custom_rules = router
+            .rules()
+            .iter()
+            .map(RoutingRule::from_config)
+            .collect();
+
         Self {
             task_mappings,
-            custom_rules: Vec::new(),
+            custom_rules,
             provider_status,
         }
     }
@@ -399,6 +466,217 @@ fn build_provider_status(fallback_manager: &AutoFallbackManager) -> Vec<Provider
         .collect()
 }
 
+// ---------------------------------------------------------------------------
+// Dry-run tester
+// ---------------------------------------------------------------------------
+
+/// Emitted when the user asks how a prompt would be routed.
+#[derive(Debug, Clone)]
+pub struct RoutingDryRunRequested(pub String);
+
+/// "Dry-run this prompt" box: shows which model a prompt would be routed to
+/// and which rules fired, without sending anything.
+pub struct RoutingTesterView {
+    theme: HiveTheme,
+    prompt_input: Entity<InputState>,
+    result: Option<DryRunResult>,
+}
+
+impl EventEmitter<RoutingDryRunRequested> for RoutingTesterView {}
+
+impl RoutingTesterView {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let prompt_input = cx.new(|cx| {
+            let mut state = InputState::new(window, cx);
+            state.set_placeholder(
+                "Type a prompt to see where it would be routed...",
+                window,
+                cx,
+            );
+            state
+        });
+
+        cx.subscribe_in(
+            &prompt_input,
+            window,
+            |this, _entity, event: &InputEvent, _window, cx| {
+                if let InputEvent::PressEnter { .. } = event {
+                    this.request_dry_run(cx);
+                }
+            },
+        )
+        .detach();
+
+        let theme = if cx.has_global::<AppTheme>() {
+            cx.global::<AppTheme>().0.clone()
+        } else {
+            HiveTheme::dark()
+        };
+
+        Self {
+            theme,
+            prompt_input,
+            result: None,
+        }
+    }
+
+    /// Replace the cached theme and trigger a re-render.
+    pub fn set_theme(&mut self, theme: HiveTheme, cx: &mut Context<Self>) {
+        self.theme = theme;
+        cx.notify();
+    }
+
+    /// Show the outcome of the last dry run.
+    pub fn set_result(&mut self, result: DryRunResult, cx: &mut Context<Self>) {
+        self.result = Some(result);
+        cx.notify();
+    }
+
+    fn request_dry_run(&mut self, cx: &mut Context<Self>) {
+        let prompt = self.prompt_input.read(cx).value().to_string();
+        if !prompt.trim().is_empty() {
+            cx.emit(RoutingDryRunRequested(prompt));
+        }
+    }
+
+    fn render_result(result: &DryRunResult, theme: &HiveTheme) -> Div {
+        let rules = if result.matched_rules.is_empty() {
+            "none".to_string()
+        } else {
+            result.matched_rules.join(", ")
+        };
+        let model_color = if result.blocked {
+            theme.accent_red
+        } else if result.overridden() {
+            theme.accent_yellow
+        } else {
+            theme.accent_green
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .gap(theme.space_1)
+            .p(theme.space_3)
+            .rounded(theme.radius_sm)
+            .bg(theme.bg_tertiary)
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .gap(theme.space_2)
+                    .child(
+                        div()
+                            .text_size(theme.font_size_sm)
+                            .text_color(model_color)
+                            .font_weight(FontWeight::BOLD)
+                            .child(result.model_id.clone()),
+                    )
+                    .child(
+                        div()
+                            .text_size(theme.font_size_xs)
+                            .text_color(theme.text_muted)
+                            .child(format!("via {} \u{00B7} {}", result.provider, result.tier)),
+                    ),
+            )
+            .child(Self::result_line("Task", &result.task_type, theme))
+            .child(Self::result_line(
+                "Heuristic choice",
+                &result.heuristic_model,
+                theme,
+            ))
+            .child(Self::result_line("Rules fired", &rules, theme))
+            .child(Self::result_line(
+                "Estimated cost",
+                &format!("${:.4}", result.estimated_cost),
+                theme,
+            ))
+            .child(
+                div()
+                    .text_size(theme.font_size_xs)
+                    .text_color(theme.text_secondary)
+                    .child(result.reasoning.clone()),
+            )
+    }
+
+    fn result_line(label: &str, value: &str, theme: &HiveTheme) -> Div {
+        div()
+            .flex()
+            .flex_row()
+            .gap(theme.space_2)
+            .text_size(theme.font_size_xs)
+            .child(
+                div()
+                    .w(px(110.0))
+                    .text_color(theme.text_muted)
+                    .child(label.to_string()),
+            )
+            .child(
+                div()
+                    .text_color(theme.text_primary)
+                    .child(value.to_string()),
+            )
+    }
+}
+
+impl Render for RoutingTesterView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = &self.theme;
+
+        let mut card = div()
+            .flex()
+            .flex_col()
+            .bg(theme.bg_surface)
+            .border_1()
+            .border_color(theme.border)
+            .rounded(theme.radius_md)
+            .p(theme.space_4)
+            .gap(theme.space_2)
+            .child(RoutingPanel::section_title("Dry Run", theme))
+            .child(RoutingPanel::section_desc(
+                "See which model a prompt would use and which rules fire, without sending it",
+                theme,
+            ))
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .gap(theme.space_2)
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w(px(0.0))
+                            .child(Input::new(&self.prompt_input).text_size(theme.font_size_sm)),
+                    )
+                    .child(
+                        div()
+                            .id("btn-routing-dry-run")
+                            .px(theme.space_3)
+                            .py(theme.space_1)
+                            .rounded(theme.radius_sm)
+                            .bg(theme.bg_tertiary)
+                            .text_size(theme.font_size_xs)
+                            .text_color(theme.accent_cyan)
+                            .cursor_pointer()
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|this, _event, _window, cx| {
+                                    this.request_dry_run(cx);
+                                }),
+                            )
+                            .child("Dry run"),
+                    ),
+            );
+
+        if let Some(result) = &self.result {
+            card = card.child(Self::render_result(result, theme));
+        }
+        card
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Panel
 // ---------------------------------------------------------------------------
@@ -407,7 +685,11 @@ fn build_provider_status(fallback_manager: &AutoFallbackManager) -> Vec<Provider
 pub struct RoutingPanel;
 
 impl RoutingPanel {
-    pub fn render(data: &RoutingData, theme: &HiveTheme) -> impl IntoElement {
+    pub fn render(
+        data: &RoutingData,
+        tester: &Entity<RoutingTesterView>,
+        theme: &HiveTheme,
+    ) -> impl IntoElement {
         let metrics = PerformanceMetrics::from_data(data);
 
         div()
@@ -425,6 +707,7 @@ impl RoutingPanel {
             .child(Self::performance_card(data, theme))
             .child(Self::fallback_chain_card(theme))
             .child(Self::custom_rules_card(data, theme))
+            .child(tester.clone())
             .child(Self::hard_rules_card(theme))
     }
 
@@ -8,6 +8,7 @@ use gpui_component::{Icon, IconName};
 use hive_ai::types::ProviderType;
 
 use crate::components::model_selector::{ModelSelected, ModelSelectorView};
+use hive_core::MasterKeySource;
 use hive_core::theme_manager::ThemeManager;
 use hive_ui_core::AppConfig;
 use hive_ui_core::{AppTheme, HiveTheme, ThemeChanged};
@@ -29,6 +30,10 @@ actions!(
         SettingsToggleClawdTalk,
         SettingsToggleSpeculativeDecoding,
         SettingsToggleSpeculativeMetrics,
+        SettingsTogglePassphrase,
+        SettingsToggleKeyring,
+        SettingsUnlockStorage,
+        SettingsLockStorage,
     ]
 );
 
@@ -41,6 +46,11 @@ actions!(
 #[derive(Debug, Clone)]
 pub struct SettingsSaved;
 
+/// Emitted after secure storage was unlocked, locked, or moved to a new
+/// master key. The workspace rebuilds AI providers from the fresh keys.
+#[derive(Debug, Clone)]
+pub struct SecureStorageChanged;
+
 // ---------------------------------------------------------------------------
 // SettingsData -- read-only snapshot for other panels
 // ---------------------------------------------------------------------------
@@ -273,9 +283,15 @@ pub struct SettingsView {
     // Theme picker
     selected_theme: String,
     available_themes: Vec<String>,
+
+    // Secure storage
+    passphrase_input: Entity<InputState>,
+    idle_lock_input: Entity<InputState>,
+    security_status: Option<String>,
 }
 
 impl EventEmitter<SettingsSaved> for SettingsView {}
+impl EventEmitter<SecureStorageChanged> for SettingsView {}
 
 impl SettingsView {
     pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
@@ -468,6 +484,28 @@ impl SettingsView {
             state
         });
 
+        // Secure storage inputs
+        let idle_lock_minutes = if cx.has_global::<AppConfig>() {
+            cx.global::<AppConfig>().0.idle_lock_minutes()
+        } else {
+            0
+        };
+        let passphrase_input = cx.new(|cx| {
+            let mut state = InputState::new(window, cx);
+            state.set_placeholder("Passphrase", window, cx);
+            state
+        });
+        let idle_lock_input = cx.new(|cx| {
+            let mut state = InputState::new(window, cx);
+            state.set_placeholder("0 (never)", window, cx);
+            if idle_lock_minutes > 0 {
+                state.set_value(idle_lock_minutes.to_string(), window, cx);
+            }
+            state
+        });
+        cx.subscribe_in(&idle_lock_input, window, Self::on_idle_lock_event)
+            .detach();
+
         // Subscribe to blur events on all text inputs → auto-save
         let all_inputs = [
             &anthropic_key_input,
@@ -570,6 +608,9 @@ impl SettingsView {
             telegram_client_id_input,
             selected_theme,
             available_themes,
+            passphrase_input,
+            idle_lock_input,
+            security_status: None,
         };
 
         // Initialize model selector with current provider availability
@@ -613,6 +654,95 @@ impl SettingsView {
         }
     }
 
+    /// Persist the idle auto-lock timeout when its input loses focus.
+    fn on_idle_lock_event(
+        &mut self,
+        state: &Entity<InputState>,
+        event: &InputEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !matches!(event, InputEvent::Blur) || !cx.has_global::<AppConfig>() {
+            return;
+        }
+        let value = state.read(cx).value().trim().to_string();
+        let result = if value.is_empty() {
+            Ok(0)
+        } else {
+            value.parse::<u32>()
+        };
+        self.security_status = Some(match result {
+            Ok(minutes) => match cx.global::<AppConfig>().0.set_idle_lock_minutes(minutes) {
+                Ok(()) if minutes == 0 => "Auto-lock disabled".into(),
+                Ok(()) => format!("Auto-lock after {minutes} idle minutes"),
+                Err(e) => format!("Failed to save auto-lock timeout: {e}"),
+            },
+            Err(_) => "Auto-lock timeout must be a whole number of minutes".into(),
+        });
+        cx.notify();
+    }
+
+    /// Run a secure-storage operation from the Security section, report the
+    /// outcome, and refresh key status on success.
+    fn apply_security_change(
+        &mut self,
+        success: &str,
+        op: impl FnOnce(&hive_core::config::ConfigManager, &str) -> anyhow::Result<()>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !cx.has_global::<AppConfig>() {
+            return;
+        }
+        let passphrase = self.passphrase_input.read(cx).value().to_string();
+        match op(&cx.global::<AppConfig>().0, &passphrase) {
+            Ok(()) => {
+                self.security_status = Some(success.to_string());
+                self.passphrase_input
+                    .update(cx, |state, cx| state.set_value("", window, cx));
+                self.refresh_key_status(window, cx);
+                cx.emit(SecureStorageChanged);
+            }
+            Err(e) => self.security_status = Some(format!("{e:#}")),
+        }
+        cx.notify();
+    }
+
+    /// Re-read which API keys are configured. Keys are blank while storage
+    /// is locked, so this runs after every unlock/lock.
+    fn refresh_key_status(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let cfg = cx.global::<AppConfig>().0.get();
+        let has = |key: &Option<String>| key.as_ref().is_some_and(|k| !k.is_empty());
+        self.had_anthropic_key = has(&cfg.anthropic_api_key);
+        self.had_openai_key = has(&cfg.openai_api_key);
+        self.had_openrouter_key = has(&cfg.openrouter_api_key);
+        self.had_google_key = has(&cfg.google_api_key);
+        self.had_groq_key = has(&cfg.groq_api_key);
+        self.had_xai_key = has(&cfg.xai_api_key);
+        self.had_huggingface_key = has(&cfg.huggingface_api_key);
+        self.had_litellm_key = has(&cfg.litellm_api_key);
+        self.had_elevenlabs_key = has(&cfg.elevenlabs_api_key);
+        self.had_telnyx_key = has(&cfg.telnyx_api_key);
+        let keys = [
+            (self.had_anthropic_key, &self.anthropic_key_input),
+            (self.had_openai_key, &self.openai_key_input),
+            (self.had_openrouter_key, &self.openrouter_key_input),
+            (self.had_google_key, &self.google_key_input),
+            (self.had_groq_key, &self.groq_key_input),
+            (self.had_xai_key, &self.xai_key_input),
+            (self.had_huggingface_key, &self.huggingface_key_input),
+            (self.had_litellm_key, &self.litellm_key_input),
+            (self.had_elevenlabs_key, &self.elevenlabs_key_input),
+            (self.had_telnyx_key, &self.telnyx_key_input),
+        ];
+        for (present, input) in keys {
+            input.update(cx, |state, cx| {
+                state.set_placeholder(key_placeholder(present), window, cx)
+            });
+        }
+        self.sync_enabled_providers(cx);
+    }
+
     /// Called when the user picks a model from the dropdown.
     fn on_model_selected(
         &mut self,
@@ -982,6 +1112,85 @@ impl Render for SettingsView {
                     cx.notify();
                 }),
             )
+            .on_action(
+                cx.listener(|this: &mut Self, _: &SettingsUnlockStorage, window, cx| {
+                    this.apply_security_change(
+                        "Secure storage unlocked",
+                        |config, passphrase| config.unlock(passphrase),
+                        window,
+                        cx,
+                    );
+                }),
+            )
+            .on_action(
+                cx.listener(|this: &mut Self, _: &SettingsLockStorage, window, cx| {
+                    this.apply_security_change(
+                        "Secure storage locked",
+                        |config, _| {
+                            config.lock();
+                            Ok(())
+                        },
+                        window,
+                        cx,
+                    );
+                }),
+            )
+            .on_action(cx.listener(
+                |this: &mut Self, _: &SettingsTogglePassphrase, window, cx| {
+                    let on_passphrase = cx.has_global::<AppConfig>()
+                        && matches!(
+                            cx.global::<AppConfig>().0.master_key_source(),
+                            Some(MasterKeySource::Passphrase)
+                        );
+                    if on_passphrase {
+                        this.apply_security_change(
+                            "Passphrase removed; using the machine key",
+                            |config, _| config.use_machine_key(),
+                            window,
+                            cx,
+                        );
+                    } else {
+                        this.apply_security_change(
+                            "Passphrase enabled",
+                            |config, passphrase| {
+                                if passphrase.is_empty() {
+                                    anyhow::bail!("Enter a passphrase first");
+                                }
+                                config.enable_passphrase(passphrase)
+                            },
+                            window,
+                            cx,
+                        );
+                    }
+                },
+            ))
+            .on_action(
+                cx.listener(|this: &mut Self, _: &SettingsToggleKeyring, window, cx| {
+                    let on_keyring = cx.has_global::<AppConfig>()
+                        && matches!(
+                            cx.global::<AppConfig>().0.master_key_source(),
+                            Some(MasterKeySource::Keyring { .. })
+                        );
+                    if on_keyring {
+                        this.apply_security_change(
+                            "Keyring disabled; using the machine key",
+                            |config, _| config.use_machine_key(),
+                            window,
+                            cx,
+                        );
+                    } else {
+                        this.apply_security_change(
+                            "Master key stored in the OS keyring",
+                            |config, _| match hive_core::keyring::detect_backend() {
+                                Some(backend) => config.enable_keyring(backend.as_ref()),
+                                None => anyhow::bail!("No OS keyring available"),
+                            },
+                            window,
+                            cx,
+                        );
+                    }
+                }),
+            )
             .child(
                 div()
                     .w_full()
@@ -1048,6 +1257,7 @@ impl Render for SettingsView {
                                     .child(self.render_budget_section(cx))
                                     .child(self.render_voice_tts_section(cx))
                                     .child(self.render_connected_accounts_section(cx))
+                                    .child(self.render_security_section(cx))
                                     .child(self.render_general_section(cx))
                                     .child(self.render_import_export_section(cx)),
                             ),
@@ -1597,6 +1807,143 @@ impl SettingsView {
             .into_any_element()
     }
 
+    fn render_security_section(&self, cx: &Context<Self>) -> AnyElement {
+        let theme = &self.theme;
+
+        let (source, locked) = if cx.has_global::<AppConfig>() {
+            let config = &cx.global::<AppConfig>().0;
+            (config.master_key_source(), config.is_locked())
+        } else {
+            (None, false)
+        };
+        let Some(source) = source else {
+            return card(theme)
+                .child(section_title("\u{1F512}", "Security", theme))
+                .child(section_desc("Secure storage is unavailable.", theme))
+                .into_any_element();
+        };
+
+        let source_label = match &source {
+            MasterKeySource::Machine => "Machine key".to_string(),
+            MasterKeySource::Passphrase => "Passphrase".to_string(),
+            MasterKeySource::Keyring { backend } => format!("OS keyring ({backend})"),
+        };
+        let on_passphrase = matches!(source, MasterKeySource::Passphrase);
+        let on_keyring = matches!(source, MasterKeySource::Keyring { .. });
+
+        let button = |id: &'static str, label: &'static str, color: Hsla| {
+            div()
+                .id(id)
+                .px(theme.space_3)
+                .py(theme.space_2)
+                .rounded(theme.radius_sm)
+                .bg(theme.bg_surface)
+                .border_1()
+                .border_color(theme.border)
+                .text_size(theme.font_size_sm)
+                .text_color(color)
+                .cursor_pointer()
+                .hover(|s| s.bg(theme.bg_tertiary))
+                .child(label)
+        };
+
+        card(theme)
+            .child(section_title("\u{1F512}", "Security", theme))
+            .child(section_desc(
+                "How the key that encrypts API keys and tokens is protected. A passphrase must be entered on every launch and after an idle auto-lock.",
+                theme,
+            ))
+            .child(separator(theme))
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .gap(theme.space_2)
+                    .child(status_dot(!locked, theme))
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_size(theme.font_size_base)
+                            .text_color(theme.text_secondary)
+                            .child(format!("Master key: {source_label}")),
+                    )
+                    .child(
+                        div()
+                            .text_size(theme.font_size_xs)
+                            .text_color(if locked {
+                                theme.accent_red
+                            } else {
+                                theme.accent_green
+                            })
+                            .child(if locked { "Locked" } else { "Unlocked" }),
+                    ),
+            )
+            .child(switch_row(
+                "Require Passphrase",
+                "passphrase-switch",
+                on_passphrase,
+                SettingsTogglePassphrase,
+                theme,
+            ))
+            .child(switch_row(
+                "Store Key in OS Keyring",
+                "keyring-switch",
+                on_keyring,
+                SettingsToggleKeyring,
+                theme,
+            ))
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .gap(theme.space_3)
+                    .child(
+                        div().flex_1().child(
+                            Input::new(&self.passphrase_input)
+                                .appearance(true)
+                                .mask_toggle()
+                                .cleanable(false),
+                        ),
+                    )
+                    .when(locked, |el| {
+                        el.child(
+                            button("settings-unlock-btn", "Unlock", theme.accent_green)
+                                .on_mouse_down(MouseButton::Left, |_event, window, cx| {
+                                    window.dispatch_action(Box::new(SettingsUnlockStorage), cx);
+                                }),
+                        )
+                    })
+                    .when(on_passphrase && !locked, |el| {
+                        el.child(
+                            button("settings-lock-btn", "Lock Now", theme.accent_yellow)
+                                .on_mouse_down(MouseButton::Left, |_event, window, cx| {
+                                    window.dispatch_action(Box::new(SettingsLockStorage), cx);
+                                }),
+                        )
+                    }),
+            )
+            .child(input_row(
+                "Auto-lock After Idle Minutes",
+                &self.idle_lock_input,
+                theme,
+            ))
+            .when_some(self.security_status.clone(), |el, status| {
+                el.child(
+                    div()
+                        .px(theme.space_3)
+                        .py(theme.space_2)
+                        .rounded(theme.radius_sm)
+                        .bg(theme.bg_primary)
+                        .text_size(theme.font_size_xs)
+                        .text_color(theme.text_muted)
+                        .child(status),
+                )
+            })
+            .into_any_element()
+    }
+
     fn render_import_export_section(&self, _cx: &Context<Self>) -> AnyElement {
         let theme = &self.theme;
 
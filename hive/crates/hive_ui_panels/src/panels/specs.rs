@@ -2,7 +2,7 @@ use gpui::*;
 use gpui_component::{Icon, IconName};
 
 use hive_ui_core::HiveTheme;
-use hive_ui_core::AgentsRunWorkflow;
+use hive_ui_core::{AgentsRunWorkflow, SpecsExecute};
 
 // ---------------------------------------------------------------------------
 // Data types
@@ -335,6 +335,7 @@ fn spec_progress_bar(spec: &SpecSummary, theme: &HiveTheme) -> Div {
 
 fn spec_card_footer(spec: &SpecSummary, theme: &HiveTheme) -> Div {
     let source_id = spec.id.clone();
+    let spec_id = spec.id.clone();
     let title = spec.title.clone();
     let status = spec.status.clone();
     let summary = format!(
@@ -379,6 +380,29 @@ fn spec_card_footer(spec: &SpecSummary, theme: &HiveTheme) -> Div {
                 })
                 .child("Run via Agents"),
         )
+        .child(
+            div()
+                .ml(theme.space_2)
+                .px(theme.space_2)
+                .py(theme.space_1)
+                .rounded(theme.radius_sm)
+                .bg(theme.bg_surface)
+                .border_1()
+                .border_color(theme.accent_aqua)
+                .text_size(theme.font_size_xs)
+                .text_color(theme.accent_aqua)
+                .font_weight(FontWeight::SEMIBOLD)
+                .cursor_pointer()
+                .on_mouse_down(MouseButton::Left, move |_event, window, cx| {
+                    window.dispatch_action(
+                        Box::new(SpecsExecute {
+                            spec_id: spec_id.clone(),
+                        }),
+                        cx,
+                    );
+                })
+                .child("Execute"),
+        )
 }
 
 fn spec_status_summary(status: &str) -> &'static str {
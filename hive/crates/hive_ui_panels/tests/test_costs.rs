@@ -14,6 +14,7 @@ fn empty_has_zero_values() {
     assert_eq!(data.total_input_tokens, 0);
     assert_eq!(data.total_output_tokens, 0);
     assert!(data.by_model.is_empty());
+    assert_eq!(data.cache.hits, 0);
 }
 
 // ---------------------------------------------------------------------------
@@ -95,3 +96,16 @@ fn from_tracker_total_tokens_match() {
     assert_eq!(data.total_input_tokens, 300);
     assert_eq!(data.total_output_tokens, 150);
 }
+
+#[test]
+fn from_tracker_includes_cache_stats() {
+    let mut tracker = CostTracker::default();
+    tracker.record_cache_miss();
+    tracker.record_cache_hit("gpt-4o", 1000, 500, false);
+    let data = CostData::from_tracker(&tracker);
+    assert_eq!(data.cache.hits, 1);
+    assert_eq!(data.cache.misses, 1);
+    assert_eq!(data.cache.saved_tokens, 1500);
+    assert!(data.cache.saved_cost > 0.0);
+    assert_eq!(data.total_requests, 0);
+}
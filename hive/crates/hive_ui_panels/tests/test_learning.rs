@@ -6,6 +6,7 @@ fn learning_panel_data_empty() {
     assert!(data.log_entries.is_empty());
     assert!(data.preferences.is_empty());
     assert!(data.prompt_suggestions.is_empty());
+    assert!(data.experiments.is_empty());
     assert!(data.routing_insights.is_empty());
     assert!(data.weak_areas.is_empty());
     assert!(data.best_model.is_none());
@@ -17,6 +18,7 @@ fn learning_panel_data_sample() {
     let data = LearningPanelData::sample();
     assert!(!data.log_entries.is_empty());
     assert!(!data.preferences.is_empty());
+    assert!(!data.experiments.is_empty());
     assert!(!data.routing_insights.is_empty());
     assert!(data.best_model.is_some());
     assert!(data.worst_model.is_some());
@@ -0,0 +1,116 @@
+use chrono::NaiveDate;
+use hive_ai::routing::ModelRouter;
+use hive_ai::types::{ChatMessage, MessageRole, ProviderType};
+use hive_core::routing_rules::{RoutingRule as ConfigRule, RuleAction, RuleConditions};
+use hive_ui_panels::panels::routing::{DryRunResult, RoutingData, RoutingRule};
+
+fn local_only_rule(name: &str, enabled: bool) -> ConfigRule {
+    ConfigRule {
+        name: name.into(),
+        enabled,
+        when: RuleConditions {
+            task_types: vec!["debugging".into()],
+            ..Default::default()
+        },
+        action: RuleAction::LocalOnly,
+    }
+}
+
+fn router_with_rules(rules: Vec<ConfigRule>) -> ModelRouter {
+    let mut router = ModelRouter::new();
+    for provider in [
+        ProviderType::Anthropic,
+        ProviderType::OpenAI,
+        ProviderType::Ollama,
+    ] {
+        router.fallback_manager().set_available(provider, true);
+    }
+    router.set_rules(rules);
+    router
+}
+
+// ---------------------------------------------------------------------------
+// RoutingRule::from_config
+// ---------------------------------------------------------------------------
+
+#[test]
+fn rule_from_config_describes_condition_and_action() {
+    let rule = RoutingRule::from_config(&local_only_rule("debug-local", false));
+    assert_eq!(rule.name, "debug-local");
+    assert_eq!(rule.condition, "task: debugging");
+    assert_eq!(rule.target_model, "local models only");
+    assert!(!rule.enabled);
+}
+
+#[test]
+fn empty_conditions_describe_as_always() {
+    let rule = RoutingRule::from_config(&ConfigRule {
+        name: "pin".into(),
+        enabled: true,
+        when: RuleConditions::default(),
+        action: RuleAction::PinModel {
+            model: "gpt-4o".into(),
+        },
+    });
+    assert_eq!(rule.condition, "always");
+    assert_eq!(rule.target_model, "pin gpt-4o");
+}
+
+// ---------------------------------------------------------------------------
+// RoutingData::from_router
+// ---------------------------------------------------------------------------
+
+#[test]
+fn from_router_lists_configured_rules() {
+    let router = router_with_rules(vec![
+        local_only_rule("first", true),
+        local_only_rule("second", false),
+    ]);
+    let data = RoutingData::from_router(&router);
+    let names: Vec<_> = data.custom_rules.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, ["first", "second"]);
+}
+
+// ---------------------------------------------------------------------------
+// DryRunResult::from_dry_run
+// ---------------------------------------------------------------------------
+
+#[test]
+fn dry_run_result_reports_override() {
+    let router = router_with_rules(vec![local_only_rule("debug-local", true)]);
+    let messages = [ChatMessage::text(
+        MessageRole::User,
+        "Debug this crash: stack trace attached",
+    )];
+    let now = NaiveDate::from_ymd_opt(2026, 3, 4)
+        .unwrap()
+        .and_hms_opt(12, 0, 0)
+        .unwrap();
+
+    let result = DryRunResult::from_dry_run(&router.dry_run(&messages, &[], None, now));
+    assert_eq!(result.matched_rules, ["debug-local"]);
+    assert_eq!(result.provider, ProviderType::Ollama.to_string());
+    assert!(result.overridden());
+    assert!(result.reasoning.contains("debug-local"));
+}
+
+#[test]
+fn dry_run_result_reports_refusal() {
+    let mut router = ModelRouter::new();
+    router
+        .fallback_manager()
+        .set_available(ProviderType::Anthropic, true);
+    router.set_rules(vec![local_only_rule("debug-local", true)]);
+    let messages = [ChatMessage::text(
+        MessageRole::User,
+        "Debug this crash: stack trace attached",
+    )];
+    let now = NaiveDate::from_ymd_opt(2026, 3, 4)
+        .unwrap()
+        .and_hms_opt(12, 0, 0)
+        .unwrap();
+
+    let result = DryRunResult::from_dry_run(&router.dry_run(&messages, &[], None, now));
+    assert!(result.blocked);
+    assert!(result.reasoning.contains("debug-local"));
+}